@@ -6,7 +6,9 @@ use std::sync::{Arc, RwLock};
 
 use lightningcss::css_modules::PatternParseError;
 use lightningcss::error::{Error, MinifyErrorKind, ParserError, PrinterError};
-use lightningcss::stylesheet::{MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, StyleSheet};
+use lightningcss::stylesheet::{
+  DraftSyntax, MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, StyleSheet, ValidationMode,
+};
 use lightningcss::targets::Browsers;
 use parcel_sourcemap::SourceMap;
 
@@ -185,6 +187,7 @@ impl Into<MinifyOptions> for TransformOptions {
         Default::default()
       },
       unused_symbols,
+      ..MinifyOptions::default()
     }
   }
 }
@@ -279,6 +282,8 @@ pub extern "C" fn lightningcss_stylesheet_parse(
       Some(lightningcss::css_modules::Config {
         pattern,
         dashed_idents: options.css_modules_dashed_idents,
+        hash_salt: None,
+        ..Default::default()
       })
     } else {
       None
@@ -286,6 +291,9 @@ pub extern "C" fn lightningcss_stylesheet_parse(
     error_recovery: options.error_recovery,
     source_index: 0,
     warnings: Some(warnings.clone()),
+    disabled_drafts: DraftSyntax::empty(),
+    validation: ValidationMode::Ignore,
+    registered_properties: Default::default(),
   };
 
   let stylesheet = unwrap!(StyleSheet::parse(code, opts), error, std::ptr::null_mut());
@@ -346,6 +354,12 @@ pub extern "C" fn lightningcss_stylesheet_to_css(
     } else {
       None
     },
+    math_fallback: None,
+    rem_to_px: None,
+    rebase_urls: None,
+    inline_assets: None,
+    emit_asset: None,
+    coverage_map: false,
   };
 
   let res = unwrap!(wrapper.stylesheet.to_css(opts), error, ToCssResult::default());
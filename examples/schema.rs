@@ -1,7 +1,7 @@
 fn main() {
   #[cfg(feature = "jsonschema")]
   {
-    let schema = schemars::schema_for!(lightningcss::stylesheet::StyleSheet);
+    let schema = lightningcss::stylesheet::StyleSheet::json_schema();
     let output = serde_json::to_string_pretty(&schema).unwrap();
     let _ = std::fs::write("node/ast.json", output);
   }
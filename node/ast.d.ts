@@ -89,6 +89,14 @@ export type Rule<D = Declaration, M = MediaQuery> = | {
     type: "starting-style";
     value: StartingStyleRule<D, M>;
   }
+| {
+    type: "value";
+    value: ValueRule;
+  }
+| {
+    type: "icss";
+    value: IcssRule;
+  }
 | {
     type: "ignored";
   }
@@ -5180,7 +5188,12 @@ export type FontStretchKeyword =
 /**
  * A value for the [font-family](https://www.w3.org/TR/css-fonts-4/#font-family-prop) property.
  */
-export type FontFamily = GenericFontFamily | String;
+export type FontFamily =
+  | GenericFontFamily
+  | String
+  | {
+      familyName: String;
+    };
 /**
  * A [generic font family](https://www.w3.org/TR/css-fonts-4/#generic-font-families) name, as used in the `font-family` property.
  *
@@ -7252,6 +7265,10 @@ export interface Variable {
    * The variable name.
    */
   name: DashedIdentReference;
+  /**
+   * The fallback value parsed according to the syntax of the referenced custom property, if it is registered and the fallback matched. Like [CustomProperty::typed_value], this is not printed or visited, and the fallback token list is normalized from it when parsing.
+   */
+  typed_fallback?: ParsedComponent | null;
 }
 /**
  * A CSS [`<dashed-ident>`](https://www.w3.org/TR/css-values-4/#dashed-idents) reference.
@@ -7288,7 +7305,7 @@ export interface Function {
  */
 export interface ImportRule<M = MediaQuery> {
   /**
-   * An optional cascade layer name, or `None` for an anonymous layer.
+   * An optional cascade layer name, or `None` for an anonymous layer. When serialized, an anonymous layer is represented as an empty list of names.
    */
   layer?: String[] | null;
   /**
@@ -7328,6 +7345,10 @@ export interface StyleRule<D = Declaration, M = MediaQuery> {
    * The selectors for the style rule.
    */
   selectors: SelectorList;
+  /**
+   * A vendor prefix override, used during selector printing. It is only serialized when set, e.g. for rules duplicated with prefixed selectors during minification.
+   */
+  vendorPrefix?: VendorPrefix;
 }
 /**
  * A CSS declaration block.
@@ -8749,6 +8770,10 @@ export interface CustomProperty {
    * The name of the property.
    */
   name: CustomPropertyName;
+  /**
+   * The property value parsed according to the syntax of a registered custom property, if it matched. This is used for interpolation, and is not printed or visited. The token list is normalized from it when parsing, so changes to the value should be made to the token list.
+   */
+  typed_value?: ParsedComponent | null;
   /**
    * The property value, stored as a raw token list.
    */
@@ -9165,6 +9190,119 @@ export interface StartingStyleRule<D = Declaration, M = MediaQuery> {
    */
   rules: Rule<D, M>[];
 }
+/**
+ * A [@value](https://github.com/css-modules/postcss-modules-values) rule, used to define and import values in CSS modules.
+ */
+export interface ValueRule {
+  /**
+   * The values defined or imported by the rule.
+   */
+  definition: ValueDefinition;
+  /**
+   * The location of the rule in the source file.
+   */
+  loc: Location;
+}
+/**
+ * The definition within a [@value](ValueRule) rule.
+ */
+export type ValueDefinition =
+  | {
+      /**
+       * The name of the value.
+       */
+      name: String;
+      type: "declaration";
+      /**
+       * The value to substitute for references to the name.
+       */
+      value: TokenOrValue[];
+    }
+  | {
+      /**
+       * The imported names.
+       */
+      names: ValueImport[];
+      /**
+       * The specifier of the file to import from.
+       */
+      specifier: String;
+      type: "import";
+    };
+/**
+ * A name imported by a [@value](ValueRule) rule.
+ */
+export interface ValueImport {
+  /**
+   * The local name of the value, if renamed using `as`.
+   */
+  alias?: String | null;
+  /**
+   * The name of the value in the imported file.
+   */
+  name: String;
+}
+/**
+ * An [ICSS](https://github.com/css-modules/icss) `:import` or `:export` rule, used to interoperate with other tools that implement CSS modules.
+ */
+export interface IcssRule {
+  /**
+   * The contents of the rule.
+   */
+  block: IcssBlock;
+  /**
+   * The location of the rule in the source file.
+   */
+  loc: Location;
+}
+/**
+ * The contents of an [ICSS rule](IcssRule).
+ */
+export type IcssBlock =
+  | {
+      /**
+       * The imported names.
+       */
+      names: IcssImport[];
+      /**
+       * The specifier of the file to import from.
+       */
+      specifier: String;
+      type: "import";
+    }
+  | {
+      /**
+       * The exported names.
+       */
+      exports: IcssExport[];
+      type: "export";
+    };
+/**
+ * A name imported by an ICSS `:import` block.
+ */
+export interface IcssImport {
+  /**
+   * The name that references to the import are replaced with in the current file.
+   */
+  local: String;
+  /**
+   * The name exported by the imported file.
+   */
+  remote: String;
+}
+/**
+ * A name exported by an ICSS `:export` block.
+ */
+export interface IcssExport {
+  /**
+   * The exported name.
+   */
+  name: String;
+  /**
+   * The exported value.
+   */
+  value: TokenOrValue[];
+}
 /**
  * An unknown at-rule, stored as raw tokens.
  */
@@ -21,6 +21,8 @@ exports.Features = {
   DoublePositionGradients: 131072,
   VendorPrefixes: 262144,
   LogicalProperties: 524288,
+  PlaceShorthands: 1048576,
+  ExtendedSystemFonts: 2097152,
   Selectors: 31,
   MediaQueries: 448,
   Colors: 64512,
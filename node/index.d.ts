@@ -1,4 +1,4 @@
-import type { Angle, CssColor, Rule, CustomProperty, EnvironmentVariable, Function, Image, LengthValue, MediaQuery, Declaration, Ratio, Resolution, Selector, SupportsCondition, Time, Token, TokenOrValue, UnknownAtRule, Url, Variable, StyleRule, DeclarationBlock, ParsedComponent, Multiplier } from './ast';
+import type { Angle, CssColor, Rule, CustomProperty, EnvironmentVariable, Function, Image, LengthValue, MediaQuery, Declaration, Ratio, Resolution, Selector, SelectorList, SupportsCondition, Time, Token, TokenOrValue, UnknownAtRule, Url, Variable, StyleRule, DeclarationBlock, ParsedComponent, Multiplier, MediaList } from './ast';
 import { Targets, Features } from './targets';
 
 export * from './ast';
@@ -33,6 +33,12 @@ export interface TransformOptions<C extends CustomAtRules> {
   nonStandard?: NonStandard,
   /** Whether to compile this file as a CSS module. */
   cssModules?: boolean | CSSModulesConfig,
+  /**
+   * A resolver used to load the files referenced by CSS modules `composes` declarations,
+   * e.g. `composes: foo from "./other.css"`. When provided, the compiled names exported by these
+   * files are included in the exports, rather than returned as dependency references.
+   */
+  resolver?: SyncResolver,
   /**
    * Whether to analyze dependencies (e.g. `@import` and `url()`).
    * When enabled, `@import` rules are removed, and `url()` dependencies
@@ -45,18 +51,77 @@ export interface TransformOptions<C extends CustomAtRules> {
    * This is useful for polyfills, for example.
    */
   pseudoClasses?: PseudoClasses,
+  /**
+   * Rewrites relative `url()` references so that they are relative to the output file
+   * rather than the source file they appear in, e.g. when bundling files from nested directories.
+   */
+  rebaseUrls?: RebaseUrls,
+  /**
+   * Inlines small files referenced by relative `url()` values as `data:` URIs,
+   * avoiding extra requests for tiny assets such as SVG icons.
+   */
+  inlineAssets?: InlineAssets,
+  /**
+   * Called with the resolved path of each file referenced by a relative `url()`. It can emit the file,
+   * e.g. by copying it to the output directory with a content hash in its name, and return the url to
+   * reference it by. Returning `undefined` leaves the url as is. The results are returned as the `assets` manifest.
+   */
+  emitAsset?: (path: string) => string | undefined | null,
+  /**
+   * Whether to return a coverage map, which maps byte ranges of the output to the source locations
+   * of the rules they were printed from, e.g. to join browser coverage data with the source rules.
+   */
+  coverageMap?: boolean,
   /**
    * A list of class names, ids, and custom identifiers (e.g. @keyframes) that are known
    * to be unused. These will be removed during minification. Note that these are not
    * selectors but individual names (without any . or # prefixes).
    */
   unusedSymbols?: string[],
+  /**
+   * Values of custom properties to inline at build time, keyed by name including the leading `--`.
+   * Matching `var()` references are replaced with these values, and declarations of the custom
+   * properties within `:root` rules are removed. References to other custom properties are left as is.
+   */
+  inlineCustomProperties?: Record<string, string>,
+  /**
+   * Values of custom properties and environment variables to substitute into `var()` and `env()`
+   * references throughout the style sheet. Fallbacks are used for undefined values, and cycles are
+   * detected. Unlike `inlineCustomProperties`, declarations of custom properties are kept.
+   */
+  substituteVariables?: {
+    /** Custom property values, keyed by name including the leading `--`. */
+    variables?: Record<string, string>,
+    /** Environment variable values, e.g. `safe-area-inset-top`. */
+    env?: Record<string, string>
+  },
   /**
    * Whether to ignore invalid rules and declarations rather than erroring.
    * When enabled, warnings are returned, and the invalid rule or declaration is
    * omitted from the output code.
    */
   errorRecovery?: boolean,
+  /**
+   * Whether to warn about declarations that are overridden by a later declaration of the same
+   * property in the same rule. Earlier declarations that are fallbacks for syntax used by the
+   * later value, e.g. a new function, length unit, or vendor prefix, are not reported.
+   */
+  warnOverriddenDeclarations?: boolean,
+  /**
+   * How to handle unknown properties (e.g. `widht`), and values that are invalid for their property.
+   * By default, these are preserved as is. With `'warn'`, a warning is returned for each one, and with
+   * `'error'`, an error is thrown (or a warning is returned and the declaration is omitted when
+   * `errorRecovery` is enabled). Vendor prefixed properties and values, and values that reference
+   * `var()` or `env()`, are not validated.
+   */
+  validation?: 'ignore' | 'warn' | 'error',
+  /**
+   * How to handle features that are not supported by the `targets`, and cannot be compiled,
+   * such as `:has()`, `@container`, or `dvh` units. By default, these are passed through as is.
+   * With `'warn'`, a warning is returned for each one, including the feature id, location, and
+   * the browsers that do not support it. With `'error'`, an error is thrown for the first one.
+   */
+  unsupportedFeatures?: 'ignore' | 'warn' | 'error',
   /**
    * An AST visitor object. This allows custom transforms or analysis to be implemented in JavaScript.
    * Multiple visitors can be composed into one using the `composeVisitors` function.
@@ -240,9 +305,26 @@ export interface DependencyOptions {
   preserveImports?: boolean
 }
 
-export type BundleOptions<C extends CustomAtRules> = Omit<TransformOptions<C>, 'code'>;
+export interface BundleOptions<C extends CustomAtRules> extends Omit<TransformOptions<C>, 'code' | 'resolver'> {
+  /**
+   * A custom resolver used to resolve `@import` specifiers and read files, e.g. to route them
+   * through a build tool's module graph. Both functions must return synchronously.
+   */
+  resolver?: SyncResolver;
+  /**
+   * An in-memory file system mapping file paths to their contents, used to read files instead of
+   * the file system, e.g. in tests or playgrounds. Relative specifiers are resolved against the path
+   * of the importing file. Ignored when a `resolver` is provided.
+   */
+  files?: Record<string, string>;
+  /**
+   * Preserves `@import` rules of remote urls, or urls matching the given patterns, rather than inlining them.
+   * Preserved rules are hoisted to the top of the bundle.
+   */
+  externalImports?: ExternalImports;
+}
 
-export interface BundleAsyncOptions<C extends CustomAtRules> extends BundleOptions<C> {
+export interface BundleAsyncOptions<C extends CustomAtRules> extends Omit<BundleOptions<C>, 'resolver' | 'emitAsset'> {
   resolver?: Resolver;
 }
 
@@ -258,9 +340,30 @@ export interface Resolver {
   resolve?: (specifier: string, originatingFile: string) => string | Promise<string>;
 }
 
+/**
+ * Custom resolver used to load CSS files synchronously in `bundle()`, and to load the dependencies
+ * of CSS modules `composes` declarations in `transform()`.
+ */
+export interface SyncResolver {
+  /** Read the given file and return its contents as a string. Defaults to reading from the file system. */
+  read?: (file: string) => string;
+
+  /**
+   * Resolve the given `@import` or `composes` specifier from the provided originating file to a
+   * path which gets passed to `read()`. Defaults to a path relative to the originating file.
+   */
+  resolve?: (specifier: string, originatingFile: string) => string;
+}
+
 export interface Drafts {
   /** Whether to enable @custom-media rules. */
-  customMedia?: boolean
+  customMedia?: boolean,
+  /** Whether to parse CSS nesting. Enabled by default. */
+  nesting?: boolean,
+  /** Whether to parse the relative color syntax, e.g. `rgb(from red r g b)`. Enabled by default. */
+  relativeColors?: boolean,
+  /** Whether to parse the `color-mix()` function. Enabled by default. */
+  colorMix?: boolean
 }
 
 export interface NonStandard {
@@ -276,6 +379,29 @@ export interface PseudoClasses {
   focusWithin?: string
 }
 
+export interface RebaseUrls {
+  /** The path of the output file. Should be absolute if the filename is absolute, or relative to the same directory otherwise. */
+  outputFile: string,
+  /** A public path to prefix rebased urls with, e.g. `/assets/`, when the directory of the output file is served from a different location. */
+  publicPath?: string
+}
+
+export interface InlineAssets {
+  /** The maximum size of a file to inline, in bytes. Defaults to 4096. */
+  maxSize?: number,
+  /** Patterns matching the resolved paths of files to inline, e.g. `*.svg`, where `*` matches any sequence of characters. */
+  include?: string[],
+  /** Patterns matching the resolved paths of files to exclude from inlining. */
+  exclude?: string[]
+}
+
+export interface ExternalImports {
+  /** Whether to preserve imports of remote urls, e.g. `https://example.com/style.css` or `//example.com/style.css`. */
+  remote?: boolean,
+  /** Patterns matching the urls of imports to preserve, where `*` matches any sequence of characters. */
+  include?: string[]
+}
+
 export interface TransformResult {
   /** The transformed code. */
   code: Uint8Array,
@@ -288,21 +414,132 @@ export interface TransformResult {
   /** `@import` and `url()` dependencies, if enabled. */
   dependencies: Dependency[] | void,
   /** Warnings that occurred during compilation. */
-  warnings: Warning[]
+  warnings: Warning[],
+  /** The CSS module exports that were removed because they were not in `usedExports`, if provided. */
+  removedExports: string[] | void,
+  /** A map of the paths of files referenced by `url()` to the urls of the emitted files, if `emitAsset` is provided. */
+  assets: Record<string, string> | void,
+  /** The byte ranges of the output printed from each rule, sorted by start offset, if `coverageMap` is enabled. */
+  coverageMap: CoverageRange[] | void
+}
+
+export interface CoverageRange {
+  /** The byte offset in the output where the rule starts. */
+  start: number,
+  /** The byte offset in the output where the rule ends, exclusive. */
+  end: number,
+  /** The location of the rule in the source file. */
+  loc: RuleLocation
+}
+
+export interface RuleLocation {
+  /** The index of the source file, e.g. within a bundle. */
+  source_index: number,
+  /** The line number (0-based). */
+  line: number,
+  /** The column number (1-based). */
+  column: number
+}
+
+export interface BundleResult extends TransformResult {
+  /** The files included in the bundle and their dependencies, e.g. to invalidate bundles in watch mode. */
+  dependencyGraph: DependencyGraph
+}
+
+export interface DependencyGraph {
+  /** The files included in the bundle, starting with the entry file. */
+  files: FileDependencies[]
+}
+
+export interface FileDependencies {
+  /** The path of the file. */
+  file: string,
+  /** The `@import` rules in the file. */
+  imports: ImportEdge[],
+  /** The files referenced by CSS modules `composes` properties and variable references. */
+  cssModules: string[],
+  /** The `url()` references in the file, other than those of `@import` rules. `data:` urls are omitted. */
+  urls: UrlEdge[]
+}
+
+export interface ImportEdge {
+  /** The url of the `@import` rule, as written. */
+  url: string,
+  /** The path of the imported file, or `null` if the rule was preserved as an external import. */
+  file: string | null,
+  /** The `layer()` condition of the `@import` rule. Anonymous layers are represented as an empty string. */
+  layer: string | null,
+  /** The `supports()` condition of the `@import` rule. */
+  supports: string | null,
+  /** The media query of the `@import` rule. */
+  media: string | null,
+  /** The location of the `@import` rule in the file. */
+  loc: Location
+}
+
+export interface UrlEdge {
+  /** The url, as written. */
+  url: string,
+  /** The path of the referenced file, resolved relative to the file containing the reference, or `null` if the url is absolute. */
+  file: string | null,
+  /** The location of the reference in the file. */
+  loc: Location
 }
 
 export interface Warning {
   message: string,
   type: string,
   value?: any,
+  /** For `UnsupportedFeature` warnings, the name of the feature, e.g. `:has()`. */
+  feature?: string,
+  /** For `UnsupportedFeature` warnings, a unique id for the feature, e.g. `has-selector`. */
+  id?: string,
+  /** For `UnsupportedFeature` warnings, the targeted browsers that do not support the feature. */
+  browsers?: UnsupportedBrowser[],
   loc: ErrorLocation
 }
 
+export interface UnsupportedBrowser {
+  /** The name of the browser, e.g. `safari`. */
+  browser: string,
+  /** The targeted version of the browser. */
+  version: string,
+  /** The first version of the browser that supports the feature, if any. */
+  supportedSince: string | null
+}
+
 export interface CSSModulesConfig {
   /** The pattern to use when renaming class names and other identifiers. Default is `[hash]_[local]`. */
   pattern?: string,
   /** Whether to rename dashed identifiers, e.g. custom properties. */
-  dashedIdents?: boolean
+  dashedIdents?: boolean,
+  /** A salt to include when hashing file names, so that generated names can be varied between builds. */
+  hashSalt?: string,
+  /**
+   * How exported names are converted in the exports object. `camelCase` adds a camel cased
+   * version of each name, `camelCaseOnly` replaces each name with its camel cased version,
+   * and `dashes` only converts dashes to camel case. Default is `asIs`.
+   */
+  exportsConvention?: 'asIs' | 'camelCase' | 'camelCaseOnly' | 'dashes',
+  /** Whether to scope `@keyframes` names and references to them in `animation` properties. Default is true. */
+  animation?: boolean,
+  /** Whether to scope container names in `@container` rules and `container` properties. Default is true. */
+  container?: boolean,
+  /** Whether to scope `@counter-style` names and references to them in `list-style` properties. Default is true. */
+  counterStyle?: boolean,
+  /** Whether to scope grid line and area names. Default is true. */
+  grid?: boolean,
+  /** Whether to scope custom identifiers in the `view-transition-name` property. Default is true. */
+  viewTransitionName?: boolean,
+  /** Whether to require that every selector contains at least one local class or id. Default is false. */
+  pure?: boolean,
+  /** Whether to append ICSS `:import` and `:export` blocks for the exports of the module to the output. Default is false. */
+  icss?: boolean,
+  /**
+   * The exported names that are used, e.g. as determined by a JavaScript bundler. When provided,
+   * rules that only apply to unused local classes and ids, and unreferenced `@keyframes`, are removed.
+   */
+  usedExports?: string[]
 }
 
 export type CSSModuleExports = {
@@ -398,6 +635,27 @@ export interface ErrorLocation extends Location {
  */
 export declare function transform<C extends CustomAtRules>(options: TransformOptions<C>): TransformResult;
 
+type MaybeAsync<T> = T extends (...args: infer A) => infer R
+  ? (...args: A) => R | Promise<R>
+  : T extends object ? { [K in keyof T]: MaybeAsync<T[K]> } : T;
+
+/** A visitor whose functions may return promises. */
+export type AsyncVisitor<C extends CustomAtRules> = MaybeAsync<Visitor<C>>;
+
+export interface TransformAsyncOptions<C extends CustomAtRules> extends Omit<TransformOptions<C>, 'visitor' | 'resolver' | 'emitAsset'> {
+  /** An AST visitor object. Its functions may return promises. */
+  visitor?: AsyncVisitor<C>,
+  /** Custom functions for reading and resolving files referenced by `composes`, which may return promises. */
+  resolver?: Resolver,
+  /** Called for each file referenced by `url()`, and may return a promise. */
+  emitAsset?: (path: string) => string | undefined | null | Promise<string | undefined | null>
+}
+
+/**
+ * An asynchronous version of `transform`, which allows visitors, resolvers and asset emitters to return promises.
+ */
+export declare function transformAsync<C extends CustomAtRules>(options: TransformAsyncOptions<C>): Promise<TransformResult>;
+
 export interface TransformAttributeOptions {
   /** The filename in which the style attribute appeared. Used for error messages and dependencies. */
   filename?: string,
@@ -420,6 +678,20 @@ export interface TransformAttributeOptions {
    * omitted from the output code.
    */
   errorRecovery?: boolean,
+  /**
+   * Whether to warn about declarations that are overridden by a later declaration of the same
+   * property in the same rule. Earlier declarations that are fallbacks for syntax used by the
+   * later value, e.g. a new function, length unit, or vendor prefix, are not reported.
+   */
+  warnOverriddenDeclarations?: boolean,
+  /**
+   * How to handle unknown properties (e.g. `widht`), and values that are invalid for their property.
+   * By default, these are preserved as is. With `'warn'`, a warning is returned for each one, and with
+   * `'error'`, an error is thrown (or a warning is returned and the declaration is omitted when
+   * `errorRecovery` is enabled). Vendor prefixed properties and values, and values that reference
+   * `var()` or `env()`, are not validated.
+   */
+  validation?: 'ignore' | 'warn' | 'error',
   /**
    * An AST visitor object. This allows custom transforms or analysis to be implemented in JavaScript.
    * Multiple visitors can be composed into one using the `composeVisitors` function.
@@ -443,6 +715,66 @@ export interface TransformAttributeResult {
  */
 export declare function transformStyleAttribute(options: TransformAttributeOptions): TransformAttributeResult;
 
+export interface Specificity {
+  /** The number of ID selectors. */
+  ids: number,
+  /** The number of class selectors, attribute selectors, and pseudo-classes. */
+  classes: number,
+  /** The number of type selectors and pseudo-elements. */
+  elements: number
+}
+
+/**
+ * Computes the specificity of each selector in a selector list.
+ */
+export declare function specificity(selector: string): Specificity[];
+
+/**
+ * Parses a selector list into the same representation used by visitors.
+ */
+export declare function parseSelector(selector: string): SelectorList;
+
+/**
+ * Parses a single rule into the same representation used by visitors.
+ */
+export declare function parseRule(rule: string): Rule;
+
+/**
+ * Parses a list of declarations, e.g. the contents of a style rule or `style` attribute,
+ * into the same representation used by visitors.
+ */
+export declare function parseDeclarations(declarations: string): DeclarationBlock;
+
+/**
+ * Parses a media query list into the same representation used by visitors.
+ */
+export declare function parseMediaQuery(media: string): MediaList;
+
+export interface PrintOptions {
+  /** Whether to enable minification. */
+  minify?: boolean
+}
+
+/**
+ * Prints a rule, as returned by `parseRule` or passed to a visitor, to a CSS string.
+ */
+export declare function printRule(rule: Rule, options?: PrintOptions): string;
+
+/**
+ * Prints a list of declarations, as returned by `parseDeclarations` or passed to a visitor, to a CSS string.
+ */
+export declare function printDeclarations(declarations: DeclarationBlock, options?: PrintOptions): string;
+
+/**
+ * Prints a selector list, as returned by `parseSelector` or passed to a visitor, to a CSS string.
+ */
+export declare function printSelector(selector: SelectorList, options?: PrintOptions): string;
+
+/**
+ * Prints a media query list, as returned by `parseMediaQuery` or passed to a visitor, to a CSS string.
+ */
+export declare function printMediaQuery(media: MediaList, options?: PrintOptions): string;
+
 /**
  * Converts a browserslist result into targets that can be passed to lightningcss.
  * @param browserslist the result of calling `browserslist`
@@ -452,14 +784,34 @@ export declare function browserslistToTargets(browserslist: string[]): Targets;
 /**
  * Bundles a CSS file and its dependencies, inlining @import rules.
  */
-export declare function bundle<C extends CustomAtRules>(options: BundleOptions<C>): TransformResult;
+export declare function bundle<C extends CustomAtRules>(options: BundleOptions<C>): BundleResult;
 
 /**
  * Bundles a CSS file and its dependencies asynchronously, inlining @import rules.
  */
-export declare function bundleAsync<C extends CustomAtRules>(options: BundleAsyncOptions<C>): Promise<TransformResult>;
+export declare function bundleAsync<C extends CustomAtRules>(options: BundleAsyncOptions<C>): Promise<BundleResult>;
 
 /**
  * Composes multiple visitor objects into a single one.
  */
 export declare function composeVisitors<C extends CustomAtRules>(visitors: Visitor<C>[]): Visitor<C>;
+
+export interface QueryVisitorOptions {
+  /**
+   * A selector list. Only style rules with a selector equal to one of these are passed to `Rule`.
+   * Selectors of nested rules include the nesting selector, e.g. `& .foo`.
+   */
+  selector?: string,
+  /** A property name. Only declarations of this property are passed to `Declaration`. */
+  property?: string,
+  /** Called for each matching style rule. The return value replaces the rule, as in a visitor. */
+  Rule?: RuleVisitor<RequiredValue<FindByType<Rule, 'style'>>>,
+  /** Called for each matching declaration. The return value replaces the declaration, as in a visitor. */
+  Declaration?: DeclarationVisitor
+}
+
+/**
+ * Creates a visitor that only visits style rules matching a selector, and declarations of a property.
+ * It can be composed with other visitors using `composeVisitors`.
+ */
+export declare function queryVisitor(options: QueryVisitorOptions): Visitor<any>;
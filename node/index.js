@@ -24,4 +24,5 @@ if (process.env.CSS_TRANSFORMER_WASM) {
 
 module.exports.browserslistToTargets = require('./browserslistToTargets');
 module.exports.composeVisitors = require('./composeVisitors');
+module.exports.queryVisitor = require('./queryVisitor')(module.exports.parseSelector);
 module.exports.Features = require('./flags').Features;
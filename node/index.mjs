@@ -1,4 +1,4 @@
 import index from './index.js';
 
-const { transform, transformStyleAttribute, bundle, bundleAsync, browserslistToTargets, composeVisitors, Features } = index;
-export { transform, transformStyleAttribute, bundle, bundleAsync, browserslistToTargets, composeVisitors, Features };
+const { transform, transformAsync, transformStyleAttribute, specificity, parseSelector, parseRule, parseDeclarations, parseMediaQuery, printRule, printDeclarations, printSelector, printMediaQuery, bundle, bundleAsync, browserslistToTargets, composeVisitors, queryVisitor, Features } = index;
+export { transform, transformAsync, transformStyleAttribute, specificity, parseSelector, parseRule, parseDeclarations, parseMediaQuery, printRule, printDeclarations, printSelector, printMediaQuery, bundle, bundleAsync, browserslistToTargets, composeVisitors, queryVisitor, Features };
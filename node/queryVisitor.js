@@ -0,0 +1,57 @@
+// @ts-check
+/** @typedef {import('./index').Visitor} Visitor */
+/** @typedef {import('./index').QueryVisitorOptions} QueryVisitorOptions */
+
+/**
+ * Creates the `queryVisitor` function, given a function to parse a selector list.
+ * @param {(selector: string) => import('./ast').Selector[]} parseSelector
+ */
+function createQueryVisitor(parseSelector) {
+  /**
+   * Creates a visitor that only visits style rules with a matching selector,
+   * and declarations of a matching property.
+   * @param {QueryVisitorOptions} options
+   * @return {Visitor}
+   */
+  return function queryVisitor(options) {
+    /** @type {Visitor} */
+    let visitor = {};
+
+    let visitRule = options.Rule;
+    if (visitRule) {
+      // Selectors are compared structurally, which also ignores differences in formatting.
+      let selectors = options.selector != null
+        ? parseSelector(options.selector).map(selector => JSON.stringify(selector))
+        : null;
+      visitor.Rule = {
+        style(rule) {
+          if (!selectors || rule.value.selectors.some(selector => selectors.includes(JSON.stringify(selector)))) {
+            return visitRule(rule);
+          }
+        }
+      };
+    }
+
+    let visitDeclaration = options.Declaration;
+    if (visitDeclaration) {
+      let property = options.property;
+      if (property == null) {
+        visitor.Declaration = visitDeclaration;
+      } else if (property.startsWith('--')) {
+        visitor.Declaration = {
+          custom: {
+            [property]: value => visitDeclaration({ property: 'custom', value })
+          }
+        };
+      } else {
+        visitor.Declaration = {
+          [property.toLowerCase()]: visitDeclaration
+        };
+      }
+    }
+
+    return visitor;
+  };
+}
+
+module.exports = createQueryVisitor;
@@ -0,0 +1,238 @@
+// Calls into JS from native code that may not run on the JS thread, and may need to await promises.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crossbeam_channel::RecvTimeoutError;
+use napi::{Env, JsUnknown};
+#[cfg(not(target_arch = "wasm32"))]
+use napi::{JsFunction, JsObject};
+#[cfg(not(target_arch = "wasm32"))]
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::threadsafe_function::{ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+
+/// A task that runs on the JS thread.
+#[cfg(not(target_arch = "wasm32"))]
+type JsTask = Box<dyn FnOnce(Env) + Send>;
+
+/// Receives the result of a JS function once it is available.
+#[cfg(not(target_arch = "wasm32"))]
+type Done<'a> = Box<dyn FnOnce(Env, napi::Result<JsUnknown>) + 'a>;
+
+/// Used by visitors, resolvers and asset emitters to call JS functions. Calls made by `transform`
+/// run synchronously on the JS thread, and cannot return promises. `transformAsync` compiles on a
+/// background thread, and sends each call to the JS thread, blocking until the returned promise
+/// settles. In WebAssembly, there are no threads, so the compiler is suspended with Asyncify instead.
+#[derive(Clone)]
+pub struct JsCaller {
+  env: Env,
+  mode: Mode,
+}
+
+#[derive(Clone)]
+enum Mode {
+  Sync,
+  #[cfg(not(target_arch = "wasm32"))]
+  Background(ThreadsafeFunction<JsTask>),
+  #[cfg(target_arch = "wasm32")]
+  Asyncify,
+}
+
+impl JsCaller {
+  /// Creates a caller that runs on the JS thread, and cannot await promises.
+  pub fn sync(env: Env) -> JsCaller {
+    JsCaller { env, mode: Mode::Sync }
+  }
+
+  /// Creates a caller that is used from a background thread. It must be created,
+  /// and all values holding it must be dropped, on the JS thread.
+  #[cfg(not(target_arch = "wasm32"))]
+  pub fn background(env: Env) -> napi::Result<JsCaller> {
+    let tsfn = ThreadsafeFunction::create(
+      env.raw(),
+      std::ptr::null_mut(),
+      0,
+      |ctx: ThreadSafeCallContext<JsTask>| {
+        (ctx.value)(ctx.env);
+        Ok(())
+      },
+    )?;
+
+    Ok(JsCaller {
+      env,
+      mode: Mode::Background(tsfn),
+    })
+  }
+
+  /// Creates a caller that awaits promises by unwinding the stack with Asyncify.
+  /// It must only be used within a function called by the driver in async.mjs.
+  #[cfg(target_arch = "wasm32")]
+  pub fn asyncify(env: Env) -> JsCaller {
+    JsCaller {
+      env,
+      mode: Mode::Asyncify,
+    }
+  }
+
+  /// Returns the JS environment. This is only valid on the JS thread, e.g. to create or release references.
+  pub fn env(&self) -> Env {
+    self.env
+  }
+
+  /// Calls a JS function with `call`, and converts its result with `convert`. If the function returns
+  /// a promise, it is awaited before the result is converted.
+  pub fn call<R>(
+    &self,
+    call: impl FnOnce(Env) -> napi::Result<JsUnknown>,
+    convert: impl FnOnce(Env, JsUnknown) -> napi::Result<R>,
+  ) -> napi::Result<R> {
+    match &self.mode {
+      Mode::Sync => {
+        let value = call(self.env)?;
+        if value.is_promise()? {
+          return Err(napi::Error::from_reason(
+            "A JS callback returned a promise. Use transformAsync to run async callbacks.".to_string(),
+          ));
+        }
+        convert(self.env, value)
+      }
+      #[cfg(not(target_arch = "wasm32"))]
+      Mode::Background(tsfn) => {
+        let (tx, rx) = crossbeam_channel::bounded(1);
+        let status = dispatch(tsfn, move |env| {
+          let done = move |env, res: napi::Result<JsUnknown>| {
+            let _ = tx.send(res.and_then(|value| convert(env, value)));
+          };
+          match call(env) {
+            Ok(value) => then(env, value, done),
+            Err(err) => done(env, Err(err)),
+          }
+        });
+
+        // The task was dropped without running.
+        if status != napi::Status::Ok {
+          return Err(napi::Error::new(
+            status,
+            "Failed to call a JS function from a background thread.".to_string(),
+          ));
+        }
+
+        // Stop waiting if the JS environment is torn down, e.g. when a worker is terminated while a
+        // promise is pending. No more JS runs after that, so the borrowed data is no longer accessed.
+        loop {
+          match rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(res) => return res,
+            Err(RecvTimeoutError::Timeout) if !tsfn.is_aborted() => continue,
+            // If the JS thread shuts down before the task runs, or the promise is garbage collected without
+            // settling, the sender is dropped without sending a result.
+            Err(_) => {
+              return Err(napi::Error::from_reason(
+                "The JS thread did not return a result.".to_string(),
+              ))
+            }
+          }
+        }
+      }
+      #[cfg(target_arch = "wasm32")]
+      Mode::Asyncify => {
+        let value = await_promise(self.env, call(self.env)?)?;
+        convert(self.env, value)
+      }
+    }
+  }
+}
+
+/// Runs a task on the JS thread. The task may borrow from the calling thread's stack,
+/// so the caller must block until the task has completed or been dropped. If the returned
+/// status is not `Ok`, the task has already been dropped.
+#[cfg(not(target_arch = "wasm32"))]
+fn dispatch<'a>(tsfn: &ThreadsafeFunction<JsTask>, task: impl FnOnce(Env) + 'a) -> napi::Status {
+  let task: Box<dyn FnOnce(Env) + 'a> = Box::new(task);
+  // SAFETY: the task only runs on the JS thread, and the calling thread waits for it to finish.
+  let task = unsafe { std::mem::transmute::<Box<dyn FnOnce(Env) + 'a>, JsTask>(task) };
+  tsfn.call(task, ThreadsafeFunctionCallMode::Blocking)
+}
+
+/// Calls `done` with a value, or with the result of a promise once it settles.
+#[cfg(not(target_arch = "wasm32"))]
+fn then<'a>(env: Env, value: JsUnknown, done: impl FnOnce(Env, napi::Result<JsUnknown>) + 'a) {
+  match value.is_promise() {
+    Ok(true) => {}
+    Ok(false) => return done(env, Ok(value)),
+    Err(err) => return done(env, Err(err)),
+  }
+
+  let done: Done<'a> = Box::new(done);
+  // SAFETY: the calling thread waits until `done` is called, or dropped along with the promise's callbacks.
+  let done = unsafe { std::mem::transmute::<Done<'a>, Done<'static>>(done) };
+
+  // The promise calls at most one of its callbacks, so they share `done`.
+  let done = Rc::new(RefCell::new(Some(done)));
+  let resolve_done = done.clone();
+  let reject_done = done.clone();
+  let res = (|| -> napi::Result<()> {
+    let promise: JsObject = value.try_into()?;
+    let then: JsFunction = promise.get_named_property("then")?;
+    let resolve = env.create_function_from_closure("resolve", move |ctx| {
+      let done = resolve_done.borrow_mut().take();
+      if let Some(done) = done {
+        done(*ctx.env, ctx.get::<JsUnknown>(0));
+      }
+      ctx.env.get_undefined()
+    })?;
+    let reject = env.create_function_from_closure("reject", move |ctx| {
+      let done = reject_done.borrow_mut().take();
+      if let Some(done) = done {
+        done(
+          *ctx.env,
+          ctx.get::<JsUnknown>(0).and_then(|err| Err(napi::Error::from(err))),
+        );
+      }
+      ctx.env.get_undefined()
+    })?;
+    then.call(Some(&promise), &[resolve, reject])?;
+    Ok(())
+  })();
+
+  if let Err(err) = res {
+    let done = done.borrow_mut().take();
+    if let Some(done) = done {
+      done(env, Err(err));
+    }
+  }
+}
+
+// This relies on Binaryen's Asyncify transform to allow Rust to call async JS functions from sync code.
+// See the comments in async.mjs for more details about how this works.
+#[cfg(target_arch = "wasm32")]
+extern "C" {
+  fn await_promise_sync(
+    promise: napi::sys::napi_value,
+    result: *mut napi::sys::napi_value,
+    error: *mut napi::sys::napi_value,
+  );
+}
+
+/// Returns the result of a promise, or the value itself if it is not a promise.
+/// This must only be called within a function called by the driver in async.mjs.
+#[cfg(target_arch = "wasm32")]
+pub fn await_promise(env: Env, value: JsUnknown) -> napi::Result<JsUnknown> {
+  use napi::{NapiRaw, NapiValue};
+
+  if !value.is_promise()? {
+    return Ok(value);
+  }
+
+  let mut result = std::ptr::null_mut();
+  let mut error = std::ptr::null_mut();
+  unsafe { await_promise_sync(value.raw(), &mut result, &mut error) };
+  if !error.is_null() {
+    let error = unsafe { JsUnknown::from_raw(env.raw(), error)? };
+    return Err(napi::Error::from(error));
+  }
+  if result.is_null() {
+    return Err(napi::Error::new(napi::Status::GenericFailure, "No result".into()));
+  }
+
+  unsafe { JsUnknown::from_raw(env.raw(), result) }
+}
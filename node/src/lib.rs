@@ -3,14 +3,25 @@
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
 use at_rule_parser::{AtRule, CustomAtRuleConfig, CustomAtRuleParser};
-use lightningcss::bundler::{BundleErrorKind, Bundler, FileProvider, SourceProvider};
+use lightningcss::bundler::{
+  resolve_css_module_dependencies, AsyncSourceProvider, BoxFuture, BundleErrorKind, Bundler, DependencyGraph,
+  ExternalImportsOptions, FileProvider, MemoryProvider, SourceProvider,
+};
 use lightningcss::css_modules::{CssModuleExports, CssModuleReferences, PatternParseError};
+use lightningcss::declaration::DeclarationBlock;
 use lightningcss::dependencies::{Dependency, DependencyOptions};
 use lightningcss::error::{Error, ErrorLocation, MinifyErrorKind, ParserError, PrinterErrorKind};
+use lightningcss::media_query::MediaList;
+use lightningcss::rules::CssRule;
+use lightningcss::selector::{SelectorList, Specificity};
 use lightningcss::stylesheet::{
-  MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, PseudoClasses, StyleAttribute, StyleSheet,
+  AssetEmitter, CoverageRange, DraftSyntax, InlineAssetsOptions, MinifyOptions, ParserFlags, ParserOptions,
+  PrinterOptions, PseudoClasses, RebaseUrlsOptions, StyleAttribute, StyleSheet, UnsupportedFeatureMode,
+  ValidationMode,
 };
+use lightningcss::substitution::VariableSubstitution;
 use lightningcss::targets::{Browsers, Features, Targets};
+use lightningcss::traits::{ParseWithOptions, ToCss};
 use lightningcss::visitor::Visit;
 use napi::bindgen_prelude::{FromNapiValue, ToNapiValue};
 use parcel_sourcemap::SourceMap;
@@ -20,14 +31,16 @@ use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, RwLock};
 
+use js_caller::JsCaller;
 use transformer::JsVisitor;
 
 mod at_rule_parser;
+mod js_caller;
 #[cfg(not(target_arch = "wasm32"))]
 mod threadsafe_function;
 mod transformer;
 
-use napi::{CallContext, Env, JsObject, JsUnknown};
+use napi::{CallContext, Env, JsObject, JsString, JsUnknown};
 use napi_derive::{js_function, module_exports};
 
 #[derive(Serialize)]
@@ -41,6 +54,10 @@ struct TransformResult<'i> {
   references: Option<CssModuleReferences>,
   dependencies: Option<Vec<Dependency>>,
   warnings: Vec<Warning<'i>>,
+  removed_exports: Option<Vec<String>>,
+  assets: Option<HashMap<String, String>>,
+  coverage_map: Option<Vec<CoverageRange>>,
+  dependency_graph: Option<DependencyGraph>,
 }
 
 impl<'i> TransformResult<'i> {
@@ -63,38 +80,143 @@ impl<'i> TransformResult<'i> {
     obj.set_named_property("references", env.to_js_value(&self.references)?)?;
     obj.set_named_property("dependencies", env.to_js_value(&self.dependencies)?)?;
     obj.set_named_property("warnings", env.to_js_value(&self.warnings)?)?;
+    obj.set_named_property("removedExports", env.to_js_value(&self.removed_exports)?)?;
+    obj.set_named_property("assets", env.to_js_value(&self.assets)?)?;
+    obj.set_named_property("coverageMap", env.to_js_value(&self.coverage_map)?)?;
+    if let Some(dependency_graph) = &self.dependency_graph {
+      obj.set_named_property("dependencyGraph", env.to_js_value(dependency_graph)?)?;
+    }
     Ok(obj.into_unknown())
   }
 }
 
+/// The options passed to `transform` or `transformAsync`, along with the JS callbacks they reference.
+struct TransformTask {
+  config: Config,
+  visitor: Option<JsVisitor>,
+  resolver: Option<JsSyncResolver>,
+  emitter: Option<JsAssetEmitter>,
+}
+
+impl TransformTask {
+  fn new(caller: JsCaller, opts: JsObject) -> napi::Result<Self> {
+    let visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
+      Some(JsVisitor::new(caller.clone(), visitor))
+    } else {
+      None
+    };
+
+    let resolver = if let Ok(resolver) = opts.get_named_property::<JsObject>("resolver") {
+      Some(JsSyncResolver::new(caller.clone(), resolver)?)
+    } else {
+      None
+    };
+
+    let emitter = JsAssetEmitter::from_options(caller.clone(), &opts)?;
+    let config: Config = caller.env().from_js_value(opts)?;
+    Ok(TransformTask {
+      config,
+      visitor,
+      resolver,
+      emitter,
+    })
+  }
+
+  /// Compiles the style sheet. Returns the source code along with the result, which borrows from it.
+  fn compile(&mut self) -> (&str, Result<TransformResult<'_>, CompileError<'_, napi::Error>>) {
+    let TransformTask {
+      config,
+      visitor,
+      resolver,
+      emitter,
+    } = self;
+    let code = unsafe { std::str::from_utf8_unchecked(&config.code) };
+    let emit_asset = emitter.as_ref().map(|emitter| move |path: &Path| emitter.emit(path));
+    let res = compile(
+      code,
+      config,
+      visitor,
+      resolver.as_ref(),
+      emit_asset.as_ref().map(|f| f as _),
+    )
+    .and_then(|res| {
+      JsAssetEmitter::check(emitter.as_ref()).map_err(CompileError::JsError)?;
+      Ok(res)
+    });
+
+    (code, res)
+  }
+}
+
+fn transform_result_into_js(
+  env: Env,
+  code: &str,
+  res: Result<TransformResult, CompileError<napi::Error>>,
+) -> napi::Result<JsUnknown> {
+  match res {
+    Ok(res) => res.into_js(env),
+    Err(err) => Err(err.into_js_error(env, Some(code))?),
+  }
+}
+
 #[js_function(1)]
 fn transform(ctx: CallContext) -> napi::Result<JsUnknown> {
-  use transformer::JsVisitor;
+  let opts = ctx.get::<JsObject>(0)?;
+  let mut task = TransformTask::new(JsCaller::sync(*ctx.env), opts)?;
+  let (code, res) = task.compile();
+  transform_result_into_js(*ctx.env, code, res)
+}
 
+/// Compiles on a background thread, so that visitors and other callbacks may return promises.
+/// Each call into JS blocks the background thread until the returned promise settles.
+#[cfg(not(target_arch = "wasm32"))]
+#[js_function(1)]
+fn transform_async(ctx: CallContext) -> napi::Result<JsObject> {
   let opts = ctx.get::<JsObject>(0)?;
-  let mut visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
-    Some(JsVisitor::new(*ctx.env, visitor))
-  } else {
-    None
-  };
+  let mut task = TransformTask::new(JsCaller::background(*ctx.env)?, opts)?;
+  let (deferred, promise) = ctx.env.create_deferred()?;
+
+  // Like bundleAsync, this must run on a thread that is not managed by Node. See `run_bundle_task`.
+  rayon::spawn(move || {
+    let (code, res) = task.compile();
+    // SAFETY: the result borrows from the task, which is moved along with it and dropped after it.
+    let (code, res) = unsafe {
+      std::mem::transmute::<
+        (&str, Result<TransformResult, CompileError<napi::Error>>),
+        (
+          &'static str,
+          Result<TransformResult<'static>, CompileError<'static, napi::Error>>,
+        ),
+      >((code, res))
+    };
 
-  let config: Config = ctx.env.from_js_value(opts)?;
-  let code = unsafe { std::str::from_utf8_unchecked(&config.code) };
-  let res = compile(code, &config, &mut visitor);
+    deferred.resolve(move |env| {
+      let res = transform_result_into_js(env, code, res);
+      // The task holds references to JS functions, so it must be dropped on the JS thread.
+      drop(task);
+      res
+    });
+  });
 
-  match res {
-    Ok(res) => res.into_js(*ctx.env),
-    Err(err) => Err(err.into_js_error(*ctx.env, Some(code))?),
-  }
+  Ok(promise)
 }
 
+/// In WebAssembly, this is called by the Asyncify driver in async.mjs, which suspends
+/// compilation while waiting for promises returned by visitors and other callbacks.
+#[cfg(target_arch = "wasm32")]
 #[js_function(1)]
-fn transform_style_attribute(ctx: CallContext) -> napi::Result<JsUnknown> {
-  use transformer::JsVisitor;
+fn transform_async(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let opts = ctx.get::<JsObject>(0)?;
+  let mut task = TransformTask::new(JsCaller::asyncify(*ctx.env), opts)?;
+  let (code, res) = task.compile();
+  transform_result_into_js(*ctx.env, code, res)
+}
 
+#[js_function(1)]
+fn transform_style_attribute(ctx: CallContext) -> napi::Result<JsUnknown> {
   let opts = ctx.get::<JsObject>(0)?;
   let mut visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
-    Some(JsVisitor::new(*ctx.env, visitor))
+    Some(JsVisitor::new(JsCaller::sync(*ctx.env), visitor))
   } else {
     None
   };
@@ -109,6 +231,326 @@ fn transform_style_attribute(ctx: CallContext) -> napi::Result<JsUnknown> {
   }
 }
 
+#[js_function(1)]
+fn specificity(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let selector = ctx.get::<JsString>(0)?.into_utf8()?;
+  let code = selector.as_str()?;
+  let res = match Specificity::parse_string(code) {
+    Ok(res) => ctx.env.to_js_value(&res),
+    Err(err) => Err(CompileError::<napi::Error>::from(err).into_js_error(*ctx.env, Some(code))?),
+  };
+  res
+}
+
+#[js_function(1)]
+fn parse_selector(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let selector = ctx.get::<JsString>(0)?.into_utf8()?;
+  let code = selector.as_str()?;
+  let res = match SelectorList::parse_string_with_options(code, ParserOptions::default()) {
+    Ok(selectors) => ctx.env.to_js_value(&selectors),
+    Err(err) => {
+      Err(CompileError::<napi::Error>::from(Error::from(err, "".into())).into_js_error(*ctx.env, Some(code))?)
+    }
+  };
+  res
+}
+
+#[js_function(1)]
+fn parse_rule(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let rule = ctx.get::<JsString>(0)?.into_utf8()?;
+  let code = rule.as_str()?;
+  let res = match CssRule::parse_string(code, ParserOptions::default()) {
+    Ok(rule) => ctx.env.to_js_value(&rule),
+    Err(err) => {
+      Err(CompileError::<napi::Error>::from(Error::from(err, "".into())).into_js_error(*ctx.env, Some(code))?)
+    }
+  };
+  res
+}
+
+#[js_function(1)]
+fn parse_declarations(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let declarations = ctx.get::<JsString>(0)?.into_utf8()?;
+  let code = declarations.as_str()?;
+  let res = match DeclarationBlock::parse_string(code, ParserOptions::default()) {
+    Ok(declarations) => ctx.env.to_js_value(&declarations),
+    Err(err) => {
+      Err(CompileError::<napi::Error>::from(Error::from(err, "".into())).into_js_error(*ctx.env, Some(code))?)
+    }
+  };
+  res
+}
+
+#[js_function(1)]
+fn parse_media_query(ctx: CallContext) -> napi::Result<JsUnknown> {
+  let media = ctx.get::<JsString>(0)?.into_utf8()?;
+  let code = media.as_str()?;
+  let res = match MediaList::parse_string(code) {
+    Ok(media) => ctx.env.to_js_value(&media),
+    Err(err) => {
+      Err(CompileError::<napi::Error>::from(Error::from(err, "".into())).into_js_error(*ctx.env, Some(code))?)
+    }
+  };
+  res
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct PrintConfig {
+  pub minify: Option<bool>,
+}
+
+/// Deserializes an AST node passed from JS, and prints it to a CSS string.
+fn print<T: Deserialize<'static> + ToCss>(ctx: &CallContext) -> napi::Result<JsUnknown> {
+  let value: T = ctx.env.from_js_value(ctx.get::<JsUnknown>(0)?).map(serde_detach::detach)?;
+  let config: PrintConfig = match ctx.get::<JsUnknown>(1) {
+    Ok(options) if options.get_type()? == napi::ValueType::Object => ctx.env.from_js_value(options)?,
+    _ => PrintConfig::default(),
+  };
+  let res = value.to_css_string(PrinterOptions {
+    minify: config.minify.unwrap_or_default(),
+    ..PrinterOptions::default()
+  });
+  match res {
+    Ok(code) => ctx.env.create_string_from_std(code).map(|s| s.into_unknown()),
+    Err(err) => Err(CompileError::<napi::Error>::from(err).into_js_error(*ctx.env, None)?),
+  }
+}
+
+#[js_function(2)]
+fn print_rule(ctx: CallContext) -> napi::Result<JsUnknown> {
+  print::<CssRule>(&ctx)
+}
+
+#[js_function(2)]
+fn print_declarations(ctx: CallContext) -> napi::Result<JsUnknown> {
+  print::<DeclarationBlock>(&ctx)
+}
+
+#[js_function(2)]
+fn print_selector(ctx: CallContext) -> napi::Result<JsUnknown> {
+  print::<SelectorList>(&ctx)
+}
+
+#[js_function(2)]
+fn print_media_query(ctx: CallContext) -> napi::Result<JsUnknown> {
+  print::<MediaList>(&ctx)
+}
+
+/// A source provider that synchronously calls the `read` and `resolve` functions passed to `transform`,
+/// `transformAsync` or `bundle`. In `transform`, it is used to resolve CSS modules `composes` dependencies
+/// on other files. In `transformAsync`, the functions may return promises.
+struct JsSyncResolver {
+  caller: JsCaller,
+  read: Option<napi::Ref<()>>,
+  resolve: Option<napi::Ref<()>>,
+  inputs: Mutex<Vec<*mut String>>,
+}
+
+impl JsSyncResolver {
+  fn new(caller: JsCaller, resolver: JsObject) -> napi::Result<Self> {
+    let env = caller.env();
+    let read = if resolver.has_named_property("read")? {
+      let read = resolver.get_named_property::<napi::JsFunction>("read")?;
+      Some(env.create_reference(read)?)
+    } else {
+      None
+    };
+
+    let resolve = if resolver.has_named_property("resolve")? {
+      let resolve = resolver.get_named_property::<napi::JsFunction>("resolve")?;
+      Some(env.create_reference(resolve)?)
+    } else {
+      None
+    };
+
+    Ok(JsSyncResolver {
+      caller,
+      read,
+      resolve,
+      inputs: Mutex::new(Vec::new()),
+    })
+  }
+}
+
+impl Drop for JsSyncResolver {
+  fn drop(&mut self) {
+    if let Some(read) = &mut self.read {
+      drop(read.unref(self.caller.env()));
+    }
+    if let Some(resolve) = &mut self.resolve {
+      drop(resolve.unref(self.caller.env()));
+    }
+    for ptr in self.inputs.lock().unwrap().iter() {
+      std::mem::drop(unsafe { Box::from_raw(*ptr) })
+    }
+  }
+}
+
+// SAFETY: the resolver only calls into JS through its JsCaller, which runs calls on the JS thread.
+unsafe impl Sync for JsSyncResolver {}
+unsafe impl Send for JsSyncResolver {}
+
+impl SourceProvider for JsSyncResolver {
+  type Error = napi::Error;
+
+  fn read<'a>(&'a self, file: &Path) -> Result<&'a str, Self::Error> {
+    let source = if let Some(read) = &self.read {
+      self.caller.call(
+        |env| {
+          let read: napi::JsFunction = env.get_reference_value_unchecked(read)?;
+          let file = env.create_string(file.to_str().unwrap())?;
+          read.call(None, &[file])
+        },
+        |_, source| {
+          let source: napi::JsString = source.try_into()?;
+          source.into_utf8()?.into_owned()
+        },
+      )?
+    } else {
+      std::fs::read_to_string(file).map_err(|e| napi::Error::from_reason(e.to_string()))?
+    };
+
+    let ptr = Box::into_raw(Box::new(source));
+    self.inputs.lock().unwrap().push(ptr);
+    // SAFETY: this is safe because the pointer is not dropped
+    // until the resolver is, and we never remove from the
+    // list of pointers stored in the vector.
+    Ok(unsafe { &*ptr })
+  }
+
+  fn resolve(&self, specifier: &str, originating_file: &Path) -> Result<PathBuf, Self::Error> {
+    if let Some(resolve) = &self.resolve {
+      self.caller.call(
+        |env| {
+          let resolve: napi::JsFunction = env.get_reference_value_unchecked(resolve)?;
+          let specifier = env.create_string(specifier)?;
+          let originating_file = env.create_string(originating_file.to_str().unwrap())?;
+          resolve.call(None, &[specifier, originating_file])
+        },
+        |_, result| {
+          let result: napi::JsString = result.try_into()?;
+          Ok(PathBuf::from_str(result.into_utf8()?.as_str()?).unwrap())
+        },
+      )
+    } else {
+      Ok(originating_file.with_file_name(specifier))
+    }
+  }
+}
+
+/// Calls the `emitAsset` function passed to `transform` or `bundle` for each file referenced by `url()`.
+/// Errors thrown by the function are rethrown once the style sheet has been printed.
+struct JsAssetEmitter {
+  caller: JsCaller,
+  emit: napi::Ref<()>,
+  error: std::cell::RefCell<Option<napi::Error>>,
+}
+
+impl JsAssetEmitter {
+  fn from_options(caller: JsCaller, opts: &JsObject) -> napi::Result<Option<Self>> {
+    if !opts.has_named_property("emitAsset")? {
+      return Ok(None);
+    }
+
+    let emit = opts.get_named_property::<napi::JsFunction>("emitAsset")?;
+    Ok(Some(JsAssetEmitter {
+      emit: caller.env().create_reference(emit)?,
+      caller,
+      error: std::cell::RefCell::new(None),
+    }))
+  }
+
+  fn emit(&self, path: &Path) -> Option<String> {
+    if self.error.borrow().is_some() {
+      return None;
+    }
+
+    match self.call(path) {
+      Ok(url) => url,
+      Err(err) => {
+        *self.error.borrow_mut() = Some(err);
+        None
+      }
+    }
+  }
+
+  fn call(&self, path: &Path) -> napi::Result<Option<String>> {
+    self.caller.call(
+      |env| {
+        let emit: napi::JsFunction = env.get_reference_value_unchecked(&self.emit)?;
+        let path = env.create_string(path.to_str().unwrap())?;
+        emit.call(None, &[path])
+      },
+      |_, result| match result.get_type()? {
+        napi::ValueType::Null | napi::ValueType::Undefined => Ok(None),
+        _ => {
+          let result: napi::JsString = result.try_into()?;
+          Ok(Some(result.into_utf8()?.into_owned()?))
+        }
+      },
+    )
+  }
+
+  /// Returns the first error thrown by the `emitAsset` function, if any.
+  fn check(emitter: Option<&JsAssetEmitter>) -> napi::Result<()> {
+    match emitter.and_then(|emitter| emitter.error.take()) {
+      Some(err) => Err(err),
+      None => Ok(()),
+    }
+  }
+}
+
+impl Drop for JsAssetEmitter {
+  fn drop(&mut self) {
+    drop(self.emit.unref(self.caller.env()));
+  }
+}
+
+// SAFETY: the emitter only calls into JS through its JsCaller, which runs calls on the JS thread.
+unsafe impl Send for JsAssetEmitter {}
+
+// Used by `bundle` so that all files are loaded on the JS thread. The futures complete immediately.
+impl AsyncSourceProvider for JsSyncResolver {
+  type Error = napi::Error;
+
+  fn read<'a>(&'a self, file: &Path) -> BoxFuture<'a, Result<&'a str, Self::Error>> {
+    let res = SourceProvider::read(self, file);
+    Box::pin(async move { res })
+  }
+
+  fn resolve<'a>(
+    &'a self,
+    specifier: &str,
+    originating_file: &Path,
+  ) -> BoxFuture<'a, Result<PathBuf, Self::Error>> {
+    let res = SourceProvider::resolve(self, specifier, originating_file);
+    Box::pin(async move { res })
+  }
+}
+
+/// Runs a future to completion on the current thread.
+#[cfg(not(target_arch = "wasm32"))]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+  struct ThreadWaker(std::thread::Thread);
+
+  impl std::task::Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+      self.0.unpark();
+    }
+  }
+
+  let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+  let mut cx = std::task::Context::from_waker(&waker);
+  let mut future = std::pin::pin!(future);
+  loop {
+    match future.as_mut().poll(&mut cx) {
+      std::task::Poll::Ready(output) => return output,
+      std::task::Poll::Pending => std::thread::park(),
+    }
+  }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 mod bundle {
   use super::*;
@@ -122,13 +564,22 @@ mod bundle {
 
     let opts = ctx.get::<JsObject>(0)?;
     let mut visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
-      Some(JsVisitor::new(*ctx.env, visitor))
+      Some(JsVisitor::new(JsCaller::sync(*ctx.env), visitor))
     } else {
       None
     };
 
+    let resolver = if let Ok(resolver) = opts.get_named_property::<JsObject>("resolver") {
+      Some(JsSyncResolver::new(JsCaller::sync(*ctx.env), resolver)?)
+    } else {
+      None
+    };
+
+    let emitter = JsAssetEmitter::from_options(JsCaller::sync(*ctx.env), &opts)?;
+    let emit_asset = emitter.as_ref().map(|emitter| move |path: &Path| emitter.emit(path));
+    let emit_asset = emit_asset.as_ref().map(|f| f as &AssetEmitter);
+
     let config: BundleConfig = ctx.env.from_js_value(opts)?;
-    let fs = FileProvider::new();
 
     // This is pretty silly, but works around a rust limitation that you cannot
     // explicitly annotate lifetime bounds on closures.
@@ -139,15 +590,38 @@ mod bundle {
       f
     }
 
-    let res = compile_bundle(
-      &fs,
-      &config,
-      visitor.as_mut().map(|visitor| annotate(|stylesheet| stylesheet.visit(visitor))),
-    );
+    let visit = visitor.as_mut().map(|visitor| annotate(|stylesheet| stylesheet.visit(visitor)));
+    if let Some(resolver) = &resolver {
+      // The resolver calls into JS, so files must be loaded on the JS thread rather than in parallel
+      // on the rayon thread pool. Bundling asynchronously loads them concurrently on the current thread.
+      let res = compile_bundle(
+        resolver,
+        &config,
+        |bundler, entry| block_on(bundler.bundle_async(entry)),
+        visit,
+        emit_asset,
+      );
 
-    match res {
-      Ok(res) => res.into_js(*ctx.env),
-      Err(err) => Err(err.into_js_error(*ctx.env, None)?),
+      match res {
+        Ok(res) => JsAssetEmitter::check(emitter.as_ref()).and_then(|_| res.into_js(*ctx.env)),
+        Err(err) => Err(err.into_js_error(*ctx.env, None)?),
+      }
+    } else if let Some(files) = &config.files {
+      let fs: MemoryProvider = files.iter().collect();
+      let res = compile_bundle(&fs, &config, |bundler, entry| bundler.bundle(entry), visit, emit_asset);
+
+      match res {
+        Ok(res) => JsAssetEmitter::check(emitter.as_ref()).and_then(|_| res.into_js(*ctx.env)),
+        Err(err) => Err(err.into_js_error(*ctx.env, None)?),
+      }
+    } else {
+      let fs = FileProvider::new();
+      let res = compile_bundle(&fs, &config, |bundler, entry| bundler.bundle(entry), visit, emit_asset);
+
+      match res {
+        Ok(res) => JsAssetEmitter::check(emitter.as_ref()).and_then(|_| res.into_js(*ctx.env)),
+        Err(err) => Err(err.into_js_error(*ctx.env, None)?),
+      }
     }
   }
 
@@ -304,7 +778,7 @@ mod bundle {
 
     let opts = ctx.get::<JsObject>(0)?;
     let visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
-      let visitor = JsVisitor::new(*ctx.env, visitor);
+      let visitor = JsVisitor::new(JsCaller::sync(*ctx.env), visitor);
       Some(visitor)
     } else {
       None
@@ -343,6 +817,9 @@ mod bundle {
         inputs: Mutex::new(Vec::new()),
       };
 
+      run_bundle_task(provider, config, visitor, *ctx.env)
+    } else if let Some(files) = &config.files {
+      let provider: MemoryProvider = files.iter().collect();
       run_bundle_task(provider, config, visitor, *ctx.env)
     } else {
       let provider = FileProvider::new();
@@ -388,6 +865,7 @@ mod bundle {
       let res = compile_bundle(
         unsafe { std::mem::transmute::<&'_ P, &'static P>(&provider) },
         &config,
+        |bundler, entry| bundler.bundle(entry),
         tsfn.map(move |tsfn| {
           move |stylesheet: &mut StyleSheet<AtRule>| {
             CHANNEL.with(|channel| {
@@ -408,6 +886,7 @@ mod bundle {
             })
           }
         }),
+        None,
       );
 
       deferred.resolve(move |env| match res {
@@ -423,7 +902,7 @@ mod bundle {
 #[cfg(target_arch = "wasm32")]
 mod bundle {
   use super::*;
-  use napi::{Env, JsFunction, JsString, NapiRaw, NapiValue, Ref};
+  use napi::{Env, JsFunction, JsString, Ref};
   use std::cell::UnsafeCell;
 
   #[js_function(1)]
@@ -432,28 +911,36 @@ mod bundle {
 
     let opts = ctx.get::<JsObject>(0)?;
     let mut visitor = if let Ok(visitor) = opts.get_named_property::<JsObject>("visitor") {
-      Some(JsVisitor::new(*ctx.env, visitor))
+      Some(JsVisitor::new(JsCaller::sync(*ctx.env), visitor))
     } else {
       None
     };
 
-    let resolver = opts.get_named_property::<JsObject>("resolver")?;
-    let read = resolver.get_named_property::<JsFunction>("read")?;
-    let resolve = if resolver.has_named_property("resolve")? {
-      let resolve = resolver.get_named_property::<JsFunction>("resolve")?;
-      Some(ctx.env.create_reference(resolve)?)
+    let emitter = JsAssetEmitter::from_options(JsCaller::sync(*ctx.env), &opts)?;
+    let emit_asset = emitter.as_ref().map(|emitter| move |path: &Path| emitter.emit(path));
+
+    // Without a file system, files are either read through the resolver or from the `files` option.
+    let provider = if opts.has_named_property("resolver")? {
+      let resolver = opts.get_named_property::<JsObject>("resolver")?;
+      let read = resolver.get_named_property::<JsFunction>("read")?;
+      let resolve = if resolver.has_named_property("resolve")? {
+        let resolve = resolver.get_named_property::<JsFunction>("resolve")?;
+        Some(ctx.env.create_reference(resolve)?)
+      } else {
+        None
+      };
+
+      Some(JsSourceProvider {
+        env: ctx.env.clone(),
+        resolve,
+        read: ctx.env.create_reference(read)?,
+        inputs: UnsafeCell::new(Vec::new()),
+      })
     } else {
       None
     };
     let config: BundleConfig = ctx.env.from_js_value(opts)?;
 
-    let provider = JsSourceProvider {
-      env: ctx.env.clone(),
-      resolve,
-      read: ctx.env.create_reference(read)?,
-      inputs: UnsafeCell::new(Vec::new()),
-    };
-
     // This is pretty silly, but works around a rust limitation that you cannot
     // explicitly annotate lifetime bounds on closures.
     fn annotate<'i, 'o, F>(f: F) -> F
@@ -463,15 +950,33 @@ mod bundle {
       f
     }
 
-    let res = compile_bundle(
-      &provider,
-      &config,
-      visitor.as_mut().map(|visitor| annotate(|stylesheet| stylesheet.visit(visitor))),
-    );
+    let visit = visitor.as_mut().map(|visitor| annotate(|stylesheet| stylesheet.visit(visitor)));
+    let emit_asset = emit_asset.as_ref().map(|f| f as _);
+    let memory_provider: MemoryProvider;
+    let res = if let Some(provider) = &provider {
+      compile_bundle(
+        provider,
+        &config,
+        |bundler, entry| bundler.bundle(entry),
+        visit,
+        emit_asset,
+      )
+      .map_err(|err| err.into_js_error(*ctx.env, None))
+    } else {
+      memory_provider = config.files.iter().flatten().collect();
+      compile_bundle(
+        &memory_provider,
+        &config,
+        |bundler, entry| bundler.bundle(entry),
+        visit,
+        emit_asset,
+      )
+      .map_err(|err| err.into_js_error(*ctx.env, None))
+    };
 
     match res {
-      Ok(res) => res.into_js(*ctx.env),
-      Err(err) => Err(err.into_js_error(*ctx.env, None)?),
+      Ok(res) => JsAssetEmitter::check(emitter.as_ref()).and_then(|_| res.into_js(*ctx.env)),
+      Err(err) => Err(err?),
     }
   }
 
@@ -494,33 +999,8 @@ mod bundle {
   unsafe impl Sync for JsSourceProvider {}
   unsafe impl Send for JsSourceProvider {}
 
-  // This relies on Binaryen's Asyncify transform to allow Rust to call async JS functions from sync code.
-  // See the comments in async.mjs for more details about how this works.
-  extern "C" {
-    fn await_promise_sync(
-      promise: napi::sys::napi_value,
-      result: *mut napi::sys::napi_value,
-      error: *mut napi::sys::napi_value,
-    );
-  }
-
-  fn get_result(env: Env, mut value: JsUnknown) -> napi::Result<JsString> {
-    if value.is_promise()? {
-      let mut result = std::ptr::null_mut();
-      let mut error = std::ptr::null_mut();
-      unsafe { await_promise_sync(value.raw(), &mut result, &mut error) };
-      if !error.is_null() {
-        let error = unsafe { JsUnknown::from_raw(env.raw(), error)? };
-        return Err(napi::Error::from(error));
-      }
-      if result.is_null() {
-        return Err(napi::Error::new(napi::Status::GenericFailure, "No result".into()));
-      }
-
-      value = unsafe { JsUnknown::from_raw(env.raw(), result)? };
-    }
-
-    value.try_into()
+  fn get_result(env: Env, value: JsUnknown) -> napi::Result<JsString> {
+    js_caller::await_promise(env, value)?.try_into()
   }
 
   impl SourceProvider for JsSourceProvider {
@@ -560,8 +1040,18 @@ mod bundle {
 #[cfg_attr(not(target_arch = "wasm32"), module_exports)]
 fn init(mut exports: JsObject) -> napi::Result<()> {
   exports.create_named_method("transform", transform)?;
+  exports.create_named_method("transformAsync", transform_async)?;
   exports.create_named_method("transformStyleAttribute", transform_style_attribute)?;
   exports.create_named_method("bundle", bundle::bundle)?;
+  exports.create_named_method("specificity", specificity)?;
+  exports.create_named_method("parseSelector", parse_selector)?;
+  exports.create_named_method("parseRule", parse_rule)?;
+  exports.create_named_method("parseDeclarations", parse_declarations)?;
+  exports.create_named_method("parseMediaQuery", parse_media_query)?;
+  exports.create_named_method("printRule", print_rule)?;
+  exports.create_named_method("printDeclarations", print_declarations)?;
+  exports.create_named_method("printSelector", print_selector)?;
+  exports.create_named_method("printMediaQuery", print_media_query)?;
 
   #[cfg(not(target_arch = "wasm32"))]
   {
@@ -626,8 +1116,16 @@ struct Config {
   pub css_modules: Option<CssModulesOption>,
   pub analyze_dependencies: Option<AnalyzeDependenciesOption>,
   pub pseudo_classes: Option<OwnedPseudoClasses>,
+  pub rebase_urls: Option<OwnedRebaseUrls>,
+  pub inline_assets: Option<InlineAssetsConfig>,
+  pub coverage_map: Option<bool>,
   pub unused_symbols: Option<HashSet<String>>,
+  pub inline_custom_properties: Option<HashMap<String, String>>,
+  pub substitute_variables: Option<SubstituteVariablesConfig>,
   pub error_recovery: Option<bool>,
+  pub warn_overridden_declarations: Option<bool>,
+  pub validation: Option<ValidationMode>,
+  pub unsupported_features: Option<UnsupportedFeatureMode>,
   pub custom_at_rules: Option<HashMap<String, CustomAtRuleConfig>>,
 }
 
@@ -656,12 +1154,32 @@ enum CssModulesOption {
 struct CssModulesConfig {
   pattern: Option<String>,
   dashed_idents: Option<bool>,
+  hash_salt: Option<String>,
+  exports_convention: Option<lightningcss::css_modules::ExportsConvention>,
+  animation: Option<bool>,
+  container: Option<bool>,
+  counter_style: Option<bool>,
+  grid: Option<bool>,
+  view_transition_name: Option<bool>,
+  pure: Option<bool>,
+  icss: Option<bool>,
+  used_exports: Option<HashSet<String>>,
+}
+
+impl CssModulesOption {
+  fn used_exports(&self) -> Option<&HashSet<String>> {
+    match self {
+      CssModulesOption::Config(c) => c.used_exports.as_ref(),
+      CssModulesOption::Bool(_) => None,
+    }
+  }
 }
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct BundleConfig {
   pub filename: String,
+  pub files: Option<HashMap<String, String>>,
   pub project_root: Option<String>,
   pub targets: Option<Browsers>,
   #[serde(default)]
@@ -675,8 +1193,16 @@ struct BundleConfig {
   pub css_modules: Option<CssModulesOption>,
   pub analyze_dependencies: Option<AnalyzeDependenciesOption>,
   pub pseudo_classes: Option<OwnedPseudoClasses>,
+  pub rebase_urls: Option<OwnedRebaseUrls>,
+  pub inline_assets: Option<InlineAssetsConfig>,
+  pub coverage_map: Option<bool>,
+  pub external_imports: Option<ExternalImportsConfig>,
   pub unused_symbols: Option<HashSet<String>>,
+  pub inline_custom_properties: Option<HashMap<String, String>>,
   pub error_recovery: Option<bool>,
+  pub warn_overridden_declarations: Option<bool>,
+  pub validation: Option<ValidationMode>,
+  pub unsupported_features: Option<UnsupportedFeatureMode>,
   pub custom_at_rules: Option<HashMap<String, CustomAtRuleConfig>>,
 }
 
@@ -702,11 +1228,100 @@ impl<'a> Into<PseudoClasses<'a>> for &'a OwnedPseudoClasses {
   }
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnedRebaseUrls {
+  pub output_file: String,
+  pub public_path: Option<String>,
+}
+
+impl<'a> From<&'a OwnedRebaseUrls> for RebaseUrlsOptions<'a> {
+  fn from(options: &'a OwnedRebaseUrls) -> RebaseUrlsOptions<'a> {
+    RebaseUrlsOptions {
+      output_file: &options.output_file,
+      public_path: options.public_path.as_deref(),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubstituteVariablesConfig {
+  #[serde(default)]
+  pub variables: HashMap<String, String>,
+  #[serde(default)]
+  pub env: HashMap<String, String>,
+}
+
+impl SubstituteVariablesConfig {
+  fn to_visitor(&self) -> Result<VariableSubstitution, Error<ParserError<'static>>> {
+    let mut substitution = VariableSubstitution::new();
+    for (name, value) in &self.variables {
+      substitution.set_var(name, value)?;
+    }
+    for (name, value) in &self.env {
+      substitution.set_env(name, value)?;
+    }
+    Ok(substitution)
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InlineAssetsConfig {
+  pub max_size: Option<u64>,
+  #[serde(default)]
+  pub include: Vec<String>,
+  #[serde(default)]
+  pub exclude: Vec<String>,
+}
+
+impl From<&InlineAssetsConfig> for InlineAssetsOptions {
+  fn from(config: &InlineAssetsConfig) -> InlineAssetsOptions {
+    InlineAssetsOptions {
+      max_size: config.max_size,
+      include: config.include.clone(),
+      exclude: config.exclude.clone(),
+    }
+  }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExternalImportsConfig {
+  #[serde(default)]
+  pub remote: bool,
+  #[serde(default)]
+  pub include: Vec<String>,
+}
+
+impl From<&ExternalImportsConfig> for ExternalImportsOptions {
+  fn from(config: &ExternalImportsConfig) -> ExternalImportsOptions {
+    ExternalImportsOptions {
+      remote: config.remote,
+      include: config.include.clone(),
+    }
+  }
+}
+
 #[derive(Serialize, Debug, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 struct Drafts {
   #[serde(default)]
   custom_media: bool,
+  nesting: Option<bool>,
+  relative_colors: Option<bool>,
+  color_mix: Option<bool>,
+}
+
+impl Drafts {
+  fn disabled(&self) -> DraftSyntax {
+    let mut disabled = DraftSyntax::empty();
+    disabled.set(DraftSyntax::NESTING, self.nesting == Some(false));
+    disabled.set(DraftSyntax::RELATIVE_COLORS, self.relative_colors == Some(false));
+    disabled.set(DraftSyntax::COLOR_MIX, self.color_mix == Some(false));
+    disabled
+  }
 }
 
 #[derive(Serialize, Debug, Deserialize, Default)]
@@ -718,12 +1333,15 @@ struct NonStandard {
 
 fn compile<'i>(
   code: &'i str,
-  config: &Config,
+  config: &'i Config,
   visitor: &mut Option<JsVisitor>,
+  resolver: Option<&'i JsSyncResolver>,
+  emit_asset: Option<&AssetEmitter>,
 ) -> Result<TransformResult<'i>, CompileError<'i, napi::Error>> {
   let drafts = config.drafts.as_ref();
   let non_standard = config.non_standard.as_ref();
   let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+  let minify_warnings = Arc::new(RwLock::new(Vec::new()));
 
   let filename = config.filename.clone().unwrap_or_default();
   let project_root = config.project_root.as_ref().map(|p| p.as_ref());
@@ -736,41 +1354,60 @@ fn compile<'i>(
     None
   };
 
-  let res = {
+  let (res, removed_exports) = {
     let mut flags = ParserFlags::empty();
     flags.set(ParserFlags::CUSTOM_MEDIA, matches!(drafts, Some(d) if d.custom_media));
     flags.set(
       ParserFlags::DEEP_SELECTOR_COMBINATOR,
       matches!(non_standard, Some(v) if v.deep_selector_combinator),
     );
+    flags.set(
+      ParserFlags::WARN_OVERRIDDEN_DECLARATIONS,
+      config.warn_overridden_declarations.unwrap_or_default(),
+    );
+
+    let css_modules = if let Some(css_modules) = &config.css_modules {
+      match css_modules {
+        CssModulesOption::Bool(true) => Some(lightningcss::css_modules::Config::default()),
+        CssModulesOption::Bool(false) => None,
+        CssModulesOption::Config(c) => Some(lightningcss::css_modules::Config {
+          pattern: if let Some(pattern) = c.pattern.as_ref() {
+            match lightningcss::css_modules::Pattern::parse(pattern) {
+              Ok(p) => p,
+              Err(e) => return Err(CompileError::PatternError(e)),
+            }
+          } else {
+            Default::default()
+          },
+          dashed_idents: c.dashed_idents.unwrap_or_default(),
+          hash_salt: c.hash_salt.as_deref(),
+          exports_convention: c.exports_convention.unwrap_or_default(),
+          animation: c.animation.unwrap_or(true),
+          container: c.container.unwrap_or(true),
+          counter_style: c.counter_style.unwrap_or(true),
+          grid: c.grid.unwrap_or(true),
+          view_transition_name: c.view_transition_name.unwrap_or(true),
+          minimal_names: None,
+          pure: c.pure.unwrap_or_default(),
+          icss: c.icss.unwrap_or_default(),
+        }),
+      }
+    } else {
+      None
+    };
 
     let mut stylesheet = StyleSheet::parse_with(
       &code,
       ParserOptions {
         filename: filename.clone(),
         flags,
-        css_modules: if let Some(css_modules) = &config.css_modules {
-          match css_modules {
-            CssModulesOption::Bool(true) => Some(lightningcss::css_modules::Config::default()),
-            CssModulesOption::Bool(false) => None,
-            CssModulesOption::Config(c) => Some(lightningcss::css_modules::Config {
-              pattern: if let Some(pattern) = c.pattern.as_ref() {
-                match lightningcss::css_modules::Pattern::parse(pattern) {
-                  Ok(p) => p,
-                  Err(e) => return Err(CompileError::PatternError(e)),
-                }
-              } else {
-                Default::default()
-              },
-              dashed_idents: c.dashed_idents.unwrap_or_default(),
-            }),
-          }
-        } else {
-          None
-        },
+        css_modules: css_modules.clone(),
         source_index: 0,
         error_recovery: config.error_recovery.unwrap_or_default(),
         warnings: warnings.clone(),
+        disabled_drafts: drafts.map_or(DraftSyntax::empty(), |d| d.disabled()),
+        validation: config.validation.unwrap_or_default(),
+        registered_properties: HashMap::new(),
       },
       &mut CustomAtRuleParser {
         configs: config.custom_at_rules.clone().unwrap_or_default(),
@@ -781,6 +1418,17 @@ fn compile<'i>(
       stylesheet.visit(visitor).map_err(CompileError::JsError)?;
     }
 
+    if let Some(substitute_variables) = &config.substitute_variables {
+      let mut substitution = substitute_variables.to_visitor().map_err(CompileError::ParseError)?;
+      stylesheet.visit(&mut substitution).unwrap();
+    }
+
+    let removed_exports = config
+      .css_modules
+      .as_ref()
+      .and_then(|c| c.used_exports())
+      .map(|used_exports| stylesheet.tree_shake_css_modules(used_exports).removed_exports);
+
     let targets = Targets {
       browsers: config.targets,
       include: Features::from_bits_truncate(config.include),
@@ -790,9 +1438,13 @@ fn compile<'i>(
     stylesheet.minify(MinifyOptions {
       targets,
       unused_symbols: config.unused_symbols.clone().unwrap_or_default(),
+      inline_custom_properties: config.inline_custom_properties.clone().unwrap_or_default(),
+      unsupported_features: config.unsupported_features.unwrap_or_default(),
+      warnings: Some(minify_warnings.clone()),
+      ..MinifyOptions::default()
     })?;
 
-    stylesheet.to_css(PrinterOptions {
+    let mut res = stylesheet.to_css(PrinterOptions {
       minify: config.minify.unwrap_or_default(),
       source_map: source_map.as_mut(),
       project_root,
@@ -809,7 +1461,19 @@ fn compile<'i>(
         None
       },
       pseudo_classes: config.pseudo_classes.as_ref().map(|p| p.into()),
-    })?
+      math_fallback: None,
+      rem_to_px: None,
+      rebase_urls: config.rebase_urls.as_ref().map(|r| r.into()),
+      inline_assets: config.inline_assets.as_ref().map(|i| i.into()),
+      emit_asset,
+      coverage_map: config.coverage_map.unwrap_or_default(),
+    })?;
+
+    if let (Some(exports), Some(resolver), Some(css_modules)) = (&mut res.exports, resolver, &css_modules) {
+      resolve_css_module_dependencies(exports, &filename, resolver, css_modules, project_root)?;
+    }
+
+    (res, removed_exports)
   };
 
   let map = if let Some(mut source_map) = source_map {
@@ -830,28 +1494,31 @@ fn compile<'i>(
     exports: res.exports,
     references: res.references,
     dependencies: res.dependencies,
-    warnings: warnings.map_or(Vec::new(), |w| {
-      Arc::try_unwrap(w)
-        .unwrap()
-        .into_inner()
-        .unwrap()
-        .into_iter()
-        .map(|w| w.into())
-        .collect()
-    }),
+    assets: res.assets,
+    coverage_map: res.coverage_map,
+    dependency_graph: None,
+    warnings: collect_warnings(warnings, minify_warnings),
+    removed_exports,
   })
 }
 
 fn compile_bundle<
   'i,
   'o,
-  P: SourceProvider,
+  P,
+  E: std::error::Error,
+  B: for<'s> FnOnce(
+    &mut Bundler<'i, 'o, 's, P, CustomAtRuleParser>,
+    &Path,
+  ) -> Result<StyleSheet<'i, 'o, AtRule<'i>>, Error<BundleErrorKind<'i, E>>>,
   F: FnOnce(&mut StyleSheet<'i, 'o, AtRule<'i>>) -> napi::Result<()>,
 >(
   fs: &'i P,
   config: &'o BundleConfig,
+  bundle: B,
   visit: Option<F>,
-) -> Result<TransformResult<'i>, CompileError<'i, P::Error>> {
+  emit_asset: Option<&AssetEmitter>,
+) -> Result<TransformResult<'i>, CompileError<'i, E>> {
   let project_root = config.project_root.as_ref().map(|p| p.as_ref());
   let mut source_map = if config.source_map.unwrap_or_default() {
     Some(SourceMap::new(project_root.unwrap_or("/")))
@@ -859,8 +1526,9 @@ fn compile_bundle<
     None
   };
   let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+  let minify_warnings = Arc::new(RwLock::new(Vec::new()));
 
-  let res = {
+  let (res, removed_exports, dependency_graph) = {
     let drafts = config.drafts.as_ref();
     let non_standard = config.non_standard.as_ref();
     let mut flags = ParserFlags::empty();
@@ -869,6 +1537,10 @@ fn compile_bundle<
       ParserFlags::DEEP_SELECTOR_COMBINATOR,
       matches!(non_standard, Some(v) if v.deep_selector_combinator),
     );
+    flags.set(
+      ParserFlags::WARN_OVERRIDDEN_DECLARATIONS,
+      config.warn_overridden_declarations.unwrap_or_default(),
+    );
 
     let parser_options = ParserOptions {
       flags,
@@ -886,6 +1558,16 @@ fn compile_bundle<
               Default::default()
             },
             dashed_idents: c.dashed_idents.unwrap_or_default(),
+            hash_salt: c.hash_salt.as_deref(),
+            exports_convention: c.exports_convention.unwrap_or_default(),
+            animation: c.animation.unwrap_or(true),
+            container: c.container.unwrap_or(true),
+            counter_style: c.counter_style.unwrap_or(true),
+            grid: c.grid.unwrap_or(true),
+            view_transition_name: c.view_transition_name.unwrap_or(true),
+            minimal_names: None,
+            pure: c.pure.unwrap_or_default(),
+            icss: c.icss.unwrap_or_default(),
           }),
         }
       } else {
@@ -895,6 +1577,9 @@ fn compile_bundle<
       warnings: warnings.clone(),
       filename: String::new(),
       source_index: 0,
+      disabled_drafts: drafts.map_or(DraftSyntax::empty(), |d| d.disabled()),
+      validation: config.validation.unwrap_or_default(),
+      registered_properties: HashMap::new(),
     };
 
     let mut at_rule_parser = CustomAtRuleParser {
@@ -903,12 +1588,22 @@ fn compile_bundle<
 
     let mut bundler =
       Bundler::new_with_at_rule_parser(fs, source_map.as_mut(), parser_options, &mut at_rule_parser);
-    let mut stylesheet = bundler.bundle(Path::new(&config.filename))?;
+    if let Some(external_imports) = &config.external_imports {
+      bundler.set_external_imports(external_imports.into());
+    }
+    let mut stylesheet = bundle(&mut bundler, Path::new(&config.filename))?;
+    let dependency_graph = bundler.dependency_graph();
 
     if let Some(visit) = visit {
       visit(&mut stylesheet).map_err(CompileError::JsError)?;
     }
 
+    let removed_exports = config
+      .css_modules
+      .as_ref()
+      .and_then(|c| c.used_exports())
+      .map(|used_exports| stylesheet.tree_shake_css_modules(used_exports).removed_exports);
+
     let targets = Targets {
       browsers: config.targets,
       include: Features::from_bits_truncate(config.include),
@@ -918,9 +1613,13 @@ fn compile_bundle<
     stylesheet.minify(MinifyOptions {
       targets,
       unused_symbols: config.unused_symbols.clone().unwrap_or_default(),
+      inline_custom_properties: config.inline_custom_properties.clone().unwrap_or_default(),
+      unsupported_features: config.unsupported_features.unwrap_or_default(),
+      warnings: Some(minify_warnings.clone()),
+      ..MinifyOptions::default()
     })?;
 
-    stylesheet.to_css(PrinterOptions {
+    let res = stylesheet.to_css(PrinterOptions {
       minify: config.minify.unwrap_or_default(),
       source_map: source_map.as_mut(),
       project_root,
@@ -937,7 +1636,15 @@ fn compile_bundle<
         None
       },
       pseudo_classes: config.pseudo_classes.as_ref().map(|p| p.into()),
-    })?
+      math_fallback: None,
+      rem_to_px: None,
+      rebase_urls: config.rebase_urls.as_ref().map(|r| r.into()),
+      inline_assets: config.inline_assets.as_ref().map(|i| i.into()),
+      emit_asset,
+      coverage_map: config.coverage_map.unwrap_or_default(),
+    })?;
+
+    (res, removed_exports, dependency_graph)
   };
 
   let map = if let Some(source_map) = &mut source_map {
@@ -952,15 +1659,11 @@ fn compile_bundle<
     exports: res.exports,
     references: res.references,
     dependencies: res.dependencies,
-    warnings: warnings.map_or(Vec::new(), |w| {
-      Arc::try_unwrap(w)
-        .unwrap()
-        .into_inner()
-        .unwrap()
-        .into_iter()
-        .map(|w| w.into())
-        .collect()
-    }),
+    assets: res.assets,
+    coverage_map: res.coverage_map,
+    dependency_graph: Some(dependency_graph),
+    warnings: collect_warnings(warnings, minify_warnings),
+    removed_exports,
   })
 }
 
@@ -981,6 +1684,10 @@ struct AttrConfig {
   pub analyze_dependencies: bool,
   #[serde(default)]
   pub error_recovery: bool,
+  #[serde(default)]
+  pub warn_overridden_declarations: bool,
+  #[serde(default)]
+  pub validation: ValidationMode,
 }
 
 #[derive(Serialize)]
@@ -1010,11 +1717,12 @@ fn compile_attr<'i>(
   config: &AttrConfig,
   visitor: &mut Option<JsVisitor>,
 ) -> Result<AttrResult<'i>, CompileError<'i, napi::Error>> {
-  let warnings = if config.error_recovery {
-    Some(Arc::new(RwLock::new(Vec::new())))
-  } else {
-    None
-  };
+  let warnings =
+    if config.error_recovery || config.warn_overridden_declarations || config.validation == ValidationMode::Warn {
+      Some(Arc::new(RwLock::new(Vec::new())))
+    } else {
+      None
+    };
   let res = {
     let filename = config.filename.clone().unwrap_or_default();
     let mut attr = StyleAttribute::parse(
@@ -1023,6 +1731,12 @@ fn compile_attr<'i>(
         filename,
         error_recovery: config.error_recovery,
         warnings: warnings.clone(),
+        flags: if config.warn_overridden_declarations {
+          ParserFlags::WARN_OVERRIDDEN_DECLARATIONS
+        } else {
+          ParserFlags::empty()
+        },
+        validation: config.validation,
         ..ParserOptions::default()
       },
     )?;
@@ -1052,6 +1766,12 @@ fn compile_attr<'i>(
         None
       },
       pseudo_classes: None,
+      math_fallback: None,
+      rem_to_px: None,
+      rebase_urls: None,
+      inline_assets: None,
+      emit_asset: None,
+      coverage_map: false,
     })?
   };
   Ok(AttrResult {
@@ -1214,10 +1934,17 @@ impl<'i, E: std::error::Error> From<CompileError<'i, E>> for napi::Error {
 struct Warning<'i> {
   message: String,
   #[serde(flatten)]
-  data: ParserError<'i>,
+  data: WarningKind<'i>,
   loc: Option<ErrorLocation>,
 }
 
+#[derive(Serialize)]
+#[serde(untagged)]
+enum WarningKind<'i> {
+  Parser(ParserError<'i>),
+  Minify(MinifyErrorKind),
+}
+
 impl<'i> From<Error<ParserError<'i>>> for Warning<'i> {
   fn from(mut e: Error<ParserError<'i>>) -> Self {
     // Convert to 1-based line numbers.
@@ -1226,8 +1953,45 @@ impl<'i> From<Error<ParserError<'i>>> for Warning<'i> {
     }
     Warning {
       message: e.kind.to_string(),
-      data: e.kind,
+      data: WarningKind::Parser(e.kind),
+      loc: e.loc,
+    }
+  }
+}
+
+impl<'i> From<Error<MinifyErrorKind>> for Warning<'i> {
+  fn from(mut e: Error<MinifyErrorKind>) -> Self {
+    if let Some(loc) = &mut e.loc {
+      loc.line += 1;
+    }
+    Warning {
+      message: e.kind.to_string(),
+      data: WarningKind::Minify(e.kind),
       loc: e.loc,
     }
   }
 }
+
+fn collect_warnings(
+  warnings: Option<Arc<RwLock<Vec<Error<ParserError<'_>>>>>>,
+  minify_warnings: Arc<RwLock<Vec<Error<MinifyErrorKind>>>>,
+) -> Vec<Warning<'_>> {
+  let mut res: Vec<Warning> = warnings.map_or(Vec::new(), |w| {
+    Arc::try_unwrap(w)
+      .unwrap()
+      .into_inner()
+      .unwrap()
+      .into_iter()
+      .map(|w| w.into())
+      .collect()
+  });
+  res.extend(
+    Arc::try_unwrap(minify_warnings)
+      .unwrap()
+      .into_inner()
+      .unwrap()
+      .into_iter()
+      .map(|w| w.into()),
+  );
+  res
+}
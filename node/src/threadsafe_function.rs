@@ -169,10 +169,18 @@ impl<T: 'static> ThreadsafeFunction<T> {
     if self.aborted.load(Ordering::Acquire) {
       return Status::Closing;
     }
-    unsafe {
-      sys::napi_call_threadsafe_function(self.raw_tsfn, Box::into_raw(Box::new(value)) as *mut _, mode.into())
+    let data = Box::into_raw(Box::new(value));
+    let status = unsafe { sys::napi_call_threadsafe_function(self.raw_tsfn, data as *mut _, mode.into()) };
+    if status != sys::Status::napi_ok {
+      // The value was not queued, so it must be dropped here.
+      drop(unsafe { Box::from_raw(data) });
     }
-    .into()
+    status.into()
+  }
+
+  /// Returns whether the environment that created the function has been torn down.
+  pub fn is_aborted(&self) -> bool {
+    self.aborted.load(Ordering::Acquire)
   }
 }
 
@@ -216,6 +224,7 @@ unsafe extern "C" fn call_js_cb<T: 'static, R>(
 {
   // env and/or callback can be null when shutting down
   if raw_env.is_null() {
+    drop(Box::<T>::from_raw(data.cast()));
     return;
   }
 
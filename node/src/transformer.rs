@@ -25,9 +25,10 @@ use serde::{Deserialize, Serialize};
 use smallvec::SmallVec;
 
 use crate::at_rule_parser::AtRule;
+use crate::js_caller::JsCaller;
 
 pub struct JsVisitor {
-  env: Env,
+  caller: JsCaller,
   visit_rule: VisitorsRef,
   rule_map: VisitorsRef,
   property_map: VisitorsRef,
@@ -55,8 +56,8 @@ pub struct JsVisitor {
   types: VisitTypes,
 }
 
-// This is so that the visitor can work with bundleAsync.
-// We ensure that we only call JsVisitor from the main JS thread.
+// This is so that the visitor can work with bundleAsync and transformAsync.
+// All calls into JS go through the JsCaller, which runs them on the main JS thread.
 unsafe impl Send for JsVisitor {}
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -92,6 +93,10 @@ impl Visitors<Ref<()>> {
       exit: self.exit.as_ref().and_then(|p| env.get_reference_value_unchecked(p).ok()),
     }
   }
+
+  fn get_stage<U: napi::NapiValue>(&self, env: &Env, stage: VisitStage) -> Option<U> {
+    self.for_stage(stage).and_then(|p| env.get_reference_value_unchecked(p).ok())
+  }
 }
 
 impl Visitors<JsObject> {
@@ -127,7 +132,7 @@ impl Drop for JsVisitor {
     macro_rules! drop {
       ($id: ident) => {
         if let Some(v) = &mut self.$id {
-          drop(v.unref(self.env));
+          drop(v.unref(self.caller.env()));
         }
       };
     }
@@ -135,10 +140,10 @@ impl Drop for JsVisitor {
     macro_rules! drop_tuple {
       ($id: ident) => {
         if let Some(v) = &mut self.$id.enter {
-          drop(v.unref(self.env));
+          drop(v.unref(self.caller.env()));
         }
         if let Some(v) = &mut self.$id.exit {
-          drop(v.unref(self.env));
+          drop(v.unref(self.caller.env()));
         }
       };
     }
@@ -171,7 +176,8 @@ impl Drop for JsVisitor {
 }
 
 impl JsVisitor {
-  pub fn new(env: Env, visitor: JsObject) -> Self {
+  pub fn new(caller: JsCaller, visitor: JsObject) -> Self {
+    let env = caller.env();
     let mut types = VisitTypes::empty();
     macro_rules! get {
       ($name: literal, $( $t: ident )|+) => {{
@@ -198,7 +204,7 @@ impl JsVisitor {
     }
 
     Self {
-      env,
+      caller,
       visit_rule: VisitorsRef::new(get!("Rule", RULES), get!("RuleExit", RULES)),
       rule_map: VisitorsRef::new(map!("Rule", RULES), get!("RuleExit", RULES)),
       visit_declaration: VisitorsRef::new(get!("Declaration", PROPERTIES), get!("DeclarationExit", PROPERTIES)),
@@ -258,67 +264,66 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
     rules: &mut lightningcss::rules::CssRuleList<'i, AtRule<'i>>,
   ) -> Result<(), Self::Error> {
     if self.types.contains(VisitTypes::RULES) {
-      let env = self.env;
-      let rule_map = self.rule_map.get::<JsObject>(&env);
-      let visit_rule = self.visit_rule.get::<JsFunction>(&env);
-
       visit_list(
+        self,
         rules,
-        |value, stage| {
-          // Use a more specific visitor function if available, but fall back to visit_rule.
-          let name = match value {
-            CssRule::Media(..) => "media",
-            CssRule::Import(..) => "import",
-            CssRule::Style(..) => "style",
-            CssRule::Keyframes(..) => "keyframes",
-            CssRule::FontFace(..) => "font-face",
-            CssRule::FontPaletteValues(..) => "font-palette-values",
-            CssRule::Page(..) => "page",
-            CssRule::Supports(..) => "supports",
-            CssRule::CounterStyle(..) => "counter-style",
-            CssRule::Namespace(..) => "namespace",
-            CssRule::CustomMedia(..) => "custom-media",
-            CssRule::LayerBlock(..) => "layer-block",
-            CssRule::LayerStatement(..) => "layer-statement",
-            CssRule::Property(..) => "property",
-            CssRule::Container(..) => "container",
-            CssRule::Scope(..) => "scope",
-            CssRule::MozDocument(..) => "moz-document",
-            CssRule::Nesting(..) => "nesting",
-            CssRule::Viewport(..) => "viewport",
-            CssRule::StartingStyle(..) => "starting-style",
-            CssRule::Unknown(v) => {
-              let name = v.name.as_ref();
-              if let Some(visit) = rule_map.custom(stage, "unknown", name) {
-                let js_value = env.to_js_value(v)?;
-                let res = visit.call(None, &[js_value])?;
-                return env.from_js_value(res).map(serde_detach::detach);
-              } else {
-                "unknown"
-              }
-            }
-            CssRule::Custom(c) => {
-              let name = c.name.as_ref();
-              if let Some(visit) = rule_map.custom(stage, "custom", name) {
-                let js_value = env.to_js_value(c)?;
-                let res = visit.call(None, &[js_value])?;
-                return env.from_js_value(res).map(serde_detach::detach);
-              } else {
-                "custom"
-              }
-            }
-            CssRule::Ignored => return Ok(None),
-          };
+        |visitor, value, stage| {
+          visitor.caller.call(
+            |env| {
+              let rule_map = visitor.rule_map.get::<JsObject>(&env);
+
+              // Use a more specific visitor function if available, but fall back to visit_rule.
+              let name = match value {
+                CssRule::Media(..) => "media",
+                CssRule::Import(..) => "import",
+                CssRule::Style(..) => "style",
+                CssRule::Keyframes(..) => "keyframes",
+                CssRule::FontFace(..) => "font-face",
+                CssRule::FontPaletteValues(..) => "font-palette-values",
+                CssRule::Page(..) => "page",
+                CssRule::Supports(..) => "supports",
+                CssRule::CounterStyle(..) => "counter-style",
+                CssRule::Namespace(..) => "namespace",
+                CssRule::CustomMedia(..) => "custom-media",
+                CssRule::LayerBlock(..) => "layer-block",
+                CssRule::LayerStatement(..) => "layer-statement",
+                CssRule::Property(..) => "property",
+                CssRule::Container(..) => "container",
+                CssRule::Scope(..) => "scope",
+                CssRule::MozDocument(..) => "moz-document",
+                CssRule::Nesting(..) => "nesting",
+                CssRule::Viewport(..) => "viewport",
+                CssRule::StartingStyle(..) => "starting-style",
+                CssRule::Value(..) => "value",
+                CssRule::Icss(..) => "icss",
+                CssRule::Unknown(v) => {
+                  let name = v.name.as_ref();
+                  if let Some(visit) = rule_map.custom(stage, "unknown", name) {
+                    return call_visitor(env, Some(&visit), v);
+                  } else {
+                    "unknown"
+                  }
+                }
+                CssRule::Custom(c) => {
+                  let name = c.name.as_ref();
+                  if let Some(visit) = rule_map.custom(stage, "custom", name) {
+                    return call_visitor(env, Some(&visit), c);
+                  } else {
+                    "custom"
+                  }
+                }
+                CssRule::Ignored => return call_visitor(env, None, value),
+              };
 
-          if let Some(visit) = rule_map.named(stage, name).as_ref().or(visit_rule.for_stage(stage)) {
-            let js_value = env.to_js_value(value)?;
-            let res = visit.call(None, &[js_value])?;
-            env.from_js_value(res).map(serde_detach::detach)
-          } else {
-            Ok(None)
-          }
+              let visit = rule_map
+                .named(stage, name)
+                .or_else(|| visitor.visit_rule.get_stage(&env, stage));
+              call_visitor(env, visit.as_ref(), value)
+            },
+            |env, res| env.from_js_value(res).map(serde_detach::detach),
+          )
         },
-        |rule| rule.visit_children(self),
+        |visitor, rule| rule.visit_children(visitor),
       )?;
 
       Ok(())
@@ -332,23 +337,12 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
     decls: &mut lightningcss::declaration::DeclarationBlock<'i>,
   ) -> Result<(), Self::Error> {
     if self.types.contains(VisitTypes::PROPERTIES) {
-      let env = self.env;
-      let property_map = self.property_map.get::<JsObject>(&env);
-      let visit_declaration = self.visit_declaration.get::<JsFunction>(&env);
-      visit_declaration_list(
-        &env,
-        &mut decls.important_declarations,
-        &visit_declaration,
-        &property_map,
-        |property| property.visit_children(self),
-      )?;
-      visit_declaration_list(
-        &env,
-        &mut decls.declarations,
-        &visit_declaration,
-        &property_map,
-        |property| property.visit_children(self),
-      )?;
+      visit_declaration_list(self, &mut decls.important_declarations, |visitor, property| {
+        property.visit_children(visitor)
+      })?;
+      visit_declaration_list(self, &mut decls.declarations, |visitor, property| {
+        property.visit_children(visitor)
+      })?;
       Ok(())
     } else {
       decls.visit_children(self)
@@ -356,58 +350,57 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
   }
 
   fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
-    visit(&self.env, length, &self.visit_length)
+    visit(&self.caller, length, &self.visit_length)
   }
 
   fn visit_angle(&mut self, angle: &mut lightningcss::values::angle::Angle) -> Result<(), Self::Error> {
-    visit(&self.env, angle, &self.visit_angle)
+    visit(&self.caller, angle, &self.visit_angle)
   }
 
   fn visit_ratio(&mut self, ratio: &mut lightningcss::values::ratio::Ratio) -> Result<(), Self::Error> {
-    visit(&self.env, ratio, &self.visit_ratio)
+    visit(&self.caller, ratio, &self.visit_ratio)
   }
 
   fn visit_resolution(
     &mut self,
     resolution: &mut lightningcss::values::resolution::Resolution,
   ) -> Result<(), Self::Error> {
-    visit(&self.env, resolution, &self.visit_resolution)
+    visit(&self.caller, resolution, &self.visit_resolution)
   }
 
   fn visit_time(&mut self, time: &mut lightningcss::values::time::Time) -> Result<(), Self::Error> {
-    visit(&self.env, time, &self.visit_time)
+    visit(&self.caller, time, &self.visit_time)
   }
 
   fn visit_color(&mut self, color: &mut lightningcss::values::color::CssColor) -> Result<(), Self::Error> {
-    visit(&self.env, color, &self.visit_color)
+    visit(&self.caller, color, &self.visit_color)
   }
 
   fn visit_image(&mut self, image: &mut lightningcss::values::image::Image<'i>) -> Result<(), Self::Error> {
-    visit(&self.env, image, &self.visit_image.enter)?;
+    visit(&self.caller, image, &self.visit_image.enter)?;
     image.visit_children(self)?;
-    visit(&self.env, image, &self.visit_image.exit)
+    visit(&self.caller, image, &self.visit_image.exit)
   }
 
   fn visit_url(&mut self, url: &mut lightningcss::values::url::Url<'i>) -> Result<(), Self::Error> {
-    visit(&self.env, url, &self.visit_url)
+    visit(&self.caller, url, &self.visit_url)
   }
 
   fn visit_media_list(&mut self, media: &mut lightningcss::media_query::MediaList<'i>) -> Result<(), Self::Error> {
     if self.types.contains(VisitTypes::MEDIA_QUERIES) {
-      let env = self.env;
-      let visit_media_query = self.visit_media_query.get::<JsFunction>(&env);
       visit_list(
+        self,
         &mut media.media_queries,
-        |value, stage| {
-          if let Some(visit) = visit_media_query.for_stage(stage) {
-            let js_value = env.to_js_value(value)?;
-            let res = visit.call(None, &[js_value])?;
-            env.from_js_value(res).map(serde_detach::detach)
-          } else {
-            Ok(None)
-          }
+        |visitor, value, stage| {
+          visitor.caller.call(
+            |env| {
+              let visit = visitor.visit_media_query.get_stage(&env, stage);
+              call_visitor(env, visit.as_ref(), value)
+            },
+            |env, res| env.from_js_value(res).map(serde_detach::detach),
+          )
         },
-        |q| q.visit_children(self),
+        |visitor, q| q.visit_children(visitor),
       )?;
       Ok(())
     } else {
@@ -417,23 +410,23 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
 
   fn visit_media_feature_value(&mut self, value: &mut MediaFeatureValue<'i>) -> Result<(), Self::Error> {
     if self.types.contains(VisitTypes::ENVIRONMENT_VARIABLES) && matches!(value, MediaFeatureValue::Env(_)) {
-      let env_map = self.env_map.get::<JsObject>(&self.env);
-      let visit_env = self.visit_env.get::<JsFunction>(&self.env);
-      let call = |stage: VisitStage, value: &mut MediaFeatureValue, env: &Env| -> napi::Result<()> {
+      let call = |stage: VisitStage, value: &mut MediaFeatureValue, visitor: &JsVisitor| -> napi::Result<()> {
         let env_var = if let MediaFeatureValue::Env(env) = value {
           env
         } else {
           return Ok(());
         };
-        let visit_type = env_map.named(stage, env_var.name.name());
-        let visit = visit_env.for_stage(stage);
-        let new_value: Option<TokenOrValue> = if let Some(visit) = visit_type.as_ref().or(visit) {
-          let js_value = env.to_js_value(env_var)?;
-          let res = visit.call(None, &[js_value])?;
-          env.from_js_value(res).map(serde_detach::detach)?
-        } else {
-          None
-        };
+        let new_value: Option<TokenOrValue> = visitor.caller.call(
+          |env| {
+            let visit = visitor
+              .env_map
+              .get::<JsObject>(&env)
+              .named(stage, env_var.name.name())
+              .or_else(|| visitor.visit_env.get_stage(&env, stage));
+            call_visitor(env, visit.as_ref(), env_var)
+          },
+          |env, res| env.from_js_value(res).map(serde_detach::detach),
+        )?;
 
         match new_value {
           None => return Ok(()),
@@ -453,9 +446,9 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
         Ok(())
       };
 
-      call(VisitStage::Enter, value, &self.env)?;
+      call(VisitStage::Enter, value, self)?;
       value.visit_children(self)?;
-      call(VisitStage::Exit, value, &self.env)?;
+      call(VisitStage::Exit, value, self)?;
       return Ok(());
     }
 
@@ -466,38 +459,38 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
     &mut self,
     condition: &mut lightningcss::rules::supports::SupportsCondition<'i>,
   ) -> Result<(), Self::Error> {
-    visit(&self.env, condition, &self.visit_supports_condition.enter)?;
+    visit(&self.caller, condition, &self.visit_supports_condition.enter)?;
     condition.visit_children(self)?;
-    visit(&self.env, condition, &self.visit_supports_condition.exit)
+    visit(&self.caller, condition, &self.visit_supports_condition.exit)
   }
 
   fn visit_custom_ident(
     &mut self,
     ident: &mut lightningcss::values::ident::CustomIdent,
   ) -> Result<(), Self::Error> {
-    visit(&self.env, ident, &self.visit_custom_ident)
+    visit(&self.caller, ident, &self.visit_custom_ident)
   }
 
   fn visit_dashed_ident(
     &mut self,
     ident: &mut lightningcss::values::ident::DashedIdent,
   ) -> Result<(), Self::Error> {
-    visit(&self.env, ident, &self.visit_dashed_ident)
+    visit(&self.caller, ident, &self.visit_dashed_ident)
   }
 
   fn visit_selector_list(
     &mut self,
     selectors: &mut lightningcss::selector::SelectorList<'i>,
   ) -> Result<(), Self::Error> {
-    if let Some(visit) = self
-      .visit_selector
-      .as_ref()
-      .and_then(|v| self.env.get_reference_value_unchecked::<JsFunction>(v).ok())
-    {
+    if let Some(visit_selector) = &self.visit_selector {
       map::<_, _, _, true>(&mut selectors.0, |value| {
-        let js_value = self.env.to_js_value(value)?;
-        let res = visit.call(None, &[js_value])?;
-        self.env.from_js_value(res).map(serde_detach::detach)
+        self.caller.call(
+          |env| {
+            let visit = env.get_reference_value_unchecked::<JsFunction>(visit_selector).ok();
+            call_visitor(env, visit.as_ref(), value)
+          },
+          |env, res| env.from_js_value(res).map(serde_detach::detach),
+        )
       })?;
     }
 
@@ -509,64 +502,60 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
     tokens: &mut lightningcss::properties::custom::TokenList<'i>,
   ) -> Result<(), Self::Error> {
     if self.types.contains(VisitTypes::TOKENS) {
-      let env = self.env;
-      let visit_token = self.visit_token.get::<JsFunction>(&env);
-      let token_map = self.token_map.get::<JsObject>(&env);
-      let visit_function = self.visit_function.get::<JsFunction>(&env);
-      let function_map = self.function_map.get::<JsObject>(&env);
-      let visit_variable = self.visit_variable.get::<JsFunction>(&env);
-      let visit_env = self.visit_env.get::<JsFunction>(&env);
-      let env_map = self.env_map.get::<JsObject>(&env);
-
       visit_list(
+        self,
         &mut tokens.0,
-        |value, stage| {
-          let (visit_type, visit) = match value {
-            TokenOrValue::Function(f) => (
-              function_map.named(stage, f.name.0.as_ref()),
-              visit_function.for_stage(stage),
-            ),
-            TokenOrValue::Var(_) => (None, visit_variable.for_stage(stage)),
-            TokenOrValue::Env(e) => (env_map.named(stage, e.name.name()), visit_env.for_stage(stage)),
-            TokenOrValue::Token(t) => {
-              let name = match t {
-                Token::Ident(_) => Some("ident"),
-                Token::AtKeyword(_) => Some("at-keyword"),
-                Token::Hash(_) => Some("hash"),
-                Token::IDHash(_) => Some("id-hash"),
-                Token::String(_) => Some("string"),
-                Token::Number { .. } => Some("number"),
-                Token::Percentage { .. } => Some("percentage"),
-                Token::Dimension { .. } => Some("dimension"),
-                _ => None,
+        |visitor, value, stage| {
+          visitor.caller.call(
+            |env| {
+              let (visit_type, visit) = match value {
+                TokenOrValue::Function(f) => (
+                  visitor.function_map.get::<JsObject>(&env).named(stage, f.name.0.as_ref()),
+                  &visitor.visit_function,
+                ),
+                TokenOrValue::Var(_) => (None, &visitor.visit_variable),
+                TokenOrValue::Env(e) => (
+                  visitor.env_map.get::<JsObject>(&env).named(stage, e.name.name()),
+                  &visitor.visit_env,
+                ),
+                TokenOrValue::Token(t) => {
+                  let name = match t {
+                    Token::Ident(_) => Some("ident"),
+                    Token::AtKeyword(_) => Some("at-keyword"),
+                    Token::Hash(_) => Some("hash"),
+                    Token::IDHash(_) => Some("id-hash"),
+                    Token::String(_) => Some("string"),
+                    Token::Number { .. } => Some("number"),
+                    Token::Percentage { .. } => Some("percentage"),
+                    Token::Dimension { .. } => Some("dimension"),
+                    _ => None,
+                  };
+                  let visit = if let Some(name) = name {
+                    visitor.token_map.get::<JsObject>(&env).named(stage, name)
+                  } else {
+                    None
+                  };
+                  (visit, &visitor.visit_token)
+                }
+                _ => return call_visitor(env, None, value),
               };
-              let visit = if let Some(name) = name {
-                token_map.named(stage, name)
-              } else {
-                None
-              };
-              (visit, visit_token.for_stage(stage))
-            }
-            _ => return Ok(None),
-          };
 
-          if let Some(visit) = visit_type.as_ref().or(visit) {
-            let js_value = match value {
-              TokenOrValue::Function(f) => env.to_js_value(f)?,
-              TokenOrValue::Var(v) => env.to_js_value(v)?,
-              TokenOrValue::Env(v) => env.to_js_value(v)?,
-              TokenOrValue::Token(t) => env.to_js_value(t)?,
-              _ => unreachable!(),
-            };
-
-            let res = visit.call(None, &[js_value])?;
-            let res: Option<TokensOrRaw> = env.from_js_value(res).map(serde_detach::detach)?;
-            Ok(res.map(|r| r.0))
-          } else {
-            Ok(None)
-          }
+              let visit = visit_type.or_else(|| visit.get_stage(&env, stage));
+              match value {
+                TokenOrValue::Function(f) => call_visitor(env, visit.as_ref(), f),
+                TokenOrValue::Var(v) => call_visitor(env, visit.as_ref(), v),
+                TokenOrValue::Env(v) => call_visitor(env, visit.as_ref(), v),
+                TokenOrValue::Token(t) => call_visitor(env, visit.as_ref(), t),
+                _ => unreachable!(),
+              }
+            },
+            |env, res| {
+              let res: Option<TokensOrRaw> = env.from_js_value(res).map(serde_detach::detach)?;
+              Ok(res.map(|r| r.0))
+            },
+          )
         },
-        |value| value.visit_children(self),
+        |visitor, value| value.visit_children(visitor),
       )?;
 
       Ok(())
@@ -576,18 +565,30 @@ impl<'i> Visitor<'i, AtRule<'i>> for JsVisitor {
   }
 }
 
+/// Calls a visitor function with a value, or returns `undefined` if there is no function.
+fn call_visitor<V: Serialize>(env: Env, visit: Option<&JsFunction>, value: &V) -> napi::Result<JsUnknown> {
+  match visit {
+    Some(visit) => {
+      let js_value = env.to_js_value(value)?;
+      visit.call(None, &[js_value])
+    }
+    None => env.get_undefined().map(|v| v.into_unknown()),
+  }
+}
+
 fn visit<V: Serialize + Deserialize<'static>>(
-  env: &Env,
+  caller: &JsCaller,
   value: &mut V,
   visit: &Option<Ref<()>>,
 ) -> napi::Result<()> {
-  if let Some(visit) = visit
-    .as_ref()
-    .and_then(|v| env.get_reference_value_unchecked::<JsFunction>(v).ok())
-  {
-    let js_value = env.to_js_value(value)?;
-    let res = visit.call(None, &[js_value])?;
-    let new_value: Option<V> = env.from_js_value(res).map(serde_detach::detach)?;
+  if let Some(visit) = visit {
+    let new_value: Option<V> = caller.call(
+      |env| {
+        let visit = env.get_reference_value_unchecked::<JsFunction>(visit).ok();
+        call_visitor(env, visit.as_ref(), value)
+      },
+      |env, res| env.from_js_value(res).map(serde_detach::detach),
+    )?;
     match new_value {
       Some(new_value) => *value = new_value,
       None => {}
@@ -597,37 +598,36 @@ fn visit<V: Serialize + Deserialize<'static>>(
   Ok(())
 }
 
-fn visit_declaration_list<'i, C: FnMut(&mut Property<'i>) -> napi::Result<()>>(
-  env: &Env,
+fn visit_declaration_list<'i, C: FnMut(&mut JsVisitor, &mut Property<'i>) -> napi::Result<()>>(
+  visitor: &mut JsVisitor,
   list: &mut Vec<Property<'i>>,
-  visit_declaration: &Visitors<JsFunction>,
-  property_map: &Visitors<JsObject>,
   visit_children: C,
 ) -> napi::Result<()> {
   visit_list(
+    visitor,
     list,
-    |value, stage| {
-      // Use a specific property visitor if available, or fall back to Property visitor.
-      let visit = match value {
-        Property::Custom(v) => {
-          if let Some(visit) = property_map.custom(stage, "custom", v.name.as_ref()) {
-            let js_value = env.to_js_value(v)?;
-            let res = visit.call(None, &[js_value])?;
-            return env.from_js_value(res).map(serde_detach::detach);
-          } else {
-            None
-          }
-        }
-        _ => property_map.named(stage, value.property_id().name()),
-      };
+    |visitor, value, stage| {
+      visitor.caller.call(
+        |env| {
+          let property_map = visitor.property_map.get::<JsObject>(&env);
+
+          // Use a specific property visitor if available, or fall back to Property visitor.
+          let visit = match value {
+            Property::Custom(v) => {
+              if let Some(visit) = property_map.custom(stage, "custom", v.name.as_ref()) {
+                return call_visitor(env, Some(&visit), v);
+              } else {
+                None
+              }
+            }
+            _ => property_map.named(stage, value.property_id().name()),
+          };
 
-      if let Some(visit) = visit.as_ref().or(visit_declaration.for_stage(stage)) {
-        let js_value = env.to_js_value(value)?;
-        let res = visit.call(None, &[js_value])?;
-        env.from_js_value(res).map(serde_detach::detach)
-      } else {
-        Ok(None)
-      }
+          let visit = visit.or_else(|| visitor.visit_declaration.get_stage(&env, stage));
+          call_visitor(env, visit.as_ref(), value)
+        },
+        |env, res| env.from_js_value(res).map(serde_detach::detach),
+      )
     },
     visit_children,
   )
@@ -636,33 +636,34 @@ fn visit_declaration_list<'i, C: FnMut(&mut Property<'i>) -> napi::Result<()>>(
 fn visit_list<
   V,
   L: List<V>,
-  F: Fn(&mut V, VisitStage) -> napi::Result<Option<ValueOrVec<V>>>,
-  C: FnMut(&mut V) -> napi::Result<()>,
+  F: Fn(&JsVisitor, &mut V, VisitStage) -> napi::Result<Option<ValueOrVec<V>>>,
+  C: FnMut(&mut JsVisitor, &mut V) -> napi::Result<()>,
 >(
+  visitor: &mut JsVisitor,
   list: &mut L,
   visit: F,
   mut visit_children: C,
 ) -> napi::Result<()> {
   map(list, |value| {
-    let mut new_value: Option<ValueOrVec<V>> = visit(value, VisitStage::Enter)?;
+    let mut new_value: Option<ValueOrVec<V>> = visit(visitor, value, VisitStage::Enter)?;
 
     match &mut new_value {
       Some(ValueOrVec::Value(v)) => {
-        visit_children(v)?;
+        visit_children(visitor, v)?;
 
-        if let Some(val) = visit(v, VisitStage::Exit)? {
+        if let Some(val) = visit(visitor, v, VisitStage::Exit)? {
           new_value = Some(val);
         }
       }
       Some(ValueOrVec::Vec(v)) => {
         map(v, |value| {
-          visit_children(value)?;
-          visit(value, VisitStage::Exit)
+          visit_children(visitor, value)?;
+          visit(visitor, value, VisitStage::Exit)
         })?;
       }
       None => {
-        visit_children(value)?;
-        if let Some(val) = visit(value, VisitStage::Exit)? {
+        visit_children(visitor, value)?;
+        if let Some(val) = visit(visitor, value, VisitStage::Exit)? {
           new_value = Some(val);
         }
       }
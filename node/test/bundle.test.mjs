@@ -3,14 +3,24 @@ import fs from 'fs';
 import { test } from 'uvu';
 import * as assert from 'uvu/assert';
 
-let bundleAsync;
+let bundle, bundleAsync;
 if (process.env.TEST_WASM === 'node') {
-  bundleAsync = (await import('../../wasm/wasm-node.mjs')).bundleAsync;
+  ({ bundle, bundleAsync } = await import('../../wasm/wasm-node.mjs'));
 } else if (process.env.TEST_WASM === 'browser') {
   let wasm = await import('../../wasm/index.mjs');
   await wasm.default();
+  bundle = function (options) {
+    if (!options.files && !options.resolver?.read) {
+      options.resolver = {
+        ...options.resolver,
+        read: (filePath) => fs.readFileSync(filePath, 'utf8')
+      };
+    }
+
+    return wasm.bundle(options);
+  }
   bundleAsync = function (options) {
-    if (!options.resolver?.read) {
+    if (!options.files && !options.resolver?.read) {
       options.resolver = {
         ...options.resolver,
         read: (filePath) => fs.readFileSync(filePath, 'utf8')
@@ -20,7 +30,7 @@ if (process.env.TEST_WASM === 'node') {
     return wasm.bundleAsync(options);
   }
 } else {
-  bundleAsync = (await import('../index.mjs')).bundleAsync;
+  ({ bundle, bundleAsync } = await import('../index.mjs'));
 }
 
 test('resolver', async () => {
@@ -369,6 +379,312 @@ test('resolve return non-string', async () => {
   });
 });
 
+test('sync resolver', () => {
+  const inMemoryFs = new Map(Object.entries({
+    'foo.css': `
+ @import 'root:bar.css';
+
+ .foo { composes: baz from 'root:hello/world.css'; color: red; }
+         `.trim(),
+
+    'bar.css': `
+ .bar { color: green; }
+         `.trim(),
+
+    'hello/world.css': `
+ .baz { color: blue; }
+         `.trim(),
+  }));
+
+  let resolved = [];
+  const { code: buffer, exports } = bundle({
+    filename: 'foo.css',
+    cssModules: {
+      pattern: '[local]'
+    },
+    resolver: {
+      read(file) {
+        const result = inMemoryFs.get(path.normalize(file));
+        if (!result) throw new Error(`Could not find ${file} in ${Array.from(inMemoryFs.keys()).join(', ')}.`);
+        return result;
+      },
+
+      resolve(specifier, originatingFile) {
+        resolved.push([specifier, originatingFile]);
+        return specifier.slice('root:'.length);
+      },
+    },
+  });
+  const code = buffer.toString('utf-8').trim();
+
+  assert.equal(code, `
+.baz {
+  color: #00f;
+}
+
+.bar {
+  color: green;
+}
+
+.foo {
+  color: red;
+}
+  `.trim());
+  assert.equal(exports.foo.composes, [{ type: 'local', name: 'baz' }]);
+  assert.equal(resolved, [
+    ['root:bar.css', 'foo.css'],
+    ['root:hello/world.css', 'foo.css'],
+  ]);
+});
+
+test('sync resolve throw', () => {
+  let error = undefined;
+  try {
+    bundle({
+      filename: 'tests/testdata/foo.css',
+      resolver: {
+        read() {
+          return `@import 'bar.css';`;
+        },
+        resolve(specifier) {
+          throw new Error(`Oh noes! Failed to resolve \`${specifier}\`.`);
+        }
+      }
+    });
+  } catch (err) {
+    error = err;
+  }
+
+  assert.equal(error.message, 'Oh noes! Failed to resolve `bar.css`.');
+});
+
+test('rebase urls', () => {
+  const inMemoryFs = new Map(Object.entries({
+    'src/index.css': `
+ @import 'root:src/components/button.css';
+
+ .a { background: url(images/bg.png); }
+         `.trim(),
+
+    'src/components/button.css': `
+ .button { background: url(../images/button.png); }
+         `.trim(),
+  }));
+
+  const { code: buffer } = bundle({
+    filename: 'src/index.css',
+    minify: true,
+    rebaseUrls: {
+      outputFile: 'dist/index.css'
+    },
+    resolver: {
+      read(file) {
+        return inMemoryFs.get(path.normalize(file));
+      },
+
+      resolve(specifier) {
+        return specifier.slice('root:'.length);
+      },
+    },
+  });
+  const code = buffer.toString('utf-8').trim();
+
+  assert.equal(code, '.button{background:url(../src/images/button.png)}.a{background:url(../src/images/bg.png)}');
+});
+
+test('in-memory files', async () => {
+  const files = {
+    'src/index.css': `
+ @import './components/button.css';
+
+ .a { color: red; }
+         `.trim(),
+
+    'src/components/button.css': `
+ @import '../theme/colors.css';
+ .button { color: green; }
+         `.trim(),
+
+    'src/theme/colors.css': `
+ .colors { color: blue; }
+         `.trim(),
+  };
+
+  let { code: buffer } = bundle({
+    filename: 'src/index.css',
+    minify: true,
+    files
+  });
+  assert.equal(buffer.toString('utf-8').trim(), '.colors{color:#00f}.button{color:green}.a{color:red}');
+
+  ({ code: buffer } = await bundleAsync({
+    filename: 'src/index.css',
+    minify: true,
+    files
+  }));
+  assert.equal(buffer.toString('utf-8').trim(), '.colors{color:#00f}.button{color:green}.a{color:red}');
+
+  let error = undefined;
+  try {
+    bundle({
+      filename: 'src/missing.css',
+      files
+    });
+  } catch (err) {
+    error = err;
+  }
+
+  assert.equal(error.message, 'File not found: src/missing.css');
+});
+
+test('dependency graph', () => {
+  const { dependencyGraph } = bundle({
+    filename: 'src/index.css',
+    files: {
+      'src/index.css': `@import 'components/button.css' print;
+.a { background: url(images/bg.png); }`,
+      'src/components/button.css': `.button { background: url(../images/button.png); }`,
+    }
+  });
+
+  assert.equal(dependencyGraph, {
+    files: [
+      {
+        file: 'src/index.css',
+        imports: [
+          {
+            url: 'components/button.css',
+            file: path.normalize('src/components/button.css'),
+            layer: null,
+            supports: null,
+            media: 'print',
+            loc: { line: 1, column: 1 }
+          }
+        ],
+        cssModules: [],
+        urls: [
+          {
+            url: 'images/bg.png',
+            file: path.normalize('src/images/bg.png'),
+            loc: { line: 2, column: 18 }
+          }
+        ]
+      },
+      {
+        file: path.normalize('src/components/button.css'),
+        imports: [],
+        cssModules: [],
+        urls: [
+          {
+            url: '../images/button.png',
+            file: path.normalize('src/images/button.png'),
+            loc: { line: 1, column: 24 }
+          }
+        ]
+      }
+    ]
+  });
+});
+
+test('external imports', () => {
+  const inMemoryFs = new Map(Object.entries({
+    'src/index.css': `
+ @import 'https://example.com/reset.css';
+ @import 'root:src/components/button.css' print;
+
+ .a { color: red; }
+         `.trim(),
+
+    'src/components/button.css': `
+ @import 'https://example.com/button.css';
+ .button { color: green; }
+         `.trim(),
+  }));
+
+  const { code: buffer } = bundle({
+    filename: 'src/index.css',
+    minify: true,
+    externalImports: {
+      remote: true
+    },
+    resolver: {
+      read(file) {
+        return inMemoryFs.get(path.normalize(file));
+      },
+
+      resolve(specifier) {
+        return specifier.slice('root:'.length);
+      },
+    },
+  });
+  const code = buffer.toString('utf-8').trim();
+
+  assert.equal(code, '@import "https://example.com/reset.css";@import "https://example.com/button.css" print;@media print{.button{color:green}}.a{color:red}');
+});
+
+test('emit assets', () => {
+  const inMemoryFs = new Map(Object.entries({
+    'src/index.css': `
+ @import 'root:src/components/button.css';
+
+ .a { background: url(images/bg.png); }
+         `.trim(),
+
+    'src/components/button.css': `
+ .button { background: url(../images/bg.png); }
+ .icon { background: url(icon.svg#check); }
+         `.trim(),
+  }));
+
+  let emitted = [];
+  const { code: buffer, assets } = bundle({
+    filename: 'src/index.css',
+    minify: true,
+    emitAsset(filePath) {
+      emitted.push(filePath);
+      return `assets/${path.basename(filePath).replace('.', '.abc123.')}`;
+    },
+    resolver: {
+      read(file) {
+        return inMemoryFs.get(path.normalize(file));
+      },
+
+      resolve(specifier) {
+        return specifier.slice('root:'.length);
+      },
+    },
+  });
+  const code = buffer.toString('utf-8').trim();
+
+  assert.equal(code, '.button{background:url(assets/bg.abc123.png)}.icon{background:url(assets/icon.abc123.svg#check)}.a{background:url(assets/bg.abc123.png)}');
+  assert.equal(emitted, ['src/images/bg.png', 'src/components/icon.svg']);
+  assert.equal(assets, {
+    'src/images/bg.png': 'assets/bg.abc123.png',
+    'src/components/icon.svg': 'assets/icon.abc123.svg',
+  });
+});
+
+test('emit asset throw', () => {
+  let error = undefined;
+  try {
+    bundle({
+      filename: 'tests/testdata/a.css',
+      emitAsset() {
+        throw new Error('Some error');
+      },
+      resolver: {
+        read() {
+          return '.a { background: url(bg.png) }';
+        },
+      },
+    });
+  } catch (err) {
+    error = err;
+  }
+
+  assert.equal(error.message, 'Some error');
+});
+
 test('should throw with location info on syntax errors', async () => {
   let error = undefined;
   try {
@@ -0,0 +1,44 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+
+let parseRule, parseDeclarations, parseMediaQuery, parseSelector, printRule, printDeclarations, printSelector, printMediaQuery;
+if (process.env.TEST_WASM === 'node') {
+  ({parseRule, parseDeclarations, parseMediaQuery, parseSelector, printRule, printDeclarations, printSelector, printMediaQuery} = await import('../../wasm/wasm-node.mjs'));
+} else if (process.env.TEST_WASM === 'browser') {
+  let wasm = await import('../../wasm/index.mjs');
+  await wasm.default();
+  ({parseRule, parseDeclarations, parseMediaQuery, parseSelector, printRule, printDeclarations, printSelector, printMediaQuery} = wasm);
+} else {
+  ({parseRule, parseDeclarations, parseMediaQuery, parseSelector, printRule, printDeclarations, printSelector, printMediaQuery} = await import('../index.mjs'));
+}
+
+test('rules', () => {
+  let rule = parseRule('.foo { color: red }');
+  assert.equal(rule.type, 'style');
+  rule.value.declarations.declarations.push({property: 'width', value: {type: 'length-percentage', value: {type: 'dimension', value: {unit: 'px', value: 10}}}});
+  assert.equal(printRule(rule), '.foo {\n  color: red;\n  width: 10px;\n}');
+  assert.equal(printRule(rule, {minify: true}), '.foo{color:red;width:10px}');
+  assert.throws(() => parseRule('..foo {}'));
+});
+
+test('declarations', () => {
+  let declarations = parseDeclarations('color: red; width: 10px !important');
+  assert.equal(declarations.declarations.length, 1);
+  assert.equal(declarations.importantDeclarations.length, 1);
+  assert.equal(printDeclarations(declarations, {minify: true}), 'color:red;width:10px!important');
+});
+
+test('selectors', () => {
+  let selector = parseSelector('.foo > .bar');
+  selector[0].push({type: 'pseudo-class', kind: 'hover'});
+  assert.equal(printSelector(selector), '.foo > .bar:hover');
+});
+
+test('media queries', () => {
+  let media = parseMediaQuery('screen and (min-width: 100px), print');
+  assert.equal(media.mediaQueries.length, 2);
+  assert.equal(printMediaQuery(media), 'screen and (width >= 100px), print');
+  assert.throws(() => parseMediaQuery('screen and ('));
+});
+
+test.run();
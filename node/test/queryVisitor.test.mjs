@@ -0,0 +1,74 @@
+// @ts-check
+
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+
+let transform, composeVisitors, queryVisitor, parseSelector;
+if (process.env.TEST_WASM === 'node') {
+  ({transform, composeVisitors, queryVisitor, parseSelector} = await import('../../wasm/wasm-node.mjs'));
+} else if (process.env.TEST_WASM === 'browser') {
+  let wasm = await import('../../wasm/index.mjs');
+  await wasm.default();
+  ({transform, composeVisitors, queryVisitor, parseSelector} = wasm);
+} else {
+  ({transform, composeVisitors, queryVisitor, parseSelector} = await import('../index.mjs'));
+}
+
+test('parseSelector', () => {
+  assert.equal(parseSelector('.foo, a'), [
+    [{type: 'class', name: 'foo'}],
+    [{type: 'type', name: 'a'}]
+  ]);
+  assert.throws(() => parseSelector('..foo'));
+});
+
+test('query by selector', () => {
+  let res = transform({
+    filename: 'test.css',
+    minify: true,
+    code: Buffer.from(`
+      .foo, .bar { color: red }
+      .baz { color: red }
+      @media print {
+        .foo { color: red }
+      }
+    `),
+    visitor: queryVisitor({
+      selector: '.foo',
+      Rule(rule) {
+        rule.value.declarations.declarations = [];
+        return rule;
+      }
+    })
+  });
+
+  assert.equal(res.code.toString(), '.baz{color:red}');
+});
+
+test('query by property', () => {
+  let res = transform({
+    filename: 'test.css',
+    minify: true,
+    code: Buffer.from(`
+      .foo { color: red; --color: red; background: red }
+    `),
+    visitor: composeVisitors([
+      queryVisitor({
+        property: 'color',
+        Declaration() {
+          return {property: 'color', value: {type: 'rgb', r: 0, g: 0, b: 255, alpha: 1}};
+        }
+      }),
+      queryVisitor({
+        property: '--color',
+        Declaration(decl) {
+          return {property: 'custom', value: {...decl.value, name: '--accent'}};
+        }
+      })
+    ])
+  });
+
+  assert.equal(res.code.toString(), '.foo{color:#00f;--accent:red;background:red}');
+});
+
+test.run();
@@ -0,0 +1,31 @@
+import { test } from 'uvu';
+import * as assert from 'uvu/assert';
+
+let specificity;
+if (process.env.TEST_WASM === 'node') {
+  ({specificity} = await import('../../wasm/wasm-node.mjs'));
+} else if (process.env.TEST_WASM === 'browser') {
+  let wasm = await import('../../wasm/index.mjs');
+  await wasm.default();
+  ({specificity} = wasm);
+} else {
+  ({specificity} = await import('../index.mjs'));
+}
+
+test('computes specificity', () => {
+  assert.equal(specificity('#foo .bar > a:hover::before'), [{ids: 1, classes: 2, elements: 2}]);
+  assert.equal(specificity('.a, div'), [{ids: 0, classes: 1, elements: 0}, {ids: 0, classes: 0, elements: 1}]);
+});
+
+test('handles :is(), :where(), :not(), and :has()', () => {
+  assert.equal(specificity(':is(#a, .b) span'), [{ids: 1, classes: 0, elements: 1}]);
+  assert.equal(specificity(':where(#a, .b) span'), [{ids: 0, classes: 0, elements: 1}]);
+  assert.equal(specificity(':not(#a, .b)'), [{ids: 1, classes: 0, elements: 0}]);
+  assert.equal(specificity('.a:has(> img, #b)'), [{ids: 1, classes: 1, elements: 0}]);
+});
+
+test('throws on invalid selectors', () => {
+  assert.throws(() => specificity('..foo'));
+});
+
+test.run();
@@ -1,5 +1,8 @@
 import { test } from 'uvu';
 import * as assert from 'uvu/assert';
+import fs from 'fs';
+import os from 'os';
+import path from 'path';
 
 let transform, Features;
 if (process.env.TEST_WASM === 'node') {
@@ -64,4 +67,147 @@ test('can disable prefixing', () => {
   assert.equal(res.code.toString(), '.foo{user-select:none}');
 });
 
+test('can resolve css modules composes dependencies', () => {
+  let res = transform({
+    filename: '/src/a.css',
+    code: Buffer.from(`.a { composes: x from '@styles/b.css'; color: red }`),
+    cssModules: true,
+    resolver: {
+      read(file) {
+        assert.equal(file, '/styles/b.css');
+        return '.x { color: green }';
+      },
+      resolve(specifier, originatingFile) {
+        assert.equal(originatingFile, '/src/a.css');
+        return specifier.replace('@styles', '/styles');
+      }
+    }
+  });
+
+  assert.equal(res.exports.a.composes.length, 1);
+  assert.equal(res.exports.a.composes[0].type, 'local');
+  assert.match(res.exports.a.composes[0].name, /_x$/);
+});
+
+test('can tree shake unused css modules exports', () => {
+  let res = transform({
+    filename: 'test.css',
+    minify: true,
+    code: Buffer.from(`.a { color: red } .b { animation: fade 1s } @keyframes fade { from { opacity: 0 } }`),
+    cssModules: {
+      usedExports: ['a']
+    }
+  });
+
+  assert.equal(res.code.toString(), '.EgL3uq_a{color:red}');
+  assert.equal(res.removedExports, ['b', 'fade']);
+});
+
+test('can inline custom properties', () => {
+  let res = transform({
+    filename: 'test.css',
+    minify: true,
+    code: Buffer.from(`:root { --brand: red; --gap: 8px } .a { color: var(--brand); margin: var(--gap) calc(var(--gap) * 2); padding: var(--other, var(--gap)) }`),
+    inlineCustomProperties: {
+      '--brand': 'green',
+      '--gap': '4px'
+    }
+  });
+
+  assert.equal(res.code.toString(), '.a{color:green;margin:4px 8px;padding:var(--other,4px)}');
+});
+
+test('can warn about overridden declarations', () => {
+  let res = transform({
+    filename: 'test.css',
+    code: Buffer.from(`.a { color: red; color: oklch(50% 0.2 120); width: 10px; width: 20px }`),
+    warnOverriddenDeclarations: true
+  });
+
+  assert.equal(res.warnings.map(w => w.message), [
+    "Declaration of 'width' is overridden by a later declaration"
+  ]);
+});
+
+test('can validate properties and values', () => {
+  let res = transform({
+    filename: 'test.css',
+    code: Buffer.from(`.a { widht: 10px; margin: foo; width: var(--w); -webkit-font-smoothing: auto }`),
+    validation: 'warn'
+  });
+
+  assert.equal(res.warnings.map(w => w.message), [
+    'Unknown property: widht',
+    'Invalid value for property: margin'
+  ]);
+
+  assert.throws(() => {
+    transform({
+      filename: 'test.css',
+      code: Buffer.from(`.a { widht: 10px }`),
+      validation: 'error'
+    });
+  }, /Unknown property: widht/);
+});
+
+test('can warn about features unsupported by the targets', () => {
+  let res = transform({
+    filename: 'test.css',
+    code: Buffer.from(`.a:has(.b) { color: red }`),
+    targets: {
+      safari: 14 << 16
+    },
+    unsupportedFeatures: 'warn'
+  });
+
+  assert.equal(res.warnings, [{
+    message: ':has() is not supported by the browser targets: safari 14 (supported since 15.4)',
+    type: 'UnsupportedFeature',
+    feature: ':has()',
+    id: 'has-selector',
+    browsers: [{browser: 'safari', version: '14', supportedSince: '15.4'}],
+    loc: {filename: 'test.css', line: 1, column: 1}
+  }]);
+});
+
+test('can generate a coverage map', () => {
+  let res = transform({
+    filename: 'test.css',
+    minify: true,
+    coverageMap: true,
+    code: Buffer.from(`.a { color: red }
+@media print {
+  .b { color: blue }
+}`)
+  });
+
+  let code = res.code.toString();
+  assert.equal(code, '.a{color:red}@media print{.b{color:#00f}}');
+  assert.equal(res.coverageMap.map(range => [code.slice(range.start, range.end), range.loc]), [
+    ['.a{color:red}', {source_index: 0, line: 0, column: 1}],
+    ['@media print{.b{color:#00f}}', {source_index: 0, line: 1, column: 1}],
+    ['.b{color:#00f}', {source_index: 0, line: 2, column: 3}]
+  ]);
+});
+
+if (!process.env.TEST_WASM) {
+  test('can inline small assets', () => {
+    let dir = fs.mkdtempSync(path.join(os.tmpdir(), 'lightningcss-'));
+    fs.writeFileSync(path.join(dir, 'icon.svg'), '<svg></svg>');
+    fs.writeFileSync(path.join(dir, 'large.svg'), `<svg>${' '.repeat(100)}</svg>`);
+
+    let res = transform({
+      filename: path.join(dir, 'test.css'),
+      minify: true,
+      code: Buffer.from(`.a { background: url(icon.svg) } .b { background: url(large.svg) }`),
+      inlineAssets: {
+        maxSize: 64
+      }
+    });
+
+    assert.equal(res.code.toString(), '.a{background:url(data:image/svg+xml,%3Csvg%3E%3C/svg%3E)}.b{background:url(large.svg)}');
+    fs.rmSync(dir, { recursive: true });
+  });
+}
+
 test.run();
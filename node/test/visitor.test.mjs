@@ -4,13 +4,13 @@ import { test } from 'uvu';
 import * as assert from 'uvu/assert';
 import fs from 'fs';
 
-let bundle, bundleAsync, transform, transformStyleAttribute;
+let bundle, bundleAsync, transform, transformAsync, transformStyleAttribute;
 if (process.env.TEST_WASM === 'node') {
-  ({ bundle, bundleAsync, transform, transformStyleAttribute } = await import('../../wasm/wasm-node.mjs'));
+  ({ bundle, bundleAsync, transform, transformAsync, transformStyleAttribute } = await import('../../wasm/wasm-node.mjs'));
 } else if (process.env.TEST_WASM === 'browser') {
   let wasm = await import('../../wasm/index.mjs');
   await wasm.default();
-  ({ transform, transformStyleAttribute } = wasm);
+  ({ transform, transformAsync, transformStyleAttribute } = wasm);
   bundle = function(options) {
     return wasm.bundle({
       ...options,
@@ -31,7 +31,7 @@ if (process.env.TEST_WASM === 'node') {
     return wasm.bundleAsync(options);
   }
 } else {
-  ({ bundle, bundleAsync, transform, transformStyleAttribute } = await import('../index.mjs'));
+  ({ bundle, bundleAsync, transform, transformAsync, transformStyleAttribute } = await import('../index.mjs'));
 }
 
 test('px to rem', () => {
@@ -1080,4 +1080,62 @@ test('media query raw', () => {
   assert.equal(res.code.toString(), '.m-1{margin:10px}@media (width>=500px){.sm\\:m-1{margin:10px}}');
 });
 
+test('async visitors', async () => {
+  let res = await transformAsync({
+    filename: 'test.css',
+    minify: true,
+    code: Buffer.from(`
+      .foo {
+        width: 32px;
+        background: url(foo.png);
+      }
+    `),
+    visitor: {
+      async Length(length) {
+        await new Promise(resolve => setTimeout(resolve, 1));
+        return {
+          unit: 'rem',
+          value: length.value / 16
+        };
+      },
+      Url(url) {
+        return Promise.resolve({ ...url, url: 'https://example.com/' + url.url });
+      }
+    }
+  });
+
+  assert.equal(res.code.toString(), '.foo{width:2rem;background:url(https://example.com/foo.png)}');
+});
+
+test('async visitor errors', async () => {
+  let error;
+  try {
+    await transformAsync({
+      filename: 'test.css',
+      code: Buffer.from('.foo { width: 32px }'),
+      visitor: {
+        async Length() {
+          throw new Error('Oops');
+        }
+      }
+    });
+  } catch (err) {
+    error = err;
+  }
+
+  assert.equal(error?.message, 'Oops');
+});
+
+test('promises are not supported by transform', () => {
+  assert.throws(() => transform({
+    filename: 'test.css',
+    code: Buffer.from('.foo { width: 32px }'),
+    visitor: {
+      async Length(length) {
+        return length;
+      }
+    }
+  }), /transformAsync/);
+});
+
 test.run();
@@ -70,6 +70,20 @@ prefixes['any-pseudo'] = {
     })
 }
 
+prefixes['initial-letter'] = {
+  browsers: Object.entries(mdn.css.properties['initial-letter'].__compat.support)
+    .flatMap(([key, value]) => {
+      value = Array.isArray(value) ? value : [value];
+      let prefixed = value.find(v => v.prefix === '-webkit-' && v.version_added);
+      if (prefixed && !value.some(v => !v.prefix && v.version_added)) {
+        key = MDN_BROWSER_MAPPING[key] || key;
+        return [`${key} ${prefixed.version_added}`, `${key} ${latestBrowserVersions[key]}`];
+      }
+
+      return [];
+    })
+}
+
 let flexSpec = {};
 let oldGradient = {};
 let p = new Map();
@@ -289,6 +303,10 @@ let mdnFeatures = {
   spaceSeparatedColorNotation: mdn.css.types.color.rgb.space_separated_parameters.__compat.support,
   textDecorationThicknessPercent: mdn.css.properties['text-decoration-thickness'].percentage.__compat.support,
   textDecorationThicknessShorthand: mdn.css.properties['text-decoration']['text-decoration-thickness'].__compat.support,
+  containerQueries: mdn.css['at-rules'].container.__compat.support,
+  envFunction: mdn.css.types.env.__compat.support,
+  grid: mdn.css.properties.grid.__compat.support,
+  initialLetter: mdn.css.properties['initial-letter'].__compat.support,
   cue: mdn.css.selectors.cue.__compat.support,
   cueFunction: mdn.css.selectors.cue.selector_argument.__compat.support,
   anyPseudo: Object.fromEntries(
@@ -459,6 +477,8 @@ let flags = [
   'DoublePositionGradients',
   'VendorPrefixes',
   'LogicalProperties',
+  'PlaceShorthands',
+  'ExtendedSystemFonts',
   ['Selectors', ['Nesting', 'NotSelectorList', 'DirSelector', 'LangSelectorList', 'IsSelector']],
   ['MediaQueries', ['MediaIntervalSyntax', 'MediaRangeSyntax', 'CustomMediaQueries']],
   ['Colors', ['ColorFunction', 'OklabColors', 'LabColors', 'P3Colors', 'HexAlphaColors', 'SpaceSeparatedColorNotation']],
@@ -466,6 +486,8 @@ let flags = [
 
 let enumify = (f) => f.replace(/^@([a-z])/, (_, x) => 'At' + x.toUpperCase()).replace(/^::([a-z])/, (_, x) => 'PseudoElement' + x.toUpperCase()).replace(/^:([a-z])/, (_, x) => 'PseudoClass' + x.toUpperCase()).replace(/(^|-)([a-z])/g, (_, a, x) => x.toUpperCase())
 
+let featureId = (f) => f.replace(/([a-z0-9])([A-Z])/g, '$1-$2').replace(/([A-Z])([A-Z][a-z])/g, '$1-$2').toLowerCase();
+
 let allBrowsers = Object.keys(browsers).filter(b => !(b in BROWSER_MAPPING)).sort();
 let browsersRs = `pub struct Browsers {
   pub ${allBrowsers.join(': Option<u32>,\n  pub ')}: Option<u32>
@@ -636,6 +658,13 @@ impl Feature {
     }\n`).join('    ')}
     false
   }
+
+  /// Returns a unique id for the feature, e.g. \`has-selector\`.
+  pub fn id(&self) -> &'static str {
+    match self {
+      ${[...compat.keys()].flat().map(enumify).sort().map(name => `Feature::${name} => "${featureId(name)}"`).join(',\n      ')}
+    }
+  }
 }
 `;
 
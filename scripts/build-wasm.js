@@ -16,6 +16,10 @@ let composeVisitors = fs.readFileSync(`${dir}/node/composeVisitors.js`, 'utf8');
 composeVisitors = composeVisitors.replace('module.exports = composeVisitors', 'export { composeVisitors }');
 fs.writeFileSync(`${dir}/wasm/composeVisitors.js`, composeVisitors);
 
+let queryVisitor = fs.readFileSync(`${dir}/node/queryVisitor.js`, 'utf8');
+queryVisitor = queryVisitor.replace('module.exports = createQueryVisitor', 'export { createQueryVisitor }');
+fs.writeFileSync(`${dir}/wasm/queryVisitor.js`, queryVisitor);
+
 let dts = fs.readFileSync(`${dir}/node/index.d.ts`, 'utf8');
 dts = dts.replace(/: Buffer/g, ': Uint8Array');
 dts += `
@@ -282,6 +282,19 @@ where
   complex_selector_specificity(iter).into()
 }
 
+/// Computes the specificity of a complex selector's components, including combinators.
+pub(crate) fn components_specificity<'i, Impl>(components: &[Component<'i, Impl>]) -> u32
+where
+  Impl: SelectorImpl<'i>,
+{
+  components
+    .split(|component| component.as_combinator().is_some())
+    .fold(Specificity::default(), |specificity, compound| {
+      specificity + complex_selector_specificity(compound.iter())
+    })
+    .into()
+}
+
 fn complex_selector_specificity<'i, Impl>(iter: slice::Iter<Component<'i, Impl>>) -> Specificity
 where
   Impl: SelectorImpl<'i>,
@@ -342,12 +355,15 @@ where
         }
         *specificity += Specificity::from(max);
       }
-      Component::Negation(ref list) | Component::Is(ref list) | Component::Any(_, ref list) => {
+      Component::Negation(ref list)
+      | Component::Is(ref list)
+      | Component::Any(_, ref list)
+      | Component::Has(ref list) => {
         // https://drafts.csswg.org/selectors/#specificity-rules:
         //
-        //     The specificity of an :is() pseudo-class is replaced by the
-        //     specificity of the most specific complex selector in its
-        //     selector list argument.
+        //     The specificity of an :is(), :not(), or :has() pseudo-class is
+        //     replaced by the specificity of the most specific complex
+        //     selector in its selector list argument.
         let mut max = 0;
         for selector in &**list {
           max = std::cmp::max(selector.specificity(), max);
@@ -355,7 +371,6 @@ where
         *specificity += Specificity::from(max);
       }
       Component::Where(..)
-      | Component::Has(..)
       | Component::ExplicitUniversalType
       | Component::ExplicitAnyNamespace
       | Component::ExplicitNoNamespace
@@ -6,7 +6,7 @@ use crate::attr::{AttrSelectorOperator, AttrSelectorWithOptionalNamespace};
 use crate::attr::{NamespaceConstraint, ParsedAttrSelectorOperation};
 use crate::attr::{ParsedCaseSensitivity, SELECTOR_WHITESPACE};
 use crate::bloom::BLOOM_HASH_MASK;
-use crate::builder::{SelectorBuilder, SelectorFlags, SpecificityAndFlags};
+use crate::builder::{components_specificity, SelectorBuilder, SelectorFlags, SpecificityAndFlags};
 use crate::context::QuirksMode;
 use crate::sink::Push;
 pub use crate::visitor::SelectorVisitor;
@@ -745,6 +745,12 @@ impl<'i, Impl: SelectorImpl<'i>> Selector<'i, Impl> {
     self.0.is_part()
   }
 
+  /// Recomputes the specificity of the selector, e.g. after its components have been modified.
+  #[inline]
+  pub fn update_specificity(&mut self) {
+    self.0.specificity = components_specificity(&self.1);
+  }
+
   #[inline]
   pub fn append(&mut self, component: Component<'i, Impl>) {
     let index = self
@@ -755,6 +761,69 @@ impl<'i, Impl: SelectorImpl<'i>> Selector<'i, Impl> {
     self.1.insert(index, component);
   }
 
+  /// Prepends a compound selector consisting of the given component,
+  /// joined to the start of this selector with the given combinator.
+  #[inline]
+  pub fn prepend(&mut self, component: Component<'i, Impl>, combinator: Combinator) {
+    // Compound selectors are stored right to left, so the first one is at the end.
+    self.1.push(Component::Combinator(combinator));
+    self.1.push(component);
+  }
+
+  /// Removes the simple selectors for which the given predicate returns false.
+  /// Compound selectors that become empty are replaced with the universal selector,
+  /// except at the start of the selector, where they are dropped along with a
+  /// following descendant combinator.
+  pub fn retain<F>(&mut self, mut f: F)
+  where
+    F: FnMut(&Component<'i, Impl>) -> bool,
+  {
+    let mut components = Vec::with_capacity(self.1.len());
+    // The combinator to the right of the current compound selector.
+    let mut right_combinator = None;
+    let mut is_empty = true;
+    for component in std::mem::take(&mut self.1) {
+      if let Some(combinator) = component.as_combinator() {
+        if is_empty && right_combinator != Some(Combinator::PseudoElement) {
+          components.push(Component::ExplicitUniversalType);
+        }
+        components.push(component);
+        right_combinator = Some(combinator);
+        is_empty = true;
+      } else if f(&component) {
+        components.push(component);
+        is_empty = false;
+      }
+    }
+
+    if is_empty {
+      match right_combinator {
+        Some(Combinator::PseudoElement) => {}
+        Some(Combinator::Descendant) => {
+          components.pop();
+        }
+        _ => components.push(Component::ExplicitUniversalType),
+      }
+    }
+
+    self.1 = components;
+  }
+
+  /// Replaces the components of this selector with the given ones, specified in parse order.
+  /// The specificity is recomputed, and the existing flags are preserved.
+  pub fn replace_components(&mut self, components: Vec<Component<'i, Impl>>) {
+    let mut builder = SelectorBuilder::default();
+    for component in components {
+      if let Some(combinator) = component.as_combinator() {
+        builder.push_combinator(combinator);
+      } else {
+        builder.push_simple_selector(component);
+      }
+    }
+    let (spec, components) = builder.build(self.has_pseudo_element(), self.is_slotted(), self.is_part());
+    *self = Selector(spec, components);
+  }
+
   #[inline]
   pub fn parts(&self) -> Option<&[Impl::Identifier]> {
     if !self.is_part() {
@@ -1376,6 +1445,12 @@ impl<'i, Impl: SelectorImpl<'i>> NthOfSelectorData<'i, Impl> {
     &*self.1
   }
 
+  /// Returns the selector list part of the selector mutably
+  #[inline]
+  pub fn selectors_mut(&mut self) -> &mut [Selector<'i, Impl>] {
+    &mut *self.1
+  }
+
   pub fn clone_selectors(&self) -> Box<[Selector<'i, Impl>]> {
     self.1.clone()
   }
@@ -4,7 +4,9 @@ use crate::{
     ParsedCaseSensitivity,
   },
   builder::SelectorBuilder,
-  parser::{Combinator, Component, LocalName, NthOfSelectorData, NthSelectorData, NthType, Selector},
+  parser::{
+    Combinator, Component, LocalName, NthOfSelectorData, NthSelectorData, NthType, PseudoElement, Selector,
+  },
   SelectorImpl,
 };
 use std::borrow::Cow;
@@ -628,11 +630,9 @@ where
     let mut combinators = self.iter_raw_match_order().rev().filter(|x| x.is_combinator());
     let compound_selectors = self.iter_raw_match_order().as_slice().split(|x| x.is_combinator()).rev();
 
+    // Compounds may be empty, e.g. before a pseudo element combinator in `.foo ::before`, but the
+    // combinator that follows them must still be serialized.
     for compound in compound_selectors {
-      if compound.is_empty() {
-        continue;
-      }
-
       for component in compound {
         seq.serialize_element(component)?;
       }
@@ -690,7 +690,10 @@ where
             match component {
               Component::Slotted(_) => builder.push_combinator(Combinator::SlotAssignment),
               Component::Part(_) => builder.push_combinator(Combinator::Part),
-              Component::PseudoElement(_) => builder.push_combinator(Combinator::PseudoElement),
+              // Unknown pseudo elements are parsed without a combinator, so they can be followed by other selectors.
+              Component::PseudoElement(ref p) if !p.is_unknown() => {
+                builder.push_combinator(Combinator::PseudoElement)
+              }
               _ => {}
             }
             builder.push_simple_selector(component);
@@ -0,0 +1,158 @@
+//! Zero-copy style sheet archives.
+//!
+//! [StyleSheet::to_archive](crate::stylesheet::StyleSheet::to_archive) encodes a style sheet using
+//! [rkyv](https://rkyv.org). The archive can be written to a file, memory mapped, and accessed with
+//! [archived_style_sheet](archived_style_sheet) without deserializing or copying it. This makes it
+//! possible to query large style sheets, such as a design system, in environments where they are loaded
+//! repeatedly, e.g. serverless functions.
+//!
+//! Archives are read-only, and store the same representation as the [postcss](crate::postcss) module,
+//! where selectors, at-rule preludes, and declaration values are strings. To transform an archived style
+//! sheet, parse it again from its CSS.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::archive::archived_style_sheet;
+//! use lightningcss::stylesheet::{ParserOptions, StyleSheet};
+//!
+//! let stylesheet = StyleSheet::parse(".foo { color: red }", ParserOptions::default()).unwrap();
+//! let bytes = stylesheet.to_archive().unwrap();
+//!
+//! // Later, e.g. after memory mapping the bytes from a file...
+//! let archived = archived_style_sheet(&bytes).unwrap();
+//! let declarations = archived.query_property("color");
+//! assert_eq!(declarations[0].value.as_str(), "red");
+//! ```
+
+use crate::postcss::{ArchivedDeclaration, ArchivedNode, ArchivedRoot, ArchivedRule, Root};
+use crate::printer::PrinterOptions;
+use crate::selector::SelectorList;
+use crate::stylesheet::ParserOptions;
+use crate::traits::{ParseWithOptions, ToCss};
+use std::fmt;
+
+/// The version of the archive format. Archives with a different version are rejected.
+const ARCHIVE_VERSION: u32 = 1;
+
+#[derive(rkyv::Archive, rkyv::Serialize)]
+#[archive(check_bytes)]
+pub(crate) struct StyleSheetArchive {
+  version: u32,
+  root: Root,
+}
+
+pub(crate) fn serialize(root: Root) -> rkyv::AlignedVec {
+  let archive = StyleSheetArchive {
+    version: ARCHIVE_VERSION,
+    root,
+  };
+  // Serializing to memory can only fail if the allocator does.
+  rkyv::to_bytes::<_, 4096>(&archive).expect("failed to serialize style sheet archive")
+}
+
+/// Validates an archive produced by [StyleSheet::to_archive](crate::stylesheet::StyleSheet::to_archive),
+/// and returns the archived style sheet without deserializing it.
+///
+/// The bytes must be aligned to 16 bytes, which is the case for memory mapped files and the
+/// [AlignedVec](rkyv::AlignedVec) returned by `to_archive`.
+pub fn archived_style_sheet(bytes: &[u8]) -> Result<&ArchivedRoot, ArchiveError> {
+  let archive = rkyv::check_archived_root::<StyleSheetArchive>(bytes).map_err(|_| ArchiveError::Invalid)?;
+  if archive.version != ARCHIVE_VERSION {
+    return Err(ArchiveError::VersionMismatch);
+  }
+  Ok(&archive.root)
+}
+
+/// An error returned when accessing an invalid archive.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchiveError {
+  /// The data is not a valid style sheet archive.
+  Invalid,
+  /// The archive was produced by an incompatible version of Lightning CSS.
+  VersionMismatch,
+}
+
+impl fmt::Display for ArchiveError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ArchiveError::Invalid => write!(f, "Invalid style sheet archive"),
+      ArchiveError::VersionMismatch => {
+        write!(
+          f,
+          "Style sheet archive was produced by an incompatible version of Lightning CSS"
+        )
+      }
+    }
+  }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl ArchivedRoot {
+  /// Finds the style rules with the given selector list, including nested rules. The selector list
+  /// is normalized before comparing, but must otherwise match the rule's entire selector list.
+  ///
+  /// Returns an empty list if the selector list is invalid.
+  pub fn query_selector(&self, selector: &str) -> Vec<&ArchivedRule> {
+    let selector = match SelectorList::parse_string_with_options(selector, ParserOptions::default())
+      .ok()
+      .and_then(|selectors| selectors.to_css_string(PrinterOptions::default()).ok())
+    {
+      Some(selector) => selector,
+      None => return Vec::new(),
+    };
+
+    let mut res = Vec::new();
+    query_selector(&self.nodes, &selector, &mut res);
+    res
+  }
+
+  /// Finds the declarations of the given property, including in nested rules.
+  pub fn query_property(&self, name: &str) -> Vec<&ArchivedDeclaration> {
+    let mut res = Vec::new();
+    query_property(&self.nodes, name, &mut res);
+    res
+  }
+}
+
+fn child_nodes(node: &ArchivedNode) -> Option<&[ArchivedNode]> {
+  match node {
+    ArchivedNode::Rule(rule) => Some(rule.nodes.as_slice()),
+    ArchivedNode::AtRule(rule) => rule.nodes.as_ref().map(|nodes| nodes.as_slice()),
+    _ => None,
+  }
+}
+
+fn query_selector<'a>(nodes: &'a [ArchivedNode], selector: &str, res: &mut Vec<&'a ArchivedRule>) {
+  for node in nodes {
+    if let ArchivedNode::Rule(rule) = node {
+      if rule.selector.as_str() == selector {
+        res.push(rule);
+      }
+    }
+
+    if let Some(nodes) = child_nodes(node) {
+      query_selector(nodes, selector, res);
+    }
+  }
+}
+
+fn query_property<'a>(nodes: &'a [ArchivedNode], name: &str, res: &mut Vec<&'a ArchivedDeclaration>) {
+  for node in nodes {
+    match node {
+      // Custom property names are case sensitive.
+      ArchivedNode::Declaration(decl)
+        if (name.starts_with("--") && decl.prop.as_str() == name)
+          || (!name.starts_with("--") && decl.prop.as_str().eq_ignore_ascii_case(name)) =>
+      {
+        res.push(decl)
+      }
+      _ => {}
+    }
+
+    if let Some(nodes) = child_nodes(node) {
+      query_property(nodes, name, res);
+    }
+  }
+}
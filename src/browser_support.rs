@@ -0,0 +1,311 @@
+//! Browser support reports.
+//!
+//! [StyleSheet::browser_support](crate::stylesheet::StyleSheet::browser_support) finds the
+//! features used by a style sheet that have compatibility data, such as `:has()`, `@container`,
+//! `oklch()` colors, or viewport units, along with the minimum browser versions that support
+//! all of them without any transforms. This is the inverse of [Targets](crate::targets::Targets):
+//! rather than compiling for a set of browsers, it reports which browsers the source already supports.
+
+use crate::compat::Feature;
+use crate::declaration::DeclarationBlock;
+use crate::media_query::{MediaFeature, MediaList, QueryFeature};
+use crate::properties::custom::EnvironmentVariable;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::selector_features;
+use crate::targets::{format_version, versions, Browsers};
+use crate::values::color::CssColor;
+use crate::values::length::LengthValue;
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::convert::Infallible;
+
+/// A report of the features used by a style sheet, and the browsers that support them, as returned by
+/// [StyleSheet::browser_support](crate::stylesheet::StyleSheet::browser_support).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct BrowserSupportReport {
+  /// The features used by the style sheet, in source order.
+  pub features: Vec<FeatureUsage>,
+  /// The minimum version of each browser that supports all of the features. Browsers that support
+  /// all of the features in every version are omitted.
+  pub minimum_versions: Vec<BrowserVersion>,
+}
+
+/// A feature used by a style sheet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct FeatureUsage {
+  /// A description of the feature, e.g. `:has()` or `@container`.
+  pub feature: String,
+  /// The location of the rule that uses the feature.
+  pub loc: Location,
+  /// The first version of each browser that supports the feature. Browsers that support
+  /// the feature in every version are omitted.
+  pub supported_since: Vec<BrowserVersion>,
+}
+
+/// The version of a browser that is required to support a feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct BrowserVersion {
+  /// The name of the browser, e.g. `safari`.
+  pub browser: String,
+  /// The first version of the browser that supports the feature, or `None` if no version does.
+  pub version: Option<String>,
+}
+
+type Versions = Vec<(&'static str, Option<u32>)>;
+
+#[derive(Default)]
+pub(crate) struct BrowserSupport {
+  features: Vec<(String, Location, Feature)>,
+  supported_since: Vec<(Feature, Versions)>,
+}
+
+impl BrowserSupport {
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>) {
+    for rule in &rules.0 {
+      self.add_rule(rule);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>) {
+    match rule {
+      CssRule::Style(style) => self.add_style_rule(style),
+      CssRule::Nesting(nesting) => {
+        self.add("@nest", nesting.loc, Feature::Nesting);
+        self.add_style_rule(&nesting.style);
+      }
+      CssRule::Media(media) => {
+        self.add_media(&media.query, media.loc);
+        self.add_rules(&media.rules);
+      }
+      CssRule::Import(import) => self.add_media(&import.media, import.loc),
+      CssRule::CustomMedia(custom_media) => {
+        self.add("@custom-media", custom_media.loc, Feature::CustomMediaQueries);
+        self.add_media(&custom_media.query, custom_media.loc);
+      }
+      CssRule::Container(container) => {
+        self.add("@container", container.loc, Feature::ContainerQueries);
+        self.add_rules(&container.rules);
+      }
+      CssRule::Namespace(namespace) => self.add("@namespace", namespace.loc, Feature::Namespaces),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          self.add_declarations(&keyframe.declarations, keyframes.loc);
+        }
+      }
+      CssRule::Page(page) => {
+        self.add_declarations(&page.declarations, page.loc);
+        for margin in &page.rules {
+          self.add_declarations(&margin.declarations, margin.loc);
+        }
+      }
+      CssRule::Viewport(viewport) => self.add_declarations(&viewport.declarations, viewport.loc),
+      _ => {}
+    }
+  }
+
+  fn add_style_rule<R>(&mut self, style: &StyleRule<R>) {
+    let mut features = Vec::new();
+    selector_features(&style.selectors.0, &mut features);
+    for (name, feature) in features {
+      self.add(name, style.loc, feature);
+    }
+
+    self.add_declarations(&style.declarations, style.loc);
+    self.add_rules(&style.rules);
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock, loc: Location) {
+    // Visit a copy of the declarations, since visitors require mutable access.
+    let mut declarations = declarations.clone();
+    let mut visitor = FeatureVisitor {
+      features: Vec::new(),
+      uncompilable_only: false,
+    };
+    let _ = declarations.visit(&mut visitor);
+    for (name, feature) in visitor.features {
+      self.add(name, loc, feature);
+    }
+  }
+
+  fn add_media(&mut self, media: &MediaList, loc: Location) {
+    let mut media = media.clone();
+    let mut visitor = FeatureVisitor {
+      features: Vec::new(),
+      uncompilable_only: false,
+    };
+    let _ = media.visit(&mut visitor);
+    for (name, feature) in visitor.features {
+      self.add(name, loc, feature);
+    }
+  }
+
+  fn add<S: Into<String>>(&mut self, name: S, loc: Location, feature: Feature) {
+    let name = name.into();
+    if self.features.iter().any(|(n, l, _)| *n == name && *l == loc) {
+      return;
+    }
+
+    if !self.supported_since.iter().any(|(f, _)| *f == feature) {
+      self.supported_since.push((feature, supported_since(feature)));
+    }
+    self.features.push((name, loc, feature));
+  }
+
+  pub(crate) fn report(self) -> BrowserSupportReport {
+    let mut minimum: Vec<(&'static str, Option<u32>)> = Vec::new();
+    let features = self
+      .features
+      .into_iter()
+      .map(|(name, loc, feature)| {
+        let (_, versions) = self.supported_since.iter().find(|(f, _)| *f == feature).unwrap();
+        for (browser, version) in versions {
+          match minimum.iter_mut().find(|(b, _)| b == browser) {
+            Some((_, min)) => {
+              *min = match (*min, *version) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => None,
+              }
+            }
+            None => minimum.push((browser, *version)),
+          }
+        }
+
+        FeatureUsage {
+          feature: name,
+          loc,
+          supported_since: to_browser_versions(versions),
+        }
+      })
+      .collect();
+
+    minimum.sort_by_key(|(browser, _)| *browser);
+    BrowserSupportReport {
+      features,
+      minimum_versions: to_browser_versions(&minimum),
+    }
+  }
+}
+
+fn to_browser_versions(versions: &Versions) -> Vec<BrowserVersion> {
+  versions
+    .iter()
+    .map(|(browser, version)| BrowserVersion {
+      browser: (*browser).into(),
+      version: version.map(format_version),
+    })
+    .collect()
+}
+
+/// Returns the first version of each browser that supports the given feature, or `None` if no
+/// version supports it. Browsers that support the feature in all versions are omitted.
+fn supported_since(feature: Feature) -> Versions {
+  let mut res = Vec::new();
+  macro_rules! browser {
+    ($browser: ident) => {
+      let browsers = |version| Browsers {
+        $browser: Some(version),
+        ..Browsers::default()
+      };
+
+      let version = versions().find(|v| feature.is_compatible(browsers(*v)));
+      if version != Some(0) {
+        res.push((stringify!($browser), version));
+      }
+    };
+  }
+
+  browser!(android);
+  browser!(chrome);
+  browser!(edge);
+  browser!(firefox);
+  browser!(ie);
+  browser!(ios_saf);
+  browser!(opera);
+  browser!(safari);
+  browser!(samsung);
+  res
+}
+
+/// Returns the features used by the declarations that cannot be compiled for browsers that do not
+/// support them, such as newer length units and `env()`.
+pub(crate) fn uncompilable_declaration_features(declarations: &DeclarationBlock) -> Vec<(String, Feature)> {
+  let mut declarations = declarations.clone();
+  let mut visitor = FeatureVisitor {
+    features: Vec::new(),
+    uncompilable_only: true,
+  };
+  let _ = declarations.visit(&mut visitor);
+  visitor.features
+}
+
+struct FeatureVisitor {
+  features: Vec<(String, Feature)>,
+  /// Whether to skip features that can be compiled, i.e. colors and media query syntax.
+  uncompilable_only: bool,
+}
+
+impl FeatureVisitor {
+  fn add(&mut self, name: String, feature: Feature) {
+    if !self.features.iter().any(|(n, _)| *n == name) {
+      self.features.push((name, feature));
+    }
+  }
+}
+
+impl<'i> Visitor<'i> for FeatureVisitor {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES
+      | VisitTypes::COLORS
+      | VisitTypes::LENGTHS
+      | VisitTypes::ENVIRONMENT_VARIABLES
+      | VisitTypes::MEDIA_QUERIES
+  }
+
+  fn visit_color(&mut self, color: &mut CssColor) -> Result<(), Self::Error> {
+    if self.uncompilable_only {
+      return Ok(());
+    }
+    if let Some((name, feature)) = color.feature() {
+      self.add(name.into(), feature);
+    }
+    Ok(())
+  }
+
+  fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
+    if let Some(feature) = length.feature() {
+      self.add(format!("{} unit", length.to_unit_value().1), feature);
+    }
+    Ok(())
+  }
+
+  fn visit_environment_variable(&mut self, env: &mut EnvironmentVariable<'i>) -> Result<(), Self::Error> {
+    self.add("env()".into(), Feature::EnvFunction);
+    env.visit_children(self)
+  }
+
+  fn visit_media_feature(&mut self, feature: &mut MediaFeature<'i>) -> Result<(), Self::Error> {
+    if self.uncompilable_only {
+      return Ok(());
+    }
+    match feature {
+      QueryFeature::Range { .. } => self.add("media query range syntax".into(), Feature::MediaRangeSyntax),
+      QueryFeature::Interval { .. } => {
+        self.add("media query interval syntax".into(), Feature::MediaIntervalSyntax)
+      }
+      _ => {}
+    }
+    feature.visit_children(self)
+  }
+}
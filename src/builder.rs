@@ -0,0 +1,377 @@
+//! Builders for constructing style sheets programmatically.
+//!
+//! Rather than formatting a string of CSS and parsing it, the builders in this module
+//! construct the AST directly. Property values are typed via the [Property](crate::properties::Property)
+//! enum, so they are checked at compile time. Selectors, media queries, and other preludes
+//! can either be constructed directly, or parsed from a string.
+//!
+//! Rule builders can be passed directly to the `rule` method of a parent builder, without
+//! calling `build` first.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   builder::{DeclarationBlockBuilder, MediaRuleBuilder, StyleRuleBuilder, StyleSheetBuilder},
+//!   printer::PrinterOptions,
+//!   properties::{display::*, Property},
+//!   values::color::{CssColor, RGBA},
+//! };
+//!
+//! let stylesheet = StyleSheetBuilder::new()
+//!   .rule(
+//!     StyleRuleBuilder::parse(".foo")
+//!       .unwrap()
+//!       .declaration(Property::Color(CssColor::RGBA(RGBA::new(255, 0, 0, 1.0))))
+//!       .important(Property::Display(Display::Keyword(DisplayKeyword::None))),
+//!   )
+//!   .rule(
+//!     MediaRuleBuilder::parse("print").unwrap().rule(
+//!       StyleRuleBuilder::parse(".bar")
+//!         .unwrap()
+//!         .declarations(DeclarationBlockBuilder::new().parse_declaration("width", "10px").unwrap()),
+//!     ),
+//!   )
+//!   .build();
+//!
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{color:red;display:none!important}@media print{.bar{width:10px}}");
+//! ```
+
+use crate::declaration::DeclarationBlock;
+use crate::error::{Error, ParserError};
+use crate::media_query::MediaList;
+use crate::parser::DefaultAtRule;
+use crate::properties::{Property, PropertyId};
+use crate::rules::container::{ContainerCondition, ContainerName, ContainerRule};
+use crate::rules::layer::{LayerBlockRule, LayerName};
+use crate::rules::media::MediaRule;
+use crate::rules::style::StyleRule;
+use crate::rules::supports::{SupportsCondition, SupportsRule};
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::SelectorList;
+use crate::stylesheet::{ParserOptions, StyleSheet};
+use crate::traits::{Parse, ParseWithOptions, ToCss};
+use crate::vendor_prefix::VendorPrefix;
+use cssparser::{ParseError, Parser, ParserInput};
+
+/// The location assigned to rules created by a builder, unless overridden.
+const DEFAULT_LOC: Location = Location {
+  source_index: 0,
+  line: 0,
+  column: 1,
+};
+
+fn parse_string<'i, T>(
+  input: &'i str,
+  parse: impl for<'t> FnOnce(&mut Parser<'i, 't>) -> Result<T, ParseError<'i, ParserError<'i>>>,
+) -> Result<T, Error<ParserError<'i>>> {
+  let mut input = ParserInput::new(input);
+  let mut parser = Parser::new(&mut input);
+  parser.parse_entirely(parse).map_err(|e| Error::from(e, String::new()))
+}
+
+/// A builder for a [DeclarationBlock](DeclarationBlock).
+#[derive(Debug, Clone, Default)]
+pub struct DeclarationBlockBuilder<'i> {
+  block: DeclarationBlock<'i>,
+}
+
+impl<'i> DeclarationBlockBuilder<'i> {
+  /// Creates an empty declaration block builder.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Adds a normal declaration.
+  pub fn declaration(mut self, property: Property<'i>) -> Self {
+    self.block.declarations.push(property);
+    self
+  }
+
+  /// Adds an `!important` declaration.
+  pub fn important(mut self, property: Property<'i>) -> Self {
+    self.block.important_declarations.push(property);
+    self
+  }
+
+  /// Parses a value for the property with the given name, and adds it as a normal declaration.
+  /// As when parsing a style sheet, values that are invalid for a known property are stored as unparsed tokens.
+  pub fn parse_declaration(self, name: &'i str, value: &'i str) -> Result<Self, Error<ParserError<'i>>> {
+    let property = parse_string(value, |input| {
+      Property::parse(PropertyId::from(name), input, &ParserOptions::default())
+    })?;
+    Ok(self.declaration(property))
+  }
+
+  /// Returns the declaration block.
+  pub fn build(self) -> DeclarationBlock<'i> {
+    self.block
+  }
+}
+
+impl<'i> From<DeclarationBlockBuilder<'i>> for DeclarationBlock<'i> {
+  fn from(builder: DeclarationBlockBuilder<'i>) -> Self {
+    builder.build()
+  }
+}
+
+/// A builder for a [StyleRule](StyleRule).
+#[derive(Debug, Clone)]
+pub struct StyleRuleBuilder<'i, R = DefaultAtRule> {
+  rule: StyleRule<'i, R>,
+}
+
+impl<'i, R> StyleRuleBuilder<'i, R> {
+  /// Creates a style rule builder with the given selectors.
+  pub fn new(selectors: SelectorList<'i>) -> Self {
+    StyleRuleBuilder {
+      rule: StyleRule {
+        selectors,
+        vendor_prefix: VendorPrefix::empty(),
+        declarations: DeclarationBlock::new(),
+        rules: CssRuleList(vec![]),
+        loc: DEFAULT_LOC,
+      },
+    }
+  }
+
+  /// Creates a style rule builder with selectors parsed from a string.
+  pub fn parse(selectors: &'i str) -> Result<Self, Error<ParserError<'i>>> {
+    let selectors = parse_string(selectors, |input| {
+      SelectorList::parse_with_options(input, &ParserOptions::default())
+    })?;
+    Ok(Self::new(selectors))
+  }
+
+  /// Adds a normal declaration.
+  pub fn declaration(mut self, property: Property<'i>) -> Self {
+    self.rule.declarations.declarations.push(property);
+    self
+  }
+
+  /// Adds an `!important` declaration.
+  pub fn important(mut self, property: Property<'i>) -> Self {
+    self.rule.declarations.important_declarations.push(property);
+    self
+  }
+
+  /// Parses a value for the property with the given name, and adds it as a normal declaration.
+  pub fn parse_declaration(self, name: &'i str, value: &'i str) -> Result<Self, Error<ParserError<'i>>> {
+    let block = DeclarationBlockBuilder::new().parse_declaration(name, value)?;
+    Ok(self.declarations(block))
+  }
+
+  /// Appends the declarations in the given block, e.g. from a [DeclarationBlockBuilder](DeclarationBlockBuilder).
+  pub fn declarations(mut self, declarations: impl Into<DeclarationBlock<'i>>) -> Self {
+    let declarations = declarations.into();
+    self.rule.declarations.declarations.extend(declarations.declarations);
+    self
+      .rule
+      .declarations
+      .important_declarations
+      .extend(declarations.important_declarations);
+    self
+  }
+
+  /// Adds a nested rule.
+  pub fn rule(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+    self.rule.rules.0.push(rule.into());
+    self
+  }
+
+  /// Sets the source location of the rule.
+  pub fn loc(mut self, loc: Location) -> Self {
+    self.rule.loc = loc;
+    self
+  }
+
+  /// Returns the style rule.
+  pub fn build(self) -> StyleRule<'i, R> {
+    self.rule
+  }
+}
+
+impl<'i, R> From<StyleRuleBuilder<'i, R>> for CssRule<'i, R> {
+  fn from(builder: StyleRuleBuilder<'i, R>) -> Self {
+    CssRule::Style(builder.rule)
+  }
+}
+
+macro_rules! define_group_rule_builder {
+  (
+    $(#[$outer:meta])*
+    $name: ident, $rule: ident, $variant: ident, $field: ident: $ty: ty,
+    $(#[$parse_meta:meta])*
+    |$input: ident| $parse: expr
+  ) => {
+    $(#[$outer])*
+    #[derive(Debug, Clone)]
+    pub struct $name<'i, R = DefaultAtRule> {
+      rule: $rule<'i, R>,
+    }
+
+    impl<'i, R> $name<'i, R> {
+      #[doc = concat!("Creates a builder for a rule with the given ", stringify!($field), ".")]
+      pub fn new($field: $ty) -> Self {
+        $name {
+          rule: $rule {
+            $field,
+            rules: CssRuleList(vec![]),
+            loc: DEFAULT_LOC,
+          },
+        }
+      }
+
+      $(#[$parse_meta])*
+      pub fn parse(input: &'i str) -> Result<Self, Error<ParserError<'i>>> {
+        Ok(Self::new(parse_string(input, |$input| $parse)?))
+      }
+
+      /// Adds a child rule.
+      pub fn rule(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+        self.rule.rules.0.push(rule.into());
+        self
+      }
+
+      /// Sets the source location of the rule.
+      pub fn loc(mut self, loc: Location) -> Self {
+        self.rule.loc = loc;
+        self
+      }
+
+      /// Returns the rule.
+      pub fn build(self) -> $rule<'i, R> {
+        self.rule
+      }
+    }
+
+    impl<'i, R> From<$name<'i, R>> for CssRule<'i, R> {
+      fn from(builder: $name<'i, R>) -> Self {
+        CssRule::$variant(builder.rule)
+      }
+    }
+  };
+}
+
+define_group_rule_builder! {
+  /// A builder for a [MediaRule](MediaRule).
+  MediaRuleBuilder, MediaRule, Media, query: MediaList<'i>,
+  /// Creates a builder for a rule with a media query list parsed from a string.
+  |input| MediaList::parse(input)
+}
+
+define_group_rule_builder! {
+  /// A builder for a [SupportsRule](SupportsRule).
+  SupportsRuleBuilder, SupportsRule, Supports, condition: SupportsCondition<'i>,
+  /// Creates a builder for a rule with a supports condition parsed from a string.
+  |input| SupportsCondition::parse(input)
+}
+
+define_group_rule_builder! {
+  /// A builder for a [LayerBlockRule](LayerBlockRule). An anonymous layer has no name.
+  LayerBlockRuleBuilder, LayerBlockRule, LayerBlock, name: Option<LayerName<'i>>,
+  /// Creates a builder for a rule with a layer name parsed from a string.
+  |input| LayerName::parse(input).map(Some)
+}
+
+/// A builder for a [ContainerRule](ContainerRule).
+#[derive(Debug, Clone)]
+pub struct ContainerRuleBuilder<'i, R = DefaultAtRule> {
+  rule: ContainerRule<'i, R>,
+}
+
+impl<'i, R> ContainerRuleBuilder<'i, R> {
+  /// Creates a builder for a rule with the given container name and condition.
+  pub fn new(name: Option<ContainerName<'i>>, condition: ContainerCondition<'i>) -> Self {
+    ContainerRuleBuilder {
+      rule: ContainerRule {
+        name,
+        condition,
+        rules: CssRuleList(vec![]),
+        loc: DEFAULT_LOC,
+      },
+    }
+  }
+
+  /// Creates a builder for a rule with a container name and condition parsed from a string.
+  pub fn parse(input: &'i str) -> Result<Self, Error<ParserError<'i>>> {
+    let (name, condition) = parse_string(input, |input| {
+      let name = input.try_parse(ContainerName::parse).ok();
+      let condition = ContainerCondition::parse(input)?;
+      Ok((name, condition))
+    })?;
+    Ok(Self::new(name, condition))
+  }
+
+  /// Adds a child rule.
+  pub fn rule(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+    self.rule.rules.0.push(rule.into());
+    self
+  }
+
+  /// Sets the source location of the rule.
+  pub fn loc(mut self, loc: Location) -> Self {
+    self.rule.loc = loc;
+    self
+  }
+
+  /// Returns the rule.
+  pub fn build(self) -> ContainerRule<'i, R> {
+    self.rule
+  }
+}
+
+impl<'i, R> From<ContainerRuleBuilder<'i, R>> for CssRule<'i, R> {
+  fn from(builder: ContainerRuleBuilder<'i, R>) -> Self {
+    CssRule::Container(builder.rule)
+  }
+}
+
+/// A builder for a [StyleSheet](StyleSheet).
+#[derive(Debug)]
+pub struct StyleSheetBuilder<'i, 'o, R = DefaultAtRule> {
+  rules: Vec<CssRule<'i, R>>,
+  options: ParserOptions<'o, 'i>,
+}
+
+impl<'i, 'o> StyleSheetBuilder<'i, 'o> {
+  /// Creates an empty style sheet builder.
+  ///
+  /// To build a style sheet containing custom at rules, use [Default](Default) instead.
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl<'i, 'o, R: ToCss + Clone> StyleSheetBuilder<'i, 'o, R> {
+  /// Sets the options for the style sheet, e.g. the filename used as its source.
+  pub fn options(mut self, options: ParserOptions<'o, 'i>) -> Self {
+    self.options = options;
+    self
+  }
+
+  /// Adds a top-level rule.
+  pub fn rule(mut self, rule: impl Into<CssRule<'i, R>>) -> Self {
+    self.rules.push(rule.into());
+    self
+  }
+
+  /// Returns the style sheet.
+  pub fn build(self) -> StyleSheet<'i, 'o, R> {
+    StyleSheet::new(
+      vec![self.options.filename.clone()],
+      CssRuleList(self.rules),
+      self.options,
+    )
+  }
+}
+
+impl<'i, 'o, R> Default for StyleSheetBuilder<'i, 'o, R> {
+  fn default() -> Self {
+    StyleSheetBuilder {
+      rules: Vec::new(),
+      options: ParserOptions::default(),
+    }
+  }
+}
@@ -23,6 +23,19 @@
 //! let stylesheet = bundler.bundle(Path::new("style.css")).unwrap();
 //! ```
 
+use crate::{
+  css_modules::{self, CssModuleExports, CssModuleReference},
+  error::{Error, ParserError, PrinterErrorKind},
+  media_query::MediaList,
+  printer::normalize_path,
+  rules::{
+    import::ImportRule,
+    media::MediaRule,
+    supports::{SupportsCondition, SupportsRule},
+    CssRule, CssRuleList,
+  },
+  stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+};
 use crate::{
   error::ErrorLocation,
   parser::DefaultAtRuleParser,
@@ -38,28 +51,23 @@ use crate::{
     Location,
   },
   traits::{AtRuleParser, ToCss},
-  values::ident::DashedIdentReference,
-};
-use crate::{
-  error::{Error, ParserError},
-  media_query::MediaList,
-  rules::{
-    import::ImportRule,
-    media::MediaRule,
-    supports::{SupportsCondition, SupportsRule},
-    CssRule, CssRuleList,
+  values::{
+    ident::DashedIdentReference,
+    url::{matches_pattern, Url},
   },
-  stylesheet::{ParserOptions, StyleSheet},
 };
+use cssparser::{Delimiter, Parser, ParserInput, Token};
 use dashmap::DashMap;
 use parcel_sourcemap::SourceMap;
 use rayon::prelude::*;
 use std::{
-  collections::HashSet,
+  collections::{HashMap, HashSet},
   fs,
   path::{Path, PathBuf},
   sync::Mutex,
 };
+#[cfg(feature = "async")]
+use std::{future::Future, pin::Pin, task::Poll};
 
 /// A Bundler combines a CSS file and all imported dependencies together into
 /// a single merged style sheet.
@@ -70,6 +78,7 @@ pub struct Bundler<'a, 'o, 's, P, T: AtRuleParser<'a>> {
   stylesheets: Mutex<Vec<BundleStyleSheet<'a, 'o, T::AtRule>>>,
   options: ParserOptions<'o, 'a>,
   at_rule_parser: Mutex<AtRuleParserValue<'s, T>>,
+  external_imports: ExternalImportsOptions,
 }
 
 enum AtRuleParserValue<'a, T> {
@@ -79,13 +88,138 @@ enum AtRuleParserValue<'a, T> {
 
 struct BundleStyleSheet<'i, 'o, T> {
   stylesheet: Option<StyleSheet<'i, 'o, T>>,
+  code: &'i str,
+  imports: Vec<ImportRule<'i>>,
   dependencies: Vec<u32>,
   css_modules_deps: Vec<u32>,
   parent_source_index: u32,
   parent_dep_index: u32,
+  conditions: Vec<ImportCondition<'i>>,
+  loc: Location,
+}
+
+/// The `layer()`, `supports()`, and media query conditions of an `@import` rule.
+#[derive(Clone, Debug, PartialEq, Default)]
+struct ImportCondition<'i> {
   layer: Option<Option<LayerName<'i>>>,
   supports: Option<SupportsCondition<'i>>,
   media: MediaList<'i>,
+  locs: ConditionLocations,
+}
+
+/// The locations of the `@import` rules that each part of an [ImportCondition] originated from,
+/// which the `@layer`, `@supports`, and `@media` rules wrapping the imported rules are mapped to
+/// in source maps. These are ignored when comparing conditions.
+#[derive(Clone, Copy, Debug, Default)]
+struct ConditionLocations {
+  layer: Option<Location>,
+  supports: Option<Location>,
+  media: Option<Location>,
+}
+
+impl PartialEq for ConditionLocations {
+  fn eq(&self, _: &ConditionLocations) -> bool {
+    true
+  }
+}
+
+impl<'i> ImportCondition<'i> {
+  fn from_import(import: &ImportRule<'i>) -> ImportCondition<'i> {
+    ImportCondition {
+      layer: import.layer.clone(),
+      supports: import.supports.clone(),
+      media: import.media.clone(),
+      locs: ConditionLocations {
+        layer: import.layer.as_ref().map(|_| import.loc),
+        supports: import.supports.as_ref().map(|_| import.loc),
+        media: (!import.media.media_queries.is_empty()).then_some(import.loc),
+      },
+    }
+  }
+
+  /// Combines this condition with the condition of an `@import` rule within the imported file,
+  /// if the result can be expressed as a single condition.
+  fn and(&self, other: &ImportCondition<'i>) -> Option<ImportCondition<'i>> {
+    let layer = match (&self.layer, &other.layer) {
+      (layer, None) | (None, layer) => layer.clone(),
+      (Some(Some(a)), Some(Some(b))) => {
+        let mut name = a.clone();
+        name.0.extend(b.0.iter().cloned());
+        Some(Some(name))
+      }
+      // Anonymous layers cannot be combined with any other layer.
+      _ => return None,
+    };
+
+    // Each query in the list is combined with the other list, which is only
+    // equivalent when the other list contains a single query.
+    let mut media = self.media.clone();
+    if !media.media_queries.is_empty() && other.media.media_queries.len() > 1 {
+      return None;
+    }
+    media.and(&other.media).ok()?;
+
+    // Wrapper rules are mapped to the outermost @import rule that each condition originated from.
+    Some(ImportCondition {
+      layer,
+      supports: combine_supports(self.supports.clone(), &other.supports),
+      media,
+      locs: ConditionLocations {
+        layer: self.locs.layer.or(other.locs.layer),
+        supports: self.locs.supports.or(other.locs.supports),
+        media: self.locs.media.or(other.locs.media),
+      },
+    })
+  }
+
+  /// Returns whether this condition applies whenever the other condition does.
+  fn covers(&self, other: &ImportCondition<'i>) -> bool {
+    (self.media.media_queries.is_empty() || self.media == other.media)
+      && (self.supports.is_none() || self.supports == other.supports)
+  }
+
+  /// Combines the media and supports conditions of another `@import` rule for the same file
+  /// with this one using a logical or, if the result can be expressed as a single condition.
+  /// The layer of this condition is kept as is.
+  fn or(&self, other: &ImportCondition<'i>) -> Option<ImportCondition<'i>> {
+    if self.covers(other) {
+      return Some(self.clone());
+    }
+
+    if other.covers(self) {
+      return Some(ImportCondition {
+        layer: self.layer.clone(),
+        locs: ConditionLocations {
+          layer: self.locs.layer,
+          ..other.locs
+        },
+        ..other.clone()
+      });
+    }
+
+    // Otherwise, one of the conditions must be the same in both rules. For example, `print` and
+    // `supports(display: grid)` cannot be combined because neither query can express the other.
+    let mut result = self.clone();
+    if self.media == other.media {
+      if let (Some(supports), Some(other_supports)) = (&mut result.supports, &other.supports) {
+        supports.or(other_supports);
+      }
+    } else if self.supports == other.supports {
+      result.media.or(&other.media);
+    } else {
+      return None;
+    }
+
+    Some(result)
+  }
+}
+
+/// The conditions that a file is loaded with, from the outermost `@import` rule to the innermost,
+/// along with the location of the rule that loaded it. Conditions of nested imports are combined where
+/// possible, and otherwise wrapped around the rules of the imported file one after another.
+#[derive(Clone)]
+struct ImportContext<'i> {
+  conditions: Vec<ImportCondition<'i>>,
   loc: Location,
 }
 
@@ -150,6 +284,368 @@ impl Drop for FileProvider {
   }
 }
 
+/// Provides an implementation of [SourceProvider](SourceProvider) that reads files from an in-memory
+/// map of paths to contents, e.g. for tests, playgrounds, or editor integrations that bundle unsaved files.
+///
+/// Specifiers are resolved as relative file paths, like [FileProvider](FileProvider). Paths are normalized,
+/// so `./` and `../` segments are resolved lexically when adding and looking up files.
+///
+/// ```
+/// use std::path::Path;
+/// use lightningcss::{
+///   bundler::{Bundler, MemoryProvider},
+///   stylesheet::ParserOptions
+/// };
+///
+/// let mut fs = MemoryProvider::new();
+/// fs.add_file("src/index.css", "@import 'components/button.css';");
+/// fs.add_file("src/components/button.css", ".button { color: red }");
+///
+/// let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+/// let stylesheet = bundler.bundle(Path::new("src/index.css")).unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryProvider {
+  files: HashMap<PathBuf, String>,
+}
+
+impl MemoryProvider {
+  /// Creates a new empty MemoryProvider.
+  pub fn new() -> MemoryProvider {
+    MemoryProvider::default()
+  }
+
+  /// Adds a file with the given contents, replacing any existing file at the same path.
+  pub fn add_file<F: AsRef<Path>, C: Into<String>>(&mut self, file: F, contents: C) {
+    self.files.insert(normalize_path(file.as_ref()), contents.into());
+  }
+
+  /// Removes the file at the given path, returning its contents if it existed.
+  pub fn remove_file<F: AsRef<Path>>(&mut self, file: F) -> Option<String> {
+    self.files.remove(&normalize_path(file.as_ref()))
+  }
+
+  fn get(&self, file: &Path) -> Result<&str, std::io::Error> {
+    match self.files.get(&normalize_path(file)) {
+      Some(contents) => Ok(contents),
+      None => Err(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        format!("File not found: {}", file.display()),
+      )),
+    }
+  }
+}
+
+impl<F: AsRef<Path>, C: Into<String>> FromIterator<(F, C)> for MemoryProvider {
+  fn from_iter<I: IntoIterator<Item = (F, C)>>(iter: I) -> Self {
+    let mut provider = MemoryProvider::new();
+    for (file, contents) in iter {
+      provider.add_file(file, contents);
+    }
+    provider
+  }
+}
+
+impl SourceProvider for MemoryProvider {
+  type Error = std::io::Error;
+
+  fn read<'a>(&'a self, file: &Path) -> Result<&'a str, Self::Error> {
+    self.get(file)
+  }
+
+  fn resolve(&self, specifier: &str, originating_file: &Path) -> Result<PathBuf, Self::Error> {
+    Ok(normalize_path(&originating_file.with_file_name(specifier)))
+  }
+}
+
+#[cfg(feature = "async")]
+impl AsyncSourceProvider for MemoryProvider {
+  type Error = std::io::Error;
+
+  fn read<'a>(&'a self, file: &Path) -> BoxFuture<'a, Result<&'a str, Self::Error>> {
+    Box::pin(std::future::ready(self.get(file)))
+  }
+
+  fn resolve<'a>(
+    &'a self,
+    specifier: &str,
+    originating_file: &Path,
+  ) -> BoxFuture<'a, Result<PathBuf, Self::Error>> {
+    Box::pin(std::future::ready(SourceProvider::resolve(
+      self,
+      specifier,
+      originating_file,
+    )))
+  }
+}
+
+/// A boxed future, as returned by an [AsyncSourceProvider](AsyncSourceProvider).
+#[cfg(feature = "async")]
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// An asynchronous version of [SourceProvider](SourceProvider), which allows files to be
+/// read and resolved concurrently, e.g. from a database, over HTTP, or through a plugin pipeline.
+///
+/// The returned futures are not tied to any particular async runtime, and do not need to be `Send`.
+/// They may only borrow the provider itself, so any arguments that are needed after the first
+/// `.await` must be copied.
+///
+/// Use [Bundler::bundle_async](Bundler::bundle_async) to bundle with an async source provider.
+#[cfg(feature = "async")]
+pub trait AsyncSourceProvider {
+  /// A custom error.
+  type Error: std::error::Error + Send + Sync;
+
+  /// Reads the contents of the given file path to a string.
+  fn read<'a>(&'a self, file: &Path) -> BoxFuture<'a, Result<&'a str, Self::Error>>;
+
+  /// Resolves the given import specifier to a file path given the file
+  /// which the import originated from.
+  fn resolve<'a>(
+    &'a self,
+    specifier: &str,
+    originating_file: &Path,
+  ) -> BoxFuture<'a, Result<PathBuf, Self::Error>>;
+}
+
+/// Waits for all of the given futures to complete, polling them concurrently, and returns their outputs in order.
+#[cfg(feature = "async")]
+async fn join_all<T>(futures: Vec<BoxFuture<'_, T>>) -> Vec<T> {
+  let mut futures: Vec<_> = futures.into_iter().map(Some).collect();
+  let mut outputs: Vec<Option<T>> = futures.iter().map(|_| None).collect();
+  std::future::poll_fn(|cx| {
+    let mut done = true;
+    for (future, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+      if let Some(f) = future {
+        match f.as_mut().poll(cx) {
+          Poll::Ready(value) => {
+            *output = Some(value);
+            *future = None;
+          }
+          Poll::Pending => done = false,
+        }
+      }
+    }
+    if done {
+      Poll::Ready(())
+    } else {
+      Poll::Pending
+    }
+  })
+  .await;
+  outputs.into_iter().map(Option::unwrap).collect()
+}
+
+/// Options for preserving `@import` rules in the bundle rather than inlining the files they reference.
+///
+/// Preserved rules are hoisted to the top of the bundle, after any leading `@layer` statements, with the
+/// conditions of the rules that imported the containing file combined into their own. An error is returned if
+/// the conditions cannot be combined into a single `@import` rule, e.g. within an anonymous layer.
+///
+/// See [Bundler::set_external_imports](Bundler::set_external_imports).
+#[derive(Debug, Clone, Default)]
+pub struct ExternalImportsOptions {
+  /// Whether to preserve imports of remote urls, i.e. those with a scheme such as `https:`, and
+  /// protocol-relative urls starting with `//`.
+  pub remote: bool,
+  /// Patterns matching the urls of imports to preserve, e.g. `https://fonts.googleapis.com/*`, where
+  /// `*` matches any sequence of characters.
+  pub include: Vec<String>,
+}
+
+impl ExternalImportsOptions {
+  fn is_external(&self, url: &str) -> bool {
+    (self.remote && is_remote_url(url)) || self.include.iter().any(|pattern| matches_pattern(pattern, url))
+  }
+}
+
+/// Returns whether a url is protocol-relative or has a scheme. Single letter schemes are treated as
+/// Windows drive letters.
+fn is_remote_url(url: &str) -> bool {
+  if url.starts_with("//") {
+    return true;
+  }
+
+  match url.split_once(':') {
+    Some((scheme, _)) => {
+      scheme.len() > 1
+        && scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+          .chars()
+          .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+    }
+    None => false,
+  }
+}
+
+/// The dependency graph of a bundle, as returned by [Bundler::dependency_graph](Bundler::dependency_graph).
+///
+/// This can be used to determine which bundles to rebuild when a file changes, e.g. in a dev server.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct DependencyGraph {
+  /// The files included in the bundle, starting with the entry file.
+  pub files: Vec<FileDependencies>,
+}
+
+impl DependencyGraph {
+  /// Returns the files that depend on the given file directly or transitively, via `@import` rules,
+  /// CSS modules dependencies, or `url()` references. These are affected when the given file changes.
+  pub fn dependents(&self, file: &str) -> Vec<&str> {
+    let mut dependents = Vec::new();
+    let mut stack = vec![file];
+    while let Some(file) = stack.pop() {
+      for node in &self.files {
+        if node.depends_on(file) && node.file != file && !dependents.contains(&node.file.as_str()) {
+          dependents.push(node.file.as_str());
+          stack.push(node.file.as_str());
+        }
+      }
+    }
+
+    dependents
+  }
+}
+
+/// A file included in a bundle, along with its dependencies.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct FileDependencies {
+  /// The path of the file.
+  pub file: String,
+  /// The `@import` rules in the file.
+  pub imports: Vec<ImportEdge>,
+  /// The files referenced by CSS modules `composes` properties and variable references.
+  pub css_modules: Vec<String>,
+  /// The `url()` references in the file, other than those of `@import` rules. `data:` urls are omitted.
+  pub urls: Vec<UrlEdge>,
+}
+
+impl FileDependencies {
+  fn depends_on(&self, file: &str) -> bool {
+    self.imports.iter().any(|import| import.file.as_deref() == Some(file))
+      || self.css_modules.iter().any(|dep| dep == file)
+      || self.urls.iter().any(|url| url.file.as_deref() == Some(file))
+  }
+}
+
+/// An `@import` rule in a bundled file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ImportEdge {
+  /// The url of the `@import` rule, as written.
+  pub url: String,
+  /// The path of the imported file, or `None` if the rule was preserved as an external import.
+  pub file: Option<String>,
+  /// An optional `layer()` condition. Anonymous layers are represented as an empty string.
+  pub layer: Option<String>,
+  /// An optional `supports()` condition.
+  pub supports: Option<String>,
+  /// A media query.
+  pub media: Option<String>,
+  /// The location of the rule in the file.
+  pub loc: crate::dependencies::Location,
+}
+
+impl ImportEdge {
+  fn new(rule: &ImportRule, file: Option<String>) -> ImportEdge {
+    let layer = rule.layer.as_ref().map(|layer| match layer {
+      Some(name) => name.to_css_string(PrinterOptions::default()).unwrap(),
+      None => String::new(),
+    });
+
+    let supports = rule
+      .supports
+      .as_ref()
+      .map(|supports| supports.to_css_string(PrinterOptions::default()).unwrap());
+
+    let media = if !rule.media.media_queries.is_empty() {
+      Some(rule.media.to_css_string(PrinterOptions::default()).unwrap())
+    } else {
+      None
+    };
+
+    ImportEdge {
+      url: rule.url.as_ref().to_owned(),
+      file,
+      layer,
+      supports,
+      media,
+      loc: crate::dependencies::Location {
+        line: rule.loc.line + 1,
+        column: rule.loc.column,
+      },
+    }
+  }
+}
+
+/// A `url()` reference in a bundled file.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct UrlEdge {
+  /// The url, as written.
+  pub url: String,
+  /// The path of the referenced file, resolved relative to the file containing the reference, or `None`
+  /// if the url is absolute.
+  pub file: Option<String>,
+  /// The location of the reference in the file.
+  pub loc: crate::dependencies::Location,
+}
+
+/// Collects the `url()` references in a file by tokenizing it, skipping `@import` rules.
+fn collect_urls(input: &mut Parser, file: &Path, urls: &mut Vec<UrlEdge>) {
+  loop {
+    input.skip_whitespace();
+    let loc = input.current_source_location();
+    let token = match input.next() {
+      Ok(token) => token.clone(),
+      Err(_) => break,
+    };
+
+    let url = match token {
+      Token::UnquotedUrl(url) => Some(url),
+      Token::Function(ref name) if name.eq_ignore_ascii_case("url") => input
+        .parse_nested_block(|input| Ok::<_, cssparser::ParseError<()>>(input.expect_string_cloned().ok()))
+        .ok()
+        .flatten(),
+      Token::AtKeyword(ref name) if name.eq_ignore_ascii_case("import") => {
+        let _ = input.parse_until_after(Delimiter::Semicolon, |input| {
+          while input.next().is_ok() {}
+          Ok::<_, cssparser::ParseError<()>>(())
+        });
+        None
+      }
+      Token::Function(_) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+        let _ = input.parse_nested_block(|input| {
+          collect_urls(input, file, urls);
+          Ok::<_, cssparser::ParseError<()>>(())
+        });
+        None
+      }
+      _ => None,
+    };
+
+    if let Some(url) = url {
+      if url.starts_with("data:") {
+        continue;
+      }
+
+      let url = Url {
+        url: url.as_ref().into(),
+        loc: loc.into(),
+      };
+      urls.push(UrlEdge {
+        file: url
+          .resolve(file)
+          .and_then(|(path, _)| path.to_str().map(|path| path.to_owned())),
+        url: url.url.to_string(),
+        loc: url.loc,
+      });
+    }
+  }
+}
+
 /// An error that could occur during bundling.
 #[derive(Debug)]
 #[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
@@ -164,6 +660,14 @@ pub enum BundleErrorKind<'i, T: std::error::Error> {
   UnsupportedMediaBooleanLogic,
   /// A custom resolver error.
   ResolverError(#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(skip))] T),
+  /// A printer error occurred while generating the exports of a CSS modules dependency.
+  PrinterError(PrinterErrorKind),
+  /// A circular `@import` was encountered.
+  CircularImport {
+    /// The locations of the `@import` rules that form the cycle. Each rule imports the file
+    /// containing the next one, and the last rule imports the file containing the first.
+    chain: Vec<ErrorLocation>,
+  },
 }
 
 impl<'i, T: std::error::Error> From<Error<ParserError<'i>>> for Error<BundleErrorKind<'i, T>> {
@@ -175,6 +679,15 @@ impl<'i, T: std::error::Error> From<Error<ParserError<'i>>> for Error<BundleErro
   }
 }
 
+impl<'i, T: std::error::Error> From<Error<PrinterErrorKind>> for Error<BundleErrorKind<'i, T>> {
+  fn from(err: Error<PrinterErrorKind>) -> Self {
+    Error {
+      kind: BundleErrorKind::PrinterError(err.kind),
+      loc: err.loc,
+    }
+  }
+}
+
 impl<'i, T: std::error::Error> std::fmt::Display for BundleErrorKind<'i, T> {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
     use BundleErrorKind::*;
@@ -184,6 +697,14 @@ impl<'i, T: std::error::Error> std::fmt::Display for BundleErrorKind<'i, T> {
       UnsupportedLayerCombination => write!(f, "Unsupported layer combination in @import"),
       UnsupportedMediaBooleanLogic => write!(f, "Unsupported boolean logic in @import media query"),
       ResolverError(err) => std::fmt::Display::fmt(&err, f),
+      PrinterError(err) => err.fmt(f),
+      CircularImport { chain } => {
+        write!(f, "Circular @import detected: ")?;
+        for loc in chain {
+          write!(f, "{} -> ", loc)?;
+        }
+        write!(f, "{}", chain[0].filename)
+      }
     }
   }
 }
@@ -196,7 +717,7 @@ impl<'i, T: std::error::Error> BundleErrorKind<'i, T> {
   }
 }
 
-impl<'a, 'o, 's, P: SourceProvider> Bundler<'a, 'o, 's, P, DefaultAtRuleParser> {
+impl<'a, 'o, 's, P> Bundler<'a, 'o, 's, P, DefaultAtRuleParser> {
   /// Creates a new Bundler using the given source provider.
   /// If a source map is given, the content of each source file included in the bundle will
   /// be added accordingly.
@@ -212,11 +733,12 @@ impl<'a, 'o, 's, P: SourceProvider> Bundler<'a, 'o, 's, P, DefaultAtRuleParser>
       stylesheets: Mutex::new(Vec::new()),
       options,
       at_rule_parser: Mutex::new(AtRuleParserValue::Owned(DefaultAtRuleParser)),
+      external_imports: ExternalImportsOptions::default(),
     }
   }
 }
 
-impl<'a, 'o, 's, P: SourceProvider, T: AtRuleParser<'a> + Clone + Sync + Send> Bundler<'a, 'o, 's, P, T>
+impl<'a, 'o, 's, P, T: AtRuleParser<'a> + Clone + Sync + Send> Bundler<'a, 'o, 's, P, T>
 where
   T::AtRule: Sync + Send + ToCss + Clone,
 {
@@ -236,30 +758,81 @@ where
       stylesheets: Mutex::new(Vec::new()),
       options,
       at_rule_parser: Mutex::new(AtRuleParserValue::Borrowed(at_rule_parser)),
+      external_imports: ExternalImportsOptions::default(),
     }
   }
 
-  /// Bundles the given entry file and all dependencies into a single style sheet.
-  pub fn bundle<'e>(
-    &mut self,
-    entry: &'e Path,
-  ) -> Result<StyleSheet<'a, 'o, T::AtRule>, Error<BundleErrorKind<'a, P::Error>>> {
-    // Phase 1: load and parse all files. This is done in parallel.
-    self.load_file(
-      &entry,
-      ImportRule {
-        url: "".into(),
-        layer: None,
-        supports: None,
-        media: MediaList::new(),
-        loc: Location {
-          source_index: 0,
-          line: 0,
-          column: 0,
-        },
+  /// Sets which `@import` rules to preserve in the bundle rather than inline, e.g. imports of remote
+  /// style sheets, which cannot be bundled.
+  pub fn set_external_imports(&mut self, options: ExternalImportsOptions) {
+    self.external_imports = options;
+  }
+
+  /// Returns the dependency graph of the files loaded by the last call to [bundle](Bundler::bundle),
+  /// including their `@import` rules, CSS modules dependencies, and `url()` references.
+  pub fn dependency_graph(&self) -> DependencyGraph {
+    let stylesheets = self.stylesheets.lock().unwrap();
+    let mut filenames = vec![String::new(); stylesheets.len()];
+    for entry in self.source_indexes.iter() {
+      filenames[*entry.value() as usize] = entry.key().to_str().unwrap().into();
+    }
+
+    let files = stylesheets
+      .iter()
+      .zip(&filenames)
+      .map(|(entry, file)| {
+        // Dependencies are stored in the order of the @import rules that were not preserved.
+        let mut dependencies = entry.dependencies.iter();
+        let imports = entry
+          .imports
+          .iter()
+          .map(|import| {
+            let file = if self.external_imports.is_external(&import.url) {
+              None
+            } else {
+              dependencies.next().map(|i| filenames[*i as usize].clone())
+            };
+            ImportEdge::new(import, file)
+          })
+          .collect();
+
+        let mut css_modules = Vec::new();
+        for i in &entry.css_modules_deps {
+          let dep = &filenames[*i as usize];
+          if !css_modules.contains(dep) {
+            css_modules.push(dep.clone());
+          }
+        }
+
+        let mut urls = Vec::new();
+        let mut input = ParserInput::new(entry.code);
+        collect_urls(&mut Parser::new(&mut input), Path::new(file), &mut urls);
+
+        FileDependencies {
+          file: file.clone(),
+          imports,
+          css_modules,
+          urls,
+        }
+      })
+      .collect();
+
+    DependencyGraph { files }
+  }
+
+  fn entry_context() -> ImportContext<'a> {
+    ImportContext {
+      conditions: vec![ImportCondition::default()],
+      loc: Location {
+        source_index: 0,
+        line: 0,
+        column: 0,
       },
-    )?;
+    }
+  }
 
+  /// Combines the loaded files into a single style sheet, once all of them have been loaded.
+  fn finish(&mut self) -> StyleSheet<'a, 'o, T::AtRule> {
     // Phase 2: determine the order that the files should be concatenated.
     self.order();
 
@@ -285,6 +858,29 @@ where
       .flat_map(|s| s.stylesheet.as_ref().unwrap().source_map_urls.iter().cloned())
       .collect();
 
+    // Add sources to the source map in source index order once all files are loaded, since files are
+    // parsed in parallel. Sources with an input source map are skipped, as these will be handled by the
+    // printer when remapping locations, so the printer needs to know the index of each remaining source.
+    if let Some(source_map) = &mut self.source_map {
+      let source_map = source_map.get_mut().unwrap();
+      stylesheet.source_map_indexes = self
+        .stylesheets
+        .get_mut()
+        .unwrap()
+        .iter()
+        .map(|s| {
+          let file = s.stylesheet.as_ref().unwrap();
+          if file.source_map_url(0).map_or(false, |url| url.starts_with("data")) {
+            return None;
+          }
+
+          let source_index = source_map.add_source(&file.sources[0]);
+          let _ = source_map.set_source_content(source_index as usize, s.code);
+          Some(source_index)
+        })
+        .collect();
+    }
+
     stylesheet.license_comments = self
       .stylesheets
       .get_mut()
@@ -293,7 +889,7 @@ where
       .flat_map(|s| s.stylesheet.as_ref().unwrap().license_comments.iter().cloned())
       .collect();
 
-    Ok(stylesheet)
+    stylesheet
   }
 
   fn find_filename(&self, source_index: u32) -> String {
@@ -302,42 +898,46 @@ where
     entry.key().to_str().unwrap().into()
   }
 
-  fn load_file(&self, file: &Path, rule: ImportRule<'a>) -> Result<u32, Error<BundleErrorKind<'a, P::Error>>> {
-    // Check if we already loaded this file.
+  /// Assigns a source index to the given file, and returns it along with whether the file
+  /// has not been seen before and still needs to be loaded.
+  fn register_file(&self, file: &Path, rule: &ImportContext<'a>) -> (u32, bool) {
+    // Check if we already loaded this file. The conditions it is loaded with are resolved
+    // once all files are loaded.
     let mut stylesheets = self.stylesheets.lock().unwrap();
-    let source_index = match self.source_indexes.get(file) {
-      Some(source_index) => {
-        // If we already loaded this file, combine the media queries and supports conditions
-        // from this import rule with the existing ones using a logical or operator.
-        let entry = &mut stylesheets[*source_index as usize];
-
-        // We cannot combine a media query and a supports query from different @import rules.
-        // e.g. @import "a.css" print; @import "a.css" supports(color: red);
-        // This would require duplicating the actual rules in the file.
-        if (!rule.media.media_queries.is_empty() && !entry.supports.is_none())
-          || (!entry.media.media_queries.is_empty() && !rule.supports.is_none())
-        {
-          return Err(Error {
-            kind: BundleErrorKind::UnsupportedImportCondition,
-            loc: Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index))),
-          });
-        }
-
-        if rule.media.media_queries.is_empty() {
-          entry.media.media_queries.clear();
-        } else if !entry.media.media_queries.is_empty() {
-          entry.media.or(&rule.media);
-        }
+    if let Some(source_index) = self.source_indexes.get(file) {
+      return (*source_index, false);
+    }
 
-        if let Some(supports) = rule.supports {
-          if let Some(existing_supports) = &mut entry.supports {
-            existing_supports.or(&supports)
-          }
-        } else {
-          entry.supports = None;
-        }
+    let source_index = stylesheets.len() as u32;
+    self.source_indexes.insert(file.to_owned(), source_index);
+
+    stylesheets.push(BundleStyleSheet {
+      stylesheet: None,
+      code: "",
+      imports: Vec::new(),
+      conditions: rule.conditions.clone(),
+      loc: rule.loc,
+      dependencies: Vec::new(),
+      css_modules_deps: Vec::new(),
+      parent_source_index: 0,
+      parent_dep_index: 0,
+    });
+
+    (source_index, true)
+  }
 
-        if let Some(layer) = &rule.layer {
+  /// Merges the conditions of another rule that loads a file into the conditions the file is
+  /// already loaded with.
+  fn merge_conditions<E: std::error::Error>(
+    &self,
+    existing: &mut Vec<ImportCondition<'a>>,
+    rule: &ImportContext<'a>,
+  ) -> Result<(), Error<BundleErrorKind<'a, E>>> {
+    match (&mut existing[..], &rule.conditions[..]) {
+      ([entry], [condition]) => {
+        // If we already loaded this file, combine the media queries and supports conditions
+        // from this import rule with the existing ones using a logical or operator.
+        if let Some(layer) = &condition.layer {
           if let Some(existing_layer) = &entry.layer {
             // We can't OR layer names without duplicating all of the nested rules, so error for now.
             if layer != existing_layer || (layer.is_none() && existing_layer.is_none()) {
@@ -346,50 +946,132 @@ where
                 loc: Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index))),
               });
             }
-          } else {
-            entry.layer = rule.layer;
           }
         }
 
-        return Ok(*source_index);
+        // If the conditions cannot be combined, e.g. `@import "a.css" print; @import "a.css" supports(color: red);`,
+        // this would require duplicating the actual rules in the file.
+        let Some(mut combined) = entry.or(condition) else {
+          return Err(Error {
+            kind: BundleErrorKind::UnsupportedImportCondition,
+            loc: Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index))),
+          });
+        };
+
+        if combined.layer.is_none() {
+          combined.layer = condition.layer.clone();
+          combined.locs.layer = condition.locs.layer;
+        }
+        *entry = combined;
       }
-      None => {
-        let source_index = stylesheets.len() as u32;
-        self.source_indexes.insert(file.to_owned(), source_index);
+      // Nested conditions that could not be combined can only be merged with identical ones,
+      // or with an unconditional import if no layers are involved.
+      (entry, conditions) if entry == conditions && !has_anonymous_layer(conditions) => {}
+      ([entry], conditions) if *entry == ImportCondition::default() && !has_layer(conditions) => {}
+      (entry, [condition]) if *condition == ImportCondition::default() && !has_layer(entry) => {
+        *existing = vec![ImportCondition::default()];
+      }
+      _ => {
+        return Err(Error {
+          kind: BundleErrorKind::UnsupportedImportCondition,
+          loc: Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index))),
+        })
+      }
+    }
 
-        stylesheets.push(BundleStyleSheet {
-          stylesheet: None,
-          layer: rule.layer.clone(),
-          media: rule.media.clone(),
-          supports: rule.supports.clone(),
-          loc: rule.loc.clone(),
-          dependencies: Vec::new(),
-          css_modules_deps: Vec::new(),
-          parent_source_index: 0,
-          parent_dep_index: 0,
-        });
+    Ok(())
+  }
 
-        source_index
+  /// Resolves the conditions that each file is loaded with from the files that import it. A file can
+  /// be imported again with different conditions after its own imports were loaded, so this is done
+  /// once all files are loaded, visiting the importers of each file before the file itself.
+  fn resolve_conditions<E: std::error::Error>(&self) -> Result<(), Error<BundleErrorKind<'a, E>>> {
+    fn visit<T>(
+      stylesheets: &[BundleStyleSheet<'_, '_, T>],
+      source_index: u32,
+      visited: &mut HashSet<u32>,
+      order: &mut Vec<u32>,
+    ) {
+      if !visited.insert(source_index) {
+        return;
       }
-    };
 
-    drop(stylesheets); // ensure we aren't holding the lock anymore
+      let entry = &stylesheets[source_index as usize];
+      for &dep_source_index in entry.dependencies.iter().chain(&entry.css_modules_deps) {
+        visit(stylesheets, dep_source_index, visited, order);
+      }
 
-    let code = self.fs.read(file).map_err(|e| Error {
-      kind: BundleErrorKind::ResolverError(e),
-      loc: if rule.loc.column == 0 {
-        None
-      } else {
-        Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index)))
+      order.push(source_index);
+    }
+
+    let mut stylesheets = self.stylesheets.lock().unwrap();
+    let mut order = Vec::new();
+    visit(&stylesheets, 0, &mut HashSet::new(), &mut order);
+
+    let mut resolved: Vec<Option<Vec<ImportCondition<'a>>>> = vec![None; stylesheets.len()];
+    resolved[0] = Some(stylesheets[0].conditions.clone());
+    for &source_index in order.iter().rev() {
+      let entry = &stylesheets[source_index as usize];
+      let rule = ImportContext {
+        conditions: resolved[source_index as usize].clone().unwrap(),
+        loc: entry.loc,
+      };
+
+      let imports = entry
+        .imports
+        .iter()
+        .filter(|import| !self.external_imports.is_external(&import.url))
+        .map(|import| Self::import_context(&rule, import));
+      let css_modules_deps = entry.css_modules_deps.iter().map(|&dep_source_index| ImportContext {
+        conditions: rule.conditions.clone(),
+        loc: stylesheets[dep_source_index as usize].loc,
+      });
+
+      for (&dep_source_index, context) in entry
+        .dependencies
+        .iter()
+        .zip(imports)
+        .chain(entry.css_modules_deps.iter().zip(css_modules_deps))
+      {
+        match &mut resolved[dep_source_index as usize] {
+          Some(conditions) => self.merge_conditions(conditions, &context)?,
+          conditions => *conditions = Some(context.conditions),
+        }
+      }
+    }
+
+    for (entry, conditions) in stylesheets.iter_mut().zip(resolved) {
+      if let Some(conditions) = conditions {
+        entry.conditions = conditions;
+      }
+    }
+
+    Ok(())
+  }
+
+  fn read_error<E: std::error::Error>(&self, err: E, rule: &ImportContext<'a>) -> Error<BundleErrorKind<'a, E>> {
+    Error {
+      kind: BundleErrorKind::ResolverError(err),
+      loc: if rule.loc.column == 0 {
+        None
+      } else {
+        Some(ErrorLocation::new(rule.loc, self.find_filename(rule.loc.source_index)))
       },
-    })?;
+    }
+  }
 
+  fn parse_file<E: std::error::Error>(
+    &self,
+    file: &Path,
+    source_index: u32,
+    code: &'a str,
+  ) -> Result<StyleSheet<'a, 'o, T::AtRule>, Error<BundleErrorKind<'a, E>>> {
     let mut opts = self.options.clone();
     let filename = file.to_str().unwrap();
     opts.filename = filename.to_owned();
     opts.source_index = source_index;
 
-    let mut stylesheet = {
+    let stylesheet = {
       let mut at_rule_parser = self.at_rule_parser.lock().unwrap();
       let at_rule_parser = match &mut *at_rule_parser {
         AtRuleParserValue::Owned(owned) => owned,
@@ -399,204 +1081,156 @@ where
       StyleSheet::<T::AtRule>::parse_with(code, opts, at_rule_parser)?
     };
 
-    if let Some(source_map) = &self.source_map {
-      // Only add source if we don't have an input source map.
-      // If we do, this will be handled by the printer when remapping locations.
-      let sm = stylesheet.source_map_url(0);
-      if sm.is_none() || !sm.unwrap().starts_with("data") {
-        let mut source_map = source_map.lock().unwrap();
-        let source_index = source_map.add_source(filename);
-        let _ = source_map.set_source_content(source_index as usize, code);
-      }
+    Ok(stylesheet)
+  }
+
+  /// Returns the context to load the file referenced by an `@import` rule with.
+  fn import_context(rule: &ImportContext<'a>, import: &ImportRule<'a>) -> ImportContext<'a> {
+    let condition = ImportCondition::from_import(import);
+
+    // Combine the conditions from the parent stylesheet with the @import rule
+    // if possible, and otherwise nest the @import rule's conditions within them.
+    // Within an anonymous layer, the conditions are always nested so that the
+    // rules of the imported file can be placed inside the parent's layer block.
+    let mut conditions = rule.conditions.clone();
+    let last = conditions.last_mut().unwrap();
+    match last.and(&condition) {
+      Some(combined) if !has_anonymous_layer(&rule.conditions) => *last = combined,
+      _ => conditions.push(condition),
     }
 
-    // Collect and load dependencies for this stylesheet in parallel.
-    let dependencies: Result<Vec<u32>, _> = stylesheet
-      .rules
-      .0
-      .par_iter_mut()
-      .filter_map(|r| {
-        // Prepend parent layer name to @layer statements.
-        if let CssRule::LayerStatement(layer) = r {
-          if let Some(Some(parent_layer)) = &rule.layer {
-            for name in &mut layer.names {
-              name.0.insert_many(0, parent_layer.0.iter().cloned())
-            }
-          }
-        }
+    ImportContext {
+      conditions,
+      loc: import.loc,
+    }
+  }
 
-        if let CssRule::Import(import) = r {
-          let specifier = &import.url;
-
-          // Combine media queries and supports conditions from parent
-          // stylesheet with @import rule using a logical and operator.
-          let mut media = rule.media.clone();
-          let result = media.and(&import.media).map_err(|_| Error {
-            kind: BundleErrorKind::UnsupportedMediaBooleanLogic,
-            loc: Some(ErrorLocation::new(
-              import.loc,
-              self.find_filename(import.loc.source_index),
-            )),
-          });
+  /// Returns the context to load a file referenced by the CSS modules `composes` property,
+  /// or a CSS variable reference, with.
+  fn css_module_dep_context(
+    rule: &ImportContext<'a>,
+    style_loc: Location,
+    loc: crate::dependencies::Location,
+  ) -> ImportContext<'a> {
+    ImportContext {
+      conditions: rule.conditions.clone(),
+      loc: Location {
+        source_index: style_loc.source_index,
+        line: loc.line,
+        column: loc.column,
+      },
+    }
+  }
 
-          if let Err(e) = result {
-            return Some(Err(e));
-          }
+  fn store_file(
+    &self,
+    source_index: u32,
+    code: &'a str,
+    stylesheet: StyleSheet<'a, 'o, T::AtRule>,
+    dependencies: Vec<u32>,
+    css_modules_deps: Vec<u32>,
+  ) {
+    let entry = &mut self.stylesheets.lock().unwrap()[source_index as usize];
+    // Keep the @import rules for the dependency graph, since they are removed when inlining.
+    entry.imports = stylesheet
+      .rules
+      .0
+      .iter()
+      .filter_map(|rule| match rule {
+        CssRule::Import(import) => Some(import.clone()),
+        _ => None,
+      })
+      .collect();
+    entry.code = code;
+    entry.stylesheet = Some(stylesheet);
+    entry.dependencies = dependencies;
+    entry.css_modules_deps = css_modules_deps;
+  }
 
-          let layer = if (rule.layer == Some(None) && import.layer.is_some())
-            || (import.layer == Some(None) && rule.layer.is_some())
+  /// Returns an error if a preserved `@import` rule cannot be hoisted out of the conditions that the
+  /// file containing it was imported with.
+  fn check_external_imports<E: std::error::Error>(&self) -> Result<(), Error<BundleErrorKind<'a, E>>> {
+    let stylesheets = self.stylesheets.lock().unwrap();
+    for entry in stylesheets.iter() {
+      for rule in &entry.stylesheet.as_ref().unwrap().rules.0 {
+        if let CssRule::Import(import) = rule {
+          if self.external_imports.is_external(&import.url) && external_import(&entry.conditions, import).is_none()
           {
-            // Cannot combine anonymous layers
-            return Some(Err(Error {
-              kind: BundleErrorKind::UnsupportedLayerCombination,
-              loc: Some(ErrorLocation::new(
-                import.loc,
-                self.find_filename(import.loc.source_index),
-              )),
-            }));
-          } else if let Some(Some(a)) = &rule.layer {
-            if let Some(Some(b)) = &import.layer {
-              let mut name = a.clone();
-              name.0.extend(b.0.iter().cloned());
-              Some(Some(name))
-            } else {
-              Some(Some(a.clone()))
-            }
-          } else {
-            import.layer.clone()
-          };
-
-          let result = match self.fs.resolve(&specifier, file) {
-            Ok(path) => self.load_file(
-              &path,
-              ImportRule {
-                layer,
-                media,
-                supports: combine_supports(rule.supports.clone(), &import.supports),
-                url: "".into(),
-                loc: import.loc,
-              },
-            ),
-            Err(err) => Err(Error {
-              kind: BundleErrorKind::ResolverError(err),
+            return Err(Error {
+              kind: BundleErrorKind::UnsupportedImportCondition,
               loc: Some(ErrorLocation::new(
                 import.loc,
                 self.find_filename(import.loc.source_index),
               )),
-            }),
-          };
-
-          Some(result)
-        } else {
-          None
+            });
+          }
         }
-      })
-      .collect();
-
-    // Collect CSS modules dependencies from the `composes` property.
-    let css_modules_deps: Result<Vec<u32>, _> = if self.options.css_modules.is_some() {
-      stylesheet
-        .rules
-        .0
-        .par_iter_mut()
-        .filter_map(|r| {
-          if let CssRule::Style(style) = r {
-            Some(
-              style
-                .declarations
-                .declarations
-                .par_iter_mut()
-                .chain(style.declarations.important_declarations.par_iter_mut())
-                .filter_map(|d| match d {
-                  Property::Composes(composes) => self
-                    .add_css_module_dep(file, &rule, style.loc, composes.loc, &mut composes.from)
-                    .map(|result| rayon::iter::Either::Left(rayon::iter::once(result))),
+      }
+    }
 
-                  // Handle variable references if the dashed_idents option is present.
-                  Property::Custom(CustomProperty { value, .. })
-                  | Property::Unparsed(UnparsedProperty { value, .. })
-                    if matches!(&self.options.css_modules, Some(css_modules) if css_modules.dashed_idents) =>
-                  {
-                    Some(rayon::iter::Either::Right(visit_vars(value).filter_map(|name| {
-                      self.add_css_module_dep(
-                        file,
-                        &rule,
-                        style.loc,
-                        // TODO: store loc in variable reference?
-                        crate::dependencies::Location {
-                          line: style.loc.line,
-                          column: style.loc.column,
-                        },
-                        &mut name.from,
-                      )
-                    })))
-                  }
-                  _ => None,
-                })
-                .flatten(),
-            )
-          } else {
-            None
-          }
-        })
-        .flatten()
-        .collect()
-    } else {
-      Ok(vec![])
-    };
+    Ok(())
+  }
 
-    let entry = &mut self.stylesheets.lock().unwrap()[source_index as usize];
-    entry.stylesheet = Some(stylesheet);
-    entry.dependencies = dependencies?;
-    entry.css_modules_deps = css_modules_deps?;
+  /// Returns an error listing the chain of `@import` rules involved if the loaded files import each other
+  /// circularly.
+  fn check_import_cycles<E: std::error::Error>(&self) -> Result<(), Error<BundleErrorKind<'a, E>>> {
+    /// Searches the dependencies of a file depth first, tracking the `@import` rules that lead to the
+    /// current file. Returns the rules that form a cycle when a file that is already in the path is reached.
+    fn find_cycle<T>(
+      stylesheets: &[BundleStyleSheet<'_, '_, T>],
+      external: &ExternalImportsOptions,
+      source_index: u32,
+      visited: &mut HashSet<u32>,
+      path: &mut Vec<Location>,
+    ) -> Option<Vec<Location>> {
+      visited.insert(source_index);
 
-    Ok(source_index)
-  }
+      let entry = &stylesheets[source_index as usize];
+      let imports = entry.stylesheet.as_ref().unwrap().rules.0.iter().filter_map(|rule| match rule {
+        CssRule::Import(import) if !external.is_external(&import.url) => Some(import.loc),
+        _ => None,
+      });
 
-  fn add_css_module_dep(
-    &self,
-    file: &Path,
-    rule: &ImportRule<'a>,
-    style_loc: Location,
-    loc: crate::dependencies::Location,
-    specifier: &mut Option<Specifier>,
-  ) -> Option<Result<u32, Error<BundleErrorKind<'a, P::Error>>>> {
-    if let Some(Specifier::File(f)) = specifier {
-      let result = match self.fs.resolve(&f, file) {
-        Ok(path) => {
-          let res = self.load_file(
-            &path,
-            ImportRule {
-              layer: rule.layer.clone(),
-              media: rule.media.clone(),
-              supports: rule.supports.clone(),
-              url: "".into(),
-              loc: Location {
-                source_index: style_loc.source_index,
-                line: loc.line,
-                column: loc.column,
-              },
-            },
-          );
+      for (&dep_source_index, loc) in entry.dependencies.iter().zip(imports) {
+        path.push(loc);
+        if let Some(start) = path.iter().position(|loc| loc.source_index == dep_source_index) {
+          return Some(path.split_off(start));
+        }
 
-          if let Ok(source_index) = res {
-            *specifier = Some(Specifier::SourceIndex(source_index));
+        if !visited.contains(&dep_source_index) {
+          if let Some(chain) = find_cycle(stylesheets, external, dep_source_index, visited, path) {
+            return Some(chain);
           }
-
-          res
         }
-        Err(err) => Err(Error {
-          kind: BundleErrorKind::ResolverError(err),
-          loc: Some(ErrorLocation::new(
-            style_loc,
-            self.find_filename(style_loc.source_index),
-          )),
-        }),
-      };
-      Some(result)
-    } else {
+
+        path.pop();
+      }
+
       None
     }
+
+    let chain = {
+      let stylesheets = self.stylesheets.lock().unwrap();
+      match find_cycle(
+        &stylesheets,
+        &self.external_imports,
+        0,
+        &mut HashSet::new(),
+        &mut Vec::new(),
+      ) {
+        Some(chain) => chain,
+        None => return Ok(()),
+      }
+    };
+
+    let chain: Vec<_> = chain
+      .into_iter()
+      .map(|loc| ErrorLocation::new(loc, self.find_filename(loc.source_index)))
+      .collect();
+    Err(Error {
+      loc: chain.last().cloned(),
+      kind: BundleErrorKind::CircularImport { chain },
+    })
   }
 
   fn order(&mut self) {
@@ -643,25 +1277,53 @@ where
   }
 
   fn inline(&mut self, dest: &mut Vec<CssRule<'a, T::AtRule>>) {
-    process(self.stylesheets.get_mut().unwrap(), 0, dest);
+    let mut imports = Vec::new();
+    process(
+      self.stylesheets.get_mut().unwrap(),
+      0,
+      0,
+      &self.external_imports,
+      &mut imports,
+      dest,
+    );
 
+    // Preserved @import rules must come before all other rules except @layer statements.
+    let index = dest
+      .iter()
+      .position(|rule| !matches!(rule, CssRule::LayerStatement(_) | CssRule::Ignored))
+      .unwrap_or(dest.len());
+    dest.splice(index..index, imports);
+
+    /// Inlines a file along with its dependencies, wrapping it in the import conditions
+    /// from the given depth onward. Outer conditions have already been applied by a parent.
     fn process<'a, T>(
       stylesheets: &mut Vec<BundleStyleSheet<'a, '_, T>>,
       source_index: u32,
+      depth: usize,
+      external: &ExternalImportsOptions,
+      imports: &mut Vec<CssRule<'a, T>>,
       dest: &mut Vec<CssRule<'a, T>>,
     ) {
       let stylesheet = &mut stylesheets[source_index as usize];
       let mut rules = std::mem::take(&mut stylesheet.stylesheet.as_mut().unwrap().rules.0);
+      let conditions = std::mem::take(&mut stylesheet.conditions);
+      let loc = stylesheet.loc;
+
+      // An anonymous layer cannot be split into multiple blocks, so dependencies must be
+      // placed inside this file's conditions rather than before them.
+      let nested = has_anonymous_layer(&conditions[depth..]);
+      let dep_depth = if nested { conditions.len() } else { depth };
+      let mut deps = Vec::new();
 
       // Hoist css modules deps
       let mut dep_index = 0;
-      for i in 0..stylesheet.css_modules_deps.len() {
+      for i in 0..stylesheets[source_index as usize].css_modules_deps.len() {
         let dep_source_index = stylesheets[source_index as usize].css_modules_deps[i];
         let resolved = &stylesheets[dep_source_index as usize];
 
         // Include the dependency if this is the first instance as computed earlier.
         if resolved.parent_source_index == source_index && resolved.parent_dep_index == dep_index as u32 {
-          process(stylesheets, dep_source_index, dest);
+          process(stylesheets, dep_source_index, dep_depth, external, imports, &mut deps);
         }
 
         dep_index += 1;
@@ -670,55 +1332,78 @@ where
       let mut import_index = 0;
       for rule in &mut rules {
         match rule {
+          CssRule::Import(import) if external.is_external(&import.url) => {
+            // Preserved imports are hoisted out of this file, so they must include all of its conditions.
+            imports.push(CssRule::Import(external_import(&conditions, import).unwrap()));
+            *rule = CssRule::Ignored;
+          }
           CssRule::Import(_) => {
             let dep_source_index = stylesheets[source_index as usize].dependencies[import_index];
             let resolved = &stylesheets[dep_source_index as usize];
 
             // Include the dependency if this is the last instance as computed earlier.
             if resolved.parent_source_index == source_index && resolved.parent_dep_index == dep_index {
-              process(stylesheets, dep_source_index, dest);
+              process(stylesheets, dep_source_index, dep_depth, external, imports, &mut deps);
             }
 
             *rule = CssRule::Ignored;
             dep_index += 1;
             import_index += 1;
           }
-          CssRule::LayerStatement(_) => {
+          CssRule::LayerStatement(layer) => {
             // @layer rules are the only rules that may appear before an @import.
-            // We must preserve this order to ensure correctness.
-            let layer = std::mem::replace(rule, CssRule::Ignored);
-            dest.push(layer);
+            // We must preserve this order to ensure correctness. When hoisted out
+            // of the import conditions, prepend the names of the enclosing layers.
+            if !nested {
+              for condition in &conditions[depth..] {
+                if let Some(Some(parent_layer)) = &condition.layer {
+                  for name in &mut layer.names {
+                    name.0.insert_many(0, parent_layer.0.iter().cloned())
+                  }
+                }
+              }
+            }
+            deps.push(std::mem::replace(rule, CssRule::Ignored));
           }
           CssRule::Ignored => {}
           _ => break,
         }
       }
 
-      // Wrap rules in the appropriate @layer, @media, and @supports rules.
-      let stylesheet = &mut stylesheets[source_index as usize];
-
-      if stylesheet.layer.is_some() {
-        rules = vec![CssRule::LayerBlock(LayerBlockRule {
-          name: stylesheet.layer.take().unwrap(),
-          rules: CssRuleList(rules),
-          loc: stylesheet.loc,
-        })]
+      if nested {
+        deps.extend(rules);
+        rules = deps;
+      } else {
+        dest.extend(deps);
       }
 
-      if !stylesheet.media.media_queries.is_empty() {
-        rules = vec![CssRule::Media(MediaRule {
-          query: std::mem::replace(&mut stylesheet.media, MediaList::new()),
-          rules: CssRuleList(rules),
-          loc: stylesheet.loc,
-        })]
-      }
+      // Wrap rules in the appropriate @layer, @media, and @supports rules,
+      // starting from the innermost import conditions.
+      for condition in conditions.into_iter().skip(depth).rev() {
+        let locs = condition.locs;
+        if let Some(name) = condition.layer {
+          rules = vec![CssRule::LayerBlock(LayerBlockRule {
+            name,
+            rules: CssRuleList(rules),
+            loc: locs.layer.unwrap_or(loc),
+          })]
+        }
+
+        if !condition.media.media_queries.is_empty() {
+          rules = vec![CssRule::Media(MediaRule {
+            query: condition.media,
+            rules: CssRuleList(rules),
+            loc: locs.media.unwrap_or(loc),
+          })]
+        }
 
-      if stylesheet.supports.is_some() {
-        rules = vec![CssRule::Supports(SupportsRule {
-          condition: stylesheet.supports.take().unwrap(),
-          rules: CssRuleList(rules),
-          loc: stylesheet.loc,
-        })]
+        if let Some(condition) = condition.supports {
+          rules = vec![CssRule::Supports(SupportsRule {
+            condition,
+            rules: CssRuleList(rules),
+            loc: locs.supports.unwrap_or(loc),
+          })]
+        }
       }
 
       dest.extend(rules);
@@ -726,84 +1411,500 @@ where
   }
 }
 
-fn combine_supports<'a>(
-  a: Option<SupportsCondition<'a>>,
-  b: &Option<SupportsCondition<'a>>,
-) -> Option<SupportsCondition<'a>> {
-  if let Some(mut a) = a {
-    if let Some(b) = b {
-      a.and(b)
-    }
-    Some(a)
-  } else {
-    b.clone()
-  }
-}
-
-fn visit_vars<'a, 'b>(
-  token_list: &'b mut TokenList<'a>,
-) -> impl ParallelIterator<Item = &'b mut DashedIdentReference<'a>> {
-  let mut stack = vec![token_list.0.iter_mut()];
-  std::iter::from_fn(move || {
-    while !stack.is_empty() {
-      let iter = stack.last_mut().unwrap();
-      match iter.next() {
-        Some(TokenOrValue::Var(var)) => {
-          if let Some(fallback) = &mut var.fallback {
-            stack.push(fallback.0.iter_mut());
-          }
-          return Some(&mut var.name);
-        }
-        Some(TokenOrValue::Env(env)) => {
-          if let Some(fallback) = &mut env.fallback {
-            stack.push(fallback.0.iter_mut());
-          }
-          if let EnvironmentVariableName::Custom(name) = &mut env.name {
-            return Some(name);
-          }
-        }
-        Some(TokenOrValue::UnresolvedColor(color)) => match color {
-          UnresolvedColor::RGB { alpha, .. } | UnresolvedColor::HSL { alpha, .. } => {
-            stack.push(alpha.0.iter_mut());
-          }
-        },
-        None => {
-          stack.pop();
-        }
-        _ => {}
-      }
-    }
-    None
-  })
-  .par_bridge()
-}
-
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use crate::{
-    css_modules::{self, CssModuleExports, CssModuleReference},
-    parser::ParserFlags,
-    stylesheet::{MinifyOptions, PrinterOptions},
-    targets::{Browsers, Targets},
-  };
-  use indoc::indoc;
-  use std::collections::HashMap;
+impl<'a, 'o, 's, P: SourceProvider, T: AtRuleParser<'a> + Clone + Sync + Send> Bundler<'a, 'o, 's, P, T>
+where
+  T::AtRule: Sync + Send + ToCss + Clone,
+{
+  /// Bundles the given entry file and all dependencies into a single style sheet.
+  pub fn bundle<'e>(
+    &mut self,
+    entry: &'e Path,
+  ) -> Result<StyleSheet<'a, 'o, T::AtRule>, Error<BundleErrorKind<'a, P::Error>>> {
+    // Phase 1: load and parse all files. This is done in parallel.
+    self.load_file(&entry, Self::entry_context())?;
+    self.check_import_cycles()?;
+    self.resolve_conditions()?;
+    self.check_external_imports()?;
 
-  #[derive(Clone)]
-  struct TestProvider {
-    map: HashMap<PathBuf, String>,
+    Ok(self.finish())
   }
 
-  impl SourceProvider for TestProvider {
-    type Error = std::io::Error;
-
-    fn read<'a>(&'a self, file: &Path) -> Result<&'a str, Self::Error> {
-      Ok(self.map.get(file).unwrap())
+  fn load_file(&self, file: &Path, rule: ImportContext<'a>) -> Result<u32, Error<BundleErrorKind<'a, P::Error>>> {
+    let (source_index, is_new) = self.register_file(file, &rule);
+    if !is_new {
+      return Ok(source_index);
     }
 
-    fn resolve(&self, specifier: &str, originating_file: &Path) -> Result<PathBuf, Self::Error> {
-      Ok(originating_file.with_file_name(specifier))
+    let code = self.fs.read(file).map_err(|e| self.read_error(e, &rule))?;
+    let mut stylesheet = self.parse_file(file, source_index, code)?;
+
+    // Collect and load dependencies for this stylesheet in parallel.
+    let dependencies: Result<Vec<u32>, _> = stylesheet
+      .rules
+      .0
+      .par_iter()
+      .filter(|r| !matches!(r, CssRule::Import(import) if self.external_imports.is_external(&import.url)))
+      .filter_map(|r| {
+        if let CssRule::Import(import) = r {
+          let result = match self.fs.resolve(&import.url, file) {
+            Ok(path) => self.load_file(&path, Self::import_context(&rule, import)),
+            Err(err) => Err(Error {
+              kind: BundleErrorKind::ResolverError(err),
+              loc: Some(ErrorLocation::new(
+                import.loc,
+                self.find_filename(import.loc.source_index),
+              )),
+            }),
+          };
+
+          Some(result)
+        } else {
+          None
+        }
+      })
+      .collect();
+
+    // Collect CSS modules dependencies from the `composes` property.
+    let css_modules_deps: Result<Vec<u32>, _> = if self.options.css_modules.is_some() {
+      stylesheet
+        .rules
+        .0
+        .par_iter_mut()
+        .filter_map(|r| {
+          if let CssRule::Style(style) = r {
+            Some(
+              style
+                .declarations
+                .declarations
+                .par_iter_mut()
+                .chain(style.declarations.important_declarations.par_iter_mut())
+                .filter_map(|d| match d {
+                  Property::Composes(composes) => self
+                    .add_css_module_dep(file, &rule, style.loc, composes.loc, &mut composes.from)
+                    .map(|result| rayon::iter::Either::Left(rayon::iter::once(result))),
+
+                  // Handle variable references if the dashed_idents option is present.
+                  Property::Custom(CustomProperty { value, .. })
+                  | Property::Unparsed(UnparsedProperty { value, .. })
+                    if matches!(&self.options.css_modules, Some(css_modules) if css_modules.dashed_idents) =>
+                  {
+                    Some(rayon::iter::Either::Right(visit_vars(value).par_bridge().filter_map(
+                      |name| {
+                        self.add_css_module_dep(
+                          file,
+                          &rule,
+                          style.loc,
+                          // TODO: store loc in variable reference?
+                          crate::dependencies::Location {
+                            line: style.loc.line,
+                            column: style.loc.column,
+                          },
+                          &mut name.from,
+                        )
+                      },
+                    )))
+                  }
+                  _ => None,
+                })
+                .flatten(),
+            )
+          } else {
+            None
+          }
+        })
+        .flatten()
+        .collect()
+    } else {
+      Ok(vec![])
+    };
+
+    self.store_file(source_index, code, stylesheet, dependencies?, css_modules_deps?);
+
+    Ok(source_index)
+  }
+
+  fn add_css_module_dep(
+    &self,
+    file: &Path,
+    rule: &ImportContext<'a>,
+    style_loc: Location,
+    loc: crate::dependencies::Location,
+    specifier: &mut Option<Specifier>,
+  ) -> Option<Result<u32, Error<BundleErrorKind<'a, P::Error>>>> {
+    if let Some(Specifier::File(f)) = specifier {
+      let result = match self.fs.resolve(&f, file) {
+        Ok(path) => {
+          let res = self.load_file(&path, Self::css_module_dep_context(rule, style_loc, loc));
+
+          if let Ok(source_index) = res {
+            *specifier = Some(Specifier::SourceIndex(source_index));
+          }
+
+          res
+        }
+        Err(err) => Err(Error {
+          kind: BundleErrorKind::ResolverError(err),
+          loc: Some(ErrorLocation::new(
+            style_loc,
+            self.find_filename(style_loc.source_index),
+          )),
+        }),
+      };
+      Some(result)
+    } else {
+      None
+    }
+  }
+}
+
+#[cfg(feature = "async")]
+impl<'a, 'o, 's, P: AsyncSourceProvider, T: AtRuleParser<'a> + Clone + Sync + Send> Bundler<'a, 'o, 's, P, T>
+where
+  T::AtRule: Sync + Send + ToCss + Clone,
+{
+  /// Bundles the given entry file and all dependencies into a single style sheet, using an
+  /// [AsyncSourceProvider](AsyncSourceProvider). The dependencies of each file are resolved and
+  /// read concurrently.
+  ///
+  /// The returned future is not `Send`, so it must be awaited on the current task, or driven by an executor
+  /// such as `futures::executor::block_on`.
+  #[allow(clippy::type_complexity)]
+  pub fn bundle_async<'b>(
+    &'b mut self,
+    entry: &Path,
+  ) -> BoxFuture<'b, Result<StyleSheet<'a, 'o, T::AtRule>, Error<BundleErrorKind<'a, P::Error>>>> {
+    let entry = entry.to_owned();
+    Box::pin(async move {
+      // Phase 1: load and parse all files. This is done concurrently.
+      self.load_file_async(entry, Self::entry_context()).await?;
+      self.check_import_cycles()?;
+      self.resolve_conditions()?;
+      self.check_external_imports()?;
+
+      Ok(self.finish())
+    })
+  }
+
+  fn load_file_async<'f>(
+    &'f self,
+    file: PathBuf,
+    rule: ImportContext<'a>,
+  ) -> BoxFuture<'f, Result<u32, Error<BundleErrorKind<'a, P::Error>>>> {
+    Box::pin(async move {
+      let (source_index, is_new) = self.register_file(&file, &rule);
+      if !is_new {
+        return Ok(source_index);
+      }
+
+      let code = self.fs.read(&file).await.map_err(|e| self.read_error(e, &rule))?;
+      let mut stylesheet = self.parse_file(&file, source_index, code)?;
+
+      // Resolve and load dependencies for this stylesheet concurrently.
+      let mut imports: Vec<BoxFuture<'_, _>> = Vec::new();
+      for r in &stylesheet.rules.0 {
+        if let CssRule::Import(import) = r {
+          if self.external_imports.is_external(&import.url) {
+            continue;
+          }
+
+          let import_context = Self::import_context(&rule, import);
+          let specifier = import.url.clone();
+          let file = &file;
+          imports.push(Box::pin(async move {
+            let path = self.fs.resolve(&specifier, file).await.map_err(|err| Error {
+              kind: BundleErrorKind::ResolverError(err),
+              loc: Some(ErrorLocation::new(
+                import_context.loc,
+                self.find_filename(import_context.loc.source_index),
+              )),
+            })?;
+            self.load_file_async(path, import_context).await
+          }));
+        }
+      }
+
+      let dependencies = join_all(imports).await.into_iter().collect::<Result<Vec<_>, _>>()?;
+
+      // Collect CSS modules dependencies from the `composes` property.
+      let mut css_modules_deps = Vec::new();
+      if let Some(css_modules) = &self.options.css_modules {
+        let mut specifiers = Vec::new();
+        for r in &mut stylesheet.rules.0 {
+          if let CssRule::Style(style) = r {
+            let style_loc = style.loc;
+            for d in style
+              .declarations
+              .declarations
+              .iter_mut()
+              .chain(style.declarations.important_declarations.iter_mut())
+            {
+              match d {
+                Property::Composes(composes) => specifiers.push((style_loc, composes.loc, &mut composes.from)),
+
+                // Handle variable references if the dashed_idents option is present.
+                Property::Custom(CustomProperty { value, .. })
+                | Property::Unparsed(UnparsedProperty { value, .. })
+                  if css_modules.dashed_idents =>
+                {
+                  for name in visit_vars(value) {
+                    let loc = crate::dependencies::Location {
+                      line: style_loc.line,
+                      column: style_loc.column,
+                    };
+                    specifiers.push((style_loc, loc, &mut name.from));
+                  }
+                }
+                _ => {}
+              }
+            }
+          }
+        }
+
+        specifiers.retain(|(_, _, specifier)| matches!(specifier, Some(Specifier::File(_))));
+
+        let mut deps: Vec<BoxFuture<'_, _>> = Vec::new();
+        for (style_loc, loc, specifier) in &specifiers {
+          let Some(Specifier::File(f)) = specifier else { unreachable!() };
+          let f = f.clone();
+          let dep_context = Self::css_module_dep_context(&rule, *style_loc, *loc);
+          let style_loc = *style_loc;
+          let file = &file;
+          deps.push(Box::pin(async move {
+            let path = self.fs.resolve(&f, file).await.map_err(|err| Error {
+              kind: BundleErrorKind::ResolverError(err),
+              loc: Some(ErrorLocation::new(
+                style_loc,
+                self.find_filename(style_loc.source_index),
+              )),
+            })?;
+            self.load_file_async(path, dep_context).await
+          }));
+        }
+
+        for ((_, _, specifier), result) in specifiers.into_iter().zip(join_all(deps).await) {
+          let source_index = result?;
+          *specifier = Some(Specifier::SourceIndex(source_index));
+          css_modules_deps.push(source_index);
+        }
+      }
+
+      self.store_file(source_index, code, stylesheet, dependencies, css_modules_deps);
+
+      Ok(source_index)
+    })
+  }
+}
+
+/// Resolves CSS modules `composes` dependencies, e.g. `composes: foo from "./other.css"`, in the exports
+/// of a style sheet that was compiled on its own rather than bundled.
+///
+/// Each referenced file is resolved and read using the given [SourceProvider](SourceProvider), and parsed
+/// with the same CSS modules config to find the compiled names that it exports. The
+/// [Dependency](CssModuleReference::Dependency) references in `exports` are then replaced with local references
+/// to these names, along with any names they compose in turn. Names that are not exported by the referenced file
+/// are omitted.
+pub fn resolve_css_module_dependencies<'a, P: SourceProvider>(
+  exports: &mut CssModuleExports,
+  filename: &str,
+  fs: &'a P,
+  config: &css_modules::Config<'a>,
+  project_root: Option<&str>,
+) -> Result<(), Error<BundleErrorKind<'a, P::Error>>> {
+  let mut cache = HashMap::new();
+  resolve_css_module_exports(exports, Path::new(filename), fs, config, project_root, &mut cache)
+}
+
+fn resolve_css_module_exports<'a, P: SourceProvider>(
+  exports: &mut CssModuleExports,
+  file: &Path,
+  fs: &'a P,
+  config: &css_modules::Config<'a>,
+  project_root: Option<&str>,
+  cache: &mut HashMap<PathBuf, CssModuleExports>,
+) -> Result<(), Error<BundleErrorKind<'a, P::Error>>> {
+  for export in exports.values_mut() {
+    if !export
+      .composes
+      .iter()
+      .any(|reference| matches!(reference, CssModuleReference::Dependency { .. }))
+    {
+      continue;
+    }
+
+    let mut composes = Vec::new();
+    for reference in std::mem::take(&mut export.composes) {
+      let CssModuleReference::Dependency { name, specifier } = reference else {
+        composes.push(reference);
+        continue;
+      };
+
+      let path = fs.resolve(&specifier, file).map_err(|err| Error {
+        kind: BundleErrorKind::ResolverError(err),
+        loc: None,
+      })?;
+
+      if !cache.contains_key(&path) {
+        // Insert a placeholder first so that circular dependencies terminate.
+        cache.insert(path.clone(), HashMap::new());
+        let mut dep_exports = load_css_module_exports(&path, fs, config, project_root)?;
+        resolve_css_module_exports(&mut dep_exports, &path, fs, config, project_root, cache)?;
+        cache.insert(path.clone(), dep_exports);
+      }
+
+      if let Some(dep) = cache[&path].get(&name) {
+        let references = std::iter::once(CssModuleReference::Local { name: dep.name.clone() });
+        for reference in references.chain(dep.composes.iter().cloned()) {
+          if !composes.contains(&reference) {
+            composes.push(reference);
+          }
+        }
+      }
+    }
+
+    export.composes = composes;
+  }
+
+  Ok(())
+}
+
+fn load_css_module_exports<'a, P: SourceProvider>(
+  file: &Path,
+  fs: &'a P,
+  config: &css_modules::Config<'a>,
+  project_root: Option<&str>,
+) -> Result<CssModuleExports, Error<BundleErrorKind<'a, P::Error>>> {
+  let code = fs.read(file).map_err(|err| Error {
+    kind: BundleErrorKind::ResolverError(err),
+    loc: None,
+  })?;
+
+  // Exports are looked up by their original names, so the exports convention is not applied here.
+  let stylesheet = StyleSheet::parse(
+    code,
+    ParserOptions {
+      filename: file.to_str().unwrap().into(),
+      css_modules: Some(css_modules::Config {
+        exports_convention: css_modules::ExportsConvention::AsIs,
+        ..config.clone()
+      }),
+      ..ParserOptions::default()
+    },
+  )?;
+
+  let res = stylesheet.to_css(PrinterOptions {
+    project_root,
+    ..PrinterOptions::default()
+  })?;
+  Ok(res.exports.unwrap_or_default())
+}
+
+/// Returns a preserved `@import` rule with the conditions that the file containing it was imported with
+/// combined into its own, or `None` if they cannot be expressed as a single `@import` rule.
+fn external_import<'i>(conditions: &[ImportCondition<'i>], import: &ImportRule<'i>) -> Option<ImportRule<'i>> {
+  // An anonymous layer cannot be referenced outside of its block.
+  if has_anonymous_layer(conditions) {
+    return None;
+  }
+
+  let mut condition = ImportCondition::default();
+  for c in conditions.iter().chain(std::iter::once(&ImportCondition::from_import(import))) {
+    condition = condition.and(c)?;
+  }
+
+  Some(ImportRule {
+    url: import.url.clone(),
+    layer: condition.layer,
+    supports: condition.supports,
+    media: condition.media,
+    loc: import.loc,
+  })
+}
+
+fn has_anonymous_layer(conditions: &[ImportCondition]) -> bool {
+  conditions.iter().any(|condition| condition.layer == Some(None))
+}
+
+fn has_layer(conditions: &[ImportCondition]) -> bool {
+  conditions.iter().any(|condition| condition.layer.is_some())
+}
+
+fn combine_supports<'a>(
+  a: Option<SupportsCondition<'a>>,
+  b: &Option<SupportsCondition<'a>>,
+) -> Option<SupportsCondition<'a>> {
+  if let Some(mut a) = a {
+    if let Some(b) = b {
+      a.and(b)
+    }
+    Some(a)
+  } else {
+    b.clone()
+  }
+}
+
+fn visit_vars<'a, 'b>(
+  token_list: &'b mut TokenList<'a>,
+) -> impl Iterator<Item = &'b mut DashedIdentReference<'a>> {
+  let mut stack = vec![token_list.0.iter_mut()];
+  std::iter::from_fn(move || {
+    while !stack.is_empty() {
+      let iter = stack.last_mut().unwrap();
+      match iter.next() {
+        Some(TokenOrValue::Var(var)) => {
+          if let Some(fallback) = &mut var.fallback {
+            stack.push(fallback.0.iter_mut());
+          }
+          return Some(&mut var.name);
+        }
+        Some(TokenOrValue::Env(env)) => {
+          if let Some(fallback) = &mut env.fallback {
+            stack.push(fallback.0.iter_mut());
+          }
+          if let EnvironmentVariableName::Custom(name) = &mut env.name {
+            return Some(name);
+          }
+        }
+        Some(TokenOrValue::UnresolvedColor(color)) => match color {
+          UnresolvedColor::RGB { alpha, .. } | UnresolvedColor::HSL { alpha, .. } => {
+            stack.push(alpha.0.iter_mut());
+          }
+        },
+        None => {
+          stack.pop();
+        }
+        _ => {}
+      }
+    }
+    None
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    css_modules::{self, CssModuleExports, CssModuleReference},
+    parser::ParserFlags,
+    stylesheet::{MinifyOptions, PrinterOptions, RebaseUrlsOptions},
+    targets::{Browsers, Targets},
+  };
+  use indoc::indoc;
+  use std::collections::HashMap;
+
+  #[derive(Clone)]
+  struct TestProvider {
+    map: HashMap<PathBuf, String>,
+  }
+
+  impl SourceProvider for TestProvider {
+    type Error = std::io::Error;
+
+    fn read<'a>(&'a self, file: &Path) -> Result<&'a str, Self::Error> {
+      Ok(self.map.get(file).unwrap())
+    }
+
+    fn resolve(&self, specifier: &str, originating_file: &Path) -> Result<PathBuf, Self::Error> {
+      Ok(originating_file.with_file_name(specifier))
     }
   }
 
@@ -1133,15 +2234,17 @@ mod tests {
       TestProvider {
         map: fs! {
           "/a.css": r#"
-          @import "b.css" print;
+          @import "b.css" supports(display: grid) print;
+          @import "b.css" supports(display: grid) screen;
+          @import "c.css" supports(display: grid) print;
+          @import "c.css" supports(display: flex) print;
           .a { color: red }
         "#,
           "/b.css": r#"
-          @import "c.css" (color);
-          .b { color: yellow }
+          .b { color: green }
         "#,
           "/c.css": r#"
-          .c { color: green }
+          .c { color: yellow }
         "#
         },
       },
@@ -1150,14 +2253,57 @@ mod tests {
     assert_eq!(
       res,
       indoc! { r#"
-      @media print and (color) {
-        .c {
-          color: green;
-        }
+      @supports (display: grid) {
+        @media print, screen {
+          .b {
+            color: green;
+          }
+        }
+      }
+
+      @supports (display: grid) or (display: flex) {
+        @media print {
+          .c {
+            color: #ff0;
+          }
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    let res = bundle(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" supports(display: grid) print;
+          @import "b.css";
+          @import "c.css" print;
+          @import "c.css" supports(display: grid) print;
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          .c { color: yellow }
+        "#
+        },
+      },
+      "/a.css",
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      .b {
+        color: green;
       }
 
       @media print {
-        .b {
+        .c {
           color: #ff0;
         }
       }
@@ -1172,15 +2318,57 @@ mod tests {
       TestProvider {
         map: fs! {
           "/a.css": r#"
-          @import "b.css";
+          @import "b.css" print;
+          @import "c.css";
           .a { color: red }
         "#,
           "/b.css": r#"
+          @import "c.css" (min-width: 500px), (color);
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          .c { color: yellow }
+        "#
+        },
+      },
+      "/a.css",
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @media print {
+        .b {
+          color: green;
+        }
+      }
+
+      .c {
+        color: #ff0;
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    let res = bundle(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" screen;
           @import "c.css";
+        "#,
+          "/b.css": r#"
+          @import "c.css" (min-width: 1px), print;
+          .b { color: green }
         "#,
           "/c.css": r#"
-          @import "a.css";
-          .c { color: green }
+          @import "d.css" supports(display: grid);
+          .c { color: red }
+        "#,
+          "/d.css": r#"
+          .d { color: blue }
         "#
         },
       },
@@ -1189,8 +2377,73 @@ mod tests {
     assert_eq!(
       res,
       indoc! { r#"
+      @media screen {
+        .b {
+          color: green;
+        }
+      }
+
+      @supports (display: grid) {
+        .d {
+          color: #00f;
+        }
+      }
+
       .c {
-        color: green;
+        color: red;
+      }
+    "#}
+    );
+
+    error_test(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" print;
+          @import "b.css" supports(display: grid);
+        "#,
+          "/b.css": r#"
+          .b { color: green }
+        "#
+        },
+      },
+      "/a.css",
+      Some(Box::new(|err| {
+        assert!(matches!(err, BundleErrorKind::UnsupportedImportCondition));
+      })),
+    );
+
+    let res = bundle(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" print;
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @import "c.css" (color);
+          .b { color: yellow }
+        "#,
+          "/c.css": r#"
+          .c { color: green }
+        "#
+        },
+      },
+      "/a.css",
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @media print and (color) {
+        .c {
+          color: green;
+        }
+      }
+
+      @media print {
+        .b {
+          color: #ff0;
+        }
       }
 
       .a {
@@ -1199,6 +2452,28 @@ mod tests {
     "#}
     );
 
+    error_test(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css";
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @import "c.css";
+        "#,
+          "/c.css": r#"
+          @import "a.css";
+          .c { color: green }
+        "#
+        },
+      },
+      "/a.css",
+      Some(Box::new(|err| {
+        assert!(matches!(err, BundleErrorKind::CircularImport { .. }));
+      })),
+    );
+
     let res = bundle(
       TestProvider {
         map: fs! {
@@ -1553,7 +2828,7 @@ mod tests {
       })),
     );
 
-    error_test(
+    let res = bundle(
       TestProvider {
         map: fs! {
           "/a.css": r#"
@@ -1570,12 +2845,29 @@ mod tests {
         },
       },
       "/a.css",
-      Some(Box::new(|err| {
-        assert!(matches!(err, BundleErrorKind::UnsupportedLayerCombination));
-      })),
     );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @layer {
+        @layer {
+          .c {
+            color: green;
+          }
+        }
 
-    error_test(
+        .b {
+          color: green;
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    let res = bundle(
       TestProvider {
         map: fs! {
           "/a.css": r#"
@@ -1592,9 +2884,26 @@ mod tests {
         },
       },
       "/a.css",
-      Some(Box::new(|err| {
-        assert!(matches!(err, BundleErrorKind::UnsupportedLayerCombination));
-      })),
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @layer {
+        @layer foo {
+          .c {
+            color: green;
+          }
+        }
+
+        .b {
+          color: green;
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
     );
 
     let res = bundle(
@@ -1731,63 +3040,181 @@ mod tests {
     //   "#
     // }, "/a.css");
 
-    // let res = bundle(fs! {
-    //   "/a.css": r#"
-    //     @import "b.css" not print;
-    //     .a { color: red }
-    //   "#,
-    //   "/b.css": r#"
-    //     @import "c.css" not screen;
-    //     .b { color: green }
-    //   "#,
-    //   "/c.css": r#"
-    //     .c { color: yellow }
-    //   "#
-    // }, "/a.css");
-  }
-
-  #[test]
-  fn test_css_module() {
-    macro_rules! map {
-      { $($key:expr => $val:expr),* } => {
-        HashMap::from([
-          $(($key.to_owned(), $val.to_owned()),)*
-        ])
-      };
-    }
-
-    let (code, exports) = bundle_css_module(
+    let res = bundle(
       TestProvider {
         map: fs! {
           "/a.css": r#"
-          @import "b.css";
+          @import "b.css" not print;
           .a { color: red }
         "#,
           "/b.css": r#"
-          .a { color: green }
+          @import "c.css" not screen;
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          .c { color: yellow }
         "#
         },
       },
       "/a.css",
-      None,
     );
     assert_eq!(
-      code,
+      res,
       indoc! { r#"
-      ._9z6RGq_a {
-        color: green;
+      @media not print {
+        @media not screen {
+          .c {
+            color: #ff0;
+          }
+        }
       }
 
-      ._6lixEq_a {
+      @media not print {
+        .b {
+          color: green;
+        }
+      }
+
+      .a {
         color: red;
       }
     "#}
     );
-    assert_eq!(
-      flatten_exports(exports),
-      map! {
-        "a" => "_6lixEq_a"
-      }
+
+    let res = bundle(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" supports(display: grid) screen;
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @import "c.css" layer(foo) print, (min-width: 500px);
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          .c { color: yellow }
+        "#
+        },
+      },
+      "/a.css",
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @supports (display: grid) {
+        @media screen {
+          @media print, (width >= 500px) {
+            @layer foo {
+              .c {
+                color: #ff0;
+              }
+            }
+          }
+        }
+      }
+
+      @supports (display: grid) {
+        @media screen {
+          .b {
+            color: green;
+          }
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    let res = bundle(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css" layer print;
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @layer x, y;
+          @import "c.css" supports(display: grid);
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          .c { color: yellow }
+        "#
+        },
+      },
+      "/a.css",
+    );
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @media print {
+        @layer {
+          @layer x, y;
+
+          @supports (display: grid) {
+            .c {
+              color: #ff0;
+            }
+          }
+
+          .b {
+            color: green;
+          }
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+  }
+
+  #[test]
+  fn test_css_module() {
+    macro_rules! map {
+      { $($key:expr => $val:expr),* } => {
+        HashMap::from([
+          $(($key.to_owned(), $val.to_owned()),)*
+        ])
+      };
+    }
+
+    let (code, exports) = bundle_css_module(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css";
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          .a { color: green }
+        "#
+        },
+      },
+      "/a.css",
+      None,
+    );
+    assert_eq!(
+      code,
+      indoc! { r#"
+      ._9z6RGq_a {
+        color: green;
+      }
+
+      ._6lixEq_a {
+        color: red;
+      }
+    "#}
+    );
+    assert_eq!(
+      flatten_exports(exports),
+      map! {
+        "a" => "_6lixEq_a"
+      }
     );
 
     let (code, exports) = bundle_css_module(
@@ -1976,6 +3403,98 @@ mod tests {
     assert_eq!(code, expected);
   }
 
+  #[test]
+  fn test_css_module_manifest() {
+    let fs = TestProvider {
+      map: fs! {
+        "/a.css": r#"
+        @import "b.css";
+        .a { color: red }
+      "#,
+        "/b.css": r#"
+        .b { color: green }
+      "#
+      },
+    };
+    let mut bundler = Bundler::new(
+      &fs,
+      None,
+      ParserOptions {
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
+    );
+    let stylesheet = bundler.bundle(Path::new("/a.css")).unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    let manifest = res.manifest.unwrap();
+    assert_eq!(manifest.len(), 2);
+    assert_eq!(manifest["/a.css"]["a"].name, "_6lixEq_a");
+    assert_eq!(manifest["/b.css"]["b"].name, "_9z6RGq_b");
+    assert_eq!(res.exports.unwrap(), manifest["/a.css"]);
+  }
+
+  #[test]
+  fn test_resolve_css_module_dependencies() {
+    macro_rules! map {
+      { $($key:expr => $val:expr),* } => {
+        HashMap::from([
+          $(($key.to_owned(), $val.to_owned()),)*
+        ])
+      };
+    }
+
+    let fs = CustomProvider {
+      map: fs! {
+        "/b.css": r#"
+        .x { composes: y; composes: z from 'foo:/c.css'; background: green }
+        .y { font: Helvetica }
+      "#,
+        "/c.css": r#"
+        .z { composes: w from 'foo:/b.css'; color: blue }
+      "#
+      },
+    };
+
+    let config = css_modules::Config::default();
+    let stylesheet = StyleSheet::parse(
+      r#"
+      .a { composes: x from 'foo:/b.css'; color: red; }
+      .b { composes: missing from 'foo:/b.css'; color: yellow }
+    "#,
+      ParserOptions {
+        filename: "/a.css".into(),
+        css_modules: Some(config.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let mut exports = stylesheet.to_css(PrinterOptions::default()).unwrap().exports.unwrap();
+    resolve_css_module_dependencies(&mut exports, "/a.css", &fs, &config, None).unwrap();
+    assert_eq!(
+      flatten_exports(exports),
+      map! {
+        "a" => "_6lixEq_a _9z6RGq_x _9z6RGq_y L4EWVa_z",
+        "b" => "_6lixEq_b"
+      }
+    );
+
+    let mut exports = StyleSheet::parse(
+      ".a { composes: x from './b.css' }",
+      ParserOptions {
+        filename: "/a.css".into(),
+        css_modules: Some(config.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap()
+    .to_css(PrinterOptions::default())
+    .unwrap()
+    .exports
+    .unwrap();
+    let err = resolve_css_module_dependencies(&mut exports, "/a.css", &fs, &config, None).unwrap_err();
+    assert!(matches!(err.kind, BundleErrorKind::ResolverError(_)));
+  }
+
   #[test]
   fn test_source_map() {
     let source = r#".imported {
@@ -2021,6 +3540,115 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_source_map_nested_imports() {
+    // b.css has an input source map, so its rules are mapped to b.scss instead.
+    let fs = TestProvider {
+      map: fs! {
+        "/a.css": "@import \"b.css\";\n  @import \"c.css\" print;\n.a { color: red }\n",
+        "/b.css": ".b { color: green }\n/*# sourceMappingURL=data:application/json;base64,eyJ2ZXJzaW9uIjozLCJzb3VyY2VzIjpbImIuc2NzcyJdLCJzb3VyY2VzQ29udGVudCI6WyIuYiB7IGNvbG9yOiBncmVlbiB9Il0sIm1hcHBpbmdzIjoiQUFBQSIsIm5hbWVzIjpbXX0= */",
+        "/c.css": "/* c */ @import \"d.css\" layer(foo);\n.c { color: blue }\n",
+        "/d.css": "\n  @import \"e.css\" supports(display: grid);\n  .d { color: pink }\n",
+        "/e.css": "\n\n    .e { color: yellow }\n"
+      },
+    };
+
+    let mut sm = parcel_sourcemap::SourceMap::new("/");
+    let mut bundler = Bundler::new(&fs, Some(&mut sm), ParserOptions::default());
+    let stylesheet = bundler.bundle(Path::new("/a.css")).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        source_map: Some(&mut sm),
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+
+    assert_eq!(
+      res.code,
+      ".b{color:green}@supports (display:grid){@media print{@layer foo{.e{color:#ff0}}}}@media print{@layer foo{.d{color:pink}}}@media print{.c{color:#00f}}.a{color:red}"
+    );
+
+    let mappings = sm
+      .get_mappings()
+      .into_iter()
+      .map(|mapping| {
+        let original = mapping.original.unwrap();
+        (
+          &res.code[mapping.generated_column as usize..][..4],
+          sm.get_source(original.source).unwrap(),
+          original.original_line,
+          original.original_column,
+        )
+      })
+      .collect::<Vec<_>>();
+
+    assert_eq!(
+      mappings,
+      vec![
+        (".b{c", "b.scss", 0, 0),
+        ("@sup", "d.css", 1, 2),
+        ("@med", "a.css", 1, 2),
+        ("@lay", "c.css", 0, 8),
+        (".e{c", "e.css", 2, 4),
+        ("@med", "a.css", 1, 2),
+        ("@lay", "c.css", 0, 8),
+        (".d{c", "d.css", 2, 2),
+        ("@med", "a.css", 1, 2),
+        (".c{c", "c.css", 1, 0),
+        (".a{c", "a.css", 2, 0),
+      ]
+    );
+    assert_eq!(sm.get_sources(), &vec!["a.css", "c.css", "d.css", "e.css", "b.scss"]);
+  }
+
+  #[test]
+  fn test_rebase_urls() {
+    let fs = TestProvider {
+      map: fs! {
+        "/src/index.css": r#"
+          @import "components/button.css";
+          @import "vendor/fonts.css";
+          .a { background: url(images/bg.png) }
+        "#,
+        "/src/components/button.css": r#"
+          .button { background: url("../images/button.png#icon") }
+        "#,
+        "/src/vendor/fonts.css": r#"
+          @font-face { font-family: Foo; src: url(fonts/foo.woff2) }
+        "#
+      },
+    };
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let stylesheet = bundler.bundle(Path::new("/src/index.css")).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        rebase_urls: Some(RebaseUrlsOptions {
+          output_file: "/dist/index.css",
+          public_path: None,
+        }),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      indoc! { r#"
+      .button {
+        background: url("../src/images/button.png#icon");
+      }
+
+      @font-face {
+        font-family: Foo;
+        src: url("../src/vendor/fonts/foo.woff2");
+      }
+
+      .a {
+        background: url("../src/images/bg.png");
+      }
+    "#}
+    );
+  }
+
   #[test]
   fn test_license_comments() {
     let res = bundle(
@@ -2054,4 +3682,551 @@ mod tests {
     "#}
     );
   }
+
+  fn bundle_external_imports<'a, P: SourceProvider>(
+    fs: &'a P,
+    entry: &str,
+    options: ExternalImportsOptions,
+  ) -> Result<String, Error<BundleErrorKind<'a, P::Error>>> {
+    let mut bundler = Bundler::new(fs, None, ParserOptions::default());
+    bundler.set_external_imports(options);
+    let stylesheet = bundler.bundle(Path::new(entry))?;
+    Ok(stylesheet.to_css(PrinterOptions::default()).unwrap().code)
+  }
+
+  #[test]
+  fn test_external_imports() {
+    let res = bundle_external_imports(
+      &TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @layer base;
+          @import "https://example.com/a.css";
+          @import "b.css" print;
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @import url("//cdn.example.com/b.css") (min-width: 500px);
+          @import "c.css" layer(foo);
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          @import "fonts/font.css" supports(display: grid);
+          .c { color: blue }
+        "#
+        },
+      },
+      "/a.css",
+      ExternalImportsOptions {
+        remote: true,
+        include: vec!["fonts/*".into()],
+      },
+    )
+    .unwrap();
+    assert_eq!(
+      res,
+      indoc! { r#"
+      @layer base;
+      @import "https://example.com/a.css";
+      @import "//cdn.example.com/b.css" print and (width >= 500px);
+      @import "fonts/font.css" layer(foo) supports(display: grid) print;
+
+      @media print {
+        @layer foo {
+          .c {
+            color: #00f;
+          }
+        }
+      }
+
+      @media print {
+        .b {
+          color: green;
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    let fs = TestProvider {
+      map: fs! {
+        "/a.css": r#"
+          @import "b.css" layer;
+          .a { color: red }
+        "#,
+        "/b.css": r#"
+          @import "https://example.com/b.css";
+          .b { color: green }
+        "#
+      },
+    };
+    let err = bundle_external_imports(
+      &fs,
+      "/a.css",
+      ExternalImportsOptions {
+        remote: true,
+        ..Default::default()
+      },
+    )
+    .unwrap_err();
+    assert!(matches!(err.kind, BundleErrorKind::UnsupportedImportCondition));
+    assert_eq!(
+      err.loc,
+      Some(ErrorLocation {
+        filename: "/b.css".into(),
+        line: 1,
+        column: 11
+      })
+    );
+  }
+
+  #[test]
+  fn test_memory_provider() {
+    let mut fs: MemoryProvider = [
+      (
+        "src/index.css",
+        r#"
+          @import "./components/button.css";
+          .a { color: red }
+        "#,
+      ),
+      (
+        "src/components/button.css",
+        r#"
+          @import "../theme/colors.css";
+          .button { color: green }
+        "#,
+      ),
+    ]
+    .into_iter()
+    .collect();
+    fs.add_file("src/theme/../theme/colors.css", ".colors { color: blue }");
+
+    let res = bundle(fs.clone(), "src/index.css");
+    assert_eq!(
+      res,
+      indoc! { r#"
+      .colors {
+        color: #00f;
+      }
+
+      .button {
+        color: green;
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+
+    assert_eq!(
+      fs.remove_file("src/theme/colors.css").as_deref(),
+      Some(".colors { color: blue }")
+    );
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let err = bundler.bundle(Path::new("src/index.css")).unwrap_err();
+    assert_eq!(
+      err.to_string(),
+      "File not found: src/theme/colors.css at src/components/button.css:1:11"
+    );
+  }
+
+  #[test]
+  fn test_dependency_graph() {
+    let fs = TestProvider {
+      map: fs! {
+        "/a.css": r#"
+          @import "b.css" layer(foo) supports(display: grid) print;
+          @import "https://example.com/reset.css";
+          .a { background: url(images/bg.png), url("data:image/png;base64,AAAA") }
+        "#,
+        "/b.css": r#"
+          @import "c.css" layer;
+          .b { composes: d from "d.css"; mask: url('../icons/mask.svg#icon') }
+          @media print {
+            .c { background: url(https://example.com/bg.png) }
+          }
+        "#,
+        "/c.css": r#"
+          .c { color: green }
+        "#,
+        "/d.css": r#"
+          .d { color: blue }
+        "#
+      },
+    };
+    let mut bundler = Bundler::new(
+      &fs,
+      None,
+      ParserOptions {
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
+    );
+    bundler.set_external_imports(ExternalImportsOptions {
+      remote: true,
+      ..Default::default()
+    });
+    bundler.bundle(Path::new("/a.css")).unwrap();
+    let graph = bundler.dependency_graph();
+
+    assert_eq!(
+      graph.files.iter().map(|f| f.file.as_str()).collect::<Vec<_>>(),
+      vec!["/a.css", "/b.css", "/c.css", "/d.css"]
+    );
+    assert_eq!(
+      graph.files[0].imports,
+      vec![
+        ImportEdge {
+          url: "b.css".into(),
+          file: Some("/b.css".into()),
+          layer: Some("foo".into()),
+          supports: Some("(display: grid)".into()),
+          media: Some("print".into()),
+          loc: crate::dependencies::Location { line: 2, column: 11 },
+        },
+        ImportEdge {
+          url: "https://example.com/reset.css".into(),
+          file: None,
+          layer: None,
+          supports: None,
+          media: None,
+          loc: crate::dependencies::Location { line: 3, column: 11 },
+        }
+      ]
+    );
+    assert_eq!(
+      graph.files[0].urls,
+      vec![UrlEdge {
+        url: "images/bg.png".into(),
+        file: Some("/images/bg.png".into()),
+        loc: crate::dependencies::Location { line: 4, column: 28 },
+      }]
+    );
+    assert_eq!(graph.files[1].imports[0].layer.as_deref(), Some(""));
+    assert_eq!(graph.files[1].css_modules, vec!["/d.css".to_owned()]);
+    assert_eq!(
+      graph.files[1]
+        .urls
+        .iter()
+        .map(|url| (url.url.as_str(), url.file.as_deref()))
+        .collect::<Vec<_>>(),
+      vec![
+        ("../icons/mask.svg#icon", Some("/icons/mask.svg")),
+        ("https://example.com/bg.png", None)
+      ]
+    );
+
+    assert_eq!(graph.dependents("/c.css"), vec!["/b.css", "/a.css"]);
+    assert_eq!(graph.dependents("/icons/mask.svg"), vec!["/b.css", "/a.css"]);
+    assert_eq!(graph.dependents("/a.css"), Vec::<&str>::new());
+  }
+
+  #[test]
+  fn test_circular_imports() {
+    error_test(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "b.css";
+          .a { color: red }
+        "#,
+          "/b.css": r#"
+          @import "c.css";
+          .b { color: green }
+        "#,
+          "/c.css": r#"
+          @import "d.css";
+          @import "b.css";
+          .c { color: blue }
+        "#,
+          "/d.css": r#"
+          .d { color: yellow }
+        "#
+        },
+      },
+      "/a.css",
+      Some(Box::new(|err| {
+        let BundleErrorKind::CircularImport { chain } = &err else {
+          unreachable!()
+        };
+        assert_eq!(
+          chain,
+          &vec![
+            ErrorLocation {
+              filename: "/b.css".into(),
+              line: 1,
+              column: 11
+            },
+            ErrorLocation {
+              filename: "/c.css".into(),
+              line: 2,
+              column: 11
+            },
+          ]
+        );
+        assert_eq!(
+          err.to_string(),
+          "Circular @import detected: /b.css:1:11 -> /c.css:2:11 -> /b.css"
+        );
+      })),
+    );
+
+    error_test(
+      TestProvider {
+        map: fs! {
+          "/a.css": r#"
+          @import "a.css";
+          .a { color: red }
+        "#
+        },
+      },
+      "/a.css",
+      Some(Box::new(|err| {
+        assert_eq!(err.to_string(), "Circular @import detected: /a.css:1:11 -> /a.css");
+      })),
+    );
+
+    let fs = TestProvider {
+      map: fs! {
+        "/a.css": r#"
+        @import "b.css" print;
+        .a { color: red }
+      "#,
+        "/b.css": r#"
+        @import "a.css" screen;
+      "#
+      },
+    };
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let err = bundler.bundle(Path::new("/a.css")).unwrap_err();
+    assert!(matches!(err.kind, BundleErrorKind::CircularImport { .. }));
+    assert_eq!(
+      err.loc,
+      Some(ErrorLocation {
+        filename: "/b.css".into(),
+        line: 1,
+        column: 9
+      })
+    );
+  }
+
+  #[cfg(feature = "async")]
+  struct AsyncTestProvider {
+    map: HashMap<PathBuf, String>,
+    in_flight: std::sync::atomic::AtomicUsize,
+    max_in_flight: std::sync::atomic::AtomicUsize,
+  }
+
+  #[cfg(feature = "async")]
+  impl AsyncTestProvider {
+    fn new(map: HashMap<PathBuf, String>) -> Self {
+      AsyncTestProvider {
+        map,
+        in_flight: Default::default(),
+        max_in_flight: Default::default(),
+      }
+    }
+  }
+
+  /// A future that yields to the executor once before completing, to simulate I/O.
+  #[cfg(feature = "async")]
+  struct YieldNow(bool);
+
+  #[cfg(feature = "async")]
+  impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<()> {
+      if self.0 {
+        return Poll::Ready(());
+      }
+      self.0 = true;
+      cx.waker().wake_by_ref();
+      Poll::Pending
+    }
+  }
+
+  #[cfg(feature = "async")]
+  impl AsyncSourceProvider for AsyncTestProvider {
+    type Error = std::io::Error;
+
+    fn read<'a>(&'a self, file: &Path) -> BoxFuture<'a, Result<&'a str, Self::Error>> {
+      use std::sync::atomic::Ordering;
+      let file = file.to_owned();
+      Box::pin(async move {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.max_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+        YieldNow(false).await;
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        self.map.get(&file).map(|s| s.as_str()).ok_or_else(|| {
+          std::io::Error::new(std::io::ErrorKind::NotFound, format!("{} not found", file.display()))
+        })
+      })
+    }
+
+    fn resolve<'a>(
+      &'a self,
+      specifier: &str,
+      originating_file: &Path,
+    ) -> BoxFuture<'a, Result<PathBuf, Self::Error>> {
+      let path = originating_file.with_file_name(specifier);
+      Box::pin(async move {
+        YieldNow(false).await;
+        Ok(path)
+      })
+    }
+  }
+
+  #[cfg(feature = "async")]
+  fn block_on<F: Future>(future: F) -> F::Output {
+    struct NoopWaker;
+
+    impl std::task::Wake for NoopWaker {
+      fn wake(self: std::sync::Arc<Self>) {}
+    }
+
+    let waker = std::sync::Arc::new(NoopWaker).into();
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+      if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+        return output;
+      }
+    }
+  }
+
+  #[cfg(feature = "async")]
+  #[test]
+  fn test_bundle_async() {
+    let fs = AsyncTestProvider::new(fs! {
+      "/a.css": r#"
+        @import "b.css" print;
+        @import "c.css";
+        .a { color: red }
+      "#,
+      "/b.css": r#"
+        .b { color: green }
+      "#,
+      "/c.css": r#"
+        @import "b.css" screen;
+        .c { color: blue }
+      "#
+    });
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let stylesheet = block_on(bundler.bundle_async(Path::new("/a.css"))).unwrap();
+    assert_eq!(
+      stylesheet.to_css(PrinterOptions::default()).unwrap().code,
+      indoc! { r#"
+      @media print, screen {
+        .b {
+          color: green;
+        }
+      }
+
+      .c {
+        color: #00f;
+      }
+
+      .a {
+        color: red;
+      }
+    "#}
+    );
+    // Both imports of a.css should have been read at the same time.
+    assert_eq!(fs.max_in_flight.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    let fs = AsyncTestProvider::new(fs! {
+      "/a.css": r#"
+        @import "b.css";
+        .a { composes: c from "c.css"; color: red }
+      "#,
+      "/b.css": r#"
+        .b { color: green }
+      "#,
+      "/c.css": r#"
+        .c { color: blue }
+      "#
+    });
+    let mut bundler = Bundler::new(
+      &fs,
+      None,
+      ParserOptions {
+        css_modules: Some(css_modules::Config::default()),
+        ..ParserOptions::default()
+      },
+    );
+    let stylesheet = block_on(bundler.bundle_async(Path::new("/a.css"))).unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      res.code,
+      indoc! { r#"
+      .L4EWVa_c {
+        color: #00f;
+      }
+
+      ._9z6RGq_b {
+        color: green;
+      }
+
+      ._6lixEq_a {
+        color: red;
+      }
+    "#}
+    );
+    assert_eq!(
+      res.exports.unwrap()["a"].composes,
+      vec![CssModuleReference::Local {
+        name: "L4EWVa_c".into()
+      }]
+    );
+
+    let fs = AsyncTestProvider::new(fs! {
+      "/a.css": r#"
+        @import "missing.css";
+      "#
+    });
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let err = block_on(bundler.bundle_async(Path::new("/a.css"))).unwrap_err();
+    assert!(matches!(err.kind, BundleErrorKind::ResolverError(_)));
+    assert_eq!(err.loc.unwrap().filename, "/a.css");
+
+    let fs = AsyncTestProvider::new(fs! {
+      "/a.css": r#"
+        @import "b.css";
+      "#,
+      "/b.css": r#"
+        @import "a.css";
+      "#
+    });
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let err = block_on(bundler.bundle_async(Path::new("/a.css"))).unwrap_err();
+    assert_eq!(
+      err.kind.to_string(),
+      "Circular @import detected: /a.css:1:9 -> /b.css:1:9 -> /a.css"
+    );
+
+    let fs: MemoryProvider = [
+      ("a.css", "@import 'b/c.css'; .a { color: red }"),
+      ("b/c.css", ".c { color: blue }"),
+    ]
+    .into_iter()
+    .collect();
+    let mut bundler = Bundler::new(&fs, None, ParserOptions::default());
+    let stylesheet = block_on(bundler.bundle_async(Path::new("a.css"))).unwrap();
+    assert_eq!(
+      stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap()
+        .code,
+      ".c{color:#00f}.a{color:red}"
+    );
+  }
 }
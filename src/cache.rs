@@ -0,0 +1,130 @@
+//! Binary serialization of style sheets, for caching parse results.
+//!
+//! Bundlers that process the same files on every build can cache the parsed style sheet of each file
+//! and skip parsing files that have not changed. [content_hash](content_hash) computes a hash of a source
+//! file and the options it is parsed with, which is stored in the header of the binary produced by
+//! [StyleSheet::to_binary](crate::stylesheet::StyleSheet::to_binary). On the next build, [binary_hash](binary_hash)
+//! reads the hash back without decoding the style sheet, and if it matches,
+//! [StyleSheet::from_binary](crate::stylesheet::StyleSheet::from_binary) decodes it.
+//!
+//! The style sheet is encoded using [MessagePack](https://msgpack.org) with the same structure as the
+//! serde JSON representation. The header also includes the [AST_VERSION](crate::stylesheet::AST_VERSION)
+//! and the version of Lightning CSS, and binaries produced by other versions are rejected.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::cache::{binary_hash, content_hash};
+//! use lightningcss::stylesheet::{ParserOptions, PrinterOptions, StyleSheet};
+//!
+//! let source = ".foo { color: red }";
+//! let hash = content_hash(source, &ParserOptions::default());
+//! let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+//! let binary = stylesheet.to_binary(hash).unwrap();
+//!
+//! // On the next build...
+//! assert_eq!(binary_hash(&binary), Some(hash));
+//! let stylesheet = StyleSheet::from_binary(&binary, ParserOptions::default()).unwrap();
+//! let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+//! assert_eq!(res.code, ".foo {\n  color: red;\n}\n");
+//! ```
+
+use crate::stylesheet::{ParserOptions, AST_VERSION};
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+const MAGIC: &[u8; 4] = b"LCSS";
+const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Returns a hash of a source file and the options that affect how it is parsed.
+///
+/// Options that do not affect the parsed style sheet, such as `warnings`, are not included.
+pub fn content_hash(source: &str, options: &ParserOptions) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  source.hash(&mut hasher);
+  options.filename.hash(&mut hasher);
+  options.source_index.hash(&mut hasher);
+  options.error_recovery.hash(&mut hasher);
+  options.flags.bits().hash(&mut hasher);
+  options.disabled_drafts.bits().hash(&mut hasher);
+  options.css_modules.is_some().hash(&mut hasher);
+  if let Some(config) = &options.css_modules {
+    config.dashed_idents.hash(&mut hasher);
+    config.pure.hash(&mut hasher);
+  }
+  hasher.finish()
+}
+
+/// Reads the content hash from the header of a binary produced by
+/// [StyleSheet::to_binary](crate::stylesheet::StyleSheet::to_binary), without decoding the style sheet.
+///
+/// Returns `None` if the header is invalid, or the binary was produced by a different version of Lightning CSS.
+pub fn binary_hash(data: &[u8]) -> Option<u64> {
+  read_header(data).ok().map(|(hash, _)| hash)
+}
+
+/// An error that occurred while encoding or decoding a binary style sheet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryError {
+  /// The data is not a binary style sheet.
+  InvalidHeader,
+  /// The binary was produced by a different version of Lightning CSS.
+  VersionMismatch,
+  /// The style sheet could not be encoded.
+  Encode(String),
+  /// The style sheet could not be decoded.
+  Decode(String),
+}
+
+impl fmt::Display for BinaryError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    use BinaryError::*;
+    match self {
+      InvalidHeader => write!(f, "Invalid binary style sheet header"),
+      VersionMismatch => write!(
+        f,
+        "Binary style sheet was produced by a different version of Lightning CSS"
+      ),
+      Encode(err) => write!(f, "Failed to encode style sheet: {}", err),
+      Decode(err) => write!(f, "Failed to decode style sheet: {}", err),
+    }
+  }
+}
+
+impl std::error::Error for BinaryError {}
+
+pub(crate) fn encode<T: serde::Serialize>(value: &T, hash: u64) -> Result<Vec<u8>, BinaryError> {
+  let mut data = Vec::new();
+  data.extend_from_slice(MAGIC);
+  data.extend_from_slice(&AST_VERSION.to_le_bytes());
+  data.push(VERSION.len() as u8);
+  data.extend_from_slice(VERSION.as_bytes());
+  data.extend_from_slice(&hash.to_le_bytes());
+  let body = rmp_serde::to_vec_named(value).map_err(|err| BinaryError::Encode(err.to_string()))?;
+  data.extend(body);
+  Ok(data)
+}
+
+pub(crate) fn decode<'de, T: serde::Deserialize<'de>>(data: &'de [u8]) -> Result<T, BinaryError> {
+  let (_, body) = read_header(data)?;
+  rmp_serde::from_slice(body).map_err(|err| BinaryError::Decode(err.to_string()))
+}
+
+fn read_header(data: &[u8]) -> Result<(u64, &[u8]), BinaryError> {
+  let data = data.strip_prefix(MAGIC).ok_or(BinaryError::InvalidHeader)?;
+  if data.len() < 5 {
+    return Err(BinaryError::InvalidHeader);
+  }
+  let (ast_version, data) = data.split_at(4);
+  let (version_len, data) = (data[0] as usize, &data[1..]);
+  if data.len() < version_len + 8 {
+    return Err(BinaryError::InvalidHeader);
+  }
+  let (version, data) = data.split_at(version_len);
+  if ast_version != AST_VERSION.to_le_bytes() || version != VERSION.as_bytes() {
+    return Err(BinaryError::VersionMismatch);
+  }
+  let (hash, body) = data.split_at(8);
+  Ok((u64::from_le_bytes(hash.try_into().unwrap()), body))
+}
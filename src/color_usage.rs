@@ -0,0 +1,159 @@
+//! Color usage extraction.
+//!
+//! [StyleSheet::color_usage](crate::stylesheet::StyleSheet::color_usage) finds every color
+//! literal in the declarations of a style sheet, normalizes it to an sRGB hex color, and groups
+//! the usages of each normalized color with the properties that use them and their locations.
+//! This is useful for auditing a color palette, or for migrating literal colors to design tokens.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::Property;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::traits::ToCss;
+use crate::values::color::{CssColor, RGBA};
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::convert::{Infallible, TryFrom};
+
+/// A color used by a style sheet, as returned by
+/// [StyleSheet::color_usage](crate::stylesheet::StyleSheet::color_usage).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ColorUsage {
+  /// The color, normalized to an sRGB hex color, e.g. `#ff0000` or `#ff000080`.
+  /// Colors outside the sRGB gamut are gamut mapped.
+  pub color: String,
+  /// The places the color is used, in source order.
+  pub usages: Vec<ColorReference>,
+}
+
+/// A usage of a color literal.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct ColorReference {
+  /// The color, serialized as it would be printed, e.g. `red` or `oklch(60% .1 200)`.
+  pub value: String,
+  /// The name of the property that uses the color, e.g. `background` or `--accent`.
+  pub property: String,
+  /// The location of the rule that contains the declaration.
+  pub loc: Location,
+}
+
+#[derive(Default)]
+pub(crate) struct ColorUsageCollector {
+  pub(crate) colors: Vec<ColorUsage>,
+}
+
+impl ColorUsageCollector {
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>) {
+    for rule in &rules.0 {
+      self.add_rule(rule);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>) {
+    match rule {
+      CssRule::Style(style) => {
+        self.add_declarations(&style.declarations, style.loc);
+        self.add_rules(&style.rules);
+      }
+      CssRule::Nesting(nesting) => {
+        self.add_declarations(&nesting.style.declarations, nesting.loc);
+        self.add_rules(&nesting.style.rules);
+      }
+      CssRule::Media(media) => self.add_rules(&media.rules),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules),
+      CssRule::Container(container) => self.add_rules(&container.rules),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          self.add_declarations(&keyframe.declarations, keyframes.loc);
+        }
+      }
+      CssRule::Page(page) => {
+        self.add_declarations(&page.declarations, page.loc);
+        for margin in &page.rules {
+          self.add_declarations(&margin.declarations, margin.loc);
+        }
+      }
+      CssRule::Viewport(viewport) => self.add_declarations(&viewport.declarations, viewport.loc),
+      _ => {}
+    }
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock, loc: Location) {
+    // Visit a copy of the declarations, since visitors require mutable access.
+    let mut declarations = declarations.clone();
+    let _ = declarations.visit(&mut ColorVisitor {
+      collector: self,
+      property: String::new(),
+      loc,
+    });
+  }
+
+  fn add(&mut self, color: String, reference: ColorReference) {
+    if let Some(usage) = self.colors.iter_mut().find(|usage| usage.color == color) {
+      usage.usages.push(reference);
+    } else {
+      self.colors.push(ColorUsage {
+        color,
+        usages: vec![reference],
+      });
+    }
+  }
+}
+
+struct ColorVisitor<'a> {
+  collector: &'a mut ColorUsageCollector,
+  property: String,
+  loc: Location,
+}
+
+impl<'a, 'i> Visitor<'i> for ColorVisitor<'a> {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES | VisitTypes::COLORS
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    self.property = property.property_id().name().to_owned();
+    property.visit_children(self)
+  }
+
+  fn visit_color(&mut self, color: &mut CssColor) -> Result<(), Self::Error> {
+    // currentColor is not a color literal.
+    let Ok(rgba) = RGBA::try_from(&*color) else {
+      return Ok(());
+    };
+
+    // The background shorthand always has a color, which defaults to transparent and is omitted
+    // when printed, so an implicit transparent cannot be distinguished from an explicit one.
+    if self.property == "background" && rgba == RGBA::transparent() {
+      return Ok(());
+    }
+
+    let Ok(value) = color.to_css_string(Default::default()) else {
+      return Ok(());
+    };
+
+    self.collector.add(
+      hex(&rgba),
+      ColorReference {
+        value,
+        property: self.property.clone(),
+        loc: self.loc,
+      },
+    );
+    Ok(())
+  }
+}
+
+fn hex(rgba: &RGBA) -> String {
+  if rgba.alpha == 255 {
+    format!("#{:02x}{:02x}{:02x}", rgba.red, rgba.green, rgba.blue)
+  } else {
+    format!("#{:02x}{:02x}{:02x}{:02x}", rgba.red, rgba.green, rgba.blue, rgba.alpha)
+  }
+}
@@ -31,6 +31,7 @@ pub enum Feature {
   ClampFunction,
   ColorFunction,
   ConicGradient,
+  ContainerQueries,
   ContainerQueryLengthUnits,
   Cue,
   CueFunction,
@@ -60,6 +61,7 @@ pub enum Feature {
   EthiopicHalehameTigListStyleType,
   EthiopicListStyleType,
   EthiopicNumericListStyleType,
+  EnvFunction,
   ExUnit,
   ExtendedSystemFonts,
   FillSize,
@@ -81,6 +83,7 @@ pub enum Feature {
   Gencontent,
   GeorgianListStyleType,
   GradientInterpolationHints,
+  Grid,
   GujaratiListStyleType,
   GurmukhiListStyleType,
   HasSelector,
@@ -93,6 +96,7 @@ pub enum Feature {
   ImageSet,
   InOutOfRange,
   IndeterminatePseudo,
+  InitialLetter,
   IsSelector,
   JapaneseFormalListStyleType,
   JapaneseInformalListStyleType,
@@ -3287,7 +3291,131 @@ impl Feature {
           }
         }
       }
-      Feature::ContainerQueryLengthUnits => {
+      Feature::EnvFunction => {
+        if let Some(version) = browsers.chrome {
+          if version < 4521984 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4259840 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3670016 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 721152 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 721408 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 655360 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.android {
+          if version < 4521984 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+      }
+      Feature::Grid => {
+        if let Some(version) = browsers.chrome {
+          if version < 3735552 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3407872 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 2883584 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 655616 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 656128 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 393216 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.android {
+          if version < 3735552 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+      }
+      Feature::InitialLetter => {
+        if let Some(version) = browsers.chrome {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6291456 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1376256 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.android {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if browsers.firefox.is_some()
+          || browsers.ie.is_some()
+          || browsers.ios_saf.is_some()
+          || browsers.safari.is_some()
+        {
+          return false;
+        }
+      }
+      Feature::ContainerQueries | Feature::ContainerQueryLengthUnits => {
         if let Some(version) = browsers.chrome {
           if version < 6881280 {
             return false;
@@ -4940,4 +5068,216 @@ impl Feature {
 
     false
   }
+
+  /// Returns a unique id for the feature, e.g. `has-selector`.
+  pub fn id(&self) -> &'static str {
+    match self {
+      Feature::AbsFunction => "abs-function",
+      Feature::AfarListStyleType => "afar-list-style-type",
+      Feature::AmharicAbegedeListStyleType => "amharic-abegede-list-style-type",
+      Feature::AmharicListStyleType => "amharic-list-style-type",
+      Feature::AnyLink => "any-link",
+      Feature::AnyPseudo => "any-pseudo",
+      Feature::ArabicIndicListStyleType => "arabic-indic-list-style-type",
+      Feature::ArmenianListStyleType => "armenian-list-style-type",
+      Feature::AsterisksListStyleType => "asterisks-list-style-type",
+      Feature::Autofill => "autofill",
+      Feature::BengaliListStyleType => "bengali-list-style-type",
+      Feature::BinaryListStyleType => "binary-list-style-type",
+      Feature::BorderImageRepeatRound => "border-image-repeat-round",
+      Feature::BorderImageRepeatSpace => "border-image-repeat-space",
+      Feature::CalcFunction => "calc-function",
+      Feature::CambodianListStyleType => "cambodian-list-style-type",
+      Feature::CapUnit => "cap-unit",
+      Feature::CaseInsensitive => "case-insensitive",
+      Feature::ChUnit => "ch-unit",
+      Feature::CircleListStyleType => "circle-list-style-type",
+      Feature::CjkDecimalListStyleType => "cjk-decimal-list-style-type",
+      Feature::CjkEarthlyBranchListStyleType => "cjk-earthly-branch-list-style-type",
+      Feature::CjkHeavenlyStemListStyleType => "cjk-heavenly-stem-list-style-type",
+      Feature::ClampFunction => "clamp-function",
+      Feature::ColorFunction => "color-function",
+      Feature::ConicGradient => "conic-gradient",
+      Feature::ContainerQueries => "container-queries",
+      Feature::ContainerQueryLengthUnits => "container-query-length-units",
+      Feature::Cue => "cue",
+      Feature::CueFunction => "cue-function",
+      Feature::CustomMediaQueries => "custom-media-queries",
+      Feature::DecimalLeadingZeroListStyleType => "decimal-leading-zero-list-style-type",
+      Feature::DecimalListStyleType => "decimal-list-style-type",
+      Feature::DefaultPseudo => "default-pseudo",
+      Feature::DevanagariListStyleType => "devanagari-list-style-type",
+      Feature::Dialog => "dialog",
+      Feature::DirSelector => "dir-selector",
+      Feature::DiscListStyleType => "disc-list-style-type",
+      Feature::DisclosureClosedListStyleType => "disclosure-closed-list-style-type",
+      Feature::DisclosureOpenListStyleType => "disclosure-open-list-style-type",
+      Feature::DoublePositionGradients => "double-position-gradients",
+      Feature::EthiopicAbegedeAmEtListStyleType => "ethiopic-abegede-am-et-list-style-type",
+      Feature::EthiopicAbegedeGezListStyleType => "ethiopic-abegede-gez-list-style-type",
+      Feature::EthiopicAbegedeListStyleType => "ethiopic-abegede-list-style-type",
+      Feature::EthiopicAbegedeTiErListStyleType => "ethiopic-abegede-ti-er-list-style-type",
+      Feature::EthiopicAbegedeTiEtListStyleType => "ethiopic-abegede-ti-et-list-style-type",
+      Feature::EthiopicHalehameAaErListStyleType => "ethiopic-halehame-aa-er-list-style-type",
+      Feature::EthiopicHalehameAaEtListStyleType => "ethiopic-halehame-aa-et-list-style-type",
+      Feature::EthiopicHalehameAmEtListStyleType => "ethiopic-halehame-am-et-list-style-type",
+      Feature::EthiopicHalehameGezListStyleType => "ethiopic-halehame-gez-list-style-type",
+      Feature::EthiopicHalehameOmEtListStyleType => "ethiopic-halehame-om-et-list-style-type",
+      Feature::EthiopicHalehameSidEtListStyleType => "ethiopic-halehame-sid-et-list-style-type",
+      Feature::EthiopicHalehameSoEtListStyleType => "ethiopic-halehame-so-et-list-style-type",
+      Feature::EthiopicHalehameTigListStyleType => "ethiopic-halehame-tig-list-style-type",
+      Feature::EthiopicListStyleType => "ethiopic-list-style-type",
+      Feature::EthiopicNumericListStyleType => "ethiopic-numeric-list-style-type",
+      Feature::EnvFunction => "env-function",
+      Feature::ExUnit => "ex-unit",
+      Feature::ExtendedSystemFonts => "extended-system-fonts",
+      Feature::FillSize => "fill-size",
+      Feature::FirstLetter => "first-letter",
+      Feature::FirstLine => "first-line",
+      Feature::FitContentFunctionSize => "fit-content-function-size",
+      Feature::FitContentSize => "fit-content-size",
+      Feature::FocusVisible => "focus-visible",
+      Feature::FocusWithin => "focus-within",
+      Feature::FontFamilySystemUi => "font-family-system-ui",
+      Feature::FontSizeRem => "font-size-rem",
+      Feature::FontSizeXXXLarge => "font-size-xxx-large",
+      Feature::FontStretchPercentage => "font-stretch-percentage",
+      Feature::FontStyleObliqueAngle => "font-style-oblique-angle",
+      Feature::FontWeightNumber => "font-weight-number",
+      Feature::FootnotesListStyleType => "footnotes-list-style-type",
+      Feature::FormValidation => "form-validation",
+      Feature::Fullscreen => "fullscreen",
+      Feature::Gencontent => "gencontent",
+      Feature::GeorgianListStyleType => "georgian-list-style-type",
+      Feature::GradientInterpolationHints => "gradient-interpolation-hints",
+      Feature::Grid => "grid",
+      Feature::GujaratiListStyleType => "gujarati-list-style-type",
+      Feature::GurmukhiListStyleType => "gurmukhi-list-style-type",
+      Feature::HasSelector => "has-selector",
+      Feature::HebrewListStyleType => "hebrew-list-style-type",
+      Feature::HexAlphaColors => "hex-alpha-colors",
+      Feature::HiraganaIrohaListStyleType => "hiragana-iroha-list-style-type",
+      Feature::HiraganaListStyleType => "hiragana-list-style-type",
+      Feature::HypotFunction => "hypot-function",
+      Feature::IcUnit => "ic-unit",
+      Feature::ImageSet => "image-set",
+      Feature::InOutOfRange => "in-out-of-range",
+      Feature::IndeterminatePseudo => "indeterminate-pseudo",
+      Feature::InitialLetter => "initial-letter",
+      Feature::IsSelector => "is-selector",
+      Feature::JapaneseFormalListStyleType => "japanese-formal-list-style-type",
+      Feature::JapaneseInformalListStyleType => "japanese-informal-list-style-type",
+      Feature::KannadaListStyleType => "kannada-list-style-type",
+      Feature::KatakanaIrohaListStyleType => "katakana-iroha-list-style-type",
+      Feature::KatakanaListStyleType => "katakana-list-style-type",
+      Feature::KhmerListStyleType => "khmer-list-style-type",
+      Feature::KoreanHangulFormalListStyleType => "korean-hangul-formal-list-style-type",
+      Feature::KoreanHanjaFormalListStyleType => "korean-hanja-formal-list-style-type",
+      Feature::KoreanHanjaInformalListStyleType => "korean-hanja-informal-list-style-type",
+      Feature::LabColors => "lab-colors",
+      Feature::LangSelectorList => "lang-selector-list",
+      Feature::LaoListStyleType => "lao-list-style-type",
+      Feature::LhUnit => "lh-unit",
+      Feature::LinearGradient => "linear-gradient",
+      Feature::LogicalBorderRadius => "logical-border-radius",
+      Feature::LogicalBorderShorthand => "logical-border-shorthand",
+      Feature::LogicalBorders => "logical-borders",
+      Feature::LogicalInset => "logical-inset",
+      Feature::LogicalMargin => "logical-margin",
+      Feature::LogicalMarginShorthand => "logical-margin-shorthand",
+      Feature::LogicalPadding => "logical-padding",
+      Feature::LogicalPaddingShorthand => "logical-padding-shorthand",
+      Feature::LogicalSize => "logical-size",
+      Feature::LogicalTextAlign => "logical-text-align",
+      Feature::LowerAlphaListStyleType => "lower-alpha-list-style-type",
+      Feature::LowerArmenianListStyleType => "lower-armenian-list-style-type",
+      Feature::LowerGreekListStyleType => "lower-greek-list-style-type",
+      Feature::LowerHexadecimalListStyleType => "lower-hexadecimal-list-style-type",
+      Feature::LowerLatinListStyleType => "lower-latin-list-style-type",
+      Feature::LowerNorwegianListStyleType => "lower-norwegian-list-style-type",
+      Feature::LowerRomanListStyleType => "lower-roman-list-style-type",
+      Feature::MalayalamListStyleType => "malayalam-list-style-type",
+      Feature::MarkerPseudo => "marker-pseudo",
+      Feature::MaxContentSize => "max-content-size",
+      Feature::MaxFunction => "max-function",
+      Feature::MediaIntervalSyntax => "media-interval-syntax",
+      Feature::MediaRangeSyntax => "media-range-syntax",
+      Feature::MinContentSize => "min-content-size",
+      Feature::MinFunction => "min-function",
+      Feature::ModFunction => "mod-function",
+      Feature::MongolianListStyleType => "mongolian-list-style-type",
+      Feature::MyanmarListStyleType => "myanmar-list-style-type",
+      Feature::Namespaces => "namespaces",
+      Feature::Nesting => "nesting",
+      Feature::NotSelectorList => "not-selector-list",
+      Feature::NthChildOf => "nth-child-of",
+      Feature::OctalListStyleType => "octal-list-style-type",
+      Feature::OklabColors => "oklab-colors",
+      Feature::OptionalPseudo => "optional-pseudo",
+      Feature::OriyaListStyleType => "oriya-list-style-type",
+      Feature::OromoListStyleType => "oromo-list-style-type",
+      Feature::OverflowShorthand => "overflow-shorthand",
+      Feature::P3Colors => "p3-colors",
+      Feature::PartPseudo => "part-pseudo",
+      Feature::PersianListStyleType => "persian-list-style-type",
+      Feature::PlaceContent => "place-content",
+      Feature::PlaceItems => "place-items",
+      Feature::PlaceSelf => "place-self",
+      Feature::Placeholder => "placeholder",
+      Feature::PlaceholderShown => "placeholder-shown",
+      Feature::QUnit => "q-unit",
+      Feature::RadialGradient => "radial-gradient",
+      Feature::ReadOnlyWrite => "read-only-write",
+      Feature::RemFunction => "rem-function",
+      Feature::RemUnit => "rem-unit",
+      Feature::RepeatingConicGradient => "repeating-conic-gradient",
+      Feature::RepeatingLinearGradient => "repeating-linear-gradient",
+      Feature::RepeatingRadialGradient => "repeating-radial-gradient",
+      Feature::RlhUnit => "rlh-unit",
+      Feature::RoundFunction => "round-function",
+      Feature::Selection => "selection",
+      Feature::Selectors2 => "selectors2",
+      Feature::Selectors3 => "selectors3",
+      Feature::Shadowdomv1 => "shadowdomv1",
+      Feature::SidamaListStyleType => "sidama-list-style-type",
+      Feature::SignFunction => "sign-function",
+      Feature::SimpChineseFormalListStyleType => "simp-chinese-formal-list-style-type",
+      Feature::SimpChineseInformalListStyleType => "simp-chinese-informal-list-style-type",
+      Feature::SomaliListStyleType => "somali-list-style-type",
+      Feature::SpaceSeparatedColorNotation => "space-separated-color-notation",
+      Feature::SquareListStyleType => "square-list-style-type",
+      Feature::StretchSize => "stretch-size",
+      Feature::StringListStyleType => "string-list-style-type",
+      Feature::SymbolsListStyleType => "symbols-list-style-type",
+      Feature::TamilListStyleType => "tamil-list-style-type",
+      Feature::TeluguListStyleType => "telugu-list-style-type",
+      Feature::TextDecorationThicknessPercent => "text-decoration-thickness-percent",
+      Feature::TextDecorationThicknessShorthand => "text-decoration-thickness-shorthand",
+      Feature::ThaiListStyleType => "thai-list-style-type",
+      Feature::TibetanListStyleType => "tibetan-list-style-type",
+      Feature::TigreListStyleType => "tigre-list-style-type",
+      Feature::TigrinyaErAbegedeListStyleType => "tigrinya-er-abegede-list-style-type",
+      Feature::TigrinyaErListStyleType => "tigrinya-er-list-style-type",
+      Feature::TigrinyaEtAbegedeListStyleType => "tigrinya-et-abegede-list-style-type",
+      Feature::TigrinyaEtListStyleType => "tigrinya-et-list-style-type",
+      Feature::TradChineseFormalListStyleType => "trad-chinese-formal-list-style-type",
+      Feature::TradChineseInformalListStyleType => "trad-chinese-informal-list-style-type",
+      Feature::UpperAlphaListStyleType => "upper-alpha-list-style-type",
+      Feature::UpperArmenianListStyleType => "upper-armenian-list-style-type",
+      Feature::UpperHexadecimalListStyleType => "upper-hexadecimal-list-style-type",
+      Feature::UpperLatinListStyleType => "upper-latin-list-style-type",
+      Feature::UpperNorwegianListStyleType => "upper-norwegian-list-style-type",
+      Feature::UpperRomanListStyleType => "upper-roman-list-style-type",
+      Feature::VbUnit => "vb-unit",
+      Feature::VhUnit => "vh-unit",
+      Feature::ViUnit => "vi-unit",
+      Feature::ViewportPercentageUnitsDynamic => "viewport-percentage-units-dynamic",
+      Feature::ViewportPercentageUnitsLarge => "viewport-percentage-units-large",
+      Feature::ViewportPercentageUnitsSmall => "viewport-percentage-units-small",
+      Feature::VmaxUnit => "vmax-unit",
+      Feature::VminUnit => "vmin-unit",
+      Feature::VwUnit => "vw-unit",
+      Feature::XResolutionUnit => "x-resolution-unit",
+    }
+  }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::compat::Feature;
 use crate::declaration::DeclarationBlock;
@@ -8,7 +8,7 @@ use crate::rules::supports::{SupportsCondition, SupportsRule};
 use crate::rules::{style::StyleRule, CssRule, CssRuleList};
 use crate::selector::{Direction, PseudoClass};
 use crate::targets::Targets;
-use crate::vendor_prefix::VendorPrefix;
+use crate::vendor_prefix::{PrefixPolicy, PrefixRule, VendorPrefix};
 use parcel_selectors::parser::Component;
 
 #[derive(Debug)]
@@ -35,10 +35,17 @@ pub(crate) struct PropertyHandlerContext<'i, 'o> {
   rtl: Vec<Property<'i>>,
   pub context: DeclarationContext,
   pub unused_symbols: &'o HashSet<String>,
+  pub prefix_rules: &'o [PrefixRule],
+  pub prefix_policies: &'o HashMap<String, PrefixPolicy>,
 }
 
 impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
-  pub fn new(targets: Targets, unused_symbols: &'o HashSet<String>) -> Self {
+  pub fn new(
+    targets: Targets,
+    unused_symbols: &'o HashSet<String>,
+    prefix_rules: &'o [PrefixRule],
+    prefix_policies: &'o HashMap<String, PrefixPolicy>,
+  ) -> Self {
     PropertyHandlerContext {
       targets,
       is_important: false,
@@ -47,6 +54,8 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
       rtl: Vec::new(),
       context: DeclarationContext::None,
       unused_symbols,
+      prefix_rules,
+      prefix_policies,
     }
   }
 
@@ -59,9 +68,15 @@ impl<'i, 'o> PropertyHandlerContext<'i, 'o> {
       rtl: Vec::new(),
       context,
       unused_symbols: self.unused_symbols,
+      prefix_rules: self.prefix_rules,
+      prefix_policies: self.prefix_policies,
     }
   }
 
+  pub fn prefix_policy(&self, name: &str) -> PrefixPolicy {
+    self.prefix_policies.get(name).copied().unwrap_or_default()
+  }
+
   pub fn should_compile_logical(&self, feature: Feature) -> bool {
     // Don't convert logical properties in style attributes because
     // our fallbacks rely on extra rules to define --ltr and --rtl.
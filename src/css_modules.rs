@@ -9,8 +9,14 @@
 //! will be updated accordingly. A map of the original names to compiled (hashed) names will be returned.
 
 use crate::error::PrinterErrorKind;
+use crate::properties::animation::AnimationName;
 use crate::properties::css_modules::{Composes, Specifier};
-use crate::selector::SelectorList;
+use crate::properties::custom::{Token, TokenOrValue, UnparsedProperty};
+use crate::properties::{Property, PropertyId};
+use crate::rules::keyframes::KeyframesName;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList};
+use crate::selector::{Component, PseudoClass, Selector, SelectorList};
 use data_encoding::{Encoding, Specification};
 use lazy_static::lazy_static;
 use pathdiff::diff_paths;
@@ -19,19 +25,238 @@ use serde::Serialize;
 use smallvec::{smallvec, SmallVec};
 use std::borrow::Cow;
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt::Write;
 use std::hash::{Hash, Hasher};
 use std::path::Path;
+use std::sync::Mutex;
 
 /// Configuration for CSS modules.
-#[derive(Default, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub struct Config<'i> {
   /// The name pattern to use when renaming class names and other identifiers.
   /// Default is `[hash]_[local]`.
   pub pattern: Pattern<'i>,
   /// Whether to rename dashed identifiers, e.g. custom properties.
   pub dashed_idents: bool,
+  /// A salt to include when hashing file names, so that generated names can be varied between builds.
+  pub hash_salt: Option<&'i str>,
+  /// How exported names are converted in the returned exports map.
+  pub exports_convention: ExportsConvention,
+  /// Whether to scope `@keyframes` names and references to them in `animation` properties.
+  pub animation: bool,
+  /// Whether to scope container names in `@container` rules and `container` properties.
+  pub container: bool,
+  /// Whether to scope `@counter-style` names and references to them in `list-style` properties.
+  pub counter_style: bool,
+  /// Whether to scope grid line and area names.
+  pub grid: bool,
+  /// Whether to scope custom identifiers in the `view-transition-name` property.
+  pub view_transition_name: bool,
+  /// A registry of minimal names to use instead of the pattern. When set, each local name
+  /// is assigned the next short name (`a`, `b`, ..., `aa`), shared across all files that
+  /// use the same registry.
+  pub minimal_names: Option<&'i MinimalNames>,
+  /// Whether to require that all selectors contain at least one local class or id,
+  /// matching the pure mode of css-loader.
+  pub pure: bool,
+  /// Whether to append ICSS `:import` and `:export` blocks for the exports of the module to
+  /// the output, for use with other tools that implement CSS modules such as css-loader.
+  pub icss: bool,
+}
+
+impl<'i> Default for Config<'i> {
+  fn default() -> Self {
+    Config {
+      pattern: Default::default(),
+      dashed_idents: false,
+      hash_salt: None,
+      exports_convention: Default::default(),
+      animation: true,
+      container: true,
+      counter_style: true,
+      grid: true,
+      view_transition_name: true,
+      minimal_names: None,
+      pure: false,
+      icss: false,
+    }
+  }
+}
+
+impl<'i> Config<'i> {
+  /// Writes the scoped name for a local identifier, using minimal names if enabled,
+  /// and otherwise the pattern.
+  pub(crate) fn write_name<W, E>(&self, hash: &str, path: &Path, local: &str, mut write: W) -> Result<(), E>
+  where
+    W: FnMut(&str) -> Result<(), E>,
+  {
+    if let Some(minimal_names) = self.minimal_names {
+      write(&minimal_names.get(hash, local))
+    } else {
+      self.pattern.write(hash, path, local, write)
+    }
+  }
+
+  #[inline]
+  fn write_name_to_string(
+    &self,
+    mut res: String,
+    hash: &str,
+    path: &Path,
+    local: &str,
+  ) -> Result<String, std::fmt::Error> {
+    self.write_name(hash, path, local, |s| res.write_str(s))?;
+    Ok(res)
+  }
+}
+
+/// A registry that assigns minimal, incrementing names (`a`, `b`, ..., `Z`, `aa`, ...) to
+/// CSS modules local names, for maximum compression of production builds.
+///
+/// Names are assigned in the order they are first requested, so builds that process files
+/// in the same order produce the same names. To keep names stable as files change, a registry
+/// can be seeded with the names from a previous build, retrieved via [names](MinimalNames::names).
+/// The registry is keyed by the name that the default `[hash]_[local]` pattern would produce.
+#[derive(Debug, Default)]
+pub struct MinimalNames {
+  inner: Mutex<MinimalNamesInner>,
+}
+
+#[derive(Debug, Default)]
+struct MinimalNamesInner {
+  names: HashMap<String, String>,
+  used: HashSet<String>,
+  next: usize,
+}
+
+impl MinimalNames {
+  /// Creates an empty registry.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Creates a registry seeded with previously assigned names.
+  pub fn with_names(names: HashMap<String, String>) -> Self {
+    let used = names.values().cloned().collect();
+    MinimalNames {
+      inner: Mutex::new(MinimalNamesInner { names, used, next: 0 }),
+    }
+  }
+
+  /// Returns a copy of the names assigned so far.
+  pub fn names(&self) -> HashMap<String, String> {
+    self.inner.lock().unwrap().names.clone()
+  }
+
+  fn get(&self, hash: &str, local: &str) -> String {
+    let mut inner = self.inner.lock().unwrap();
+    let key = format!("{}_{}", hash, local);
+    if let Some(name) = inner.names.get(&key) {
+      return name.clone();
+    }
+
+    let name = loop {
+      let name = minimal_name(inner.next);
+      inner.next += 1;
+      if !inner.used.contains(&name) {
+        break name;
+      }
+    };
+    inner.used.insert(name.clone());
+    inner.names.insert(key, name.clone());
+    name
+  }
+}
+
+/// Returns the name at the given index in the sequence `a`, `b`, ..., `Z`, `aa`, `ba`, ...
+fn minimal_name(mut index: usize) -> String {
+  // Identifiers cannot start with a digit or hyphen.
+  const FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+  const REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789_-";
+
+  let mut name = String::new();
+  name.push(FIRST[index % FIRST.len()] as char);
+  index /= FIRST.len();
+  while index > 0 {
+    index -= 1;
+    name.push(REST[index % REST.len()] as char);
+    index /= REST.len();
+  }
+  name
+}
+
+/// Controls how the names in the CSS modules exports map are converted, e.g. to
+/// allow `styles.fooBar` to be used in JavaScript for a `.foo-bar` class.
+///
+/// These match the `exportLocalsConvention` option of css-loader.
+#[derive(Default, Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+pub enum ExportsConvention {
+  /// Names are exported as is.
+  #[default]
+  AsIs,
+  /// Names are exported as is, along with a camel cased version.
+  CamelCase,
+  /// Only the camel cased version of each name is exported.
+  CamelCaseOnly,
+  /// Only dashes in names are converted to camel case.
+  Dashes,
+}
+
+impl ExportsConvention {
+  /// Converts the names in an exports map according to the convention.
+  ///
+  /// For [CamelCase](ExportsConvention::CamelCase), names that already exist in the map are not overridden.
+  pub fn apply(&self, exports: CssModuleExports) -> CssModuleExports {
+    match self {
+      ExportsConvention::AsIs => exports,
+      ExportsConvention::CamelCase => {
+        let mut res = exports.clone();
+        for (name, export) in exports {
+          let camel = camel_case(&name, false);
+          if camel != name {
+            res.entry(camel).or_insert(export);
+          }
+        }
+        res
+      }
+      ExportsConvention::CamelCaseOnly => exports
+        .into_iter()
+        .map(|(name, export)| (camel_case(&name, false), export))
+        .collect(),
+      ExportsConvention::Dashes => exports
+        .into_iter()
+        .map(|(name, export)| (camel_case(&name, true), export))
+        .collect(),
+    }
+  }
+}
+
+/// Converts `foo-bar` or `foo_bar` to `fooBar`. If `dashes_only` is true, underscores are preserved.
+/// Dashed identifiers are left as is, since they are referenced by their full name.
+fn camel_case(name: &str, dashes_only: bool) -> String {
+  if name.starts_with("--") {
+    return name.into();
+  }
+
+  let mut res = String::with_capacity(name.len());
+  let mut upper = false;
+  for c in name.chars() {
+    if c == '-' || (!dashes_only && c == '_') {
+      upper = !res.is_empty();
+    } else if upper {
+      res.extend(c.to_uppercase());
+      upper = false;
+    } else {
+      res.push(c);
+    }
+  }
+  res
 }
 
 /// A CSS modules class name pattern.
@@ -86,7 +311,10 @@ impl<'i> Pattern<'i> {
             "[name]" => Segment::Name,
             "[local]" => Segment::Local,
             "[hash]" => Segment::Hash,
-            s => return Err(PatternParseError::UnknownPlaceholder(s.into(), start_idx)),
+            s => match s[1..end_idx].strip_prefix("hash:").and_then(|len| len.parse::<usize>().ok()) {
+              Some(len) if len > 0 => Segment::TruncatedHash(len),
+              _ => return Err(PatternParseError::UnknownPlaceholder(s.into(), start_idx)),
+            },
           };
           segments.push(segment);
           start_idx += end_idx + 1;
@@ -127,24 +355,26 @@ impl<'i> Pattern<'i> {
           write(local)?;
         }
         Segment::Hash => {
-          write(hash)?;
+          write(truncate_hash(hash, HASH_LEN))?;
+        }
+        Segment::TruncatedHash(len) => {
+          write(truncate_hash(hash, *len))?;
         }
       }
     }
     Ok(())
   }
+}
 
-  #[inline]
-  fn write_to_string(
-    &self,
-    mut res: String,
-    hash: &str,
-    path: &Path,
-    local: &str,
-  ) -> Result<String, std::fmt::Error> {
-    self.write(hash, path, local, |s| res.write_str(s))?;
-    Ok(res)
-  }
+/// The number of characters in a `[hash]` segment.
+const HASH_LEN: usize = 6;
+
+/// Truncates a hash to the given number of characters. Hashes may be longer than
+/// `[hash]` when the pattern also has a longer `[hash:<length>]` segment.
+fn truncate_hash(hash: &str, len: usize) -> &str {
+  // Keep the underscore added to hashes that start with a digit.
+  let len = if hash.starts_with('_') { len + 1 } else { len };
+  &hash[..len.min(hash.len())]
 }
 
 /// A segment in a CSS modules class name pattern.
@@ -160,6 +390,9 @@ pub enum Segment<'i> {
   Local,
   /// A hash of the file name.
   Hash,
+  /// A hash of the file name, truncated to the given number of characters.
+  /// Lengths above 6 characters use a longer hash, up to 11 characters.
+  TruncatedHash(usize),
 }
 
 /// A referenced name within a CSS module, e.g. via the `composes` property.
@@ -210,6 +443,248 @@ pub type CssModuleExports = HashMap<String, CssModuleExport>;
 /// A map of placeholders to references.
 pub type CssModuleReferences = HashMap<String, CssModuleReference>;
 
+/// A map of source filenames to the exports of each CSS module.
+pub type CssModuleManifest = HashMap<String, CssModuleExports>;
+
+/// Generates a TypeScript declaration file for the exports of a CSS module.
+///
+/// The declaration types the default export as an object containing each exported name,
+/// so that references to names that are not defined in the CSS module are type errors.
+pub fn typescript_declarations(exports: &CssModuleExports) -> String {
+  let mut names: Vec<&String> = exports.keys().collect();
+  names.sort();
+
+  let mut res = String::from("declare const styles: {\n");
+  for name in names {
+    res.push_str("  readonly \"");
+    for c in name.chars() {
+      if c == '"' || c == '\\' {
+        res.push('\\');
+      }
+      res.push(c);
+    }
+    res.push_str("\": string;\n");
+  }
+  res.push_str("};\nexport default styles;\n");
+  res
+}
+
+/// A report of the exports removed from a CSS module by
+/// [tree_shake_css_modules](crate::stylesheet::StyleSheet::tree_shake_css_modules).
+#[derive(PartialEq, Debug, Clone, Default)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct TreeShakeReport {
+  /// The local class names, ids, and `@keyframes` names that were removed, in sorted order.
+  pub removed_exports: Vec<String>,
+}
+
+/// Removes style rules whose selectors only match unused local classes or ids, and `@keyframes`
+/// rules that are not used or referenced by any remaining rule.
+pub(crate) fn tree_shake<R>(
+  rules: &mut CssRuleList<R>,
+  used_exports: &HashSet<String>,
+  config: &Config,
+) -> TreeShakeReport {
+  // Classes composed by a used class are applied along with it, so they are used as well.
+  let mut composes = HashMap::new();
+  collect_composes(rules, &mut composes);
+  let mut used = used_exports.clone();
+  let mut stack: Vec<&str> = used_exports.iter().map(|name| name.as_str()).collect();
+  while let Some(name) = stack.pop() {
+    for composed in composes.get(name).into_iter().flatten() {
+      if used.insert(composed.to_string()) {
+        stack.push(composed);
+      }
+    }
+  }
+
+  let mut before = HashSet::new();
+  collect_local_names(rules, config, &mut before);
+
+  remove_unused_style_rules(rules, &used);
+  if config.animation {
+    let mut referenced = HashSet::new();
+    collect_animation_names(rules, &mut referenced);
+    rules.0.retain(|rule| match rule {
+      CssRule::Keyframes(keyframes) => {
+        let name = keyframes_name(&keyframes.name);
+        used.contains(name) || referenced.contains(name)
+      }
+      _ => true,
+    });
+  }
+
+  let mut after = HashSet::new();
+  collect_local_names(rules, config, &mut after);
+  let mut removed_exports: Vec<String> = before.difference(&after).map(|name| name.to_string()).collect();
+  removed_exports.sort();
+  TreeShakeReport { removed_exports }
+}
+
+fn keyframes_name<'a>(name: &'a KeyframesName) -> &'a str {
+  match name {
+    KeyframesName::Ident(ident) => ident.0.as_ref(),
+    KeyframesName::Custom(s) => s.as_ref(),
+  }
+}
+
+/// Returns the rules nested within a rule, if any.
+fn child_rules<'a, 'i, R>(rule: &'a mut CssRule<'i, R>) -> Option<&'a mut CssRuleList<'i, R>> {
+  match rule {
+    CssRule::Style(style) => Some(&mut style.rules),
+    CssRule::Media(media) => Some(&mut media.rules),
+    CssRule::Supports(supports) => Some(&mut supports.rules),
+    CssRule::Container(container) => Some(&mut container.rules),
+    CssRule::LayerBlock(layer) => Some(&mut layer.rules),
+    CssRule::MozDocument(document) => Some(&mut document.rules),
+    CssRule::Scope(scope) => Some(&mut scope.rules),
+    CssRule::StartingStyle(starting_style) => Some(&mut starting_style.rules),
+    _ => None,
+  }
+}
+
+/// Calls the given function for each style rule, including nested rules.
+fn for_each_style_rule<'i, R, F: FnMut(&StyleRule<'i, R>)>(rules: &mut CssRuleList<'i, R>, f: &mut F) {
+  for rule in &mut rules.0 {
+    if let CssRule::Style(style) = rule {
+      f(style);
+    }
+    if let Some(rules) = child_rules(rule) {
+      for_each_style_rule(rules, f);
+    }
+  }
+}
+
+fn collect_composes<R>(rules: &mut CssRuleList<R>, composes: &mut HashMap<String, Vec<String>>) {
+  for_each_style_rule(rules, &mut |style| {
+    for (property, _) in style.declarations.iter() {
+      if let Property::Composes(Composes { names, from: None, .. }) = property {
+        for selector in &style.selectors.0 {
+          if let (1, Some(Component::Class(class))) = (selector.len(), selector.iter_raw_match_order().next()) {
+            composes
+              .entry(class.0.to_string())
+              .or_default()
+              .extend(names.iter().map(|name| name.0.to_string()));
+          }
+        }
+      }
+    }
+  });
+}
+
+fn collect_local_names<R>(rules: &mut CssRuleList<R>, config: &Config, names: &mut HashSet<String>) {
+  fn collect_selector(selector: &Selector, names: &mut HashSet<String>) {
+    for component in selector.iter_raw_match_order() {
+      match component {
+        Component::Class(name) | Component::ID(name) => {
+          names.insert(name.0.to_string());
+        }
+        Component::NonTSPseudoClass(PseudoClass::Local { selector }) => collect_selector(selector, names),
+        Component::Negation(selectors)
+        | Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Has(selectors)
+        | Component::Any(_, selectors) => {
+          for selector in selectors.iter() {
+            collect_selector(selector, names);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  for rule in &mut rules.0 {
+    if let CssRule::Keyframes(keyframes) = rule {
+      if config.animation {
+        names.insert(keyframes_name(&keyframes.name).to_string());
+      }
+    }
+  }
+
+  for_each_style_rule(rules, &mut |style| {
+    for selector in &style.selectors.0 {
+      collect_selector(selector, names);
+    }
+  });
+}
+
+/// Returns whether a selector can only match elements with an unused local class or id.
+fn is_unused_selector(selector: &Selector, used: &HashSet<String>) -> bool {
+  selector.iter_raw_match_order().any(|component| match component {
+    Component::Class(name) | Component::ID(name) => !used.contains(name.0.as_ref()),
+    Component::NonTSPseudoClass(PseudoClass::Local { selector }) => is_unused_selector(selector, used),
+    Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Has(selectors)
+    | Component::Any(_, selectors) => selectors.iter().all(|selector| is_unused_selector(selector, used)),
+    _ => false,
+  })
+}
+
+fn remove_unused_style_rules<R>(rules: &mut CssRuleList<R>, used: &HashSet<String>) {
+  rules.0.retain_mut(|rule| {
+    if let CssRule::Style(style) = rule {
+      if style.selectors.0.iter().all(|selector| is_unused_selector(selector, used)) {
+        return false;
+      }
+    }
+
+    // Remove grouping rules such as @media that no longer contain any rules.
+    match child_rules(rule) {
+      Some(rules) if !rules.0.is_empty() => {
+        remove_unused_style_rules(rules, used);
+        !rules.0.is_empty() || matches!(rule, CssRule::Style(..))
+      }
+      _ => true,
+    }
+  });
+}
+
+fn collect_animation_names<R>(rules: &mut CssRuleList<R>, names: &mut HashSet<String>) {
+  fn collect_name(name: &AnimationName, names: &mut HashSet<String>) {
+    match name {
+      AnimationName::Ident(ident) => {
+        names.insert(ident.0.to_string());
+      }
+      AnimationName::String(s) => {
+        names.insert(s.to_string());
+      }
+      AnimationName::None => {}
+    }
+  }
+
+  for_each_style_rule(rules, &mut |style| {
+    for (property, _) in style.declarations.iter() {
+      match property {
+        Property::AnimationName(animation_names, _) => {
+          for name in animation_names {
+            collect_name(name, names);
+          }
+        }
+        Property::Animation(animations, _) => {
+          for animation in animations {
+            collect_name(&animation.name, names);
+          }
+        }
+        // Names within unparsed values, e.g. containing var(), may be referenced by any identifier.
+        Property::Unparsed(UnparsedProperty {
+          property_id: PropertyId::Animation(..) | PropertyId::AnimationName(..),
+          value,
+        }) => {
+          for token in &value.0 {
+            if let TokenOrValue::Token(Token::Ident(ident)) = token {
+              names.insert(ident.to_string());
+            }
+          }
+        }
+        _ => {}
+      }
+    }
+  });
+}
+
 lazy_static! {
   static ref ENCODER: Encoding = {
     let mut spec = Specification::new();
@@ -247,10 +722,21 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
           }
           _ => Cow::Borrowed(*path),
         };
-        hash(
-          &source.to_string_lossy(),
-          matches!(config.pattern.segments[0], Segment::Hash),
-        )
+        let source = match config.hash_salt {
+          Some(salt) => Cow::Owned(format!("{}{}", salt, source.to_string_lossy())),
+          None => source.to_string_lossy(),
+        };
+        let at_start = matches!(config.pattern.segments[0], Segment::Hash | Segment::TruncatedHash(..));
+        if config
+          .pattern
+          .segments
+          .iter()
+          .any(|segment| matches!(segment, Segment::TruncatedHash(len) if *len > HASH_LEN))
+        {
+          long_hash(&source, at_start)
+        } else {
+          hash(&source, at_start)
+        }
       })
       .collect();
     Self {
@@ -263,21 +749,30 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
   }
 
   pub fn add_local(&mut self, exported: &str, local: &str, source_index: u32) {
+    self.add_local_with_suffix(exported, local, "", source_index)
+  }
+
+  /// Adds a local name, with a suffix that is appended after the generated name rather than
+  /// being included in it, e.g. the `-start` and `-end` of implicit grid line names.
+  pub(crate) fn add_local_with_suffix(&mut self, exported: &str, local: &str, suffix: &str, source_index: u32) {
     self.exports_by_source_index[source_index as usize]
       .entry(exported.into())
-      .or_insert_with(|| CssModuleExport {
-        name: self
+      .or_insert_with(|| {
+        let mut name = self
           .config
-          .pattern
-          .write_to_string(
+          .write_name_to_string(
             String::new(),
             &self.hashes[source_index as usize],
             &self.sources[source_index as usize],
             local,
           )
-          .unwrap(),
-        composes: vec![],
-        is_referenced: false,
+          .unwrap();
+        name.push_str(suffix);
+        CssModuleExport {
+          name,
+          composes: vec![],
+          is_referenced: false,
+        }
       });
   }
 
@@ -287,8 +782,7 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
       .or_insert_with(|| CssModuleExport {
         name: self
           .config
-          .pattern
-          .write_to_string(
+          .write_name_to_string(
             "--".into(),
             &self.hashes[source_index as usize],
             &self.sources[source_index as usize],
@@ -300,6 +794,45 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
       });
   }
 
+  pub fn add_value(&mut self, name: &str, value: &str, source_index: u32) {
+    self.exports_by_source_index[source_index as usize].insert(
+      name.into(),
+      CssModuleExport {
+        name: value.into(),
+        composes: vec![],
+        is_referenced: false,
+      },
+    );
+  }
+
+  /// Adds a reference to a value imported from another file via `@value`, returning
+  /// a placeholder to substitute for it.
+  pub fn import_value(&mut self, name: &str, local: &str, specifier: &str, source_index: u32) -> String {
+    let placeholder = self.import_reference(name, specifier, source_index);
+    self.exports_by_source_index[source_index as usize].insert(
+      local.into(),
+      CssModuleExport {
+        name: placeholder.clone(),
+        composes: vec![],
+        is_referenced: false,
+      },
+    );
+    placeholder
+  }
+
+  /// Adds a reference to a name exported by another file, returning a placeholder to substitute for it.
+  pub fn import_reference(&mut self, name: &str, specifier: &str, source_index: u32) -> String {
+    let placeholder = dependency_placeholder(&self.hashes[source_index as usize], name, specifier);
+    self.references.insert(
+      placeholder.clone(),
+      CssModuleReference::Dependency {
+        name: name.to_string(),
+        specifier: specifier.to_string(),
+      },
+    );
+    placeholder
+  }
+
   pub fn reference(&mut self, name: &str, source_index: u32) {
     match self.exports_by_source_index[source_index as usize].entry(name.into()) {
       std::collections::hash_map::Entry::Occupied(mut entry) => {
@@ -309,8 +842,7 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
         entry.insert(CssModuleExport {
           name: self
             .config
-            .pattern
-            .write_to_string(
+            .write_name_to_string(
               String::new(),
               &self.hashes[source_index as usize],
               &self.sources[source_index as usize],
@@ -338,8 +870,7 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
         return Some(
           self
             .config
-            .pattern
-            .write_to_string(
+            .write_name_to_string(
               String::new(),
               &self.hashes[*source_index as usize],
               &self.sources[*source_index as usize],
@@ -358,8 +889,7 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
             entry.insert(CssModuleExport {
               name: self
                 .config
-                .pattern
-                .write_to_string(
+                .write_name_to_string(
                   "--".into(),
                   &self.hashes[source_index as usize],
                   &self.sources[source_index as usize],
@@ -400,8 +930,7 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
                 None => CssModuleReference::Local {
                   name: self
                     .config
-                    .pattern
-                    .write_to_string(
+                    .write_name_to_string(
                       String::new(),
                       &self.hashes[source_index as usize],
                       &self.sources[source_index as usize],
@@ -454,12 +983,27 @@ impl<'a, 'b, 'c> CssModule<'a, 'b, 'c> {
   }
 }
 
+/// Returns the placeholder for a name imported from another file, which is replaced
+/// with the name exported by that file once it is resolved.
+pub(crate) fn dependency_placeholder(hash: &str, name: &str, specifier: &str) -> String {
+  self::hash(&format!("{}_{}_{}", hash, name, specifier), true)
+}
+
 pub(crate) fn hash(s: &str, at_start: bool) -> String {
+  encode_hash(s, 4, at_start)
+}
+
+/// Returns a 64 bit hash, encoded as 11 characters.
+fn long_hash(s: &str, at_start: bool) -> String {
+  encode_hash(s, 8, at_start)
+}
+
+fn encode_hash(s: &str, bytes: usize, at_start: bool) -> String {
   let mut hasher = DefaultHasher::new();
   s.hash(&mut hasher);
-  let hash = hasher.finish() as u32;
+  let hash = hasher.finish();
 
-  let hash = ENCODER.encode(&hash.to_le_bytes());
+  let hash = ENCODER.encode(&hash.to_le_bytes()[..bytes]);
   if at_start && matches!(hash.as_bytes()[0], b'0'..=b'9') {
     format!("_{}", hash)
   } else {
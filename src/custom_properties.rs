@@ -0,0 +1,158 @@
+//! Custom property analysis.
+//!
+//! [StyleSheet::analyze_custom_properties](crate::stylesheet::StyleSheet::analyze_custom_properties)
+//! cross-references the custom properties defined in a style sheet with the `var()` references to them.
+//! It reports custom properties that are never referenced, and references to custom properties that are
+//! never defined and have no fallback. Custom properties may also be defined or referenced outside the
+//! style sheet, e.g. by JavaScript or another style sheet, so the results should be treated as hints.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::custom::{CustomProperty, CustomPropertyName, Variable};
+use crate::properties::Property;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::collections::HashSet;
+use std::convert::Infallible;
+
+/// A report of unused and undefined custom properties, as returned by
+/// [StyleSheet::analyze_custom_properties](crate::stylesheet::StyleSheet::analyze_custom_properties).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct CustomPropertyReport {
+  /// Custom property definitions, including `@property` rules, that are never referenced by a `var()`.
+  pub unused: Vec<CustomPropertyLocation>,
+  /// `var()` references to custom properties that are never defined and have no fallback.
+  pub undefined: Vec<CustomPropertyLocation>,
+}
+
+/// A custom property definition or reference.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct CustomPropertyLocation {
+  /// The name of the custom property, including the leading `--`.
+  pub name: String,
+  /// The location of the rule that contains the definition or reference.
+  pub loc: Location,
+}
+
+#[derive(Default)]
+pub(crate) struct CustomPropertyAnalysis {
+  definitions: Vec<CustomPropertyLocation>,
+  /// References without a fallback.
+  references: Vec<CustomPropertyLocation>,
+  /// The names of all referenced custom properties, with or without a fallback.
+  referenced: HashSet<String>,
+}
+
+impl CustomPropertyAnalysis {
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>) {
+    for rule in &rules.0 {
+      self.add_rule(rule);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>) {
+    match rule {
+      CssRule::Style(style) => {
+        self.add_declarations(&style.declarations, style.loc);
+        self.add_rules(&style.rules);
+      }
+      CssRule::Nesting(nesting) => {
+        self.add_declarations(&nesting.style.declarations, nesting.loc);
+        self.add_rules(&nesting.style.rules);
+      }
+      CssRule::Property(property) => self.definitions.push(CustomPropertyLocation {
+        name: property.name.0.to_string(),
+        loc: property.loc,
+      }),
+      CssRule::Media(media) => self.add_rules(&media.rules),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules),
+      CssRule::Container(container) => self.add_rules(&container.rules),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          self.add_declarations(&keyframe.declarations, keyframes.loc);
+        }
+      }
+      CssRule::Page(page) => {
+        self.add_declarations(&page.declarations, page.loc);
+        for margin in &page.rules {
+          self.add_declarations(&margin.declarations, margin.loc);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock, loc: Location) {
+    // Visit a copy of the declarations, since visitors require mutable access.
+    let mut declarations = declarations.clone();
+    let _ = declarations.visit(&mut CustomPropertyVisitor { analysis: self, loc });
+  }
+
+  pub(crate) fn report(self) -> CustomPropertyReport {
+    let defined: HashSet<&str> = self.definitions.iter().map(|d| d.name.as_str()).collect();
+    let undefined = self
+      .references
+      .iter()
+      .filter(|r| !defined.contains(r.name.as_str()))
+      .cloned()
+      .collect();
+
+    let unused = self
+      .definitions
+      .iter()
+      .filter(|d| !self.referenced.contains(&d.name))
+      .cloned()
+      .collect();
+
+    CustomPropertyReport { unused, undefined }
+  }
+}
+
+struct CustomPropertyVisitor<'a> {
+  analysis: &'a mut CustomPropertyAnalysis,
+  loc: Location,
+}
+
+impl<'a> CustomPropertyVisitor<'a> {
+  fn add(list: &mut Vec<CustomPropertyLocation>, name: &str, loc: Location) {
+    if !list.iter().any(|item| item.name == name && item.loc == loc) {
+      list.push(CustomPropertyLocation { name: name.into(), loc });
+    }
+  }
+}
+
+impl<'a, 'i> Visitor<'i> for CustomPropertyVisitor<'a> {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES | VisitTypes::TOKENS | VisitTypes::VARIABLES
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    if let Property::Custom(CustomProperty {
+      name: CustomPropertyName::Custom(name),
+      ..
+    }) = property
+    {
+      Self::add(&mut self.analysis.definitions, &name.0, self.loc);
+    }
+    property.visit_children(self)
+  }
+
+  fn visit_variable(&mut self, var: &mut Variable<'i>) -> Result<(), Self::Error> {
+    // References to custom properties in other files are resolved by CSS modules.
+    if var.name.from.is_none() {
+      let name = &var.name.ident.0;
+      self.analysis.referenced.insert(name.to_string());
+      if var.fallback.is_none() {
+        Self::add(&mut self.analysis.references, name, self.loc);
+      }
+    }
+    var.visit_children(self)
+  }
+}
@@ -3,12 +3,16 @@
 use std::borrow::Cow;
 use std::ops::Range;
 
-use crate::context::PropertyHandlerContext;
+use crate::compat::Feature;
+use crate::context::{DeclarationContext, PropertyHandlerContext};
 use crate::error::{ParserError, PrinterError};
-use crate::parser::ParserOptions;
-use crate::printer::Printer;
+use crate::parser::{ParserFlags, ParserOptions, ValidationMode};
+use crate::printer::{Printer, PrinterOptions};
 use crate::properties::box_shadow::BoxShadowHandler;
-use crate::properties::custom::CustomPropertyName;
+use crate::properties::custom::{
+  self, has_disabled_color_syntax, is_unparsed_standard_property, CustomProperty, CustomPropertyName,
+  TokenOrValue, UnparsedProperty,
+};
 use crate::properties::masking::MaskHandler;
 use crate::properties::{
   align::AlignHandler,
@@ -32,10 +36,14 @@ use crate::properties::{
   transition::TransitionHandler,
 };
 use crate::properties::{Property, PropertyId};
+use crate::targets::{Browsers, Targets};
 use crate::traits::{PropertyHandler, ToCss};
+use crate::values::ident::Ident;
+use crate::values::length::LengthValue;
 use crate::values::string::CowArcStr;
+use crate::vendor_prefix::{PrefixPolicy, VendorPrefix};
 #[cfg(feature = "visitor")]
-use crate::visitor::Visit;
+use crate::visitor::{Visit, VisitTypes, Visitor};
 use cssparser::*;
 
 /// A CSS declaration block.
@@ -44,7 +52,6 @@ use cssparser::*;
 /// and a list of normal declarations. This reduces memory usage compared
 /// with storing a boolean along with each property.
 #[derive(Debug, PartialEq, Clone, Default)]
-#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_declaration_block, PROPERTIES))]
 #[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
 #[cfg_attr(
   feature = "serde",
@@ -61,6 +68,50 @@ pub struct DeclarationBlock<'i> {
   pub declarations: Vec<Property<'i>>,
 }
 
+// Manually implemented so that visitors can replace or remove properties.
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for DeclarationBlock<'i> {
+  const CHILD_TYPES: VisitTypes = VisitTypes::from_bits_retain(
+    <Property<'i> as Visit<'i, T, V>>::CHILD_TYPES.bits() | VisitTypes::PROPERTIES.bits(),
+  );
+
+  fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if visitor.visit_types().contains(VisitTypes::PROPERTIES) {
+      visitor.visit_declaration_block(self)
+    } else {
+      self.visit_children(visitor)
+    }
+  }
+
+  fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if !<Self as Visit<'i, T, V>>::CHILD_TYPES.intersects(visitor.visit_types()) {
+      return Ok(());
+    }
+
+    if !visitor.visit_types().contains(VisitTypes::PROPERTIES) {
+      self.important_declarations.visit(visitor)?;
+      return self.declarations.visit(visitor);
+    }
+
+    for declarations in [&mut self.important_declarations, &mut self.declarations] {
+      let mut i = 0;
+      while i < declarations.len() {
+        match visitor.replace_property(&mut declarations[i])? {
+          Some(properties) => {
+            let len = properties.len();
+            declarations.splice(i..i + 1, properties);
+            i += len;
+          }
+          None => i += 1,
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
 impl<'i> DeclarationBlock<'i> {
   /// Parses a declaration block from CSS syntax.
   pub fn parse<'a, 'o, 't>(
@@ -72,6 +123,7 @@ impl<'i> DeclarationBlock<'i> {
     let mut decl_parser = PropertyDeclarationParser {
       important_declarations: &mut important_declarations,
       declarations: &mut declarations,
+      sources: DeclarationSources::default(),
       options,
     };
     let mut parser = RuleBodyParser::new(input, &mut decl_parser);
@@ -142,6 +194,37 @@ impl<'i> ToCss for DeclarationBlock<'i> {
     write!(self.important_declarations, true);
     Ok(())
   }
+
+  /// Returns whether the declaration blocks are semantically equivalent. Both blocks are minified
+  /// first, so e.g. longhands are equivalent to the shorthand they combine into, and overridden
+  /// declarations are ignored. The order of declarations of different properties is also ignored.
+  fn semantic_eq(&self, other: &Self) -> bool {
+    fn canonical(block: &DeclarationBlock) -> Result<Vec<(String, String)>, PrinterError> {
+      let options = || PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      };
+      let mut block = block.clone();
+      block.minify_standalone(Targets::default());
+      let mut res = block
+        .iter()
+        .map(|(property, important)| {
+          Ok((
+            property.property_id().name().to_string(),
+            property.to_css_string(important, options())?,
+          ))
+        })
+        .collect::<Result<Vec<_>, PrinterError>>()?;
+      // Fallbacks for the same property must remain in order, so the sort must be stable.
+      res.sort_by(|a, b| a.0.cmp(&b.0));
+      Ok(res)
+    }
+
+    match (canonical(self), canonical(other)) {
+      (Ok(a), Ok(b)) => a == b,
+      _ => false,
+    }
+  }
 }
 
 impl<'i> DeclarationBlock<'i> {
@@ -206,6 +289,62 @@ impl<'i> DeclarationBlock<'i> {
     important_handler.finalize(context);
     self.important_declarations = std::mem::take(&mut important_handler.decls);
     self.declarations = std::mem::take(&mut handler.decls);
+
+    if !context.prefix_rules.is_empty() {
+      add_custom_prefixes(&mut self.important_declarations, context);
+      add_custom_prefixes(&mut self.declarations, context);
+    }
+
+    if !context.prefix_policies.is_empty() {
+      apply_prefix_policies(&mut self.important_declarations, context);
+      apply_prefix_policies(&mut self.declarations, context);
+    }
+
+    // iOS Safari 11.0 and 11.1 only support safe area insets via the legacy constant() syntax.
+    if context.targets.browsers.is_some_and(|browsers| {
+      !Feature::EnvFunction.is_compatible(Browsers {
+        safari: browsers.safari,
+        ios_saf: browsers.ios_saf,
+        ..Browsers::default()
+      })
+    }) {
+      add_constant_fallbacks(&mut self.important_declarations);
+      add_constant_fallbacks(&mut self.declarations);
+    }
+  }
+
+  /// Minifies the declarations for the given targets, outside of a style sheet.
+  fn minify_standalone(&mut self, targets: Targets) {
+    let unused_symbols = Default::default();
+    let prefix_policies = Default::default();
+    let mut context = PropertyHandlerContext::new(targets, &unused_symbols, &[], &prefix_policies);
+    context.context = DeclarationContext::StyleAttribute;
+    self.minify(
+      &mut DeclarationHandler::default(),
+      &mut DeclarationHandler::default(),
+      &mut context,
+    );
+  }
+
+  /// Removes vendor prefixed declarations that are not needed by the targets. If the unprefixed
+  /// property is not declared in the block, the declaration is unprefixed instead.
+  /// Returns the number of bytes reclaimed in the minified output.
+  pub(crate) fn remove_obsolete_prefixes(&mut self, targets: Targets) -> usize {
+    remove_obsolete_prefixes(&mut self.declarations, false, targets)
+      + remove_obsolete_prefixes(&mut self.important_declarations, true, targets)
+  }
+
+  /// Replaces `var()` references to the given custom properties with their values, and re-parses
+  /// the declarations that contained them. Within a `:root` rule, declarations of the custom
+  /// properties themselves are removed.
+  #[cfg(feature = "substitute_variables")]
+  pub(crate) fn inline_custom_properties(
+    &mut self,
+    vars: &std::collections::HashMap<&str, crate::properties::custom::TokenList<'i>>,
+    is_root: bool,
+  ) {
+    inline_custom_properties(&mut self.declarations, vars, is_root);
+    inline_custom_properties(&mut self.important_declarations, vars, is_root);
   }
 
   /// Returns whether the declaration block is empty.
@@ -251,6 +390,289 @@ impl<'i> DeclarationBlock<'i> {
   }
 }
 
+#[cfg(feature = "substitute_variables")]
+fn inline_custom_properties<'i>(
+  decls: &mut DeclarationList<'i>,
+  vars: &std::collections::HashMap<&str, crate::properties::custom::TokenList<'i>>,
+  is_root: bool,
+) {
+  use static_self::IntoOwned;
+
+  if is_root {
+    decls.retain(|decl| {
+      !matches!(decl, Property::Custom(custom) if matches!(&custom.name, CustomPropertyName::Custom(name) if vars.contains_key(name.0.as_ref())))
+    });
+  }
+
+  for decl in decls.iter_mut() {
+    match decl {
+      Property::Unparsed(unparsed) => {
+        if !unparsed.value.inline_variables(vars) {
+          continue;
+        }
+
+        // Re-parse the property now that the values are known, so that it can be minified.
+        // If it is still invalid, keep the tokens as is.
+        let mut css = String::new();
+        let mut dest = Printer::new(&mut css, PrinterOptions::default());
+        if unparsed.value.to_css(&mut dest, false).is_err() {
+          continue;
+        }
+
+        let property = Property::parse_string(unparsed.property_id.clone(), &css, ParserOptions::default())
+          .ok()
+          .map(|property| property.into_owned());
+        if let Some(property) = property {
+          *decl = property;
+        }
+      }
+      Property::Custom(custom) => {
+        custom.value.inline_variables(vars);
+      }
+      _ => {}
+    }
+  }
+}
+
+fn remove_obsolete_prefixes(decls: &mut DeclarationList, important: bool, targets: Targets) -> usize {
+  let minified_len = |decl: &Property| {
+    decl
+      .to_css_string(
+        important,
+        PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        },
+      )
+      .map_or(0, |s| s.len())
+  };
+
+  let mut bytes = 0;
+  let mut i = 0;
+  while i < decls.len() {
+    let property_id = decls[i].property_id();
+    let prefix = property_id.prefix();
+    if prefix.is_empty() || prefix.contains(VendorPrefix::None) {
+      i += 1;
+      continue;
+    }
+
+    // Some properties only exist with a vendor prefix (e.g. -webkit-box-orient).
+    let mut unprefixed = PropertyId::from(CowArcStr::from(property_id.name().to_owned()));
+    if unprefixed.prefix() != VendorPrefix::None {
+      i += 1;
+      continue;
+    }
+
+    unprefixed.set_prefixes_for_targets(targets);
+    let needed = unprefixed.prefix();
+    if !needed.contains(VendorPrefix::None) || needed.contains(prefix) {
+      i += 1;
+      continue;
+    }
+
+    let mut without_prefix = decls[i].clone();
+    match &mut without_prefix {
+      Property::Unparsed(unparsed) => unparsed.property_id = property_id.with_prefix(VendorPrefix::None),
+      property => property.set_prefix(VendorPrefix::None),
+    }
+
+    let is_unprefixed = |decl: &Property| {
+      let id = decl.property_id();
+      id.prefix().contains(VendorPrefix::None) && id.name() == property_id.name()
+    };
+
+    if decls.iter().any(is_unprefixed) {
+      // The prefixed declaration is only redundant if an equivalent unprefixed declaration overrides it.
+      // Otherwise, it may be intentionally different (e.g. an older syntax), so leave it alone.
+      if decls[i + 1..].iter().any(|decl| is_unprefixed(decl) && *decl == without_prefix) {
+        // Account for the semicolon separating declarations as well.
+        bytes += minified_len(&decls[i]) + 1;
+        decls.remove(i);
+      } else {
+        i += 1;
+      }
+      continue;
+    }
+
+    let len = minified_len(&decls[i]);
+    decls[i] = without_prefix;
+    bytes += len.saturating_sub(minified_len(&decls[i]));
+    i += 1;
+  }
+
+  bytes
+}
+
+/// Inserts a `constant()` fallback before declarations referencing `env(safe-area-inset-*)`.
+fn add_constant_fallbacks(decls: &mut DeclarationList) {
+  let mut i = 0;
+  while i < decls.len() {
+    if let Property::Unparsed(unparsed) = &decls[i] {
+      if let Some(value) = unparsed.value.get_constant_fallback() {
+        let fallback = Property::Unparsed(UnparsedProperty {
+          property_id: unparsed.property_id.clone(),
+          value,
+        });
+
+        if i == 0 || decls[i - 1] != fallback {
+          decls.insert(i, fallback);
+          i += 1;
+        }
+      }
+    }
+    i += 1;
+  }
+}
+
+/// Applies user-supplied prefix rules to a list of declarations.
+fn add_custom_prefixes<'i>(decls: &mut DeclarationList<'i>, context: &PropertyHandlerContext<'i, '_>) {
+  let mut i = 0;
+  while i < decls.len() {
+    let property_id = decls[i].property_id();
+    let is_unknown =
+      matches!(&decls[i], Property::Custom(custom) if matches!(custom.name, CustomPropertyName::Unknown(..)));
+    // Only unprefixed properties are considered. Known properties must support vendor prefixes,
+    // and unknown properties must not already have a vendor prefix in their name.
+    let policy = context.prefix_policy(property_id.name());
+    if (is_unknown && property_id.name().starts_with('-'))
+      || (!is_unknown && !property_id.prefix().contains(VendorPrefix::None))
+      || policy == PrefixPolicy::Never
+    {
+      i += 1;
+      continue;
+    }
+
+    let mut value = None;
+    let mut prefixes = VendorPrefix::empty();
+    for rule in context.prefix_rules {
+      if !rule.property.eq_ignore_ascii_case(property_id.name())
+        || (policy == PrefixPolicy::Auto && !rule.applies_to(&context.targets))
+      {
+        continue;
+      }
+
+      if !rule.values.is_empty() {
+        let value = value.get_or_insert_with(|| {
+          decls[i]
+            .value_to_css_string(PrinterOptions {
+              minify: true,
+              ..PrinterOptions::default()
+            })
+            .unwrap_or_default()
+        });
+        if !rule.matches_value(value) {
+          continue;
+        }
+      }
+
+      prefixes |= rule.prefixes;
+    }
+
+    prefixes.remove(VendorPrefix::None);
+    if prefixes.is_empty() {
+      i += 1;
+      continue;
+    }
+
+    match &mut decls[i] {
+      Property::Custom(custom) => {
+        let custom = custom.clone();
+        let mut inserted = Vec::new();
+        for prefix in prefixes {
+          let name = format!("{}{}", cssparser::ToCss::to_css_string(&prefix), custom.name.as_ref());
+          let exists = decls
+            .iter()
+            .any(|decl| matches!(decl, Property::Custom(c) if c.name.as_ref().eq_ignore_ascii_case(&name)));
+          if !exists {
+            let mut prefixed = custom.clone();
+            prefixed.name = CustomPropertyName::Unknown(Ident(name.into()));
+            inserted.push(Property::Custom(prefixed));
+          }
+        }
+        let len = inserted.len();
+        decls.splice(i..i, inserted);
+        i += len;
+      }
+      Property::Unparsed(unparsed) => {
+        let prefix = unparsed.property_id.prefix();
+        unparsed.property_id = unparsed.property_id.with_prefix(prefix | prefixes);
+      }
+      property => {
+        property.set_prefix(property_id.prefix() | prefixes);
+      }
+    }
+
+    i += 1;
+  }
+}
+
+/// Applies per-property prefix policies to a list of declarations.
+fn apply_prefix_policies<'i>(decls: &mut DeclarationList<'i>, context: &PropertyHandlerContext<'i, '_>) {
+  let mut i = 0;
+  while i < decls.len() {
+    let property_id = decls[i].property_id();
+    let (name, prefix) = match &property_id {
+      // Unknown properties carry their prefix in the name.
+      PropertyId::Custom(CustomPropertyName::Unknown(name)) => match unprefixed_name(name) {
+        Some(name) => (name, VendorPrefix::WebKit),
+        None => (name.as_ref(), VendorPrefix::None),
+      },
+      PropertyId::Custom(_) | PropertyId::All => {
+        i += 1;
+        continue;
+      }
+      property_id => (property_id.name(), property_id.prefix()),
+    };
+
+    let policy = context.prefix_policy(name);
+    if policy == PrefixPolicy::Auto || prefix.is_empty() {
+      i += 1;
+      continue;
+    }
+
+    // Remove prefixed-only declarations that are overridden by an unprefixed one.
+    if policy == PrefixPolicy::Never && !prefix.contains(VendorPrefix::None) {
+      let has_unprefixed = decls.iter().any(|decl| match decl.property_id() {
+        PropertyId::Custom(CustomPropertyName::Unknown(other)) => other.as_ref().eq_ignore_ascii_case(name),
+        other => other.name() == name && other.prefix().contains(VendorPrefix::None),
+      });
+      if has_unprefixed {
+        decls.remove(i);
+      } else {
+        i += 1;
+      }
+      continue;
+    }
+
+    let prefixes = match policy {
+      PrefixPolicy::Always if prefix.contains(VendorPrefix::None) => prefix | property_id.allowed_prefixes(),
+      PrefixPolicy::Always => prefix,
+      _ => VendorPrefix::None,
+    };
+
+    match &mut decls[i] {
+      Property::Custom(_) => {}
+      Property::Unparsed(unparsed) => {
+        unparsed.property_id = unparsed.property_id.with_prefix(prefixes);
+      }
+      property => property.set_prefix(prefixes),
+    }
+
+    i += 1;
+  }
+}
+
+/// Returns the name of an unknown property without its vendor prefix, if it has one.
+fn unprefixed_name(name: &str) -> Option<&str> {
+  ["-webkit-", "-moz-", "-ms-", "-o-"].iter().find_map(|prefix| {
+    name
+      .get(..prefix.len())
+      .filter(|start| start.eq_ignore_ascii_case(prefix))
+      .map(|_| &name[prefix.len()..])
+  })
+}
+
 impl<'i> DeclarationBlock<'i> {
   /// Returns an iterator over all properties in the declaration.
   pub fn iter(&self) -> impl std::iter::DoubleEndedIterator<Item = (&Property<'i>, bool)> {
@@ -401,9 +823,83 @@ impl<'i> DeclarationBlock<'i> {
   }
 }
 
+/// A single CSS declaration, e.g. `color: red !important`.
+///
+/// This can be used to process individual declarations without parsing a style sheet,
+/// e.g. in linters or when processing style attributes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Declaration<'i> {
+  /// The property and its value.
+  pub property: Property<'i>,
+  /// Whether the declaration has an `!important` flag.
+  pub important: bool,
+}
+
+impl<'i> Declaration<'i> {
+  /// Parses a single `property: value` declaration from a string, with an optional
+  /// `!important` flag and trailing semicolon.
+  pub fn parse_string<'o>(
+    input: &'i str,
+    options: ParserOptions<'o, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut input = ParserInput::new(input);
+    let mut parser = Parser::new(&mut input);
+    let mut declarations = DeclarationList::new();
+    let mut important_declarations = DeclarationList::new();
+    parser.parse_entirely(|input| {
+      input.parse_until_before(Delimiter::Semicolon, |input| {
+        let name = input.expect_ident_cloned()?;
+        input.expect_colon()?;
+        parse_declaration(
+          name,
+          input,
+          &mut declarations,
+          &mut important_declarations,
+          &mut DeclarationSources::default(),
+          &options,
+        )
+      })?;
+      let _ = input.try_parse(|input| input.expect_semicolon());
+      Ok(())
+    })?;
+
+    Ok(match declarations.pop() {
+      Some(property) => Declaration {
+        property,
+        important: false,
+      },
+      None => Declaration {
+        property: important_declarations.pop().unwrap(),
+        important: true,
+      },
+    })
+  }
+
+  /// Serializes the declaration in a canonical form, compiled for the targets in the printer options.
+  ///
+  /// Values are minified as they would be in a style sheet, e.g. `margin: 1px 1px` becomes
+  /// `margin: 1px`, and vendor prefixes and fallbacks are added for the targets, so the
+  /// result may contain multiple declarations separated by semicolons.
+  pub fn to_canonical_css(&self, options: PrinterOptions) -> Result<String, PrinterError> {
+    let mut block = DeclarationBlock::new();
+    if self.important {
+      block.important_declarations.push(self.property.clone());
+    } else {
+      block.declarations.push(self.property.clone());
+    }
+
+    block.minify_standalone(options.targets);
+    let mut dest = String::new();
+    let mut printer = Printer::new(&mut dest, options);
+    block.to_css(&mut printer)?;
+    Ok(dest)
+  }
+}
+
 struct PropertyDeclarationParser<'a, 'o, 'i> {
   important_declarations: &'a mut Vec<Property<'i>>,
   declarations: &'a mut Vec<Property<'i>>,
+  sources: DeclarationSources<'i>,
   options: &'a ParserOptions<'o, 'i>,
 }
 
@@ -422,6 +918,7 @@ impl<'a, 'o, 'i> cssparser::DeclarationParser<'i> for PropertyDeclarationParser<
       input,
       &mut self.declarations,
       &mut self.important_declarations,
+      &mut self.sources,
       &self.options,
     )
   }
@@ -455,8 +952,16 @@ pub(crate) fn parse_declaration<'i, 't>(
   input: &mut cssparser::Parser<'i, 't>,
   declarations: &mut DeclarationList<'i>,
   important_declarations: &mut DeclarationList<'i>,
+  sources: &mut DeclarationSources<'i>,
   options: &ParserOptions<'_, 'i>,
 ) -> Result<(), cssparser::ParseError<'i, ParserError<'i>>> {
+  // Record where the value starts, for diagnostics.
+  let state = input.state();
+  input.skip_whitespace();
+  let loc = input.current_source_location();
+  let start = input.position();
+  input.reset(&state);
+  let property_name = name.clone();
   // Stop if we hit a `{` token in a non-custom property to
   // avoid ambiguity between nested rules and declarations.
   // https://github.com/w3c/csswg-drafts/issues/9317
@@ -466,6 +971,7 @@ pub(crate) fn parse_declaration<'i, 't>(
     delimiters = delimiters | Delimiter::CurlyBracketBlock;
   }
   let property = input.parse_until_before(delimiters, |input| Property::parse(property_id, input, options))?;
+  let value = input.slice_from(start);
   let important = input
     .try_parse(|input| {
       input.expect_delim('!')?;
@@ -473,6 +979,29 @@ pub(crate) fn parse_declaration<'i, 't>(
     })
     .is_ok();
   input.expect_exhausted()?;
+  if options.validation != ValidationMode::Ignore {
+    validate_declaration(&property, &property_name, value, loc, options)?;
+  }
+  if options.flags.contains(ParserFlags::WARN_OVERRIDDEN_DECLARATIONS) {
+    let source = DeclarationSource {
+      loc,
+      value,
+      overridden: false,
+    };
+    sources.add(
+      source,
+      &property,
+      important,
+      declarations,
+      important_declarations,
+      |loc| {
+        options.warn(ParseError {
+          kind: ParseErrorKind::Custom(ParserError::OverriddenDeclaration(property_name.clone().into())),
+          location: loc,
+        })
+      },
+    );
+  }
   if important {
     important_declarations.push(property);
   } else {
@@ -481,8 +1010,172 @@ pub(crate) fn parse_declaration<'i, 't>(
   Ok(())
 }
 
+/// Reports unknown properties and invalid values according to [ParserOptions::validation](ParserOptions::validation).
+fn validate_declaration<'i>(
+  property: &Property<'i>,
+  name: &CowRcStr<'i>,
+  value: &'i str,
+  loc: SourceLocation,
+  options: &ParserOptions<'_, 'i>,
+) -> Result<(), ParseError<'i, ParserError<'i>>> {
+  let kind = match property {
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Unknown(Ident(unknown)),
+      ..
+    }) if !unknown.starts_with('-') && !is_unparsed_standard_property(unknown) => {
+      ParserError::UnknownProperty(name.clone().into())
+    }
+    Property::Unparsed(unparsed)
+      if (unparsed.property_id.prefix() - VendorPrefix::None).is_empty()
+        && !unparsed.value.has_substitutions()
+        && !unparsed.value.is_css_wide_keyword()
+        && !is_unparsed_none(unparsed)
+        && !syntax_features(value).iter().any(|feature| feature.starts_with('-'))
+        && !has_disabled_color_syntax(&mut Parser::new(&mut ParserInput::new(value)), options.disabled_drafts) =>
+    {
+      ParserError::InvalidPropertyValue(name.clone().into())
+    }
+    Property::Custom(CustomProperty {
+      name: CustomPropertyName::Custom(custom_name),
+      value,
+      typed_value: None,
+    }) if options.registered_syntax(custom_name.as_ref()).is_some()
+      && !value.has_substitutions()
+      && !value.is_css_wide_keyword() =>
+    {
+      ParserError::InvalidPropertyValue(name.clone().into())
+    }
+    Property::Custom(CustomProperty { value, .. }) | Property::Unparsed(UnparsedProperty { value, .. }) => {
+      match value.invalid_registered_fallback(options) {
+        Some(name) => ParserError::InvalidVariableFallback(name.0.clone()),
+        None => return Ok(()),
+      }
+    }
+    _ => return Ok(()),
+  };
+
+  let err = ParseError {
+    kind: ParseErrorKind::Custom(kind),
+    location: loc,
+  };
+  if options.validation == ValidationMode::Error {
+    return Err(err);
+  }
+  options.warn(err);
+  Ok(())
+}
+
+/// Returns whether the value is a valid `none` keyword for a property that stores it unparsed.
+fn is_unparsed_none(unparsed: &UnparsedProperty) -> bool {
+  matches!(unparsed.property_id, PropertyId::BoxShadow(_) | PropertyId::TextShadow)
+    && matches!(
+      &unparsed.value.0[..],
+      [TokenOrValue::Token(custom::Token::Ident(ident))] if ident.eq_ignore_ascii_case("none")
+    )
+}
+
 pub(crate) type DeclarationList<'i> = Vec<Property<'i>>;
 
+/// The source locations and values of the declarations parsed so far in a block, parallel to
+/// its declaration lists. Used to warn about declarations that are overridden by later ones.
+#[derive(Default)]
+pub(crate) struct DeclarationSources<'i> {
+  declarations: Vec<DeclarationSource<'i>>,
+  important_declarations: Vec<DeclarationSource<'i>>,
+}
+
+struct DeclarationSource<'i> {
+  loc: SourceLocation,
+  value: &'i str,
+  overridden: bool,
+}
+
+impl<'i> DeclarationSources<'i> {
+  fn add<F: Fn(SourceLocation)>(
+    &mut self,
+    mut source: DeclarationSource<'i>,
+    property: &Property<'i>,
+    important: bool,
+    declarations: &DeclarationList<'i>,
+    important_declarations: &DeclarationList<'i>,
+    warn: F,
+  ) {
+    let property_id = property.property_id();
+    let check = |sources: &mut Vec<DeclarationSource<'i>>, list: &DeclarationList<'i>| {
+      for (earlier, decl) in sources.iter_mut().zip(list) {
+        if !earlier.overridden && decl.property_id() == property_id && !is_fallback(earlier.value, source.value) {
+          earlier.overridden = true;
+          warn(earlier.loc);
+        }
+      }
+    };
+
+    if important {
+      check(&mut self.important_declarations, important_declarations);
+      check(&mut self.declarations, declarations);
+      self.important_declarations.push(source);
+    } else {
+      check(&mut self.declarations, declarations);
+      // A normal declaration always loses to an earlier `!important` one.
+      if important_declarations.iter().any(|decl| decl.property_id() == property_id) {
+        source.overridden = true;
+        warn(source.loc);
+      }
+      self.declarations.push(source);
+    }
+  }
+
+  pub(crate) fn clear(&mut self) {
+    self.declarations.clear();
+    self.important_declarations.clear();
+  }
+}
+
+/// Returns whether `value` uses syntax that `earlier` does not, such as a function or a length
+/// unit with limited browser support, or either value is vendor prefixed. In that case, the earlier
+/// declaration is likely an intentional fallback for browsers that do not support the later one.
+fn is_fallback(earlier: &str, value: &str) -> bool {
+  let earlier = syntax_features(earlier);
+  earlier.iter().any(|feature| feature.starts_with('-'))
+    || syntax_features(value).iter().any(|feature| !earlier.contains(feature))
+}
+
+fn syntax_features(value: &str) -> Vec<String> {
+  fn collect(input: &mut Parser, features: &mut Vec<String>) {
+    while let Ok(token) = input.next() {
+      let token = token.clone();
+      match &token {
+        Token::Function(name) => features.push(format!("{}()", name.to_ascii_lowercase())),
+        Token::Ident(name) if name.starts_with('-') && !name.starts_with("--") => {
+          features.push(name.to_ascii_lowercase())
+        }
+        Token::Dimension { unit, .. } => {
+          if matches!(LengthValue::try_from(&token), Ok(length) if length.feature().is_some()) {
+            features.push(unit.to_ascii_lowercase())
+          }
+        }
+        _ => {}
+      }
+
+      if matches!(
+        token,
+        Token::Function(..) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock
+      ) {
+        let _ = input.parse_nested_block(|input| {
+          collect(input, features);
+          Ok::<_, ParseError<'_, ()>>(())
+        });
+      }
+    }
+  }
+
+  let mut input = ParserInput::new(value);
+  let mut parser = Parser::new(&mut input);
+  let mut features = Vec::new();
+  collect(&mut parser, &mut features);
+  features
+}
+
 #[derive(Default)]
 pub(crate) struct DeclarationHandler<'i> {
   background: BackgroundHandler<'i>,
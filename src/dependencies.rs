@@ -7,6 +7,10 @@
 //! When dependency analysis is enabled, `@import` rules are removed, and `url()`
 //! dependencies are replaced with hashed placeholders that can be substituted with
 //! the final urls later (e.g. after bundling and content hashing).
+//!
+//! Alternatively, [StyleSheet::analyze_dependencies](super::stylesheet::StyleSheet::analyze_dependencies)
+//! returns all `@import`, `url()`, and `image-set()` references in a style sheet without
+//! printing or rewriting it.
 
 use crate::css_modules::hash;
 use crate::printer::PrinterOptions;
@@ -117,8 +121,70 @@ impl UrlDependency {
   }
 }
 
+/// The kind of reference to a dependency.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "kebab-case"))]
+pub enum DependencyKind {
+  /// An `@import` rule.
+  Import,
+  /// A `url()` function.
+  Url,
+  /// An image within an `image-set()` function, referenced either via `url()` or a string.
+  ImageSet,
+}
+
+/// A reference to a dependency, as returned by
+/// [StyleSheet::analyze_dependencies](super::stylesheet::StyleSheet::analyze_dependencies).
+#[derive(Debug, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+pub struct DependencyReference {
+  /// The kind of reference.
+  pub kind: DependencyKind,
+  /// The specifier of the dependency, as written in the source.
+  pub specifier: String,
+  /// The `layer()` condition of an `@import` rule. Anonymous layers are represented as an empty string.
+  pub layer: Option<String>,
+  /// The `supports()` condition of an `@import` rule.
+  pub supports: Option<String>,
+  /// The media query of an `@import` rule.
+  pub media: Option<String>,
+  /// The location of the specifier in the source file.
+  pub loc: SourceRange,
+}
+
+impl DependencyReference {
+  /// Creates a new dependency reference from an `@import` rule.
+  pub(crate) fn import(rule: &ImportRule, filename: &str) -> DependencyReference {
+    let dep = ImportDependency::new(rule, filename);
+    DependencyReference {
+      kind: DependencyKind::Import,
+      specifier: dep.url,
+      layer: rule.layer.as_ref().map(|layer| match layer {
+        Some(name) => name.to_css_string(PrinterOptions::default()).unwrap(),
+        None => String::new(),
+      }),
+      supports: dep.supports,
+      media: dep.media,
+      loc: dep.loc,
+    }
+  }
+
+  /// Creates a new dependency reference from a `url()` or an `image-set()` option.
+  pub(crate) fn url(url: &Url, kind: DependencyKind, filename: &str) -> DependencyReference {
+    DependencyReference {
+      kind,
+      specifier: url.url.to_string(),
+      layer: None,
+      supports: None,
+      media: None,
+      loc: SourceRange::new(filename, url.loc, 4, url.url.len()),
+    }
+  }
+}
+
 /// Represents the range of source code where a dependency was found.
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 #[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
 #[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
 pub struct SourceRange {
@@ -0,0 +1,266 @@
+//! Structural style sheet diffs.
+//!
+//! [StyleSheet::diff](crate::stylesheet::StyleSheet::diff) compares two style sheets, and reports the
+//! rules and declarations that were added, removed, or changed. Rules and declarations are compared
+//! by their minified serialization, so differences in formatting, comments, and the representation
+//! of equivalent values (e.g. `#ff0000` and `red`) are ignored.
+//!
+//! Style rules are matched by their selectors and the preludes of the rules they are nested in.
+//! Other rules, such as `@font-face` or `@keyframes`, are matched by their entire contents, so a
+//! change to one is reported as a removed rule and an added rule.
+
+use crate::declaration::DeclarationBlock;
+use crate::error::PrinterError;
+use crate::printer::PrinterOptions;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::traits::ToCss;
+
+/// The differences between two style sheets, as returned by
+/// [StyleSheet::diff](crate::stylesheet::StyleSheet::diff).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct StyleSheetDiff {
+  /// The rules that were added, removed, or changed. Added and changed rules are listed in the
+  /// order they appear in the new style sheet, followed by removed rules in the order they
+  /// appeared in the old style sheet.
+  pub rules: Vec<RuleDiff>,
+}
+
+/// A rule that was added, removed, or changed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct RuleDiff {
+  /// The kind of change.
+  pub kind: DiffKind,
+  /// The preludes of the rules that contain the rule, e.g. `@media print`, followed by the
+  /// selectors of a style rule, or the minified contents of any other rule.
+  pub path: Vec<String>,
+  /// The location of the rule in the old style sheet, unless it was added or is a custom at-rule.
+  pub old_loc: Option<Location>,
+  /// The location of the rule in the new style sheet, unless it was removed or is a custom at-rule.
+  pub new_loc: Option<Location>,
+  /// The declarations that were added, removed, or changed. All declarations of an added
+  /// or removed style rule are included.
+  pub declarations: Vec<DeclarationDiff>,
+}
+
+/// A declaration that was added, removed, or changed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct DeclarationDiff {
+  /// The kind of change.
+  pub kind: DiffKind,
+  /// The name of the property, including any vendor prefix.
+  pub property: String,
+  /// The minified value in the old style sheet, including `!important`, unless it was added.
+  pub old_value: Option<String>,
+  /// The minified value in the new style sheet, including `!important`, unless it was removed.
+  pub new_value: Option<String>,
+}
+
+/// A kind of change in a [StyleSheetDiff](StyleSheetDiff).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(rename_all = "lowercase")
+)]
+pub enum DiffKind {
+  /// The rule or declaration was added.
+  Added,
+  /// The rule or declaration was removed.
+  Removed,
+  /// The rule or declaration was changed.
+  Changed,
+}
+
+/// A flattened rule.
+pub(crate) struct DiffEntry {
+  path: Vec<String>,
+  loc: Option<Location>,
+  declarations: Vec<(String, String)>,
+}
+
+pub(crate) fn flatten<T: ToCss + Clone>(
+  rules: &CssRuleList<T>,
+  path: &mut Vec<String>,
+  entries: &mut Vec<DiffEntry>,
+) -> Result<(), PrinterError> {
+  for rule in &rules.0 {
+    let style = match rule {
+      CssRule::Style(style) => Some(style),
+      CssRule::Nesting(nesting) => Some(&nesting.style),
+      _ => None,
+    };
+
+    if let Some(style) = style {
+      path.push(style.selectors.to_css_string(minify())?);
+      entries.push(DiffEntry {
+        path: path.clone(),
+        loc: Some(style.loc),
+        declarations: declarations(&style.declarations)?,
+      });
+      flatten(&style.rules, path, entries)?;
+      path.pop();
+      continue;
+    }
+
+    // Group rules are flattened, using the serialization of an empty copy of the rule as the prelude.
+    let mut empty = rule.clone();
+    let children = match (&mut empty, rule) {
+      (CssRule::Media(empty), CssRule::Media(media)) => Some((&mut empty.rules, &media.rules)),
+      (CssRule::Supports(empty), CssRule::Supports(supports)) => Some((&mut empty.rules, &supports.rules)),
+      (CssRule::MozDocument(empty), CssRule::MozDocument(document)) => Some((&mut empty.rules, &document.rules)),
+      (CssRule::LayerBlock(empty), CssRule::LayerBlock(layer)) => Some((&mut empty.rules, &layer.rules)),
+      (CssRule::Container(empty), CssRule::Container(container)) => Some((&mut empty.rules, &container.rules)),
+      (CssRule::Scope(empty), CssRule::Scope(scope)) => Some((&mut empty.rules, &scope.rules)),
+      (CssRule::StartingStyle(empty), CssRule::StartingStyle(starting_style)) => {
+        Some((&mut empty.rules, &starting_style.rules))
+      }
+      _ => None,
+    };
+
+    match children {
+      Some((empty_rules, rules)) => {
+        empty_rules.0.clear();
+        let prelude = empty.to_css_string(PrinterOptions::default())?;
+        let prelude = prelude.rfind('{').map_or(&*prelude, |index| &prelude[..index]).trim_end();
+        path.push(prelude.into());
+        flatten(rules, path, entries)?;
+        path.pop();
+      }
+      None => {
+        let contents = rule.to_css_string(minify())?;
+        if contents.is_empty() {
+          continue;
+        }
+        path.push(contents);
+        entries.push(DiffEntry {
+          path: path.clone(),
+          loc: rule.loc(),
+          declarations: Vec::new(),
+        });
+        path.pop();
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn minify() -> PrinterOptions<'static> {
+  PrinterOptions {
+    minify: true,
+    ..PrinterOptions::default()
+  }
+}
+
+fn declarations(declarations: &DeclarationBlock) -> Result<Vec<(String, String)>, PrinterError> {
+  let mut res = Vec::new();
+  for (property, important) in declarations.iter() {
+    let mut value = property.value_to_css_string(minify())?;
+    if important {
+      value.push_str("!important");
+    }
+    res.push((property.property_id().to_css_string(minify())?, value));
+  }
+  Ok(res)
+}
+
+pub(crate) fn diff(old: Vec<DiffEntry>, new: Vec<DiffEntry>) -> StyleSheetDiff {
+  let mut matched = vec![false; old.len()];
+  let mut rules = Vec::new();
+
+  for entry in &new {
+    // Rules with the same path are matched in order.
+    let index = old
+      .iter()
+      .enumerate()
+      .position(|(index, old)| !matched[index] && old.path == entry.path);
+
+    match index {
+      Some(index) => {
+        matched[index] = true;
+        let declarations = diff_declarations(&old[index].declarations, &entry.declarations);
+        if !declarations.is_empty() {
+          rules.push(RuleDiff {
+            kind: DiffKind::Changed,
+            path: entry.path.clone(),
+            old_loc: old[index].loc,
+            new_loc: entry.loc,
+            declarations,
+          });
+        }
+      }
+      None => rules.push(RuleDiff {
+        kind: DiffKind::Added,
+        path: entry.path.clone(),
+        old_loc: None,
+        new_loc: entry.loc,
+        declarations: diff_declarations(&[], &entry.declarations),
+      }),
+    }
+  }
+
+  for (entry, matched) in old.iter().zip(matched) {
+    if !matched {
+      rules.push(RuleDiff {
+        kind: DiffKind::Removed,
+        path: entry.path.clone(),
+        old_loc: entry.loc,
+        new_loc: None,
+        declarations: diff_declarations(&entry.declarations, &[]),
+      });
+    }
+  }
+
+  StyleSheetDiff { rules }
+}
+
+fn diff_declarations(old: &[(String, String)], new: &[(String, String)]) -> Vec<DeclarationDiff> {
+  let mut matched = vec![false; old.len()];
+  let mut res = Vec::new();
+
+  for (property, value) in new {
+    // Declarations of the same property, e.g. fallbacks, are matched in order.
+    let index = old
+      .iter()
+      .enumerate()
+      .position(|(index, (old_property, _))| !matched[index] && old_property == property);
+
+    match index {
+      Some(index) => {
+        matched[index] = true;
+        if old[index].1 != *value {
+          res.push(DeclarationDiff {
+            kind: DiffKind::Changed,
+            property: property.clone(),
+            old_value: Some(old[index].1.clone()),
+            new_value: Some(value.clone()),
+          });
+        }
+      }
+      None => res.push(DeclarationDiff {
+        kind: DiffKind::Added,
+        property: property.clone(),
+        old_value: None,
+        new_value: Some(value.clone()),
+      }),
+    }
+  }
+
+  for ((property, value), matched) in old.iter().zip(matched) {
+    if !matched {
+      res.push(DeclarationDiff {
+        kind: DiffKind::Removed,
+        property: property.clone(),
+        old_value: Some(value.clone()),
+        new_value: None,
+      });
+    }
+  }
+
+  res
+}
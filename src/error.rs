@@ -2,13 +2,14 @@
 
 use crate::properties::custom::Token;
 use crate::rules::Location;
-#[cfg(feature = "into_owned")]
-use static_self::IntoOwned;
+use crate::targets::UnsupportedBrowser;
 use crate::values::string::CowArcStr;
 use cssparser::{BasicParseErrorKind, ParseError, ParseErrorKind};
 use parcel_selectors::parser::SelectorParseErrorKind;
 #[cfg(any(feature = "serde", feature = "nodejs"))]
 use serde::Serialize;
+#[cfg(feature = "into_owned")]
+use static_self::IntoOwned;
 use std::fmt;
 
 /// An error with a source location.
@@ -88,6 +89,14 @@ pub enum ParserError<'i> {
   InvalidNesting,
   /// The @nest rule is deprecated.
   DeprecatedNestRule,
+  /// A declaration is overridden by a later declaration of the same property.
+  OverriddenDeclaration(CowArcStr<'i>),
+  /// An unknown property was encountered.
+  UnknownProperty(CowArcStr<'i>),
+  /// A property value was invalid, and could only be parsed as unparsed tokens.
+  InvalidPropertyValue(CowArcStr<'i>),
+  /// The fallback of a `var()` reference did not match the syntax of the registered custom property.
+  InvalidVariableFallback(CowArcStr<'i>),
   /// An invalid selector in an `@page` rule.
   InvalidPageSelector,
   /// An invalid value was encountered.
@@ -104,6 +113,10 @@ pub enum ParserError<'i> {
   UnexpectedToken(#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(skip))] Token<'i>),
   /// Maximum nesting depth was reached.
   MaximumNestingDepth,
+  /// A CSS modules `:global` or `:local` block contained something other than nested style rules.
+  InvalidCssModulesBlock,
+  /// An ICSS `:import` or `:export` block was encountered outside the top level of a stylesheet.
+  UnexpectedIcssBlock,
 }
 
 impl<'i> fmt::Display for ParserError<'i> {
@@ -118,6 +131,10 @@ impl<'i> fmt::Display for ParserError<'i> {
       InvalidMediaQuery => write!(f, "Invalid media query"),
       InvalidNesting => write!(f, "Invalid nesting"),
       DeprecatedNestRule => write!(f, "The @nest rule is deprecated"),
+      OverriddenDeclaration(name) => write!(f, "Declaration of '{}' is overridden by a later declaration", name),
+      UnknownProperty(name) => write!(f, "Unknown property: {}", name),
+      InvalidPropertyValue(name) => write!(f, "Invalid value for property: {}", name),
+      InvalidVariableFallback(name) => write!(f, "Invalid fallback for registered custom property: {}", name),
       InvalidPageSelector => write!(f, "Invalid page selector"),
       InvalidValue => write!(f, "Invalid value"),
       QualifiedRuleInvalid => write!(f, "Invalid qualified rule"),
@@ -132,6 +149,14 @@ impl<'i> fmt::Display for ParserError<'i> {
       ),
       UnexpectedToken(token) => write!(f, "Unexpected token {:?}", token),
       MaximumNestingDepth => write!(f, "Overflowed the maximum nesting depth"),
+      InvalidCssModulesBlock => write!(
+        f,
+        "A :global or :local block may only contain nested style rules, e.g. :global {{ .foo {{ ... }} }}"
+      ),
+      UnexpectedIcssBlock => write!(
+        f,
+        ":import and :export blocks are only allowed at the top level of a stylesheet"
+      ),
     }
   }
 }
@@ -232,6 +257,12 @@ pub enum SelectorError<'i> {
   ),
   /// An unsupported pseudo class or pseudo element was encountered.
   UnsupportedPseudoClassOrElement(CowArcStr<'i>),
+  /// A CSS modules `:global` or `:local` pseudo class without arguments was not followed by a selector.
+  DanglingCssModulesPseudoClass,
+  /// A CSS modules `:global` or `:local` pseudo class without arguments was used within another pseudo class.
+  NestedCssModulesPseudoClass,
+  /// A selector in a CSS module does not contain a local class or id, and the `pure` option is enabled.
+  ImpureCssModulesSelector,
 }
 
 impl<'i> fmt::Display for SelectorError<'i> {
@@ -257,6 +288,9 @@ impl<'i> fmt::Display for SelectorError<'i> {
       UnexpectedIdent(name) => write!(f, "Unexpected identifier: {}", name),
       UnexpectedTokenInAttributeSelector(token) => write!(f, "Unexpected token in attribute selector: {:?}", token),
       UnsupportedPseudoClassOrElement(name) => write!(f, "Unsupported pseudo class or element: {}", name),
+      DanglingCssModulesPseudoClass => write!(f, "Expected a selector after :global or :local"),
+      NestedCssModulesPseudoClass => write!(f, ":global and :local without arguments are only allowed at the top level of a selector, not within other pseudo classes"),
+      ImpureCssModulesSelector => write!(f, "Selector is not pure. Pure selectors must contain at least one local class or id"),
     }
   }
 }
@@ -337,6 +371,20 @@ pub enum MinifyErrorKind {
     /// The source location of the `@custom-media` rule with unsupported boolean logic.
     custom_media_loc: Location,
   },
+  /// A feature is not supported by the browser targets, and cannot be compiled.
+  UnsupportedFeature {
+    /// The name of the unsupported feature, e.g. `:has()`.
+    feature: String,
+    /// A unique id for the feature, e.g. `has-selector`, for use by tools.
+    id: String,
+    /// The browser targets that do not support the feature.
+    browsers: Vec<UnsupportedBrowser>,
+  },
+  /// The value of a custom property to inline could not be parsed.
+  InvalidCustomPropertyValue {
+    /// The name of the custom property.
+    name: String,
+  },
 }
 
 impl fmt::Display for MinifyErrorKind {
@@ -349,6 +397,20 @@ impl fmt::Display for MinifyErrorKind {
         f,
         "Boolean logic with media types in @custom-media rules is not supported by Lightning CSS"
       ),
+      UnsupportedFeature { feature, browsers, .. } => {
+        write!(f, "{} is not supported by the browser targets: ", feature)?;
+        for (i, browser) in browsers.iter().enumerate() {
+          if i > 0 {
+            write!(f, ", ")?;
+          }
+          write!(f, "{} {}", browser.browser, browser.version)?;
+          if let Some(version) = &browser.supported_since {
+            write!(f, " (supported since {})", version)?;
+          }
+        }
+        Ok(())
+      }
+      InvalidCustomPropertyValue { name } => write!(f, "Invalid value for custom property {}", name),
     }
   }
 }
@@ -364,24 +426,37 @@ impl MinifyErrorKind {
 /// A printer error.
 pub type PrinterError = Error<PrinterErrorKind>;
 
-/// A printer error type.
-#[derive(Debug, PartialEq)]
-#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
-#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(tag = "type"))]
-pub enum PrinterErrorKind {
-  /// An ambiguous relative `url()` was encountered in a custom property declaration.
-  AmbiguousUrlInCustomProperty {
-    /// The ambiguous URL.
-    url: String,
-  },
-  /// A [std::fmt::Error](std::fmt::Error) was encountered in the underlying destination.
-  FmtError,
-  /// The CSS modules `composes` property cannot be used within nested rules.
-  InvalidComposesNesting,
-  /// The CSS modules `composes` property cannot be used with a simple class selector.
-  InvalidComposesSelector,
-  /// The CSS modules pattern must end with `[local]` for use in CSS grid.
-  InvalidCssModulesPatternInGrid,
+pub use printer_error_kind::PrinterErrorKind;
+
+// The derived impls reference the deprecated variant, so the enum lives in its
+// own module to keep the `allow(deprecated)` scoped to them.
+#[allow(deprecated)]
+mod printer_error_kind {
+  #[cfg(any(feature = "serde", feature = "nodejs"))]
+  use serde::Serialize;
+
+  /// A printer error type.
+  #[derive(Debug, PartialEq)]
+  #[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+  #[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(tag = "type"))]
+  pub enum PrinterErrorKind {
+    /// An ambiguous relative `url()` was encountered in a custom property declaration.
+    AmbiguousUrlInCustomProperty {
+      /// The ambiguous URL.
+      url: String,
+    },
+    /// A [std::fmt::Error](std::fmt::Error) was encountered in the underlying destination.
+    FmtError,
+    /// The CSS modules `composes` property cannot be used within nested rules.
+    InvalidComposesNesting,
+    /// The CSS modules `composes` property cannot be used with a simple class selector.
+    InvalidComposesSelector,
+    /// The CSS modules pattern must end with `[local]` for use in CSS grid.
+    #[deprecated(
+      note = "grid line names are now scoped with any CSS modules pattern, so this error is no longer emitted"
+    )]
+    InvalidCssModulesPatternInGrid,
+  }
 }
 
 impl From<fmt::Error> for PrinterError {
@@ -401,6 +476,7 @@ impl fmt::Display for PrinterErrorKind {
       FmtError => write!(f, "Printer error"),
       InvalidComposesNesting => write!(f, "The `composes` property cannot be used within nested rules"),
       InvalidComposesSelector => write!(f, "The `composes` property cannot be used with a simple class selector"),
+      #[allow(deprecated)]
       InvalidCssModulesPatternInGrid => write!(f, "The CSS modules `pattern` config must end with `[local]` for use in CSS grid line names."),
     }
   }
@@ -413,3 +489,58 @@ impl PrinterErrorKind {
     self.to_string()
   }
 }
+
+/// An error that occurs when converting a value to an absolute unit.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(tag = "type"))]
+pub enum UnitConversionError {
+  /// The unit depends on context that was not provided, e.g. the viewport size for `vw`.
+  MissingContext {
+    /// The unit that could not be resolved.
+    unit: String,
+  },
+  /// A percentage was encountered, but no percentage basis was provided.
+  MissingPercentageBasis,
+  /// A math function that cannot be evaluated was encountered.
+  UnsupportedMathFunction,
+}
+
+impl fmt::Display for UnitConversionError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    use UnitConversionError::*;
+    match self {
+      MissingContext { unit } => write!(f, "Cannot convert {} to px without more context", unit),
+      MissingPercentageBasis => write!(f, "Cannot convert a percentage to px without a percentage basis"),
+      UnsupportedMathFunction => write!(f, "Cannot evaluate math function"),
+    }
+  }
+}
+
+impl std::error::Error for UnitConversionError {}
+
+/// An error that occurs when building a [GridTemplateModel](crate::properties::grid::GridTemplateModel).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(tag = "type"))]
+pub enum GridTemplateError {
+  /// A track list uses an `auto-fill` or `auto-fit` repetition, whose number of tracks depends on layout.
+  AutoRepeat,
+  /// A named grid area does not form a single filled-in rectangle.
+  NonRectangularArea {
+    /// The name of the grid area.
+    name: String,
+  },
+}
+
+impl fmt::Display for GridTemplateError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    use GridTemplateError::*;
+    match self {
+      AutoRepeat => write!(f, "Cannot expand an auto-fill or auto-fit repetition without layout"),
+      NonRectangularArea { name } => write!(f, "Grid area {} is not rectangular", name),
+    }
+  }
+}
+
+impl std::error::Error for GridTemplateError {}
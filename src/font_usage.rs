@@ -0,0 +1,232 @@
+//! Font usage analysis.
+//!
+//! [StyleSheet::font_usage](crate::stylesheet::StyleSheet::font_usage) cross-references the font
+//! families referenced by `font-family` and `font` declarations with the `@font-face` rules that
+//! provide them. It reports the `@font-face` rules for each family along with the unicode ranges
+//! they cover, and the `@font-face` rules for families that are never referenced. This is useful
+//! for font subsetting, and for removing unused fonts.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::font::FontFamily;
+use crate::properties::Property;
+use crate::rules::font_face::{FontFaceProperty, FontFaceRule, UnicodeRange};
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::traits::ToCss;
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::convert::Infallible;
+
+/// A report of the fonts used by a style sheet, as returned by
+/// [StyleSheet::font_usage](crate::stylesheet::StyleSheet::font_usage).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct FontUsageReport {
+  /// The font families referenced by declarations, in source order. Generic families such as
+  /// `sans-serif` are not included.
+  pub families: Vec<FontFamilyUsage>,
+  /// `@font-face` rules for font families that are never referenced.
+  pub unused_font_faces: Vec<FontFaceLocation>,
+}
+
+/// A font family referenced by a style sheet.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct FontFamilyUsage {
+  /// The name of the font family, as first written.
+  pub family: String,
+  /// The locations of the rules that reference the font family.
+  pub references: Vec<Location>,
+  /// The locations of the `@font-face` rules that provide the font family. If empty, the font
+  /// family must be installed locally or provided by another style sheet.
+  pub font_faces: Vec<Location>,
+  /// The unicode ranges covered by the `@font-face` rules that provide the font family, merged and
+  /// serialized, e.g. `U+0-FF`. Empty if there are no `@font-face` rules for the font family.
+  pub unicode_range: Vec<String>,
+}
+
+/// An `@font-face` rule.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct FontFaceLocation {
+  /// The font family provided by the `@font-face` rule.
+  pub family: String,
+  /// The location of the `@font-face` rule.
+  pub loc: Location,
+}
+
+struct FontFace {
+  family: String,
+  loc: Location,
+  unicode_range: Vec<(u32, u32)>,
+}
+
+#[derive(Default)]
+pub(crate) struct FontUsage {
+  font_faces: Vec<FontFace>,
+  families: Vec<FontFamilyUsage>,
+}
+
+impl FontUsage {
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>) {
+    for rule in &rules.0 {
+      self.add_rule(rule);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>) {
+    match rule {
+      CssRule::FontFace(font_face) => self.add_font_face(font_face),
+      CssRule::Style(style) => {
+        self.add_declarations(&style.declarations, style.loc);
+        self.add_rules(&style.rules);
+      }
+      CssRule::Nesting(nesting) => {
+        self.add_declarations(&nesting.style.declarations, nesting.loc);
+        self.add_rules(&nesting.style.rules);
+      }
+      CssRule::Media(media) => self.add_rules(&media.rules),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules),
+      CssRule::Container(container) => self.add_rules(&container.rules),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          self.add_declarations(&keyframe.declarations, keyframes.loc);
+        }
+      }
+      CssRule::Page(page) => {
+        self.add_declarations(&page.declarations, page.loc);
+        for margin in &page.rules {
+          self.add_declarations(&margin.declarations, margin.loc);
+        }
+      }
+      _ => {}
+    }
+  }
+
+  fn add_font_face(&mut self, rule: &FontFaceRule) {
+    let mut family = None;
+    // A font face without a unicode-range covers all code points.
+    let mut unicode_range = vec![(0, 0x10FFFF)];
+    for property in &rule.properties {
+      match property {
+        FontFaceProperty::FontFamily(FontFamily::FamilyName(name)) => family = Some(name.to_string()),
+        FontFaceProperty::UnicodeRange(ranges) => {
+          unicode_range = ranges.iter().map(|range| (range.start, range.end)).collect()
+        }
+        _ => {}
+      }
+    }
+
+    if let Some(family) = family {
+      self.font_faces.push(FontFace {
+        family,
+        loc: rule.loc,
+        unicode_range,
+      });
+    }
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock, loc: Location) {
+    // Visit a copy of the declarations, since visitors require mutable access.
+    let mut declarations = declarations.clone();
+    let _ = declarations.visit(&mut FontFamilyVisitor { usage: self, loc });
+  }
+
+  fn add_reference(&mut self, family: &str, loc: Location) {
+    // Font family names are matched case-insensitively.
+    if let Some(usage) = self.families.iter_mut().find(|usage| usage.family.eq_ignore_ascii_case(family)) {
+      if !usage.references.contains(&loc) {
+        usage.references.push(loc);
+      }
+    } else {
+      self.families.push(FontFamilyUsage {
+        family: family.into(),
+        references: vec![loc],
+        font_faces: Vec::new(),
+        unicode_range: Vec::new(),
+      });
+    }
+  }
+
+  pub(crate) fn report(mut self) -> FontUsageReport {
+    for usage in &mut self.families {
+      let mut ranges = Vec::new();
+      for font_face in &self.font_faces {
+        if font_face.family.eq_ignore_ascii_case(&usage.family) {
+          usage.font_faces.push(font_face.loc);
+          ranges.extend_from_slice(&font_face.unicode_range);
+        }
+      }
+
+      usage.unicode_range = merge_ranges(ranges)
+        .into_iter()
+        .filter_map(|(start, end)| UnicodeRange { start, end }.to_css_string(Default::default()).ok())
+        .collect();
+    }
+
+    let families = &self.families;
+    let unused_font_faces = self
+      .font_faces
+      .iter()
+      .filter(|font_face| {
+        !families
+          .iter()
+          .any(|usage| usage.family.eq_ignore_ascii_case(&font_face.family))
+      })
+      .map(|font_face| FontFaceLocation {
+        family: font_face.family.clone(),
+        loc: font_face.loc,
+      })
+      .collect();
+
+    FontUsageReport {
+      families: self.families,
+      unused_font_faces,
+    }
+  }
+}
+
+/// Sorts and merges overlapping and adjacent ranges.
+fn merge_ranges(mut ranges: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+  ranges.sort_unstable();
+  let mut merged: Vec<(u32, u32)> = Vec::new();
+  for (start, end) in ranges {
+    match merged.last_mut() {
+      Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+      _ => merged.push((start, end)),
+    }
+  }
+  merged
+}
+
+struct FontFamilyVisitor<'a> {
+  usage: &'a mut FontUsage,
+  loc: Location,
+}
+
+impl<'a, 'i> Visitor<'i> for FontFamilyVisitor<'a> {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    let families = match property {
+      Property::FontFamily(families) => &*families,
+      Property::Font(font) => &font.family,
+      _ => return Ok(()),
+    };
+
+    for family in families {
+      if let FontFamily::FamilyName(name) = family {
+        self.usage.add_reference(name, self.loc);
+      }
+    }
+    Ok(())
+  }
+}
@@ -17,25 +17,61 @@
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+#[cfg(feature = "archive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "archive")))]
+pub mod archive;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod browser_support;
+pub mod builder;
 #[cfg(feature = "bundler")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bundler")))]
 pub mod bundler;
+#[cfg(feature = "binary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "binary")))]
+pub mod cache;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod color_usage;
 mod compat;
 mod context;
 pub mod css_modules;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod custom_properties;
 pub mod declaration;
 pub mod dependencies;
+pub mod diff;
 pub mod error;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod font_usage;
+pub mod interpolate;
 mod logical;
 mod macros;
+pub mod matching;
 pub mod media_query;
 mod parser;
+#[cfg(feature = "plugin")]
+#[cfg_attr(docsrs, doc(cfg(feature = "plugin")))]
+pub mod plugin;
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub mod postcss;
 mod prefixes;
 pub mod printer;
 pub mod properties;
+pub mod query;
 pub mod rules;
 pub mod selector;
+pub mod selector_lints;
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub mod stats;
 pub mod stylesheet;
+#[cfg(feature = "substitute_variables")]
+#[cfg_attr(docsrs, doc(cfg(feature = "substitute_variables")))]
+pub mod substitution;
 pub mod targets;
 pub mod traits;
 pub mod values;
@@ -57,14 +93,16 @@ mod tests {
   use crate::properties::Property;
   use crate::rules::CssRule;
   use crate::rules::Location;
+  use crate::selector::DirFallback;
   use crate::stylesheet::*;
-  use crate::targets::{Browsers, Features, Targets};
+  use crate::targets::{Browsers, FeatureToggleError, Features, Targets, UnsupportedBrowser};
   use crate::traits::{Parse, ToCss};
   use crate::values::color::CssColor;
-  use crate::vendor_prefix::VendorPrefix;
+  use crate::vendor_prefix::{PrefixPolicy, PrefixRule, VendorPrefix};
   use cssparser::SourceLocation;
   use indoc::indoc;
   use std::collections::HashMap;
+  use std::sync::{Arc, RwLock};
 
   fn test(source: &str, expected: &str) {
     test_with_options(source, expected, ParserOptions::default())
@@ -93,20 +131,41 @@ mod tests {
     assert_eq!(res.code, expected);
   }
 
-  fn prefix_test(source: &str, expected: &str, targets: Browsers) {
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
+  fn prefix_test(source: &str, expected: &str, targets: impl Into<Targets>) {
+    prefix_test_with_options(
+      source,
+      expected,
+      MinifyOptions {
         targets: targets.into(),
         ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet
-      .to_css(PrinterOptions {
-        targets: targets.into(),
+      },
+    )
+  }
+
+  fn prefix_test_with_options(source: &str, expected: &str, options: MinifyOptions) {
+    let targets = options.targets;
+    options_test(
+      source,
+      expected,
+      ParserOptions::default(),
+      options,
+      PrinterOptions {
+        targets,
         ..PrinterOptions::default()
-      })
-      .unwrap();
+      },
+    )
+  }
+
+  fn options_test<'i, 'o>(
+    source: &'i str,
+    expected: &'i str,
+    parser_options: ParserOptions<'o, 'i>,
+    minify_options: MinifyOptions,
+    printer_options: PrinterOptions,
+  ) {
+    let mut stylesheet = StyleSheet::parse(&source, parser_options).unwrap();
+    stylesheet.minify(minify_options).unwrap();
+    let res = stylesheet.to_css(printer_options).unwrap();
     assert_eq!(res.code, expected);
   }
 
@@ -3226,6 +3285,74 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_logical_dir_fallback() {
+    prefix_test_with_options(
+      r#"
+      .foo {
+        margin-inline-start: 2px;
+        margin-inline-end: 4px;
+      }
+    "#,
+      indoc! {r#"
+      [dir="ltr"] .foo {
+        margin-left: 2px;
+        margin-right: 4px;
+      }
+
+      [dir="rtl"] .foo {
+        margin-left: 4px;
+        margin-right: 2px;
+      }
+    "#
+      },
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+
+    prefix_test_with_options(
+      r#"
+      .foo, .bar::after {
+        padding-inline-start: 2px;
+      }
+    "#,
+      indoc! {r#"
+      [dir="ltr"] .foo {
+        padding-left: 2px;
+      }
+
+      [dir="ltr"] .bar:after {
+        padding-left: 2px;
+      }
+
+      [dir="rtl"] .foo {
+        padding-right: 2px;
+      }
+
+      [dir="rtl"] .bar:after {
+        padding-right: 2px;
+      }
+    "#
+      },
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+  }
+
   #[test]
   fn test_length() {
     for prop in &[
@@ -3928,6 +4055,97 @@ mod tests {
     minify_test(".foo { aspect-ratio: 2 / 3 auto }", ".foo{aspect-ratio:auto 2/3}");
   }
 
+  #[test]
+  fn test_viewport_unit_fallbacks() {
+    prefix_test(
+      r#"
+      .foo {
+        height: 100dvh;
+        min-height: calc(100svh - 60px);
+        max-width: 50lvw;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        max-width: 50vw;
+        max-width: 50lvw;
+        height: 100vh;
+        height: 100dvh;
+        min-height: calc(100vh - 60px);
+        min-height: calc(100svh - 60px);
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        height: 100vh;
+        height: 100dvh;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        height: 100vh;
+        height: 100dvh;
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        height: 100dvh;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        height: 100dvh;
+      }
+    "#},
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test_with_options(
+      r#"
+      .foo {
+        height: 100dvh;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        height: 100vh;
+      }
+
+      @supports (height: 100dvh) {
+        .foo {
+          height: 100dvh;
+        }
+      }
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(14 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
+    );
+  }
+
   #[test]
   pub fn test_background() {
     test(
@@ -5554,6 +5772,76 @@ mod tests {
         ..Browsers::default()
       },
     );
+    prefix_test(
+      r#"
+      .foo {
+        place-content: space-between flex-end;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        align-content: space-between;
+        justify-content: flex-end;
+      }
+    "#},
+      Targets {
+        browsers: Some(Browsers {
+          chrome: Some(60 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::PlaceShorthands,
+        exclude: Features::empty(),
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        align-content: space-between;
+        justify-content: flex-end;
+        align-self: center;
+        justify-self: end;
+        align-items: start;
+        justify-items: stretch;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        align-content: space-between;
+        justify-content: flex-end;
+        align-self: center;
+        justify-self: end;
+        align-items: start;
+        justify-items: stretch;
+      }
+    "#},
+      Targets {
+        browsers: None,
+        include: Features::PlaceShorthands,
+        exclude: Features::empty(),
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        place-self: center end;
+        place-items: start stretch;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        place-self: center end;
+        place-items: start stretch;
+      }
+    "#},
+      Targets {
+        browsers: Some(Browsers {
+          chrome: Some(30 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::empty(),
+        exclude: Features::PlaceShorthands,
+      },
+    );
     prefix_test(
       r#"
       .foo {
@@ -5862,7 +6150,7 @@ mod tests {
         line-height: 1.2em;
       }
     "#,
-      indoc! {".foo{font:italic small-caps 700 125% 12px/1.2em Helvetica,Times New Roman,sans-serif}"
+      indoc! {".foo{font:italic small-caps 700 expanded 12px/1.2em Helvetica,Times New Roman,sans-serif}"
       },
     );
 
@@ -6026,17 +6314,17 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        font-family: ui-sans-serif, system-ui, -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, "Helvetica Neue", Arial, "Noto Sans", sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol", "Noto Color Emoji";
+        font-family: ui-monospace, monospace;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-family: ui-sans-serif, system-ui, -apple-system, BlinkMacSystemFont, Segoe UI, Roboto, Noto Sans, Ubuntu, Cantarell, Helvetica Neue, Arial, sans-serif, Apple Color Emoji, Segoe UI Emoji, Segoe UI Symbol, Noto Color Emoji;
+        font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, Roboto Mono, Ubuntu Mono, Liberation Mono, Courier New, monospace;
       }
     "#
       },
       Browsers {
-        firefox: Some(91 << 16),
+        chrome: Some(100 << 16),
         ..Browsers::default()
       },
     );
@@ -6044,37 +6332,36 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        font-size: 22px;
-        font-size: max(2cqw, 22px);
+        font: 16px ui-serif, serif;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-size: 22px;
-        font-size: max(2cqw, 22px);
+        font: 16px ui-serif, Iowan Old Style, Georgia, Cambria, Noto Serif, Times New Roman, serif;
       }
     "#
       },
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
+      Targets {
+        browsers: None,
+        include: Features::ExtendedSystemFonts,
+        exclude: Features::empty(),
       },
     );
+
     prefix_test(
       r#"
       .foo {
-        font-size: 22px;
-        font-size: max(2cqw, 22px);
+        font-family: ui-sans-serif, system-ui, sans-serif;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-size: max(2cqw, 22px);
+        font-family: ui-sans-serif, -apple-system, BlinkMacSystemFont, Segoe UI, Roboto, Noto Sans, Ubuntu, Cantarell, Helvetica Neue, system-ui, sans-serif;
       }
     "#
       },
       Browsers {
-        safari: Some(16 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
@@ -6082,65 +6369,179 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        font-size: 22px;
-        font-size: xxx-large;
+        font-family: ui-rounded;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-size: 22px;
-        font-size: xxx-large;
+        font-family: ui-rounded, SF Pro Rounded, Hiragino Maru Gothic ProN, Arial Rounded MT, Quicksand, Comfortaa, Manjari;
       }
     "#
       },
       Browsers {
-        chrome: Some(70 << 16),
+        chrome: Some(100 << 16),
         ..Browsers::default()
       },
     );
+
     prefix_test(
       r#"
       .foo {
-        font-size: 22px;
-        font-size: xxx-large;
+        font-family: ui-rounded;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-size: xxx-large;
+        font-family: ui-rounded;
       }
     "#
       },
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
+      Targets {
+        browsers: Some(Browsers {
+          chrome: Some(100 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::empty(),
+        exclude: Features::ExtendedSystemFonts,
       },
     );
 
     prefix_test(
       r#"
       .foo {
-        font-weight: 700;
-        font-weight: 789;
+        font-family: ui-rounded;
       }
     "#,
       indoc! {r#"
       .foo {
-        font-weight: 700;
-        font-weight: 789;
+        font-family: ui-rounded;
       }
     "#
       },
       Browsers {
-        chrome: Some(60 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
+
     prefix_test(
       r#"
       .foo {
-        font-weight: 700;
-        font-weight: 789;
+        font-family: ui-sans-serif, system-ui, -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, "Helvetica Neue", Arial, "Noto Sans", sans-serif, "Apple Color Emoji", "Segoe UI Emoji", "Segoe UI Symbol", "Noto Color Emoji";
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-family: ui-sans-serif, -apple-system, BlinkMacSystemFont, Segoe UI, Roboto, Noto Sans, Ubuntu, Cantarell, Helvetica Neue, system-ui, Arial, sans-serif, Apple Color Emoji, Segoe UI Emoji, Segoe UI Symbol, Noto Color Emoji;
+      }
+    "#
+      },
+      Browsers {
+        firefox: Some(91 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        font-size: 22px;
+        font-size: max(2cqw, 22px);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-size: 22px;
+        font-size: max(2cqw, 22px);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        font-size: 22px;
+        font-size: max(2cqw, 22px);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-size: max(2cqw, 22px);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        font-size: 22px;
+        font-size: xxx-large;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-size: 22px;
+        font-size: xxx-large;
+      }
+    "#
+      },
+      Browsers {
+        chrome: Some(70 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        font-size: 22px;
+        font-size: xxx-large;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-size: xxx-large;
+      }
+    "#
+      },
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        font-weight: 700;
+        font-weight: 789;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        font-weight: 700;
+        font-weight: 789;
+      }
+    "#
+      },
+      Browsers {
+        chrome: Some(60 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        font-weight: 700;
+        font-weight: 789;
       }
     "#,
       indoc! {r#"
@@ -6757,6 +7158,110 @@ mod tests {
       },
     );
 
+    let safari8 = Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
+    };
+    prefix_test_with_options(
+      "a:dir(rtl) {color:red}",
+      indoc! {r#"
+      [dir="rtl"] a {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ".foo > a:dir(ltr)::after {color:red}",
+      indoc! {r#"
+      [dir="ltr"] .foo > a:after {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ":dir(rtl) .foo {color:red}",
+      indoc! {r#"
+      [dir="rtl"] .foo {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ".foo :dir(rtl) {color:red}",
+      indoc! {r#"
+      [dir="rtl"] .foo * {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      "a:dir(rtl) {color:red}",
+      indoc! {r#"
+      a:dir(rtl) {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: Browsers {
+          firefox: Some(60 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      "a:dir(rtl):dir(ltr) {color:red}",
+      indoc! {r#"
+      a:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)):not(:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        color: red;
+      }
+
+      a:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)):not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::Attribute,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      "a:not(:dir(ltr)) {color:red}",
+      indoc! {r#"
+      a:not([dir="ltr"]) {
+        color: red;
+      }
+      "#},
+      MinifyOptions {
+        targets: safari8.into(),
+        dir_fallback: DirFallback::OwnAttribute,
+        ..MinifyOptions::default()
+      },
+    );
+
     minify_test(".foo::cue {color: red}", ".foo::cue{color:red}");
     minify_test(".foo::cue-region {color: red}", ".foo::cue-region{color:red}");
     minify_test(".foo::cue(b) {color: red}", ".foo::cue(b){color:red}");
@@ -7598,795 +8103,1305 @@ mod tests {
   }
 
   #[test]
-  fn test_trig() {
-    minify_test(".foo { width: calc(2px * pi); }", ".foo{width:6.28319px}");
-    minify_test(".foo { width: calc(2px / pi); }", ".foo{width:.63662px}");
-    // minify_test(
-    //   ".foo { width: calc(2px * infinity); }",
-    //   ".foo{width:calc(2px*infinity)}",
-    // );
-    // minify_test(
-    //   ".foo { width: calc(2px * -infinity); }",
-    //   ".foo{width:calc(2px*-infinity)}",
-    // );
-    minify_test(".foo { width: calc(100px * sin(45deg))", ".foo{width:70.7107px}");
-    minify_test(".foo { width: calc(100px * sin(.125turn))", ".foo{width:70.7107px}");
-    minify_test(
-      ".foo { width: calc(100px * sin(3.14159265 / 4))",
-      ".foo{width:70.7107px}",
+  fn test_math_fallback() {
+    let chrome70 = Browsers {
+      chrome: Some(70 << 16),
+      ..Browsers::default()
+    };
+    options_test(
+      ".foo { font-size: clamp(1rem, 2.5vw, 2rem) }",
+      indoc! {r#"
+        .foo {
+          font-size: 2.5vw;
+          font-size: max(1rem, min(2.5vw, 2rem));
+        }
+      "#},
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
+      },
     );
-    minify_test(".foo { width: calc(100px * sin(pi / 4))", ".foo{width:70.7107px}");
-    minify_test(
-      ".foo { width: calc(100px * sin(22deg + 23deg))",
-      ".foo{width:70.7107px}",
+    options_test(
+      ".foo { font-size: clamp(1rem, 2.5vw, 2rem) }",
+      indoc! {r#"
+        .foo {
+          font-size: 25px;
+          font-size: max(1rem, min(2.5vw, 2rem));
+        }
+      "#},
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions {
+          viewport_width: Some(1000.0),
+          ..MathFallbackOptions::default()
+        }),
+        ..PrinterOptions::default()
+      },
     );
-
-    minify_test(".foo { width: calc(2px * cos(45deg))", ".foo{width:1.41421px}");
-    minify_test(".foo { width: calc(2px * tan(45deg))", ".foo{width:2px}");
-
-    minify_test(".foo { rotate: asin(sin(45deg))", ".foo{rotate:45deg}");
-    minify_test(".foo { rotate: asin(1)", ".foo{rotate:90deg}");
-    minify_test(".foo { rotate: asin(-1)", ".foo{rotate:-90deg}");
-    minify_test(".foo { rotate: asin(0.5)", ".foo{rotate:30deg}");
-    minify_test(".foo { rotate: asin(45deg)", ".foo{rotate:asin(45deg)}"); // invalid
-    minify_test(".foo { rotate: asin(-20)", ".foo{rotate:asin(-20)}"); // evaluates to NaN
-    minify_test(".foo { width: asin(sin(45deg))", ".foo{width:asin(sin(45deg))}"); // invalid
-
-    minify_test(".foo { rotate: acos(cos(45deg))", ".foo{rotate:45deg}");
-    minify_test(".foo { rotate: acos(-1)", ".foo{rotate:180deg}");
-    minify_test(".foo { rotate: acos(0)", ".foo{rotate:90deg}");
-    minify_test(".foo { rotate: acos(1)", ".foo{rotate:none}");
-    minify_test(".foo { rotate: acos(45deg)", ".foo{rotate:acos(45deg)}"); // invalid
-    minify_test(".foo { rotate: acos(-20)", ".foo{rotate:acos(-20)}"); // evaluates to NaN
-
-    minify_test(".foo { rotate: atan(tan(45deg))", ".foo{rotate:45deg}");
-    minify_test(".foo { rotate: atan(1)", ".foo{rotate:45deg}");
-    minify_test(".foo { rotate: atan(0)", ".foo{rotate:none}");
-    minify_test(".foo { rotate: atan(45deg)", ".foo{rotate:atan(45deg)}"); // invalid
-
-    minify_test(".foo { rotate: atan2(1px, -1px)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1vw, -1vw)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1, -1)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1ms, -1ms)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1%, -1%)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1deg, -1deg)", ".foo{rotate:135deg}");
-    minify_test(".foo { rotate: atan2(1cm, 1mm)", ".foo{rotate:84.2894deg}");
-    minify_test(".foo { rotate: atan2(0, -1)", ".foo{rotate:180deg}");
-    minify_test(".foo { rotate: atan2(-1, 1)", ".foo{rotate:-45deg}");
-    // incompatible units
-    minify_test(".foo { rotate: atan2(1px, -1vw)", ".foo{rotate:atan2(1px,-1vw)}");
-  }
-
-  #[test]
-  fn test_exp() {
-    minify_test(".foo { width: hypot()", ".foo{width:hypot()}");
-    minify_test(".foo { width: hypot(1px)", ".foo{width:1px}");
-    minify_test(".foo { width: hypot(1px, 2px)", ".foo{width:2.23607px}");
-    minify_test(".foo { width: hypot(1px, 2px, 3px)", ".foo{width:3.74166px}");
-    minify_test(".foo { width: hypot(1px, 2vw)", ".foo{width:hypot(1px,2vw)}");
-    minify_test(".foo { width: hypot(1px, 2px, 3vw)", ".foo{width:hypot(1px,2px,3vw)}");
-    minify_test(".foo { width: calc(100px * hypot(3, 4))", ".foo{width:500px}");
-    minify_test(".foo { width: calc(1px * pow(2, sqrt(100))", ".foo{width:1024px}");
-    minify_test(".foo { width: calc(100px * pow(2, pow(2, 2)", ".foo{width:1600px}");
-    minify_test(".foo { width: calc(1px * log(1))", ".foo{width:0}");
-    minify_test(".foo { width: calc(1px * log(10, 10))", ".foo{width:1px}");
-    minify_test(".foo { width: calc(1px * exp(0))", ".foo{width:1px}");
-    minify_test(".foo { width: calc(1px * log(e))", ".foo{width:1px}");
-    minify_test(".foo { width: calc(1px * (e - exp(1)))", ".foo{width:0}");
-    minify_test(
-      ".foo { width: calc(1px * (exp(log(1) + exp(0)*2))",
-      ".foo{width:7.38906px}",
-    );
-  }
-
-  #[test]
-  fn test_sign() {
-    minify_test(".foo { width: abs(1px)", ".foo{width:1px}");
-    minify_test(".foo { width: abs(-1px)", ".foo{width:1px}");
-    minify_test(".foo { width: abs(1%)", ".foo{width:abs(1%)}"); // spec says percentages must be against resolved value
-
-    minify_test(".foo { width: calc(10px * sign(-1vw)", ".foo{width:-10px}");
-    minify_test(".foo { width: calc(10px * sign(1%)", ".foo{width:calc(10px*sign(1%))}");
-  }
-
-  #[test]
-  fn test_box_shadow() {
-    minify_test(
-      ".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4) }",
-      ".foo{box-shadow:64px 64px 12px 40px #0006}",
-    );
-    minify_test(
-      ".foo { box-shadow: 12px 12px 0px 8px rgba(0,0,0,0.4) inset }",
-      ".foo{box-shadow:inset 12px 12px 0 8px #0006}",
-    );
-    minify_test(
-      ".foo { box-shadow: inset 12px 12px 0px 8px rgba(0,0,0,0.4) }",
-      ".foo{box-shadow:inset 12px 12px 0 8px #0006}",
-    );
-    minify_test(
-      ".foo { box-shadow: 12px 12px 8px 0px rgba(0,0,0,0.4) }",
-      ".foo{box-shadow:12px 12px 8px #0006}",
-    );
-    minify_test(
-      ".foo { box-shadow: 12px 12px 0px 0px rgba(0,0,0,0.4) }",
-      ".foo{box-shadow:12px 12px #0006}",
-    );
-    minify_test(
-      ".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4), 12px 12px 0px 8px rgba(0,0,0,0.4) inset }",
-      ".foo{box-shadow:64px 64px 12px 40px #0006,inset 12px 12px 0 8px #0006}",
-    );
-
-    prefix_test(
-      ".foo { box-shadow: 12px 12px lab(40% 56.6 39) }",
-      indoc! { r#"
+    options_test(
+      ".foo { font-size: clamp(1rem, 2.5vw, 2rem) }",
+      indoc! {r#"
         .foo {
-          box-shadow: 12px 12px #b32323;
-          box-shadow: 12px 12px lab(40% 56.6 39);
+          font-size: 20px;
+          font-size: max(1rem, min(2.5vw, 2rem));
         }
       "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions {
+          viewport_width: Some(1000.0),
+          root_font_size: Some(10.0),
+          ..MathFallbackOptions::default()
+        }),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo { box-shadow: 12px 12px lab(40% 56.6 39) }",
-      indoc! { r#"
+    options_test(
+      ".foo { padding: clamp(1em, 2vw + 4px, 3em) }",
+      indoc! {r#"
         .foo {
-          -webkit-box-shadow: 12px 12px #b32323;
-          box-shadow: 12px 12px #b32323;
-          box-shadow: 12px 12px lab(40% 56.6 39);
+          padding: calc(2vw + 4px);
+          padding: max(1em, min(2vw + 4px, 3em));
         }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions {
+          viewport_width: Some(1000.0),
+          ..MathFallbackOptions::default()
+        }),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo { box-shadow: 12px 12px lab(40% 56.6 39), 12px 12px yellow }",
-      indoc! { r#"
+    options_test(
+      ".foo { width: min(50%, 500px) }",
+      indoc! {r#"
         .foo {
-          -webkit-box-shadow: 12px 12px #b32323, 12px 12px #ff0;
-          box-shadow: 12px 12px #b32323, 12px 12px #ff0;
-          box-shadow: 12px 12px lab(40% 56.6 39), 12px 12px #ff0;
+          width: 50%;
+          width: min(50%, 500px);
         }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo { -webkit-box-shadow: 12px 12px #0006 }",
-      indoc! { r#"
+    options_test(
+      ".foo { width: max(100vw - 20px, 300px) !important }",
+      indoc! {r#"
         .foo {
-          -webkit-box-shadow: 12px 12px rgba(0, 0, 0, .4);
+          width: calc(100vw - 20px) !important;
+          width: max(100vw - 20px, 300px) !important;
         }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo {
-        -webkit-box-shadow: 12px 12px #0006;
-        -moz-box-shadow: 12px 12px #0009;
-      }",
-      indoc! { r#"
+    options_test(
+      ".foo { margin: min(10px, 1vh) auto }",
+      indoc! {r#"
         .foo {
-          -webkit-box-shadow: 12px 12px rgba(0, 0, 0, .4);
-          -moz-box-shadow: 12px 12px rgba(0, 0, 0, .6);
+          margin: 10px auto;
+          margin: min(10px, 1vh) auto;
         }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo {
-        -webkit-box-shadow: 12px 12px #0006;
-        -moz-box-shadow: 12px 12px #0006;
-        box-shadow: 12px 12px #0006;
-      }",
-      indoc! { r#"
+    options_test(
+      ".foo { width: calc(100% - 20px) }",
+      indoc! {r#"
         .foo {
-          box-shadow: 12px 12px #0006;
+          width: calc(100% - 20px);
         }
       "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: chrome70.into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: chrome70.into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      ".foo { box-shadow: var(--foo) 12px lab(40% 56.6 39) }",
-      indoc! { r#"
+    options_test(
+      ".foo { font-size: clamp(1rem, 2.5vw, 2rem) }",
+      indoc! {r#"
         .foo {
-          box-shadow: var(--foo) 12px #b32323;
-        }
-
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            box-shadow: var(--foo) 12px lab(40% 56.6 39);
-          }
+          font-size: clamp(1rem, 2.5vw, 2rem);
         }
       "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      ParserOptions::default(),
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(100 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        targets: Browsers {
+          chrome: Some(100 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      r#"
-      .foo {
-        box-shadow: 0px 0px 22px red;
-        box-shadow: 0px 0px max(2cqw, 22px) red;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        box-shadow: 0 0 22px red;
-        box-shadow: 0 0 max(2cqw, 22px) red;
+    // The fallback is printed with the same printer state, e.g. for dependencies.
+    let safari10 = Browsers {
+      safari: Some(10 << 16),
+      ..Browsers::default()
+    };
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { background: url(a.png) 0 0 / min(10px, 5vw) no-repeat }",
+      ParserOptions {
+        filename: "test.css".into(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: safari10.into(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets: safari10.into(),
+        analyze_dependencies: Some(Default::default()),
+        math_fallback: Some(MathFallbackOptions::default()),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".foo{background:url(\"F5r5lG\") 0 0/10px no-repeat;background:url(\"F5r5lG\") 0 0/min(10px,5vw) no-repeat}"
+    );
+    let dependencies = res.dependencies.unwrap();
+    assert_eq!(dependencies.len(), 2);
+    for dependency in dependencies {
+      match dependency {
+        Dependency::Url(dep) => {
+          assert_eq!(dep.url, "a.png");
+          assert_eq!(dep.placeholder, "F5r5lG");
+        }
+        Dependency::Import(..) => unreachable!(),
       }
-    "#
+    }
+  }
+
+  #[test]
+  fn test_rem_to_px() {
+    options_test(
+      ".foo { font-size: 1.5rem; margin: 1rem 2em; width: calc(100% - 2rem) }",
+      ".foo{width:calc(100% - 32px);margin:16px 2em;font-size:24px}",
+      ParserOptions::default(),
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rem_to_px: Some(RemToPxOptions::default()),
+        ..PrinterOptions::default()
       },
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
+    );
+    options_test(
+      ".foo { margin: 1rem 2em }",
+      ".foo{margin:10px 20px}",
+      ParserOptions::default(),
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rem_to_px: Some(RemToPxOptions {
+          root_font_size: Some(10.0),
+          convert_em: true,
+          ..RemToPxOptions::default()
+        }),
+        ..PrinterOptions::default()
       },
     );
-    prefix_test(
-      r#"
-      .foo {
-        box-shadow: 0px 0px 22px red;
-        box-shadow: 0px 0px max(2cqw, 22px) red;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        box-shadow: 0 0 max(2cqw, 22px) red;
-      }
-    "#
+    options_test(
+      ".foo { --gap: 1rem; padding: var(--gap, 0.5rem) }",
+      ".foo{--gap:16px;padding:var(--gap,8px)}",
+      ParserOptions::default(),
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rem_to_px: Some(RemToPxOptions::default()),
+        ..PrinterOptions::default()
       },
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
+    );
+    options_test(
+      "@media (min-width: 40rem) { .foo { padding: 1rem } }",
+      "@media (width>=40rem){.foo{padding:16px}}",
+      ParserOptions::default(),
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rem_to_px: Some(RemToPxOptions::default()),
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      "@media (min-width: 40rem) { .foo { padding: 1rem } }",
+      "@media (width>=640px){.foo{padding:16px}}",
+      ParserOptions::default(),
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rem_to_px: Some(RemToPxOptions {
+          media_queries: true,
+          ..RemToPxOptions::default()
+        }),
+        ..PrinterOptions::default()
       },
     );
+  }
 
-    prefix_test(
-      r#"
-      .foo {
-        box-shadow: 0px 0px 22px red;
-        box-shadow: 0px 0px 22px lab(40% 56.6 39);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        box-shadow: 0 0 22px red;
-        box-shadow: 0 0 22px lab(40% 56.6 39);
-      }
-    "#
+  #[test]
+  fn test_rebase_urls() {
+    let options = RebaseUrlsOptions {
+      output_file: "dist/out.css",
+      public_path: None,
+    };
+    options_test(
+      ".foo { background: url(../images/bg.png) }",
+      ".foo{background:url(../src/images/bg.png)}",
+      ParserOptions {
+        filename: "src/styles/main.css".into(),
+        ..ParserOptions::default()
       },
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rebase_urls: Some(options),
+        ..PrinterOptions::default()
       },
     );
-    prefix_test(
-      r#"
-      .foo {
-        box-shadow: 0px 0px 22px red;
-        box-shadow: 0px 0px 22px lab(40% 56.6 39);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        box-shadow: 0 0 22px lab(40% 56.6 39);
-      }
-    "#
+    options_test("@font-face { font-family: Foo; src: url(./fonts/foo.woff2?v=1) format(woff2), url(\"fonts/foo.eot?#iefix\") }", "@font-face{font-family:Foo;src:url(../src/fonts/foo.woff2?v=1)format(\"woff2\"),url(../src/fonts/foo.eot?#iefix)}", ParserOptions { filename: "src/main.css".into(), ..ParserOptions::default() }, MinifyOptions::default(), PrinterOptions {minify: true, rebase_urls: Some(options), ..PrinterOptions::default()});
+    options_test(
+      ".foo { background: url(bg.png) }",
+      ".foo{background:url(bg.png)}",
+      ParserOptions {
+        filename: "dist/main.css".into(),
+        ..ParserOptions::default()
       },
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rebase_urls: Some(options),
+        ..PrinterOptions::default()
       },
     );
-
-    prefix_test(
-      r#"
-      .foo {
-        box-shadow: var(--fallback);
-        box-shadow: 0px 0px 22px lab(40% 56.6 39);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        box-shadow: var(--fallback);
-        box-shadow: 0 0 22px lab(40% 56.6 39);
-      }
-    "#
+    options_test(".foo { background: url(/bg.png), url(data:image/png;base64,AAAA), url(https://example.com/bg.png) } .bar { filter: url(#blur) }", ".foo{background:url(/bg.png),url(data:image/png;base64,AAAA),url(https://example.com/bg.png)}.bar{filter:url(#blur)}", ParserOptions { filename: "src/main.css".into(), ..ParserOptions::default() }, MinifyOptions::default(), PrinterOptions {minify: true, rebase_urls: Some(options), ..PrinterOptions::default()});
+    options_test(
+      ".foo { background: url(../../assets/icon.svg) }",
+      ".foo{background:url(../assets/icon.svg)}",
+      ParserOptions {
+        filename: "/project/src/components/button.css".into(),
+        ..ParserOptions::default()
       },
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rebase_urls: Some(RebaseUrlsOptions {
+          output_file: "/project/dist/out.css",
+          public_path: None,
+        }),
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      ".foo { background: url(icon.svg) }",
+      ".foo{background:url(/static/components/icon.svg)}",
+      ParserOptions {
+        filename: "src/components/button.css".into(),
+        ..ParserOptions::default()
+      },
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        rebase_urls: Some(RebaseUrlsOptions {
+          output_file: "src/out.css",
+          public_path: Some("/static"),
+        }),
+        ..PrinterOptions::default()
       },
     );
   }
 
   #[test]
-  fn test_media() {
-    minify_test(
-      "@media (min-width: 240px) { .foo { color: chartreuse }}",
-      "@media (width>=240px){.foo{color:#7fff00}}",
+  fn test_inline_assets() {
+    use assert_fs::prelude::*;
+
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir
+      .child("icons/check.svg")
+      .write_str(r#"<svg xmlns="http://www.w3.org/2000/svg"><path d="M0 0h1"/></svg>"#)
+      .unwrap();
+    dir
+      .child("images/dot.png")
+      .write_binary(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a])
+      .unwrap();
+    dir.child("images/dot").write_binary(&[0xff, 0xd8, 0xff, 0xe0]).unwrap();
+    dir.child("images/large.png").write_binary(&[0; 5000]).unwrap();
+    dir.child("data.txt").write_str("hello").unwrap();
+    let filename = dir.child("style.css");
+    let filename = filename.path().to_str().unwrap();
+
+    options_test(".foo { background: url(icons/check.svg) }", ".foo{background:url(\"data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22%3E%3Cpath d=%22M0 0h1%22/%3E%3C/svg%3E\")}", ParserOptions { filename: filename.into(), ..ParserOptions::default() }, MinifyOptions::default(), PrinterOptions {minify: true, inline_assets: Some(InlineAssetsOptions::default()), ..PrinterOptions::default()});
+    options_test(
+      ".foo { background: url(./images/dot.png), url(images/dot) }",
+      ".foo{background:url(data:image/png;base64,iVBORw0KGgo=),url(data:image/jpeg;base64,/9j/4A==)}",
+      ParserOptions {
+        filename: filename.into(),
+        ..ParserOptions::default()
+      },
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        inline_assets: Some(InlineAssetsOptions::default()),
+        ..PrinterOptions::default()
+      },
     );
-    minify_test(
-      "@media (width < 240px) { .foo { color: chartreuse }}",
-      "@media (width<240px){.foo{color:#7fff00}}",
+    options_test(
+      ".foo { background: url(images/large.png), url(data.txt), url(missing.png), url(icons/check.svg#icon) }",
+      ".foo{background:url(images/large.png),url(data.txt),url(missing.png),url(icons/check.svg#icon)}",
+      ParserOptions {
+        filename: filename.into(),
+        ..ParserOptions::default()
+      },
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        inline_assets: Some(InlineAssetsOptions::default()),
+        ..PrinterOptions::default()
+      },
     );
-    minify_test(
-      "@media (width <= 240px) { .foo { color: chartreuse }}",
-      "@media (width<=240px){.foo{color:#7fff00}}",
+    options_test(
+      ".foo { background: url(images/dot.png), url(images/dot) }",
+      ".foo{background:url(images/dot.png),url(data:image/jpeg;base64,/9j/4A==)}",
+      ParserOptions {
+        filename: filename.into(),
+        ..ParserOptions::default()
+      },
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        inline_assets: Some(InlineAssetsOptions {
+          max_size: Some(4),
+          ..InlineAssetsOptions::default()
+        }),
+        ..PrinterOptions::default()
+      },
     );
-    minify_test(
-      "@media (width > 240px) { .foo { color: chartreuse }}",
-      "@media (width>240px){.foo{color:#7fff00}}",
+    options_test(".foo { background: url(images/dot.png), url(icons/check.svg) }", ".foo{background:url(images/dot.png),url(\"data:image/svg+xml,%3Csvg xmlns=%22http://www.w3.org/2000/svg%22%3E%3Cpath d=%22M0 0h1%22/%3E%3C/svg%3E\")}", ParserOptions { filename: filename.into(), ..ParserOptions::default() }, MinifyOptions::default(), PrinterOptions {minify: true, inline_assets: Some(InlineAssetsOptions {
+        include: vec!["*.svg".into()],
+        ..InlineAssetsOptions::default()
+      }), ..PrinterOptions::default()});
+    options_test(
+      ".foo { background: url(images/dot.png), url(icons/check.svg) }",
+      ".foo{background:url(data:image/png;base64,iVBORw0KGgo=),url(icons/check.svg)}",
+      ParserOptions {
+        filename: filename.into(),
+        ..ParserOptions::default()
+      },
+      MinifyOptions::default(),
+      PrinterOptions {
+        minify: true,
+        inline_assets: Some(InlineAssetsOptions {
+          exclude: vec!["*/icons/*".into()],
+          ..InlineAssetsOptions::default()
+        }),
+        ..PrinterOptions::default()
+      },
     );
-    minify_test(
-      "@media (width >= 240px) { .foo { color: chartreuse }}",
-      "@media (width>=240px){.foo{color:#7fff00}}",
+  }
+
+  #[test]
+  fn test_emit_asset() {
+    let emitted = std::cell::RefCell::new(Vec::new());
+    let emit_asset = |path: &std::path::Path| {
+      let path = path.to_str().unwrap().to_owned();
+      if path.ends_with(".txt") {
+        return None;
+      }
+      emitted.borrow_mut().push(path.clone());
+      Some(format!("assets/{}", path.replace('/', "-")))
+    };
+
+    let mut stylesheet = StyleSheet::parse(
+      r#"
+      .foo { background: url(../images/bg.png) }
+      .bar { background: url(../images/bg.png#icon), url(data.txt), url(/bg.png) }
+      @font-face { font-family: Foo; src: url(fonts/foo.woff2?v=1) }
+    "#,
+      ParserOptions {
+        filename: "src/style.css".into(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        emit_asset: Some(&emit_asset),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".foo{background:url(assets/images-bg.png)}.bar{background:url(assets/images-bg.png#icon),url(data.txt),url(/bg.png)}@font-face{font-family:Foo;src:url(assets/src-fonts-foo.woff2?v=1)}"
     );
-    minify_test(
-      "@media (240px < width) { .foo { color: chartreuse }}",
-      "@media (width>240px){.foo{color:#7fff00}}",
+    assert_eq!(*emitted.borrow(), vec!["images/bg.png", "src/fonts/foo.woff2"]);
+    assert_eq!(
+      res.assets,
+      Some(HashMap::from([
+        ("images/bg.png".into(), "assets/images-bg.png".into()),
+        ("src/fonts/foo.woff2".into(), "assets/src-fonts-foo.woff2".into()),
+      ]))
     );
+  }
+
+  #[test]
+  fn test_trig() {
+    minify_test(".foo { width: calc(2px * pi); }", ".foo{width:6.28319px}");
+    minify_test(".foo { width: calc(2px / pi); }", ".foo{width:.63662px}");
+    // minify_test(
+    //   ".foo { width: calc(2px * infinity); }",
+    //   ".foo{width:calc(2px*infinity)}",
+    // );
+    // minify_test(
+    //   ".foo { width: calc(2px * -infinity); }",
+    //   ".foo{width:calc(2px*-infinity)}",
+    // );
+    minify_test(".foo { width: calc(100px * sin(45deg))", ".foo{width:70.7107px}");
+    minify_test(".foo { width: calc(100px * sin(.125turn))", ".foo{width:70.7107px}");
     minify_test(
-      "@media (240px <= width) { .foo { color: chartreuse }}",
-      "@media (width>=240px){.foo{color:#7fff00}}",
+      ".foo { width: calc(100px * sin(3.14159265 / 4))",
+      ".foo{width:70.7107px}",
     );
+    minify_test(".foo { width: calc(100px * sin(pi / 4))", ".foo{width:70.7107px}");
     minify_test(
-      "@media (240px > width) { .foo { color: chartreuse }}",
-      "@media (width<240px){.foo{color:#7fff00}}",
+      ".foo { width: calc(100px * sin(22deg + 23deg))",
+      ".foo{width:70.7107px}",
     );
+
+    minify_test(".foo { width: calc(2px * cos(45deg))", ".foo{width:1.41421px}");
+    minify_test(".foo { width: calc(2px * tan(45deg))", ".foo{width:2px}");
+
+    minify_test(".foo { rotate: asin(sin(45deg))", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: asin(1)", ".foo{rotate:90deg}");
+    minify_test(".foo { rotate: asin(-1)", ".foo{rotate:-90deg}");
+    minify_test(".foo { rotate: asin(0.5)", ".foo{rotate:30deg}");
+    minify_test(".foo { rotate: asin(45deg)", ".foo{rotate:asin(45deg)}"); // invalid
+    minify_test(".foo { rotate: asin(-20)", ".foo{rotate:asin(-20)}"); // evaluates to NaN
+    minify_test(".foo { width: asin(sin(45deg))", ".foo{width:asin(sin(45deg))}"); // invalid
+
+    minify_test(".foo { rotate: acos(cos(45deg))", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: acos(-1)", ".foo{rotate:180deg}");
+    minify_test(".foo { rotate: acos(0)", ".foo{rotate:90deg}");
+    minify_test(".foo { rotate: acos(1)", ".foo{rotate:none}");
+    minify_test(".foo { rotate: acos(45deg)", ".foo{rotate:acos(45deg)}"); // invalid
+    minify_test(".foo { rotate: acos(-20)", ".foo{rotate:acos(-20)}"); // evaluates to NaN
+
+    minify_test(".foo { rotate: atan(tan(45deg))", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: atan(1)", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: atan(0)", ".foo{rotate:none}");
+    minify_test(".foo { rotate: atan(45deg)", ".foo{rotate:atan(45deg)}"); // invalid
+
+    minify_test(".foo { rotate: atan2(1px, -1px)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1vw, -1vw)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1, -1)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1ms, -1ms)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1%, -1%)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1deg, -1deg)", ".foo{rotate:135deg}");
+    minify_test(".foo { rotate: atan2(1cm, 1mm)", ".foo{rotate:84.2894deg}");
+    minify_test(".foo { rotate: atan2(0, -1)", ".foo{rotate:180deg}");
+    minify_test(".foo { rotate: atan2(-1, 1)", ".foo{rotate:-45deg}");
+    // incompatible units
+    minify_test(".foo { rotate: atan2(1px, -1vw)", ".foo{rotate:atan2(1px,-1vw)}");
+  }
+
+  #[test]
+  fn test_exp() {
+    minify_test(".foo { width: hypot()", ".foo{width:hypot()}");
+    minify_test(".foo { width: hypot(1px)", ".foo{width:1px}");
+    minify_test(".foo { width: hypot(1px, 2px)", ".foo{width:2.23607px}");
+    minify_test(".foo { width: hypot(1px, 2px, 3px)", ".foo{width:3.74166px}");
+    minify_test(".foo { width: hypot(1px, 2vw)", ".foo{width:hypot(1px,2vw)}");
+    minify_test(".foo { width: hypot(1px, 2px, 3vw)", ".foo{width:hypot(1px,2px,3vw)}");
+    minify_test(".foo { width: calc(100px * hypot(3, 4))", ".foo{width:500px}");
+    minify_test(".foo { width: calc(1px * pow(2, sqrt(100))", ".foo{width:1024px}");
+    minify_test(".foo { width: calc(100px * pow(2, pow(2, 2)", ".foo{width:1600px}");
+    minify_test(".foo { width: calc(1px * log(1))", ".foo{width:0}");
+    minify_test(".foo { width: calc(1px * log(10, 10))", ".foo{width:1px}");
+    minify_test(".foo { width: calc(1px * exp(0))", ".foo{width:1px}");
+    minify_test(".foo { width: calc(1px * log(e))", ".foo{width:1px}");
+    minify_test(".foo { width: calc(1px * (e - exp(1)))", ".foo{width:0}");
     minify_test(
-      "@media (240px >= width) { .foo { color: chartreuse }}",
-      "@media (width<=240px){.foo{color:#7fff00}}",
+      ".foo { width: calc(1px * (exp(log(1) + exp(0)*2))",
+      ".foo{width:7.38906px}",
     );
+  }
+
+  #[test]
+  fn test_sign() {
+    minify_test(".foo { width: abs(1px)", ".foo{width:1px}");
+    minify_test(".foo { width: abs(-1px)", ".foo{width:1px}");
+    minify_test(".foo { width: abs(1%)", ".foo{width:abs(1%)}"); // spec says percentages must be against resolved value
+
+    minify_test(".foo { width: calc(10px * sign(-1vw)", ".foo{width:-10px}");
+    minify_test(".foo { width: calc(10px * sign(1%)", ".foo{width:calc(10px*sign(1%))}");
+  }
+
+  #[test]
+  fn test_box_shadow() {
     minify_test(
-      "@media (100px < width < 200px) { .foo { color: chartreuse }}",
-      "@media (100px<width<200px){.foo{color:#7fff00}}",
+      ".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4) }",
+      ".foo{box-shadow:64px 64px 12px 40px #0006}",
     );
     minify_test(
-      "@media (100px <= width <= 200px) { .foo { color: chartreuse }}",
-      "@media (100px<=width<=200px){.foo{color:#7fff00}}",
+      ".foo { box-shadow: 12px 12px 0px 8px rgba(0,0,0,0.4) inset }",
+      ".foo{box-shadow:inset 12px 12px 0 8px #0006}",
     );
     minify_test(
-      "@media (min-width: 30em) and (max-width: 50em) { .foo { color: chartreuse }}",
-      "@media (width>=30em) and (width<=50em){.foo{color:#7fff00}}",
+      ".foo { box-shadow: inset 12px 12px 0px 8px rgba(0,0,0,0.4) }",
+      ".foo{box-shadow:inset 12px 12px 0 8px #0006}",
     );
     minify_test(
-      "@media screen, print { .foo { color: chartreuse }}",
-      "@media screen,print{.foo{color:#7fff00}}",
+      ".foo { box-shadow: 12px 12px 8px 0px rgba(0,0,0,0.4) }",
+      ".foo{box-shadow:12px 12px 8px #0006}",
     );
     minify_test(
-      "@media (hover: hover) { .foo { color: chartreuse }}",
-      "@media (hover:hover){.foo{color:#7fff00}}",
+      ".foo { box-shadow: 12px 12px 0px 0px rgba(0,0,0,0.4) }",
+      ".foo{box-shadow:12px 12px #0006}",
     );
     minify_test(
-      "@media (hover) { .foo { color: chartreuse }}",
-      "@media (hover){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (aspect-ratio: 11/5) { .foo { color: chartreuse }}",
-      "@media (aspect-ratio:11/5){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (aspect-ratio: 2/1) { .foo { color: chartreuse }}",
-      "@media (aspect-ratio:2){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (aspect-ratio: 2) { .foo { color: chartreuse }}",
-      "@media (aspect-ratio:2){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media not screen and (color) { .foo { color: chartreuse }}",
-      "@media not screen and (color){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media only screen and (color) { .foo { color: chartreuse }}",
-      "@media only screen and (color){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (update: slow) or (hover: none) { .foo { color: chartreuse }}",
-      "@media (update:slow) or (hover:none){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (width < 600px) and (height < 600px) { .foo { color: chartreuse }}",
-      "@media (width<600px) and (height<600px){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (not (color)) or (hover) { .foo { color: chartreuse }}",
-      "@media (not (color)) or (hover){.foo{color:#7fff00}}",
-    );
-    error_test(
-      "@media (example, all,), speech { .foo { color: chartreuse }}",
-      ParserError::UnexpectedToken(Token::Comma),
-    );
-    error_test(
-      "@media &test, speech { .foo { color: chartreuse }}",
-      ParserError::UnexpectedToken(Token::Delim('&')),
-    );
-    error_test(
-      "@media &test { .foo { color: chartreuse }}",
-      ParserError::UnexpectedToken(Token::Delim('&')),
-    );
-    minify_test(
-      "@media (min-width: calc(200px + 40px)) { .foo { color: chartreuse }}",
-      "@media (width>=240px){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (min-width: calc(1em + 5px)) { .foo { color: chartreuse }}",
-      "@media (width>=calc(1em + 5px)){.foo{color:#7fff00}}",
-    );
-    minify_test("@media { .foo { color: chartreuse }}", ".foo{color:#7fff00}");
-    minify_test("@media all { .foo { color: chartreuse }}", ".foo{color:#7fff00}");
-    minify_test(
-      "@media not (((color) or (hover))) { .foo { color: chartreuse }}",
-      "@media not ((color) or (hover)){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (hover) and ((color) and (test)) { .foo { color: chartreuse }}",
-      "@media (hover) and (color) and (test){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (grid: 1) { .foo { color: chartreuse }}",
-      "@media (grid:1){.foo{color:#7fff00}}",
-    );
-    minify_test(
-      "@media (width >= calc(2px + 4px)) { .foo { color: chartreuse }}",
-      "@media (width>=6px){.foo{color:#7fff00}}",
+      ".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4), 12px 12px 0px 8px rgba(0,0,0,0.4) inset }",
+      ".foo{box-shadow:64px 64px 12px 40px #0006,inset 12px 12px 0 8px #0006}",
     );
 
     prefix_test(
-      r#"
-        @media (width >= 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo { box-shadow: 12px 12px lab(40% 56.6 39) }",
       indoc! { r#"
-        @media (min-width: 240px) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          box-shadow: 12px 12px #b32323;
+          box-shadow: 12px 12px lab(40% 56.6 39);
         }
       "#},
       Browsers {
-        firefox: Some(60 << 16),
+        chrome: Some(90 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (width >= 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo { box-shadow: 12px 12px lab(40% 56.6 39) }",
       indoc! { r#"
-        @media (width >= 240px) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          -webkit-box-shadow: 12px 12px #b32323;
+          box-shadow: 12px 12px #b32323;
+          box-shadow: 12px 12px lab(40% 56.6 39);
         }
       "#},
       Browsers {
-        firefox: Some(64 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (color > 2) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo { box-shadow: 12px 12px lab(40% 56.6 39), 12px 12px yellow }",
       indoc! { r#"
-        @media (min-color: 3) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          -webkit-box-shadow: 12px 12px #b32323, 12px 12px #ff0;
+          box-shadow: 12px 12px #b32323, 12px 12px #ff0;
+          box-shadow: 12px 12px lab(40% 56.6 39), 12px 12px #ff0;
         }
       "#},
       Browsers {
-        firefox: Some(60 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (color < 2) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo { -webkit-box-shadow: 12px 12px #0006 }",
       indoc! { r#"
-        @media (max-color: 1) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          -webkit-box-shadow: 12px 12px rgba(0, 0, 0, .4);
         }
       "#},
       Browsers {
-        firefox: Some(60 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (width > 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo {
+        -webkit-box-shadow: 12px 12px #0006;
+        -moz-box-shadow: 12px 12px #0009;
+      }",
       indoc! { r#"
-        @media (min-width: 240.001px) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          -webkit-box-shadow: 12px 12px rgba(0, 0, 0, .4);
+          -moz-box-shadow: 12px 12px rgba(0, 0, 0, .6);
         }
       "#},
       Browsers {
-        firefox: Some(60 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (width <= 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo {
+        -webkit-box-shadow: 12px 12px #0006;
+        -moz-box-shadow: 12px 12px #0006;
+        box-shadow: 12px 12px #0006;
+      }",
       indoc! { r#"
-        @media (max-width: 240px) {
-          .foo {
-            color: #7fff00;
-          }
+        .foo {
+          box-shadow: 12px 12px #0006;
         }
       "#},
       Browsers {
-        firefox: Some(60 << 16),
+        chrome: Some(95 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-        @media (width <= 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
+      ".foo { box-shadow: var(--foo) 12px lab(40% 56.6 39) }",
       indoc! { r#"
-        @media (width <= 240px) {
+        .foo {
+          box-shadow: var(--foo) 12px #b32323;
+        }
+
+        @supports (color: lab(0% 0 0)) {
           .foo {
-            color: #7fff00;
+            box-shadow: var(--foo) 12px lab(40% 56.6 39);
           }
         }
       "#},
       Browsers {
-        firefox: Some(64 << 16),
+        chrome: Some(90 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media (width < 240px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
-      indoc! { r#"
-        @media (max-width: 239.999px) {
-          .foo {
-            color: #7fff00;
-          }
-        }
-      "#},
+      .foo {
+        box-shadow: 0px 0px 22px red;
+        box-shadow: 0px 0px max(2cqw, 22px) red;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: 0 0 22px red;
+        box-shadow: 0 0 max(2cqw, 22px) red;
+      }
+    "#
+      },
       Browsers {
-        firefox: Some(60 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
-        @media (100px <= width <= 200px) {
-          .foo {
-            color: chartreuse;
-          }
-        }
-      "#,
-      indoc! { r#"
-        @media (min-width: 100px) and (max-width: 200px) {
-          .foo {
-            color: #7fff00;
-          }
-        }
-      "#},
+      .foo {
+        box-shadow: 0px 0px 22px red;
+        box-shadow: 0px 0px max(2cqw, 22px) red;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: 0 0 max(2cqw, 22px) red;
+      }
+    "#
+      },
       Browsers {
-        firefox: Some(85 << 16),
+        safari: Some(16 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media not (100px <= width <= 200px) {
-          .foo {
-            color: chartreuse;
+      .foo {
+        box-shadow: 0px 0px 22px red;
+        box-shadow: 0px 0px 22px lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: 0 0 22px red;
+        box-shadow: 0 0 22px lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      .foo {
+        box-shadow: 0px 0px 22px red;
+        box-shadow: 0px 0px 22px lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: 0 0 22px lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        box-shadow: var(--fallback);
+        box-shadow: 0px 0px 22px lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: var(--fallback);
+        box-shadow: 0 0 22px lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_media() {
+    minify_test(
+      "@media (min-width: 240px) { .foo { color: chartreuse }}",
+      "@media (width>=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width < 240px) { .foo { color: chartreuse }}",
+      "@media (width<240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width <= 240px) { .foo { color: chartreuse }}",
+      "@media (width<=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width > 240px) { .foo { color: chartreuse }}",
+      "@media (width>240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width >= 240px) { .foo { color: chartreuse }}",
+      "@media (width>=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (240px < width) { .foo { color: chartreuse }}",
+      "@media (width>240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (240px <= width) { .foo { color: chartreuse }}",
+      "@media (width>=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (240px > width) { .foo { color: chartreuse }}",
+      "@media (width<240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (240px >= width) { .foo { color: chartreuse }}",
+      "@media (width<=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (100px < width < 200px) { .foo { color: chartreuse }}",
+      "@media (100px<width<200px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (100px <= width <= 200px) { .foo { color: chartreuse }}",
+      "@media (100px<=width<=200px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (min-width: 30em) and (max-width: 50em) { .foo { color: chartreuse }}",
+      "@media (width>=30em) and (width<=50em){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media screen, print { .foo { color: chartreuse }}",
+      "@media screen,print{.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (hover: hover) { .foo { color: chartreuse }}",
+      "@media (hover:hover){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (hover) { .foo { color: chartreuse }}",
+      "@media (hover){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (aspect-ratio: 11/5) { .foo { color: chartreuse }}",
+      "@media (aspect-ratio:11/5){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (aspect-ratio: 2/1) { .foo { color: chartreuse }}",
+      "@media (aspect-ratio:2){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (aspect-ratio: 2) { .foo { color: chartreuse }}",
+      "@media (aspect-ratio:2){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media not screen and (color) { .foo { color: chartreuse }}",
+      "@media not screen and (color){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media only screen and (color) { .foo { color: chartreuse }}",
+      "@media only screen and (color){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (update: slow) or (hover: none) { .foo { color: chartreuse }}",
+      "@media (update:slow) or (hover:none){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width < 600px) and (height < 600px) { .foo { color: chartreuse }}",
+      "@media (width<600px) and (height<600px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (not (color)) or (hover) { .foo { color: chartreuse }}",
+      "@media (not (color)) or (hover){.foo{color:#7fff00}}",
+    );
+    error_test(
+      "@media (example, all,), speech { .foo { color: chartreuse }}",
+      ParserError::UnexpectedToken(Token::Comma),
+    );
+    error_test(
+      "@media &test, speech { .foo { color: chartreuse }}",
+      ParserError::UnexpectedToken(Token::Delim('&')),
+    );
+    error_test(
+      "@media &test { .foo { color: chartreuse }}",
+      ParserError::UnexpectedToken(Token::Delim('&')),
+    );
+    minify_test(
+      "@media (min-width: calc(200px + 40px)) { .foo { color: chartreuse }}",
+      "@media (width>=240px){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (min-width: calc(1em + 5px)) { .foo { color: chartreuse }}",
+      "@media (width>=calc(1em + 5px)){.foo{color:#7fff00}}",
+    );
+    minify_test("@media { .foo { color: chartreuse }}", ".foo{color:#7fff00}");
+    minify_test("@media all { .foo { color: chartreuse }}", ".foo{color:#7fff00}");
+    minify_test(
+      "@media not (((color) or (hover))) { .foo { color: chartreuse }}",
+      "@media not ((color) or (hover)){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (hover) and ((color) and (test)) { .foo { color: chartreuse }}",
+      "@media (hover) and (color) and (test){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (grid: 1) { .foo { color: chartreuse }}",
+      "@media (grid:1){.foo{color:#7fff00}}",
+    );
+    minify_test(
+      "@media (width >= calc(2px + 4px)) { .foo { color: chartreuse }}",
+      "@media (width>=6px){.foo{color:#7fff00}}",
+    );
+
+    prefix_test(
+      r#"
+        @media (width >= 240px) {
+          .foo {
+            color: chartreuse;
           }
         }
       "#,
       indoc! { r#"
-        @media not ((min-width: 100px) and (max-width: 200px)) {
+        @media (min-width: 240px) {
           .foo {
             color: #7fff00;
           }
         }
       "#},
       Browsers {
-        firefox: Some(85 << 16),
+        firefox: Some(60 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media (hover) and (100px <= width <= 200px) {
+        @media (width >= 240px) {
           .foo {
             color: chartreuse;
           }
         }
       "#,
       indoc! { r#"
-        @media (hover) and (min-width: 100px) and (max-width: 200px) {
+        @media (width >= 240px) {
           .foo {
             color: #7fff00;
           }
         }
       "#},
       Browsers {
-        firefox: Some(85 << 16),
+        firefox: Some(64 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media (hover) or (100px <= width <= 200px) {
+        @media (color > 2) {
           .foo {
             color: chartreuse;
           }
         }
       "#,
       indoc! { r#"
-        @media (hover) or ((min-width: 100px) and (max-width: 200px)) {
+        @media (min-color: 3) {
           .foo {
             color: #7fff00;
           }
         }
       "#},
       Browsers {
-        firefox: Some(85 << 16),
+        firefox: Some(60 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media (100px < width < 200px) {
+        @media (color < 2) {
           .foo {
             color: chartreuse;
           }
         }
       "#,
       indoc! { r#"
-        @media (min-width: 100.001px) and (max-width: 199.999px) {
+        @media (max-color: 1) {
           .foo {
             color: #7fff00;
           }
         }
       "#},
       Browsers {
-        firefox: Some(85 << 16),
+        firefox: Some(60 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-        @media (200px >= width >= 100px) {
+        @media (width > 240px) {
           .foo {
             color: chartreuse;
           }
         }
       "#,
       indoc! { r#"
-        @media (max-width: 200px) and (min-width: 100px) {
+        @media (min-width: 240.001px) {
           .foo {
             color: #7fff00;
           }
         }
       "#},
       Browsers {
-        firefox: Some(85 << 16),
+        firefox: Some(60 << 16),
         ..Browsers::default()
       },
     );
 
-    test(
-      r#"
-      @media not all {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      "\n",
-    );
-
     prefix_test(
       r#"
-      @media (width > calc(1px + 1rem)) {
-        .foo { color: yellow; }
-      }
+        @media (width <= 240px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
       "#,
       indoc! { r#"
-        @media (min-width: calc(1.001px + 1rem)) {
+        @media (max-width: 240px) {
           .foo {
-            color: #ff0;
+            color: #7fff00;
           }
         }
       "#},
       Browsers {
-        chrome: Some(85 << 16),
+        firefox: Some(60 << 16),
         ..Browsers::default()
       },
     );
+
     prefix_test(
       r#"
-      @media (width > max(10px, 1rem)) {
-        .foo { color: yellow; }
-      }
-      "#,
-      indoc! { r#"
-        @media (min-width: calc(max(10px, 1rem) + .001px)) {
+        @media (width <= 240px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (width <= 240px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(64 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (width < 240px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (max-width: 239.999px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(60 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (100px <= width <= 200px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (min-width: 100px) and (max-width: 200px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media not (100px <= width <= 200px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media not ((min-width: 100px) and (max-width: 200px)) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (hover) and (100px <= width <= 200px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (hover) and (min-width: 100px) and (max-width: 200px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (hover) or (100px <= width <= 200px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (hover) or ((min-width: 100px) and (max-width: 200px)) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (100px < width < 200px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (min-width: 100.001px) and (max-width: 199.999px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      r#"
+        @media (200px >= width >= 100px) {
+          .foo {
+            color: chartreuse;
+          }
+        }
+      "#,
+      indoc! { r#"
+        @media (max-width: 200px) and (min-width: 100px) {
+          .foo {
+            color: #7fff00;
+          }
+        }
+      "#},
+      Browsers {
+        firefox: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    test(
+      r#"
+      @media not all {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      "\n",
+    );
+
+    prefix_test(
+      r#"
+      @media (width > calc(1px + 1rem)) {
+        .foo { color: yellow; }
+      }
+      "#,
+      indoc! { r#"
+        @media (min-width: calc(1.001px + 1rem)) {
+          .foo {
+            color: #ff0;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(85 << 16),
+        ..Browsers::default()
+      },
+    );
+    prefix_test(
+      r#"
+      @media (width > max(10px, 1rem)) {
+        .foo { color: yellow; }
+      }
+      "#,
+      indoc! { r#"
+        @media (min-width: calc(max(10px, 1rem) + .001px)) {
           .foo {
             color: #ff0;
           }
@@ -12318,31 +13333,244 @@ mod tests {
         ..Browsers::default()
       },
     );
+
+    let safari_12 = Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
+    };
+    for name in &["linear-gradient", "repeating-linear-gradient"] {
+      prefix_test(
+        &format!(".foo {{ background: {}(red 10% 40%, blue) }}", name),
+        &format!(
+          indoc! {r#"
+          .foo {{
+            background: {}(red 10%, red 40%, #00f);
+          }}
+          "#},
+          name
+        ),
+        safari_12,
+      );
+    }
+    for name in &["radial-gradient", "repeating-radial-gradient"] {
+      prefix_test(
+        &format!(".foo {{ background: {}(red, blue 20px 40px, yellow) }}", name),
+        &format!(
+          indoc! {r#"
+          .foo {{
+            background: {}(red, #00f 20px, #00f 40px, #ff0);
+          }}
+          "#},
+          name
+        ),
+        safari_12,
+      );
+    }
+    for name in &["conic-gradient", "repeating-conic-gradient"] {
+      prefix_test(
+        &format!(".foo {{ background: {}(red 0deg 90deg, blue 90deg 180deg) }}", name),
+        &format!(
+          indoc! {r#"
+          .foo {{
+            background: {}(red 0deg, red 90deg, #00f 90deg, #00f 180deg);
+          }}
+          "#},
+          name
+        ),
+        safari_12,
+      );
+      prefix_test(
+        &format!(".foo {{ background: {}(red 0deg 90deg, blue 90deg 180deg) }}", name),
+        &format!(
+          indoc! {r#"
+          .foo {{
+            background: {}(red 0deg 90deg, #00f 90deg 180deg);
+          }}
+          "#},
+          name
+        ),
+        Browsers {
+          safari: Some(15 << 16),
+          ..Browsers::default()
+        },
+      );
+    }
   }
 
   #[test]
-  fn test_font_face() {
+  fn test_font_variant() {
+    use crate::properties::font::FontVariant;
+
     minify_test(
-      r#"@font-face {
-      src: url("test.woff");
-      font-family: "Helvetica";
-      font-weight: bold;
-      font-style: italic;
-    }"#,
-      "@font-face{src:url(test.woff);font-family:Helvetica;font-weight:700;font-style:italic}",
+      ".foo { font-variant-ligatures: normal }",
+      ".foo{font-variant-ligatures:normal}",
     );
-    minify_test("@font-face {src: url(test.woff);}", "@font-face{src:url(test.woff)}");
-    minify_test("@font-face {src: local(\"Test\");}", "@font-face{src:local(Test)}");
     minify_test(
-      "@font-face {src: local(\"Foo Bar\");}",
-      "@font-face{src:local(Foo Bar)}",
+      ".foo { font-variant-ligatures: none }",
+      ".foo{font-variant-ligatures:none}",
     );
-    minify_test("@font-face {src: local(Test);}", "@font-face{src:local(Test)}");
-    minify_test("@font-face {src: local(Foo Bar);}", "@font-face{src:local(Foo Bar)}");
-
     minify_test(
-      "@font-face {src: url(\"test.woff\") format(woff);}",
-      "@font-face{src:url(test.woff)format(\"woff\")}",
+      ".foo { font-variant-ligatures: no-contextual common-ligatures }",
+      ".foo{font-variant-ligatures:common-ligatures no-contextual}",
+    );
+    minify_test(
+      ".foo { font-variant-ligatures: no-common-ligatures no-discretionary-ligatures no-historical-ligatures no-contextual }",
+      ".foo{font-variant-ligatures:none}",
+    );
+    minify_test(
+      ".foo { font-variant-numeric: slashed-zero tabular-nums lining-nums }",
+      ".foo{font-variant-numeric:lining-nums tabular-nums slashed-zero}",
+    );
+    minify_test(
+      ".foo { font-variant-numeric: ordinal diagonal-fractions }",
+      ".foo{font-variant-numeric:diagonal-fractions ordinal}",
+    );
+    minify_test(
+      ".foo { font-variant-east-asian: ruby jis78 }",
+      ".foo{font-variant-east-asian:jis78 ruby}",
+    );
+    minify_test(
+      ".foo { font-variant-alternates: styleset(a, b) historical-forms stylistic(x) }",
+      ".foo{font-variant-alternates:stylistic(x) historical-forms styleset(a,b)}",
+    );
+    minify_test(
+      ".foo { font-variant-position: super }",
+      ".foo{font-variant-position:super}",
+    );
+    minify_test(".foo { font-variant-emoji: emoji }", ".foo{font-variant-emoji:emoji}");
+    minify_test(".foo { font-kerning: none }", ".foo{font-kerning:none}");
+    minify_test(".foo { font-optical-sizing: auto }", ".foo{font-optical-sizing:auto}");
+
+    minify_test(".foo { font-variant: normal }", ".foo{font-variant:normal}");
+    minify_test(".foo { font-variant: none }", ".foo{font-variant:none}");
+    minify_test(
+      ".foo { font-variant: tabular-nums small-caps common-ligatures }",
+      ".foo{font-variant:common-ligatures small-caps tabular-nums}",
+    );
+    minify_test(
+      ".foo { font-variant: super jis04 swash(fancy) }",
+      ".foo{font-variant:swash(fancy) jis04 super}",
+    );
+    minify_test(
+      ".foo { font-variant: small-caps; font-variant-numeric: oldstyle-nums }",
+      ".foo{font-variant:small-caps oldstyle-nums}",
+    );
+    minify_test(
+      ".foo { font-variant-caps: small-caps; font-variant-numeric: oldstyle-nums }",
+      ".foo{font-variant-caps:small-caps;font-variant-numeric:oldstyle-nums}",
+    );
+    minify_test(
+      r#"
+      .foo {
+        font-variant-ligatures: none;
+        font-variant-caps: small-caps;
+        font-variant-alternates: normal;
+        font-variant-numeric: normal;
+        font-variant-east-asian: normal;
+        font-variant-position: normal;
+        font-variant-emoji: normal;
+      }
+    "#,
+      ".foo{font-variant:no-common-ligatures no-discretionary-ligatures no-historical-ligatures no-contextual small-caps}",
+    );
+    minify_test(
+      ".foo { font-variant: small-caps var(--x) }",
+      ".foo{font-variant:small-caps var(--x)}",
+    );
+
+    for invalid in [
+      "normal small-caps",
+      "none small-caps",
+      "small-caps small-caps",
+      "lining-nums oldstyle-nums",
+      "small-caps normal",
+    ] {
+      assert!(FontVariant::parse_string(invalid).is_err(), "{}", invalid);
+    }
+
+    // The font shorthand resets the font-variant subproperties, font-kerning, and font-optical-sizing.
+    minify_test(
+      ".foo { font-variant-ligatures: none; font-kerning: none; font: 12px Helvetica }",
+      ".foo{font:12px Helvetica}",
+    );
+    minify_test(
+      ".foo { font: 12px Helvetica; font-variant-numeric: tabular-nums; font-kerning: none }",
+      ".foo{font:12px Helvetica;font-variant-numeric:tabular-nums;font-kerning:none}",
+    );
+    minify_test(
+      ".foo { font: 12px Helvetica; font-variant: small-caps tabular-nums }",
+      ".foo{font:small-caps 12px Helvetica;font-variant-numeric:tabular-nums}",
+    );
+    minify_test(
+      ".foo { font: 12px Helvetica; font-variant: all-small-caps }",
+      ".foo{font:12px Helvetica;font-variant-caps:all-small-caps}",
+    );
+    minify_test(
+      ".foo { font: 12px Helvetica; font-variation-settings: normal }",
+      ".foo{font:12px Helvetica;font-variation-settings:normal}",
+    );
+    minify_test(
+      ".foo { font-variant-numeric: var(--x); font-family: Helvetica; font-size: 12px; font-style: normal; font-weight: normal; font-stretch: normal; line-height: normal; font-variant-caps: normal }",
+      ".foo{font-variant-numeric:var(--x);font-family:Helvetica;font-size:12px;font-style:normal;font-variant-caps:normal;font-weight:400;font-stretch:100%;line-height:normal}",
+    );
+
+    // Only font-stretch keywords are valid in the font shorthand.
+    minify_test(
+      ".foo { font: condensed 12px Helvetica; font-stretch: 62.5% }",
+      ".foo{font:extra-condensed 12px Helvetica}",
+    );
+    minify_test(
+      ".foo { font: condensed 12px Helvetica; font-stretch: 80% }",
+      ".foo{font:12px Helvetica;font-stretch:80%}",
+    );
+    test(
+      ".foo { font: condensed 12px Helvetica; font-stretch: 75%; }",
+      indoc! {r#"
+      .foo {
+        font: condensed 12px Helvetica;
+      }
+    "#},
+    );
+
+    // Round trips from WPT font shorthand serialization tests.
+    minify_test(
+      ".foo { font: italic small-caps bold condensed 16px/2 cursive }",
+      ".foo{font:italic small-caps 700 condensed 16px/2 cursive}",
+    );
+    minify_test(".foo { font: 16px/normal serif }", ".foo{font:16px serif}");
+    minify_test(
+      ".foo { font: normal normal normal normal 16px serif }",
+      ".foo{font:16px serif}",
+    );
+    minify_test(
+      ".foo { font: oblique 10deg 300 ultra-expanded 1em/1.5 \"Open Sans\", sans-serif }",
+      ".foo{font:oblique 10deg 300 ultra-expanded 1em/1.5 Open Sans,sans-serif}",
+    );
+  }
+
+  #[test]
+  fn test_font_face() {
+    minify_test(
+      r#"@font-face {
+      src: url("test.woff");
+      font-family: "Helvetica";
+      font-weight: bold;
+      font-style: italic;
+    }"#,
+      "@font-face{src:url(test.woff);font-family:Helvetica;font-weight:700;font-style:italic}",
+    );
+    minify_test("@font-face {src: url(test.woff);}", "@font-face{src:url(test.woff)}");
+    minify_test("@font-face {src: local(\"Test\");}", "@font-face{src:local(Test)}");
+    minify_test(
+      "@font-face {src: local(\"Foo Bar\");}",
+      "@font-face{src:local(Foo Bar)}",
+    );
+    minify_test("@font-face {src: local(Test);}", "@font-face{src:local(Test)}");
+    minify_test("@font-face {src: local(Foo Bar);}", "@font-face{src:local(Foo Bar)}");
+
+    minify_test(
+      "@font-face {src: url(\"test.woff\") format(woff);}",
+      "@font-face{src:url(test.woff)format(\"woff\")}",
     );
     minify_test(
       "@font-face {src: url(\"test.ttc\") format(collection), url(test.ttf) format(truetype);}",
@@ -13119,1063 +14347,1296 @@ mod tests {
   }
 
   #[test]
-  fn test_display() {
-    minify_test(".foo { display: block }", ".foo{display:block}");
-    minify_test(".foo { display: block flow }", ".foo{display:block}");
-    minify_test(".foo { display: flow-root }", ".foo{display:flow-root}");
-    minify_test(".foo { display: block flow-root }", ".foo{display:flow-root}");
-    minify_test(".foo { display: inline }", ".foo{display:inline}");
-    minify_test(".foo { display: inline flow }", ".foo{display:inline}");
-    minify_test(".foo { display: inline-block }", ".foo{display:inline-block}");
-    minify_test(".foo { display: inline flow-root }", ".foo{display:inline-block}");
-    minify_test(".foo { display: run-in }", ".foo{display:run-in}");
-    minify_test(".foo { display: run-in flow }", ".foo{display:run-in}");
-    minify_test(".foo { display: list-item }", ".foo{display:list-item}");
-    minify_test(".foo { display: block flow list-item }", ".foo{display:list-item}");
-    minify_test(".foo { display: inline list-item }", ".foo{display:inline list-item}");
-    minify_test(
-      ".foo { display: inline flow list-item }",
-      ".foo{display:inline list-item}",
-    );
-    minify_test(".foo { display: flex }", ".foo{display:flex}");
-    minify_test(".foo { display: block flex }", ".foo{display:flex}");
-    minify_test(".foo { display: inline-flex }", ".foo{display:inline-flex}");
-    minify_test(".foo { display: inline flex }", ".foo{display:inline-flex}");
-    minify_test(".foo { display: grid }", ".foo{display:grid}");
-    minify_test(".foo { display: block grid }", ".foo{display:grid}");
-    minify_test(".foo { display: inline-grid }", ".foo{display:inline-grid}");
-    minify_test(".foo { display: inline grid }", ".foo{display:inline-grid}");
-    minify_test(".foo { display: ruby }", ".foo{display:ruby}");
-    minify_test(".foo { display: inline ruby }", ".foo{display:ruby}");
-    minify_test(".foo { display: block ruby }", ".foo{display:block ruby}");
-    minify_test(".foo { display: table }", ".foo{display:table}");
-    minify_test(".foo { display: block table }", ".foo{display:table}");
-    minify_test(".foo { display: inline-table }", ".foo{display:inline-table}");
-    minify_test(".foo { display: inline table }", ".foo{display:inline-table}");
-    minify_test(".foo { display: table-row-group }", ".foo{display:table-row-group}");
-    minify_test(".foo { display: contents }", ".foo{display:contents}");
-    minify_test(".foo { display: none }", ".foo{display:none}");
-    minify_test(".foo { display: -webkit-flex }", ".foo{display:-webkit-flex}");
-    minify_test(".foo { display: -ms-flexbox }", ".foo{display:-ms-flexbox}");
-    minify_test(".foo { display: -webkit-box }", ".foo{display:-webkit-box}");
-    minify_test(".foo { display: -moz-box }", ".foo{display:-moz-box}");
-    minify_test(
-      ".foo { display: -webkit-flex; display: -moz-box; display: flex }",
-      ".foo{display:-webkit-flex;display:-moz-box;display:flex}",
-    );
-    minify_test(
-      ".foo { display: -webkit-flex; display: flex; display: -moz-box }",
-      ".foo{display:-webkit-flex;display:flex;display:-moz-box}",
-    );
-    minify_test(".foo { display: flex; display: grid }", ".foo{display:grid}");
-    minify_test(
-      ".foo { display: -webkit-inline-flex; display: -moz-inline-box; display: inline-flex }",
-      ".foo{display:-webkit-inline-flex;display:-moz-inline-box;display:inline-flex}",
-    );
-    minify_test(
-      ".foo { display: flex; display: var(--grid); }",
-      ".foo{display:flex;display:var(--grid)}",
-    );
-    prefix_test(
-      ".foo{ display: flex }",
+  fn test_prefix_rules() {
+    let touch_callout = || PrefixRule {
+      property: "touch-callout".into(),
+      values: vec!["none".into()],
+      browsers: Some(Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      }),
+      prefixes: VendorPrefix::WebKit,
+    };
+
+    prefix_test_with_options(
+      r#"
+      .foo {
+        touch-callout: none;
+      }
+    "#,
       indoc! {r#"
       .foo {
-        display: -webkit-box;
-        display: -moz-box;
-        display: -webkit-flex;
-        display: -ms-flexbox;
-        display: flex;
+        -webkit-touch-callout: none;
+        touch-callout: none;
       }
-      "#},
-      Browsers {
-        safari: Some(4 << 16),
-        firefox: Some(14 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(13 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![touch_callout()],
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
-      ".foo{ display: flex; display: -webkit-box; }",
+
+    prefix_test_with_options(
+      r#"
+      .foo {
+        touch-callout: none;
+      }
+    "#,
       indoc! {r#"
       .foo {
-        display: -webkit-box;
+        touch-callout: none;
       }
-      "#},
-      Browsers {
-        safari: Some(4 << 16),
-        firefox: Some(14 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(15 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![touch_callout()],
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
-      ".foo{ display: -webkit-box; display: flex; }",
+
+    prefix_test_with_options(
+      r#"
+      .foo {
+        touch-callout: default;
+      }
+    "#,
       indoc! {r#"
       .foo {
-        display: -webkit-box;
-        display: -moz-box;
-        display: -webkit-flex;
-        display: -ms-flexbox;
-        display: flex;
+        touch-callout: default;
       }
-      "#},
-      Browsers {
-        safari: Some(4 << 16),
-        firefox: Some(14 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(13 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![touch_callout()],
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        display: -webkit-box;
-        display: -moz-box;
-        display: -webkit-flex;
-        display: -ms-flexbox;
-        display: flex;
+        -webkit-touch-callout: none;
+        touch-callout: none;
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        display: flex;
+        -webkit-touch-callout: none;
+        touch-callout: none;
       }
-      "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(13 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![touch_callout()],
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        display: -webkit-box;
-        display: flex;
-        display: -moz-box;
-        display: -webkit-flex;
-        display: -ms-flexbox;
+        text-stroke: 1px red;
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        display: -moz-box;
-        display: -webkit-flex;
-        display: -ms-flexbox;
+        -webkit-text-stroke: 1px red;
+        -moz-text-stroke: 1px red;
+        text-stroke: 1px red;
       }
-      "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
-    prefix_test(
-      ".foo{ display: inline-flex }",
-      indoc! {r#"
-      .foo {
-        display: -webkit-inline-box;
-        display: -moz-inline-box;
-        display: -webkit-inline-flex;
-        display: -ms-inline-flexbox;
-        display: inline-flex;
-      }
-      "#},
-      Browsers {
-        safari: Some(4 << 16),
-        firefox: Some(14 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(100 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![PrefixRule {
+          property: "text-stroke".into(),
+          values: vec![],
+          browsers: None,
+          prefixes: VendorPrefix::WebKit | VendorPrefix::Moz,
+        }],
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        display: -webkit-inline-box;
-        display: -moz-inline-box;
-        display: -webkit-inline-flex;
-        display: -ms-inline-flexbox;
-        display: inline-flex;
+        user-select: none;
+        mask-image: linear-gradient(red, blue);
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        display: inline-flex;
+        -webkit-user-select: none;
+        user-select: none;
+        -webkit-mask-image: linear-gradient(red, #00f);
+        mask-image: linear-gradient(red, #00f);
       }
-      "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(120 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![
+          PrefixRule {
+            property: "user-select".into(),
+            values: vec![],
+            browsers: Some(Browsers {
+              chrome: Some(130 << 16),
+              ..Browsers::default()
+            }),
+            prefixes: VendorPrefix::WebKit,
+          },
+          PrefixRule {
+            property: "mask-image".into(),
+            values: vec!["linear-gradient(*".into()],
+            browsers: None,
+            prefixes: VendorPrefix::WebKit,
+          },
+        ],
+        ..MinifyOptions::default()
       },
     );
   }
 
   #[test]
-  fn test_visibility() {
-    minify_test(".foo { visibility: visible }", ".foo{visibility:visible}");
-    minify_test(".foo { visibility: hidden }", ".foo{visibility:hidden}");
-    minify_test(".foo { visibility: collapse }", ".foo{visibility:collapse}");
-    minify_test(".foo { visibility: Visible }", ".foo{visibility:visible}");
-  }
-
-  #[test]
-  fn test_text_transform() {
-    minify_test(".foo { text-transform: uppercase }", ".foo{text-transform:uppercase}");
-    minify_test(".foo { text-transform: lowercase }", ".foo{text-transform:lowercase}");
-    minify_test(".foo { text-transform: capitalize }", ".foo{text-transform:capitalize}");
-    minify_test(".foo { text-transform: none }", ".foo{text-transform:none}");
-    minify_test(".foo { text-transform: full-width }", ".foo{text-transform:full-width}");
-    minify_test(
-      ".foo { text-transform: full-size-kana }",
-      ".foo{text-transform:full-size-kana}",
-    );
-    minify_test(
-      ".foo { text-transform: uppercase full-width }",
-      ".foo{text-transform:uppercase full-width}",
-    );
-    minify_test(
-      ".foo { text-transform: full-width uppercase }",
-      ".foo{text-transform:uppercase full-width}",
-    );
-    minify_test(
-      ".foo { text-transform: uppercase full-width full-size-kana }",
-      ".foo{text-transform:uppercase full-width full-size-kana}",
-    );
-    minify_test(
-      ".foo { text-transform: full-width uppercase full-size-kana }",
-      ".foo{text-transform:uppercase full-width full-size-kana}",
-    );
-  }
-
-  #[test]
-  fn test_whitespace() {
-    minify_test(".foo { white-space: normal }", ".foo{white-space:normal}");
-    minify_test(".foo { white-space: pre }", ".foo{white-space:pre}");
-    minify_test(".foo { white-space: nowrap }", ".foo{white-space:nowrap}");
-    minify_test(".foo { white-space: pre-wrap }", ".foo{white-space:pre-wrap}");
-    minify_test(".foo { white-space: break-spaces }", ".foo{white-space:break-spaces}");
-    minify_test(".foo { white-space: pre-line }", ".foo{white-space:pre-line}");
-    minify_test(".foo { white-space: NoWrAp }", ".foo{white-space:nowrap}");
-  }
-
-  #[test]
-  fn test_tab_size() {
-    minify_test(".foo { tab-size: 8 }", ".foo{tab-size:8}");
-    minify_test(".foo { tab-size: 4px }", ".foo{tab-size:4px}");
-    minify_test(".foo { -moz-tab-size: 4px }", ".foo{-moz-tab-size:4px}");
-    minify_test(".foo { -o-tab-size: 4px }", ".foo{-o-tab-size:4px}");
-    prefix_test(
-      ".foo{ tab-size: 4 }",
+  fn test_prefix_policies() {
+    prefix_test_with_options(
+      r#"
+      .foo {
+        text-size-adjust: none;
+      }
+    "#,
       indoc! {r#"
       .foo {
-        -moz-tab-size: 4;
-        -o-tab-size: 4;
-        tab-size: 4;
+        -webkit-text-size-adjust: none;
+        -moz-text-size-adjust: none;
+        -ms-text-size-adjust: none;
+        text-size-adjust: none;
       }
-      "#},
-      Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(50 << 16),
-        opera: Some(12 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(110 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("text-size-adjust".into(), PrefixPolicy::Always)]),
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        -moz-tab-size: 4;
-        -o-tab-size: 4;
-        tab-size: 4;
+        appearance: none;
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        tab-size: 4;
+        appearance: none;
       }
-      "#},
-      Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(94 << 16),
-        opera: Some(30 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          firefox: Some(50 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("appearance".into(), PrefixPolicy::Never)]),
+        ..MinifyOptions::default()
       },
     );
-  }
-
-  #[test]
-  fn test_word_break() {
-    minify_test(".foo { word-break: normal }", ".foo{word-break:normal}");
-    minify_test(".foo { word-break: keep-all }", ".foo{word-break:keep-all}");
-    minify_test(".foo { word-break: break-all }", ".foo{word-break:break-all}");
-    minify_test(".foo { word-break: break-word }", ".foo{word-break:break-word}");
-  }
-
-  #[test]
-  fn test_line_break() {
-    minify_test(".foo { line-break: auto }", ".foo{line-break:auto}");
-    minify_test(".foo { line-break: Loose }", ".foo{line-break:loose}");
-    minify_test(".foo { line-break: anywhere }", ".foo{line-break:anywhere}");
-  }
-
-  #[test]
-  fn test_wrap() {
-    minify_test(".foo { overflow-wrap: nOrmal }", ".foo{overflow-wrap:normal}");
-    minify_test(".foo { overflow-wrap: break-Word }", ".foo{overflow-wrap:break-word}");
-    minify_test(".foo { overflow-wrap: Anywhere }", ".foo{overflow-wrap:anywhere}");
-    minify_test(".foo { word-wrap: Normal }", ".foo{word-wrap:normal}");
-    minify_test(".foo { word-wrap: Break-wOrd }", ".foo{word-wrap:break-word}");
-    minify_test(".foo { word-wrap: Anywhere }", ".foo{word-wrap:anywhere}");
-  }
 
-  #[test]
-  fn test_hyphens() {
-    minify_test(".foo { hyphens: manual }", ".foo{hyphens:manual}");
-    minify_test(".foo { hyphens: auto }", ".foo{hyphens:auto}");
-    minify_test(".foo { hyphens: none }", ".foo{hyphens:none}");
-    minify_test(".foo { -webkit-hyphens: manual }", ".foo{-webkit-hyphens:manual}");
-    minify_test(".foo { -moz-hyphens: manual }", ".foo{-moz-hyphens:manual}");
-    minify_test(".foo { -ms-hyphens: manual }", ".foo{-ms-hyphens:manual}");
-    prefix_test(
-      ".foo{ hyphens: manual }",
+    prefix_test_with_options(
+      r#"
+      .foo {
+        -webkit-appearance: none;
+        appearance: var(--appearance);
+      }
+    "#,
       indoc! {r#"
       .foo {
-        -webkit-hyphens: manual;
-        -moz-hyphens: manual;
-        -ms-hyphens: manual;
-        hyphens: manual;
+        appearance: var(--appearance);
       }
-      "#},
-      Browsers {
-        safari: Some(14 << 16),
-        firefox: Some(40 << 16),
-        ie: Some(10 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("appearance".into(), PrefixPolicy::Never)]),
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        -webkit-hyphens: manual;
-        -moz-hyphens: manual;
-        -ms-hyphens: manual;
-        hyphens: manual;
+        -webkit-appearance: none;
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        -webkit-hyphens: manual;
-        hyphens: manual;
+        -webkit-appearance: none;
       }
-      "#},
-      Browsers {
-        safari: Some(14 << 16),
-        chrome: Some(88 << 16),
-        firefox: Some(88 << 16),
-        edge: Some(79 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("appearance".into(), PrefixPolicy::Never)]),
+        ..MinifyOptions::default()
       },
     );
-    prefix_test(
+
+    prefix_test_with_options(
       r#"
       .foo {
-        -webkit-hyphens: manual;
-        -moz-hyphens: manual;
-        -ms-hyphens: manual;
-        hyphens: manual;
+        appearance: none;
       }
-      "#,
+    "#,
       indoc! {r#"
       .foo {
-        hyphens: manual;
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        appearance: none;
       }
-      "#},
-      Browsers {
-        chrome: Some(88 << 16),
-        firefox: Some(88 << 16),
-        edge: Some(79 << 16),
-        ..Browsers::default()
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(8 << 16),
+          firefox: Some(50 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("appearance".into(), PrefixPolicy::Auto)]),
+        ..MinifyOptions::default()
+      },
+    );
+
+    prefix_test_with_options(
+      r#"
+      .foo {
+        -webkit-overscroll-behavior: contain;
+        overscroll-behavior: contain;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overscroll-behavior: contain;
+      }
+    "#},
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(60 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_policies: HashMap::from([("overscroll-behavior".into(), PrefixPolicy::Never)]),
+        ..MinifyOptions::default()
       },
     );
+
+    let mut stylesheet = StyleSheet::parse(
+      r#"
+      .foo {
+        overscroll-behavior: contain;
+      }
+    "#,
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        targets: Browsers {
+          chrome: Some(110 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        prefix_rules: vec![PrefixRule {
+          property: "overscroll-behavior".into(),
+          values: vec![],
+          browsers: Some(Browsers {
+            chrome: Some(63 << 16),
+            ..Browsers::default()
+          }),
+          prefixes: VendorPrefix::WebKit,
+        }],
+        prefix_policies: [("overscroll-behavior".into(), PrefixPolicy::Always)].into_iter().collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      res.code,
+      indoc! {r#"
+      .foo {
+        -webkit-overscroll-behavior: contain;
+        overscroll-behavior: contain;
+      }
+    "#}
+    );
   }
 
   #[test]
-  fn test_text_align() {
-    minify_test(".foo { text-align: left }", ".foo{text-align:left}");
-    minify_test(".foo { text-align: Left }", ".foo{text-align:left}");
-    minify_test(".foo { text-align: END }", ".foo{text-align:end}");
-    minify_test(".foo { text-align: left }", ".foo{text-align:left}");
+  fn test_remove_obsolete_prefixes() {
+    fn obsolete_prefixes_test(source: &str, expected: &str, targets: Targets, expected_bytes: usize) {
+      let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      let bytes = stylesheet.remove_obsolete_prefixes(targets);
+      let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+      assert_eq!(res.code, expected);
+      assert_eq!(bytes, expected_bytes);
+    }
 
-    prefix_test(
+    let chrome = Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
+
+    obsolete_prefixes_test(
       r#"
       .foo {
-        text-align: start;
+        -webkit-transition: opacity 200ms;
+        -moz-transition: opacity 200ms;
+        transition: opacity 200ms;
       }
     "#,
       indoc! {r#"
-      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        text-align: left;
+      .foo {
+        transition: opacity .2s;
       }
+    "#},
+      chrome.into(),
+      59,
+    );
 
-      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        text-align: right;
+    obsolete_prefixes_test(
+      r#"
+      .foo {
+        transition: opacity 200ms;
+        -webkit-transition: opacity 200ms;
       }
-    "#
-      },
-      Browsers {
-        safari: Some(2 << 16),
-        ..Browsers::default()
-      },
+    "#,
+      indoc! {r#"
+      .foo {
+        transition: opacity .2s;
+        -webkit-transition: opacity .2s;
+      }
+    "#},
+      chrome.into(),
+      0,
     );
 
-    prefix_test(
+    obsolete_prefixes_test(
       r#"
       .foo {
-        text-align: end;
+        -webkit-transition: opacity 500ms;
+        transition: opacity 200ms;
       }
     "#,
       indoc! {r#"
-      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        text-align: right;
+      .foo {
+        -webkit-transition: opacity .5s;
+        transition: opacity .2s;
       }
+    "#},
+      chrome.into(),
+      0,
+    );
 
-      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        text-align: left;
+    obsolete_prefixes_test(
+      r#"
+      .foo {
+        -webkit-border-radius: 5px;
+        -webkit-box-orient: vertical;
       }
-    "#
-      },
-      Browsers {
-        safari: Some(2 << 16),
-        ..Browsers::default()
-      },
+    "#,
+      indoc! {r#"
+      .foo {
+        border-radius: 5px;
+        -webkit-box-orient: vertical;
+      }
+    "#},
+      chrome.into(),
+      8,
     );
 
-    prefix_test(
+    obsolete_prefixes_test(
       r#"
       .foo {
-        text-align: start;
+        -webkit-transition: opacity 200ms;
+        transition: opacity 200ms;
       }
     "#,
       indoc! {r#"
       .foo {
-        text-align: start;
+        -webkit-transition: opacity .2s;
+        transition: opacity .2s;
       }
-    "#
-      },
+    "#},
       Browsers {
-        safari: Some(14 << 16),
+        safari: Some(5 << 16),
         ..Browsers::default()
-      },
+      }
+      .into(),
+      0,
     );
 
-    prefix_test(
+    obsolete_prefixes_test(
       r#"
-      .foo > .bar {
-        text-align: start;
+      .foo::-webkit-input-placeholder {
+        color: red;
+      }
+      .foo::-moz-placeholder {
+        color: red;
+      }
+      .foo::placeholder {
+        color: red;
+      }
+      .bar:-webkit-any-link {
+        color: red;
       }
     "#,
       indoc! {r#"
-      .foo > .bar:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        text-align: left;
+      .foo::placeholder {
+        color: red;
       }
 
-      .foo > .bar:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        text-align: right;
+      .bar:any-link {
+        color: red;
       }
-    "#
-      },
-      Browsers {
-        safari: Some(2 << 16),
-        ..Browsers::default()
-      },
+    "#},
+      chrome.into(),
+      83,
     );
 
-    prefix_test(
+    obsolete_prefixes_test(
       r#"
-      .foo:after {
-        text-align: start;
+      @-webkit-keyframes foo {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
+      @keyframes foo {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
+      @-webkit-keyframes bar {
+        from { -webkit-transform: scale(0) }
+        to { -webkit-transform: scale(1) }
       }
     "#,
       indoc! {r#"
-      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))):after {
-        text-align: left;
+      @keyframes foo {
+        from {
+          opacity: 0;
+        }
+
+        to {
+          opacity: 1;
+        }
       }
 
-      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)):after {
-        text-align: right;
+      @keyframes bar {
+        from {
+          transform: scale(0);
+        }
+
+        to {
+          transform: scale(1);
+        }
       }
-    "#
-      },
-      Browsers {
-        safari: Some(2 << 16),
-        ..Browsers::default()
-      },
+    "#},
+      chrome.into(),
+      74,
     );
 
-    prefix_test(
+    obsolete_prefixes_test(
       r#"
-      .foo:hover {
-        text-align: start;
+      .foo {
+        -webkit-border-radius: 5px;
       }
     "#,
       indoc! {r#"
-      .foo:hover:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        text-align: left;
-      }
-
-      .foo:hover:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        text-align: right;
+      .foo {
+        -webkit-border-radius: 5px;
       }
-    "#
-      },
-      Browsers {
-        safari: Some(2 << 16),
-        ..Browsers::default()
-      },
+    "#},
+      Targets::default(),
+      0,
     );
   }
 
   #[test]
-  fn test_text_align_last() {
-    minify_test(".foo { text-align-last: left }", ".foo{text-align-last:left}");
-    minify_test(".foo { text-align-last: justify }", ".foo{text-align-last:justify}");
+  fn test_feature_toggles() {
+    let mut targets: Targets = Browsers {
+      chrome: Some(120 << 16),
+      ..Browsers::default()
+    }
+    .into();
+    targets
+      .set_feature_toggles("nesting: compile, logical-properties: compile")
+      .unwrap();
     prefix_test(
-      ".foo{ text-align-last: left }",
+      ".foo { .bar { margin-inline-start: 10px } }",
       indoc! {r#"
-      .foo {
-        -moz-text-align-last: left;
-        text-align-last: left;
+      .foo .bar:dir(ltr) {
+        margin-left: 10px;
       }
-      "#},
-      Browsers {
-        firefox: Some(40 << 16),
-        ..Browsers::default()
-      },
+
+      .foo .bar:dir(rtl) {
+        margin-right: 10px;
+      }
+    "#},
+      targets,
     );
+
+    let mut targets: Targets = Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    }
+    .into();
+    targets.set_feature_toggles(" lab-colors : preserve ,").unwrap();
     prefix_test(
-      r#"
-      .foo {
-        -moz-text-align-last: left;
-        text-align-last: left;
-      }
-      "#,
+      ".foo { color: lab(40% 56.6 39) }",
       indoc! {r#"
       .foo {
-        text-align-last: left;
+        color: lab(40% 56.6 39);
       }
-      "#},
-      Browsers {
-        firefox: Some(88 << 16),
-        ..Browsers::default()
-      },
+    "#},
+      targets,
     );
-  }
 
-  #[test]
-  fn test_text_justify() {
-    minify_test(".foo { text-justify: auto }", ".foo{text-justify:auto}");
-    minify_test(".foo { text-justify: inter-word }", ".foo{text-justify:inter-word}");
-  }
+    targets.set_feature_toggles("lab-colors: compile").unwrap();
+    assert!(targets.include.contains(Features::LabColors));
+    assert!(!targets.exclude.contains(Features::LabColors));
 
-  #[test]
-  fn test_word_spacing() {
-    minify_test(".foo { word-spacing: normal }", ".foo{word-spacing:normal}");
-    minify_test(".foo { word-spacing: 3px }", ".foo{word-spacing:3px}");
+    targets.set_feature_toggles("media-queries: preserve").unwrap();
+    assert!(targets.exclude.contains(Features::MediaQueries));
+
+    assert_eq!(
+      targets.set_feature_toggles("oklch: preserve"),
+      Err(FeatureToggleError::UnknownFeature("oklch".into()))
+    );
+    assert_eq!(
+      targets.set_feature_toggles("nesting: maybe"),
+      Err(FeatureToggleError::InvalidToggle("nesting: maybe".into()))
+    );
+    assert_eq!(
+      targets.set_feature_toggles("nesting"),
+      Err(FeatureToggleError::InvalidToggle("nesting".into()))
+    );
   }
 
   #[test]
-  fn test_letter_spacing() {
-    minify_test(".foo { letter-spacing: normal }", ".foo{letter-spacing:normal}");
-    minify_test(".foo { letter-spacing: 3px }", ".foo{letter-spacing:3px}");
-  }
+  fn test_supports_guards() {
+    let chrome90 = Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    };
+    prefix_test_with_options(
+      ".a { color: lab(40% 56.6 39); width: 100px }",
+      indoc! {r#"
+        .a {
+          color: #b32323;
+          width: 100px;
+        }
 
-  #[test]
-  fn test_text_indent() {
-    minify_test(".foo { text-indent: 20px }", ".foo{text-indent:20px}");
-    minify_test(".foo { text-indent: 10% }", ".foo{text-indent:10%}");
-    minify_test(".foo { text-indent: 3em hanging }", ".foo{text-indent:3em hanging}");
-    minify_test(".foo { text-indent: 3em each-line }", ".foo{text-indent:3em each-line}");
-    minify_test(
-      ".foo { text-indent: 3em hanging each-line }",
-      ".foo{text-indent:3em hanging each-line}",
+        @supports (color: lab(40% 56.6 39)) {
+          .a {
+            color: lab(40% 56.6 39);
+          }
+        }
+      "#},
+      MinifyOptions {
+        targets: chrome90.into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
     );
-    minify_test(
-      ".foo { text-indent: 3em each-line hanging }",
-      ".foo{text-indent:3em hanging each-line}",
+    prefix_test_with_options(
+      ".a { color: lab(40% 56.6 39) !important; background: lab(40% 56.6 39) }",
+      indoc! {r#"
+        .a {
+          background: #b32323;
+          color: #b32323 !important;
+        }
+
+        @supports (background: lab(40% 56.6 39)) {
+          .a {
+            background: lab(40% 56.6 39);
+          }
+        }
+
+        @supports (color: lab(40% 56.6 39)) {
+          .a {
+            color: lab(40% 56.6 39) !important;
+          }
+        }
+      "#},
+      MinifyOptions {
+        targets: chrome90.into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
     );
-    minify_test(
-      ".foo { text-indent: each-line 3em hanging }",
-      ".foo{text-indent:3em hanging each-line}",
+    prefix_test_with_options(
+      ".a { color: red; color: lab(40% 56.6 39) } .b { color: lab(40% 56.6 39) }",
+      indoc! {r#"
+        .a {
+          color: red;
+        }
+
+        @supports (color: lab(40% 56.6 39)) {
+          .a {
+            color: lab(40% 56.6 39);
+          }
+        }
+
+        .b {
+          color: #b32323;
+        }
+
+        @supports (color: lab(40% 56.6 39)) {
+          .b {
+            color: lab(40% 56.6 39);
+          }
+        }
+      "#},
+      MinifyOptions {
+        targets: chrome90.into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
     );
-    minify_test(
-      ".foo { text-indent: each-line hanging 3em }",
-      ".foo{text-indent:3em hanging each-line}",
+    prefix_test_with_options(
+      ".a, .b:focus-visible { color: lab(40% 56.6 39) }",
+      indoc! {r#"
+        .a {
+          color: color(display-p3 .643308 .192455 .167712);
+        }
+
+        @supports (color: lab(40% 56.6 39)) {
+          .a {
+            color: lab(40% 56.6 39);
+          }
+        }
+
+        @supports selector(.b:focus-visible) {
+          .b:focus-visible {
+            color: color(display-p3 .643308 .192455 .167712);
+          }
+        }
+
+        @supports (color: lab(40% 56.6 39)) {
+          .b:focus-visible {
+            color: lab(40% 56.6 39);
+          }
+        }
+      "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(14 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ".a { color: lab(40% 56.6 39) }",
+      indoc! {r#"
+        .a {
+          color: lab(40% 56.6 39);
+        }
+      "#},
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(120 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        supports_guards: true,
+        ..MinifyOptions::default()
+      },
     );
   }
 
   #[test]
-  fn test_text_size_adjust() {
-    minify_test(".foo { text-size-adjust: none }", ".foo{text-size-adjust:none}");
-    minify_test(".foo { text-size-adjust: auto }", ".foo{text-size-adjust:auto}");
-    minify_test(".foo { text-size-adjust: 80% }", ".foo{text-size-adjust:80%}");
-    prefix_test(
+  fn test_unsupported_features() {
+    fn unsupported_features_test(source: &str, targets: Browsers, expected: Vec<(&str, u32)>) {
+      let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      let warnings = Arc::new(RwLock::new(Vec::new()));
+      stylesheet
+        .minify(MinifyOptions {
+          targets: targets.into(),
+          unsupported_features: UnsupportedFeatureMode::Warn,
+          warnings: Some(warnings.clone()),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let warnings = warnings.read().unwrap();
+      let warnings = warnings
+        .iter()
+        .map(|w| (w.kind.to_string(), w.loc.as_ref().unwrap().line))
+        .collect::<Vec<_>>();
+      assert_eq!(
+        warnings,
+        expected.into_iter().map(|(w, l)| (w.to_owned(), l)).collect::<Vec<_>>()
+      );
+    }
+
+    let safari14 = Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    };
+    unsupported_features_test(
       r#"
-      .foo {
-        text-size-adjust: none;
+      .a:has(> img) {
+        color: red;
       }
-    "#,
-      indoc! {r#"
-      .foo {
-        -webkit-text-size-adjust: none;
-        -moz-text-size-adjust: none;
-        -ms-text-size-adjust: none;
-        text-size-adjust: none;
+
+      .b:where(.c, :focus-visible) {
+        color: red;
       }
-    "#},
+
+      .d:focus-within {
+        color: red;
+      }
+      "#,
+      safari14,
+      vec![
+        (
+          ":has() is not supported by the browser targets: safari 14 (supported since 15.4)",
+          1,
+        ),
+        (
+          ":focus-visible is not supported by the browser targets: safari 14 (supported since 15.4)",
+          5,
+        ),
+      ],
+    );
+    unsupported_features_test(
+      r#"
+      @container (width > 100px) {
+        .a:has(.b) {
+          color: red;
+        }
+      }
+      "#,
       Browsers {
-        ios_saf: Some(16 << 16),
-        edge: Some(15 << 16),
-        firefox: Some(20 << 16),
+        safari: Some(15 << 16),
+        ie: Some(11 << 16),
         ..Browsers::default()
       },
+      vec![
+        (
+          "@container is not supported by the browser targets: ie 11, safari 15 (supported since 16)",
+          1,
+        ),
+        (
+          ":has() is not supported by the browser targets: ie 11, safari 15 (supported since 15.4)",
+          2,
+        ),
+      ],
     );
-    prefix_test(
+    #[cfg(feature = "visitor")]
+    unsupported_features_test(
       r#"
-      .foo {
-        -webkit-text-size-adjust: none;
-        -moz-text-size-adjust: none;
-        -ms-text-size-adjust: none;
-        text-size-adjust: none;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-size-adjust: none;
+      .a {
+        height: 100dvh;
+        padding-top: env(safe-area-inset-top);
+        color: lab(50% 20 30);
       }
-    "#},
+      "#,
+      Browsers {
+        safari: Some(11 << 16),
+        ..Browsers::default()
+      },
+      vec![
+        (
+          "env() is not supported by the browser targets: safari 11 (supported since 11.1)",
+          1,
+        ),
+        (
+          "dvh unit is not supported by the browser targets: safari 11 (supported since 15.4)",
+          1,
+        ),
+      ],
+    );
+    unsupported_features_test(
+      ".a:has(.b) { color: red } .c:focus-visible { color: red }",
       Browsers {
         chrome: Some(110 << 16),
         ..Browsers::default()
       },
+      vec![],
+    );
+
+    let mut stylesheet = StyleSheet::parse(".foo:has(.bar) { color: red }", ParserOptions::default()).unwrap();
+    let res = stylesheet.minify(MinifyOptions {
+      targets: safari14.into(),
+      unsupported_features: UnsupportedFeatureMode::Error,
+      ..MinifyOptions::default()
+    });
+    assert_eq!(
+      res,
+      Err(Error {
+        kind: MinifyErrorKind::UnsupportedFeature {
+          feature: ":has()".into(),
+          id: "has-selector".into(),
+          browsers: vec![UnsupportedBrowser {
+            browser: "safari".into(),
+            version: "14".into(),
+            supported_since: Some("15.4".into()),
+          }],
+        },
+        loc: Some(ErrorLocation {
+          filename: "".into(),
+          line: 0,
+          column: 1,
+        }),
+      })
     );
+
+    let mut stylesheet = StyleSheet::parse(".foo:has(.bar) { color: red }", ParserOptions::default()).unwrap();
+    let res = stylesheet.minify(MinifyOptions {
+      targets: safari14.into(),
+      ..MinifyOptions::default()
+    });
+    assert_eq!(res, Ok(()));
   }
 
   #[test]
-  fn test_text_decoration() {
-    minify_test(".foo { text-decoration-line: none }", ".foo{text-decoration-line:none}");
+  fn test_display() {
+    minify_test(".foo { display: block }", ".foo{display:block}");
+    minify_test(".foo { display: block flow }", ".foo{display:block}");
+    minify_test(".foo { display: flow-root }", ".foo{display:flow-root}");
+    minify_test(".foo { display: block flow-root }", ".foo{display:flow-root}");
+    minify_test(".foo { display: inline }", ".foo{display:inline}");
+    minify_test(".foo { display: inline flow }", ".foo{display:inline}");
+    minify_test(".foo { display: inline-block }", ".foo{display:inline-block}");
+    minify_test(".foo { display: inline flow-root }", ".foo{display:inline-block}");
+    minify_test(".foo { display: run-in }", ".foo{display:run-in}");
+    minify_test(".foo { display: run-in flow }", ".foo{display:run-in}");
+    minify_test(".foo { display: list-item }", ".foo{display:list-item}");
+    minify_test(".foo { display: block flow list-item }", ".foo{display:list-item}");
+    minify_test(".foo { display: inline list-item }", ".foo{display:inline list-item}");
     minify_test(
-      ".foo { text-decoration-line: underline }",
-      ".foo{text-decoration-line:underline}",
+      ".foo { display: inline flow list-item }",
+      ".foo{display:inline list-item}",
     );
+    minify_test(".foo { display: flex }", ".foo{display:flex}");
+    minify_test(".foo { display: block flex }", ".foo{display:flex}");
+    minify_test(".foo { display: inline-flex }", ".foo{display:inline-flex}");
+    minify_test(".foo { display: inline flex }", ".foo{display:inline-flex}");
+    minify_test(".foo { display: grid }", ".foo{display:grid}");
+    minify_test(".foo { display: block grid }", ".foo{display:grid}");
+    minify_test(".foo { display: inline-grid }", ".foo{display:inline-grid}");
+    minify_test(".foo { display: inline grid }", ".foo{display:inline-grid}");
+    minify_test(".foo { display: ruby }", ".foo{display:ruby}");
+    minify_test(".foo { display: inline ruby }", ".foo{display:ruby}");
+    minify_test(".foo { display: block ruby }", ".foo{display:block ruby}");
+    minify_test(".foo { display: table }", ".foo{display:table}");
+    minify_test(".foo { display: block table }", ".foo{display:table}");
+    minify_test(".foo { display: inline-table }", ".foo{display:inline-table}");
+    minify_test(".foo { display: inline table }", ".foo{display:inline-table}");
+    minify_test(".foo { display: table-row-group }", ".foo{display:table-row-group}");
+    minify_test(".foo { display: contents }", ".foo{display:contents}");
+    minify_test(".foo { display: none }", ".foo{display:none}");
+    minify_test(".foo { display: -webkit-flex }", ".foo{display:-webkit-flex}");
+    minify_test(".foo { display: -ms-flexbox }", ".foo{display:-ms-flexbox}");
+    minify_test(".foo { display: -webkit-box }", ".foo{display:-webkit-box}");
+    minify_test(".foo { display: -moz-box }", ".foo{display:-moz-box}");
     minify_test(
-      ".foo { text-decoration-line: overline }",
-      ".foo{text-decoration-line:overline}",
+      ".foo { display: -webkit-flex; display: -moz-box; display: flex }",
+      ".foo{display:-webkit-flex;display:-moz-box;display:flex}",
     );
     minify_test(
-      ".foo { text-decoration-line: line-through }",
-      ".foo{text-decoration-line:line-through}",
+      ".foo { display: -webkit-flex; display: flex; display: -moz-box }",
+      ".foo{display:-webkit-flex;display:flex;display:-moz-box}",
     );
+    minify_test(".foo { display: flex; display: grid }", ".foo{display:grid}");
     minify_test(
-      ".foo { text-decoration-line: blink }",
-      ".foo{text-decoration-line:blink}",
+      ".foo { display: -webkit-inline-flex; display: -moz-inline-box; display: inline-flex }",
+      ".foo{display:-webkit-inline-flex;display:-moz-inline-box;display:inline-flex}",
     );
     minify_test(
-      ".foo { text-decoration-line: underline overline }",
-      ".foo{text-decoration-line:underline overline}",
+      ".foo { display: flex; display: var(--grid); }",
+      ".foo{display:flex;display:var(--grid)}",
     );
-    minify_test(
-      ".foo { text-decoration-line: overline underline }",
-      ".foo{text-decoration-line:underline overline}",
+    prefix_test(
+      ".foo{ display: flex }",
+      indoc! {r#"
+      .foo {
+        display: -webkit-box;
+        display: -moz-box;
+        display: -webkit-flex;
+        display: -ms-flexbox;
+        display: flex;
+      }
+      "#},
+      Browsers {
+        safari: Some(4 << 16),
+        firefox: Some(14 << 16),
+        ie: Some(10 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { text-decoration-line: overline line-through underline }",
-      ".foo{text-decoration-line:underline overline line-through}",
-    );
-    minify_test(
-      ".foo { text-decoration-line: spelling-error }",
-      ".foo{text-decoration-line:spelling-error}",
-    );
-    minify_test(
-      ".foo { text-decoration-line: grammar-error }",
-      ".foo{text-decoration-line:grammar-error}",
-    );
-    minify_test(
-      ".foo { -webkit-text-decoration-line: overline underline }",
-      ".foo{-webkit-text-decoration-line:underline overline}",
-    );
-    minify_test(
-      ".foo { -moz-text-decoration-line: overline underline }",
-      ".foo{-moz-text-decoration-line:underline overline}",
-    );
-
-    minify_test(
-      ".foo { text-decoration-style: solid }",
-      ".foo{text-decoration-style:solid}",
-    );
-    minify_test(
-      ".foo { text-decoration-style: dotted }",
-      ".foo{text-decoration-style:dotted}",
-    );
-    minify_test(
-      ".foo { -webkit-text-decoration-style: solid }",
-      ".foo{-webkit-text-decoration-style:solid}",
-    );
-
-    minify_test(
-      ".foo { text-decoration-color: yellow }",
-      ".foo{text-decoration-color:#ff0}",
-    );
-    minify_test(
-      ".foo { -webkit-text-decoration-color: yellow }",
-      ".foo{-webkit-text-decoration-color:#ff0}",
-    );
-
-    minify_test(".foo { text-decoration: none }", ".foo{text-decoration:none}");
-    minify_test(
-      ".foo { text-decoration: underline dotted }",
-      ".foo{text-decoration:underline dotted}",
-    );
-    minify_test(
-      ".foo { text-decoration: underline dotted yellow }",
-      ".foo{text-decoration:underline dotted #ff0}",
-    );
-    minify_test(
-      ".foo { text-decoration: yellow dotted underline }",
-      ".foo{text-decoration:underline dotted #ff0}",
-    );
-    minify_test(
-      ".foo { text-decoration: underline overline dotted yellow }",
-      ".foo{text-decoration:underline overline dotted #ff0}",
-    );
-    minify_test(
-      ".foo { -webkit-text-decoration: yellow dotted underline }",
-      ".foo{-webkit-text-decoration:underline dotted #ff0}",
-    );
-    minify_test(
-      ".foo { -moz-text-decoration: yellow dotted underline }",
-      ".foo{-moz-text-decoration:underline dotted #ff0}",
-    );
-
-    test(
-      r#"
-      .foo {
-        text-decoration-line: underline;
-        text-decoration-style: dotted;
-        text-decoration-color: yellow;
-        text-decoration-thickness: 2px;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-decoration: underline 2px dotted #ff0;
-      }
-    "#},
-    );
-
-    test(
-      r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-style: dotted;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-decoration: underline dotted;
-      }
-    "#},
-    );
-
-    test(
-      r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-style: var(--style);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-style: var(--style);
-      }
-    "#},
-    );
-
-    test(
-      r#"
-      .foo {
-        -webkit-text-decoration: underline;
-        -webkit-text-decoration-style: dotted;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        -webkit-text-decoration: underline dotted;
-      }
-    "#},
-    );
-
     prefix_test(
-      r#"
-      .foo {
-        text-decoration: underline dotted;
-      }
-    "#,
+      ".foo{ display: flex; display: -webkit-box; }",
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: underline dotted;
-        text-decoration: underline dotted;
+        display: -webkit-box;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(4 << 16),
+        firefox: Some(14 << 16),
+        ie: Some(10 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
-      r#"
-      .foo {
-        text-decoration-line: underline;
-      }
-    "#,
+      ".foo{ display: -webkit-box; display: flex; }",
       indoc! {r#"
       .foo {
-        -webkit-text-decoration-line: underline;
-        -moz-text-decoration-line: underline;
-        text-decoration-line: underline;
+        display: -webkit-box;
+        display: -moz-box;
+        display: -webkit-flex;
+        display: -ms-flexbox;
+        display: flex;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(4 << 16),
+        firefox: Some(14 << 16),
+        ie: Some(10 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        text-decoration-style: dotted;
+        display: -webkit-box;
+        display: -moz-box;
+        display: -webkit-flex;
+        display: -ms-flexbox;
+        display: flex;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration-style: dotted;
-        -moz-text-decoration-style: dotted;
-        text-decoration-style: dotted;
+        display: flex;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        text-decoration-color: yellow;
+        display: -webkit-box;
+        display: flex;
+        display: -moz-box;
+        display: -webkit-flex;
+        display: -ms-flexbox;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration-color: #ff0;
-        -moz-text-decoration-color: #ff0;
-        text-decoration-color: #ff0;
+        display: -moz-box;
+        display: -webkit-flex;
+        display: -ms-flexbox;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
-      r#"
-      .foo {
-        text-decoration: underline;
-      }
-    "#,
+      ".foo{ display: inline-flex }",
       indoc! {r#"
       .foo {
-        text-decoration: underline;
+        display: -webkit-inline-box;
+        display: -moz-inline-box;
+        display: -webkit-inline-flex;
+        display: -ms-inline-flexbox;
+        display: inline-flex;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(4 << 16),
+        firefox: Some(14 << 16),
+        ie: Some(10 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        -webkit-text-decoration: underline dotted;
-        text-decoration: underline dotted;
+        display: -webkit-inline-box;
+        display: -moz-inline-box;
+        display: -webkit-inline-flex;
+        display: -ms-inline-flexbox;
+        display: inline-flex;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: underline dotted;
-        text-decoration: underline dotted;
+        display: inline-flex;
       }
-    "#},
+      "#},
       Browsers {
         safari: Some(14 << 16),
-        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      r#"
-      .foo {
-        text-decoration: double underline;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        -webkit-text-decoration: underline double;
-        text-decoration: underline double;
-      }
-    "#},
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_visibility() {
+    minify_test(".foo { visibility: visible }", ".foo{visibility:visible}");
+    minify_test(".foo { visibility: hidden }", ".foo{visibility:hidden}");
+    minify_test(".foo { visibility: collapse }", ".foo{visibility:collapse}");
+    minify_test(".foo { visibility: Visible }", ".foo{visibility:visible}");
+  }
+
+  #[test]
+  fn test_text_transform() {
+    minify_test(".foo { text-transform: uppercase }", ".foo{text-transform:uppercase}");
+    minify_test(".foo { text-transform: lowercase }", ".foo{text-transform:lowercase}");
+    minify_test(".foo { text-transform: capitalize }", ".foo{text-transform:capitalize}");
+    minify_test(".foo { text-transform: none }", ".foo{text-transform:none}");
+    minify_test(".foo { text-transform: full-width }", ".foo{text-transform:full-width}");
+    minify_test(
+      ".foo { text-transform: full-size-kana }",
+      ".foo{text-transform:full-size-kana}",
+    );
+    minify_test(
+      ".foo { text-transform: uppercase full-width }",
+      ".foo{text-transform:uppercase full-width}",
+    );
+    minify_test(
+      ".foo { text-transform: full-width uppercase }",
+      ".foo{text-transform:uppercase full-width}",
+    );
+    minify_test(
+      ".foo { text-transform: uppercase full-width full-size-kana }",
+      ".foo{text-transform:uppercase full-width full-size-kana}",
+    );
+    minify_test(
+      ".foo { text-transform: full-width uppercase full-size-kana }",
+      ".foo{text-transform:uppercase full-width full-size-kana}",
     );
+  }
+
+  #[test]
+  fn test_whitespace() {
+    minify_test(".foo { white-space: normal }", ".foo{white-space:normal}");
+    minify_test(".foo { white-space: pre }", ".foo{white-space:pre}");
+    minify_test(".foo { white-space: nowrap }", ".foo{white-space:nowrap}");
+    minify_test(".foo { white-space: pre-wrap }", ".foo{white-space:pre-wrap}");
+    minify_test(".foo { white-space: break-spaces }", ".foo{white-space:break-spaces}");
+    minify_test(".foo { white-space: pre-line }", ".foo{white-space:pre-line}");
+    minify_test(".foo { white-space: NoWrAp }", ".foo{white-space:nowrap}");
+  }
 
+  #[test]
+  fn test_tab_size() {
+    minify_test(".foo { tab-size: 8 }", ".foo{tab-size:8}");
+    minify_test(".foo { tab-size: 4px }", ".foo{tab-size:4px}");
+    minify_test(".foo { -moz-tab-size: 4px }", ".foo{-moz-tab-size:4px}");
+    minify_test(".foo { -o-tab-size: 4px }", ".foo{-o-tab-size:4px}");
     prefix_test(
-      r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-style: double;
-      }
-    "#,
+      ".foo{ tab-size: 4 }",
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: underline double;
-        text-decoration: underline double;
+        -moz-tab-size: 4;
+        -o-tab-size: 4;
+        tab-size: 4;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(16 << 16),
+        safari: Some(8 << 16),
+        firefox: Some(50 << 16),
+        opera: Some(12 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        text-decoration: underline;
-        text-decoration-color: red;
+        -moz-tab-size: 4;
+        -o-tab-size: 4;
+        tab-size: 4;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: underline red;
-        text-decoration: underline red;
+        tab-size: 4;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(16 << 16),
+        safari: Some(8 << 16),
+        firefox: Some(94 << 16),
+        opera: Some(30 << 16),
         ..Browsers::default()
       },
     );
+  }
+
+  #[test]
+  fn test_word_break() {
+    minify_test(".foo { word-break: normal }", ".foo{word-break:normal}");
+    minify_test(".foo { word-break: keep-all }", ".foo{word-break:keep-all}");
+    minify_test(".foo { word-break: break-all }", ".foo{word-break:break-all}");
+    minify_test(".foo { word-break: break-word }", ".foo{word-break:break-word}");
+  }
 
+  #[test]
+  fn test_line_break() {
+    minify_test(".foo { line-break: auto }", ".foo{line-break:auto}");
+    minify_test(".foo { line-break: Loose }", ".foo{line-break:loose}");
+    minify_test(".foo { line-break: anywhere }", ".foo{line-break:anywhere}");
+  }
+
+  #[test]
+  fn test_wrap() {
+    minify_test(".foo { overflow-wrap: nOrmal }", ".foo{overflow-wrap:normal}");
+    minify_test(".foo { overflow-wrap: break-Word }", ".foo{overflow-wrap:break-word}");
+    minify_test(".foo { overflow-wrap: Anywhere }", ".foo{overflow-wrap:anywhere}");
+    minify_test(".foo { word-wrap: Normal }", ".foo{word-wrap:normal}");
+    minify_test(".foo { word-wrap: Break-wOrd }", ".foo{word-wrap:break-word}");
+    minify_test(".foo { word-wrap: Anywhere }", ".foo{word-wrap:anywhere}");
+  }
+
+  #[test]
+  fn test_hyphens() {
+    minify_test(".foo { hyphens: manual }", ".foo{hyphens:manual}");
+    minify_test(".foo { hyphens: auto }", ".foo{hyphens:auto}");
+    minify_test(".foo { hyphens: none }", ".foo{hyphens:none}");
+    minify_test(".foo { -webkit-hyphens: manual }", ".foo{-webkit-hyphens:manual}");
+    minify_test(".foo { -moz-hyphens: manual }", ".foo{-moz-hyphens:manual}");
+    minify_test(".foo { -ms-hyphens: manual }", ".foo{-ms-hyphens:manual}");
     prefix_test(
-      r#"
-      .foo {
-        text-decoration: var(--test);
-      }
-    "#,
+      ".foo{ hyphens: manual }",
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: var(--test);
-        text-decoration: var(--test);
+        -webkit-hyphens: manual;
+        -moz-hyphens: manual;
+        -ms-hyphens: manual;
+        hyphens: manual;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(14 << 16),
+        firefox: Some(40 << 16),
+        ie: Some(10 << 16),
         ..Browsers::default()
       },
     );
-
-    minify_test(
-      ".foo { text-decoration-skip-ink: all }",
-      ".foo{text-decoration-skip-ink:all}",
-    );
-    minify_test(
-      ".foo { -webkit-text-decoration-skip-ink: all }",
-      ".foo{-webkit-text-decoration-skip-ink:all}",
-    );
-
     prefix_test(
       r#"
       .foo {
-        text-decoration: lch(50.998% 135.363 338) underline;
+        -webkit-hyphens: manual;
+        -moz-hyphens: manual;
+        -ms-hyphens: manual;
+        hyphens: manual;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration: underline #ee00be;
-        text-decoration: underline #ee00be;
-        -webkit-text-decoration: underline lch(50.998% 135.363 338);
-        text-decoration: underline lch(50.998% 135.363 338);
+        -webkit-hyphens: manual;
+        hyphens: manual;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        safari: Some(14 << 16),
+        chrome: Some(88 << 16),
+        firefox: Some(88 << 16),
+        edge: Some(79 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        text-decoration-color: lch(50.998% 135.363 338);
+        -webkit-hyphens: manual;
+        -moz-hyphens: manual;
+        -ms-hyphens: manual;
+        hyphens: manual;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-text-decoration-color: #ee00be;
-        -moz-text-decoration-color: #ee00be;
-        text-decoration-color: #ee00be;
-        -webkit-text-decoration-color: lch(50.998% 135.363 338);
-        -moz-text-decoration-color: lch(50.998% 135.363 338);
-        text-decoration-color: lch(50.998% 135.363 338);
+        hyphens: manual;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(8 << 16),
-        firefox: Some(30 << 16),
+        chrome: Some(88 << 16),
+        firefox: Some(88 << 16),
+        edge: Some(79 << 16),
         ..Browsers::default()
       },
     );
+  }
+
+  #[test]
+  fn test_text_align() {
+    minify_test(".foo { text-align: left }", ".foo{text-align:left}");
+    minify_test(".foo { text-align: Left }", ".foo{text-align:left}");
+    minify_test(".foo { text-align: END }", ".foo{text-align:end}");
+    minify_test(".foo { text-align: left }", ".foo{text-align:left}");
 
     prefix_test(
       r#"
       .foo {
-        text-decoration: lch(50.998% 135.363 338) var(--style);
+        text-align: start;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-decoration: #ee00be var(--style);
+      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        text-align: left;
       }
 
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          text-decoration: lab(50.998% 125.506 -50.7078) var(--style);
-        }
+      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        text-align: right;
       }
-    "#},
+    "#
+      },
       Browsers {
-        chrome: Some(90 << 16),
+        safari: Some(2 << 16),
         ..Browsers::default()
       },
     );
@@ -14183,17 +15644,21 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        text-decoration: underline 10px;
+        text-align: end;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-thickness: 10px;
+      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        text-align: right;
       }
-    "#},
+
+      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        text-align: left;
+      }
+    "#
+      },
       Browsers {
-        safari: Some(15 << 16),
+        safari: Some(2 << 16),
         ..Browsers::default()
       },
     );
@@ -14201,491 +15666,450 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        text-decoration: underline 10px;
+        text-align: start;
       }
     "#,
       indoc! {r#"
       .foo {
-        text-decoration: underline 10px;
+        text-align: start;
       }
-    "#},
+    "#
+      },
       Browsers {
-        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-      .foo {
-        text-decoration: underline 10%;
+      .foo > .bar {
+        text-align: start;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-decoration: underline;
-        text-decoration-thickness: calc(1em / 10);
+      .foo > .bar:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        text-align: left;
       }
-    "#},
-      Browsers {
-        safari: Some(12 << 16),
-        ..Browsers::default()
-      },
-    );
 
-    prefix_test(
-      r#"
-      .foo {
-        text-decoration: underline 10%;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-decoration: underline 10%;
+      .foo > .bar:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        text-align: right;
       }
-    "#},
+    "#
+      },
       Browsers {
-        firefox: Some(89 << 16),
+        safari: Some(2 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-      .foo {
-        text-decoration-thickness: 10%;
+      .foo:after {
+        text-align: start;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-decoration-thickness: calc(1em / 10);
+      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))):after {
+        text-align: left;
       }
-    "#},
+
+      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)):after {
+        text-align: right;
+      }
+    "#
+      },
       Browsers {
-        safari: Some(12 << 16),
+        safari: Some(2 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
       r#"
-      .foo {
-        text-decoration-thickness: 10%;
+      .foo:hover {
+        text-align: start;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-decoration-thickness: 10%;
+      .foo:hover:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        text-align: left;
       }
-    "#},
+
+      .foo:hover:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        text-align: right;
+      }
+    "#
+      },
       Browsers {
-        firefox: Some(89 << 16),
+        safari: Some(2 << 16),
         ..Browsers::default()
       },
     );
   }
 
   #[test]
-  fn test_text_emphasis() {
-    minify_test(".foo { text-emphasis-style: none }", ".foo{text-emphasis-style:none}");
-    minify_test(
-      ".foo { text-emphasis-style: filled }",
-      ".foo{text-emphasis-style:filled}",
-    );
-    minify_test(".foo { text-emphasis-style: open }", ".foo{text-emphasis-style:open}");
-    minify_test(".foo { text-emphasis-style: dot }", ".foo{text-emphasis-style:dot}");
-    minify_test(
-      ".foo { text-emphasis-style: filled dot }",
-      ".foo{text-emphasis-style:dot}",
-    );
-    minify_test(
-      ".foo { text-emphasis-style: dot filled }",
-      ".foo{text-emphasis-style:dot}",
-    );
-    minify_test(
-      ".foo { text-emphasis-style: open dot }",
-      ".foo{text-emphasis-style:open dot}",
-    );
-    minify_test(
-      ".foo { text-emphasis-style: dot open }",
-      ".foo{text-emphasis-style:open dot}",
-    );
-    minify_test(".foo { text-emphasis-style: \"x\" }", ".foo{text-emphasis-style:\"x\"}");
-
-    minify_test(".foo { text-emphasis-color: yellow }", ".foo{text-emphasis-color:#ff0}");
-
-    minify_test(".foo { text-emphasis: none }", ".foo{text-emphasis:none}");
-    minify_test(".foo { text-emphasis: filled }", ".foo{text-emphasis:filled}");
-    minify_test(
-      ".foo { text-emphasis: filled yellow }",
-      ".foo{text-emphasis:filled #ff0}",
-    );
-    minify_test(
-      ".foo { text-emphasis: dot filled yellow }",
-      ".foo{text-emphasis:dot #ff0}",
-    );
-
-    test(
-      r#"
-      .foo {
-        text-emphasis-style: filled;
-        text-emphasis-color: yellow;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-emphasis: filled #ff0;
-      }
-    "#},
-    );
-
-    test(
-      r#"
-      .foo {
-        text-emphasis: filled red;
-        text-emphasis-color: yellow;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-emphasis: filled #ff0;
-      }
-    "#},
-    );
-
-    test(
-      r#"
-      .foo {
-        text-emphasis: filled yellow;
-        text-emphasis-color: var(--color);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        text-emphasis: filled #ff0;
-        text-emphasis-color: var(--color);
-      }
-    "#},
-    );
-
+  fn test_text_align_last() {
+    minify_test(".foo { text-align-last: left }", ".foo{text-align-last:left}");
+    minify_test(".foo { text-align-last: justify }", ".foo{text-align-last:justify}");
     prefix_test(
-      r#"
-      .foo {
-        text-emphasis-style: filled;
-      }
-    "#,
+      ".foo{ text-align-last: left }",
       indoc! {r#"
       .foo {
-        -webkit-text-emphasis-style: filled;
-        text-emphasis-style: filled;
+        -moz-text-align-last: left;
+        text-align-last: left;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(10 << 16),
-        chrome: Some(30 << 16),
-        firefox: Some(45 << 16),
+        firefox: Some(40 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        -webkit-text-emphasis-style: filled;
-        text-emphasis-style: filled;
+        -moz-text-align-last: left;
+        text-align-last: left;
       }
-    "#,
+      "#,
       indoc! {r#"
       .foo {
-        text-emphasis-style: filled;
+        text-align-last: left;
       }
-    "#},
+      "#},
       Browsers {
-        safari: Some(10 << 16),
-        firefox: Some(45 << 16),
+        firefox: Some(88 << 16),
         ..Browsers::default()
       },
     );
+  }
+
+  #[test]
+  fn test_text_justify() {
+    minify_test(".foo { text-justify: auto }", ".foo{text-justify:auto}");
+    minify_test(".foo { text-justify: inter-word }", ".foo{text-justify:inter-word}");
+  }
+
+  #[test]
+  fn test_word_spacing() {
+    minify_test(".foo { word-spacing: normal }", ".foo{word-spacing:normal}");
+    minify_test(".foo { word-spacing: 3px }", ".foo{word-spacing:3px}");
+  }
+
+  #[test]
+  fn test_letter_spacing() {
+    minify_test(".foo { letter-spacing: normal }", ".foo{letter-spacing:normal}");
+    minify_test(".foo { letter-spacing: 3px }", ".foo{letter-spacing:3px}");
+  }
 
+  #[test]
+  fn test_text_indent() {
+    minify_test(".foo { text-indent: 20px }", ".foo{text-indent:20px}");
+    minify_test(".foo { text-indent: 10% }", ".foo{text-indent:10%}");
+    minify_test(".foo { text-indent: 3em hanging }", ".foo{text-indent:3em hanging}");
+    minify_test(".foo { text-indent: 3em each-line }", ".foo{text-indent:3em each-line}");
     minify_test(
-      ".foo { text-emphasis-position: over }",
-      ".foo{text-emphasis-position:over}",
+      ".foo { text-indent: 3em hanging each-line }",
+      ".foo{text-indent:3em hanging each-line}",
     );
     minify_test(
-      ".foo { text-emphasis-position: under }",
-      ".foo{text-emphasis-position:under}",
+      ".foo { text-indent: 3em each-line hanging }",
+      ".foo{text-indent:3em hanging each-line}",
     );
     minify_test(
-      ".foo { text-emphasis-position: over right }",
-      ".foo{text-emphasis-position:over}",
+      ".foo { text-indent: each-line 3em hanging }",
+      ".foo{text-indent:3em hanging each-line}",
     );
     minify_test(
-      ".foo { text-emphasis-position: over left }",
-      ".foo{text-emphasis-position:over left}",
+      ".foo { text-indent: each-line hanging 3em }",
+      ".foo{text-indent:3em hanging each-line}",
     );
+  }
 
+  #[test]
+  fn test_text_size_adjust() {
+    minify_test(".foo { text-size-adjust: none }", ".foo{text-size-adjust:none}");
+    minify_test(".foo { text-size-adjust: auto }", ".foo{text-size-adjust:auto}");
+    minify_test(".foo { text-size-adjust: 80% }", ".foo{text-size-adjust:80%}");
     prefix_test(
       r#"
       .foo {
-        text-emphasis-position: over;
+        text-size-adjust: none;
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-text-emphasis-position: over;
-        text-emphasis-position: over;
+        -webkit-text-size-adjust: none;
+        -moz-text-size-adjust: none;
+        -ms-text-size-adjust: none;
+        text-size-adjust: none;
       }
     "#},
       Browsers {
-        safari: Some(10 << 16),
-        chrome: Some(30 << 16),
-        firefox: Some(45 << 16),
+        ios_saf: Some(16 << 16),
+        edge: Some(15 << 16),
+        firefox: Some(20 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
       .foo {
-        text-emphasis-position: over left;
+        -webkit-text-size-adjust: none;
+        -moz-text-size-adjust: none;
+        -ms-text-size-adjust: none;
+        text-size-adjust: none;
       }
     "#,
       indoc! {r#"
       .foo {
-        text-emphasis-position: over left;
+        text-size-adjust: none;
       }
     "#},
       Browsers {
-        safari: Some(10 << 16),
-        chrome: Some(30 << 16),
-        firefox: Some(45 << 16),
+        chrome: Some(110 << 16),
         ..Browsers::default()
       },
     );
+  }
 
+  #[test]
+  fn test_initial_letter() {
+    minify_test(".foo { initial-letter: normal }", ".foo{initial-letter:normal}");
+    minify_test(".foo { initial-letter: 3 }", ".foo{initial-letter:3}");
+    minify_test(".foo { initial-letter: 3.5 2 }", ".foo{initial-letter:3.5 2}");
+    minify_test(".foo { initial-letter: 3 drop }", ".foo{initial-letter:3}");
+    minify_test(".foo { initial-letter: raise 3 }", ".foo{initial-letter:3 raise}");
+    minify_test(".foo { initial-letter: 0.5 }", ".foo{initial-letter:.5}");
+    minify_test(".foo { initial-letter: 3 0 }", ".foo{initial-letter:3 0}");
     prefix_test(
       r#"
-      .foo {
-        text-emphasis-position: var(--test);
+      .foo::first-letter {
+        initial-letter: 3;
       }
     "#,
       indoc! {r#"
-      .foo {
-        -webkit-text-emphasis-position: var(--test);
-        text-emphasis-position: var(--test);
+      .foo:first-letter {
+        -webkit-initial-letter: 3;
+        initial-letter: 3;
       }
     "#},
       Browsers {
-        safari: Some(10 << 16),
-        chrome: Some(30 << 16),
-        firefox: Some(45 << 16),
+        safari: Some(14 << 16),
+        chrome: Some(110 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
       r#"
-      .foo {
-        text-emphasis: filled lch(50.998% 135.363 338);
+      .foo::first-letter {
+        -webkit-initial-letter: 3;
+        initial-letter: 3;
       }
     "#,
       indoc! {r#"
-      .foo {
-        -webkit-text-emphasis: filled #ee00be;
-        text-emphasis: filled #ee00be;
-        -webkit-text-emphasis: filled lch(50.998% 135.363 338);
-        text-emphasis: filled lch(50.998% 135.363 338);
+      .foo:first-letter {
+        initial-letter: 3;
       }
     "#},
       Browsers {
-        chrome: Some(25 << 16),
-        firefox: Some(48 << 16),
+        chrome: Some(110 << 16),
         ..Browsers::default()
       },
     );
-
-    prefix_test(
+    prefix_test_with_options(
       r#"
-      .foo {
-        text-emphasis-color: lch(50.998% 135.363 338);
+      .foo::first-letter {
+        initial-letter: 3 2;
+        color: red;
       }
     "#,
       indoc! {r#"
-      .foo {
-        -webkit-text-emphasis-color: #ee00be;
-        text-emphasis-color: #ee00be;
-        -webkit-text-emphasis-color: lch(50.998% 135.363 338);
-        text-emphasis-color: lch(50.998% 135.363 338);
+      .foo:first-letter {
+        -webkit-initial-letter: 3 2;
+        initial-letter: 3 2;
+        color: red;
+      }
+
+      @supports not ((-webkit-initial-letter: 3 2) or (initial-letter: 3 2)) {
+        .foo:first-letter {
+          float: left;
+          font-size: 3em;
+          line-height: 1;
+        }
       }
     "#},
-      Browsers {
-        chrome: Some(25 << 16),
-        firefox: Some(48 << 16),
-        ..Browsers::default()
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(14 << 16),
+          firefox: Some(100 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        initial_letter_fallback: true,
+        ..MinifyOptions::default()
       },
     );
-
-    prefix_test(
+    prefix_test_with_options(
       r#"
-      .foo {
-        text-emphasis: lch(50.998% 135.363 338) var(--style);
+      .foo::first-letter {
+        initial-letter: 3;
       }
     "#,
       indoc! {r#"
-      .foo {
-        text-emphasis: #ee00be var(--style);
-      }
-
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          text-emphasis: lab(50.998% 125.506 -50.7078) var(--style);
-        }
+      .foo:first-letter {
+        initial-letter: 3;
       }
     "#},
-      Browsers {
-        safari: Some(8 << 16),
-        ..Browsers::default()
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(110 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        initial_letter_fallback: true,
+        ..MinifyOptions::default()
       },
     );
   }
 
   #[test]
-  fn test_text_shadow() {
+  fn test_text_decoration() {
+    minify_test(".foo { text-decoration-line: none }", ".foo{text-decoration-line:none}");
     minify_test(
-      ".foo { text-shadow: 1px 1px 2px yellow; }",
-      ".foo{text-shadow:1px 1px 2px #ff0}",
+      ".foo { text-decoration-line: underline }",
+      ".foo{text-decoration-line:underline}",
     );
     minify_test(
-      ".foo { text-shadow: 1px 1px 2px 3px yellow; }",
-      ".foo{text-shadow:1px 1px 2px 3px #ff0}",
+      ".foo { text-decoration-line: overline }",
+      ".foo{text-decoration-line:overline}",
     );
     minify_test(
-      ".foo { text-shadow: 1px 1px 0 yellow; }",
-      ".foo{text-shadow:1px 1px #ff0}",
+      ".foo { text-decoration-line: line-through }",
+      ".foo{text-decoration-line:line-through}",
     );
     minify_test(
-      ".foo { text-shadow: 1px 1px yellow; }",
-      ".foo{text-shadow:1px 1px #ff0}",
+      ".foo { text-decoration-line: blink }",
+      ".foo{text-decoration-line:blink}",
     );
     minify_test(
-      ".foo { text-shadow: 1px 1px yellow, 2px 3px red; }",
-      ".foo{text-shadow:1px 1px #ff0,2px 3px red}",
+      ".foo { text-decoration-line: underline overline }",
+      ".foo{text-decoration-line:underline overline}",
     );
-
-    prefix_test(
-      ".foo { text-shadow: 12px 12px lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          text-shadow: 12px 12px #b32323;
-          text-shadow: 12px 12px lab(40% 56.6 39);
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      ".foo { text-decoration-line: overline underline }",
+      ".foo{text-decoration-line:underline overline}",
     );
-
-    prefix_test(
-      ".foo { text-shadow: 12px 12px lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          text-shadow: 12px 12px #b32323;
-          text-shadow: 12px 12px color(display-p3 .643308 .192455 .167712);
-          text-shadow: 12px 12px lab(40% 56.6 39);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      ".foo { text-decoration-line: overline line-through underline }",
+      ".foo{text-decoration-line:underline overline line-through}",
+    );
+    minify_test(
+      ".foo { text-decoration-line: spelling-error }",
+      ".foo{text-decoration-line:spelling-error}",
+    );
+    minify_test(
+      ".foo { text-decoration-line: grammar-error }",
+      ".foo{text-decoration-line:grammar-error}",
+    );
+    minify_test(
+      ".foo { -webkit-text-decoration-line: overline underline }",
+      ".foo{-webkit-text-decoration-line:underline overline}",
+    );
+    minify_test(
+      ".foo { -moz-text-decoration-line: overline underline }",
+      ".foo{-moz-text-decoration-line:underline overline}",
     );
 
-    prefix_test(
-      ".foo { text-shadow: 12px 12px lab(40% 56.6 39), 12px 12px yellow }",
-      indoc! { r#"
-        .foo {
-          text-shadow: 12px 12px #b32323, 12px 12px #ff0;
-          text-shadow: 12px 12px lab(40% 56.6 39), 12px 12px #ff0;
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      ".foo { text-decoration-style: solid }",
+      ".foo{text-decoration-style:solid}",
+    );
+    minify_test(
+      ".foo { text-decoration-style: dotted }",
+      ".foo{text-decoration-style:dotted}",
+    );
+    minify_test(
+      ".foo { -webkit-text-decoration-style: solid }",
+      ".foo{-webkit-text-decoration-style:solid}",
     );
 
-    prefix_test(
-      ".foo { text-shadow: var(--foo) 12px lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          text-shadow: var(--foo) 12px #b32323;
-        }
+    minify_test(
+      ".foo { text-decoration-color: yellow }",
+      ".foo{text-decoration-color:#ff0}",
+    );
+    minify_test(
+      ".foo { -webkit-text-decoration-color: yellow }",
+      ".foo{-webkit-text-decoration-color:#ff0}",
+    );
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            text-shadow: var(--foo) 12px lab(40% 56.6 39);
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+    minify_test(".foo { text-decoration: none }", ".foo{text-decoration:none}");
+    minify_test(
+      ".foo { text-decoration: underline dotted }",
+      ".foo{text-decoration:underline dotted}",
+    );
+    minify_test(
+      ".foo { text-decoration: underline dotted yellow }",
+      ".foo{text-decoration:underline dotted #ff0}",
+    );
+    minify_test(
+      ".foo { text-decoration: yellow dotted underline }",
+      ".foo{text-decoration:underline dotted #ff0}",
+    );
+    minify_test(
+      ".foo { text-decoration: underline overline dotted yellow }",
+      ".foo{text-decoration:underline overline dotted #ff0}",
+    );
+    minify_test(
+      ".foo { -webkit-text-decoration: yellow dotted underline }",
+      ".foo{-webkit-text-decoration:underline dotted #ff0}",
+    );
+    minify_test(
+      ".foo { -moz-text-decoration: yellow dotted underline }",
+      ".foo{-moz-text-decoration:underline dotted #ff0}",
     );
-  }
 
-  #[test]
-  fn test_break() {
-    prefix_test(
+    test(
       r#"
       .foo {
-        box-decoration-break: clone;
+        text-decoration-line: underline;
+        text-decoration-style: dotted;
+        text-decoration-color: yellow;
+        text-decoration-thickness: 2px;
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-box-decoration-break: clone;
-        box-decoration-break: clone;
+        text-decoration: underline 2px dotted #ff0;
       }
     "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
+    test(
       r#"
       .foo {
-        box-decoration-break: clone;
+        text-decoration: underline;
+        text-decoration-style: dotted;
       }
     "#,
       indoc! {r#"
       .foo {
-        box-decoration-break: clone;
+        text-decoration: underline dotted;
       }
     "#},
-      Browsers {
-        firefox: Some(95 << 16),
-        ..Browsers::default()
-      },
     );
-  }
 
-  #[test]
-  fn test_position() {
     test(
       r#"
       .foo {
-        position: relative;
-        position: absolute;
+        text-decoration: underline;
+        text-decoration-style: var(--style);
       }
     "#,
       indoc! {r#"
       .foo {
-        position: absolute;
+        text-decoration: underline;
+        text-decoration-style: var(--style);
       }
     "#},
     );
@@ -14693,14 +16117,13 @@ mod tests {
     test(
       r#"
       .foo {
-        position: -webkit-sticky;
-        position: sticky;
+        -webkit-text-decoration: underline;
+        -webkit-text-decoration-style: dotted;
       }
     "#,
       indoc! {r#"
       .foo {
-        position: -webkit-sticky;
-        position: sticky;
+        -webkit-text-decoration: underline dotted;
       }
     "#},
     );
@@ -14708,17 +16131,18 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        position: sticky;
+        text-decoration: underline dotted;
       }
     "#,
       indoc! {r#"
       .foo {
-        position: -webkit-sticky;
-        position: sticky;
+        -webkit-text-decoration: underline dotted;
+        text-decoration: underline dotted;
       }
     "#},
       Browsers {
         safari: Some(8 << 16),
+        firefox: Some(30 << 16),
         ..Browsers::default()
       },
     );
@@ -14726,147 +16150,153 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        position: -webkit-sticky;
-        position: sticky;
+        text-decoration-line: underline;
       }
     "#,
       indoc! {r#"
       .foo {
-        position: sticky;
+        -webkit-text-decoration-line: underline;
+        -moz-text-decoration-line: underline;
+        text-decoration-line: underline;
       }
     "#},
       Browsers {
-        safari: Some(13 << 16),
-        ..Browsers::default()
+        safari: Some(8 << 16),
+        firefox: Some(30 << 16),
+        ..Browsers::default()
       },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        top: 0;
-        left: 0;
-        bottom: 0;
-        right: 0;
+        text-decoration-style: dotted;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset: 0;
+        -webkit-text-decoration-style: dotted;
+        -moz-text-decoration-style: dotted;
+        text-decoration-style: dotted;
       }
     "#},
+      Browsers {
+        safari: Some(8 << 16),
+        firefox: Some(30 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        top: 2px;
-        left: 4px;
-        bottom: 2px;
-        right: 4px;
+        text-decoration-color: yellow;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset: 2px 4px;
+        -webkit-text-decoration-color: #ff0;
+        -moz-text-decoration-color: #ff0;
+        text-decoration-color: #ff0;
       }
     "#},
+      Browsers {
+        safari: Some(8 << 16),
+        firefox: Some(30 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        top: 1px;
-        left: 2px;
-        bottom: 3px;
-        right: 4px;
+        text-decoration: underline;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset: 1px 4px 3px 2px;
+        text-decoration: underline;
       }
     "#},
+      Browsers {
+        safari: Some(8 << 16),
+        firefox: Some(30 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        inset-block-start: 2px;
-        inset-block-end: 2px;
-        inset-inline-start: 4px;
-        inset-inline-end: 4px;
+        -webkit-text-decoration: underline dotted;
+        text-decoration: underline dotted;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset-block: 2px;
-        inset-inline: 4px;
+        -webkit-text-decoration: underline dotted;
+        text-decoration: underline dotted;
       }
     "#},
+      Browsers {
+        safari: Some(14 << 16),
+        firefox: Some(45 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        inset-block-start: 2px;
-        inset-block-end: 3px;
-        inset-inline-start: 4px;
-        inset-inline-end: 5px;
+        text-decoration: double underline;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset-block: 2px 3px;
-        inset-inline: 4px 5px;
+        -webkit-text-decoration: underline double;
+        text-decoration: underline double;
       }
     "#},
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        inset-block-start: 2px;
-        inset-block-end: 3px;
-        inset: 4px;
-        inset-inline-start: 4px;
-        inset-inline-end: 5px;
+        text-decoration: underline;
+        text-decoration-style: double;
       }
     "#,
       indoc! {r#"
       .foo {
-        inset: 4px;
-        inset-inline: 4px 5px;
+        -webkit-text-decoration: underline double;
+        text-decoration: underline double;
       }
     "#},
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
     );
 
     prefix_test(
       r#"
       .foo {
-        inset-inline-start: 2px;
+        text-decoration: underline;
+        text-decoration-color: red;
       }
     "#,
       indoc! {r#"
-      .foo:not(:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        left: 2px;
-      }
-
-      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        left: 2px;
-      }
-
-      .foo:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        right: 2px;
-      }
-
-      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        right: 2px;
+      .foo {
+        -webkit-text-decoration: underline red;
+        text-decoration: underline red;
       }
-    "#
-      },
+    "#},
       Browsers {
-        safari: Some(8 << 16),
+        safari: Some(16 << 16),
         ..Browsers::default()
       },
     );
@@ -14874,53 +16304,48 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        inset-inline-start: 2px;
-        inset-inline-end: 4px;
+        text-decoration: var(--test);
       }
     "#,
       indoc! {r#"
-      .foo:not(:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        left: 2px;
-        right: 4px;
-      }
-
-      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
-        left: 2px;
-        right: 4px;
-      }
-
-      .foo:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        left: 4px;
-        right: 2px;
-      }
-
-      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
-        left: 4px;
-        right: 2px;
+      .foo {
+        -webkit-text-decoration: var(--test);
+        text-decoration: var(--test);
       }
-    "#
-      },
+    "#},
       Browsers {
         safari: Some(8 << 16),
+        firefox: Some(30 << 16),
         ..Browsers::default()
       },
     );
 
+    minify_test(
+      ".foo { text-decoration-skip-ink: all }",
+      ".foo{text-decoration-skip-ink:all}",
+    );
+    minify_test(
+      ".foo { -webkit-text-decoration-skip-ink: all }",
+      ".foo{-webkit-text-decoration-skip-ink:all}",
+    );
+
     prefix_test(
       r#"
       .foo {
-        inset-inline: 2px;
+        text-decoration: lch(50.998% 135.363 338) underline;
       }
     "#,
       indoc! {r#"
       .foo {
-        left: 2px;
-        right: 2px;
+        -webkit-text-decoration: underline #ee00be;
+        text-decoration: underline #ee00be;
+        -webkit-text-decoration: underline lch(50.998% 135.363 338);
+        text-decoration: underline lch(50.998% 135.363 338);
       }
-    "#
-      },
+    "#},
       Browsers {
         safari: Some(8 << 16),
+        firefox: Some(30 << 16),
         ..Browsers::default()
       },
     );
@@ -14928,17 +16353,22 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        inset-block-start: 2px;
+        text-decoration-color: lch(50.998% 135.363 338);
       }
     "#,
       indoc! {r#"
       .foo {
-        top: 2px;
+        -webkit-text-decoration-color: #ee00be;
+        -moz-text-decoration-color: #ee00be;
+        text-decoration-color: #ee00be;
+        -webkit-text-decoration-color: lch(50.998% 135.363 338);
+        -moz-text-decoration-color: lch(50.998% 135.363 338);
+        text-decoration-color: lch(50.998% 135.363 338);
       }
-    "#
-      },
+    "#},
       Browsers {
         safari: Some(8 << 16),
+        firefox: Some(30 << 16),
         ..Browsers::default()
       },
     );
@@ -14946,17 +16376,22 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        inset-block-end: 2px;
+        text-decoration: lch(50.998% 135.363 338) var(--style);
       }
     "#,
       indoc! {r#"
       .foo {
-        bottom: 2px;
+        text-decoration: #ee00be var(--style);
       }
-    "#
-      },
+
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          text-decoration: lab(50.998% 125.506 -50.7078) var(--style);
+        }
+      }
+    "#},
       Browsers {
-        safari: Some(8 << 16),
+        chrome: Some(90 << 16),
         ..Browsers::default()
       },
     );
@@ -14964,140 +16399,207 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        top: 1px;
-        left: 2px;
-        bottom: 3px;
-        right: 4px;
+        text-decoration: underline 10px;
       }
     "#,
       indoc! {r#"
       .foo {
-        top: 1px;
-        bottom: 3px;
-        left: 2px;
-        right: 4px;
+        text-decoration: underline;
+        text-decoration-thickness: 10px;
       }
     "#},
       Browsers {
-        safari: Some(8 << 16),
+        safari: Some(15 << 16),
         ..Browsers::default()
       },
     );
-  }
-
-  #[test]
-  fn test_overflow() {
-    minify_test(".foo { overflow: hidden }", ".foo{overflow:hidden}");
-    minify_test(".foo { overflow: hidden hidden }", ".foo{overflow:hidden}");
-    minify_test(".foo { overflow: hidden auto }", ".foo{overflow:hidden auto}");
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        overflow-x: hidden;
-        overflow-y: auto;
+        text-decoration: underline 10px;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden auto;
+        text-decoration: underline 10px;
       }
     "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        overflow: hidden;
-        overflow-y: auto;
+        text-decoration: underline 10%;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden auto;
+        text-decoration: underline;
+        text-decoration-thickness: calc(1em / 10);
       }
     "#},
+      Browsers {
+        safari: Some(12 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
+
+    prefix_test(
       r#"
       .foo {
-        overflow: hidden;
-        overflow-y: var(--y);
+        text-decoration: underline 10%;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden;
-        overflow-y: var(--y);
+        text-decoration: underline 10%;
       }
     "#},
+      Browsers {
+        firefox: Some(89 << 16),
+        ..Browsers::default()
+      },
     );
+
     prefix_test(
       r#"
       .foo {
-        overflow: hidden auto;
+        text-decoration-thickness: 10%;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow-x: hidden;
-        overflow-y: auto;
+        text-decoration-thickness: calc(1em / 10);
       }
     "#},
       Browsers {
-        chrome: Some(67 << 16),
+        safari: Some(12 << 16),
         ..Browsers::default()
       },
     );
+
     prefix_test(
       r#"
       .foo {
-        overflow: hidden hidden;
+        text-decoration-thickness: 10%;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden;
+        text-decoration-thickness: 10%;
       }
     "#},
       Browsers {
-        chrome: Some(67 << 16),
+        firefox: Some(89 << 16),
         ..Browsers::default()
       },
     );
-    prefix_test(
+  }
+
+  #[test]
+  fn test_text_emphasis() {
+    minify_test(".foo { text-emphasis-style: none }", ".foo{text-emphasis-style:none}");
+    minify_test(
+      ".foo { text-emphasis-style: filled }",
+      ".foo{text-emphasis-style:filled}",
+    );
+    minify_test(".foo { text-emphasis-style: open }", ".foo{text-emphasis-style:open}");
+    minify_test(".foo { text-emphasis-style: dot }", ".foo{text-emphasis-style:dot}");
+    minify_test(
+      ".foo { text-emphasis-style: filled dot }",
+      ".foo{text-emphasis-style:dot}",
+    );
+    minify_test(
+      ".foo { text-emphasis-style: dot filled }",
+      ".foo{text-emphasis-style:dot}",
+    );
+    minify_test(
+      ".foo { text-emphasis-style: open dot }",
+      ".foo{text-emphasis-style:open dot}",
+    );
+    minify_test(
+      ".foo { text-emphasis-style: dot open }",
+      ".foo{text-emphasis-style:open dot}",
+    );
+    minify_test(".foo { text-emphasis-style: \"x\" }", ".foo{text-emphasis-style:\"x\"}");
+
+    minify_test(".foo { text-emphasis-color: yellow }", ".foo{text-emphasis-color:#ff0}");
+
+    minify_test(".foo { text-emphasis: none }", ".foo{text-emphasis:none}");
+    minify_test(".foo { text-emphasis: filled }", ".foo{text-emphasis:filled}");
+    minify_test(
+      ".foo { text-emphasis: filled yellow }",
+      ".foo{text-emphasis:filled #ff0}",
+    );
+    minify_test(
+      ".foo { text-emphasis: dot filled yellow }",
+      ".foo{text-emphasis:dot #ff0}",
+    );
+
+    test(
       r#"
       .foo {
-        overflow: hidden auto;
+        text-emphasis-style: filled;
+        text-emphasis-color: yellow;
       }
     "#,
       indoc! {r#"
       .foo {
-        overflow: hidden auto;
+        text-emphasis: filled #ff0;
+      }
+    "#},
+    );
+
+    test(
+      r#"
+      .foo {
+        text-emphasis: filled red;
+        text-emphasis-color: yellow;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        text-emphasis: filled #ff0;
+      }
+    "#},
+    );
+
+    test(
+      r#"
+      .foo {
+        text-emphasis: filled yellow;
+        text-emphasis-color: var(--color);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        text-emphasis: filled #ff0;
+        text-emphasis-color: var(--color);
       }
     "#},
-      Browsers {
-        chrome: Some(68 << 16),
-        ..Browsers::default()
-      },
     );
 
-    minify_test(".foo { text-overflow: ellipsis }", ".foo{text-overflow:ellipsis}");
     prefix_test(
       r#"
       .foo {
-        text-overflow: ellipsis;
+        text-emphasis-style: filled;
       }
     "#,
       indoc! {r#"
       .foo {
-        -o-text-overflow: ellipsis;
-        text-overflow: ellipsis;
+        -webkit-text-emphasis-style: filled;
+        text-emphasis-style: filled;
       }
     "#},
       Browsers {
-        safari: Some(4 << 16),
-        opera: Some(10 << 16),
+        safari: Some(10 << 16),
+        chrome: Some(30 << 16),
+        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
@@ -15105,76 +16607,55 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        -o-text-overflow: ellipsis;
-        text-overflow: ellipsis;
+        -webkit-text-emphasis-style: filled;
+        text-emphasis-style: filled;
       }
     "#,
       indoc! {r#"
       .foo {
-        text-overflow: ellipsis;
+        text-emphasis-style: filled;
       }
     "#},
       Browsers {
-        safari: Some(4 << 16),
-        opera: Some(14 << 16),
+        safari: Some(10 << 16),
+        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
-  }
 
-  #[test]
-  fn test_ui() {
-    minify_test(".foo { resize: both }", ".foo{resize:both}");
-    minify_test(".foo { resize: Horizontal }", ".foo{resize:horizontal}");
-    minify_test(".foo { cursor: ew-resize }", ".foo{cursor:ew-resize}");
     minify_test(
-      ".foo { cursor: url(\"test.cur\"), ew-resize }",
-      ".foo{cursor:url(test.cur),ew-resize}",
+      ".foo { text-emphasis-position: over }",
+      ".foo{text-emphasis-position:over}",
     );
     minify_test(
-      ".foo { cursor: url(\"test.cur\"), url(\"foo.cur\"), ew-resize }",
-      ".foo{cursor:url(test.cur),url(foo.cur),ew-resize}",
+      ".foo { text-emphasis-position: under }",
+      ".foo{text-emphasis-position:under}",
     );
-    minify_test(".foo { caret-color: auto }", ".foo{caret-color:auto}");
-    minify_test(".foo { caret-color: yellow }", ".foo{caret-color:#ff0}");
-    minify_test(".foo { caret-shape: block }", ".foo{caret-shape:block}");
-    minify_test(".foo { caret: yellow block }", ".foo{caret:#ff0 block}");
-    minify_test(".foo { caret: block yellow }", ".foo{caret:#ff0 block}");
-    minify_test(".foo { caret: block }", ".foo{caret:block}");
-    minify_test(".foo { caret: yellow }", ".foo{caret:#ff0}");
-    minify_test(".foo { caret: auto auto }", ".foo{caret:auto}");
-    minify_test(".foo { caret: auto }", ".foo{caret:auto}");
-    minify_test(".foo { caret: yellow auto }", ".foo{caret:#ff0}");
-    minify_test(".foo { caret: auto block }", ".foo{caret:block}");
-    minify_test(".foo { user-select: none }", ".foo{user-select:none}");
-    minify_test(".foo { -webkit-user-select: none }", ".foo{-webkit-user-select:none}");
-    minify_test(".foo { accent-color: auto }", ".foo{accent-color:auto}");
-    minify_test(".foo { accent-color: yellow }", ".foo{accent-color:#ff0}");
-    minify_test(".foo { appearance: None }", ".foo{appearance:none}");
     minify_test(
-      ".foo { -webkit-appearance: textfield }",
-      ".foo{-webkit-appearance:textfield}",
+      ".foo { text-emphasis-position: over right }",
+      ".foo{text-emphasis-position:over}",
+    );
+    minify_test(
+      ".foo { text-emphasis-position: over left }",
+      ".foo{text-emphasis-position:over left}",
     );
 
     prefix_test(
       r#"
       .foo {
-        user-select: none;
+        text-emphasis-position: over;
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-user-select: none;
-        -moz-user-select: none;
-        -ms-user-select: none;
-        user-select: none;
+        -webkit-text-emphasis-position: over;
+        text-emphasis-position: over;
       }
     "#},
       Browsers {
-        safari: Some(8 << 16),
-        opera: Some(5 << 16),
-        firefox: Some(10 << 16),
-        ie: Some(10 << 16),
+        safari: Some(10 << 16),
+        chrome: Some(30 << 16),
+        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
@@ -15182,23 +16663,18 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        -webkit-user-select: none;
-        -moz-user-select: none;
-        -ms-user-select: none;
-        user-select: none;
+        text-emphasis-position: over left;
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-user-select: none;
-        user-select: none;
+        text-emphasis-position: over left;
       }
     "#},
       Browsers {
-        safari: Some(8 << 16),
-        opera: Some(80 << 16),
-        firefox: Some(80 << 16),
-        edge: Some(80 << 16),
+        safari: Some(10 << 16),
+        chrome: Some(30 << 16),
+        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
@@ -15206,21 +16682,19 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        -webkit-user-select: none;
-        -moz-user-select: none;
-        -ms-user-select: none;
-        user-select: none;
+        text-emphasis-position: var(--test);
       }
     "#,
       indoc! {r#"
       .foo {
-        user-select: none;
+        -webkit-text-emphasis-position: var(--test);
+        text-emphasis-position: var(--test);
       }
     "#},
       Browsers {
-        opera: Some(80 << 16),
-        firefox: Some(80 << 16),
-        edge: Some(80 << 16),
+        safari: Some(10 << 16),
+        chrome: Some(30 << 16),
+        firefox: Some(45 << 16),
         ..Browsers::default()
       },
     );
@@ -15228,22 +16702,20 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        appearance: none;
+        text-emphasis: filled lch(50.998% 135.363 338);
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-appearance: none;
-        -moz-appearance: none;
-        -ms-appearance: none;
-        appearance: none;
+        -webkit-text-emphasis: filled #ee00be;
+        text-emphasis: filled #ee00be;
+        -webkit-text-emphasis: filled lch(50.998% 135.363 338);
+        text-emphasis: filled lch(50.998% 135.363 338);
       }
     "#},
       Browsers {
-        safari: Some(8 << 16),
-        chrome: Some(80 << 16),
-        firefox: Some(10 << 16),
-        ie: Some(11 << 16),
+        chrome: Some(25 << 16),
+        firefox: Some(48 << 16),
         ..Browsers::default()
       },
     );
@@ -15251,23 +16723,20 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        -webkit-appearance: none;
-        -moz-appearance: none;
-        -ms-appearance: none;
-        appearance: none;
+        text-emphasis-color: lch(50.998% 135.363 338);
       }
     "#,
       indoc! {r#"
       .foo {
-        -webkit-appearance: none;
-        appearance: none;
+        -webkit-text-emphasis-color: #ee00be;
+        text-emphasis-color: #ee00be;
+        -webkit-text-emphasis-color: lch(50.998% 135.363 338);
+        text-emphasis-color: lch(50.998% 135.363 338);
       }
     "#},
       Browsers {
-        safari: Some(15 << 16),
-        chrome: Some(85 << 16),
-        firefox: Some(80 << 16),
-        edge: Some(85 << 16),
+        chrome: Some(25 << 16),
+        firefox: Some(48 << 16),
         ..Browsers::default()
       },
     );
@@ -15275,48 +16744,71 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        -webkit-appearance: none;
-        -moz-appearance: none;
-        -ms-appearance: none;
-        appearance: none;
+        text-emphasis: lch(50.998% 135.363 338) var(--style);
       }
     "#,
       indoc! {r#"
       .foo {
-        appearance: none;
+        text-emphasis: #ee00be var(--style);
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          text-emphasis: lab(50.998% 125.506 -50.7078) var(--style);
+        }
       }
     "#},
       Browsers {
-        chrome: Some(85 << 16),
-        firefox: Some(80 << 16),
-        edge: Some(85 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      ".foo { caret-color: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          caret-color: #ee00be;
-          caret-color: color(display-p3 .972962 -.362078 .804206);
-          caret-color: lch(50.998% 135.363 338);
+  #[test]
+  fn test_text_shadow() {
+    minify_test(
+      ".foo { text-shadow: 1px 1px 2px yellow; }",
+      ".foo{text-shadow:1px 1px 2px #ff0}",
+    );
+    minify_test(
+      ".foo { text-shadow: 1px 1px 2px 3px yellow; }",
+      ".foo{text-shadow:1px 1px 2px 3px #ff0}",
+    );
+    minify_test(
+      ".foo { text-shadow: 1px 1px 0 yellow; }",
+      ".foo{text-shadow:1px 1px #ff0}",
+    );
+    minify_test(
+      ".foo { text-shadow: 1px 1px yellow; }",
+      ".foo{text-shadow:1px 1px #ff0}",
+    );
+    minify_test(
+      ".foo { text-shadow: 1px 1px yellow, 2px 3px red; }",
+      ".foo{text-shadow:1px 1px #ff0,2px 3px red}",
+    );
+
+    prefix_test(
+      ".foo { text-shadow: 12px 12px lab(40% 56.6 39) }",
+      indoc! { r#"
+        .foo {
+          text-shadow: 12px 12px #b32323;
+          text-shadow: 12px 12px lab(40% 56.6 39);
         }
       "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { caret: lch(50.998% 135.363 338) block }",
+      ".foo { text-shadow: 12px 12px lab(40% 56.6 39) }",
       indoc! { r#"
         .foo {
-          caret: #ee00be block;
-          caret: color(display-p3 .972962 -.362078 .804206) block;
-          caret: lch(50.998% 135.363 338) block;
+          text-shadow: 12px 12px #b32323;
+          text-shadow: 12px 12px color(display-p3 .643308 .192455 .167712);
+          text-shadow: 12px 12px lab(40% 56.6 39);
         }
       "#},
       Browsers {
@@ -15327,211 +16819,104 @@ mod tests {
     );
 
     prefix_test(
-      ".foo { caret: lch(50.998% 135.363 338) var(--foo) }",
+      ".foo { text-shadow: 12px 12px lab(40% 56.6 39), 12px 12px yellow }",
       indoc! { r#"
         .foo {
-          caret: #ee00be var(--foo);
+          text-shadow: 12px 12px #b32323, 12px 12px #ff0;
+          text-shadow: 12px 12px lab(40% 56.6 39), 12px 12px #ff0;
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
+    );
+
+    prefix_test(
+      ".foo { text-shadow: var(--foo) 12px lab(40% 56.6 39) }",
+      indoc! { r#"
+        .foo {
+          text-shadow: var(--foo) 12px #b32323;
         }
 
         @supports (color: lab(0% 0 0)) {
           .foo {
-            caret: lab(50.998% 125.506 -50.7078) var(--foo);
+            text-shadow: var(--foo) 12px lab(40% 56.6 39);
           }
         }
       "#},
       Browsers {
-        chrome: Some(90 << 16),
+        chrome: Some(4 << 16),
         ..Browsers::default()
       },
     );
   }
 
   #[test]
-  fn test_list() {
-    minify_test(".foo { list-style-type: disc; }", ".foo{list-style-type:disc}");
-    minify_test(".foo { list-style-type: \"★\"; }", ".foo{list-style-type:\"★\"}");
-    minify_test(
-      ".foo { list-style-type: symbols(cyclic '○' '●'); }",
-      ".foo{list-style-type:symbols(cyclic \"○\" \"●\")}",
-    );
-    minify_test(
-      ".foo { list-style-type: symbols('○' '●'); }",
-      ".foo{list-style-type:symbols(\"○\" \"●\")}",
-    );
-    minify_test(
-      ".foo { list-style-type: symbols(symbolic '○' '●'); }",
-      ".foo{list-style-type:symbols(\"○\" \"●\")}",
-    );
-    minify_test(
-      ".foo { list-style-type: symbols(symbolic url('ellipse.png')); }",
-      ".foo{list-style-type:symbols(url(ellipse.png))}",
-    );
-    minify_test(
-      ".foo { list-style-image: url('ellipse.png'); }",
-      ".foo{list-style-image:url(ellipse.png)}",
-    );
-    minify_test(
-      ".foo { list-style-position: outside; }",
-      ".foo{list-style-position:outside}",
-    );
-    minify_test(
-      ".foo { list-style: \"★\" url(ellipse.png) outside; }",
-      ".foo{list-style:\"★\" url(ellipse.png)}",
-    );
-
-    test(
-      r#"
-      .foo {
-        list-style-type: disc;
-        list-style-image: url(ellipse.png);
-        list-style-position: outside;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        list-style: url("ellipse.png");
-      }
-    "#},
-    );
-
-    test(
+  fn test_break() {
+    prefix_test(
       r#"
       .foo {
-        list-style: \"★\" url(ellipse.png) outside;
-        list-style-image: none;
+        box-decoration-break: clone;
       }
     "#,
       indoc! {r#"
       .foo {
-        list-style: \"★\";
+        -webkit-box-decoration-break: clone;
+        box-decoration-break: clone;
       }
     "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        list-style: \"★\" url(ellipse.png) outside;
-        list-style-image: var(--img);
+        box-decoration-break: clone;
       }
     "#,
       indoc! {r#"
       .foo {
-        list-style: \"★\" url("ellipse.png");
-        list-style-image: var(--img);
+        box-decoration-break: clone;
       }
     "#},
-    );
-
-    prefix_test(
-      ".foo { list-style-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          list-style-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
-          list-style-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
-          list-style-image: linear-gradient(#ff0f0e, #7773ff);
-          list-style-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
-    );
-
-    prefix_test(
-      ".foo { list-style: \"★\" linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          list-style: "★" linear-gradient(#ff0f0e, #7773ff);
-          list-style: "★" linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
-    );
-
-    prefix_test(
-      ".foo { list-style: var(--foo) linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          list-style: var(--foo) linear-gradient(#ff0f0e, #7773ff);
-        }
-
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            list-style: var(--foo) linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586));
-          }
-        }
-      "#},
       Browsers {
-        chrome: Some(90 << 16),
+        firefox: Some(95 << 16),
         ..Browsers::default()
       },
     );
   }
 
   #[test]
-  fn test_image_set() {
-    // Spec: https://drafts.csswg.org/css-images-4/#image-set-notation
-    // WPT: https://github.com/web-platform-tests/wpt/blob/master/css/css-images/image-set/image-set-parsing.html
-    // test image-set(<string>)
-    minify_test(
-      ".foo { background: image-set(\"foo.png\" 2x, url(bar.png) 1x) }",
-      ".foo{background:image-set(\"foo.png\" 2x,\"bar.png\" 1x)}",
-    );
-
-    // test image-set(type(<string>))
-    minify_test(
-      ".foo { background: image-set('foo.webp' type('webp'), url(foo.jpg)) }",
-      ".foo{background:image-set(\"foo.webp\" 1x type(\"webp\"),\"foo.jpg\" 1x)}",
-    );
-    minify_test(
-      ".foo { background: image-set('foo.avif' 2x type('image/avif'), url(foo.png)) }",
-      ".foo{background:image-set(\"foo.avif\" 2x type(\"image/avif\"),\"foo.png\" 1x)}",
-    );
-    minify_test(
-      ".foo { background: image-set(url('example.png') 3x type('image/png')) }",
-      ".foo{background:image-set(\"example.png\" 3x type(\"image/png\"))}",
-    );
-
-    minify_test(
-      ".foo { background: image-set(url(example.png) type('image/png') 1x) }",
-      ".foo{background:image-set(\"example.png\" 1x type(\"image/png\"))}",
-    );
-
-    minify_test(
-      ".foo { background: -webkit-image-set(url(\"foo.png\") 2x, url(bar.png) 1x) }",
-      ".foo{background:-webkit-image-set(url(foo.png) 2x,url(bar.png) 1x)}",
-    );
-
+  fn test_position() {
     test(
       r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
-        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
+        position: relative;
+        position: absolute;
       }
     "#,
       indoc! {r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
-        background: image-set("foo.png" 2x, "bar.png" 1x);
+        position: absolute;
       }
     "#},
     );
 
-    // test image-set(<gradient>)
     test(
       r#"
       .foo {
-        background: image-set(linear-gradient(cornflowerblue, white) 1x, url("detailed-gradient.png") 3x);
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#,
       indoc! {r#"
       .foo {
-        background: image-set(linear-gradient(#6495ed, #fff) 1x, "detailed-gradient.png" 3x);
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#},
     );
@@ -15539,18 +16924,17 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
+        position: sticky;
       }
     "#,
       indoc! {r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
-        background: image-set("foo.png" 2x, "bar.png" 1x);
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#},
       Browsers {
-        chrome: Some(85 << 16),
-        firefox: Some(80 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
@@ -15558,609 +16942,617 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
-        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#,
       indoc! {r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
-        background: image-set("foo.png" 2x, "bar.png" 1x);
+        position: sticky;
       }
     "#},
       Browsers {
-        firefox: Some(80 << 16),
+        safari: Some(13 << 16),
         ..Browsers::default()
       },
     );
 
-    prefix_test(
+    test(
       r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
+        top: 0;
+        left: 0;
+        bottom: 0;
+        right: 0;
       }
     "#,
       indoc! {r#"
       .foo {
-        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+        inset: 0;
       }
     "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
     );
 
-    for property in &[
-      "background",
-      "background-image",
-      "border-image-source",
-      "border-image",
-      "border-image-source",
-      "-webkit-mask-image",
-      "-webkit-mask",
-      "list-style-image",
-      "list-style",
-    ] {
-      prefix_test(
-        &format!(
-          r#"
-        .foo {{
-          {}: url(foo.png);
-          {}: image-set(url("foo.png") 2x, url(bar.png) 1x);
-        }}
-      "#,
-          property, property
-        ),
-        &format!(
-          indoc! {r#"
-        .foo {{
-          {}: url("foo.png");
-          {}: image-set("foo.png" 2x, "bar.png" 1x);
-        }}
-      "#},
-          property, property
-        ),
-        Browsers {
-          ie: Some(11 << 16),
-          chrome: Some(95 << 16),
-          ..Browsers::default()
-        },
-      );
-
-      prefix_test(
-        &format!(
-          r#"
-        .foo {{
-          {}: url(foo.png);
-          {}: image-set(url("foo.png") 2x, url(bar.png) 1x);
-        }}
-      "#,
-          property, property
-        ),
-        &format!(
-          indoc! {r#"
-        .foo {{
-          {}: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
-          {}: image-set("foo.png" 2x, "bar.png" 1x);
-        }}
-      "#},
-          property, property
-        ),
-        Browsers {
-          chrome: Some(95 << 16),
-          ..Browsers::default()
-        },
-      );
-    }
-  }
+    test(
+      r#"
+      .foo {
+        top: 2px;
+        left: 4px;
+        bottom: 2px;
+        right: 4px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        inset: 2px 4px;
+      }
+    "#},
+    );
 
-  #[test]
-  fn test_color() {
-    minify_test(".foo { color: yellow }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgb(255, 255, 0) }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgba(255, 255, 0, 1) }", ".foo{color:#ff0}");
-    minify_test(".foo { color: rgba(255, 255, 0, 0.8) }", ".foo{color:#ff0c}");
-    minify_test(".foo { color: rgb(128, 128, 128) }", ".foo{color:gray}");
-    minify_test(".foo { color: rgb(123, 255, 255) }", ".foo{color:#7bffff}");
-    minify_test(".foo { color: rgba(123, 255, 255, 0.5) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(123 255 255) }", ".foo{color:#7bffff}");
-    minify_test(".foo { color: rgb(123 255 255 / .5) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(123 255 255 / 50%) }", ".foo{color:#7bffff80}");
-    minify_test(".foo { color: rgb(48% 100% 100% / 50%) }", ".foo{color:#7affff80}");
-    minify_test(".foo { color: hsl(100deg, 100%, 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100, 100%, 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100 100% 50%) }", ".foo{color:#5f0}");
-    minify_test(".foo { color: hsl(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsl(100 100% 50% / .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsla(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: hsla(100 100% 50% / .8) }", ".foo{color:#5f0c}");
-    minify_test(".foo { color: transparent }", ".foo{color:#0000}");
-    minify_test(".foo { color: currentColor }", ".foo{color:currentColor}");
-    minify_test(".foo { color: hwb(194 0% 0%) }", ".foo{color:#00c4ff}");
-    minify_test(".foo { color: hwb(194 0% 0% / 50%) }", ".foo{color:#00c4ff80}");
-    minify_test(".foo { color: hwb(194 0% 50%) }", ".foo{color:#006280}");
-    minify_test(".foo { color: hwb(194 50% 0%) }", ".foo{color:#80e1ff}");
-    minify_test(".foo { color: hwb(194 50% 50%) }", ".foo{color:gray}");
-    // minify_test(".foo { color: ActiveText }", ".foo{color:ActiveTet}");
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+    test(
+      r#"
+      .foo {
+        top: 1px;
+        left: 2px;
+        bottom: 3px;
+        right: 4px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        inset: 1px 4px 3px 2px;
+      }
+    "#},
     );
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664 / 100%); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+
+    test(
+      r#"
+      .foo {
+        inset-block-start: 2px;
+        inset-block-end: 2px;
+        inset-inline-start: 4px;
+        inset-inline-end: 4px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        inset-block: 2px;
+        inset-inline: 4px;
+      }
+    "#},
     );
-    minify_test(
-      ".foo { color: lab(29.2345% 39.3825 20.0664 / 50%); }",
-      ".foo{color:lab(29.2345% 39.3825 20.0664/.5)}",
+
+    test(
+      r#"
+      .foo {
+        inset-block-start: 2px;
+        inset-block-end: 3px;
+        inset-inline-start: 4px;
+        inset-inline-end: 5px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        inset-block: 2px 3px;
+        inset-inline: 4px 5px;
+      }
+    "#},
     );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 27); }",
-      ".foo{color:lch(29.2345% 44.2 27)}",
+
+    test(
+      r#"
+      .foo {
+        inset-block-start: 2px;
+        inset-block-end: 3px;
+        inset: 4px;
+        inset-inline-start: 4px;
+        inset-inline-end: 5px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        inset: 4px;
+        inset-inline: 4px 5px;
+      }
+    "#},
     );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 45deg); }",
-      ".foo{color:lch(29.2345% 44.2 45)}",
+
+    prefix_test(
+      r#"
+      .foo {
+        inset-inline-start: 2px;
+      }
+    "#,
+      indoc! {r#"
+      .foo:not(:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        left: 2px;
+      }
+
+      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        left: 2px;
+      }
+
+      .foo:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        right: 2px;
+      }
+
+      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        right: 2px;
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 .5turn); }",
-      ".foo{color:lch(29.2345% 44.2 180)}",
-    );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 27 / 100%); }",
-      ".foo{color:lch(29.2345% 44.2 27)}",
-    );
-    minify_test(
-      ".foo { color: lch(29.2345% 44.2 27 / 50%); }",
-      ".foo{color:lch(29.2345% 44.2 27/.5)}",
-    );
-    minify_test(
-      ".foo { color: oklab(40.101% 0.1147 0.0453); }",
-      ".foo{color:oklab(40.101% .1147 .0453)}",
-    );
-    minify_test(
-      ".foo { color: oklch(40.101% 0.12332 21.555); }",
-      ".foo{color:oklch(40.101% .12332 21.555)}",
-    );
-    minify_test(
-      ".foo { color: oklch(40.101% 0.12332 .5turn); }",
-      ".foo{color:oklch(40.101% .12332 180)}",
-    );
-    minify_test(
-      ".foo { color: color(display-p3 1 0.5 0); }",
-      ".foo{color:color(display-p3 1 .5)}",
-    );
-    minify_test(
-      ".foo { color: color(display-p3 100% 50% 0%); }",
-      ".foo{color:color(display-p3 1 .5)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz-d50 0.2005 0.14089 0.4472); }",
-      ".foo{color:color(xyz-d50 .2005 .14089 .4472)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz-d50 20.05% 14.089% 44.72%); }",
-      ".foo{color:color(xyz-d50 .2005 .14089 .4472)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz-d65 0.2005 0.14089 0.4472); }",
-      ".foo{color:color(xyz .2005 .14089 .4472)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz-d65 20.05% 14.089% 44.72%); }",
-      ".foo{color:color(xyz .2005 .14089 .4472)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz 0.2005 0.14089 0.4472); }",
-      ".foo{color:color(xyz .2005 .14089 .4472)}",
-    );
-    minify_test(
-      ".foo { color: color(xyz 20.05% 14.089% 44.72%); }",
-      ".foo{color:color(xyz .2005 .14089 .4472)}",
-    );
-    minify_test(".foo { color: color(xyz 0.2005 0 0); }", ".foo{color:color(xyz .2005)}");
-    minify_test(".foo { color: color(xyz 0 0 0); }", ".foo{color:color(xyz)}");
-    minify_test(".foo { color: color(xyz 0 1 0); }", ".foo{color:color(xyz 0 1)}");
-    minify_test(".foo { color: color(xyz 0 1); }", ".foo{color:color(xyz 0 1)}");
-    minify_test(".foo { color: color(xyz 1); }", ".foo{color:color(xyz 1)}");
-    minify_test(".foo { color: color(xyz); }", ".foo{color:color(xyz)}");
-    minify_test(
-      ".foo { color: color(xyz 0 1 0 / 20%); }",
-      ".foo{color:color(xyz 0 1/.2)}",
-    );
-    minify_test(".foo { color: color(xyz / 20%); }", ".foo{color:color(xyz/.2)}");
-    minify_test(
-      ".foo { color: color(display-p3 100% 50% 0 / 20%); }",
-      ".foo{color:color(display-p3 1 .5/.2)}",
-    );
-    minify_test(
-      ".foo { color: color(display-p3 100% / 20%); }",
-      ".foo{color:color(display-p3 1/.2)}",
-    );
-    minify_test(".foo { color: hsl(none none none) }", ".foo{color:#000}");
-    minify_test(".foo { color: hwb(none none none) }", ".foo{color:red}");
-    minify_test(".foo { color: rgb(none none none) }", ".foo{color:#000}");
-
-    // If the browser doesn't support `#rrggbbaa` color syntax, it is converted to `transparent`.
-    attr_test(
-      "color: rgba(0, 0, 0, 0)",
-      "color:transparent",
-      true,
-      Some(Browsers {
-        chrome: Some(61 << 16), // Chrome >= 62 supports `#rrggbbaa` color.
-        ..Browsers::default()
-      }),
-    );
-
-    attr_test(
-      "color: #0000",
-      "color:transparent",
-      true,
-      Some(Browsers {
-        chrome: Some(61 << 16), // Chrome >= 62 supports `#rrggbbaa` color.
-        ..Browsers::default()
-      }),
-    );
-
-    attr_test(
-      "color: transparent",
-      "color:transparent",
-      true,
-      Some(Browsers {
-        chrome: Some(61 << 16),
-        ..Browsers::default()
-      }),
-    );
-
-    attr_test(
-      "color: rgba(0, 0, 0, 0)",
-      "color: rgba(0, 0, 0, 0)",
-      false,
-      Some(Browsers {
-        chrome: Some(61 << 16),
-        ..Browsers::default()
-      }),
-    );
-
-    attr_test(
-      "color: rgba(255, 0, 0, 0)",
-      "color:rgba(255,0,0,0)",
-      true,
-      Some(Browsers {
-        chrome: Some(61 << 16),
-        ..Browsers::default()
-      }),
-    );
-
-    attr_test(
-      "color: rgba(255, 0, 0, 0)",
-      "color:#f000",
-      true,
-      Some(Browsers {
-        chrome: Some(62 << 16),
-        ..Browsers::default()
-      }),
+
+    prefix_test(
+      r#"
+      .foo {
+        inset-inline-start: 2px;
+        inset-inline-end: 4px;
+      }
+    "#,
+      indoc! {r#"
+      .foo:not(:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        left: 2px;
+        right: 4px;
+      }
+
+      .foo:not(:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi))) {
+        left: 2px;
+        right: 4px;
+      }
+
+      .foo:-webkit-any(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        left: 4px;
+        right: 2px;
+      }
+
+      .foo:is(:lang(ae), :lang(ar), :lang(arc), :lang(bcc), :lang(bqi), :lang(ckb), :lang(dv), :lang(fa), :lang(glk), :lang(he), :lang(ku), :lang(mzn), :lang(nqo), :lang(pnb), :lang(ps), :lang(sd), :lang(ug), :lang(ur), :lang(yi)) {
+        left: 4px;
+        right: 2px;
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      },
     );
 
     prefix_test(
-      ".foo { color: rgba(123, 456, 789, 0.5) }",
-      indoc! { r#"
-        .foo {
-          color: #7bffff80;
-        }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
+      r#"
+      .foo {
+        inset-inline: 2px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        left: 2px;
+        right: 2px;
+      }
+    "#
       },
-    );
-
-    prefix_test(
-      ".foo { color: rgba(123, 255, 255, 0.5) }",
-      indoc! { r#"
-        .foo {
-          color: rgba(123, 255, 255, .5);
-        }
-      "#},
       Browsers {
-        ie: Some(11 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { color: #7bffff80 }",
-      indoc! { r#"
-        .foo {
-          color: rgba(123, 255, 255, .5);
-        }
-      "#},
-      Browsers {
-        ie: Some(11 << 16),
-        ..Browsers::default()
+      r#"
+      .foo {
+        inset-block-start: 2px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        top: 2px;
+      }
+    "#
       },
-    );
-
-    prefix_test(
-      ".foo { color: rgba(123, 456, 789, 0.5) }",
-      indoc! { r#"
-        .foo {
-          color: rgba(123, 255, 255, .5);
-        }
-      "#},
       Browsers {
-        firefox: Some(48 << 16),
-        safari: Some(10 << 16),
-        ios_saf: Some(9 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { color: rgba(123, 456, 789, 0.5) }",
-      indoc! { r#"
-        .foo {
-          color: #7bffff80;
-        }
-      "#},
-      Browsers {
-        firefox: Some(49 << 16),
-        safari: Some(10 << 16),
-        ios_saf: Some(10 << 16),
-        ..Browsers::default()
+      r#"
+      .foo {
+        inset-block-end: 2px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        bottom: 2px;
+      }
+    "#
       },
-    );
-
-    prefix_test(
-      ".foo { background-color: lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          background-color: #b32323;
-          background-color: lab(40% 56.6 39);
-        }
-      "#},
       Browsers {
-        chrome: Some(90 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: lch(40% 68.735435 34.568626) }",
-      indoc! { r#"
-        .foo {
-          background-color: #b32323;
-          background-color: lch(40% 68.7354 34.5686);
-        }
-      "#},
+      r#"
+      .foo {
+        top: 1px;
+        left: 2px;
+        bottom: 3px;
+        right: 4px;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        top: 1px;
+        bottom: 3px;
+        left: 2px;
+        right: 4px;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
+        safari: Some(8 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
-      indoc! { r#"
-        .foo {
-          background-color: #c65d07;
-          background-color: lab(52.2319% 40.1449 59.9171);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_overflow() {
+    minify_test(".foo { overflow: hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden auto }", ".foo{overflow:hidden auto}");
+
+    test(
+      r#"
+      .foo {
+        overflow-x: hidden;
+        overflow-y: auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#},
     );
 
+    test(
+      r#"
+      .foo {
+        overflow: hidden;
+        overflow-y: auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#},
+    );
+    test(
+      r#"
+      .foo {
+        overflow: hidden;
+        overflow-y: var(--y);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden;
+        overflow-y: var(--y);
+      }
+    "#},
+    );
     prefix_test(
-      ".foo { background-color: oklch(40% 0.1268735435 34.568626) }",
-      indoc! { r#"
-        .foo {
-          background-color: #7e250f;
-          background-color: lab(29.2661% 38.2437 35.3889);
-        }
-      "#},
+      r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow-x: hidden;
+        overflow-y: auto;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
+        chrome: Some(67 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
-      ".foo { background-color: lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          background-color: lab(40% 56.6 39);
-        }
-      "#},
+      r#"
+      .foo {
+        overflow: hidden hidden;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden;
+      }
+    "#},
       Browsers {
-        safari: Some(15 << 16),
+        chrome: Some(67 << 16),
         ..Browsers::default()
       },
     );
-
     prefix_test(
-      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
-      indoc! { r#"
-        .foo {
-          background-color: #c65d07;
-          background-color: lab(52.2319% 40.1449 59.9171);
-        }
-      "#},
+      r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        overflow: hidden auto;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(15 << 16),
+        chrome: Some(68 << 16),
         ..Browsers::default()
       },
     );
 
+    minify_test(".foo { text-overflow: ellipsis }", ".foo{text-overflow:ellipsis}");
     prefix_test(
-      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
-      indoc! { r#"
-        .foo {
-          background-color: #c65d07;
-          background-color: color(display-p3 .724144 .386777 .148795);
-          background-color: lab(52.2319% 40.1449 59.9171);
-        }
-      "#},
+      r#"
+      .foo {
+        text-overflow: ellipsis;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -o-text-overflow: ellipsis;
+        text-overflow: ellipsis;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
+        safari: Some(4 << 16),
+        opera: Some(10 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: lab(40% 56.6 39) }",
-      indoc! { r#"
-        .foo {
-          background-color: #b32323;
-          background-color: color(display-p3 .643308 .192455 .167712);
-          background-color: lab(40% 56.6 39);
-        }
-      "#},
+      r#"
+      .foo {
+        -o-text-overflow: ellipsis;
+        text-overflow: ellipsis;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        text-overflow: ellipsis;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
+        safari: Some(4 << 16),
+        opera: Some(14 << 16),
         ..Browsers::default()
       },
     );
+  }
+
+  #[test]
+  fn test_ui() {
+    minify_test(".foo { resize: both }", ".foo{resize:both}");
+    minify_test(".foo { resize: Horizontal }", ".foo{resize:horizontal}");
+    minify_test(".foo { cursor: ew-resize }", ".foo{cursor:ew-resize}");
+    minify_test(
+      ".foo { cursor: url(\"test.cur\"), ew-resize }",
+      ".foo{cursor:url(test.cur),ew-resize}",
+    );
+    minify_test(
+      ".foo { cursor: url(\"test.cur\"), url(\"foo.cur\"), ew-resize }",
+      ".foo{cursor:url(test.cur),url(foo.cur),ew-resize}",
+    );
+    minify_test(".foo { caret-color: auto }", ".foo{caret-color:auto}");
+    minify_test(".foo { caret-color: yellow }", ".foo{caret-color:#ff0}");
+    minify_test(".foo { caret-shape: block }", ".foo{caret-shape:block}");
+    minify_test(".foo { caret: yellow block }", ".foo{caret:#ff0 block}");
+    minify_test(".foo { caret: block yellow }", ".foo{caret:#ff0 block}");
+    minify_test(".foo { caret: block }", ".foo{caret:block}");
+    minify_test(".foo { caret: yellow }", ".foo{caret:#ff0}");
+    minify_test(".foo { caret: auto auto }", ".foo{caret:auto}");
+    minify_test(".foo { caret: auto }", ".foo{caret:auto}");
+    minify_test(".foo { caret: yellow auto }", ".foo{caret:#ff0}");
+    minify_test(".foo { caret: auto block }", ".foo{caret:block}");
+    minify_test(".foo { user-select: none }", ".foo{user-select:none}");
+    minify_test(".foo { -webkit-user-select: none }", ".foo{-webkit-user-select:none}");
+    minify_test(".foo { accent-color: auto }", ".foo{accent-color:auto}");
+    minify_test(".foo { accent-color: yellow }", ".foo{accent-color:#ff0}");
+    minify_test(".foo { appearance: None }", ".foo{appearance:none}");
+    minify_test(
+      ".foo { -webkit-appearance: textfield }",
+      ".foo{-webkit-appearance:textfield}",
+    );
 
     prefix_test(
-      ".foo { background-color: oklch(59.686% 0.15619 49.7694); }",
-      indoc! { r#"
-        .foo {
-          background-color: #c65d06;
-          background-color: lab(52.2321% 40.1417 59.9527);
-        }
-      "#},
+      r#"
+      .foo {
+        user-select: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -webkit-user-select: none;
+        -moz-user-select: none;
+        -ms-user-select: none;
+        user-select: none;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(15 << 16),
+        safari: Some(8 << 16),
+        opera: Some(5 << 16),
+        firefox: Some(10 << 16),
+        ie: Some(10 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(sRGB 0.41587 0.503670 0.36664); }",
-      indoc! { r#"
-        .foo {
-          background-color: #6a805d;
-          background-color: color(srgb .41587 .50367 .36664);
-        }
-      "#},
+      r#"
+      .foo {
+        -webkit-user-select: none;
+        -moz-user-select: none;
+        -ms-user-select: none;
+        user-select: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -webkit-user-select: none;
+        user-select: none;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
+        safari: Some(8 << 16),
+        opera: Some(80 << 16),
+        firefox: Some(80 << 16),
+        edge: Some(80 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
-      indoc! { r#"
-        .foo {
-          background-color: #6a805d;
-          background-color: color(display-p3 .43313 .50108 .3795);
-        }
-      "#},
+      r#"
+      .foo {
+        -webkit-user-select: none;
+        -moz-user-select: none;
+        -ms-user-select: none;
+        user-select: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        user-select: none;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
+        opera: Some(80 << 16),
+        firefox: Some(80 << 16),
+        edge: Some(80 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
-      indoc! { r#"
-        .foo {
-          background-color: #6a805d;
-          background-color: color(display-p3 .43313 .50108 .3795);
-        }
-      "#},
+      r#"
+      .foo {
+        appearance: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        -ms-appearance: none;
+        appearance: none;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
+        safari: Some(8 << 16),
+        chrome: Some(80 << 16),
+        firefox: Some(10 << 16),
+        ie: Some(11 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
-      indoc! { r#"
-        .foo {
-          background-color: color(display-p3 .43313 .50108 .3795);
-        }
-      "#},
+      r#"
+      .foo {
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        -ms-appearance: none;
+        appearance: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        -webkit-appearance: none;
+        appearance: none;
+      }
+    "#},
       Browsers {
-        safari: Some(14 << 16),
+        safari: Some(15 << 16),
+        chrome: Some(85 << 16),
+        firefox: Some(80 << 16),
+        edge: Some(85 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
-      indoc! { r#"
-        .foo {
-          background-color: #6a805d;
-          background-color: color(display-p3 .43313 .50108 .3795);
-        }
-      "#},
+      r#"
+      .foo {
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        -ms-appearance: none;
+        appearance: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        appearance: none;
+      }
+    "#},
       Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(15 << 16),
+        chrome: Some(85 << 16),
+        firefox: Some(80 << 16),
+        edge: Some(85 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      ".foo { caret-color: lch(50.998% 135.363 338) }",
       indoc! { r#"
         .foo {
-          background-color: #6a805d;
-          background-color: color(display-p3 .43313 .50108 .3795);
+          caret-color: #ee00be;
+          caret-color: color(display-p3 .972962 -.362078 .804206);
+          caret-color: lch(50.998% 135.363 338);
         }
       "#},
       Browsers {
         chrome: Some(90 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(a98-rgb 0.44091 0.49971 0.37408); }",
+      ".foo { caret: lch(50.998% 135.363 338) block }",
       indoc! { r#"
         .foo {
-          background-color: #6a805d;
-          background-color: color(a98-rgb .44091 .49971 .37408);
+          caret: #ee00be block;
+          caret: color(display-p3 .972962 -.362078 .804206) block;
+          caret: lch(50.998% 135.363 338) block;
         }
       "#},
       Browsers {
         chrome: Some(90 << 16),
+        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(a98-rgb 0.44091 0.49971 0.37408); }",
+      ".foo { caret: lch(50.998% 135.363 338) var(--foo) }",
       indoc! { r#"
         .foo {
-          background-color: color(a98-rgb .44091 .49971 .37408);
+          caret: #ee00be var(--foo);
         }
-      "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
-    );
 
-    prefix_test(
-      ".foo { background-color: color(prophoto-rgb 0.36589 0.41717 0.31333); }",
-      indoc! { r#"
-        .foo {
-          background-color: #6a805d;
-          background-color: color(prophoto-rgb .36589 .41717 .31333);
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            caret: lab(50.998% 125.506 -50.7078) var(--foo);
+          }
         }
       "#},
       Browsers {
@@ -16168,41 +17560,107 @@ mod tests {
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      ".foo { background-color: color(rec2020 0.42210 0.47580 0.35605); }",
-      indoc! { r#"
-        .foo {
-          background-color: #728765;
-          background-color: color(rec2020 .4221 .4758 .35605);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_list() {
+    minify_test(".foo { list-style-type: disc; }", ".foo{list-style-type:disc}");
+    minify_test(".foo { list-style-type: \"★\"; }", ".foo{list-style-type:\"★\"}");
+    minify_test(
+      ".foo { list-style-type: symbols(cyclic '○' '●'); }",
+      ".foo{list-style-type:symbols(cyclic \"○\" \"●\")}",
+    );
+    minify_test(
+      ".foo { list-style-type: symbols('○' '●'); }",
+      ".foo{list-style-type:symbols(\"○\" \"●\")}",
+    );
+    minify_test(
+      ".foo { list-style-type: symbols(symbolic '○' '●'); }",
+      ".foo{list-style-type:symbols(\"○\" \"●\")}",
+    );
+    minify_test(
+      ".foo { list-style-type: symbols(symbolic url('ellipse.png')); }",
+      ".foo{list-style-type:symbols(url(ellipse.png))}",
+    );
+    minify_test(
+      ".foo { list-style-image: url('ellipse.png'); }",
+      ".foo{list-style-image:url(ellipse.png)}",
+    );
+    minify_test(
+      ".foo { list-style-position: outside; }",
+      ".foo{list-style-position:outside}",
+    );
+    minify_test(
+      ".foo { list-style: \"★\" url(ellipse.png) outside; }",
+      ".foo{list-style:\"★\" url(ellipse.png)}",
+    );
+
+    test(
+      r#"
+      .foo {
+        list-style-type: disc;
+        list-style-image: url(ellipse.png);
+        list-style-position: outside;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        list-style: url("ellipse.png");
+      }
+    "#},
+    );
+
+    test(
+      r#"
+      .foo {
+        list-style: \"★\" url(ellipse.png) outside;
+        list-style-image: none;
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        list-style: \"★\";
+      }
+    "#},
+    );
+
+    test(
+      r#"
+      .foo {
+        list-style: \"★\" url(ellipse.png) outside;
+        list-style-image: var(--img);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        list-style: \"★\" url("ellipse.png");
+        list-style-image: var(--img);
+      }
+    "#},
     );
 
     prefix_test(
-      ".foo { background-color: color(xyz-d50 0.2005 0.14089 0.4472); }",
+      ".foo { list-style-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
       indoc! { r#"
         .foo {
-          background-color: #7654cd;
-          background-color: color(xyz-d50 .2005 .14089 .4472);
+          list-style-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
+          list-style-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
+          list-style-image: linear-gradient(#ff0f0e, #7773ff);
+          list-style-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
         }
       "#},
       Browsers {
-        chrome: Some(90 << 16),
+        chrome: Some(8 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      ".foo { background-color: color(xyz-d65 0.21661 0.14602 0.59452); }",
+      ".foo { list-style: \"★\" linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
       indoc! { r#"
         .foo {
-          background-color: #7654cd;
-          background-color: color(xyz .21661 .14602 .59452);
+          list-style: "★" linear-gradient(#ff0f0e, #7773ff);
+          list-style: "★" linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
         }
       "#},
       Browsers {
@@ -16212,110 +17670,104 @@ mod tests {
     );
 
     prefix_test(
-      ".foo { background-color: lch(50.998% 135.363 338) }",
+      ".foo { list-style: var(--foo) linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
       indoc! { r#"
         .foo {
-          background-color: #ee00be;
-          background-color: color(display-p3 .972962 -.362078 .804206);
-          background-color: lch(50.998% 135.363 338);
+          list-style: var(--foo) linear-gradient(#ff0f0e, #7773ff);
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            list-style: var(--foo) linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586));
+          }
         }
       "#},
       Browsers {
         chrome: Some(90 << 16),
-        safari: Some(14 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      ".foo { color: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          color: #ee00be;
-          color: color(display-p3 .972962 -.362078 .804206);
-          color: lch(50.998% 135.363 338);
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_image_set() {
+    // Spec: https://drafts.csswg.org/css-images-4/#image-set-notation
+    // WPT: https://github.com/web-platform-tests/wpt/blob/master/css/css-images/image-set/image-set-parsing.html
+    // test image-set(<string>)
+    minify_test(
+      ".foo { background: image-set(\"foo.png\" 2x, url(bar.png) 1x) }",
+      ".foo{background:image-set(\"foo.png\" 2x,\"bar.png\" 1x)}",
     );
 
-    prefix_test(
-      ".foo { background: var(--image) lch(40% 68.735435 34.568626) }",
-      indoc! { r#"
-        .foo {
-          background: var(--image) #b32323;
-        }
+    // test image-set(type(<string>))
+    minify_test(
+      ".foo { background: image-set('foo.webp' type('webp'), url(foo.jpg)) }",
+      ".foo{background:image-set(\"foo.webp\" 1x type(\"webp\"),\"foo.jpg\" 1x)}",
+    );
+    minify_test(
+      ".foo { background: image-set('foo.avif' 2x type('image/avif'), url(foo.png)) }",
+      ".foo{background:image-set(\"foo.avif\" 2x type(\"image/avif\"),\"foo.png\" 1x)}",
+    );
+    minify_test(
+      ".foo { background: image-set(url('example.png') 3x type('image/png')) }",
+      ".foo{background:image-set(\"example.png\" 3x type(\"image/png\"))}",
+    );
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            background: var(--image) lab(40% 56.6 39);
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    minify_test(
+      ".foo { background: image-set(url(example.png) type('image/png') 1x) }",
+      ".foo{background:image-set(\"example.png\" 1x type(\"image/png\"))}",
     );
 
-    prefix_test(
+    minify_test(
+      ".foo { background: -webkit-image-set(url(\"foo.png\") 2x, url(bar.png) 1x) }",
+      ".foo{background:-webkit-image-set(url(foo.png) 2x,url(bar.png) 1x)}",
+    );
+
+    test(
       r#"
       .foo {
-        color: red;
-        color: lab(40% 56.6 39);
+        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
+        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
       }
     "#,
       indoc! {r#"
       .foo {
-        color: red;
-        color: lab(40% 56.6 39);
+        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+        background: image-set("foo.png" 2x, "bar.png" 1x);
       }
-    "#
-      },
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+    "#},
     );
-    prefix_test(
+
+    // test image-set(<gradient>)
+    test(
       r#"
       .foo {
-        color: red;
-        color: lab(40% 56.6 39);
+        background: image-set(linear-gradient(cornflowerblue, white) 1x, url("detailed-gradient.png") 3x);
       }
     "#,
       indoc! {r#"
       .foo {
-        color: lab(40% 56.6 39);
+        background: image-set(linear-gradient(#6495ed, #fff) 1x, "detailed-gradient.png" 3x);
       }
-    "#
-      },
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
-      },
+    "#},
     );
 
     prefix_test(
       r#"
       .foo {
-        color: var(--fallback);
-        color: lab(40% 56.6 39);
+        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
       }
     "#,
       indoc! {r#"
       .foo {
-        color: var(--fallback);
-        color: lab(40% 56.6 39);
+        background: url("bar.png");
+        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+        background: image-set("foo.png" 2x, "bar.png" 1x);
       }
-    "#
-      },
+    "#},
       Browsers {
-        safari: Some(14 << 16),
+        chrome: Some(85 << 16),
+        firefox: Some(80 << 16),
         ..Browsers::default()
       },
     );
@@ -16323,18 +17775,19 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        color: var(--fallback);
-        color: lab(40% 56.6 39);
+        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
+        background: image-set(url("foo.png") 2x, url(bar.png) 1x);
       }
     "#,
       indoc! {r#"
       .foo {
-        color: lab(40% 56.6 39);
+        background: url("bar.png");
+        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+        background: image-set("foo.png" 2x, "bar.png" 1x);
       }
-    "#
-      },
+    "#},
       Browsers {
-        safari: Some(16 << 16),
+        firefox: Some(80 << 16),
         ..Browsers::default()
       },
     );
@@ -16342,57 +17795,106 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        color: red;
-        color: var(--foo, lab(40% 56.6 39));
+        background: -webkit-image-set(url("foo.png") 2x, url(bar.png) 1x);
       }
     "#,
       indoc! {r#"
       .foo {
-        color: var(--foo, color(display-p3 .643308 .192455 .167712));
-      }
-
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          color: var(--foo, lab(40% 56.6 39));
-        }
+        background: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
       }
-    "#
-      },
+    "#},
       Browsers {
-        safari: Some(14 << 16),
+        chrome: Some(95 << 16),
         ..Browsers::default()
       },
     );
 
+    for property in &[
+      "background",
+      "background-image",
+      "border-image-source",
+      "border-image",
+      "border-image-source",
+      "-webkit-mask-image",
+      "-webkit-mask",
+      "list-style-image",
+      "list-style",
+    ] {
+      prefix_test(
+        &format!(
+          r#"
+        .foo {{
+          {}: url(foo.png);
+          {}: image-set(url("foo.png") 2x, url(bar.png) 1x);
+        }}
+      "#,
+          property, property
+        ),
+        &format!(
+          indoc! {r#"
+        .foo {{
+          {}: url("foo.png");
+          {}: image-set("foo.png" 2x, "bar.png" 1x);
+        }}
+      "#},
+          property, property
+        ),
+        Browsers {
+          ie: Some(11 << 16),
+          chrome: Some(95 << 16),
+          ..Browsers::default()
+        },
+      );
+
+      prefix_test(
+        &format!(
+          r#"
+        .foo {{
+          {}: url(foo.png);
+          {}: image-set(url("foo.png") 2x, url(bar.png) 1x);
+        }}
+      "#,
+          property, property
+        ),
+        &format!(
+          indoc! {r#"
+        .foo {{
+          {}: -webkit-image-set(url("foo.png") 2x, url("bar.png") 1x);
+          {}: image-set("foo.png" 2x, "bar.png" 1x);
+        }}
+      "#},
+          property, property
+        ),
+        Browsers {
+          chrome: Some(95 << 16),
+          ..Browsers::default()
+        },
+      );
+    }
+
+    minify_test(
+      ".foo { background: image-set(url(foo.png) 192dpi, url(bar.png) 96dpi) }",
+      ".foo{background:image-set(\"foo.png\" 2x,\"bar.png\" 1x)}",
+    );
+    minify_test(
+      ".foo { background: -webkit-image-set(url(foo.png) 2dppx, url(bar.png) 1dppx) }",
+      ".foo{background:-webkit-image-set(url(foo.png) 2x,url(bar.png) 1x)}",
+    );
+
     prefix_test(
       r#"
       .foo {
-        --a: rgb(0 0 0 / var(--alpha));
-        --b: rgb(50% 50% 50% / var(--alpha));
-        --c: rgb(var(--x) 0 0);
-        --d: rgb(0 var(--x) 0);
-        --e: rgb(0 0 var(--x));
-        --f: rgb(var(--x) 0 0 / var(--alpha));
-        --g: rgb(0 var(--x) 0 / var(--alpha));
-        --h: rgb(0 0 var(--x) / var(--alpha));
-        --i: rgb(none 0 0 / var(--alpha));
+        background-image: image-set(url("foo.png") 3x, url(bar.png) 2x), linear-gradient(red, blue);
       }
-      "#,
-      indoc! { r#"
-        .foo {
-          --a: rgba(0, 0, 0, var(--alpha));
-          --b: rgba(128, 128, 128, var(--alpha));
-          --c: rgb(var(--x) 0 0);
-          --d: rgb(0 var(--x) 0);
-          --e: rgb(0 0 var(--x));
-          --f: rgb(var(--x) 0 0 / var(--alpha));
-          --g: rgb(0 var(--x) 0 / var(--alpha));
-          --h: rgb(0 0 var(--x) / var(--alpha));
-          --i: rgb(none 0 0 / var(--alpha));
-        }
-      "#},
+    "#,
+      indoc! {r#"
+      .foo {
+        background-image: url("bar.png"), linear-gradient(red, #00f);
+        background-image: image-set("foo.png" 3x, "bar.png" 2x), linear-gradient(red, #00f);
+      }
+    "#},
       Browsers {
-        safari: Some(11 << 16),
+        firefox: Some(80 << 16),
         ..Browsers::default()
       },
     );
@@ -16400,1389 +17902,2150 @@ mod tests {
     prefix_test(
       r#"
       .foo {
-        --a: rgb(0 0 0 / var(--alpha));
-        --b: rgb(50% 50% 50% / var(--alpha));
-        --c: rgb(var(--x) 0 0);
-        --d: rgb(0 var(--x) 0);
-        --e: rgb(0 0 var(--x));
-        --f: rgb(var(--x) 0 0 / var(--alpha));
-        --g: rgb(0 var(--x) 0 / var(--alpha));
-        --h: rgb(0 0 var(--x) / var(--alpha));
-        --i: rgb(none 0 0 / var(--alpha));
+        list-style-image: image-set(url("foo.png") 2x, url(bar.png) 192dpi, url(baz.png) 1x);
       }
-      "#,
-      indoc! { r#"
-        .foo {
-          --a: rgb(0 0 0 / var(--alpha));
-          --b: rgb(128 128 128 / var(--alpha));
-          --c: rgb(var(--x) 0 0);
-          --d: rgb(0 var(--x) 0);
-          --e: rgb(0 0 var(--x));
-          --f: rgb(var(--x) 0 0 / var(--alpha));
-          --g: rgb(0 var(--x) 0 / var(--alpha));
-          --h: rgb(0 0 var(--x) / var(--alpha));
-          --i: rgb(none 0 0 / var(--alpha));
-        }
-      "#},
+    "#,
+      indoc! {r#"
+      .foo {
+        list-style-image: url("baz.png");
+        list-style-image: image-set("foo.png" 2x, "bar.png" 2x, "baz.png" 1x);
+      }
+    "#},
       Browsers {
-        safari: Some(13 << 16),
+        ie: Some(11 << 16),
         ..Browsers::default()
       },
     );
+  }
 
-    prefix_test(
-      r#"
-      .foo {
-        --a: hsl(270 100% 50% / var(--alpha));
-        --b: hsl(var(--x) 0 0);
-        --c: hsl(0 var(--x) 0);
-        --d: hsl(0 0 var(--x));
-        --e: hsl(var(--x) 0 0 / var(--alpha));
-        --f: hsl(0 var(--x) 0 / var(--alpha));
-        --g: hsl(0 0 var(--x) / var(--alpha));
-        --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
-        --i: hsl(none 100% 50% / var(--alpha));
-      }
-      "#,
+  #[test]
+  fn test_color() {
+    minify_test(".foo { color: yellow }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgb(255, 255, 0) }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgba(255, 255, 0, 1) }", ".foo{color:#ff0}");
+    minify_test(".foo { color: rgba(255, 255, 0, 0.8) }", ".foo{color:#ff0c}");
+    minify_test(".foo { color: rgb(128, 128, 128) }", ".foo{color:gray}");
+    minify_test(".foo { color: rgb(123, 255, 255) }", ".foo{color:#7bffff}");
+    minify_test(".foo { color: rgba(123, 255, 255, 0.5) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(123 255 255) }", ".foo{color:#7bffff}");
+    minify_test(".foo { color: rgb(123 255 255 / .5) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(123 255 255 / 50%) }", ".foo{color:#7bffff80}");
+    minify_test(".foo { color: rgb(48% 100% 100% / 50%) }", ".foo{color:#7affff80}");
+    minify_test(".foo { color: hsl(100deg, 100%, 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100, 100%, 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100 100% 50%) }", ".foo{color:#5f0}");
+    minify_test(".foo { color: hsl(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsl(100 100% 50% / .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsla(100, 100%, 50%, .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: hsla(100 100% 50% / .8) }", ".foo{color:#5f0c}");
+    minify_test(".foo { color: transparent }", ".foo{color:#0000}");
+    minify_test(".foo { color: currentColor }", ".foo{color:currentColor}");
+    minify_test(".foo { color: hwb(194 0% 0%) }", ".foo{color:#00c4ff}");
+    minify_test(".foo { color: hwb(194 0% 0% / 50%) }", ".foo{color:#00c4ff80}");
+    minify_test(".foo { color: hwb(194 0% 50%) }", ".foo{color:#006280}");
+    minify_test(".foo { color: hwb(194 50% 0%) }", ".foo{color:#80e1ff}");
+    minify_test(".foo { color: hwb(194 50% 50%) }", ".foo{color:gray}");
+    // minify_test(".foo { color: ActiveText }", ".foo{color:ActiveTet}");
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+    );
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664 / 100%); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664)}",
+    );
+    minify_test(
+      ".foo { color: lab(29.2345% 39.3825 20.0664 / 50%); }",
+      ".foo{color:lab(29.2345% 39.3825 20.0664/.5)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 27); }",
+      ".foo{color:lch(29.2345% 44.2 27)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 45deg); }",
+      ".foo{color:lch(29.2345% 44.2 45)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 .5turn); }",
+      ".foo{color:lch(29.2345% 44.2 180)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 27 / 100%); }",
+      ".foo{color:lch(29.2345% 44.2 27)}",
+    );
+    minify_test(
+      ".foo { color: lch(29.2345% 44.2 27 / 50%); }",
+      ".foo{color:lch(29.2345% 44.2 27/.5)}",
+    );
+    minify_test(
+      ".foo { color: oklab(40.101% 0.1147 0.0453); }",
+      ".foo{color:oklab(40.101% .1147 .0453)}",
+    );
+    minify_test(
+      ".foo { color: oklch(40.101% 0.12332 21.555); }",
+      ".foo{color:oklch(40.101% .12332 21.555)}",
+    );
+    minify_test(
+      ".foo { color: oklch(40.101% 0.12332 .5turn); }",
+      ".foo{color:oklch(40.101% .12332 180)}",
+    );
+    minify_test(
+      ".foo { color: color(display-p3 1 0.5 0); }",
+      ".foo{color:color(display-p3 1 .5)}",
+    );
+    minify_test(
+      ".foo { color: color(display-p3 100% 50% 0%); }",
+      ".foo{color:color(display-p3 1 .5)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz-d50 0.2005 0.14089 0.4472); }",
+      ".foo{color:color(xyz-d50 .2005 .14089 .4472)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz-d50 20.05% 14.089% 44.72%); }",
+      ".foo{color:color(xyz-d50 .2005 .14089 .4472)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz-d65 0.2005 0.14089 0.4472); }",
+      ".foo{color:color(xyz .2005 .14089 .4472)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz-d65 20.05% 14.089% 44.72%); }",
+      ".foo{color:color(xyz .2005 .14089 .4472)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz 0.2005 0.14089 0.4472); }",
+      ".foo{color:color(xyz .2005 .14089 .4472)}",
+    );
+    minify_test(
+      ".foo { color: color(xyz 20.05% 14.089% 44.72%); }",
+      ".foo{color:color(xyz .2005 .14089 .4472)}",
+    );
+    minify_test(".foo { color: color(xyz 0.2005 0 0); }", ".foo{color:color(xyz .2005)}");
+    minify_test(".foo { color: color(xyz 0 0 0); }", ".foo{color:color(xyz)}");
+    minify_test(".foo { color: color(xyz 0 1 0); }", ".foo{color:color(xyz 0 1)}");
+    minify_test(".foo { color: color(xyz 0 1); }", ".foo{color:color(xyz 0 1)}");
+    minify_test(".foo { color: color(xyz 1); }", ".foo{color:color(xyz 1)}");
+    minify_test(".foo { color: color(xyz); }", ".foo{color:color(xyz)}");
+    minify_test(
+      ".foo { color: color(xyz 0 1 0 / 20%); }",
+      ".foo{color:color(xyz 0 1/.2)}",
+    );
+    minify_test(".foo { color: color(xyz / 20%); }", ".foo{color:color(xyz/.2)}");
+    minify_test(
+      ".foo { color: color(display-p3 100% 50% 0 / 20%); }",
+      ".foo{color:color(display-p3 1 .5/.2)}",
+    );
+    minify_test(
+      ".foo { color: color(display-p3 100% / 20%); }",
+      ".foo{color:color(display-p3 1/.2)}",
+    );
+    minify_test(".foo { color: hsl(none none none) }", ".foo{color:#000}");
+    minify_test(".foo { color: hwb(none none none) }", ".foo{color:red}");
+    minify_test(".foo { color: rgb(none none none) }", ".foo{color:#000}");
+
+    // If the browser doesn't support `#rrggbbaa` color syntax, it is converted to `transparent`.
+    attr_test(
+      "color: rgba(0, 0, 0, 0)",
+      "color:transparent",
+      true,
+      Some(Browsers {
+        chrome: Some(61 << 16), // Chrome >= 62 supports `#rrggbbaa` color.
+        ..Browsers::default()
+      }),
+    );
+
+    attr_test(
+      "color: #0000",
+      "color:transparent",
+      true,
+      Some(Browsers {
+        chrome: Some(61 << 16), // Chrome >= 62 supports `#rrggbbaa` color.
+        ..Browsers::default()
+      }),
+    );
+
+    attr_test(
+      "color: transparent",
+      "color:transparent",
+      true,
+      Some(Browsers {
+        chrome: Some(61 << 16),
+        ..Browsers::default()
+      }),
+    );
+
+    attr_test(
+      "color: rgba(0, 0, 0, 0)",
+      "color: rgba(0, 0, 0, 0)",
+      false,
+      Some(Browsers {
+        chrome: Some(61 << 16),
+        ..Browsers::default()
+      }),
+    );
+
+    attr_test(
+      "color: rgba(255, 0, 0, 0)",
+      "color:rgba(255,0,0,0)",
+      true,
+      Some(Browsers {
+        chrome: Some(61 << 16),
+        ..Browsers::default()
+      }),
+    );
+
+    attr_test(
+      "color: rgba(255, 0, 0, 0)",
+      "color:#f000",
+      true,
+      Some(Browsers {
+        chrome: Some(62 << 16),
+        ..Browsers::default()
+      }),
+    );
+
+    prefix_test(
+      ".foo { color: rgba(123, 456, 789, 0.5) }",
       indoc! { r#"
         .foo {
-          --a: hsla(270, 100%, 50%, var(--alpha));
-          --b: hsl(var(--x) 0 0);
-          --c: hsl(0 var(--x) 0);
-          --d: hsl(0 0 var(--x));
-          --e: hsl(var(--x) 0 0 / var(--alpha));
-          --f: hsl(0 var(--x) 0 / var(--alpha));
-          --g: hsl(0 0 var(--x) / var(--alpha));
-          --h: hsla(270, 100%, 50%, calc(var(--alpha) / 2));
-          --i: hsl(none 100% 50% / var(--alpha));
+          color: #7bffff80;
         }
       "#},
       Browsers {
-        safari: Some(11 << 16),
+        chrome: Some(95 << 16),
         ..Browsers::default()
       },
     );
 
     prefix_test(
-      r#"
-      .foo {
-        --a: hsl(270 100% 50% / var(--alpha));
-        --b: hsl(var(--x) 0 0);
-        --c: hsl(0 var(--x) 0);
-        --d: hsl(0 0 var(--x));
-        --e: hsl(var(--x) 0 0 / var(--alpha));
-        --f: hsl(0 var(--x) 0 / var(--alpha));
-        --g: hsl(0 0 var(--x) / var(--alpha));
-        --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
-        --i: hsl(none 100% 50% / var(--alpha));
-      }
-      "#,
+      ".foo { color: rgba(123, 255, 255, 0.5) }",
       indoc! { r#"
         .foo {
-          --a: hsl(270 100% 50% / var(--alpha));
-          --b: hsl(var(--x) 0 0);
-          --c: hsl(0 var(--x) 0);
-          --d: hsl(0 0 var(--x));
-          --e: hsl(var(--x) 0 0 / var(--alpha));
-          --f: hsl(0 var(--x) 0 / var(--alpha));
-          --g: hsl(0 0 var(--x) / var(--alpha));
-          --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
-          --i: hsl(none 100% 50% / var(--alpha));
+          color: rgba(123, 255, 255, .5);
         }
       "#},
       Browsers {
-        safari: Some(13 << 16),
+        ie: Some(11 << 16),
         ..Browsers::default()
       },
     );
 
-    test(
-      r#"
-      .foo {
-        --a: rgb(50% 50% 50% / calc(100% / 2));
-        --b: hsl(calc(360deg / 2) 50% 50%);
-        --c: oklab(40.101% calc(0.1 + 0.2) 0.0453);
-        --d: color(display-p3 0.43313 0.50108 calc(0.1 + 0.2));
-        --e: rgb(calc(255 / 2), calc(255 / 2), calc(255 / 2));
-      }
-      "#,
+    prefix_test(
+      ".foo { color: #7bffff80 }",
       indoc! { r#"
         .foo {
-          --a: #80808080;
-          --b: #40bfbf;
-          --c: oklab(40.101% .3 .0453);
-          --d: color(display-p3 .43313 .50108 .3);
-          --e: gray;
+          color: rgba(123, 255, 255, .5);
         }
       "#},
+      Browsers {
+        ie: Some(11 << 16),
+        ..Browsers::default()
+      },
     );
-  }
-
-  #[test]
-  fn test_relative_color() {
-    fn test(input: &str, output: &str) {
-      let output = CssColor::parse_string(output)
-        .unwrap()
-        .to_css_string(PrinterOptions {
-          minify: true,
-          ..PrinterOptions::default()
-        })
-        .unwrap();
-      minify_test(
-        &format!(".foo {{ color: {} }}", input),
-        &format!(".foo{{color:{}}}", output),
-      );
-    }
 
-    test("lab(from indianred calc(l * .8) a b)", "lab(43.1402% 45.7516 23.1557)");
-    test("lch(from indianred calc(l + 10%) c h)", "lch(63.9252% 51.2776 26.8448)");
-    test("lch(from indianred l calc(c - 50) h)", "lch(53.9252% 1.27763 26.8448)");
-    test(
-      "lch(from indianred l c calc(h + 180deg))",
-      "lch(53.9252% 51.2776 206.845)",
-    );
-    test("lch(from orchid l 30 h)", "lch(62.7526% 30 326.969)");
-    test("lch(from orchid l 30 h)", "lch(62.7526% 30 326.969)");
-    test("lch(from peru calc(l * 0.8) c h)", "lch(49.8022% 54.0117 63.6804)");
-    test("rgb(from indianred 255 g b)", "rgb(255, 92, 92)");
-    test("rgb(from indianred r g b / .5)", "rgba(205, 92, 92, .5)");
-    test(
-      "rgb(from rgba(205, 92, 92, .5) r g b / calc(alpha + .2))",
-      "rgba(205, 92, 92, .7)",
-    );
-    test(
-      "rgb(from rgba(205, 92, 92, .5) r g b / calc(alpha + 20%))",
-      "rgba(205, 92, 92, .7)",
-    );
-    test("lch(from indianred l sin(c) h)", "lch(53.9252% .84797 26.8448)");
-    test("lch(from indianred l sqrt(c) h)", "lch(53.9252% 7.16084 26.8448)");
-    test("lch(from indianred l c sin(h))", "lch(53.9252% 51.2776 .990043)");
-    minify_test(
-      ".foo{color:lch(from currentColor l c sin(h))}",
-      ".foo{color:lch(from currentColor l c sin(h))}",
+    prefix_test(
+      ".foo { color: rgba(123, 456, 789, 0.5) }",
+      indoc! { r#"
+        .foo {
+          color: rgba(123, 255, 255, .5);
+        }
+      "#},
+      Browsers {
+        firefox: Some(48 << 16),
+        safari: Some(10 << 16),
+        ios_saf: Some(9 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // The following tests were converted from WPT: https://github.com/web-platform-tests/wpt/blob/master/css/css-color/parsing/relative-color-valid.html
-    // Find: test_valid_value\(`color`, `(.*?)`,\s*`(.*?)`\)
-    // Replace: test("$1", "$2")
-
-    // Testing no modifications.
-    test("rgb(from rebeccapurple r g b)", "#639");
-    test("rgb(from rebeccapurple r g b / alpha)", "#639");
-    test("rgb(from rgb(20%, 40%, 60%, 80%) r g b / alpha)", "#369c");
-    test("rgb(from hsl(120deg 20% 50% / .5) r g b / alpha)", "#66996680");
+    prefix_test(
+      ".foo { color: rgba(123, 456, 789, 0.5) }",
+      indoc! { r#"
+        .foo {
+          color: #7bffff80;
+        }
+      "#},
+      Browsers {
+        firefox: Some(49 << 16),
+        safari: Some(10 << 16),
+        ios_saf: Some(10 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Test nesting relative colors.
-    test("rgb(from rgb(from rebeccapurple r g b) r g b)", "#639");
+    prefix_test(
+      ".foo { background-color: lab(40% 56.6 39) }",
+      indoc! { r#"
+        .foo {
+          background-color: #b32323;
+          background-color: lab(40% 56.6 39);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Testing non-sRGB origin colors to see gamut mapping.
-    test("rgb(from color(display-p3 0 1 0) r g b / alpha)", "#00f942"); // Naive clip based mapping would give rgb(0, 255, 0).
-    test("rgb(from lab(100% 104.3 -50.9) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("rgb(from lab(0% 104.3 -50.9) r g b)", "#2a0022"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black.
-    test("rgb(from lch(100% 116 334) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("rgb(from lch(0% 116 334) r g b)", "#2a0022"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black.
-    test("rgb(from oklab(100% 0.365 -0.16) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 92, 255).
-    test("rgb(from oklab(0% 0.365 -0.16) r g b)", "#000"); // Naive clip based mapping would give rgb(19, 0, 24).
-    test("rgb(from oklch(100% 0.399 336.3) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 91, 255).
-    test("rgb(from oklch(0% 0.399 336.3) r g b)", "#000"); // Naive clip based mapping would give rgb(20, 0, 24).
+    prefix_test(
+      ".foo { background-color: lch(40% 68.735435 34.568626) }",
+      indoc! { r#"
+        .foo {
+          background-color: #b32323;
+          background-color: lch(40% 68.7354 34.5686);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Testing replacement with 0.
-    test("rgb(from rebeccapurple 0 0 0)", "rgb(0, 0, 0)");
-    test("rgb(from rebeccapurple 0 0 0 / 0)", "rgba(0, 0, 0, 0)");
-    test("rgb(from rebeccapurple 0 g b / alpha)", "rgb(0, 51, 153)");
-    test("rgb(from rebeccapurple r 0 b / alpha)", "rgb(102, 0, 153)");
-    test("rgb(from rebeccapurple r g 0 / alpha)", "rgb(102, 51, 0)");
-    test("rgb(from rebeccapurple r g b / 0)", "rgba(102, 51, 153, 0)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) 0 g b / alpha)",
-      "rgba(0, 102, 153, 0.8)",
+    prefix_test(
+      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
+      indoc! { r#"
+        .foo {
+          background-color: #c65d07;
+          background-color: lab(52.2319% 40.1449 59.9171);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r 0 b / alpha)",
-      "rgba(51, 0, 153, 0.8)",
+
+    prefix_test(
+      ".foo { background-color: oklch(40% 0.1268735435 34.568626) }",
+      indoc! { r#"
+        .foo {
+          background-color: #7e250f;
+          background-color: lab(29.2661% 38.2437 35.3889);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g 0 / alpha)",
-      "rgba(51, 102, 0, 0.8)",
+
+    prefix_test(
+      ".foo { background-color: lab(40% 56.6 39) }",
+      indoc! { r#"
+        .foo {
+          background-color: lab(40% 56.6 39);
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
-    test("rgb(from rgb(20%, 40%, 60%, 80%) r g b / 0)", "rgba(51, 102, 153, 0)");
 
-    // Testing replacement with a number.
-    test("rgb(from rebeccapurple 25 g b / alpha)", "rgb(25, 51, 153)");
-    test("rgb(from rebeccapurple r 25 b / alpha)", "rgb(102, 25, 153)");
-    test("rgb(from rebeccapurple r g 25 / alpha)", "rgb(102, 51, 25)");
-    test("rgb(from rebeccapurple r g b / .25)", "rgba(102, 51, 153, 0.25)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) 25 g b / alpha)",
-      "rgba(25, 102, 153, 0.8)",
+    prefix_test(
+      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
+      indoc! { r#"
+        .foo {
+          background-color: #c65d07;
+          background-color: lab(52.2319% 40.1449 59.9171);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r 25 b / alpha)",
-      "rgba(51, 25, 153, 0.8)",
+
+    prefix_test(
+      ".foo { background-color: oklab(59.686% 0.1009 0.1192); }",
+      indoc! { r#"
+        .foo {
+          background-color: #c65d07;
+          background-color: color(display-p3 .724144 .386777 .148795);
+          background-color: lab(52.2319% 40.1449 59.9171);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g 25 / alpha)",
-      "rgba(51, 102, 25, 0.8)",
+
+    prefix_test(
+      ".foo { background-color: lab(40% 56.6 39) }",
+      indoc! { r#"
+        .foo {
+          background-color: #b32323;
+          background-color: color(display-p3 .643308 .192455 .167712);
+          background-color: lab(40% 56.6 39);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g b / .20)",
-      "rgba(51, 102, 153, 0.2)",
+
+    prefix_test(
+      ".foo { background-color: oklch(59.686% 0.15619 49.7694); }",
+      indoc! { r#"
+        .foo {
+          background-color: #c65d06;
+          background-color: lab(52.2321% 40.1417 59.9527);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing replacement with a percentage.
-    test("rgb(from rebeccapurple 20% g b / alpha)", "rgb(51, 51, 153)");
-    test("rgb(from rebeccapurple r 20% b / alpha)", "rgb(102, 51, 153)");
-    test("rgb(from rebeccapurple r g 20% / alpha)", "rgb(102, 51, 51)");
-    test("rgb(from rebeccapurple r g b / 20%)", "rgba(102, 51, 153, 0.2)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) 20% g b / alpha)",
-      "rgba(51, 102, 153, 0.8)",
-    );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r 20% b / alpha)",
-      "rgba(51, 51, 153, 0.8)",
-    );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g 20% / alpha)",
-      "rgba(51, 102, 51, 0.8)",
-    );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g b / 20%)",
-      "rgba(51, 102, 153, 0.2)",
+    prefix_test(
+      ".foo { background-color: color(sRGB 0.41587 0.503670 0.36664); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(srgb .41587 .50367 .36664);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing replacement with a number for r, g, b but percent for alpha.
-    test("rgb(from rebeccapurple 25 g b / 25%)", "rgba(25, 51, 153, 0.25)");
-    test("rgb(from rebeccapurple r 25 b / 25%)", "rgba(102, 25, 153, 0.25)");
-    test("rgb(from rebeccapurple r g 25 / 25%)", "rgba(102, 51, 25, 0.25)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) 25 g b / 25%)",
-      "rgba(25, 102, 153, 0.25)",
-    );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r 25 b / 25%)",
-      "rgba(51, 25, 153, 0.25)",
-    );
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) r g 25 / 25%)",
-      "rgba(51, 102, 25, 0.25)",
+    prefix_test(
+      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(display-p3 .43313 .50108 .3795);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing permutation.
-    test("rgb(from rebeccapurple g b r)", "rgb(51, 153, 102)");
-    test("rgb(from rebeccapurple b alpha r / g)", "rgba(153, 255, 102, 0.2)");
-    test("rgb(from rebeccapurple r r r / r)", "rgba(102, 102, 102, 0.4)");
-    test(
-      "rgb(from rebeccapurple alpha alpha alpha / alpha)",
-      "rgb(255, 255, 255)",
+    prefix_test(
+      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(display-p3 .43313 .50108 .3795);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test("rgb(from rgb(20%, 40%, 60%, 80%) g b r)", "rgb(102, 153, 51)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) b alpha r / g)",
-      "rgba(153, 204, 51, 0.4)",
+
+    prefix_test(
+      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      indoc! { r#"
+        .foo {
+          background-color: color(display-p3 .43313 .50108 .3795);
+        }
+      "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test("rgb(from rgb(20%, 40%, 60%, 80%) r r r / r)", "rgba(51, 51, 51, 0.2)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) alpha alpha alpha / alpha)",
-      "rgba(204, 204, 204, 0.8)",
+
+    prefix_test(
+      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(display-p3 .43313 .50108 .3795);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing mixes of number and percentage. (These would not be allowed in the non-relative syntax).
-    test("rgb(from rebeccapurple r 20% 10)", "rgb(102, 51, 10)");
-    test("rgb(from rebeccapurple r 10 20%)", "rgb(102, 10, 51)");
-    test("rgb(from rebeccapurple 0% 10 10)", "rgb(0, 10, 10)");
-    test("rgb(from rgb(20%, 40%, 60%, 80%) r 20% 10)", "rgb(51, 51, 10)");
-    test("rgb(from rgb(20%, 40%, 60%, 80%) r 10 20%)", "rgb(51, 10, 51)");
-    test("rgb(from rgb(20%, 40%, 60%, 80%) 0% 10 10)", "rgb(0, 10, 10)");
+    prefix_test(
+      ".foo { background-color: color(display-p3 0.43313 0.50108 0.37950); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(display-p3 .43313 .50108 .3795);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Testing with calc().
-    test("rgb(from rebeccapurple calc(r) calc(g) calc(b))", "rgb(102, 51, 153)");
-    test("rgb(from rebeccapurple r calc(g * 2) 10)", "rgb(102, 102, 10)");
-    test("rgb(from rebeccapurple b calc(r * .5) 10)", "rgb(153, 51, 10)");
-    test("rgb(from rebeccapurple r calc(g * .5 + g * .5) 10)", "rgb(102, 51, 10)");
-    test("rgb(from rebeccapurple r calc(b * .5 - g * .5) 10)", "rgb(102, 51, 10)");
-    test(
-      "rgb(from rgb(20%, 40%, 60%, 80%) calc(r) calc(g) calc(b) / calc(alpha))",
-      "rgba(51, 102, 153, 0.8)",
+    prefix_test(
+      ".foo { background-color: color(a98-rgb 0.44091 0.49971 0.37408); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(a98-rgb .44091 .49971 .37408);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing with 'none'.
-    test("rgb(from rebeccapurple none none none)", "rgb(0, 0, 0)");
-    test("rgb(from rebeccapurple none none none / none)", "rgba(0, 0, 0, 0)");
-    test("rgb(from rebeccapurple r g none)", "rgb(102, 51, 0)");
-    test("rgb(from rebeccapurple r g none / alpha)", "rgb(102, 51, 0)");
-    test("rgb(from rebeccapurple r g b / none)", "rgba(102, 51, 153, 0)");
-    test(
-      "rgb(from rgb(20% 40% 60% / 80%) r g none / alpha)",
-      "rgba(51, 102, 0, 0.8)",
+    prefix_test(
+      ".foo { background-color: color(a98-rgb 0.44091 0.49971 0.37408); }",
+      indoc! { r#"
+        .foo {
+          background-color: color(a98-rgb .44091 .49971 .37408);
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
-    test("rgb(from rgb(20% 40% 60% / 80%) r g b / none)", "rgba(51, 102, 153, 0)");
-    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
-    test("rgb(from rgb(none none none) r g b)", "rgb(0, 0, 0)");
-    test("rgb(from rgb(none none none / none) r g b / alpha)", "rgba(0, 0, 0, 0)");
-    test("rgb(from rgb(20% none 60%) r g b)", "rgb(51, 0, 153)");
-    test(
-      "rgb(from rgb(20% 40% 60% / none) r g b / alpha)",
-      "rgba(51, 102, 153, 0)",
+
+    prefix_test(
+      ".foo { background-color: color(prophoto-rgb 0.36589 0.41717 0.31333); }",
+      indoc! { r#"
+        .foo {
+          background-color: #6a805d;
+          background-color: color(prophoto-rgb .36589 .41717 .31333);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // hsl(from ...)
+    prefix_test(
+      ".foo { background-color: color(rec2020 0.42210 0.47580 0.35605); }",
+      indoc! { r#"
+        .foo {
+          background-color: #728765;
+          background-color: color(rec2020 .4221 .4758 .35605);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Testing no modifications.
-    test("hsl(from rebeccapurple h s l)", "rgb(102, 51, 153)");
-    test("hsl(from rebeccapurple h s l / alpha)", "rgb(102, 51, 153)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h s l / alpha)",
-      "rgba(51, 102, 153, 0.8)",
+    prefix_test(
+      ".foo { background-color: color(xyz-d50 0.2005 0.14089 0.4472); }",
+      indoc! { r#"
+        .foo {
+          background-color: #7654cd;
+          background-color: color(xyz-d50 .2005 .14089 .4472);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "hsl(from hsl(120deg 20% 50% / .5) h s l / alpha)",
-      "rgba(102, 153, 102, 0.5)",
+
+    prefix_test(
+      ".foo { background-color: color(xyz-d65 0.21661 0.14602 0.59452); }",
+      indoc! { r#"
+        .foo {
+          background-color: #7654cd;
+          background-color: color(xyz .21661 .14602 .59452);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Test nesting relative colors.
-    test("hsl(from hsl(from rebeccapurple h s l) h s l)", "rgb(102, 51, 153)");
+    prefix_test(
+      ".foo { background-color: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          background-color: #ee00be;
+          background-color: color(display-p3 .972962 -.362078 .804206);
+          background-color: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    // Testing non-sRGB origin colors to see gamut mapping.
-    test("hsl(from color(display-p3 0 1 0) h s l / alpha)", "rgb(0, 249, 66)"); // Naive clip based mapping would give rgb(0, 255, 0).
-    test("hsl(from lab(100% 104.3 -50.9) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("hsl(from lab(0% 104.3 -50.9) h s l)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
-    test("hsl(from lch(100% 116 334) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("hsl(from lch(0% 116 334) h s l)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
-    test("hsl(from oklab(100% 0.365 -0.16) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 92, 255).
-    test("hsl(from oklab(0% 0.365 -0.16) h s l)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(19, 0, 24).
-    test("hsl(from oklch(100% 0.399 336.3) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 91, 255).
-    test("hsl(from oklch(0% 0.399 336.3) h s l)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(20, 0, 24).
-
-    // Testing replacement with 0.
-    test("hsl(from rebeccapurple 0 0% 0%)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple 0deg 0% 0%)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple 0 0% 0% / 0)", "rgba(0, 0, 0, 0)");
-    test("hsl(from rebeccapurple 0deg 0% 0% / 0)", "rgba(0, 0, 0, 0)");
-    test("hsl(from rebeccapurple 0 s l / alpha)", "rgb(153, 51, 51)");
-    test("hsl(from rebeccapurple 0deg s l / alpha)", "rgb(153, 51, 51)");
-    test("hsl(from rebeccapurple h 0% l / alpha)", "rgb(102, 102, 102)");
-    test("hsl(from rebeccapurple h s 0% / alpha)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple h s l / 0)", "rgba(102, 51, 153, 0)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) 0 s l / alpha)",
-      "rgba(153, 51, 51, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) 0deg s l / alpha)",
-      "rgba(153, 51, 51, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h 0% l / alpha)",
-      "rgba(102, 102, 102, 0.8)",
+    prefix_test(
+      ".foo { color: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          color: #ee00be;
+          color: color(display-p3 .972962 -.362078 .804206);
+          color: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test("hsl(from rgb(20%, 40%, 60%, 80%) h s 0% / alpha)", "rgba(0, 0, 0, 0.8)");
-    test("hsl(from rgb(20%, 40%, 60%, 80%) h s l / 0)", "rgba(51, 102, 153, 0)");
 
-    // Testing replacement with a constant.
-    test("hsl(from rebeccapurple 25 s l / alpha)", "rgb(153, 94, 51)");
-    test("hsl(from rebeccapurple 25deg s l / alpha)", "rgb(153, 94, 51)");
-    test("hsl(from rebeccapurple h 20% l / alpha)", "rgb(102, 82, 122)");
-    test("hsl(from rebeccapurple h s 20% / alpha)", "rgb(51, 25, 77)");
-    test("hsl(from rebeccapurple h s l / .25)", "rgba(102, 51, 153, 0.25)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) 25 s l / alpha)",
-      "rgba(153, 94, 51, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) 25deg s l / alpha)",
-      "rgba(153, 94, 51, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h 20% l / alpha)",
-      "rgba(82, 102, 122, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h s 20% / alpha)",
-      "rgba(25, 51, 77, 0.8)",
-    );
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h s l / .2)",
-      "rgba(51, 102, 153, 0.2)",
+    prefix_test(
+      ".foo { background: var(--image) lch(40% 68.735435 34.568626) }",
+      indoc! { r#"
+        .foo {
+          background: var(--image) #b32323;
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            background: var(--image) lab(40% 56.6 39);
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing valid permutation (types match).
-    test("hsl(from rebeccapurple h l s)", "rgb(128, 77, 179)");
-    test("hsl(from rebeccapurple h alpha l / s)", "rgba(102, 0, 204, 0.5)");
-    test("hsl(from rebeccapurple h l l / l)", "rgba(102, 61, 143, 0.4)");
-    test("hsl(from rebeccapurple h alpha alpha / alpha)", "rgb(255, 255, 255)");
-    test("hsl(from rgb(20%, 40%, 60%, 80%) h l s)", "rgb(77, 128, 179)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h alpha l / s)",
-      "rgba(20, 102, 184, 0.5)",
+    prefix_test(
+      r#"
+      .foo {
+        color: red;
+        color: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: red;
+        color: lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    test("hsl(from rgb(20%, 40%, 60%, 80%) h l l / l)", "rgba(61, 102, 143, 0.4)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) h alpha alpha / alpha)",
-      "rgba(163, 204, 245, 0.8)",
+    prefix_test(
+      r#"
+      .foo {
+        color: red;
+        color: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing with calc().
-    test("hsl(from rebeccapurple calc(h) calc(s) calc(l))", "rgb(102, 51, 153)");
-    test(
-      "hsl(from rgb(20%, 40%, 60%, 80%) calc(h) calc(s) calc(l) / calc(alpha))",
-      "rgba(51, 102, 153, 0.8)",
+    prefix_test(
+      r#"
+      .foo {
+        color: var(--fallback);
+        color: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: var(--fallback);
+        color: lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Testing with 'none'.
-    test("hsl(from rebeccapurple none none none)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple none none none / none)", "rgba(0, 0, 0, 0)");
-    test("hsl(from rebeccapurple h s none)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple h s none / alpha)", "rgb(0, 0, 0)");
-    test("hsl(from rebeccapurple h s l / none)", "rgba(102, 51, 153, 0)");
-    test("hsl(from rebeccapurple none s l / alpha)", "rgb(153, 51, 51)");
-    test(
-      "hsl(from hsl(120deg 20% 50% / .5) h s none / alpha)",
-      "rgba(0, 0, 0, 0.5)",
-    );
-    test(
-      "hsl(from hsl(120deg 20% 50% / .5) h s l / none)",
-      "rgba(102, 153, 102, 0)",
-    );
-    test(
-      "hsl(from hsl(120deg 20% 50% / .5) none s l / alpha)",
-      "rgba(153, 102, 102, 0.5)",
-    );
-    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
-    test("hsl(from hsl(none none none) h s l)", "rgb(0, 0, 0)");
-    test("hsl(from hsl(none none none / none) h s l / alpha)", "rgba(0, 0, 0, 0)");
-    test("hsl(from hsl(120deg none 50% / .5) h s l)", "rgb(128, 128, 128)");
-    test(
-      "hsl(from hsl(120deg 20% 50% / none) h s l / alpha)",
-      "rgba(102, 153, 102, 0)",
-    );
-    test(
-      "hsl(from hsl(none 20% 50% / .5) h s l / alpha)",
-      "rgba(153, 102, 102, 0.5)",
+    prefix_test(
+      r#"
+      .foo {
+        color: var(--fallback);
+        color: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: lab(40% 56.6 39);
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // hwb(from ...)
+    prefix_test(
+      r#"
+      .foo {
+        color: red;
+        color: var(--foo, lab(40% 56.6 39));
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: var(--foo, color(display-p3 .643308 .192455 .167712));
+      }
 
-    // Testing no modifications.
-    test("hwb(from rebeccapurple h w b)", "rgb(102, 51, 153)");
-    test("hwb(from rebeccapurple h w b / alpha)", "rgb(102, 51, 153)");
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h w b / alpha)",
-      "rgba(51, 102, 153, 0.8)",
-    );
-    test(
-      "hwb(from hsl(120deg 20% 50% / .5) h w b / alpha)",
-      "rgba(102, 153, 102, 0.5)",
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          color: var(--foo, lab(40% 56.6 39));
+        }
+      }
+    "#
+      },
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    // Test nesting relative colors.
-    test("hwb(from hwb(from rebeccapurple h w b) h w b)", "rgb(102, 51, 153)");
-
-    // Testing non-sRGB origin colors to see gamut mapping.
-    test("hwb(from color(display-p3 0 1 0) h w b / alpha)", "rgb(0, 249, 66)"); // Naive clip based mapping would give rgb(0, 255, 0).
-    test("hwb(from lab(100% 104.3 -50.9) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("hwb(from lab(0% 104.3 -50.9) h w b)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
-    test("hwb(from lch(100% 116 334) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
-    test("hwb(from lch(0% 116 334) h w b)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
-    test("hwb(from oklab(100% 0.365 -0.16) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 92, 255).
-    test("hwb(from oklab(0% 0.365 -0.16) h w b)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(19, 0, 24).
-    test("hwb(from oklch(100% 0.399 336.3) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 91, 255).
-    test("hwb(from oklch(0% 0.399 336.3) h w b)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(20, 0, 24).
-
-    // Testing replacement with 0.
-    test("hwb(from rebeccapurple 0 0% 0%)", "rgb(255, 0, 0)");
-    test("hwb(from rebeccapurple 0deg 0% 0%)", "rgb(255, 0, 0)");
-    test("hwb(from rebeccapurple 0 0% 0% / 0)", "rgba(255, 0, 0, 0)");
-    test("hwb(from rebeccapurple 0deg 0% 0% / 0)", "rgba(255, 0, 0, 0)");
-    test("hwb(from rebeccapurple 0 w b / alpha)", "rgb(153, 51, 51)");
-    test("hwb(from rebeccapurple 0deg w b / alpha)", "rgb(153, 51, 51)");
-    test("hwb(from rebeccapurple h 0% b / alpha)", "rgb(77, 0, 153)");
-    test("hwb(from rebeccapurple h w 0% / alpha)", "rgb(153, 51, 255)");
-    test("hwb(from rebeccapurple h w b / 0)", "rgba(102, 51, 153, 0)");
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) 0 w b / alpha)",
-      "rgba(153, 51, 51, 0.8)",
+    prefix_test(
+      r#"
+      .foo {
+        --a: rgb(0 0 0 / var(--alpha));
+        --b: rgb(50% 50% 50% / var(--alpha));
+        --c: rgb(var(--x) 0 0);
+        --d: rgb(0 var(--x) 0);
+        --e: rgb(0 0 var(--x));
+        --f: rgb(var(--x) 0 0 / var(--alpha));
+        --g: rgb(0 var(--x) 0 / var(--alpha));
+        --h: rgb(0 0 var(--x) / var(--alpha));
+        --i: rgb(none 0 0 / var(--alpha));
+      }
+      "#,
+      indoc! { r#"
+        .foo {
+          --a: rgba(0, 0, 0, var(--alpha));
+          --b: rgba(128, 128, 128, var(--alpha));
+          --c: rgb(var(--x) 0 0);
+          --d: rgb(0 var(--x) 0);
+          --e: rgb(0 0 var(--x));
+          --f: rgb(var(--x) 0 0 / var(--alpha));
+          --g: rgb(0 var(--x) 0 / var(--alpha));
+          --h: rgb(0 0 var(--x) / var(--alpha));
+          --i: rgb(none 0 0 / var(--alpha));
+        }
+      "#},
+      Browsers {
+        safari: Some(11 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) 0deg w b / alpha)",
-      "rgba(153, 51, 51, 0.8)",
+
+    prefix_test(
+      r#"
+      .foo {
+        --a: rgb(0 0 0 / var(--alpha));
+        --b: rgb(50% 50% 50% / var(--alpha));
+        --c: rgb(var(--x) 0 0);
+        --d: rgb(0 var(--x) 0);
+        --e: rgb(0 0 var(--x));
+        --f: rgb(var(--x) 0 0 / var(--alpha));
+        --g: rgb(0 var(--x) 0 / var(--alpha));
+        --h: rgb(0 0 var(--x) / var(--alpha));
+        --i: rgb(none 0 0 / var(--alpha));
+      }
+      "#,
+      indoc! { r#"
+        .foo {
+          --a: rgb(0 0 0 / var(--alpha));
+          --b: rgb(128 128 128 / var(--alpha));
+          --c: rgb(var(--x) 0 0);
+          --d: rgb(0 var(--x) 0);
+          --e: rgb(0 0 var(--x));
+          --f: rgb(var(--x) 0 0 / var(--alpha));
+          --g: rgb(0 var(--x) 0 / var(--alpha));
+          --h: rgb(0 0 var(--x) / var(--alpha));
+          --i: rgb(none 0 0 / var(--alpha));
+        }
+      "#},
+      Browsers {
+        safari: Some(13 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h 0% b / alpha)",
-      "rgba(0, 77, 153, 0.8)",
+
+    prefix_test(
+      r#"
+      .foo {
+        --a: hsl(270 100% 50% / var(--alpha));
+        --b: hsl(var(--x) 0 0);
+        --c: hsl(0 var(--x) 0);
+        --d: hsl(0 0 var(--x));
+        --e: hsl(var(--x) 0 0 / var(--alpha));
+        --f: hsl(0 var(--x) 0 / var(--alpha));
+        --g: hsl(0 0 var(--x) / var(--alpha));
+        --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
+        --i: hsl(none 100% 50% / var(--alpha));
+      }
+      "#,
+      indoc! { r#"
+        .foo {
+          --a: hsla(270, 100%, 50%, var(--alpha));
+          --b: hsl(var(--x) 0 0);
+          --c: hsl(0 var(--x) 0);
+          --d: hsl(0 0 var(--x));
+          --e: hsl(var(--x) 0 0 / var(--alpha));
+          --f: hsl(0 var(--x) 0 / var(--alpha));
+          --g: hsl(0 0 var(--x) / var(--alpha));
+          --h: hsla(270, 100%, 50%, calc(var(--alpha) / 2));
+          --i: hsl(none 100% 50% / var(--alpha));
+        }
+      "#},
+      Browsers {
+        safari: Some(11 << 16),
+        ..Browsers::default()
+      },
     );
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h w 0% / alpha)",
-      "rgba(51, 153, 255, 0.8)",
+
+    prefix_test(
+      r#"
+      .foo {
+        --a: hsl(270 100% 50% / var(--alpha));
+        --b: hsl(var(--x) 0 0);
+        --c: hsl(0 var(--x) 0);
+        --d: hsl(0 0 var(--x));
+        --e: hsl(var(--x) 0 0 / var(--alpha));
+        --f: hsl(0 var(--x) 0 / var(--alpha));
+        --g: hsl(0 0 var(--x) / var(--alpha));
+        --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
+        --i: hsl(none 100% 50% / var(--alpha));
+      }
+      "#,
+      indoc! { r#"
+        .foo {
+          --a: hsl(270 100% 50% / var(--alpha));
+          --b: hsl(var(--x) 0 0);
+          --c: hsl(0 var(--x) 0);
+          --d: hsl(0 0 var(--x));
+          --e: hsl(var(--x) 0 0 / var(--alpha));
+          --f: hsl(0 var(--x) 0 / var(--alpha));
+          --g: hsl(0 0 var(--x) / var(--alpha));
+          --h: hsl(270 100% 50% / calc(var(--alpha) / 2));
+          --i: hsl(none 100% 50% / var(--alpha));
+        }
+      "#},
+      Browsers {
+        safari: Some(13 << 16),
+        ..Browsers::default()
+      },
     );
-    test("hwb(from rgb(20%, 40%, 60%, 80%) h w b / 0)", "rgba(51, 102, 153, 0)");
 
-    // Testing replacement with a constant.
-    test("hwb(from rebeccapurple 25 w b / alpha)", "rgb(153, 94, 51)");
-    test("hwb(from rebeccapurple 25deg w b / alpha)", "rgb(153, 94, 51)");
-    test("hwb(from rebeccapurple h 20% b / alpha)", "rgb(102, 51, 153)");
-    test("hwb(from rebeccapurple h w 20% / alpha)", "rgb(128, 51, 204)");
-    test("hwb(from rebeccapurple h w b / .2)", "rgba(102, 51, 153, 0.2)");
     test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) 25 w b / alpha)",
-      "rgba(153, 94, 51, 0.8)",
+      r#"
+      .foo {
+        --a: rgb(50% 50% 50% / calc(100% / 2));
+        --b: hsl(calc(360deg / 2) 50% 50%);
+        --c: oklab(40.101% calc(0.1 + 0.2) 0.0453);
+        --d: color(display-p3 0.43313 0.50108 calc(0.1 + 0.2));
+        --e: rgb(calc(255 / 2), calc(255 / 2), calc(255 / 2));
+      }
+      "#,
+      indoc! { r#"
+        .foo {
+          --a: #80808080;
+          --b: #40bfbf;
+          --c: oklab(40.101% .3 .0453);
+          --d: color(display-p3 .43313 .50108 .3);
+          --e: gray;
+        }
+      "#},
     );
+  }
+
+  #[test]
+  fn test_relative_color() {
+    fn test(input: &str, output: &str) {
+      let output = CssColor::parse_string(output)
+        .unwrap()
+        .to_css_string(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      minify_test(
+        &format!(".foo {{ color: {} }}", input),
+        &format!(".foo{{color:{}}}", output),
+      );
+    }
+
+    test("lab(from indianred calc(l * .8) a b)", "lab(43.1402% 45.7516 23.1557)");
+    test("lch(from indianred calc(l + 10%) c h)", "lch(63.9252% 51.2776 26.8448)");
+    test("lch(from indianred l calc(c - 50) h)", "lch(53.9252% 1.27763 26.8448)");
     test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) 25deg w b / alpha)",
-      "rgba(153, 94, 51, 0.8)",
+      "lch(from indianred l c calc(h + 180deg))",
+      "lch(53.9252% 51.2776 206.845)",
     );
+    test("lch(from orchid l 30 h)", "lch(62.7526% 30 326.969)");
+    test("lch(from orchid l 30 h)", "lch(62.7526% 30 326.969)");
+    test("lch(from peru calc(l * 0.8) c h)", "lch(49.8022% 54.0117 63.6804)");
+    test("rgb(from indianred 255 g b)", "rgb(255, 92, 92)");
+    test("rgb(from indianred r g b / .5)", "rgba(205, 92, 92, .5)");
     test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h 20% b / alpha)",
-      "rgba(51, 102, 153, 0.8)",
+      "rgb(from rgba(205, 92, 92, .5) r g b / calc(alpha + .2))",
+      "rgba(205, 92, 92, .7)",
     );
     test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h w 20% / alpha)",
-      "rgba(51, 128, 204, 0.8)",
+      "rgb(from rgba(205, 92, 92, .5) r g b / calc(alpha + 20%))",
+      "rgba(205, 92, 92, .7)",
     );
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h w b / .2)",
-      "rgba(51, 102, 153, 0.2)",
+    test("lch(from indianred l sin(c) h)", "lch(53.9252% .84797 26.8448)");
+    test("lch(from indianred l sqrt(c) h)", "lch(53.9252% 7.16084 26.8448)");
+    test("lch(from indianred l c sin(h))", "lch(53.9252% 51.2776 .990043)");
+    minify_test(
+      ".foo{color:lch(from currentColor l c sin(h))}",
+      ".foo{color:lch(from currentColor l c sin(h))}",
     );
 
-    // Testing valid permutation (types match).
-    test("hwb(from rebeccapurple h b w)", "rgb(153, 102, 204)");
-    test("hwb(from rebeccapurple h alpha w / b)", "rgba(213, 213, 213, 0.4)");
-    test("hwb(from rebeccapurple h w w / w)", "rgba(128, 51, 204, 0.2)");
-    test("hwb(from rebeccapurple h alpha alpha / alpha)", "rgb(128, 128, 128)");
-    test("hwb(from rgb(20%, 40%, 60%, 80%) h b w)", "rgb(102, 153, 204)");
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h alpha w / b)",
-      "rgba(204, 204, 204, 0.4)",
-    );
-    test("hwb(from rgb(20%, 40%, 60%, 80%) h w w / w)", "rgba(51, 128, 204, 0.2)");
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) h alpha alpha / alpha)",
-      "rgba(128, 128, 128, 0.8)",
-    );
+    // The following tests were converted from WPT: https://github.com/web-platform-tests/wpt/blob/master/css/css-color/parsing/relative-color-valid.html
+    // Find: test_valid_value\(`color`, `(.*?)`,\s*`(.*?)`\)
+    // Replace: test("$1", "$2")
 
-    // Testing with calc().
-    test("hwb(from rebeccapurple calc(h) calc(w) calc(b))", "rgb(102, 51, 153)");
-    test(
-      "hwb(from rgb(20%, 40%, 60%, 80%) calc(h) calc(w) calc(b) / calc(alpha))",
-      "rgba(51, 102, 153, 0.8)",
-    );
+    // Testing no modifications.
+    test("rgb(from rebeccapurple r g b)", "#639");
+    test("rgb(from rebeccapurple r g b / alpha)", "#639");
+    test("rgb(from rgb(20%, 40%, 60%, 80%) r g b / alpha)", "#369c");
+    test("rgb(from hsl(120deg 20% 50% / .5) r g b / alpha)", "#66996680");
 
-    // Testing with 'none'.
-    test("hwb(from rebeccapurple none none none)", "rgb(255, 0, 0)");
-    test("hwb(from rebeccapurple none none none / none)", "rgba(255, 0, 0, 0)");
-    test("hwb(from rebeccapurple h w none)", "rgb(153, 51, 255)");
-    test("hwb(from rebeccapurple h w none / alpha)", "rgb(153, 51, 255)");
-    test("hwb(from rebeccapurple h w b / none)", "rgba(102, 51, 153, 0)");
-    test("hwb(from rebeccapurple none w b / alpha)", "rgb(153, 51, 51)");
+    // Test nesting relative colors.
+    test("rgb(from rgb(from rebeccapurple r g b) r g b)", "#639");
+
+    // Testing non-sRGB origin colors to see gamut mapping.
+    test("rgb(from color(display-p3 0 1 0) r g b / alpha)", "#00f942"); // Naive clip based mapping would give rgb(0, 255, 0).
+    test("rgb(from lab(100% 104.3 -50.9) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("rgb(from lab(0% 104.3 -50.9) r g b)", "#2a0022"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black.
+    test("rgb(from lch(100% 116 334) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("rgb(from lch(0% 116 334) r g b)", "#2a0022"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black.
+    test("rgb(from oklab(100% 0.365 -0.16) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 92, 255).
+    test("rgb(from oklab(0% 0.365 -0.16) r g b)", "#000"); // Naive clip based mapping would give rgb(19, 0, 24).
+    test("rgb(from oklch(100% 0.399 336.3) r g b)", "#fff"); // Naive clip based mapping would give rgb(255, 91, 255).
+    test("rgb(from oklch(0% 0.399 336.3) r g b)", "#000"); // Naive clip based mapping would give rgb(20, 0, 24).
+
+    // Testing replacement with 0.
+    test("rgb(from rebeccapurple 0 0 0)", "rgb(0, 0, 0)");
+    test("rgb(from rebeccapurple 0 0 0 / 0)", "rgba(0, 0, 0, 0)");
+    test("rgb(from rebeccapurple 0 g b / alpha)", "rgb(0, 51, 153)");
+    test("rgb(from rebeccapurple r 0 b / alpha)", "rgb(102, 0, 153)");
+    test("rgb(from rebeccapurple r g 0 / alpha)", "rgb(102, 51, 0)");
+    test("rgb(from rebeccapurple r g b / 0)", "rgba(102, 51, 153, 0)");
     test(
-      "hwb(from hwb(120deg 20% 50% / .5) h w none / alpha)",
-      "rgba(51, 255, 51, 0.5)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) 0 g b / alpha)",
+      "rgba(0, 102, 153, 0.8)",
     );
     test(
-      "hwb(from hwb(120deg 20% 50% / .5) h w b / none)",
-      "rgba(51, 128, 51, 0)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) r 0 b / alpha)",
+      "rgba(51, 0, 153, 0.8)",
     );
     test(
-      "hwb(from hwb(120deg 20% 50% / .5) none w b / alpha)",
-      "rgba(128, 51, 51, 0.5)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g 0 / alpha)",
+      "rgba(51, 102, 0, 0.8)",
     );
-    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
-    test("hwb(from hwb(none none none) h w b)", "rgb(255, 0, 0)");
+    test("rgb(from rgb(20%, 40%, 60%, 80%) r g b / 0)", "rgba(51, 102, 153, 0)");
+
+    // Testing replacement with a number.
+    test("rgb(from rebeccapurple 25 g b / alpha)", "rgb(25, 51, 153)");
+    test("rgb(from rebeccapurple r 25 b / alpha)", "rgb(102, 25, 153)");
+    test("rgb(from rebeccapurple r g 25 / alpha)", "rgb(102, 51, 25)");
+    test("rgb(from rebeccapurple r g b / .25)", "rgba(102, 51, 153, 0.25)");
     test(
-      "hwb(from hwb(none none none / none) h w b / alpha)",
-      "rgba(255, 0, 0, 0)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) 25 g b / alpha)",
+      "rgba(25, 102, 153, 0.8)",
     );
-    test("hwb(from hwb(120deg none 50% / .5) h w b)", "rgb(0, 128, 0)");
     test(
-      "hwb(from hwb(120deg 20% 50% / none) h w b / alpha)",
-      "rgba(51, 128, 51, 0)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) r 25 b / alpha)",
+      "rgba(51, 25, 153, 0.8)",
     );
     test(
-      "hwb(from hwb(none 20% 50% / .5) h w b / alpha)",
-      "rgba(128, 51, 51, 0.5)",
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g 25 / alpha)",
+      "rgba(51, 102, 25, 0.8)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g b / .20)",
+      "rgba(51, 102, 153, 0.2)",
     );
 
-    for color_space in &["lab", "oklab"] {
-      // Testing no modifications.
-      test(
-        &format!("{}(from {}(25% 20 50) l a b)", color_space, color_space),
-        &format!("{}(25% 20 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a b / alpha)", color_space, color_space),
-        &format!("{}(25% 20 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a b / alpha)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0.4)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(200% 300 400 / 500%) l a b / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(200% 300 400)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(-200% -300 -400 / -500%) l a b / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(0% -300 -400 / 0)", color_space),
-      );
-
-      // Test nesting relative colors.
-      test(
-        &format!(
-          "{}(from {}(from {}(25% 20 50) l a b) l a b)",
-          color_space, color_space, color_space
-        ),
-        &format!("{}(25% 20 50)", color_space),
-      );
+    // Testing replacement with a percentage.
+    test("rgb(from rebeccapurple 20% g b / alpha)", "rgb(51, 51, 153)");
+    test("rgb(from rebeccapurple r 20% b / alpha)", "rgb(102, 51, 153)");
+    test("rgb(from rebeccapurple r g 20% / alpha)", "rgb(102, 51, 51)");
+    test("rgb(from rebeccapurple r g b / 20%)", "rgba(102, 51, 153, 0.2)");
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) 20% g b / alpha)",
+      "rgba(51, 102, 153, 0.8)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r 20% b / alpha)",
+      "rgba(51, 51, 153, 0.8)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g 20% / alpha)",
+      "rgba(51, 102, 51, 0.8)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g b / 20%)",
+      "rgba(51, 102, 153, 0.2)",
+    );
 
-      // Testing non-${colorSpace} origin to see conversion.
-      test(
-        &format!("{}(from color(display-p3 0 0 0) l a b / alpha)", color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
+    // Testing replacement with a number for r, g, b but percent for alpha.
+    test("rgb(from rebeccapurple 25 g b / 25%)", "rgba(25, 51, 153, 0.25)");
+    test("rgb(from rebeccapurple r 25 b / 25%)", "rgba(102, 25, 153, 0.25)");
+    test("rgb(from rebeccapurple r g 25 / 25%)", "rgba(102, 51, 25, 0.25)");
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) 25 g b / 25%)",
+      "rgba(25, 102, 153, 0.25)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r 25 b / 25%)",
+      "rgba(51, 25, 153, 0.25)",
+    );
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) r g 25 / 25%)",
+      "rgba(51, 102, 25, 0.25)",
+    );
 
-      // Testing replacement with 0.
-      test(
-        &format!("{}(from {}(25% 20 50) 0% 0 0)", color_space, color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) 0% 0 0 / 0)", color_space, color_space),
-        &format!("{}(0% 0 0 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) 0% a b / alpha)", color_space, color_space),
-        &format!("{}(0% 20 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l 0 b / alpha)", color_space, color_space),
-        &format!("{}(25% 0 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a 0 / alpha)", color_space, color_space),
-        &format!("{}(25% 20 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a b / 0)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) 0% a b / alpha)", color_space, color_space),
-        &format!("{}(0% 20 50 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l 0 b / alpha)", color_space, color_space),
-        &format!("{}(25% 0 50 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a 0 / alpha)", color_space, color_space),
-        &format!("{}(25% 20 0 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a b / 0)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0)", color_space),
-      );
+    // Testing permutation.
+    test("rgb(from rebeccapurple g b r)", "rgb(51, 153, 102)");
+    test("rgb(from rebeccapurple b alpha r / g)", "rgba(153, 255, 102, 0.2)");
+    test("rgb(from rebeccapurple r r r / r)", "rgba(102, 102, 102, 0.4)");
+    test(
+      "rgb(from rebeccapurple alpha alpha alpha / alpha)",
+      "rgb(255, 255, 255)",
+    );
+    test("rgb(from rgb(20%, 40%, 60%, 80%) g b r)", "rgb(102, 153, 51)");
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) b alpha r / g)",
+      "rgba(153, 204, 51, 0.4)",
+    );
+    test("rgb(from rgb(20%, 40%, 60%, 80%) r r r / r)", "rgba(51, 51, 51, 0.2)");
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) alpha alpha alpha / alpha)",
+      "rgba(204, 204, 204, 0.8)",
+    );
 
-      // Testing replacement with a constant.
-      test(
-        &format!("{}(from {}(25% 20 50) 35% a b / alpha)", color_space, color_space),
-        &format!("{}(35% 20 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l 35 b / alpha)", color_space, color_space),
-        &format!("{}(25% 35 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a 35 / alpha)", color_space, color_space),
-        &format!("{}(25% 20 35)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a b / .35)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0.35)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) 35% a b / alpha)", color_space, color_space),
-        &format!("{}(35% 20 50 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l 35 b / alpha)", color_space, color_space),
-        &format!("{}(25% 35 50 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a 35 / alpha)", color_space, color_space),
-        &format!("{}(25% 20 35 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a b / .35)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0.35)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) 200% 300 400 / 500)",
-          color_space, color_space
-        ),
-        &format!("{}(200% 300 400)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) -200% -300 -400 / -500)",
-          color_space, color_space
-        ),
-        &format!("{}(0% -300 -400 / 0)", color_space),
-      );
+    // Testing mixes of number and percentage. (These would not be allowed in the non-relative syntax).
+    test("rgb(from rebeccapurple r 20% 10)", "rgb(102, 51, 10)");
+    test("rgb(from rebeccapurple r 10 20%)", "rgb(102, 10, 51)");
+    test("rgb(from rebeccapurple 0% 10 10)", "rgb(0, 10, 10)");
+    test("rgb(from rgb(20%, 40%, 60%, 80%) r 20% 10)", "rgb(51, 51, 10)");
+    test("rgb(from rgb(20%, 40%, 60%, 80%) r 10 20%)", "rgb(51, 10, 51)");
+    test("rgb(from rgb(20%, 40%, 60%, 80%) 0% 10 10)", "rgb(0, 10, 10)");
 
-      // Testing valid permutation (types match).
-      test(
-        &format!("{}(from {}(25% 20 50) l b a)", color_space, color_space),
-        &format!("{}(25% 50 20)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a a / a)", color_space, color_space),
-        &format!("{}(25% 20 20)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l b a)", color_space, color_space),
-        &format!("{}(25% 50 20)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a a / a)", color_space, color_space),
-        &format!("{}(25% 20 20)", color_space),
-      );
+    // Testing with calc().
+    test("rgb(from rebeccapurple calc(r) calc(g) calc(b))", "rgb(102, 51, 153)");
+    test("rgb(from rebeccapurple r calc(g * 2) 10)", "rgb(102, 102, 10)");
+    test("rgb(from rebeccapurple b calc(r * .5) 10)", "rgb(153, 51, 10)");
+    test("rgb(from rebeccapurple r calc(g * .5 + g * .5) 10)", "rgb(102, 51, 10)");
+    test("rgb(from rebeccapurple r calc(b * .5 - g * .5) 10)", "rgb(102, 51, 10)");
+    test(
+      "rgb(from rgb(20%, 40%, 60%, 80%) calc(r) calc(g) calc(b) / calc(alpha))",
+      "rgba(51, 102, 153, 0.8)",
+    );
 
-      // Testing with calc().
-      test(
-        &format!(
-          "{}(from {}(25% 20 50) calc(l) calc(a) calc(b))",
-          color_space, color_space
-        ),
-        &format!("{}(25% 20 50)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(25% 20 50 / 40%) calc(l) calc(a) calc(b) / calc(alpha))",
-          color_space, color_space
-        ),
-        &format!("{}(25% 20 50 / 0.4)", color_space),
-      );
+    // Testing with 'none'.
+    test("rgb(from rebeccapurple none none none)", "rgb(0, 0, 0)");
+    test("rgb(from rebeccapurple none none none / none)", "rgba(0, 0, 0, 0)");
+    test("rgb(from rebeccapurple r g none)", "rgb(102, 51, 0)");
+    test("rgb(from rebeccapurple r g none / alpha)", "rgb(102, 51, 0)");
+    test("rgb(from rebeccapurple r g b / none)", "rgba(102, 51, 153, 0)");
+    test(
+      "rgb(from rgb(20% 40% 60% / 80%) r g none / alpha)",
+      "rgba(51, 102, 0, 0.8)",
+    );
+    test("rgb(from rgb(20% 40% 60% / 80%) r g b / none)", "rgba(51, 102, 153, 0)");
+    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
+    test("rgb(from rgb(none none none) r g b)", "rgb(0, 0, 0)");
+    test("rgb(from rgb(none none none / none) r g b / alpha)", "rgba(0, 0, 0, 0)");
+    test("rgb(from rgb(20% none 60%) r g b)", "rgb(51, 0, 153)");
+    test(
+      "rgb(from rgb(20% 40% 60% / none) r g b / alpha)",
+      "rgba(51, 102, 153, 0)",
+    );
 
-      // Testing with 'none'.
-      test(
-        &format!("{}(from {}(25% 20 50) none none none)", color_space, color_space),
-        &format!("{}(none none none)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) none none none / none)", color_space, color_space),
-        &format!("{}(none none none / none)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a none)", color_space, color_space),
-        &format!("{}(25% 20 none)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a none / alpha)", color_space, color_space),
-        &format!("{}(25% 20 none)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50) l a b / none)", color_space, color_space),
-        &format!("{}(25% 20 50 / none)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(25% 20 50 / 40%) l a none / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(25% 20 none / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / 40%) l a b / none)", color_space, color_space),
-        &format!("{}(25% 20 50 / none)", color_space),
-      );
-      // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
-      test(
-        &format!("{}(from {}(none none none) l a b)", color_space, color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(none none none / none) l a b / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(0% 0 0 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% none 50) l a b)", color_space, color_space),
-        &format!("{}(25% 0 50)", color_space),
-      );
-      test(
-        &format!("{}(from {}(25% 20 50 / none) l a b / alpha)", color_space, color_space),
-        &format!("{}(25% 20 50 / 0)", color_space),
-      );
-    }
+    // hsl(from ...)
 
-    // test_valid_value\(`color`, `\$\{colorSpace\}\(from \$\{colorSpace\}\((.*?)`,\s*`\$\{colorSpace\}(.*?)`\)
-    // test(&format!("{}(from {}($1", color_space, color_space), &format!("{}$2", color_space))
+    // Testing no modifications.
+    test("hsl(from rebeccapurple h s l)", "rgb(102, 51, 153)");
+    test("hsl(from rebeccapurple h s l / alpha)", "rgb(102, 51, 153)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h s l / alpha)",
+      "rgba(51, 102, 153, 0.8)",
+    );
+    test(
+      "hsl(from hsl(120deg 20% 50% / .5) h s l / alpha)",
+      "rgba(102, 153, 102, 0.5)",
+    );
 
-    for color_space in &["lch", "oklch"] {
-      // Testing no modifications.
-      test(
-        &format!("{}(from {}(70% 45 30) l c h)", color_space, color_space),
-        &format!("{}(70% 45 30)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) l c h / alpha)", color_space, color_space),
-        &format!("{}(70% 45 30)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c h / alpha)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0.4)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(200% 300 400 / 500%) l c h / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(200% 300 40)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(-200% -300 -400 / -500%) l c h / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(0% 0 320 / 0)", color_space),
-      );
+    // Test nesting relative colors.
+    test("hsl(from hsl(from rebeccapurple h s l) h s l)", "rgb(102, 51, 153)");
 
-      // Test nesting relative colors.
-      test(
-        &format!(
-          "{}(from {}(from {}(70% 45 30) l c h) l c h)",
-          color_space, color_space, color_space
-        ),
-        &format!("{}(70% 45 30)", color_space),
-      );
+    // Testing non-sRGB origin colors to see gamut mapping.
+    test("hsl(from color(display-p3 0 1 0) h s l / alpha)", "rgb(0, 249, 66)"); // Naive clip based mapping would give rgb(0, 255, 0).
+    test("hsl(from lab(100% 104.3 -50.9) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("hsl(from lab(0% 104.3 -50.9) h s l)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
+    test("hsl(from lch(100% 116 334) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("hsl(from lch(0% 116 334) h s l)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
+    test("hsl(from oklab(100% 0.365 -0.16) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 92, 255).
+    test("hsl(from oklab(0% 0.365 -0.16) h s l)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(19, 0, 24).
+    test("hsl(from oklch(100% 0.399 336.3) h s l)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 91, 255).
+    test("hsl(from oklch(0% 0.399 336.3) h s l)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(20, 0, 24).
 
-      // Testing non-sRGB origin colors (no gamut mapping will happen since the destination is not a bounded RGB color space).
-      test(
-        &format!("{}(from color(display-p3 0 0 0) l c h / alpha)", color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
+    // Testing replacement with 0.
+    test("hsl(from rebeccapurple 0 0% 0%)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple 0deg 0% 0%)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple 0 0% 0% / 0)", "rgba(0, 0, 0, 0)");
+    test("hsl(from rebeccapurple 0deg 0% 0% / 0)", "rgba(0, 0, 0, 0)");
+    test("hsl(from rebeccapurple 0 s l / alpha)", "rgb(153, 51, 51)");
+    test("hsl(from rebeccapurple 0deg s l / alpha)", "rgb(153, 51, 51)");
+    test("hsl(from rebeccapurple h 0% l / alpha)", "rgb(102, 102, 102)");
+    test("hsl(from rebeccapurple h s 0% / alpha)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple h s l / 0)", "rgba(102, 51, 153, 0)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) 0 s l / alpha)",
+      "rgba(153, 51, 51, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) 0deg s l / alpha)",
+      "rgba(153, 51, 51, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h 0% l / alpha)",
+      "rgba(102, 102, 102, 0.8)",
+    );
+    test("hsl(from rgb(20%, 40%, 60%, 80%) h s 0% / alpha)", "rgba(0, 0, 0, 0.8)");
+    test("hsl(from rgb(20%, 40%, 60%, 80%) h s l / 0)", "rgba(51, 102, 153, 0)");
 
-      // Testing replacement with 0.
-      test(
-        &format!("{}(from {}(70% 45 30) 0% 0 0)", color_space, color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) 0% 0 0deg)", color_space, color_space),
-        &format!("{}(0% 0 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) 0% 0 0 / 0)", color_space, color_space),
-        &format!("{}(0% 0 0 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) 0% 0 0deg / 0)", color_space, color_space),
-        &format!("{}(0% 0 0 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) 0% c h / alpha)", color_space, color_space),
-        &format!("{}(0% 45 30)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) l 0 h / alpha)", color_space, color_space),
-        &format!("{}(70% 0 30)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) l c 0 / alpha)", color_space, color_space),
-        &format!("{}(70% 45 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) l c 0deg / alpha)", color_space, color_space),
-        &format!("{}(70% 45 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30) l c h / 0)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30 / 40%) 0% c h / alpha)", color_space, color_space),
-        &format!("{}(0% 45 30 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30 / 40%) l 0 h / alpha)", color_space, color_space),
-        &format!("{}(70% 0 30 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c 0 / alpha)", color_space, color_space),
-        &format!("{}(70% 45 0 / 0.4)", color_space),
-      );
-      test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) l c 0deg / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(70% 45 0 / 0.4)", color_space),
-      );
-      test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c h / 0)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0)", color_space),
-      );
+    // Testing replacement with a constant.
+    test("hsl(from rebeccapurple 25 s l / alpha)", "rgb(153, 94, 51)");
+    test("hsl(from rebeccapurple 25deg s l / alpha)", "rgb(153, 94, 51)");
+    test("hsl(from rebeccapurple h 20% l / alpha)", "rgb(102, 82, 122)");
+    test("hsl(from rebeccapurple h s 20% / alpha)", "rgb(51, 25, 77)");
+    test("hsl(from rebeccapurple h s l / .25)", "rgba(102, 51, 153, 0.25)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) 25 s l / alpha)",
+      "rgba(153, 94, 51, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) 25deg s l / alpha)",
+      "rgba(153, 94, 51, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h 20% l / alpha)",
+      "rgba(82, 102, 122, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h s 20% / alpha)",
+      "rgba(25, 51, 77, 0.8)",
+    );
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h s l / .2)",
+      "rgba(51, 102, 153, 0.2)",
+    );
 
-      // Testing replacement with a constant.
-      test(
-        &format!("{}(from {}(70% 45 30) 25% c h / alpha)", color_space, color_space),
-        &format!("{}(25% 45 30)", color_space),
-      );
+    // Testing valid permutation (types match).
+    test("hsl(from rebeccapurple h l s)", "rgb(128, 77, 179)");
+    test("hsl(from rebeccapurple h alpha l / s)", "rgba(102, 0, 204, 0.5)");
+    test("hsl(from rebeccapurple h l l / l)", "rgba(102, 61, 143, 0.4)");
+    test("hsl(from rebeccapurple h alpha alpha / alpha)", "rgb(255, 255, 255)");
+    test("hsl(from rgb(20%, 40%, 60%, 80%) h l s)", "rgb(77, 128, 179)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h alpha l / s)",
+      "rgba(20, 102, 184, 0.5)",
+    );
+    test("hsl(from rgb(20%, 40%, 60%, 80%) h l l / l)", "rgba(61, 102, 143, 0.4)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) h alpha alpha / alpha)",
+      "rgba(163, 204, 245, 0.8)",
+    );
+
+    // Testing with calc().
+    test("hsl(from rebeccapurple calc(h) calc(s) calc(l))", "rgb(102, 51, 153)");
+    test(
+      "hsl(from rgb(20%, 40%, 60%, 80%) calc(h) calc(s) calc(l) / calc(alpha))",
+      "rgba(51, 102, 153, 0.8)",
+    );
+
+    // Testing with 'none'.
+    test("hsl(from rebeccapurple none none none)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple none none none / none)", "rgba(0, 0, 0, 0)");
+    test("hsl(from rebeccapurple h s none)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple h s none / alpha)", "rgb(0, 0, 0)");
+    test("hsl(from rebeccapurple h s l / none)", "rgba(102, 51, 153, 0)");
+    test("hsl(from rebeccapurple none s l / alpha)", "rgb(153, 51, 51)");
+    test(
+      "hsl(from hsl(120deg 20% 50% / .5) h s none / alpha)",
+      "rgba(0, 0, 0, 0.5)",
+    );
+    test(
+      "hsl(from hsl(120deg 20% 50% / .5) h s l / none)",
+      "rgba(102, 153, 102, 0)",
+    );
+    test(
+      "hsl(from hsl(120deg 20% 50% / .5) none s l / alpha)",
+      "rgba(153, 102, 102, 0.5)",
+    );
+    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
+    test("hsl(from hsl(none none none) h s l)", "rgb(0, 0, 0)");
+    test("hsl(from hsl(none none none / none) h s l / alpha)", "rgba(0, 0, 0, 0)");
+    test("hsl(from hsl(120deg none 50% / .5) h s l)", "rgb(128, 128, 128)");
+    test(
+      "hsl(from hsl(120deg 20% 50% / none) h s l / alpha)",
+      "rgba(102, 153, 102, 0)",
+    );
+    test(
+      "hsl(from hsl(none 20% 50% / .5) h s l / alpha)",
+      "rgba(153, 102, 102, 0.5)",
+    );
+
+    // hwb(from ...)
+
+    // Testing no modifications.
+    test("hwb(from rebeccapurple h w b)", "rgb(102, 51, 153)");
+    test("hwb(from rebeccapurple h w b / alpha)", "rgb(102, 51, 153)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h w b / alpha)",
+      "rgba(51, 102, 153, 0.8)",
+    );
+    test(
+      "hwb(from hsl(120deg 20% 50% / .5) h w b / alpha)",
+      "rgba(102, 153, 102, 0.5)",
+    );
+
+    // Test nesting relative colors.
+    test("hwb(from hwb(from rebeccapurple h w b) h w b)", "rgb(102, 51, 153)");
+
+    // Testing non-sRGB origin colors to see gamut mapping.
+    test("hwb(from color(display-p3 0 1 0) h w b / alpha)", "rgb(0, 249, 66)"); // Naive clip based mapping would give rgb(0, 255, 0).
+    test("hwb(from lab(100% 104.3 -50.9) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("hwb(from lab(0% 104.3 -50.9) h w b)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
+    test("hwb(from lch(100% 116 334) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 150, 255).
+    test("hwb(from lch(0% 116 334) h w b)", "rgb(42, 0, 34)"); // Naive clip based mapping would give rgb(90, 0, 76). NOTE: 0% lightness in Lab/LCH does not automatically correspond with sRGB black,
+    test("hwb(from oklab(100% 0.365 -0.16) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 92, 255).
+    test("hwb(from oklab(0% 0.365 -0.16) h w b)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(19, 0, 24).
+    test("hwb(from oklch(100% 0.399 336.3) h w b)", "rgb(255, 255, 255)"); // Naive clip based mapping would give rgb(255, 91, 255).
+    test("hwb(from oklch(0% 0.399 336.3) h w b)", "rgb(0, 0, 0)"); // Naive clip based mapping would give rgb(20, 0, 24).
+
+    // Testing replacement with 0.
+    test("hwb(from rebeccapurple 0 0% 0%)", "rgb(255, 0, 0)");
+    test("hwb(from rebeccapurple 0deg 0% 0%)", "rgb(255, 0, 0)");
+    test("hwb(from rebeccapurple 0 0% 0% / 0)", "rgba(255, 0, 0, 0)");
+    test("hwb(from rebeccapurple 0deg 0% 0% / 0)", "rgba(255, 0, 0, 0)");
+    test("hwb(from rebeccapurple 0 w b / alpha)", "rgb(153, 51, 51)");
+    test("hwb(from rebeccapurple 0deg w b / alpha)", "rgb(153, 51, 51)");
+    test("hwb(from rebeccapurple h 0% b / alpha)", "rgb(77, 0, 153)");
+    test("hwb(from rebeccapurple h w 0% / alpha)", "rgb(153, 51, 255)");
+    test("hwb(from rebeccapurple h w b / 0)", "rgba(102, 51, 153, 0)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) 0 w b / alpha)",
+      "rgba(153, 51, 51, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) 0deg w b / alpha)",
+      "rgba(153, 51, 51, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h 0% b / alpha)",
+      "rgba(0, 77, 153, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h w 0% / alpha)",
+      "rgba(51, 153, 255, 0.8)",
+    );
+    test("hwb(from rgb(20%, 40%, 60%, 80%) h w b / 0)", "rgba(51, 102, 153, 0)");
+
+    // Testing replacement with a constant.
+    test("hwb(from rebeccapurple 25 w b / alpha)", "rgb(153, 94, 51)");
+    test("hwb(from rebeccapurple 25deg w b / alpha)", "rgb(153, 94, 51)");
+    test("hwb(from rebeccapurple h 20% b / alpha)", "rgb(102, 51, 153)");
+    test("hwb(from rebeccapurple h w 20% / alpha)", "rgb(128, 51, 204)");
+    test("hwb(from rebeccapurple h w b / .2)", "rgba(102, 51, 153, 0.2)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) 25 w b / alpha)",
+      "rgba(153, 94, 51, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) 25deg w b / alpha)",
+      "rgba(153, 94, 51, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h 20% b / alpha)",
+      "rgba(51, 102, 153, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h w 20% / alpha)",
+      "rgba(51, 128, 204, 0.8)",
+    );
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h w b / .2)",
+      "rgba(51, 102, 153, 0.2)",
+    );
+
+    // Testing valid permutation (types match).
+    test("hwb(from rebeccapurple h b w)", "rgb(153, 102, 204)");
+    test("hwb(from rebeccapurple h alpha w / b)", "rgba(213, 213, 213, 0.4)");
+    test("hwb(from rebeccapurple h w w / w)", "rgba(128, 51, 204, 0.2)");
+    test("hwb(from rebeccapurple h alpha alpha / alpha)", "rgb(128, 128, 128)");
+    test("hwb(from rgb(20%, 40%, 60%, 80%) h b w)", "rgb(102, 153, 204)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h alpha w / b)",
+      "rgba(204, 204, 204, 0.4)",
+    );
+    test("hwb(from rgb(20%, 40%, 60%, 80%) h w w / w)", "rgba(51, 128, 204, 0.2)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) h alpha alpha / alpha)",
+      "rgba(128, 128, 128, 0.8)",
+    );
+
+    // Testing with calc().
+    test("hwb(from rebeccapurple calc(h) calc(w) calc(b))", "rgb(102, 51, 153)");
+    test(
+      "hwb(from rgb(20%, 40%, 60%, 80%) calc(h) calc(w) calc(b) / calc(alpha))",
+      "rgba(51, 102, 153, 0.8)",
+    );
+
+    // Testing with 'none'.
+    test("hwb(from rebeccapurple none none none)", "rgb(255, 0, 0)");
+    test("hwb(from rebeccapurple none none none / none)", "rgba(255, 0, 0, 0)");
+    test("hwb(from rebeccapurple h w none)", "rgb(153, 51, 255)");
+    test("hwb(from rebeccapurple h w none / alpha)", "rgb(153, 51, 255)");
+    test("hwb(from rebeccapurple h w b / none)", "rgba(102, 51, 153, 0)");
+    test("hwb(from rebeccapurple none w b / alpha)", "rgb(153, 51, 51)");
+    test(
+      "hwb(from hwb(120deg 20% 50% / .5) h w none / alpha)",
+      "rgba(51, 255, 51, 0.5)",
+    );
+    test(
+      "hwb(from hwb(120deg 20% 50% / .5) h w b / none)",
+      "rgba(51, 128, 51, 0)",
+    );
+    test(
+      "hwb(from hwb(120deg 20% 50% / .5) none w b / alpha)",
+      "rgba(128, 51, 51, 0.5)",
+    );
+    // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
+    test("hwb(from hwb(none none none) h w b)", "rgb(255, 0, 0)");
+    test(
+      "hwb(from hwb(none none none / none) h w b / alpha)",
+      "rgba(255, 0, 0, 0)",
+    );
+    test("hwb(from hwb(120deg none 50% / .5) h w b)", "rgb(0, 128, 0)");
+    test(
+      "hwb(from hwb(120deg 20% 50% / none) h w b / alpha)",
+      "rgba(51, 128, 51, 0)",
+    );
+    test(
+      "hwb(from hwb(none 20% 50% / .5) h w b / alpha)",
+      "rgba(128, 51, 51, 0.5)",
+    );
+
+    for color_space in &["lab", "oklab"] {
+      // Testing no modifications.
       test(
-        &format!("{}(from {}(70% 45 30) l 25 h / alpha)", color_space, color_space),
-        &format!("{}(70% 25 30)", color_space),
+        &format!("{}(from {}(25% 20 50) l a b)", color_space, color_space),
+        &format!("{}(25% 20 50)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c 25 / alpha)", color_space, color_space),
-        &format!("{}(70% 45 25)", color_space),
+        &format!("{}(from {}(25% 20 50) l a b / alpha)", color_space, color_space),
+        &format!("{}(25% 20 50)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c 25deg / alpha)", color_space, color_space),
-        &format!("{}(70% 45 25)", color_space),
+        &format!("{}(from {}(25% 20 50 / 40%) l a b / alpha)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0.4)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c h / .25)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0.25)", color_space),
+        &format!(
+          "{}(from {}(200% 300 400 / 500%) l a b / alpha)",
+          color_space, color_space
+        ),
+        &format!("{}(200% 300 400)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) 25% c h / alpha)", color_space, color_space),
-        &format!("{}(25% 45 30 / 0.4)", color_space),
+        &format!(
+          "{}(from {}(-200% -300 -400 / -500%) l a b / alpha)",
+          color_space, color_space
+        ),
+        &format!("{}(0% -300 -400 / 0)", color_space),
       );
+
+      // Test nesting relative colors.
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) l 25 h / alpha)", color_space, color_space),
-        &format!("{}(70% 25 30 / 0.4)", color_space),
+        &format!(
+          "{}(from {}(from {}(25% 20 50) l a b) l a b)",
+          color_space, color_space, color_space
+        ),
+        &format!("{}(25% 20 50)", color_space),
       );
+
+      // Testing non-${colorSpace} origin to see conversion.
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c 25 / alpha)", color_space, color_space),
-        &format!("{}(70% 45 25 / 0.4)", color_space),
+        &format!("{}(from color(display-p3 0 0 0) l a b / alpha)", color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
+
+      // Testing replacement with 0.
       test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) l c 25deg / alpha)",
-          color_space, color_space
-        ),
-        &format!("{}(70% 45 25 / 0.4)", color_space),
+        &format!("{}(from {}(25% 20 50) 0% 0 0)", color_space, color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c h / .25)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0.25)", color_space),
+        &format!("{}(from {}(25% 20 50) 0% 0 0 / 0)", color_space, color_space),
+        &format!("{}(0% 0 0 / 0)", color_space),
       );
       test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) 200% 300 400 / 500)",
-          color_space, color_space
-        ),
-        &format!("{}(200% 300 400)", color_space),
+        &format!("{}(from {}(25% 20 50) 0% a b / alpha)", color_space, color_space),
+        &format!("{}(0% 20 50)", color_space),
       );
       test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) -200% -300 -400 / -500)",
-          color_space, color_space
-        ),
-        &format!("{}(0% 0 -400 / 0)", color_space),
+        &format!("{}(from {}(25% 20 50) l 0 b / alpha)", color_space, color_space),
+        &format!("{}(25% 0 50)", color_space),
       );
       test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) 50% 120 400deg / 500)",
-          color_space, color_space
-        ),
-        &format!("{}(50% 120 400)", color_space),
+        &format!("{}(from {}(25% 20 50) l a 0 / alpha)", color_space, color_space),
+        &format!("{}(25% 20 0)", color_space),
       );
       test(
-        &format!(
-          "{}(from {}(70% 45 30 / 40%) 50% 120 -400deg / -500)",
-          color_space, color_space
-        ),
-        &format!("{}(50% 120 -400 / 0)", color_space),
+        &format!("{}(from {}(25% 20 50) l a b / 0)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) 0% a b / alpha)", color_space, color_space),
+        &format!("{}(0% 20 50 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l 0 b / alpha)", color_space, color_space),
+        &format!("{}(25% 0 50 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l a 0 / alpha)", color_space, color_space),
+        &format!("{}(25% 20 0 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l a b / 0)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0)", color_space),
       );
 
-      // Testing valid permutation (types match).
-      // NOTE: 'c' is a valid hue, as hue is <angle>|<number>.
+      // Testing replacement with a constant.
       test(
-        &format!("{}(from {}(70% 45 30) alpha c h / l)", color_space, color_space),
-        &format!("{}(100% 45 30 / 0.7)", color_space),
+        &format!("{}(from {}(25% 20 50) 35% a b / alpha)", color_space, color_space),
+        &format!("{}(35% 20 50)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c c / alpha)", color_space, color_space),
-        &format!("{}(70% 45 45)", color_space),
+        &format!("{}(from {}(25% 20 50) l 35 b / alpha)", color_space, color_space),
+        &format!("{}(25% 35 50)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) alpha c h / alpha)", color_space, color_space),
-        &format!("{}(100% 45 30)", color_space),
+        &format!("{}(from {}(25% 20 50) l a 35 / alpha)", color_space, color_space),
+        &format!("{}(25% 20 35)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) alpha c c / alpha)", color_space, color_space),
-        &format!("{}(100% 45 45)", color_space),
+        &format!("{}(from {}(25% 20 50) l a b / .35)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0.35)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) alpha c h / l)", color_space, color_space),
-        &format!("{}(40% 45 30 / 0.7)", color_space),
+        &format!("{}(from {}(25% 20 50 / 40%) 35% a b / alpha)", color_space, color_space),
+        &format!("{}(35% 20 50 / 0.4)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c c / alpha)", color_space, color_space),
-        &format!("{}(70% 45 45 / 0.4)", color_space),
+        &format!("{}(from {}(25% 20 50 / 40%) l 35 b / alpha)", color_space, color_space),
+        &format!("{}(25% 35 50 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l a 35 / alpha)", color_space, color_space),
+        &format!("{}(25% 20 35 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l a b / .35)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0.35)", color_space),
       );
       test(
         &format!(
-          "{}(from {}(70% 45 30 / 40%) alpha c h / alpha)",
+          "{}(from {}(70% 45 30 / 40%) 200% 300 400 / 500)",
           color_space, color_space
         ),
-        &format!("{}(40% 45 30 / 0.4)", color_space),
+        &format!("{}(200% 300 400)", color_space),
       );
       test(
         &format!(
-          "{}(from {}(70% 45 30 / 40%) alpha c c / alpha)",
+          "{}(from {}(70% 45 30 / 40%) -200% -300 -400 / -500)",
           color_space, color_space
         ),
-        &format!("{}(40% 45 45 / 0.4)", color_space),
+        &format!("{}(0% -300 -400 / 0)", color_space),
+      );
+
+      // Testing valid permutation (types match).
+      test(
+        &format!("{}(from {}(25% 20 50) l b a)", color_space, color_space),
+        &format!("{}(25% 50 20)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50) l a a / a)", color_space, color_space),
+        &format!("{}(25% 20 20)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l b a)", color_space, color_space),
+        &format!("{}(25% 50 20)", color_space),
+      );
+      test(
+        &format!("{}(from {}(25% 20 50 / 40%) l a a / a)", color_space, color_space),
+        &format!("{}(25% 20 20)", color_space),
       );
 
       // Testing with calc().
       test(
         &format!(
-          "{}(from {}(70% 45 30) calc(l) calc(c) calc(h))",
+          "{}(from {}(25% 20 50) calc(l) calc(a) calc(b))",
           color_space, color_space
         ),
-        &format!("{}(70% 45 30)", color_space),
+        &format!("{}(25% 20 50)", color_space),
       );
       test(
         &format!(
-          "{}(from {}(70% 45 30 / 40%) calc(l) calc(c) calc(h) / calc(alpha))",
+          "{}(from {}(25% 20 50 / 40%) calc(l) calc(a) calc(b) / calc(alpha))",
           color_space, color_space
         ),
-        &format!("{}(70% 45 30 / 0.4)", color_space),
+        &format!("{}(25% 20 50 / 0.4)", color_space),
       );
 
       // Testing with 'none'.
       test(
-        &format!("{}(from {}(70% 45 30) none none none)", color_space, color_space),
+        &format!("{}(from {}(25% 20 50) none none none)", color_space, color_space),
         &format!("{}(none none none)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) none none none / none)", color_space, color_space),
+        &format!("{}(from {}(25% 20 50) none none none / none)", color_space, color_space),
         &format!("{}(none none none / none)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c none)", color_space, color_space),
-        &format!("{}(70% 45 none)", color_space),
+        &format!("{}(from {}(25% 20 50) l a none)", color_space, color_space),
+        &format!("{}(25% 20 none)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c none / alpha)", color_space, color_space),
-        &format!("{}(70% 45 none)", color_space),
+        &format!("{}(from {}(25% 20 50) l a none / alpha)", color_space, color_space),
+        &format!("{}(25% 20 none)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30) l c h / none)", color_space, color_space),
-        &format!("{}(70% 45 30 / none)", color_space),
+        &format!("{}(from {}(25% 20 50) l a b / none)", color_space, color_space),
+        &format!("{}(25% 20 50 / none)", color_space),
       );
       test(
         &format!(
-          "{}(from {}(70% 45 30 / 40%) l c none / alpha)",
+          "{}(from {}(25% 20 50 / 40%) l a none / alpha)",
           color_space, color_space
         ),
-        &format!("{}(70% 45 none / 0.4)", color_space),
+        &format!("{}(25% 20 none / 0.4)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / 40%) l c h / none)", color_space, color_space),
-        &format!("{}(70% 45 30 / none)", color_space),
+        &format!("{}(from {}(25% 20 50 / 40%) l a b / none)", color_space, color_space),
+        &format!("{}(25% 20 50 / none)", color_space),
       );
       // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
       test(
-        &format!("{}(from {}(none none none) l c h)", color_space, color_space),
+        &format!("{}(from {}(none none none) l a b)", color_space, color_space),
         &format!("{}(0% 0 0)", color_space),
       );
       test(
         &format!(
-          "{}(from {}(none none none / none) l c h / alpha)",
+          "{}(from {}(none none none / none) l a b / alpha)",
           color_space, color_space
         ),
         &format!("{}(0% 0 0 / 0)", color_space),
       );
       test(
-        &format!("{}(from {}(70% none 30) l c h)", color_space, color_space),
-        &format!("{}(70% 0 30)", color_space),
+        &format!("{}(from {}(25% none 50) l a b)", color_space, color_space),
+        &format!("{}(25% 0 50)", color_space),
       );
       test(
-        &format!("{}(from {}(70% 45 30 / none) l c h / alpha)", color_space, color_space),
-        &format!("{}(70% 45 30 / 0)", color_space),
+        &format!("{}(from {}(25% 20 50 / none) l a b / alpha)", color_space, color_space),
+        &format!("{}(25% 20 50 / 0)", color_space),
       );
     }
 
-    // test_valid_value\(`color`, `color\(from color\(\$\{colorSpace\}(.*?) \$\{colorSpace\}(.*?)`,\s*`color\(\$\{colorSpace\}(.*?)`\)
-    // test(&format!("color(from color({}$1 {}$2", color_space, color_space), &format!("color({}$3", color_space))
+    // test_valid_value\(`color`, `\$\{colorSpace\}\(from \$\{colorSpace\}\((.*?)`,\s*`\$\{colorSpace\}(.*?)`\)
+    // test(&format!("{}(from {}($1", color_space, color_space), &format!("{}$2", color_space))
 
-    for color_space in &["srgb", "srgb-linear", "a98-rgb", "rec2020", "prophoto-rgb"] {
+    for color_space in &["lch", "oklch"] {
       // Testing no modifications.
       test(
-        &format!("color(from color({} 0.7 0.5 0.3) {} r g b)", color_space, color_space),
-        &format!("color({} 0.7 0.5 0.3)", color_space),
+        &format!("{}(from {}(70% 45 30) l c h)", color_space, color_space),
+        &format!("{}(70% 45 30)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0.5 0.3)", color_space),
+        &format!("{}(from {}(70% 45 30) l c h / alpha)", color_space, color_space),
+        &format!("{}(70% 45 30)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c h / alpha)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0.4)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b)",
+          "{}(from {}(200% 300 400 / 500%) l c h / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3)", color_space),
+        &format!("{}(200% 300 40)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / alpha)",
+          "{}(from {}(-200% -300 -400 / -500%) l c h / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0.4)", color_space),
+        &format!("{}(0% 0 320 / 0)", color_space),
       );
 
       // Test nesting relative colors.
       test(
         &format!(
-          "color(from color(from color({} 0.7 0.5 0.3) {} r g b) {} r g b)",
+          "{}(from {}(from {}(70% 45 30) l c h) l c h)",
           color_space, color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3)", color_space),
+        &format!("{}(70% 45 30)", color_space),
+      );
+
+      // Testing non-sRGB origin colors (no gamut mapping will happen since the destination is not a bounded RGB color space).
+      test(
+        &format!("{}(from color(display-p3 0 0 0) l c h / alpha)", color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
 
       // Testing replacement with 0.
       test(
-        &format!("color(from color({} 0.7 0.5 0.3) {} 0 0 0)", color_space, color_space),
-        &format!("color({} 0 0 0)", color_space),
+        &format!("{}(from {}(70% 45 30) 0% 0 0)", color_space, color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 0 0 0 / 0)",
-          color_space, color_space
-        ),
-        &format!("color({} 0 0 0 / 0)", color_space),
+        &format!("{}(from {}(70% 45 30) 0% 0 0deg)", color_space, color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 0 g b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0 0.5 0.3)", color_space),
+        &format!("{}(from {}(70% 45 30) 0% 0 0 / 0)", color_space, color_space),
+        &format!("{}(0% 0 0 / 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r 0 b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0 0.3)", color_space),
+        &format!("{}(from {}(70% 45 30) 0% 0 0deg / 0)", color_space, color_space),
+        &format!("{}(0% 0 0 / 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g 0 / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0.5 0)", color_space),
+        &format!("{}(from {}(70% 45 30) 0% c h / alpha)", color_space, color_space),
+        &format!("{}(0% 45 30)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g b / 0)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0.5 0.3 / 0)", color_space),
+        &format!("{}(from {}(70% 45 30) l 0 h / alpha)", color_space, color_space),
+        &format!("{}(70% 0 30)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} 0 g b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0 0.5 0.3 / 0.4)", color_space),
+        &format!("{}(from {}(70% 45 30) l c 0 / alpha)", color_space, color_space),
+        &format!("{}(70% 45 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 0 b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0 0.3 / 0.4)", color_space),
+        &format!("{}(from {}(70% 45 30) l c 0deg / alpha)", color_space, color_space),
+        &format!("{}(70% 45 0)", color_space),
       );
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 0 / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.7 0.5 0 / 0.4)", color_space),
+        &format!("{}(from {}(70% 45 30) l c h / 0)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) 0% c h / alpha)", color_space, color_space),
+        &format!("{}(0% 45 30 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l 0 h / alpha)", color_space, color_space),
+        &format!("{}(70% 0 30 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c 0 / alpha)", color_space, color_space),
+        &format!("{}(70% 45 0 / 0.4)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 0)",
+          "{}(from {}(70% 45 30 / 40%) l c 0deg / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0)", color_space),
+        &format!("{}(70% 45 0 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c h / 0)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0)", color_space),
       );
 
       // Testing replacement with a constant.
       test(
-        &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 0.2 g b / alpha)",
-          color_space, color_space
-        ),
-        &format!("color({} 0.2 0.5 0.3)", color_space),
+        &format!("{}(from {}(70% 45 30) 25% c h / alpha)", color_space, color_space),
+        &format!("{}(25% 45 30)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l 25 h / alpha)", color_space, color_space),
+        &format!("{}(70% 25 30)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c 25 / alpha)", color_space, color_space),
+        &format!("{}(70% 45 25)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c 25deg / alpha)", color_space, color_space),
+        &format!("{}(70% 45 25)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c h / .25)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0.25)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) 25% c h / alpha)", color_space, color_space),
+        &format!("{}(25% 45 30 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l 25 h / alpha)", color_space, color_space),
+        &format!("{}(70% 25 30 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c 25 / alpha)", color_space, color_space),
+        &format!("{}(70% 45 25 / 0.4)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 20% g b / alpha)",
+          "{}(from {}(70% 45 30 / 40%) l c 25deg / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.2 0.5 0.3)", color_space),
+        &format!("{}(70% 45 25 / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c h / .25)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0.25)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r 0.2 b / alpha)",
+          "{}(from {}(70% 45 30 / 40%) 200% 300 400 / 500)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.2 0.3)", color_space),
+        &format!("{}(200% 300 400)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r 20% b / alpha)",
+          "{}(from {}(70% 45 30 / 40%) -200% -300 -400 / -500)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.2 0.3)", color_space),
+        &format!("{}(0% 0 -400 / 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g 0.2 / alpha)",
+          "{}(from {}(70% 45 30 / 40%) 50% 120 400deg / 500)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.2)", color_space),
+        &format!("{}(50% 120 400)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g 20% / alpha)",
+          "{}(from {}(70% 45 30 / 40%) 50% 120 -400deg / -500)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.2)", color_space),
+        &format!("{}(50% 120 -400 / 0)", color_space),
+      );
+
+      // Testing valid permutation (types match).
+      // NOTE: 'c' is a valid hue, as hue is <angle>|<number>.
+      test(
+        &format!("{}(from {}(70% 45 30) alpha c h / l)", color_space, color_space),
+        &format!("{}(100% 45 30 / 0.7)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c c / alpha)", color_space, color_space),
+        &format!("{}(70% 45 45)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) alpha c h / alpha)", color_space, color_space),
+        &format!("{}(100% 45 30)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) alpha c c / alpha)", color_space, color_space),
+        &format!("{}(100% 45 45)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) alpha c h / l)", color_space, color_space),
+        &format!("{}(40% 45 30 / 0.7)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c c / alpha)", color_space, color_space),
+        &format!("{}(70% 45 45 / 0.4)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g b / 0.2)",
+          "{}(from {}(70% 45 30 / 40%) alpha c h / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+        &format!("{}(40% 45 30 / 0.4)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} r g b / 20%)",
+          "{}(from {}(70% 45 30 / 40%) alpha c c / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+        &format!("{}(40% 45 45 / 0.4)", color_space),
       );
+
+      // Testing with calc().
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} 0.2 g b / alpha)",
+          "{}(from {}(70% 45 30) calc(l) calc(c) calc(h))",
           color_space, color_space
         ),
-        &format!("color({} 0.2 0.5 0.3 / 0.4)", color_space),
+        &format!("{}(70% 45 30)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} 20% g b / alpha)",
+          "{}(from {}(70% 45 30 / 40%) calc(l) calc(c) calc(h) / calc(alpha))",
           color_space, color_space
         ),
-        &format!("color({} 0.2 0.5 0.3 / 0.4)", color_space),
+        &format!("{}(70% 45 30 / 0.4)", color_space),
+      );
+
+      // Testing with 'none'.
+      test(
+        &format!("{}(from {}(70% 45 30) none none none)", color_space, color_space),
+        &format!("{}(none none none)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) none none none / none)", color_space, color_space),
+        &format!("{}(none none none / none)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c none)", color_space, color_space),
+        &format!("{}(70% 45 none)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c none / alpha)", color_space, color_space),
+        &format!("{}(70% 45 none)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30) l c h / none)", color_space, color_space),
+        &format!("{}(70% 45 30 / none)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 0.2 b / alpha)",
+          "{}(from {}(70% 45 30 / 40%) l c none / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.2 0.3 / 0.4)", color_space),
+        &format!("{}(70% 45 none / 0.4)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / 40%) l c h / none)", color_space, color_space),
+        &format!("{}(70% 45 30 / none)", color_space),
+      );
+      // FIXME: Clarify with spec editors if 'none' should pass through to the constants.
+      test(
+        &format!("{}(from {}(none none none) l c h)", color_space, color_space),
+        &format!("{}(0% 0 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 20% b / alpha)",
+          "{}(from {}(none none none / none) l c h / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.2 0.3 / 0.4)", color_space),
+        &format!("{}(0% 0 0 / 0)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% none 30) l c h)", color_space, color_space),
+        &format!("{}(70% 0 30)", color_space),
+      );
+      test(
+        &format!("{}(from {}(70% 45 30 / none) l c h / alpha)", color_space, color_space),
+        &format!("{}(70% 45 30 / 0)", color_space),
+      );
+    }
+
+    // test_valid_value\(`color`, `color\(from color\(\$\{colorSpace\}(.*?) \$\{colorSpace\}(.*?)`,\s*`color\(\$\{colorSpace\}(.*?)`\)
+    // test(&format!("color(from color({}$1 {}$2", color_space, color_space), &format!("color({}$3", color_space))
+
+    for color_space in &["srgb", "srgb-linear", "a98-rgb", "rec2020", "prophoto-rgb"] {
+      // Testing no modifications.
+      test(
+        &format!("color(from color({} 0.7 0.5 0.3) {} r g b)", color_space, color_space),
+        &format!("color({} 0.7 0.5 0.3)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 0.2 / alpha)",
+          "color(from color({} 0.7 0.5 0.3) {} r g b / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.2 / 0.4)", color_space),
+        &format!("color({} 0.7 0.5 0.3)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 20% / alpha)",
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.2 / 0.4)", color_space),
+        &format!("color({} 0.7 0.5 0.3)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 0.2)",
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+        &format!("color({} 0.7 0.5 0.3 / 0.4)", color_space),
       );
+
+      // Test nesting relative colors.
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 20%)",
-          color_space, color_space
+          "color(from color(from color({} 0.7 0.5 0.3) {} r g b) {} r g b)",
+          color_space, color_space, color_space
         ),
-        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+        &format!("color({} 0.7 0.5 0.3)", color_space),
       );
+
+      // Testing replacement with 0.
       test(
-        &format!("color(from color({} 0.7 0.5 0.3) {} 2 3 4)", color_space, color_space),
-        &format!("color({} 2 3 4)", color_space),
+        &format!("color(from color({} 0.7 0.5 0.3) {} 0 0 0)", color_space, color_space),
+        &format!("color({} 0 0 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 2 3 4 / 5)",
+          "color(from color({} 0.7 0.5 0.3) {} 0 0 0 / 0)",
           color_space, color_space
         ),
-        &format!("color({} 2 3 4)", color_space),
+        &format!("color({} 0 0 0 / 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} -2 -3 -4)",
+          "color(from color({} 0.7 0.5 0.3) {} 0 g b / alpha)",
           color_space, color_space
         ),
-        &format!("color({} -2 -3 -4)", color_space),
+        &format!("color({} 0 0.5 0.3)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} -2 -3 -4 / -5)",
+          "color(from color({} 0.7 0.5 0.3) {} r 0 b / alpha)",
           color_space, color_space
         ),
-        &format!("color({} -2 -3 -4 / 0)", color_space),
+        &format!("color({} 0.7 0 0.3)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 200% 300% 400%)",
+          "color(from color({} 0.7 0.5 0.3) {} r g 0 / alpha)",
           color_space, color_space
         ),
-        &format!("color({} 2 3 4)", color_space),
+        &format!("color({} 0.7 0.5 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} 200% 300% 400% / 500%)",
+          "color(from color({} 0.7 0.5 0.3) {} r g b / 0)",
           color_space, color_space
         ),
-        &format!("color({} 2 3 4)", color_space),
+        &format!("color({} 0.7 0.5 0.3 / 0)", color_space),
       );
       test(
         &format!(
-          "color(from color({} 0.7 0.5 0.3) {} -200% -300% -400%)",
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} 0 g b / alpha)",
           color_space, color_space
         ),
-        &format!("color({} -2 -3 -4)", color_space),
+        &format!("color({} 0 0.5 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 0 b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 0 / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 0)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.3 / 0)", color_space),
+      );
+
+      // Testing replacement with a constant.
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} 0.2 g b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.2 0.5 0.3)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} 20% g b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.2 0.5 0.3)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r 0.2 b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.2 0.3)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r 20% b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.2 0.3)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r g 0.2 / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.2)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r g 20% / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.2)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r g b / 0.2)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} r g b / 20%)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} 0.2 g b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.2 0.5 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} 20% g b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.2 0.5 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 0.2 b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.2 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r 20% b / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.2 0.3 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 0.2 / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.2 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g 20% / alpha)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.2 / 0.4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 0.2)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3 / 40%) {} r g b / 20%)",
+          color_space, color_space
+        ),
+        &format!("color({} 0.7 0.5 0.3 / 0.2)", color_space),
+      );
+      test(
+        &format!("color(from color({} 0.7 0.5 0.3) {} 2 3 4)", color_space, color_space),
+        &format!("color({} 2 3 4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} 2 3 4 / 5)",
+          color_space, color_space
+        ),
+        &format!("color({} 2 3 4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} -2 -3 -4)",
+          color_space, color_space
+        ),
+        &format!("color({} -2 -3 -4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} -2 -3 -4 / -5)",
+          color_space, color_space
+        ),
+        &format!("color({} -2 -3 -4 / 0)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} 200% 300% 400%)",
+          color_space, color_space
+        ),
+        &format!("color({} 2 3 4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} 200% 300% 400% / 500%)",
+          color_space, color_space
+        ),
+        &format!("color({} 2 3 4)", color_space),
+      );
+      test(
+        &format!(
+          "color(from color({} 0.7 0.5 0.3) {} -200% -300% -400%)",
+          color_space, color_space
+        ),
+        &format!("color({} -2 -3 -4)", color_space),
       );
       test(
         &format!(
@@ -19788,1518 +22051,3296 @@ mod tests {
 
   #[cfg(feature = "grid")]
   #[test]
-  fn test_grid() {
-    minify_test(
-      ".foo { grid-template-columns: [first nav-start]  150px [main-start] 1fr [last]; }",
-      ".foo{grid-template-columns:[first nav-start]150px[main-start]1fr[last]}",
-    );
-    minify_test(
-      ".foo { grid-template-columns: 150px 1fr; }",
-      ".foo{grid-template-columns:150px 1fr}",
+  fn test_grid() {
+    minify_test(
+      ".foo { grid-template-columns: [first nav-start]  150px [main-start] 1fr [last]; }",
+      ".foo{grid-template-columns:[first nav-start]150px[main-start]1fr[last]}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: 150px 1fr; }",
+      ".foo{grid-template-columns:150px 1fr}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, 1fr); }",
+      ".foo{grid-template-columns:repeat(4,1fr)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(2, [e] 40px); }",
+      ".foo{grid-template-columns:repeat(2,[e]40px)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] 250px [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]250px[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] 60% [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]60%[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] 1fr [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]1fr[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] min-content [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]min-content[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] max-content [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]max-content[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] auto [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]auto[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] minmax(100px, 1fr) [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]minmax(100px,1fr)[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, [col-start] fit-content(200px) [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,[col-start]fit-content(200px)[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(4, 10px [col-start] 30% [col-middle] auto [col-end]); }",
+      ".foo{grid-template-columns:repeat(4,10px[col-start]30%[col-middle]auto[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(5, auto); }",
+      ".foo{grid-template-columns:repeat(5,auto)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(auto-fill, 250px); }",
+      ".foo{grid-template-columns:repeat(auto-fill,250px)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(auto-fit, 250px); }",
+      ".foo{grid-template-columns:repeat(auto-fit,250px)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(auto-fill, [col-start] 250px [col-end]); }",
+      ".foo{grid-template-columns:repeat(auto-fill,[col-start]250px[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: repeat(auto-fill, [col-start] minmax(100px, 1fr) [col-end]); }",
+      ".foo{grid-template-columns:repeat(auto-fill,[col-start]minmax(100px,1fr)[col-end])}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: minmax(min-content, 1fr); }",
+      ".foo{grid-template-columns:minmax(min-content,1fr)}",
+    );
+    minify_test(
+      ".foo { grid-template-columns: 200px repeat(auto-fill, 100px) 300px; }",
+      ".foo{grid-template-columns:200px repeat(auto-fill,100px) 300px}",
+    );
+    minify_test(".foo { grid-template-columns: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }", ".foo{grid-template-columns:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}");
+    minify_test(
+      ".foo { grid-template-rows: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }",
+      ".foo{grid-template-rows:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}",
+    );
+
+    minify_test(".foo { grid-auto-rows: auto; }", ".foo{grid-auto-rows:auto}");
+    minify_test(".foo { grid-auto-rows: 1fr; }", ".foo{grid-auto-rows:1fr}");
+    minify_test(".foo { grid-auto-rows: 100px; }", ".foo{grid-auto-rows:100px}");
+    minify_test(
+      ".foo { grid-auto-rows: min-content; }",
+      ".foo{grid-auto-rows:min-content}",
+    );
+    minify_test(
+      ".foo { grid-auto-rows: max-content; }",
+      ".foo{grid-auto-rows:max-content}",
+    );
+    minify_test(
+      ".foo { grid-auto-rows: minmax(100px,auto); }",
+      ".foo{grid-auto-rows:minmax(100px,auto)}",
+    );
+    minify_test(
+      ".foo { grid-auto-rows: fit-content(20%); }",
+      ".foo{grid-auto-rows:fit-content(20%)}",
+    );
+    minify_test(
+      ".foo { grid-auto-rows: 100px minmax(100px, auto) 10% 0.5fr fit-content(400px); }",
+      ".foo{grid-auto-rows:100px minmax(100px,auto) 10% .5fr fit-content(400px)}",
+    );
+    minify_test(
+      ".foo { grid-auto-columns: 100px minmax(100px, auto) 10% 0.5fr fit-content(400px); }",
+      ".foo{grid-auto-columns:100px minmax(100px,auto) 10% .5fr fit-content(400px)}",
+    );
+
+    minify_test(
+      r#"
+      .foo {
+        grid-template-areas: "head head"
+                             "nav  main"
+                             "foot ....";
+      }
+    "#,
+      ".foo{grid-template-areas:\"head head\"\"nav main\"\"foot.\"}",
+    );
+    minify_test(
+      r#"
+      .foo {
+        grid-template-areas: "head head"
+                             "nav  main"
+                             ".... foot";
+      }
+    "#,
+      ".foo{grid-template-areas:\"head head\"\"nav main\"\".foot\"}",
+    );
+    minify_test(
+      r#"
+      .foo {
+        grid-template-areas: "head head"
+                             "nav  main"
+                             ".... ....";
+      }
+    "#,
+      ".foo{grid-template-areas:\"head head\"\"nav main\"\". .\"}",
+    );
+
+    test(
+      r#"
+      .foo {
+        grid-template-areas: "head head" "nav  main" "foot ....";
+      }
+    "#,
+      indoc! { r#"
+      .foo {
+        grid-template-areas: "head head"
+                             "nav main"
+                             "foot .";
+      }
+    "#},
+    );
+
+    minify_test(
+      r#"
+      .foo {
+        grid-template: [header-top] "a   a   a"     [header-bottom]
+                       [main-top] "b   b   b" 1fr [main-bottom];
+      }
+    "#,
+      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]}",
+    );
+    minify_test(
+      r#"
+      .foo {
+        grid-template: "head head"
+                       "nav  main" 1fr
+                       "foot ....";
+      }
+    "#,
+      ".foo{grid-template:\"head head\"\"nav main\"1fr\"foot.\"}",
+    );
+    minify_test(
+      r#"
+      .foo {
+        grid-template: [header-top] "a   a   a"     [header-bottom]
+                         [main-top] "b   b   b" 1fr [main-bottom]
+                                  / auto 1fr auto;
+      }
+    "#,
+      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]/auto 1fr auto}",
+    );
+
+    minify_test(
+      ".foo { grid-template: auto 1fr / auto 1fr auto; }",
+      ".foo{grid-template:auto 1fr/auto 1fr auto}",
+    );
+    minify_test(
+      ".foo { grid-template: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3] / [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }",
+      ".foo{grid-template:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]/[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}"
+    );
+
+    test(
+      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]/auto 1fr auto}",
+      indoc! {r#"
+        .foo {
+          grid-template: [header-top] "a a a" [header-bottom]
+                         [main-top] "b b b" 1fr [main-bottom]
+                         / auto 1fr auto;
+        }
+      "#},
+    );
+    test(
+      ".foo{grid-template:[header-top]\"a a a\"[main-top]\"b b b\"1fr/auto 1fr auto}",
+      indoc! {r#"
+        .foo {
+          grid-template: [header-top] "a a a"
+                         [main-top] "b b b" 1fr
+                         / auto 1fr auto;
+        }
+      "#},
+    );
+
+    minify_test(".foo { grid-auto-flow: row }", ".foo{grid-auto-flow:row}");
+    minify_test(".foo { grid-auto-flow: column }", ".foo{grid-auto-flow:column}");
+    minify_test(".foo { grid-auto-flow: row dense }", ".foo{grid-auto-flow:dense}");
+    minify_test(".foo { grid-auto-flow: dense row }", ".foo{grid-auto-flow:dense}");
+    minify_test(
+      ".foo { grid-auto-flow: column dense }",
+      ".foo{grid-auto-flow:column dense}",
+    );
+    minify_test(
+      ".foo { grid-auto-flow: dense column }",
+      ".foo{grid-auto-flow:column dense}",
+    );
+
+    minify_test(".foo { grid: none }", ".foo{grid:none}");
+    minify_test(".foo { grid: \"a\" 100px \"b\" 1fr }", ".foo{grid:\"a\"100px\"b\"1fr}");
+    minify_test(
+      ".foo { grid: [linename1] \"a\" 100px [linename2] }",
+      ".foo{grid:[linename1]\"a\"100px[linename2]}",
+    );
+    minify_test(
+      ".foo { grid: \"a\" 200px \"b\" min-content }",
+      ".foo{grid:\"a\"200px\"b\"min-content}",
+    );
+    minify_test(
+      ".foo { grid: \"a\" minmax(100px, max-content) \"b\" 20% }",
+      ".foo{grid:\"a\"minmax(100px,max-content)\"b\"20%}",
+    );
+    minify_test(".foo { grid: 100px / 200px }", ".foo{grid:100px/200px}");
+    minify_test(
+      ".foo { grid: minmax(400px, min-content) / repeat(auto-fill, 50px) }",
+      ".foo{grid:minmax(400px,min-content)/repeat(auto-fill,50px)}",
+    );
+
+    minify_test(".foo { grid: 200px / auto-flow }", ".foo{grid:200px/auto-flow}");
+    minify_test(".foo { grid: 30% / auto-flow dense }", ".foo{grid:30%/auto-flow dense}");
+    minify_test(".foo { grid: 30% / dense auto-flow }", ".foo{grid:30%/auto-flow dense}");
+    minify_test(
+      ".foo { grid: repeat(3, [line1 line2 line3] 200px) / auto-flow 300px }",
+      ".foo{grid:repeat(3,[line1 line2 line3]200px)/auto-flow 300px}",
+    );
+    minify_test(
+      ".foo { grid: [line1] minmax(20em, max-content) / auto-flow dense 40% }",
+      ".foo{grid:[line1]minmax(20em,max-content)/auto-flow dense 40%}",
+    );
+    minify_test(".foo { grid: none / auto-flow 1fr }", ".foo{grid:none/auto-flow 1fr}");
+
+    minify_test(".foo { grid: auto-flow / 200px }", ".foo{grid:none/200px}");
+    minify_test(".foo { grid: auto-flow dense / 30% }", ".foo{grid:auto-flow dense/30%}");
+    minify_test(".foo { grid: dense auto-flow / 30% }", ".foo{grid:auto-flow dense/30%}");
+    minify_test(
+      ".foo { grid: auto-flow 300px / repeat(3, [line1 line2 line3] 200px) }",
+      ".foo{grid:auto-flow 300px/repeat(3,[line1 line2 line3]200px)}",
+    );
+    minify_test(
+      ".foo { grid: auto-flow dense 40% / [line1] minmax(20em, max-content) }",
+      ".foo{grid:auto-flow dense 40%/[line1]minmax(20em,max-content)}",
+    );
+
+    minify_test(".foo { grid-row-start: auto }", ".foo{grid-row-start:auto}");
+    minify_test(".foo { grid-row-start: some-area }", ".foo{grid-row-start:some-area}");
+    minify_test(".foo { grid-row-start: 2 }", ".foo{grid-row-start:2}");
+    minify_test(
+      ".foo { grid-row-start: 2 some-line }",
+      ".foo{grid-row-start:2 some-line}",
+    );
+    minify_test(
+      ".foo { grid-row-start: some-line 2 }",
+      ".foo{grid-row-start:2 some-line}",
+    );
+    minify_test(".foo { grid-row-start: span 3 }", ".foo{grid-row-start:span 3}");
+    minify_test(
+      ".foo { grid-row-start: span some-line }",
+      ".foo{grid-row-start:span some-line}",
+    );
+    minify_test(
+      ".foo { grid-row-start: span some-line 1 }",
+      ".foo{grid-row-start:span some-line}",
+    );
+    minify_test(
+      ".foo { grid-row-start: span 1 some-line }",
+      ".foo{grid-row-start:span some-line}",
+    );
+    minify_test(
+      ".foo { grid-row-start: span 5 some-line }",
+      ".foo{grid-row-start:span 5 some-line}",
+    );
+    minify_test(
+      ".foo { grid-row-start: span some-line 5 }",
+      ".foo{grid-row-start:span 5 some-line}",
+    );
+
+    minify_test(
+      ".foo { grid-row-end: span 1 some-line }",
+      ".foo{grid-row-end:span some-line}",
+    );
+    minify_test(
+      ".foo { grid-column-start: span 1 some-line }",
+      ".foo{grid-column-start:span some-line}",
+    );
+    minify_test(
+      ".foo { grid-column-end: span 1 some-line }",
+      ".foo{grid-column-end:span some-line}",
+    );
+
+    minify_test(".foo { grid-row: 1 }", ".foo{grid-row:1}");
+    minify_test(".foo { grid-row: 1 / auto }", ".foo{grid-row:1}");
+    minify_test(".foo { grid-row: 1 / 1 }", ".foo{grid-row:1/1}");
+    minify_test(".foo { grid-row: 1 / 3 }", ".foo{grid-row:1/3}");
+    minify_test(".foo { grid-row: 1 / span 2 }", ".foo{grid-row:1/span 2}");
+    minify_test(".foo { grid-row: main-start }", ".foo{grid-row:main-start}");
+    minify_test(
+      ".foo { grid-row: main-start / main-end }",
+      ".foo{grid-row:main-start/main-end}",
+    );
+    minify_test(
+      ".foo { grid-row: main-start / main-start }",
+      ".foo{grid-row:main-start}",
+    );
+    minify_test(".foo { grid-column: 1 / auto }", ".foo{grid-column:1}");
+
+    minify_test(".foo { grid-area: a }", ".foo{grid-area:a}");
+    minify_test(".foo { grid-area: a / a / a / a }", ".foo{grid-area:a}");
+    minify_test(".foo { grid-area: a / b / a / b }", ".foo{grid-area:a/b}");
+    minify_test(".foo { grid-area: a / b / c / b }", ".foo{grid-area:a/b/c}");
+    minify_test(".foo { grid-area: a / b / c / d }", ".foo{grid-area:a/b/c/d}");
+
+    minify_test(".foo { grid-area: auto / auto / auto / auto }", ".foo{grid-area:auto}");
+    minify_test(".foo { grid-area: 1 / auto }", ".foo{grid-area:1}");
+    minify_test(".foo { grid-area: 1 / 2 / 3 / 4 }", ".foo{grid-area:1/2/3/4}");
+    minify_test(".foo { grid-area: 1 / 1 / 1 / 1 }", ".foo{grid-area:1/1/1/1}");
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: auto 1fr / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: "a a a"
+                               "b b b";
+          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
+          grid-template-columns: auto 1fr auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: [header-top] "a a a" [header-bottom]
+                         [main-top] "b b b" 1fr [main-bottom]
+                         / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: "a a a"
+                               "b b b";
+          grid-template-columns: repeat(3, 1fr);
+          grid-template-rows: auto 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template-rows: auto 1fr;
+          grid-template-columns: repeat(3, 1fr);
+          grid-template-areas: "a a a"
+                               "b b b";
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: "a a a"
+                               "b b b";
+          grid-template-columns: auto 1fr auto;
+          grid-template-rows: repeat(2, 1fr);
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template-rows: repeat(2, 1fr);
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: "a a a"
+                               "b b b";
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: ". a a ."
+                               ". b b .";
+          grid-template-rows: auto 1fr;
+          grid-template-columns: 10px 1fr 1fr 10px;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: ". a a ."
+                         ". b b ." 1fr
+                         / 10px 1fr 1fr 10px;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: none;
+          grid-template-columns: auto 1fr auto;
+          grid-template-rows: repeat(2, 1fr);
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: repeat(2, 1fr) / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: none;
+          grid-template-columns: none;
+          grid-template-rows: none;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: none;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: "a a a"
+                               "b b b";
+          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
+          grid-template-columns: auto 1fr auto;
+          grid-auto-flow: row;
+          grid-auto-rows: auto;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: [header-top] "a a a" [header-bottom]
+                [main-top] "b b b" 1fr [main-bottom]
+                / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: none;
+          grid-template-columns: auto 1fr auto;
+          grid-template-rows: repeat(2, 1fr);
+          grid-auto-flow: row;
+          grid-auto-rows: auto;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: repeat(2, 1fr) / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: none;
+          grid-template-columns: none;
+          grid-template-rows: none;
+          grid-auto-flow: row;
+          grid-auto-rows: auto;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: none;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-areas: "a a a"
+                               "b b b";
+          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
+          grid-template-columns: auto 1fr auto;
+          grid-auto-flow: column;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: [header-top] "a a a" [header-bottom]
+                         [main-top] "b b b" 1fr [main-bottom]
+                         / auto 1fr auto;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+          grid-auto-flow: column;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+          grid-auto-flow: row;
+          grid-auto-rows: auto;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: auto 1fr / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+          grid-auto-flow: column;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: auto 1fr / auto 1fr auto;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+          grid-auto-flow: column;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: none;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+          grid-auto-flow: column;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: none / auto 1fr auto;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: 1fr;
+          grid-auto-flow: column;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: none;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+          grid-auto-flow: row;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: auto-flow 1fr / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: none;
+          grid-template-columns: auto 1fr auto;
+          grid-template-areas: none;
+          grid-auto-flow: row dense;
+          grid-auto-rows: 1fr;
+          grid-auto-columns: auto;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: auto-flow dense 1fr / auto 1fr auto;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr auto;
+          grid-template-columns: none;
+          grid-template-areas: none;
+          grid-auto-flow: column;
+          grid-auto-rows: auto;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: auto 1fr auto / auto-flow 1fr;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr auto;
+          grid-template-columns: none;
+          grid-template-areas: none;
+          grid-auto-flow: column dense;
+          grid-auto-rows: auto;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: auto 1fr auto / auto-flow dense 1fr;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-template-rows: auto 1fr auto;
+          grid-template-columns: none;
+          grid-template-areas: none;
+          grid-auto-flow: var(--auto-flow);
+          grid-auto-rows: auto;
+          grid-auto-columns: 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-template: auto 1fr auto / none;
+          grid-auto-flow: var(--auto-flow);
+          grid-auto-rows: auto;
+          grid-auto-columns: 1fr;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid: auto 1fr auto / auto-flow dense 1fr;
+          grid-template-rows: 1fr 1fr 1fr;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid: 1fr 1fr 1fr / auto-flow dense 1fr;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-row-start: a;
+          grid-row-end: a;
+          grid-column-start: a;
+          grid-column-end: a;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-area: a;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-row-start: 1;
+          grid-row-end: 2;
+          grid-column-start: 3;
+          grid-column-end: 4;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-area: 1 / 3 / 2 / 4;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-row-start: a;
+          grid-row-end: a;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-row: a;
+        }
+      "#},
+    );
+
+    test(
+      r#"
+        .foo{
+          grid-column-start: a;
+          grid-column-end: a;
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          grid-column: a;
+        }
+      "#},
+    );
+  }
+
+  #[cfg(feature = "grid")]
+  #[test]
+  fn test_ms_grid() {
+    fn ms_grid_test(source: &str, expected: &str, targets: Browsers, expected_warnings: Vec<&str>) {
+      let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      let warnings = Arc::new(RwLock::new(Vec::new()));
+      stylesheet
+        .minify(MinifyOptions {
+          targets: targets.into(),
+          ms_grid: true,
+          unsupported_features: UnsupportedFeatureMode::Warn,
+          warnings: Some(warnings.clone()),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+      assert_eq!(res.code, expected);
+      let warnings = warnings.read().unwrap();
+      assert_eq!(
+        warnings.iter().map(|w| w.kind.to_string()).collect::<Vec<_>>(),
+        expected_warnings
+      );
+    }
+
+    let ie = Browsers {
+      ie: Some(11 << 16),
+      chrome: Some(100 << 16),
+      ..Browsers::default()
+    };
+
+    ms_grid_test(
+      r#"
+      .grid {
+        display: grid;
+        grid-template-columns: 200px repeat(2, 1fr minmax(100px, auto));
+        grid-template-rows: auto 50%;
+      }
+
+      .item {
+        grid-column: 2 / 4;
+        grid-row: 1 / span 2;
+      }
+
+      .inline {
+        display: inline-grid;
+        grid-area: 2 / 1;
+      }
+    "#,
+      indoc! {r#"
+      .grid {
+        -ms-grid-rows: auto 50%;
+        grid-template-rows: auto 50%;
+        -ms-grid-columns: 200px (1fr minmax(100px, auto)) [2];
+        grid-template-columns: 200px repeat(2, 1fr minmax(100px, auto));
+        display: -ms-grid;
+        display: grid;
+      }
+
+      .item {
+        -ms-grid-row: 1;
+        -ms-grid-row-span: 2;
+        -ms-grid-column: 2;
+        -ms-grid-column-span: 2;
+        grid-area: 1 / 2 / span 2 / 4;
+      }
+
+      .inline {
+        -ms-grid-row: 2;
+        -ms-grid-column: 1;
+        grid-area: 2 / 1;
+        display: -ms-inline-grid;
+        display: inline-grid;
+      }
+    "#},
+      ie,
+      vec![],
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, 1fr); }",
-      ".foo{grid-template-columns:repeat(4,1fr)}",
+
+    ms_grid_test(
+      r#"
+      .grid {
+        display: grid;
+        grid-template-areas: "a b";
+        grid-auto-flow: column;
+      }
+
+      .item {
+        grid-column: a;
+        grid-row: 1 / -1;
+      }
+    "#,
+      indoc! {r#"
+      .grid {
+        grid-template-areas: "a b";
+        grid-auto-flow: column;
+        display: -ms-grid;
+        display: grid;
+      }
+
+      .item {
+        grid-area: 1 / a / -1;
+      }
+    "#},
+      ie,
+      vec![
+        "grid-template-areas is not supported by the browser targets: ie 11",
+        "grid-auto-flow is not supported by the browser targets: ie 11",
+        "grid-area is not supported by the browser targets: ie 11",
+      ],
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(2, [e] 40px); }",
-      ".foo{grid-template-columns:repeat(2,[e]40px)}",
+
+    ms_grid_test(
+      r#"
+      .grid {
+        display: grid;
+        grid-template-columns: 1fr 1fr;
+      }
+    "#,
+      indoc! {r#"
+      .grid {
+        grid-template-columns: 1fr 1fr;
+        display: grid;
+      }
+    "#},
+      Browsers {
+        edge: Some(16 << 16),
+        ..Browsers::default()
+      },
+      vec![],
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] 250px [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]250px[col-end])}",
+  }
+
+  #[test]
+  fn test_css_modules_minimal_names() {
+    let minimal_names = crate::css_modules::MinimalNames::new();
+    css_modules_test(
+      r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        composes: foo;
+        animation: fade 1s;
+      }
+
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
+    "#,
+      indoc! {r#"
+      .a {
+        color: red;
+      }
+
+      .b {
+        animation: 1s c;
+      }
+
+      @keyframes c {
+        from {
+          opacity: 0;
+        }
+
+        to {
+          opacity: 1;
+        }
+      }
+    "#},
+      map! {
+        "foo" => "a",
+        "bar" => "b" "a",
+        "fade" => "c" referenced: true
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        minimal_names: Some(&minimal_names),
+        ..Default::default()
+      },
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] 60% [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]60%[col-end])}",
+
+    // Names are shared across files, and seeded names are preserved.
+    let minimal_names =
+      crate::css_modules::MinimalNames::with_names(HashMap::from([("EgL3uq_bar".into(), "a".into())]));
+    css_modules_test(
+      r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        color: green;
+      }
+    "#,
+      indoc! {r#"
+      .b {
+        color: red;
+      }
+
+      .a {
+        color: green;
+      }
+    "#},
+      map! {
+        "foo" => "b",
+        "bar" => "a"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        minimal_names: Some(&minimal_names),
+        ..Default::default()
+      },
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] 1fr [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]1fr[col-end])}",
+    let stylesheet = StyleSheet::parse(
+      ".foo { color: red }",
+      ParserOptions {
+        filename: "other.css".into(),
+        css_modules: Some(crate::css_modules::Config {
+          minimal_names: Some(&minimal_names),
+          ..Default::default()
+        }),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, ".c {\n  color: red;\n}\n");
+    assert_eq!(minimal_names.names().len(), 3);
+
+    // Grid line names keep the suffixes of the implicit lines generated for each area.
+    #[cfg(feature = "grid")]
+    css_modules_test(
+      r#"
+      .grid {
+        grid-template-areas: "header main";
+      }
+
+      .main {
+        grid-column: header-start / main-end;
+      }
+    "#,
+      indoc! {r#"
+      .d {
+        grid-template-areas: "e f";
+      }
+
+      .f {
+        grid-column: e-start / f-end;
+      }
+    "#},
+      map! {
+        "grid" => "d",
+        "header" => "e",
+        "main" => "f",
+        "header-start" => "e-start",
+        "main-end" => "f-end"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        minimal_names: Some(&minimal_names),
+        ..Default::default()
+      },
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] min-content [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]min-content[col-end])}",
+  }
+
+  #[test]
+  fn test_css_modules_pure() {
+    let pure_test = |source: &'static str| {
+      StyleSheet::parse(
+        source,
+        ParserOptions {
+          css_modules: Some(crate::css_modules::Config {
+            pure: true,
+            ..Default::default()
+          }),
+          ..ParserOptions::default()
+        },
+      )
+      .map(|_| ())
+    };
+
+    assert!(pure_test(".foo { color: red }").is_ok());
+    assert!(pure_test("#foo { color: red }").is_ok());
+    assert!(pure_test("div.foo:hover { color: red }").is_ok());
+    assert!(pure_test(":global(.a) .b { color: red }").is_ok());
+    assert!(pure_test(":global .a :local(.b) { color: red }").is_ok());
+    assert!(pure_test("div:not(.foo) { color: red }").is_ok());
+    assert!(pure_test(".foo { :global(.dark) & { color: red } }").is_ok());
+    assert!(pure_test(".foo { div { color: red } }").is_ok());
+    assert!(pure_test(":local { div .foo { color: red } }").is_ok());
+    assert!(pure_test("@media print { .foo { color: red } }").is_ok());
+    assert!(pure_test("@keyframes fade { from { opacity: 0 } }").is_ok());
+
+    for source in [
+      "div { color: red }",
+      ":root { color: red }",
+      "*, .foo { color: red }",
+      ":global(.foo) { color: red }",
+      ":global .foo { color: red }",
+      "@media print { body { color: red } }",
+      ":global { .foo { color: red } }",
+    ] {
+      assert_eq!(
+        pure_test(source).unwrap_err().kind,
+        ParserError::SelectorError(SelectorError::ImpureCssModulesSelector),
+        "{}",
+        source
+      );
+    }
+
+    let err = pure_test(".foo { color: red }\n\n  html body { color: red }").unwrap_err();
+    assert_eq!(
+      err.loc,
+      Some(ErrorLocation {
+        filename: "".into(),
+        line: 2,
+        column: 3
+      })
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] max-content [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]max-content[col-end])}",
+  }
+
+  #[test]
+  fn test_css_modules_values() {
+    css_modules_test(
+      r#"
+      @value primary: #333;
+      @value gap: 8px;
+      @value border: 1px solid primary;
+      @value small: (max-width: 599px);
+
+      .foo {
+        color: primary;
+        border: border;
+        margin: gap calc(gap * 2);
+      }
+
+      @media small {
+        .foo {
+          color: red;
+        }
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo {
+        color: #333;
+        border: 1px solid #333;
+        margin: 8px calc(8px * 2);
+      }
+
+      @media (max-width: 599px) {
+        .EgL3uq_foo {
+          color: red;
+        }
+      }
+    "#},
+      map! {
+        "primary" => "#333",
+        "gap" => "8px",
+        "border" => "1px solid #333",
+        "small" => "(max-width: 599px)",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      Default::default(),
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] auto [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]auto[col-end])}",
+
+    css_modules_test(
+      r#"
+      @value primary, secondary as accent from "./colors.css";
+
+      .foo {
+        color: primary;
+        background: accent;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo {
+        color: v0doMa;
+        background: gLV3PG;
+      }
+    "#},
+      map! {
+        "primary" => "v0doMa",
+        "accent" => "gLV3PG",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::from([
+        (
+          "v0doMa".into(),
+          CssModuleReference::Dependency {
+            name: "primary".into(),
+            specifier: "./colors.css".into(),
+          },
+        ),
+        (
+          "gLV3PG".into(),
+          CssModuleReference::Dependency {
+            name: "secondary".into(),
+            specifier: "./colors.css".into(),
+          },
+        ),
+      ]),
+      Default::default(),
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] minmax(100px, 1fr) [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]minmax(100px,1fr)[col-end])}",
+  }
+
+  #[test]
+  fn test_css_modules_icss() {
+    css_modules_test(
+      r#"
+      :import("./colors.css") {
+        i_primary: primary;
+      }
+
+      :export {
+        brand: i_primary;
+        gap: 8px;
+      }
+
+      .foo {
+        color: i_primary;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo {
+        color: v0doMa;
+      }
+    "#},
+      map! {
+        "brand" => "v0doMa",
+        "gap" => "8px",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::from([(
+        "v0doMa".into(),
+        CssModuleReference::Dependency {
+          name: "primary".into(),
+          specifier: "./colors.css".into(),
+        },
+      )]),
+      Default::default(),
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, [col-start] fit-content(200px) [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,[col-start]fit-content(200px)[col-end])}",
+
+    css_modules_test(
+      r#"
+      @value primary from "./colors.css";
+
+      .foo {
+        color: primary;
+      }
+
+      .bar {
+        composes: foo;
+        composes: baz from "./b.css";
+        background: green;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo {
+        color: v0doMa;
+      }
+
+      .EgL3uq_bar {
+        background: green;
+      }
+
+      :import("./b.css") {
+        L2e2nG: baz;
+      }
+
+      :import("./colors.css") {
+        v0doMa: primary;
+      }
+
+      :export {
+        bar: EgL3uq_bar EgL3uq_foo L2e2nG;
+        foo: EgL3uq_foo;
+        primary: v0doMa;
+      }
+    "#},
+      map! {
+        "primary" => "v0doMa",
+        "foo" => "EgL3uq_foo",
+        "bar" => "EgL3uq_bar" "EgL3uq_foo" "baz" from "./b.css"
+      },
+      HashMap::from([(
+        "v0doMa".into(),
+        CssModuleReference::Dependency {
+          name: "primary".into(),
+          specifier: "./colors.css".into(),
+        },
+      )]),
+      crate::css_modules::Config {
+        icss: true,
+        ..Default::default()
+      },
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(4, 10px [col-start] 30% [col-middle] auto [col-end]); }",
-      ".foo{grid-template-columns:repeat(4,10px[col-start]30%[col-middle]auto[col-end])}",
+
+    let res = StyleSheet::parse(
+      ".foo { color: red }",
+      ParserOptions {
+        filename: "test.css".into(),
+        css_modules: Some(crate::css_modules::Config {
+          icss: true,
+          ..Default::default()
+        }),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap()
+    .to_css(PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    })
+    .unwrap();
+    assert_eq!(res.code, ".EgL3uq_foo{color:red}:export{foo:EgL3uq_foo}");
+
+    // ICSS blocks are only recognized at the top level of CSS modules.
+    let res = StyleSheet::parse(
+      "@media print { :export { a: b } }",
+      ParserOptions {
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(5, auto); }",
-      ".foo{grid-template-columns:repeat(5,auto)}",
+    assert_eq!(res.unwrap_err().kind, ParserError::UnexpectedIcssBlock);
+    minify_test(":export { color: red }", ":export{color:red}");
+  }
+
+  #[test]
+  fn test_css_modules_tree_shake() {
+    let mut stylesheet = StyleSheet::parse(
+      r#"
+      .used {
+        animation: spin 1s;
+      }
+
+      .unused {
+        animation: fade 1s;
+      }
+
+      .unused .used, #unused-id, :is(.unused, .other) {
+        color: green;
+      }
+
+      .used, .unused {
+        color: yellow;
+      }
+
+      .used:not(.unused) {
+        color: orange;
+      }
+
+      .composer {
+        composes: composed;
+      }
+
+      .composed {
+        color: purple;
+      }
+
+      :global(.global), div {
+        color: black;
+      }
+
+      @media print {
+        .unused {
+          color: red;
+        }
+      }
+
+      .used {
+        .unused & {
+          color: pink;
+        }
+      }
+
+      @keyframes fade {
+        from { opacity: 0 }
+      }
+
+      @keyframes spin {
+        from { opacity: 0 }
+      }
+
+      @keyframes exported {
+        from { opacity: 0 }
+      }
+    "#,
+      ParserOptions {
+        filename: "test.css".into(),
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let used =
+      std::collections::HashSet::from(["used".to_string(), "composer".to_string(), "exported".to_string()]);
+    let report = stylesheet.tree_shake_css_modules(&used);
+    assert_eq!(
+      report.removed_exports,
+      vec!["fade".to_string(), "other".to_string(), "unused-id".to_string()]
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(auto-fill, 250px); }",
-      ".foo{grid-template-columns:repeat(auto-fill,250px)}",
+
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      res.code,
+      indoc! {r#"
+      .EgL3uq_used {
+        animation: 1s EgL3uq_spin;
+      }
+
+      .EgL3uq_used, .EgL3uq_unused {
+        color: #ff0;
+      }
+
+      .EgL3uq_used:not(.EgL3uq_unused) {
+        color: orange;
+      }
+
+      .EgL3uq_composer {
+      }
+
+      .EgL3uq_composed {
+        color: purple;
+      }
+
+      .global, div {
+        color: #000;
+      }
+
+      .EgL3uq_used {
+      }
+
+      @keyframes EgL3uq_spin {
+        from {
+          opacity: 0;
+        }
+      }
+
+      @keyframes EgL3uq_exported {
+        from {
+          opacity: 0;
+        }
+      }
+    "#}
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(auto-fit, 250px); }",
-      ".foo{grid-template-columns:repeat(auto-fit,250px)}",
+  }
+
+  #[test]
+  fn test_css_modules_typescript_declarations() {
+    let stylesheet = StyleSheet::parse(
+      r#"
+      .foo {
+        color: red;
+      }
+
+      .bar-baz {
+        animation: fade 1s;
+      }
+
+      @keyframes fade {
+        from { opacity: 0 }
+      }
+    "#,
+      ParserOptions {
+        filename: "test.css".into(),
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(
+      crate::css_modules::typescript_declarations(&res.exports.unwrap()),
+      indoc! {r#"
+      declare const styles: {
+        readonly "bar-baz": string;
+        readonly "fade": string;
+        readonly "foo": string;
+      };
+      export default styles;
+    "#}
     );
-    minify_test(
-      ".foo { grid-template-columns: repeat(auto-fill, [col-start] 250px [col-end]); }",
-      ".foo{grid-template-columns:repeat(auto-fill,[col-start]250px[col-end])}",
+    assert_eq!(
+      crate::css_modules::typescript_declarations(&HashMap::new()),
+      "declare const styles: {\n};\nexport default styles;\n"
     );
+  }
+
+  #[test]
+  fn test_moz_document() {
     minify_test(
-      ".foo { grid-template-columns: repeat(auto-fill, [col-start] minmax(100px, 1fr) [col-end]); }",
-      ".foo{grid-template-columns:repeat(auto-fill,[col-start]minmax(100px,1fr)[col-end])}",
+      r#"
+      @-moz-document url-prefix() {
+        h1 {
+          color: yellow;
+        }
+      }
+    "#,
+      "@-moz-document url-prefix(){h1{color:#ff0}}",
     );
     minify_test(
-      ".foo { grid-template-columns: minmax(min-content, 1fr); }",
-      ".foo{grid-template-columns:minmax(min-content,1fr)}",
+      r#"
+      @-moz-document url-prefix("") {
+        h1 {
+          color: yellow;
+        }
+      }
+    "#,
+      "@-moz-document url-prefix(){h1{color:#ff0}}",
     );
-    minify_test(
-      ".foo { grid-template-columns: 200px repeat(auto-fill, 100px) 300px; }",
-      ".foo{grid-template-columns:200px repeat(auto-fill,100px) 300px}",
+    error_test(
+      "@-moz-document url-prefix(foo) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("foo".into())),
     );
-    minify_test(".foo { grid-template-columns: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }", ".foo{grid-template-columns:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}");
-    minify_test(
-      ".foo { grid-template-rows: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }",
-      ".foo{grid-template-rows:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}",
+    error_test(
+      "@-moz-document url-prefix(\"foo\") {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::String("foo".into())),
     );
+  }
 
-    minify_test(".foo { grid-auto-rows: auto; }", ".foo{grid-auto-rows:auto}");
-    minify_test(".foo { grid-auto-rows: 1fr; }", ".foo{grid-auto-rows:1fr}");
-    minify_test(".foo { grid-auto-rows: 100px; }", ".foo{grid-auto-rows:100px}");
+  #[test]
+  fn test_custom_properties() {
+    minify_test(".foo { --test: ; }", ".foo{--test: }");
+    minify_test(".foo { --test:  ; }", ".foo{--test: }");
+    minify_test(".foo { --test: foo; }", ".foo{--test:foo}");
+    minify_test(".foo { --test:  foo; }", ".foo{--test:foo}");
+    minify_test(".foo { --test: foo ; }", ".foo{--test:foo}");
+    minify_test(".foo { --test: foo  ; }", ".foo{--test:foo}");
+    minify_test(".foo { --test:foo; }", ".foo{--test:foo}");
+    minify_test(".foo { --test:foo ; }", ".foo{--test:foo}");
+    minify_test(".foo { --test: var(--foo, 20px); }", ".foo{--test:var(--foo,20px)}");
     minify_test(
-      ".foo { grid-auto-rows: min-content; }",
-      ".foo{grid-auto-rows:min-content}",
+      ".foo { transition: var(--foo, 20px),\nvar(--bar, 40px); }",
+      ".foo{transition:var(--foo,20px),var(--bar,40px)}",
     );
     minify_test(
-      ".foo { grid-auto-rows: max-content; }",
-      ".foo{grid-auto-rows:max-content}",
+      ".foo { background: var(--color) var(--image); }",
+      ".foo{background:var(--color)var(--image)}",
     );
     minify_test(
-      ".foo { grid-auto-rows: minmax(100px,auto); }",
-      ".foo{grid-auto-rows:minmax(100px,auto)}",
+      ".foo { height: calc(var(--spectrum-global-dimension-size-300) / 2);",
+      ".foo{height:calc(var(--spectrum-global-dimension-size-300)/2)}",
     );
     minify_test(
-      ".foo { grid-auto-rows: fit-content(20%); }",
-      ".foo{grid-auto-rows:fit-content(20%)}",
+      ".foo { color: var(--color, rgb(255, 255, 0)); }",
+      ".foo{color:var(--color,#ff0)}",
     );
     minify_test(
-      ".foo { grid-auto-rows: 100px minmax(100px, auto) 10% 0.5fr fit-content(400px); }",
-      ".foo{grid-auto-rows:100px minmax(100px,auto) 10% .5fr fit-content(400px)}",
+      ".foo { color: var(--color, #ffff00); }",
+      ".foo{color:var(--color,#ff0)}",
     );
     minify_test(
-      ".foo { grid-auto-columns: 100px minmax(100px, auto) 10% 0.5fr fit-content(400px); }",
-      ".foo{grid-auto-columns:100px minmax(100px,auto) 10% .5fr fit-content(400px)}",
+      ".foo { color: var(--color, rgb(var(--red), var(--green), 0)); }",
+      ".foo{color:var(--color,rgb(var(--red),var(--green),0))}",
     );
+    minify_test(".foo { --test: .5s; }", ".foo{--test:.5s}");
+    minify_test(".foo { --theme-sizes-1\\/12: 2 }", ".foo{--theme-sizes-1\\/12:2}");
+    minify_test(".foo { --test: 0px; }", ".foo{--test:0px}");
 
-    minify_test(
+    prefix_test(
       r#"
       .foo {
-        grid-template-areas: "head head"
-                             "nav  main"
-                             "foot ....";
+        --custom: lab(40% 56.6 39);
       }
     "#,
-      ".foo{grid-template-areas:\"head head\"\"nav main\"\"foot.\"}",
-    );
-    minify_test(
-      r#"
+      indoc! {r#"
       .foo {
-        grid-template-areas: "head head"
-                             "nav  main"
-                             ".... foot";
+        --custom: #b32323;
       }
-    "#,
-      ".foo{grid-template-areas:\"head head\"\"nav main\"\".foot\"}",
-    );
-    minify_test(
-      r#"
-      .foo {
-        grid-template-areas: "head head"
-                             "nav  main"
-                             ".... ....";
+
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --custom: lab(40% 56.6 39);
+        }
       }
-    "#,
-      ".foo{grid-template-areas:\"head head\"\"nav main\"\". .\"}",
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
       .foo {
-        grid-template-areas: "head head" "nav  main" "foot ....";
+        --custom: lab(40% 56.6 39) !important;
       }
     "#,
-      indoc! { r#"
+      indoc! {r#"
       .foo {
-        grid-template-areas: "head head"
-                             "nav main"
-                             "foot .";
+        --custom: #b32323 !important;
       }
-    "#},
-    );
 
-    minify_test(
-      r#"
-      .foo {
-        grid-template: [header-top] "a   a   a"     [header-bottom]
-                       [main-top] "b   b   b" 1fr [main-bottom];
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --custom: lab(40% 56.6 39) !important;
+        }
       }
-    "#,
-      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]}",
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
+
+    prefix_test(
       r#"
       .foo {
-        grid-template: "head head"
-                       "nav  main" 1fr
-                       "foot ....";
+        --custom: lab(40% 56.6 39);
       }
     "#,
-      ".foo{grid-template:\"head head\"\"nav main\"1fr\"foot.\"}",
-    );
-    minify_test(
-      r#"
+      indoc! {r#"
       .foo {
-        grid-template: [header-top] "a   a   a"     [header-bottom]
-                         [main-top] "b   b   b" 1fr [main-bottom]
-                                  / auto 1fr auto;
+        --custom: #b32323;
       }
-    "#,
-      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]/auto 1fr auto}",
-    );
-
-    minify_test(
-      ".foo { grid-template: auto 1fr / auto 1fr auto; }",
-      ".foo{grid-template:auto 1fr/auto 1fr auto}",
-    );
-    minify_test(
-      ".foo { grid-template: [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3] / [linename1 linename2] 100px repeat(auto-fit, [linename1] 300px) [linename3]; }",
-      ".foo{grid-template:[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]/[linename1 linename2]100px repeat(auto-fit,[linename1]300px)[linename3]}"
-    );
 
-    test(
-      ".foo{grid-template:[header-top]\"a a a\"[header-bottom main-top]\"b b b\"1fr[main-bottom]/auto 1fr auto}",
-      indoc! {r#"
-        .foo {
-          grid-template: [header-top] "a a a" [header-bottom]
-                         [main-top] "b b b" 1fr [main-bottom]
-                         / auto 1fr auto;
-        }
-      "#},
-    );
-    test(
-      ".foo{grid-template:[header-top]\"a a a\"[main-top]\"b b b\"1fr/auto 1fr auto}",
-      indoc! {r#"
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template: [header-top] "a a a"
-                         [main-top] "b b b" 1fr
-                         / auto 1fr auto;
+          --custom: color(display-p3 .643308 .192455 .167712);
         }
-      "#},
-    );
-
-    minify_test(".foo { grid-auto-flow: row }", ".foo{grid-auto-flow:row}");
-    minify_test(".foo { grid-auto-flow: column }", ".foo{grid-auto-flow:column}");
-    minify_test(".foo { grid-auto-flow: row dense }", ".foo{grid-auto-flow:dense}");
-    minify_test(".foo { grid-auto-flow: dense row }", ".foo{grid-auto-flow:dense}");
-    minify_test(
-      ".foo { grid-auto-flow: column dense }",
-      ".foo{grid-auto-flow:column dense}",
-    );
-    minify_test(
-      ".foo { grid-auto-flow: dense column }",
-      ".foo{grid-auto-flow:column dense}",
-    );
-
-    minify_test(".foo { grid: none }", ".foo{grid:none}");
-    minify_test(".foo { grid: \"a\" 100px \"b\" 1fr }", ".foo{grid:\"a\"100px\"b\"1fr}");
-    minify_test(
-      ".foo { grid: [linename1] \"a\" 100px [linename2] }",
-      ".foo{grid:[linename1]\"a\"100px[linename2]}",
-    );
-    minify_test(
-      ".foo { grid: \"a\" 200px \"b\" min-content }",
-      ".foo{grid:\"a\"200px\"b\"min-content}",
-    );
-    minify_test(
-      ".foo { grid: \"a\" minmax(100px, max-content) \"b\" 20% }",
-      ".foo{grid:\"a\"minmax(100px,max-content)\"b\"20%}",
-    );
-    minify_test(".foo { grid: 100px / 200px }", ".foo{grid:100px/200px}");
-    minify_test(
-      ".foo { grid: minmax(400px, min-content) / repeat(auto-fill, 50px) }",
-      ".foo{grid:minmax(400px,min-content)/repeat(auto-fill,50px)}",
-    );
-
-    minify_test(".foo { grid: 200px / auto-flow }", ".foo{grid:200px/auto-flow}");
-    minify_test(".foo { grid: 30% / auto-flow dense }", ".foo{grid:30%/auto-flow dense}");
-    minify_test(".foo { grid: 30% / dense auto-flow }", ".foo{grid:30%/auto-flow dense}");
-    minify_test(
-      ".foo { grid: repeat(3, [line1 line2 line3] 200px) / auto-flow 300px }",
-      ".foo{grid:repeat(3,[line1 line2 line3]200px)/auto-flow 300px}",
-    );
-    minify_test(
-      ".foo { grid: [line1] minmax(20em, max-content) / auto-flow dense 40% }",
-      ".foo{grid:[line1]minmax(20em,max-content)/auto-flow dense 40%}",
-    );
-    minify_test(".foo { grid: none / auto-flow 1fr }", ".foo{grid:none/auto-flow 1fr}");
-
-    minify_test(".foo { grid: auto-flow / 200px }", ".foo{grid:none/200px}");
-    minify_test(".foo { grid: auto-flow dense / 30% }", ".foo{grid:auto-flow dense/30%}");
-    minify_test(".foo { grid: dense auto-flow / 30% }", ".foo{grid:auto-flow dense/30%}");
-    minify_test(
-      ".foo { grid: auto-flow 300px / repeat(3, [line1 line2 line3] 200px) }",
-      ".foo{grid:auto-flow 300px/repeat(3,[line1 line2 line3]200px)}",
-    );
-    minify_test(
-      ".foo { grid: auto-flow dense 40% / [line1] minmax(20em, max-content) }",
-      ".foo{grid:auto-flow dense 40%/[line1]minmax(20em,max-content)}",
-    );
+      }
 
-    minify_test(".foo { grid-row-start: auto }", ".foo{grid-row-start:auto}");
-    minify_test(".foo { grid-row-start: some-area }", ".foo{grid-row-start:some-area}");
-    minify_test(".foo { grid-row-start: 2 }", ".foo{grid-row-start:2}");
-    minify_test(
-      ".foo { grid-row-start: 2 some-line }",
-      ".foo{grid-row-start:2 some-line}",
-    );
-    minify_test(
-      ".foo { grid-row-start: some-line 2 }",
-      ".foo{grid-row-start:2 some-line}",
-    );
-    minify_test(".foo { grid-row-start: span 3 }", ".foo{grid-row-start:span 3}");
-    minify_test(
-      ".foo { grid-row-start: span some-line }",
-      ".foo{grid-row-start:span some-line}",
-    );
-    minify_test(
-      ".foo { grid-row-start: span some-line 1 }",
-      ".foo{grid-row-start:span some-line}",
-    );
-    minify_test(
-      ".foo { grid-row-start: span 1 some-line }",
-      ".foo{grid-row-start:span some-line}",
-    );
-    minify_test(
-      ".foo { grid-row-start: span 5 some-line }",
-      ".foo{grid-row-start:span 5 some-line}",
-    );
-    minify_test(
-      ".foo { grid-row-start: span some-line 5 }",
-      ".foo{grid-row-start:span 5 some-line}",
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --custom: lab(40% 56.6 39);
+        }
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    minify_test(
-      ".foo { grid-row-end: span 1 some-line }",
-      ".foo{grid-row-end:span some-line}",
-    );
-    minify_test(
-      ".foo { grid-column-start: span 1 some-line }",
-      ".foo{grid-column-start:span some-line}",
-    );
-    minify_test(
-      ".foo { grid-column-end: span 1 some-line }",
-      ".foo{grid-column-end:span some-line}",
-    );
+    prefix_test(
+      r#"
+      .foo {
+        --custom: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        --custom: color(display-p3 .643308 .192455 .167712);
+      }
 
-    minify_test(".foo { grid-row: 1 }", ".foo{grid-row:1}");
-    minify_test(".foo { grid-row: 1 / auto }", ".foo{grid-row:1}");
-    minify_test(".foo { grid-row: 1 / 1 }", ".foo{grid-row:1/1}");
-    minify_test(".foo { grid-row: 1 / 3 }", ".foo{grid-row:1/3}");
-    minify_test(".foo { grid-row: 1 / span 2 }", ".foo{grid-row:1/span 2}");
-    minify_test(".foo { grid-row: main-start }", ".foo{grid-row:main-start}");
-    minify_test(
-      ".foo { grid-row: main-start / main-end }",
-      ".foo{grid-row:main-start/main-end}",
-    );
-    minify_test(
-      ".foo { grid-row: main-start / main-start }",
-      ".foo{grid-row:main-start}",
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --custom: lab(40% 56.6 39);
+        }
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(".foo { grid-column: 1 / auto }", ".foo{grid-column:1}");
 
-    minify_test(".foo { grid-area: a }", ".foo{grid-area:a}");
-    minify_test(".foo { grid-area: a / a / a / a }", ".foo{grid-area:a}");
-    minify_test(".foo { grid-area: a / b / a / b }", ".foo{grid-area:a/b}");
-    minify_test(".foo { grid-area: a / b / c / b }", ".foo{grid-area:a/b/c}");
-    minify_test(".foo { grid-area: a / b / c / d }", ".foo{grid-area:a/b/c/d}");
+    prefix_test(
+      r#"
+      .foo {
+        --custom: lab(40% 56.6 39);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        --custom: lab(40% 56.6 39);
+      }
+    "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    minify_test(".foo { grid-area: auto / auto / auto / auto }", ".foo{grid-area:auto}");
-    minify_test(".foo { grid-area: 1 / auto }", ".foo{grid-area:1}");
-    minify_test(".foo { grid-area: 1 / 2 / 3 / 4 }", ".foo{grid-area:1/2/3/4}");
-    minify_test(".foo { grid-area: 1 / 1 / 1 / 1 }", ".foo{grid-area:1/1/1/1}");
+    prefix_test(
+      r#"
+      .foo {
+        --custom: oklab(59.686% 0.1009 0.1192);
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        --custom: lab(52.2319% 40.1449 59.9171);
+      }
+    "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-rows: auto 1fr;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-        }
-      "#,
+      .foo {
+        --custom: oklab(59.686% 0.1009 0.1192);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --custom: color(display-p3 .724144 .386777 .148795);
+      }
+
+      @supports (color: lab(0% 0 0)) {
         .foo {
-          grid-template: auto 1fr / auto 1fr auto;
+          --custom: lab(52.2319% 40.1449 59.9171);
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: "a a a"
-                               "b b b";
-          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
-          grid-template-columns: auto 1fr auto;
-        }
-      "#,
+      .foo {
+        --custom: oklab(59.686% 0.1009 0.1192);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --custom: #c65d07;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template: [header-top] "a a a" [header-bottom]
-                         [main-top] "b b b" 1fr [main-bottom]
-                         / auto 1fr auto;
+          --custom: color(display-p3 .724144 .386777 .148795);
         }
-      "#},
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --custom: lab(52.2319% 40.1449 59.9171);
+        }
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: "a a a"
-                               "b b b";
-          grid-template-columns: repeat(3, 1fr);
-          grid-template-rows: auto 1fr;
-        }
-      "#,
+      .foo {
+        --foo: oklab(59.686% 0.1009 0.1192);
+        --bar: lab(40% 56.6 39);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --foo: #c65d07;
+        --bar: #b32323;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template-rows: auto 1fr;
-          grid-template-columns: repeat(3, 1fr);
-          grid-template-areas: "a a a"
-                               "b b b";
+          --foo: color(display-p3 .724144 .386777 .148795);
+          --bar: color(display-p3 .643308 .192455 .167712);
         }
-      "#},
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        .foo {
+          --foo: lab(52.2319% 40.1449 59.9171);
+          --bar: lab(40% 56.6 39);
+        }
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: "a a a"
-                               "b b b";
-          grid-template-columns: auto 1fr auto;
-          grid-template-rows: repeat(2, 1fr);
-        }
-      "#,
+      .foo {
+        --foo: color(display-p3 0 1 0);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --foo: #00f942;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template-rows: repeat(2, 1fr);
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: "a a a"
-                               "b b b";
+          --foo: color(display-p3 0 1 0);
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: ". a a ."
-                               ". b b .";
-          grid-template-rows: auto 1fr;
-          grid-template-columns: 10px 1fr 1fr 10px;
-        }
-      "#,
+      .foo {
+        --foo: color(display-p3 0 1 0);
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          grid-template: ". a a ."
-                         ". b b ." 1fr
-                         / 10px 1fr 1fr 10px;
-        }
-      "#},
+      .foo {
+        --foo: color(display-p3 0 1 0);
+      }
+    "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: none;
-          grid-template-columns: auto 1fr auto;
-          grid-template-rows: repeat(2, 1fr);
-        }
-      "#,
+      .foo {
+        --foo: color(display-p3 0 1 0);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --foo: #00f942;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template: repeat(2, 1fr) / auto 1fr auto;
+          --foo: color(display-p3 0 1 0);
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        safari: Some(15 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: none;
-          grid-template-columns: none;
-          grid-template-rows: none;
-        }
-      "#,
+      .foo {
+        --foo: color(display-p3 0 1 0);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --foo: #00f942;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          grid-template: none;
+          --foo: color(display-p3 0 1 0);
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: "a a a"
-                               "b b b";
-          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
-          grid-template-columns: auto 1fr auto;
-          grid-auto-flow: row;
-          grid-auto-rows: auto;
-          grid-auto-columns: auto;
-        }
-      "#,
+      .foo {
+        text-decoration: underline;
+      }
+
+      .foo {
+        --custom: lab(40% 56.6 39);
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --custom: #b32323;
+        text-decoration: underline;
+      }
+
+      @supports (color: lab(0% 0 0)) {
         .foo {
-          grid: [header-top] "a a a" [header-bottom]
-                [main-top] "b b b" 1fr [main-bottom]
-                / auto 1fr auto;
+          --custom: lab(40% 56.6 39);
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: none;
-          grid-template-columns: auto 1fr auto;
-          grid-template-rows: repeat(2, 1fr);
-          grid-auto-flow: row;
-          grid-auto-rows: auto;
-          grid-auto-columns: auto;
-        }
-      "#,
+      .foo {
+        --custom: lab(40% 56.6 39);
+      }
+
+      .foo {
+        text-decoration: underline;
+      }
+    "#,
       indoc! {r#"
+      .foo {
+        --custom: #b32323;
+      }
+
+      @supports (color: lab(0% 0 0)) {
         .foo {
-          grid: repeat(2, 1fr) / auto 1fr auto;
+          --custom: lab(40% 56.6 39);
         }
-      "#},
+      }
+
+      .foo {
+        text-decoration: underline;
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-areas: none;
-          grid-template-columns: none;
-          grid-template-rows: none;
-          grid-auto-flow: row;
-          grid-auto-rows: auto;
-          grid-auto-columns: auto;
+      @keyframes foo {
+        from {
+          --custom: lab(40% 56.6 39);
         }
-      "#,
+
+        to {
+          --custom: lch(50.998% 135.363 338);
+        }
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          grid: none;
+      @keyframes foo {
+        from {
+          --custom: #b32323;
         }
-      "#},
-    );
 
-    test(
-      r#"
-        .foo{
-          grid-template-areas: "a a a"
-                               "b b b";
-          grid-template-rows: [header-top] auto [header-bottom main-top] 1fr [main-bottom];
-          grid-template-columns: auto 1fr auto;
-          grid-auto-flow: column;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
+        to {
+          --custom: #ee00be;
         }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-template: [header-top] "a a a" [header-bottom]
-                         [main-top] "b b b" 1fr [main-bottom]
-                         / auto 1fr auto;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
-          grid-auto-flow: column;
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        @keyframes foo {
+          from {
+            --custom: lab(40% 56.6 39);
+          }
+
+          to {
+            --custom: lab(50.998% 125.506 -50.7078);
+          }
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-rows: auto 1fr;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-          grid-auto-flow: row;
-          grid-auto-rows: auto;
-          grid-auto-columns: auto;
+      @keyframes foo {
+        from {
+          --custom: lab(40% 56.6 39);
         }
-      "#,
+
+        to {
+          --custom: lch(50.998% 135.363 338);
+        }
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          grid: auto 1fr / auto 1fr auto;
+      @keyframes foo {
+        from {
+          --custom: #b32323;
         }
-      "#},
-    );
 
-    test(
-      r#"
-        .foo{
-          grid-template-rows: auto 1fr;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-          grid-auto-flow: column;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
+        to {
+          --custom: #ee00be;
         }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-template: auto 1fr / auto 1fr auto;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
-          grid-auto-flow: column;
+      }
+
+      @supports (color: color(display-p3 0 0 0)) {
+        @keyframes foo {
+          from {
+            --custom: color(display-p3 .643308 .192455 .167712);
+          }
+
+          to {
+            --custom: color(display-p3 .972962 -.362078 .804206);
+          }
+        }
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        @keyframes foo {
+          from {
+            --custom: lab(40% 56.6 39);
+          }
+
+          to {
+            --custom: lab(50.998% 125.506 -50.7078);
+          }
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-rows: none;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-          grid-auto-flow: column;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-template: none / auto 1fr auto;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: 1fr;
-          grid-auto-flow: column;
+      @keyframes foo {
+        from {
+          --custom: #ff0;
+          opacity: 0;
         }
-      "#},
-    );
 
-    test(
-      r#"
-        .foo{
-          grid-template-rows: none;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-          grid-auto-flow: row;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: auto;
+        to {
+          --custom: lch(50.998% 135.363 338);
+          opacity: 1;
         }
-      "#,
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          grid: auto-flow 1fr / auto 1fr auto;
+      @keyframes foo {
+        from {
+          --custom: #ff0;
+          opacity: 0;
         }
-      "#},
-    );
 
-    test(
-      r#"
-        .foo{
-          grid-template-rows: none;
-          grid-template-columns: auto 1fr auto;
-          grid-template-areas: none;
-          grid-auto-flow: row dense;
-          grid-auto-rows: 1fr;
-          grid-auto-columns: auto;
+        to {
+          --custom: #ee00be;
+          opacity: 1;
         }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid: auto-flow dense 1fr / auto 1fr auto;
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        @keyframes foo {
+          from {
+            --custom: #ff0;
+            opacity: 0;
+          }
+
+          to {
+            --custom: lab(50.998% 125.506 -50.7078);
+            opacity: 1;
+          }
         }
-      "#},
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    test(
+    prefix_test(
       r#"
-        .foo{
-          grid-template-rows: auto 1fr auto;
-          grid-template-columns: none;
-          grid-template-areas: none;
-          grid-auto-flow: column;
-          grid-auto-rows: auto;
-          grid-auto-columns: 1fr;
+      @keyframes foo {
+        from {
+          text-decoration: var(--foo) lab(29.2345% 39.3825 20.0664);
         }
-      "#,
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          grid: auto 1fr auto / auto-flow 1fr;
+      @keyframes foo {
+        from {
+          text-decoration: var(--foo) #7d2329;
         }
-      "#},
+      }
+
+      @supports (color: lab(0% 0 0)) {
+        @keyframes foo {
+          from {
+            text-decoration: var(--foo) lab(29.2345% 39.3825 20.0664);
+          }
+        }
+      }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
+  }
 
+  #[test]
+  fn test_charset() {
     test(
       r#"
-        .foo{
-          grid-template-rows: auto 1fr auto;
-          grid-template-columns: none;
-          grid-template-areas: none;
-          grid-auto-flow: column dense;
-          grid-auto-rows: auto;
-          grid-auto-columns: 1fr;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid: auto 1fr auto / auto-flow dense 1fr;
+      @charset "UTF-8";
+
+      .foo {
+        color: red;
+      }
+
+      @charset "UTF-8";
+
+      .bar {
+        color: yellow;
+      }
+    "#,
+      indoc! { r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        color: #ff0;
+      }
+    "#},
+    )
+  }
+
+  #[test]
+  fn test_style_attr() {
+    attr_test("color: yellow; flex: 1 1 auto", "color: #ff0; flex: auto", false, None);
+    attr_test("color: yellow; flex: 1 1 auto", "color:#ff0;flex:auto", true, None);
+    attr_test(
+      "border-inline-start: 2px solid red",
+      "border-inline-start: 2px solid red",
+      false,
+      Some(Browsers {
+        safari: Some(12 << 16),
+        ..Browsers::default()
+      }),
+    );
+    attr_test(
+      "color: lab(40% 56.6 39);",
+      "color:#b32323;color:lab(40% 56.6 39)",
+      true,
+      Some(Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      }),
+    );
+    attr_test(
+      "--foo: lab(40% 56.6 39);",
+      "--foo:#b32323",
+      true,
+      Some(Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      }),
+    );
+    attr_test(
+      "text-decoration: var(--foo) lab(40% 56.6 39);",
+      "text-decoration:var(--foo)#b32323",
+      true,
+      Some(Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      }),
+    );
+  }
+
+  #[test]
+  fn test_color_conversion() {
+    use crate::values::color::{ColorGamut, OKLCH, P3, SRGB};
+
+    let color = |source: &str| CssColor::parse_string(source).unwrap();
+    let approx = |a: f32, b: f32| assert!((a - b).abs() < 0.001, "{} != {}", a, b);
+
+    let oklch = OKLCH::try_from(&color("red").convert::<OKLCH>().unwrap()).unwrap();
+    approx(oklch.l, 0.62796);
+    approx(oklch.c, 0.25768);
+    approx(oklch.h, 29.2339);
+    assert_eq!(
+      color("color(display-p3 1 1 1)")
+        .convert::<SRGB>()
+        .unwrap()
+        .to_css_string(PrinterOptions::default())
+        .unwrap(),
+      "#fff"
+    );
+    assert!(color("#fff").convert::<P3>().is_ok());
+    assert!(CssColor::current_color().convert::<SRGB>().is_err());
+
+    let wide = color("oklch(60% 0.4 30)");
+    assert!(!P3::try_from(&wide.convert::<P3>().unwrap()).unwrap().in_gamut());
+    let mapped = P3::try_from(&wide.to_gamut::<P3>().unwrap()).unwrap();
+    assert!(mapped.in_gamut());
+    let mapped = OKLCH::from(mapped);
+    assert!((mapped.l - 0.6).abs() < 0.01);
+    assert!(mapped.c < 0.4);
+
+    approx(color("white").luminance().unwrap(), 1.0);
+    approx(color("black").luminance().unwrap(), 0.0);
+    approx(color("white").contrast(&color("black")).unwrap(), 21.0);
+    approx(color("black").contrast(&color("white")).unwrap(), 21.0);
+    approx(color("#777").contrast(&color("#fff")).unwrap(), 4.478);
+    assert!(color("red").contrast(&CssColor::current_color()).is_err());
+  }
+
+  #[test]
+  fn test_unit_conversion() {
+    use crate::error::UnitConversionError;
+    use crate::values::length::{Length, LengthPercentage, UnitContext};
+    use crate::values::resolution::Resolution;
+
+    let context = UnitContext {
+      root_font_size: Some(16.0),
+      font_size: Some(20.0),
+      viewport_width: Some(1000.0),
+      viewport_height: Some(500.0),
+      percentage_basis: Some(200.0),
+      ..UnitContext::default()
+    };
+    let px =
+      |source: &str, context: &UnitContext| Length::parse_string(source).unwrap().to_px_with_context(context);
+
+    assert_eq!(px("1in", &UnitContext::default()), Ok(96.0));
+    assert_eq!(px("2rem", &context), Ok(32.0));
+    assert_eq!(px("2em", &context), Ok(40.0));
+    assert_eq!(px("1ch", &context), Ok(10.0));
+    assert_eq!(px("10vw", &context), Ok(100.0));
+    assert_eq!(px("10vmin", &context), Ok(50.0));
+    assert_eq!(px("10dvmax", &context), Ok(100.0));
+    assert_eq!(px("calc(1rem + 10vh)", &context), Ok(66.0));
+    assert_eq!(px("min(1em, 10px)", &context), Ok(10.0));
+    assert_eq!(px("clamp(1px, 10vw, 2rem)", &context), Ok(32.0));
+    assert_eq!(
+      px(
+        "2em",
+        &UnitContext {
+          root_font_size: Some(10.0),
+          ..UnitContext::default()
         }
-      "#},
+      ),
+      Ok(20.0)
+    );
+    assert_eq!(
+      px("10vw", &UnitContext::default()),
+      Err(UnitConversionError::MissingContext { unit: "vw".into() })
+    );
+    assert_eq!(
+      px("10cqw", &context),
+      Err(UnitConversionError::MissingContext { unit: "cqw".into() })
+    );
+    assert_eq!(
+      px("calc(1px + 1lh)", &context),
+      Err(UnitConversionError::MissingContext { unit: "lh".into() })
+    );
+
+    let lp = |source: &str, context: &UnitContext| {
+      LengthPercentage::parse_string(source).unwrap().to_px_with_context(context)
+    };
+    assert_eq!(lp("50%", &context), Ok(100.0));
+    assert_eq!(lp("calc(50% - 1rem)", &context), Ok(84.0));
+    assert_eq!(
+      lp("50%", &UnitContext::default()),
+      Err(UnitConversionError::MissingPercentageBasis)
+    );
+
+    let resolution = Resolution::Dppx(2.0);
+    assert_eq!(resolution.to_dpi(), 192.0);
+    assert_eq!(Resolution::Dpi(96.0).to_dppx(), 1.0);
+    assert_eq!(
+      resolution.to_device_px(&Length::parse_string("1rem").unwrap(), &context),
+      Ok(32.0)
+    );
+  }
+
+  #[test]
+  fn test_calc_evaluation() {
+    use crate::error::UnitConversionError;
+    use crate::values::calc::{CalcDependencies, CalcEvaluation};
+    use crate::values::length::{Length, LengthPercentage, UnitContext};
+
+    let context = UnitContext {
+      root_font_size: Some(16.0),
+      font_size: Some(20.0),
+      viewport_width: Some(1000.0),
+      viewport_height: Some(500.0),
+      container_width: Some(400.0),
+      container_height: Some(300.0),
+      percentage_basis: Some(200.0),
+      ..UnitContext::default()
+    };
+    let evaluate = |source: &str, context: &UnitContext| Length::parse_string(source).unwrap().evaluate(context);
+    let result = |value, dependencies| -> Result<CalcEvaluation, UnitConversionError> {
+      Ok(CalcEvaluation { value, dependencies })
+    };
+
+    assert_eq!(
+      evaluate("calc(1in + 4px)", &context),
+      result(100.0, CalcDependencies::empty())
+    );
+    assert_eq!(
+      evaluate("calc(1rem + 10vw)", &context),
+      result(116.0, CalcDependencies::RootFontSize | CalcDependencies::ViewportWidth)
+    );
+    assert_eq!(
+      evaluate("max(2em, 10vmin)", &context),
+      result(
+        50.0,
+        CalcDependencies::FontSize | CalcDependencies::ViewportWidth | CalcDependencies::ViewportHeight
+      )
+    );
+    assert_eq!(
+      evaluate("10cqi", &context),
+      result(40.0, CalcDependencies::ContainerWidth)
+    );
+    assert_eq!(
+      evaluate("calc(10cqmin + 1px)", &context),
+      result(
+        31.0,
+        CalcDependencies::ContainerWidth | CalcDependencies::ContainerHeight
+      )
+    );
+    assert_eq!(
+      evaluate("round(up, 10vh + 3px, 10px)", &context),
+      result(60.0, CalcDependencies::ViewportHeight)
+    );
+    assert_eq!(
+      evaluate("mod(-1rem, 10px)", &context),
+      result(4.0, CalcDependencies::RootFontSize)
     );
-
-    test(
-      r#"
-        .foo{
-          grid-template-rows: auto 1fr auto;
-          grid-template-columns: none;
-          grid-template-areas: none;
-          grid-auto-flow: var(--auto-flow);
-          grid-auto-rows: auto;
-          grid-auto-columns: 1fr;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-template: auto 1fr auto / none;
-          grid-auto-flow: var(--auto-flow);
-          grid-auto-rows: auto;
-          grid-auto-columns: 1fr;
-        }
-      "#},
+    assert_eq!(
+      evaluate("rem(1rem, 10px)", &context),
+      result(6.0, CalcDependencies::RootFontSize)
     );
-
-    test(
-      r#"
-        .foo{
-          grid: auto 1fr auto / auto-flow dense 1fr;
-          grid-template-rows: 1fr 1fr 1fr;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid: 1fr 1fr 1fr / auto-flow dense 1fr;
-        }
-      "#},
+    assert_eq!(
+      evaluate("hypot(3cqw, 4cqw)", &context),
+      result(20.0, CalcDependencies::ContainerWidth)
     );
-
-    test(
-      r#"
-        .foo{
-          grid-row-start: a;
-          grid-row-end: a;
-          grid-column-start: a;
-          grid-column-end: a;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-area: a;
+    assert_eq!(
+      evaluate(
+        "2em",
+        &UnitContext {
+          root_font_size: Some(10.0),
+          ..UnitContext::default()
         }
-      "#},
+      ),
+      result(20.0, CalcDependencies::RootFontSize)
+    );
+    assert_eq!(
+      evaluate("calc(10cqw + 1px)", &UnitContext::default()),
+      Err(UnitConversionError::MissingContext { unit: "cqw".into() })
     );
 
-    test(
-      r#"
-        .foo{
-          grid-row-start: 1;
-          grid-row-end: 2;
-          grid-column-start: 3;
-          grid-column-end: 4;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-area: 1 / 3 / 2 / 4;
-        }
-      "#},
+    let lp = |source: &str| LengthPercentage::parse_string(source).unwrap().evaluate(&context);
+    assert_eq!(
+      lp("calc(50% - 1em)"),
+      result(80.0, CalcDependencies::PercentageBasis | CalcDependencies::FontSize)
+    );
+    assert_eq!(
+      lp("clamp(10px, 5%, 20px)"),
+      result(10.0, CalcDependencies::PercentageBasis)
     );
+  }
 
-    test(
-      r#"
-        .foo{
-          grid-row-start: a;
-          grid-row-end: a;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-row: a;
-        }
-      "#},
+  #[test]
+  fn test_transform_matrix() {
+    use crate::error::UnitConversionError;
+    use crate::properties::transform::{Matrix3d, Transform, TransformContext, TransformList};
+    use crate::values::length::LengthPercentage;
+    use crate::values::percentage::NumberOrPercentage;
+
+    let assert_matrix_eq = |a: &Matrix3d<f32>, b: &Matrix3d<f32>| {
+      let a = [
+        a.m11, a.m12, a.m13, a.m14, a.m21, a.m22, a.m23, a.m24, a.m31, a.m32, a.m33, a.m34, a.m41, a.m42, a.m43,
+        a.m44,
+      ];
+      let b = [
+        b.m11, b.m12, b.m13, b.m14, b.m21, b.m22, b.m23, b.m24, b.m31, b.m32, b.m33, b.m34, b.m41, b.m42, b.m43,
+        b.m44,
+      ];
+      for (a, b) in a.iter().zip(b.iter()) {
+        assert!((a - b).abs() < 1e-4, "{:?} != {:?}", a, b);
+      }
+    };
+
+    let list = TransformList::parse_string("translate(50%, 10px) rotate(-90deg)").unwrap();
+    assert_eq!(list.to_matrix(), None);
+    assert_eq!(
+      list.to_matrix_with_context(&TransformContext::default()),
+      Err(UnitConversionError::MissingPercentageBasis)
     );
 
-    test(
-      r#"
-        .foo{
-          grid-column-start: a;
-          grid-column-end: a;
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          grid-column: a;
-        }
-      "#},
+    let context = TransformContext {
+      reference_width: Some(200.0),
+      reference_height: Some(100.0),
+      ..TransformContext::default()
+    };
+    let matrix = list.to_matrix_with_context(&context).unwrap();
+    let mut expected = Matrix3d::identity();
+    expected.m11 = 0.0;
+    expected.m12 = -1.0;
+    expected.m21 = 1.0;
+    expected.m22 = 0.0;
+    expected.m41 = 100.0;
+    expected.m42 = 10.0;
+    assert_matrix_eq(&matrix, &expected);
+
+    let decomposed = matrix.decompose_components().unwrap();
+    assert_eq!(decomposed.translate, [100.0, 10.0, 0.0]);
+    assert_matrix_eq(&decomposed.to_matrix(), &matrix);
+    let list = decomposed.to_transform_list();
+    assert_eq!(list.0.len(), 2);
+    assert!(matches!(&list.0[1], Transform::Rotate(angle) if (angle.to_degrees() + 90.0).abs() < 1e-3));
+    assert_matrix_eq(&list.to_matrix_with_context(&context).unwrap(), &matrix);
+
+    let list = TransformList::parse_string("translate(10px, 20px) scale(2)").unwrap();
+    let decomposed = list.to_matrix_with_context(&context).unwrap().decompose_components().unwrap();
+    assert_eq!(
+      decomposed.to_transform_list().0,
+      vec![
+        Transform::Translate(LengthPercentage::px(10.0), LengthPercentage::px(20.0)),
+        Transform::Scale(NumberOrPercentage::Number(2.0), NumberOrPercentage::Number(2.0))
+      ]
     );
+
+    for source in [
+      "translate(10px, 20px) rotate(30deg) skewX(20deg) scale(2, 3)",
+      "perspective(100px) translate3d(1px, 2px, 3px) rotate3d(1, 2, 3, 70deg) scale3d(2, -1, 0.5)",
+      "rotate(400deg) skew(10deg, 20deg)",
+      "matrix3d(1, 0, 0, 0, 0.1, 1, 0, 0, 0.3, -0.2, 1, 0, 5, 6, 7, 1)",
+    ] {
+      let matrix = TransformList::parse_string(source)
+        .unwrap()
+        .to_matrix_with_context(&context)
+        .unwrap();
+      let decomposed = matrix.decompose_components().unwrap();
+      let mut normalized = matrix.clone();
+      normalized.scale_by_factor(1.0 / matrix.m44);
+      assert_matrix_eq(&decomposed.to_matrix(), &normalized);
+      let recomposed = decomposed.to_transform_list().to_matrix_with_context(&context).unwrap();
+      assert_matrix_eq(&recomposed, &normalized);
+    }
+
+    assert_eq!(Matrix3d::scale(0.0, 1.0, 1.0).decompose_components(), None);
   }
 
   #[test]
-  fn test_moz_document() {
+  fn test_offset_path() {
+    minify_test(".foo { offset-path: none }", ".foo{offset-path:none}");
     minify_test(
-      r#"
-      @-moz-document url-prefix() {
-        h1 {
-          color: yellow;
-        }
-      }
-    "#,
-      "@-moz-document url-prefix(){h1{color:#ff0}}",
+      ".foo { offset-path: ray(45deg closest-side contain at 10px 20px) }",
+      ".foo{offset-path:ray(45deg contain at 10px 20px)}",
     );
     minify_test(
-      r#"
-      @-moz-document url-prefix("") {
-        h1 {
-          color: yellow;
-        }
-      }
-    "#,
-      "@-moz-document url-prefix(){h1{color:#ff0}}",
+      ".foo { offset-path: ray(sides 45deg) }",
+      ".foo{offset-path:ray(45deg sides)}",
     );
-    error_test(
-      "@-moz-document url-prefix(foo) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("foo".into())),
+    minify_test(
+      ".foo { offset-path: path('M 0 0 L 100 100') }",
+      ".foo{offset-path:path(\"M0 0 100 100\")}",
     );
-    error_test(
-      "@-moz-document url-prefix(\"foo\") {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::String("foo".into())),
+    minify_test(
+      ".foo { offset-path: url(#path) border-box }",
+      ".foo{offset-path:url(#path)}",
+    );
+    minify_test(".foo { offset-path: padding-box }", ".foo{offset-path:padding-box}");
+    minify_test(
+      ".foo { offset-path: view-box circle(50px) }",
+      ".foo{offset-path:circle(50px) view-box}",
     );
+    minify_test(".foo { offset-path: ray(contain) }", ".foo{offset-path:ray(contain)}");
   }
 
   #[test]
-  fn test_custom_properties() {
-    minify_test(".foo { --test: ; }", ".foo{--test: }");
-    minify_test(".foo { --test:  ; }", ".foo{--test: }");
-    minify_test(".foo { --test: foo; }", ".foo{--test:foo}");
-    minify_test(".foo { --test:  foo; }", ".foo{--test:foo}");
-    minify_test(".foo { --test: foo ; }", ".foo{--test:foo}");
-    minify_test(".foo { --test: foo  ; }", ".foo{--test:foo}");
-    minify_test(".foo { --test:foo; }", ".foo{--test:foo}");
-    minify_test(".foo { --test:foo ; }", ".foo{--test:foo}");
-    minify_test(".foo { --test: var(--foo, 20px); }", ".foo{--test:var(--foo,20px)}");
+  fn test_shape_outside() {
+    minify_test(".foo { shape-outside: none }", ".foo{shape-outside:none}");
     minify_test(
-      ".foo { transition: var(--foo, 20px),\nvar(--bar, 40px); }",
-      ".foo{transition:var(--foo,20px),var(--bar,40px)}",
+      ".foo { shape-outside: circle(50%) margin-box }",
+      ".foo{shape-outside:circle(50%)}",
     );
     minify_test(
-      ".foo { background: var(--color) var(--image); }",
-      ".foo{background:var(--color)var(--image)}",
+      ".foo { shape-outside: border-box inset(10px round 5px) }",
+      ".foo{shape-outside:inset(10px round 5px) border-box}",
     );
+    minify_test(".foo { shape-outside: content-box }", ".foo{shape-outside:content-box}");
     minify_test(
-      ".foo { height: calc(var(--spectrum-global-dimension-size-300) / 2);",
-      ".foo{height:calc(var(--spectrum-global-dimension-size-300)/2)}",
+      ".foo { shape-outside: url('image.png') }",
+      ".foo{shape-outside:url(image.png)}",
     );
     minify_test(
-      ".foo { color: var(--color, rgb(255, 255, 0)); }",
-      ".foo{color:var(--color,#ff0)}",
+      ".foo { shape-outside: polygon(0 0, 100% 0, 100% 100%) }",
+      ".foo{shape-outside:polygon(0 0,100% 0,100% 100%)}",
     );
+  }
+
+  #[test]
+  fn test_easing_evaluation() {
+    use crate::values::easing::{EasingFunction, StepPosition};
+
     minify_test(
-      ".foo { color: var(--color, #ffff00); }",
-      ".foo{color:var(--color,#ff0)}",
+      ".foo { transition-timing-function: linear(0, 0.25 75%, 1) }",
+      ".foo{transition-timing-function:linear(0,.25 75%,1)}",
     );
     minify_test(
-      ".foo { color: var(--color, rgb(var(--red), var(--green), 0)); }",
-      ".foo{color:var(--color,rgb(var(--red),var(--green),0))}",
+      ".foo { transition-timing-function: linear(0 0%, 1 100%) }",
+      ".foo{transition-timing-function:linear}",
     );
-    minify_test(".foo { --test: .5s; }", ".foo{--test:.5s}");
-    minify_test(".foo { --theme-sizes-1\\/12: 2 }", ".foo{--theme-sizes-1\\/12:2}");
-    minify_test(".foo { --test: 0px; }", ".foo{--test:0px}");
+    minify_test(
+      ".foo { transition-timing-function: linear(0, 0.5 25% 75%, 1) }",
+      ".foo{transition-timing-function:linear(0,.5 25%,.5 75%,1)}",
+    );
+    minify_test(
+      ".foo { animation-timing-function: linear(50% 0.5, 1) }",
+      ".foo{animation-timing-function:linear(.5 50%,1)}",
+    );
+    assert!(EasingFunction::parse_string("linear(0)").is_err());
+    assert!(EasingFunction::parse_string("linear()").is_err());
 
-    prefix_test(
-      r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        --custom: #b32323;
-      }
+    let close = |a: f32, b: f32| (a - b).abs() < 1e-3;
+    let easing = EasingFunction::parse_string("ease").unwrap();
+    assert_eq!(easing.evaluate(0.0), 0.0);
+    assert_eq!(easing.evaluate(1.0), 1.0);
+    assert!(close(easing.evaluate(0.5), 0.8024));
+    assert!(close(EasingFunction::EaseInOut.evaluate(0.5), 0.5));
+    assert!(close(
+      EasingFunction::parse_string("cubic-bezier(0.25, 0.1, 0.25, 1)")
+        .unwrap()
+        .evaluate(0.5),
+      0.8024
+    ));
+    assert!(close(
+      EasingFunction::parse_string("cubic-bezier(0.5, 2, 0.5, -1)")
+        .unwrap()
+        .evaluate(-1.0),
+      -4.0
+    ));
+    assert_eq!(EasingFunction::Linear.evaluate(0.3), 0.3);
+
+    let steps = |count, position| EasingFunction::Steps { count, position };
+    assert_eq!(steps(4, StepPosition::End).evaluate(0.5), 0.5);
+    assert_eq!(steps(4, StepPosition::End).evaluate(0.2), 0.0);
+    assert_eq!(steps(4, StepPosition::Start).evaluate(0.1), 0.25);
+    assert_eq!(steps(3, StepPosition::JumpNone).evaluate(0.5), 0.5);
+    assert_eq!(steps(3, StepPosition::JumpBoth).evaluate(0.0), 0.25);
+    assert_eq!(steps(3, StepPosition::End).evaluate(1.0), 1.0);
+
+    let easing = EasingFunction::parse_string("linear(0, 0.25 75%, 1)").unwrap();
+    assert_eq!(easing.evaluate(0.375), 0.125);
+    assert_eq!(easing.evaluate(0.75), 0.25);
+    assert_eq!(easing.evaluate(0.875), 0.625);
+    assert_eq!(easing.evaluate(1.5), 2.5);
+    let easing = EasingFunction::parse_string("linear(0, 0.5, 1)").unwrap();
+    assert_eq!(easing.evaluate(0.25), 0.25);
+    let easing = EasingFunction::parse_string("linear(0, 0.5 25% 75%, 1)").unwrap();
+    assert_eq!(easing.evaluate(0.5), 0.5);
+  }
 
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          --custom: lab(40% 56.6 39);
-        }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
+  #[test]
+  fn test_grid_template_model() {
+    use crate::error::GridTemplateError;
+    use crate::properties::grid::{GridAreaRect, GridTemplate, GridTemplateAreas, GridTemplateModel, TrackSizing};
+    use crate::values::ident::CustomIdentList;
+
+    let template =
+      GridTemplate::parse_string(r#"[header-start] "header header" 50px [header-end] "nav main" 1fr / 200px 1fr"#)
+        .unwrap();
+    let model = template.model().unwrap();
+    assert_eq!(model.rows.len(), 2);
+    assert_eq!(model.rows[0].start_line_names[0].0.as_ref(), "header-start");
+    assert_eq!(model.rows[0].end_line_names[0].0.as_ref(), "header-end");
+    assert_eq!(
+      model.rows[0].size.to_css_string(PrinterOptions::default()).unwrap(),
+      "50px"
+    );
+    assert_eq!(model.columns.len(), 2);
+    assert_eq!(
+      model.area("header"),
+      Some(&GridAreaRect {
+        name: "header".into(),
+        row_start: 0,
+        row_end: 1,
+        column_start: 0,
+        column_end: 2,
+      })
+    );
+    assert_eq!(model.area("main").map(|a| (a.row_start, a.column_start)), Some((1, 1)));
+    assert_eq!(model.area("footer"), None);
+
+    let columns = TrackSizing::parse_string("[a] 200px repeat(2, [b] 1fr) [c]")
+      .unwrap()
+      .tracks()
+      .unwrap();
+    assert_eq!(columns.len(), 3);
+    fn names<'a>(names: &'a CustomIdentList<'_>) -> Vec<&'a str> {
+      names.iter().map(|n| n.0.as_ref()).collect()
+    }
+    assert_eq!(names(&columns[0].start_line_names), ["a"]);
+    assert_eq!(names(&columns[0].end_line_names), ["b"]);
+    assert_eq!(names(&columns[1].end_line_names), ["b"]);
+    assert_eq!(names(&columns[2].end_line_names), ["c"]);
+
+    let areas = GridTemplateAreas::parse_string(r#""a a ." "a a b" ". . b""#).unwrap();
+    assert_eq!((areas.rows(), areas.columns()), (3, 3));
+    assert_eq!(areas.get(1, 2), Some("b"));
+    assert_eq!(areas.get(0, 2), None);
+    assert_eq!(areas.get(0, 3), None);
+    assert_eq!(areas.area_rect("b").map(|a| (a.row_start, a.row_end)), Some((1, 3)));
+    let model = GridTemplateModel::new(&TrackSizing::None, &TrackSizing::None, &areas).unwrap();
+    assert_eq!((model.rows.len(), model.columns.len()), (3, 3));
+
+    let areas = GridTemplateAreas::parse_string(r#""a a" "a b""#).unwrap();
+    assert_eq!(
+      areas.area_rects(),
+      Err(GridTemplateError::NonRectangularArea { name: "a".into() })
+    );
+    let areas = GridTemplateAreas::parse_string(r#""a b a""#).unwrap();
+    assert!(areas.area_rects().is_err());
+    assert_eq!(
+      TrackSizing::parse_string("repeat(auto-fill, 100px)").unwrap().tracks(),
+      Err(GridTemplateError::AutoRepeat)
+    );
+  }
+
+  #[test]
+  fn test_semantic_eq() {
+    use crate::declaration::DeclarationBlock;
+    use crate::properties::Property;
+    use crate::values::length::Length;
+
+    assert!(CssColor::parse_string("#fff")
+      .unwrap()
+      .semantic_eq(&CssColor::parse_string("white").unwrap()));
+    assert!(!CssColor::parse_string("#fff")
+      .unwrap()
+      .semantic_eq(&CssColor::parse_string("#ffe").unwrap()));
+    assert!(Length::parse_string("0px")
+      .unwrap()
+      .semantic_eq(&Length::parse_string("0").unwrap()));
+    assert!(Length::parse_string("calc(1px + 2px)")
+      .unwrap()
+      .semantic_eq(&Length::parse_string("3px").unwrap()));
+
+    let property = |name: &'static str, value: &'static str| {
+      Property::parse_string(name.into(), value, ParserOptions::default()).unwrap()
+    };
+    assert!(property("color", "#ff0000").semantic_eq(&property("color", "red")));
+    assert!(!property("color", "red").semantic_eq(&property("background-color", "red")));
+    assert!(!property("transition", "opacity 1s").semantic_eq(&property("-webkit-transition", "opacity 1s")));
+
+    let block = |source: &'static str| DeclarationBlock::parse_string(source, ParserOptions::default()).unwrap();
+    assert!(block("color: red; width: 10px").semantic_eq(&block("width:10px;color:#f00")));
+    assert!(
+      block("margin-top: 1px; margin-right: 1px; margin-bottom: 1px; margin-left: 1px")
+        .semantic_eq(&block("margin: 1px"))
+    );
+    assert!(block("color: red !important; width: 0px").semantic_eq(&block("width: 0; color: red !important")));
+    assert!(!block("width: 10px").semantic_eq(&block("width: 10px !important")));
+    assert!(!block("width: 10px").semantic_eq(&block("width: 10px; height: 10px")));
+  }
+
+  #[test]
+  fn test_expand_shorthand() {
+    use crate::properties::Property;
+
+    fn expand(name: &str, value: &str) -> Vec<String> {
+      Property::parse_string(name.into(), value, ParserOptions::default())
+        .unwrap()
+        .expand()
+        .iter()
+        .map(|p| p.to_css_string(false, PrinterOptions::default()).unwrap())
+        .collect()
+    }
+
+    fn contract(name: &str, longhands: &[(&str, &str)]) -> Option<String> {
+      let longhands: Vec<_> = longhands
+        .iter()
+        .map(|(name, value)| Property::parse_string((*name).into(), value, ParserOptions::default()).unwrap())
+        .collect();
+      Property::from_longhands(&name.into(), &longhands)
+        .map(|p| p.to_css_string(false, PrinterOptions::default()).unwrap())
+    }
+
+    assert_eq!(
+      expand("margin", "1px 2px"),
+      vec![
+        "margin-top: 1px",
+        "margin-right: 2px",
+        "margin-bottom: 1px",
+        "margin-left: 2px"
+      ]
+    );
+    assert_eq!(
+      expand("border-top", "2px solid red"),
+      vec![
+        "border-top-width: 2px",
+        "border-top-style: solid",
+        "border-top-color: red"
+      ]
+    );
+    assert_eq!(expand("border", "1px solid").len(), 12);
+    assert_eq!(expand("color", "red"), vec!["color: red"]);
+    assert_eq!(expand("margin", "var(--x)"), vec!["margin: var(--x)"]);
+
+    assert_eq!(
+      contract(
+        "margin",
+        &[
+          ("margin-left", "2px"),
+          ("margin-top", "1px"),
+          ("margin-right", "2px"),
+          ("margin-bottom", "1px")
+        ]
+      ),
+      Some("margin: 1px 2px".into())
+    );
+    assert_eq!(
+      contract(
+        "border",
+        &[
+          ("border-width", "1px"),
+          ("border-style", "solid"),
+          ("border-color", "red")
+        ]
+      ),
+      Some("border: 1px solid red".into())
+    );
+    assert_eq!(
+      contract("margin", &[("margin-top", "1px"), ("margin-right", "2px")]),
+      None
+    );
+    assert_eq!(
+      contract(
+        "border",
+        &[
+          ("border-width", "1px 2px"),
+          ("border-style", "solid"),
+          ("border-color", "red")
+        ]
+      ),
+      None
+    );
+  }
+
+  #[test]
+  fn test_canonical_declaration() {
+    use crate::declaration::Declaration;
+
+    fn test(source: &str, expected: &str, minify: bool, targets: Option<Browsers>) {
+      let decl = Declaration::parse_string(source, ParserOptions::default()).unwrap();
+      let res = decl
+        .to_canonical_css(PrinterOptions {
+          targets: targets.into(),
+          minify,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res, expected);
+    }
+
+    test("color: yellow", "color: #ff0", false, None);
+    test("flex: 1 1 auto;", "flex:auto", true, None);
+    test(
+      "margin: 1px 1px 1px 1px !important",
+      "margin: 1px !important",
+      false,
+      None,
+    );
+    test(
+      "color: lab(40% 56.6 39)",
+      "color:#b32323;color:lab(40% 56.6 39)",
+      true,
+      Some(Browsers {
+        safari: Some(8 << 16),
         ..Browsers::default()
-      },
+      }),
+    );
+
+    let decl = Declaration::parse_string("width: calc(10px + 20px)", ParserOptions::default()).unwrap();
+    assert!(matches!(decl.property, Property::Width(..)));
+    assert_eq!(
+      decl.property.value_to_css_string(PrinterOptions::default()).unwrap(),
+      "30px"
     );
+    assert!(!decl.important);
+    assert!(Declaration::parse_string("color: red; width: 10px", ParserOptions::default()).is_err());
+    assert!(Declaration::parse_string("", ParserOptions::default()).is_err());
+  }
 
-    prefix_test(
+  #[test]
+  fn test_nesting() {
+    nesting_test(
       r#"
-      .foo {
-        --custom: lab(40% 56.6 39) !important;
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        --custom: #b32323 !important;
-      }
-
-      @supports (color: lab(0% 0 0)) {
         .foo {
-          --custom: lab(40% 56.6 39) !important;
+          color: blue;
+          & > .bar { color: red; }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
-    );
-
-    prefix_test(
-      r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#,
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: #b32323;
-      }
-
-      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          --custom: color(display-p3 .643308 .192455 .167712);
+          color: #00f;
         }
-      }
 
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          --custom: lab(40% 56.6 39);
+        .foo > .bar {
+          color: red;
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#,
+        .foo {
+          color: blue;
+          &.bar { color: red; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: color(display-p3 .643308 .192455 .167712);
-      }
-
-      @supports (color: lab(0% 0 0)) {
         .foo {
-          --custom: lab(40% 56.6 39);
+          color: #00f;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
 
-    prefix_test(
-      r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
+        .foo.bar {
+          color: red;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --custom: oklab(59.686% 0.1009 0.1192);
-      }
-    "#,
+        .foo, .bar {
+          color: blue;
+          & + .baz, &.qux { color: red; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: lab(52.2319% 40.1449 59.9171);
-      }
-    "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
+        .foo, .bar {
+          color: #00f;
+        }
+
+        :is(.foo, .bar) + .baz, :is(.foo, .bar).qux {
+          color: red;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --custom: oklab(59.686% 0.1009 0.1192);
-      }
-    "#,
+        .foo {
+          color: blue;
+          & .bar & .baz & .qux { color: red; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: color(display-p3 .724144 .386777 .148795);
-      }
-
-      @supports (color: lab(0% 0 0)) {
         .foo {
-          --custom: lab(52.2319% 40.1449 59.9171);
+          color: #00f;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+
+        .foo .bar .foo .baz .foo .qux {
+          color: red;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --custom: oklab(59.686% 0.1009 0.1192);
-      }
-    "#,
+        .foo {
+          color: blue;
+          & { padding: 2ch; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: #c65d07;
-      }
-
-      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          --custom: color(display-p3 .724144 .386777 .148795);
+          color: #00f;
         }
-      }
 
-      @supports (color: lab(0% 0 0)) {
         .foo {
-          --custom: lab(52.2319% 40.1449 59.9171);
+          padding: 2ch;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --foo: oklab(59.686% 0.1009 0.1192);
-        --bar: lab(40% 56.6 39);
-      }
-    "#,
+        .foo {
+          color: blue;
+          && { padding: 2ch; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --foo: #c65d07;
-        --bar: #b32323;
-      }
-
-      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          --foo: color(display-p3 .724144 .386777 .148795);
-          --bar: color(display-p3 .643308 .192455 .167712);
+          color: #00f;
         }
-      }
 
-      @supports (color: lab(0% 0 0)) {
-        .foo {
-          --foo: lab(52.2319% 40.1449 59.9171);
-          --bar: lab(40% 56.6 39);
+        .foo.foo {
+          padding: 2ch;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --foo: color(display-p3 0 1 0);
-      }
-    "#,
+        .error, .invalid {
+          &:hover > .baz { color: red; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --foo: #00f942;
-      }
-
-      @supports (color: color(display-p3 0 0 0)) {
-        .foo {
-          --foo: color(display-p3 0 1 0);
+        :is(.error, .invalid):hover > .baz {
+          color: red;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --foo: color(display-p3 0 1 0);
-      }
-    "#,
+        .foo {
+          &:is(.bar, &.baz) { color: red; }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --foo: color(display-p3 0 1 0);
-      }
-    "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
+        .foo:is(.bar, .foo.baz) {
+          color: red;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --foo: color(display-p3 0 1 0);
-      }
-    "#,
+        figure {
+          margin: 0;
+
+          & > figcaption {
+            background: hsl(0 0% 0% / 50%);
+
+            & > p {
+              font-size: .9rem;
+            }
+          }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --foo: #00f942;
-      }
+        figure {
+          margin: 0;
+        }
 
-      @supports (color: color(display-p3 0 0 0)) {
-        .foo {
-          --foo: color(display-p3 0 1 0);
+        figure > figcaption {
+          background: #00000080;
         }
-      }
-    "#},
-      Browsers {
-        safari: Some(15 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+
+        figure > figcaption > p {
+          font-size: .9rem;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --foo: color(display-p3 0 1 0);
-      }
-    "#,
-      indoc! {r#"
-      .foo {
-        --foo: #00f942;
-      }
+        .foo {
+          display: grid;
 
-      @supports (color: color(display-p3 0 0 0)) {
+          @media (orientation: landscape) {
+            grid-auto-flow: column;
+          }
+        }
+      "#,
+      indoc! {r#"
         .foo {
-          --foo: color(display-p3 0 1 0);
+          display: grid;
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+
+        @media (orientation: landscape) {
+          .foo {
+            grid-auto-flow: column;
+          }
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        text-decoration: underline;
-      }
+        .foo {
+          display: grid;
 
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
-    "#,
+          @media (orientation: landscape) {
+            grid-auto-flow: column;
+
+            @media (width > 1024px) {
+              max-inline-size: 1024px;
+            }
+          }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: #b32323;
-        text-decoration: underline;
-      }
+        .foo {
+          display: grid;
+        }
 
-      @supports (color: lab(0% 0 0)) {
+        @media (orientation: landscape) {
+          .foo {
+            grid-auto-flow: column;
+          }
+
+          @media (min-width: 1024px) {
+            .foo {
+              max-inline-size: 1024px;
+            }
+          }
+        }
+      "#},
+    );
+
+    nesting_test(
+      r#"
         .foo {
-          --custom: lab(40% 56.6 39);
+          @media (min-width: 640px) {
+            color: red !important;
+          }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#,
+      indoc! {r#"
+        @media (min-width: 640px) {
+          .foo {
+            color: red !important;
+          }
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      .foo {
-        --custom: lab(40% 56.6 39);
-      }
+        .foo {
+          display: grid;
 
-      .foo {
-        text-decoration: underline;
-      }
-    "#,
+          @supports (foo: bar) {
+            grid-auto-flow: column;
+          }
+        }
+      "#,
       indoc! {r#"
-      .foo {
-        --custom: #b32323;
-      }
-
-      @supports (color: lab(0% 0 0)) {
         .foo {
-          --custom: lab(40% 56.6 39);
+          display: grid;
         }
-      }
 
-      .foo {
-        text-decoration: underline;
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+        @supports (foo: bar) {
+          .foo {
+            grid-auto-flow: column;
+          }
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      @keyframes foo {
-        from {
-          --custom: lab(40% 56.6 39);
-        }
+        .foo {
+          display: grid;
 
-        to {
-          --custom: lch(50.998% 135.363 338);
+          @container (min-width: 100px) {
+            grid-auto-flow: column;
+          }
         }
-      }
-    "#,
+      "#,
       indoc! {r#"
-      @keyframes foo {
-        from {
-          --custom: #b32323;
+        .foo {
+          display: grid;
         }
 
-        to {
-          --custom: #ee00be;
+        @container (width >= 100px) {
+          .foo {
+            grid-auto-flow: column;
+          }
         }
-      }
+      "#},
+    );
 
-      @supports (color: lab(0% 0 0)) {
-        @keyframes foo {
-          from {
-            --custom: lab(40% 56.6 39);
+    nesting_test(
+      r#"
+        .foo {
+          display: grid;
+
+          @layer test {
+            grid-auto-flow: column;
           }
+        }
+      "#,
+      indoc! {r#"
+        .foo {
+          display: grid;
+        }
 
-          to {
-            --custom: lab(50.998% 125.506 -50.7078);
+        @layer test {
+          .foo {
+            grid-auto-flow: column;
           }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      @keyframes foo {
-        from {
-          --custom: lab(40% 56.6 39);
-        }
+        .foo {
+          display: grid;
 
-        to {
-          --custom: lch(50.998% 135.363 338);
+          @layer {
+            grid-auto-flow: column;
+          }
         }
-      }
-    "#,
+      "#,
       indoc! {r#"
-      @keyframes foo {
-        from {
-          --custom: #b32323;
+        .foo {
+          display: grid;
         }
 
-        to {
-          --custom: #ee00be;
+        @layer {
+          .foo {
+            grid-auto-flow: column;
+          }
         }
-      }
+      "#},
+    );
 
-      @supports (color: color(display-p3 0 0 0)) {
-        @keyframes foo {
-          from {
-            --custom: color(display-p3 .643308 .192455 .167712);
+    nesting_test(
+      r#"
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
+
+        .foo {
+          &div {
+            color: red;
           }
 
-          to {
-            --custom: color(display-p3 .972962 -.362078 .804206);
+          &* {
+            color: green;
           }
-        }
-      }
 
-      @supports (color: lab(0% 0 0)) {
-        @keyframes foo {
-          from {
-            --custom: lab(40% 56.6 39);
+          &|x {
+            color: red;
           }
 
-          to {
-            --custom: lab(50.998% 125.506 -50.7078);
+          &*|x {
+            color: green;
+          }
+
+          &toto|x {
+            color: red;
           }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
+      "#,
+      indoc! {r#"
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
 
-    prefix_test(
-      r#"
-      @keyframes foo {
-        from {
-          --custom: #ff0;
-          opacity: 0;
+        div.foo {
+          color: red;
         }
 
-        to {
-          --custom: lch(50.998% 135.363 338);
-          opacity: 1;
+        *.foo {
+          color: green;
         }
-      }
-    "#,
-      indoc! {r#"
-      @keyframes foo {
-        from {
-          --custom: #ff0;
-          opacity: 0;
+
+        |x.foo {
+          color: red;
         }
 
-        to {
-          --custom: #ee00be;
-          opacity: 1;
+        *|x.foo {
+          color: green;
         }
-      }
 
-      @supports (color: lab(0% 0 0)) {
-        @keyframes foo {
-          from {
-            --custom: #ff0;
-            opacity: 0;
-          }
+        toto|x.foo {
+          color: red;
+        }
+      "#},
+    );
 
-          to {
-            --custom: lab(50.998% 125.506 -50.7078);
-            opacity: 1;
+    nesting_test(
+      r#"
+        .foo {
+          &article > figure {
+            color: red;
           }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#,
+      indoc! {r#"
+        article.foo > figure {
+          color: red;
+        }
+      "#},
     );
 
-    prefix_test(
+    nesting_test(
       r#"
-      @keyframes foo {
-        from {
-          text-decoration: var(--foo) lab(29.2345% 39.3825 20.0664);
+        div {
+          &.bar {
+            background: green;
+          }
         }
-      }
-    "#,
+      "#,
       indoc! {r#"
-      @keyframes foo {
-        from {
-          text-decoration: var(--foo) #7d2329;
+        div.bar {
+          background: green;
         }
-      }
+      "#},
+    );
 
-      @supports (color: lab(0% 0 0)) {
-        @keyframes foo {
-          from {
-            text-decoration: var(--foo) lab(29.2345% 39.3825 20.0664);
+    nesting_test(
+      r#"
+        div > .foo {
+          &span {
+            background: green;
           }
         }
-      }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+      "#,
+      indoc! {r#"
+        span:is(div > .foo) {
+          background: green;
+        }
+      "#},
     );
-  }
 
-  #[test]
-  fn test_charset() {
-    test(
+    nesting_test(
       r#"
-      @charset "UTF-8";
-
-      .foo {
-        color: red;
-      }
-
-      @charset "UTF-8";
-
-      .bar {
-        color: yellow;
-      }
-    "#,
-      indoc! { r#"
-      .foo {
-        color: red;
-      }
-
-      .bar {
-        color: #ff0;
-      }
-    "#},
-    )
-  }
-
-  #[test]
-  fn test_style_attr() {
-    attr_test("color: yellow; flex: 1 1 auto", "color: #ff0; flex: auto", false, None);
-    attr_test("color: yellow; flex: 1 1 auto", "color:#ff0;flex:auto", true, None);
-    attr_test(
-      "border-inline-start: 2px solid red",
-      "border-inline-start: 2px solid red",
-      false,
-      Some(Browsers {
-        safari: Some(12 << 16),
-        ..Browsers::default()
-      }),
-    );
-    attr_test(
-      "color: lab(40% 56.6 39);",
-      "color:#b32323;color:lab(40% 56.6 39)",
-      true,
-      Some(Browsers {
-        safari: Some(8 << 16),
-        ..Browsers::default()
-      }),
-    );
-    attr_test(
-      "--foo: lab(40% 56.6 39);",
-      "--foo:#b32323",
-      true,
-      Some(Browsers {
-        safari: Some(8 << 16),
-        ..Browsers::default()
-      }),
+        .foo {
+          & h1 {
+            background: green;
+          }
+        }
+      "#,
+      indoc! {r#"
+        .foo h1 {
+          background: green;
+        }
+      "#},
     );
-    attr_test(
-      "text-decoration: var(--foo) lab(40% 56.6 39);",
-      "text-decoration:var(--foo)#b32323",
-      true,
-      Some(Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      }),
+
+    nesting_test(
+      r#"
+        .foo .bar {
+          &h1 {
+            background: green;
+          }
+        }
+      "#,
+      indoc! {r#"
+        h1:is(.foo .bar) {
+          background: green;
+        }
+      "#},
     );
-  }
 
-  #[test]
-  fn test_nesting() {
     nesting_test(
       r#"
-        .foo {
-          color: blue;
-          & > .bar { color: red; }
+        .foo.bar {
+          &h1 {
+            background: green;
+          }
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
-        }
-
-        .foo > .bar {
-          color: red;
+        h1.foo.bar {
+          background: green;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          color: blue;
-          &.bar { color: red; }
+        .foo .bar {
+          &h1 .baz {
+            background: green;
+          }
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
+        h1:is(.foo .bar) .baz {
+          background: green;
         }
+      "#},
+    );
 
-        .foo.bar {
-          color: red;
+    nesting_test(
+      r#"
+        .foo .bar {
+          &.baz {
+            background: green;
+          }
+        }
+      "#,
+      indoc! {r#"
+        .foo .bar.baz {
+          background: green;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo, .bar {
-          color: blue;
-          & + .baz, &.qux { color: red; }
+        .foo {
+          color: red;
+          @nest .parent & {
+            color: blue;
+          }
         }
       "#,
       indoc! {r#"
-        .foo, .bar {
-          color: #00f;
+        .foo {
+          color: red;
         }
 
-        :is(.foo, .bar) + .baz, :is(.foo, .bar).qux {
-          color: red;
+        .parent .foo {
+          color: #00f;
         }
       "#},
     );
@@ -21307,17 +25348,19 @@ mod tests {
     nesting_test(
       r#"
         .foo {
-          color: blue;
-          & .bar & .baz & .qux { color: red; }
+          color: red;
+          @nest :not(&) {
+            color: blue;
+          }
         }
       "#,
       indoc! {r#"
         .foo {
-          color: #00f;
+          color: red;
         }
 
-        .foo .bar .foo .baz .foo .qux {
-          color: red;
+        :not(.foo) {
+          color: #00f;
         }
       "#},
     );
@@ -21326,7 +25369,12 @@ mod tests {
       r#"
         .foo {
           color: blue;
-          & { padding: 2ch; }
+          @nest .bar & {
+            color: red;
+            &.baz {
+              color: green;
+            }
+          }
         }
       "#,
       indoc! {r#"
@@ -21334,8 +25382,12 @@ mod tests {
           color: #00f;
         }
 
-        .foo {
-          padding: 2ch;
+        .bar .foo {
+          color: red;
+        }
+
+        .bar .foo.baz {
+          color: green;
         }
       "#},
     );
@@ -21343,29 +25395,44 @@ mod tests {
     nesting_test(
       r#"
         .foo {
-          color: blue;
-          && { padding: 2ch; }
+          @nest :not(&) {
+            color: red;
+          }
+
+          & h1 {
+            background: green;
+          }
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
+        :not(.foo) {
+          color: red;
         }
 
-        .foo.foo {
-          padding: 2ch;
+        .foo h1 {
+          background: green;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .error, .invalid {
-          &:hover > .baz { color: red; }
+        .foo {
+          & h1 {
+            background: green;
+          }
+
+          @nest :not(&) {
+            color: red;
+          }
         }
       "#,
       indoc! {r#"
-        :is(.error, .invalid):hover > .baz {
+        .foo h1 {
+          background: green;
+        }
+
+        :not(.foo) {
           color: red;
         }
       "#},
@@ -21373,184 +25440,239 @@ mod tests {
 
     nesting_test(
       r#"
-        .foo {
-          &:is(.bar, &.baz) { color: red; }
+        .foo .bar {
+          @nest h1& {
+            background: green;
+          }
         }
       "#,
       indoc! {r#"
-        .foo:is(.bar, .foo.baz) {
-          color: red;
+        h1:is(.foo .bar) {
+          background: green;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        figure {
-          margin: 0;
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
 
-          & > figcaption {
-            background: hsl(0 0% 0% / 50%);
+        div {
+          @nest .foo& {
+            color: red;
+          }
+        }
 
-            & > p {
-              font-size: .9rem;
-            }
+        * {
+          @nest .foo& {
+            color: red;
+          }
+        }
+
+        |x {
+          @nest .foo& {
+            color: red;
+          }
+        }
+
+        *|x {
+          @nest .foo& {
+            color: red;
+          }
+        }
+
+        toto|x {
+          @nest .foo& {
+            color: red;
           }
         }
       "#,
       indoc! {r#"
-        figure {
-          margin: 0;
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
+
+        .foo:is(div) {
+          color: red;
         }
 
-        figure > figcaption {
-          background: #00000080;
+        .foo:is(*) {
+          color: red;
         }
 
-        figure > figcaption > p {
-          font-size: .9rem;
+        .foo:is(|x) {
+          color: red;
+        }
+
+        .foo:is(*|x) {
+          color: red;
+        }
+
+        .foo:is(toto|x) {
+          color: red;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          display: grid;
+        .foo .bar {
+          @nest h1 .baz& {
+            background: green;
+          }
+        }
+      "#,
+      indoc! {r#"
+        h1 .baz:is(.foo .bar) {
+          background: green;
+        }
+      "#},
+    );
 
-          @media (orientation: landscape) {
-            grid-auto-flow: column;
+    nesting_test(
+      r#"
+        .foo .bar {
+          @nest .baz& {
+            background: green;
           }
         }
       "#,
       indoc! {r#"
-        .foo {
-          display: grid;
+        .baz:is(.foo .bar) {
+          background: green;
         }
+      "#},
+    );
 
-        @media (orientation: landscape) {
-          .foo {
-            grid-auto-flow: column;
+    nesting_test(
+      r#"
+        .foo .bar {
+          @nest .baz & {
+            background: green;
           }
         }
+      "#,
+      indoc! {r#"
+        .baz :is(.foo .bar) {
+          background: green;
+        }
       "#},
     );
 
     nesting_test(
       r#"
         .foo {
-          display: grid;
-
-          @media (orientation: landscape) {
-            grid-auto-flow: column;
-
-            @media (width > 1024px) {
-              max-inline-size: 1024px;
-            }
+          color: red;
+          @nest & > .bar {
+            color: blue;
           }
         }
       "#,
       indoc! {r#"
         .foo {
-          display: grid;
+          color: red;
         }
 
-        @media (orientation: landscape) {
-          .foo {
-            grid-auto-flow: column;
-          }
-
-          @media (min-width: 1024px) {
-            .foo {
-              max-inline-size: 1024px;
-            }
-          }
+        .foo > .bar {
+          color: #00f;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          @media (min-width: 640px) {
-            color: red !important;
-          }
+      .foo {
+        color: red;
+        .bar {
+          color: blue;
         }
+      }
       "#,
       indoc! {r#"
-        @media (min-width: 640px) {
-          .foo {
-            color: red !important;
-          }
-        }
+      .foo {
+        color: red;
+      }
+
+      .foo .bar {
+        color: #00f;
+      }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          display: grid;
-
-          @supports (foo: bar) {
-            grid-auto-flow: column;
-          }
+      .foo {
+        color: red;
+        .bar & {
+          color: blue;
         }
+      }
       "#,
       indoc! {r#"
-        .foo {
-          display: grid;
-        }
+      .foo {
+        color: red;
+      }
 
-        @supports (foo: bar) {
-          .foo {
-            grid-auto-flow: column;
-          }
-        }
+      .bar .foo {
+        color: #00f;
+      }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          display: grid;
+      .foo {
+        color: red;
+        + .bar + & { color: blue; }
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        color: red;
+      }
 
-          @container (min-width: 100px) {
-            grid-auto-flow: column;
-          }
+      .foo + .bar + .foo {
+        color: #00f;
+      }
+      "#},
+    );
+
+    nesting_test(
+      r#"
+      .foo {
+        color: red;
+        .bar & {
+          color: blue;
         }
+      }
       "#,
       indoc! {r#"
-        .foo {
-          display: grid;
-        }
+      .foo {
+        color: red;
+      }
 
-        @container (width >= 100px) {
-          .foo {
-            grid-auto-flow: column;
-          }
-        }
+      .bar .foo {
+        color: #00f;
+      }
       "#},
     );
 
     nesting_test(
       r#"
         .foo {
-          display: grid;
-
-          @layer test {
-            grid-auto-flow: column;
+          color: red;
+          .parent & {
+            color: blue;
           }
         }
       "#,
       indoc! {r#"
         .foo {
-          display: grid;
+          color: red;
         }
 
-        @layer test {
-          .foo {
-            grid-auto-flow: column;
-          }
+        .parent .foo {
+          color: #00f;
         }
       "#},
     );
@@ -21558,119 +25680,106 @@ mod tests {
     nesting_test(
       r#"
         .foo {
-          display: grid;
-
-          @layer {
-            grid-auto-flow: column;
+          color: red;
+          :not(&) {
+            color: blue;
           }
         }
       "#,
       indoc! {r#"
         .foo {
-          display: grid;
+          color: red;
         }
 
-        @layer {
-          .foo {
-            grid-auto-flow: column;
-          }
+        :not(.foo) {
+          color: #00f;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
-
         .foo {
-          &div {
-            color: red;
-          }
-
-          &* {
-            color: green;
-          }
-
-          &|x {
-            color: red;
-          }
-
-          &*|x {
-            color: green;
-          }
-
-          &toto|x {
+          color: blue;
+          .bar & {
             color: red;
+            &.baz {
+              color: green;
+            }
           }
         }
       "#,
       indoc! {r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
-
-        div.foo {
-          color: red;
-        }
-
-        *.foo {
-          color: green;
+        .foo {
+          color: #00f;
         }
 
-        |x.foo {
+        .bar .foo {
           color: red;
         }
 
-        *|x.foo {
+        .bar .foo.baz {
           color: green;
         }
-
-        toto|x.foo {
-          color: red;
-        }
       "#},
     );
 
     nesting_test(
       r#"
         .foo {
-          &article > figure {
+          :not(&) {
             color: red;
           }
+
+          & h1 {
+            background: green;
+          }
         }
       "#,
       indoc! {r#"
-        article.foo > figure {
+        :not(.foo) {
           color: red;
         }
+
+        .foo h1 {
+          background: green;
+        }
       "#},
     );
 
     nesting_test(
       r#"
-        div {
-          &.bar {
+        .foo {
+          & h1 {
             background: green;
           }
+
+          :not(&) {
+            color: red;
+          }
         }
       "#,
       indoc! {r#"
-        div.bar {
+        .foo h1 {
           background: green;
         }
+
+        :not(.foo) {
+          color: red;
+        }
       "#},
     );
 
     nesting_test(
       r#"
-        div > .foo {
-          &span {
+        .foo .bar {
+          :is(h1)& {
             background: green;
           }
         }
       "#,
       indoc! {r#"
-        span:is(div > .foo) {
+        :is(h1):is(.foo .bar) {
           background: green;
         }
       "#},
@@ -21678,44 +25787,75 @@ mod tests {
 
     nesting_test(
       r#"
-        .foo {
-          & h1 {
-            background: green;
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
+
+        div {
+          .foo& {
+            color: red;
           }
         }
-      "#,
-      indoc! {r#"
-        .foo h1 {
-          background: green;
+
+        * {
+          .foo& {
+            color: red;
+          }
         }
-      "#},
-    );
 
-    nesting_test(
-      r#"
-        .foo .bar {
-          &h1 {
-            background: green;
+        |x {
+          .foo& {
+            color: red;
+          }
+        }
+
+        *|x {
+          .foo& {
+            color: red;
+          }
+        }
+
+        toto|x {
+          .foo& {
+            color: red;
           }
         }
       "#,
       indoc! {r#"
-        h1:is(.foo .bar) {
-          background: green;
+        @namespace "http://example.com/foo";
+        @namespace toto "http://toto.example.org";
+
+        .foo:is(div) {
+          color: red;
+        }
+
+        .foo:is(*) {
+          color: red;
+        }
+
+        .foo:is(|x) {
+          color: red;
+        }
+
+        .foo:is(*|x) {
+          color: red;
+        }
+
+        .foo:is(toto|x) {
+          color: red;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo.bar {
-          &h1 {
+        .foo .bar {
+          :is(h1) .baz& {
             background: green;
           }
         }
       "#,
       indoc! {r#"
-        h1.foo.bar {
+        :is(h1) .baz:is(.foo .bar) {
           background: green;
         }
       "#},
@@ -21724,13 +25864,13 @@ mod tests {
     nesting_test(
       r#"
         .foo .bar {
-          &h1 .baz {
+          .baz& {
             background: green;
           }
         }
       "#,
       indoc! {r#"
-        h1:is(.foo .bar) .baz {
+        .baz:is(.foo .bar) {
           background: green;
         }
       "#},
@@ -21739,13 +25879,13 @@ mod tests {
     nesting_test(
       r#"
         .foo .bar {
-          &.baz {
+          .baz & {
             background: green;
           }
         }
       "#,
       indoc! {r#"
-        .foo .bar.baz {
+        .baz :is(.foo .bar) {
           background: green;
         }
       "#},
@@ -21754,10 +25894,10 @@ mod tests {
     nesting_test(
       r#"
         .foo {
-          color: red;
-          @nest .parent & {
+          .bar {
             color: blue;
           }
+          color: red;
         }
       "#,
       indoc! {r#"
@@ -21765,7 +25905,7 @@ mod tests {
           color: red;
         }
 
-        .parent .foo {
+        .foo .bar {
           color: #00f;
         }
       "#},
@@ -21773,19 +25913,18 @@ mod tests {
 
     nesting_test(
       r#"
-        .foo {
+        article {
+          color: green;
+          & { color: blue; }
           color: red;
-          @nest :not(&) {
-            color: blue;
-          }
         }
       "#,
       indoc! {r#"
-        .foo {
+        article {
           color: red;
         }
 
-        :not(.foo) {
+        article {
           color: #00f;
         }
       "#},
@@ -21793,50 +25932,52 @@ mod tests {
 
     nesting_test(
       r#"
-        .foo {
-          color: blue;
-          @nest .bar & {
-            color: red;
-            &.baz {
-              color: green;
-            }
-          }
+        & .foo {
+          color: red;
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
+        :scope .foo {
+          color: red;
         }
+      "#},
+    );
 
-        .bar .foo {
+    nesting_test(
+      r#"
+        &.foo {
           color: red;
         }
-
-        .bar .foo.baz {
-          color: green;
+      "#,
+      indoc! {r#"
+        :scope.foo {
+          color: red;
         }
       "#},
     );
 
     nesting_test(
       r#"
-        .foo {
-          @nest :not(&) {
-            color: red;
-          }
-
-          & h1 {
-            background: green;
-          }
+        .foo& {
+          color: red;
         }
       "#,
       indoc! {r#"
-        :not(.foo) {
+        .foo:scope {
           color: red;
         }
+      "#},
+    );
 
-        .foo h1 {
-          background: green;
+    nesting_test(
+      r#"
+        &html {
+          color: red;
+        }
+      "#,
+      indoc! {r#"
+        html:scope {
+          color: red;
         }
       "#},
     );
@@ -21844,21 +25985,18 @@ mod tests {
     nesting_test(
       r#"
         .foo {
-          & h1 {
-            background: green;
-          }
-
-          @nest :not(&) {
+          color: blue;
+          div {
             color: red;
           }
         }
       "#,
       indoc! {r#"
-        .foo h1 {
-          background: green;
+        .foo {
+          color: #00f;
         }
 
-        :not(.foo) {
+        .foo div {
           color: red;
         }
       "#},
@@ -21866,4799 +26004,7796 @@ mod tests {
 
     nesting_test(
       r#"
-        .foo .bar {
-          @nest h1& {
-            background: green;
+        div {
+          color: blue;
+
+          button:focus {
+            color: red;
           }
         }
       "#,
       indoc! {r#"
-        h1:is(.foo .bar) {
-          background: green;
+        div {
+          color: #00f;
+        }
+
+        div button:focus {
+          color: red;
         }
       "#},
     );
-
     nesting_test(
       r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
-
         div {
-          @nest .foo& {
+          color: blue;
+
+          --button:focus {
             color: red;
           }
         }
+      "#,
+      indoc! {r#"
+        div {
+          color: #00f;
+          --button: focus { color: red; };
+        }
+      "#},
+    );
 
-        * {
-          @nest .foo& {
+    nesting_test_no_targets(
+      r#"
+        .foo {
+          color: blue;
+          @nest .bar & {
             color: red;
+            &.baz {
+              color: green;
+            }
           }
         }
+      "#,
+      indoc! {r#"
+        .foo {
+          color: #00f;
 
-        |x {
-          @nest .foo& {
+          @nest .bar & {
             color: red;
+
+            &.baz {
+              color: green;
+            }
           }
         }
+      "#},
+    );
 
-        *|x {
-          @nest .foo& {
+    nesting_test_no_targets(
+      r#"
+        .foo {
+          color: blue;
+          &div {
             color: red;
           }
-        }
 
-        toto|x {
-          @nest .foo& {
-            color: red;
+          &span {
+            color: purple;
           }
         }
       "#,
       indoc! {r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
-
-        .foo:is(div) {
-          color: red;
-        }
-
-        .foo:is(*) {
-          color: red;
-        }
-
-        .foo:is(|x) {
-          color: red;
-        }
+        .foo {
+          color: #00f;
 
-        .foo:is(*|x) {
-          color: red;
-        }
+          &div {
+            color: red;
+          }
 
-        .foo:is(toto|x) {
-          color: red;
+          &span {
+            color: purple;
+          }
         }
       "#},
     );
 
-    nesting_test(
+    nesting_test_no_targets(
       r#"
-        .foo .bar {
-          @nest h1 .baz& {
-            background: green;
-          }
+        .error, .invalid {
+          &:hover > .baz { color: red; }
         }
       "#,
       indoc! {r#"
-        h1 .baz:is(.foo .bar) {
-          background: green;
+        .error, .invalid {
+          &:hover > .baz {
+            color: red;
+          }
         }
       "#},
     );
 
-    nesting_test(
+    nesting_test_with_targets(
       r#"
-        .foo .bar {
-          @nest .baz& {
-            background: green;
-          }
+        .foo {
+          color: blue;
+          & > .bar { color: red; }
         }
       "#,
       indoc! {r#"
-        .baz:is(.foo .bar) {
-          background: green;
+        .foo {
+          color: #00f;
+        }
+
+        .foo > .bar {
+          color: red;
         }
       "#},
+      Targets {
+        browsers: Some(Browsers {
+          chrome: Some(112 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::Nesting,
+        exclude: Features::empty(),
+      },
     );
-
-    nesting_test(
+    nesting_test_with_targets(
       r#"
-        .foo .bar {
-          @nest .baz & {
-            background: green;
-          }
+        .foo {
+          color: blue;
+          & > .bar { color: red; }
         }
       "#,
       indoc! {r#"
-        .baz :is(.foo .bar) {
-          background: green;
+        .foo {
+          color: #00f;
+
+          & > .bar {
+            color: red;
+          }
         }
       "#},
+      Targets {
+        browsers: Some(Browsers {
+          chrome: Some(50 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::empty(),
+        exclude: Features::Nesting,
+      },
     );
 
-    nesting_test(
+    let mut stylesheet = StyleSheet::parse(
       r#"
-        .foo {
+      .foo {
+        color: blue;
+        .bar {
           color: red;
-          @nest & > .bar {
-            color: blue;
+        }
+      }
+      "#,
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{color:#00f;& .bar{color:red}}");
+
+    nesting_test_with_targets(
+      r#"
+        .a {
+          &.b,
+          &.c {
+            &.d {
+              color: red;
+            }
           }
         }
       "#,
       indoc! {r#"
-        .foo {
+        .a.b.d {
           color: red;
         }
 
-        .foo > .bar {
-          color: #00f;
+        .a.c.d {
+          color: red;
         }
       "#},
+      Targets {
+        browsers: Some(Browsers {
+          safari: Some(13 << 16),
+          ..Browsers::default()
+        }),
+        include: Features::Nesting,
+        exclude: Features::empty(),
+      },
     );
+  }
 
-    nesting_test(
+  #[test]
+  fn test_css_modules() {
+    css_modules_test(
       r#"
-      .foo {
-        color: red;
-        .bar {
-          color: blue;
-        }
-      }
-      "#,
-      indoc! {r#"
       .foo {
         color: red;
       }
 
-      .foo .bar {
-        color: #00f;
+      #id {
+        animation: 2s test;
       }
-      "#},
-    );
 
-    nesting_test(
-      r#"
-      .foo {
-        color: red;
-        .bar & {
-          color: blue;
-        }
-      }
-      "#,
-      indoc! {r#"
-      .foo {
-        color: red;
+      @keyframes test {
+        from { color: red }
+        to { color: yellow }
       }
 
-      .bar .foo {
-        color: #00f;
+      @counter-style circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
       }
-      "#},
-    );
 
-    nesting_test(
-      r#"
-      .foo {
-        color: red;
-        + .bar + & { color: blue; }
+      ul {
+        list-style: circles;
       }
-      "#,
-      indoc! {r#"
-      .foo {
-        color: red;
+
+      ol {
+        list-style-type: none;
       }
 
-      .foo + .bar + .foo {
-        color: #00f;
+      li {
+        list-style-type: disc;
       }
-      "#},
-    );
 
-    nesting_test(
-      r#"
-      .foo {
-        color: red;
-        .bar & {
-          color: blue;
-        }
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
       }
-      "#,
+    "#,
       indoc! {r#"
-      .foo {
+      .EgL3uq_foo {
         color: red;
       }
 
-      .bar .foo {
-        color: #00f;
+      #EgL3uq_id {
+        animation: 2s EgL3uq_test;
       }
-      "#},
-    );
 
-    nesting_test(
-      r#"
-        .foo {
-          color: red;
-          .parent & {
-            color: blue;
-          }
-        }
-      "#,
-      indoc! {r#"
-        .foo {
+      @keyframes EgL3uq_test {
+        from {
           color: red;
         }
 
-        .parent .foo {
-          color: #00f;
+        to {
+          color: #ff0;
         }
-      "#},
-    );
+      }
 
-    nesting_test(
-      r#"
-        .foo {
-          color: red;
-          :not(&) {
-            color: blue;
-          }
-        }
-      "#,
-      indoc! {r#"
-        .foo {
-          color: red;
+      @counter-style EgL3uq_circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
+      }
+
+      ul {
+        list-style: EgL3uq_circles;
+      }
+
+      ol {
+        list-style-type: none;
+      }
+
+      li {
+        list-style-type: disc;
+      }
+
+      @keyframes EgL3uq_fade {
+        from {
+          opacity: 0;
         }
 
-        :not(.foo) {
-          color: #00f;
+        to {
+          opacity: 1;
         }
-      "#},
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "id" => "EgL3uq_id",
+        "test" => "EgL3uq_test" referenced: true,
+        "circles" => "EgL3uq_circles" referenced: true,
+        "fade" => "EgL3uq_fade"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    #[cfg(feature = "grid")]
+    css_modules_test(
       r#"
-        .foo {
-          color: blue;
-          .bar & {
-            color: red;
-            &.baz {
-              color: green;
-            }
-          }
-        }
-      "#,
+      body {
+        grid: [header-top] "a a a" [header-bottom]
+              [main-top] "b b b" 1fr [main-bottom]
+              / auto 1fr auto;
+      }
+
+      header {
+        grid-area: a;
+      }
+
+      main {
+        grid-row: main-top / main-bottom;
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
-        }
+      body {
+        grid: [EgL3uq_header-top] "EgL3uq_a EgL3uq_a EgL3uq_a" [EgL3uq_header-bottom]
+              [EgL3uq_main-top] "EgL3uq_b EgL3uq_b EgL3uq_b" 1fr [EgL3uq_main-bottom]
+              / auto 1fr auto;
+      }
 
-        .bar .foo {
-          color: red;
-        }
+      header {
+        grid-area: EgL3uq_a;
+      }
 
-        .bar .foo.baz {
-          color: green;
+      main {
+        grid-row: EgL3uq_main-top / EgL3uq_main-bottom;
+      }
+    "#},
+      map! {
+        "header-top" => "EgL3uq_header-top",
+        "header-bottom" => "EgL3uq_header-bottom",
+        "main-top" => "EgL3uq_main-top",
+        "main-bottom" => "EgL3uq_main-bottom",
+        "a" => "EgL3uq_a",
+        "b" => "EgL3uq_b"
+      },
+      HashMap::new(),
+      Default::default(),
+    );
+
+    #[cfg(feature = "grid")]
+    css_modules_test(
+      r#"
+        .grid {
+          grid-template-areas: "foo";
         }
-      "#},
-    );
 
-    nesting_test(
-      r#"
         .foo {
-          :not(&) {
-            color: red;
-          }
+          grid-area: foo;
+        }
 
-          & h1 {
-            background: green;
-          }
+        .bar {
+          grid-column-start: foo-start;
         }
       "#,
       indoc! {r#"
-        :not(.foo) {
-          color: red;
+        .EgL3uq_grid {
+          grid-template-areas: "EgL3uq_foo";
         }
 
-        .foo h1 {
-          background: green;
+        .EgL3uq_foo {
+          grid-area: EgL3uq_foo;
+        }
+
+        .EgL3uq_bar {
+          grid-column-start: EgL3uq_foo-start;
         }
       "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "foo-start" => "EgL3uq_foo-start",
+        "grid" => "EgL3uq_grid",
+        "bar" => "EgL3uq_bar"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo {
-          & h1 {
-            background: green;
-          }
+      test {
+        transition-property: opacity;
+      }
+    "#,
+      indoc! {r#"
+      test {
+        transition-property: opacity;
+      }
+    "#},
+      map! {},
+      HashMap::new(),
+      Default::default(),
+    );
 
-          :not(&) {
-            color: red;
-          }
-        }
-      "#,
+    css_modules_test(
+      r#"
+      :global(.foo) {
+        color: red;
+      }
+
+      :local(.bar) {
+        color: yellow;
+      }
+
+      .bar :global(.baz) {
+        color: purple;
+      }
+    "#,
       indoc! {r#"
-        .foo h1 {
-          background: green;
-        }
+      .foo {
+        color: red;
+      }
 
-        :not(.foo) {
-          color: red;
-        }
-      "#},
+      .EgL3uq_bar {
+        color: #ff0;
+      }
+
+      .EgL3uq_bar .baz {
+        color: purple;
+      }
+    "#},
+      map! {
+        "bar" => "EgL3uq_bar"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    // :global(:local(.hi)) {
+    //   color: green;
+    // }
+
+    css_modules_test(
       r#"
-        .foo .bar {
-          :is(h1)& {
-            background: green;
-          }
-        }
-      "#,
+      .test {
+        composes: foo;
+        background: white;
+      }
+
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        :is(h1):is(.foo .bar) {
-          background: green;
-        }
-      "#},
+      .EgL3uq_test {
+        background: #fff;
+      }
+
+      .EgL3uq_foo {
+        color: red;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "EgL3uq_foo",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
+      .a, .b {
+        composes: foo;
+        background: white;
+      }
 
-        div {
-          .foo& {
-            color: red;
-          }
-        }
+      .foo {
+        color: red;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_a, .EgL3uq_b {
+        background: #fff;
+      }
 
-        * {
-          .foo& {
-            color: red;
-          }
-        }
+      .EgL3uq_foo {
+        color: red;
+      }
+    "#},
+      map! {
+        "a" => "EgL3uq_a" "EgL3uq_foo",
+        "b" => "EgL3uq_b" "EgL3uq_foo",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      Default::default(),
+    );
 
-        |x {
-          .foo& {
-            color: red;
-          }
-        }
+    css_modules_test(
+      r#"
+      .test {
+        composes: foo bar;
+        background: white;
+      }
 
-        *|x {
-          .foo& {
-            color: red;
-          }
-        }
+      .foo {
+        color: red;
+      }
 
-        toto|x {
-          .foo& {
-            color: red;
-          }
-        }
-      "#,
+      .bar {
+        color: yellow;
+      }
+    "#,
       indoc! {r#"
-        @namespace "http://example.com/foo";
-        @namespace toto "http://toto.example.org";
-
-        .foo:is(div) {
-          color: red;
-        }
-
-        .foo:is(*) {
-          color: red;
-        }
+      .EgL3uq_test {
+        background: #fff;
+      }
 
-        .foo:is(|x) {
-          color: red;
-        }
+      .EgL3uq_foo {
+        color: red;
+      }
 
-        .foo:is(*|x) {
-          color: red;
-        }
+      .EgL3uq_bar {
+        color: #ff0;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "EgL3uq_foo" "EgL3uq_bar",
+        "foo" => "EgL3uq_foo",
+        "bar" => "EgL3uq_bar"
+      },
+      HashMap::new(),
+      Default::default(),
+    );
 
-        .foo:is(toto|x) {
-          color: red;
-        }
-      "#},
+    css_modules_test(
+      r#"
+      .test {
+        composes: foo from global;
+        background: white;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_test {
+        background: #fff;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "foo" global: true
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo .bar {
-          :is(h1) .baz& {
-            background: green;
-          }
-        }
-      "#,
+      .test {
+        composes: foo bar from global;
+        background: white;
+      }
+    "#,
       indoc! {r#"
-        :is(h1) .baz:is(.foo .bar) {
-          background: green;
-        }
-      "#},
+      .EgL3uq_test {
+        background: #fff;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "foo" global: true "bar" global: true
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo .bar {
-          .baz& {
-            background: green;
-          }
-        }
-      "#,
+      .test {
+        composes: foo from "foo.css";
+        background: white;
+      }
+    "#,
       indoc! {r#"
-        .baz:is(.foo .bar) {
-          background: green;
-        }
-      "#},
+      .EgL3uq_test {
+        background: #fff;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "foo" from "foo.css"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo .bar {
-          .baz & {
-            background: green;
-          }
-        }
-      "#,
+      .test {
+        composes: foo bar from "foo.css";
+        background: white;
+      }
+    "#,
       indoc! {r#"
-        .baz :is(.foo .bar) {
-          background: green;
-        }
-      "#},
+      .EgL3uq_test {
+        background: #fff;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "foo" from "foo.css" "bar" from "foo.css"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo {
-          .bar {
-            color: blue;
-          }
-          color: red;
-        }
-      "#,
+      .test {
+        composes: foo;
+        composes: foo from "foo.css";
+        composes: bar from "bar.css";
+        background: white;
+      }
+
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          color: red;
-        }
+      .EgL3uq_test {
+        background: #fff;
+      }
 
-        .foo .bar {
-          color: #00f;
-        }
-      "#},
+      .EgL3uq_foo {
+        color: red;
+      }
+    "#},
+      map! {
+        "test" => "EgL3uq_test" "EgL3uq_foo" "foo" from "foo.css" "bar" from "bar.css",
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      Default::default(),
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        article {
-          color: green;
-          & { color: blue; }
-          color: red;
-        }
-      "#,
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        article {
-          color: red;
-        }
+      .test-EgL3uq-foo {
+        color: red;
+      }
+    "#},
+      map! {
+        "foo" => "test-EgL3uq-foo"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        pattern: crate::css_modules::Pattern::parse("test-[hash]-[local]").unwrap(),
+        ..Default::default()
+      },
+    );
 
-        article {
-          color: #00f;
-        }
-      "#},
+    css_modules_test(
+      r#"
+      .foo {
+        color: red;
+      }
+    "#,
+      indoc! {r#"
+      .test__foo--EgL3 {
+        color: red;
+      }
+    "#},
+      map! {
+        "foo" => "test__foo--EgL3"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        pattern: crate::css_modules::Pattern::parse("[name]__[local]--[hash:4]").unwrap(),
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        & .foo {
-          color: red;
-        }
-      "#,
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        :scope .foo {
-          color: red;
-        }
-      "#},
+      .test__foo--EgL3utzW {
+        color: red;
+      }
+    "#},
+      map! {
+        "foo" => "test__foo--EgL3utzW"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        pattern: crate::css_modules::Pattern::parse("[name]__[local]--[hash:8]").unwrap(),
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        &.foo {
-          color: red;
-        }
-      "#,
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        :scope.foo {
-          color: red;
-        }
-      "#},
+      .test__foo--EgL3ut--EgL3utzW {
+        color: red;
+      }
+    "#},
+      map! {
+        "foo" => "test__foo--EgL3ut--EgL3utzW"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        pattern: crate::css_modules::Pattern::parse("[name]__[local]--[hash]--[hash:8]").unwrap(),
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo& {
-          color: red;
-        }
-      "#,
+      .foo {
+        color: red;
+      }
+    "#,
       indoc! {r#"
-        .foo:scope {
-          color: red;
-        }
-      "#},
+      .ptPxma_foo {
+        color: red;
+      }
+    "#},
+      map! {
+        "foo" => "ptPxma_foo"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        hash_salt: Some("v2"),
+        ..Default::default()
+      },
+    );
+
+    css_modules_test(
+      r#"
+      .foo-bar {
+        color: red;
+      }
+
+      .baz_qux {
+        color: green;
+      }
+    "#,
+      indoc! {r#"
+      .EgL3uq_foo-bar {
+        color: red;
+      }
+
+      .EgL3uq_baz_qux {
+        color: green;
+      }
+    "#},
+      map! {
+        "foo-bar" => "EgL3uq_foo-bar",
+        "fooBar" => "EgL3uq_foo-bar",
+        "baz_qux" => "EgL3uq_baz_qux",
+        "bazQux" => "EgL3uq_baz_qux"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        exports_convention: crate::css_modules::ExportsConvention::CamelCase,
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        &html {
-          color: red;
-        }
-      "#,
+      .foo-bar {
+        color: red;
+      }
+
+      .baz_qux {
+        color: green;
+      }
+    "#,
       indoc! {r#"
-        html:scope {
-          color: red;
-        }
-      "#},
+      .EgL3uq_foo-bar {
+        color: red;
+      }
+
+      .EgL3uq_baz_qux {
+        color: green;
+      }
+    "#},
+      map! {
+        "fooBar" => "EgL3uq_foo-bar",
+        "bazQux" => "EgL3uq_baz_qux"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        exports_convention: crate::css_modules::ExportsConvention::CamelCaseOnly,
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        .foo {
-          color: blue;
-          div {
-            color: red;
-          }
-        }
-      "#,
+      .foo-bar {
+        color: red;
+      }
+
+      .baz_qux {
+        color: green;
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
-        }
+      .EgL3uq_foo-bar {
+        color: red;
+      }
 
-        .foo div {
-          color: red;
-        }
-      "#},
+      .EgL3uq_baz_qux {
+        color: green;
+      }
+    "#},
+      map! {
+        "fooBar" => "EgL3uq_foo-bar",
+        "baz_qux" => "EgL3uq_baz_qux"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        exports_convention: crate::css_modules::ExportsConvention::Dashes,
+        ..Default::default()
+      },
     );
 
-    nesting_test(
+    css_modules_test(
       r#"
-        div {
-          color: blue;
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
 
-          button:focus {
-            color: red;
-          }
+      .foo {
+        animation: fade 1s;
+        container: sidebar / inline-size;
+        list-style-type: circles;
+        grid-template-areas: "header";
+        grid-area: header;
+        view-transition-name: hero;
+      }
+
+      @container sidebar (width > 100px) {
+        .foo {
+          color: red;
         }
-      "#,
+      }
+
+      @counter-style circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
+      }
+    "#,
       indoc! {r#"
-        div {
-          color: #00f;
+      @keyframes fade {
+        from {
+          opacity: 0;
         }
 
-        div button:focus {
-          color: red;
+        to {
+          opacity: 1;
         }
-      "#},
-    );
-    nesting_test(
-      r#"
-        div {
-          color: blue;
+      }
 
-          --button:focus {
-            color: red;
-          }
-        }
-      "#,
-      indoc! {r#"
-        div {
-          color: #00f;
-          --button: focus { color: red; };
+      .EgL3uq_foo {
+        view-transition-name: hero;
+        grid-area: header;
+        grid-template-areas: "header";
+        list-style-type: circles;
+        animation: 1s fade;
+        container: sidebar / inline-size;
+      }
+
+      @container sidebar (width > 100px) {
+        .EgL3uq_foo {
+          color: red;
         }
-      "#},
+      }
+
+      @counter-style circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        animation: false,
+        container: false,
+        counter_style: false,
+        grid: false,
+        view_transition_name: false,
+        ..Default::default()
+      },
     );
 
-    nesting_test_no_targets(
+    css_modules_test(
       r#"
-        .foo {
-          color: blue;
-          @nest .bar & {
-            color: red;
-            &.baz {
-              color: green;
-            }
-          }
-        }
-      "#,
+      .foo {
+        view-transition-name: hero;
+        container-name: sidebar;
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
+      .EgL3uq_foo {
+        view-transition-name: EgL3uq_hero;
+        container-name: sidebar;
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "hero" => "EgL3uq_hero"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        container: false,
+        ..Default::default()
+      },
+    );
 
-          @nest .bar & {
-            color: red;
+    assert!(crate::css_modules::Pattern::parse("[hash:0]").is_err());
+    assert!(crate::css_modules::Pattern::parse("[hash:abc]").is_err());
 
-            &.baz {
-              color: green;
-            }
-          }
+    #[cfg(feature = "grid")]
+    css_modules_test(
+      r#"
+        .grid {
+          grid-template-areas: "foo";
         }
-      "#},
-    );
 
-    nesting_test_no_targets(
-      r#"
         .foo {
-          color: blue;
-          &div {
-            color: red;
-          }
+          grid-area: foo;
+        }
 
-          &span {
-            color: purple;
-          }
+        .bar {
+          grid-column-start: foo-start;
+          grid-row: foo-start / foo-end;
         }
       "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
-
-          &div {
-            color: red;
-          }
-
-          &span {
-            color: purple;
-          }
+        .test-grid-EgL3uq {
+          grid-template-areas: "test-foo-EgL3uq";
         }
-      "#},
-    );
 
-    nesting_test_no_targets(
-      r#"
-        .error, .invalid {
-          &:hover > .baz { color: red; }
+        .test-foo-EgL3uq {
+          grid-area: test-foo-EgL3uq;
         }
-      "#,
-      indoc! {r#"
-        .error, .invalid {
-          &:hover > .baz {
-            color: red;
-          }
+
+        .test-bar-EgL3uq {
+          grid-row: test-foo-EgL3uq-start / test-foo-EgL3uq-end;
+          grid-column-start: test-foo-EgL3uq-start;
         }
       "#},
+      map! {
+        "foo" => "test-foo-EgL3uq",
+        "foo-start" => "test-foo-EgL3uq-start",
+        "foo-end" => "test-foo-EgL3uq-end",
+        "grid" => "test-grid-EgL3uq",
+        "bar" => "test-bar-EgL3uq"
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        pattern: crate::css_modules::Pattern::parse("test-[local]-[hash]").unwrap(),
+        ..Default::default()
+      },
     );
 
-    nesting_test_with_targets(
+    css_modules_test(
       r#"
-        .foo {
-          color: blue;
-          & > .bar { color: red; }
-        }
-      "#,
+      :global .foo .bar {
+        color: red;
+      }
+
+      .foo :global .bar :local .baz {
+        color: green;
+      }
+
+      .foo:global.bar > :global .baz::before {
+        color: blue;
+      }
+    "#,
       indoc! {r#"
-        .foo {
-          color: #00f;
-        }
+      .foo .bar {
+        color: red;
+      }
 
-        .foo > .bar {
-          color: red;
-        }
-      "#},
-      Targets {
-        browsers: Some(Browsers {
-          chrome: Some(112 << 16),
-          ..Browsers::default()
-        }),
-        include: Features::Nesting,
-        exclude: Features::empty(),
+      .EgL3uq_foo .bar .EgL3uq_baz {
+        color: green;
+      }
+
+      .EgL3uq_foo.bar > .baz:before {
+        color: #00f;
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "baz" => "EgL3uq_baz"
       },
+      HashMap::new(),
+      Default::default(),
     );
-    nesting_test_with_targets(
+
+    css_modules_test(
       r#"
+      :global {
         .foo {
-          color: blue;
-          & > .bar { color: red; }
+          color: red;
         }
-      "#,
-      indoc! {r#"
-        .foo {
-          color: #00f;
 
-          & > .bar {
-            color: red;
+        .bar:not(.baz) {
+          color: green;
+
+          .qux {
+            color: blue;
           }
         }
-      "#},
-      Targets {
-        browsers: Some(Browsers {
-          chrome: Some(50 << 16),
-          ..Browsers::default()
-        }),
-        include: Features::empty(),
-        exclude: Features::Nesting,
+      }
+
+      :local {
+        .foo {
+          color: yellow;
+        }
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      .bar:not(.baz) {
+        color: green;
+
+        & .qux {
+          color: #00f;
+        }
+      }
+
+      .EgL3uq_foo {
+        color: #ff0;
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo"
       },
+      HashMap::new(),
+      Default::default(),
     );
 
-    let mut stylesheet = StyleSheet::parse(
+    css_modules_test(
       r#"
       .foo {
-        color: blue;
-        .bar {
+        :global .bar {
           color: red;
         }
-      }
-      "#,
-      ParserOptions::default(),
-    )
-    .unwrap();
-    stylesheet.minify(MinifyOptions::default()).unwrap();
-    let res = stylesheet
-      .to_css(PrinterOptions {
-        minify: true,
-        ..PrinterOptions::default()
-      })
-      .unwrap();
-    assert_eq!(res.code, ".foo{color:#00f;& .bar{color:red}}");
 
-    nesting_test_with_targets(
-      r#"
-        .a {
-          &.b,
-          &.c {
-            &.d {
-              color: red;
-            }
+        :global {
+          .baz {
+            color: green;
           }
         }
-      "#,
+      }
+    "#,
       indoc! {r#"
-        .a.b.d {
+      .EgL3uq_foo {
+        & .bar {
           color: red;
         }
 
-        .a.c.d {
-          color: red;
+        & .baz {
+          color: green;
         }
-      "#},
-      Targets {
-        browsers: Some(Browsers {
-          safari: Some(13 << 16),
-          ..Browsers::default()
-        }),
-        include: Features::Nesting,
-        exclude: Features::empty(),
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo"
       },
+      HashMap::new(),
+      Default::default(),
     );
-  }
 
-  #[test]
-  fn test_css_modules() {
+    let css_modules_error_test = |source: &str, error: ParserError| {
+      let res = StyleSheet::parse(
+        source,
+        ParserOptions {
+          css_modules: Some(Default::default()),
+          ..ParserOptions::default()
+        },
+      );
+      match res {
+        Ok(_) => unreachable!(),
+        Err(e) => assert_eq!(e.kind, error),
+      }
+    };
+
+    css_modules_error_test(
+      ".foo :global { color: red }",
+      ParserError::SelectorError(SelectorError::DanglingCssModulesPseudoClass),
+    );
+    css_modules_error_test(
+      ":not(:global .foo) { color: red }",
+      ParserError::SelectorError(SelectorError::NestedCssModulesPseudoClass),
+    );
+    css_modules_error_test(":global { color: red }", ParserError::InvalidCssModulesBlock);
+    css_modules_error_test(
+      ":global { @media print { .foo { color: red } } }",
+      ParserError::InvalidCssModulesBlock,
+    );
+    css_modules_error_test(":global { .foo & { color: red } }", ParserError::InvalidCssModulesBlock);
+
     css_modules_test(
       r#"
+      @property --foo {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: yellow;
+      }
+
       .foo {
-        color: red;
+        --foo: red;
+        color: var(--foo);
+      }
+    "#,
+      indoc! {r#"
+      @property --foo {
+        syntax: "<color>";
+        inherits: false;
+        initial-value: #ff0;
       }
 
-      #id {
-        animation: 2s test;
+      .EgL3uq_foo {
+        --foo: red;
+        color: var(--foo);
       }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo"
+      },
+      HashMap::new(),
+      Default::default(),
+    );
 
-      @keyframes test {
-        from { color: red }
-        to { color: yellow }
+    css_modules_test(
+      r#"
+      @property --foo {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: yellow;
       }
 
-      @counter-style circles {
-        symbols: Ⓐ Ⓑ Ⓒ;
+      @font-palette-values --Cooler {
+        font-family: Bixa;
+        base-palette: 1;
+        override-colors: 1 #7EB7E4;
       }
 
-      ul {
-        list-style: circles;
+      .foo {
+        --foo: red;
+        --bar: green;
+        color: var(--foo);
+        font-palette: --Cooler;
+      }
+
+      .bar {
+        color: var(--color from "./b.css");
+      }
+    "#,
+      indoc! {r#"
+      @property --EgL3uq_foo {
+        syntax: "<color>";
+        inherits: false;
+        initial-value: #ff0;
+      }
+
+      @font-palette-values --EgL3uq_Cooler {
+        font-family: Bixa;
+        base-palette: 1;
+        override-colors: 1 #7eb7e4;
+      }
+
+      .EgL3uq_foo {
+        --EgL3uq_foo: red;
+        --EgL3uq_bar: green;
+        color: var(--EgL3uq_foo);
+        font-palette: --EgL3uq_Cooler;
+      }
+
+      .EgL3uq_bar {
+        color: var(--ma1CsG);
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "--foo" => "--EgL3uq_foo" referenced: true,
+        "--bar" => "--EgL3uq_bar",
+        "bar" => "EgL3uq_bar",
+        "--Cooler" => "--EgL3uq_Cooler" referenced: true
+      },
+      HashMap::from([(
+        "--ma1CsG".into(),
+        CssModuleReference::Dependency {
+          name: "--color".into(),
+          specifier: "./b.css".into(),
+        },
+      )]),
+      crate::css_modules::Config {
+        dashed_idents: true,
+        ..Default::default()
+      },
+    );
+
+    // Stable hashes between project roots.
+    fn test_project_root(project_root: &str, filename: &str, hash: &str) {
+      let stylesheet = StyleSheet::parse(
+        r#"
+        .foo {
+          background: red;
+        }
+        "#,
+        ParserOptions {
+          filename: filename.into(),
+          css_modules: Some(Default::default()),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          project_root: Some(project_root),
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(
+        res.code,
+        format!(
+          indoc! {r#"
+      .{}_foo {{
+        background: red;
+      }}
+      "#},
+          hash
+        )
+      );
+    }
+
+    test_project_root("/foo/bar", "/foo/bar/test.css", "EgL3uq");
+    test_project_root("/foo", "/foo/test.css", "EgL3uq");
+    test_project_root("/foo/bar", "/foo/bar/baz/test.css", "xLEkNW");
+    test_project_root("/foo", "/foo/baz/test.css", "xLEkNW");
+  }
+
+  #[test]
+  fn test_pseudo_replacement() {
+    let source = r#"
+      .foo:hover {
+        color: red;
       }
 
-      ol {
-        list-style-type: none;
+      .foo:active {
+        color: yellow;
       }
 
-      li {
-        list-style-type: disc;
+      .foo:focus-visible {
+        color: purple;
       }
+    "#;
 
-      @keyframes fade {
-        from { opacity: 0 }
-        to { opacity: 1 }
-      }
-    "#,
-      indoc! {r#"
-      .EgL3uq_foo {
+    let expected = indoc! { r#"
+      .foo.is-hovered {
         color: red;
       }
 
-      #EgL3uq_id {
-        animation: 2s EgL3uq_test;
+      .foo.is-active {
+        color: #ff0;
       }
 
-      @keyframes EgL3uq_test {
-        from {
-          color: red;
-        }
-
-        to {
-          color: #ff0;
-        }
+      .foo.focus-visible {
+        color: purple;
       }
+    "#};
 
-      @counter-style EgL3uq_circles {
-        symbols: Ⓐ Ⓑ Ⓒ;
-      }
+    let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        pseudo_classes: Some(PseudoClasses {
+          hover: Some("is-hovered"),
+          active: Some("is-active"),
+          focus_visible: Some("focus-visible"),
+          ..PseudoClasses::default()
+        }),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
 
-      ul {
-        list-style: EgL3uq_circles;
+    let source = r#"
+      .foo:hover {
+        color: red;
       }
+    "#;
 
-      ol {
-        list-style-type: none;
+    let expected = indoc! { r#"
+      .EgL3uq_foo.EgL3uq_is-hovered {
+        color: red;
       }
+    "#};
 
-      li {
-        list-style-type: disc;
-      }
+    let stylesheet = StyleSheet::parse(
+      &source,
+      ParserOptions {
+        filename: "test.css".into(),
+        css_modules: Some(Default::default()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        pseudo_classes: Some(PseudoClasses {
+          hover: Some("is-hovered"),
+          ..PseudoClasses::default()
+        }),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+  }
 
-      @keyframes EgL3uq_fade {
-        from {
-          opacity: 0;
+  #[test]
+  fn test_focus_visible_class() {
+    prefix_test_with_options(
+      ".foo:focus-visible { outline: 2px solid blue }",
+      indoc! {r#"
+        .foo.focus-visible {
+          outline: 2px solid #00f;
         }
 
-        to {
-          opacity: 1;
+        @supports selector(:focus-visible) {
+          .foo:focus-visible {
+            outline: 2px solid #00f;
+          }
         }
-      }
-    "#},
-      map! {
-        "foo" => "EgL3uq_foo",
-        "id" => "EgL3uq_id",
-        "test" => "EgL3uq_test" referenced: true,
-        "circles" => "EgL3uq_circles" referenced: true,
-        "fade" => "EgL3uq_fade"
+      "#},
+      MinifyOptions {
+        focus_visible_class: Some("focus-visible".into()),
+        ..MinifyOptions::default()
       },
-      HashMap::new(),
-      Default::default(),
     );
-
-    #[cfg(feature = "grid")]
-    css_modules_test(
-      r#"
-      body {
-        grid: [header-top] "a a a" [header-bottom]
-              [main-top] "b b b" 1fr [main-bottom]
-              / auto 1fr auto;
-      }
-
-      header {
-        grid-area: a;
-      }
-
-      main {
-        grid-row: main-top / main-bottom;
-      }
-    "#,
+    prefix_test_with_options(
+      ".foo:focus-visible, .bar { outline: none }",
       indoc! {r#"
-      body {
-        grid: [EgL3uq_header-top] "EgL3uq_a EgL3uq_a EgL3uq_a" [EgL3uq_header-bottom]
-              [EgL3uq_main-top] "EgL3uq_b EgL3uq_b EgL3uq_b" 1fr [EgL3uq_main-bottom]
-              / auto 1fr auto;
-      }
-
-      header {
-        grid-area: EgL3uq_a;
-      }
+        .foo.js-focus-visible, .bar {
+          outline: none;
+        }
 
-      main {
-        grid-row: EgL3uq_main-top / EgL3uq_main-bottom;
-      }
-    "#},
-      map! {
-        "header-top" => "EgL3uq_header-top",
-        "header-bottom" => "EgL3uq_header-bottom",
-        "main-top" => "EgL3uq_main-top",
-        "main-bottom" => "EgL3uq_main-bottom",
-        "a" => "EgL3uq_a",
-        "b" => "EgL3uq_b"
+        @supports selector(:focus-visible) {
+          .foo:focus-visible {
+            outline: none;
+          }
+        }
+      "#},
+      MinifyOptions {
+        targets: Browsers {
+          safari: Some(14 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        focus_visible_class: Some("js-focus-visible".into()),
+        ..MinifyOptions::default()
       },
-      HashMap::new(),
-      Default::default(),
     );
-
-    #[cfg(feature = "grid")]
-    css_modules_test(
-      r#"
-        .grid {
-          grid-template-areas: "foo";
+    prefix_test_with_options(
+      ".foo:not(:focus-visible) { outline: none }",
+      indoc! {r#"
+        .foo:not(.focus-visible) {
+          outline: none;
         }
 
-        .foo {
-          grid-area: foo;
+        @supports selector(:focus-visible) {
+          .foo:not(:focus-visible) {
+            outline: none;
+          }
         }
+      "#},
+      MinifyOptions {
+        focus_visible_class: Some("focus-visible".into()),
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      "@media (min-width: 100px) { .foo:focus-visible { color: red } }",
+      indoc! {r#"
+        @media (width >= 100px) {
+          .foo.focus-visible {
+            color: red;
+          }
 
-        .bar {
-          grid-column-start: foo-start;
+          @supports selector(:focus-visible) {
+            .foo:focus-visible {
+              color: red;
+            }
+          }
         }
-      "#,
+      "#},
+      MinifyOptions {
+        focus_visible_class: Some("focus-visible".into()),
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ".foo:focus { color: red }",
       indoc! {r#"
-        .EgL3uq_grid {
-          grid-template-areas: "EgL3uq_foo";
-        }
-
-        .EgL3uq_foo {
-          grid-area: EgL3uq_foo;
+        .foo:focus {
+          color: red;
         }
-
-        .EgL3uq_bar {
-          grid-column-start: EgL3uq_foo-start;
+      "#},
+      MinifyOptions {
+        focus_visible_class: Some("focus-visible".into()),
+        ..MinifyOptions::default()
+      },
+    );
+    prefix_test_with_options(
+      ".foo:focus-visible { color: red }",
+      indoc! {r#"
+        .foo:focus-visible {
+          color: red;
         }
       "#},
-      map! {
-        "foo" => "EgL3uq_foo",
-        "foo-start" => "EgL3uq_foo-start",
-        "grid" => "EgL3uq_grid",
-        "bar" => "EgL3uq_bar"
+      MinifyOptions {
+        targets: Browsers {
+          chrome: Some(100 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        focus_visible_class: Some("focus-visible".into()),
+        ..MinifyOptions::default()
+      },
+    );
+  }
+
+  #[test]
+  fn test_pseudo_class_states() {
+    options_test(
+      "a:hover { color: red }",
+      "a:hover,a.\\:hover{color:red}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates::escaped()),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      ".foo:focus, .bar { color: red }",
+      ".foo:focus,.bar,.foo.\\:focus{color:red}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates::escaped()),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      "button:hover:active { color: red }",
+      "button:hover:active,button.\\:hover.\\:active{color:red}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates::escaped()),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      ".foo:not(:hover) { color: red }",
+      ".foo:not(:hover),.foo:not(.\\:hover){color:red}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates::escaped()),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      ".foo { &:hover { color: red } }",
+      ".foo{&:hover,&.\\:hover{color:red}}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates::escaped()),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    options_test(
+      "a:hover { color: red } a:focus-within { color: green }",
+      "a:hover{color:red}a:focus-within,a.is-focus-within{color:green}",
+      ParserOptions::default(),
+      MinifyOptions {
+        pseudo_class_states: Some(PseudoClassStates {
+          focus_within: Some("is-focus-within".into()),
+          ..PseudoClassStates::default()
+        }),
+        ..MinifyOptions::default()
+      },
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
       },
-      HashMap::new(),
-      Default::default(),
-    );
-
-    css_modules_test(
-      r#"
-      test {
-        transition-property: opacity;
-      }
-    "#,
-      indoc! {r#"
-      test {
-        transition-property: opacity;
-      }
-    "#},
-      map! {},
-      HashMap::new(),
-      Default::default(),
     );
+  }
 
-    css_modules_test(
-      r#"
-      :global(.foo) {
+  #[test]
+  fn test_unused_symbols() {
+    let source = r#"
+      .foo {
         color: red;
       }
 
-      :local(.bar) {
-        color: yellow;
+      .bar {
+        color: green;
       }
 
-      .bar :global(.baz) {
+      .bar:hover {
         color: purple;
       }
-    "#,
-      indoc! {r#"
-      .foo {
-        color: red;
-      }
 
-      .EgL3uq_bar {
-        color: #ff0;
+      .bar .baz {
+        background: red;
       }
 
-      .EgL3uq_bar .baz {
-        color: purple;
+      .baz:is(.bar) {
+        background: green;
       }
-    "#},
-      map! {
-        "bar" => "EgL3uq_bar"
-      },
-      HashMap::new(),
-      Default::default(),
-    );
-
-    // :global(:local(.hi)) {
-    //   color: green;
-    // }
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo;
-        background: white;
+      #id {
+        animation: 2s test;
       }
 
-      .foo {
+      #other_id {
         color: red;
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+
+      @keyframes test {
+        from { color: red }
+        to { color: yellow }
       }
 
-      .EgL3uq_foo {
-        color: red;
+      @counter-style circles {
+        symbols: Ⓐ Ⓑ Ⓒ;
       }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "EgL3uq_foo",
-        "foo" => "EgL3uq_foo"
-      },
-      HashMap::new(),
-      Default::default(),
-    );
 
-    css_modules_test(
-      r#"
-      .a, .b {
-        composes: foo;
-        background: white;
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
       }
+    "#;
 
+    let expected = indoc! {r#"
       .foo {
         color: red;
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_a, .EgL3uq_b {
-        background: #fff;
-      }
 
-      .EgL3uq_foo {
-        color: red;
+      #id {
+        animation: 2s test;
       }
-    "#},
-      map! {
-        "a" => "EgL3uq_a" "EgL3uq_foo",
-        "b" => "EgL3uq_b" "EgL3uq_foo",
-        "foo" => "EgL3uq_foo"
-      },
-      HashMap::new(),
-      Default::default(),
-    );
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo bar;
-        background: white;
+      @keyframes test {
+        from {
+          color: red;
+        }
+
+        to {
+          color: #ff0;
+        }
       }
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["bar", "other_id", "fade", "circles"]
+          .iter()
+          .map(|s| String::from(*s))
+          .collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
 
+    let source = r#"
       .foo {
         color: red;
-      }
 
-      .bar {
-        color: yellow;
-      }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+        &.bar {
+          color: green;
+        }
       }
+    "#;
 
-      .EgL3uq_foo {
+    let expected = indoc! {r#"
+      .foo {
         color: red;
       }
+    "#};
 
-      .EgL3uq_bar {
-        color: #ff0;
-      }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "EgL3uq_foo" "EgL3uq_bar",
-        "foo" => "EgL3uq_foo",
-        "bar" => "EgL3uq_bar"
-      },
-      HashMap::new(),
-      Default::default(),
-    );
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["bar"].iter().map(|s| String::from(*s)).collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo from global;
-        background: white;
+    let source = r#"
+      .foo {
+        color: red;
+
+        &.bar {
+          color: purple;
+        }
+
+        @nest &.bar {
+          color: orange;
+        }
+
+        @nest :not(&) {
+          color: green;
+        }
+
+        @media (orientation: portrait) {
+          color: brown;
+        }
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+
+      .x {
+        color: purple;
+
+        &.y {
+          color: green;
+        }
       }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "foo" global: true
-      },
-      HashMap::new(),
-      Default::default(),
-    );
+    "#;
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo bar from global;
-        background: white;
+    let expected = indoc! {r#"
+      :not(.foo) {
+        color: green;
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["foo", "x"].iter().map(|s| String::from(*s)).collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        targets: Browsers {
+          chrome: Some(95 << 16),
+          ..Browsers::default()
+        }
+        .into(),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, expected);
+
+    let source = r#"
+      @property --EgL3uq_foo {
+        syntax: "<color>";
+        inherits: false;
+        initial-value: #ff0;
       }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "foo" global: true "bar" global: true
-      },
-      HashMap::new(),
-      Default::default(),
-    );
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo from "foo.css";
-        background: white;
+      @font-palette-values --EgL3uq_Cooler {
+        font-family: Bixa;
+        base-palette: 1;
+        override-colors: 1 #7EB7E4;
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+
+      .EgL3uq_foo {
+        --EgL3uq_foo: red;
       }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "foo" from "foo.css"
-      },
-      HashMap::new(),
-      Default::default(),
-    );
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo bar from "foo.css";
-        background: white;
+      .EgL3uq_bar {
+        color: green;
       }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
+    "#;
+
+    let expected = indoc! {r#"
+      .EgL3uq_bar {
+        color: green;
       }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "foo" from "foo.css" "bar" from "foo.css"
-      },
-      HashMap::new(),
-      Default::default(),
+    "#};
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet
+      .minify(MinifyOptions {
+        unused_symbols: vec!["--EgL3uq_foo", "--EgL3uq_Cooler"]
+          .iter()
+          .map(|s| String::from(*s))
+          .collect(),
+        ..MinifyOptions::default()
+      })
+      .unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, expected);
+  }
+
+  #[test]
+  fn test_svg() {
+    minify_test(".foo { fill: yellow; }", ".foo{fill:#ff0}");
+    minify_test(".foo { fill: url(#foo); }", ".foo{fill:url(#foo)}");
+    minify_test(".foo { fill: url(#foo) none; }", ".foo{fill:url(#foo) none}");
+    minify_test(".foo { fill: url(#foo) yellow; }", ".foo{fill:url(#foo) #ff0}");
+    minify_test(".foo { fill: none; }", ".foo{fill:none}");
+    minify_test(".foo { fill: context-fill; }", ".foo{fill:context-fill}");
+    minify_test(".foo { fill: context-stroke; }", ".foo{fill:context-stroke}");
+
+    minify_test(".foo { stroke: yellow; }", ".foo{stroke:#ff0}");
+    minify_test(".foo { stroke: url(#foo); }", ".foo{stroke:url(#foo)}");
+    minify_test(".foo { stroke: url(#foo) none; }", ".foo{stroke:url(#foo) none}");
+    minify_test(".foo { stroke: url(#foo) yellow; }", ".foo{stroke:url(#foo) #ff0}");
+    minify_test(".foo { stroke: none; }", ".foo{stroke:none}");
+    minify_test(".foo { stroke: context-fill; }", ".foo{stroke:context-fill}");
+    minify_test(".foo { stroke: context-stroke; }", ".foo{stroke:context-stroke}");
+
+    minify_test(".foo { marker-start: url(#foo); }", ".foo{marker-start:url(#foo)}");
+
+    minify_test(".foo { stroke-dasharray: 4 1 2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(".foo { stroke-dasharray: 4,1,2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(".foo { stroke-dasharray: 4, 1, 2; }", ".foo{stroke-dasharray:4 1 2}");
+    minify_test(
+      ".foo { stroke-dasharray: 4px, 1px, 2px; }",
+      ".foo{stroke-dasharray:4 1 2}",
     );
 
-    css_modules_test(
-      r#"
-      .test {
-        composes: foo;
-        composes: foo from "foo.css";
-        composes: bar from "bar.css";
-        background: white;
-      }
+    minify_test(".foo { mask: url('foo.svg'); }", ".foo{mask:url(foo.svg)}");
+    minify_test(
+      ".foo { mask: url(masks.svg#star) luminance }",
+      ".foo{mask:url(masks.svg#star) luminance}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) 40px 20px }",
+      ".foo{mask:url(masks.svg#star) 40px 20px}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) 0 0 / 50px 50px }",
+      ".foo{mask:url(masks.svg#star) 0 0/50px 50px}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) repeat-x }",
+      ".foo{mask:url(masks.svg#star) repeat-x}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) stroke-box }",
+      ".foo{mask:url(masks.svg#star) stroke-box}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) stroke-box stroke-box }",
+      ".foo{mask:url(masks.svg#star) stroke-box}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) border-box }",
+      ".foo{mask:url(masks.svg#star)}",
+    );
+    minify_test(
+      ".foo { mask: url(masks.svg#star) left / 16px repeat-y, url(masks.svg#circle) right / 16px repeat-y }",
+      ".foo{mask:url(masks.svg#star) 0/16px repeat-y,url(masks.svg#circle) 100%/16px repeat-y}",
+    );
 
-      .foo {
-        color: red;
-      }
-    "#,
-      indoc! {r#"
-      .EgL3uq_test {
-        background: #fff;
-      }
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25; }",
+      ".foo{mask-border:url(border-mask.png) 25}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space alpha; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space luminance; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    );
+    minify_test(
+      ".foo { mask-border: url('border-mask.png') luminance 25 / 35px / 12px space; }",
+      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    );
 
-      .EgL3uq_foo {
-        color: red;
-      }
-    "#},
-      map! {
-        "test" => "EgL3uq_test" "EgL3uq_foo" "foo" from "foo.css" "bar" from "bar.css",
-        "foo" => "EgL3uq_foo"
-      },
-      HashMap::new(),
-      Default::default(),
+    minify_test(
+      ".foo { clip-path: url('clip.svg#star'); }",
+      ".foo{clip-path:url(clip.svg#star)}",
+    );
+    minify_test(".foo { clip-path: margin-box; }", ".foo{clip-path:margin-box}");
+    minify_test(
+      ".foo { clip-path: inset(100px 50px); }",
+      ".foo{clip-path:inset(100px 50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: inset(100px 50px round 5px); }",
+      ".foo{clip-path:inset(100px 50px round 5px)}",
+    );
+    minify_test(
+      ".foo { clip-path: inset(100px 50px round 5px 5px 5px 5px); }",
+      ".foo{clip-path:inset(100px 50px round 5px)}",
+    );
+    minify_test(".foo { clip-path: circle(50px); }", ".foo{clip-path:circle(50px)}");
+    minify_test(
+      ".foo { clip-path: circle(50px at center center); }",
+      ".foo{clip-path:circle(50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 50% 50%); }",
+      ".foo{clip-path:circle(50px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px); }",
+      ".foo{clip-path:circle(50px at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(closest-side at 0 100px); }",
+      ".foo{clip-path:circle(at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(farthest-side at 0 100px); }",
+      ".foo{clip-path:circle(farthest-side at 0 100px)}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(closest-side at 50% 50%); }",
+      ".foo{clip-path:circle()}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(50px 60px at 0 10% 20%); }",
+      ".foo{clip-path:ellipse(50px 60px at 0 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(50px 60px at center center); }",
+      ".foo{clip-path:ellipse(50px 60px)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(closest-side closest-side at 50% 50%); }",
+      ".foo{clip-path:ellipse()}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(closest-side closest-side at 10% 20%); }",
+      ".foo{clip-path:ellipse(at 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: ellipse(farthest-side closest-side at 10% 20%); }",
+      ".foo{clip-path:ellipse(farthest-side closest-side at 10% 20%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(nonzero, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: polygon(evenodd, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
+      ".foo{clip-path:polygon(evenodd,50% 0%,100% 50%,50% 100%,0% 50%)}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M 10 10 L 20 20 L 30 10 Z'); }",
+      ".foo{clip-path:path(\"M10 10 20 20 30 10Z\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path(nonzero, 'M 100 100 L 101 101 L 102 100'); }",
+      ".foo{clip-path:path(\"M100 100l1 1 1-1\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path(evenodd, 'M 0.5 -0.5 L 0.25 0.75'); }",
+      ".foo{clip-path:path(evenodd,\"M.5-.5.25.75\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M0,0 A 10 10 0 0 1 20 0 z'); }",
+      ".foo{clip-path:path(\"M0 0A10 10 0 0 1 20 0z\")}",
+    );
+    minify_test(
+      ".foo { clip-path: path('M1e2 0 m -5 5 7 7'); }",
+      ".foo{clip-path:path(\"M100 0m-5 5 7 7\")}",
+    );
+    minify_test(".foo { clip-path: path(''); }", ".foo{clip-path:path(\"\")}");
+    minify_test(
+      ".foo { clip-path: path('L 10 10'); }",
+      ".foo{clip-path:path(\"L 10 10\")}",
     );
-
-    css_modules_test(
-      r#"
-      .foo {
-        color: red;
-      }
-    "#,
+    test(
+      ".foo { clip-path: path(evenodd, 'M0,0 h10 v10 z') }",
       indoc! {r#"
-      .test-EgL3uq-foo {
-        color: red;
-      }
-    "#},
-      map! {
-        "foo" => "test-EgL3uq-foo"
-      },
-      HashMap::new(),
-      crate::css_modules::Config {
-        pattern: crate::css_modules::Pattern::parse("test-[hash]-[local]").unwrap(),
-        ..Default::default()
-      },
-    );
-
-    let stylesheet = StyleSheet::parse(
-      r#"
-        .grid {
-          grid-template-areas: "foo";
-        }
-
         .foo {
-          grid-area: foo;
-        }
-
-        .bar {
-          grid-column-start: foo-start;
+          clip-path: path(evenodd, "M 0 0 h 10 v 10 z");
         }
-      "#,
-      ParserOptions {
-        css_modules: Some(crate::css_modules::Config {
-          pattern: crate::css_modules::Pattern::parse("test-[local]-[hash]").unwrap(),
-          ..Default::default()
-        }),
-        ..ParserOptions::default()
-      },
-    )
-    .unwrap();
-    if let Err(err) = stylesheet.to_css(PrinterOptions::default()) {
-      assert_eq!(err.kind, PrinterErrorKind::InvalidCssModulesPatternInGrid);
-    } else {
-      unreachable!()
-    }
-
-    css_modules_test(
-      r#"
-      @property --foo {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: yellow;
-      }
-
-      .foo {
-        --foo: red;
-        color: var(--foo);
-      }
-    "#,
-      indoc! {r#"
-      @property --foo {
-        syntax: "<color>";
-        inherits: false;
-        initial-value: #ff0;
-      }
-
-      .EgL3uq_foo {
-        --foo: red;
-        color: var(--foo);
-      }
-    "#},
-      map! {
-        "foo" => "EgL3uq_foo"
-      },
-      HashMap::new(),
-      Default::default(),
+      "#},
+    );
+    minify_test(
+      ".foo { clip-path: xywh(0 1% 2px 3em round 0 1px); }",
+      ".foo{clip-path:xywh(0 1% 2px 3em round 0 1px)}",
+    );
+    minify_test(
+      ".foo { clip-path: xywh(0 0 -1px 2px); }",
+      ".foo{clip-path:xywh(0 0 -1px 2px)}",
+    );
+    minify_test(
+      ".foo { clip-path: rect(auto 10px 20px auto) padding-box; }",
+      ".foo{clip-path:rect(auto 10px 20px auto) padding-box}",
+    );
+    minify_test(".foo { clip-path: rect(0 0 0 0); }", ".foo{clip-path:rect(0 0 0 0)}");
+    minify_test(
+      ".foo { clip-path: shape(evenodd from 0px 0px, line to 10px 10px, curve by 5px 5px with 1px 1px / 2px 2px, smooth to 0 0, arc to 10% 10% of 5px 5px cw rotate 0deg, hline by 5px, vline to 50%, close); }",
+      ".foo{clip-path:shape(evenodd from 0 0,line to 10px 10px,curve by 5px 5px with 1px 1px/2px 2px,smooth to 0 0,arc to 10% 10% of 5px cw,hline by 5px,vline to 50%,close)}",
+    );
+    minify_test(
+      ".foo { clip-path: shape(from 0 0, arc by 10px 0 of 5px 10px rotate 45deg large, curve to 1px 1px with 2px 2px); }",
+      ".foo{clip-path:shape(from 0 0,arc by 10px 0 of 5px 10px large rotate 45deg,curve to 1px 1px with 2px 2px)}",
+    );
+    minify_test(
+      ".foo { clip-path: padding-box circle(50px at 0 100px); }",
+      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px) padding-box; }",
+      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+    );
+    minify_test(
+      ".foo { clip-path: circle(50px at 0 100px) border-box; }",
+      ".foo{clip-path:circle(50px at 0 100px)}",
     );
 
-    css_modules_test(
-      r#"
-      @property --foo {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: yellow;
-      }
-
-      @font-palette-values --Cooler {
-        font-family: Bixa;
-        base-palette: 1;
-        override-colors: 1 #7EB7E4;
-      }
-
-      .foo {
-        --foo: red;
-        --bar: green;
-        color: var(--foo);
-        font-palette: --Cooler;
-      }
-
-      .bar {
-        color: var(--color from "./b.css");
-      }
-    "#,
-      indoc! {r#"
-      @property --EgL3uq_foo {
-        syntax: "<color>";
-        inherits: false;
-        initial-value: #ff0;
-      }
-
-      @font-palette-values --EgL3uq_Cooler {
-        font-family: Bixa;
-        base-palette: 1;
-        override-colors: 1 #7eb7e4;
-      }
-
-      .EgL3uq_foo {
-        --EgL3uq_foo: red;
-        --EgL3uq_bar: green;
-        color: var(--EgL3uq_foo);
-        font-palette: --EgL3uq_Cooler;
-      }
-
-      .EgL3uq_bar {
-        color: var(--ma1CsG);
-      }
-    "#},
-      map! {
-        "foo" => "EgL3uq_foo",
-        "--foo" => "--EgL3uq_foo" referenced: true,
-        "--bar" => "--EgL3uq_bar",
-        "bar" => "EgL3uq_bar",
-        "--Cooler" => "--EgL3uq_Cooler" referenced: true
-      },
-      HashMap::from([(
-        "--ma1CsG".into(),
-        CssModuleReference::Dependency {
-          name: "--color".into(),
-          specifier: "./b.css".into(),
-        },
-      )]),
-      crate::css_modules::Config {
-        dashed_idents: true,
-        ..Default::default()
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          -webkit-clip-path: circle(50px);
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(30 << 16),
+        ..Browsers::default()
       },
     );
 
-    // Stable hashes between project roots.
-    fn test_project_root(project_root: &str, filename: &str, hash: &str) {
-      let stylesheet = StyleSheet::parse(
-        r#"
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
         .foo {
-          background: red;
+          clip-path: circle(50px);
         }
-        "#,
-        ParserOptions {
-          filename: filename.into(),
-          css_modules: Some(Default::default()),
-          ..ParserOptions::default()
-        },
-      )
-      .unwrap();
-      let res = stylesheet
-        .to_css(PrinterOptions {
-          project_root: Some(project_root),
-          ..PrinterOptions::default()
-        })
-        .unwrap();
-      assert_eq!(
-        res.code,
-        format!(
-          indoc! {r#"
-      .{}_foo {{
-        background: red;
-      }}
       "#},
-          hash
-        )
-      );
-    }
-
-    test_project_root("/foo/bar", "/foo/bar/test.css", "EgL3uq");
-    test_project_root("/foo", "/foo/test.css", "EgL3uq");
-    test_project_root("/foo/bar", "/foo/bar/baz/test.css", "xLEkNW");
-    test_project_root("/foo", "/foo/baz/test.css", "xLEkNW");
-  }
-
-  #[test]
-  fn test_pseudo_replacement() {
-    let source = r#"
-      .foo:hover {
-        color: red;
-      }
-
-      .foo:active {
-        color: yellow;
-      }
-
-      .foo:focus-visible {
-        color: purple;
-      }
-    "#;
-
-    let expected = indoc! { r#"
-      .foo.is-hovered {
-        color: red;
-      }
-
-      .foo.is-active {
-        color: #ff0;
-      }
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      .foo.focus-visible {
-        color: purple;
-      }
-    "#};
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          -webkit-clip-path: circle(50px);
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    let res = stylesheet
-      .to_css(PrinterOptions {
-        pseudo_classes: Some(PseudoClasses {
-          hover: Some("is-hovered"),
-          active: Some("is-active"),
-          focus_visible: Some("focus-visible"),
-          ..PseudoClasses::default()
-        }),
-        ..PrinterOptions::default()
-      })
-      .unwrap();
-    assert_eq!(res.code, expected);
+    prefix_test(
+      ".foo { clip-path: circle(50px); }",
+      indoc! { r#"
+        .foo {
+          clip-path: circle(50px);
+        }
+      "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let source = r#"
-      .foo:hover {
-        color: red;
-      }
-    "#;
+    prefix_test(
+      ".foo { fill: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          fill: #ee00be;
+          fill: color(display-p3 .972962 -.362078 .804206);
+          fill: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let expected = indoc! { r#"
-      .EgL3uq_foo.EgL3uq_is-hovered {
-        color: red;
-      }
-    "#};
+    prefix_test(
+      ".foo { stroke: lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          stroke: #ee00be;
+          stroke: color(display-p3 .972962 -.362078 .804206);
+          stroke: lch(50.998% 135.363 338);
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let stylesheet = StyleSheet::parse(
-      &source,
-      ParserOptions {
-        filename: "test.css".into(),
-        css_modules: Some(Default::default()),
-        ..ParserOptions::default()
+    prefix_test(
+      ".foo { fill: url(#foo) lch(50.998% 135.363 338) }",
+      indoc! { r##"
+        .foo {
+          fill: url("#foo") #ee00be;
+          fill: url("#foo") color(display-p3 .972962 -.362078 .804206);
+          fill: url("#foo") lch(50.998% 135.363 338);
+        }
+      "##},
+      Browsers {
+        chrome: Some(90 << 16),
+        safari: Some(14 << 16),
+        ..Browsers::default()
       },
-    )
-    .unwrap();
-    let res = stylesheet
-      .to_css(PrinterOptions {
-        pseudo_classes: Some(PseudoClasses {
-          hover: Some("is-hovered"),
-          ..PseudoClasses::default()
-        }),
-        ..PrinterOptions::default()
-      })
-      .unwrap();
-    assert_eq!(res.code, expected);
-  }
+    );
 
-  #[test]
-  fn test_unused_symbols() {
-    let source = r#"
-      .foo {
-        color: red;
-      }
+    prefix_test(
+      ".foo { fill: var(--url) lch(50.998% 135.363 338) }",
+      indoc! { r#"
+        .foo {
+          fill: var(--url) #ee00be;
+        }
 
-      .bar {
-        color: green;
-      }
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            fill: var(--url) lab(50.998% 125.506 -50.7078);
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      .bar:hover {
-        color: purple;
-      }
+    prefix_test(
+      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
+          -webkit-mask-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
+          mask-image: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      .bar .baz {
-        background: red;
-      }
+    prefix_test(
+      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
+          mask-image: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      .baz:is(.bar) {
-        background: green;
-      }
+    prefix_test(
+      ".foo { mask-image: linear-gradient(red, green) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: linear-gradient(red, green);
+          mask-image: linear-gradient(red, green);
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      #id {
-        animation: 2s test;
-      }
+    prefix_test(
+      ".foo { -webkit-mask-image: url(x.svg); mask-image: url(x.svg); }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: url("x.svg");
+          mask-image: url("x.svg");
+        }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      #other_id {
-        color: red;
-      }
+    prefix_test(
+      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
+          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
+          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      @keyframes test {
-        from { color: red }
-        to { color: yellow }
-      }
+    prefix_test(
+      ".foo { mask: -webkit-linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
+          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+        }
+      "#},
+      Browsers {
+        chrome: Some(8 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      @counter-style circles {
-        symbols: Ⓐ Ⓑ Ⓒ;
-      }
+    prefix_test(
+      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px var(--foo) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
+          mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
+        }
 
-      @keyframes fade {
-        from { opacity: 0 }
-        to { opacity: 1 }
-      }
-    "#;
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            -webkit-mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
+            mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let expected = indoc! {r#"
-      .foo {
-        color: red;
-      }
+    prefix_test(
+      ".foo { mask: url(masks.svg#star) luminance }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask: url("masks.svg#star");
+          -webkit-mask-source-type: luminance;
+          mask: url("masks.svg#star") luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      #id {
-        animation: 2s test;
-      }
+    prefix_test(
+      ".foo { mask-image: url(masks.svg#star) }",
+      indoc! { r#"
+        .foo {
+          -webkit-mask-image: url("masks.svg#star");
+          mask-image: url("masks.svg#star");
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      @keyframes test {
-        from {
-          color: red;
+    prefix_test(
+      r#"
+        .foo {
+          mask-image: url(masks.svg#star);
+          mask-position: 25% 75%;
+          mask-size: cover;
+          mask-repeat: no-repeat;
+          mask-clip: padding-box;
+          mask-origin: content-box;
+          mask-composite: subtract;
+          mask-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box subtract luminance;
         }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-        to {
-          color: #ff0;
+    prefix_test(
+      r#"
+        .foo {
+          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-position: 25% 75%;
+          mask-size: cover;
+          mask-repeat: no-repeat;
+          mask-clip: padding-box;
+          mask-origin: content-box;
+          mask-composite: subtract;
+          mask-mode: luminance;
         }
-      }
-    "#};
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
+          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box;
+          -webkit-mask-composite: source-out;
+          -webkit-mask-source-type: luminance;
+          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
-        unused_symbols: vec!["bar", "other_id", "fade", "circles"]
-          .iter()
-          .map(|s| String::from(*s))
-          .collect(),
-        ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
+    test(
+      r#"
+        .foo {
+          mask: none center / 100% no-repeat;
+          mask-image: var(--svg);
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          mask: none center / 100% no-repeat;
+          mask-image: var(--svg);
+        }
+      "#},
+    );
 
-    let source = r#"
-      .foo {
-        color: red;
+    prefix_test(
+      r#"
+        .foo {
+          mask-composite: subtract;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-composite: source-out;
+          mask-composite: subtract;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-        &.bar {
-          color: green;
+    prefix_test(
+      r#"
+        .foo {
+          mask-mode: luminance;
         }
-      }
-    "#;
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-source-type: luminance;
+          mask-mode: luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let expected = indoc! {r#"
-      .foo {
-        color: red;
-      }
-    "#};
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: url('border-mask.png') 25 / 35px / 12px space luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("border-mask.png") 25 / 35px / 12px space;
+          mask-border: url("border-mask.png") 25 / 35px / 12px space luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
-        unused_symbols: vec!["bar"].iter().map(|s| String::from(*s)).collect(),
-        ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space;
+          mask-border: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space luminance;
+          -webkit-mask-box-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space;
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let source = r#"
-      .foo {
-        color: red;
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-source: linear-gradient(#ff0f0e, #7773ff);
+          mask-border-source: linear-gradient(#ff0f0e, #7773ff);
+          -webkit-mask-box-image-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-        &.bar {
-          color: purple;
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-source: url(foo.png);
+          mask-border-slice: 10 40 10 40;
+          mask-border-width: 10px;
+          mask-border-outset: 0;
+          mask-border-repeat: round round;
+          mask-border-mode: luminance;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
+          mask-border: url("foo.png") 10 40 / 10px round luminance;
         }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-        @nest &.bar {
-          color: orange;
+    prefix_test(
+      r#"
+        .foo {
+          -webkit-mask-box-image-source: url(foo.png);
+          -webkit-mask-box-image-slice: 10 40 10 40;
+          -webkit-mask-box-image-width: 10px;
+          -webkit-mask-box-image-outset: 0;
+          -webkit-mask-box-image-repeat: round round;
         }
-
-        @nest :not(&) {
-          color: green;
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
         }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-        @media (orientation: portrait) {
-          color: brown;
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-slice: 10 40 10 40;
         }
-      }
-
-      .x {
-        color: purple;
-
-        &.y {
-          color: green;
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-slice: 10 40;
+          mask-border-slice: 10 40;
         }
-      }
-    "#;
-
-    let expected = indoc! {r#"
-      :not(.foo) {
-        color: green;
-      }
-    "#};
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
-        unused_symbols: vec!["foo", "x"].iter().map(|s| String::from(*s)).collect(),
-        ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet
-      .to_css(PrinterOptions {
-        targets: Browsers {
-          chrome: Some(95 << 16),
-          ..Browsers::default()
+    prefix_test(
+      r#"
+        .foo {
+          mask-border-slice: var(--foo);
         }
-        .into(),
-        ..PrinterOptions::default()
-      })
-      .unwrap();
-    assert_eq!(res.code, expected);
-
-    let source = r#"
-      @property --EgL3uq_foo {
-        syntax: "<color>";
-        inherits: false;
-        initial-value: #ff0;
-      }
-
-      @font-palette-values --EgL3uq_Cooler {
-        font-family: Bixa;
-        base-palette: 1;
-        override-colors: 1 #7EB7E4;
-      }
-
-      .EgL3uq_foo {
-        --EgL3uq_foo: red;
-      }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image-slice: var(--foo);
+          mask-border-slice: var(--foo);
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-      .EgL3uq_bar {
-        color: green;
-      }
-    "#;
+    prefix_test(
+      r#"
+        .foo {
+          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) var(--foo);
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) var(--foo);
+          mask-border: linear-gradient(#ff0f0e, #7773ff) var(--foo);
+        }
 
-    let expected = indoc! {r#"
-      .EgL3uq_bar {
-        color: green;
-      }
-    "#};
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            -webkit-mask-box-image: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
+            mask-border: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
+          }
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet
-      .minify(MinifyOptions {
-        unused_symbols: vec!["--EgL3uq_foo", "--EgL3uq_Cooler"]
-          .iter()
-          .map(|s| String::from(*s))
-          .collect(),
-        ..MinifyOptions::default()
-      })
-      .unwrap();
-    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
-    assert_eq!(res.code, expected);
-  }
+    prefix_test(
+      r#"
+        .foo {
+          transition: mask 200ms;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition: -webkit-mask .2s, mask .2s;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-  #[test]
-  fn test_svg() {
-    minify_test(".foo { fill: yellow; }", ".foo{fill:#ff0}");
-    minify_test(".foo { fill: url(#foo); }", ".foo{fill:url(#foo)}");
-    minify_test(".foo { fill: url(#foo) none; }", ".foo{fill:url(#foo) none}");
-    minify_test(".foo { fill: url(#foo) yellow; }", ".foo{fill:url(#foo) #ff0}");
-    minify_test(".foo { fill: none; }", ".foo{fill:none}");
-    minify_test(".foo { fill: context-fill; }", ".foo{fill:context-fill}");
-    minify_test(".foo { fill: context-stroke; }", ".foo{fill:context-stroke}");
+    prefix_test(
+      r#"
+        .foo {
+          transition: mask-border 200ms;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition: -webkit-mask-box-image .2s, mask-border .2s;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    minify_test(".foo { stroke: yellow; }", ".foo{stroke:#ff0}");
-    minify_test(".foo { stroke: url(#foo); }", ".foo{stroke:url(#foo)}");
-    minify_test(".foo { stroke: url(#foo) none; }", ".foo{stroke:url(#foo) none}");
-    minify_test(".foo { stroke: url(#foo) yellow; }", ".foo{stroke:url(#foo) #ff0}");
-    minify_test(".foo { stroke: none; }", ".foo{stroke:none}");
-    minify_test(".foo { stroke: context-fill; }", ".foo{stroke:context-fill}");
-    minify_test(".foo { stroke: context-stroke; }", ".foo{stroke:context-stroke}");
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask, mask;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    minify_test(".foo { marker-start: url(#foo); }", ".foo{marker-start:url(#foo)}");
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask-border;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask-box-image, mask-border;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
+    );
 
-    minify_test(".foo { stroke-dasharray: 4 1 2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(".foo { stroke-dasharray: 4,1,2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(".foo { stroke-dasharray: 4, 1, 2; }", ".foo{stroke-dasharray:4 1 2}");
-    minify_test(
-      ".foo { stroke-dasharray: 4px, 1px, 2px; }",
-      ".foo{stroke-dasharray:4 1 2}",
+    prefix_test(
+      r#"
+        .foo {
+          transition-property: mask-composite, mask-mode;
+        }
+      "#,
+      indoc! { r#"
+        .foo {
+          transition-property: -webkit-mask-composite, mask-composite, -webkit-mask-source-type, mask-mode;
+        }
+    "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
+  }
 
-    minify_test(".foo { mask: url('foo.svg'); }", ".foo{mask:url(foo.svg)}");
-    minify_test(
-      ".foo { mask: url(masks.svg#star) luminance }",
-      ".foo{mask:url(masks.svg#star) luminance}",
-    );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) 40px 20px }",
-      ".foo{mask:url(masks.svg#star) 40px 20px}",
-    );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) 0 0 / 50px 50px }",
-      ".foo{mask:url(masks.svg#star) 0 0/50px 50px}",
-    );
+  #[test]
+  fn test_filter() {
     minify_test(
-      ".foo { mask: url(masks.svg#star) repeat-x }",
-      ".foo{mask:url(masks.svg#star) repeat-x}",
+      ".foo { filter: url('filters.svg#filter-id'); }",
+      ".foo{filter:url(filters.svg#filter-id)}",
     );
+    minify_test(".foo { filter: blur(5px); }", ".foo{filter:blur(5px)}");
+    minify_test(".foo { filter: blur(0px); }", ".foo{filter:blur()}");
+    minify_test(".foo { filter: brightness(10%); }", ".foo{filter:brightness(10%)}");
+    minify_test(".foo { filter: brightness(100%); }", ".foo{filter:brightness()}");
     minify_test(
-      ".foo { mask: url(masks.svg#star) stroke-box }",
-      ".foo{mask:url(masks.svg#star) stroke-box}",
+      ".foo { filter: drop-shadow(16px 16px 20px yellow); }",
+      ".foo{filter:drop-shadow(16px 16px 20px #ff0)}",
     );
     minify_test(
-      ".foo { mask: url(masks.svg#star) stroke-box stroke-box }",
-      ".foo{mask:url(masks.svg#star) stroke-box}",
+      ".foo { filter: contrast(175%) brightness(3%); }",
+      ".foo{filter:contrast(175%)brightness(3%)}",
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) border-box }",
-      ".foo{mask:url(masks.svg#star)}",
+    minify_test(".foo { filter: hue-rotate(0) }", ".foo{filter:hue-rotate()}");
+
+    prefix_test(
+      ".foo { filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: blur(5px);
+          filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { mask: url(masks.svg#star) left / 16px repeat-y, url(masks.svg#circle) right / 16px repeat-y }",
-      ".foo{mask:url(masks.svg#star) 0/16px repeat-y,url(masks.svg#circle) 100%/16px repeat-y}",
+
+    prefix_test(
+      ".foo { filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
     );
 
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25; }",
-      ".foo{mask-border:url(border-mask.png) 25}",
+    prefix_test(
+      ".foo { backdrop-filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          backdrop-filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        chrome: Some(80 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space alpha; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space}",
+
+    prefix_test(
+      ".foo { backdrop-filter: blur(5px) }",
+      indoc! { r#"
+        .foo {
+          -webkit-backdrop-filter: blur(5px);
+          backdrop-filter: blur(5px);
+        }
+      "#},
+      Browsers {
+        safari: Some(15 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') 25 / 35px / 12px space luminance; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-backdrop-filter: blur(8px);
+        backdrop-filter: blur(8px);
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        -webkit-backdrop-filter: blur(8px);
+        backdrop-filter: blur(8px);
+      }
+      "#},
+      Browsers {
+        safari: Some(16 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { mask-border: url('border-mask.png') luminance 25 / 35px / 12px space; }",
-      ".foo{mask-border:url(border-mask.png) 25/35px/12px space luminance}",
+
+    prefix_test(
+      ".foo { filter: var(--foo) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: var(--foo);
+          filter: var(--foo);
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
     );
 
-    minify_test(
-      ".foo { clip-path: url('clip.svg#star'); }",
-      ".foo{clip-path:url(clip.svg#star)}",
+    prefix_test(
+      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          -webkit-filter: drop-shadow(16px 16px 20px #b32323);
+          filter: drop-shadow(16px 16px 20px #b32323);
+          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39));
+        }
+      "#},
+      Browsers {
+        chrome: Some(20 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(".foo { clip-path: margin-box; }", ".foo{clip-path:margin-box}");
-    minify_test(
-      ".foo { clip-path: inset(100px 50px); }",
-      ".foo{clip-path:inset(100px 50px)}",
+
+    prefix_test(
+      ".foo { filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          filter: contrast(175%) drop-shadow(16px 16px 20px #b32323);
+          filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { clip-path: inset(100px 50px round 5px); }",
-      ".foo{clip-path:inset(100px 50px round 5px)}",
+
+    prefix_test(
+      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px yellow) }",
+      indoc! { r#"
+        .foo {
+          filter: drop-shadow(16px 16px 20px #b32323) drop-shadow(16px 16px 20px #ff0);
+          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px #ff0);
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(
-      ".foo { clip-path: inset(100px 50px round 5px 5px 5px 5px); }",
-      ".foo{clip-path:inset(100px 50px round 5px)}",
+
+    prefix_test(
+      ".foo { filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
+      indoc! { r#"
+        .foo {
+          filter: var(--foo) drop-shadow(16px 16px 20px #b32323);
+        }
+
+        @supports (color: lab(0% 0 0)) {
+          .foo {
+            filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(4 << 16),
+        ..Browsers::default()
+      },
     );
-    minify_test(".foo { clip-path: circle(50px); }", ".foo{clip-path:circle(50px)}");
+  }
+
+  #[test]
+  fn test_viewport() {
     minify_test(
-      ".foo { clip-path: circle(50px at center center); }",
-      ".foo{clip-path:circle(50px)}",
+      r#"
+    @viewport {
+      width: 100vw;
+    }"#,
+      "@viewport{width:100vw}",
     );
     minify_test(
-      ".foo { clip-path: circle(50px at 50% 50%); }",
-      ".foo{clip-path:circle(50px)}",
+      r#"
+    @-ms-viewport {
+      width: device-width;
+    }"#,
+      "@-ms-viewport{width:device-width}",
     );
+  }
+
+  #[test]
+  fn test_at_scope() {
     minify_test(
-      ".foo { clip-path: circle(50px at 0 100px); }",
-      ".foo{clip-path:circle(50px at 0 100px)}",
+      r#"
+      @scope {
+        .foo {
+          display: flex;
+        }
+      }
+      "#,
+      "@scope{.foo{display:flex}}",
     );
     minify_test(
-      ".foo { clip-path: circle(closest-side at 0 100px); }",
-      ".foo{clip-path:circle(at 0 100px)}",
+      r#"
+      @scope {
+        :scope {
+          display: flex;
+          color: lightblue;
+        }
+      }"#,
+      "@scope{:scope{color:#add8e6;display:flex}}",
     );
     minify_test(
-      ".foo { clip-path: circle(farthest-side at 0 100px); }",
-      ".foo{clip-path:circle(farthest-side at 0 100px)}",
+      r#"
+      @scope (.light-scheme) {
+        a { color: yellow; }
+      }
+      "#,
+      "@scope(.light-scheme){a{color:#ff0}}",
     );
     minify_test(
-      ".foo { clip-path: circle(closest-side at 50% 50%); }",
-      ".foo{clip-path:circle()}",
+      r#"
+      @scope (.media-object) to (.content > *) {
+        a { color: yellow; }
+      }
+      "#,
+      "@scope(.media-object) to (.content>*){a{color:#ff0}}",
     );
     minify_test(
-      ".foo { clip-path: ellipse(50px 60px at 0 10% 20%); }",
-      ".foo{clip-path:ellipse(50px 60px at 0 10% 20%)}",
+      r#"
+      @scope to (.content > *) {
+        a { color: yellow; }
+      }
+      "#,
+      "@scope to (.content>*){a{color:#ff0}}",
     );
     minify_test(
-      ".foo { clip-path: ellipse(50px 60px at center center); }",
-      ".foo{clip-path:ellipse(50px 60px)}",
+      r#"
+      @scope (#my-component) {
+        & { color: yellow; }
+      }
+      "#,
+      "@scope(#my-component){&{color:#ff0}}",
     );
     minify_test(
-      ".foo { clip-path: ellipse(closest-side closest-side at 50% 50%); }",
-      ".foo{clip-path:ellipse()}",
+      r#"
+      @scope (.parent-scope) {
+        @scope (:scope > .child-scope) to (:scope .limit) {
+          .content { color: yellow; }
+        }
+      }
+      "#,
+      "@scope(.parent-scope){@scope(:scope>.child-scope) to (:scope .limit){.content{color:#ff0}}}",
     );
     minify_test(
-      ".foo { clip-path: ellipse(closest-side closest-side at 10% 20%); }",
-      ".foo{clip-path:ellipse(at 10% 20%)}",
+      r#"
+      .foo {
+        @scope (.bar) {
+          color: yellow;
+        }
+      }
+      "#,
+      ".foo{@scope(.bar){&{color:#ff0}}}",
     );
-    minify_test(
-      ".foo { clip-path: ellipse(farthest-side closest-side at 10% 20%); }",
-      ".foo{clip-path:ellipse(farthest-side closest-side at 10% 20%)}",
+    nesting_test(
+      r#"
+      .foo {
+        @scope (.bar) {
+          color: yellow;
+        }
+      }
+      "#,
+      indoc! {r#"
+        @scope (.bar) {
+          :scope {
+            color: #ff0;
+          }
+        }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: polygon(50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+    nesting_test(
+      r#"
+      .parent {
+        color: blue;
+
+        @scope (& > .scope) to (& .limit) {
+          & .content {
+            color: yellow;
+          }
+        }
+      }
+      "#,
+      indoc! {r#"
+        .parent {
+          color: #00f;
+        }
+
+        @scope (.parent > .scope) to (.parent > .scope .limit) {
+          :scope .content {
+            color: #ff0;
+          }
+        }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: polygon(nonzero, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(50% 0%,100% 50%,50% 100%,0% 50%)}",
+  }
+
+  #[test]
+  fn test_custom_media() {
+    custom_media_test(
+      r#"
+      @custom-media --modern (color), (hover);
+
+      @media (--modern) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media ((color) or (hover)) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: polygon(evenodd, 50% 0%, 100% 50%, 50% 100%, 0% 50%); }",
-      ".foo{clip-path:polygon(evenodd,50% 0%,100% 50%,50% 100%,0% 50%)}",
+
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+
+      @media (--color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: padding-box circle(50px at 0 100px); }",
-      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+
+    custom_media_test(
+      r#"
+      @custom-media --a (color);
+      @custom-media --b (--a);
+
+      @media (--b) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) and (width > 1024px) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: circle(50px at 0 100px) padding-box; }",
-      ".foo{clip-path:circle(50px at 0 100px) padding-box}",
+
+    custom_media_test(
+      r#"
+      @custom-media --not-color not (color);
+
+      @media not (--not-color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
     );
-    minify_test(
-      ".foo { clip-path: circle(50px at 0 100px) border-box; }",
-      ".foo{clip-path:circle(50px at 0 100px)}",
+
+    custom_media_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media (--color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
     );
 
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          -webkit-clip-path: circle(50px);
-          clip-path: circle(50px);
+    custom_media_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media print and (--color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --not-color-print not print and (color);
+
+      @media not print and (--not-color-print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print and (color) {
+        .a {
+          color: green;
+        }
+      }
+      "#},
+    );
+
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(30 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          clip-path: circle(50px);
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media not (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          -webkit-clip-path: circle(50px);
-          clip-path: circle(50px);
+    custom_media_test(
+      r#"
+      @custom-media --print not print;
+
+      @media not (--print) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        safari: Some(8 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { clip-path: circle(50px); }",
-      indoc! { r#"
-        .foo {
-          clip-path: circle(50px);
+    custom_media_test(
+      r#"
+      @custom-media --print print;
+
+      @media ((--print)) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { fill: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          fill: #ee00be;
-          fill: color(display-p3 .972962 -.362078 .804206);
-          fill: lch(50.998% 135.363 338);
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --print print;
+
+      @media (--print) and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color) {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { stroke: lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          stroke: #ee00be;
-          stroke: color(display-p3 .972962 -.362078 .804206);
-          stroke: lch(50.998% 135.363 338);
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --not-print not print;
+
+      @media (--not-print) and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media not print and (color) {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { fill: url(#foo) lch(50.998% 135.363 338) }",
-      indoc! { r##"
-        .foo {
-          fill: url("#foo") #ee00be;
-          fill: url("#foo") color(display-p3 .972962 -.362078 .804206);
-          fill: url("#foo") lch(50.998% 135.363 338);
-        }
-      "##},
-      Browsers {
-        chrome: Some(90 << 16),
-        safari: Some(14 << 16),
-        ..Browsers::default()
-      },
-    );
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --screen screen;
+      @custom-media --print print;
 
-    prefix_test(
-      ".foo { fill: var(--url) lch(50.998% 135.363 338) }",
-      indoc! { r#"
-        .foo {
-          fill: var(--url) #ee00be;
+      @media (--print) and (--color), (--screen) and (--color) {
+        .a {
+          color: green;
         }
-
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            fill: var(--url) lab(50.998% 125.506 -50.7078);
-          }
+      }
+      "#,
+      indoc! {r#"
+      @media print and (color), screen and (color) {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff));
-          -webkit-mask-image: -webkit-linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
-          mask-image: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+    custom_media_test(
+      r#"
+      @custom-media --color print and (color), print and (script);
+
+      @media (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media print and ((color) or (script)) {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: linear-gradient(#ff0f0e, #7773ff);
-          mask-image: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+      @custom-media --not-color not all and (--color);
+
+      @media (--not-color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+        @media not all and (color) {
+          .a {
+            color: green;
+          }
         }
       "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { mask-image: linear-gradient(red, green) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: linear-gradient(red, green);
-          mask-image: linear-gradient(red, green);
+    custom_media_test(
+      r#"
+      @custom-media --color (color);
+
+      @media not all and (--color) {
+        .a {
+          color: green;
+        }
+      }
+      "#,
+      indoc! {r#"
+        @media not all and (color) {
+          .a {
+            color: green;
+          }
         }
       "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { -webkit-mask-image: url(x.svg); mask-image: url(x.svg); }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: url("x.svg");
-          mask-image: url("x.svg");
+    custom_media_test(
+      r#"
+      @media (--print) {
+        .a {
+          color: green;
         }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
-    );
+      }
 
-    prefix_test(
-      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
-          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          mask: linear-gradient(#ff0f0e, #7773ff) 40px 20px;
-          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
-          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px;
+      @custom-media --print print;
+      "#,
+      indoc! {r#"
+      @media print {
+        .a {
+          color: green;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { mask: -webkit-linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px 20px }",
-      indoc! { r#"
+    custom_media_test(
+      r#"
+      @custom-media --not-width not (min-width: 300px);
+      @media screen and ((prefers-color-scheme: dark) or (--not-width)) {
         .foo {
-          -webkit-mask: -webkit-gradient(linear, 0 0, 0 100%, from(#ff0f0e), to(#7773ff)) 40px 20px;
-          -webkit-mask: -webkit-linear-gradient(#ff0f0e, #7773ff) 40px 20px;
+          order: 6;
+        }
+      }
+      "#,
+      indoc! {r#"
+      @media screen and ((prefers-color-scheme: dark) or (not (width >= 300px))) {
+        .foo {
+          order: 6;
         }
+      }
       "#},
-      Browsers {
-        chrome: Some(8 << 16),
-        ..Browsers::default()
-      },
     );
 
-    prefix_test(
-      ".foo { mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 40px var(--foo) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
-          mask: linear-gradient(#ff0f0e, #7773ff) 40px var(--foo);
+    fn custom_media_error_test(source: &str, err: Error<MinifyErrorKind>) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          filename: "test.css".into(),
+          flags: ParserFlags::CUSTOM_MEDIA,
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet.minify(MinifyOptions {
+        targets: Browsers {
+          chrome: Some(95 << 16),
+          ..Browsers::default()
         }
+        .into(),
+        ..MinifyOptions::default()
+      });
+      assert_eq!(res, Err(err))
+    }
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            -webkit-mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
-            mask: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) 40px var(--foo);
-          }
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media screen and (--color-print) {
+        .a {
+          color: green;
         }
-      "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
-      ".foo { mask: url(masks.svg#star) luminance }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask: url("masks.svg#star");
-          -webkit-mask-source-type: luminance;
-          mask: url("masks.svg#star") luminance;
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+
+      @media not print and (--color-print) {
+        .a {
+          color: green;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      }
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
-      ".foo { mask-image: url(masks.svg#star) }",
-      indoc! { r#"
-        .foo {
-          -webkit-mask-image: url("masks.svg#star");
-          mask-image: url("masks.svg#star");
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+    custom_media_error_test(
+      r#"
+      @custom-media --color-print print and (color);
+      @custom-media --color-screen screen and (color);
+
+      @media (--color-print) or (--color-screen) {}
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask-image: url(masks.svg#star);
-          mask-position: 25% 75%;
-          mask-size: cover;
-          mask-repeat: no-repeat;
-          mask-clip: padding-box;
-          mask-origin: content-box;
-          mask-composite: subtract;
-          mask-mode: luminance;
-        }
+      @custom-media --color-print print and (color);
+      @custom-media --color-screen screen and (color);
+
+      @media (--color-print) and (--color-screen) {}
       "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: url("masks.svg#star") 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-position: 25% 75%;
-          mask-size: cover;
-          mask-repeat: no-repeat;
-          mask-clip: padding-box;
-          mask-origin: content-box;
-          mask-composite: subtract;
-          mask-mode: luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: linear-gradient(#ff0f0e, #7773ff) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-          -webkit-mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box;
-          -webkit-mask-composite: source-out;
-          -webkit-mask-source-type: luminance;
-          mask: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25% 75% / cover no-repeat content-box padding-box subtract luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      @custom-media --screen screen;
+      @custom-media --print print;
+
+      @media (--print) and (--screen) {}
+      "#,
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
       },
     );
 
-    test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask: none center / 100% no-repeat;
-          mask-image: var(--svg);
+      @custom-media --not-print not print and (color);
+      @custom-media --not-screen not screen and (color);
+
+      @media ((script) or ((--not-print) and (--not-screen))) {
+        .a {
+          color: green;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          mask: none center / 100% no-repeat;
-          mask-image: var(--svg);
-        }
-      "#},
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 2,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
+      },
     );
 
-    prefix_test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask-composite: subtract;
+      @custom-media --color screen and (color), print and (color);
+
+      @media (--color) {
+        .a {
+          color: green;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-composite: source-out;
-          mask-composite: subtract;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      Error {
+        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
+          custom_media_loc: Location {
+            source_index: 0,
+            line: 1,
+            column: 7,
+          },
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 3,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask-mode: luminance;
+      @media (--not-defined) {
+        .a {
+          color: green;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-source-type: luminance;
-          mask-mode: luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      Error {
+        kind: MinifyErrorKind::CustomMediaNotDefined {
+          name: "--not-defined".into(),
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 1,
+          column: 7,
+        }),
       },
     );
 
-    prefix_test(
+    custom_media_error_test(
       r#"
-        .foo {
-          mask-border: url('border-mask.png') 25 / 35px / 12px space luminance;
+      @custom-media --circular-mq-a (--circular-mq-b);
+      @custom-media --circular-mq-b (--circular-mq-a);
+
+      @media (--circular-mq-a) {
+        body {
+          order: 3;
         }
+      }
       "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("border-mask.png") 25 / 35px / 12px space;
-          mask-border: url("border-mask.png") 25 / 35px / 12px space luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      Error {
+        kind: MinifyErrorKind::CircularCustomMedia {
+          name: "--circular-mq-a".into(),
+        },
+        loc: Some(ErrorLocation {
+          filename: "test.css".into(),
+          line: 4,
+          column: 7,
+        }),
       },
     );
+  }
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space;
-          mask-border: linear-gradient(#ff0f0e, #7773ff) 25 / 35px / 12px space luminance;
-          -webkit-mask-box-image: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space;
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) 25 / 35px / 12px space luminance;
+  #[test]
+  fn test_disabled_drafts() {
+    let source = r#"
+      .foo {
+        color: rgb(from red r g 255);
+        background: color-mix(in srgb, red, blue);
+
+        &:hover {
+          color: red;
         }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+      }
+    "#;
+
+    minify_test_with_options(
+      source,
+      ".foo{color:#f0f;background:purple;&:hover{color:red}}",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::empty(),
+        ..ParserOptions::default()
+      },
+    );
+    minify_test_with_options(
+      ".foo { color: rgb(from red r g 255); background: color-mix(in srgb, red, blue) }",
+      ".foo{color:rgb(from red r g 255);background:purple}",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::RELATIVE_COLORS,
+        ..ParserOptions::default()
+      },
+    );
+    minify_test_with_options(
+      ".foo { color: rgb(from red r g 255); background: color-mix(in srgb, red, blue) }",
+      ".foo{color:#f0f;background:color-mix(in srgb,red,blue)}",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::COLOR_MIX,
+        ..ParserOptions::default()
+      },
+    );
+    minify_test_with_options(
+      ".foo { --foo: color-mix(in srgb, red, blue); border: 1px solid rgb(from red r g b / 50%) }",
+      ".foo{--foo:color-mix(in srgb,red,blue);border:1px solid rgb(from red r g b/50%)}",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::RELATIVE_COLORS | DraftSyntax::COLOR_MIX,
+        ..ParserOptions::default()
+      },
+    );
+    minify_test_with_options(
+      ".foo { color: rgb(255 0 0) }",
+      ".foo{color:red}",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::RELATIVE_COLORS | DraftSyntax::COLOR_MIX,
+        ..ParserOptions::default()
+      },
+    );
+
+    match StyleSheet::parse(
+      source,
+      ParserOptions {
+        disabled_drafts: DraftSyntax::NESTING,
+        ..ParserOptions::default()
+      },
+    ) {
+      Ok(_) => unreachable!(),
+      Err(e) => assert_eq!(e.kind, ParserError::InvalidNesting),
+    }
+
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { color: red } .bar { &:hover { color: blue } } .baz { @media print { color: green } }",
+      ParserOptions {
+        disabled_drafts: DraftSyntax::NESTING,
+        error_recovery: true,
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
       },
+    )
+    .unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{color:red}");
+    assert_eq!(warnings.unwrap().read().unwrap().len(), 2);
+  }
+
+  #[test]
+  fn test_dependencies() {
+    fn dep_test(source: &str, expected: &str, deps: Vec<(&str, &str)>) {
+      let mut stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          filename: "test.css".into(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          analyze_dependencies: Some(Default::default()),
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+      let dependencies = res.dependencies.unwrap();
+      assert_eq!(dependencies.len(), deps.len());
+      for (i, (url, placeholder)) in deps.into_iter().enumerate() {
+        match &dependencies[i] {
+          Dependency::Url(dep) => {
+            assert_eq!(dep.url, url);
+            assert_eq!(dep.placeholder, placeholder);
+          }
+          Dependency::Import(dep) => {
+            assert_eq!(dep.url, url);
+            assert_eq!(dep.placeholder, placeholder);
+          }
+        }
+      }
+    }
+
+    fn dep_error_test(source: &str, error: PrinterErrorKind) {
+      let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      let res = stylesheet.to_css(PrinterOptions {
+        analyze_dependencies: Some(Default::default()),
+        ..PrinterOptions::default()
+      });
+      match res {
+        Err(e) => assert_eq!(e.kind, error),
+        _ => unreachable!(),
+      }
+    }
+
+    dep_test(
+      ".foo { background: image-set('./img12x.png', './img21x.png' 2x)}",
+      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
+      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-source: linear-gradient(#ff0f0e, #7773ff);
-          mask-border-source: linear-gradient(#ff0f0e, #7773ff);
-          -webkit-mask-box-image-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-          mask-border-source: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364));
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { background: image-set(url(./img12x.png), url('./img21x.png') 2x)}",
+      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
+      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-source: url(foo.png);
-          mask-border-slice: 10 40 10 40;
-          mask-border-width: 10px;
-          mask-border-outset: 0;
-          mask-border-repeat: round round;
-          mask-border-mode: luminance;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
-          mask-border: url("foo.png") 10 40 / 10px round luminance;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { --test: url(/foo.png) }",
+      ".foo{--test:url(\"lDnnrG\")}",
+      vec![("/foo.png", "lDnnrG")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          -webkit-mask-box-image-source: url(foo.png);
-          -webkit-mask-box-image-slice: 10 40 10 40;
-          -webkit-mask-box-image-width: 10px;
-          -webkit-mask-box-image-outset: 0;
-          -webkit-mask-box-image-repeat: round round;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: url("foo.png") 10 40 / 10px round;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { --test: url(\"/foo.png\") }",
+      ".foo{--test:url(\"lDnnrG\")}",
+      vec![("/foo.png", "lDnnrG")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-slice: 10 40 10 40;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-slice: 10 40;
-          mask-border-slice: 10 40;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { --test: url(\"http://example.com/foo.png\") }",
+      ".foo{--test:url(\"3X1zSW\")}",
+      vec![("http://example.com/foo.png", "3X1zSW")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border-slice: var(--foo);
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image-slice: var(--foo);
-          mask-border-slice: var(--foo);
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { --test: url(\"data:image/svg+xml;utf8,<svg></svg>\") }",
+      ".foo{--test:url(\"-vl-rG\")}",
+      vec![("data:image/svg+xml;utf8,<svg></svg>", "-vl-rG")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          mask-border: linear-gradient(lch(56.208% 136.76 46.312), lch(51% 135.366 301.364)) var(--foo);
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          -webkit-mask-box-image: linear-gradient(#ff0f0e, #7773ff) var(--foo);
-          mask-border: linear-gradient(#ff0f0e, #7773ff) var(--foo);
-        }
+    dep_test(
+      ".foo { background: url(\"foo.png\") var(--test) }",
+      ".foo{background:url(\"Vwkwkq\") var(--test)}",
+      vec![("foo.png", "Vwkwkq")],
+    );
 
-        @supports (color: lab(0% 0 0)) {
-          .foo {
-            -webkit-mask-box-image: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
-            mask-border: linear-gradient(lab(56.208% 94.4644 98.8928), lab(51% 70.4544 -115.586)) var(--foo);
-          }
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_error_test(
+      ".foo { --test: url(\"foo.png\") }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          transition: mask 200ms;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition: -webkit-mask .2s, mask .2s;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_error_test(
+      ".foo { --test: url(foo.png) }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          transition: mask-border 200ms;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition: -webkit-mask-box-image .2s, mask-border .2s;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
+    dep_error_test(
+      ".foo { --test: url(./foo.png) }",
+      PrinterErrorKind::AmbiguousUrlInCustomProperty {
+        url: "./foo.png".into(),
       },
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          transition-property: mask;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask, mask;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { behavior: url(#foo) }",
+      ".foo{behavior:url(\"Zn9-2q\")}",
+      vec![("#foo", "Zn9-2q")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          transition-property: mask-border;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask-box-image, mask-border;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      ".foo { --foo: url(#foo) }",
+      ".foo{--foo:url(\"Zn9-2q\")}",
+      vec![("#foo", "Zn9-2q")],
     );
 
-    prefix_test(
-      r#"
-        .foo {
-          transition-property: mask-composite, mask-mode;
-        }
-      "#,
-      indoc! { r#"
-        .foo {
-          transition-property: -webkit-mask-composite, mask-composite, -webkit-mask-source-type, mask-mode;
-        }
-    "#},
-      Browsers {
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
+    dep_test(
+      "@import \"test.css\"; .foo { color: red }",
+      "@import \"hHsogW\";.foo{color:red}",
+      vec![("test.css", "hHsogW")],
+    );
+  }
+
+  #[test]
+  fn test_analyze_dependencies() {
+    use crate::dependencies::{DependencyKind, DependencyReference, Location, SourceRange};
+
+    fn analyze(source: &str) -> Vec<DependencyReference> {
+      let stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          filename: "test.css".into(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.analyze_dependencies().unwrap()
+    }
+
+    fn range(line: u32, start: u32, end: u32) -> SourceRange {
+      SourceRange {
+        file_path: "test.css".into(),
+        start: Location { line, column: start },
+        end: Location { line, column: end },
+      }
+    }
+
+    let deps = analyze(indoc! {r#"
+      @import "a.css" layer(base) supports(display: grid) screen;
+      @import url(b.css) layer;
+      .foo { background: url(c.png) }
+      .bar { background: image-set("d.png" 1x, url(e.png) 2x) }
+      .baz { --bg: url(f.png) }
+    "#});
+
+    assert_eq!(
+      deps,
+      vec![
+        DependencyReference {
+          kind: DependencyKind::Import,
+          specifier: "a.css".into(),
+          layer: Some("base".into()),
+          supports: Some("(display: grid)".into()),
+          media: Some("screen".into()),
+          loc: range(1, 9, 15),
+        },
+        DependencyReference {
+          kind: DependencyKind::Import,
+          specifier: "b.css".into(),
+          layer: Some("".into()),
+          supports: None,
+          media: None,
+          loc: range(2, 9, 15),
+        },
+        DependencyReference {
+          kind: DependencyKind::Url,
+          specifier: "c.png".into(),
+          layer: None,
+          supports: None,
+          media: None,
+          loc: range(3, 24, 28),
+        },
+        DependencyReference {
+          kind: DependencyKind::ImageSet,
+          specifier: "d.png".into(),
+          layer: None,
+          supports: None,
+          media: None,
+          loc: range(4, 34, 38),
+        },
+        DependencyReference {
+          kind: DependencyKind::ImageSet,
+          specifier: "e.png".into(),
+          layer: None,
+          supports: None,
+          media: None,
+          loc: range(4, 46, 50),
+        },
+        DependencyReference {
+          kind: DependencyKind::Url,
+          specifier: "f.png".into(),
+          layer: None,
+          supports: None,
+          media: None,
+          loc: range(5, 18, 22),
+        },
+      ]
     );
+
+    // Urls are not rewritten.
+    let stylesheet = StyleSheet::parse(".foo { background: url(c.png) }", ParserOptions::default()).unwrap();
+    stylesheet.analyze_dependencies().unwrap();
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.code, ".foo {\n  background: url(\"c.png\");\n}\n");
   }
 
+  #[cfg(feature = "visitor")]
   #[test]
-  fn test_filter() {
-    minify_test(
-      ".foo { filter: url('filters.svg#filter-id'); }",
-      ".foo{filter:url(filters.svg#filter-id)}",
+  fn test_stats() {
+    use crate::selector::Specificity;
+
+    let source = indoc! {r#"
+      .a, #b { color: red; z-index: 10; font-family: Helvetica, "Open Sans", sans-serif }
+      @media (min-width: 100px) {
+        .c:hover { background: #00f linear-gradient(red, rgb(0 0 255 / .5)); z-index: 1 }
+      }
+      @keyframes fade {
+        from { color: red }
+        to { color: transparent }
+      }
+      .d { --accent: #0f0; font: 12px Arial; & .e { z-index: auto } }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let stats = stylesheet.stats().unwrap();
+
+    assert_eq!(
+      stats.rules.into_iter().collect::<Vec<_>>(),
+      vec![("keyframes", 1), ("media", 1), ("style", 4)]
     );
-    minify_test(".foo { filter: blur(5px); }", ".foo{filter:blur(5px)}");
-    minify_test(".foo { filter: blur(0px); }", ".foo{filter:blur()}");
-    minify_test(".foo { filter: brightness(10%); }", ".foo{filter:brightness(10%)}");
-    minify_test(".foo { filter: brightness(100%); }", ".foo{filter:brightness()}");
-    minify_test(
-      ".foo { filter: drop-shadow(16px 16px 20px yellow); }",
-      ".foo{filter:drop-shadow(16px 16px 20px #ff0)}",
+    assert_eq!(stats.declarations, 10);
+    assert_eq!(stats.declarations_per_rule, vec![3, 2, 2, 1]);
+    assert_eq!(
+      stats.specificity,
+      vec![
+        Specificity {
+          ids: 0,
+          classes: 1,
+          elements: 0
+        },
+        Specificity {
+          ids: 1,
+          classes: 0,
+          elements: 0
+        },
+        Specificity {
+          ids: 0,
+          classes: 2,
+          elements: 0
+        },
+        Specificity {
+          ids: 0,
+          classes: 1,
+          elements: 0
+        },
+        Specificity {
+          ids: 0,
+          classes: 2,
+          elements: 0
+        },
+      ]
     );
-    minify_test(
-      ".foo { filter: contrast(175%) brightness(3%); }",
-      ".foo{filter:contrast(175%)brightness(3%)}",
+    assert_eq!(
+      stats.colors.into_iter().collect::<Vec<_>>(),
+      vec!["#0000", "#0000ff80", "#00f", "#0f0", "red"]
     );
-    minify_test(".foo { filter: hue-rotate(0) }", ".foo{filter:hue-rotate()}");
-
-    prefix_test(
-      ".foo { filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: blur(5px);
-          filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
+    assert_eq!(
+      stats.font_families.into_iter().collect::<Vec<_>>(),
+      vec!["Arial", "Helvetica", "Open Sans", "sans-serif"]
     );
-
-    prefix_test(
-      ".foo { filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
-      },
+    assert_eq!(stats.z_indexes.into_iter().collect::<Vec<_>>(), vec![1, 10]);
+    assert_eq!(
+      stats.size,
+      stylesheet.to_css(PrinterOptions::default()).unwrap().code.len()
     );
+    assert!(stats.minified_size < stats.size);
+  }
 
-    prefix_test(
-      ".foo { backdrop-filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          backdrop-filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        chrome: Some(80 << 16),
-        ..Browsers::default()
-      },
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_browser_support() {
+    let source = indoc! {r#"
+      .a:has(> img) { color: oklch(60% 0.1 200); width: 10dvh; height: 10dvh }
+      @media (width >= 600px) {
+        .b { padding: env(safe-area-inset-top) }
+      }
+      .c { color: red }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let report = stylesheet.browser_support();
+
+    let features: Vec<_> = report
+      .features
+      .iter()
+      .map(|usage| (usage.feature.as_str(), usage.loc.line))
+      .collect();
+    assert_eq!(
+      features,
+      vec![
+        (":has()", 0),
+        ("oklch()", 0),
+        ("dvh unit", 0),
+        ("media query range syntax", 1),
+        ("env()", 2),
+      ]
     );
 
-    prefix_test(
-      ".foo { backdrop-filter: blur(5px) }",
-      indoc! { r#"
-        .foo {
-          -webkit-backdrop-filter: blur(5px);
-          backdrop-filter: blur(5px);
-        }
-      "#},
-      Browsers {
-        safari: Some(15 << 16),
-        ..Browsers::default()
-      },
+    let version = |versions: &Vec<crate::browser_support::BrowserVersion>, browser: &str| {
+      versions.iter().find(|v| v.browser == browser).map(|v| v.version.clone())
+    };
+
+    let has = &report.features[0].supported_since;
+    assert_eq!(version(has, "chrome"), Some(Some("105".into())));
+    assert_eq!(version(has, "safari"), Some(Some("15.4".into())));
+    assert_eq!(version(has, "ie"), Some(None));
+
+    // The minimum versions support every feature.
+    assert_eq!(version(&report.minimum_versions, "ie"), Some(None));
+    assert_eq!(version(&report.minimum_versions, "chrome"), Some(Some("111".into())));
+    assert_eq!(version(&report.minimum_versions, "firefox"), Some(Some("121".into())));
+
+    let stylesheet = StyleSheet::parse(".a > .b { color: red }", ParserOptions::default()).unwrap();
+    let report = stylesheet.browser_support();
+    assert!(report.features.is_empty());
+    assert!(report.minimum_versions.is_empty());
+  }
+
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_analyze_custom_properties() {
+    use crate::custom_properties::CustomPropertyLocation;
+
+    let source = indoc! {r#"
+      @property --size {
+        syntax: '<length>';
+        inherits: false;
+        initial-value: 0px;
+      }
+      :root { --a: red; --b: blue; --c: green }
+      .foo { color: var(--b); width: var(--size) }
+      .bar { color: var(--missing); background: var(--other, red) }
+      @media (min-width: 100px) {
+        .baz { border-color: var(--c, var(--nope)) }
+      }
+      .qux { --d: var(--a) }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let report = stylesheet.analyze_custom_properties();
+
+    let names = |list: &Vec<CustomPropertyLocation>| list.iter().map(|l| l.name.clone()).collect::<Vec<_>>();
+    assert_eq!(names(&report.unused), vec!["--d"]);
+    assert_eq!(names(&report.undefined), vec!["--missing", "--nope"]);
+    assert_eq!(report.undefined[0].loc.line, 7);
+    assert_eq!(report.unused[0].loc.line, 11);
+
+    let stylesheet =
+      StyleSheet::parse(".foo { --a: 1; width: calc(var(--a) * 1px) }", ParserOptions::default()).unwrap();
+    assert_eq!(stylesheet.analyze_custom_properties(), Default::default());
+  }
+
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_color_usage() {
+    let source = indoc! {r#"
+      :root { --accent: #f00 }
+      .a { color: red; border: 1px solid rgb(255 0 0) }
+      .b { background: linear-gradient(#0000ff80, currentColor); color: hsl(240deg 100% 50% / .5) }
+      @media (min-width: 100px) {
+        .c { outline-color: oklch(62.8% 0.25768 29.23) }
+      }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let colors = stylesheet
+      .color_usage()
+      .into_iter()
+      .map(|usage| {
+        (
+          usage.color,
+          usage
+            .usages
+            .into_iter()
+            .map(|r| (r.value, r.property, r.loc.line))
+            .collect::<Vec<_>>(),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      colors,
+      vec![
+        (
+          "#ff0000".into(),
+          vec![
+            ("red".into(), "--accent".into(), 0),
+            ("red".into(), "color".into(), 1),
+            ("red".into(), "border".into(), 1),
+            ("oklch(62.8% .25768 29.23)".into(), "outline-color".into(), 4),
+          ]
+        ),
+        (
+          "#0000ff80".into(),
+          vec![
+            ("#0000ff80".into(), "background".into(), 2),
+            ("#0000ff80".into(), "color".into(), 2),
+          ]
+        ),
+      ]
     );
-    prefix_test(
-      r#"
-      .foo {
-        -webkit-backdrop-filter: blur(8px);
-        backdrop-filter: blur(8px);
+  }
+
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_font_usage() {
+    let source = indoc! {r#"
+      @font-face {
+        font-family: Inter;
+        src: url(inter-latin.woff2);
+        unicode-range: U+0-FF, U+131;
       }
-      "#,
-      indoc! {r#"
-      .foo {
-        -webkit-backdrop-filter: blur(8px);
-        backdrop-filter: blur(8px);
+      @font-face {
+        font-family: "Inter";
+        src: url(inter-latin-ext.woff2);
+        unicode-range: U+100-24F;
       }
-      "#},
-      Browsers {
-        safari: Some(16 << 16),
-        ..Browsers::default()
-      },
+      @font-face {
+        font-family: Unused;
+        src: url(unused.woff2);
+      }
+      .a { font-family: inter, Helvetica, sans-serif }
+      .b { font: 12px "Inter" }
+      @media print {
+        .c { font-family: Georgia, serif }
+      }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let report = stylesheet.font_usage();
+
+    let families = report
+      .families
+      .iter()
+      .map(|usage| {
+        (
+          usage.family.as_str(),
+          usage.references.iter().map(|loc| loc.line).collect::<Vec<_>>(),
+          usage.font_faces.iter().map(|loc| loc.line).collect::<Vec<_>>(),
+          usage.unicode_range.clone(),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      families,
+      vec![
+        ("inter", vec![14, 15], vec![0, 5], vec!["U+0-24F".to_string()]),
+        ("Helvetica", vec![14], vec![], vec![]),
+        ("Georgia", vec![17], vec![], vec![]),
+      ]
     );
+    assert_eq!(report.unused_font_faces.len(), 1);
+    assert_eq!(report.unused_font_faces[0].family, "Unused");
+    assert_eq!(report.unused_font_faces[0].loc.line, 10);
+  }
 
-    prefix_test(
-      ".foo { filter: var(--foo) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: var(--foo);
-          filter: var(--foo);
-        }
-      "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
+  #[test]
+  fn test_interpolate() {
+    use crate::interpolate::interpolate;
+    use crate::properties::{Property, PropertyId};
+
+    fn test(name: &str, from: &str, to: &str, progress: f32, expected: Option<&str>) {
+      let from = Property::parse_string(PropertyId::from(name), from, ParserOptions::default()).unwrap();
+      let to = Property::parse_string(PropertyId::from(name), to, ParserOptions::default()).unwrap();
+      let res =
+        interpolate(&from, &to, progress).map(|v| v.value_to_css_string(PrinterOptions::default()).unwrap());
+      assert_eq!(res.as_deref(), expected);
+    }
+
+    test("stroke-width", "10px", "30px", 0.25, Some("15px"));
+    test("stroke-width", "10px", "50%", 0.5, Some("calc(5px + 25%)"));
+    test("opacity", "0", "1", 1.5, Some("1.5"));
+    test("color", "black", "white", 0.4, Some("#666"));
+    test("color", "currentColor", "white", 0.5, None);
+    test(
+      "transform",
+      "translateX(10px) rotate(90deg)",
+      "translateX(30px)",
+      0.5,
+      Some("translateX(20px)rotate(45deg)"),
+    );
+    test(
+      "transform",
+      "translateX(10px)",
+      "translateY(20px)",
+      0.5,
+      Some("translate3d(5px, 10px, 0)"),
+    );
+    test("transform", "none", "scale(3)", 0.5, Some("scale(2)"));
+    test("transform", "rotateX(10deg)", "rotateY(20deg)", 0.5, None);
+    test(
+      "transform",
+      "matrix(1, 0, 0, 1, 0, 0)",
+      "matrix(2, 0, 0, 2, 0, 0)",
+      0.5,
+      None,
+    );
+    test(
+      "box-shadow",
+      "2px 2px black",
+      "4px 6px black, 10px 10px black",
+      1.0,
+      Some("4px 6px #000, 10px 10px #000"),
+    );
+    test("box-shadow", "2px 2px black", "inset 2px 2px black", 0.5, None);
+    test(
+      "text-shadow",
+      "2px 2px 4px black",
+      "6px 2px 8px black",
+      0.5,
+      Some("4px 2px 6px #000"),
     );
+    test("-webkit-transform", "scale(1)", "scale(2)", 0.5, Some("scale(1.5)"));
+  }
 
-    prefix_test(
-      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          -webkit-filter: drop-shadow(16px 16px 20px #b32323);
-          filter: drop-shadow(16px 16px 20px #b32323);
-          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39));
+  #[test]
+  fn test_diff() {
+    use crate::diff::{DeclarationDiff, DiffKind};
+
+    let old = StyleSheet::parse(
+      indoc! {r#"
+        .a { color: red; width: 10px }
+        .b { color: blue }
+        @media print {
+          .a { display: none }
         }
+        @font-face { font-family: Foo; src: url(foo.woff2) }
+        .c { display: -webkit-box; display: flex }
       "#},
-      Browsers {
-        chrome: Some(20 << 16),
-        ..Browsers::default()
-      },
-    );
-
-    prefix_test(
-      ".foo { filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          filter: contrast(175%) drop-shadow(16px 16px 20px #b32323);
-          filter: contrast(175%) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let new = StyleSheet::parse(
+      indoc! {r#"
+        /* Reformatted */
+        .a{color:#ff0000;width:12px;height:5px}
+        @media print {
+          .a { display: none !important }
         }
+        .d { color: green }
+        @font-face { font-family: Foo; src: url(foo.woff2) }
+        .c { display: -webkit-box; display: grid }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+      ParserOptions::default(),
+    )
+    .unwrap();
+
+    let diff = old.diff(&new).unwrap();
+    let rules = diff
+      .rules
+      .iter()
+      .map(|rule| {
+        (
+          rule.kind,
+          rule.path.clone(),
+          rule.old_loc.map(|loc| loc.line),
+          rule.new_loc.map(|loc| loc.line),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(
+      rules,
+      vec![
+        (DiffKind::Changed, vec![".a".to_string()], Some(0), Some(1)),
+        (
+          DiffKind::Changed,
+          vec!["@media print".to_string(), ".a".to_string()],
+          Some(3),
+          Some(3)
+        ),
+        (DiffKind::Added, vec![".d".to_string()], None, Some(5)),
+        (DiffKind::Changed, vec![".c".to_string()], Some(6), Some(7)),
+        (DiffKind::Removed, vec![".b".to_string()], Some(1), None),
+      ]
     );
 
-    prefix_test(
-      ".foo { filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px yellow) }",
-      indoc! { r#"
-        .foo {
-          filter: drop-shadow(16px 16px 20px #b32323) drop-shadow(16px 16px 20px #ff0);
-          filter: drop-shadow(16px 16px 20px lab(40% 56.6 39)) drop-shadow(16px 16px 20px #ff0);
+    let declaration = |kind, property: &str, old: Option<&str>, new: Option<&str>| DeclarationDiff {
+      kind,
+      property: property.into(),
+      old_value: old.map(|v| v.into()),
+      new_value: new.map(|v| v.into()),
+    };
+    assert_eq!(
+      diff.rules[0].declarations,
+      vec![
+        declaration(DiffKind::Changed, "width", Some("10px"), Some("12px")),
+        declaration(DiffKind::Added, "height", None, Some("5px")),
+      ]
+    );
+    assert_eq!(
+      diff.rules[1].declarations,
+      vec![declaration(
+        DiffKind::Changed,
+        "display",
+        Some("none"),
+        Some("none!important")
+      )]
+    );
+    assert_eq!(
+      diff.rules[3].declarations,
+      vec![declaration(DiffKind::Changed, "display", Some("flex"), Some("grid"))]
+    );
+    assert_eq!(
+      diff.rules[4].declarations,
+      vec![declaration(DiffKind::Removed, "color", Some("#00f"), None)]
+    );
+
+    assert!(old.diff(&old).unwrap().rules.is_empty());
+  }
+
+  #[test]
+  fn test_coverage_map() {
+    fn coverage_map(source: &str, css_modules: bool) -> (String, Vec<(String, u32, u32)>) {
+      let stylesheet = StyleSheet::parse(
+        source,
+        ParserOptions {
+          filename: "test.css".into(),
+          css_modules: if css_modules {
+            Some(crate::css_modules::Config::default())
+          } else {
+            None
+          },
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          coverage_map: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      let ranges = res
+        .coverage_map
+        .unwrap()
+        .into_iter()
+        .map(|range| {
+          (
+            res.code[range.start..range.end].to_owned(),
+            range.loc.line,
+            range.loc.column,
+          )
+        })
+        .collect();
+      (res.code, ranges)
+    }
+
+    let (code, ranges) = coverage_map(
+      indoc! {r#"
+        .a { color: red }
+        @media print {
+          .b { color: blue }
+          .c { color: green }
+        }
+        .d {
+          width: 10px;
+          .e { width: 20px }
         }
       "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
+      false,
+    );
+    assert_eq!(
+      code,
+      ".a{color:red}@media print{.b{color:#00f}.c{color:green}}.d{width:10px;& .e{width:20px}}"
+    );
+    assert_eq!(
+      ranges,
+      vec![
+        (".a{color:red}".into(), 0, 1),
+        ("@media print{.b{color:#00f}.c{color:green}}".into(), 1, 1),
+        (".b{color:#00f}".into(), 2, 3),
+        (".c{color:green}".into(), 3, 3),
+        (".d{width:10px;& .e{width:20px}}".into(), 5, 1),
+        ("& .e{width:20px}".into(), 7, 3),
+      ]
     );
 
-    prefix_test(
-      ".foo { filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39)) }",
-      indoc! { r#"
-        .foo {
-          filter: var(--foo) drop-shadow(16px 16px 20px #b32323);
+    // Offsets account for CSS module names that are longer once escaped.
+    let (_, ranges) = coverage_map(r".a\.b { color: red } .c { color: blue }", true);
+    assert_eq!(ranges.len(), 2);
+    assert!(ranges[0].0.ends_with(r"_a\.b{color:red}"), "{}", ranges[0].0);
+    assert!(ranges[1].0.ends_with("_c{color:#00f}"), "{}", ranges[1].0);
+    assert!(ranges[1].0.starts_with('.'));
+  }
+
+  #[test]
+  fn test_query() {
+    use crate::query::RuleHandle;
+    use crate::values::color::RGBA;
+
+    let source = indoc! {r#"
+      .foo, .bar { color: red }
+      @media print {
+        .foo { color: blue !important; background: red }
+      }
+      .baz {
+        color: green;
+        & .foo { --Color: red; --color: green }
+      }
+    "#};
+    let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+
+    let rules = stylesheet.query_selector(".foo").unwrap();
+    assert_eq!(
+      rules,
+      vec![RuleHandle { path: vec![0] }, RuleHandle { path: vec![1, 0] }]
+    );
+    assert_eq!(
+      stylesheet.query_selector(".baz .foo, & .foo").unwrap(),
+      vec![RuleHandle { path: vec![2, 0] }]
+    );
+    assert!(stylesheet.query_selector(".qux").unwrap().is_empty());
+    assert!(stylesheet.query_selector("!!").is_err());
+
+    let colors = stylesheet.query_property("COLOR");
+    assert_eq!(colors.len(), 3);
+    assert!(colors[1].important);
+    assert_eq!(colors[1].rule, rules[1]);
+    assert_eq!(stylesheet.query_property("--color").len(), 1);
+
+    for handle in &colors {
+      if let Some(Property::Color(color)) = stylesheet.declaration_mut(handle) {
+        *color = CssColor::RGBA(RGBA::new(0, 0, 0, 1.0));
+      }
+    }
+
+    if let Some(CssRule::Style(style)) = stylesheet.rule_mut(&rules[1]) {
+      style.declarations.declarations.clear();
+    }
+
+    assert!(stylesheet.rule(&RuleHandle { path: vec![3] }).is_none());
+    assert_eq!(
+      stylesheet.to_css(PrinterOptions::default()).unwrap().code,
+      indoc! {r#"
+        .foo, .bar {
+          color: #000;
         }
 
-        @supports (color: lab(0% 0 0)) {
+        @media print {
           .foo {
-            filter: var(--foo) drop-shadow(16px 16px 20px lab(40% 56.6 39));
+            color: #000 !important;
           }
         }
-      "#},
-      Browsers {
-        chrome: Some(4 << 16),
-        ..Browsers::default()
-      },
-    );
-  }
 
-  #[test]
-  fn test_viewport() {
-    minify_test(
-      r#"
-    @viewport {
-      width: 100vw;
-    }"#,
-      "@viewport{width:100vw}",
-    );
-    minify_test(
-      r#"
-    @-ms-viewport {
-      width: device-width;
-    }"#,
-      "@-ms-viewport{width:device-width}",
+        .baz {
+          color: #000;
+
+          & .foo {
+            --Color: red;
+            --color: green;
+          }
+        }
+      "#}
     );
   }
 
   #[test]
-  fn test_at_scope() {
-    minify_test(
-      r#"
-      @scope {
-        .foo {
-          display: flex;
-        }
+  fn test_selector_matcher() {
+    use crate::matching::Element;
+    use crate::selector::PseudoElement;
+
+    struct Node {
+      name: &'static str,
+      attrs: Vec<(&'static str, &'static str)>,
+      parent: Option<usize>,
+      children: Vec<usize>,
+    }
+
+    #[derive(Clone)]
+    struct NodeRef<'a> {
+      nodes: &'a [Node],
+      index: usize,
+    }
+
+    impl<'a> NodeRef<'a> {
+      fn sibling(&self, offset: isize) -> Option<Self> {
+        let siblings = &self.nodes[self.nodes[self.index].parent?].children;
+        let position = siblings.iter().position(|index| *index == self.index)? as isize + offset;
+        let index = *siblings.get(usize::try_from(position).ok()?)?;
+        Some(NodeRef {
+          nodes: self.nodes,
+          index,
+        })
       }
-      "#,
-      "@scope{.foo{display:flex}}",
-    );
-    minify_test(
-      r#"
-      @scope {
-        :scope {
-          display: flex;
-          color: lightblue;
-        }
-      }"#,
-      "@scope{:scope{color:#add8e6;display:flex}}",
-    );
-    minify_test(
-      r#"
-      @scope (.light-scheme) {
-        a { color: yellow; }
+    }
+
+    impl<'a> Element for NodeRef<'a> {
+      fn local_name(&self) -> &str {
+        self.nodes[self.index].name
       }
-      "#,
-      "@scope(.light-scheme){a{color:#ff0}}",
-    );
-    minify_test(
-      r#"
-      @scope (.media-object) to (.content > *) {
-        a { color: yellow; }
+
+      fn attr(&self, name: &str) -> Option<&str> {
+        self.nodes[self.index].attrs.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
       }
-      "#,
-      "@scope(.media-object) to (.content>*){a{color:#ff0}}",
-    );
-    minify_test(
-      r#"
-      @scope to (.content > *) {
-        a { color: yellow; }
+
+      fn parent(&self) -> Option<Self> {
+        let index = self.nodes[self.index].parent?;
+        Some(NodeRef {
+          nodes: self.nodes,
+          index,
+        })
       }
-      "#,
-      "@scope to (.content>*){a{color:#ff0}}",
-    );
-    minify_test(
-      r#"
-      @scope (#my-component) {
-        & { color: yellow; }
+
+      fn prev_sibling(&self) -> Option<Self> {
+        self.sibling(-1)
       }
-      "#,
-      "@scope(#my-component){&{color:#ff0}}",
-    );
-    minify_test(
-      r#"
-      @scope (.parent-scope) {
-        @scope (:scope > .child-scope) to (:scope .limit) {
-          .content { color: yellow; }
-        }
+
+      fn next_sibling(&self) -> Option<Self> {
+        self.sibling(1)
       }
-      "#,
-      "@scope(.parent-scope){@scope(:scope>.child-scope) to (:scope .limit){.content{color:#ff0}}}",
-    );
-    minify_test(
-      r#"
-      .foo {
-        @scope (.bar) {
-          color: yellow;
-        }
+
+      fn is_empty(&self) -> bool {
+        self.nodes[self.index].children.is_empty()
       }
-      "#,
-      ".foo{@scope(.bar){&{color:#ff0}}}",
-    );
-    nesting_test(
+    }
+
+    fn node(
+      name: &'static str,
+      attrs: Vec<(&'static str, &'static str)>,
+      parent: Option<usize>,
+      children: Vec<usize>,
+    ) -> Node {
+      Node {
+        name,
+        attrs,
+        parent,
+        children,
+      }
+    }
+
+    let nodes = vec![
+      node("html", vec![], None, vec![1]),
+      node("body", vec![("class", "home")], Some(0), vec![2]),
+      node(
+        "div",
+        vec![("id", "main"), ("class", "card featured")],
+        Some(1),
+        vec![3, 4],
+      ),
+      node("a", vec![("href", "/"), ("class", "link")], Some(2), vec![]),
+      node("p", vec![], Some(2), vec![]),
+    ];
+
+    let stylesheet = StyleSheet::parse(
       r#"
-      .foo {
-        @scope (.bar) {
-          color: yellow;
-        }
+      .card { color: red }
+      #main { color: green }
+      div.card > a { color: blue }
+      .home .featured { padding: 0 }
+      .card::before { content: "" }
+      a:hover { color: pink }
+      @media (min-width: 500px) {
+        .link { margin: 0 }
       }
-      "#,
-      indoc! {r#"
-        @scope (.bar) {
-          :scope {
-            color: #ff0;
-          }
-        }
-      "#},
+      .card {
+        & > p { margin: 0 }
+        .home & { color: orange }
+      }
+      p:first-child, p:last-child { color: red }
+      :has(> a) { color: red }
+      a:any-link, :empty { color: red }
+    "#,
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let matcher = stylesheet.selector_matcher();
+
+    let matches = |index| {
+      matcher
+        .match_element(&NodeRef { nodes: &nodes, index })
+        .into_iter()
+        .map(|m| {
+          (
+            m.rule.to_string(),
+            m.selector,
+            (m.specificity.ids, m.specificity.classes, m.specificity.elements),
+            m.pseudo_element,
+          )
+        })
+        .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+      matches(2),
+      vec![
+        ("0".into(), 0, (0, 1, 0), None),
+        ("7".into(), 0, (0, 1, 0), None),
+        ("4".into(), 0, (0, 1, 1), Some(PseudoElement::Before)),
+        ("3".into(), 0, (0, 2, 0), None),
+        ("7.1".into(), 0, (0, 2, 0), None),
+        ("1".into(), 0, (1, 0, 0), None),
+      ]
     );
-    nesting_test(
-      r#"
-      .parent {
-        color: blue;
+    assert_eq!(
+      matches(3),
+      vec![
+        ("6.0".into(), 0, (0, 1, 0), None),
+        ("10".into(), 1, (0, 1, 0), None),
+        ("10".into(), 0, (0, 1, 1), None),
+        ("2".into(), 0, (0, 1, 2), None),
+      ]
+    );
+    assert_eq!(
+      matches(4),
+      vec![
+        ("10".into(), 1, (0, 1, 0), None),
+        ("7.0".into(), 0, (0, 1, 1), None),
+        ("8".into(), 1, (0, 1, 1), None),
+      ]
+    );
+    assert_eq!(matches(0), vec![]);
+  }
+  #[test]
+  fn test_scope_selectors() {
+    use crate::selector::SelectorList;
+    use crate::traits::ParseWithOptions;
 
-        @scope (& > .scope) to (& .limit) {
-          & .content {
-            color: yellow;
-          }
-        }
+    fn scope_test<'i>(source: &'i str, scope: &'i str, expected: &str) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      let scope = SelectorList::parse_string_with_options(scope, ParserOptions::default()).unwrap();
+      stylesheet.scope_selectors(&scope);
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    scope_test(
+      ".foo, a > b { color: red }",
+      ".widget",
+      ".widget .foo,.widget a>b{color:red}",
+    );
+    scope_test(".foo { color: red }", "[data-widget]", "[data-widget] .foo{color:red}");
+    scope_test(".foo { color: red }", "#app .widget", "#app .widget .foo{color:red}");
+    scope_test(".foo { color: red }", ".a, .b", ":is(.a,.b) .foo{color:red}");
+    scope_test(".widget .foo { color: red }", ".widget", ".widget .foo{color:red}");
+    scope_test(
+      ".widget.dark .foo { color: red }",
+      ".widget",
+      ".widget.dark .foo{color:red}",
+    );
+    scope_test(":root { --color: red }", ".widget", ".widget{--color:red}");
+    scope_test("html, body { margin: 0 }", ".widget", ".widget,.widget{margin:0}");
+    scope_test("html body .foo { color: red }", ".widget", ".widget .foo{color:red}");
+    scope_test(
+      "html > body > .foo { color: red }",
+      ".widget",
+      ".widget>.foo{color:red}",
+    );
+    scope_test(
+      "body.dark .foo { color: red }",
+      ".widget",
+      ".widget.dark .foo{color:red}",
+    );
+    scope_test(
+      ":is(html, body) .foo { color: red }",
+      ".widget",
+      ".widget .foo{color:red}",
+    );
+    scope_test(
+      "body::before { content: '' }",
+      ".widget",
+      ".widget:before{content:\"\"}",
+    );
+    scope_test(
+      "::selection { color: red }",
+      ".widget",
+      ".widget ::selection{color:red}",
+    );
+    scope_test(
+      ".foo { color: red; & .bar { color: blue } .baz & { color: green } }",
+      ".widget",
+      ".widget .foo{color:red;& .bar{color:#00f}.baz &{color:green}}",
+    );
+    scope_test(
+      "@media print { .foo { color: red } } @layer base { div { color: red } }",
+      ".widget",
+      "@media print{.widget .foo{color:red}}@layer base{.widget div{color:red}}",
+    );
+    scope_test(
+      "@keyframes fade { from { opacity: 0 } to { opacity: 1 } }",
+      ".widget",
+      "@keyframes fade{0%{opacity:0}to{opacity:1}}",
+    );
+    scope_test(
+      "@scope (.card) { img { color: red } }",
+      ".widget",
+      "@scope(.widget .card){img{color:red}}",
+    );
+    scope_test(
+      "@scope { img { color: red } }",
+      ".widget",
+      "@scope(.widget){img{color:red}}",
+    );
+  }
+
+  #[test]
+  fn test_handles() {
+    use crate::query::{DeclarationHandle, InvalidHandle, RuleHandle};
+
+    let source = indoc! {r#"
+      .foo { color: red }
+      @media print {
+        .bar { color: blue !important; background: red }
       }
-      "#,
-      indoc! {r#"
-        .parent {
-          color: #00f;
-        }
+      .baz {
+        @nest .qux & { width: 10px }
+      }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
 
-        @scope (.parent > .scope) to (.parent > .scope .limit) {
-          :scope .content {
-            color: #ff0;
-          }
-        }
-      "#},
+    let rules = stylesheet.rule_handles();
+    assert_eq!(
+      rules.iter().map(|handle| handle.to_string()).collect::<Vec<_>>(),
+      vec!["0", "1", "1.0", "2", "2.0"]
+    );
+    assert_eq!("1.0".parse::<RuleHandle>(), Ok(RuleHandle { path: vec![1, 0] }));
+    assert_eq!("".parse::<RuleHandle>(), Err(InvalidHandle));
+    assert_eq!("1.x".parse::<RuleHandle>(), Err(InvalidHandle));
+
+    let declarations = stylesheet.query_property("color");
+    assert_eq!(declarations[1].to_string(), "1.0#!0");
+    assert_eq!("1.0#!0".parse::<DeclarationHandle>(), Ok(declarations[1].clone()));
+    assert_eq!("1.0".parse::<DeclarationHandle>(), Err(InvalidHandle));
+
+    assert_eq!(stylesheet.rule_pointer(&rules[4]).unwrap(), "/rules/2/value/rules/0");
+    assert_eq!(
+      stylesheet.declaration_pointer(&declarations[1]).unwrap(),
+      "/rules/1/value/rules/0/value/declarations/importantDeclarations/0"
+    );
+    let width = stylesheet.query_property("width");
+    assert_eq!(
+      stylesheet.declaration_pointer(&width[0]).unwrap(),
+      "/rules/2/value/rules/0/value/style/declarations/declarations/0"
+    );
+    assert!(stylesheet.rule_pointer(&RuleHandle { path: vec![3] }).is_none());
+
+    #[cfg(feature = "serde")]
+    {
+      let mut json = serde_json::to_value(&stylesheet).unwrap();
+      let pointer = stylesheet.declaration_pointer(&declarations[1]).unwrap();
+      assert_eq!(json.pointer(&pointer).unwrap()["property"], "color");
+      *json.pointer_mut(&pointer).unwrap() = serde_json::json!({
+        "property": "color",
+        "value": { "type": "currentcolor" }
+      });
+
+      let handle = serde_json::to_string(&declarations[1]).unwrap();
+      assert_eq!(handle, "\"1.0#!0\"");
+      let handle: DeclarationHandle = serde_json::from_str(&handle).unwrap();
+      let json = json.to_string();
+      let stylesheet: StyleSheet = serde_json::from_str(&json).unwrap();
+      assert_eq!(
+        stylesheet
+          .declaration(&handle)
+          .unwrap()
+          .value_to_css_string(PrinterOptions::default())
+          .unwrap(),
+        "currentColor"
+      );
+    }
+  }
+
+  #[test]
+  fn test_media_list_parse_string() {
+    use crate::media_query::MediaList;
+
+    let media = MediaList::parse_string("screen and (min-width: 100px), print").unwrap();
+    assert_eq!(media.media_queries.len(), 2);
+    assert_eq!(
+      media.to_css_string(PrinterOptions::default()).unwrap(),
+      "screen and (width >= 100px), print"
+    );
+    assert!(MediaList::parse_string("screen and (").is_err());
+  }
+
+  #[test]
+  fn test_media_query_simplify() {
+    use crate::media_query::{MediaList, MediaMatch};
+
+    fn simplify(source: &str, expected: &str, expected_match: MediaMatch) {
+      let mut media = MediaList::parse_string(source).unwrap();
+      assert_eq!(media.simplify(), expected_match);
+      assert_eq!(media.to_css_string(PrinterOptions::default()).unwrap(), expected);
+    }
+
+    simplify("screen", "screen", MediaMatch::Conditional);
+    simplify("all", "all", MediaMatch::Always);
+    simplify("only all", "all", MediaMatch::Always);
+    simplify("not all", "not all", MediaMatch::Never);
+    simplify(
+      "print, all and (min-width: 100px)",
+      "print, (width >= 100px)",
+      MediaMatch::Conditional,
+    );
+    simplify("print, all", "all", MediaMatch::Always);
+    simplify("print, print", "print", MediaMatch::Conditional);
+    simplify("(not (not (hover)))", "(hover)", MediaMatch::Conditional);
+    simplify("not all and (not (hover))", "(hover)", MediaMatch::Conditional);
+    simplify(
+      "not screen and (hover)",
+      "not screen and (hover)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(min-width: 100px) and (min-width: 200px) and (max-width: 500px)",
+      "(width >= 200px) and (width <= 500px)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(width > 100px) and (width >= 100px)",
+      "(width > 100px)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(100px <= width < 400px) and (width < 300px) and (hover)",
+      "(width >= 100px) and (width < 300px) and (hover)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(min-width: 500px) and (max-width: 500px)",
+      "(width: 500px)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "((hover) and (color)) and ((hover) and (min-resolution: 2dppx))",
+      "(hover) and (color) and (resolution >= 2x)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(min-width: 100px) or (min-width: 200px) or (hover)",
+      "(width >= 100px) or (hover)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "(min-width: 1em) and (min-width: 20px)",
+      "(width >= 1em) and (width >= 20px)",
+      MediaMatch::Conditional,
+    );
+
+    // Branches that are always true or false are removed.
+    simplify(
+      "(min-width: 500px) and (max-width: 400px)",
+      "not all",
+      MediaMatch::Never,
+    );
+    simplify("(500px < width < 400px)", "not all", MediaMatch::Never);
+    simplify(
+      "screen and (width > 10px) and (width < 10px)",
+      "not all",
+      MediaMatch::Never,
+    );
+    simplify(
+      "not screen and (width > 10px) and (width < 10px)",
+      "all",
+      MediaMatch::Always,
+    );
+    simplify(
+      "print, (min-width: 500px) and (max-width: 400px)",
+      "print",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "((min-width: 500px) and (max-width: 400px)) or (hover)",
+      "(hover)",
+      MediaMatch::Conditional,
+    );
+    simplify("(max-width: 500px) or (min-width: 400px)", "all", MediaMatch::Always);
+    simplify("(width < 500px) or (width >= 500px)", "all", MediaMatch::Always);
+    simplify(
+      "(width < 500px) or (width > 500px)",
+      "(width > 500px) or (width < 500px)",
+      MediaMatch::Conditional,
+    );
+    simplify(
+      "screen and (not ((max-width: 500px) or (min-width: 400px)))",
+      "not all",
+      MediaMatch::Never,
+    );
+    simplify(
+      "screen and ((max-width: 500px) or (min-width: 400px))",
+      "screen",
+      MediaMatch::Conditional,
     );
   }
 
   #[test]
-  fn test_custom_media() {
-    custom_media_test(
-      r#"
-      @custom-media --modern (color), (hover);
+  fn test_media_query_matches() {
+    use crate::media_query::{Environment, MediaFeatureValue, MediaList, MediaType};
+    use crate::values::ident::Ident;
+
+    let mut env = Environment {
+      width: Some(800.0),
+      height: Some(600.0),
+      resolution: Some(2.0),
+      ..Environment::default()
+    };
+    env.features.insert(
+      "prefers-color-scheme".into(),
+      MediaFeatureValue::Ident(Ident("dark".into())),
+    );
+    env
+      .features
+      .insert("hover".into(), MediaFeatureValue::Ident(Ident("none".into())));
+    let matches = |source: &str, env: &Environment| MediaList::parse_string(source).unwrap().matches(env);
+
+    assert!(matches("", &env));
+    assert!(matches("all", &env));
+    assert!(matches("screen", &env));
+    assert!(!matches("print", &env));
+    assert!(matches("not print", &env));
+    assert!(!matches("tv", &env));
+    assert!(matches("print, (min-width: 600px)", &env));
+    assert!(matches("screen and (min-width: 600px) and (max-width: 800px)", &env));
+    assert!(!matches("screen and (min-width: 801px)", &env));
+    assert!(matches("(width >= 50em)", &env));
+    assert!(!matches("(width > 50em)", &env));
+    assert!(matches("(400px < width <= 800px)", &env));
+    assert!(!matches("(400px < width < 800px)", &env));
+    assert!(matches("(600px >= height)", &env));
+    assert!(matches("(width: 800px)", &env));
+    assert!(matches("(orientation: landscape)", &env));
+    assert!(matches("(min-aspect-ratio: 4/3)", &env));
+    assert!(!matches("(aspect-ratio > 16/9)", &env));
+    assert!(matches("(min-resolution: 2dppx)", &env));
+    assert!(matches("(resolution: 192dpi)", &env));
+    assert!(matches("(-webkit-min-device-pixel-ratio: 1.5)", &env));
+    assert!(matches("(min-device-width: 800px)", &env));
+    assert!(matches("(prefers-color-scheme: dark)", &env));
+    assert!(!matches("(prefers-color-scheme: light)", &env));
+    assert!(!matches("(hover)", &env));
+    assert!(matches("not all and (hover)", &env));
+    assert!(matches("(width)", &env));
+    assert!(matches(
+      "((hover) or (width > 500px)) and (not (prefers-color-scheme: light))",
+      &env
+    ));
+
+    // Unknown features and values that are not provided by the environment are unknown.
+    assert!(!matches("(prefers-reduced-motion: reduce)", &env));
+    assert!(!matches("not all and (prefers-reduced-motion: reduce)", &env));
+    assert!(!matches("(unknown-feature: 1)", &env));
+    assert!(matches("(unknown-feature: 1) or (min-width: 100px)", &env));
+    assert!(!matches("(unknown-feature: 1) and (min-width: 100px)", &env));
+    assert!(matches(
+      "not all and ((unknown-feature: 1) and (min-width: 1000px))",
+      &env
+    ));
+
+    let print = Environment {
+      media_type: MediaType::Print,
+      ..Environment::default()
+    };
+    assert!(matches("print", &print));
+    assert!(!matches("screen", &print));
+    assert!(!matches("(min-width: 100px)", &print));
+  }
 
-      @media (--modern) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media ((color) or (hover)) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
-    );
+  #[test]
+  fn test_fingerprint() {
+    use crate::declaration::DeclarationBlock;
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
+    let rule = |source| CssRule::parse_string(source, ParserOptions::default()).unwrap();
+    let fingerprint = |source| rule(source).fingerprint().unwrap();
 
-      @media (--color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media (color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    assert_eq!(
+      fingerprint(".foo { color: #ff0000; & .bar { width: 10px } }"),
+      fingerprint("\n\n.foo{color:red;&   .bar{width:10.0px}}")
+    );
+    assert_ne!(fingerprint(".foo { color: red }"), fingerprint(".foo { color: blue }"));
+    assert_ne!(fingerprint(".foo { color: red }"), fingerprint(".bar { color: red }"));
+    assert_ne!(
+      fingerprint("@media print { .foo { color: red } }"),
+      fingerprint("@media screen { .foo { color: red } }")
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --a (color);
-      @custom-media --b (--a);
+    let declarations = |source| {
+      DeclarationBlock::parse_string(source, ParserOptions::default())
+        .unwrap()
+        .fingerprint()
+        .unwrap()
+    };
+    assert_eq!(
+      declarations("color: red; width: 10px"),
+      declarations("color:#f00;width:10px")
+    );
+    assert_ne!(
+      declarations("color: red; width: 10px"),
+      declarations("width: 10px; color: red")
+    );
+    assert_ne!(declarations("color: red"), declarations("color: red !important"));
+    assert_eq!(
+      declarations("color: red; width: 10px"),
+      declarations("color: rgb(255 0 0); width: 10px")
+    );
+  }
 
-      @media (--b) and (width > 1024px) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media (color) and (width > 1024px) {
-        .a {
-          color: green;
-        }
+  #[test]
+  fn test_lint_selectors() {
+    use crate::selector_lints::{SelectorLintKind, SelectorLintOptions};
+
+    let source = indoc! {r#"
+      .a .b > .c .d .e { color: red }
+      .a .b .c .d { color: red }
+      .nav * { color: red }
+      .list > :first-child, .foo::before, * { color: red }
+      .a:not(:has(.b)), .a:has(.b) { color: red }
+      .a .b .c {
+        .d & { color: red }
+        .e .f { color: red }
       }
-      "#},
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let lints = stylesheet
+      .lint_selectors(&SelectorLintOptions::default())
+      .into_iter()
+      .map(|lint| (lint.kind, lint.selector, lint.loc.line))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      lints,
+      vec![
+        (
+          SelectorLintKind::DescendantDepth { depth: 4, max: 3 },
+          ".a .b > .c .d .e".into(),
+          0
+        ),
+        (SelectorLintKind::UniversalKeySelector, ".nav *".into(), 2),
+        (SelectorLintKind::UniversalKeySelector, ".list > :first-child".into(), 3),
+        (
+          SelectorLintKind::HasNotDepth { depth: 2, max: 1 },
+          ".a:not(:has(.b))".into(),
+          4
+        ),
+        (
+          SelectorLintKind::DescendantDepth { depth: 4, max: 3 },
+          "& .e .f".into(),
+          7
+        ),
+      ]
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --not-color not (color);
+    let lints = stylesheet.lint_selectors(&SelectorLintOptions {
+      max_descendant_depth: None,
+      universal_key_selectors: false,
+      max_has_not_depth: Some(2),
+    });
+    assert!(lints.is_empty());
+  }
 
-      @media not (--not-color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+  #[test]
+  fn test_overridden_declaration_warnings() {
+    fn test(source: &str, expected: Vec<(&str, u32, u32)>) {
+      let warnings = Arc::new(RwLock::new(Vec::new()));
+      StyleSheet::parse(
+        source,
+        ParserOptions {
+          flags: ParserFlags::WARN_OVERRIDDEN_DECLARATIONS,
+          warnings: Some(warnings.clone()),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      let warnings = warnings.read().unwrap();
+      let warnings = warnings
+        .iter()
+        .map(|w| {
+          let loc = w.loc.as_ref().unwrap();
+          (w.kind.to_string(), loc.line, loc.column)
+        })
+        .collect::<Vec<_>>();
+      assert_eq!(
+        warnings,
+        expected.into_iter().map(|(w, l, c)| (w.to_owned(), l, c)).collect::<Vec<_>>()
+      );
+    }
+
+    test(
       indoc! {r#"
-      @media (color) {
-        .a {
+        .foo {
+          color: red;
+          width: 10px;
+          color: blue;
           color: green;
         }
-      }
       "#},
+      vec![
+        ("Declaration of 'color' is overridden by a later declaration", 1, 10),
+        ("Declaration of 'color' is overridden by a later declaration", 3, 10),
+      ],
     );
-
-    custom_media_test(
-      r#"
-      @custom-media --color-print print and (color);
-
-      @media (--color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+    test(
       indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
+        .foo {
+          color: red;
+          color: oklch(50% 0.2 120);
+          width: 100vh;
+          width: 100dvh;
+          display: -webkit-box;
+          display: flex;
+          background: red;
+          background: linear-gradient(red, blue);
         }
-      }
       "#},
+      vec![],
     );
-
-    custom_media_test(
-      r#"
-      @custom-media --color-print print and (color);
-
-      @media print and (--color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+    test(
       indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
+        .foo {
+          color: red !important;
+          color: blue;
+          width: 10px;
+          width: 20px !important;
         }
-      }
       "#},
+      vec![
+        ("Declaration of 'color' is overridden by a later declaration", 2, 10),
+        ("Declaration of 'width' is overridden by a later declaration", 3, 10),
+      ],
     );
-
-    custom_media_test(
-      r#"
-      @custom-media --not-color-print not print and (color);
-
-      @media not print and (--not-color-print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+    test(
       indoc! {r#"
-      @media not print and (color) {
-        .a {
-          color: green;
+        .foo {
+          color: red;
+          .bar {
+            color: red;
+          }
+          --foo: 1px;
+          --foo: 2px;
         }
-      }
       "#},
+      vec![("Declaration of '--foo' is overridden by a later declaration", 5, 10)],
+    );
+    test(
+      ".foo { color: red; color: blue }",
+      vec![("Declaration of 'color' is overridden by a later declaration", 0, 15)],
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+    StyleSheet::parse(
+      ".foo { color: red; color: blue }",
+      ParserOptions {
+        warnings: Some(warnings.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    assert!(warnings.read().unwrap().is_empty());
+  }
 
-      @media (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
+  #[test]
+  fn test_validation() {
+    let source = indoc! {r#"
+      .foo {
+        widht: 10px;
+        margin: foo;
+        color: inherit;
+        width: var(--width);
+        pointer-events: none;
+        z-index: auto;
+        box-shadow: none;
+        -webkit-font-smoothing: antialiased;
+        background: -webkit-gradient(linear, left top, left bottom, from(red), to(blue));
+        --foo: bar;
       }
-      "#},
+    "#};
+
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+    StyleSheet::parse(
+      source,
+      ParserOptions {
+        validation: ValidationMode::Warn,
+        warnings: Some(warnings.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let warnings = warnings.read().unwrap();
+    assert_eq!(
+      warnings
+        .iter()
+        .map(|w| {
+          let loc = w.loc.as_ref().unwrap();
+          (w.kind.to_string(), loc.line, loc.column)
+        })
+        .collect::<Vec<_>>(),
+      vec![
+        ("Unknown property: widht".into(), 1, 10),
+        ("Invalid value for property: margin".into(), 2, 11),
+      ]
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
+    let err = StyleSheet::parse(
+      source,
+      ParserOptions {
+        validation: ValidationMode::Error,
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ParserError::UnknownProperty("widht".into()));
 
-      @media not (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media not print {
-        .a {
-          color: green;
-        }
-      }
-      "#},
-    );
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+    let stylesheet = StyleSheet::parse(
+      ".foo { widht: 10px; width: 20px }",
+      ParserOptions {
+        validation: ValidationMode::Error,
+        error_recovery: true,
+        warnings: Some(warnings.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{width:20px}");
+    assert_eq!(warnings.read().unwrap().len(), 1);
+  }
 
-    custom_media_test(
-      r#"
-      @custom-media --print not print;
+  #[test]
+  fn test_specificity() {
+    use crate::selector::Specificity;
+
+    fn specificity_test(source: &str, expected: Vec<(u32, u32, u32)>) {
+      let res = Specificity::parse_string(source).unwrap();
+      let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(ids, classes, elements)| Specificity { ids, classes, elements })
+        .collect();
+      assert_eq!(res, expected);
+    }
 
-      @media not (--print) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
+    specificity_test("*", vec![(0, 0, 0)]);
+    specificity_test("div", vec![(0, 0, 1)]);
+    specificity_test("#foo .bar > a:hover::before", vec![(1, 2, 2)]);
+    specificity_test("[type=text], .foo", vec![(0, 1, 0), (0, 1, 0)]);
+    specificity_test(":is(#a, .b) span", vec![(1, 0, 1)]);
+    specificity_test(":where(#a, .b) span", vec![(0, 0, 1)]);
+    specificity_test(":not(#a, .b)", vec![(1, 0, 0)]);
+    specificity_test(".a:has(> img, #b)", vec![(1, 1, 0)]);
+    specificity_test("li:nth-child(2n of .important)", vec![(0, 2, 1)]);
+    assert!(Specificity::parse_string("..foo").is_err());
+  }
+
+  #[test]
+  fn test_api() {
+    let stylesheet = StyleSheet::parse(".foo:hover { color: red }", ParserOptions::default()).unwrap();
+    match &stylesheet.rules.0[0] {
+      CssRule::Style(s) => {
+        assert_eq!(&s.selectors.to_string(), ".foo:hover");
       }
-      "#},
-    );
+      _ => unreachable!(),
+    }
 
-    custom_media_test(
-      r#"
-      @custom-media --print print;
+    let color = CssColor::parse_string("#f0f").unwrap();
+    assert_eq!(color.to_css_string(PrinterOptions::default()).unwrap(), "#f0f");
 
-      @media ((--print)) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
+    let rule = CssRule::parse_string(".foo { color: red }", ParserOptions::default()).unwrap();
+    assert_eq!(
+      rule.to_css_string(PrinterOptions::default()).unwrap(),
       indoc! {r#"
-      @media print {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+    .foo {
+      color: red;
+    }"#}
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --print print;
+    let property = Property::parse_string("color".into(), "#f0f", ParserOptions::default()).unwrap();
+    assert_eq!(
+      property.to_css_string(false, PrinterOptions::default()).unwrap(),
+      "color: #f0f"
+    );
+    assert_eq!(
+      property.to_css_string(true, PrinterOptions::default()).unwrap(),
+      "color: #f0f !important"
+    );
 
-      @media (--print) and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color) {
-        .a {
-          color: green;
-        }
+    let code = indoc! { r#"
+      .foo {
+        color: green;
       }
-      "#},
-    );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --not-print not print;
+      .bar {
+        color: red;
+        background: pink;
+      }
 
-      @media (--not-print) and (--color) {
-        .a {
+      @media print {
+        .baz {
           color: green;
         }
       }
-      "#,
-      indoc! {r#"
-      @media not print and (color) {
-        .a {
-          color: green;
-        }
+    "#};
+    let stylesheet = StyleSheet::parse(code, ParserOptions::default()).unwrap();
+    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
+      let (key, val) = style.property_location(code, 0).unwrap();
+      assert_eq!(
+        key,
+        SourceLocation { line: 5, column: 3 }..SourceLocation { line: 5, column: 8 }
+      );
+      assert_eq!(
+        val,
+        SourceLocation { line: 5, column: 10 }..SourceLocation { line: 5, column: 13 }
+      );
+    }
+
+    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
+      let (key, val) = style.property_location(code, 1).unwrap();
+      assert_eq!(
+        key,
+        SourceLocation { line: 6, column: 3 }..SourceLocation { line: 6, column: 13 }
+      );
+      assert_eq!(
+        val,
+        SourceLocation { line: 6, column: 15 }..SourceLocation { line: 6, column: 19 }
+      );
+    }
+    if let CssRule::Media(media) = &stylesheet.rules.0[2] {
+      if let CssRule::Style(style) = &media.rules.0[0] {
+        let (key, val) = style.property_location(code, 0).unwrap();
+        assert_eq!(
+          key,
+          SourceLocation { line: 11, column: 5 }..SourceLocation { line: 11, column: 10 }
+        );
+        assert_eq!(
+          val,
+          SourceLocation { line: 11, column: 12 }..SourceLocation { line: 11, column: 17 }
+        );
       }
-      "#},
+    }
+
+    let mut property = Property::Transform(Default::default(), VendorPrefix::WebKit);
+    property.set_prefix(VendorPrefix::None);
+    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::None));
+    property.set_prefix(VendorPrefix::Moz);
+    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::Moz));
+    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz);
+    assert_eq!(
+      property,
+      Property::Transform(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --screen screen;
-      @custom-media --print print;
+    let mut property = Property::TextDecorationLine(Default::default(), VendorPrefix::None);
+    property.set_prefix(VendorPrefix::Ms);
+    assert_eq!(
+      property,
+      Property::TextDecorationLine(Default::default(), VendorPrefix::None)
+    );
+    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz | VendorPrefix::Ms);
+    assert_eq!(
+      property,
+      Property::TextDecorationLine(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
+    );
 
-      @media (--print) and (--color), (--screen) and (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and (color), screen and (color) {
-        .a {
-          color: green;
-        }
+    let mut property = Property::AccentColor(Default::default());
+    property.set_prefix(VendorPrefix::WebKit);
+    assert_eq!(property, Property::AccentColor(Default::default()));
+  }
+
+  #[cfg(feature = "substitute_variables")]
+  #[test]
+  fn test_substitute_vars() {
+    use crate::properties::custom::TokenList;
+    use crate::traits::ParseWithOptions;
+
+    fn test(property: Property, vars: HashMap<&str, &str>, expected: &str) {
+      if let Property::Unparsed(unparsed) = property {
+        let vars = vars
+          .into_iter()
+          .map(|(k, v)| {
+            (
+              k,
+              TokenList::parse_string_with_options(v, ParserOptions::default()).unwrap(),
+            )
+          })
+          .collect();
+        let substituted = unparsed.substitute_variables(&vars).unwrap();
+        assert_eq!(
+          substituted.to_css_string(false, PrinterOptions::default()).unwrap(),
+          expected
+        );
+      } else {
+        panic!("Not an unparsed property");
       }
-      "#},
+    }
+
+    let property = Property::parse_string("color".into(), "var(--test)", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--test", "yellow")]), "color: #ff0");
+
+    let property =
+      Property::parse_string("color".into(), "var(--test, var(--foo))", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--foo", "yellow")]), "color: #ff0");
+    let property = Property::parse_string(
+      "color".into(),
+      "var(--test, var(--foo, yellow))",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    test(property, HashMap::new(), "color: #ff0");
+
+    let property =
+      Property::parse_string("width".into(), "calc(var(--a) + var(--b))", ParserOptions::default()).unwrap();
+    test(property, HashMap::from([("--a", "2px"), ("--b", "4px")]), "width: 6px");
+
+    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
+    test(
+      property,
+      HashMap::from([("--a", "var(--b)"), ("--b", "yellow")]),
+      "color: #ff0",
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color print and (color), print and (script);
+    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
+    test(
+      property,
+      HashMap::from([("--a", "var(--b)"), ("--b", "var(--c)"), ("--c", "var(--a)")]),
+      "color: var(--a)",
+    );
+  }
 
-      @media (--color) {
-        .a {
-          color: green;
-        }
-      }
-      "#,
-      indoc! {r#"
-      @media print and ((color) or (script)) {
-        .a {
-          color: green;
-        }
-      }
-      "#},
+  #[cfg(feature = "substitute_variables")]
+  #[test]
+  fn test_inline_custom_properties() {
+    fn test(source: &str, expected: &str, values: HashMap<&str, &str>) {
+      let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+      stylesheet
+        .minify(MinifyOptions {
+          inline_custom_properties: values.into_iter().map(|(k, v)| (k.into(), v.into())).collect(),
+          ..MinifyOptions::default()
+        })
+        .unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      assert_eq!(res.code, expected);
+    }
+
+    test(
+      ":root { --brand: red; --spacing: 4px } .foo { color: var(--brand); padding: var(--spacing) calc(var(--spacing) * 2) }",
+      ".foo{color:red;padding:4px 8px}",
+      HashMap::from([("--brand", "red"), ("--spacing", "4px")]),
+    );
+    test(
+      ":root { --brand: red; --other: blue } .foo { color: var(--brand, green); background: var(--other) }",
+      ":root{--other:blue}.foo{color:red;background:var(--other)}",
+      HashMap::from([("--brand", "red")]),
+    );
+    test(
+      ".foo { color: var(--other, var(--brand)); border: 1px solid var(--missing, var(--brand)) }",
+      ".foo{color:var(--other,red);border:1px solid var(--missing,red)}",
+      HashMap::from([("--brand", "red")]),
+    );
+    test(
+      ".foo { --local: var(--brand); color: var(--accent) }",
+      ".foo{--local:rebeccapurple;color:#639}",
+      HashMap::from([("--brand", "rebeccapurple"), ("--accent", "var(--brand)")]),
+    );
+    test(
+      ".foo { --brand: blue } :root, .dark { --brand: blue } @media (prefers-color-scheme: dark) { .foo { color: var(--brand) } }",
+      ".foo,:root,.dark{--brand:blue}@media (prefers-color-scheme:dark){.foo{color:green}}",
+      HashMap::from([("--brand", "green")]),
+    );
+    test(
+      ".foo { color: var(--a) }",
+      ".foo{color:var(--a)}",
+      HashMap::from([("--a", "var(--b)"), ("--b", "var(--a)")]),
     );
 
-    custom_media_test(
-      r#"
-      @custom-media --color (color);
-      @custom-media --not-color not all and (--color);
+    let mut stylesheet = StyleSheet::parse(".foo { color: var(--brand) }", ParserOptions::default()).unwrap();
+    let err = stylesheet
+      .minify(MinifyOptions {
+        inline_custom_properties: HashMap::from([("--brand".into(), ")".into())]),
+        ..MinifyOptions::default()
+      })
+      .unwrap_err();
+    assert_eq!(
+      err.kind,
+      MinifyErrorKind::InvalidCustomPropertyValue { name: "--brand".into() }
+    );
+  }
 
-      @media (--not-color) {
-        .a {
-          color: green;
+  #[test]
+  fn test_layer() {
+    minify_test("@layer foo;", "@layer foo;");
+    minify_test("@layer foo, bar;", "@layer foo,bar;");
+    minify_test("@layer foo.bar;", "@layer foo.bar;");
+    minify_test("@layer foo.bar, baz;", "@layer foo.bar,baz;");
+
+    minify_test(
+      r#"
+      @layer foo {
+        .bar {
+          color: red;
         }
       }
-      "#,
-      indoc! {r#"
-        @media not all and (color) {
-          .a {
-            color: green;
-          }
-        }
-      "#},
+    "#,
+      "@layer foo{.bar{color:red}}",
     );
-
-    custom_media_test(
+    minify_test(
       r#"
-      @custom-media --color (color);
-
-      @media not all and (--color) {
-        .a {
-          color: green;
+      @layer foo.bar {
+        .bar {
+          color: red;
         }
       }
-      "#,
-      indoc! {r#"
-        @media not all and (color) {
-          .a {
-            color: green;
-          }
-        }
-      "#},
+    "#,
+      "@layer foo.bar{.bar{color:red}}",
     );
+    minify_test(r#"
+      @layer base {
+        p { max-width: 70ch; }
+      }
 
-    custom_media_test(
-      r#"
-      @media (--print) {
-        .a {
-          color: green;
+      @layer framework {
+        @layer base {
+          p { margin-block: 0.75em; }
         }
-      }
 
-      @custom-media --print print;
-      "#,
-      indoc! {r#"
-      @media print {
-        .a {
-          color: green;
+        @layer theme {
+          p { color: #222; }
         }
       }
-      "#},
-    );
-
-    custom_media_test(
+    "#, "@layer base{p{max-width:70ch}}@layer framework{@layer base{p{margin-block:.75em}}@layer theme{p{color:#222}}}");
+    minify_test(
       r#"
-      @custom-media --not-width not (min-width: 300px);
-      @media screen and ((prefers-color-scheme: dark) or (--not-width)) {
-        .foo {
-          order: 6;
+      @layer {
+        .bar {
+          color: red;
         }
       }
-      "#,
-      indoc! {r#"
-      @media screen and ((prefers-color-scheme: dark) or (not (width >= 300px))) {
-        .foo {
-          order: 6;
+    "#,
+      "@layer{.bar{color:red}}",
+    );
+    minify_test(
+      r#"
+      @layer foo\20 bar, baz;
+    "#,
+      "@layer foo\\ bar,baz;",
+    );
+    minify_test(
+      r#"
+      @layer one.two\20 three\#four\.five {
+        .bar {
+          color: red;
         }
       }
-      "#},
+    "#,
+      "@layer one.two\\ three\\#four\\.five{.bar{color:red}}",
     );
 
-    fn custom_media_error_test(source: &str, err: Error<MinifyErrorKind>) {
-      let mut stylesheet = StyleSheet::parse(
-        &source,
-        ParserOptions {
-          filename: "test.css".into(),
-          flags: ParserFlags::CUSTOM_MEDIA,
-          ..ParserOptions::default()
-        },
-      )
-      .unwrap();
-      let res = stylesheet.minify(MinifyOptions {
-        targets: Browsers {
-          chrome: Some(95 << 16),
-          ..Browsers::default()
-        }
-        .into(),
-        ..MinifyOptions::default()
-      });
-      assert_eq!(res, Err(err))
-    }
-
-    custom_media_error_test(
+    error_test("@layer;", ParserError::UnexpectedToken(Token::Semicolon));
+    error_test("@layer foo, bar {};", ParserError::AtRuleBodyInvalid);
+    minify_test("@import 'test.css' layer;", "@import \"test.css\" layer;");
+    minify_test("@import 'test.css' layer(foo);", "@import \"test.css\" layer(foo);");
+    minify_test(
+      "@import 'test.css' layer(foo.bar);",
+      "@import \"test.css\" layer(foo.bar);",
+    );
+    minify_test(
+      "@import 'test.css' layer(foo\\20 bar);",
+      "@import \"test.css\" layer(foo\\ bar);",
+    );
+    error_test(
+      "@import 'test.css' layer(foo, bar) {};",
+      ParserError::UnexpectedToken(Token::Comma),
+    );
+    minify_test(
       r#"
-      @custom-media --color-print print and (color);
-
-      @media screen and (--color-print) {
-        .a {
-          color: green;
+      @layer one {
+        body {
+          background: red;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
-    );
 
-    custom_media_error_test(
-      r#"
-      @custom-media --color-print print and (color);
+      body {
+        background: red;
+      }
 
-      @media not print and (--color-print) {
-        .a {
-          color: green;
+      @layer two {
+        body {
+          background: green;
         }
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
-    );
-
-    custom_media_error_test(
-      r#"
-      @custom-media --color-print print and (color);
-      @custom-media --color-screen screen and (color);
 
-      @media (--color-print) or (--color-screen) {}
+      @layer one {
+        body {
+          background: yellow;
+        }
+      }
       "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+      "@layer one{body{background:#ff0}}body{background:red}@layer two{body{background:green}}",
     );
+  }
 
-    custom_media_error_test(
+  #[test]
+  fn test_property() {
+    minify_test(
       r#"
-      @custom-media --color-print print and (color);
-      @custom-media --color-screen screen and (color);
-
-      @media (--color-print) and (--color-screen) {}
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: yellow;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>\";inherits:false;initial-value:#ff0}",
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --screen screen;
-      @custom-media --print print;
+      @property --property-name {
+        syntax: '<length>';
+        inherits: true;
+        initial-value: 25px;
+      }
+    "#,
+      "@property --property-name{syntax:\"<length>\";inherits:true;initial-value:25px}",
+    );
 
-      @media (--print) and (--screen) {}
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    error_test(
+      r#"
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
+        initial-value: 25px;
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Dimension {
+        has_sign: false,
+        value: 25.0,
+        int_value: Some(25),
+        unit: "px".into(),
+      }),
     );
 
-    custom_media_error_test(
+    error_test(
       r#"
-      @custom-media --not-print not print and (color);
-      @custom-media --not-screen not screen and (color);
+      @property --property-name {
+        syntax: '<length>';
+        inherits: false;
+        initial-value: var(--some-value);
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("var".into())),
+    );
 
-      @media ((script) or ((--not-print) and (--not-screen))) {
-        .a {
-          color: green;
-        }
+    error_test(
+      r#"
+      @property --property-name {
+        syntax: '<color>';
+        inherits: false;
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 2,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
 
-    custom_media_error_test(
+    minify_test(
       r#"
-      @custom-media --color screen and (color), print and (color);
+      @property --property-name {
+        syntax: '*';
+        inherits: false;
+      }
+    "#,
+      "@property --property-name{syntax:\"*\";inherits:false}",
+    );
 
-      @media (--color) {
-        .a {
-          color: green;
-        }
+    error_test(
+      r#"
+      @property --property-name {
+        syntax: '*';
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::UnsupportedCustomMediaBooleanLogic {
-          custom_media_loc: Location {
-            source_index: 0,
-            line: 1,
-            column: 7,
-          },
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 3,
-          column: 7,
-        }),
-      },
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
 
-    custom_media_error_test(
+    error_test(
       r#"
-      @media (--not-defined) {
-        .a {
-          color: green;
-        }
+      @property --property-name {
+        inherits: false;
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::CustomMediaNotDefined {
-          name: "--not-defined".into(),
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 1,
-          column: 7,
-        }),
-      },
+    "#,
+      ParserError::AtRuleBodyInvalid,
     );
 
-    custom_media_error_test(
+    error_test(
       r#"
-      @custom-media --circular-mq-a (--circular-mq-b);
-      @custom-media --circular-mq-b (--circular-mq-a);
+      @property property-name {
+        syntax: '*';
+        inherits: false;
+      }
+    "#,
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("property-name".into())),
+    );
 
-      @media (--circular-mq-a) {
-        body {
-          order: 3;
-        }
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: 'custom | <color>';
+        inherits: false;
+        initial-value: yellow;
       }
-      "#,
-      Error {
-        kind: MinifyErrorKind::CircularCustomMedia {
-          name: "--circular-mq-a".into(),
-        },
-        loc: Some(ErrorLocation {
-          filename: "test.css".into(),
-          line: 4,
-          column: 7,
-        }),
-      },
+    "#,
+      "@property --property-name{syntax:\"custom|<color>\";inherits:false;initial-value:#ff0}",
     );
-  }
 
-  #[test]
-  fn test_dependencies() {
-    fn dep_test(source: &str, expected: &str, deps: Vec<(&str, &str)>) {
-      let mut stylesheet = StyleSheet::parse(
-        &source,
-        ParserOptions {
-          filename: "test.css".into(),
-          ..ParserOptions::default()
-        },
-      )
-      .unwrap();
-      stylesheet.minify(MinifyOptions::default()).unwrap();
-      let res = stylesheet
-        .to_css(PrinterOptions {
-          analyze_dependencies: Some(Default::default()),
-          minify: true,
-          ..PrinterOptions::default()
-        })
-        .unwrap();
-      assert_eq!(res.code, expected);
-      let dependencies = res.dependencies.unwrap();
-      assert_eq!(dependencies.len(), deps.len());
-      for (i, (url, placeholder)) in deps.into_iter().enumerate() {
-        match &dependencies[i] {
-          Dependency::Url(dep) => {
-            assert_eq!(dep.url, url);
-            assert_eq!(dep.placeholder, placeholder);
-          }
-          Dependency::Import(dep) => {
-            assert_eq!(dep.url, url);
-            assert_eq!(dep.placeholder, placeholder);
-          }
-        }
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<transform-list>';
+        inherits: false;
+        initial-value: translate(200px,300px) translate(100px,200px) scale(2);
       }
-    }
+    "#, "@property --property-name{syntax:\"<transform-list>\";inherits:false;initial-value:matrix(2,0,0,2,300,500)}");
 
-    fn dep_error_test(source: &str, error: PrinterErrorKind) {
-      let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-      let res = stylesheet.to_css(PrinterOptions {
-        analyze_dependencies: Some(Default::default()),
-        ..PrinterOptions::default()
-      });
-      match res {
-        Err(e) => assert_eq!(e.kind, error),
-        _ => unreachable!(),
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<time>';
+        inherits: false;
+        initial-value: 1000ms;
       }
-    }
+    "#,
+      "@property --property-name{syntax:\"<time>\";inherits:false;initial-value:1s}",
+    );
 
-    dep_test(
-      ".foo { background: image-set('./img12x.png', './img21x.png' 2x)}",
-      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
-      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<url>';
+        inherits: false;
+        initial-value: url("foo.png");
+      }
+    "#,
+      "@property --property-name{syntax:\"<url>\";inherits:false;initial-value:url(foo.png)}",
+    );
+
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<image>';
+        inherits: false;
+        initial-value: linear-gradient(yellow, blue);
+      }
+    "#,
+      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
     );
 
-    dep_test(
-      ".foo { background: image-set(url(./img12x.png), url('./img21x.png') 2x)}",
-      ".foo{background:image-set(\"hXFI8W\" 1x,\"5TkpBa\" 2x)}",
-      vec![("./img12x.png", "hXFI8W"), ("./img21x.png", "5TkpBa")],
+    minify_test(
+      r#"
+      @property --property-name {
+        initial-value: linear-gradient(yellow, blue);
+        inherits: false;
+        syntax: '<image>';
+      }
+    "#,
+      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
     );
 
-    dep_test(
-      ".foo { --test: url(/foo.png) }",
-      ".foo{--test:url(\"lDnnrG\")}",
-      vec![("/foo.png", "lDnnrG")],
+    test(
+      r#"
+      @property --property-name {
+        syntax: '<length>|none';
+        inherits: false;
+        initial-value: none;
+      }
+    "#,
+      indoc! {r#"
+      @property --property-name {
+        syntax: "<length> | none";
+        inherits: false;
+        initial-value: none;
+      }
+    "#},
     );
 
-    dep_test(
-      ".foo { --test: url(\"/foo.png\") }",
-      ".foo{--test:url(\"lDnnrG\")}",
-      vec![("/foo.png", "lDnnrG")],
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<color>#';
+        inherits: false;
+        initial-value: yellow, blue;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>#\";inherits:false;initial-value:#ff0,#00f}",
     );
-
-    dep_test(
-      ".foo { --test: url(\"http://example.com/foo.png\") }",
-      ".foo{--test:url(\"3X1zSW\")}",
-      vec![("http://example.com/foo.png", "3X1zSW")],
+    minify_test(
+      r#"
+      @property --property-name {
+        syntax: '<color>+';
+        inherits: false;
+        initial-value: yellow blue;
+      }
+    "#,
+      "@property --property-name{syntax:\"<color>+\";inherits:false;initial-value:#ff0 #00f}",
     );
+  }
 
-    dep_test(
-      ".foo { --test: url(\"data:image/svg+xml;utf8,<svg></svg>\") }",
-      ".foo{--test:url(\"-vl-rG\")}",
-      vec![("data:image/svg+xml;utf8,<svg></svg>", "-vl-rG")],
+  #[test]
+  fn test_registered_custom_properties() {
+    use crate::interpolate::interpolate;
+    use crate::values::syntax::SyntaxString;
+
+    // Values are parsed according to the registered syntax, even before the @property rule.
+    minify_test(
+      r#"
+      .foo {
+        --size: calc(10px + 5px);
+        --other: 0px;
+        --any: 0px;
+        width: var(--size, calc(1px + 2px));
+      }
+      .bar {
+        --size: red;
+      }
+      @property --size {
+        syntax: '<length>';
+        inherits: false;
+        initial-value: 0px;
+      }
+      @property --any {
+        syntax: '*';
+        inherits: false;
+      }
+    "#,
+      ".foo{--size:15px;--other:0px;--any:0px;width:var(--size,3px)}.bar{--size:red}@property --size{syntax:\"<length>\";inherits:false;initial-value:0}@property --any{syntax:\"*\";inherits:false}",
     );
 
-    dep_test(
-      ".foo { background: url(\"foo.png\") var(--test) }",
-      ".foo{background:url(\"Vwkwkq\") var(--test)}",
-      vec![("foo.png", "Vwkwkq")],
+    minify_test(
+      r#"
+      @property --list {
+        syntax: '<length>#';
+        inherits: false;
+        initial-value: 0px;
+      }
+      .foo {
+        --list: 0px , calc(1px * 2);
+      }
+    "#,
+      "@property --list{syntax:\"<length>#\";inherits:false;initial-value:0}.foo{--list:0,2px}",
     );
 
-    dep_error_test(
-      ".foo { --test: url(\"foo.png\") }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    // @property rules nested in conditional group rules and layers are registered too, but not within style rules.
+    minify_test(
+      r#"
+      .foo {
+        --size: calc(10px + 5px);
+        --color: calc(10px + 5px);
+      }
+      @layer base {
+        @supports (display: grid) {
+          @property --size {
+            syntax: '<length>';
+            inherits: false;
+            initial-value: 0px;
+          }
+        }
+      }
+      .bar {
+        @property --color {
+          syntax: '<length>';
+          inherits: false;
+          initial-value: 0px;
+        }
+      }
+    "#,
+      ".foo{--size:15px;--color:calc(10px + 5px)}@layer base{@supports (display:grid){@property --size{syntax:\"<length>\";inherits:false;initial-value:0}}}.bar{@property --color{syntax: \"<length>\"; inherits: false; initial-value: 0px;}}",
     );
 
-    dep_error_test(
-      ".foo { --test: url(foo.png) }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty { url: "foo.png".into() },
+    // Properties registered externally take precedence over @property rules.
+    let mut registered_properties = HashMap::new();
+    registered_properties.insert("--size".into(), SyntaxString::parse_string("<length>").unwrap());
+    registered_properties.insert("--count".into(), SyntaxString::parse_string("<integer>").unwrap());
+    let stylesheet = StyleSheet::parse(
+      "@property --size { syntax: '<color>'; inherits: false; initial-value: red } .foo { --size: calc(1px + 2px) }",
+      ParserOptions {
+        registered_properties: registered_properties.clone(),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "@property --size{syntax:\"<color>\";inherits:false;initial-value:red}.foo{--size:3px}"
+    );
+
+    // Registered properties are interpolated according to their syntax.
+    fn test_interpolate(
+      registered_properties: &HashMap<String, SyntaxString>,
+      name: &str,
+      from: &str,
+      to: &str,
+      expected: Option<&str>,
+    ) {
+      let parse = |value| {
+        let options = ParserOptions {
+          registered_properties: registered_properties.clone(),
+          ..ParserOptions::default()
+        };
+        Property::parse_string(name.into(), value, options).unwrap()
+      };
+      let res = interpolate(&parse(from), &parse(to), 0.5)
+        .map(|v| v.value_to_css_string(PrinterOptions::default()).unwrap());
+      assert_eq!(res.as_deref(), expected);
+    }
+
+    test_interpolate(&registered_properties, "--size", "10px", "20px", Some("15px"));
+    test_interpolate(&registered_properties, "--size", "10px", "var(--x)", None);
+    test_interpolate(&registered_properties, "--count", "1", "2", Some("2"));
+    test_interpolate(&registered_properties, "--other", "10px", "20px", None);
+
+    // Values and var() fallbacks that do not match the registered syntax are reported.
+    let source = indoc! {r#"
+      @property --size { syntax: '<length>'; inherits: false; initial-value: 0px }
+      .foo { --size: red; width: var(--size, blue); height: var(--size, 1px); --size: var(--x); --size: inherit }
+    "#};
+    let warnings = Arc::new(RwLock::new(Vec::new()));
+    StyleSheet::parse(
+      source,
+      ParserOptions {
+        validation: ValidationMode::Warn,
+        warnings: Some(warnings.clone()),
+        ..ParserOptions::default()
+      },
+    )
+    .unwrap();
+    let warnings = warnings.read().unwrap();
+    assert_eq!(
+      warnings
+        .iter()
+        .map(|w| {
+          let loc = w.loc.as_ref().unwrap();
+          (w.kind.to_string(), loc.line, loc.column)
+        })
+        .collect::<Vec<_>>(),
+      vec![
+        ("Invalid value for property: --size".into(), 1, 16),
+        ("Invalid fallback for registered custom property: --size".into(), 1, 28),
+      ]
     );
 
-    dep_error_test(
-      ".foo { --test: url(./foo.png) }",
-      PrinterErrorKind::AmbiguousUrlInCustomProperty {
-        url: "./foo.png".into(),
+    let err = StyleSheet::parse(
+      "@property --size { syntax: '<length>'; inherits: false; initial-value: 0px } .foo { width: var(--size, blue) }",
+      ParserOptions {
+        validation: ValidationMode::Error,
+        ..ParserOptions::default()
       },
+    )
+    .unwrap_err();
+    assert_eq!(err.kind, ParserError::InvalidVariableFallback("--size".into()));
+  }
+
+  #[test]
+  fn test_quoting_unquoting_urls() {
+    // Quotes remain double quotes when not minifying
+    test(
+      r#".foo {
+      background-image: url("0123abcd");
+    }"#,
+      r#".foo {
+  background-image: url("0123abcd");
+}
+"#,
     );
 
-    dep_test(
-      ".foo { behavior: url(#foo) }",
-      ".foo{behavior:url(\"Zn9-2q\")}",
-      vec![("#foo", "Zn9-2q")],
+    // Quotes removed when minifying
+    minify_test(
+      r#".foo {
+      background-image: url("0123abcd");
+    }"#,
+      r#".foo{background-image:url(0123abcd)}"#,
     );
 
-    dep_test(
-      ".foo { --foo: url(#foo) }",
-      ".foo{--foo:url(\"Zn9-2q\")}",
-      vec![("#foo", "Zn9-2q")],
+    // Doubles quotes added if not present when not minifying
+    test(
+      r#".foo {
+      background-image: url(0123abcd);
+    }"#,
+      r#".foo {
+  background-image: url("0123abcd");
+}
+"#,
     );
 
-    dep_test(
-      "@import \"test.css\"; .foo { color: red }",
-      "@import \"hHsogW\";.foo{color:red}",
-      vec![("test.css", "hHsogW")],
+    // No quotes changed if not present when not minifying
+    minify_test(
+      r#".foo {
+      background-image: url(0123abcd);
+    }"#,
+      r#".foo{background-image:url(0123abcd)}"#,
     );
   }
 
   #[test]
-  fn test_api() {
-    let stylesheet = StyleSheet::parse(".foo:hover { color: red }", ParserOptions::default()).unwrap();
-    match &stylesheet.rules.0[0] {
-      CssRule::Style(s) => {
-        assert_eq!(&s.selectors.to_string(), ".foo:hover");
-      }
-      _ => unreachable!(),
+  fn test_zindex() {
+    minify_test(".foo { z-index: 2 }", ".foo{z-index:2}");
+    minify_test(".foo { z-index: -2 }", ".foo{z-index:-2}");
+    minify_test(".foo { z-index: 999999 }", ".foo{z-index:999999}");
+    minify_test(".foo { z-index: 9999999 }", ".foo{z-index:9999999}");
+    minify_test(".foo { z-index: -9999999 }", ".foo{z-index:-9999999}");
+  }
+
+  #[test]
+  #[cfg(feature = "sourcemap")]
+  fn test_input_source_map() {
+    let source = r#".imported {
+      content: "yay, file support!";
     }
 
-    let color = CssColor::parse_string("#f0f").unwrap();
-    assert_eq!(color.to_css_string(PrinterOptions::default()).unwrap(), "#f0f");
+    .selector {
+      margin: 1em;
+      background-color: #f60;
+    }
 
-    let rule = CssRule::parse_string(".foo { color: red }", ParserOptions::default()).unwrap();
-    assert_eq!(
-      rule.to_css_string(PrinterOptions::default()).unwrap(),
-      indoc! {r#"
-    .foo {
-      color: red;
-    }"#}
-    );
+    .selector .nested {
+      margin: 0.5em;
+    }
 
-    let property = Property::parse_string("color".into(), "#f0f", ParserOptions::default()).unwrap();
-    assert_eq!(
-      property.to_css_string(false, PrinterOptions::default()).unwrap(),
-      "color: #f0f"
-    );
+    /*# sourceMappingURL=data:application/json;base64,ewoJInZlcnNpb24iOiAzLAoJInNvdXJjZVJvb3QiOiAicm9vdCIsCgkiZmlsZSI6ICJzdGRvdXQiLAoJInNvdXJjZXMiOiBbCgkJInN0ZGluIiwKCQkic2Fzcy9fdmFyaWFibGVzLnNjc3MiLAoJCSJzYXNzL19kZW1vLnNjc3MiCgldLAoJInNvdXJjZXNDb250ZW50IjogWwoJCSJAaW1wb3J0IFwiX3ZhcmlhYmxlc1wiO1xuQGltcG9ydCBcIl9kZW1vXCI7XG5cbi5zZWxlY3RvciB7XG4gIG1hcmdpbjogJHNpemU7XG4gIGJhY2tncm91bmQtY29sb3I6ICRicmFuZENvbG9yO1xuXG4gIC5uZXN0ZWQge1xuICAgIG1hcmdpbjogJHNpemUgLyAyO1xuICB9XG59IiwKCQkiJGJyYW5kQ29sb3I6ICNmNjA7XG4kc2l6ZTogMWVtOyIsCgkJIi5pbXBvcnRlZCB7XG4gIGNvbnRlbnQ6IFwieWF5LCBmaWxlIHN1cHBvcnQhXCI7XG59IgoJXSwKCSJtYXBwaW5ncyI6ICJBRUFBLFNBQVMsQ0FBQztFQUNSLE9BQU8sRUFBRSxvQkFBcUI7Q0FDL0I7O0FGQ0QsU0FBUyxDQUFDO0VBQ1IsTUFBTSxFQ0hELEdBQUc7RURJUixnQkFBZ0IsRUNMTCxJQUFJO0NEVWhCOztBQVBELFNBQVMsQ0FJUCxPQUFPLENBQUM7RUFDTixNQUFNLEVDUEgsS0FBRztDRFFQIiwKCSJuYW1lcyI6IFtdCn0= */"#;
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let mut sm = parcel_sourcemap::SourceMap::new("/");
+    stylesheet
+      .to_css(PrinterOptions {
+        source_map: Some(&mut sm),
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    let map = sm.to_json(None).unwrap();
     assert_eq!(
-      property.to_css_string(true, PrinterOptions::default()).unwrap(),
-      "color: #f0f !important"
+      map,
+      r#"{"version":3,"sourceRoot":null,"mappings":"AAAA,uCCGA,2CAAA","sources":["sass/_demo.scss","stdin"],"sourcesContent":[".imported {\n  content: \"yay, file support!\";\n}","@import \"_variables\";\n@import \"_demo\";\n\n.selector {\n  margin: $size;\n  background-color: $brandColor;\n\n  .nested {\n    margin: $size / 2;\n  }\n}"],"names":[]}"#
     );
+  }
 
-    let code = indoc! { r#"
-      .foo {
-        color: green;
-      }
-
-      .bar {
-        color: red;
-        background: pink;
-      }
+  #[test]
+  fn test_minify_dual() {
+    let source = r#"
+      .foo {
+        color: lab(40% 56.6 39);
 
-      @media print {
-        .baz {
-          color: green;
+        &:hover {
+          color: red;
         }
       }
-    "#};
-    let stylesheet = StyleSheet::parse(code, ParserOptions::default()).unwrap();
-    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
-      let (key, val) = style.property_location(code, 0).unwrap();
-      assert_eq!(
-        key,
-        SourceLocation { line: 5, column: 3 }..SourceLocation { line: 5, column: 8 }
-      );
-      assert_eq!(
-        val,
-        SourceLocation { line: 5, column: 10 }..SourceLocation { line: 5, column: 13 }
-      );
-    }
+    "#;
 
-    if let CssRule::Style(style) = &stylesheet.rules.0[1] {
-      let (key, val) = style.property_location(code, 1).unwrap();
-      assert_eq!(
-        key,
-        SourceLocation { line: 6, column: 3 }..SourceLocation { line: 6, column: 13 }
-      );
-      assert_eq!(
-        val,
-        SourceLocation { line: 6, column: 15 }..SourceLocation { line: 6, column: 19 }
-      );
-    }
-    if let CssRule::Media(media) = &stylesheet.rules.0[2] {
-      if let CssRule::Style(style) = &media.rules.0[0] {
-        let (key, val) = style.property_location(code, 0).unwrap();
-        assert_eq!(
-          key,
-          SourceLocation { line: 11, column: 5 }..SourceLocation { line: 11, column: 10 }
-        );
-        assert_eq!(
-          val,
-          SourceLocation { line: 11, column: 12 }..SourceLocation { line: 11, column: 17 }
-        );
-      }
-    }
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    let modern_targets = Browsers {
+      chrome: Some(120 << 16),
+      ..Browsers::default()
+    };
+    let legacy_targets = Browsers {
+      chrome: Some(80 << 16),
+      ..Browsers::default()
+    };
+    let legacy = stylesheet
+      .minify_dual(
+        MinifyOptions {
+          targets: modern_targets.into(),
+          ..MinifyOptions::default()
+        },
+        MinifyOptions {
+          targets: legacy_targets.into(),
+          ..MinifyOptions::default()
+        },
+      )
+      .unwrap();
 
-    let mut property = Property::Transform(Default::default(), VendorPrefix::WebKit);
-    property.set_prefix(VendorPrefix::None);
-    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::None));
-    property.set_prefix(VendorPrefix::Moz);
-    assert_eq!(property, Property::Transform(Default::default(), VendorPrefix::Moz));
-    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz);
-    assert_eq!(
-      property,
-      Property::Transform(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
-    );
+    let mut modern_map = parcel_sourcemap::SourceMap::new("/");
+    let modern = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        targets: modern_targets.into(),
+        source_map: Some(&mut modern_map),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(modern.code, ".foo{color:lab(40% 56.6 39);&:hover{color:red}}");
 
-    let mut property = Property::TextDecorationLine(Default::default(), VendorPrefix::None);
-    property.set_prefix(VendorPrefix::Ms);
-    assert_eq!(
-      property,
-      Property::TextDecorationLine(Default::default(), VendorPrefix::None)
-    );
-    property.set_prefix(VendorPrefix::WebKit | VendorPrefix::Moz | VendorPrefix::Ms);
+    let mut legacy_map = parcel_sourcemap::SourceMap::new("/");
+    let legacy = legacy
+      .to_css(PrinterOptions {
+        minify: true,
+        targets: legacy_targets.into(),
+        source_map: Some(&mut legacy_map),
+        ..PrinterOptions::default()
+      })
+      .unwrap();
     assert_eq!(
-      property,
-      Property::TextDecorationLine(Default::default(), VendorPrefix::WebKit | VendorPrefix::Moz)
+      legacy.code,
+      ".foo{color:#b32323;color:lab(40% 56.6 39)}.foo:hover{color:red}"
     );
 
-    let mut property = Property::AccentColor(Default::default());
-    property.set_prefix(VendorPrefix::WebKit);
-    assert_eq!(property, Property::AccentColor(Default::default()));
+    assert!(modern_map.get_mappings().len() > 0);
+    assert!(legacy_map.get_mappings().len() > 0);
   }
 
-  #[cfg(feature = "substitute_variables")]
   #[test]
-  fn test_substitute_vars() {
-    use crate::properties::custom::TokenList;
-    use crate::traits::ParseWithOptions;
+  fn test_error_recovery() {
+    use std::sync::{Arc, RwLock};
+    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
+    test_with_options(
+      r#"
+      h1(>h1) {
+        color: red;
+      }
 
-    fn test(property: Property, vars: HashMap<&str, &str>, expected: &str) {
-      if let Property::Unparsed(unparsed) = property {
-        let vars = vars
-          .into_iter()
-          .map(|(k, v)| {
-            (
-              k,
-              TokenList::parse_string_with_options(v, ParserOptions::default()).unwrap(),
-            )
-          })
-          .collect();
-        let substituted = unparsed.substitute_variables(&vars).unwrap();
-        assert_eq!(
-          substituted.to_css_string(false, PrinterOptions::default()).unwrap(),
-          expected
-        );
-      } else {
-        panic!("Not an unparsed property");
+      .foo {
+        color: red;
       }
-    }
 
-    let property = Property::parse_string("color".into(), "var(--test)", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--test", "yellow")]), "color: #ff0");
+      .clearfix {
+        *zoom: 1;
+        background: red;
+      }
 
-    let property =
-      Property::parse_string("color".into(), "var(--test, var(--foo))", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--foo", "yellow")]), "color: #ff0");
-    let property = Property::parse_string(
-      "color".into(),
-      "var(--test, var(--foo, yellow))",
-      ParserOptions::default(),
-    )
-    .unwrap();
-    test(property, HashMap::new(), "color: #ff0");
+      @media (hover) {
+        h1(>h1) {
+          color: red;
+        }
 
-    let property =
-      Property::parse_string("width".into(), "calc(var(--a) + var(--b))", ParserOptions::default()).unwrap();
-    test(property, HashMap::from([("--a", "2px"), ("--b", "4px")]), "width: 6px");
+        .bar {
+          color: red;
+        }
+      }
+    "#,
+      indoc! { r#"
+      .foo {
+        color: red;
+      }
 
-    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
-    test(
-      property,
-      HashMap::from([("--a", "var(--b)"), ("--b", "yellow")]),
-      "color: #ff0",
-    );
+      .clearfix {
+        background: red;
+      }
 
-    let property = Property::parse_string("color".into(), "var(--a)", ParserOptions::default()).unwrap();
-    test(
-      property,
-      HashMap::from([("--a", "var(--b)"), ("--b", "var(--c)"), ("--c", "var(--a)")]),
-      "color: var(--a)",
+      @media (hover) {
+        .bar {
+          color: red;
+        }
+      }
+    "#},
+      ParserOptions {
+        filename: "test.css".into(),
+        error_recovery: true,
+        warnings: warnings.clone(),
+        ..ParserOptions::default()
+      },
     );
+    let w = warnings.unwrap();
+    let warnings = w.read().unwrap();
+    assert_eq!(
+      *warnings,
+      vec![
+        Error {
+          kind: ParserError::SelectorError(SelectorError::EmptySelector),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 1,
+            column: 7
+          })
+        },
+        Error {
+          kind: ParserError::UnexpectedToken(Token::Semicolon),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 10,
+            column: 17
+          })
+        },
+        Error {
+          kind: ParserError::SelectorError(SelectorError::EmptySelector),
+          loc: Some(ErrorLocation {
+            filename: "test.css".into(),
+            line: 15,
+            column: 9
+          })
+        },
+      ]
+    )
   }
 
   #[test]
-  fn test_layer() {
-    minify_test("@layer foo;", "@layer foo;");
-    minify_test("@layer foo, bar;", "@layer foo,bar;");
-    minify_test("@layer foo.bar;", "@layer foo.bar;");
-    minify_test("@layer foo.bar, baz;", "@layer foo.bar,baz;");
+  fn test_invalid() {
+    error_test(
+      ".a{color: hsla(120, 62.32%;}",
+      ParserError::UnexpectedToken(Token::CloseCurlyBracket),
+    );
+    error_test(
+      ".a{--foo: url(foo\\) b\\)ar)}",
+      ParserError::UnexpectedToken(Token::BadUrl("foo\\) b\\)ar".into())),
+    );
+  }
 
+  #[test]
+  fn test_container_queries() {
+    // with name
     minify_test(
       r#"
-      @layer foo {
-        .bar {
+      @container my-layout (inline-size > 45em) {
+        .foo {
           color: red;
         }
       }
     "#,
-      "@layer foo{.bar{color:red}}",
+      "@container my-layout (inline-size>45em){.foo{color:red}}",
     );
+
     minify_test(
       r#"
-      @layer foo.bar {
-        .bar {
+      @container my-layout ( not (width > 500px) ) {
+        .foo {
           color: red;
         }
       }
     "#,
-      "@layer foo.bar{.bar{color:red}}",
+      "@container my-layout not (width>500px){.foo{color:red}}",
     );
-    minify_test(r#"
-      @layer base {
-        p { max-width: 70ch; }
-      }
-
-      @layer framework {
-        @layer base {
-          p { margin-block: 0.75em; }
-        }
 
-        @layer theme {
-          p { color: #222; }
-        }
-      }
-    "#, "@layer base{p{max-width:70ch}}@layer framework{@layer base{p{margin-block:.75em}}@layer theme{p{color:#222}}}");
     minify_test(
       r#"
-      @layer {
-        .bar {
+      @container my-layout not (width > 500px) {
+        .foo {
           color: red;
         }
       }
     "#,
-      "@layer{.bar{color:red}}",
+      "@container my-layout not (width>500px){.foo{color:red}}",
     );
+
     minify_test(
       r#"
-      @layer foo\20 bar, baz;
+      @container not (width > 500px) {
+        .foo {
+          color: red;
+        }
+      }
     "#,
-      "@layer foo\\ bar,baz;",
+      "@container not (width>500px){.foo{color:red}}",
     );
+
     minify_test(
       r#"
-      @layer one.two\20 three\#four\.five {
-        .bar {
+      @container my-layout ((width: 100px) and (not (height: 100px))) {
+        .foo {
           color: red;
         }
       }
     "#,
-      "@layer one.two\\ three\\#four\\.five{.bar{color:red}}",
+      "@container my-layout (width:100px) and (not (height:100px)){.foo{color:red}}",
     );
 
-    error_test("@layer;", ParserError::UnexpectedToken(Token::Semicolon));
-    error_test("@layer foo, bar {};", ParserError::AtRuleBodyInvalid);
-    minify_test("@import 'test.css' layer;", "@import \"test.css\" layer;");
-    minify_test("@import 'test.css' layer(foo);", "@import \"test.css\" layer(foo);");
-    minify_test(
-      "@import 'test.css' layer(foo.bar);",
-      "@import \"test.css\" layer(foo.bar);",
-    );
-    minify_test(
-      "@import 'test.css' layer(foo\\20 bar);",
-      "@import \"test.css\" layer(foo\\ bar);",
-    );
-    error_test(
-      "@import 'test.css' layer(foo, bar) {};",
-      ParserError::UnexpectedToken(Token::Comma),
-    );
     minify_test(
       r#"
-      @layer one {
-        body {
-          background: red;
-        }
-      }
-
-      body {
-        background: red;
-      }
-
-      @layer two {
-        body {
-          background: green;
+      @container my-layout (width = max(10px, 10em)) {
+        .foo {
+          color: red;
         }
       }
+    "#,
+      "@container my-layout (width=max(10px,10em)){.foo{color:red}}",
+    );
 
-      @layer one {
-        body {
-          background: yellow;
+    // without name
+    minify_test(
+      r#"
+      @container (inline-size > 45em) {
+        .foo {
+          color: red;
         }
       }
-      "#,
-      "@layer one{body{background:#ff0}}body{background:red}@layer two{body{background:green}}",
+    "#,
+      "@container (inline-size>45em){.foo{color:red}}",
     );
-  }
 
-  #[test]
-  fn test_property() {
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: yellow;
+      @container (inline-size > 45em) and (inline-size < 100em) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<color>\";inherits:false;initial-value:#ff0}",
+      "@container (inline-size>45em) and (inline-size<100em){.foo{color:red}}",
     );
 
+    // calc()
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<length>';
-        inherits: true;
-        initial-value: 25px;
+      @container (width > calc(100vw - 50px)) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<length>\";inherits:true;initial-value:25px}",
+      "@container (width>calc(100vw - 50px)){.foo{color:red}}",
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
-        initial-value: 25px;
+      @container (calc(100vh - 50px) <= height ) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Dimension {
-        has_sign: false,
-        value: 25.0,
-        int_value: Some(25),
-        unit: "px".into(),
-      }),
+      "@container (height>=calc(100vh - 50px)){.foo{color:red}}",
     );
 
-    error_test(
+    // merge adjacent
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<length>';
-        inherits: false;
-        initial-value: var(--some-value);
+      @container my-layout (inline-size > 45em) {
+        .foo {
+          color: red;
+        }
+      }
+
+      @container my-layout (inline-size > 45em) {
+        .foo {
+          background: yellow;
+        }
+
+        .bar {
+          color: white;
+        }
       }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("var".into())),
+      "@container my-layout (inline-size>45em){.foo{color:red;background:#ff0}.bar{color:#fff}}",
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>';
-        inherits: false;
-      }
+    .foo {
+      container-name: foo bar;
+      container-type: size;
+    }
     "#,
-      ParserError::AtRuleBodyInvalid,
+      ".foo{container:foo bar/size}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '*';
-        inherits: false;
-      }
+    .foo {
+      container-name: foo bar;
+      container-type: normal;
+    }
     "#,
-      "@property --property-name{syntax:\"*\";inherits:false}",
+      ".foo{container:foo bar}",
+    );
+    minify_test(
+      ".foo{ container-type: inline-size }",
+      ".foo{container-type:inline-size}",
+    );
+    minify_test(".foo{ container-name: none; }", ".foo{container-name:none}");
+    minify_test(".foo{ container-name: foo; }", ".foo{container-name:foo}");
+    minify_test(".foo{ container: foo / normal; }", ".foo{container:foo}");
+    minify_test(
+      ".foo{ container: foo / inline-size; }",
+      ".foo{container:foo/inline-size}",
     );
+    minify_test(".foo { width: calc(1cqw + 2cqw) }", ".foo{width:3cqw}");
+    minify_test(".foo { width: calc(1cqh + 2cqh) }", ".foo{width:3cqh}");
+    minify_test(".foo { width: calc(1cqi + 2cqi) }", ".foo{width:3cqi}");
+    minify_test(".foo { width: calc(1cqb + 2cqb) }", ".foo{width:3cqb}");
+    minify_test(".foo { width: calc(1cqmin + 2cqmin) }", ".foo{width:3cqmin}");
+    minify_test(".foo { width: calc(1cqmax + 2cqmax) }", ".foo{width:3cqmax}");
 
-    error_test(
+    // Unlike in @media, there is no need to convert the range syntax in @container,
+    // because browsers all support this syntax.
+    prefix_test(
       r#"
-      @property --property-name {
-        syntax: '*';
+      @container (width > 100px) {
+        .foo { padding: 5px; }
       }
-    "#,
-      ParserError::AtRuleBodyInvalid,
+      "#,
+      indoc! { r#"
+        @container (width > 100px) {
+          .foo {
+            padding: 5px;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(105 << 16),
+        ..Browsers::default()
+      },
     );
-
-    error_test(
+    prefix_test(
       r#"
-      @property --property-name {
-        inherits: false;
+      @container (min-width: 100px) {
+        .foo { padding: 5px; }
       }
-    "#,
-      ParserError::AtRuleBodyInvalid,
+      "#,
+      indoc! { r#"
+        @container (width >= 100px) {
+          .foo {
+            padding: 5px;
+          }
+        }
+      "#},
+      Browsers {
+        chrome: Some(105 << 16),
+        ..Browsers::default()
+      },
     );
 
-    error_test(
+    minify_test(
       r#"
-      @property property-name {
-        syntax: '*';
-        inherits: false;
+      @container style(--responsive: true) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("property-name".into())),
+      "@container style(--responsive:true){.foo{color:red}}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: 'custom | <color>';
-        inherits: false;
-        initial-value: yellow;
+      @container style(--responsive: true) and style(color: yellow) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"custom|<color>\";inherits:false;initial-value:#ff0}",
+      "@container style(--responsive:true) and style(color:#ff0){.foo{color:red}}",
     );
-
-    minify_test(r#"
-      @property --property-name {
-        syntax: '<transform-list>';
-        inherits: false;
-        initial-value: translate(200px,300px) translate(100px,200px) scale(2);
-      }
-    "#, "@property --property-name{syntax:\"<transform-list>\";inherits:false;initial-value:matrix(2,0,0,2,300,500)}");
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<time>';
-        inherits: false;
-        initial-value: 1000ms;
+      @container not style(--responsive: true) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<time>\";inherits:false;initial-value:1s}",
+      "@container not style(--responsive:true){.foo{color:red}}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<url>';
-        inherits: false;
-        initial-value: url("foo.png");
+      @container (inline-size > 45em) and style(--responsive: true) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<url>\";inherits:false;initial-value:url(foo.png)}",
+      "@container (inline-size>45em) and style(--responsive:true){.foo{color:red}}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<image>';
-        inherits: false;
-        initial-value: linear-gradient(yellow, blue);
+      @container style((accent-color: yellow) or (--bar: 10px)) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
+      "@container style((accent-color:#ff0) or (--bar:10px)){.foo{color:red}}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        initial-value: linear-gradient(yellow, blue);
-        inherits: false;
-        syntax: '<image>';
+      @container style(not ((width: calc(10px + 20px)) and ((--bar: url(x))))) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<image>\";inherits:false;initial-value:linear-gradient(#ff0,#00f)}",
+      "@container style(not ((width:30px) and (--bar:url(x)))){.foo{color:red}}",
     );
-
-    test(
+    minify_test(
       r#"
-      @property --property-name {
-        syntax: '<length>|none';
-        inherits: false;
-        initial-value: none;
+      @container style(color: yellow !important) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      indoc! {r#"
-      @property --property-name {
-        syntax: "<length> | none";
-        inherits: false;
-        initial-value: none;
-      }
-    "#},
+      "@container style(color:yellow){.foo{color:red}}",
     );
-
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>#';
-        inherits: false;
-        initial-value: yellow, blue;
+      @container style(--foo:) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<color>#\";inherits:false;initial-value:#ff0,#00f}",
+      "@container style(--foo:){.foo{color:red}}",
     );
     minify_test(
       r#"
-      @property --property-name {
-        syntax: '<color>+';
-        inherits: false;
-        initial-value: yellow blue;
+      @container style(--foo: ) {
+        .foo {
+          color: red;
+        }
       }
     "#,
-      "@property --property-name{syntax:\"<color>+\";inherits:false;initial-value:#ff0 #00f}",
-    );
-  }
-
-  #[test]
-  fn test_quoting_unquoting_urls() {
-    // Quotes remain double quotes when not minifying
-    test(
-      r#".foo {
-      background-image: url("0123abcd");
-    }"#,
-      r#".foo {
-  background-image: url("0123abcd");
-}
-"#,
-    );
-
-    // Quotes removed when minifying
-    minify_test(
-      r#".foo {
-      background-image: url("0123abcd");
-    }"#,
-      r#".foo{background-image:url(0123abcd)}"#,
-    );
-
-    // Doubles quotes added if not present when not minifying
-    test(
-      r#".foo {
-      background-image: url(0123abcd);
-    }"#,
-      r#".foo {
-  background-image: url("0123abcd");
-}
-"#,
+      "@container style(--foo:){.foo{color:red}}",
     );
-
-    // No quotes changed if not present when not minifying
     minify_test(
-      r#".foo {
-      background-image: url(0123abcd);
-    }"#,
-      r#".foo{background-image:url(0123abcd)}"#,
-    );
-  }
-
-  #[test]
-  fn test_zindex() {
-    minify_test(".foo { z-index: 2 }", ".foo{z-index:2}");
-    minify_test(".foo { z-index: -2 }", ".foo{z-index:-2}");
-    minify_test(".foo { z-index: 999999 }", ".foo{z-index:999999}");
-    minify_test(".foo { z-index: 9999999 }", ".foo{z-index:9999999}");
-    minify_test(".foo { z-index: -9999999 }", ".foo{z-index:-9999999}");
-  }
-
-  #[test]
-  #[cfg(feature = "sourcemap")]
-  fn test_input_source_map() {
-    let source = r#".imported {
-      content: "yay, file support!";
-    }
-
-    .selector {
-      margin: 1em;
-      background-color: #f60;
-    }
-
-    .selector .nested {
-      margin: 0.5em;
-    }
-
-    /*# sourceMappingURL=data:application/json;base64,ewoJInZlcnNpb24iOiAzLAoJInNvdXJjZVJvb3QiOiAicm9vdCIsCgkiZmlsZSI6ICJzdGRvdXQiLAoJInNvdXJjZXMiOiBbCgkJInN0ZGluIiwKCQkic2Fzcy9fdmFyaWFibGVzLnNjc3MiLAoJCSJzYXNzL19kZW1vLnNjc3MiCgldLAoJInNvdXJjZXNDb250ZW50IjogWwoJCSJAaW1wb3J0IFwiX3ZhcmlhYmxlc1wiO1xuQGltcG9ydCBcIl9kZW1vXCI7XG5cbi5zZWxlY3RvciB7XG4gIG1hcmdpbjogJHNpemU7XG4gIGJhY2tncm91bmQtY29sb3I6ICRicmFuZENvbG9yO1xuXG4gIC5uZXN0ZWQge1xuICAgIG1hcmdpbjogJHNpemUgLyAyO1xuICB9XG59IiwKCQkiJGJyYW5kQ29sb3I6ICNmNjA7XG4kc2l6ZTogMWVtOyIsCgkJIi5pbXBvcnRlZCB7XG4gIGNvbnRlbnQ6IFwieWF5LCBmaWxlIHN1cHBvcnQhXCI7XG59IgoJXSwKCSJtYXBwaW5ncyI6ICJBRUFBLFNBQVMsQ0FBQztFQUNSLE9BQU8sRUFBRSxvQkFBcUI7Q0FDL0I7O0FGQ0QsU0FBUyxDQUFDO0VBQ1IsTUFBTSxFQ0hELEdBQUc7RURJUixnQkFBZ0IsRUNMTCxJQUFJO0NEVWhCOztBQVBELFNBQVMsQ0FJUCxPQUFPLENBQUM7RUFDTixNQUFNLEVDUEgsS0FBRztDRFFQIiwKCSJuYW1lcyI6IFtdCn0= */"#;
-
-    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
-    stylesheet.minify(MinifyOptions::default()).unwrap();
-    let mut sm = parcel_sourcemap::SourceMap::new("/");
-    stylesheet
-      .to_css(PrinterOptions {
-        source_map: Some(&mut sm),
-        minify: true,
-        ..PrinterOptions::default()
-      })
-      .unwrap();
-    let map = sm.to_json(None).unwrap();
-    assert_eq!(
-      map,
-      r#"{"version":3,"sourceRoot":null,"mappings":"AAAA,uCCGA,2CAAA","sources":["sass/_demo.scss","stdin"],"sourcesContent":[".imported {\n  content: \"yay, file support!\";\n}","@import \"_variables\";\n@import \"_demo\";\n\n.selector {\n  margin: $size;\n  background-color: $brandColor;\n\n  .nested {\n    margin: $size / 2;\n  }\n}"],"names":[]}"#
-    );
-  }
-
-  #[test]
-  fn test_error_recovery() {
-    use std::sync::{Arc, RwLock};
-    let warnings = Some(Arc::new(RwLock::new(Vec::new())));
-    test_with_options(
       r#"
-      h1(>h1) {
-        color: red;
-      }
-
-      .foo {
-        color: red;
-      }
-
-      .clearfix {
-        *zoom: 1;
-        background: red;
-      }
-
-      @media (hover) {
-        h1(>h1) {
-          color: red;
-        }
-
-        .bar {
+      @container style(--my-prop: foo - bar ()) {
+        .foo {
           color: red;
         }
       }
     "#,
-      indoc! { r#"
-      .foo {
-        color: red;
-      }
+      "@container style(--my-prop:foo - bar ()){.foo{color:red}}",
+    );
 
-      .clearfix {
-        background: red;
-      }
+    // Disallow 'none', 'not', 'and', 'or' as a `<container-name>`
+    // https://github.com/w3c/csswg-drafts/issues/7203#issuecomment-1144257312
+    // https://chromium-review.googlesource.com/c/chromium/src/+/3698402
+    error_test(
+      "@container none (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("none".into())),
+    );
 
-      @media (hover) {
-        .bar {
-          color: red;
-        }
-      }
-    "#},
-      ParserOptions {
-        filename: "test.css".into(),
-        error_recovery: true,
-        warnings: warnings.clone(),
-        ..ParserOptions::default()
-      },
+    error_test(
+      "@container and (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("and".into())),
+    );
+
+    error_test(
+      "@container or (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("or".into())),
+    );
+
+    // Disallow CSS wide keywords as a `<container-name>`
+    error_test(
+      "@container revert-layer (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("revert-layer".into())),
     );
-    let w = warnings.unwrap();
-    let warnings = w.read().unwrap();
-    assert_eq!(
-      *warnings,
-      vec![
-        Error {
-          kind: ParserError::SelectorError(SelectorError::EmptySelector),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 1,
-            column: 7
-          })
-        },
-        Error {
-          kind: ParserError::UnexpectedToken(Token::Semicolon),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 10,
-            column: 17
-          })
-        },
-        Error {
-          kind: ParserError::SelectorError(SelectorError::EmptySelector),
-          loc: Some(ErrorLocation {
-            filename: "test.css".into(),
-            line: 15,
-            column: 9
-          })
-        },
-      ]
-    )
-  }
 
-  #[test]
-  fn test_invalid() {
     error_test(
-      ".a{color: hsla(120, 62.32%;}",
-      ParserError::UnexpectedToken(Token::CloseCurlyBracket),
+      "@container initial (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("initial".into())),
     );
+
+    // <ident> contains spaces
+    // https://github.com/web-platform-tests/wpt/blob/39f0da08fbbe33d0582a35749b6dbf8bd067a52d/css/css-contain/container-queries/at-container-parsing.html#L160-L178
     error_test(
-      ".a{--foo: url(foo\\) b\\)ar)}",
-      ParserError::UnexpectedToken(Token::BadUrl("foo\\) b\\)ar".into())),
+      "@container foo bar (width < 100vw) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("bar".into())),
+    );
+
+    error_test("@container (inline-size <= foo) {}", ParserError::InvalidMediaQuery);
+    error_test("@container (orientation <= 10px) {}", ParserError::InvalidMediaQuery);
+
+    error_test("@container style(width) {}", ParserError::EndOfInput);
+    error_test(
+      "@container style(style(--foo: bar)) {}",
+      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("style".into())),
     );
   }
 
   #[test]
-  fn test_container_queries() {
-    // with name
-    minify_test(
-      r#"
-      @container my-layout (inline-size > 45em) {
-        .foo {
-          color: red;
-        }
+  fn test_unknown_at_rules() {
+    minify_test("@foo;", "@foo;");
+    minify_test("@foo bar;", "@foo bar;");
+    minify_test("@foo (bar: baz);", "@foo (bar: baz);");
+    test(
+      r#"@foo test {
+      div {
+        color: red;
       }
-    "#,
-      "@container my-layout (inline-size>45em){.foo{color:red}}",
+    }"#,
+      indoc! {r#"
+      @foo test {
+        div { color: red; }
+      }
+      "#},
     );
-
     minify_test(
-      r#"
-      @container my-layout ( not (width > 500px) ) {
-        .foo {
-          color: red;
-        }
+      r#"@foo test {
+      div {
+        color: red;
       }
-    "#,
-      "@container my-layout not (width>500px){.foo{color:red}}",
+    }"#,
+      "@foo test{div { color: red; }}",
     );
-
     minify_test(
-      r#"
-      @container my-layout not (width > 500px) {
-        .foo {
-          color: red;
-        }
+      r#"@foo test {
+        foo: bar;
+      }"#,
+      "@foo test{foo: bar;}",
+    );
+    test(
+      r#"@foo {
+        foo: bar;
+      }"#,
+      indoc! {r#"
+      @foo {
+        foo: bar;
       }
-    "#,
-      "@container my-layout not (width>500px){.foo{color:red}}",
+      "#},
     );
-
     minify_test(
+      r#"@foo {
+        foo: bar;
+      }"#,
+      "@foo{foo: bar;}",
+    );
+  }
+
+  #[test]
+  fn test_resolution() {
+    prefix_test(
       r#"
-      @container not (width > 500px) {
-        .foo {
-          color: red;
+      @media (resolution: 1dppx) {
+        body {
+          background: red;
         }
       }
-    "#,
-      "@container not (width>500px){.foo{color:red}}",
+      "#,
+      indoc! { r#"
+      @media (resolution: 1dppx) {
+        body {
+          background: red;
+        }
+      }
+      "#},
+      Browsers {
+        chrome: Some(50 << 16),
+        ..Browsers::default()
+      },
     );
 
-    minify_test(
+    prefix_test(
       r#"
-      @container my-layout ((width: 100px) and (not (height: 100px))) {
-        .foo {
-          color: red;
+      @media (resolution: 1dppx) {
+        body {
+          background: red;
         }
       }
-    "#,
-      "@container my-layout (width:100px) and (not (height:100px)){.foo{color:red}}",
+      "#,
+      indoc! { r#"
+      @media (resolution: 1x) {
+        body {
+          background: red;
+        }
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      },
     );
+  }
 
+  #[test]
+  fn test_environment() {
     minify_test(
       r#"
-      @container my-layout (width = max(10px, 10em)) {
-        .foo {
-          color: red;
+      @media (max-width: env(--branding-small)) {
+        body {
+          padding: env(--branding-padding);
         }
       }
     "#,
-      "@container my-layout (width=max(10px,10em)){.foo{color:red}}",
+      "@media (width<=env(--branding-small)){body{padding:env(--branding-padding)}}",
     );
 
-    // without name
     minify_test(
       r#"
-      @container (inline-size > 45em) {
-        .foo {
-          color: red;
+      @media (max-width: env(--branding-small 1)) {
+        body {
+          padding: env(--branding-padding 2);
         }
       }
     "#,
-      "@container (inline-size>45em){.foo{color:red}}",
+      "@media (width<=env(--branding-small 1)){body{padding:env(--branding-padding 2)}}",
     );
 
     minify_test(
       r#"
-      @container (inline-size > 45em) and (inline-size < 100em) {
-        .foo {
-          color: red;
+      @media (max-width: env(--branding-small 1, 20px)) {
+        body {
+          padding: env(--branding-padding 2, 20px);
         }
       }
     "#,
-      "@container (inline-size>45em) and (inline-size<100em){.foo{color:red}}",
+      "@media (width<=env(--branding-small 1,20px)){body{padding:env(--branding-padding 2,20px)}}",
     );
 
-    // calc()
     minify_test(
       r#"
-      @container (width > calc(100vw - 50px)) {
-        .foo {
-          color: red;
+      @media (max-width: env(safe-area-inset-top)) {
+        body {
+          padding: env(safe-area-inset-top);
         }
       }
     "#,
-      "@container (width>calc(100vw - 50px)){.foo{color:red}}",
+      "@media (width<=env(safe-area-inset-top)){body{padding:env(safe-area-inset-top)}}",
     );
 
     minify_test(
       r#"
-      @container (calc(100vh - 50px) <= height ) {
-        .foo {
-          color: red;
+      @media (max-width: env(unknown)) {
+        body {
+          padding: env(unknown);
         }
       }
     "#,
-      "@container (height>=calc(100vh - 50px)){.foo{color:red}}",
+      "@media (width<=env(unknown)){body{padding:env(unknown)}}",
     );
 
-    // merge adjacent
-    minify_test(
+    prefix_test(
       r#"
-      @container my-layout (inline-size > 45em) {
-        .foo {
-          color: red;
-        }
+      .foo {
+        color: env(--brand-color, color(display-p3 0 1 0));
+      }
+    "#,
+      indoc! {r#"
+      .foo {
+        color: env(--brand-color, #00f942);
       }
 
-      @container my-layout (inline-size > 45em) {
+      @supports (color: color(display-p3 0 0 0)) {
         .foo {
-          background: yellow;
-        }
-
-        .bar {
-          color: white;
+          color: env(--brand-color, color(display-p3 0 1 0));
         }
       }
-    "#,
-      "@container my-layout (inline-size>45em){.foo{color:red;background:#ff0}.bar{color:#fff}}",
+    "#},
+      Browsers {
+        safari: Some(15 << 16),
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      },
     );
 
-    minify_test(
-      r#"
-    .foo {
-      container-name: foo bar;
-      container-type: size;
-    }
-    "#,
-      ".foo{container:foo bar/size}",
-    );
-    minify_test(
+    let ios11 = Browsers {
+      ios_saf: Some(11 << 16),
+      ..Browsers::default()
+    };
+    prefix_test(
       r#"
-    .foo {
-      container-name: foo bar;
-      container-type: normal;
-    }
+      .foo {
+        padding-top: env(safe-area-inset-top);
+        padding-bottom: calc(10px + env(safe-area-inset-bottom, 20px));
+        margin: var(--margin, env(safe-area-inset-left));
+      }
     "#,
-      ".foo{container:foo bar}",
-    );
-    minify_test(
-      ".foo{ container-type: inline-size }",
-      ".foo{container-type:inline-size}",
-    );
-    minify_test(".foo{ container-name: none; }", ".foo{container-name:none}");
-    minify_test(".foo{ container-name: foo; }", ".foo{container-name:foo}");
-    minify_test(".foo{ container: foo / normal; }", ".foo{container:foo}");
-    minify_test(
-      ".foo{ container: foo / inline-size; }",
-      ".foo{container:foo/inline-size}",
+      indoc! {r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top);
+        padding-top: env(safe-area-inset-top);
+        padding-bottom: calc(10px + constant(safe-area-inset-bottom, 20px));
+        padding-bottom: calc(10px + env(safe-area-inset-bottom, 20px));
+        margin: var(--margin, constant(safe-area-inset-left));
+        margin: var(--margin, env(safe-area-inset-left));
+      }
+    "#},
+      ios11,
     );
-    minify_test(".foo { width: calc(1cqw + 2cqw) }", ".foo{width:3cqw}");
-    minify_test(".foo { width: calc(1cqh + 2cqh) }", ".foo{width:3cqh}");
-    minify_test(".foo { width: calc(1cqi + 2cqi) }", ".foo{width:3cqi}");
-    minify_test(".foo { width: calc(1cqb + 2cqb) }", ".foo{width:3cqb}");
-    minify_test(".foo { width: calc(1cqmin + 2cqmin) }", ".foo{width:3cqmin}");
-    minify_test(".foo { width: calc(1cqmax + 2cqmax) }", ".foo{width:3cqmax}");
-
-    // Unlike in @media, there is no need to convert the range syntax in @container,
-    // because browsers all support this syntax.
     prefix_test(
       r#"
-      @container (width > 100px) {
-        .foo { padding: 5px; }
+      .foo {
+        padding-top: constant(safe-area-inset-top);
+        padding-top: env(safe-area-inset-top);
+        width: env(--custom);
+        --inset: env(safe-area-inset-top);
       }
-      "#,
-      indoc! { r#"
-        @container (width > 100px) {
-          .foo {
-            padding: 5px;
-          }
-        }
-      "#},
-      Browsers {
-        chrome: Some(105 << 16),
-        ..Browsers::default()
-      },
+    "#,
+      indoc! {r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top);
+        padding-top: env(safe-area-inset-top);
+        width: env(--custom);
+        --inset: env(safe-area-inset-top);
+      }
+    "#},
+      ios11,
     );
     prefix_test(
       r#"
-      @container (min-width: 100px) {
-        .foo { padding: 5px; }
+      .foo {
+        padding-top: env(safe-area-inset-top);
       }
-      "#,
-      indoc! { r#"
-        @container (width >= 100px) {
-          .foo {
-            padding: 5px;
-          }
-        }
-      "#},
+    "#,
+      indoc! {r#"
+      .foo {
+        padding-top: env(safe-area-inset-top);
+      }
+    "#},
       Browsers {
-        chrome: Some(105 << 16),
+        ios_saf: Some(12 << 16),
+        chrome: Some(60 << 16),
         ..Browsers::default()
       },
     );
-
     minify_test(
+      ".foo { padding-top: env(safe-area-inset-top) }",
+      ".foo{padding-top:env(safe-area-inset-top)}",
+    );
+
+    css_modules_test(
       r#"
-      @container style(--responsive: true) {
+      @media (max-width: env(--branding-small)) {
         .foo {
-          color: red;
+          color: env(--brand-color);
         }
       }
     "#,
-      "@container style(--responsive:true){.foo{color:red}}",
+      indoc! {r#"
+      @media (width <= env(--EgL3uq_branding-small)) {
+        .EgL3uq_foo {
+          color: env(--EgL3uq_brand-color);
+        }
+      }
+    "#},
+      map! {
+        "foo" => "EgL3uq_foo",
+        "--brand-color" => "--EgL3uq_brand-color" referenced: true,
+        "--branding-small" => "--EgL3uq_branding-small" referenced: true
+      },
+      HashMap::new(),
+      crate::css_modules::Config {
+        dashed_idents: true,
+        ..Default::default()
+      },
     );
+  }
+
+  #[test]
+  fn test_license_comments() {
     minify_test(
       r#"
-      @container style(--responsive: true) and style(color: yellow) {
-        .foo {
-          color: red;
-        }
+      /*! Copyright 2023 Someone awesome */
+      /* Some other comment */
+      .foo {
+        color: red;
       }
     "#,
-      "@container style(--responsive:true) and style(color:#ff0){.foo{color:red}}",
+      indoc! {r#"
+      /*! Copyright 2023 Someone awesome */
+      .foo{color:red}"#},
     );
+
     minify_test(
       r#"
-      @container not style(--responsive: true) {
-        .foo {
-          color: red;
-        }
+      /*! Copyright 2023 Someone awesome */
+      /*! Copyright 2023 Someone else */
+      .foo {
+        color: red;
       }
     "#,
-      "@container not style(--responsive:true){.foo{color:red}}",
+      indoc! {r#"
+      /*! Copyright 2023 Someone awesome */
+      /*! Copyright 2023 Someone else */
+      .foo{color:red}"#},
     );
+  }
+
+  #[test]
+  fn test_starting_style() {
     minify_test(
       r#"
-      @container (inline-size > 45em) and style(--responsive: true) {
-        .foo {
-          color: red;
+      @starting-style {
+        h1 {
+          background: yellow;
         }
       }
-    "#,
-      "@container (inline-size>45em) and style(--responsive:true){.foo{color:red}}",
+      "#,
+      "@starting-style{h1{background:#ff0}}",
+    );
+    minify_test("@starting-style {}", "");
+
+    nesting_test(
+      r#"
+      h1 {
+        background: red;
+        @starting-style {
+          background: yellow;
+        }
+      }
+      "#,
+      indoc! {r#"
+      h1 {
+        background: red;
+      }
+
+      @starting-style {
+        h1 {
+          background: #ff0;
+        }
+      }
+      "#},
+    );
+  }
+
+  #[test]
+  fn test_builder() {
+    use crate::builder::*;
+    use crate::parser::DefaultAtRule;
+    use crate::properties::display::{Display, DisplayKeyword};
+    use crate::rules::style::StyleRule;
+
+    let stylesheet = StyleSheetBuilder::new()
+      .options(ParserOptions {
+        filename: "built.css".into(),
+        ..ParserOptions::default()
+      })
+      .rule(
+        StyleRuleBuilder::parse(".foo, .bar > a")
+          .unwrap()
+          .declaration(Property::Color(CssColor::parse_string("red").unwrap()))
+          .important(Property::Display(Display::Keyword(DisplayKeyword::None)))
+          .rule(
+            StyleRuleBuilder::parse("&:hover")
+              .unwrap()
+              .declarations(DeclarationBlockBuilder::new().parse_declaration("opacity", "0.5").unwrap()),
+          ),
+      )
+      .rule(
+        LayerBlockRuleBuilder::parse("base").unwrap().rule(
+          MediaRuleBuilder::parse("print and (min-width: 100px)")
+            .unwrap()
+            .rule(StyleRuleBuilder::parse("h1").unwrap().build()),
+        ),
+      )
+      .rule(LayerBlockRuleBuilder::new(None))
+      .rule(
+        SupportsRuleBuilder::parse("(display: grid)").unwrap().rule(
+          StyleRuleBuilder::parse(".grid").unwrap().declarations(
+            DeclarationBlockBuilder::new()
+              .parse_declaration("display", "grid")
+              .unwrap()
+              .important(Property::Display(Display::Keyword(DisplayKeyword::Contents))),
+          ),
+        ),
+      )
+      .rule(
+        ContainerRuleBuilder::parse("sidebar (width > 400px)")
+          .unwrap()
+          .rule(StyleRuleBuilder::parse(".card").unwrap()),
+      )
+      .build();
+
+    assert_eq!(stylesheet.sources, vec!["built.css".to_string()]);
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".foo,.bar>a{color:red;display:none!important;&:hover{opacity:.5}}@layer base{@media print and (width>=100px){h1{}}}@layer{}@supports (display:grid){.grid{display:grid;display:contents!important}}@container sidebar (width>400px){.card{}}"
+    );
+
+    // The result can be minified like a parsed style sheet.
+    let mut stylesheet = StyleSheetBuilder::new()
+      .rule(
+        StyleRuleBuilder::parse(".a").unwrap().declarations(
+          DeclarationBlockBuilder::new()
+            .parse_declaration("margin-top", "10px")
+            .unwrap()
+            .parse_declaration("margin-right", "10px")
+            .unwrap()
+            .parse_declaration("margin-bottom", "10px")
+            .unwrap()
+            .parse_declaration("margin-left", "10px")
+            .unwrap(),
+        ),
+      )
+      .build();
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".a{margin:10px}");
+
+    let rule: StyleRule = StyleRuleBuilder::parse(".a").unwrap().build();
+    assert_eq!(rule.selectors.0.len(), 1);
+
+    assert!(StyleRuleBuilder::<DefaultAtRule>::parse(".a {").is_err());
+    assert!(SupportsRuleBuilder::<DefaultAtRule>::parse("display: grid").is_err());
+    assert!(DeclarationBlockBuilder::new().parse_declaration("width", "10px }").is_err());
+  }
+
+  #[cfg(feature = "into_owned")]
+  #[test]
+  fn test_into_owned() {
+    use crate::traits::IntoOwned;
+
+    fn parse(source: String) -> StyleSheet<'static, 'static> {
+      let stylesheet = StyleSheet::parse(
+        &source,
+        ParserOptions {
+          filename: "test.css".into(),
+          ..ParserOptions::default()
+        },
+      )
+      .unwrap();
+      stylesheet.into_owned()
+    }
+
+    let mut stylesheet = parse(
+      r#"
+      /*! license */
+      @import "foo.css" layer(base);
+      .foo::before { content: "hi"; font-family: "Helvetica Neue"; --custom: foo(bar) }
+      @media (min-width: 100px) { .bar { color: red } }
+      @font-face { font-family: Foo; src: url(foo.woff2) }
+      "#
+      .into(),
+    );
+    stylesheet.minify(MinifyOptions::default()).unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "/*! license */\n@import \"foo.css\" layer(base);.foo:before{content:\"hi\";--custom:foo(bar);font-family:Helvetica Neue}@media (width>=100px){.bar{color:red}}@font-face{font-family:Foo;src:url(foo.woff2)}"
+    );
+    assert_eq!(stylesheet.sources, vec!["test.css".to_string()]);
+  }
+
+  #[cfg(feature = "into_owned")]
+  #[test]
+  fn test_threads() {
+    use crate::traits::IntoOwned;
+
+    let source = String::from(".foo { color: red } .bar { color: red }");
+    let stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap().into_owned();
+    drop(source);
+
+    let stylesheet = std::thread::spawn(move || {
+      let mut stylesheet = stylesheet;
+      stylesheet.minify(MinifyOptions::default()).unwrap();
+      stylesheet
+    })
+    .join()
+    .unwrap();
+
+    let stylesheet = std::sync::Arc::new(stylesheet);
+    let handles: Vec<_> = (0..2)
+      .map(|_| {
+        let stylesheet = stylesheet.clone();
+        std::thread::spawn(move || {
+          stylesheet
+            .to_css(PrinterOptions {
+              minify: true,
+              ..PrinterOptions::default()
+            })
+            .unwrap()
+            .code
+        })
+      })
+      .collect();
+    for handle in handles {
+      assert_eq!(handle.join().unwrap(), ".foo,.bar{color:red}");
+    }
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_postcss() {
+    use crate::postcss::{Node, Root};
+
+    let source = indoc! {r#"
+      /*! license */
+      @import "foo.css" print;
+      .foo { color: red; width: 10px !important }
+      @media print {
+        .bar { color: blue }
+        .baz { color: green }
+      }
+      .qux {
+        color: green;
+        &:hover { color: red }
+      }
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let expected = stylesheet.to_css(PrinterOptions::default()).unwrap().code;
+
+    let root = stylesheet.to_postcss().unwrap();
+    assert_eq!(root.nodes.len(), 5);
+    assert!(matches!(&root.nodes[0], Node::Comment(comment) if comment.text == "! license "));
+    assert!(
+      matches!(&root.nodes[1], Node::AtRule(rule) if rule.name == "import" && rule.params == "\"foo.css\" print" && rule.nodes.is_none())
+    );
+    match &root.nodes[2] {
+      Node::Rule(rule) => {
+        assert_eq!(rule.selector, ".foo");
+        assert!(
+          matches!(&rule.nodes[0], Node::Declaration(decl) if decl.prop == "color" && decl.value == "red" && !decl.important)
+        );
+        assert!(
+          matches!(&rule.nodes[1], Node::Declaration(decl) if decl.prop == "width" && decl.value == "10px" && decl.important)
+        );
+      }
+      _ => unreachable!(),
+    }
+    match &root.nodes[3] {
+      Node::AtRule(rule) => {
+        assert_eq!(rule.name, "media");
+        assert_eq!(rule.params, "print");
+        assert_eq!(rule.nodes.as_ref().unwrap().len(), 2);
+      }
+      _ => unreachable!(),
+    }
+    match &root.nodes[4] {
+      Node::Rule(rule) => {
+        assert!(matches!(&rule.nodes[0], Node::Declaration(..)));
+        assert!(matches!(&rule.nodes[1], Node::Rule(rule) if rule.selector == "&:hover"));
+      }
+      _ => unreachable!(),
+    }
+
+    let json = serde_json::to_value(&root).unwrap();
+    assert_eq!(json["type"], "root");
+    assert_eq!(json["nodes"][2]["type"], "rule");
+    assert_eq!(json["nodes"][2]["nodes"][1]["type"], "decl");
+    assert_eq!(json["nodes"][2]["nodes"][1]["important"], true);
+    assert_eq!(json["nodes"][2]["nodes"][1]["raws"]["before"], "\n  ");
+
+    let root: Root = serde_json::from_value(json).unwrap();
+    let stylesheet = StyleSheet::from_postcss(&root, ParserOptions::default()).unwrap();
+    assert_eq!(stylesheet.to_css(PrinterOptions::default()).unwrap().code, expected);
+
+    let root: Root = serde_json::from_str(
+      r##"{"type":"root","nodes":[{"type":"rule","selector":".a","nodes":[{"type":"decl","prop":"color","value":"#ff0000","raws":{"before":"\n  ","between":": "}}],"raws":{}}],"raws":{}}"##,
+    )
+    .unwrap();
+    let stylesheet = StyleSheet::from_postcss(&root, ParserOptions::default()).unwrap();
+    assert_eq!(
+      stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap()
+        .code,
+      ".a{color:red}"
+    );
+  }
+
+  #[cfg(feature = "substitute_variables")]
+  #[test]
+  fn test_variable_substitution() {
+    use crate::substitution::VariableSubstitution;
+    use crate::visitor::Visit;
+
+    fn substitute(source: &str, substitution: &mut VariableSubstitution) -> String {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      stylesheet.visit(substitution).unwrap();
+      let res = stylesheet
+        .to_css(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .unwrap();
+      res.code
+    }
+
+    let mut substitution = VariableSubstitution::new();
+    substitution.set_var("--a", "var(--b) 2px").unwrap();
+    substitution.set_var("--b", "1px").unwrap();
+    substitution.set_var("--cycle", "var(--cycle2)").unwrap();
+    substitution.set_var("--cycle2", "var(--cycle)").unwrap();
+    substitution.set_var("--color", "rgb(0 0 255 / var(--alpha))").unwrap();
+    substitution.set_var("--alpha", "50%").unwrap();
+    substitution.set_env("safe-area-inset-left", "10px").unwrap();
+
+    assert_eq!(
+      substitute(".foo { margin: var(--a) }", &mut substitution),
+      ".foo{margin:1px 2px}"
+    );
+    assert_eq!(
+      substitute(".foo { width: var(--cycle, 5px) }", &mut substitution),
+      ".foo{width:5px}"
+    );
+    assert_eq!(
+      substitute(".foo { width: var(--missing, var(--b)) }", &mut substitution),
+      ".foo{width:1px}"
+    );
+    assert_eq!(
+      substitute(
+        ".foo { width: calc(var(--b) + env(safe-area-inset-left)) }",
+        &mut substitution
+      ),
+      ".foo{width:11px}"
+    );
+    assert_eq!(
+      substitute(".foo { color: var(--color) }", &mut substitution),
+      ".foo{color:#0000ff80}"
+    );
+    assert_eq!(
+      substitute(".foo { --x: var(--b) var(--missing) }", &mut substitution),
+      ".foo{--x:1px var(--missing)}"
     );
-    minify_test(
-      r#"
-      @container style((accent-color: yellow) or (--bar: 10px)) {
-        .foo {
-          color: red;
-        }
-      }
-    "#,
-      "@container style((accent-color:#ff0) or (--bar:10px)){.foo{color:red}}",
+    assert_eq!(substitution.unresolved(), &["--missing".to_string()]);
+
+    let mut substitution = VariableSubstitution::new();
+    substitution.set_var("--cycle", "var(--cycle)").unwrap();
+    assert_eq!(
+      substitute(".foo { width: var(--cycle) }", &mut substitution),
+      ".foo{width:var(--cycle)}"
     );
-    minify_test(
-      r#"
-      @container style(not ((width: calc(10px + 20px)) and ((--bar: url(x))))) {
-        .foo {
-          color: red;
-        }
+    assert_eq!(substitution.unresolved(), &["--cycle".to_string()]);
+  }
+
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visit_context() {
+    use crate::properties::Property;
+    use crate::visitor::{RuleContext, Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct ContextVisitor<'i> {
+      stack: Vec<RuleContext<'i>>,
+      colors: Vec<Vec<String>>,
+    }
+
+    impl<'i> Visitor<'i> for ContextVisitor<'i> {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::PROPERTIES
       }
-    "#,
-      "@container style(not ((width:30px) and (--bar:url(x)))){.foo{color:red}}",
-    );
-    minify_test(
-      r#"
-      @container style(color: yellow !important) {
-        .foo {
-          color: red;
-        }
+
+      fn context(&mut self) -> Option<&mut Vec<RuleContext<'i>>> {
+        Some(&mut self.stack)
       }
-    "#,
-      "@container style(color:yellow){.foo{color:red}}",
-    );
-    minify_test(
-      r#"
-      @container style(--foo:) {
-        .foo {
-          color: red;
+
+      fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+        if let Property::Color(_) = property {
+          let opts = PrinterOptions::default;
+          let context = self
+            .stack
+            .iter()
+            .map(|context| match context {
+              RuleContext::Style(selectors) => selectors.to_css_string(opts()).unwrap(),
+              RuleContext::Media(media) => format!("@media {}", media.to_css_string(opts()).unwrap()),
+              RuleContext::Supports(condition) => {
+                format!("@supports {}", condition.to_css_string(opts()).unwrap())
+              }
+              RuleContext::Layer(Some(name)) => format!("@layer {}", name.to_css_string(opts()).unwrap()),
+              _ => "other".into(),
+            })
+            .collect();
+          self.colors.push(context);
         }
+        Ok(())
       }
-    "#,
-      "@container style(--foo:){.foo{color:red}}",
-    );
-    minify_test(
+    }
+
+    let mut stylesheet = StyleSheet::parse(
       r#"
-      @container style(--foo: ) {
-        .foo {
-          color: red;
+      .a { color: red }
+      @media print {
+        @supports (display: grid) {
+          .b { color: red }
         }
       }
-    "#,
-      "@container style(--foo:){.foo{color:red}}",
-    );
-    minify_test(
-      r#"
-      @container style(--my-prop: foo - bar ()) {
-        .foo {
-          color: red;
-        }
+      @layer foo {
+        .d { color: red }
       }
+      .e { color: red }
     "#,
-      "@container style(--my-prop:foo - bar ()){.foo{color:red}}",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut visitor = ContextVisitor {
+      stack: Vec::new(),
+      colors: Vec::new(),
+    };
+    stylesheet.visit(&mut visitor).unwrap();
+    assert!(visitor.stack.is_empty());
+    assert_eq!(
+      visitor.colors,
+      vec![
+        vec![".a"],
+        vec!["@media print", "@supports (display: grid)", ".b"],
+        vec!["@layer foo", ".d"],
+        vec![".e"],
+      ]
     );
+  }
 
-    // Disallow 'none', 'not', 'and', 'or' as a `<container-name>`
-    // https://github.com/w3c/csswg-drafts/issues/7203#issuecomment-1144257312
-    // https://chromium-review.googlesource.com/c/chromium/src/+/3698402
-    error_test(
-      "@container none (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("none".into())),
-    );
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visit_replace() {
+    use crate::properties::{Property, PropertyId};
+    use crate::rules::CssRule;
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct ReplaceVisitor;
+
+    impl<'i> Visitor<'i> for ReplaceVisitor {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::RULES | VisitTypes::PROPERTIES
+      }
+
+      fn replace_rule(&mut self, rule: &mut CssRule<'i>) -> Result<Option<Vec<CssRule<'i>>>, Self::Error> {
+        if let CssRule::Style(style) = rule {
+          let selector = style.selectors.to_css_string(PrinterOptions::default()).unwrap();
+          match selector.as_str() {
+            ".remove" => return Ok(Some(vec![])),
+            ".split" => {
+              let mut print =
+                CssRule::parse_string("@media print { .split {} }", ParserOptions::default()).unwrap();
+              if let CssRule::Media(media) = &mut print {
+                media.rules.0[0] = rule.clone();
+              }
+              return Ok(Some(vec![rule.clone(), print]));
+            }
+            _ => {}
+          }
+        }
 
-    error_test(
-      "@container and (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("and".into())),
-    );
+        self.visit_rule(rule)?;
+        Ok(None)
+      }
 
-    error_test(
-      "@container or (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("or".into())),
-    );
+      fn replace_property(
+        &mut self,
+        property: &mut Property<'i>,
+      ) -> Result<Option<Vec<Property<'i>>>, Self::Error> {
+        match property.property_id() {
+          PropertyId::Color => Ok(Some(vec![])),
+          PropertyId::Margin => Ok(Some(vec![
+            Property::parse_string(PropertyId::MarginBlock, "10px", ParserOptions::default()).unwrap(),
+            Property::parse_string(PropertyId::MarginInline, "auto", ParserOptions::default()).unwrap(),
+          ])),
+          _ => Ok(None),
+        }
+      }
+    }
 
-    // Disallow CSS wide keywords as a `<container-name>`
-    error_test(
-      "@container revert-layer (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("revert-layer".into())),
-    );
+    let mut stylesheet = StyleSheet::parse(
+      ".remove { width: 1px } .split { width: 20px } .foo { color: red !important; margin: 1px auto; width: 3px }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet.visit(&mut ReplaceVisitor).unwrap();
 
-    error_test(
-      "@container initial (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("initial".into())),
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".split{width:20px}@media print{.split{width:20px}}.foo{margin-block:10px;margin-inline:auto;width:3px}"
     );
+  }
 
-    // <ident> contains spaces
-    // https://github.com/web-platform-tests/wpt/blob/39f0da08fbbe33d0582a35749b6dbf8bd067a52d/css/css-contain/container-queries/at-container-parsing.html#L160-L178
-    error_test(
-      "@container foo bar (width < 100vw) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Ident("bar".into())),
-    );
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visit_unknown_at_rules() {
+    use crate::properties::custom::TokenOrValue;
+    use crate::rules::unknown::UnknownAtRule;
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
 
-    error_test("@container (inline-size <= foo) {}", ParserError::InvalidMediaQuery);
-    error_test("@container (orientation <= 10px) {}", ParserError::InvalidMediaQuery);
+    struct HideVisitor;
 
-    error_test("@container style(width) {}", ParserError::EndOfInput);
-    error_test(
-      "@container style(style(--foo: bar)) {}",
-      ParserError::UnexpectedToken(crate::properties::custom::Token::Function("style".into())),
-    );
-  }
+    impl<'i> Visitor<'i> for HideVisitor {
+      type Error = Infallible;
 
-  #[test]
-  fn test_unknown_at_rules() {
-    minify_test("@foo;", "@foo;");
-    minify_test("@foo bar;", "@foo bar;");
-    minify_test("@foo (bar: baz);", "@foo (bar: baz);");
-    test(
-      r#"@foo test {
-      div {
-        color: red;
-      }
-    }"#,
-      indoc! {r#"
-      @foo test {
-        div { color: red; }
-      }
-      "#},
-    );
-    minify_test(
-      r#"@foo test {
-      div {
-        color: red;
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::RULES
       }
-    }"#,
-      "@foo test{div { color: red; }}",
-    );
-    minify_test(
-      r#"@foo test {
-        foo: bar;
-      }"#,
-      "@foo test{foo: bar;}",
-    );
-    test(
-      r#"@foo {
-        foo: bar;
-      }"#,
-      indoc! {r#"
-      @foo {
-        foo: bar;
+
+      fn replace_unknown_at_rule(
+        &mut self,
+        rule: &mut UnknownAtRule<'i>,
+      ) -> Result<Option<Vec<CssRule<'i>>>, Self::Error> {
+        if rule.name != "hide" {
+          return Ok(None);
+        }
+
+        match rule.prelude.0.first() {
+          Some(TokenOrValue::Token(Token::Ident(name))) if name.as_ref() == "ads" => {
+            Ok(Some(vec![CssRule::parse_string(
+              "@media print { .ads { display: none } }",
+              ParserOptions::default(),
+            )
+            .unwrap()]))
+          }
+          _ => Ok(Some(vec![])),
+        }
       }
-      "#},
-    );
-    minify_test(
-      r#"@foo {
-        foo: bar;
-      }"#,
-      "@foo{foo: bar;}",
+    }
+
+    let mut stylesheet = StyleSheet::parse(
+      "@hide ads; @media screen { @hide nav; .a { color: red } } @other foo;",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet.visit(&mut HideVisitor).unwrap();
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "@media print{.ads{display:none}}@media screen{.a{color:red}}@other foo;"
     );
   }
 
+  #[cfg(feature = "visitor")]
   #[test]
-  fn test_resolution() {
-    prefix_test(
+  fn test_url_rewriter() {
+    use crate::values::url::UrlRewriter;
+    use crate::visitor::Visit;
+
+    let mut stylesheet = StyleSheet::parse(
       r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
-        }
-      }
+      @import "theme.css";
+      @font-face { font-family: Foo; src: url(foo.woff2) format("woff2") }
+      .foo { background: image-set(url(a.png) 1x, url(b.png) 2x); cursor: url(c.svg), pointer; --icon: url(d.svg) }
+      .bar { background: url("e f.png") }
       "#,
-      indoc! { r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
-        }
-      }
-      "#},
-      Browsers {
-        chrome: Some(50 << 16),
-        ..Browsers::default()
-      },
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet
+      .visit(&mut UrlRewriter::new(|url| format!("/assets/{}", url)))
+      .unwrap();
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      concat!(
+        "@import \"/assets/theme.css\";",
+        "@font-face{font-family:Foo;src:url(/assets/foo.woff2)format(\"woff2\")}",
+        ".foo{background:image-set(\"/assets/a.png\" 1x,\"/assets/b.png\" 2x);",
+        "cursor:url(/assets/c.svg),pointer;--icon:url(/assets/d.svg)}",
+        ".bar{background:url(\"/assets/e f.png\")}"
+      )
     );
+  }
 
-    prefix_test(
-      r#"
-      @media (resolution: 1dppx) {
-        body {
-          background: red;
-        }
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visit_selector_components() {
+    use crate::rules::CssRule;
+    use crate::selector::{Component, PseudoClass};
+    use crate::values::ident::Ident;
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct PrefixVisitor;
+
+    impl<'i> Visitor<'i> for PrefixVisitor {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::SELECTOR_COMPONENTS
       }
-      "#,
-      indoc! { r#"
-      @media (resolution: 1x) {
-        body {
-          background: red;
+
+      fn visit_selector_component(&mut self, component: &mut Component<'i>) -> Result<(), Self::Error> {
+        match component {
+          Component::Class(name) => *name = Ident(format!("app-{}", name.0).into()),
+          Component::ID(name) => *component = Component::Class(Ident(format!("id-{}", name.0).into())),
+          Component::NonTSPseudoClass(PseudoClass::Hover) => {
+            *component = Component::Class(Ident("is-hover".into()))
+          }
+          _ => {}
         }
+        component.visit_children(self)
       }
-      "#},
-      Browsers {
-        chrome: Some(95 << 16),
-        ..Browsers::default()
-      },
+    }
+
+    let mut stylesheet = StyleSheet::parse(
+      ".foo:hover > .bar, :is(.baz, #qux) span { color: red }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet.visit(&mut PrefixVisitor).unwrap();
+
+    if let CssRule::Style(style) = &stylesheet.rules.0[0] {
+      assert_eq!(style.selectors.0[0].specificity(), 3 << 10);
+      assert_eq!(style.selectors.0[1].specificity(), 1 << 10 | 1);
+    } else {
+      unreachable!()
+    }
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      ".app-foo.is-hover>.app-bar,:is(.app-baz,.id-qux) span{color:red}"
     );
   }
 
+  #[cfg(feature = "visitor")]
   #[test]
-  fn test_environment() {
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small)) {
-        body {
-          padding: env(--branding-padding);
-        }
+  fn test_visit_raw_tokens() {
+    use crate::properties::custom::Token;
+    use crate::visitor::{TokenSpan, Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct IdentVisitor {
+      idents: Vec<(String, TokenSpan)>,
+    }
+
+    impl<'i> Visitor<'i> for IdentVisitor {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::RAW_TOKENS
       }
-    "#,
-      "@media (width<=env(--branding-small)){body{padding:env(--branding-padding)}}",
-    );
 
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small 1)) {
-        body {
-          padding: env(--branding-padding 2);
+      fn visit_raw_token(&mut self, token: &mut Token<'i>, span: TokenSpan) -> Result<(), Self::Error> {
+        if let Token::Ident(ident) = token {
+          self.idents.push((ident.to_string(), span));
+          if ident.as_ref() == "a" {
+            *ident = "x".into();
+          }
         }
+        Ok(())
       }
-    "#,
-      "@media (width<=env(--branding-small 1)){body{padding:env(--branding-padding 2)}}",
-    );
+    }
 
-    minify_test(
-      r#"
-      @media (max-width: env(--branding-small 1, 20px)) {
-        body {
-          padding: env(--branding-padding 2, 20px);
-        }
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { --foo: a b fn(a); color: var(--c, a) }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut visitor = IdentVisitor { idents: Vec::new() };
+    stylesheet.visit(&mut visitor).unwrap();
+
+    let idents: Vec<_> = visitor
+      .idents
+      .iter()
+      .map(|(ident, span)| (ident.as_str(), span.index))
+      .collect();
+    assert_eq!(idents, vec![("a", 0), ("b", 2), ("a", 0), ("a", 0)]);
+    assert_eq!(
+      visitor.idents[0].1,
+      TokenSpan {
+        index: 0,
+        start: 0,
+        end: 1
       }
-    "#,
-      "@media (width<=env(--branding-small 1,20px)){body{padding:env(--branding-padding 2,20px)}}",
     );
+    assert_eq!(visitor.idents[1].1.start, 2);
 
-    minify_test(
-      r#"
-      @media (max-width: env(safe-area-inset-top)) {
-        body {
-          padding: env(safe-area-inset-top);
-        }
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{--foo:x b fn(x);color:var(--c,x)}");
+  }
+  #[cfg(feature = "visitor")]
+  #[test]
+  fn test_visit_registered_custom_properties() {
+    use crate::properties::custom::{Token, TokenOrValue};
+    use crate::visitor::{Visit, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    struct IdentVisitor;
+
+    impl<'i> Visitor<'i> for IdentVisitor {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::TOKENS
       }
-    "#,
-      "@media (width<=env(safe-area-inset-top)){body{padding:env(safe-area-inset-top)}}",
-    );
 
-    minify_test(
-      r#"
-      @media (max-width: env(unknown)) {
-        body {
-          padding: env(unknown);
+      fn visit_token(&mut self, token: &mut TokenOrValue<'i>) -> Result<(), Self::Error> {
+        if let TokenOrValue::Token(Token::Ident(ident)) = token {
+          if ident.as_ref() == "foo" {
+            *ident = "bar".into();
+          }
         }
+        token.visit_children(self)
       }
-    "#,
-      "@media (width<=env(unknown)){body{padding:env(unknown)}}",
+    }
+
+    // Registered values are printed from the same token list as unregistered ones.
+    let mut stylesheet = StyleSheet::parse(
+      "@property --x { syntax: '<custom-ident>'; inherits: false; initial-value: a } .a { --x: foo } .b { --y: foo } .c { width: var(--x, foo) }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    stylesheet.visit(&mut IdentVisitor).unwrap();
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(
+      res.code,
+      "@property --x{syntax:\"<custom-ident>\";inherits:false;initial-value:a}.a{--x:bar}.b{--y:bar}.c{width:var(--x,bar)}"
     );
+  }
 
-    prefix_test(
-      r#"
-      .foo {
-        color: env(--brand-color, color(display-p3 0 1 0));
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_visit_parallel() {
+    use crate::values::length::LengthValue;
+    use crate::values::url::Url;
+    use crate::visitor::{ParallelVisitor, VisitTypes, Visitor};
+    use std::convert::Infallible;
+
+    #[derive(Default)]
+    struct UrlVisitor {
+      urls: Vec<String>,
+    }
+
+    impl<'i> Visitor<'i> for UrlVisitor {
+      type Error = Infallible;
+
+      fn visit_types(&self) -> VisitTypes {
+        VisitTypes::URLS | VisitTypes::LENGTHS
       }
-    "#,
-      indoc! {r#"
-      .foo {
-        color: env(--brand-color, #00f942);
+
+      fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+        self.urls.push(url.url.to_string());
+        Ok(())
       }
 
-      @supports (color: color(display-p3 0 0 0)) {
-        .foo {
-          color: env(--brand-color, color(display-p3 0 1 0));
+      fn visit_length(&mut self, length: &mut LengthValue) -> Result<(), Self::Error> {
+        if let LengthValue::Px(px) = length {
+          *length = LengthValue::Rem(*px / 16.0);
         }
+        Ok(())
       }
-    "#},
-      Browsers {
-        safari: Some(15 << 16),
-        chrome: Some(90 << 16),
-        ..Browsers::default()
-      },
-    );
+    }
 
-    css_modules_test(
-      r#"
-      @media (max-width: env(--branding-small)) {
-        .foo {
-          color: env(--brand-color);
-        }
+    impl<'i> ParallelVisitor<'i> for UrlVisitor {
+      fn fork(&self) -> Self {
+        UrlVisitor::default()
       }
-    "#,
-      indoc! {r#"
-      @media (width <= env(--EgL3uq_branding-small)) {
-        .EgL3uq_foo {
-          color: env(--EgL3uq_brand-color);
-        }
+
+      fn join(&mut self, other: Self) {
+        self.urls.extend(other.urls);
       }
-    "#},
-      map! {
-        "foo" => "EgL3uq_foo",
-        "--brand-color" => "--EgL3uq_brand-color" referenced: true,
-        "--branding-small" => "--EgL3uq_branding-small" referenced: true
-      },
-      HashMap::new(),
-      crate::css_modules::Config {
-        dashed_idents: true,
-        ..Default::default()
-      },
-    );
+    }
+
+    let mut source = String::new();
+    for i in 0..200 {
+      source.push_str(&format!(".a{} {{ background: url({}.png); width: 32px }}\n", i, i));
+      if i % 10 == 0 {
+        source.push_str(&format!(
+          "@media print {{ .b{} {{ background: url(print{}.png) }} }}\n",
+          i, i
+        ));
+      }
+    }
+
+    let mut stylesheet = StyleSheet::parse(&source, ParserOptions::default()).unwrap();
+    let mut visitor = UrlVisitor::default();
+    stylesheet.visit_parallel(&mut visitor).unwrap();
+
+    let mut expected = Vec::new();
+    for i in 0..200 {
+      expected.push(format!("{}.png", i));
+      if i % 10 == 0 {
+        expected.push(format!("print{}.png", i));
+      }
+    }
+    assert_eq!(visitor.urls, expected);
+
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert!(res.code.contains("width:2rem"));
+    assert!(!res.code.contains("px"));
   }
 
+  #[cfg(all(feature = "plugin", unix))]
   #[test]
-  fn test_license_comments() {
-    minify_test(
-      r#"
-      /*! Copyright 2023 Someone awesome */
-      /* Some other comment */
-      .foo {
-        color: red;
+  fn test_plugin() {
+    use crate::plugin::{Plugin, PluginError, PluginFilter};
+    use crate::visitor::Visit;
+    use std::process::Command;
+
+    let script = r#"
+      read line
+      echo '{"type":"init","properties":["color"]}'
+      while read line; do
+        case "$line" in
+          *'"type":"done"'*) exit 0;;
+          *'"r":255'*) echo '{"type":"replace","node":{"property":"color","value":{"type":"rgb","r":0,"g":0,"b":255,"alpha":1}}}';;
+          *) echo '{"type":"unchanged"}';;
+        esac
+      done
+    "#;
+
+    let mut stylesheet = StyleSheet::parse(
+      ".foo { color: red; width: 10px } @media print { .bar { color: green } }",
+      ParserOptions::default(),
+    )
+    .unwrap();
+    let mut plugin = Plugin::spawn(Command::new("sh").arg("-c").arg(script)).unwrap();
+    assert_eq!(
+      plugin.filter(),
+      &PluginFilter {
+        rules: vec![],
+        properties: vec!["color".into()]
       }
-    "#,
-      indoc! {r#"
-      /*! Copyright 2023 Someone awesome */
-      .foo{color:red}"#},
     );
+    stylesheet.visit(&mut plugin).unwrap();
+    plugin.finish().unwrap();
+    let res = stylesheet
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })
+      .unwrap();
+    assert_eq!(res.code, ".foo{color:#00f;width:10px}@media print{.bar{color:green}}");
 
-    minify_test(
-      r#"
-      /*! Copyright 2023 Someone awesome */
-      /*! Copyright 2023 Someone else */
-      .foo {
-        color: red;
+    let script = r#"
+      read line
+      echo '{"type":"init","rules":["style"]}'
+      read line
+      echo '{"type":"error","message":"oops"}'
+    "#;
+    let mut stylesheet = StyleSheet::parse(".foo { color: red }", ParserOptions::default()).unwrap();
+    let mut plugin = Plugin::spawn(Command::new("sh").arg("-c").arg(script)).unwrap();
+    match stylesheet.visit(&mut plugin) {
+      Err(PluginError::Plugin(message)) => assert_eq!(message, "oops"),
+      res => panic!("expected plugin error, got {:?}", res),
+    }
+  }
+
+  #[cfg(feature = "archive")]
+  #[test]
+  fn test_archive() {
+    use crate::archive::{archived_style_sheet, ArchiveError};
+
+    let source = indoc! {r#"
+      .foo, .bar { color: red }
+      @media print {
+        .foo { color: blue !important; --Color: green }
       }
-    "#,
-      indoc! {r#"
-      /*! Copyright 2023 Someone awesome */
-      /*! Copyright 2023 Someone else */
-      .foo{color:red}"#},
-    );
+    "#};
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let bytes = stylesheet.to_archive().unwrap();
+    let archived = archived_style_sheet(&bytes).unwrap();
+
+    assert_eq!(archived.nodes.len(), 2);
+    let rules = archived.query_selector(".foo,.bar");
+    assert_eq!(rules.len(), 1);
+    assert_eq!(rules[0].nodes.len(), 1);
+    assert_eq!(archived.query_selector(".foo").len(), 1);
+    assert!(archived.query_selector("!!").is_empty());
+
+    let colors = archived.query_property("COLOR");
+    assert_eq!(colors.len(), 2);
+    assert_eq!(colors[1].value.as_str(), "#00f");
+    assert!(colors[1].important);
+    assert_eq!(archived.query_property("--Color").len(), 1);
+    assert!(archived.query_property("--color").is_empty());
+
+    assert!(matches!(archived_style_sheet(&[0; 64]), Err(ArchiveError::Invalid)));
   }
 
+  #[cfg(feature = "binary")]
   #[test]
-  fn test_starting_style() {
-    minify_test(
-      r#"
-      @starting-style {
-        h1 {
-          background: yellow;
+  fn test_binary() {
+    use crate::cache::{binary_hash, content_hash, BinaryError};
+
+    let source = r#"
+      @import "foo.css" layer(base) supports(display: grid);
+      .foo { color: rgb(255 0 0 / .5); background: url(foo.png) no-repeat; --x: calc(1px + var(--y)) }
+      @media (min-width: 100px) { .bar:hover > .baz { grid-template-areas: "a b" } }
+      @keyframes fade { from { opacity: 0 } to { opacity: 1 } }
+    "#;
+    let stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+    let expected = stylesheet.to_css(PrinterOptions::default()).unwrap().code;
+
+    let hash = content_hash(source, &ParserOptions::default());
+    let binary = stylesheet.to_binary(hash).unwrap();
+    assert_eq!(binary_hash(&binary), Some(hash));
+    let decoded = StyleSheet::from_binary(&binary, ParserOptions::default()).unwrap();
+    assert_eq!(decoded.to_css(PrinterOptions::default()).unwrap().code, expected);
+
+    assert_ne!(content_hash(".foo { color: blue }", &ParserOptions::default()), hash);
+    assert_ne!(
+      content_hash(
+        source,
+        &ParserOptions {
+          filename: "other.css".into(),
+          ..ParserOptions::default()
         }
-      }
-      "#,
-      "@starting-style{h1{background:#ff0}}",
+      ),
+      hash
     );
-    minify_test("@starting-style {}", "");
 
-    nesting_test(
-      r#"
-      h1 {
-        background: red;
-        @starting-style {
-          background: yellow;
-        }
-      }
-      "#,
-      indoc! {r#"
-      h1 {
-        background: red;
-      }
+    assert_eq!(binary_hash(b"not a style sheet"), None);
+    assert_eq!(
+      StyleSheet::from_binary(b"not a style sheet", ParserOptions::default()).unwrap_err(),
+      BinaryError::InvalidHeader
+    );
+    let mut old = binary.clone();
+    old[4] = old[4].wrapping_add(1);
+    assert_eq!(binary_hash(&old), None);
+    assert_eq!(
+      StyleSheet::from_binary(&old, ParserOptions::default()).unwrap_err(),
+      BinaryError::VersionMismatch
+    );
+  }
 
-      @starting-style {
-        h1 {
-          background: #ff0;
-        }
+  #[cfg(feature = "jsonschema")]
+  #[test]
+  fn test_json_schema() {
+    let schema = serde_json::to_value(StyleSheet::json_schema()).unwrap();
+    assert_eq!(schema["version"], crate::stylesheet::AST_VERSION);
+    assert_eq!(schema["title"], "StyleSheet");
+    assert!(schema["definitions"]["DeclarationBlock"].is_object());
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_serde_round_trip() {
+    fn round_trip(source: &str, targets: Option<Browsers>) {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default()).unwrap();
+      if let Some(targets) = targets {
+        stylesheet
+          .minify(MinifyOptions {
+            targets: targets.into(),
+            ..MinifyOptions::default()
+          })
+          .unwrap();
       }
-      "#},
+      let expected = stylesheet.to_css(PrinterOptions::default()).unwrap().code;
+
+      let json = serde_json::to_string(&stylesheet).unwrap();
+      let deserialized: StyleSheet = serde_json::from_str(&json).unwrap();
+      let res = deserialized.to_css(PrinterOptions::default()).unwrap();
+      assert_eq!(res.code, expected, "{}", json);
+      assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+    }
+
+    round_trip(
+      ".foo { color: rgb(none none none / none); background: lch(50% none 20) }",
+      None,
+    );
+    round_trip(".foo { color: color(srgb .41587 .50367 .36664) }", None);
+    round_trip(
+      ".foo { --color: hsl(270 100% 50% / var(--alpha)); --n: +1.5 -2 50.01% 3.25px }",
+      None,
+    );
+    round_trip(
+      ".foo { grid-auto-flow: column dense } .bar { grid-auto-flow: row }",
+      None,
+    );
+    round_trip(".foo { font-family: \"sans-serif\", serif, foo bar }", None);
+    round_trip(".foo ::unknown .bar, .foo::before { color: red }", None);
+    round_trip("@import \"a.css\" layer; @import \"b.css\" layer(foo.bar);", None);
+    round_trip(
+      ".foo { color: red } .bar { color: blue } .foo { color: red }",
+      Some(Browsers::default()),
+    );
+    round_trip(
+      ":is(.foo, .bar) :is(.baz) { color: red }",
+      Some(Browsers {
+        safari: Some(13 << 16),
+        ..Browsers::default()
+      }),
     );
   }
 }
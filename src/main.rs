@@ -1,11 +1,18 @@
 use atty::Stream;
 use clap::{ArgGroup, Parser};
-use lightningcss::bundler::{Bundler, FileProvider};
-use lightningcss::stylesheet::{MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, StyleSheet};
-use lightningcss::targets::Browsers;
+use lightningcss::bundler::{Bundler, ExternalImportsOptions, FileProvider};
+use lightningcss::css_modules::{CssModuleManifest, ExportsConvention, MinimalNames};
+use lightningcss::stylesheet::{
+  DraftSyntax, InlineAssetsOptions, MinifyOptions, ParserFlags, ParserOptions, PrinterOptions, RebaseUrlsOptions,
+  StyleSheet, ValidationMode,
+};
+use lightningcss::targets::{Browsers, Targets};
 use parcel_sourcemap::SourceMap;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::{Arc, RwLock};
 use std::{ffi, fs, io, path::Path};
 
@@ -39,6 +46,9 @@ struct CliArgs {
   /// Enable parsing custom media queries
   #[clap(long, value_parser)]
   custom_media: bool,
+  /// Disable parsing individual draft syntaxes, e.g. "nesting,relative-colors,color-mix"
+  #[clap(long, value_parser, value_delimiter = ',')]
+  disable_drafts: Vec<String>,
   /// Enable CSS modules in output.
   /// If no filename is provided, <output_file>.json will be used.
   /// If no --output-file is specified, code and exports will be printed to stdout as JSON.
@@ -48,17 +58,72 @@ struct CliArgs {
   css_modules_pattern: Option<String>,
   #[clap(long, requires = "css_modules", value_parser)]
   css_modules_dashed_idents: bool,
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_hash_salt: Option<String>,
+  /// How exported names are converted, e.g. "camel-case" to also export `.foo-bar` as `fooBar`
+  #[clap(long, requires = "css_modules", value_parser = ["as-is", "camel-case", "camel-case-only", "dashes"])]
+  css_modules_exports_convention: Option<String>,
+  /// Write a TypeScript declaration file for the CSS modules exports, at <output_file>.d.ts
+  #[clap(long, requires_all = &["css_modules", "output_file"], value_parser)]
+  css_modules_dts: bool,
+  /// Use minimal incrementing class names (a, b, ..., aa) instead of the pattern.
+  /// If a filename is provided, names are read from it when it exists, and written back after compiling,
+  /// so that names stay stable between builds.
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_minimal_names: Option<Option<String>>,
+  /// Require that every selector contains at least one local class or id
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_pure: bool,
+  /// Append ICSS :import and :export blocks for the CSS modules exports to the output
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_icss: bool,
+  /// Write a JSON manifest mapping each source file to its CSS modules exports
+  #[clap(long, requires = "css_modules", value_parser)]
+  css_modules_manifest: Option<String>,
   /// Enable sourcemap, at <output_file>.map
   #[clap(long, requires = "output_file", value_parser)]
   sourcemap: bool,
   #[clap(long, value_parser)]
   bundle: bool,
+  /// Preserve @import rules of remote urls when bundling, rather than inlining them
+  #[clap(long, requires = "bundle", value_parser)]
+  external_imports: bool,
+  /// Preserve @import rules of urls matching the given patterns when bundling, e.g. "https://fonts.googleapis.com/*"
+  #[clap(long, requires = "bundle", value_parser)]
+  external_imports_include: Vec<String>,
+  /// Rewrite relative url() references to be relative to the output file
+  #[clap(long, requires = "output_file", value_parser)]
+  rebase_urls: bool,
+  /// A public path to prefix rebased urls with, e.g. /assets/
+  #[clap(long, requires = "rebase-urls", value_parser)]
+  public_path: Option<String>,
+  /// Inline files referenced by url() as data URIs, if smaller than the given size in bytes (default: 4096)
+  #[clap(long, value_parser)]
+  inline_assets: Option<Option<u64>>,
+  /// Only inline files matching the given patterns, e.g. "*.svg"
+  #[clap(long, requires = "inline-assets", value_parser)]
+  inline_assets_include: Vec<String>,
+  /// Do not inline files matching the given patterns
+  #[clap(long, requires = "inline-assets", value_parser)]
+  inline_assets_exclude: Vec<String>,
+  /// Copy files referenced by url() next to the output file, with a content hash in their filenames
+  #[clap(long, requires = "output_file", value_parser)]
+  emit_assets: bool,
+  /// Write a JSON manifest mapping each file referenced by url() to its emitted filename
+  #[clap(long, requires = "emit-assets", value_parser)]
+  asset_manifest: Option<String>,
   #[clap(short, long, value_parser)]
   targets: Vec<String>,
   #[clap(long, value_parser)]
   browserslist: bool,
+  /// Override the compilation of individual features, e.g. "nesting: compile, colors: preserve"
+  #[clap(long, value_parser)]
+  features: Option<String>,
   #[clap(long, value_parser)]
   error_recovery: bool,
+  /// How to handle unknown properties and invalid property values
+  #[clap(long, value_parser = ["ignore", "warn", "error"])]
+  validation: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -116,6 +181,14 @@ pub fn main() -> Result<(), std::io::Error> {
     vec![(filename, contents)]
   };
 
+  let minimal_names = match &cli_args.css_modules_minimal_names {
+    Some(Some(file)) if Path::new(file).exists() => Some(MinimalNames::with_names(serde_json::from_str(
+      &fs::read_to_string(file)?,
+    )?)),
+    Some(_) => Some(MinimalNames::new()),
+    None => None,
+  };
+
   let css_modules = if let Some(_) = cli_args.css_modules {
     let pattern = if let Some(pattern) = cli_args.css_modules_pattern.as_ref() {
       match lightningcss::css_modules::Pattern::parse(pattern) {
@@ -132,6 +205,16 @@ pub fn main() -> Result<(), std::io::Error> {
     Some(lightningcss::css_modules::Config {
       pattern,
       dashed_idents: cli_args.css_modules_dashed_idents,
+      hash_salt: cli_args.css_modules_hash_salt.as_deref(),
+      exports_convention: match cli_args.css_modules_exports_convention.as_deref() {
+        Some("camel-case") => ExportsConvention::CamelCase,
+        Some("camel-case-only") => ExportsConvention::CamelCaseOnly,
+        Some("dashes") => ExportsConvention::Dashes,
+        _ => ExportsConvention::AsIs,
+      },
+      minimal_names: minimal_names.as_ref(),
+      pure: cli_args.css_modules_pure,
+      icss: cli_args.css_modules_icss,
       ..Default::default()
     })
   } else {
@@ -139,9 +222,17 @@ pub fn main() -> Result<(), std::io::Error> {
   };
 
   let fs = FileProvider::new();
+  let mut manifest = CssModuleManifest::new();
+  let mut asset_manifest = HashMap::new();
+
+  let validation = match cli_args.validation.as_deref() {
+    Some("warn") => ValidationMode::Warn,
+    Some("error") => ValidationMode::Error,
+    _ => ValidationMode::Ignore,
+  };
 
   for (filename, source) in inputs {
-    let warnings = if cli_args.error_recovery {
+    let warnings = if cli_args.error_recovery || validation == ValidationMode::Warn {
       Some(Arc::new(RwLock::new(Vec::new())))
     } else {
       None
@@ -163,20 +254,64 @@ pub fn main() -> Result<(), std::io::Error> {
       None
     };
 
+    // Input filenames are relative to the project root, so the output file must be as well.
+    let rebase_output = match &output_file {
+      Some(output_file) if cli_args.rebase_urls => {
+        if output_file.is_absolute() {
+          pathdiff::diff_paths(output_file, &project_root)
+        } else {
+          Some(output_file.to_path_buf())
+        }
+      }
+      _ => None,
+    }
+    .map(|output_file| output_file.to_string_lossy().into_owned());
+
+    let assets_dir = match &output_file {
+      Some(output_file) if cli_args.emit_assets => Some(output_file.parent().unwrap_or(Path::new(""))),
+      _ => None,
+    };
+    let emit = |path: &Path| -> Option<String> {
+      match emit_asset(path, assets_dir?) {
+        Ok(filename) => Some(filename),
+        Err(err) => {
+          eprintln!("Failed to emit asset {}: {}", path.display(), err);
+          None
+        }
+      }
+    };
+
     let res = {
       let mut flags = ParserFlags::empty();
       flags.set(ParserFlags::CUSTOM_MEDIA, cli_args.custom_media);
 
+      let mut disabled_drafts = DraftSyntax::empty();
+      for name in &cli_args.disable_drafts {
+        match DraftSyntax::from_name(&name.trim().replace('-', "_").to_ascii_uppercase()) {
+          Some(draft) => disabled_drafts |= draft,
+          None => {
+            eprintln!("Unknown draft syntax: {}", name);
+            std::process::exit(1);
+          }
+        }
+      }
+
       let mut options = ParserOptions {
         flags,
+        disabled_drafts,
         css_modules: css_modules.clone(),
         error_recovery: cli_args.error_recovery,
         warnings: warnings.clone(),
+        validation,
         ..ParserOptions::default()
       };
 
       let mut stylesheet = if cli_args.bundle {
         let mut bundler = Bundler::new(&fs, source_map.as_mut(), options);
+        bundler.set_external_imports(ExternalImportsOptions {
+          remote: cli_args.external_imports,
+          include: cli_args.external_imports_include.clone(),
+        });
         bundler.bundle(Path::new(&filename)).unwrap()
       } else {
         if let Some(sm) = &mut source_map {
@@ -187,7 +322,7 @@ pub fn main() -> Result<(), std::io::Error> {
         StyleSheet::parse(&source, options).unwrap()
       };
 
-      let targets = if !cli_args.targets.is_empty() {
+      let mut targets: Targets = if !cli_args.targets.is_empty() {
         Browsers::from_browserslist(&cli_args.targets).unwrap()
       } else if cli_args.browserslist {
         Browsers::load_browserslist().unwrap()
@@ -196,6 +331,13 @@ pub fn main() -> Result<(), std::io::Error> {
       }
       .into();
 
+      if let Some(features) = &cli_args.features {
+        if let Err(err) = targets.set_feature_toggles(features) {
+          eprintln!("{}", err);
+          std::process::exit(1);
+        }
+      }
+
       stylesheet
         .minify(MinifyOptions {
           targets,
@@ -209,6 +351,16 @@ pub fn main() -> Result<(), std::io::Error> {
           source_map: source_map.as_mut(),
           project_root: Some(&project_root.to_string_lossy()),
           targets,
+          rebase_urls: rebase_output.as_deref().map(|output_file| RebaseUrlsOptions {
+            output_file,
+            public_path: cli_args.public_path.as_deref(),
+          }),
+          inline_assets: cli_args.inline_assets.map(|max_size| InlineAssetsOptions {
+            max_size,
+            include: cli_args.inline_assets_include.clone(),
+            exclude: cli_args.inline_assets_exclude.clone(),
+          }),
+          emit_asset: if assets_dir.is_some() { Some(&emit) } else { None },
           ..PrinterOptions::default()
         })
         .unwrap()
@@ -240,6 +392,14 @@ pub fn main() -> Result<(), std::io::Error> {
       }
     }
 
+    if let Some(res_manifest) = res.manifest {
+      manifest.extend(res_manifest);
+    }
+
+    if let Some(assets) = res.assets {
+      asset_manifest.extend(assets);
+    }
+
     if let Some(output_file) = &output_file {
       let mut code = res.code;
       if cli_args.sourcemap {
@@ -264,6 +424,13 @@ pub fn main() -> Result<(), std::io::Error> {
         if let Some(exports) = res.exports {
           let css_modules_json = serde_json::to_string(&exports)?;
           fs::write(css_modules_filename.as_ref(), css_modules_json)?;
+          if cli_args.css_modules_dts {
+            let dts_filename = output_file.to_string_lossy() + ".d.ts";
+            fs::write(
+              dts_filename.as_ref(),
+              lightningcss::css_modules::typescript_declarations(&exports),
+            )?;
+          }
         }
       }
     } else {
@@ -281,9 +448,38 @@ pub fn main() -> Result<(), std::io::Error> {
     }
   }
 
+  if let Some(manifest_filename) = &cli_args.css_modules_manifest {
+    fs::write(manifest_filename, serde_json::to_string(&manifest)?)?;
+  }
+
+  if let Some(asset_manifest_filename) = &cli_args.asset_manifest {
+    fs::write(asset_manifest_filename, serde_json::to_string(&asset_manifest)?)?;
+  }
+
+  if let (Some(Some(file)), Some(minimal_names)) = (&cli_args.css_modules_minimal_names, &minimal_names) {
+    fs::write(file, serde_json::to_string(&minimal_names.names())?)?;
+  }
+
   Ok(())
 }
 
+/// Copies an asset into the given directory, with a hash of its contents in the filename.
+fn emit_asset(path: &Path, dir: &Path) -> Result<String, std::io::Error> {
+  let contents = fs::read(path)?;
+  let mut hasher = DefaultHasher::new();
+  contents.hash(&mut hasher);
+  let hash = format!("{:016x}", hasher.finish());
+  let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("asset");
+  let filename = match path.extension().and_then(|e| e.to_str()) {
+    Some(extension) => format!("{}.{}.{}", stem, &hash[..8], extension),
+    None => format!("{}.{}", stem, &hash[..8]),
+  };
+
+  fs::create_dir_all(dir)?;
+  fs::write(dir.join(&filename), contents)?;
+  Ok(filename)
+}
+
 fn infer_css_modules_filename(path: &Path) -> Result<String, std::io::Error> {
   if path.extension() == Some(ffi::OsStr::new("json")) {
     Err(io::Error::new(
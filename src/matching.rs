@@ -0,0 +1,432 @@
+//! Selector matching.
+//!
+//! [StyleSheet::selector_matcher](crate::stylesheet::StyleSheet::selector_matcher) compiles the selectors
+//! of a style sheet's style rules into a [SelectorMatcher](SelectorMatcher), which can be matched against
+//! elements of a document tree, e.g. to extract the critical CSS for a page or to inline styles into HTML
+//! emails. The document is provided by implementing the [Element](Element) trait, so any DOM or HTML parser
+//! can be used.
+//!
+//! Selectors of nested rules are resolved against their parent rules, and selectors are indexed by the
+//! id, class, or tag name of their rightmost compound selector so that only candidate selectors are matched
+//! against each element. Each match includes the specificity of the selector, and matches are returned in
+//! cascade order.
+//!
+//! The document is static, so user action pseudo classes such as `:hover` never match, and the shadow DOM
+//! is not supported. Selectors that cannot be matched, e.g. those with `:has()` or `:nth-child(… of S)`, and
+//! rules inside `@scope` and `@starting-style` are ignored.
+
+use crate::query::RuleHandle;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList};
+use crate::selector::{Combinator, Component, PseudoClass, PseudoElement, Selector, Selectors, Specificity};
+use crate::values::ident::Ident;
+use crate::values::string::{CSSString, CowArcStr};
+use parcel_selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
+use parcel_selectors::context::{MatchingContext, MatchingMode, QuirksMode};
+use parcel_selectors::matching::{matches_selector, ElementSelectorFlags};
+use parcel_selectors::parser::NthType;
+use parcel_selectors::OpaqueElement;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An element in a document tree, which selectors are matched against.
+///
+/// The id and classes of an element are read from its `id` and `class` attributes. Elements are
+/// assumed to be HTML elements, so tag names and attribute names should be lower case.
+pub trait Element: Sized + Clone {
+  /// Returns the tag name of the element.
+  fn local_name(&self) -> &str;
+
+  /// Returns the value of an attribute, or `None` if the element does not have it.
+  fn attr(&self, name: &str) -> Option<&str>;
+
+  /// Returns the parent element, or `None` for the root element.
+  fn parent(&self) -> Option<Self>;
+
+  /// Returns the previous sibling element, skipping text and comment nodes.
+  fn prev_sibling(&self) -> Option<Self>;
+
+  /// Returns the next sibling element, skipping text and comment nodes.
+  fn next_sibling(&self) -> Option<Self>;
+
+  /// Returns whether the element has no children, including text nodes.
+  fn is_empty(&self) -> bool;
+
+  /// Returns whether the element is a hyperlink. By default, `<a>`, `<area>`, and `<link>` elements
+  /// with an `href` attribute are links.
+  fn is_link(&self) -> bool {
+    matches!(self.local_name(), "a" | "area" | "link") && self.attr("href").is_some()
+  }
+
+  /// Returns whether the element matches a pseudo class. By default, `:link` and `:any-link` match links,
+  /// `:checked` and `:disabled` match elements with the corresponding attribute, and all other pseudo classes
+  /// never match.
+  fn match_pseudo_class(&self, pseudo_class: &PseudoClass) -> bool {
+    match pseudo_class {
+      PseudoClass::Link | PseudoClass::AnyLink(..) => self.is_link(),
+      PseudoClass::Checked => self.attr("checked").is_some(),
+      PseudoClass::Disabled => self.attr("disabled").is_some(),
+      _ => false,
+    }
+  }
+}
+
+/// A style rule selector that matched an element, as returned by
+/// [SelectorMatcher::match_element](SelectorMatcher::match_element).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectorMatch<'i> {
+  /// The style rule that contains the selector.
+  pub rule: RuleHandle,
+  /// The index of the selector within the style rule's selector list.
+  pub selector: usize,
+  /// The specificity of the selector, including the parent rules' selectors for nested rules.
+  pub specificity: Specificity,
+  /// The pseudo element the rule applies to, e.g. `::before`, or `None` if it applies to the element itself.
+  pub pseudo_element: Option<PseudoElement<'i>>,
+}
+
+struct IndexedSelector<'i> {
+  rule: RuleHandle,
+  index: usize,
+  /// The selector for the originating element, without the pseudo element.
+  selector: Selector<'i>,
+  specificity: Specificity,
+  pseudo_element: Option<PseudoElement<'i>>,
+}
+
+/// The selectors of a style sheet, compiled into an index for matching against elements.
+/// See the [matching](self) module for more details.
+#[derive(Default)]
+pub struct SelectorMatcher<'i> {
+  selectors: Vec<IndexedSelector<'i>>,
+  ids: HashMap<String, Vec<usize>>,
+  classes: HashMap<String, Vec<usize>>,
+  local_names: HashMap<String, Vec<usize>>,
+  universal: Vec<usize>,
+}
+
+impl<'i> SelectorMatcher<'i> {
+  pub(crate) fn add_rules<R>(
+    &mut self,
+    rules: &CssRuleList<'i, R>,
+    parents: Option<&[Selector<'i>]>,
+    path: &mut Vec<usize>,
+  ) {
+    for (index, rule) in rules.0.iter().enumerate() {
+      path.push(index);
+      match rule {
+        CssRule::Style(style) => self.add_style_rule(style, parents, path),
+        CssRule::Nesting(nesting) => self.add_style_rule(&nesting.style, parents, path),
+        CssRule::Media(media) => self.add_rules(&media.rules, parents, path),
+        CssRule::Supports(supports) => self.add_rules(&supports.rules, parents, path),
+        CssRule::MozDocument(document) => self.add_rules(&document.rules, parents, path),
+        CssRule::LayerBlock(layer) => self.add_rules(&layer.rules, parents, path),
+        CssRule::Container(container) => self.add_rules(&container.rules, parents, path),
+        _ => {}
+      }
+      path.pop();
+    }
+  }
+
+  fn add_style_rule<R>(
+    &mut self,
+    style: &StyleRule<'i, R>,
+    parents: Option<&[Selector<'i>]>,
+    path: &mut Vec<usize>,
+  ) {
+    let selectors = style
+      .selectors
+      .0
+      .iter()
+      .map(|selector| resolve_nesting(selector, parents))
+      .collect::<Vec<_>>();
+
+    for (index, selector) in selectors.iter().enumerate() {
+      if is_supported(selector) {
+        self.add_selector(RuleHandle { path: path.clone() }, index, selector);
+      }
+    }
+
+    self.add_rules(&style.rules, Some(&selectors), path);
+  }
+
+  fn add_selector(&mut self, rule: RuleHandle, index: usize, selector: &Selector<'i>) {
+    // Split off the pseudo element, which is matched against the originating element instead.
+    let mut components = selector.iter_raw_match_order().as_slice();
+    let mut pseudo_element = None;
+    if let Some(position) = components
+      .iter()
+      .position(|c| matches!(c, Component::Combinator(Combinator::PseudoElement)))
+    {
+      match &components[..position] {
+        [Component::PseudoElement(pseudo)] => pseudo_element = Some(pseudo.clone()),
+        // State pseudo classes after a pseudo element never match a static document.
+        _ => return,
+      }
+      components = &components[position + 1..];
+    }
+
+    let selector_index = self.selectors.len();
+    let bucket = match key(components) {
+      Some(Component::ID(id)) => self.ids.entry(id.0.to_string()).or_default(),
+      Some(Component::Class(class)) => self.classes.entry(class.0.to_string()).or_default(),
+      Some(Component::LocalName(name)) => self.local_names.entry(name.lower_name.0.to_string()).or_default(),
+      _ => &mut self.universal,
+    };
+    bucket.push(selector_index);
+
+    let originating = if components.is_empty() {
+      vec![Component::ExplicitUniversalType]
+    } else {
+      components.iter().rev().cloned().collect()
+    };
+
+    self.selectors.push(IndexedSelector {
+      rule,
+      index,
+      selector: Selector::from(originating),
+      specificity: Specificity::from_selector(selector),
+      pseudo_element,
+    });
+  }
+
+  /// Returns the selectors that match an element, in cascade order: sorted by specificity, and then by
+  /// source order.
+  pub fn match_element<E: Element>(&self, element: &E) -> Vec<SelectorMatch<'i>> {
+    let mut candidates = self.universal.clone();
+    let mut add = |bucket: &HashMap<String, Vec<usize>>, key: &str| {
+      if let Some(selectors) = bucket.get(key) {
+        candidates.extend_from_slice(selectors);
+      }
+    };
+
+    add(&self.local_names, element.local_name());
+    if let Some(id) = element.attr("id") {
+      add(&self.ids, id);
+    }
+    if let Some(classes) = element.attr("class") {
+      for class in classes.split_ascii_whitespace() {
+        add(&self.classes, class);
+      }
+    }
+
+    // Candidates are indices into the selectors, so sorting them also sorts by source order.
+    candidates.sort_unstable();
+    candidates.dedup();
+
+    let element = ElementRef(element.clone());
+    let mut res = candidates
+      .into_iter()
+      .map(|index| &self.selectors[index])
+      .filter(|indexed| {
+        let mut context = MatchingContext::new(MatchingMode::Normal, None, None, QuirksMode::NoQuirks);
+        matches_selector(&indexed.selector, 0, None, &element, &mut context, &mut |_, _| {})
+      })
+      .map(|indexed| SelectorMatch {
+        rule: indexed.rule.clone(),
+        selector: indexed.index,
+        specificity: indexed.specificity,
+        pseudo_element: indexed.pseudo_element.clone(),
+      })
+      .collect::<Vec<_>>();
+
+    // The sort is stable, so matches with the same specificity stay in source order.
+    res.sort_by_key(|m| m.specificity);
+    res
+  }
+}
+
+/// Replaces the nesting selectors in a selector with `:is()` containing the parent rule's selectors,
+/// or with `:scope` at the top level. The selectors of nested rules always contain a nesting selector,
+/// which is added implicitly by the parser if needed.
+fn resolve_nesting<'i>(selector: &Selector<'i>, parents: Option<&[Selector<'i>]>) -> Selector<'i> {
+  let resolve_list = |selectors: &[Selector<'i>]| -> Box<[Selector<'i>]> {
+    selectors.iter().map(|selector| resolve_nesting(selector, parents)).collect()
+  };
+
+  let components = selector
+    .iter_raw_parse_order_from(0)
+    .map(|component| match component {
+      Component::Nesting => match parents {
+        Some(parents) => Component::Is(parents.into()),
+        None => Component::Scope,
+      },
+      Component::Negation(selectors) => Component::Negation(resolve_list(selectors)),
+      Component::Is(selectors) => Component::Is(resolve_list(selectors)),
+      Component::Where(selectors) => Component::Where(resolve_list(selectors)),
+      Component::Any(prefix, selectors) => Component::Any(*prefix, resolve_list(selectors)),
+      Component::Host(Some(selector)) => Component::Host(Some(resolve_nesting(selector, parents))),
+      Component::Slotted(selector) => Component::Slotted(resolve_nesting(selector, parents)),
+      component => component.clone(),
+    })
+    .collect::<Vec<_>>();
+
+  Selector::from(components)
+}
+
+/// Returns whether a selector can be matched, i.e. it does not contain `:has()`, `:nth-child(… of S)`,
+/// or `:nth-col()`, which the matching engine does not support.
+fn is_supported(selector: &Selector) -> bool {
+  selector.iter_raw_match_order().all(|component| match component {
+    Component::Has(..) | Component::NthOf(..) => false,
+    Component::Nth(data) => !matches!(data.ty, NthType::Col | NthType::LastCol),
+    Component::Negation(selectors)
+    | Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Any(_, selectors) => selectors.iter().all(is_supported),
+    Component::Host(Some(selector)) | Component::Slotted(selector) => is_supported(selector),
+    _ => true,
+  })
+}
+
+/// Returns the component of the rightmost compound selector that the selector is indexed by:
+/// an id, a class, or a tag name, in order of preference.
+fn key<'a, 'i>(components: &'a [Component<'i>]) -> Option<&'a Component<'i>> {
+  let compound = components
+    .iter()
+    .take_while(|c| !matches!(c, Component::Combinator(..)))
+    .collect::<Vec<_>>();
+  compound
+    .iter()
+    .find(|c| matches!(c, Component::ID(..)))
+    .or_else(|| compound.iter().find(|c| matches!(c, Component::Class(..))))
+    .or_else(|| compound.iter().find(|c| matches!(c, Component::LocalName(..))))
+    .copied()
+}
+
+/// Implements the selector engine's element trait for an [Element](Element).
+#[derive(Clone)]
+struct ElementRef<E>(E);
+
+impl<E: Element> fmt::Debug for ElementRef<E> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_tuple("ElementRef").field(&self.0.local_name()).finish()
+  }
+}
+
+const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+impl<'i, E: Element> parcel_selectors::Element<'i> for ElementRef<E> {
+  type Impl = Selectors;
+
+  fn opaque(&self) -> OpaqueElement {
+    // Only used for :scope, :host, and the nth index cache, none of which depend on element identity here.
+    OpaqueElement::new(&self.0)
+  }
+
+  fn parent_element(&self) -> Option<Self> {
+    self.0.parent().map(ElementRef)
+  }
+
+  fn parent_node_is_shadow_root(&self) -> bool {
+    false
+  }
+
+  fn containing_shadow_host(&self) -> Option<Self> {
+    None
+  }
+
+  fn is_pseudo_element(&self) -> bool {
+    false
+  }
+
+  fn prev_sibling_element(&self) -> Option<Self> {
+    self.0.prev_sibling().map(ElementRef)
+  }
+
+  fn next_sibling_element(&self) -> Option<Self> {
+    self.0.next_sibling().map(ElementRef)
+  }
+
+  fn is_html_element_in_html_document(&self) -> bool {
+    true
+  }
+
+  fn has_local_name(&self, local_name: &Ident<'i>) -> bool {
+    self.0.local_name() == local_name.0.as_ref()
+  }
+
+  fn has_namespace(&self, ns: &CowArcStr<'i>) -> bool {
+    ns.as_ref() == XHTML_NAMESPACE
+  }
+
+  fn is_same_type(&self, other: &Self) -> bool {
+    self.0.local_name() == other.0.local_name()
+  }
+
+  fn attr_matches(
+    &self,
+    ns: &NamespaceConstraint<&CowArcStr<'i>>,
+    local_name: &Ident<'i>,
+    operation: &AttrSelectorOperation<&CSSString<'i>>,
+  ) -> bool {
+    let in_no_namespace = match ns {
+      NamespaceConstraint::Any => true,
+      NamespaceConstraint::Specific(ns) => ns.is_empty(),
+    };
+    in_no_namespace
+      && self
+        .0
+        .attr(local_name.0.as_ref())
+        .map_or(false, |value| operation.eval_str(value))
+  }
+
+  fn match_non_ts_pseudo_class<F>(
+    &self,
+    pc: &PseudoClass<'i>,
+    _context: &mut MatchingContext<'_, 'i, Self::Impl>,
+    _flags_setter: &mut F,
+  ) -> bool
+  where
+    F: FnMut(&Self, ElementSelectorFlags),
+  {
+    self.0.match_pseudo_class(pc)
+  }
+
+  fn match_pseudo_element(
+    &self,
+    _pe: &PseudoElement<'i>,
+    _context: &mut MatchingContext<'_, 'i, Self::Impl>,
+  ) -> bool {
+    false
+  }
+
+  fn is_link(&self) -> bool {
+    self.0.is_link()
+  }
+
+  fn is_html_slot_element(&self) -> bool {
+    false
+  }
+
+  fn has_id(&self, id: &Ident<'i>, case_sensitivity: CaseSensitivity) -> bool {
+    self
+      .0
+      .attr("id")
+      .map_or(false, |value| case_sensitivity.eq(value.as_bytes(), id.0.as_bytes()))
+  }
+
+  fn has_class(&self, name: &Ident<'i>, case_sensitivity: CaseSensitivity) -> bool {
+    self.0.attr("class").map_or(false, |classes| {
+      classes
+        .split_ascii_whitespace()
+        .any(|class| case_sensitivity.eq(class.as_bytes(), name.0.as_bytes()))
+    })
+  }
+
+  fn imported_part(&self, _name: &Ident<'i>) -> Option<Ident<'i>> {
+    None
+  }
+
+  fn is_part(&self, _name: &Ident<'i>) -> bool {
+    false
+  }
+
+  fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+
+  fn is_root(&self) -> bool {
+    self.0.parent().is_none()
+  }
+}
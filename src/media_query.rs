@@ -12,9 +12,10 @@ use crate::stylesheet::ParserOptions;
 use crate::targets::{should_compile, Targets};
 use crate::traits::{Parse, ToCss};
 use crate::values::ident::{DashedIdent, Ident};
+use crate::values::length::{Length, UnitContext};
 use crate::values::number::{CSSInteger, CSSNumber};
 use crate::values::string::CowArcStr;
-use crate::values::{length::Length, ratio::Ratio, resolution::Resolution};
+use crate::values::{ratio::Ratio, resolution::Resolution};
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
@@ -74,6 +75,15 @@ impl<'i> MediaList<'i> {
     Ok(MediaList { media_queries })
   }
 
+  /// Parse a media query list from a string.
+  pub fn parse_string(input: &'i str) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut input = ParserInput::new(input);
+    let mut parser = Parser::new(&mut input);
+    let result = Self::parse(&mut parser)?;
+    parser.expect_exhausted()?;
+    Ok(result)
+  }
+
   pub(crate) fn transform_custom_media(
     &mut self,
     loc: Location,
@@ -118,6 +128,53 @@ impl<'i> MediaList<'i> {
     !self.media_queries.is_empty() && self.media_queries.iter().all(|mq| mq.never_matches())
   }
 
+  /// Returns whether the media query list matches the given environment, i.e. whether any of
+  /// its media queries match. An empty media query list always matches.
+  pub fn matches(&self, env: &Environment) -> bool {
+    self.media_queries.is_empty() || self.media_queries.iter().any(|mq| mq.matches(env))
+  }
+
+  /// Normalizes and simplifies the media query list, and returns whether it always matches,
+  /// never matches, or depends on the environment.
+  ///
+  /// Double negations are resolved, nested conditions with the same operator are flattened,
+  /// redundant range constraints on the same feature are merged, and branches that are always
+  /// true or false are removed. Media queries that never match are removed from the list. If the
+  /// list always matches, it is replaced with `all`, and if it never matches, with `not all`.
+  pub fn simplify(&mut self) -> MediaMatch {
+    if self.media_queries.is_empty() {
+      return MediaMatch::Always;
+    }
+
+    let mut media_queries: Vec<MediaQuery<'i>> = Vec::new();
+    for mut query in std::mem::take(&mut self.media_queries) {
+      match query.simplify() {
+        MediaMatch::Always => {
+          self.media_queries = vec![query];
+          return MediaMatch::Always;
+        }
+        MediaMatch::Never => {}
+        MediaMatch::Conditional => {
+          if !media_queries.contains(&query) {
+            media_queries.push(query);
+          }
+        }
+      }
+    }
+
+    if media_queries.is_empty() {
+      self.media_queries = vec![MediaQuery {
+        qualifier: Some(Qualifier::Not),
+        media_type: MediaType::All,
+        condition: None,
+      }];
+      return MediaMatch::Never;
+    }
+
+    self.media_queries = media_queries;
+    MediaMatch::Conditional
+  }
+
   /// Attempts to combine the given media query list into this one. The resulting media query
   /// list matches if both the original media query lists would have matched.
   ///
@@ -174,6 +231,18 @@ impl<'i> ToCss for MediaList<'i> {
   }
 }
 
+/// Whether a media query list matches independently of the environment, as returned by
+/// [MediaList::simplify](MediaList::simplify).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MediaMatch {
+  /// The media query list always matches.
+  Always,
+  /// The media query list never matches.
+  Never,
+  /// Whether the media query list matches depends on the environment.
+  Conditional,
+}
+
 enum_property! {
   /// A [media query qualifier](https://drafts.csswg.org/mediaqueries/#mq-prefix).
   pub enum Qualifier {
@@ -342,6 +411,69 @@ impl<'i> MediaQuery<'i> {
     self.qualifier == Some(Qualifier::Not) && self.media_type == MediaType::All && self.condition == None
   }
 
+  /// Returns whether the media query matches the given environment.
+  ///
+  /// Media features that are unknown, or whose value is not provided by the environment,
+  /// evaluate to "unknown" as defined in the spec. If the result of the query is unknown,
+  /// it does not match.
+  pub fn matches(&self, env: &Environment) -> bool {
+    let media_type = match (&self.media_type, &env.media_type) {
+      (MediaType::All, _) | (MediaType::Print, MediaType::Print) | (MediaType::Screen, MediaType::Screen) => true,
+      (MediaType::Custom(a), MediaType::Custom(b)) => a.eq_ignore_ascii_case(b),
+      _ => false,
+    };
+
+    let result = if media_type {
+      match &self.condition {
+        Some(condition) => condition.evaluate(env),
+        None => Some(true),
+      }
+    } else {
+      Some(false)
+    };
+
+    match self.qualifier {
+      Some(Qualifier::Not) => result.map(|result| !result),
+      _ => result,
+    }
+    .unwrap_or(false)
+  }
+
+  /// Normalizes and simplifies the media query, and returns whether it always matches, never
+  /// matches, or depends on the environment. See [MediaList::simplify](MediaList::simplify).
+  pub fn simplify(&mut self) -> MediaMatch {
+    match self.condition.take().map(MediaCondition::simplify) {
+      Some(SimplifiedCondition::Constant(false)) => {
+        // The query never matches, so its negation always matches.
+        let negated = self.qualifier == Some(Qualifier::Not);
+        *self = MediaQuery {
+          qualifier: if negated { None } else { Some(Qualifier::Not) },
+          media_type: MediaType::All,
+          condition: None,
+        };
+        return if negated { MediaMatch::Always } else { MediaMatch::Never };
+      }
+      Some(SimplifiedCondition::Condition(MediaCondition::Not(condition)))
+        if self.qualifier == Some(Qualifier::Not) && self.media_type == MediaType::All =>
+      {
+        // `not all and (not (x))` => `(x)`
+        self.qualifier = None;
+        self.condition = Some(*condition);
+      }
+      Some(SimplifiedCondition::Condition(condition)) => self.condition = Some(condition),
+      Some(SimplifiedCondition::Constant(true)) | None => {}
+    }
+
+    if self.condition.is_some() || self.media_type != MediaType::All {
+      MediaMatch::Conditional
+    } else if self.qualifier == Some(Qualifier::Not) {
+      MediaMatch::Never
+    } else {
+      self.qualifier = None;
+      MediaMatch::Always
+    }
+  }
+
   /// Attempts to combine the given media query into this one. The resulting media query
   /// matches if both of the original media queries would have matched.
   ///
@@ -422,7 +554,11 @@ impl<'i> ToCss for MediaQuery<'i> {
       }
       MediaType::Print => dest.write_str("print")?,
       MediaType::Screen => dest.write_str("screen")?,
-      MediaType::Custom(ref desc) => dest.write_str(desc)?,
+      MediaType::Custom(ref desc) => {
+        if !dest.write_css_module_value(desc)? {
+          dest.write_str(desc)?
+        }
+      }
     }
 
     let condition = match self.condition {
@@ -437,7 +573,10 @@ impl<'i> ToCss for MediaQuery<'i> {
       false
     };
 
-    to_css_with_parens_if_needed(condition, dest, needs_parens)
+    let in_media_query = std::mem::replace(&mut dest.in_media_query, true);
+    let res = to_css_with_parens_if_needed(condition, dest, needs_parens);
+    dest.in_media_query = in_media_query;
+    res
   }
 }
 
@@ -484,6 +623,84 @@ impl<'i, 'de: 'i> serde::Deserialize<'de> for MediaQuery<'i> {
   }
 }
 
+/// A simulated device that media queries can be evaluated against, e.g. to determine which
+/// rules apply without a browser. See [MediaQuery::matches](MediaQuery::matches).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Environment {
+  /// The media type of the device. Defaults to `screen`.
+  pub media_type: MediaType<'static>,
+  /// The width of the viewport in pixels.
+  pub width: Option<CSSNumber>,
+  /// The height of the viewport in pixels.
+  pub height: Option<CSSNumber>,
+  /// The width of the output device in pixels. Defaults to the viewport width.
+  pub device_width: Option<CSSNumber>,
+  /// The height of the output device in pixels. Defaults to the viewport height.
+  pub device_height: Option<CSSNumber>,
+  /// The resolution of the device in dots per pixel unit (dppx).
+  pub resolution: Option<CSSNumber>,
+  /// The initial font size in pixels, which relative lengths such as `em` resolve against.
+  /// Defaults to 16.
+  pub font_size: CSSNumber,
+  /// Values of other media features, such as `prefers-color-scheme` or `hover`, keyed by
+  /// their lower case name. These take precedence over values derived from the above fields.
+  pub features: HashMap<String, MediaFeatureValue<'static>>,
+}
+
+impl Default for Environment {
+  fn default() -> Environment {
+    Environment {
+      media_type: MediaType::Screen,
+      width: None,
+      height: None,
+      device_width: None,
+      device_height: None,
+      resolution: None,
+      font_size: 16.0,
+      features: HashMap::new(),
+    }
+  }
+}
+
+impl Environment {
+  fn feature_value(&self, name: &str) -> Option<MediaFeatureValue<'static>> {
+    if let Some(value) = self.features.get(name) {
+      return Some(value.clone());
+    }
+
+    let device_width = self.device_width.or(self.width);
+    let device_height = self.device_height.or(self.height);
+    Some(match name {
+      "width" => MediaFeatureValue::Length(Length::px(self.width?)),
+      "height" => MediaFeatureValue::Length(Length::px(self.height?)),
+      "aspect-ratio" => MediaFeatureValue::Ratio(Ratio(self.width?, self.height?)),
+      "orientation" => {
+        let orientation = if self.height? >= self.width? {
+          "portrait"
+        } else {
+          "landscape"
+        };
+        MediaFeatureValue::Ident(Ident(orientation.into()))
+      }
+      "device-width" => MediaFeatureValue::Length(Length::px(device_width?)),
+      "device-height" => MediaFeatureValue::Length(Length::px(device_height?)),
+      "device-aspect-ratio" => MediaFeatureValue::Ratio(Ratio(device_width?, device_height?)),
+      "resolution" => MediaFeatureValue::Resolution(Resolution::Dppx(self.resolution?)),
+      "-webkit-device-pixel-ratio" | "-moz-device-pixel-ratio" => MediaFeatureValue::Number(self.resolution?),
+      _ => return None,
+    })
+  }
+
+  fn unit_context(&self) -> UnitContext {
+    UnitContext {
+      root_font_size: Some(self.font_size),
+      viewport_width: self.width,
+      viewport_height: self.height,
+      ..UnitContext::default()
+    }
+  }
+}
+
 enum_property! {
   /// A binary `and` or `or` operator.
   pub enum Operator {
@@ -639,6 +856,92 @@ impl<'i> Parse<'i> for MediaCondition<'i> {
   }
 }
 
+/// The result of simplifying a media condition.
+enum SimplifiedCondition<'i> {
+  /// The condition is always true or always false.
+  Constant(bool),
+  /// The condition depends on the environment.
+  Condition(MediaCondition<'i>),
+}
+
+impl<'i> MediaCondition<'i> {
+  fn simplify(self) -> SimplifiedCondition<'i> {
+    match self {
+      MediaCondition::Feature(feature) => match feature.range() {
+        Some((_, range)) if range.is_empty() => SimplifiedCondition::Constant(false),
+        _ => SimplifiedCondition::Condition(MediaCondition::Feature(feature)),
+      },
+      MediaCondition::Not(condition) => match condition.simplify() {
+        SimplifiedCondition::Constant(value) => SimplifiedCondition::Constant(!value),
+        SimplifiedCondition::Condition(MediaCondition::Not(condition)) => {
+          SimplifiedCondition::Condition(*condition)
+        }
+        SimplifiedCondition::Condition(condition) => {
+          SimplifiedCondition::Condition(MediaCondition::Not(Box::new(condition)))
+        }
+      },
+      MediaCondition::Operation { operator, conditions } => {
+        // The value that determines the result of the whole operation, i.e. `false` for `and`
+        // and `true` for `or`. Other constant values can be removed.
+        let short_circuit = operator == Operator::Or;
+        let mut simplified: Vec<MediaCondition<'i>> = Vec::new();
+        let mut push = |condition: MediaCondition<'i>| {
+          if !simplified.contains(&condition) {
+            simplified.push(condition);
+          }
+        };
+        for condition in conditions {
+          match condition.simplify() {
+            SimplifiedCondition::Constant(value) if value == short_circuit => {
+              return SimplifiedCondition::Constant(value)
+            }
+            SimplifiedCondition::Constant(_) => {}
+            SimplifiedCondition::Condition(MediaCondition::Operation {
+              operator: nested_operator,
+              conditions,
+            }) if nested_operator == operator => conditions.into_iter().for_each(&mut push),
+            SimplifiedCondition::Condition(condition) => push(condition),
+          }
+        }
+
+        if let Some(value) = merge_ranges(&mut simplified, operator) {
+          return SimplifiedCondition::Constant(value);
+        }
+
+        match simplified.len() {
+          0 => SimplifiedCondition::Constant(!short_circuit),
+          1 => SimplifiedCondition::Condition(simplified.remove(0)),
+          _ => SimplifiedCondition::Condition(MediaCondition::Operation {
+            operator,
+            conditions: simplified,
+          }),
+        }
+      }
+    }
+  }
+
+  /// Evaluates the condition against the given environment, using three-valued logic.
+  /// Returns `None` if the result is unknown.
+  fn evaluate(&self, env: &Environment) -> Option<bool> {
+    match self {
+      MediaCondition::Feature(feature) => feature.evaluate(env),
+      MediaCondition::Not(condition) => condition.evaluate(env).map(|result| !result),
+      MediaCondition::Operation { operator, conditions } => {
+        let mut result = Some(*operator == Operator::And);
+        for condition in conditions {
+          result = match (operator, result, condition.evaluate(env)) {
+            (Operator::And, Some(false), _) | (Operator::And, _, Some(false)) => return Some(false),
+            (Operator::Or, Some(true), _) | (Operator::Or, _, Some(true)) => return Some(true),
+            (_, Some(a), Some(_)) => Some(a),
+            _ => None,
+          };
+        }
+        result
+      }
+    }
+  }
+}
+
 /// Parse a single query condition.
 pub(crate) fn parse_query_condition<'t, 'i, P: QueryCondition<'i>>(
   input: &mut Parser<'i, 't>,
@@ -1023,6 +1326,287 @@ where
   }
 }
 
+impl<'i> MediaFeature<'i> {
+  fn evaluate(&self, env: &Environment) -> Option<bool> {
+    let name = match self {
+      QueryFeature::Plain { name, .. }
+      | QueryFeature::Boolean { name }
+      | QueryFeature::Range { name, .. }
+      | QueryFeature::Interval { name, .. } => name,
+    };
+    let value = match name {
+      MediaFeatureName::Standard(id) => env.feature_value(id.as_str())?,
+      MediaFeatureName::Unknown(ident) => env.feature_value(&ident.0.to_ascii_lowercase())?,
+      MediaFeatureName::Custom(_) => return None,
+    };
+
+    let context = env.unit_context();
+    match self {
+      QueryFeature::Plain { value: expected, .. } => {
+        value.compare(expected, MediaFeatureComparison::Equal, &context)
+      }
+      QueryFeature::Boolean { .. } => value.is_truthy(&context),
+      QueryFeature::Range {
+        operator,
+        value: expected,
+        ..
+      } => value.compare(expected, *operator, &context),
+      QueryFeature::Interval {
+        start,
+        start_operator,
+        end,
+        end_operator,
+        ..
+      } => {
+        let start = start.compare(&value, *start_operator, &context)?;
+        let end = value.compare(end, *end_operator, &context)?;
+        Some(start && end)
+      }
+    }
+  }
+}
+
+/// A lower or upper bound on the value of a media feature.
+#[derive(Clone)]
+struct RangeBound<'i> {
+  value: MediaFeatureValue<'i>,
+  number: CSSNumber,
+  inclusive: bool,
+}
+
+impl<'i> RangeBound<'i> {
+  /// Returns whether this bound is more restrictive than another bound in the same direction.
+  fn is_tighter(&self, other: &RangeBound<'i>, lower: bool) -> bool {
+    if self.number == other.number {
+      !self.inclusive && other.inclusive
+    } else {
+      (self.number > other.number) == lower
+    }
+  }
+}
+
+/// The range of values a media feature is constrained to by one or more conditions.
+#[derive(Clone, Default)]
+struct FeatureRange<'i> {
+  lower: Option<RangeBound<'i>>,
+  upper: Option<RangeBound<'i>>,
+}
+
+impl<'i> FeatureRange<'i> {
+  fn add_bound(&mut self, bound: RangeBound<'i>, lower: bool, tighter: bool) {
+    let current = if lower { &mut self.lower } else { &mut self.upper };
+    match current {
+      Some(current) if current.is_tighter(&bound, lower) == tighter => {}
+      _ => *current = Some(bound),
+    }
+  }
+
+  /// Merges another range into this one. For `and`, the result is the intersection of the
+  /// ranges, and for `or`, each bound is replaced with the least restrictive one.
+  fn merge(&mut self, other: FeatureRange<'i>, operator: Operator) {
+    let tighter = operator == Operator::And;
+    if let Some(lower) = other.lower {
+      self.add_bound(lower, true, tighter);
+    }
+    if let Some(upper) = other.upper {
+      self.add_bound(upper, false, tighter);
+    }
+  }
+
+  /// Returns whether no value can satisfy both bounds.
+  fn is_empty(&self) -> bool {
+    match (&self.lower, &self.upper) {
+      (Some(lower), Some(upper)) => {
+        lower.number > upper.number || (lower.number == upper.number && !(lower.inclusive && upper.inclusive))
+      }
+      _ => false,
+    }
+  }
+
+  /// Returns whether every value satisfies at least one of the bounds.
+  fn is_full(&self) -> bool {
+    match (&self.lower, &self.upper) {
+      (Some(lower), Some(upper)) => {
+        upper.number > lower.number || (lower.number == upper.number && (lower.inclusive || upper.inclusive))
+      }
+      _ => false,
+    }
+  }
+
+  fn into_conditions(self, id: MediaFeatureId) -> Vec<MediaCondition<'i>> {
+    let feature = |operator, value| {
+      MediaCondition::Feature(QueryFeature::Range {
+        name: MediaFeatureName::Standard(id),
+        operator,
+        value,
+      })
+    };
+
+    match (self.lower, self.upper) {
+      (Some(lower), Some(upper)) if lower.number == upper.number && lower.inclusive && upper.inclusive => {
+        vec![MediaCondition::Feature(QueryFeature::Plain {
+          name: MediaFeatureName::Standard(id),
+          value: lower.value,
+        })]
+      }
+      (lower, upper) => {
+        let lower = lower.map(|lower| {
+          let operator = if lower.inclusive {
+            MediaFeatureComparison::GreaterThanEqual
+          } else {
+            MediaFeatureComparison::GreaterThan
+          };
+          feature(operator, lower.value)
+        });
+        let upper = upper.map(|upper| {
+          let operator = if upper.inclusive {
+            MediaFeatureComparison::LessThanEqual
+          } else {
+            MediaFeatureComparison::LessThan
+          };
+          feature(operator, upper.value)
+        });
+        lower.into_iter().chain(upper).collect()
+      }
+    }
+  }
+}
+
+impl<'i> MediaFeature<'i> {
+  /// Returns the range of values this feature is constrained to, if its values can be
+  /// compared statically, e.g. lengths with absolute units.
+  fn range(&self) -> Option<(MediaFeatureId, FeatureRange<'i>)> {
+    let to_bound = |value: &MediaFeatureValue<'i>, inclusive| {
+      value.to_number(&UnitContext::default()).map(|number| RangeBound {
+        value: value.clone(),
+        number,
+        inclusive,
+      })
+    };
+
+    // Returns whether the comparison `feature <operator> value` is a lower bound, and whether it is inclusive.
+    let direction = |operator: MediaFeatureComparison| match operator {
+      MediaFeatureComparison::GreaterThan => (Some(true), false),
+      MediaFeatureComparison::GreaterThanEqual => (Some(true), true),
+      MediaFeatureComparison::LessThan => (Some(false), false),
+      MediaFeatureComparison::LessThanEqual => (Some(false), true),
+      MediaFeatureComparison::Equal => (None, true),
+    };
+
+    let mut range = FeatureRange::default();
+    let mut add = |operator: MediaFeatureComparison, value: &MediaFeatureValue<'i>| -> Option<()> {
+      let (lower, inclusive) = direction(operator);
+      let bound = to_bound(value, inclusive)?;
+      match lower {
+        Some(lower) => range.add_bound(bound, lower, true),
+        None => {
+          range.add_bound(bound.clone(), true, true);
+          range.add_bound(bound, false, true);
+        }
+      }
+      Some(())
+    };
+
+    let id = match self {
+      QueryFeature::Plain {
+        name: MediaFeatureName::Standard(id),
+        value,
+      } => {
+        add(MediaFeatureComparison::Equal, value)?;
+        id
+      }
+      QueryFeature::Range {
+        name: MediaFeatureName::Standard(id),
+        operator,
+        value,
+      } => {
+        add(*operator, value)?;
+        id
+      }
+      QueryFeature::Interval {
+        name: MediaFeatureName::Standard(id),
+        start,
+        start_operator,
+        end,
+        end_operator,
+      } => {
+        // The start value is on the left, so `start < feature` is equivalent to `feature > start`.
+        add(start_operator.opposite(), start)?;
+        add(*end_operator, end)?;
+        id
+      }
+      _ => return None,
+    };
+
+    Some((*id, range))
+  }
+}
+
+/// Merges features that constrain the range of the same feature within an `and` or `or` operation.
+/// Returns a constant if the operation is always true or false as a result.
+fn merge_ranges<'i>(conditions: &mut Vec<MediaCondition<'i>>, operator: Operator) -> Option<bool> {
+  let mut groups: Vec<(MediaFeatureId, FeatureRange<'i>, usize)> = Vec::new();
+  let mut membership = Vec::with_capacity(conditions.len());
+  for condition in conditions.iter() {
+    let range = match condition {
+      MediaCondition::Feature(feature) => feature.range(),
+      _ => None,
+    };
+
+    membership.push(match range {
+      // Only one-sided ranges can be combined with `or`.
+      Some((id, range)) if operator == Operator::And || range.lower.is_some() != range.upper.is_some() => {
+        match groups.iter().position(|(group_id, ..)| *group_id == id) {
+          Some(index) => {
+            let (_, group, count) = &mut groups[index];
+            group.merge(range, operator);
+            *count += 1;
+            Some(index)
+          }
+          None => {
+            groups.push((id, range, 1));
+            Some(groups.len() - 1)
+          }
+        }
+      }
+      _ => None,
+    });
+  }
+
+  for (_, range, _) in &groups {
+    match operator {
+      Operator::And if range.is_empty() => return Some(false),
+      Operator::Or if range.is_full() => return Some(true),
+      _ => {}
+    }
+  }
+
+  // Replace each group of merged features with the combined range, at the position of the first one.
+  let counts: Vec<usize> = groups.iter().map(|(.., count)| *count).collect();
+  let mut replacements: Vec<Option<Vec<MediaCondition<'i>>>> = groups
+    .into_iter()
+    .map(|(id, range, count)| {
+      if count > 1 {
+        Some(range.into_conditions(id))
+      } else {
+        None
+      }
+    })
+    .collect();
+  for (condition, group) in std::mem::take(conditions).into_iter().zip(membership) {
+    match group {
+      Some(index) if counts[index] > 1 => {
+        if let Some(replacement) = replacements[index].take() {
+          conditions.extend(replacement);
+        }
+      }
+      _ => conditions.push(condition),
+    }
+  }
+
+  None
+}
+
 impl<'i, FeatureId: FeatureToCss> ToCss for QueryFeature<'i, FeatureId> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -1467,6 +2051,53 @@ impl<'i> MediaFeatureValue<'i> {
   }
 }
 
+impl<'i> MediaFeatureValue<'i> {
+  fn to_number(&self, context: &UnitContext) -> Option<CSSNumber> {
+    match self {
+      MediaFeatureValue::Length(length) => length.to_px_with_context(context).ok(),
+      MediaFeatureValue::Number(number) => Some(*number),
+      MediaFeatureValue::Integer(integer) => Some(*integer as CSSNumber),
+      MediaFeatureValue::Boolean(boolean) => Some(if *boolean { 1.0 } else { 0.0 }),
+      MediaFeatureValue::Resolution(resolution) => Some(resolution.to_dppx()),
+      MediaFeatureValue::Ratio(Ratio(a, b)) => Some(a / b),
+      MediaFeatureValue::Ident(..) | MediaFeatureValue::Env(..) => None,
+    }
+  }
+
+  /// Compares this value to another using the given operator, e.g. `self < other`.
+  /// Returns `None` if the values cannot be compared.
+  fn compare(
+    &self,
+    other: &MediaFeatureValue,
+    operator: MediaFeatureComparison,
+    context: &UnitContext,
+  ) -> Option<bool> {
+    if let (MediaFeatureValue::Ident(a), MediaFeatureValue::Ident(b)) = (self, other) {
+      return match operator {
+        MediaFeatureComparison::Equal => Some(a.0.eq_ignore_ascii_case(&b.0)),
+        _ => None,
+      };
+    }
+
+    let ordering = self.to_number(context)?.partial_cmp(&other.to_number(context)?)?;
+    Some(match operator {
+      MediaFeatureComparison::Equal => ordering.is_eq(),
+      MediaFeatureComparison::GreaterThan => ordering.is_gt(),
+      MediaFeatureComparison::GreaterThanEqual => ordering.is_ge(),
+      MediaFeatureComparison::LessThan => ordering.is_lt(),
+      MediaFeatureComparison::LessThanEqual => ordering.is_le(),
+    })
+  }
+
+  /// Evaluates the value in a boolean context, where it is false for zero and `none`.
+  fn is_truthy(&self, context: &UnitContext) -> Option<bool> {
+    match self {
+      MediaFeatureValue::Ident(ident) => Some(!ident.0.eq_ignore_ascii_case("none")),
+      value => value.to_number(context).map(|number| number != 0.0),
+    }
+  }
+}
+
 impl<'i> MediaFeatureValue<'i> {
   /// Parses a single media query feature value, with an expected type.
   /// If the type is unknown, pass MediaFeatureType::Unknown instead.
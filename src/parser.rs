@@ -1,14 +1,16 @@
-use crate::declaration::{parse_declaration, DeclarationBlock, DeclarationList};
-use crate::error::{Error, ParserError, PrinterError};
+use crate::declaration::{parse_declaration, DeclarationBlock, DeclarationList, DeclarationSources};
+use crate::error::{Error, ParserError, PrinterError, SelectorError};
 use crate::media_query::*;
 use crate::printer::Printer;
 use crate::properties::custom::TokenList;
 use crate::rules::container::{ContainerCondition, ContainerName, ContainerRule};
 use crate::rules::font_palette_values::FontPaletteValuesRule;
+use crate::rules::icss::{icss_block_prelude, IcssBlock, IcssRule};
 use crate::rules::layer::{LayerBlockRule, LayerStatementRule};
 use crate::rules::property::PropertyRule;
 use crate::rules::scope::ScopeRule;
 use crate::rules::starting_style::StartingStyleRule;
+use crate::rules::value::{ValueDefinition, ValueRule};
 use crate::rules::viewport::ViewportRule;
 
 use crate::rules::{
@@ -28,16 +30,21 @@ use crate::rules::{
   unknown::UnknownAtRule,
   CssRule, CssRuleList, Location,
 };
-use crate::selector::{Component, SelectorList, SelectorParser};
+use crate::selector::{
+  css_module_block_mode, is_pure_css_module_selector, resolve_css_module_modes, strip_css_module_block_nesting,
+  Component, SelectorList, SelectorParser,
+};
 use crate::traits::Parse;
 use crate::values::ident::{CustomIdent, DashedIdent};
 use crate::values::string::CowArcStr;
+use crate::values::syntax::SyntaxString;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
 use bitflags::bitflags;
 use cssparser::*;
 use parcel_selectors::parser::{NestingRequirement, ParseErrorRecovery};
+use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
 bitflags! {
@@ -50,9 +57,45 @@ bitflags! {
     const CUSTOM_MEDIA = 1 << 1;
     /// Whether to enable the non-standard >>> and /deep/ selector combinators used by Vue and Angular.
     const DEEP_SELECTOR_COMBINATOR = 1 << 2;
+    /// Whether to warn about declarations that are overridden by a later declaration of the same
+    /// property in the same rule. Earlier declarations that act as fallbacks for syntax used by the
+    /// later value, such as a new function, length unit, or vendor prefix, are not reported.
+    const WARN_OVERRIDDEN_DECLARATIONS = 1 << 3;
   }
 }
 
+bitflags! {
+  /// Draft syntax that is parsed by default, but may be individually disabled.
+  ///
+  /// When disabled, nested rules are reported as invalid, and relative colors and
+  /// `color-mix()` are preserved as unparsed tokens rather than being resolved.
+  #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+  pub struct DraftSyntax: u8 {
+    /// The [CSS nesting](https://www.w3.org/TR/css-nesting-1/) syntax.
+    const NESTING = 1 << 0;
+    /// The [relative color](https://drafts.csswg.org/css-color-5/#relative-colors) syntax, e.g. `rgb(from red r g b)`.
+    const RELATIVE_COLORS = 1 << 1;
+    /// The [color-mix()](https://drafts.csswg.org/css-color-5/#color-mix) function.
+    const COLOR_MIX = 1 << 2;
+  }
+}
+
+/// How to handle unknown properties, and property values that are invalid and can only be
+/// preserved as unparsed tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "lowercase"))]
+pub enum ValidationMode {
+  /// Preserve unknown properties and invalid values as is.
+  #[default]
+  Ignore,
+  /// Add a warning for each unknown property or invalid value to [ParserOptions::warnings](ParserOptions::warnings).
+  Warn,
+  /// Return an error for each unknown property or invalid value. When `error_recovery` is enabled,
+  /// the declaration is omitted and a warning is added instead.
+  Error,
+}
+
 /// CSS parsing options.
 #[derive(Clone, Debug, Default)]
 pub struct ParserOptions<'o, 'i> {
@@ -69,6 +112,16 @@ pub struct ParserOptions<'o, 'i> {
   pub warnings: Option<Arc<RwLock<Vec<Error<ParserError<'i>>>>>>,
   /// Feature flags to enable.
   pub flags: ParserFlags,
+  /// Draft syntax to disable.
+  pub disabled_drafts: DraftSyntax,
+  /// How to handle unknown properties and invalid property values. Vendor prefixed properties and
+  /// values, and values that reference `var()` or `env()`, are not validated.
+  pub validation: ValidationMode,
+  /// The syntax of registered custom properties, keyed by name including the leading `--`, e.g. as
+  /// registered by `CSS.registerProperty()` in JavaScript. Values of these properties, and `var()`
+  /// fallbacks referencing them, are parsed according to their syntax. When parsing a style sheet, top-level
+  /// `@property` rules are added automatically, but do not override the properties registered here.
+  pub registered_properties: HashMap<String, SyntaxString>,
 }
 
 impl<'o, 'i> ParserOptions<'o, 'i> {
@@ -80,6 +133,14 @@ impl<'o, 'i> ParserOptions<'o, 'i> {
       }
     }
   }
+
+  /// Returns the syntax of a registered custom property, unless it is the universal syntax.
+  pub(crate) fn registered_syntax(&self, name: &str) -> Option<&SyntaxString> {
+    match self.registered_properties.get(name) {
+      Some(SyntaxString::Universal) | None => None,
+      syntax => syntax,
+    }
+  }
 }
 
 #[derive(Clone, Default)]
@@ -158,8 +219,10 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> TopLevelRuleParser<'a,
       at_rule_parser: self.at_rule_parser,
       declarations: DeclarationList::new(),
       important_declarations: DeclarationList::new(),
+      sources: DeclarationSources::default(),
       rules: &mut self.rules,
       is_in_style_rule: false,
+      is_in_global_block: false,
       allow_declarations: false,
     }
   }
@@ -214,6 +277,8 @@ pub enum AtRulePrelude<'i, T> {
   StartingStyle,
   /// A @scope rule prelude.
   Scope(Option<SelectorList<'i>>, Option<SelectorList<'i>>),
+  /// A CSS modules @value prelude.
+  Value(ValueDefinition<'i>),
   /// An unknown prelude.
   Unknown(CowArcStr<'i>, TokenList<'i>),
   /// A custom prelude.
@@ -249,6 +314,7 @@ impl<'i, T> AtRulePrelude<'i, T> {
       | Self::Import(..)
       | Self::CustomMedia(..)
       | Self::Viewport(..)
+      | Self::Value(..)
       | Self::Charset => false,
     }
   }
@@ -313,9 +379,9 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for TopLev
         let media = MediaList::parse(input)?;
         return Ok(AtRulePrelude::CustomMedia(name, media))
       },
-      "property" => {
-        let name = DashedIdent::parse(input)?;
-        return Ok(AtRulePrelude::Property(name))
+      "value" if self.options.css_modules.is_some() => {
+        let definition = ValueDefinition::parse(input, self.options)?;
+        return Ok(AtRulePrelude::Value(definition))
       },
       _ => {}
     }
@@ -374,6 +440,10 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for TopLev
         self.rules.0.push(CssRule::CustomMedia(CustomMediaRule { name, query, loc }));
         Ok(())
       }
+      AtRulePrelude::Value(definition) => {
+        self.rules.0.push(CssRule::Value(ValueRule { definition, loc }));
+        Ok(())
+      }
       AtRulePrelude::Layer(_) => {
         // @layer statements are allowed before @import rules, but cannot be interleaved.
         if self.state <= State::Layers {
@@ -425,6 +495,20 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> QualifiedRuleParser<'i>
     start: &ParserState,
     input: &mut Parser<'i, 't>,
   ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
+    if self.options.css_modules.is_some() {
+      if let Some(specifier) = icss_block_prelude(&prelude) {
+        let loc = start.source_location();
+        let loc = Location {
+          source_index: self.options.source_index,
+          line: loc.line,
+          column: loc.column,
+        };
+        let block = IcssBlock::parse(specifier, input, self.options)?;
+        self.rules.0.push(CssRule::Icss(IcssRule { block, loc }));
+        return Ok(());
+      }
+    }
+
     QualifiedRuleParser::parse_block(&mut self.nested(), prelude, start, input)
   }
 }
@@ -434,8 +518,10 @@ pub struct NestedRuleParser<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> {
   pub at_rule_parser: &'a mut T,
   declarations: DeclarationList<'i>,
   important_declarations: DeclarationList<'i>,
+  sources: DeclarationSources<'i>,
   rules: &'a mut CssRuleList<'i, T::AtRule>,
   is_in_style_rule: bool,
+  is_in_global_block: bool,
   allow_declarations: bool,
 }
 
@@ -451,8 +537,10 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> NestedRuleParser<'a, 'o
       at_rule_parser: self.at_rule_parser,
       declarations: DeclarationList::new(),
       important_declarations: DeclarationList::new(),
+      sources: DeclarationSources::default(),
       rules: &mut rules,
       is_in_style_rule: self.is_in_style_rule || is_style_rule,
+      is_in_global_block: self.is_in_global_block,
       allow_declarations: self.allow_declarations || self.is_in_style_rule || is_style_rule,
     };
 
@@ -466,6 +554,7 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> NestedRuleParser<'a, 'o
           if parse_declarations {
             iter.parser.declarations.clear();
             iter.parser.important_declarations.clear();
+            iter.parser.sources.clear();
             errors.push(e);
           } else {
             if iter.parser.options.error_recovery {
@@ -530,6 +619,59 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> NestedRuleParser<'a, 'o
     Ok(rules)
   }
 
+  /// Parses the contents of a CSS modules `:global { ... }` or `:local { ... }` block. The nested rules are hoisted
+  /// into the parent, and their selectors resolved in the mode of the block.
+  fn parse_css_module_block<'t>(
+    &mut self,
+    is_global: bool,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<(), ParseError<'i, ParserError<'i>>> {
+    let was_in_global_block = std::mem::replace(&mut self.is_in_global_block, is_global);
+    let result = self.parse_nested(input, true);
+    self.is_in_global_block = was_in_global_block;
+    let (declarations, rules) = result?;
+    if !declarations.is_empty() {
+      return Err(input.new_custom_error(ParserError::InvalidCssModulesBlock));
+    }
+
+    // Within a parent style rule, the block is equivalent to the parent (e.g. `& :global` is `&`),
+    // so the nested rules can be hoisted as is. At the top level, the implicit `&` must be removed.
+    for mut rule in rules.0 {
+      let is_valid = match &mut rule {
+        _ if self.is_in_style_rule => true,
+        CssRule::Style(style) => style.selectors.0.iter_mut().all(strip_css_module_block_nesting),
+        _ => false,
+      };
+      if !is_valid {
+        return Err(input.new_custom_error(ParserError::InvalidCssModulesBlock));
+      }
+
+      // Hoisted rules are no longer nested at the top level, so they must be pure on their own.
+      if let CssRule::Style(style) = &rule {
+        if !self.is_in_style_rule
+          && self.is_css_module_pure()
+          && !style.selectors.0.iter().all(is_pure_css_module_selector)
+        {
+          let location = SourceLocation {
+            line: style.loc.line,
+            column: style.loc.column,
+          };
+          return Err(
+            location.new_custom_error(ParserError::SelectorError(SelectorError::ImpureCssModulesSelector)),
+          );
+        }
+      }
+
+      self.rules.0.push(rule);
+    }
+
+    Ok(())
+  }
+
+  fn is_css_module_pure(&self) -> bool {
+    self.options.css_modules.as_ref().map_or(false, |css_modules| css_modules.pure)
+  }
+
   fn loc(&self, start: &ParserState) -> Location {
     let loc = start.source_location();
     Location {
@@ -669,6 +811,11 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
 
         AtRulePrelude::Scope(scope_start, scope_end)
       },
+      // @property rules are also valid within conditional group rules and layers.
+      "property" if !self.is_in_style_rule => {
+        let name = DashedIdent::parse(input)?;
+        AtRulePrelude::Property(name)
+      },
       "nest" if self.is_in_style_rule => {
         self.options.warn(input.new_custom_error(ParserError::DeprecatedNestRule));
         let selector_parser = SelectorParser {
@@ -685,6 +832,13 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
       return Err(input.new_error(BasicParseErrorKind::AtRuleInvalid(name.clone())));
     }
 
+    if self.is_in_style_rule
+      && self.options.disabled_drafts.contains(DraftSyntax::NESTING)
+      && !matches!(result, AtRulePrelude::Unknown(..) | AtRulePrelude::Custom(..))
+    {
+      return Err(input.new_custom_error(ParserError::InvalidNesting));
+    }
+
     Ok(result)
   }
 
@@ -818,6 +972,7 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> AtRuleParser<'i> for Ne
       AtRulePrelude::Import(..)
       | AtRulePrelude::Namespace(..)
       | AtRulePrelude::CustomMedia(..)
+      | AtRulePrelude::Value(..)
       | AtRulePrelude::Charset => {
         // These rules don't have blocks.
         Err(input.new_unexpected_token_error(Token::CurlyBracketBlock))
@@ -916,25 +1071,49 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> QualifiedRuleParser<'i>
     &mut self,
     input: &mut Parser<'i, 't>,
   ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+    let location = input.current_source_location();
     let selector_parser = SelectorParser {
       is_nesting_allowed: true,
       options: &self.options,
     };
-    if self.is_in_style_rule {
+    let mut selectors = if self.is_in_style_rule {
+      if self.options.disabled_drafts.contains(DraftSyntax::NESTING) {
+        return Err(input.new_custom_error(ParserError::InvalidNesting));
+      }
       SelectorList::parse_relative(
         &selector_parser,
         input,
         ParseErrorRecovery::DiscardList,
         NestingRequirement::Implicit,
-      )
+      )?
     } else {
       SelectorList::parse(
         &selector_parser,
         input,
         ParseErrorRecovery::DiscardList,
         NestingRequirement::None,
-      )
+      )?
+    };
+
+    // ICSS `:import` and `:export` blocks are handled by the top level parser.
+    if self.options.css_modules.is_some() && icss_block_prelude(&selectors).is_some() {
+      return Ok(selectors);
+    }
+
+    // Resolve CSS modules :global and :local pseudo classes without arguments, except for `:global { ... }` blocks.
+    if self.options.css_modules.is_some() && css_module_block_mode(&selectors).is_none() {
+      if let Err(err) = resolve_css_module_modes(&mut selectors, self.is_in_global_block) {
+        return Err(input.new_custom_error(ParserError::SelectorError(err)));
+      }
+
+      if self.is_css_module_pure() && !selectors.0.iter().all(is_pure_css_module_selector) {
+        return Err(
+          location.new_custom_error(ParserError::SelectorError(SelectorError::ImpureCssModulesSelector)),
+        );
+      }
     }
+
+    Ok(selectors)
   }
 
   fn parse_block<'t>(
@@ -943,6 +1122,16 @@ impl<'a, 'o, 'b, 'i, T: crate::traits::AtRuleParser<'i>> QualifiedRuleParser<'i>
     start: &ParserState,
     input: &mut Parser<'i, 't>,
   ) -> Result<(), ParseError<'i, Self::Error>> {
+    if self.options.css_modules.is_some() {
+      if let Some(is_global) = css_module_block_mode(&selectors) {
+        return self.parse_css_module_block(is_global, input);
+      }
+
+      if icss_block_prelude(&selectors).is_some() {
+        return Err(start.source_location().new_custom_error(ParserError::UnexpectedIcssBlock));
+      }
+    }
+
     let loc = self.loc(start);
     let (declarations, rules) = self.parse_nested(input, true)?;
     self.rules.0.push(CssRule::Style(StyleRule {
@@ -973,6 +1162,7 @@ impl<'a, 'o, 'i, T: crate::traits::AtRuleParser<'i>> cssparser::DeclarationParse
       input,
       &mut self.declarations,
       &mut self.important_declarations,
+      &mut self.sources,
       &self.options,
     )
   }
@@ -1061,8 +1251,10 @@ pub fn parse_rule_list<'a, 'o, 'i, 't, T: crate::traits::AtRuleParser<'i>>(
     at_rule_parser,
     declarations: DeclarationList::new(),
     important_declarations: DeclarationList::new(),
+    sources: DeclarationSources::default(),
     rules: &mut CssRuleList(Vec::new()),
     is_in_style_rule: false,
+    is_in_global_block: false,
     allow_declarations: false,
   };
 
@@ -1081,8 +1273,10 @@ pub fn parse_style_block<'a, 'o, 'i, 't, T: crate::traits::AtRuleParser<'i>>(
     at_rule_parser,
     declarations: DeclarationList::new(),
     important_declarations: DeclarationList::new(),
+    sources: DeclarationSources::default(),
     rules: &mut CssRuleList(Vec::new()),
     is_in_style_rule: is_nested,
+    is_in_global_block: false,
     allow_declarations: true,
   };
 
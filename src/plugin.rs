@@ -0,0 +1,285 @@
+//! Out-of-process plugins.
+//!
+//! A [Plugin](Plugin) is a child process that transforms rules and properties of a style sheet, which allows
+//! plugins to be written in any language. The plugin communicates with Lightning CSS over its stdin and stdout
+//! using newline delimited JSON messages, where rules and properties use the same serde representation as
+//! the rest of the AST (see [AST_VERSION](crate::stylesheet::AST_VERSION)).
+//!
+//! Only the node types that a plugin requests are sent to it, so the whole style sheet does not need to be
+//! serialized. A plugin is used like any other [Visitor](crate::visitor::Visitor), so it can be run at any
+//! point in a pipeline, before or after other transforms.
+//!
+//! # Protocol
+//!
+//! 1. Lightning CSS sends `{"type": "init", "version": 1}`, where `version` is the AST version.
+//! 2. The plugin responds with the node types it wants to receive:
+//!    `{"type": "init", "rules": ["style", "media"], "properties": ["color"]}`. Rules are identified by their
+//!    serialized `type`, and properties by their unprefixed name. `"*"` matches all rules or properties.
+//! 3. For each matching node, in document order, Lightning CSS sends `{"type": "rule", "node": ...}` or
+//!    `{"type": "property", "node": ...}`. The plugin responds with `{"type": "unchanged"}`,
+//!    `{"type": "replace", "node": ...}`, or `{"type": "error", "message": "..."}`. The children of a rule
+//!    that was replaced are not visited again.
+//! 4. When the style sheet has been visited, Lightning CSS sends `{"type": "done"}` and closes stdin, and
+//!    the plugin should exit.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::process::Command;
+//! use lightningcss::{
+//!   plugin::Plugin,
+//!   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+//!   visitor::Visit,
+//! };
+//!
+//! let mut stylesheet = StyleSheet::parse(".foo { color: red }", ParserOptions::default()).unwrap();
+//! let mut plugin = Plugin::spawn(Command::new("python3").arg("plugin.py")).unwrap();
+//! stylesheet.visit(&mut plugin).unwrap();
+//! plugin.finish().unwrap();
+//!
+//! let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+//! ```
+
+use crate::properties::Property;
+use crate::rules::CssRule;
+use crate::stylesheet::AST_VERSION;
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+/// The node types that a [Plugin](Plugin) receives, as declared by the plugin during initialization.
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+pub struct PluginFilter {
+  /// The serialized `type` of the rules to receive, e.g. `"style"` or `"media"`.
+  #[serde(default)]
+  pub rules: Vec<String>,
+  /// The unprefixed names of the properties to receive, e.g. `"color"` or `"--foo"`.
+  #[serde(default)]
+  pub properties: Vec<String>,
+}
+
+impl PluginFilter {
+  fn matches_rule(&self, rule: &CssRule) -> bool {
+    let rule_type = rule_type(rule);
+    self.rules.iter().any(|r| r == "*" || r == rule_type)
+  }
+
+  fn matches_property(&self, property: &Property) -> bool {
+    let id = property.property_id();
+    let name = id.name();
+    // Custom property names are case sensitive.
+    let is_custom = name.starts_with("--");
+    self
+      .properties
+      .iter()
+      .any(|p| p == "*" || (is_custom && p == name) || (!is_custom && p.eq_ignore_ascii_case(name)))
+  }
+}
+
+/// An error that occurred while running a [Plugin](Plugin).
+#[derive(Debug)]
+pub enum PluginError {
+  /// An error communicating with the plugin process.
+  Io(std::io::Error),
+  /// The plugin sent an invalid message.
+  Protocol(String),
+  /// The plugin reported an error.
+  Plugin(String),
+}
+
+impl fmt::Display for PluginError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    use PluginError::*;
+    match self {
+      Io(err) => write!(f, "Plugin I/O error: {}", err),
+      Protocol(err) => write!(f, "Invalid plugin message: {}", err),
+      Plugin(err) => write!(f, "Plugin error: {}", err),
+    }
+  }
+}
+
+impl std::error::Error for PluginError {}
+
+impl From<std::io::Error> for PluginError {
+  fn from(err: std::io::Error) -> Self {
+    PluginError::Io(err)
+  }
+}
+
+#[derive(serde::Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Request<'a, 'i> {
+  Init { version: u32 },
+  Rule { node: &'a CssRule<'i> },
+  Property { node: &'a Property<'i> },
+  Done,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum InitResponse {
+  Init(PluginFilter),
+  Error { message: String },
+}
+
+#[derive(serde::Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+enum Response<T> {
+  Unchanged,
+  Replace { node: T },
+  Error { message: String },
+}
+
+/// A plugin running in a child process. See the [module documentation](self) for details.
+pub struct Plugin {
+  child: Child,
+  stdin: Option<ChildStdin>,
+  stdout: BufReader<ChildStdout>,
+  filter: PluginFilter,
+}
+
+impl Plugin {
+  /// Spawns a plugin process and initializes it. The command's stdin and stdout are replaced with pipes.
+  pub fn spawn(command: &mut Command) -> Result<Plugin, PluginError> {
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn()?;
+    let stdin = child.stdin.take();
+    let stdout = BufReader::new(child.stdout.take().unwrap());
+    let mut plugin = Plugin {
+      child,
+      stdin,
+      stdout,
+      filter: PluginFilter::default(),
+    };
+
+    let line = plugin.send(&Request::Init { version: AST_VERSION })?;
+    match serde_json::from_str(&line).map_err(|err| PluginError::Protocol(err.to_string()))? {
+      InitResponse::Init(filter) => plugin.filter = filter,
+      InitResponse::Error { message } => return Err(PluginError::Plugin(message)),
+    }
+    Ok(plugin)
+  }
+
+  /// Returns the node types that the plugin receives.
+  pub fn filter(&self) -> &PluginFilter {
+    &self.filter
+  }
+
+  /// Notifies the plugin that the style sheet is done, and waits for it to exit.
+  pub fn finish(mut self) -> Result<(), PluginError> {
+    let stdin = self.stdin.as_mut().unwrap();
+    serde_json::to_writer(&mut *stdin, &Request::Done).map_err(|err| PluginError::Protocol(err.to_string()))?;
+    stdin.write_all(b"\n")?;
+    // Close stdin so the plugin sees EOF.
+    self.stdin = None;
+    let status = self.child.wait()?;
+    if !status.success() {
+      return Err(PluginError::Plugin(format!("plugin exited with {}", status)));
+    }
+    Ok(())
+  }
+
+  fn send(&mut self, request: &Request) -> Result<String, PluginError> {
+    let stdin = self.stdin.as_mut().unwrap();
+    serde_json::to_writer(&mut *stdin, request).map_err(|err| PluginError::Protocol(err.to_string()))?;
+    stdin.write_all(b"\n")?;
+    stdin.flush()?;
+
+    let mut line = String::new();
+    if self.stdout.read_line(&mut line)? == 0 {
+      return Err(PluginError::Protocol("plugin exited before responding".into()));
+    }
+    Ok(line)
+  }
+}
+
+impl Drop for Plugin {
+  fn drop(&mut self) {
+    // The plugin was not finished, e.g. due to an error.
+    if self.stdin.take().is_some() {
+      let _ = self.child.kill();
+      let _ = self.child.wait();
+    }
+  }
+}
+
+impl<'i> Visitor<'i> for Plugin {
+  type Error = PluginError;
+
+  fn visit_types(&self) -> VisitTypes {
+    let mut types = VisitTypes::empty();
+    if !self.filter.rules.is_empty() {
+      types |= VisitTypes::RULES;
+    }
+    if !self.filter.properties.is_empty() {
+      types |= VisitTypes::PROPERTIES;
+    }
+    types
+  }
+
+  fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+    if self.filter.matches_rule(rule) {
+      let line = self.send(&Request::Rule { node: rule })?;
+      let response: Response<CssRule<'i>> = serde_json::from_str(&line)
+        .map(serde_detach::detach)
+        .map_err(|err| PluginError::Protocol(err.to_string()))?;
+      match response {
+        Response::Unchanged => {}
+        Response::Replace { node } => {
+          *rule = node;
+          return Ok(());
+        }
+        Response::Error { message } => return Err(PluginError::Plugin(message)),
+      }
+    }
+
+    rule.visit_children(self)
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    if self.filter.matches_property(property) {
+      let line = self.send(&Request::Property { node: property })?;
+      let response: Response<Property<'i>> = serde_json::from_str(&line)
+        .map(serde_detach::detach)
+        .map_err(|err| PluginError::Protocol(err.to_string()))?;
+      match response {
+        Response::Unchanged => {}
+        Response::Replace { node } => *property = node,
+        Response::Error { message } => return Err(PluginError::Plugin(message)),
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Returns the serialized `type` of a rule.
+fn rule_type(rule: &CssRule) -> &'static str {
+  match rule {
+    CssRule::Media(..) => "media",
+    CssRule::Import(..) => "import",
+    CssRule::Style(..) => "style",
+    CssRule::Keyframes(..) => "keyframes",
+    CssRule::FontFace(..) => "font-face",
+    CssRule::FontPaletteValues(..) => "font-palette-values",
+    CssRule::Page(..) => "page",
+    CssRule::Supports(..) => "supports",
+    CssRule::CounterStyle(..) => "counter-style",
+    CssRule::Namespace(..) => "namespace",
+    CssRule::MozDocument(..) => "moz-document",
+    CssRule::Nesting(..) => "nesting",
+    CssRule::Viewport(..) => "viewport",
+    CssRule::CustomMedia(..) => "custom-media",
+    CssRule::LayerStatement(..) => "layer-statement",
+    CssRule::LayerBlock(..) => "layer-block",
+    CssRule::Property(..) => "property",
+    CssRule::Container(..) => "container",
+    CssRule::Scope(..) => "scope",
+    CssRule::StartingStyle(..) => "starting-style",
+    CssRule::Value(..) => "value",
+    CssRule::Icss(..) => "icss",
+    CssRule::Ignored => "ignored",
+    CssRule::Unknown(..) => "unknown",
+    CssRule::Custom(..) => "custom",
+  }
+}
@@ -0,0 +1,403 @@
+//! Conversion to and from the PostCSS AST.
+//!
+//! [StyleSheet::to_postcss](crate::stylesheet::StyleSheet::to_postcss) converts a style sheet to the
+//! JSON representation of a [PostCSS](https://postcss.org) AST, as returned by `root.toJSON()`. It can be
+//! passed to `postcss.fromJSON` to run existing PostCSS plugins on it, and the result can be converted
+//! back with [StyleSheet::from_postcss](crate::stylesheet::StyleSheet::from_postcss). This allows PostCSS
+//! plugins to run in the middle of a Lightning CSS pipeline, e.g. while migrating away from them.
+//!
+//! Selectors, at-rule preludes, and declaration values are stored as strings, as in PostCSS. Since Lightning
+//! CSS does not preserve the original formatting, the `raws` of each node describe the formatting
+//! Lightning CSS would print with, rather than the original source.
+
+use cssparser::*;
+use serde::{Deserialize, Serialize};
+
+/// The root node of a PostCSS AST.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "root")]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+pub struct Root {
+  /// The top-level nodes.
+  pub nodes: Vec<Node>,
+  /// Formatting information.
+  #[serde(default)]
+  pub raws: Raws,
+}
+
+/// A node in a PostCSS AST.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+pub enum Node {
+  /// A style rule.
+  #[serde(rename = "rule")]
+  Rule(Rule),
+  /// An at-rule.
+  #[serde(rename = "atrule")]
+  AtRule(AtRule),
+  /// A declaration.
+  #[serde(rename = "decl")]
+  Declaration(Declaration),
+  /// A comment.
+  #[serde(rename = "comment")]
+  Comment(Comment),
+}
+
+/// A style rule, e.g. `.foo { color: red }`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+#[cfg_attr(
+  feature = "archive",
+  archive(bound(serialize = "__S: rkyv::ser::ScratchSpace + rkyv::ser::Serializer")),
+  archive_attr(check_bytes(
+    bound = "__C: rkyv::validation::ArchiveContext, <__C as rkyv::Fallible>::Error: std::error::Error"
+  ))
+)]
+pub struct Rule {
+  /// The selector list.
+  pub selector: String,
+  /// The declarations and nested rules within the rule.
+  #[serde(default)]
+  #[cfg_attr(feature = "archive", omit_bounds, archive_attr(omit_bounds))]
+  pub nodes: Vec<Node>,
+  /// Formatting information.
+  #[serde(default)]
+  pub raws: Raws,
+}
+
+/// An at-rule, e.g. `@media print { ... }` or `@import "foo.css";`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+#[cfg_attr(
+  feature = "archive",
+  archive(bound(serialize = "__S: rkyv::ser::ScratchSpace + rkyv::ser::Serializer")),
+  archive_attr(check_bytes(
+    bound = "__C: rkyv::validation::ArchiveContext, <__C as rkyv::Fallible>::Error: std::error::Error"
+  ))
+)]
+pub struct AtRule {
+  /// The name of the at-rule, without the `@`.
+  pub name: String,
+  /// The prelude of the at-rule.
+  #[serde(default)]
+  pub params: String,
+  /// The contents of the at-rule's block, or `None` if it has no block.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[cfg_attr(feature = "archive", omit_bounds, archive_attr(omit_bounds))]
+  pub nodes: Option<Vec<Node>>,
+  /// Formatting information.
+  #[serde(default)]
+  pub raws: Raws,
+}
+
+/// A declaration, e.g. `color: red`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+pub struct Declaration {
+  /// The property name.
+  pub prop: String,
+  /// The value, without `!important`.
+  pub value: String,
+  /// Whether the declaration is `!important`.
+  #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+  pub important: bool,
+  /// Formatting information.
+  #[serde(default)]
+  pub raws: Raws,
+}
+
+/// A comment.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+pub struct Comment {
+  /// The text of the comment, without the `/*` and `*/` delimiters.
+  pub text: String,
+  /// Formatting information.
+  #[serde(default)]
+  pub raws: Raws,
+}
+
+/// Formatting information for a PostCSS node. Fields that are not set use PostCSS's defaults.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "archive", derive(rkyv::Archive, rkyv::Serialize), archive(check_bytes))]
+pub struct Raws {
+  /// The whitespace before the node.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub before: Option<String>,
+  /// The whitespace and symbols between the property and value of a declaration, or between the
+  /// selector or params of a rule and its block.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub between: Option<String>,
+  /// The whitespace between the last child of a rule and the end of its block.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub after: Option<String>,
+  /// The whitespace between the name and params of an at-rule.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub after_name: Option<String>,
+  /// Whether the last declaration in a rule has a semicolon.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub semicolon: Option<bool>,
+}
+
+impl Root {
+  /// Prints the PostCSS AST to a CSS string.
+  pub fn to_css_string(&self) -> String {
+    let mut dest = String::new();
+    write_nodes(&self.nodes, &mut dest);
+    dest
+  }
+}
+
+fn write_nodes(nodes: &[Node], dest: &mut String) {
+  for node in nodes {
+    match node {
+      Node::Rule(rule) => {
+        dest.push_str(&rule.selector);
+        dest.push('{');
+        write_nodes(&rule.nodes, dest);
+        dest.push('}');
+      }
+      Node::AtRule(rule) => {
+        dest.push('@');
+        dest.push_str(&rule.name);
+        if !rule.params.is_empty() {
+          dest.push(' ');
+          dest.push_str(&rule.params);
+        }
+        if let Some(nodes) = &rule.nodes {
+          dest.push('{');
+          write_nodes(nodes, dest);
+          dest.push('}');
+        } else {
+          dest.push(';');
+        }
+      }
+      Node::Declaration(decl) => {
+        dest.push_str(&decl.prop);
+        dest.push(':');
+        dest.push_str(&decl.value);
+        if decl.important {
+          dest.push_str(" !important");
+        }
+        dest.push(';');
+      }
+      Node::Comment(comment) => {
+        dest.push_str("/*");
+        dest.push_str(&comment.text);
+        dest.push_str("*/");
+      }
+    }
+  }
+}
+
+/// Converts CSS printed by Lightning CSS to a PostCSS AST.
+pub(crate) fn parse(code: &str) -> Root {
+  let mut input = ParserInput::new(code);
+  let mut parser = Parser::new(&mut input);
+  let mut nodes = Vec::new();
+
+  // Comments are skipped by the parser, but license comments are printed at the start of the file.
+  let mut state = parser.state();
+  while let Ok(token) = parser.next_including_whitespace_and_comments() {
+    match token {
+      Token::WhiteSpace(..) => {}
+      Token::Comment(text) => nodes.push(Node::Comment(Comment {
+        text: (*text).to_owned(),
+        raws: Raws {
+          before: Some(if nodes.is_empty() { "" } else { "\n" }.into()),
+          ..Raws::default()
+        },
+      })),
+      _ => break,
+    }
+    state = parser.state();
+  }
+  parser.reset(&state);
+
+  let comments = nodes.len();
+  let mut node_parser = NodeParser { code, depth: 0 };
+  for mut node in StyleSheetParser::new(&mut parser, &mut node_parser).flatten() {
+    if let Node::Rule(Rule { raws, .. }) | Node::AtRule(AtRule { raws, .. }) = &mut node {
+      raws.before = Some(
+        if nodes.is_empty() {
+          ""
+        } else if nodes.len() == comments {
+          "\n"
+        } else {
+          "\n\n"
+        }
+        .into(),
+      );
+    }
+    nodes.push(node);
+  }
+
+  Root {
+    nodes,
+    raws: Raws {
+      after: Some("\n".into()),
+      ..Raws::default()
+    },
+  }
+}
+
+struct NodeParser<'a> {
+  code: &'a str,
+  depth: usize,
+}
+
+impl<'a> NodeParser<'a> {
+  fn parse_nodes<'i, 't>(&mut self, input: &mut Parser<'i, 't>) -> Vec<Node> {
+    let indent = "  ".repeat(self.depth + 1);
+    self.depth += 1;
+    let mut nodes: Vec<Node> = RuleBodyParser::new(input, self).flatten().collect();
+    self.depth -= 1;
+
+    // Lightning CSS separates nested rules from the preceding node by an empty line.
+    for (i, node) in nodes.iter_mut().enumerate() {
+      let before = match (i, &*node) {
+        (0, _) | (_, Node::Declaration(..)) => format!("\n{}", indent),
+        _ => format!("\n\n{}", indent),
+      };
+      match node {
+        Node::Rule(Rule { raws, .. })
+        | Node::AtRule(AtRule { raws, .. })
+        | Node::Declaration(Declaration { raws, .. })
+        | Node::Comment(Comment { raws, .. }) => raws.before = Some(before),
+      }
+    }
+    nodes
+  }
+
+  fn block_raws(&self, nodes: &[Node]) -> Raws {
+    Raws {
+      between: Some(" ".into()),
+      after: Some(format!("\n{}", "  ".repeat(self.depth))),
+      semicolon: Some(matches!(nodes.last(), Some(Node::Declaration(..)))),
+      ..Raws::default()
+    }
+  }
+}
+
+fn consume_all<'i, 't>(input: &mut Parser<'i, 't>) -> String {
+  let start = input.position();
+  while input.next().is_ok() {}
+  input.slice_from(start).trim().to_owned()
+}
+
+impl<'a, 'i> QualifiedRuleParser<'i> for NodeParser<'a> {
+  type Prelude = String;
+  type QualifiedRule = Node;
+  type Error = ();
+
+  fn parse_prelude<'t>(
+    &mut self,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+    Ok(consume_all(input))
+  }
+
+  fn parse_block<'t>(
+    &mut self,
+    selector: Self::Prelude,
+    _start: &ParserState,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
+    let nodes = self.parse_nodes(input);
+    let raws = self.block_raws(&nodes);
+    Ok(Node::Rule(Rule { selector, nodes, raws }))
+  }
+}
+
+impl<'a, 'i> AtRuleParser<'i> for NodeParser<'a> {
+  type Prelude = (String, String);
+  type AtRule = Node;
+  type Error = ();
+
+  fn parse_prelude<'t>(
+    &mut self,
+    name: CowRcStr<'i>,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+    Ok((name.to_string(), consume_all(input)))
+  }
+
+  fn rule_without_block(
+    &mut self,
+    (name, params): Self::Prelude,
+    _start: &ParserState,
+  ) -> Result<Self::AtRule, ()> {
+    Ok(Node::AtRule(AtRule {
+      raws: Raws {
+        after_name: Some(if params.is_empty() { "" } else { " " }.into()),
+        ..Raws::default()
+      },
+      name,
+      params,
+      nodes: None,
+    }))
+  }
+
+  fn parse_block<'t>(
+    &mut self,
+    (name, params): Self::Prelude,
+    _start: &ParserState,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self::AtRule, ParseError<'i, Self::Error>> {
+    let nodes = self.parse_nodes(input);
+    let mut raws = self.block_raws(&nodes);
+    raws.after_name = Some(if params.is_empty() { "" } else { " " }.into());
+    Ok(Node::AtRule(AtRule {
+      name,
+      params,
+      nodes: Some(nodes),
+      raws,
+    }))
+  }
+}
+
+impl<'a, 'i> DeclarationParser<'i> for NodeParser<'a> {
+  type Declaration = Node;
+  type Error = ();
+
+  fn parse_value<'t>(
+    &mut self,
+    name: CowRcStr<'i>,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self::Declaration, ParseError<'i, Self::Error>> {
+    let value = consume_all(input);
+
+    // A nested rule such as `a:hover { ... }` also starts with an identifier followed by a colon.
+    // If the "value" is followed by a block, parse it as a rule instead.
+    if self.code[input.position().byte_index()..].trim_start().starts_with('{') {
+      return Err(input.new_custom_error(()));
+    }
+
+    let (value, important) = match value.strip_suffix("!important") {
+      Some(value) => (value.trim_end().to_owned(), true),
+      None => (value, false),
+    };
+
+    Ok(Node::Declaration(Declaration {
+      prop: name.to_string(),
+      value,
+      important,
+      raws: Raws {
+        between: Some(": ".into()),
+        ..Raws::default()
+      },
+    }))
+  }
+}
+
+impl<'a, 'i> RuleBodyItemParser<'i, Node, ()> for NodeParser<'a> {
+  fn parse_qualified(&self) -> bool {
+    true
+  }
+
+  fn parse_declarations(&self) -> bool {
+    true
+  }
+}
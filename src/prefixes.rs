@@ -96,6 +96,7 @@ pub enum Feature {
   Hyphens,
   ImageRendering,
   ImageSet,
+  InitialLetter,
   InlineFlex,
   InlineGrid,
   Isolate,
@@ -1413,6 +1414,18 @@ impl Feature {
           }
         }
       }
+      Feature::InitialLetter => {
+        if let Some(version) = browsers.ios_saf {
+          if version >= 589824 {
+            prefixes |= VendorPrefix::WebKit;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version >= 589824 {
+            prefixes |= VendorPrefix::WebKit;
+          }
+        }
+      }
       Feature::TextSizeAdjust => {
         if browsers.firefox.is_some() {
           prefixes |= VendorPrefix::Moz;
@@ -1,15 +1,20 @@
 //! CSS serialization and source map generation.
 
+use crate::compat::Feature;
 use crate::css_modules::CssModule;
-use crate::dependencies::{Dependency, DependencyOptions};
+use crate::dependencies::{Dependency, DependencyOptions, DependencyReference};
 use crate::error::{Error, ErrorLocation, PrinterError, PrinterErrorKind};
 use crate::rules::{Location, StyleContext};
 use crate::selector::SelectorList;
 use crate::targets::Targets;
+use crate::values::length::{LengthValue, UnitContext};
+use crate::values::url::Url;
 use crate::vendor_prefix::VendorPrefix;
 use cssparser::{serialize_identifier, serialize_name};
 #[cfg(feature = "sourcemap")]
 use parcel_sourcemap::{OriginalLocation, SourceMap};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
 
 /// Options that control how CSS is serialized to a string.
 #[derive(Default)]
@@ -35,6 +40,25 @@ pub struct PrinterOptions<'a> {
   /// A mapping of pseudo classes to replace with class names that can be applied
   /// from JavaScript. Useful for polyfills, for example.
   pub pseudo_classes: Option<PseudoClasses<'a>>,
+  /// Options for generating static fallbacks for math functions such as `clamp()`,
+  /// `min()`, and `max()` when they are unsupported by the targets.
+  pub math_fallback: Option<MathFallbackOptions>,
+  /// Options for converting `rem` (and optionally `em`) lengths to `px`.
+  pub rem_to_px: Option<RemToPxOptions>,
+  /// Options for rewriting relative `url()` references to be relative to the output file.
+  pub rebase_urls: Option<RebaseUrlsOptions<'a>>,
+  /// Options for inlining small files referenced by relative `url()` values as `data:` URIs.
+  pub inline_assets: Option<InlineAssetsOptions>,
+  /// A callback to emit files referenced by relative `url()` values, e.g. by copying them to an
+  /// output directory with a content hash in their filename. It receives the resolved path of each
+  /// file, and returns the url to reference the emitted file by, or `None` to leave the url as is.
+  /// Each file is emitted once, and the results are returned as a manifest in the
+  /// [ToCssResult](super::stylesheet::ToCssResult). Urls collected when analyzing dependencies are not emitted.
+  pub emit_asset: Option<&'a AssetEmitter<'a>>,
+  /// Whether to generate a coverage map, which maps byte ranges of the output to the source
+  /// locations of the rules they were printed from. If true, the map is returned as part of the
+  /// [ToCssResult](super::stylesheet::ToCssResult).
+  pub coverage_map: bool,
 }
 
 /// A mapping of user action pseudo classes to replace with class names.
@@ -54,6 +78,128 @@ pub struct PseudoClasses<'a> {
   pub focus_within: Option<&'a str>,
 }
 
+/// Options for generating static fallbacks for math functions.
+///
+/// When enabled, declarations containing `clamp()`, `min()`, or `max()` are preceded by
+/// a fallback declaration with a static value for browsers that do not support them.
+/// If all arguments can be resolved to `px` (e.g. viewport-relative units when a viewport
+/// size is given), the function is computed as a `px` value. Otherwise, the preferred value
+/// of `clamp()`, or the first argument of `min()` and `max()`, is used.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Default, Debug, Clone, Copy)]
+pub struct MathFallbackOptions {
+  /// The viewport width, in `px`, to assume when resolving viewport-relative units.
+  pub viewport_width: Option<f32>,
+  /// The viewport height, in `px`, to assume when resolving viewport-relative units.
+  pub viewport_height: Option<f32>,
+  /// The root font size, in `px`, to assume when resolving `rem` units. Defaults to `16px`.
+  pub root_font_size: Option<f32>,
+}
+
+impl MathFallbackOptions {
+  /// Returns the context used to resolve the arguments of math functions to `px`.
+  pub(crate) fn unit_context(&self) -> UnitContext {
+    UnitContext {
+      root_font_size: Some(self.root_font_size.unwrap_or(16.0)),
+      viewport_width: self.viewport_width,
+      viewport_height: self.viewport_height,
+      ..UnitContext::default()
+    }
+  }
+}
+
+/// Options for converting `rem` lengths to `px`, for targets such as email clients
+/// that do not handle `rem` units well.
+///
+/// Lengths are converted everywhere they are serialized, including inside `calc()`
+/// and in the fallbacks of custom properties and `var()` references.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Default, Debug, Clone, Copy)]
+pub struct RemToPxOptions {
+  /// The root font size, in `px`. Defaults to `16px`.
+  pub root_font_size: Option<f32>,
+  /// Whether to also convert `em` lengths, assuming they are relative to the root font size.
+  pub convert_em: bool,
+  /// Whether to convert lengths within media queries.
+  pub media_queries: bool,
+}
+
+impl RemToPxOptions {
+  /// Returns the context used to resolve `rem` and `em` lengths to `px`.
+  /// The font size is not set, so `em` lengths resolve against the root font size.
+  pub(crate) fn unit_context(&self) -> UnitContext {
+    UnitContext {
+      root_font_size: Some(self.root_font_size.unwrap_or(16.0)),
+      ..UnitContext::default()
+    }
+  }
+}
+
+/// Options for rewriting relative `url()` references so that they remain correct when the
+/// output is written to a different location than the source files, e.g. after bundling
+/// files from multiple directories.
+///
+/// Each relative url is resolved against the directory of the source file it appears in,
+/// and made relative to the directory of the output file. Paths are resolved lexically,
+/// so the source filenames and the output file should either both be absolute, or both be
+/// relative to the same directory. Urls collected when analyzing dependencies are not rebased.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Default, Debug, Clone, Copy)]
+pub struct RebaseUrlsOptions<'a> {
+  /// The path of the output file.
+  pub output_file: &'a str,
+  /// A public path to prefix rebased urls with, e.g. `/assets/`, when the directory
+  /// of the output file is served from a different location.
+  pub public_path: Option<&'a str>,
+}
+
+/// Options for inlining small files referenced by relative `url()` values as `data:` URIs,
+/// avoiding extra requests for tiny assets such as SVG icons.
+///
+/// Files are resolved relative to the source file that references them, and read from the
+/// file system. SVG files are percent-encoded, and other files are base64 encoded. Urls that
+/// cannot be read, exceed the size limit, have an unknown MIME type, include a query string or
+/// fragment, or are filtered out are left as is. Urls collected when analyzing dependencies are not inlined.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Default, Debug, Clone)]
+pub struct InlineAssetsOptions {
+  /// The maximum size of a file to inline, in bytes. Defaults to 4 KiB.
+  pub max_size: Option<u64>,
+  /// Patterns matching the resolved paths of files to inline, e.g. `*.svg`, where `*` matches
+  /// any sequence of characters. If empty, all files are included.
+  pub include: Vec<String>,
+  /// Patterns matching the resolved paths of files to exclude from inlining.
+  pub exclude: Vec<String>,
+}
+
+/// A callback that emits a file referenced by a relative `url()` value, and returns the url to
+/// reference the emitted file by.
+///
+/// See [PrinterOptions](PrinterOptions).
+pub type AssetEmitter<'a> = dyn Fn(&Path) -> Option<String> + 'a;
+
+/// A byte range of the output that was printed from a rule, as returned in a coverage map.
+///
+/// The ranges of rules that contain other rules, such as `@media`, include the ranges of the rules
+/// they contain. This allows coverage data reported by browsers for the output, e.g. from the Chrome
+/// DevTools protocol, to be mapped back to the rules in the source files.
+///
+/// See [PrinterOptions](PrinterOptions).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct CoverageRange {
+  /// The byte offset in the output where the rule starts.
+  pub start: usize,
+  /// The byte offset in the output where the rule ends, exclusive.
+  pub end: usize,
+  /// The location of the rule in the source file.
+  pub loc: Location,
+}
+
 /// A `Printer` represents a destination to output serialized CSS, as used in
 /// the [ToCss](super::traits::ToCss) trait. It can wrap any destination that
 /// implements [std::fmt::Write](std::fmt::Write), such as a [String](String).
@@ -72,10 +218,15 @@ pub struct Printer<'a, 'b, 'c, W> {
   #[cfg(feature = "sourcemap")]
   #[cfg_attr(docsrs, doc(cfg(feature = "sourcemap")))]
   pub(crate) source_maps: Vec<Option<SourceMap>>,
+  #[cfg(feature = "sourcemap")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "sourcemap")))]
+  pub(crate) source_map_indexes: Vec<Option<u32>>,
   pub(crate) loc: Location,
   indent: u8,
   line: u32,
   col: u32,
+  /// The number of bytes written so far.
+  pub(crate) offset: usize,
   pub(crate) minify: bool,
   pub(crate) targets: Targets,
   /// Vendor prefix override. When non-empty, it overrides
@@ -84,8 +235,23 @@ pub struct Printer<'a, 'b, 'c, W> {
   pub(crate) in_calc: bool,
   pub(crate) css_module: Option<CssModule<'a, 'b, 'c>>,
   pub(crate) dependencies: Option<Vec<Dependency>>,
+  /// References to dependencies collected by [StyleSheet::analyze_dependencies](crate::stylesheet::StyleSheet::analyze_dependencies).
+  pub(crate) dependency_references: Option<Vec<DependencyReference>>,
+  pub(crate) in_image_set: bool,
   pub(crate) remove_imports: bool,
   pub(crate) pseudo_classes: Option<PseudoClasses<'a>>,
+  pub(crate) math_fallback: Option<MathFallbackOptions>,
+  pub(crate) static_math: bool,
+  pub(crate) rem_to_px: Option<RemToPxOptions>,
+  pub(crate) in_media_query: bool,
+  pub(crate) rebase_urls: Option<RebaseUrlsOptions<'a>>,
+  pub(crate) inline_assets: Option<InlineAssetsOptions>,
+  pub(crate) emit_asset: Option<&'a AssetEmitter<'a>>,
+  /// A map of the paths of emitted assets to the urls they are referenced by.
+  pub(crate) assets: Option<HashMap<String, String>>,
+  /// Values defined by CSS modules `@value` rules, indexed by source index.
+  pub(crate) css_module_values: Vec<HashMap<String, String>>,
+  pub(crate) coverage_map: Option<Vec<CoverageRange>>,
   context: Option<&'a StyleContext<'a, 'b>>,
 }
 
@@ -99,6 +265,8 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       source_map: options.source_map,
       #[cfg(feature = "sourcemap")]
       source_maps: Vec::new(),
+      #[cfg(feature = "sourcemap")]
+      source_map_indexes: Vec::new(),
       loc: Location {
         source_index: 0,
         line: 0,
@@ -107,6 +275,7 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       indent: 0,
       line: 0,
       col: 0,
+      offset: 0,
       minify: options.minify,
       targets: options.targets,
       vendor_prefix: VendorPrefix::empty(),
@@ -117,12 +286,126 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       } else {
         None
       },
+      dependency_references: None,
+      in_image_set: false,
       remove_imports: matches!(&options.analyze_dependencies, Some(d) if d.remove_imports),
       pseudo_classes: options.pseudo_classes,
+      math_fallback: options.math_fallback,
+      static_math: false,
+      rem_to_px: options.rem_to_px,
+      in_media_query: false,
+      rebase_urls: options.rebase_urls,
+      inline_assets: options.inline_assets,
+      emit_asset: options.emit_asset,
+      assets: options.emit_asset.map(|_| HashMap::new()),
+      css_module_values: Vec::new(),
+      coverage_map: if options.coverage_map { Some(Vec::new()) } else { None },
       context: None,
     }
   }
 
+  /// Returns whether static fallbacks should be generated for math functions.
+  pub(crate) fn needs_math_fallback(&self) -> bool {
+    self.math_fallback.is_some()
+      && !self.static_math
+      && (!self.targets.is_compatible(Feature::MinFunction) || !self.targets.is_compatible(Feature::MaxFunction))
+  }
+
+  /// Returns the `px` value to print in place of the given length when
+  /// converting `rem` lengths to `px`.
+  pub(crate) fn rem_to_px(&self, length: &LengthValue) -> Option<f32> {
+    let options = self.rem_to_px?;
+    if self.in_media_query && !options.media_queries {
+      return None;
+    }
+
+    match length {
+      LengthValue::Rem(..) => {}
+      LengthValue::Em(..) if options.convert_em => {}
+      _ => return None,
+    }
+
+    length.to_px_with_context(&options.unit_context()).ok()
+  }
+
+  /// Resolves a relative `url()` against the directory of the source file being printed,
+  /// returning the resolved path along with its query string and fragment, if any.
+  pub(crate) fn resolve_url<'u>(&self, url: &'u Url) -> Option<(PathBuf, &'u str)> {
+    self.sources.and_then(|_| url.resolve(Path::new(self.filename())))
+  }
+
+  /// Emits the file referenced by a relative `url()` if needed, and returns the url to print in its place.
+  pub(crate) fn emit_asset(&mut self, url: &Url) -> Option<String> {
+    let emit_asset = self.emit_asset?;
+    let (path, suffix) = self.resolve_url(url)?;
+    let key = path.to_str()?.to_owned();
+    let assets = self.assets.as_mut()?;
+    let emitted = match assets.get(&key) {
+      Some(emitted) => emitted.clone(),
+      None => {
+        let emitted = emit_asset(&path)?;
+        assets.insert(key, emitted.clone());
+        emitted
+      }
+    };
+
+    Some(emitted + suffix)
+  }
+
+  /// Returns the url to print in place of a relative `url()` when rebasing urls.
+  pub(crate) fn rebase_url(&self, url: &Url) -> Option<String> {
+    let options = self.rebase_urls?;
+
+    // Preserve the query string and fragment, e.g. `font.eot?#iefix`.
+    let (path, suffix) = self.resolve_url(url)?;
+    let output_dir = Path::new(options.output_file).parent()?;
+    let relative = pathdiff::diff_paths(path, normalize_path(output_dir))?;
+
+    let mut rebased = String::new();
+    if let Some(public_path) = options.public_path {
+      rebased.push_str(public_path);
+      if !public_path.ends_with('/') {
+        rebased.push('/');
+      }
+    }
+
+    for (i, component) in relative.components().enumerate() {
+      if i > 0 {
+        rebased.push('/');
+      }
+      rebased.push_str(component.as_os_str().to_str()?);
+    }
+
+    rebased.push_str(suffix);
+    Some(rebased)
+  }
+
+  /// Returns whether a value has a static fallback for math functions, i.e. whether it contains any
+  /// math functions. The fallback is printed by setting `static_math` while printing the value, so
+  /// that it uses the state of this printer, e.g. for dependencies and CSS modules.
+  pub(crate) fn has_static_math_fallback<F>(&self, f: F) -> Result<bool, PrinterError>
+  where
+    F: Fn(&mut Printer<String>) -> Result<(), PrinterError>,
+  {
+    let options = || PrinterOptions {
+      minify: self.minify,
+      targets: self.targets,
+      math_fallback: self.math_fallback,
+      rem_to_px: self.rem_to_px,
+      ..PrinterOptions::default()
+    };
+
+    let mut value = String::new();
+    f(&mut Printer::new(&mut value, options()))?;
+
+    let mut fallback = String::new();
+    let mut printer = Printer::new(&mut fallback, options());
+    printer.static_math = true;
+    f(&mut printer)?;
+
+    Ok(fallback != value)
+  }
+
   /// Returns the current source filename that is being printed.
   pub fn filename(&self) -> &'c str {
     if let Some(sources) = self.sources {
@@ -142,6 +425,7 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
   /// If such a string is written, it will break source maps.
   pub fn write_str(&mut self, s: &str) -> Result<(), PrinterError> {
     self.col += s.len() as u32;
+    self.offset += s.len();
     self.dest.write_str(s)?;
     Ok(())
   }
@@ -154,6 +438,7 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
     } else {
       self.col += 1;
     }
+    self.offset += c.len_utf8();
     self.dest.write_char(c)?;
     Ok(())
   }
@@ -195,6 +480,17 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
     Ok(())
   }
 
+  /// Writes a blank line to separate a rule from the preceding output, if any.
+  pub(crate) fn newline_between_rules(&mut self) -> Result<(), PrinterError> {
+    if self.line > 0 || self.col > 0 {
+      if !self.minify {
+        self.write_char('\n')?;
+      }
+      self.newline()?;
+    }
+    Ok(())
+  }
+
   /// Increases the current indent level.
   pub fn indent(&mut self) {
     self.indent += 2;
@@ -230,7 +526,12 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
       let mut original = OriginalLocation {
         original_line: loc.line,
         original_column: loc.column - 1,
-        source: loc.source_index,
+        source: self
+          .source_map_indexes
+          .get(loc.source_index as usize)
+          .copied()
+          .flatten()
+          .unwrap_or(loc.source_index),
         name: None,
       };
 
@@ -264,33 +565,96 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
     }
   }
 
+  pub(crate) fn add_css_module_value(&mut self, name: &str, value: String, source_index: u32) {
+    let source_index = source_index as usize;
+    if self.css_module_values.len() <= source_index {
+      self.css_module_values.resize_with(source_index + 1, HashMap::new);
+    }
+    self.css_module_values[source_index].insert(name.into(), value);
+  }
+
+  /// Writes the value of the CSS modules `@value` with the given name, if one is defined
+  /// in the current source file. Returns whether a value was written.
+  pub(crate) fn write_css_module_value(&mut self, name: &str) -> Result<bool, PrinterError> {
+    let value = self
+      .css_module_values
+      .get(self.loc.source_index as usize)
+      .and_then(|values| values.get(name))
+      .cloned();
+    if let Some(value) = value {
+      self.write_str(&value)?;
+      Ok(true)
+    } else {
+      Ok(false)
+    }
+  }
+
   /// Writes a CSS identifier to the underlying destination, escaping it
   /// as appropriate. If the `css_modules` option was enabled, then a hash
   /// is added, and the mapping is added to the CSS module.
   pub fn write_ident(&mut self, ident: &str) -> Result<(), PrinterError> {
-    if let Some(css_module) = &mut self.css_module {
-      let dest = &mut self.dest;
-      let mut first = true;
-      css_module.config.pattern.write(
-        &css_module.hashes[self.loc.source_index as usize],
-        &css_module.sources[self.loc.source_index as usize],
-        ident,
-        |s| {
-          self.col += s.len() as u32;
-          if first {
-            first = false;
-            serialize_identifier(s, dest)
-          } else {
-            serialize_name(s, dest)
-          }
-        },
-      )?;
+    self.write_ident_with_css_module(ident, true)
+  }
 
-      css_module.add_local(&ident, &ident, self.loc.source_index);
+  /// Writes a CSS identifier to the underlying destination, only applying the CSS module
+  /// pattern if `handle_css_module` is true. Used for identifier types that can be
+  /// individually excluded from scoping via the CSS modules config.
+  pub(crate) fn write_ident_with_css_module(
+    &mut self,
+    ident: &str,
+    handle_css_module: bool,
+  ) -> Result<(), PrinterError> {
+    if handle_css_module && self.css_module.is_some() {
+      self.write_ident_with_css_module_suffix(ident, "")
     } else {
       serialize_identifier(ident, self)?;
+      Ok(())
+    }
+  }
+
+  /// Writes a CSS modules local name followed by an unscoped suffix, which is exported under the
+  /// combined name. This is used for the `-start` and `-end` suffixes of grid line names, which must
+  /// match the line names the browser generates for each grid area regardless of the pattern.
+  pub(crate) fn write_ident_with_css_module_suffix(
+    &mut self,
+    local: &str,
+    suffix: &str,
+  ) -> Result<(), PrinterError> {
+    let Some(css_module) = &mut self.css_module else {
+      serialize_identifier(local, self)?;
+      return serialize_name(suffix, self).map_err(|e| e.into());
+    };
+
+    // Escaping may change the length of the name, so count the bytes actually written.
+    let mut dest = ByteCounter {
+      dest: &mut *self.dest,
+      len: 0,
+    };
+    let mut first = true;
+    css_module.config.write_name(
+      &css_module.hashes[self.loc.source_index as usize],
+      &css_module.sources[self.loc.source_index as usize],
+      local,
+      |s| {
+        self.col += s.len() as u32;
+        if first {
+          first = false;
+          serialize_identifier(s, &mut dest)
+        } else {
+          serialize_name(s, &mut dest)
+        }
+      },
+    )?;
+
+    if suffix.is_empty() {
+      css_module.add_local(local, local, self.loc.source_index);
+    } else {
+      serialize_name(suffix, &mut dest)?;
+      self.col += suffix.len() as u32;
+      css_module.add_local_with_suffix(&format!("{}{}", local, suffix), local, suffix, self.loc.source_index);
     }
 
+    self.offset += dest.len;
     Ok(())
   }
 
@@ -299,16 +663,20 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
 
     match &mut self.css_module {
       Some(css_module) if css_module.config.dashed_idents => {
-        let dest = &mut self.dest;
-        css_module.config.pattern.write(
+        let mut dest = ByteCounter {
+          dest: &mut *self.dest,
+          len: 0,
+        };
+        css_module.config.write_name(
           &css_module.hashes[self.loc.source_index as usize],
           &css_module.sources[self.loc.source_index as usize],
           &ident[2..],
           |s| {
             self.col += s.len() as u32;
-            serialize_name(s, dest)
+            serialize_name(s, &mut dest)
           },
         )?;
+        self.offset += dest.len;
 
         if is_declaration {
           css_module.add_dashed(ident, self.loc.source_index);
@@ -371,6 +739,39 @@ impl<'a, 'b, 'c, W: std::fmt::Write + Sized> Printer<'a, 'b, 'c, W> {
 impl<'a, 'b, 'c, W: std::fmt::Write + Sized> std::fmt::Write for Printer<'a, 'b, 'c, W> {
   fn write_str(&mut self, s: &str) -> std::fmt::Result {
     self.col += s.len() as u32;
+    self.offset += s.len();
     self.dest.write_str(s)
   }
 }
+
+/// Counts the bytes written to a destination.
+struct ByteCounter<'w, W> {
+  dest: &'w mut W,
+  len: usize,
+}
+
+impl<'w, W: std::fmt::Write> std::fmt::Write for ByteCounter<'w, W> {
+  fn write_str(&mut self, s: &str) -> std::fmt::Result {
+    self.len += s.len();
+    self.dest.write_str(s)
+  }
+}
+
+/// Resolves `.` and `..` components of a path without accessing the file system.
+pub(crate) fn normalize_path(path: &Path) -> PathBuf {
+  let mut result = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => match result.components().next_back() {
+        Some(Component::Normal(_)) => {
+          result.pop();
+        }
+        Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+        _ => result.push(component),
+      },
+      _ => result.push(component),
+    }
+  }
+  result
+}
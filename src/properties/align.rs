@@ -9,6 +9,7 @@ use crate::error::{ParserError, PrinterError};
 use crate::macros::*;
 use crate::prefixes::{is_flex_2009, Feature};
 use crate::printer::Printer;
+use crate::targets::Features;
 use crate::traits::{FromStandard, Parse, PropertyHandler, Shorthand, ToCss};
 use crate::values::length::LengthPercentage;
 use crate::vendor_prefix::VendorPrefix;
@@ -1289,7 +1290,10 @@ impl AlignHandler {
 
     legacy_property!(AlignContent, align_content, , FlexLinePack);
     legacy_property!(JustifyContent, justify_content, BoxPack, FlexPack);
-    if context.targets.is_compatible(compat::Feature::PlaceContent) {
+    if !context
+      .targets
+      .should_compile(compat::Feature::PlaceContent, Features::PlaceShorthands)
+    {
       shorthand!(
         PlaceContent,
         AlignContent,
@@ -1302,14 +1306,20 @@ impl AlignHandler {
     standard_property!(JustifyContent, justify_content);
 
     legacy_property!(AlignSelf, align_self, , FlexItemAlign);
-    if context.targets.is_compatible(compat::Feature::PlaceSelf) {
+    if !context
+      .targets
+      .should_compile(compat::Feature::PlaceSelf, Features::PlaceShorthands)
+    {
       shorthand!(PlaceSelf, AlignSelf, align_self, justify_self);
     }
     standard_property!(AlignSelf, align_self);
     unprefixed_property!(JustifySelf, justify_self);
 
     legacy_property!(AlignItems, align_items, BoxAlign, FlexAlign);
-    if context.targets.is_compatible(compat::Feature::PlaceItems) {
+    if !context
+      .targets
+      .should_compile(compat::Feature::PlaceItems, Features::PlaceShorthands)
+    {
       shorthand!(PlaceItems, AlignItems, align_items, justify_items);
     }
     standard_property!(AlignItems, align_items);
@@ -6,7 +6,7 @@ use crate::prefixes::Feature;
 use crate::printer::Printer;
 use crate::properties::PropertyId;
 use crate::rules::supports::SupportsCondition;
-use crate::stylesheet::ParserOptions;
+use crate::stylesheet::{DraftSyntax, ParserOptions};
 use crate::targets::{should_compile, Targets};
 use crate::traits::{Parse, ParseWithOptions, ToCss};
 use crate::values::angle::Angle;
@@ -19,10 +19,11 @@ use crate::values::number::CSSInteger;
 use crate::values::percentage::Percentage;
 use crate::values::resolution::Resolution;
 use crate::values::string::CowArcStr;
+use crate::values::syntax::ParsedComponent;
 use crate::values::time::Time;
 use crate::values::url::Url;
 #[cfg(feature = "visitor")]
-use crate::visitor::Visit;
+use crate::visitor::{TokenSpan, Visit, VisitTypes, Visitor};
 use cssparser::color::parse_hash_color;
 use cssparser::*;
 
@@ -41,6 +42,12 @@ pub struct CustomProperty<'i> {
   pub name: CustomPropertyName<'i>,
   /// The property value, stored as a raw token list.
   pub value: TokenList<'i>,
+  /// The property value parsed according to the syntax of a registered custom property, if it matched.
+  /// This is used for interpolation, and is not printed or visited. The token list is normalized
+  /// from it when parsing, so changes to the value should be made to the token list.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub typed_value: Option<ParsedComponent<'i>>,
 }
 
 impl<'i> CustomProperty<'i> {
@@ -50,13 +57,38 @@ impl<'i> CustomProperty<'i> {
     input: &mut Parser<'i, 't>,
     options: &ParserOptions<'_, 'i>,
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
-    let value = input.parse_until_before(Delimiter::Bang | Delimiter::Semicolon, |input| {
-      TokenList::parse(input, options, 0)
+    let (value, typed_value) = input.parse_until_before(Delimiter::Bang | Delimiter::Semicolon, |input| {
+      let typed_value = match &name {
+        CustomPropertyName::Custom(name) => parse_registered_value(name, input, options),
+        CustomPropertyName::Unknown(..) => None,
+      };
+      let value = TokenList::parse(input, options, 0)?;
+      // Normalize the value according to the registered syntax.
+      let value = typed_value.as_ref().and_then(ParsedComponent::to_token_list).unwrap_or(value);
+      Ok((value, typed_value))
     })?;
-    Ok(CustomProperty { name, value })
+    Ok(CustomProperty {
+      name,
+      value,
+      typed_value,
+    })
   }
 }
 
+/// Parses a value according to the syntax of a registered custom property, without consuming any input.
+/// Returns `None` if the property is not registered, or the value does not match its syntax.
+fn parse_registered_value<'i, 't>(
+  name: &DashedIdent<'i>,
+  input: &mut Parser<'i, 't>,
+  options: &ParserOptions<'_, 'i>,
+) -> Option<ParsedComponent<'i>> {
+  let syntax = options.registered_syntax(name.as_ref())?;
+  let state = input.state();
+  let value = syntax.parse_value(input).ok();
+  input.reset(&state);
+  value
+}
+
 /// A CSS custom property name.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -198,15 +230,56 @@ impl<'i> UnparsedProperty<'i> {
 
 /// A raw list of CSS tokens, with embedded parsed values.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_token_list, TOKENS))]
 #[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
 #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
 pub struct TokenList<'i>(#[cfg_attr(feature = "serde", serde(borrow))] pub Vec<TokenOrValue<'i>>);
 
+// Implemented manually rather than derived so that raw tokens can be visited along with their spans.
+#[cfg(feature = "visitor")]
+impl<'i, V: ?Sized + Visitor<'i, T>, T: Visit<'i, T, V>> Visit<'i, T, V> for TokenList<'i> {
+  const CHILD_TYPES: VisitTypes = VisitTypes::from_bits_retain(
+    <TokenOrValue<'i> as Visit<'i, T, V>>::CHILD_TYPES.bits()
+      | VisitTypes::TOKENS.bits()
+      | VisitTypes::RAW_TOKENS.bits(),
+  );
+
+  fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if visitor.visit_types().contains(VisitTypes::TOKENS) {
+      visitor.visit_token_list(self)
+    } else {
+      self.visit_children(visitor)
+    }
+  }
+
+  fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if !<Self as Visit<'i, T, V>>::CHILD_TYPES.intersects(visitor.visit_types()) {
+      return Ok(());
+    }
+
+    if visitor.visit_types().contains(VisitTypes::RAW_TOKENS) {
+      let spans = self.token_spans();
+      for (index, (token, span)) in self.0.iter_mut().zip(spans).enumerate() {
+        if let TokenOrValue::Token(token) = token {
+          visitor.visit_raw_token(
+            token,
+            TokenSpan {
+              index,
+              start: span.start,
+              end: span.end,
+            },
+          )?;
+        }
+      }
+    }
+
+    self.0.visit(visitor)
+  }
+}
+
 /// A raw CSS token, or a parsed value.
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_token, TOKENS), visit_types(TOKENS | COLORS | URLS | VARIABLES | ENVIRONMENT_VARIABLES | FUNCTIONS | LENGTHS | ANGLES | TIMES | RESOLUTIONS | DASHED_IDENTS))]
+#[cfg_attr(feature = "visitor", derive(Visit), visit(visit_token, TOKENS), visit_types(TOKENS | RAW_TOKENS | COLORS | URLS | VARIABLES | ENVIRONMENT_VARIABLES | FUNCTIONS | LENGTHS | ANGLES | TIMES | RESOLUTIONS | DASHED_IDENTS))]
 #[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
 #[cfg_attr(
   feature = "serde",
@@ -381,8 +454,13 @@ impl<'i> TokenList<'i> {
         }
         Ok(&cssparser::Token::Function(ref f)) => {
           // Attempt to parse embedded color values into hex tokens.
-          let f = f.into();
-          if let Some(color) = try_parse_color_token(&f, &state, input) {
+          let f: CowArcStr<'i> = f.into();
+          let color = if is_disabled_color_function(&f, input, options.disabled_drafts) {
+            None
+          } else {
+            try_parse_color_token(&f, &state, input)
+          };
+          if let Some(color) = color {
             tokens.push(TokenOrValue::Color(color));
             last_is_delim = false;
             last_is_whitespace = false;
@@ -523,7 +601,93 @@ fn try_parse_color_token<'i, 't>(
   None
 }
 
+/// Returns whether the function that was just parsed uses a disabled color syntax,
+/// e.g. `color-mix()` or the relative color syntax.
+fn is_disabled_color_function(f: &str, input: &mut Parser<'_, '_>, disabled: DraftSyntax) -> bool {
+  if disabled.is_empty() {
+    return false;
+  }
+
+  match_ignore_ascii_case! { f,
+    "color-mix" => disabled.contains(DraftSyntax::COLOR_MIX),
+    "rgb" | "rgba" | "hsl" | "hsla" | "hwb" | "lab" | "lch" | "oklab" | "oklch" | "color" if disabled.contains(DraftSyntax::RELATIVE_COLORS) => {
+      let state = input.state();
+      let is_relative = input
+        .parse_nested_block(|input| {
+          input.expect_ident_matching("from")?;
+          while input.next().is_ok() {}
+          Ok::<_, ParseError<'_, ParserError<'_>>>(())
+        })
+        .is_ok();
+      input.reset(&state);
+      is_relative
+    },
+    _ => false
+  }
+}
+
+/// Returns whether the input contains any color syntax that was disabled via
+/// [ParserOptions::disabled_drafts](ParserOptions). The input is not reset.
+pub(crate) fn has_disabled_color_syntax(input: &mut Parser<'_, '_>, disabled: DraftSyntax) -> bool {
+  if !disabled.intersects(DraftSyntax::RELATIVE_COLORS | DraftSyntax::COLOR_MIX) {
+    return false;
+  }
+
+  while let Ok(token) = input.next() {
+    let found = match token {
+      cssparser::Token::Function(f) => {
+        let f = f.clone();
+        is_disabled_color_function(&f, input, disabled)
+          || input
+            .parse_nested_block(|input| {
+              Ok::<_, ParseError<'_, ParserError<'_>>>(has_disabled_color_syntax(input, disabled))
+            })
+            .unwrap_or(false)
+      }
+      cssparser::Token::ParenthesisBlock
+      | cssparser::Token::SquareBracketBlock
+      | cssparser::Token::CurlyBracketBlock => input
+        .parse_nested_block(|input| {
+          Ok::<_, ParseError<'_, ParserError<'_>>>(has_disabled_color_syntax(input, disabled))
+        })
+        .unwrap_or(false),
+      _ => false,
+    };
+
+    if found {
+      return true;
+    }
+  }
+
+  false
+}
+
 impl<'i> TokenList<'i> {
+  /// Returns the byte range of each token or value within the minified serialization of the list.
+  #[cfg(feature = "visitor")]
+  fn token_spans(&self) -> Vec<std::ops::Range<usize>> {
+    use crate::stylesheet::PrinterOptions;
+
+    let mut css = String::new();
+    let mut dest = Printer::new(
+      &mut css,
+      PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      },
+    );
+    self
+      .0
+      .iter()
+      .map(|token| {
+        let start = dest.offset;
+        // Values that cannot be printed on their own, e.g. unresolved colors, get an empty span.
+        let _ = TokenList(vec![token.clone()]).to_css(&mut dest, false);
+        start..dest.offset
+      })
+      .collect()
+  }
+
   pub(crate) fn to_css<W>(&self, dest: &mut Printer<W>, is_custom_property: bool) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
@@ -569,7 +733,10 @@ impl<'i> TokenList<'i> {
         }
         TokenOrValue::Length(v) => {
           // Do not serialize unitless zero lengths in custom properties as it may break calc().
-          let (value, unit) = v.to_unit_value();
+          let (value, unit) = match dest.rem_to_px(v) {
+            Some(px) => (px, "px"),
+            None => v.to_unit_value(),
+          };
           serialize_dimension(value, unit, dest)?;
           false
         }
@@ -617,6 +784,12 @@ impl<'i> TokenList<'i> {
             value.to_css(dest)?;
             false
           }
+          Token::Ident(name) => {
+            if !dest.write_css_module_value(name)? {
+              token.to_css(dest)?;
+            }
+            false
+          }
           _ => {
             token.to_css(dest)?;
             matches!(token, Token::WhiteSpace(..))
@@ -722,25 +895,27 @@ pub enum Token<'a> {
   Delim(char),
 
   /// A [`<number-token>`](https://drafts.csswg.org/css-syntax/#number-token-diagram)
+  #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_number"))]
   Number {
     /// Whether the number had a `+` or `-` sign.
     ///
     /// This is used is some cases like the <An+B> micro syntax. (See the `parse_nth` function.)
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     has_sign: bool,
 
     /// The value as a float
     value: f32,
 
     /// If the origin source did not include a fractional part, the value as an integer.
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     int_value: Option<i32>,
   },
 
   /// A [`<percentage-token>`](https://drafts.csswg.org/css-syntax/#percentage-token-diagram)
+  #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_percentage"))]
   Percentage {
     /// Whether the number had a `+` or `-` sign.
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     has_sign: bool,
 
     /// The value as a float, divided by 100 so that the nominal range is 0.0 to 1.0.
@@ -749,23 +924,24 @@ pub enum Token<'a> {
 
     /// If the origin source did not include a fractional part, the value as an integer.
     /// It is **not** divided by 100.
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     int_value: Option<i32>,
   },
 
   /// A [`<dimension-token>`](https://drafts.csswg.org/css-syntax/#dimension-token-diagram)
+  #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_dimension"))]
   Dimension {
     /// Whether the number had a `+` or `-` sign.
     ///
     /// This is used is some cases like the <An+B> micro syntax. (See the `parse_nth` function.)
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     has_sign: bool,
 
     /// The value as a float
     value: f32,
 
     /// If the origin source did not include a fractional part, the value as an integer.
-    #[cfg_attr(feature = "serde", serde(skip))]
+    #[cfg_attr(feature = "serde", serde(skip_serializing))]
     int_value: Option<i32>,
 
     /// The unit, e.g. "px" in `12px`
@@ -928,6 +1104,66 @@ impl<'a> From<&cssparser::Token<'a>> for Token<'a> {
   }
 }
 
+/// Returns the integer value of a number token, which determines whether it is serialized with a
+/// fractional part. This is not included in serialized tokens, so it is restored from the value.
+#[cfg(feature = "serde")]
+fn int_value(value: f32) -> Option<i32> {
+  if value.fract() == 0.0 && value.abs() <= i32::MAX as f32 {
+    Some(value as i32)
+  } else {
+    None
+  }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_number<'de, D>(deserializer: D) -> Result<(bool, f32, Option<i32>), D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  #[derive(serde::Deserialize)]
+  struct Number {
+    value: f32,
+  }
+
+  let Number { value } = serde::Deserialize::deserialize(deserializer)?;
+  Ok((false, value, int_value(value)))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_percentage<'de, D>(deserializer: D) -> Result<(bool, f32, Option<i32>), D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  #[derive(serde::Deserialize)]
+  struct Percentage {
+    value: f32,
+  }
+
+  // The integer value is not divided by 100.
+  let Percentage { value } = serde::Deserialize::deserialize(deserializer)?;
+  let percent = value * 100.0;
+  let int_value = int_value(percent.round()).filter(|int_value| (*int_value as f32 - percent).abs() < 1e-4);
+  Ok((false, value, int_value))
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_dimension<'i, 'de: 'i, D>(
+  deserializer: D,
+) -> Result<(bool, f32, Option<i32>, CowArcStr<'i>), D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  #[derive(serde::Deserialize)]
+  struct Dimension<'i> {
+    value: f32,
+    #[serde(borrow)]
+    unit: CowArcStr<'i>,
+  }
+
+  let Dimension { value, unit } = serde::Deserialize::deserialize(deserializer)?;
+  Ok((false, value, int_value(value), unit))
+}
+
 impl<'a> ToCss for Token<'a> {
   #[inline]
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
@@ -1168,12 +1404,205 @@ impl<'i> TokenList<'i> {
     res
   }
 
+  /// Returns a copy of the token list with `env(safe-area-inset-*)` replaced by the legacy `constant()`
+  /// syntax supported by iOS Safari 11.0 and 11.1, or `None` if no safe area insets are referenced.
+  pub(crate) fn get_constant_fallback(&self) -> Option<Self> {
+    let mut has_constant = false;
+    let tokens = self
+      .0
+      .iter()
+      .map(|token| match token {
+        TokenOrValue::Env(EnvironmentVariable {
+          name: EnvironmentVariableName::UA(name),
+          indices,
+          fallback,
+        }) if indices.is_empty()
+          && matches!(
+            name,
+            UAEnvironmentVariable::SafeAreaInsetTop
+              | UAEnvironmentVariable::SafeAreaInsetRight
+              | UAEnvironmentVariable::SafeAreaInsetBottom
+              | UAEnvironmentVariable::SafeAreaInsetLeft
+          ) =>
+        {
+          has_constant = true;
+          let mut arguments = vec![TokenOrValue::Token(Token::Ident(name.as_str().to_owned().into()))];
+          if let Some(fallback) = fallback {
+            arguments.push(TokenOrValue::Token(Token::Comma));
+            arguments.extend(fallback.get_constant_fallback().unwrap_or_else(|| fallback.clone()).0);
+          }
+          TokenOrValue::Function(Function {
+            name: Ident("constant".into()),
+            arguments: TokenList(arguments),
+          })
+        }
+        TokenOrValue::Function(f) => match f.arguments.get_constant_fallback() {
+          Some(arguments) => {
+            has_constant = true;
+            TokenOrValue::Function(Function {
+              name: f.name.clone(),
+              arguments,
+            })
+          }
+          None => token.clone(),
+        },
+        TokenOrValue::Var(v) => match v.fallback.as_ref().and_then(|fallback| fallback.get_constant_fallback()) {
+          Some(fallback) => {
+            has_constant = true;
+            TokenOrValue::Var(Variable {
+              name: v.name.clone(),
+              fallback: Some(fallback),
+              typed_fallback: None,
+            })
+          }
+          None => token.clone(),
+        },
+        _ => token.clone(),
+      })
+      .collect();
+
+    if has_constant {
+      Some(TokenList(tokens))
+    } else {
+      None
+    }
+  }
+
+  /// Returns whether the token list references a `var()` or `env()`, in which case it
+  /// can only be validated once the references are substituted at computed value time.
+  pub(crate) fn has_substitutions(&self) -> bool {
+    self.0.iter().any(|token| match token {
+      TokenOrValue::Var(..) | TokenOrValue::Env(..) | TokenOrValue::UnresolvedColor(..) => true,
+      TokenOrValue::Function(f) => f.arguments.has_substitutions(),
+      _ => false,
+    })
+  }
+
+  /// Returns the name of the first `var()` reference to a registered custom property whose fallback
+  /// does not match the registered syntax. Fallbacks that contain substitutions are not checked.
+  pub(crate) fn invalid_registered_fallback(&self, options: &ParserOptions<'_, 'i>) -> Option<&DashedIdent<'i>> {
+    self.0.iter().find_map(|token| match token {
+      TokenOrValue::Var(Variable {
+        name,
+        fallback: Some(fallback),
+        typed_fallback: None,
+      }) if name.from.is_none()
+        && options.registered_syntax(name.ident.as_ref()).is_some()
+        && !fallback.has_substitutions() =>
+      {
+        Some(&name.ident)
+      }
+      TokenOrValue::Var(Variable {
+        fallback: Some(fallback),
+        ..
+      }) => fallback.invalid_registered_fallback(options),
+      TokenOrValue::Function(f) => f.arguments.invalid_registered_fallback(options),
+      _ => None,
+    })
+  }
+
+  /// Returns whether the token list consists of a single CSS-wide keyword, e.g. `inherit`.
+  pub(crate) fn is_css_wide_keyword(&self) -> bool {
+    let mut tokens = self.0.iter().filter(|token| !token.is_whitespace());
+    match (tokens.next(), tokens.next()) {
+      (Some(TokenOrValue::Token(Token::Ident(ident))), None) => {
+        match_ignore_ascii_case! { ident,
+          "inherit" | "initial" | "unset" | "revert" | "revert-layer" => true,
+          _ => false
+        }
+      }
+      _ => false,
+    }
+  }
+
   /// Substitutes variables with the provided values.
   #[cfg(feature = "substitute_variables")]
   #[cfg_attr(docsrs, doc(cfg(feature = "substitute_variables")))]
   pub fn substitute_variables(&mut self, vars: &std::collections::HashMap<&str, TokenList<'i>>) {
     self.visit(&mut VarInliner { vars }).unwrap()
   }
+
+  /// Replaces `var()` references to the given custom properties with their values, including within
+  /// fallbacks and nested functions. References to other custom properties are left as is, along with
+  /// their fallbacks, and circular references are ignored. Returns whether any references were replaced.
+  #[cfg(feature = "substitute_variables")]
+  pub(crate) fn inline_variables(&mut self, vars: &std::collections::HashMap<&str, TokenList<'i>>) -> bool {
+    self.inline_variables_with_stack(vars, &mut Vec::new())
+  }
+
+  /// Replaces the `var()` or `env()` reference at the given index with its value, and returns the
+  /// number of tokens that were inserted.
+  #[cfg(feature = "substitute_variables")]
+  pub(crate) fn splice_variable_value(&mut self, i: usize, mut value: TokenList<'i>) -> usize {
+    // Whitespace following a var() is removed during parsing, and added back when printing.
+    // Insert it explicitly unless the value ends with a token that does the same.
+    let needs_whitespace = matches!(self.0.get(i + 1), Some(next) if !matches!(
+      next,
+      TokenOrValue::Token(Token::Comma | Token::CloseParenthesis | Token::WhiteSpace(..))
+    )) && !matches!(
+      value.0.last(),
+      Some(
+        TokenOrValue::Var(..)
+          | TokenOrValue::Env(..)
+          | TokenOrValue::Function(..)
+          | TokenOrValue::Token(Token::CloseParenthesis)
+      )
+    );
+    if needs_whitespace {
+      value.0.push(Token::WhiteSpace(" ".into()).into());
+    }
+
+    let len = value.0.len();
+    self.0.splice(i..i + 1, value.0);
+    len
+  }
+
+  #[cfg(feature = "substitute_variables")]
+  fn inline_variables_with_stack(
+    &mut self,
+    vars: &std::collections::HashMap<&str, TokenList<'i>>,
+    stack: &mut Vec<CowArcStr<'i>>,
+  ) -> bool {
+    let mut inlined = false;
+    let mut i = 0;
+    while i < self.0.len() {
+      match &mut self.0[i] {
+        TokenOrValue::Var(var) => {
+          let name = &var.name.ident.0;
+          if var.name.from.is_none() && !stack.contains(name) {
+            if let Some(value) = vars.get(name.as_ref()) {
+              // Inline any references within the value itself before splicing it in.
+              let mut value = value.clone();
+              stack.push(name.clone());
+              value.inline_variables_with_stack(vars, stack);
+              stack.pop();
+
+              inlined = true;
+              i += self.splice_variable_value(i, value);
+              continue;
+            }
+          }
+
+          if let Some(fallback) = &mut var.fallback {
+            inlined |= fallback.inline_variables_with_stack(vars, stack);
+          }
+        }
+        TokenOrValue::Env(env) => {
+          if let Some(fallback) = &mut env.fallback {
+            inlined |= fallback.inline_variables_with_stack(vars, stack);
+          }
+        }
+        TokenOrValue::Function(f) => inlined |= f.arguments.inline_variables_with_stack(vars, stack),
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+          inlined |= alpha.inline_variables_with_stack(vars, stack)
+        }
+        _ => {}
+      }
+      i += 1;
+    }
+    inlined
+  }
 }
 
 #[cfg(feature = "substitute_variables")]
@@ -1231,6 +1660,12 @@ pub struct Variable<'i> {
   pub name: DashedIdentReference<'i>,
   /// A fallback value in case the variable is not defined.
   pub fallback: Option<TokenList<'i>>,
+  /// The fallback value parsed according to the syntax of the referenced custom property, if it is
+  /// registered and the fallback matched. Like [CustomProperty::typed_value], this is not printed
+  /// or visited, and the fallback token list is normalized from it when parsing.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+  pub typed_fallback: Option<ParsedComponent<'i>>,
 }
 
 impl<'i> Variable<'i> {
@@ -1241,13 +1676,28 @@ impl<'i> Variable<'i> {
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let name = DashedIdentReference::parse_with_options(input, options)?;
 
+    let mut typed_fallback = None;
     let fallback = if input.try_parse(|input| input.expect_comma()).is_ok() {
-      Some(TokenList::parse(input, options, depth)?)
+      // References to custom properties in other files are not registered in this one.
+      if name.from.is_none() {
+        typed_fallback = parse_registered_value(&name.ident, input, options);
+      }
+      let fallback = TokenList::parse(input, options, depth)?;
+      Some(
+        typed_fallback
+          .as_ref()
+          .and_then(ParsedComponent::to_token_list)
+          .unwrap_or(fallback),
+      )
     } else {
       None
     };
 
-    Ok(Variable { name, fallback })
+    Ok(Variable {
+      name,
+      fallback,
+      typed_fallback,
+    })
   }
 
   fn to_css<W>(&self, dest: &mut Printer<W>, is_custom_property: bool) -> Result<(), PrinterError>
@@ -1267,6 +1717,7 @@ impl<'i> Variable<'i> {
     Variable {
       name: self.name.clone(),
       fallback: self.fallback.as_ref().map(|fallback| fallback.get_fallback(kind)),
+      typed_fallback: None,
     }
   }
 }
@@ -1615,3 +2066,183 @@ impl<'i> UnresolvedColor<'i> {
     }
   }
 }
+
+/// Returns whether the given property name is a standard CSS property or descriptor that is
+/// not otherwise parsed, and is therefore represented as an unknown custom property.
+pub(crate) fn is_unparsed_standard_property(name: &str) -> bool {
+  UNPARSED_STANDARD_PROPERTIES
+    .binary_search(&name.to_ascii_lowercase().as_str())
+    .is_ok()
+}
+
+// Must be sorted.
+const UNPARSED_STANDARD_PROPERTIES: &[&str] = &[
+  "alignment-baseline",
+  "all",
+  "anchor-name",
+  "animation-composition",
+  "animation-range",
+  "animation-range-end",
+  "animation-range-start",
+  "animation-timeline",
+  "background-blend-mode",
+  "baseline-shift",
+  "baseline-source",
+  "bleed",
+  "border-collapse",
+  "break-after",
+  "break-before",
+  "break-inside",
+  "caption-side",
+  "clear",
+  "clip",
+  "color-scheme",
+  "column-count",
+  "column-fill",
+  "column-rule",
+  "column-rule-color",
+  "column-rule-style",
+  "column-rule-width",
+  "column-span",
+  "column-width",
+  "columns",
+  "contain",
+  "contain-intrinsic-block-size",
+  "contain-intrinsic-height",
+  "contain-intrinsic-inline-size",
+  "contain-intrinsic-size",
+  "contain-intrinsic-width",
+  "content",
+  "content-visibility",
+  "counter-increment",
+  "counter-reset",
+  "counter-set",
+  "cx",
+  "cy",
+  "d",
+  "direction",
+  "dominant-baseline",
+  "empty-cells",
+  "field-sizing",
+  "float",
+  "flood-color",
+  "flood-opacity",
+  "font-feature-settings",
+  "font-language-override",
+  "font-size-adjust",
+  "font-synthesis",
+  "font-synthesis-position",
+  "font-synthesis-small-caps",
+  "font-synthesis-style",
+  "font-synthesis-weight",
+  "font-variation-settings",
+  "forced-color-adjust",
+  "grid-column-gap",
+  "grid-gap",
+  "grid-row-gap",
+  "hanging-punctuation",
+  "hyphenate-character",
+  "hyphenate-limit-chars",
+  "image-orientation",
+  "image-resolution",
+  "inset-area",
+  "isolation",
+  "lighting-color",
+  "line-clamp",
+  "line-height-step",
+  "margin-trim",
+  "marks",
+  "math-depth",
+  "math-shift",
+  "math-style",
+  "max-zoom",
+  "min-zoom",
+  "mix-blend-mode",
+  "object-fit",
+  "object-position",
+  "offset",
+  "offset-anchor",
+  "offset-distance",
+  "offset-position",
+  "offset-rotate",
+  "orientation",
+  "orphans",
+  "outline-offset",
+  "overflow-anchor",
+  "overflow-block",
+  "overflow-clip-margin",
+  "overflow-inline",
+  "overlay",
+  "overscroll-behavior",
+  "overscroll-behavior-block",
+  "overscroll-behavior-inline",
+  "overscroll-behavior-x",
+  "overscroll-behavior-y",
+  "page",
+  "page-break-after",
+  "page-break-before",
+  "page-break-inside",
+  "page-orientation",
+  "paint-order",
+  "pointer-events",
+  "position-anchor",
+  "position-area",
+  "position-try",
+  "position-try-fallbacks",
+  "position-try-order",
+  "position-visibility",
+  "print-color-adjust",
+  "quotes",
+  "r",
+  "ruby-align",
+  "ruby-position",
+  "rx",
+  "ry",
+  "scroll-behavior",
+  "scroll-snap-align",
+  "scroll-snap-stop",
+  "scroll-snap-type",
+  "scroll-timeline",
+  "scroll-timeline-axis",
+  "scroll-timeline-name",
+  "scrollbar-color",
+  "scrollbar-gutter",
+  "scrollbar-width",
+  "shape-image-threshold",
+  "shape-margin",
+  "size",
+  "speak",
+  "speak-as",
+  "stop-color",
+  "stop-opacity",
+  "table-layout",
+  "text-anchor",
+  "text-combine-upright",
+  "text-decoration-skip",
+  "text-orientation",
+  "text-spacing-trim",
+  "text-underline-offset",
+  "text-underline-position",
+  "text-wrap",
+  "text-wrap-mode",
+  "text-wrap-style",
+  "timeline-scope",
+  "touch-action",
+  "transition-behavior",
+  "unicode-bidi",
+  "user-zoom",
+  "vector-effect",
+  "view-timeline",
+  "view-timeline-axis",
+  "view-timeline-inset",
+  "view-timeline-name",
+  "view-transition-class",
+  "viewport-fit",
+  "white-space-collapse",
+  "widows",
+  "will-change",
+  "writing-mode",
+  "x",
+  "y",
+  "zoom",
+];
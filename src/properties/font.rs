@@ -11,6 +11,7 @@ use crate::macros::*;
 use crate::printer::Printer;
 use crate::targets::should_compile;
 use crate::traits::{IsCompatible, Parse, PropertyHandler, Shorthand, ToCss};
+use crate::values::ident::{CustomIdent, CustomIdentList};
 use crate::values::length::LengthValue;
 use crate::values::number::CSSNumber;
 use crate::values::string::CowArcStr;
@@ -351,6 +352,29 @@ impl Into<Percentage> for &FontStretch {
   }
 }
 
+impl FontStretch {
+  /// Returns the equivalent font stretch keyword, if any.
+  pub fn to_keyword(&self) -> Option<FontStretchKeyword> {
+    use FontStretchKeyword::*;
+    match self {
+      FontStretch::Keyword(keyword) => Some(*keyword),
+      FontStretch::Percentage(percentage) => [
+        UltraCondensed,
+        ExtraCondensed,
+        Condensed,
+        SemiCondensed,
+        Normal,
+        SemiExpanded,
+        Expanded,
+        ExtraExpanded,
+        UltraExpanded,
+      ]
+      .into_iter()
+      .find(|keyword| Into::<Percentage>::into(keyword) == *percentage),
+    }
+  }
+}
+
 impl ToCss for FontStretch {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -431,14 +455,79 @@ impl IsCompatible for GenericFontFamily {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
 #[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
-#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
 pub enum FontFamily<'i> {
   /// A generic family name.
   Generic(GenericFontFamily),
   /// A custom family name.
+  FamilyName(CowArcStr<'i>),
+}
+
+/// The serialized form of a [FontFamily](FontFamily). Family names are serialized as strings,
+/// unless they would be ambiguous with a generic family name, e.g. a quoted `"sans-serif"`.
+#[cfg(any(feature = "serde", feature = "jsonschema"))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(untagged))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+enum SerializedFontFamily<'i> {
+  Generic(GenericFontFamily),
   #[cfg_attr(feature = "serde", serde(borrow))]
   FamilyName(CowArcStr<'i>),
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  QuotedFamilyName {
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    family_name: CowArcStr<'i>,
+  },
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'i> serde::Serialize for FontFamily<'i> {
+  fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+  where
+    S: serde::Serializer,
+  {
+    match self {
+      FontFamily::Generic(generic) => SerializedFontFamily::Generic(*generic),
+      FontFamily::FamilyName(name) if GenericFontFamily::parse_string(name).is_ok() => {
+        SerializedFontFamily::QuotedFamilyName {
+          family_name: name.clone(),
+        }
+      }
+      FontFamily::FamilyName(name) => SerializedFontFamily::FamilyName(name.clone()),
+    }
+    .serialize(serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<'i, 'de: 'i> serde::Deserialize<'de> for FontFamily<'i> {
+  fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+  where
+    D: serde::Deserializer<'de>,
+  {
+    Ok(match SerializedFontFamily::deserialize(deserializer)? {
+      SerializedFontFamily::Generic(generic) => FontFamily::Generic(generic),
+      SerializedFontFamily::FamilyName(name) | SerializedFontFamily::QuotedFamilyName { family_name: name } => {
+        FontFamily::FamilyName(name)
+      }
+    })
+  }
+}
+
+#[cfg(feature = "jsonschema")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+impl<'i> schemars::JsonSchema for FontFamily<'i> {
+  fn is_referenceable() -> bool {
+    true
+  }
+
+  fn json_schema(gen: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+    SerializedFontFamily::json_schema(gen)
+  }
+
+  fn schema_name() -> String {
+    "FontFamily".into()
+  }
 }
 
 impl<'i> Parse<'i> for FontFamily<'i> {
@@ -643,6 +732,666 @@ impl IsCompatible for FontVariantCaps {
   }
 }
 
+macro_rules! parse_keyword_component {
+  ($input: ident, $field: expr, $ty: ty) => {
+    if $field.is_none() {
+      if let Ok(value) = $input.try_parse(<$ty>::parse) {
+        $field = Some(value);
+        return true;
+      }
+    }
+  };
+}
+
+macro_rules! parse_flag_component {
+  ($input: ident, $field: expr, $name: literal) => {
+    if !$field && $input.try_parse(|input| input.expect_ident_matching($name)).is_ok() {
+      $field = true;
+      return true;
+    }
+  };
+}
+
+/// Writes a space-separated list of components, or `normal` if there are none.
+struct ComponentWriter {
+  first: bool,
+}
+
+impl ComponentWriter {
+  fn new() -> Self {
+    ComponentWriter { first: true }
+  }
+
+  fn write<W, T: ToCss + ?Sized>(&mut self, value: &T, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if !self.first {
+      dest.write_char(' ')?;
+    }
+    self.first = false;
+    value.to_css(dest)
+  }
+
+  fn write_str<W>(&mut self, value: &str, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if !self.first {
+      dest.write_char(' ')?;
+    }
+    self.first = false;
+    dest.write_str(value)
+  }
+
+  fn finish<W>(self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.first {
+      dest.write_str("normal")?;
+    }
+    Ok(())
+  }
+}
+
+enum_property! {
+  /// A [`<common-lig-values>`](https://www.w3.org/TR/css-fonts-4/#common-lig-values) value,
+  /// as used in the `font-variant-ligatures` property.
+  pub enum CommonLigValues {
+    /// Enables common ligatures.
+    "common-ligatures": CommonLigatures,
+    /// Disables common ligatures.
+    "no-common-ligatures": NoCommonLigatures,
+  }
+}
+
+enum_property! {
+  /// A [`<discretionary-lig-values>`](https://www.w3.org/TR/css-fonts-4/#discretionary-lig-values) value,
+  /// as used in the `font-variant-ligatures` property.
+  pub enum DiscretionaryLigValues {
+    /// Enables discretionary ligatures.
+    "discretionary-ligatures": DiscretionaryLigatures,
+    /// Disables discretionary ligatures.
+    "no-discretionary-ligatures": NoDiscretionaryLigatures,
+  }
+}
+
+enum_property! {
+  /// A [`<historical-lig-values>`](https://www.w3.org/TR/css-fonts-4/#historical-lig-values) value,
+  /// as used in the `font-variant-ligatures` property.
+  pub enum HistoricalLigValues {
+    /// Enables historical ligatures.
+    "historical-ligatures": HistoricalLigatures,
+    /// Disables historical ligatures.
+    "no-historical-ligatures": NoHistoricalLigatures,
+  }
+}
+
+enum_property! {
+  /// A [`<contextual-alt-values>`](https://www.w3.org/TR/css-fonts-4/#contextual-alt-values) value,
+  /// as used in the `font-variant-ligatures` property.
+  pub enum ContextualAltValues {
+    /// Enables contextual alternates.
+    "contextual": Contextual,
+    /// Disables contextual alternates.
+    "no-contextual": NoContextual,
+  }
+}
+
+/// A value for the [font-variant-ligatures](https://www.w3.org/TR/css-fonts-4/#font-variant-ligatures-prop) property.
+///
+/// The `normal` keyword is represented by the default value, with no components specified, and the
+/// `none` keyword disables all of the components.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub struct FontVariantLigatures {
+  /// Common ligatures.
+  pub common: Option<CommonLigValues>,
+  /// Discretionary ligatures.
+  pub discretionary: Option<DiscretionaryLigValues>,
+  /// Historical ligatures.
+  pub historical: Option<HistoricalLigValues>,
+  /// Contextual alternates.
+  pub contextual: Option<ContextualAltValues>,
+}
+
+impl FontVariantLigatures {
+  /// Returns the value of the `none` keyword, which disables all ligatures and contextual forms.
+  pub fn none() -> Self {
+    FontVariantLigatures {
+      common: Some(CommonLigValues::NoCommonLigatures),
+      discretionary: Some(DiscretionaryLigValues::NoDiscretionaryLigatures),
+      historical: Some(HistoricalLigValues::NoHistoricalLigatures),
+      contextual: Some(ContextualAltValues::NoContextual),
+    }
+  }
+
+  fn parse_component<'i, 't>(&mut self, input: &mut Parser<'i, 't>) -> bool {
+    parse_keyword_component!(input, self.common, CommonLigValues);
+    parse_keyword_component!(input, self.discretionary, DiscretionaryLigValues);
+    parse_keyword_component!(input, self.historical, HistoricalLigValues);
+    parse_keyword_component!(input, self.contextual, ContextualAltValues);
+    false
+  }
+
+  fn write_components<W>(&self, writer: &mut ComponentWriter, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if let Some(common) = &self.common {
+      writer.write(common, dest)?;
+    }
+    if let Some(discretionary) = &self.discretionary {
+      writer.write(discretionary, dest)?;
+    }
+    if let Some(historical) = &self.historical {
+      writer.write(historical, dest)?;
+    }
+    if let Some(contextual) = &self.contextual {
+      writer.write(contextual, dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl<'i> Parse<'i> for FontVariantLigatures {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariantLigatures::default());
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(FontVariantLigatures::none());
+    }
+
+    let mut value = FontVariantLigatures::default();
+    while value.parse_component(input) {}
+    if value == FontVariantLigatures::default() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+    Ok(value)
+  }
+}
+
+impl ToCss for FontVariantLigatures {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if *self == FontVariantLigatures::none() {
+      return dest.write_str("none");
+    }
+
+    let mut writer = ComponentWriter::new();
+    self.write_components(&mut writer, dest)?;
+    writer.finish(dest)
+  }
+}
+
+enum_property! {
+  /// A [`<numeric-figure-values>`](https://www.w3.org/TR/css-fonts-4/#numeric-figure-values) value,
+  /// as used in the `font-variant-numeric` property.
+  pub enum NumericFigureValues {
+    /// Enables lining numerals.
+    "lining-nums": LiningNums,
+    /// Enables old-style numerals.
+    "oldstyle-nums": OldstyleNums,
+  }
+}
+
+enum_property! {
+  /// A [`<numeric-spacing-values>`](https://www.w3.org/TR/css-fonts-4/#numeric-spacing-values) value,
+  /// as used in the `font-variant-numeric` property.
+  pub enum NumericSpacingValues {
+    /// Enables proportional numerals.
+    "proportional-nums": ProportionalNums,
+    /// Enables tabular numerals.
+    "tabular-nums": TabularNums,
+  }
+}
+
+enum_property! {
+  /// A [`<numeric-fraction-values>`](https://www.w3.org/TR/css-fonts-4/#numeric-fraction-values) value,
+  /// as used in the `font-variant-numeric` property.
+  pub enum NumericFractionValues {
+    /// Enables diagonal fractions.
+    "diagonal-fractions": DiagonalFractions,
+    /// Enables stacked fractions.
+    "stacked-fractions": StackedFractions,
+  }
+}
+
+/// A value for the [font-variant-numeric](https://www.w3.org/TR/css-fonts-4/#font-variant-numeric-prop) property.
+///
+/// The `normal` keyword is represented by the default value, with no components specified.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub struct FontVariantNumeric {
+  /// The style of numerals.
+  pub figure: Option<NumericFigureValues>,
+  /// The spacing of numerals.
+  pub spacing: Option<NumericSpacingValues>,
+  /// The style of fractions.
+  pub fraction: Option<NumericFractionValues>,
+  /// Enables ordinal markers.
+  pub ordinal: bool,
+  /// Enables slashed zeros.
+  pub slashed_zero: bool,
+}
+
+impl FontVariantNumeric {
+  fn parse_component<'i, 't>(&mut self, input: &mut Parser<'i, 't>) -> bool {
+    parse_keyword_component!(input, self.figure, NumericFigureValues);
+    parse_keyword_component!(input, self.spacing, NumericSpacingValues);
+    parse_keyword_component!(input, self.fraction, NumericFractionValues);
+    parse_flag_component!(input, self.ordinal, "ordinal");
+    parse_flag_component!(input, self.slashed_zero, "slashed-zero");
+    false
+  }
+
+  fn write_components<W>(&self, writer: &mut ComponentWriter, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if let Some(figure) = &self.figure {
+      writer.write(figure, dest)?;
+    }
+    if let Some(spacing) = &self.spacing {
+      writer.write(spacing, dest)?;
+    }
+    if let Some(fraction) = &self.fraction {
+      writer.write(fraction, dest)?;
+    }
+    if self.ordinal {
+      writer.write_str("ordinal", dest)?;
+    }
+    if self.slashed_zero {
+      writer.write_str("slashed-zero", dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl<'i> Parse<'i> for FontVariantNumeric {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariantNumeric::default());
+    }
+
+    let mut value = FontVariantNumeric::default();
+    while value.parse_component(input) {}
+    if value == FontVariantNumeric::default() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+    Ok(value)
+  }
+}
+
+impl ToCss for FontVariantNumeric {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let mut writer = ComponentWriter::new();
+    self.write_components(&mut writer, dest)?;
+    writer.finish(dest)
+  }
+}
+
+enum_property! {
+  /// An [`<east-asian-variant-values>`](https://www.w3.org/TR/css-fonts-4/#east-asian-variant-values) value,
+  /// as used in the `font-variant-east-asian` property.
+  pub enum EastAsianVariantValues {
+    /// Glyphs from the JIS X 0208-1978 standard.
+    "jis78": Jis78,
+    /// Glyphs from the JIS X 0208-1983 standard.
+    "jis83": Jis83,
+    /// Glyphs from the JIS X 0208-1990 standard.
+    "jis90": Jis90,
+    /// Glyphs from the JIS X 0213:2004 standard.
+    "jis04": Jis04,
+    /// Simplified Chinese glyphs.
+    "simplified": Simplified,
+    /// Traditional Chinese glyphs.
+    "traditional": Traditional,
+  }
+}
+
+enum_property! {
+  /// An [`<east-asian-width-values>`](https://www.w3.org/TR/css-fonts-4/#east-asian-width-values) value,
+  /// as used in the `font-variant-east-asian` property.
+  pub enum EastAsianWidthValues {
+    /// Full-width glyphs.
+    "full-width": FullWidth,
+    /// Proportionally spaced glyphs.
+    "proportional-width": ProportionalWidth,
+  }
+}
+
+/// A value for the [font-variant-east-asian](https://www.w3.org/TR/css-fonts-4/#font-variant-east-asian-prop) property.
+///
+/// The `normal` keyword is represented by the default value, with no components specified.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub struct FontVariantEastAsian {
+  /// The glyph variant standard.
+  pub variant: Option<EastAsianVariantValues>,
+  /// The glyph width.
+  pub width: Option<EastAsianWidthValues>,
+  /// Enables ruby variant glyphs.
+  pub ruby: bool,
+}
+
+impl FontVariantEastAsian {
+  fn parse_component<'i, 't>(&mut self, input: &mut Parser<'i, 't>) -> bool {
+    parse_keyword_component!(input, self.variant, EastAsianVariantValues);
+    parse_keyword_component!(input, self.width, EastAsianWidthValues);
+    parse_flag_component!(input, self.ruby, "ruby");
+    false
+  }
+
+  fn write_components<W>(&self, writer: &mut ComponentWriter, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if let Some(variant) = &self.variant {
+      writer.write(variant, dest)?;
+    }
+    if let Some(width) = &self.width {
+      writer.write(width, dest)?;
+    }
+    if self.ruby {
+      writer.write_str("ruby", dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl<'i> Parse<'i> for FontVariantEastAsian {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariantEastAsian::default());
+    }
+
+    let mut value = FontVariantEastAsian::default();
+    while value.parse_component(input) {}
+    if value == FontVariantEastAsian::default() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+    Ok(value)
+  }
+}
+
+impl ToCss for FontVariantEastAsian {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let mut writer = ComponentWriter::new();
+    self.write_components(&mut writer, dest)?;
+    writer.finish(dest)
+  }
+}
+
+/// A value for the [font-variant-alternates](https://www.w3.org/TR/css-fonts-4/#font-variant-alternates-prop) property.
+///
+/// The `normal` keyword is represented by the default value, with no components specified. The functions
+/// reference feature values defined by `@font-feature-values` rules.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub struct FontVariantAlternates<'i> {
+  /// The `stylistic()` function.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub stylistic: Option<CustomIdent<'i>>,
+  /// Enables historical forms.
+  pub historical_forms: bool,
+  /// The `styleset()` function.
+  pub styleset: CustomIdentList<'i>,
+  /// The `character-variant()` function.
+  pub character_variant: CustomIdentList<'i>,
+  /// The `swash()` function.
+  pub swash: Option<CustomIdent<'i>>,
+  /// The `ornaments()` function.
+  pub ornaments: Option<CustomIdent<'i>>,
+  /// The `annotation()` function.
+  pub annotation: Option<CustomIdent<'i>>,
+}
+
+impl<'i> FontVariantAlternates<'i> {
+  fn parse_component<'t>(&mut self, input: &mut Parser<'i, 't>) -> bool {
+    macro_rules! function {
+      ($field: expr, $name: literal) => {
+        if $field.is_none() {
+          if let Ok(value) = input.try_parse(|input| {
+            input.expect_function_matching($name)?;
+            input.parse_nested_block(CustomIdent::parse)
+          }) {
+            $field = Some(value);
+            return true;
+          }
+        }
+      };
+    }
+
+    macro_rules! list_function {
+      ($field: expr, $name: literal) => {
+        if $field.is_empty() {
+          if let Ok(value) = input.try_parse(|input| {
+            input.expect_function_matching($name)?;
+            input.parse_nested_block(|input| input.parse_comma_separated(CustomIdent::parse))
+          }) {
+            $field = value.into();
+            return true;
+          }
+        }
+      };
+    }
+
+    function!(self.stylistic, "stylistic");
+    parse_flag_component!(input, self.historical_forms, "historical-forms");
+    list_function!(self.styleset, "styleset");
+    list_function!(self.character_variant, "character-variant");
+    function!(self.swash, "swash");
+    function!(self.ornaments, "ornaments");
+    function!(self.annotation, "annotation");
+    false
+  }
+
+  fn write_components<W>(&self, writer: &mut ComponentWriter, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    macro_rules! function {
+      ($name: literal, $values: expr) => {{
+        writer.write_str(concat!($name, "("), dest)?;
+        let mut first = true;
+        for value in $values {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          value.to_css(dest)?;
+        }
+        dest.write_char(')')?;
+      }};
+    }
+
+    if let Some(stylistic) = &self.stylistic {
+      function!("stylistic", std::iter::once(stylistic));
+    }
+    if self.historical_forms {
+      writer.write_str("historical-forms", dest)?;
+    }
+    if !self.styleset.is_empty() {
+      function!("styleset", &self.styleset);
+    }
+    if !self.character_variant.is_empty() {
+      function!("character-variant", &self.character_variant);
+    }
+    if let Some(swash) = &self.swash {
+      function!("swash", std::iter::once(swash));
+    }
+    if let Some(ornaments) = &self.ornaments {
+      function!("ornaments", std::iter::once(ornaments));
+    }
+    if let Some(annotation) = &self.annotation {
+      function!("annotation", std::iter::once(annotation));
+    }
+    Ok(())
+  }
+}
+
+impl<'i> Parse<'i> for FontVariantAlternates<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariantAlternates::default());
+    }
+
+    let mut value = FontVariantAlternates::default();
+    while value.parse_component(input) {}
+    if value == FontVariantAlternates::default() {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+    Ok(value)
+  }
+}
+
+impl<'i> ToCss for FontVariantAlternates<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let mut writer = ComponentWriter::new();
+    self.write_components(&mut writer, dest)?;
+    writer.finish(dest)
+  }
+}
+
+enum_property! {
+  /// A value for the [font-variant-position](https://www.w3.org/TR/css-fonts-4/#font-variant-position-prop) property.
+  pub enum FontVariantPosition {
+    /// No superscript or subscript glyphs are used.
+    "normal": Normal,
+    /// Subscript glyphs are used.
+    "sub": Sub,
+    /// Superscript glyphs are used.
+    "super": Super,
+  }
+}
+
+impl Default for FontVariantPosition {
+  fn default() -> FontVariantPosition {
+    FontVariantPosition::Normal
+  }
+}
+
+enum_property! {
+  /// A value for the [font-variant-emoji](https://www.w3.org/TR/css-fonts-4/#font-variant-emoji-prop) property.
+  pub enum FontVariantEmoji {
+    /// The user agent chooses the presentation of emoji.
+    "normal": Normal,
+    /// Emoji are rendered with a text presentation.
+    "text": Text,
+    /// Emoji are rendered with an emoji presentation.
+    "emoji": Emoji,
+    /// The presentation follows the Unicode default for each character.
+    "unicode": Unicode,
+  }
+}
+
+impl Default for FontVariantEmoji {
+  fn default() -> FontVariantEmoji {
+    FontVariantEmoji::Normal
+  }
+}
+
+enum_property! {
+  /// A value for the [font-kerning](https://www.w3.org/TR/css-fonts-4/#font-kerning-prop) property.
+  pub enum FontKerning {
+    /// The user agent decides whether kerning is applied.
+    "auto": Auto,
+    /// Kerning is applied.
+    "normal": Normal,
+    /// Kerning is not applied.
+    "none": None,
+  }
+}
+
+impl Default for FontKerning {
+  fn default() -> FontKerning {
+    FontKerning::Auto
+  }
+}
+
+enum_property! {
+  /// A value for the [font-optical-sizing](https://www.w3.org/TR/css-fonts-4/#font-optical-sizing-def) property.
+  pub enum FontOpticalSizing {
+    /// The user agent may modify the shape of glyphs based on the font size.
+    "auto": Auto,
+    /// Optical sizing is disabled.
+    "none": None,
+  }
+}
+
+impl Default for FontOpticalSizing {
+  fn default() -> FontOpticalSizing {
+    FontOpticalSizing::Auto
+  }
+}
+
+macro_rules! impl_always_compatible {
+  ($($t: ty),+) => {
+    $(
+      impl IsCompatible for $t {
+        fn is_compatible(&self, _browsers: crate::targets::Browsers) -> bool {
+          true
+        }
+      }
+    )+
+  };
+}
+
+impl_always_compatible!(
+  FontVariantLigatures,
+  FontVariantNumeric,
+  FontVariantEastAsian,
+  FontVariantAlternates<'_>,
+  FontVariantPosition,
+  FontVariantEmoji,
+  FontKerning,
+  FontOpticalSizing
+);
+
 /// A value for the [line-height](https://www.w3.org/TR/2020/WD-css-inline-3-20200827/#propdef-line-height) property.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -779,7 +1528,7 @@ define_shorthand! {
     style: FontStyle(FontStyle),
     /// The font weight.
     weight: FontWeight(FontWeight),
-    /// The font stretch.
+    /// The font stretch. Only keywords can be represented in the shorthand.
     stretch: FontStretch(FontStretch),
     /// The line height.
     line_height: LineHeight(LineHeight),
@@ -884,8 +1633,13 @@ impl<'i> ToCss for Font<'i> {
       dest.write_char(' ')?;
     }
 
-    if self.stretch != FontStretch::default() {
-      self.stretch.to_css(dest)?;
+    // Only keywords are valid for font-stretch within the font shorthand.
+    let stretch = self.stretch.to_keyword();
+    if stretch != Some(FontStretchKeyword::Normal) {
+      match stretch {
+        Some(keyword) => keyword.to_css(dest)?,
+        None => self.stretch.to_css(dest)?,
+      }
       dest.write_char(' ')?;
     }
 
@@ -910,9 +1664,152 @@ impl<'i> ToCss for Font<'i> {
   }
 }
 
+define_shorthand! {
+  /// A value for the [font-variant](https://www.w3.org/TR/css-fonts-4/#font-variant-prop) shorthand property.
+  pub struct FontVariant<'i> {
+    /// The ligatures and contextual forms.
+    ligatures: FontVariantLigatures(FontVariantLigatures),
+    /// How the text should be capitalized.
+    caps: FontVariantCaps(FontVariantCaps),
+    /// The alternate glyphs.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    alternates: FontVariantAlternates(FontVariantAlternates<'i>),
+    /// The numeric glyphs.
+    numeric: FontVariantNumeric(FontVariantNumeric),
+    /// The East Asian glyphs.
+    east_asian: FontVariantEastAsian(FontVariantEastAsian),
+    /// The superscript or subscript glyphs.
+    position: FontVariantPosition(FontVariantPosition),
+    /// The presentation of emoji.
+    emoji: FontVariantEmoji(FontVariantEmoji),
+  }
+}
+
+impl<'i> Default for FontVariant<'i> {
+  fn default() -> Self {
+    FontVariant {
+      ligatures: FontVariantLigatures::default(),
+      caps: FontVariantCaps::default(),
+      alternates: FontVariantAlternates::default(),
+      numeric: FontVariantNumeric::default(),
+      east_asian: FontVariantEastAsian::default(),
+      position: FontVariantPosition::default(),
+      emoji: FontVariantEmoji::default(),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for FontVariant<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(FontVariant::default());
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(FontVariant {
+        ligatures: FontVariantLigatures::none(),
+        ..FontVariant::default()
+      });
+    }
+
+    // The `normal` keyword is only valid on its own, so it is not accepted for the keyword longhands here.
+    fn parse_non_normal<'i, 't, T: Parse<'i> + Default + PartialEq>(
+      input: &mut Parser<'i, 't>,
+    ) -> Result<T, ParseError<'i, ParserError<'i>>> {
+      let value = T::parse(input)?;
+      if value == T::default() {
+        return Err(input.new_custom_error(ParserError::InvalidValue));
+      }
+      Ok(value)
+    }
+
+    let mut value = FontVariant::default();
+    let mut caps = None;
+    let mut position = None;
+    let mut emoji = None;
+    let mut count = 0;
+    loop {
+      if value.ligatures.parse_component(input)
+        || value.alternates.parse_component(input)
+        || value.numeric.parse_component(input)
+        || value.east_asian.parse_component(input)
+      {
+        count += 1;
+        continue;
+      }
+
+      if caps.is_none() {
+        if let Ok(val) = input.try_parse(parse_non_normal::<FontVariantCaps>) {
+          caps = Some(val);
+          count += 1;
+          continue;
+        }
+      }
+
+      if position.is_none() {
+        if let Ok(val) = input.try_parse(parse_non_normal::<FontVariantPosition>) {
+          position = Some(val);
+          count += 1;
+          continue;
+        }
+      }
+
+      if emoji.is_none() {
+        if let Ok(val) = input.try_parse(parse_non_normal::<FontVariantEmoji>) {
+          emoji = Some(val);
+          count += 1;
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    if count == 0 {
+      return Err(input.new_custom_error(ParserError::InvalidDeclaration));
+    }
+
+    value.caps = caps.unwrap_or_default();
+    value.position = position.unwrap_or_default();
+    value.emoji = emoji.unwrap_or_default();
+    Ok(value)
+  }
+}
+
+impl<'i> ToCss for FontVariant<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let none = FontVariant {
+      ligatures: FontVariantLigatures::none(),
+      ..FontVariant::default()
+    };
+    if *self == none {
+      return dest.write_str("none");
+    }
+
+    let mut writer = ComponentWriter::new();
+    self.ligatures.write_components(&mut writer, dest)?;
+    if self.caps != FontVariantCaps::default() {
+      writer.write(&self.caps, dest)?;
+    }
+    self.alternates.write_components(&mut writer, dest)?;
+    self.numeric.write_components(&mut writer, dest)?;
+    self.east_asian.write_components(&mut writer, dest)?;
+    if self.position != FontVariantPosition::default() {
+      writer.write(&self.position, dest)?;
+    }
+    if self.emoji != FontVariantEmoji::default() {
+      writer.write(&self.emoji, dest)?;
+    }
+    writer.finish(dest)
+  }
+}
+
 property_bitflags! {
   #[derive(Default, Debug)]
-  struct FontProperty: u8 {
+  struct FontProperty: u32 {
     const FontFamily = 1 << 0;
     const FontSize = 1 << 1;
     const FontStyle = 1 << 2;
@@ -921,9 +1818,28 @@ property_bitflags! {
     const LineHeight = 1 << 5;
     const FontVariantCaps = 1 << 6;
     const Font = Self::FontFamily.bits() | Self::FontSize.bits() | Self::FontStyle.bits() | Self::FontWeight.bits() | Self::FontStretch.bits() | Self::LineHeight.bits() | Self::FontVariantCaps.bits();
+    const FontVariantLigatures = 1 << 7;
+    const FontVariantAlternates = 1 << 8;
+    const FontVariantNumeric = 1 << 9;
+    const FontVariantEastAsian = 1 << 10;
+    const FontVariantPosition = 1 << 11;
+    const FontVariantEmoji = 1 << 12;
+    const FontVariant = Self::FontVariantLigatures.bits() | Self::FontVariantCaps.bits() | Self::FontVariantAlternates.bits() | Self::FontVariantNumeric.bits() | Self::FontVariantEastAsian.bits() | Self::FontVariantPosition.bits() | Self::FontVariantEmoji.bits();
+    const FontKerning = 1 << 13;
+    const FontOpticalSizing = 1 << 14;
   }
 }
 
+/// Longhands that are reset to their initial values by the `font` shorthand, but cannot be set by it.
+const FONT_RESET_PROPERTIES: FontProperty = FontProperty::FontVariantLigatures
+  .union(FontProperty::FontVariantAlternates)
+  .union(FontProperty::FontVariantNumeric)
+  .union(FontProperty::FontVariantEastAsian)
+  .union(FontProperty::FontVariantPosition)
+  .union(FontProperty::FontVariantEmoji)
+  .union(FontProperty::FontKerning)
+  .union(FontProperty::FontOpticalSizing);
+
 #[derive(Default, Debug)]
 pub(crate) struct FontHandler<'i> {
   family: Option<Vec<FontFamily<'i>>>,
@@ -933,6 +1849,14 @@ pub(crate) struct FontHandler<'i> {
   stretch: Option<FontStretch>,
   line_height: Option<LineHeight>,
   variant_caps: Option<FontVariantCaps>,
+  ligatures: Option<FontVariantLigatures>,
+  alternates: Option<FontVariantAlternates<'i>>,
+  numeric: Option<FontVariantNumeric>,
+  east_asian: Option<FontVariantEastAsian>,
+  position: Option<FontVariantPosition>,
+  emoji: Option<FontVariantEmoji>,
+  kerning: Option<FontKerning>,
+  optical_sizing: Option<FontOpticalSizing>,
   flushed_properties: FontProperty,
   has_any: bool,
 }
@@ -970,6 +1894,24 @@ impl<'i> PropertyHandler<'i> for FontHandler<'i> {
       FontStretch(val) => property!(stretch, val),
       FontVariantCaps(val) => property!(variant_caps, val),
       LineHeight(val) => property!(line_height, val),
+      FontVariantLigatures(val) => property!(ligatures, val),
+      FontVariantAlternates(val) => property!(alternates, val),
+      FontVariantNumeric(val) => property!(numeric, val),
+      FontVariantEastAsian(val) => property!(east_asian, val),
+      FontVariantPosition(val) => property!(position, val),
+      FontVariantEmoji(val) => property!(emoji, val),
+      FontKerning(val) => property!(kerning, val),
+      FontOpticalSizing(val) => property!(optical_sizing, val),
+      FontVariant(val) => {
+        self.variant_caps = Some(val.caps);
+        self.ligatures = Some(val.ligatures.clone());
+        self.alternates = Some(val.alternates.clone());
+        self.numeric = Some(val.numeric.clone());
+        self.east_asian = Some(val.east_asian.clone());
+        self.position = Some(val.position);
+        self.emoji = Some(val.emoji);
+        self.has_any = true;
+      }
       Font(val) => {
         flush!(family, val.family);
         flush!(size, val.size);
@@ -985,8 +1927,16 @@ impl<'i> PropertyHandler<'i> for FontHandler<'i> {
         self.stretch = Some(val.stretch.clone());
         self.line_height = Some(val.line_height.clone());
         self.variant_caps = Some(val.variant_caps.clone());
+        // The font shorthand also resets longhands that it cannot set.
+        self.ligatures = Some(Default::default());
+        self.alternates = Some(Default::default());
+        self.numeric = Some(Default::default());
+        self.east_asian = Some(Default::default());
+        self.position = Some(Default::default());
+        self.emoji = Some(Default::default());
+        self.kerning = Some(Default::default());
+        self.optical_sizing = Some(Default::default());
         self.has_any = true;
-        // TODO: reset other properties
       }
       Unparsed(val) if is_font_property(&val.property_id) => {
         self.flush(dest, context);
@@ -995,6 +1945,11 @@ impl<'i> PropertyHandler<'i> for FontHandler<'i> {
           .insert(FontProperty::try_from(&val.property_id).unwrap());
         dest.push(property.clone());
       }
+      Custom(custom) if is_unparsed_font_reset_property(custom.name.as_ref()) => {
+        // Ensure that a preceding font shorthand does not reset this property.
+        self.flush(dest, context);
+        return false;
+      }
       _ => return false,
     }
 
@@ -1024,7 +1979,11 @@ impl<'i> FontHandler<'i> {
 
     let mut family = std::mem::take(&mut self.family);
     if !self.flushed_properties.contains(FontProperty::FontFamily) {
-      family = compatible_font_family(family, !should_compile!(context.targets, FontFamilySystemUi));
+      family = compatible_font_family(
+        family,
+        !should_compile!(context.targets, FontFamilySystemUi),
+        !should_compile!(context.targets, ExtendedSystemFonts),
+      );
     }
     let size = std::mem::take(&mut self.size);
     let style = std::mem::take(&mut self.style);
@@ -1032,6 +1991,14 @@ impl<'i> FontHandler<'i> {
     let stretch = std::mem::take(&mut self.stretch);
     let line_height = std::mem::take(&mut self.line_height);
     let variant_caps = std::mem::take(&mut self.variant_caps);
+    let ligatures = std::mem::take(&mut self.ligatures);
+    let alternates = std::mem::take(&mut self.alternates);
+    let numeric = std::mem::take(&mut self.numeric);
+    let east_asian = std::mem::take(&mut self.east_asian);
+    let position = std::mem::take(&mut self.position);
+    let emoji = std::mem::take(&mut self.emoji);
+    let kerning = std::mem::take(&mut self.kerning);
+    let optical_sizing = std::mem::take(&mut self.optical_sizing);
 
     if let Some(family) = &mut family {
       if family.len() > 1 {
@@ -1041,6 +2008,9 @@ impl<'i> FontHandler<'i> {
       }
     }
 
+    // Longhands that were already flushed, e.g. because they contained variables,
+    // would be reset by a font shorthand that follows them.
+    let is_font_reset_safe = !self.flushed_properties.intersects(FONT_RESET_PROPERTIES);
     if family.is_some()
       && size.is_some()
       && style.is_some()
@@ -1048,8 +2018,13 @@ impl<'i> FontHandler<'i> {
       && stretch.is_some()
       && line_height.is_some()
       && variant_caps.is_some()
+      && is_font_reset_safe
     {
+      // The `font` property only accepts CSS 2.1 values for font-variant caps,
+      // and keywords for font-stretch. Other values must be added as separate properties.
       let caps = variant_caps.unwrap();
+      let stretch = stretch.unwrap();
+      let stretch_keyword = stretch.to_keyword();
       push!(
         Font,
         Font {
@@ -1057,7 +2032,7 @@ impl<'i> FontHandler<'i> {
           size: size.unwrap(),
           style: style.unwrap(),
           weight: weight.unwrap(),
-          stretch: stretch.unwrap(),
+          stretch: stretch_keyword.map_or_else(FontStretch::default, FontStretch::Keyword),
           line_height: line_height.unwrap(),
           variant_caps: if caps.is_css2() {
             caps
@@ -1067,11 +2042,18 @@ impl<'i> FontHandler<'i> {
         }
       );
 
-      // The `font` property only accepts CSS 2.1 values for font-variant caps.
-      // If we have a CSS 3+ value, we need to add a separate property.
+      if stretch_keyword.is_none() {
+        push!(FontStretch, stretch);
+      }
+
       if !caps.is_css2() {
-        push!(FontVariantCaps, variant_caps.unwrap());
+        push!(FontVariantCaps, caps);
       }
+
+      // Longhands with their initial values are already reset by the font shorthand.
+      self.push_variant_longhands(decls, ligatures, alternates, numeric, east_asian, position, emoji, true);
+      self.push_longhand(decls, kerning, true);
+      self.push_longhand(decls, optical_sizing, true);
     } else {
       if let Some(val) = family {
         push!(FontFamily, val);
@@ -1085,8 +2067,45 @@ impl<'i> FontHandler<'i> {
         push!(FontStyle, val);
       }
 
-      if let Some(val) = variant_caps {
-        push!(FontVariantCaps, val);
+      match (
+        ligatures,
+        variant_caps,
+        alternates,
+        numeric,
+        east_asian,
+        position,
+        emoji,
+      ) {
+        (
+          Some(ligatures),
+          Some(caps),
+          Some(alternates),
+          Some(numeric),
+          Some(east_asian),
+          Some(position),
+          Some(emoji),
+        ) => {
+          push!(
+            FontVariant,
+            FontVariant {
+              ligatures,
+              caps,
+              alternates,
+              numeric,
+              east_asian,
+              position,
+              emoji,
+            }
+          );
+        }
+        (ligatures, variant_caps, alternates, numeric, east_asian, position, emoji) => {
+          if let Some(val) = variant_caps {
+            push!(FontVariantCaps, val);
+          }
+          self.push_variant_longhands(
+            decls, ligatures, alternates, numeric, east_asian, position, emoji, false,
+          );
+        }
       }
 
       if let Some(val) = weight {
@@ -1100,11 +2119,79 @@ impl<'i> FontHandler<'i> {
       if let Some(val) = line_height {
         push!(LineHeight, val);
       }
+
+      self.push_longhand(decls, kerning, false);
+      self.push_longhand(decls, optical_sizing, false);
+    }
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn push_variant_longhands(
+    &mut self,
+    decls: &mut DeclarationList<'i>,
+    ligatures: Option<FontVariantLigatures>,
+    alternates: Option<FontVariantAlternates<'i>>,
+    numeric: Option<FontVariantNumeric>,
+    east_asian: Option<FontVariantEastAsian>,
+    position: Option<FontVariantPosition>,
+    emoji: Option<FontVariantEmoji>,
+    is_reset: bool,
+  ) {
+    self.push_longhand(decls, ligatures, is_reset);
+    self.push_longhand(decls, alternates, is_reset);
+    self.push_longhand(decls, numeric, is_reset);
+    self.push_longhand(decls, east_asian, is_reset);
+    self.push_longhand(decls, position, is_reset);
+    self.push_longhand(decls, emoji, is_reset);
+  }
+
+  /// Pushes a longhand that is reset by the font shorthand. If `is_reset` is true, a font
+  /// shorthand was already pushed, so initial values are omitted.
+  fn push_longhand<T: FontResetLonghand<'i>>(
+    &mut self,
+    decls: &mut DeclarationList<'i>,
+    value: Option<T>,
+    is_reset: bool,
+  ) {
+    if let Some(value) = value {
+      if is_reset && value == T::default() {
+        return;
+      }
+      self.flushed_properties.insert(T::FLAG);
+      decls.push(value.into_property());
     }
   }
 }
 
-const SYSTEM_UI: FontFamily = FontFamily::Generic(GenericFontFamily::SystemUI);
+/// A longhand that is reset to its initial value by the `font` shorthand.
+trait FontResetLonghand<'i>: Default + PartialEq {
+  const FLAG: FontProperty;
+  fn into_property(self) -> Property<'i>;
+}
+
+macro_rules! impl_font_reset_longhand {
+  ($($t: ty => $prop: ident),+) => {
+    $(
+      impl<'i> FontResetLonghand<'i> for $t {
+        const FLAG: FontProperty = FontProperty::$prop;
+        fn into_property(self) -> Property<'i> {
+          Property::$prop(self)
+        }
+      }
+    )+
+  };
+}
+
+impl_font_reset_longhand!(
+  FontVariantLigatures => FontVariantLigatures,
+  FontVariantAlternates<'i> => FontVariantAlternates,
+  FontVariantNumeric => FontVariantNumeric,
+  FontVariantEastAsian => FontVariantEastAsian,
+  FontVariantPosition => FontVariantPosition,
+  FontVariantEmoji => FontVariantEmoji,
+  FontKerning => FontKerning,
+  FontOpticalSizing => FontOpticalSizing
+);
 
 const DEFAULT_SYSTEM_FONTS: &[&str] = &[
   // #1: Supported as the '-apple-system' value (macOS, Safari >= 9.2 < 11, Firefox >= 43)
@@ -1119,23 +2206,68 @@ const DEFAULT_SYSTEM_FONTS: &[&str] = &[
   "Helvetica Neue",
 ];
 
-/// [`system-ui`](https://www.w3.org/TR/css-fonts-4/#system-ui-def) is a special generic font family
-/// It is platform dependent but if not supported by the target will simply be ignored
-/// This list is an attempt at providing that support
+const SERIF_SYSTEM_FONTS: &[&str] = &[
+  "Iowan Old Style", // macOS, iOS
+  "Georgia",
+  "Cambria",    // Windows
+  "Noto Serif", // Android, Plasma
+  "Times New Roman",
+];
+
+const MONOSPACE_SYSTEM_FONTS: &[&str] = &[
+  "SFMono-Regular", // macOS
+  "Menlo",          // macOS < 10.11
+  "Consolas",       // Windows
+  "Roboto Mono",    // Android
+  "Ubuntu Mono",    // Ubuntu
+  "Liberation Mono",
+  "Courier New",
+];
+
+const ROUNDED_SYSTEM_FONTS: &[&str] = &[
+  "SF Pro Rounded",            // macOS, iOS
+  "Hiragino Maru Gothic ProN", // macOS, iOS
+  "Arial Rounded MT",
+  "Quicksand",
+  "Comfortaa",
+  "Manjari",
+];
+
+/// [`system-ui`](https://www.w3.org/TR/css-fonts-4/#system-ui-def) and the
+/// [`ui-*`](https://www.w3.org/TR/css-fonts-4/#ui-serif-def) generic font families are platform
+/// dependent but if not supported by the target will simply be ignored.
+/// These lists are an attempt at providing that support.
 #[inline]
-fn compatible_font_family(mut family: Option<Vec<FontFamily>>, is_supported: bool) -> Option<Vec<FontFamily>> {
-  if is_supported {
+fn compatible_font_family(
+  mut family: Option<Vec<FontFamily>>,
+  is_system_ui_supported: bool,
+  is_extended_supported: bool,
+) -> Option<Vec<FontFamily>> {
+  if is_system_ui_supported && is_extended_supported {
     return family;
   }
 
   if let Some(families) = &mut family {
-    if let Some(position) = families.iter().position(|v| *v == SYSTEM_UI) {
+    let mut i = 0;
+    while i < families.len() {
+      let fallbacks = match &families[i] {
+        FontFamily::Generic(GenericFontFamily::SystemUI) if !is_system_ui_supported => DEFAULT_SYSTEM_FONTS,
+        FontFamily::Generic(generic) if !is_extended_supported => match generic {
+          GenericFontFamily::UISansSerif => DEFAULT_SYSTEM_FONTS,
+          GenericFontFamily::UISerif => SERIF_SYSTEM_FONTS,
+          GenericFontFamily::UIMonospace => MONOSPACE_SYSTEM_FONTS,
+          GenericFontFamily::UIRounded => ROUNDED_SYSTEM_FONTS,
+          _ => &[],
+        },
+        _ => &[],
+      };
+
+      i += 1;
       families.splice(
-        (position + 1)..(position + 1),
-        DEFAULT_SYSTEM_FONTS
-          .iter()
-          .map(|name| FontFamily::FamilyName(CowArcStr::from(*name))),
+        i..i,
+        fallbacks.iter().map(|name| FontFamily::FamilyName(CowArcStr::from(*name))),
       );
+      i += fallbacks.len();
     }
   }
 
@@ -1152,7 +2284,29 @@ fn is_font_property(property_id: &PropertyId) -> bool {
     | PropertyId::FontStretch
     | PropertyId::FontVariantCaps
     | PropertyId::LineHeight
-    | PropertyId::Font => true,
+    | PropertyId::Font
+    | PropertyId::FontVariantLigatures
+    | PropertyId::FontVariantAlternates
+    | PropertyId::FontVariantNumeric
+    | PropertyId::FontVariantEastAsian
+    | PropertyId::FontVariantPosition
+    | PropertyId::FontVariantEmoji
+    | PropertyId::FontVariant
+    | PropertyId::FontKerning
+    | PropertyId::FontOpticalSizing => true,
     _ => false,
   }
 }
+
+/// Returns whether the given property is reset by the font shorthand, but is not otherwise parsed.
+#[inline]
+fn is_unparsed_font_reset_property(name: &str) -> bool {
+  [
+    "font-feature-settings",
+    "font-language-override",
+    "font-size-adjust",
+    "font-variation-settings",
+  ]
+  .iter()
+  .any(|p| name.eq_ignore_ascii_case(p))
+}
@@ -4,12 +4,16 @@
 
 use crate::context::PropertyHandlerContext;
 use crate::declaration::{DeclarationBlock, DeclarationList};
-use crate::error::{Error, ErrorLocation, ParserError, PrinterError, PrinterErrorKind};
+use crate::error::{GridTemplateError, ParserError, PrinterError};
 use crate::macros::{define_shorthand, impl_shorthand};
 use crate::printer::Printer;
+use crate::properties::custom::{
+  self, CustomProperty, CustomPropertyName, Function, TokenList, TokenOrValue, UnparsedProperty,
+};
+use crate::properties::display::{Display, DisplayInside, DisplayOutside, DisplayPair};
 use crate::properties::{Property, PropertyId};
 use crate::traits::{Parse, PropertyHandler, Shorthand, ToCss};
-use crate::values::ident::CustomIdent;
+use crate::values::ident::{CustomIdent, Ident};
 use crate::values::length::serialize_dimension;
 use crate::values::number::{CSSInteger, CSSNumber};
 use crate::values::{ident::CustomIdentList, length::LengthPercentage};
@@ -430,21 +434,19 @@ fn write_ident<W>(name: &str, dest: &mut Printer<W>) -> Result<(), PrinterError>
 where
   W: std::fmt::Write,
 {
-  if let Some(css_module) = &mut dest.css_module {
-    if let Some(last) = css_module.config.pattern.segments.last() {
-      if !matches!(last, crate::css_modules::Segment::Local) {
-        return Err(Error {
-          kind: PrinterErrorKind::InvalidCssModulesPatternInGrid,
-          loc: Some(ErrorLocation {
-            filename: dest.filename().into(),
-            line: dest.loc.line,
-            column: dest.loc.column,
-          }),
-        });
+  let css_module_grid_enabled = dest.css_module.as_ref().map_or(false, |css_module| css_module.config.grid);
+  if css_module_grid_enabled {
+    // Browsers generate implicit `<area>-start` and `<area>-end` line names for each grid area,
+    // so only the area name is scoped in order for these to match with any naming pattern.
+    for suffix in ["-start", "-end"] {
+      if let Some(area) = name.strip_suffix(suffix) {
+        if !area.is_empty() {
+          return dest.write_ident_with_css_module_suffix(area, suffix);
+        }
       }
     }
   }
-  dest.write_ident(name)?;
+  dest.write_ident_with_css_module(name, css_module_grid_enabled)?;
   Ok(())
 }
 
@@ -971,6 +973,239 @@ impl<'i> GridTemplate<'i> {
   }
 }
 
+impl GridTemplateAreas {
+  /// Returns the number of rows in the grid template areas.
+  pub fn rows(&self) -> u32 {
+    match self {
+      GridTemplateAreas::None => 0,
+      GridTemplateAreas::Areas { columns, areas } => (areas.len() as u32).checked_div(*columns).unwrap_or(0),
+    }
+  }
+
+  /// Returns the number of columns in the grid template areas.
+  pub fn columns(&self) -> u32 {
+    match self {
+      GridTemplateAreas::None => 0,
+      GridTemplateAreas::Areas { columns, .. } => *columns,
+    }
+  }
+
+  /// Returns the name of the area in the given cell, or `None` if the cell is unnamed or out of bounds.
+  pub fn get(&self, row: u32, column: u32) -> Option<&str> {
+    match self {
+      GridTemplateAreas::Areas { columns, areas } if column < *columns => {
+        areas.get((row * columns + column) as usize).and_then(|area| area.as_deref())
+      }
+      _ => None,
+    }
+  }
+
+  /// Returns the rectangle occupied by the area with the given name, if it is defined and rectangular.
+  pub fn area_rect(&self, name: &str) -> Option<GridAreaRect> {
+    self.area_rects().ok()?.into_iter().find(|rect| rect.name == name)
+  }
+
+  /// Returns the rectangles occupied by each named area, in the order they first appear.
+  ///
+  /// Returns an error if an area does not form a single filled-in rectangle, which makes
+  /// the `grid-template-areas` value invalid.
+  pub fn area_rects(&self) -> Result<Vec<GridAreaRect>, GridTemplateError> {
+    let (columns, areas) = match self {
+      GridTemplateAreas::None => return Ok(Vec::new()),
+      GridTemplateAreas::Areas { columns, areas } => (*columns, areas),
+    };
+
+    let mut rects: Vec<GridAreaRect> = Vec::new();
+    let mut cells = Vec::new();
+    for (i, name) in areas.iter().enumerate() {
+      let name = match name {
+        Some(name) => name,
+        None => continue,
+      };
+
+      let (row, column) = (i as u32 / columns, i as u32 % columns);
+      if let Some(index) = rects.iter().position(|rect| rect.name == *name) {
+        let rect = &mut rects[index];
+        rect.row_start = rect.row_start.min(row);
+        rect.row_end = rect.row_end.max(row + 1);
+        rect.column_start = rect.column_start.min(column);
+        rect.column_end = rect.column_end.max(column + 1);
+        cells[index] += 1;
+      } else {
+        rects.push(GridAreaRect {
+          name: name.clone(),
+          row_start: row,
+          row_end: row + 1,
+          column_start: column,
+          column_end: column + 1,
+        });
+        cells.push(1);
+      }
+    }
+
+    // An area is rectangular if it fills its entire bounding box.
+    for (rect, cells) in rects.iter().zip(cells) {
+      if (rect.row_end - rect.row_start) * (rect.column_end - rect.column_start) != cells {
+        return Err(GridTemplateError::NonRectangularArea {
+          name: rect.name.clone(),
+        });
+      }
+    }
+
+    Ok(rects)
+  }
+}
+
+/// The rectangle occupied by a named grid area.
+///
+/// Rows and columns are zero-based track indices, and the end indices are exclusive.
+/// Add one to get the corresponding grid line numbers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridAreaRect {
+  /// The name of the grid area.
+  pub name: String,
+  /// The first row occupied by the area.
+  pub row_start: u32,
+  /// The row after the last row occupied by the area.
+  pub row_end: u32,
+  /// The first column occupied by the area.
+  pub column_start: u32,
+  /// The column after the last column occupied by the area.
+  pub column_end: u32,
+}
+
+/// A track in an explicit grid, with its size and the names of the lines on either side of it.
+///
+/// See [GridTemplateModel](GridTemplateModel).
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridTrack<'i> {
+  /// The names of the line before the track.
+  pub start_line_names: CustomIdentList<'i>,
+  /// The size of the track.
+  pub size: TrackSize,
+  /// The names of the line after the track.
+  pub end_line_names: CustomIdentList<'i>,
+}
+
+/// A structured model of an explicit grid, as defined by the `grid-template-rows`,
+/// `grid-template-columns`, and `grid-template-areas` properties.
+///
+/// `repeat()` functions are expanded into individual tracks, and the areas are resolved
+/// into rectangles. If the areas span more tracks than are explicitly sized, the extra
+/// tracks are sized `auto`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridTemplateModel<'i> {
+  /// The row tracks.
+  pub rows: Vec<GridTrack<'i>>,
+  /// The column tracks.
+  pub columns: Vec<GridTrack<'i>>,
+  /// The named grid areas.
+  pub areas: Vec<GridAreaRect>,
+}
+
+impl<'i> GridTemplateModel<'i> {
+  /// Builds a grid model from the values of the `grid-template-rows`, `grid-template-columns`,
+  /// and `grid-template-areas` properties.
+  ///
+  /// Returns an error if a track list uses an `auto-fill` or `auto-fit` repetition, whose number
+  /// of tracks depends on layout, or if a grid area is not rectangular.
+  pub fn new(
+    rows: &TrackSizing<'i>,
+    columns: &TrackSizing<'i>,
+    areas: &GridTemplateAreas,
+  ) -> Result<Self, GridTemplateError> {
+    let mut row_tracks = rows.tracks()?;
+    let mut column_tracks = columns.tracks()?;
+    pad_tracks(&mut row_tracks, areas.rows() as usize);
+    pad_tracks(&mut column_tracks, areas.columns() as usize);
+    Ok(GridTemplateModel {
+      rows: row_tracks,
+      columns: column_tracks,
+      areas: areas.area_rects()?,
+    })
+  }
+
+  /// Returns the rectangle occupied by the area with the given name, if any.
+  pub fn area(&self, name: &str) -> Option<&GridAreaRect> {
+    self.areas.iter().find(|area| area.name == name)
+  }
+}
+
+fn pad_tracks(tracks: &mut Vec<GridTrack>, len: usize) {
+  while tracks.len() < len {
+    tracks.push(GridTrack {
+      start_line_names: tracks.last().map(|track| track.end_line_names.clone()).unwrap_or_default(),
+      size: TrackSize::default(),
+      end_line_names: CustomIdentList::new(),
+    });
+  }
+}
+
+impl<'i> TrackSizing<'i> {
+  /// Returns the explicit tracks defined by the track sizing value, with `repeat()` functions expanded.
+  ///
+  /// Returns an error if the track list uses an `auto-fill` or `auto-fit` repetition.
+  pub fn tracks(&self) -> Result<Vec<GridTrack<'i>>, GridTemplateError> {
+    let list = match self {
+      TrackSizing::None => return Ok(Vec::new()),
+      TrackSizing::TrackList(list) => list,
+    };
+
+    // Line names between tracks, followed by the track sizes.
+    let mut lines: Vec<CustomIdentList<'i>> = vec![list.line_names.first().cloned().unwrap_or_default()];
+    let mut sizes = Vec::new();
+    for (i, item) in list.items.iter().enumerate() {
+      match item {
+        TrackListItem::TrackSize(size) => {
+          sizes.push(size.clone());
+          lines.push(CustomIdentList::new());
+        }
+        TrackListItem::TrackRepeat(repeat) => {
+          let count = match repeat.count {
+            RepeatCount::Number(count) => count.max(0),
+            RepeatCount::AutoFill | RepeatCount::AutoFit => return Err(GridTemplateError::AutoRepeat),
+          };
+          for _ in 0..count {
+            for (j, size) in repeat.track_sizes.iter().enumerate() {
+              if let Some(names) = repeat.line_names.get(j) {
+                lines.last_mut().unwrap().extend(names.iter().cloned());
+              }
+              sizes.push(size.clone());
+              lines.push(CustomIdentList::new());
+            }
+            if let Some(names) = repeat.line_names.get(repeat.track_sizes.len()) {
+              lines.last_mut().unwrap().extend(names.iter().cloned());
+            }
+          }
+        }
+      }
+
+      if let Some(names) = list.line_names.get(i + 1) {
+        lines.last_mut().unwrap().extend(names.iter().cloned());
+      }
+    }
+
+    Ok(
+      sizes
+        .into_iter()
+        .enumerate()
+        .map(|(i, size)| GridTrack {
+          start_line_names: lines[i].clone(),
+          size,
+          end_line_names: lines[i + 1].clone(),
+        })
+        .collect(),
+    )
+  }
+}
+
+impl<'i> GridTemplate<'i> {
+  /// Returns a structured model of the grid template. See [GridTemplateModel](GridTemplateModel).
+  pub fn model(&self) -> Result<GridTemplateModel<'i>, GridTemplateError> {
+    GridTemplateModel::new(&self.rows, &self.columns, &self.areas)
+  }
+}
+
 impl_shorthand! {
   GridTemplate(GridTemplate<'i>) {
     rows: [GridTemplateRows],
@@ -1014,10 +1249,11 @@ struct SerializedGridAutoFlow {
 impl From<GridAutoFlow> for SerializedGridAutoFlow {
   fn from(flow: GridAutoFlow) -> Self {
     Self {
-      direction: if flow.contains(GridAutoFlow::Row) {
-        AutoFlowDirection::Row
-      } else {
+      // `Row` is the empty set of flags, so check for `Column` instead.
+      direction: if flow.contains(GridAutoFlow::Column) {
         AutoFlowDirection::Column
+      } else {
+        AutoFlowDirection::Row
       },
       dense: flow.contains(GridAutoFlow::Dense),
     }
@@ -1835,3 +2071,242 @@ fn is_grid_property(property_id: &PropertyId) -> bool {
     _ => false,
   }
 }
+
+/// Inserts `-ms-grid` equivalents before grid declarations, for Internet Explorer and legacy Edge.
+/// Returns the names of the properties that could not be translated.
+pub(crate) fn add_ms_grid_fallbacks(decls: &mut DeclarationList) -> Vec<String> {
+  let mut unsupported = Vec::new();
+  let mut i = 0;
+  while i < decls.len() {
+    let mut fallbacks = Vec::new();
+    let mut translated = true;
+    match &decls[i] {
+      Property::Display(Display::Pair(DisplayPair {
+        outside,
+        inside: DisplayInside::Grid,
+        is_list_item: false,
+      })) => {
+        let value = match outside {
+          DisplayOutside::Inline => "-ms-inline-grid",
+          _ => "-ms-grid",
+        };
+        fallbacks.push(Property::Unparsed(UnparsedProperty {
+          property_id: PropertyId::Display,
+          value: TokenList(vec![custom::Token::Ident(value.into()).into()]),
+        }));
+      }
+      Property::GridTemplateColumns(columns) => {
+        translated = ms_grid_tracks("-ms-grid-columns", columns, &mut fallbacks);
+      }
+      Property::GridTemplateRows(rows) => {
+        translated = ms_grid_tracks("-ms-grid-rows", rows, &mut fallbacks);
+      }
+      Property::GridTemplate(GridTemplate { rows, columns, areas })
+      | Property::Grid(Grid {
+        rows, columns, areas, ..
+      }) => {
+        translated = *areas == GridTemplateAreas::None
+          && ms_grid_tracks("-ms-grid-rows", rows, &mut fallbacks)
+          && ms_grid_tracks("-ms-grid-columns", columns, &mut fallbacks);
+      }
+      Property::GridTemplateAreas(areas) => translated = *areas == GridTemplateAreas::None,
+      Property::GridAutoFlow(..) | Property::GridAutoRows(..) | Property::GridAutoColumns(..) => {
+        translated = false
+      }
+      Property::GridRow(GridRow { start, end }) => {
+        translated = ms_grid_placement(MS_GRID_ROW, start, end, &mut fallbacks);
+      }
+      Property::GridColumn(GridColumn { start, end }) => {
+        translated = ms_grid_placement(MS_GRID_COLUMN, start, end, &mut fallbacks);
+      }
+      Property::GridArea(area) => {
+        translated = ms_grid_placement(MS_GRID_ROW, &area.row_start, &area.row_end, &mut fallbacks)
+          && ms_grid_placement(MS_GRID_COLUMN, &area.column_start, &area.column_end, &mut fallbacks);
+      }
+      Property::GridRowStart(start) => {
+        translated = ms_grid_placement(MS_GRID_ROW, start, &GridLine::Auto, &mut fallbacks);
+      }
+      Property::GridColumnStart(start) => {
+        translated = ms_grid_placement(MS_GRID_COLUMN, start, &GridLine::Auto, &mut fallbacks);
+      }
+      Property::GridRowEnd(end) | Property::GridColumnEnd(end) => translated = *end == GridLine::Auto,
+      _ => {}
+    }
+
+    if !translated {
+      unsupported.push(decls[i].property_id().name().to_owned());
+      fallbacks.clear();
+    }
+
+    let len = fallbacks.len();
+    decls.splice(i..i, fallbacks);
+    i += len + 1;
+  }
+
+  unsupported
+}
+
+fn ms_grid_property<'i>(name: &'static str, value: Vec<TokenOrValue<'i>>) -> Property<'i> {
+  Property::Custom(CustomProperty {
+    name: CustomPropertyName::Unknown(Ident(name.into())),
+    value: TokenList(value),
+    typed_value: None,
+  })
+}
+
+/// Translates a track list to `-ms-grid-rows` or `-ms-grid-columns`. Line names are dropped,
+/// and `repeat()` is converted to the `(<track-size>+)[<count>]` syntax.
+fn ms_grid_tracks<'i>(name: &'static str, tracks: &TrackSizing<'i>, dest: &mut Vec<Property<'i>>) -> bool {
+  let list = match tracks {
+    TrackSizing::None => return true,
+    TrackSizing::TrackList(list) => list,
+  };
+
+  let mut tokens = Vec::new();
+  for item in &list.items {
+    if !tokens.is_empty() {
+      tokens.push(custom::Token::WhiteSpace(" ".into()).into());
+    }
+
+    match item {
+      TrackListItem::TrackSize(size) => {
+        if !ms_grid_track_size(size, &mut tokens) {
+          return false;
+        }
+      }
+      TrackListItem::TrackRepeat(TrackRepeat {
+        count: RepeatCount::Number(count),
+        track_sizes,
+        ..
+      }) => {
+        tokens.push(custom::Token::ParenthesisBlock.into());
+        for (i, size) in track_sizes.iter().enumerate() {
+          if i > 0 {
+            tokens.push(custom::Token::WhiteSpace(" ".into()).into());
+          }
+          if !ms_grid_track_size(size, &mut tokens) {
+            return false;
+          }
+        }
+        tokens.push(custom::Token::CloseParenthesis.into());
+        tokens.push(custom::Token::SquareBracketBlock.into());
+        tokens.push(
+          custom::Token::Number {
+            has_sign: false,
+            value: *count as f32,
+            int_value: Some(*count),
+          }
+          .into(),
+        );
+        tokens.push(custom::Token::CloseSquareBracket.into());
+      }
+      TrackListItem::TrackRepeat(..) => return false,
+    }
+  }
+
+  dest.push(ms_grid_property(name, tokens));
+  true
+}
+
+fn ms_grid_track_size(size: &TrackSize, dest: &mut Vec<TokenOrValue<'_>>) -> bool {
+  match size {
+    TrackSize::TrackBreadth(breadth) => ms_grid_track_breadth(breadth, dest),
+    TrackSize::MinMax { min, max } => {
+      let mut arguments = Vec::new();
+      if !ms_grid_track_breadth(min, &mut arguments) {
+        return false;
+      }
+      arguments.push(custom::Token::Comma.into());
+      if !ms_grid_track_breadth(max, &mut arguments) {
+        return false;
+      }
+      dest.push(TokenOrValue::Function(Function {
+        name: Ident("minmax".into()),
+        arguments: TokenList(arguments),
+      }));
+      true
+    }
+    TrackSize::FitContent(..) => false,
+  }
+}
+
+fn ms_grid_track_breadth(breadth: &TrackBreadth, dest: &mut Vec<TokenOrValue<'_>>) -> bool {
+  let token = match breadth {
+    TrackBreadth::Length(LengthPercentage::Dimension(length)) => TokenOrValue::Length(length.clone()),
+    TrackBreadth::Length(LengthPercentage::Percentage(percentage)) => {
+      let value = percentage.0 * 100.0;
+      custom::Token::Percentage {
+        has_sign: false,
+        unit_value: percentage.0,
+        int_value: if value.fract() == 0.0 { Some(value as i32) } else { None },
+      }
+      .into()
+    }
+    TrackBreadth::Length(LengthPercentage::Calc(..)) => return false,
+    TrackBreadth::Flex(flex) => custom::Token::Dimension {
+      has_sign: false,
+      value: *flex,
+      int_value: None,
+      unit: "fr".into(),
+    }
+    .into(),
+    TrackBreadth::MinContent => custom::Token::Ident("min-content".into()).into(),
+    TrackBreadth::MaxContent => custom::Token::Ident("max-content".into()).into(),
+    TrackBreadth::Auto => custom::Token::Ident("auto".into()).into(),
+  };
+
+  dest.push(token);
+  true
+}
+
+const MS_GRID_ROW: (&str, &str) = ("-ms-grid-row", "-ms-grid-row-span");
+const MS_GRID_COLUMN: (&str, &str) = ("-ms-grid-column", "-ms-grid-column-span");
+
+/// Translates a pair of grid lines to `-ms-grid-row` and `-ms-grid-row-span`, or the column equivalents.
+/// Only positive line numbers and spans without line names are supported.
+fn ms_grid_placement(
+  (line_name, span_name): (&'static str, &'static str),
+  start: &GridLine,
+  end: &GridLine,
+  dest: &mut Vec<Property<'_>>,
+) -> bool {
+  let (line, span) = match (start, end) {
+    (GridLine::Auto, GridLine::Auto) => return true,
+    (GridLine::Line { index, name: None }, GridLine::Auto) if *index > 0 => (*index, 1),
+    (
+      GridLine::Line { index, name: None },
+      GridLine::Span {
+        index: span,
+        name: None,
+      },
+    ) if *index > 0 => (*index, *span),
+    (GridLine::Line { index, name: None }, GridLine::Line { index: end, name: None })
+      if *end > *index && *index > 0 =>
+    {
+      (*index, *end - *index)
+    }
+    (
+      GridLine::Span {
+        index: span,
+        name: None,
+      },
+      GridLine::Line { index: end, name: None },
+    ) if *end - *span > 0 => (*end - *span, *span),
+    _ => return false,
+  };
+
+  let number = |value: CSSInteger| {
+    vec![custom::Token::Number {
+      has_sign: false,
+      value: value as f32,
+      int_value: Some(value),
+    }
+    .into()]
+  };
+
+  dest.push(ms_grid_property(line_name, number(line)));
+  if span > 1 {
+    dest.push(ms_grid_property(span_name, number(span)));
+  }
+  true
+}
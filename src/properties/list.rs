@@ -248,10 +248,16 @@ impl ToCss for CounterStyle<'_> {
     match self {
       CounterStyle::Predefined(style) => style.to_css(dest),
       CounterStyle::Name(name) => {
-        if let Some(css_module) = &mut dest.css_module {
-          css_module.reference(&name.0, dest.loc.source_index)
+        let css_module_counter_style_enabled = dest
+          .css_module
+          .as_ref()
+          .map_or(false, |css_module| css_module.config.counter_style);
+        if css_module_counter_style_enabled {
+          if let Some(css_module) = &mut dest.css_module {
+            css_module.reference(&name.0, dest.loc.source_index)
+          }
         }
-        name.to_css(dest)
+        name.to_css_with_options(dest, css_module_counter_style_enabled)
       }
       CounterStyle::Symbols { system: t, symbols } => {
         dest.write_str("symbols(")?;
@@ -109,10 +109,12 @@ pub mod grid;
 pub mod list;
 pub(crate) mod margin_padding;
 pub mod masking;
+pub mod motion;
 pub mod outline;
 pub mod overflow;
 pub mod position;
 pub(crate) mod prefix_handler;
+pub mod shapes;
 pub mod size;
 pub mod svg;
 pub mod text;
@@ -158,8 +160,10 @@ use grid::*;
 use list::*;
 use margin_padding::*;
 use masking::*;
+use motion::*;
 use outline::*;
 use overflow::*;
+use shapes::*;
 use size::*;
 use smallvec::{smallvec, SmallVec};
 #[cfg(feature = "into_owned")]
@@ -322,7 +326,7 @@ macro_rules! define_properties {
         }
       }
 
-      fn with_prefix(&self, prefix: VendorPrefix) -> PropertyId<'i> {
+      pub(crate) fn with_prefix(&self, prefix: VendorPrefix) -> PropertyId<'i> {
         use PropertyId::*;
         match self {
           $(
@@ -344,6 +348,19 @@ macro_rules! define_properties {
         }
       }
 
+      /// Returns all vendor prefixes the property can be used with, including
+      /// `VendorPrefix::None` if it can be used unprefixed.
+      pub(crate) fn allowed_prefixes(&self) -> VendorPrefix {
+        use PropertyId::*;
+        match self {
+          $(
+            $(#[$meta])*
+            $property$((vp_name!($vp, _p)))? => get_allowed_prefixes!($($unprefixed)?) $(| VendorPrefix::$prefix)*,
+          )+
+          _ => VendorPrefix::None
+        }
+      }
+
       pub(crate) fn set_prefixes_for_targets(&mut self, targets: Targets) {
         match self {
           $(
@@ -678,8 +695,13 @@ macro_rules! define_properties {
       /// Parses a CSS property by name.
       pub fn parse<'t>(property_id: PropertyId<'i>, input: &mut Parser<'i, 't>, options: &ParserOptions<'_, 'i>) -> Result<Property<'i>, ParseError<'i, ParserError<'i>>> {
         let state = input.state();
+        let has_disabled_drafts = has_disabled_color_syntax(input, options.disabled_drafts);
+        input.reset(&state);
 
         match property_id {
+          PropertyId::Custom(name) => return Ok(Property::Custom(CustomProperty::parse(name, input, options)?)),
+          // Draft syntax that was disabled is preserved as-is in an unparsed property.
+          _ if has_disabled_drafts => {}
           $(
             $(#[$meta])*
             PropertyId::$property$((vp_name!($vp, prefix)))? $(if options.$condition.is_some())? => {
@@ -690,7 +712,6 @@ macro_rules! define_properties {
               }
             },
           )+
-          PropertyId::Custom(name) => return Ok(Property::Custom(CustomProperty::parse(name, input, options)?)),
           _ => {}
         };
 
@@ -752,6 +773,13 @@ macro_rules! define_properties {
       pub fn value_to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError> where W: std::fmt::Write {
         use Property::*;
 
+        // Scoping of view-transition-name can be disabled independently of other custom idents.
+        if let ViewTransitionName(name) = self {
+          let css_module_view_transition_name_enabled =
+            dest.css_module.as_ref().map_or(false, |css_module| css_module.config.view_transition_name);
+          return name.to_css_with_options(dest, css_module_view_transition_name_enabled);
+        }
+
         match self {
           $(
             $(#[$meta])*
@@ -833,7 +861,23 @@ macro_rules! define_properties {
             return Ok(())
           }
         };
+        // Static fallback for browsers that don't support math functions.
+        let math_fallback =
+          dest.needs_math_fallback() && dest.has_static_math_fallback(|dest| self.value_to_css(dest))?;
+
         for p in prefix {
+          if math_fallback {
+            start!();
+            p.to_css(dest)?;
+            dest.write_str(name)?;
+            dest.delim(':', false)?;
+            dest.static_math = true;
+            let res = self.value_to_css(dest);
+            dest.static_math = false;
+            res?;
+            write_important!();
+          }
+
           start!();
           p.to_css(dest)?;
           dest.write_str(name)?;
@@ -896,6 +940,54 @@ macro_rules! define_properties {
         )+
         Err(())
       }
+
+      /// Expands a shorthand property into its longhand properties, e.g. `margin` into `margin-top`,
+      /// `margin-right`, `margin-bottom`, and `margin-left`. Other properties, including shorthands
+      /// containing `var()` references that cannot be expanded until computed, are returned as is.
+      pub fn expand(&self) -> Vec<Property<'i>> {
+        if matches!(self, Property::Unparsed(..) | Property::Custom(..)) {
+          return vec![self.clone()]
+        }
+
+        match self.property_id().longhands() {
+          Some(longhands) => longhands
+            .iter()
+            .filter_map(|property_id| self.longhand(property_id))
+            .flat_map(|longhand| longhand.expand())
+            .collect(),
+          None => vec![self.clone()]
+        }
+      }
+
+      /// Combines longhand properties into the given shorthand, e.g. `margin-top`, `margin-right`,
+      /// `margin-bottom`, and `margin-left` into `margin`. The longhands may be in any order, and
+      /// may include other shorthands which are expanded. Returns `None` if a longhand is missing,
+      /// or the values cannot be represented by the shorthand.
+      pub fn from_longhands(property_id: &PropertyId, longhands: &[Property<'i>]) -> Option<Property<'i>> {
+        let decls = DeclarationBlock {
+          declarations: longhands.to_vec(),
+          important_declarations: vec![],
+        };
+        property_id.shorthand_value(&decls).map(|(property, _)| property)
+      }
+
+      /// Returns whether this property is semantically equivalent to another, i.e. it is the same
+      /// property, including vendor prefix, and the values have the same minified serialization,
+      /// e.g. `color: #ff0000` and `color: red`.
+      pub fn semantic_eq(&self, other: &Property) -> bool {
+        if self.property_id() != other.property_id() {
+          return false
+        }
+
+        let options = || PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        };
+        match (self.value_to_css_string(options()), other.value_to_css_string(options())) {
+          (Ok(a), Ok(b)) => a == b,
+          _ => false
+        }
+      }
     }
 
     #[cfg(feature = "serde")]
@@ -1429,6 +1521,15 @@ define_properties! {
   "font-style": FontStyle(FontStyle),
   "font-variant-caps": FontVariantCaps(FontVariantCaps),
   "line-height": LineHeight(LineHeight),
+  "font-variant-ligatures": FontVariantLigatures(FontVariantLigatures),
+  "font-variant-alternates": FontVariantAlternates(FontVariantAlternates<'i>),
+  "font-variant-numeric": FontVariantNumeric(FontVariantNumeric),
+  "font-variant-east-asian": FontVariantEastAsian(FontVariantEastAsian),
+  "font-variant-position": FontVariantPosition(FontVariantPosition),
+  "font-variant-emoji": FontVariantEmoji(FontVariantEmoji),
+  "font-variant": FontVariant(FontVariant<'i>) shorthand: true,
+  "font-kerning": FontKerning(FontKerning),
+  "font-optical-sizing": FontOpticalSizing(FontOpticalSizing),
   "font": Font(Font<'i>) shorthand: true,
   "vertical-align": VerticalAlign(VerticalAlign),
   "font-palette": FontPalette(DashedIdentReference<'i>),
@@ -1492,6 +1593,7 @@ define_properties! {
 
   // https://w3c.github.io/csswg-drafts/css-size-adjust/
   "text-size-adjust": TextSizeAdjust(TextSizeAdjust, VendorPrefix) / WebKit / Moz / Ms,
+  "initial-letter": InitialLetter(InitialLetter, VendorPrefix) / WebKit,
 
   // https://www.w3.org/TR/css-break-3/
   "box-decoration-break": BoxDecorationBreak(BoxDecorationBreak, VendorPrefix) / WebKit,
@@ -1572,6 +1674,12 @@ define_properties! {
   "mask-box-image-outset": WebKitMaskBoxImageOutset(Rect<LengthOrNumber>, VendorPrefix) / WebKit unprefixed: false,
   "mask-box-image-repeat": WebKitMaskBoxImageRepeat(BorderImageRepeat, VendorPrefix) / WebKit unprefixed: false,
 
+  // https://drafts.fxtf.org/motion-1/
+  "offset-path": OffsetPath(OffsetPath<'i>),
+
+  // https://www.w3.org/TR/css-shapes-1/
+  "shape-outside": ShapeOutside(ShapeOutside<'i>),
+
   // https://drafts.fxtf.org/filter-effects-1/
   "filter": Filter(FilterList<'i>, VendorPrefix) / WebKit,
   "backdrop-filter": BackdropFilter(FilterList<'i>, VendorPrefix) / WebKit,
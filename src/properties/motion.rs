@@ -0,0 +1,281 @@
+//! CSS properties related to motion paths.
+
+use crate::error::{ParserError, PrinterError};
+use crate::macros::enum_property;
+use crate::printer::Printer;
+use crate::traits::{Parse, ToCss};
+use crate::values::{angle::Angle, position::Position, shape::BasicShape, url::Url};
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+/// A value for the [offset-path](https://drafts.fxtf.org/motion-1/#offset-path-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum OffsetPath<'i> {
+  /// No offset path.
+  None,
+  /// A path, positioned according to the reference box.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Path {
+    /// The path.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    path: Box<MotionPath<'i>>,
+    /// A reference box that the path is positioned according to.
+    reference_box: CoordBox,
+  },
+  /// The shape of a reference box.
+  #[cfg_attr(feature = "serde", serde(with = "crate::serialization::ValueWrapper::<CoordBox>"))]
+  Box(CoordBox),
+}
+
+/// An [`<offset-path>`](https://drafts.fxtf.org/motion-1/#typedef-offset-path) value, used within
+/// the `offset-path` property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum MotionPath<'i> {
+  /// A ray from the initial position of the element.
+  Ray(Ray),
+  /// A url reference to an SVG shape element.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  Url(Url<'i>),
+  /// A basic shape.
+  Shape(BasicShape),
+}
+
+/// A [`ray()`](https://drafts.fxtf.org/motion-1/#ray-function) function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Ray {
+  /// The direction of the ray.
+  pub angle: Angle,
+  /// The length of the ray.
+  pub size: RaySize,
+  /// Whether the length of the ray is reduced so that the element stays within the path.
+  pub contain: bool,
+  /// The starting point of the ray. If omitted, the `offset-position` property is used.
+  pub position: Option<Position>,
+}
+
+enum_property! {
+  /// A [`<ray-size>`](https://drafts.fxtf.org/motion-1/#typedef-ray-size) value, which
+  /// determines the length of a [ray](Ray).
+  pub enum RaySize {
+    /// The distance to the closest side of the containing block.
+    "closest-side": ClosestSide,
+    /// The distance to the closest corner of the containing block.
+    "closest-corner": ClosestCorner,
+    /// The distance to the farthest side of the containing block.
+    "farthest-side": FarthestSide,
+    /// The distance to the farthest corner of the containing block.
+    "farthest-corner": FarthestCorner,
+    /// The distance to the point where the ray intersects the containing block.
+    "sides": Sides,
+  }
+}
+
+impl Default for RaySize {
+  fn default() -> RaySize {
+    RaySize::ClosestSide
+  }
+}
+
+enum_property! {
+  /// A [`<coord-box>`](https://drafts.csswg.org/css-box-4/#typedef-coord-box) value
+  /// as used in the `offset-path` property.
+  pub enum CoordBox {
+    /// The content box.
+    "content-box": ContentBox,
+    /// The padding box.
+    "padding-box": PaddingBox,
+    /// The border box.
+    "border-box": BorderBox,
+    /// The object bounding box.
+    "fill-box": FillBox,
+    /// The stroke bounding box.
+    "stroke-box": StrokeBox,
+    /// The nearest SVG viewport.
+    "view-box": ViewBox,
+  }
+}
+
+impl Default for CoordBox {
+  fn default() -> CoordBox {
+    CoordBox::BorderBox
+  }
+}
+
+impl<'i> Parse<'i> for OffsetPath<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(OffsetPath::None);
+    }
+
+    let mut path = None;
+    let mut reference_box = None;
+    loop {
+      if path.is_none() {
+        if let Ok(value) = input.try_parse(MotionPath::parse) {
+          path = Some(value);
+          continue;
+        }
+      }
+
+      if reference_box.is_none() {
+        if let Ok(value) = input.try_parse(CoordBox::parse) {
+          reference_box = Some(value);
+          continue;
+        }
+      }
+
+      break;
+    }
+
+    match (path, reference_box) {
+      (Some(path), reference_box) => Ok(OffsetPath::Path {
+        path: Box::new(path),
+        reference_box: reference_box.unwrap_or_default(),
+      }),
+      (None, Some(reference_box)) => Ok(OffsetPath::Box(reference_box)),
+      (None, None) => Err(input.new_custom_error(ParserError::InvalidValue)),
+    }
+  }
+}
+
+impl<'i> ToCss for OffsetPath<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      OffsetPath::None => dest.write_str("none"),
+      OffsetPath::Path { path, reference_box } => {
+        path.to_css(dest)?;
+        if *reference_box != CoordBox::default() {
+          dest.write_char(' ')?;
+          reference_box.to_css(dest)?;
+        }
+        Ok(())
+      }
+      OffsetPath::Box(reference_box) => reference_box.to_css(dest),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for MotionPath<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(url) = input.try_parse(Url::parse) {
+      return Ok(MotionPath::Url(url));
+    }
+
+    if let Ok(ray) = input.try_parse(|input| {
+      input.expect_function_matching("ray")?;
+      input.parse_nested_block(Ray::parse)
+    }) {
+      return Ok(MotionPath::Ray(ray));
+    }
+
+    Ok(MotionPath::Shape(BasicShape::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for MotionPath<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      MotionPath::Ray(ray) => {
+        dest.write_str("ray(")?;
+        ray.to_css(dest)?;
+        dest.write_char(')')
+      }
+      MotionPath::Url(url) => url.to_css(dest),
+      MotionPath::Shape(shape) => shape.to_css(dest),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for Ray {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut angle = None;
+    let mut size = None;
+    let mut contain = false;
+    let mut position = None;
+    loop {
+      if angle.is_none() {
+        if let Ok(value) = input.try_parse(Angle::parse) {
+          angle = Some(value);
+          continue;
+        }
+      }
+
+      if size.is_none() {
+        if let Ok(value) = input.try_parse(RaySize::parse) {
+          size = Some(value);
+          continue;
+        }
+      }
+
+      if !contain && input.try_parse(|input| input.expect_ident_matching("contain")).is_ok() {
+        contain = true;
+        continue;
+      }
+
+      if position.is_none() && input.try_parse(|input| input.expect_ident_matching("at")).is_ok() {
+        position = Some(Position::parse(input)?);
+        continue;
+      }
+
+      break;
+    }
+
+    match angle {
+      Some(angle) => Ok(Ray {
+        angle,
+        size: size.unwrap_or_default(),
+        contain,
+        position,
+      }),
+      None => Err(input.new_custom_error(ParserError::InvalidValue)),
+    }
+  }
+}
+
+impl ToCss for Ray {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.angle.to_css(dest)?;
+    if self.size != RaySize::default() {
+      dest.write_char(' ')?;
+      self.size.to_css(dest)?;
+    }
+    if self.contain {
+      dest.write_str(" contain")?;
+    }
+    if let Some(position) = &self.position {
+      dest.write_str(" at ")?;
+      position.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
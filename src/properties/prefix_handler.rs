@@ -74,6 +74,7 @@ define_prefixes! {
   ClipPath,
   BoxDecorationBreak,
   TextSizeAdjust,
+  InitialLetter,
 }
 
 macro_rules! define_fallbacks {
@@ -130,7 +131,8 @@ macro_rules! define_fallbacks {
                   condition,
                   Property::Custom(CustomProperty {
                     name: custom.name.clone(),
-                    value: fallback
+                    value: fallback,
+                    typed_value: None
                   })
                 );
               }
@@ -0,0 +1,115 @@
+//! CSS properties related to shapes.
+
+use crate::error::{ParserError, PrinterError};
+use crate::macros::enum_property;
+use crate::printer::Printer;
+use crate::traits::{Parse, ToCss};
+use crate::values::{image::Image, shape::BasicShape};
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+/// A value for the [shape-outside](https://www.w3.org/TR/css-shapes-1/#shape-outside-property) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ShapeOutside<'i> {
+  /// The float area is unaffected.
+  None,
+  /// A basic shape, positioned according to the reference box.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Shape {
+    /// A basic shape.
+    shape: Box<BasicShape>,
+    /// A reference box that the shape is positioned according to.
+    reference_box: ShapeBox,
+  },
+  /// The shape of a reference box.
+  #[cfg_attr(feature = "serde", serde(with = "crate::serialization::ValueWrapper::<ShapeBox>"))]
+  Box(ShapeBox),
+  /// A shape extracted from the alpha channel of an image.
+  #[cfg_attr(
+    feature = "serde",
+    serde(borrow, with = "crate::serialization::ValueWrapper::<Image>")
+  )]
+  Image(Image<'i>),
+}
+
+enum_property! {
+  /// A [`<shape-box>`](https://www.w3.org/TR/css-shapes-1/#typedef-shape-box) value
+  /// as used in the `shape-outside` property.
+  pub enum ShapeBox {
+    /// The margin box.
+    "margin-box": MarginBox,
+    /// The border box.
+    "border-box": BorderBox,
+    /// The padding box.
+    "padding-box": PaddingBox,
+    /// The content box.
+    "content-box": ContentBox,
+  }
+}
+
+impl Default for ShapeBox {
+  fn default() -> ShapeBox {
+    ShapeBox::MarginBox
+  }
+}
+
+impl<'i> Parse<'i> for ShapeOutside<'i> {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(ShapeOutside::None);
+    }
+
+    if let Ok(shape) = input.try_parse(BasicShape::parse) {
+      let b = input.try_parse(ShapeBox::parse).unwrap_or_default();
+      return Ok(ShapeOutside::Shape {
+        shape: Box::new(shape),
+        reference_box: b,
+      });
+    }
+
+    if let Ok(b) = input.try_parse(ShapeBox::parse) {
+      if let Ok(shape) = input.try_parse(BasicShape::parse) {
+        return Ok(ShapeOutside::Shape {
+          shape: Box::new(shape),
+          reference_box: b,
+        });
+      }
+      return Ok(ShapeOutside::Box(b));
+    }
+
+    Ok(ShapeOutside::Image(Image::parse(input)?))
+  }
+}
+
+impl<'i> ToCss for ShapeOutside<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      ShapeOutside::None => dest.write_str("none"),
+      ShapeOutside::Shape {
+        shape,
+        reference_box: b,
+      } => {
+        shape.to_css(dest)?;
+        if *b != ShapeBox::default() {
+          dest.write_char(' ')?;
+          b.to_css(dest)?;
+        }
+        Ok(())
+      }
+      ShapeOutside::Box(b) => b.to_css(dest),
+      ShapeOutside::Image(image) => image.to_css(dest),
+    }
+  }
+}
@@ -8,7 +8,8 @@ use crate::logical::PropertyCategory;
 use crate::macros::{enum_property, property_bitflags};
 use crate::printer::Printer;
 use crate::properties::{Property, PropertyId};
-use crate::traits::{IsCompatible, Parse, PropertyHandler, ToCss};
+use crate::targets::Targets;
+use crate::traits::{FallbackValues, IsCompatible, Parse, PropertyHandler, ToCss, ViewportFallback};
 use crate::values::length::LengthPercentage;
 use crate::values::ratio::Ratio;
 use crate::vendor_prefix::VendorPrefix;
@@ -366,6 +367,31 @@ property_bitflags! {
   }
 }
 
+macro_rules! impl_viewport_fallback {
+  ($t: ident) => {
+    impl FallbackValues for $t {
+      fn get_fallbacks(&mut self, targets: Targets) -> Vec<Self> {
+        let mut fallback = self.clone();
+        let replaced = match (&mut fallback, targets.browsers) {
+          ($t::LengthPercentage(l) | $t::FitContentFunction(l), Some(browsers)) => {
+            l.replace_viewport_units(browsers)
+          }
+          _ => false,
+        };
+
+        if replaced {
+          vec![fallback]
+        } else {
+          vec![]
+        }
+      }
+    }
+  };
+}
+
+impl_viewport_fallback!(Size);
+impl_viewport_fallback!(MaxSize);
+
 #[derive(Default)]
 pub(crate) struct SizeHandler {
   width: Option<Size>,
@@ -503,6 +529,13 @@ impl SizeHandler {
             $size::FitContent(VendorPrefix::None) => prefix!($prop, $size, FitContent),
             _ => {}
           }
+          // Add a fallback for viewport units, unless a previous value was already flushed as a fallback.
+          let mut val = val;
+          if !self.flushed_properties.contains(SizeProperty::$prop) {
+            for fallback in val.get_fallbacks(context.targets) {
+              dest.push(Property::$prop(fallback));
+            }
+          }
           dest.push(Property::$prop(val.clone()));
           self.flushed_properties.insert(SizeProperty::$prop);
         }
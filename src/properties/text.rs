@@ -15,6 +15,7 @@ use crate::traits::{FallbackValues, IsCompatible, Parse, PropertyHandler, Shorth
 use crate::values::calc::{Calc, MathFunction};
 use crate::values::color::{ColorFallbackKind, CssColor};
 use crate::values::length::{Length, LengthPercentage, LengthValue};
+use crate::values::number::{CSSInteger, CSSNumber};
 use crate::values::percentage::Percentage;
 use crate::values::string::CSSString;
 use crate::vendor_prefix::VendorPrefix;
@@ -512,6 +513,128 @@ impl ToCss for TextSizeAdjust {
   }
 }
 
+/// A value for the [initial-letter](https://drafts.csswg.org/css-inline-3/#sizing-drop-initials) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub enum InitialLetter {
+  /// No special initial-letter effect.
+  Normal,
+  /// An initial letter spanning a number of lines.
+  Size {
+    /// The number of lines the initial letter occupies.
+    size: CSSNumber,
+    /// The number of lines the initial letter sinks.
+    sink: Option<InitialLetterSink>,
+  },
+}
+
+/// The sink value of the [initial-letter](https://drafts.csswg.org/css-inline-3/#sizing-drop-initials) property.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", content = "value", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub enum InitialLetterSink {
+  /// An explicit number of lines to sink.
+  Lines(CSSInteger),
+  /// Sink by the size, rounded down.
+  Drop,
+  /// Sink by one line, raising the initial letter.
+  Raise,
+}
+
+impl<'i> Parse<'i> for InitialLetterSink {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    if let Ok(lines) = input.try_parse(CSSInteger::parse) {
+      if lines < 1 {
+        return Err(location.new_custom_error(ParserError::InvalidValue));
+      }
+      return Ok(InitialLetterSink::Lines(lines));
+    }
+
+    let ident = input.expect_ident_cloned()?;
+    match_ignore_ascii_case! {&*ident,
+      "drop" => Ok(InitialLetterSink::Drop),
+      "raise" => Ok(InitialLetterSink::Raise),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+impl ToCss for InitialLetterSink {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      InitialLetterSink::Lines(lines) => lines.to_css(dest),
+      InitialLetterSink::Drop => dest.write_str("drop"),
+      InitialLetterSink::Raise => dest.write_str("raise"),
+    }
+  }
+}
+
+impl<'i> Parse<'i> for InitialLetter {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(InitialLetter::Normal);
+    }
+
+    // The drop and raise keywords may come before the size.
+    let location = input.current_source_location();
+    let mut sink = input
+      .try_parse(|input| match InitialLetterSink::parse(input)? {
+        InitialLetterSink::Lines(..) => Err(input.new_custom_error(ParserError::InvalidValue)),
+        keyword => Ok(keyword),
+      })
+      .ok();
+    let size = CSSNumber::parse(input)?;
+    if size < 1.0 {
+      return Err(location.new_custom_error(ParserError::InvalidValue));
+    }
+
+    if sink.is_none() {
+      sink = input.try_parse(InitialLetterSink::parse).ok();
+    }
+
+    Ok(InitialLetter::Size { size, sink })
+  }
+}
+
+impl ToCss for InitialLetter {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    match self {
+      InitialLetter::Normal => dest.write_str("normal"),
+      InitialLetter::Size { size, sink } => {
+        size.to_css(dest)?;
+        // `drop` is the default sink.
+        match sink {
+          None | Some(InitialLetterSink::Drop) => Ok(()),
+          Some(sink) => {
+            dest.write_str(" ")?;
+            sink.to_css(dest)
+          }
+        }
+      }
+    }
+  }
+}
+
 bitflags! {
   /// A value for the [text-decoration-line](https://www.w3.org/TR/2020/WD-css-text-decor-4-20200506/#text-decoration-line-property) property.
   ///
@@ -3,7 +3,7 @@
 use super::{Property, PropertyId};
 use crate::context::PropertyHandlerContext;
 use crate::declaration::DeclarationList;
-use crate::error::{ParserError, PrinterError};
+use crate::error::{ParserError, PrinterError, UnitConversionError};
 use crate::macros::enum_property;
 use crate::prefixes::Feature;
 use crate::printer::Printer;
@@ -11,7 +11,7 @@ use crate::stylesheet::PrinterOptions;
 use crate::traits::{Parse, PropertyHandler, ToCss, Zero};
 use crate::values::{
   angle::Angle,
-  length::{Length, LengthPercentage},
+  length::{Length, LengthPercentage, UnitContext},
   percentage::NumberOrPercentage,
 };
 use crate::vendor_prefix::VendorPrefix;
@@ -143,6 +143,46 @@ impl TransformList {
     }
     Some(matrix)
   }
+
+  /// Composes the transform list into a 3D matrix, resolving percentages and relative lengths using
+  /// the given context.
+  ///
+  /// Unlike [to_matrix](TransformList::to_matrix), this accepts angles of any size.
+  pub fn to_matrix_with_context(&self, context: &TransformContext) -> Result<Matrix3d<f32>, UnitConversionError> {
+    let mut matrix = Matrix3d::identity();
+    for transform in &self.0 {
+      matrix = transform.to_matrix_with_context(context)?.multiply(&matrix);
+    }
+    Ok(matrix)
+  }
+}
+
+/// Context used to resolve a [TransformList](TransformList) to a matrix.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransformContext {
+  /// The width of the reference box, which percentages along the X axis resolve against.
+  pub reference_width: Option<f32>,
+  /// The height of the reference box, which percentages along the Y axis resolve against.
+  pub reference_height: Option<f32>,
+  /// Context used to resolve relative lengths.
+  pub units: UnitContext,
+}
+
+impl TransformContext {
+  fn resolve(&self, value: &LengthPercentage, basis: Option<f32>) -> Result<f32, UnitConversionError> {
+    value.to_px_with_context(&UnitContext {
+      percentage_basis: basis,
+      ..self.units.clone()
+    })
+  }
+
+  fn resolve_x(&self, value: &LengthPercentage) -> Result<f32, UnitConversionError> {
+    self.resolve(value, self.reference_width)
+  }
+
+  fn resolve_y(&self, value: &LengthPercentage) -> Result<f32, UnitConversionError> {
+    self.resolve(value, self.reference_height)
+  }
 }
 
 /// An individual [transform function](https://www.w3.org/TR/2019/CR-css-transforms-1-20190214/#two-d-transform-functions).
@@ -840,6 +880,255 @@ impl Matrix3d<f32> {
 
     Some(TransformList(transforms))
   }
+
+  /// Decomposes the matrix into its translation, scale, skew, perspective, and rotation components,
+  /// as defined by the [CSS Transforms](https://drafts.csswg.org/css-transforms-2/#decomposing-a-3d-matrix) spec.
+  ///
+  /// Returns `None` if the matrix cannot be decomposed.
+  pub fn decompose_components(&self) -> Option<DecomposedMatrix3d> {
+    let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let length = |a: [f32; 3]| dot(a, a).sqrt();
+    let combine =
+      |a: [f32; 3], b: [f32; 3], bscl: f32| [a[0] + bscl * b[0], a[1] + bscl * b[1], a[2] + bscl * b[2]];
+    let normalize = |a: [f32; 3], len: f32| [a[0] / len, a[1] / len, a[2] / len];
+    let cross = |a: [f32; 3], b: [f32; 3]| {
+      [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+      ]
+    };
+
+    if self.m44 == 0.0 {
+      return None;
+    }
+
+    // Normalize the matrix.
+    let mut matrix = self.clone();
+    matrix.scale_by_factor(1.0 / self.m44);
+
+    // The perspective matrix is used to solve for perspective, and to test for singularity
+    // of the upper 3x3 component.
+    let mut perspective_matrix = matrix.clone();
+    perspective_matrix.m14 = 0.0;
+    perspective_matrix.m24 = 0.0;
+    perspective_matrix.m34 = 0.0;
+    perspective_matrix.m44 = 1.0;
+
+    if perspective_matrix.determinant() == 0.0 {
+      return None;
+    }
+
+    let perspective = if matrix.m14 != 0.0 || matrix.m24 != 0.0 || matrix.m34 != 0.0 {
+      let right_hand_side = [matrix.m14, matrix.m24, matrix.m34, matrix.m44];
+      perspective_matrix.inverse()?.transpose().multiply_vector(&right_hand_side)
+    } else {
+      [0.0, 0.0, 0.0, 1.0]
+    };
+
+    let translate = [matrix.m41, matrix.m42, matrix.m43];
+
+    let mut row = [
+      [matrix.m11, matrix.m12, matrix.m13],
+      [matrix.m21, matrix.m22, matrix.m23],
+      [matrix.m31, matrix.m32, matrix.m33],
+    ];
+
+    // Compute the X scale factor and normalize the first row.
+    let mut scale = [length(row[0]), 0.0, 0.0];
+    row[0] = normalize(row[0], scale[0]);
+
+    // Compute the XY shear factor and make the second row orthogonal to the first.
+    let mut skew = [dot(row[0], row[1]), 0.0, 0.0];
+    row[1] = combine(row[1], row[0], -skew[0]);
+
+    // Compute the Y scale factor and normalize the second row.
+    scale[1] = length(row[1]);
+    row[1] = normalize(row[1], scale[1]);
+    skew[0] /= scale[1];
+
+    // Compute the XZ and YZ shears, and orthogonalize the third row.
+    skew[1] = dot(row[0], row[2]);
+    row[2] = combine(row[2], row[0], -skew[1]);
+    skew[2] = dot(row[1], row[2]);
+    row[2] = combine(row[2], row[1], -skew[2]);
+
+    // Compute the Z scale factor and normalize the third row.
+    scale[2] = length(row[2]);
+    row[2] = normalize(row[2], scale[2]);
+    skew[1] /= scale[2];
+    skew[2] /= scale[2];
+
+    // The rows are now orthonormal. If the determinant is -1, there is a coordinate system flip,
+    // so negate the rows and scale factors.
+    if dot(row[0], cross(row[1], row[2])) < 0.0 {
+      scale = scale.map(|s| -s);
+      row = row.map(|r| r.map(|v| -v));
+    }
+
+    let mut quaternion = [
+      0.5 * (1.0 + row[0][0] - row[1][1] - row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 - row[0][0] + row[1][1] - row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 - row[0][0] - row[1][1] + row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 + row[0][0] + row[1][1] + row[2][2]).max(0.0).sqrt(),
+    ];
+
+    if row[2][1] > row[1][2] {
+      quaternion[0] = -quaternion[0];
+    }
+    if row[0][2] > row[2][0] {
+      quaternion[1] = -quaternion[1];
+    }
+    if row[1][0] > row[0][1] {
+      quaternion[2] = -quaternion[2];
+    }
+
+    Some(DecomposedMatrix3d {
+      translate,
+      scale,
+      skew,
+      perspective,
+      quaternion,
+    })
+  }
+}
+
+/// The components of a decomposed 3D matrix.
+///
+/// See [Matrix3d::decompose_components](Matrix3d::decompose_components).
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecomposedMatrix3d {
+  /// The translation along the X, Y, and Z axes, in pixels.
+  pub translate: [f32; 3],
+  /// The scale factors along the X, Y, and Z axes.
+  pub scale: [f32; 3],
+  /// The XY, XZ, and YZ shear factors.
+  pub skew: [f32; 3],
+  /// The perspective components of the matrix.
+  pub perspective: [f32; 4],
+  /// The rotation, as an `[x, y, z, w]` quaternion.
+  pub quaternion: [f32; 4],
+}
+
+impl DecomposedMatrix3d {
+  /// Recomposes the components into a matrix.
+  pub fn to_matrix(&self) -> Matrix3d<f32> {
+    let [tx, ty, tz] = self.translate;
+    let [sx, sy, sz] = self.scale;
+
+    let mut matrix = Matrix3d::identity();
+    matrix.m14 = self.perspective[0];
+    matrix.m24 = self.perspective[1];
+    matrix.m34 = self.perspective[2];
+    matrix.m44 = self.perspective[3];
+
+    matrix = Matrix3d::translate(tx, ty, tz).multiply(&matrix);
+    matrix = self.rotation_matrix().multiply(&matrix);
+    matrix = self.skew_matrix().multiply(&matrix);
+    Matrix3d::scale(sx, sy, sz).multiply(&matrix)
+  }
+
+  /// Converts the components to a list of transform functions.
+  ///
+  /// Perspective components that cannot be represented by the `perspective()` function,
+  /// and shears involving the Z axis, are represented by `matrix3d()` functions.
+  pub fn to_transform_list(&self) -> TransformList {
+    // Decomposition introduces rounding errors, so compare components approximately.
+    const EPSILON: f32 = 1e-5;
+    let eq = |a: f32, b: f32| (a - b).abs() < EPSILON;
+
+    let mut transforms = Vec::new();
+
+    match self.perspective {
+      [x, y, z, w] if eq(x, 0.0) && eq(y, 0.0) && eq(w, 1.0) => {
+        if !eq(z, 0.0) {
+          transforms.push(Transform::Perspective(Length::px(-1.0 / z)));
+        }
+      }
+      [x, y, z, w] => {
+        let mut matrix = Matrix3d::identity();
+        matrix.m14 = x;
+        matrix.m24 = y;
+        matrix.m34 = z;
+        matrix.m44 = w;
+        transforms.push(Transform::Matrix3d(matrix));
+      }
+    }
+
+    match self.translate {
+      [x, y, z] if eq(z, 0.0) => {
+        if !eq(x, 0.0) || !eq(y, 0.0) {
+          transforms.push(Transform::Translate(LengthPercentage::px(x), LengthPercentage::px(y)));
+        }
+      }
+      [x, y, z] => transforms.push(Transform::Translate3d(
+        LengthPercentage::px(x),
+        LengthPercentage::px(y),
+        Length::px(z),
+      )),
+    }
+
+    let [x, y, z, w] = self.quaternion;
+    let len = (x * x + y * y + z * z).sqrt();
+    if !eq(len, 0.0) {
+      let angle = (2.0 * len.atan2(w)).to_degrees();
+      if eq(x, 0.0) && eq(y, 0.0) {
+        transforms.push(Transform::Rotate(Angle::Deg(if z > 0.0 { angle } else { -angle })));
+      } else {
+        transforms.push(Transform::Rotate3d(x / len, y / len, z / len, Angle::Deg(angle)));
+      }
+    }
+
+    match self.skew {
+      [xy, xz, yz] if eq(xz, 0.0) && eq(yz, 0.0) => {
+        if !eq(xy, 0.0) {
+          transforms.push(Transform::SkewX(Angle::Deg(xy.atan().to_degrees())));
+        }
+      }
+      _ => transforms.push(Transform::Matrix3d(self.skew_matrix())),
+    }
+
+    match self.scale {
+      [x, y, z] if eq(z, 1.0) => {
+        if !eq(x, 1.0) || !eq(y, 1.0) {
+          transforms.push(Transform::Scale(
+            NumberOrPercentage::Number(x),
+            NumberOrPercentage::Number(y),
+          ));
+        }
+      }
+      [x, y, z] => transforms.push(Transform::Scale3d(
+        NumberOrPercentage::Number(x),
+        NumberOrPercentage::Number(y),
+        NumberOrPercentage::Number(z),
+      )),
+    }
+
+    TransformList(transforms)
+  }
+
+  fn rotation_matrix(&self) -> Matrix3d<f32> {
+    let [x, y, z, w] = self.quaternion;
+    let mut matrix = Matrix3d::identity();
+    matrix.m11 = 1.0 - 2.0 * (y * y + z * z);
+    matrix.m12 = 2.0 * (x * y + z * w);
+    matrix.m13 = 2.0 * (x * z - y * w);
+    matrix.m21 = 2.0 * (x * y - z * w);
+    matrix.m22 = 1.0 - 2.0 * (x * x + z * z);
+    matrix.m23 = 2.0 * (y * z + x * w);
+    matrix.m31 = 2.0 * (x * z + y * w);
+    matrix.m32 = 2.0 * (y * z - x * w);
+    matrix.m33 = 1.0 - 2.0 * (x * x + y * y);
+    matrix
+  }
+
+  fn skew_matrix(&self) -> Matrix3d<f32> {
+    let mut matrix = Matrix3d::identity();
+    matrix.m21 = self.skew[0];
+    matrix.m31 = self.skew[1];
+    matrix.m32 = self.skew[2];
+    matrix
+  }
 }
 
 impl<'i> Parse<'i> for Transform {
@@ -1360,6 +1649,38 @@ impl Transform {
     }
     None
   }
+
+  /// Converts the transform to a 3D matrix, resolving percentages and relative lengths using the given context.
+  pub fn to_matrix_with_context(&self, context: &TransformContext) -> Result<Matrix3d<f32>, UnitConversionError> {
+    let matrix = match self {
+      Transform::Translate(x, y) => Matrix3d::translate(context.resolve_x(x)?, context.resolve_y(y)?, 0.0),
+      Transform::TranslateX(x) => Matrix3d::translate(context.resolve_x(x)?, 0.0, 0.0),
+      Transform::TranslateY(y) => Matrix3d::translate(0.0, context.resolve_y(y)?, 0.0),
+      Transform::TranslateZ(z) => Matrix3d::translate(0.0, 0.0, z.to_px_with_context(&context.units)?),
+      Transform::Translate3d(x, y, z) => Matrix3d::translate(
+        context.resolve_x(x)?,
+        context.resolve_y(y)?,
+        z.to_px_with_context(&context.units)?,
+      ),
+      Transform::Scale(x, y) => Matrix3d::scale(x.into(), y.into(), 1.0),
+      Transform::ScaleX(x) => Matrix3d::scale(x.into(), 1.0, 1.0),
+      Transform::ScaleY(y) => Matrix3d::scale(1.0, y.into(), 1.0),
+      Transform::ScaleZ(z) => Matrix3d::scale(1.0, 1.0, z.into()),
+      Transform::Scale3d(x, y, z) => Matrix3d::scale(x.into(), y.into(), z.into()),
+      Transform::Rotate(angle) | Transform::RotateZ(angle) => Matrix3d::rotate(0.0, 0.0, 1.0, angle.to_radians()),
+      Transform::RotateX(angle) => Matrix3d::rotate(1.0, 0.0, 0.0, angle.to_radians()),
+      Transform::RotateY(angle) => Matrix3d::rotate(0.0, 1.0, 0.0, angle.to_radians()),
+      Transform::Rotate3d(x, y, z, angle) => Matrix3d::rotate(*x, *y, *z, angle.to_radians()),
+      Transform::Skew(x, y) => Matrix3d::skew(x.to_radians(), y.to_radians()),
+      Transform::SkewX(x) => Matrix3d::skew(x.to_radians(), 0.0),
+      Transform::SkewY(y) => Matrix3d::skew(0.0, y.to_radians()),
+      // Depths less than 1px are treated as 1px.
+      Transform::Perspective(len) => Matrix3d::perspective(len.to_px_with_context(&context.units)?.max(1.0)),
+      Transform::Matrix(m) => m.to_matrix3d(),
+      Transform::Matrix3d(m) => m.clone(),
+    };
+    Ok(matrix)
+  }
 }
 
 enum_property! {
@@ -0,0 +1,359 @@
+//! Rule and declaration queries.
+//!
+//! [StyleSheet::query_selector](crate::stylesheet::StyleSheet::query_selector) and
+//! [StyleSheet::query_property](crate::stylesheet::StyleSheet::query_property) find style rules
+//! with a given selector, or declarations of a given property, without writing a custom
+//! [Visitor](crate::visitor::Visitor). They return handles, which can be passed to
+//! [StyleSheet::rule_mut](crate::stylesheet::StyleSheet::rule_mut) and
+//! [StyleSheet::declaration_mut](crate::stylesheet::StyleSheet::declaration_mut) to modify
+//! the matched rules and declarations.
+//!
+//! Handles refer to rules by their position in the style sheet, so they are invalidated when
+//! rules are inserted or removed, or when the style sheet is minified.
+//!
+//! Handles can also be used as identifiers for nodes when exchanging a style sheet with external
+//! tools. They are printed as a string of indices, e.g. `0.2` for the third rule nested in the first
+//! rule, or `0.2#1` for its second declaration (`0.2#!1` if `!important`), and can be parsed back
+//! with [FromStr](std::str::FromStr). Since positions are preserved by the serde representation,
+//! a handle remains valid across a serialize, modify, and deserialize round trip as long as no rules
+//! or declarations are inserted or removed before it. [StyleSheet::rule_pointer](crate::stylesheet::StyleSheet::rule_pointer)
+//! and [StyleSheet::declaration_pointer](crate::stylesheet::StyleSheet::declaration_pointer) return
+//! the location of a node within the serialized output as a JSON pointer.
+
+use crate::declaration::DeclarationBlock;
+use crate::printer::PrinterOptions;
+use crate::properties::Property;
+use crate::rules::{CssRule, CssRuleList};
+use crate::traits::ToCss;
+use std::fmt::{self, Write};
+use std::str::FromStr;
+
+/// A handle to a rule in a style sheet, returned by
+/// [StyleSheet::query_selector](crate::stylesheet::StyleSheet::query_selector).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuleHandle {
+  /// The index of the rule within each of its ancestors' rule lists, starting from the top level.
+  pub path: Vec<usize>,
+}
+
+/// A handle to a declaration in a style sheet, returned by
+/// [StyleSheet::query_property](crate::stylesheet::StyleSheet::query_property).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeclarationHandle {
+  /// The rule that contains the declaration.
+  pub rule: RuleHandle,
+  /// Whether the declaration is `!important`.
+  pub important: bool,
+  /// The index of the declaration within the rule's normal or `!important` declarations.
+  pub index: usize,
+}
+
+impl fmt::Display for RuleHandle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (i, index) in self.path.iter().enumerate() {
+      if i > 0 {
+        f.write_char('.')?;
+      }
+      write!(f, "{}", index)?;
+    }
+    Ok(())
+  }
+}
+
+impl FromStr for RuleHandle {
+  type Err = InvalidHandle;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let path = s
+      .split('.')
+      .map(|index| index.parse().map_err(|_| InvalidHandle))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(RuleHandle { path })
+  }
+}
+
+impl fmt::Display for DeclarationHandle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{}#{}{}",
+      self.rule,
+      if self.important { "!" } else { "" },
+      self.index
+    )
+  }
+}
+
+impl FromStr for DeclarationHandle {
+  type Err = InvalidHandle;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let (rule, index) = s.split_once('#').ok_or(InvalidHandle)?;
+    let (important, index) = match index.strip_prefix('!') {
+      Some(index) => (true, index),
+      None => (false, index),
+    };
+    Ok(DeclarationHandle {
+      rule: rule.parse()?,
+      important,
+      index: index.parse().map_err(|_| InvalidHandle)?,
+    })
+  }
+}
+
+macro_rules! impl_string_serde {
+  ($t: ident) => {
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    impl serde::Serialize for $t {
+      fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+      }
+    }
+
+    #[cfg(feature = "serde")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+    impl<'de> serde::Deserialize<'de> for $t {
+      fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = std::borrow::Cow::<str>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+      }
+    }
+  };
+}
+
+impl_string_serde!(RuleHandle);
+impl_string_serde!(DeclarationHandle);
+
+/// An error returned when parsing an invalid [RuleHandle](RuleHandle) or [DeclarationHandle](DeclarationHandle).
+#[derive(Debug, Clone, PartialEq)]
+pub struct InvalidHandle;
+
+impl fmt::Display for InvalidHandle {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "Invalid handle")
+  }
+}
+
+impl std::error::Error for InvalidHandle {}
+
+pub(crate) fn rule_handles<R>(rules: &CssRuleList<R>, path: &mut Vec<usize>, res: &mut Vec<RuleHandle>) {
+  for (index, rule) in rules.0.iter().enumerate() {
+    path.push(index);
+    res.push(RuleHandle { path: path.clone() });
+    if let Some(rules) = child_rules(rule) {
+      rule_handles(rules, path, res);
+    }
+    path.pop();
+  }
+}
+
+pub(crate) fn query_selector<R>(
+  rules: &CssRuleList<R>,
+  selectors: &[String],
+  path: &mut Vec<usize>,
+  res: &mut Vec<RuleHandle>,
+) {
+  for (index, rule) in rules.0.iter().enumerate() {
+    path.push(index);
+    let style = match rule {
+      CssRule::Style(style) => Some(style),
+      CssRule::Nesting(nesting) => Some(&nesting.style),
+      _ => None,
+    };
+
+    if let Some(style) = style {
+      // Compare serialized selectors, which also normalizes them.
+      if style.selectors.0.iter().any(|selector| {
+        selector
+          .to_css_string(PrinterOptions::default())
+          .map_or(false, |selector| selectors.contains(&selector))
+      }) {
+        res.push(RuleHandle { path: path.clone() });
+      }
+    }
+
+    if let Some(rules) = child_rules(rule) {
+      query_selector(rules, selectors, path, res);
+    }
+    path.pop();
+  }
+}
+
+pub(crate) fn query_property<R>(
+  rules: &CssRuleList<R>,
+  name: &str,
+  path: &mut Vec<usize>,
+  res: &mut Vec<DeclarationHandle>,
+) {
+  for (index, rule) in rules.0.iter().enumerate() {
+    path.push(index);
+    if let Some(declarations) = declarations(rule) {
+      let mut add = |list: &[Property], important: bool| {
+        for (index, property) in list.iter().enumerate() {
+          let id = property.property_id();
+          // Custom property names are case sensitive.
+          let matches = if name.starts_with("--") {
+            id.name() == name
+          } else {
+            id.name().eq_ignore_ascii_case(name)
+          };
+
+          if matches {
+            res.push(DeclarationHandle {
+              rule: RuleHandle { path: path.clone() },
+              important,
+              index,
+            });
+          }
+        }
+      };
+
+      add(&declarations.declarations, false);
+      add(&declarations.important_declarations, true);
+    }
+
+    if let Some(rules) = child_rules(rule) {
+      query_property(rules, name, path, res);
+    }
+    path.pop();
+  }
+}
+
+pub(crate) fn rule<'a, 'i, R>(rules: &'a CssRuleList<'i, R>, handle: &RuleHandle) -> Option<&'a CssRule<'i, R>> {
+  let (last, ancestors) = handle.path.split_last()?;
+  let mut rules = rules;
+  for index in ancestors {
+    rules = child_rules(rules.0.get(*index)?)?;
+  }
+  rules.0.get(*last)
+}
+
+pub(crate) fn rule_mut<'a, 'i, R>(
+  rules: &'a mut CssRuleList<'i, R>,
+  handle: &RuleHandle,
+) -> Option<&'a mut CssRule<'i, R>> {
+  let (last, ancestors) = handle.path.split_last()?;
+  let mut rules = rules;
+  for index in ancestors {
+    rules = child_rules_mut(rules.0.get_mut(*index)?)?;
+  }
+  rules.0.get_mut(*last)
+}
+
+pub(crate) fn declaration<'a, 'i, R>(
+  rules: &'a CssRuleList<'i, R>,
+  handle: &DeclarationHandle,
+) -> Option<&'a Property<'i>> {
+  let declarations = declarations(rule(rules, &handle.rule)?)?;
+  if handle.important {
+    declarations.important_declarations.get(handle.index)
+  } else {
+    declarations.declarations.get(handle.index)
+  }
+}
+
+pub(crate) fn declaration_mut<'a, 'i, R>(
+  rules: &'a mut CssRuleList<'i, R>,
+  handle: &DeclarationHandle,
+) -> Option<&'a mut Property<'i>> {
+  let declarations = declarations_mut(rule_mut(rules, &handle.rule)?)?;
+  if handle.important {
+    declarations.important_declarations.get_mut(handle.index)
+  } else {
+    declarations.declarations.get_mut(handle.index)
+  }
+}
+
+pub(crate) fn rule_pointer<R>(rules: &CssRuleList<R>, handle: &RuleHandle) -> Option<String> {
+  let (last, ancestors) = handle.path.split_last()?;
+  let mut pointer = String::from("/rules");
+  let mut rules = rules;
+  for index in ancestors {
+    let rule = rules.0.get(*index)?;
+    rules = child_rules(rule)?;
+    write!(pointer, "/{}{}/rules", index, value_pointer(rule)).unwrap();
+  }
+  rules.0.get(*last)?;
+  write!(pointer, "/{}", last).unwrap();
+  Some(pointer)
+}
+
+pub(crate) fn declaration_pointer<R>(rules: &CssRuleList<R>, handle: &DeclarationHandle) -> Option<String> {
+  declaration(rules, handle)?;
+  let rule = rule(rules, &handle.rule)?;
+  let mut pointer = rule_pointer(rules, &handle.rule)?;
+  write!(
+    pointer,
+    "{}/declarations/{}/{}",
+    value_pointer(rule),
+    if handle.important {
+      "importantDeclarations"
+    } else {
+      "declarations"
+    },
+    handle.index
+  )
+  .unwrap();
+  Some(pointer)
+}
+
+/// Returns the JSON pointer to the style rule within the serialized representation of a rule.
+fn value_pointer<R>(rule: &CssRule<R>) -> &'static str {
+  match rule {
+    CssRule::Nesting(..) => "/value/style",
+    _ => "/value",
+  }
+}
+
+fn child_rules<'a, 'i, R>(rule: &'a CssRule<'i, R>) -> Option<&'a CssRuleList<'i, R>> {
+  match rule {
+    CssRule::Style(style) => Some(&style.rules),
+    CssRule::Nesting(nesting) => Some(&nesting.style.rules),
+    CssRule::Media(media) => Some(&media.rules),
+    CssRule::Supports(supports) => Some(&supports.rules),
+    CssRule::MozDocument(document) => Some(&document.rules),
+    CssRule::LayerBlock(layer) => Some(&layer.rules),
+    CssRule::Container(container) => Some(&container.rules),
+    CssRule::Scope(scope) => Some(&scope.rules),
+    CssRule::StartingStyle(starting_style) => Some(&starting_style.rules),
+    _ => None,
+  }
+}
+
+fn child_rules_mut<'a, 'i, R>(rule: &'a mut CssRule<'i, R>) -> Option<&'a mut CssRuleList<'i, R>> {
+  match rule {
+    CssRule::Style(style) => Some(&mut style.rules),
+    CssRule::Nesting(nesting) => Some(&mut nesting.style.rules),
+    CssRule::Media(media) => Some(&mut media.rules),
+    CssRule::Supports(supports) => Some(&mut supports.rules),
+    CssRule::MozDocument(document) => Some(&mut document.rules),
+    CssRule::LayerBlock(layer) => Some(&mut layer.rules),
+    CssRule::Container(container) => Some(&mut container.rules),
+    CssRule::Scope(scope) => Some(&mut scope.rules),
+    CssRule::StartingStyle(starting_style) => Some(&mut starting_style.rules),
+    _ => None,
+  }
+}
+
+fn declarations<'a, 'i, R>(rule: &'a CssRule<'i, R>) -> Option<&'a DeclarationBlock<'i>> {
+  match rule {
+    CssRule::Style(style) => Some(&style.declarations),
+    CssRule::Nesting(nesting) => Some(&nesting.style.declarations),
+    CssRule::Page(page) => Some(&page.declarations),
+    CssRule::Viewport(viewport) => Some(&viewport.declarations),
+    CssRule::CounterStyle(counter_style) => Some(&counter_style.declarations),
+    _ => None,
+  }
+}
+
+fn declarations_mut<'a, 'i, R>(rule: &'a mut CssRule<'i, R>) -> Option<&'a mut DeclarationBlock<'i>> {
+  match rule {
+    CssRule::Style(style) => Some(&mut style.declarations),
+    CssRule::Nesting(nesting) => Some(&mut nesting.style.declarations),
+    CssRule::Page(page) => Some(&mut page.declarations),
+    CssRule::Viewport(viewport) => Some(&mut viewport.declarations),
+    CssRule::CounterStyle(counter_style) => Some(&mut counter_style.declarations),
+    _ => None,
+  }
+}
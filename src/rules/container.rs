@@ -268,7 +268,9 @@ impl<'i> ToCss for ContainerName<'i> {
   where
     W: std::fmt::Write,
   {
-    self.0.to_css(dest)
+    let css_module_container_enabled =
+      dest.css_module.as_ref().map_or(false, |css_module| css_module.config.container);
+    self.0.to_css_with_options(dest, css_module_container_enabled)
   }
 }
 
@@ -35,7 +35,11 @@ impl<'i> ToCss for CounterStyleRule<'i> {
     #[cfg(feature = "sourcemap")]
     dest.add_mapping(self.loc);
     dest.write_str("@counter-style ")?;
-    self.name.to_css(dest)?;
+    let css_module_counter_style_enabled = dest
+      .css_module
+      .as_ref()
+      .map_or(false, |css_module| css_module.config.counter_style);
+    self.name.to_css_with_options(dest, css_module_counter_style_enabled)?;
     self.declarations.to_css_block(dest)
   }
 }
@@ -340,6 +340,7 @@ impl<'i> FontPaletteValuesRule<'i> {
         FontPaletteValuesProperty::Custom(custom) => FontPaletteValuesProperty::Custom(CustomProperty {
           name: custom.name.clone(),
           value: custom.value.get_fallback(kind),
+          typed_value: None,
         }),
         _ => property.clone(),
       })
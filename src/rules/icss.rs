@@ -0,0 +1,295 @@
+//! The ICSS `:import` and `:export` rules.
+
+use super::value::print_css_module_value;
+use super::Location;
+use crate::css_modules::{dependency_placeholder, CssModuleExports, CssModuleReference};
+use crate::error::{ParserError, PrinterError};
+use crate::printer::Printer;
+use crate::properties::custom::{Token, TokenList, TokenOrValue};
+use crate::selector::{Component, PseudoClass, SelectorList};
+use crate::stylesheet::ParserOptions;
+use crate::traits::ToCss;
+use crate::values::string::CowArcStr;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// An [ICSS](https://github.com/css-modules/icss) `:import` or `:export` rule, used to
+/// interoperate with other tools that implement CSS modules.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct IcssRule<'i> {
+  /// The contents of the rule.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub block: IcssBlock<'i>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+/// The contents of an [ICSS rule](IcssRule).
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum IcssBlock<'i> {
+  /// An `:import("./file.css") { local: remote }` block.
+  Import {
+    /// The specifier of the file to import from.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[cfg_attr(feature = "visitor", skip_visit)]
+    specifier: CowArcStr<'i>,
+    /// The imported names.
+    names: Vec<IcssImport<'i>>,
+  },
+  /// An `:export { name: value }` block.
+  Export {
+    /// The exported names.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    exports: Vec<IcssExport<'i>>,
+  },
+}
+
+/// A name imported by an ICSS `:import` block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct IcssImport<'i> {
+  /// The name that references to the import are replaced with in the current file.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub local: CowArcStr<'i>,
+  /// The name exported by the imported file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub remote: CowArcStr<'i>,
+}
+
+/// A name exported by an ICSS `:export` block.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct IcssExport<'i> {
+  /// The exported name.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub name: CowArcStr<'i>,
+  /// The exported value.
+  pub value: TokenList<'i>,
+}
+
+/// Returns whether the given selectors form an ICSS `:import("./file.css")` or `:export` prelude,
+/// and the import specifier if any.
+pub(crate) fn icss_block_prelude<'i>(selectors: &SelectorList<'i>) -> Option<Option<CowArcStr<'i>>> {
+  if selectors.0.len() != 1 || selectors.0[0].len() != 1 {
+    return None;
+  }
+
+  match selectors.0[0].iter_raw_match_order().next()? {
+    Component::NonTSPseudoClass(PseudoClass::Custom { name }) if name.eq_ignore_ascii_case("export") => Some(None),
+    Component::NonTSPseudoClass(PseudoClass::CustomFunction { name, arguments })
+      if name.eq_ignore_ascii_case("import") =>
+    {
+      let mut iter = arguments.0.iter().filter(|token| !token.is_whitespace());
+      match (iter.next(), iter.next()) {
+        (Some(TokenOrValue::Token(Token::String(specifier))), None) => Some(Some(specifier.clone())),
+        _ => None,
+      }
+    }
+    _ => None,
+  }
+}
+
+impl<'i> IcssBlock<'i> {
+  pub(crate) fn parse<'t>(
+    specifier: Option<CowArcStr<'i>>,
+    input: &mut Parser<'i, 't>,
+    options: &ParserOptions<'_, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    match specifier {
+      Some(specifier) => {
+        let mut names = Vec::new();
+        while !input.is_exhausted() {
+          let local = input.expect_ident_cloned()?.into();
+          input.expect_colon()?;
+          let remote = input
+            .parse_until_after(Delimiter::Semicolon, |input| {
+              input.expect_ident_cloned().map_err(|e| e.into())
+            })?
+            .into();
+          names.push(IcssImport { local, remote });
+        }
+        Ok(IcssBlock::Import { specifier, names })
+      }
+      None => {
+        let mut exports = Vec::new();
+        while !input.is_exhausted() {
+          let name = input.expect_ident_cloned()?.into();
+          input.expect_colon()?;
+          let value =
+            input.parse_until_after(Delimiter::Semicolon, |input| TokenList::parse(input, options, 0))?;
+          exports.push(IcssExport { name, value });
+        }
+        Ok(IcssBlock::Export { exports })
+      }
+    }
+  }
+}
+
+impl<'i> IcssRule<'i> {
+  /// Registers the imports and exports of this rule with the CSS module being printed.
+  /// Imported names are substituted in values, in the same way as `@value` imports.
+  pub(crate) fn register<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let source_index = self.loc.source_index;
+    match &self.block {
+      IcssBlock::Import { specifier, names } => {
+        for import in names {
+          if let Some(css_module) = &mut dest.css_module {
+            let placeholder = css_module.import_reference(&import.remote, specifier, source_index);
+            dest.add_css_module_value(&import.local, placeholder, source_index);
+          }
+        }
+      }
+      IcssBlock::Export { exports } => {
+        for export in exports {
+          let value = print_css_module_value(&export.value, self.loc, dest)?;
+          if let Some(css_module) = &mut dest.css_module {
+            css_module.add_value(&export.name, value.trim(), source_index);
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+impl<'i> ToCss for IcssRule<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    match &self.block {
+      IcssBlock::Import { specifier, names } => {
+        dest.write_str(":import(")?;
+        serialize_string(specifier, dest)?;
+        dest.write_char(')')?;
+        write_block(dest, names.len(), |dest, i| {
+          serialize_identifier(&names[i].local, dest)?;
+          dest.delim(':', false)?;
+          serialize_identifier(&names[i].remote, dest)?;
+          Ok(())
+        })
+      }
+      IcssBlock::Export { exports } => {
+        dest.write_str(":export")?;
+        write_block(dest, exports.len(), |dest, i| {
+          serialize_identifier(&exports[i].name, dest)?;
+          dest.delim(':', false)?;
+          exports[i].value.to_css(dest, false)
+        })
+      }
+    }
+  }
+}
+
+/// Writes the `{ ... }` block of an ICSS rule, calling `write` for each declaration.
+fn write_block<W, F>(dest: &mut Printer<W>, len: usize, mut write: F) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+  F: FnMut(&mut Printer<W>, usize) -> Result<(), PrinterError>,
+{
+  dest.whitespace()?;
+  dest.write_char('{')?;
+  dest.indent();
+  for i in 0..len {
+    dest.newline()?;
+    write(dest, i)?;
+    if i + 1 < len || !dest.minify {
+      dest.write_char(';')?;
+    }
+  }
+  dest.dedent();
+  dest.newline()?;
+  dest.write_char('}')
+}
+
+/// Writes the exports of a CSS module as an ICSS `:export` block, preceded by `:import` blocks
+/// for the names it references from other files, so that the output can be consumed by other
+/// tools that implement CSS modules.
+pub(crate) fn write_icss<W>(
+  dest: &mut Printer<W>,
+  hash: &str,
+  exports: &CssModuleExports,
+  references: &HashMap<String, CssModuleReference>,
+) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  let mut imports: BTreeMap<&str, BTreeMap<String, &str>> = BTreeMap::new();
+  for (placeholder, reference) in references {
+    if let CssModuleReference::Dependency { name, specifier } = reference {
+      imports.entry(specifier).or_default().insert(placeholder.clone(), name);
+    }
+  }
+
+  let mut values: BTreeMap<&str, String> = BTreeMap::new();
+  for (name, export) in exports {
+    let mut value = export.name.clone();
+    for reference in &export.composes {
+      value.push(' ');
+      match reference {
+        CssModuleReference::Local { name } | CssModuleReference::Global { name } => value.push_str(name),
+        CssModuleReference::Dependency { name, specifier } => {
+          let placeholder = dependency_placeholder(hash, name, specifier);
+          value.push_str(&placeholder);
+          imports.entry(specifier).or_default().insert(placeholder, name);
+        }
+      }
+    }
+    values.insert(name, value);
+  }
+
+  for (specifier, names) in imports {
+    dest.newline_between_rules()?;
+    dest.write_str(":import(")?;
+    serialize_string(specifier, dest)?;
+    dest.write_char(')')?;
+    let names: Vec<_> = names.into_iter().collect();
+    write_block(dest, names.len(), |dest, i| {
+      dest.write_str(&names[i].0)?;
+      dest.delim(':', false)?;
+      dest.write_str(names[i].1)
+    })?;
+  }
+
+  if !values.is_empty() {
+    let values: Vec<_> = values.into_iter().collect();
+    dest.newline_between_rules()?;
+    dest.write_str(":export")?;
+    write_block(dest, values.len(), |dest, i| {
+      dest.write_str(values[i].0)?;
+      dest.delim(':', false)?;
+      dest.write_str(&values[i].1)
+    })?;
+  }
+
+  Ok(())
+}
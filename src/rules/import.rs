@@ -25,7 +25,14 @@ pub struct ImportRule<'i> {
   #[cfg_attr(feature = "visitor", skip_visit)]
   pub url: CowArcStr<'i>,
   /// An optional cascade layer name, or `None` for an anonymous layer.
+  /// When serialized, an anonymous layer is represented as an empty list of names.
   #[cfg_attr(feature = "visitor", skip_visit)]
+  #[cfg_attr(
+    feature = "serde",
+    serde(borrow, serialize_with = "serialize_layer", deserialize_with = "deserialize_layer")
+  )]
+  // schemars cannot generate a default value for fields with a lifetime and `serialize_with`.
+  #[cfg_attr(all(feature = "serde", not(feature = "jsonschema")), serde(default))]
   pub layer: Option<Option<LayerName<'i>>>,
   /// An optional `supports()` condition.
   pub supports: Option<SupportsCondition<'i>>,
@@ -37,6 +44,27 @@ pub struct ImportRule<'i> {
   pub loc: Location,
 }
 
+#[cfg(feature = "serde")]
+fn serialize_layer<S>(layer: &Option<Option<LayerName>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+  S: serde::Serializer,
+{
+  match layer {
+    Some(Some(name)) => serializer.serialize_some(name),
+    Some(None) => serializer.serialize_some(&[] as &[CowArcStr]),
+    None => serializer.serialize_none(),
+  }
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_layer<'i, 'de: 'i, D>(deserializer: D) -> Result<Option<Option<LayerName<'i>>>, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let layer: Option<LayerName<'i>> = serde::Deserialize::deserialize(deserializer)?;
+  Ok(layer.map(|name| if name.0.is_empty() { None } else { Some(name) }))
+}
+
 impl<'i> ToCss for ImportRule<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
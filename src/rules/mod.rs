@@ -42,6 +42,7 @@ pub mod custom_media;
 pub mod document;
 pub mod font_face;
 pub mod font_palette_values;
+pub mod icss;
 pub mod import;
 pub mod keyframes;
 pub mod layer;
@@ -55,6 +56,7 @@ pub mod starting_style;
 pub mod style;
 pub mod supports;
 pub mod unknown;
+pub mod value;
 pub mod viewport;
 
 use self::font_palette_values::FontPaletteValuesRule;
@@ -62,26 +64,32 @@ use self::layer::{LayerBlockRule, LayerStatementRule};
 use self::property::PropertyRule;
 use crate::context::PropertyHandlerContext;
 use crate::declaration::{DeclarationBlock, DeclarationHandler};
-use crate::dependencies::{Dependency, ImportDependency};
-use crate::error::{MinifyError, ParserError, PrinterError, PrinterErrorKind};
+use crate::dependencies::{Dependency, DependencyReference, ImportDependency};
+use crate::error::{MinifyError, MinifyErrorKind, ParserError, PrinterError, PrinterErrorKind};
 use crate::parser::{parse_rule_list, parse_style_block, DefaultAtRule, DefaultAtRuleParser, TopLevelRuleParser};
 use crate::prefixes::Feature;
-use crate::printer::Printer;
+use crate::printer::{CoverageRange, Printer, PrinterOptions};
+#[cfg(feature = "grid")]
+use crate::properties::grid::add_ms_grid_fallbacks;
 use crate::rules::keyframes::KeyframesName;
-use crate::selector::{is_compatible, is_equivalent, Component, Selector, SelectorList};
-use crate::stylesheet::ParserOptions;
+use crate::selector::{
+  focus_visible_to_class, is_compatible, is_equivalent, pseudo_class_states_to_classes, scope_selector,
+  uncompilable_features, Component, DirFallback, Selector, SelectorList,
+};
+use crate::stylesheet::{ParserOptions, PseudoClassStates, UnsupportedFeatureMode};
 use crate::targets::Targets;
 use crate::traits::{AtRuleParser, ToCss};
 use crate::values::string::CowArcStr;
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
-use crate::visitor::{Visit, VisitTypes, Visitor};
+use crate::visitor::{visit_in_context, Visit, VisitTypes, Visitor};
 use container::ContainerRule;
 use counter_style::CounterStyleRule;
 use cssparser::{parse_one_rule, ParseError, Parser, ParserInput};
 use custom_media::CustomMediaRule;
 use document::MozDocumentRule;
 use font_face::FontFaceRule;
+use icss::IcssRule;
 use import::ImportRule;
 use itertools::Itertools;
 use keyframes::KeyframesRule;
@@ -95,8 +103,9 @@ use starting_style::StartingStyleRule;
 use std::collections::{HashMap, HashSet};
 use std::hash::{BuildHasherDefault, Hasher};
 use style::StyleRule;
-use supports::SupportsRule;
+use supports::{SupportsCondition, SupportsRule};
 use unknown::UnknownAtRule;
+use value::ValueRule;
 use viewport::ViewportRule;
 
 #[derive(Clone)]
@@ -174,6 +183,10 @@ pub enum CssRule<'i, R = DefaultAtRule> {
   Scope(ScopeRule<'i, R>),
   /// A `@starting-style` rule.
   StartingStyle(StartingStyleRule<'i, R>),
+  /// A CSS modules `@value` rule.
+  Value(ValueRule<'i>),
+  /// An ICSS `:import` or `:export` rule.
+  Icss(IcssRule<'i>),
   /// A placeholder for a rule that was removed.
   Ignored,
   /// An unknown at-rule.
@@ -229,7 +242,12 @@ impl<'i, 'de: 'i, R: serde::Deserialize<'de>> serde::Deserialize<'de> for CssRul
         }
 
         let rule_type = rule_type.ok_or_else(|| serde::de::Error::missing_field("type"))?;
-        let content = value.ok_or_else(|| serde::de::Error::missing_field("value"))?;
+        // Ignored rules are serialized without a value.
+        let content = match value {
+          Some(value) => value,
+          None if rule_type.as_ref() == "ignored" => serde::__private::de::Content::Unit,
+          None => return Err(serde::de::Error::missing_field("value")),
+        };
         Ok(PartialRule { rule_type, content })
       }
     }
@@ -318,6 +336,14 @@ impl<'i, 'de: 'i, R: serde::Deserialize<'de>> serde::Deserialize<'de> for CssRul
         let rule = StartingStyleRule::deserialize(deserializer)?;
         Ok(CssRule::StartingStyle(rule))
       }
+      "value" => {
+        let rule = ValueRule::deserialize(deserializer)?;
+        Ok(CssRule::Value(rule))
+      }
+      "icss" => {
+        let rule = IcssRule::deserialize(deserializer)?;
+        Ok(CssRule::Icss(rule))
+      }
       "ignored" => Ok(CssRule::Ignored),
       "unknown" => {
         let rule = UnknownAtRule::deserialize(deserializer)?;
@@ -332,12 +358,51 @@ impl<'i, 'de: 'i, R: serde::Deserialize<'de>> serde::Deserialize<'de> for CssRul
   }
 }
 
+macro_rules! impl_from_rule {
+  ($($variant: ident($ty: ty)),+) => {
+    $(
+      impl<'i, R> From<$ty> for CssRule<'i, R> {
+        fn from(rule: $ty) -> Self {
+          CssRule::$variant(rule)
+        }
+      }
+    )+
+  };
+}
+
+impl_from_rule!(
+  Media(MediaRule<'i, R>),
+  Import(ImportRule<'i>),
+  Style(StyleRule<'i, R>),
+  Keyframes(KeyframesRule<'i>),
+  FontFace(FontFaceRule<'i>),
+  FontPaletteValues(FontPaletteValuesRule<'i>),
+  Page(PageRule<'i>),
+  Supports(SupportsRule<'i, R>),
+  CounterStyle(CounterStyleRule<'i>),
+  Namespace(NamespaceRule<'i>),
+  MozDocument(MozDocumentRule<'i, R>),
+  Nesting(NestingRule<'i, R>),
+  Viewport(ViewportRule<'i>),
+  CustomMedia(CustomMediaRule<'i>),
+  LayerStatement(LayerStatementRule<'i>),
+  LayerBlock(LayerBlockRule<'i, R>),
+  Property(PropertyRule<'i>),
+  Container(ContainerRule<'i, R>),
+  Scope(ScopeRule<'i, R>),
+  StartingStyle(StartingStyleRule<'i, R>),
+  Value(ValueRule<'i>),
+  Icss(IcssRule<'i>),
+  Unknown(UnknownAtRule<'i>)
+);
+
 impl<'a, 'i, T: ToCss> ToCss for CssRule<'i, T> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
   {
-    match self {
+    let start = dest.offset;
+    let res = match self {
       CssRule::Media(media) => media.to_css(dest),
       CssRule::Import(import) => import.to_css(dest),
       CssRule::Style(style) => style.to_css(dest),
@@ -358,13 +423,33 @@ impl<'a, 'i, T: ToCss> ToCss for CssRule<'i, T> {
       CssRule::StartingStyle(rule) => rule.to_css(dest),
       CssRule::Container(container) => container.to_css(dest),
       CssRule::Scope(scope) => scope.to_css(dest),
+      CssRule::Value(value) => value.to_css(dest),
+      CssRule::Icss(icss) => icss.to_css(dest),
       CssRule::Unknown(unknown) => unknown.to_css(dest),
       CssRule::Custom(rule) => rule.to_css(dest).map_err(|_| PrinterError {
         kind: PrinterErrorKind::FmtError,
         loc: None,
       }),
       CssRule::Ignored => Ok(()),
+    };
+
+    if let (Some(coverage_map), Some(loc)) = (&mut dest.coverage_map, self.loc()) {
+      // Rules that print nothing, e.g. empty rules when minifying, are omitted.
+      // Keep the ranges sorted by their start offset. The ranges of nested rules were added first.
+      if dest.offset > start {
+        let index = coverage_map.partition_point(|range| range.start < start);
+        coverage_map.insert(
+          index,
+          CoverageRange {
+            start,
+            end: dest.offset,
+            loc,
+          },
+        );
+      }
     }
+
+    res
   }
 }
 
@@ -408,6 +493,36 @@ impl<'i, T> CssRule<'i, T> {
     let mut parser = Parser::new(&mut input);
     Self::parse_with(&mut parser, &options, at_rule_parser)
   }
+
+  /// Returns the location of the rule in the source file, or `None` for custom at-rules.
+  pub(crate) fn loc(&self) -> Option<Location> {
+    Some(match self {
+      CssRule::Media(rule) => rule.loc,
+      CssRule::Import(rule) => rule.loc,
+      CssRule::Style(rule) => rule.loc,
+      CssRule::Keyframes(rule) => rule.loc,
+      CssRule::FontFace(rule) => rule.loc,
+      CssRule::FontPaletteValues(rule) => rule.loc,
+      CssRule::Page(rule) => rule.loc,
+      CssRule::Supports(rule) => rule.loc,
+      CssRule::CounterStyle(rule) => rule.loc,
+      CssRule::Namespace(rule) => rule.loc,
+      CssRule::MozDocument(rule) => rule.loc,
+      CssRule::Nesting(rule) => rule.loc,
+      CssRule::Viewport(rule) => rule.loc,
+      CssRule::CustomMedia(rule) => rule.loc,
+      CssRule::LayerStatement(rule) => rule.loc,
+      CssRule::LayerBlock(rule) => rule.loc,
+      CssRule::Property(rule) => rule.loc,
+      CssRule::Container(rule) => rule.loc,
+      CssRule::Scope(rule) => rule.loc,
+      CssRule::StartingStyle(rule) => rule.loc,
+      CssRule::Value(rule) => rule.loc,
+      CssRule::Icss(rule) => rule.loc,
+      CssRule::Unknown(rule) => rule.loc,
+      CssRule::Ignored | CssRule::Custom(_) => return None,
+    })
+  }
 }
 
 /// A list of CSS rules.
@@ -459,6 +574,33 @@ impl<'i, T> CssRuleList<'i, T> {
   ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     parse_style_block(input, options, at_rule_parser, is_nested)
   }
+
+  /// Prepends a scope to the selectors of the style rules in the list, including those within conditional
+  /// group rules such as `@media`. Nested style rules are relative to their parent, so they are not changed.
+  pub(crate) fn scope_selectors(&mut self, scope: &Selector<'i>) {
+    for rule in &mut self.0 {
+      match rule {
+        CssRule::Style(style) => style.scope_selectors(scope),
+        CssRule::Nesting(nesting) => nesting.style.scope_selectors(scope),
+        CssRule::Media(media) => media.rules.scope_selectors(scope),
+        CssRule::Supports(supports) => supports.rules.scope_selectors(scope),
+        CssRule::MozDocument(document) => document.rules.scope_selectors(scope),
+        CssRule::LayerBlock(layer) => layer.rules.scope_selectors(scope),
+        CssRule::Container(container) => container.rules.scope_selectors(scope),
+        CssRule::StartingStyle(starting_style) => starting_style.rules.scope_selectors(scope),
+        CssRule::Scope(rule) => match &mut rule.scope_start {
+          // Rules within @scope are relative to the scoping root, so only the root needs to be scoped.
+          Some(scope_start) => {
+            for selector in scope_start.0.iter_mut() {
+              scope_selector(selector, scope);
+            }
+          }
+          None => rule.scope_start = Some(SelectorList::from(scope.clone())),
+        },
+        _ => {}
+      }
+    }
+  }
 }
 
 // Manually implemented to avoid circular child types.
@@ -471,12 +613,33 @@ impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for Css
     if visitor.visit_types().contains(VisitTypes::RULES) {
       visitor.visit_rule_list(self)
     } else {
-      self.0.visit(visitor)
+      self.visit_children(visitor)
     }
   }
 
   fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
-    self.0.visit(visitor)
+    let replace = visitor.visit_types().contains(VisitTypes::RULES);
+    let mut i = 0;
+    while i < self.0.len() {
+      let res = visit_in_context(visitor, &mut self.0[i], |visitor, rule| {
+        if replace {
+          visitor.replace_rule(rule)
+        } else {
+          rule.visit(visitor).map(|_| None)
+        }
+      });
+
+      match res? {
+        Some(rules) => {
+          let len = rules.len();
+          self.0.splice(i..i + 1, rules);
+          i += len;
+        }
+        None => i += 1,
+      }
+    }
+
+    Ok(())
   }
 }
 
@@ -486,10 +649,53 @@ pub(crate) struct MinifyContext<'a, 'i> {
   pub important_handler: &'a mut DeclarationHandler<'i>,
   pub handler_context: PropertyHandlerContext<'i, 'a>,
   pub unused_symbols: &'a HashSet<String>,
+  pub dir_fallback: DirFallback,
+  pub focus_visible_class: Option<&'a str>,
+  pub pseudo_class_states: Option<&'a PseudoClassStates>,
+  pub unsupported_features: UnsupportedFeatureMode,
+  pub supports_guards: bool,
+  pub initial_letter_fallback: bool,
+  pub ms_grid: bool,
+  pub warnings: Vec<MinifyError>,
   pub custom_media: Option<HashMap<CowArcStr<'i>, CustomMediaRule<'i>>>,
   pub css_modules: bool,
 }
 
+impl<'a, 'i> MinifyContext<'a, 'i> {
+  /// Reports a feature that is not supported by the targets, and cannot be compiled.
+  pub(crate) fn report_unsupported(
+    &mut self,
+    name: &str,
+    feature: crate::compat::Feature,
+    loc: Location,
+  ) -> Result<(), MinifyError> {
+    if self.unsupported_features == UnsupportedFeatureMode::Ignore {
+      return Ok(());
+    }
+
+    let browsers = match self.targets.browsers {
+      Some(browsers) if !feature.is_compatible(browsers) => browsers.unsupported(feature),
+      _ => return Ok(()),
+    };
+
+    let error = MinifyError {
+      kind: MinifyErrorKind::UnsupportedFeature {
+        feature: name.into(),
+        id: feature.id().into(),
+        browsers,
+      },
+      loc,
+    };
+
+    if self.unsupported_features == UnsupportedFeatureMode::Error {
+      return Err(error);
+    }
+
+    self.warnings.push(error);
+    Ok(())
+  }
+}
+
 impl<'i, T: Clone> CssRuleList<'i, T> {
   pub(crate) fn minify(
     &mut self,
@@ -579,6 +785,7 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
             }
           }
 
+          context.report_unsupported("@container", crate::compat::Feature::ContainerQueries, container.loc)?;
           if container.minify(context, parent_is_unused)? {
             continue;
           }
@@ -607,6 +814,63 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
             continue;
           }
 
+          // Replace :focus-visible with the polyfill class, and keep a copy of the original
+          // selectors behind @supports selector(:focus-visible) for browsers that support it.
+          let focus_visible = match context.focus_visible_class {
+            Some(class)
+              if !context
+                .targets
+                .browsers
+                .is_some_and(|browsers| crate::compat::Feature::FocusVisible.is_compatible(browsers)) =>
+            {
+              let mut original = SmallVec::<[Selector; 1]>::new();
+              for selector in style.selectors.0.iter_mut() {
+                let clone = selector.clone();
+                if focus_visible_to_class(std::slice::from_mut(selector), class) {
+                  original.push(clone);
+                }
+              }
+
+              if original.is_empty() {
+                None
+              } else {
+                let mut clone = style.clone();
+                clone.selectors = SelectorList::new(original);
+                clone.update_prefix(context);
+                Some(CssRule::Supports(SupportsRule {
+                  condition: SupportsCondition::Selector(":focus-visible".into()),
+                  rules: CssRuleList(vec![CssRule::Style(clone)]),
+                  loc: style.loc,
+                }))
+              }
+            }
+            _ => None,
+          };
+
+          // Duplicate selectors containing interactive pseudo classes with their simulated class equivalents.
+          if let Some(states) = context.pseudo_class_states {
+            let mut simulated = Vec::new();
+            for selector in style.selectors.0.iter() {
+              let mut clone = selector.clone();
+              if pseudo_class_states_to_classes(std::slice::from_mut(&mut clone), states) {
+                simulated.push(clone);
+              }
+            }
+            style.selectors.0.extend(simulated);
+          }
+
+          if context.unsupported_features != UnsupportedFeatureMode::Ignore {
+            let mut features = Vec::new();
+            uncompilable_features(&style.selectors.0, &mut features);
+            for (name, feature) in features {
+              context.report_unsupported(name, feature, style.loc)?;
+            }
+            #[cfg(feature = "visitor")]
+            for (name, feature) in crate::browser_support::uncompilable_declaration_features(&style.declarations) {
+              context.report_unsupported(&name, feature, style.loc)?;
+            }
+          }
+
           // If some of the selectors in this rule are not compatible with the targets,
           // we need to either wrap in :is() or split them into multiple rules.
           let incompatible = if style.selectors.0.len() > 1
@@ -647,6 +911,40 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
 
           style.update_prefix(context);
 
+          // Translate grid layouts to the -ms-grid syntax for Internet Explorer and legacy Edge if requested.
+          #[cfg(feature = "grid")]
+          if context.ms_grid
+            && context
+              .targets
+              .prefixes(VendorPrefix::None, Feature::GridTemplateColumns)
+              .contains(VendorPrefix::Ms)
+          {
+            let mut unsupported = add_ms_grid_fallbacks(&mut style.declarations.important_declarations);
+            unsupported.extend(add_ms_grid_fallbacks(&mut style.declarations.declarations));
+            for name in unsupported {
+              context.report_unsupported(&name, crate::compat::Feature::Grid, style.loc)?;
+            }
+          }
+
+          // Move values with sibling fallbacks into @supports rules if requested.
+          let guards = if context.supports_guards {
+            style.get_supports_guards()
+          } else {
+            Vec::new()
+          };
+
+          // Emulate drop caps with a floated first letter in browsers without initial-letter support.
+          let initial_letter = if context.initial_letter_fallback
+            && !context
+              .targets
+              .browsers
+              .is_some_and(|browsers| crate::compat::Feature::InitialLetter.is_compatible(browsers))
+          {
+            style.get_initial_letter_fallback()
+          } else {
+            None
+          };
+
           // Attempt to merge the new rule with the last rule we added.
           let mut merged = false;
           if let Some(CssRule::Style(last_style_rule)) = rules.last_mut() {
@@ -685,8 +983,9 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
               clone.update_prefix(context);
 
               // Also add rules for logical properties and @supports overrides.
-              let supports = context.handler_context.get_supports_rules(&clone);
+              let mut supports = context.handler_context.get_supports_rules(&clone);
               let logical = context.handler_context.get_logical_rules(&clone);
+              supports.splice(0..0, with_selectors(&guards, &clone));
               (clone, logical, supports)
             })
             .collect::<Vec<_>>();
@@ -748,10 +1047,25 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
             rules.extend(logical.0)
           }
 
+          rules.extend(guards);
           rules.extend(supports);
           for (rule, logical, supports) in incompatible_rules {
             if !rule.is_empty() {
-              rules.push(CssRule::Style(rule));
+              // Wrap rules using selectors that are not supported by all targets in @supports selector().
+              if context.supports_guards {
+                if let Ok(selector) = rule.selectors.to_css_string(PrinterOptions::default()) {
+                  let loc = rule.loc;
+                  rules.push(CssRule::Supports(SupportsRule {
+                    condition: SupportsCondition::Selector(selector.into()),
+                    rules: CssRuleList(vec![CssRule::Style(rule)]),
+                    loc,
+                  }));
+                } else {
+                  rules.push(CssRule::Style(rule));
+                }
+              } else {
+                rules.push(CssRule::Style(rule));
+              }
             }
             if !logical.is_empty() {
               let mut logical = CssRuleList(logical);
@@ -765,6 +1079,8 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
             rules.push(CssRule::Style(nested_rule));
           }
 
+          rules.extend(initial_letter);
+          rules.extend(focus_visible);
           continue;
         }
         CssRule::CounterStyle(counter_style) => {
@@ -811,6 +1127,26 @@ impl<'i, T: Clone> CssRuleList<'i, T> {
   }
 }
 
+/// Clones the given `@supports` guards from [StyleRule::get_supports_guards](StyleRule::get_supports_guards)
+/// with the selectors of another style rule.
+fn with_selectors<'i, T: Clone>(guards: &[CssRule<'i, T>], style: &StyleRule<'i, T>) -> Vec<CssRule<'i, T>> {
+  guards
+    .iter()
+    .cloned()
+    .map(|mut guard| {
+      if let CssRule::Supports(supports) = &mut guard {
+        for rule in &mut supports.rules.0 {
+          if let CssRule::Style(rule) = rule {
+            rule.selectors = style.selectors.clone();
+            rule.vendor_prefix = style.vendor_prefix;
+          }
+        }
+      }
+      guard
+    })
+    .collect()
+}
+
 fn merge_style_rules<'i, T>(
   style: &mut StyleRule<'i, T>,
   last_style_rule: &mut StyleRule<'i, T>,
@@ -870,6 +1206,121 @@ fn merge_style_rules<'i, T>(
   false
 }
 
+impl<'i, T: ToCss> CssRuleList<'i, T> {
+  /// Removes vendor prefixes that are not needed by the targets.
+  /// Returns the number of bytes reclaimed in the minified output.
+  pub(crate) fn remove_obsolete_prefixes(&mut self, targets: Targets) -> usize {
+    let minified_len = |rule: &CssRule<'i, T>| {
+      rule
+        .to_css_string(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .map_or(0, |s| s.len())
+    };
+
+    let mut unprefixed_keyframes: Vec<KeyframesName<'i>> = self
+      .0
+      .iter()
+      .filter_map(|rule| match rule {
+        CssRule::Keyframes(keyframes) if keyframes.vendor_prefix.contains(VendorPrefix::None) => {
+          Some(keyframes.name.clone())
+        }
+        _ => None,
+      })
+      .collect();
+
+    let mut bytes = 0;
+    let mut rules: Vec<CssRule<'i, T>> = Vec::with_capacity(self.0.len());
+    for mut rule in std::mem::take(&mut self.0) {
+      match &mut rule {
+        CssRule::Style(style) => {
+          bytes += style.remove_obsolete_prefixes(targets);
+
+          // Autoprefixed rules are usually followed by the unprefixed version,
+          // which is now identical. Keep only the last one.
+          if let Some(CssRule::Style(last)) = rules.last() {
+            if last.selectors == style.selectors
+              && last.declarations == style.declarations
+              && last.rules.0.is_empty()
+              && style.rules.0.is_empty()
+            {
+              bytes += minified_len(rules.last().unwrap());
+              rules.pop();
+            }
+          }
+        }
+        CssRule::Nesting(nesting) => bytes += nesting.style.remove_obsolete_prefixes(targets),
+        CssRule::Keyframes(keyframes) => {
+          for keyframe in &mut keyframes.keyframes {
+            bytes += keyframe.declarations.remove_obsolete_prefixes(targets);
+          }
+
+          let prefix = keyframes.vendor_prefix;
+          if !prefix.is_empty()
+            && !prefix.contains(VendorPrefix::None)
+            && !targets.prefixes(VendorPrefix::None, Feature::AtKeyframes).intersects(prefix)
+          {
+            if unprefixed_keyframes.contains(&keyframes.name) {
+              bytes += minified_len(&rule);
+              continue;
+            }
+
+            bytes += cssparser::ToCss::to_css_string(&prefix).len();
+            keyframes.vendor_prefix = VendorPrefix::None;
+            unprefixed_keyframes.push(keyframes.name.clone());
+          }
+        }
+        CssRule::Media(media) => bytes += media.rules.remove_obsolete_prefixes(targets),
+        CssRule::Supports(supports) => bytes += supports.rules.remove_obsolete_prefixes(targets),
+        CssRule::Container(container) => bytes += container.rules.remove_obsolete_prefixes(targets),
+        CssRule::LayerBlock(layer) => bytes += layer.rules.remove_obsolete_prefixes(targets),
+        CssRule::MozDocument(document) => bytes += document.rules.remove_obsolete_prefixes(targets),
+        CssRule::Scope(scope) => bytes += scope.rules.remove_obsolete_prefixes(targets),
+        CssRule::StartingStyle(starting_style) => bytes += starting_style.rules.remove_obsolete_prefixes(targets),
+        CssRule::Page(page) => bytes += page.declarations.remove_obsolete_prefixes(targets),
+        _ => {}
+      }
+
+      rules.push(rule);
+    }
+
+    self.0 = rules;
+    bytes
+  }
+}
+
+impl<'i, T: ToCss> CssRuleList<'i, T> {
+  /// Replaces `var()` references to the given custom properties with their values,
+  /// and removes their declarations from `:root` rules.
+  #[cfg(feature = "substitute_variables")]
+  pub(crate) fn inline_custom_properties(
+    &mut self,
+    vars: &std::collections::HashMap<&str, crate::properties::custom::TokenList<'i>>,
+  ) {
+    for rule in &mut self.0 {
+      match rule {
+        CssRule::Style(style) => style.inline_custom_properties(vars),
+        CssRule::Nesting(nesting) => nesting.style.inline_custom_properties(vars),
+        CssRule::Keyframes(keyframes) => {
+          for keyframe in &mut keyframes.keyframes {
+            keyframe.declarations.inline_custom_properties(vars, false);
+          }
+        }
+        CssRule::Media(media) => media.rules.inline_custom_properties(vars),
+        CssRule::Supports(supports) => supports.rules.inline_custom_properties(vars),
+        CssRule::Container(container) => container.rules.inline_custom_properties(vars),
+        CssRule::LayerBlock(layer) => layer.rules.inline_custom_properties(vars),
+        CssRule::MozDocument(document) => document.rules.inline_custom_properties(vars),
+        CssRule::Scope(scope) => scope.rules.inline_custom_properties(vars),
+        CssRule::StartingStyle(starting_style) => starting_style.rules.inline_custom_properties(vars),
+        CssRule::Page(page) => page.declarations.inline_custom_properties(vars, false),
+        _ => {}
+      }
+    }
+  }
+}
+
 impl<'a, 'i, T: ToCss> ToCss for CssRuleList<'i, T> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -883,6 +1334,16 @@ impl<'a, 'i, T: ToCss> ToCss for CssRuleList<'i, T> {
         continue;
       }
 
+      // @value and ICSS rules are removed from the output when compiling CSS modules.
+      if matches!(rule, CssRule::Value(..) | CssRule::Icss(..)) && dest.css_module.is_some() {
+        continue;
+      }
+
+      if let (CssRule::Import(rule), Some(_)) = (&rule, &dest.dependency_references) {
+        let reference = DependencyReference::import(rule, dest.filename());
+        dest.dependency_references.as_mut().unwrap().push(reference);
+      }
+
       // Skip @import rules if collecting dependencies.
       if let CssRule::Import(rule) = &rule {
         if dest.remove_imports {
@@ -1,7 +1,7 @@
 //! The `@page` rule.
 
 use super::Location;
-use crate::declaration::{parse_declaration, DeclarationBlock};
+use crate::declaration::{parse_declaration, DeclarationBlock, DeclarationSources};
 use crate::error::{ParserError, PrinterError};
 use crate::macros::enum_property;
 use crate::printer::Printer;
@@ -184,6 +184,7 @@ impl<'i> PageRule<'i> {
     let mut rules = Vec::new();
     let mut rule_parser = PageRuleParser {
       declarations: &mut declarations,
+      sources: DeclarationSources::default(),
       rules: &mut rules,
       options: &options,
     };
@@ -301,6 +302,7 @@ impl<'i> ToCss for PageSelector<'i> {
 
 struct PageRuleParser<'a, 'o, 'i> {
   declarations: &'a mut DeclarationBlock<'i>,
+  sources: DeclarationSources<'i>,
   rules: &'a mut Vec<PageMarginRule<'i>>,
   options: &'a ParserOptions<'o, 'i>,
 }
@@ -319,6 +321,7 @@ impl<'a, 'o, 'i> cssparser::DeclarationParser<'i> for PageRuleParser<'a, 'o, 'i>
       input,
       &mut self.declarations.declarations,
       &mut self.declarations.important_declarations,
+      &mut self.sources,
       &self.options,
     )
   }
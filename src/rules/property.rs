@@ -13,6 +13,7 @@ use crate::{
   },
 };
 use cssparser::*;
+use std::collections::HashMap;
 
 /// A [@property](https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule) rule.
 #[derive(Debug, PartialEq, Clone)]
@@ -103,6 +104,73 @@ impl<'i> PropertyRule<'i> {
   }
 }
 
+/// Adds the syntax of each `@property` rule in a style sheet to a registry of custom properties,
+/// including rules nested in `@media`, `@supports`, `@container`, `@layer` and `@document` blocks.
+/// Properties that are already registered are not overridden.
+pub(crate) fn register_property_rules(code: &str, registry: &mut HashMap<String, SyntaxString>) {
+  // Avoid tokenizing the style sheet twice when there are no @property rules.
+  if !code.as_bytes().windows(9).any(|w| w.eq_ignore_ascii_case(b"@property")) {
+    return;
+  }
+
+  let mut input = ParserInput::new(code);
+  let mut parser = Parser::new(&mut input);
+  let mut rules = HashMap::new();
+  collect_property_rules(&mut parser, &mut rules);
+
+  for (name, syntax) in rules {
+    registry.entry(name).or_insert(syntax);
+  }
+}
+
+fn collect_property_rules<'i, 't>(parser: &mut Parser<'i, 't>, rules: &mut HashMap<String, SyntaxString>) {
+  // Whether the next block belongs to a rule that may contain @property rules.
+  let mut is_group_rule = false;
+  loop {
+    match parser.next() {
+      Ok(Token::AtKeyword(name)) if name.eq_ignore_ascii_case("property") => {}
+      Ok(Token::AtKeyword(name)) => {
+        is_group_rule = match_ignore_ascii_case! { &*name,
+          "media" | "supports" | "container" | "layer" | "document" | "-moz-document" => true,
+          _ => false
+        };
+        continue;
+      }
+      Ok(Token::CurlyBracketBlock) => {
+        if is_group_rule {
+          let _ = parser.parse_nested_block(|input| {
+            collect_property_rules(input, rules);
+            Ok::<_, ParseError<ParserError>>(())
+          });
+        }
+        is_group_rule = false;
+        continue;
+      }
+      Ok(Token::Semicolon) => {
+        is_group_rule = false;
+        continue;
+      }
+      Ok(_) => continue,
+      Err(_) => break,
+    }
+
+    // Invalid rules are ignored, and later rules override earlier ones.
+    let rule: Result<PropertyRule, ParseError<ParserError>> = parser.try_parse(|input| {
+      let name = DashedIdent::parse(input)?;
+      input.expect_curly_bracket_block()?;
+      let loc = Location {
+        source_index: 0,
+        line: 0,
+        column: 0,
+      };
+      input.parse_nested_block(|input| PropertyRule::parse(name, input, loc))
+    });
+    if let Ok(rule) = rule {
+      rules.insert(rule.name.0.to_string(), rule.syntax);
+    }
+  }
+}
+
 impl<'i> ToCss for PropertyRule<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
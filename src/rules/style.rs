@@ -10,11 +10,20 @@ use crate::declaration::DeclarationBlock;
 use crate::error::ParserError;
 use crate::error::{MinifyError, PrinterError, PrinterErrorKind};
 use crate::parser::DefaultAtRule;
-use crate::printer::Printer;
-use crate::rules::CssRuleList;
-use crate::selector::{downlevel_selectors, get_prefix, is_compatible, is_unused, SelectorList};
+use crate::printer::{Printer, PrinterOptions};
+use crate::properties::font::{FontSize, LineHeight};
+use crate::properties::text::InitialLetter;
+use crate::properties::{Property, PropertyId};
+use crate::rules::supports::{SupportsCondition, SupportsRule};
+use crate::rules::{CssRule, CssRuleList};
+use crate::selector::{
+  downlevel_selectors, get_prefix, is_compatible, is_unused, remove_obsolete_prefixes, scope_selector, Selector,
+  SelectorList,
+};
+use crate::stylesheet::ParserOptions;
 use crate::targets::{should_compile, Targets};
 use crate::traits::ToCss;
+use crate::values::length::{LengthPercentage, LengthValue};
 use crate::vendor_prefix::VendorPrefix;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
@@ -31,7 +40,15 @@ pub struct StyleRule<'i, R = DefaultAtRule> {
   #[cfg_attr(feature = "serde", serde(borrow))]
   pub selectors: SelectorList<'i>,
   /// A vendor prefix override, used during selector printing.
-  #[cfg_attr(feature = "serde", serde(skip, default = "VendorPrefix::empty"))]
+  /// It is only serialized when set, e.g. for rules duplicated with prefixed selectors during minification.
+  #[cfg_attr(
+    feature = "serde",
+    serde(
+      rename = "vendorPrefix",
+      default = "VendorPrefix::empty",
+      skip_serializing_if = "VendorPrefix::is_empty"
+    )
+  )]
   #[cfg_attr(feature = "visitor", skip_visit)]
   pub vendor_prefix: VendorPrefix,
   /// The declarations within the style rule.
@@ -87,6 +104,118 @@ impl<'i, T: Clone> StyleRule<'i, T> {
 
     Ok(false)
   }
+
+  /// Moves the last declaration of each property that has sibling fallbacks (e.g. `color: #ff0; color: lab(...)`)
+  /// into a `@supports` rule, so that the fallbacks are only overridden by browsers that support the value.
+  pub(crate) fn get_supports_guards(&mut self) -> Vec<CssRule<'i, T>> {
+    let mut guards: Vec<SupportsRule<'i, T>> = Vec::new();
+
+    macro_rules! guard {
+      ($declarations: ident) => {
+        let declarations = std::mem::take(&mut self.declarations.$declarations);
+        let has_fallbacks = declarations
+          .iter()
+          .enumerate()
+          .map(|(i, property)| {
+            let property_id = property.property_id();
+            !matches!(property, Property::Custom(..))
+              && declarations[..i].iter().any(|p| p.property_id() == property_id)
+              && !declarations[i + 1..].iter().any(|p| p.property_id() == property_id)
+          })
+          .collect::<Vec<_>>();
+
+        for (property, has_fallbacks) in declarations.into_iter().zip(has_fallbacks) {
+          if !has_fallbacks {
+            self.declarations.$declarations.push(property);
+            continue;
+          }
+
+          let value = match property.value_to_css_string(PrinterOptions::default()) {
+            Ok(value) => value,
+            Err(_) => {
+              self.declarations.$declarations.push(property);
+              continue;
+            }
+          };
+
+          let condition = SupportsCondition::Declaration {
+            property_id: property.property_id(),
+            value: value.into(),
+          };
+          let guard = match guards.iter().position(|guard| guard.condition == condition) {
+            Some(index) => &mut guards[index],
+            None => {
+              guards.push(SupportsRule {
+                condition,
+                rules: CssRuleList(vec![CssRule::Style(StyleRule {
+                  selectors: self.selectors.clone(),
+                  vendor_prefix: self.vendor_prefix,
+                  declarations: DeclarationBlock::default(),
+                  rules: CssRuleList(vec![]),
+                  loc: self.loc,
+                })]),
+                loc: self.loc,
+              });
+              guards.last_mut().unwrap()
+            }
+          };
+
+          if let Some(CssRule::Style(style)) = guard.rules.0.first_mut() {
+            style.declarations.$declarations.push(property);
+          }
+        }
+      };
+    }
+
+    guard!(declarations);
+    guard!(important_declarations);
+    guards.into_iter().map(CssRule::Supports).collect()
+  }
+
+  /// Returns a rule that approximates the last `initial-letter` declaration with a floated, enlarged
+  /// letter, for browsers that support neither the standard nor the prefixed property.
+  pub(crate) fn get_initial_letter_fallback(&self) -> Option<CssRule<'i, T>> {
+    let value = self
+      .declarations
+      .declarations
+      .iter()
+      .chain(self.declarations.important_declarations.iter())
+      .rev()
+      .find_map(|property| match property {
+        Property::InitialLetter(value @ InitialLetter::Size { .. }, _) => Some(value),
+        _ => None,
+      })?;
+
+    let size = match value {
+      InitialLetter::Size { size, .. } => *size,
+      InitialLetter::Normal => return None,
+    };
+
+    let float = Property::parse_string("float".into(), "left", ParserOptions::default()).ok()?;
+    let declarations = vec![
+      float,
+      Property::FontSize(FontSize::Length(LengthPercentage::Dimension(LengthValue::Em(size)))),
+      Property::LineHeight(LineHeight::Number(1.0)),
+    ];
+
+    Some(CssRule::Supports(SupportsRule {
+      condition: SupportsCondition::Not(Box::new(SupportsCondition::Declaration {
+        property_id: PropertyId::InitialLetter(VendorPrefix::WebKit | VendorPrefix::None),
+        value: value.to_css_string(PrinterOptions::default()).ok()?.into(),
+      })),
+      rules: CssRuleList(vec![CssRule::Style(StyleRule {
+        selectors: self.selectors.clone(),
+        vendor_prefix: self.vendor_prefix,
+        declarations: DeclarationBlock {
+          declarations,
+          important_declarations: vec![],
+        },
+        rules: CssRuleList(vec![]),
+        loc: self.loc,
+      })]),
+      loc: self.loc,
+    }))
+  }
 }
 
 impl<'i, T> StyleRule<'i, T> {
@@ -101,6 +230,13 @@ impl<'i, T> StyleRule<'i, T> {
     is_compatible(&self.selectors.0, targets)
   }
 
+  /// Prepends a scope to each of the rule's selectors.
+  pub(crate) fn scope_selectors(&mut self, scope: &Selector<'i>) {
+    for selector in self.selectors.0.iter_mut() {
+      scope_selector(selector, scope);
+    }
+  }
+
   /// Returns the line and column range of the property key and value at the given index in this style rule.
   ///
   /// For performance and memory efficiency in non-error cases, source locations are not stored during parsing.
@@ -158,7 +294,8 @@ impl<'i, T> StyleRule<'i, T> {
   pub(crate) fn update_prefix(&mut self, context: &mut MinifyContext<'_, 'i>) {
     self.vendor_prefix = get_prefix(&self.selectors);
     if self.vendor_prefix.contains(VendorPrefix::None) && context.targets.should_compile_selectors() {
-      self.vendor_prefix = downlevel_selectors(self.selectors.0.as_mut_slice(), *context.targets);
+      self.vendor_prefix =
+        downlevel_selectors(self.selectors.0.as_mut_slice(), *context.targets, context.dir_fallback);
     }
   }
 }
@@ -224,6 +361,48 @@ impl<'a, 'i, T: ToCss> ToCss for StyleRule<'i, T> {
 }
 
 impl<'a, 'i, T: ToCss> StyleRule<'i, T> {
+  /// Replaces `var()` references to the given custom properties with their values in the declarations
+  /// and nested rules. If the rule only matches `:root`, declarations of the custom properties are removed.
+  #[cfg(feature = "substitute_variables")]
+  pub(crate) fn inline_custom_properties(
+    &mut self,
+    vars: &std::collections::HashMap<&str, crate::properties::custom::TokenList<'i>>,
+  ) {
+    let is_root = self.selectors.0.iter().all(|selector| {
+      matches!(
+        (selector.len(), selector.iter_raw_match_order().next()),
+        (1, Some(crate::selector::Component::Root))
+      )
+    });
+
+    self.declarations.inline_custom_properties(vars, is_root);
+    self.rules.inline_custom_properties(vars);
+  }
+
+  /// Removes vendor prefixes from the selectors, declarations, and nested rules
+  /// that are not needed by the targets. Returns the number of bytes reclaimed.
+  pub(crate) fn remove_obsolete_prefixes(&mut self, targets: Targets) -> usize {
+    let selectors_len = |selectors: &SelectorList<'i>| {
+      selectors
+        .to_css_string(PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        })
+        .map_or(0, |s| s.len())
+    };
+
+    let mut bytes = 0;
+    let len = selectors_len(&self.selectors);
+    if remove_obsolete_prefixes(&mut self.selectors, targets) {
+      bytes += len.saturating_sub(selectors_len(&self.selectors));
+      self.vendor_prefix = VendorPrefix::empty();
+    }
+
+    bytes += self.declarations.remove_obsolete_prefixes(targets);
+    bytes += self.rules.remove_obsolete_prefixes(targets);
+    bytes
+  }
+
   fn to_css_base<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
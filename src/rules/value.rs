@@ -0,0 +1,215 @@
+//! The CSS modules `@value` rule.
+
+use super::Location;
+use crate::error::{ParserError, PrinterError};
+use crate::printer::{Printer, PrinterOptions};
+use crate::properties::custom::TokenList;
+use crate::stylesheet::ParserOptions;
+use crate::traits::ToCss;
+use crate::values::string::CowArcStr;
+#[cfg(feature = "visitor")]
+use crate::visitor::Visit;
+use cssparser::*;
+
+/// A [@value](https://github.com/css-modules/postcss-modules-values) rule, used to
+/// define and import values in CSS modules.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct ValueRule<'i> {
+  /// The values defined or imported by the rule.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  pub definition: ValueDefinition<'i>,
+  /// The location of the rule in the source file.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub loc: Location,
+}
+
+/// The definition within a [@value](ValueRule) rule.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ValueDefinition<'i> {
+  /// A value declared in the current file, e.g. `@value primary: #333;`.
+  Declaration {
+    /// The name of the value.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[cfg_attr(feature = "visitor", skip_visit)]
+    name: CowArcStr<'i>,
+    /// The value to substitute for references to the name.
+    value: TokenList<'i>,
+  },
+  /// Values imported from another file, e.g. `@value primary, secondary as accent from "./colors.css";`.
+  Import {
+    /// The imported names.
+    names: Vec<ValueImport<'i>>,
+    /// The specifier of the file to import from.
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    #[cfg_attr(feature = "visitor", skip_visit)]
+    specifier: CowArcStr<'i>,
+  },
+}
+
+/// A name imported by a [@value](ValueRule) rule.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct ValueImport<'i> {
+  /// The name of the value in the imported file.
+  #[cfg_attr(feature = "serde", serde(borrow))]
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub name: CowArcStr<'i>,
+  /// The local name of the value, if renamed using `as`.
+  #[cfg_attr(feature = "visitor", skip_visit)]
+  pub alias: Option<CowArcStr<'i>>,
+}
+
+impl<'i> ValueImport<'i> {
+  /// Returns the name the value is referenced by in the current file.
+  pub fn local_name(&self) -> &str {
+    self.alias.as_ref().unwrap_or(&self.name)
+  }
+}
+
+impl<'i> ValueDefinition<'i> {
+  pub(crate) fn parse<'t>(
+    input: &mut Parser<'i, 't>,
+    options: &ParserOptions<'_, 'i>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(import) = input.try_parse(Self::parse_import) {
+      return Ok(import);
+    }
+
+    let name = input.expect_ident_cloned()?.into();
+    // The colon is optional, e.g. `@value primary #333;`.
+    input.try_parse(|input| input.expect_colon()).ok();
+    let value = TokenList::parse(input, options, 0)?;
+    if value.0.iter().all(|token| token.is_whitespace()) {
+      return Err(input.new_custom_error(ParserError::InvalidValue));
+    }
+
+    Ok(ValueDefinition::Declaration { name, value })
+  }
+
+  fn parse_import<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut names = Vec::new();
+    loop {
+      let name = input.expect_ident_cloned()?.into();
+      let alias = if input.try_parse(|input| input.expect_ident_matching("as")).is_ok() {
+        Some(input.expect_ident_cloned()?.into())
+      } else {
+        None
+      };
+      names.push(ValueImport { name, alias });
+      if input.try_parse(|input| input.expect_comma()).is_err() {
+        break;
+      }
+    }
+    input.expect_ident_matching("from")?;
+    let specifier = input.expect_string_cloned()?.into();
+    Ok(ValueDefinition::Import { names, specifier })
+  }
+}
+
+impl<'i> ValueRule<'i> {
+  /// Registers the values defined by this rule with the CSS module being printed, so that
+  /// references to them are substituted and they are included in the exports.
+  pub(crate) fn register<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    let source_index = self.loc.source_index;
+    match &self.definition {
+      ValueDefinition::Declaration { name, value } => {
+        let s = print_css_module_value(value, self.loc, dest)?;
+        if let Some(css_module) = &mut dest.css_module {
+          css_module.add_value(name, &s, source_index);
+        }
+        dest.add_css_module_value(name, s, source_index);
+      }
+      ValueDefinition::Import { names, specifier } => {
+        for import in names {
+          if let Some(css_module) = &mut dest.css_module {
+            let placeholder = css_module.import_value(&import.name, import.local_name(), specifier, source_index);
+            dest.add_css_module_value(import.local_name(), placeholder, source_index);
+          }
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Prints a CSS modules value to a string, substituting the values defined so far,
+/// so that values may reference other values.
+pub(crate) fn print_css_module_value<W>(
+  value: &TokenList,
+  loc: Location,
+  dest: &mut Printer<W>,
+) -> Result<String, PrinterError>
+where
+  W: std::fmt::Write,
+{
+  let mut s = String::new();
+  let mut printer = Printer::new(
+    &mut s,
+    PrinterOptions {
+      minify: dest.minify,
+      targets: dest.targets,
+      ..PrinterOptions::default()
+    },
+  );
+  printer.loc = loc;
+  printer.css_module_values = std::mem::take(&mut dest.css_module_values);
+  let res = value.to_css(&mut printer, false);
+  dest.css_module_values = std::mem::take(&mut printer.css_module_values);
+  res?;
+  Ok(s)
+}
+
+impl<'i> ToCss for ValueRule<'i> {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    #[cfg(feature = "sourcemap")]
+    dest.add_mapping(self.loc);
+    dest.write_str("@value ")?;
+    match &self.definition {
+      ValueDefinition::Declaration { name, value } => {
+        serialize_identifier(name, dest)?;
+        dest.delim(':', false)?;
+        value.to_css(dest, false)?;
+      }
+      ValueDefinition::Import { names, specifier } => {
+        let mut first = true;
+        for import in names {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          serialize_identifier(&import.name, dest)?;
+          if let Some(alias) = &import.alias {
+            dest.write_str(" as ")?;
+            serialize_identifier(alias, dest)?;
+          }
+        }
+        dest.write_str(" from ")?;
+        serialize_string(specifier, dest)?;
+      }
+    }
+    dest.write_char(';')
+  }
+}
@@ -1,12 +1,12 @@
 //! CSS selectors.
 
 use crate::compat::Feature;
-use crate::error::{ParserError, PrinterError};
+use crate::error::{Error, ParserError, PrinterError, SelectorError};
 use crate::parser::ParserFlags;
 use crate::printer::Printer;
 use crate::properties::custom::TokenList;
 use crate::rules::StyleContext;
-use crate::stylesheet::{ParserOptions, PrinterOptions};
+use crate::stylesheet::{ParserOptions, PrinterOptions, PseudoClassStates};
 use crate::targets::{should_compile, Targets};
 use crate::traits::{Parse, ParseWithOptions, ToCss};
 use crate::values::ident::{CustomIdent, Ident};
@@ -52,7 +52,7 @@ fn _assert_into_owned() {
   _assert::<SelectorList>();
 }
 
-use private::Selectors;
+pub(crate) use private::Selectors;
 
 /// A list of selectors.
 pub type SelectorList<'i> = parcel_selectors::SelectorList<'i, Selectors>;
@@ -63,6 +63,41 @@ pub type Component<'i> = parcel_selectors::parser::Component<'i, Selectors>;
 /// A combinator.
 pub use parcel_selectors::parser::Combinator;
 
+/// The [specificity](https://drafts.csswg.org/selectors/#specificity-rules) of a selector,
+/// as an (a, b, c) triple.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct Specificity {
+  /// The number of ID selectors (a).
+  pub ids: u32,
+  /// The number of class selectors, attribute selectors, and pseudo-classes (b).
+  pub classes: u32,
+  /// The number of type selectors and pseudo-elements (c).
+  pub elements: u32,
+}
+
+impl Specificity {
+  /// Computes the specificity of a selector.
+  ///
+  /// `:is()`, `:not()`, `:has()`, and `:nth-child(… of S)` take the specificity of their most
+  /// specific argument, and `:where()` has no specificity.
+  pub fn from_selector(selector: &Selector) -> Specificity {
+    let specificity = selector.specificity();
+    Specificity {
+      ids: specificity >> 20,
+      classes: (specificity >> 10) & 0x3ff,
+      elements: specificity & 0x3ff,
+    }
+  }
+
+  /// Parses a selector list, and returns the specificity of each selector within it.
+  pub fn parse_string(input: &str) -> Result<Vec<Specificity>, Error<ParserError<'_>>> {
+    let selectors = SelectorList::parse_string_with_options(input, ParserOptions::default())
+      .map_err(|e| Error::from(e, "".into()))?;
+    Ok(selectors.0.iter().map(Specificity::from_selector).collect())
+  }
+}
+
 impl<'i> SelectorImpl<'i> for Selectors {
   type AttrValue = CSSString<'i>;
   type Identifier = Ident<'i>;
@@ -190,6 +225,9 @@ impl<'a, 'o, 'i> parcel_selectors::parser::Parser<'i> for SelectorParser<'a, 'o,
       "corner-present" => WebKitScrollbar(WebKitScrollbarPseudoClass::CornerPresent),
       "window-inactive" => WebKitScrollbar(WebKitScrollbarPseudoClass::WindowInactive),
 
+      "local" if self.options.css_modules.is_some() => LocalMode,
+      "global" if self.options.css_modules.is_some() => GlobalMode,
+
       _ => {
         if !name.starts_with('-') {
           self.options.warn(loc.new_custom_error(SelectorParseErrorKind::UnsupportedPseudoClassOrElement(name.clone())));
@@ -365,6 +403,39 @@ enum_property! {
   }
 }
 
+/// How to match the text direction of an element in browsers that do not support
+/// the [:dir()](https://drafts.csswg.org/selectors-4/#the-dir-pseudo) pseudo class.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DirFallback {
+  /// Match the languages that are written right to left using `:lang()`.
+  #[default]
+  Lang,
+  /// Match the `dir` HTML attribute on an ancestor element, e.g. `[dir="rtl"] .foo`.
+  /// This assumes the direction is inherited from an element such as the document root.
+  /// `:dir()` within other pseudo classes such as `:is()` or `:not()` falls back to `:lang()`.
+  Attribute,
+  /// Match the `dir` HTML attribute on the element itself, e.g. `.foo[dir="rtl"]`.
+  /// This assumes every element matched by `:dir()` sets the attribute explicitly.
+  OwnAttribute,
+}
+
+/// Returns an attribute selector matching elements with the given `dir` attribute.
+pub(crate) fn dir_attribute<'i>(dir: Direction) -> Component<'i> {
+  Component::AttributeInNoNamespace {
+    local_name: Ident("dir".into()),
+    operator: AttrSelectorOperator::Equal,
+    value: CSSString(
+      match dir {
+        Direction::Ltr => "ltr",
+        Direction::Rtl => "rtl",
+      }
+      .into(),
+    ),
+    case_sensitivity: ParsedCaseSensitivity::AsciiCaseInsensitiveIfInHtmlElementInHtmlDocument,
+    never_matches: false,
+  }
+}
+
 /// A pseudo class.
 #[derive(Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(
@@ -514,6 +585,10 @@ pub enum PseudoClass<'i> {
     /// A global selector.
     selector: Box<Selector<'i>>,
   },
+  /// The CSS modules :local pseudo class without arguments, which makes the rest of the selector local.
+  LocalMode,
+  /// The CSS modules :global pseudo class without arguments, which makes the rest of the selector global.
+  GlobalMode,
 
   /// A [webkit scrollbar](https://webkit.org/blog/363/styling-scrollbars/) pseudo class.
   // https://webkit.org/blog/363/styling-scrollbars/
@@ -766,6 +841,8 @@ where
       dest.css_module = css_module;
       Ok(())
     }
+    LocalMode => dest.write_str(":local"),
+    GlobalMode => dest.write_str(":global"),
 
     // https://webkit.org/blog/363/styling-scrollbars/
     WebKitScrollbar(s) => {
@@ -823,20 +900,27 @@ impl<'i> PseudoClass<'i> {
   }
 
   pub(crate) fn get_necessary_prefixes(&mut self, targets: Targets) -> VendorPrefix {
+    match self.prefix_feature() {
+      Some((p, feature)) => {
+        *p = targets.prefixes(*p, feature);
+        *p
+      }
+      None => VendorPrefix::empty(),
+    }
+  }
+
+  fn prefix_feature(&mut self) -> Option<(&mut VendorPrefix, crate::prefixes::Feature)> {
     use crate::prefixes::Feature;
     use PseudoClass::*;
-    let (p, feature) = match self {
-      Fullscreen(p) => (p, Feature::PseudoClassFullscreen),
-      AnyLink(p) => (p, Feature::PseudoClassAnyLink),
-      ReadOnly(p) => (p, Feature::PseudoClassReadOnly),
-      ReadWrite(p) => (p, Feature::PseudoClassReadWrite),
-      PlaceholderShown(p) => (p, Feature::PseudoClassPlaceholderShown),
-      Autofill(p) => (p, Feature::PseudoClassAutofill),
-      _ => return VendorPrefix::empty(),
-    };
-
-    *p = targets.prefixes(*p, feature);
-    *p
+    match self {
+      Fullscreen(p) => Some((p, Feature::PseudoClassFullscreen)),
+      AnyLink(p) => Some((p, Feature::PseudoClassAnyLink)),
+      ReadOnly(p) => Some((p, Feature::PseudoClassReadOnly)),
+      ReadWrite(p) => Some((p, Feature::PseudoClassReadWrite)),
+      PlaceholderShown(p) => Some((p, Feature::PseudoClassPlaceholderShown)),
+      Autofill(p) => Some((p, Feature::PseudoClassAutofill)),
+      _ => None,
+    }
   }
 }
 
@@ -1233,18 +1317,25 @@ impl<'i> PseudoElement<'i> {
   }
 
   pub(crate) fn get_necessary_prefixes(&mut self, targets: Targets) -> VendorPrefix {
+    match self.prefix_feature() {
+      Some((p, feature)) => {
+        *p = targets.prefixes(*p, feature);
+        *p
+      }
+      None => VendorPrefix::empty(),
+    }
+  }
+
+  fn prefix_feature(&mut self) -> Option<(&mut VendorPrefix, crate::prefixes::Feature)> {
     use crate::prefixes::Feature;
     use PseudoElement::*;
-    let (p, feature) = match self {
-      Selection(p) => (p, Feature::PseudoElementSelection),
-      Placeholder(p) => (p, Feature::PseudoElementPlaceholder),
-      Backdrop(p) => (p, Feature::PseudoElementBackdrop),
-      FileSelectorButton(p) => (p, Feature::PseudoElementFileSelectorButton),
-      _ => return VendorPrefix::empty(),
-    };
-
-    *p = targets.prefixes(*p, feature);
-    *p
+    match self {
+      Selection(p) => Some((p, Feature::PseudoElementSelection)),
+      Placeholder(p) => Some((p, Feature::PseudoElementPlaceholder)),
+      Backdrop(p) => Some((p, Feature::PseudoElementBackdrop)),
+      FileSelectorButton(p) => Some((p, Feature::PseudoElementFileSelectorButton)),
+      _ => None,
+    }
   }
 }
 
@@ -1929,6 +2020,43 @@ pub(crate) fn get_prefix(selectors: &SelectorList) -> VendorPrefix {
   prefix
 }
 
+/// Removes vendor prefixes from pseudo classes and elements that are not needed by the targets.
+/// The selectors are only changed if none of their prefixes are needed. Returns whether they were changed.
+pub(crate) fn remove_obsolete_prefixes(selectors: &mut SelectorList, targets: Targets) -> bool {
+  let mut result = selectors.clone();
+  let mut changed = false;
+  for selector in result.0.iter_mut() {
+    for component in selector.iter_mut_raw_match_order() {
+      let prefixed = match component {
+        Component::NonTSPseudoClass(pc) => pc.prefix_feature(),
+        Component::PseudoElement(pe) => pe.prefix_feature(),
+        // :-webkit-any() is not equivalent to :is(), so leave it alone.
+        Component::Any(..) => return false,
+        _ => None,
+      };
+
+      if let Some((prefix, feature)) = prefixed {
+        if prefix.contains(VendorPrefix::None) {
+          continue;
+        }
+
+        if targets.prefixes(VendorPrefix::None, feature).intersects(*prefix) {
+          return false;
+        }
+
+        *prefix = VendorPrefix::None;
+        changed = true;
+      }
+    }
+  }
+
+  if changed {
+    *selectors = result;
+  }
+
+  changed
+}
+
 const RTL_LANGS: &[&str] = &[
   "ae", "ar", "arc", "bcc", "bqi", "ckb", "dv", "fa", "glk", "he", "ku", "mzn", "nqo", "pnb", "ps", "sd", "ug",
   "ur", "yi",
@@ -1936,25 +2064,73 @@ const RTL_LANGS: &[&str] = &[
 
 /// Downlevels the given selectors to be compatible with the given browser targets.
 /// Returns the necessary vendor prefixes.
-pub(crate) fn downlevel_selectors(selectors: &mut [Selector], targets: Targets) -> VendorPrefix {
+pub(crate) fn downlevel_selectors(
+  selectors: &mut [Selector],
+  targets: Targets,
+  dir_fallback: DirFallback,
+) -> VendorPrefix {
+  if dir_fallback == DirFallback::Attribute && should_compile!(targets, DirSelector) {
+    for selector in selectors.iter_mut() {
+      dir_to_ancestor_attribute(selector);
+    }
+  }
+
+  downlevel_nested_selectors(selectors, targets, dir_fallback)
+}
+
+fn downlevel_nested_selectors(
+  selectors: &mut [Selector],
+  targets: Targets,
+  dir_fallback: DirFallback,
+) -> VendorPrefix {
   let mut necessary_prefixes = VendorPrefix::empty();
   for selector in selectors {
     for component in selector.iter_mut_raw_match_order() {
-      necessary_prefixes |= downlevel_component(component, targets);
+      necessary_prefixes |= downlevel_component(component, targets, dir_fallback);
     }
   }
 
   necessary_prefixes
 }
 
-fn downlevel_component<'i>(component: &mut Component<'i>, targets: Targets) -> VendorPrefix {
+/// Replaces the top-level :dir() pseudo classes in a selector with a `[dir]` attribute
+/// selector matching an ancestor, e.g. `.foo:dir(rtl)` becomes `[dir="rtl"] .foo`.
+///
+/// Selectors with conflicting directions, e.g. `a:dir(rtl):dir(ltr)`, are left unchanged,
+/// since they cannot be expressed by a single ancestor.
+fn dir_to_ancestor_attribute(selector: &mut Selector) {
+  let mut direction = None;
+  for component in selector.iter_raw_match_order() {
+    if let Component::NonTSPseudoClass(PseudoClass::Dir { direction: dir }) = component {
+      match direction {
+        Some(direction) if direction != *dir => return,
+        _ => direction = Some(*dir),
+      }
+    }
+  }
+
+  if let Some(direction) = direction {
+    selector.retain(|component| !matches!(component, Component::NonTSPseudoClass(PseudoClass::Dir { .. })));
+    selector.prepend(dir_attribute(direction), Combinator::Descendant);
+  }
+}
+
+fn downlevel_component<'i>(
+  component: &mut Component<'i>,
+  targets: Targets,
+  dir_fallback: DirFallback,
+) -> VendorPrefix {
   match component {
     Component::NonTSPseudoClass(pc) => {
       match pc {
         PseudoClass::Dir { direction: dir } => {
           if should_compile!(targets, DirSelector) {
-            *component = downlevel_dir(*dir, targets);
-            downlevel_component(component, targets)
+            *component = if dir_fallback == DirFallback::OwnAttribute {
+              dir_attribute(*dir)
+            } else {
+              downlevel_dir(*dir, targets)
+            };
+            downlevel_component(component, targets, dir_fallback)
           } else {
             VendorPrefix::empty()
           }
@@ -1964,7 +2140,7 @@ fn downlevel_component<'i>(component: &mut Component<'i>, targets: Targets) -> V
           // compile this to :is(:lang(a), :lang(b)) etc.
           if langs.len() > 1 && should_compile!(targets, LangSelectorList) {
             *component = Component::Is(lang_list_to_selectors(&langs));
-            downlevel_component(component, targets)
+            downlevel_component(component, targets, dir_fallback)
           } else {
             VendorPrefix::empty()
           }
@@ -1974,7 +2150,7 @@ fn downlevel_component<'i>(component: &mut Component<'i>, targets: Targets) -> V
     }
     Component::PseudoElement(pe) => pe.get_necessary_prefixes(targets),
     Component::Is(selectors) => {
-      let mut necessary_prefixes = downlevel_selectors(&mut **selectors, targets);
+      let mut necessary_prefixes = downlevel_nested_selectors(&mut **selectors, targets, dir_fallback);
 
       // Convert :is to :-webkit-any/:-moz-any if needed.
       // All selectors must be simple, no combinators are supported.
@@ -1990,7 +2166,7 @@ fn downlevel_component<'i>(component: &mut Component<'i>, targets: Targets) -> V
       necessary_prefixes
     }
     Component::Negation(selectors) => {
-      let mut necessary_prefixes = downlevel_selectors(&mut **selectors, targets);
+      let mut necessary_prefixes = downlevel_nested_selectors(&mut **selectors, targets, dir_fallback);
 
       // Downlevel :not(.a, .b) -> :not(:is(.a, .b)) if not list is unsupported.
       // We need to use :is() / :-webkit-any() rather than :not(.a):not(.b) to ensure the specificity is equivalent.
@@ -2009,12 +2185,408 @@ fn downlevel_component<'i>(component: &mut Component<'i>, targets: Targets) -> V
       necessary_prefixes
     }
     Component::Where(selectors) | Component::Any(_, selectors) | Component::Has(selectors) => {
-      downlevel_selectors(&mut **selectors, targets)
+      downlevel_nested_selectors(&mut **selectors, targets, dir_fallback)
     }
     _ => VendorPrefix::empty(),
   }
 }
 
+/// Collects the features used by the given selectors that cannot be compiled for older browsers,
+/// along with the names to report them with.
+pub(crate) fn uncompilable_features(selectors: &[Selector], features: &mut Vec<(&'static str, Feature)>) {
+  for selector in selectors {
+    for component in selector.iter_raw_match_order() {
+      let feature = match component {
+        Component::Has(selectors) => {
+          uncompilable_features(selectors, features);
+          (":has()", Feature::HasSelector)
+        }
+        Component::Where(selectors) => {
+          uncompilable_features(selectors, features);
+          (":where()", Feature::IsSelector)
+        }
+        Component::NthOf(n) => {
+          uncompilable_features(n.selectors(), features);
+          (":nth-child(An+B of S)", Feature::NthChildOf)
+        }
+        Component::Is(selectors) | Component::Negation(selectors) | Component::Any(_, selectors) => {
+          uncompilable_features(selectors, features);
+          continue;
+        }
+        Component::Part(_) => ("::part()", Feature::PartPseudo),
+        Component::NonTSPseudoClass(PseudoClass::FocusVisible) => (":focus-visible", Feature::FocusVisible),
+        Component::NonTSPseudoClass(PseudoClass::FocusWithin) => (":focus-within", Feature::FocusWithin),
+        _ => continue,
+      };
+
+      if !features.contains(&feature) {
+        features.push(feature);
+      }
+    }
+  }
+}
+
+/// Collects the selector features used by the given selectors that are newer than CSS 2 and
+/// Selectors Level 3, e.g. for a browser support report.
+#[cfg(feature = "visitor")]
+pub(crate) fn selector_features(selectors: &[Selector], features: &mut Vec<(&'static str, Feature)>) {
+  for selector in selectors {
+    for component in selector.iter_raw_match_order() {
+      let feature = match component {
+        Component::Has(selectors) => {
+          selector_features(selectors, features);
+          (":has()", Feature::HasSelector)
+        }
+        Component::Is(selectors) => {
+          selector_features(selectors, features);
+          (":is()", Feature::IsSelector)
+        }
+        Component::Where(selectors) => {
+          selector_features(selectors, features);
+          (":where()", Feature::IsSelector)
+        }
+        Component::Negation(selectors) => {
+          selector_features(selectors, features);
+          if selectors.len() > 1 {
+            (":not() with a selector list", Feature::NotSelectorList)
+          } else {
+            continue;
+          }
+        }
+        Component::NthOf(n) => {
+          selector_features(n.selectors(), features);
+          (":nth-child(An+B of S)", Feature::NthChildOf)
+        }
+        Component::Nesting => ("&", Feature::Nesting),
+        Component::Host(_) => (":host", Feature::Shadowdomv1),
+        Component::Slotted(_) => ("::slotted()", Feature::Shadowdomv1),
+        Component::Part(_) => ("::part()", Feature::PartPseudo),
+        Component::AttributeInNoNamespace { case_sensitivity, .. }
+          if *case_sensitivity == ParsedCaseSensitivity::AsciiCaseInsensitive =>
+        {
+          ("case-insensitive attribute selectors", Feature::CaseInsensitive)
+        }
+        Component::NonTSPseudoClass(pseudo) => match pseudo {
+          PseudoClass::Lang { languages } if languages.len() > 1 => {
+            (":lang() with a language list", Feature::LangSelectorList)
+          }
+          PseudoClass::Dir { .. } => (":dir()", Feature::DirSelector),
+          PseudoClass::AnyLink(VendorPrefix::None) => (":any-link", Feature::AnyLink),
+          PseudoClass::FocusVisible => (":focus-visible", Feature::FocusVisible),
+          PseudoClass::FocusWithin => (":focus-within", Feature::FocusWithin),
+          PseudoClass::Fullscreen(VendorPrefix::None) => (":fullscreen", Feature::Fullscreen),
+          PseudoClass::PlaceholderShown(VendorPrefix::None) => (":placeholder-shown", Feature::PlaceholderShown),
+          PseudoClass::Autofill(VendorPrefix::None) => (":autofill", Feature::Autofill),
+          _ => continue,
+        },
+        Component::PseudoElement(pseudo) => match pseudo {
+          PseudoElement::Marker => ("::marker", Feature::MarkerPseudo),
+          PseudoElement::Placeholder(VendorPrefix::None) => ("::placeholder", Feature::Placeholder),
+          PseudoElement::Selection(VendorPrefix::None) => ("::selection", Feature::Selection),
+          PseudoElement::Backdrop(VendorPrefix::None) => ("::backdrop", Feature::Dialog),
+          _ => continue,
+        },
+        _ => continue,
+      };
+
+      if !features.contains(&feature) {
+        features.push(feature);
+      }
+    }
+  }
+}
+
+/// Replaces `:focus-visible` pseudo classes in the given selectors with the given class name,
+/// e.g. `.foo:focus-visible` becomes `.foo.focus-visible`. Returns whether any were replaced.
+pub(crate) fn focus_visible_to_class(selectors: &mut [Selector], class: &str) -> bool {
+  pseudo_classes_to_classes(selectors, &|pseudo_class| match pseudo_class {
+    PseudoClass::FocusVisible => Some(class),
+    _ => None,
+  })
+}
+
+pub(crate) fn pseudo_class_states_to_classes(selectors: &mut [Selector], states: &PseudoClassStates) -> bool {
+  pseudo_classes_to_classes(selectors, &|pseudo_class| match pseudo_class {
+    PseudoClass::Hover => states.hover.as_deref(),
+    PseudoClass::Active => states.active.as_deref(),
+    PseudoClass::Focus => states.focus.as_deref(),
+    PseudoClass::FocusVisible => states.focus_visible.as_deref(),
+    PseudoClass::FocusWithin => states.focus_within.as_deref(),
+    _ => None,
+  })
+}
+
+fn pseudo_classes_to_classes<'a>(
+  selectors: &mut [Selector],
+  class_for: &dyn Fn(&PseudoClass) -> Option<&'a str>,
+) -> bool {
+  let mut replaced = false;
+  for selector in selectors {
+    for component in selector.iter_mut_raw_match_order() {
+      replaced |= match component {
+        Component::NonTSPseudoClass(pseudo_class) => match class_for(pseudo_class) {
+          Some(class) => {
+            *component = Component::Class(Ident(class.to_owned().into()));
+            true
+          }
+          None => false,
+        },
+        Component::Is(selectors)
+        | Component::Where(selectors)
+        | Component::Negation(selectors)
+        | Component::Any(_, selectors)
+        | Component::Has(selectors) => pseudo_classes_to_classes(selectors, class_for),
+        _ => false,
+      };
+    }
+  }
+
+  replaced
+}
+
+/// Returns whether a selector contains at least one local class or id, as required by the CSS modules
+/// `pure` option. Nested selectors are always pure, since they are relative to a pure parent.
+pub(crate) fn is_pure_css_module_selector(selector: &Selector) -> bool {
+  selector.iter_raw_match_order().any(|component| match component {
+    Component::Class(..) | Component::ID(..) | Component::Nesting => true,
+    Component::NonTSPseudoClass(PseudoClass::Local { selector }) => is_pure_css_module_selector(selector),
+    Component::Negation(selectors)
+    | Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Has(selectors)
+    | Component::Any(_, selectors) => selectors.iter().any(is_pure_css_module_selector),
+    _ => false,
+  })
+}
+
+/// Returns whether the given selectors form a CSS modules `:global` or `:local` block, e.g. `:global { .foo {} }`,
+/// and if so, whether the block is global. Nested blocks are preceded by an implicit nesting selector.
+pub(crate) fn css_module_block_mode(selectors: &SelectorList) -> Option<bool> {
+  if selectors.0.len() != 1 {
+    return None;
+  }
+
+  let mut iter = selectors.0[0].iter_raw_match_order();
+  let is_global = match iter.next()? {
+    Component::NonTSPseudoClass(PseudoClass::GlobalMode) => true,
+    Component::NonTSPseudoClass(PseudoClass::LocalMode) => false,
+    _ => return None,
+  };
+
+  match (iter.next(), iter.next(), iter.next()) {
+    (None, _, _) | (Some(Component::Combinator(Combinator::Descendant)), Some(Component::Nesting), None) => {
+      Some(is_global)
+    }
+    _ => None,
+  }
+}
+
+/// Rewrites the CSS modules `:global` and `:local` pseudo classes without arguments into their functional forms,
+/// e.g. `.foo :global .bar .baz` becomes `.foo :global(.bar) :global(.baz)`. A compound selector containing only
+/// a mode switch is removed along with the following combinator.
+pub(crate) fn resolve_css_module_modes<'i>(
+  selectors: &mut SelectorList<'i>,
+  is_global: bool,
+) -> Result<(), SelectorError<'i>> {
+  for selector in selectors.0.iter_mut() {
+    if selector.iter_raw_match_order().any(has_nested_mode_switch) {
+      return Err(SelectorError::NestedCssModulesPseudoClass);
+    }
+
+    if !is_global && !selector.iter_raw_match_order().any(is_mode_switch) {
+      continue;
+    }
+
+    let mut is_global = is_global;
+    let mut components = Vec::new();
+    let mut global = Vec::new();
+    let mut combinators = selector.iter_raw_match_order().rev().filter_map(|x| x.as_combinator());
+    for compound in selector.iter_raw_match_order().as_slice().split(|x| x.is_combinator()).rev() {
+      let len = components.len();
+      for component in compound {
+        match component {
+          Component::NonTSPseudoClass(PseudoClass::GlobalMode) => is_global = true,
+          Component::NonTSPseudoClass(PseudoClass::LocalMode) => {
+            push_global(&mut global, &mut components);
+            is_global = false;
+          }
+          Component::Nesting
+          | Component::PseudoElement(..)
+          | Component::NonTSPseudoClass(PseudoClass::Global { .. } | PseudoClass::Local { .. }) => {
+            push_global(&mut global, &mut components);
+            components.push(component.clone());
+          }
+          _ if is_global => global.push(component.clone()),
+          _ => {
+            push_global(&mut global, &mut components);
+            components.push(component.clone());
+          }
+        }
+      }
+      push_global(&mut global, &mut components);
+
+      match combinators.next() {
+        Some(combinator) if components.len() > len => components.push(Component::Combinator(combinator)),
+        Some(_) => {}
+        None if components.len() > len => {}
+        None => return Err(SelectorError::DanglingCssModulesPseudoClass),
+      }
+    }
+
+    selector.replace_components(components);
+  }
+
+  Ok(())
+}
+
+fn push_global<'i>(global: &mut Vec<Component<'i>>, components: &mut Vec<Component<'i>>) {
+  if !global.is_empty() {
+    components.push(Component::NonTSPseudoClass(PseudoClass::Global {
+      selector: Box::new(Selector::from(std::mem::take(global))),
+    }));
+  }
+}
+
+fn is_mode_switch(component: &Component) -> bool {
+  matches!(
+    component,
+    Component::NonTSPseudoClass(PseudoClass::GlobalMode | PseudoClass::LocalMode)
+  )
+}
+
+fn has_nested_mode_switch(component: &Component) -> bool {
+  let selectors: &[Selector] = match component {
+    Component::Is(selectors)
+    | Component::Where(selectors)
+    | Component::Negation(selectors)
+    | Component::Any(_, selectors)
+    | Component::Has(selectors) => selectors,
+    Component::NthOf(nth) => nth.selectors(),
+    Component::NonTSPseudoClass(PseudoClass::Global { selector } | PseudoClass::Local { selector }) => {
+      std::slice::from_ref(&**selector)
+    }
+    _ => return false,
+  };
+
+  selectors.iter().any(|selector| {
+    selector
+      .iter_raw_match_order()
+      .any(|c| is_mode_switch(c) || has_nested_mode_switch(c))
+  })
+}
+
+/// Removes the leading implicit nesting selector from a rule within a top level CSS modules `:global` or `:local`
+/// block, e.g. `& .foo` becomes `.foo`. Returns false if the nesting selector is used in any other way.
+pub(crate) fn strip_css_module_block_nesting(selector: &mut Selector) -> bool {
+  let mut components = Vec::new();
+  let mut combinators = selector.iter_raw_match_order().rev().filter_map(|x| x.as_combinator());
+  for compound in selector.iter_raw_match_order().as_slice().split(|x| x.is_combinator()).rev() {
+    components.extend(compound.iter().cloned());
+    if let Some(combinator) = combinators.next() {
+      components.push(Component::Combinator(combinator));
+    }
+  }
+
+  if components.len() < 3
+    || !matches!(
+      &components[..2],
+      [Component::Nesting, Component::Combinator(Combinator::Descendant)]
+    )
+    || components[2..].iter().any(|c| matches!(c, Component::Nesting))
+  {
+    return false;
+  }
+
+  components.drain(..2);
+  selector.replace_components(components);
+  true
+}
+
+/// Prepends a scope to a selector, e.g. `.foo` becomes `.widget .foo`. Leading `:root`, `html`, and `body`
+/// selectors refer to the document, so they are replaced with the scope instead, e.g. `html body .foo` becomes
+/// `.widget .foo`, and `body.dark .foo` becomes `.widget.dark .foo`. Selectors that already start with the scope
+/// are not changed.
+pub(crate) fn scope_selector<'i>(selector: &mut Selector<'i>, scope: &Selector<'i>) {
+  let (scope_compounds, scope_combinators) = split_compounds(scope);
+  let (compounds, combinators) = split_compounds(selector);
+
+  let n = scope_compounds.len();
+  if compounds.len() >= n
+    && compounds[..n - 1] == scope_compounds[..n - 1]
+    && combinators[..n - 1] == scope_combinators[..]
+    && scope_compounds[n - 1].iter().all(|c| compounds[n - 1].contains(c))
+  {
+    return;
+  }
+
+  // Skip leading compound selectors that only match the document root, e.g. `html` in `html body .foo`.
+  let mut start = 0;
+  let has_root = compounds[0].iter().any(is_document_root);
+  while start + 1 < compounds.len()
+    && compounds[start].iter().all(is_document_root)
+    && matches!(combinators[start], Combinator::Descendant | Combinator::Child)
+    && compounds[start + 1].iter().any(is_document_root)
+  {
+    start += 1;
+  }
+
+  let mut components = Vec::new();
+  for (i, compound) in scope_compounds.iter().enumerate() {
+    components.extend(compound.iter().cloned());
+    if let Some(combinator) = scope_combinators.get(i) {
+      components.push(Component::Combinator(*combinator));
+    }
+  }
+
+  if has_root {
+    // Merge the rest of the compound selector with the scope.
+    components.extend(compounds[start].iter().filter(|c| !is_document_root(c)).cloned());
+  } else {
+    components.push(Component::Combinator(Combinator::Descendant));
+    if compounds[start].is_empty() {
+      // e.g. `::before`, which has an empty compound selector before the pseudo element.
+      components.push(Component::ExplicitUniversalType);
+    }
+    components.extend(compounds[start].iter().cloned());
+  }
+
+  for (compound, combinator) in compounds[start + 1..].iter().zip(&combinators[start..]) {
+    components.push(Component::Combinator(*combinator));
+    components.extend(compound.iter().cloned());
+  }
+
+  selector.replace_components(components);
+}
+
+/// Splits a selector into its compound selectors and the combinators between them, in parse order.
+fn split_compounds<'a, 'i>(selector: &'a Selector<'i>) -> (Vec<&'a [Component<'i>]>, Vec<Combinator>) {
+  let compounds = selector
+    .iter_raw_match_order()
+    .as_slice()
+    .split(|x| x.is_combinator())
+    .rev()
+    .collect();
+  let combinators = selector
+    .iter_raw_match_order()
+    .rev()
+    .filter_map(|x| x.as_combinator())
+    .collect();
+  (compounds, combinators)
+}
+
+/// Returns whether a simple selector only matches the root element or the body, i.e. `:root`, `html`, `body`,
+/// or `:is()` and `:where()` containing only these.
+fn is_document_root(component: &Component) -> bool {
+  match component {
+    Component::Root => true,
+    Component::LocalName(name) => matches!(name.lower_name.0.as_ref(), "html" | "body"),
+    Component::Is(selectors) | Component::Where(selectors) => selectors
+      .iter()
+      .all(|selector| selector.iter_raw_match_order().all(is_document_root)),
+    _ => false,
+  }
+}
+
 fn lang_list_to_selectors<'i>(langs: &Vec<CowArcStr<'i>>) -> Box<[Selector<'i>]> {
   langs
     .iter()
@@ -2087,7 +2659,7 @@ pub(crate) fn is_unused(
 #[cfg(feature = "visitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
 impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for SelectorList<'i> {
-  const CHILD_TYPES: VisitTypes = VisitTypes::SELECTORS;
+  const CHILD_TYPES: VisitTypes = crate::visit_types!(SELECTORS | SELECTOR_COMPONENTS);
 
   fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
     if visitor.visit_types().contains(VisitTypes::SELECTORS) {
@@ -2105,17 +2677,63 @@ impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for Sel
 #[cfg(feature = "visitor")]
 #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
 impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for Selector<'i> {
-  const CHILD_TYPES: VisitTypes = VisitTypes::SELECTORS;
+  const CHILD_TYPES: VisitTypes = crate::visit_types!(SELECTORS | SELECTOR_COMPONENTS);
 
   fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
-    visitor.visit_selector(self)
+    if visitor.visit_types().contains(VisitTypes::SELECTORS) {
+      visitor.visit_selector(self)
+    } else {
+      self.visit_children(visitor)
+    }
   }
 
-  fn visit_children(&mut self, _visitor: &mut V) -> Result<(), V::Error> {
+  fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    if !visitor.visit_types().contains(VisitTypes::SELECTOR_COMPONENTS) {
+      return Ok(());
+    }
+
+    self
+      .iter_mut_raw_match_order()
+      .try_for_each(|component| Visit::visit(component, visitor))?;
+    self.update_specificity();
     Ok(())
   }
 }
 
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+impl<'i, T: Visit<'i, T, V>, V: ?Sized + Visitor<'i, T>> Visit<'i, T, V> for Component<'i> {
+  const CHILD_TYPES: VisitTypes = VisitTypes::SELECTOR_COMPONENTS;
+
+  fn visit(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    visitor.visit_selector_component(self)
+  }
+
+  fn visit_children(&mut self, visitor: &mut V) -> Result<(), V::Error> {
+    match self {
+      Component::Negation(selectors)
+      | Component::Is(selectors)
+      | Component::Where(selectors)
+      | Component::Has(selectors)
+      | Component::Any(_, selectors) => {
+        selectors.iter_mut().try_for_each(|selector| Visit::visit(selector, visitor))
+      }
+      Component::NthOf(nth_of) => nth_of
+        .selectors_mut()
+        .iter_mut()
+        .try_for_each(|selector| Visit::visit(selector, visitor)),
+      Component::Slotted(selector) | Component::Host(Some(selector)) => Visit::visit(selector, visitor),
+      Component::NonTSPseudoClass(PseudoClass::Local { selector })
+      | Component::NonTSPseudoClass(PseudoClass::Global { selector })
+      | Component::PseudoElement(PseudoElement::CueFunction { selector })
+      | Component::PseudoElement(PseudoElement::CueRegionFunction { selector }) => {
+        Visit::visit(&mut **selector, visitor)
+      }
+      _ => Ok(()),
+    }
+  }
+}
+
 impl<'i> ParseWithOptions<'i> for Selector<'i> {
   fn parse_with_options<'t>(
     input: &mut Parser<'i, 't>,
@@ -0,0 +1,226 @@
+//! Selector complexity lints.
+//!
+//! [StyleSheet::lint_selectors](crate::stylesheet::StyleSheet::lint_selectors) reports selectors that
+//! may be slow to match, according to the thresholds in [SelectorLintOptions](SelectorLintOptions):
+//! long chains of descendant and child combinators, key selectors (the rightmost compound selector)
+//! that match any element, and deeply nested `:has()` and `:not()` pseudo classes.
+//! Each lint includes the serialized selector and the location of its rule, so the results can
+//! be used to fail a build in CI.
+
+use crate::printer::PrinterOptions;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList, Location};
+use crate::selector::{Combinator, Component, Selector};
+use crate::traits::ToCss;
+
+/// Thresholds for [StyleSheet::lint_selectors](crate::stylesheet::StyleSheet::lint_selectors).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase", default))]
+pub struct SelectorLintOptions {
+  /// The maximum number of descendant and child combinators in a selector, including those
+  /// inherited from parent rules via nesting. `None` disables the lint.
+  pub max_descendant_depth: Option<usize>,
+  /// Whether to report selectors with combinators whose key selector matches any element,
+  /// e.g. `.foo *` or `.foo > :first-child`.
+  pub universal_key_selectors: bool,
+  /// The maximum depth of nested `:has()` and `:not()` pseudo classes, e.g. `:not(:has(.foo))` has
+  /// a depth of 2. `None` disables the lint.
+  pub max_has_not_depth: Option<usize>,
+}
+
+impl Default for SelectorLintOptions {
+  fn default() -> Self {
+    SelectorLintOptions {
+      max_descendant_depth: Some(3),
+      universal_key_selectors: true,
+      max_has_not_depth: Some(1),
+    }
+  }
+}
+
+/// A selector that exceeds a threshold in [SelectorLintOptions](SelectorLintOptions).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+pub struct SelectorLint {
+  /// The kind of lint.
+  pub kind: SelectorLintKind,
+  /// The selector, serialized as it would be printed.
+  pub selector: String,
+  /// The location of the rule that contains the selector.
+  pub loc: Location,
+}
+
+/// A kind of [SelectorLint](SelectorLint).
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+  any(feature = "serde", feature = "nodejs"),
+  derive(serde::Serialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+pub enum SelectorLintKind {
+  /// The selector has more descendant and child combinators than allowed.
+  DescendantDepth {
+    /// The number of descendant and child combinators.
+    depth: usize,
+    /// The maximum allowed.
+    max: usize,
+  },
+  /// The key selector matches any element.
+  UniversalKeySelector,
+  /// The selector nests `:has()` and `:not()` deeper than allowed.
+  HasNotDepth {
+    /// The depth of nested `:has()` and `:not()` pseudo classes.
+    depth: usize,
+    /// The maximum allowed.
+    max: usize,
+  },
+}
+
+pub(crate) struct SelectorLinter<'a> {
+  options: &'a SelectorLintOptions,
+  pub(crate) lints: Vec<SelectorLint>,
+}
+
+impl<'a> SelectorLinter<'a> {
+  pub(crate) fn new(options: &'a SelectorLintOptions) -> Self {
+    SelectorLinter {
+      options,
+      lints: Vec::new(),
+    }
+  }
+
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>, nesting_depth: usize) {
+    for rule in &rules.0 {
+      self.add_rule(rule, nesting_depth);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>, nesting_depth: usize) {
+    match rule {
+      CssRule::Style(style) => self.add_style_rule(style, nesting_depth),
+      CssRule::Nesting(nesting) => self.add_style_rule(&nesting.style, nesting_depth),
+      CssRule::Media(media) => self.add_rules(&media.rules, nesting_depth),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules, nesting_depth),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules, nesting_depth),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules, nesting_depth),
+      CssRule::Container(container) => self.add_rules(&container.rules, nesting_depth),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules, 0),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules, nesting_depth),
+      _ => {}
+    }
+  }
+
+  fn add_style_rule<R>(&mut self, style: &StyleRule<R>, nesting_depth: usize) {
+    // The nesting selector has the depth of the deepest selector in the parent rule.
+    let mut max = 0;
+    for selector in &style.selectors.0 {
+      let mut depth = descendant_depth(selector);
+      if selector.iter_raw_match_order().any(|c| matches!(c, Component::Nesting)) {
+        depth += nesting_depth;
+      }
+      max = max.max(depth);
+
+      if let Some(max_depth) = self.options.max_descendant_depth {
+        if depth > max_depth {
+          self.add(
+            SelectorLintKind::DescendantDepth { depth, max: max_depth },
+            selector,
+            style.loc,
+          );
+        }
+      }
+
+      if self.options.universal_key_selectors && has_universal_key(selector) {
+        self.add(SelectorLintKind::UniversalKeySelector, selector, style.loc);
+      }
+
+      if let Some(max_depth) = self.options.max_has_not_depth {
+        let depth = has_not_depth(selector);
+        if depth > max_depth {
+          self.add(
+            SelectorLintKind::HasNotDepth { depth, max: max_depth },
+            selector,
+            style.loc,
+          );
+        }
+      }
+    }
+
+    self.add_rules(&style.rules, max);
+  }
+
+  fn add(&mut self, kind: SelectorLintKind, selector: &Selector, loc: Location) {
+    self.lints.push(SelectorLint {
+      kind,
+      selector: selector.to_css_string(PrinterOptions::default()).unwrap_or_default(),
+      loc,
+    })
+  }
+}
+
+/// Returns the number of descendant and child combinators in a selector.
+fn descendant_depth(selector: &Selector) -> usize {
+  selector
+    .iter_raw_match_order()
+    .filter(|c| {
+      matches!(
+        c,
+        Component::Combinator(
+          Combinator::Descendant | Combinator::Child | Combinator::DeepDescendant | Combinator::Deep
+        )
+      )
+    })
+    .count()
+}
+
+/// Returns whether a selector with combinators has a key selector that matches any element,
+/// i.e. one without a type, class, id, or attribute selector.
+fn has_universal_key(selector: &Selector) -> bool {
+  for component in selector.iter_raw_match_order() {
+    match component {
+      // Pseudo elements apply to the compound selector to their left.
+      Component::Combinator(Combinator::PseudoElement) => continue,
+      Component::Combinator(_) => return true,
+      Component::LocalName(_)
+      | Component::ID(_)
+      | Component::Class(_)
+      | Component::AttributeInNoNamespaceExists { .. }
+      | Component::AttributeInNoNamespace { .. }
+      | Component::AttributeOther(_)
+      | Component::Nesting
+      | Component::Is(_)
+      | Component::Where(_)
+      | Component::Any(..)
+      | Component::Root
+      | Component::Scope
+      | Component::Host(_)
+      | Component::Slotted(_)
+      | Component::Part(_) => return false,
+      _ => {}
+    }
+  }
+
+  false
+}
+
+/// Returns the maximum depth of nested `:has()` and `:not()` pseudo classes in a selector.
+fn has_not_depth(selector: &Selector) -> usize {
+  selector
+    .iter_raw_match_order()
+    .map(|component| match component {
+      Component::Has(selectors) | Component::Negation(selectors) => 1 + max_has_not_depth(selectors),
+      Component::Is(selectors) | Component::Where(selectors) | Component::Any(_, selectors) => {
+        max_has_not_depth(selectors)
+      }
+      Component::NthOf(n) => max_has_not_depth(n.selectors()),
+      Component::Host(Some(selector)) | Component::Slotted(selector) => has_not_depth(selector),
+      _ => 0,
+    })
+    .max()
+    .unwrap_or(0)
+}
+
+fn max_has_not_depth(selectors: &[Selector]) -> usize {
+  selectors.iter().map(has_not_depth).max().unwrap_or(0)
+}
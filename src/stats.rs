@@ -0,0 +1,182 @@
+//! Style sheet statistics.
+//!
+//! [StyleSheet::stats](crate::stylesheet::StyleSheet::stats) walks a style sheet and produces a
+//! [StyleSheetStats](StyleSheetStats) report, including the number of rules of each type, the
+//! number of declarations per style rule, the specificity of each selector, the distinct
+//! colors, font families, and z-indexes that are used, and the size of the style sheet.
+//! The report can be serialized, e.g. to JSON, for use by linters and dashboards.
+
+use crate::declaration::DeclarationBlock;
+use crate::properties::position::ZIndex;
+use crate::properties::Property;
+use crate::rules::style::StyleRule;
+use crate::rules::{CssRule, CssRuleList};
+use crate::selector::{Component, Specificity};
+use crate::traits::ToCss;
+use crate::values::color::CssColor;
+use crate::visitor::{Visit, VisitTypes, Visitor};
+use std::collections::{BTreeMap, BTreeSet};
+use std::convert::Infallible;
+
+/// Statistics about a style sheet, as returned by [StyleSheet::stats](crate::stylesheet::StyleSheet::stats).
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct StyleSheetStats {
+  /// The number of rules of each type, keyed by the same names used when serializing rules
+  /// (e.g. `style`, `media`, or `font-face`). Nested rules are included.
+  pub rules: BTreeMap<&'static str, usize>,
+  /// The total number of declarations in all rules.
+  pub declarations: usize,
+  /// The number of declarations in each style rule, in source order.
+  pub declarations_per_rule: Vec<usize>,
+  /// The specificity of each selector in each style rule, in source order.
+  pub specificity: Vec<Specificity>,
+  /// The distinct colors used in declarations, serialized as they would be printed.
+  pub colors: BTreeSet<String>,
+  /// The distinct font families used in `font-family` and `font` declarations.
+  pub font_families: BTreeSet<String>,
+  /// The distinct integer values used in `z-index` declarations.
+  pub z_indexes: BTreeSet<i32>,
+  /// The size of the style sheet in bytes, when printed.
+  pub size: usize,
+  /// The size of the style sheet in bytes, when printed with `minify` enabled.
+  pub minified_size: usize,
+}
+
+impl StyleSheetStats {
+  pub(crate) fn add_rules<R>(&mut self, rules: &CssRuleList<R>, nesting: Specificity) {
+    for rule in &rules.0 {
+      self.add_rule(rule, nesting);
+    }
+  }
+
+  fn add_rule<R>(&mut self, rule: &CssRule<R>, nesting: Specificity) {
+    *self.rules.entry(rule_type(rule)).or_default() += 1;
+    match rule {
+      CssRule::Style(style) => self.add_style_rule(style, nesting),
+      CssRule::Nesting(nesting_rule) => self.add_style_rule(&nesting_rule.style, nesting),
+      CssRule::Media(media) => self.add_rules(&media.rules, nesting),
+      CssRule::Supports(supports) => self.add_rules(&supports.rules, nesting),
+      CssRule::MozDocument(document) => self.add_rules(&document.rules, nesting),
+      CssRule::LayerBlock(layer) => self.add_rules(&layer.rules, nesting),
+      CssRule::Container(container) => self.add_rules(&container.rules, nesting),
+      CssRule::Scope(scope) => self.add_rules(&scope.rules, Specificity::default()),
+      CssRule::StartingStyle(starting_style) => self.add_rules(&starting_style.rules, nesting),
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          self.add_declarations(&keyframe.declarations);
+        }
+      }
+      CssRule::Page(page) => {
+        self.add_declarations(&page.declarations);
+        for margin in &page.rules {
+          self.add_declarations(&margin.declarations);
+        }
+      }
+      CssRule::Viewport(viewport) => self.add_declarations(&viewport.declarations),
+      CssRule::CounterStyle(counter_style) => self.add_declarations(&counter_style.declarations),
+      _ => {}
+    }
+  }
+
+  fn add_style_rule<R>(&mut self, style: &StyleRule<R>, nesting: Specificity) {
+    self.declarations_per_rule.push(style.declarations.len());
+
+    // The nesting selector has the specificity of the most specific selector in the parent rule.
+    let mut max = Specificity::default();
+    for selector in &style.selectors.0 {
+      let mut specificity = Specificity::from_selector(selector);
+      for component in selector.iter_raw_match_order() {
+        if matches!(component, Component::Nesting) {
+          specificity.ids += nesting.ids;
+          specificity.classes += nesting.classes;
+          specificity.elements += nesting.elements;
+        }
+      }
+      max = max.max(specificity);
+      self.specificity.push(specificity);
+    }
+
+    self.add_declarations(&style.declarations);
+    self.add_rules(&style.rules, max);
+  }
+
+  fn add_declarations(&mut self, declarations: &DeclarationBlock) {
+    self.declarations += declarations.len();
+    // Visit a copy of the declarations, since visitors require mutable access.
+    let mut declarations = declarations.clone();
+    let _ = declarations.visit(&mut DeclarationStats { stats: self });
+  }
+}
+
+struct DeclarationStats<'a> {
+  stats: &'a mut StyleSheetStats,
+}
+
+impl<'a, 'i> Visitor<'i> for DeclarationStats<'a> {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES | VisitTypes::COLORS
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    let families = match property {
+      Property::FontFamily(families) => Some(&*families),
+      Property::Font(font) => Some(&font.family),
+      _ => None,
+    };
+
+    if let Some(families) = families {
+      for family in families {
+        if let Ok(family) = family.to_css_string(Default::default()) {
+          self.stats.font_families.insert(family);
+        }
+      }
+    }
+
+    if let Property::ZIndex(ZIndex::Integer(z)) = property {
+      self.stats.z_indexes.insert(*z);
+    }
+
+    property.visit_children(self)
+  }
+
+  fn visit_color(&mut self, color: &mut CssColor) -> Result<(), Self::Error> {
+    if let Ok(color) = color.to_css_string(Default::default()) {
+      self.stats.colors.insert(color);
+    }
+    Ok(())
+  }
+}
+
+fn rule_type<R>(rule: &CssRule<R>) -> &'static str {
+  match rule {
+    CssRule::Media(..) => "media",
+    CssRule::Import(..) => "import",
+    CssRule::Style(..) => "style",
+    CssRule::Keyframes(..) => "keyframes",
+    CssRule::FontFace(..) => "font-face",
+    CssRule::FontPaletteValues(..) => "font-palette-values",
+    CssRule::Page(..) => "page",
+    CssRule::Supports(..) => "supports",
+    CssRule::CounterStyle(..) => "counter-style",
+    CssRule::Namespace(..) => "namespace",
+    CssRule::MozDocument(..) => "moz-document",
+    CssRule::Nesting(..) => "nesting",
+    CssRule::Viewport(..) => "viewport",
+    CssRule::CustomMedia(..) => "custom-media",
+    CssRule::LayerStatement(..) => "layer-statement",
+    CssRule::LayerBlock(..) => "layer-block",
+    CssRule::Property(..) => "property",
+    CssRule::Container(..) => "container",
+    CssRule::Scope(..) => "scope",
+    CssRule::StartingStyle(..) => "starting-style",
+    CssRule::Value(..) => "value",
+    CssRule::Icss(..) => "icss",
+    CssRule::Ignored => "ignored",
+    CssRule::Unknown(..) => "unknown",
+    CssRule::Custom(..) => "custom",
+  }
+}
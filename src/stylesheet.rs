@@ -3,27 +3,60 @@
 //! A [StyleSheet](StyleSheet) represents a `.css` file or `<style>` element in HTML.
 //! A [StyleAttribute](StyleAttribute) represents an inline `style` attribute in HTML.
 
+#[cfg(feature = "visitor")]
+use crate::browser_support::{BrowserSupport, BrowserSupportReport};
+#[cfg(feature = "binary")]
+use crate::cache::BinaryError;
+#[cfg(feature = "visitor")]
+use crate::color_usage::{ColorUsage, ColorUsageCollector};
 use crate::context::{DeclarationContext, PropertyHandlerContext};
-use crate::css_modules::{CssModule, CssModuleExports, CssModuleReferences};
+use crate::css_modules::{
+  tree_shake, CssModule, CssModuleExports, CssModuleManifest, CssModuleReferences, TreeShakeReport,
+};
+#[cfg(feature = "visitor")]
+use crate::custom_properties::{CustomPropertyAnalysis, CustomPropertyReport};
 use crate::declaration::{DeclarationBlock, DeclarationHandler};
-use crate::dependencies::Dependency;
-use crate::error::{Error, ErrorLocation, MinifyErrorKind, ParserError, PrinterError, PrinterErrorKind};
+use crate::dependencies::{Dependency, DependencyReference};
+use crate::diff::{self, StyleSheetDiff};
+use crate::error::{
+  Error, ErrorLocation, MinifyError, MinifyErrorKind, ParserError, PrinterError, PrinterErrorKind,
+};
+#[cfg(feature = "visitor")]
+use crate::font_usage::{FontUsage, FontUsageReport};
+use crate::matching::SelectorMatcher;
 use crate::parser::{DefaultAtRule, DefaultAtRuleParser, TopLevelRuleParser};
+#[cfg(feature = "serde")]
+use crate::postcss;
 use crate::printer::Printer;
+use crate::properties::Property;
+use crate::query::{self, DeclarationHandle, RuleHandle};
+use crate::rules::icss::write_icss;
+use crate::rules::property::register_property_rules;
 use crate::rules::{CssRule, CssRuleList, MinifyContext};
-use crate::targets::{should_compile, Targets};
-use crate::traits::{AtRuleParser, ToCss};
+use crate::selector::{Component, DirFallback, Selector, SelectorList};
+use crate::selector_lints::{SelectorLint, SelectorLintOptions, SelectorLinter};
+#[cfg(feature = "visitor")]
+use crate::stats::StyleSheetStats;
+use crate::targets::{should_compile, Features, Targets};
+use crate::traits::{AtRuleParser, ParseWithOptions, ToCss};
 use crate::values::string::CowArcStr;
+use crate::vendor_prefix::{PrefixPolicy, PrefixRule};
+#[cfg(feature = "parallel")]
+use crate::visitor::{visit_in_context, ParallelVisitor};
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
 use cssparser::{Parser, ParserInput, StyleSheetParser};
 #[cfg(feature = "sourcemap")]
 use parcel_sourcemap::SourceMap;
 use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
 
-pub use crate::parser::{ParserFlags, ParserOptions};
+pub use crate::parser::{DraftSyntax, ParserFlags, ParserOptions, ValidationMode};
 pub use crate::printer::PrinterOptions;
-pub use crate::printer::PseudoClasses;
+pub use crate::printer::{
+  AssetEmitter, CoverageRange, InlineAssetsOptions, MathFallbackOptions, PseudoClasses, RebaseUrlsOptions,
+  RemToPxOptions,
+};
 
 /// A CSS style sheet, representing a `.css` file or inline `<style>` element.
 ///
@@ -31,6 +64,10 @@ pub use crate::printer::PseudoClasses;
 /// or created using a [Bundler](super::bundler::Bundler). Then, they can be
 /// minified and transformed for a set of target browsers, and serialied to a string.
 ///
+/// Style sheets are `Send` and `Sync`, so they can be shared between threads. Use
+/// [IntoOwned](crate::traits::IntoOwned) to move a style sheet to a thread that may
+/// outlive the source string.
+///
 /// # Example
 ///
 /// ```
@@ -59,7 +96,7 @@ pub use crate::printer::PseudoClasses;
 /// let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
 /// assert_eq!(res.code, ".foo, .bar {\n  color: red;\n}\n");
 /// ```
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(
   feature = "serde",
   derive(serde::Serialize, serde::Deserialize),
@@ -79,6 +116,10 @@ pub struct StyleSheet<'i, 'o, T = DefaultAtRule> {
   pub sources: Vec<String>,
   /// The source map URL extracted from the original style sheet.
   pub(crate) source_map_urls: Vec<Option<String>>,
+  /// The index of each source within the output source map, if it differs from its index in `sources`.
+  /// Sources with an input source map have no index, since locations are remapped to the original files.
+  #[cfg_attr(feature = "serde", serde(skip))]
+  pub(crate) source_map_indexes: Vec<Option<u32>>,
   /// The license comments that appeared at the start of the file.
   pub license_comments: Vec<CowArcStr<'i>>,
   #[cfg_attr(feature = "serde", serde(skip))]
@@ -95,6 +136,94 @@ pub struct MinifyOptions {
   /// A list of known unused symbols, including CSS class names,
   /// ids, and `@keyframe` names. The declarations of these will be removed.
   pub unused_symbols: HashSet<String>,
+  /// How to compile `:dir()` selectors, including those in the fallback rules
+  /// generated for logical properties, when the targets do not support them.
+  pub dir_fallback: DirFallback,
+  /// Additional vendor prefixing rules to apply on top of the built-in prefix data.
+  pub prefix_rules: Vec<PrefixRule>,
+  /// Per-property overrides of whether vendor prefixes are added, keyed by the lowercase, unprefixed property name.
+  /// Useful when the built-in compatibility data for a property doesn't match the browsers you need to support.
+  pub prefix_policies: HashMap<String, PrefixPolicy>,
+  /// A class name to replace `:focus-visible` with, for use with a JavaScript polyfill.
+  /// The original selectors are preserved behind `@supports selector(:focus-visible)`.
+  /// Not applied when all browser targets support `:focus-visible`.
+  pub focus_visible_class: Option<String>,
+  /// Class names to simulate interactive pseudo class states with. Style rules containing
+  /// these pseudo classes are duplicated with the classes in place of the pseudo classes,
+  /// e.g. `a:hover` becomes `a:hover, a.\:hover`.
+  pub pseudo_class_states: Option<PseudoClassStates>,
+  /// How to handle features that are not supported by the browser targets and cannot be compiled,
+  /// such as `:has()` or `@container`. By default, these are passed through as is.
+  pub unsupported_features: UnsupportedFeatureMode,
+  /// Whether to guard modern syntax with `@supports` rather than relying on declaration order alone.
+  /// When enabled, values with fallbacks for older browsers are moved into a `@supports` rule
+  /// following the fallbacks, and rules split off due to selectors that are unsupported by some targets
+  /// are wrapped in `@supports selector(...)`.
+  pub supports_guards: bool,
+  /// Whether to emulate `initial-letter` drop caps with a floated, enlarged letter in browsers
+  /// that support neither `initial-letter` nor `-webkit-initial-letter`. The fallback is wrapped
+  /// in `@supports not (initial-letter: ...)`, and is not applied when all browser targets support it.
+  pub initial_letter_fallback: bool,
+  /// Whether to translate simple grid layouts to the `-ms-grid` syntax when the browser targets include
+  /// Internet Explorer or legacy Edge. Only explicit track lists and placement by positive line numbers
+  /// are translated. Other grid features, such as `grid-template-areas` and auto-placement, are reported
+  /// according to `unsupported_features`.
+  pub ms_grid: bool,
+  /// Values of custom properties to inline at build time, keyed by name including the leading `--`,
+  /// e.g. for compiling a theme. `var()` references to these properties are replaced with the values,
+  /// ignoring their fallbacks, and declarations of the properties in `:root` rules are removed.
+  /// References to other custom properties are left as is.
+  #[cfg(feature = "substitute_variables")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "substitute_variables")))]
+  pub inline_custom_properties: HashMap<String, String>,
+  /// A list that unsupported feature warnings are added to when `unsupported_features`
+  /// is set to [UnsupportedFeatureMode::Warn](UnsupportedFeatureMode::Warn).
+  pub warnings: Option<Arc<RwLock<Vec<Error<MinifyErrorKind>>>>>,
+}
+
+/// Class names to simulate interactive pseudo class states with, e.g. for visual regression
+/// testing or style guides.
+///
+/// See [MinifyOptions](MinifyOptions).
+#[derive(Debug, Clone, Default)]
+pub struct PseudoClassStates {
+  /// The class name to simulate `:hover` with.
+  pub hover: Option<String>,
+  /// The class name to simulate `:active` with.
+  pub active: Option<String>,
+  /// The class name to simulate `:focus` with.
+  pub focus: Option<String>,
+  /// The class name to simulate `:focus-visible` with.
+  pub focus_visible: Option<String>,
+  /// The class name to simulate `:focus-within` with.
+  pub focus_within: Option<String>,
+}
+
+impl PseudoClassStates {
+  /// Simulates `:hover`, `:active`, and `:focus` with classes of the same name,
+  /// e.g. `.\:hover`.
+  pub fn escaped() -> Self {
+    PseudoClassStates {
+      hover: Some(":hover".into()),
+      active: Some(":active".into()),
+      focus: Some(":focus".into()),
+      ..PseudoClassStates::default()
+    }
+  }
+}
+
+/// How to handle features that are not supported by the browser targets, and cannot be compiled.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "lowercase"))]
+pub enum UnsupportedFeatureMode {
+  /// Pass unsupported features through as is.
+  #[default]
+  Ignore,
+  /// Add a warning for each unsupported feature to [MinifyOptions::warnings](MinifyOptions::warnings).
+  Warn,
+  /// Return an error for the first unsupported feature.
+  Error,
 }
 
 /// A result returned from `to_css`, including the serialize CSS
@@ -109,16 +238,103 @@ pub struct ToCssResult {
   /// A map of CSS module references, if the `css_modules` config
   /// had `dashed_idents` enabled.
   pub references: Option<CssModuleReferences>,
+  /// A map of source filenames to CSS module exports, if the `css_modules`
+  /// option was enabled during parsing. When bundling, this includes an
+  /// entry for each file in the bundle.
+  pub manifest: Option<CssModuleManifest>,
   /// A list of dependencies (e.g. `@import` or `url()`) found in
   /// the style sheet, if the `analyze_dependencies` option is enabled.
   pub dependencies: Option<Vec<Dependency>>,
+  /// A map of the paths of files referenced by `url()` to the urls of the emitted
+  /// files, if the `emit_asset` option is provided.
+  pub assets: Option<HashMap<String, String>>,
+  /// A list of the byte ranges of the output printed from each rule, sorted by their start
+  /// offset, if the `coverage_map` option is enabled.
+  pub coverage_map: Option<Vec<CoverageRange>>,
 }
 
+/// The version of the serialized AST format, as produced by the `serde` feature and described by
+/// [StyleSheet::json_schema](StyleSheet::json_schema).
+///
+/// This is incremented whenever a change to the format may break existing consumers, such as
+/// renaming or removing a field or enum variant, or changing the type of a value. Additions, such as
+/// a new property or rule type, do not change the version.
+#[cfg(feature = "serde")]
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+pub const AST_VERSION: u32 = 1;
+
 impl<'i, 'o> StyleSheet<'i, 'o, DefaultAtRule> {
   /// Parse a style sheet from a string.
   pub fn parse(code: &'i str, options: ParserOptions<'o, 'i>) -> Result<Self, Error<ParserError<'i>>> {
     Self::parse_with(code, options, &mut DefaultAtRuleParser)
   }
+
+  /// Returns a JSON Schema describing the serialized representation of a style sheet.
+  ///
+  /// The [AST_VERSION](AST_VERSION) is included in the `version` field of the schema.
+  #[cfg(feature = "jsonschema")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "jsonschema")))]
+  pub fn json_schema() -> schemars::schema::RootSchema {
+    let mut schema = schemars::schema_for!(StyleSheet);
+    schema.schema.extensions.insert("version".into(), AST_VERSION.into());
+    schema
+  }
+
+  /// Creates a style sheet from a PostCSS AST, e.g. after running PostCSS plugins on the result of
+  /// [to_postcss](StyleSheet::to_postcss). See the [postcss](crate::postcss) module for more details.
+  ///
+  /// The returned style sheet owns all of its data. Parser warnings are not collected.
+  #[cfg(feature = "serde")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+  pub fn from_postcss(
+    root: &postcss::Root,
+    options: ParserOptions<'o, '_>,
+  ) -> Result<StyleSheet<'static, 'o>, Error<ParserError<'static>>> {
+    use static_self::IntoOwned;
+
+    let code = root.to_css_string();
+    let options = ParserOptions {
+      filename: options.filename,
+      css_modules: options.css_modules,
+      source_index: options.source_index,
+      error_recovery: options.error_recovery,
+      warnings: None,
+      flags: options.flags,
+      disabled_drafts: options.disabled_drafts,
+      validation: options.validation,
+      registered_properties: options.registered_properties,
+    };
+    let result = StyleSheet::parse(&code, options);
+    match result {
+      Ok(stylesheet) => Ok(stylesheet.into_owned_with_options()),
+      Err(err) => Err(Error {
+        kind: err.kind.into_owned(),
+        loc: err.loc,
+      }),
+    }
+  }
+
+  /// Encodes the style sheet in a compact binary format, for caching parse results between builds.
+  ///
+  /// The given hash, typically computed by [content_hash](crate::cache::content_hash), is stored in the
+  /// header of the binary and can be read back with [binary_hash](crate::cache::binary_hash).
+  /// See the [cache](crate::cache) module for more details.
+  #[cfg(feature = "binary")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "binary")))]
+  pub fn to_binary(&self, hash: u64) -> Result<Vec<u8>, BinaryError> {
+    crate::cache::encode(self, hash)
+  }
+
+  /// Decodes a style sheet from the binary format produced by [to_binary](StyleSheet::to_binary).
+  ///
+  /// The options should be the same as those the style sheet was originally parsed with.
+  #[cfg(feature = "binary")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "binary")))]
+  pub fn from_binary(data: &'i [u8], options: ParserOptions<'o, 'i>) -> Result<Self, BinaryError> {
+    let mut stylesheet: Self = crate::cache::decode(data)?;
+    stylesheet.options = options;
+    Ok(stylesheet)
+  }
 }
 
 impl<'i, 'o, T> StyleSheet<'i, 'o, T>
@@ -134,6 +350,7 @@ where
     StyleSheet {
       sources,
       source_map_urls: Vec::new(),
+      source_map_indexes: Vec::new(),
       license_comments: Vec::new(),
       rules,
       options,
@@ -146,6 +363,10 @@ where
     mut options: ParserOptions<'o, 'i>,
     at_rule_parser: &mut P,
   ) -> Result<Self, Error<ParserError<'i>>> {
+    // @property rules may be defined after the custom properties they register are used. Do a pre-scan
+    // here so that their values can be parsed according to the registered syntax.
+    register_property_rules(code, &mut options.registered_properties);
+
     let mut input = ParserInput::new(&code);
     let mut parser = Parser::new(&mut input);
     let mut license_comments = Vec::new();
@@ -185,6 +406,7 @@ where
     Ok(StyleSheet {
       sources: vec![options.filename.clone()],
       source_map_urls: vec![parser.current_source_map_url().map(|s| s.to_owned())],
+      source_map_indexes: Vec::new(),
       rules,
       license_comments,
       options,
@@ -203,9 +425,33 @@ where
     SourceMap::from_data_url("/", self.source_map_url(source_index)?).ok()
   }
 
+  /// Removes unused CSS modules exports, given the set of exported names that are used,
+  /// e.g. as determined by a JavaScript bundler's usage analysis.
+  ///
+  /// Style rules whose selectors only match local classes or ids that are not used are removed,
+  /// along with `@keyframes` rules that are no longer used or referenced. Classes composed by a
+  /// used class are considered used. Returns a report of the exports that were removed.
+  /// Has no effect if the style sheet was not parsed as a CSS module.
+  pub fn tree_shake_css_modules(&mut self, used_exports: &HashSet<String>) -> TreeShakeReport {
+    match &self.options.css_modules {
+      Some(config) => tree_shake(&mut self.rules, used_exports, config),
+      None => TreeShakeReport::default(),
+    }
+  }
+
   /// Minify and transform the style sheet for the provided browser targets.
   pub fn minify(&mut self, options: MinifyOptions) -> Result<(), Error<MinifyErrorKind>> {
-    let context = PropertyHandlerContext::new(options.targets, &options.unused_symbols);
+    #[cfg(feature = "substitute_variables")]
+    if !options.inline_custom_properties.is_empty() {
+      self.inline_custom_properties(&options.inline_custom_properties)?;
+    }
+
+    let context = PropertyHandlerContext::new(
+      options.targets,
+      &options.unused_symbols,
+      &options.prefix_rules,
+      &options.prefix_policies,
+    );
     let mut handler = DeclarationHandler::default();
     let mut important_handler = DeclarationHandler::default();
 
@@ -231,21 +477,137 @@ where
       important_handler: &mut important_handler,
       handler_context: context,
       unused_symbols: &options.unused_symbols,
+      dir_fallback: options.dir_fallback,
+      focus_visible_class: options.focus_visible_class.as_deref(),
+      pseudo_class_states: options.pseudo_class_states.as_ref(),
+      unsupported_features: options.unsupported_features,
+      supports_guards: options.supports_guards,
+      initial_letter_fallback: options.initial_letter_fallback,
+      ms_grid: options.ms_grid,
+      warnings: Vec::new(),
       custom_media,
       css_modules: self.options.css_modules.is_some(),
     };
 
-    self.rules.minify(&mut ctx, false).map_err(|e| Error {
+    let sources = &self.sources;
+    let to_error = |e: MinifyError| Error {
       kind: e.kind,
-      loc: Some(ErrorLocation::new(
-        e.loc,
-        self.sources[e.loc.source_index as usize].clone(),
-      )),
-    })?;
+      loc: Some(ErrorLocation::new(e.loc, sources[e.loc.source_index as usize].clone())),
+    };
+
+    self.rules.minify(&mut ctx, false).map_err(to_error)?;
+
+    if let Some(warnings) = &options.warnings {
+      if let Ok(mut warnings) = warnings.write() {
+        warnings.extend(ctx.warnings.drain(..).map(to_error));
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Replaces `var()` references to the given custom properties with their values before minifying,
+  /// so that the declarations containing them can be parsed and minified as usual.
+  #[cfg(feature = "substitute_variables")]
+  fn inline_custom_properties(&mut self, values: &HashMap<String, String>) -> Result<(), Error<MinifyErrorKind>> {
+    use crate::properties::custom::TokenList;
+    use crate::traits::ParseWithOptions;
+    use static_self::IntoOwned;
+
+    let mut vars = HashMap::new();
+    for (name, value) in values {
+      let tokens = TokenList::parse_string_with_options(value, ParserOptions::default()).map_err(|_| Error {
+        kind: MinifyErrorKind::InvalidCustomPropertyValue { name: name.clone() },
+        loc: None,
+      })?;
+      let mut tokens: TokenList = tokens.into_owned();
+      while tokens.0.last().map_or(false, |token| token.is_whitespace()) {
+        tokens.0.pop();
+      }
+      if tokens.0.first().map_or(false, |token| token.is_whitespace()) {
+        tokens.0.remove(0);
+      }
+      vars.insert(name.as_str(), tokens);
+    }
 
+    self.rules.inline_custom_properties(&vars);
     Ok(())
   }
 
+  /// Minifies the style sheet for two sets of targets from a single parse, e.g. a modern build and a
+  /// heavily compiled legacy build for differential serving. This style sheet is minified using the
+  /// `modern` options, and a copy minified using the `legacy` options is returned. Each can then be
+  /// serialized with [to_css](StyleSheet::to_css), using separate printer options and source maps.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use lightningcss::stylesheet::{StyleSheet, ParserOptions, MinifyOptions, PrinterOptions};
+  /// use lightningcss::targets::Browsers;
+  ///
+  /// let mut modern = StyleSheet::parse(".foo { color: lab(40% 56.6 39) }", ParserOptions::default()).unwrap();
+  /// let legacy_targets = Browsers {
+  ///   chrome: Some(90 << 16),
+  ///   ..Browsers::default()
+  /// };
+  /// let legacy = modern
+  ///   .minify_dual(
+  ///     MinifyOptions::default(),
+  ///     MinifyOptions {
+  ///       targets: legacy_targets.into(),
+  ///       ..MinifyOptions::default()
+  ///     },
+  ///   )
+  ///   .unwrap();
+  ///
+  /// let modern = modern.to_css(PrinterOptions::default()).unwrap();
+  /// let legacy = legacy.to_css(PrinterOptions::default()).unwrap();
+  /// assert_eq!(modern.code, ".foo {\n  color: lab(40% 56.6 39);\n}\n");
+  /// assert_eq!(legacy.code, ".foo {\n  color: #b32323;\n  color: lab(40% 56.6 39);\n}\n");
+  /// ```
+  pub fn minify_dual(
+    &mut self,
+    modern: MinifyOptions,
+    legacy: MinifyOptions,
+  ) -> Result<StyleSheet<'i, 'o, T>, Error<MinifyErrorKind>> {
+    let mut legacy_stylesheet = self.clone();
+    legacy_stylesheet.minify(legacy)?;
+    self.minify(modern)?;
+    Ok(legacy_stylesheet)
+  }
+
+  /// Removes vendor prefixes that are not needed by the given targets, i.e. the reverse of
+  /// autoprefixing. This applies to prefixed declarations, `@keyframes` rules, and selectors.
+  /// Prefixed items are removed if an equivalent unprefixed item overrides them, and are
+  /// unprefixed if there is no unprefixed item at all.
+  ///
+  /// Returns the number of bytes reclaimed in the minified output.
+  pub fn remove_obsolete_prefixes(&mut self, targets: Targets) -> usize {
+    // Without browser targets, there is no way to know which prefixes are obsolete.
+    if targets.browsers.is_none() && !targets.exclude.contains(Features::VendorPrefixes) {
+      return 0;
+    }
+
+    self.rules.remove_obsolete_prefixes(targets)
+  }
+
+  /// Scopes the style sheet to a container element, e.g. to sandbox third party CSS within a widget.
+  /// The given selector, such as a class, attribute, or id selector, is prepended to the selectors of all style
+  /// rules, e.g. `.foo` becomes `.widget .foo`. If the scope is a list of multiple selectors, it is wrapped
+  /// in `:is()`.
+  ///
+  /// Leading `:root`, `html`, and `body` selectors are replaced with the scope, e.g. `body.dark .foo` becomes
+  /// `.widget.dark .foo`. The scoping roots of `@scope` rules are scoped in the same way, and `@scope` rules
+  /// without a scoping root are scoped to the container. Nested rules, and rules such as `@keyframes` and
+  /// `@font-face` that do not have selectors, are not changed.
+  pub fn scope_selectors(&mut self, scope: &SelectorList<'i>) {
+    let scope = match scope.0.as_slice() {
+      [selector] => selector.clone(),
+      selectors => Selector::from(Component::Is(selectors.into())),
+    };
+    self.rules.scope_selectors(&scope);
+  }
+
   /// Serialize the style sheet to a CSS string.
   pub fn to_css(&self, options: PrinterOptions) -> Result<ToCssResult, Error<PrinterErrorKind>> {
     // Make sure we always have capacity > 0: https://github.com/napi-rs/napi-rs/issues/1124.
@@ -261,6 +623,7 @@ where
     #[cfg(feature = "sourcemap")]
     if printer.source_map.is_some() {
       printer.source_maps = self.sources.iter().enumerate().map(|(i, _)| self.source_map(i)).collect();
+      printer.source_map_indexes = self.source_map_indexes.clone();
     }
 
     for comment in &self.license_comments {
@@ -273,16 +636,43 @@ where
       let mut references = HashMap::new();
       printer.css_module = Some(CssModule::new(config, &self.sources, project_root, &mut references));
 
+      // Register @value definitions and ICSS rules up front so they can be referenced anywhere in the file.
+      for rule in &self.rules.0 {
+        match rule {
+          CssRule::Value(value) => value.register(&mut printer)?,
+          CssRule::Icss(icss) => icss.register(&mut printer)?,
+          _ => {}
+        }
+      }
+
       self.rules.to_css(&mut printer)?;
+
+      let css_module = printer.css_module.take().unwrap();
+      if config.icss {
+        if let (Some(hash), Some(exports)) =
+          (css_module.hashes.first(), css_module.exports_by_source_index.first())
+        {
+          let exports = config.exports_convention.apply(exports.clone());
+          write_icss(&mut printer, hash, &exports, css_module.references)?;
+        }
+      }
       printer.newline()?;
 
+      let manifest: CssModuleManifest = self
+        .sources
+        .iter()
+        .zip(css_module.exports_by_source_index)
+        .map(|(source, exports)| (source.clone(), config.exports_convention.apply(exports)))
+        .collect();
+
       Ok(ToCssResult {
         dependencies: printer.dependencies,
-        exports: Some(std::mem::take(
-          &mut printer.css_module.unwrap().exports_by_source_index[0],
-        )),
+        assets: printer.assets,
+        coverage_map: printer.coverage_map,
+        exports: self.sources.first().and_then(|source| manifest.get(source).cloned()),
         code: dest,
         references: Some(references),
+        manifest: Some(manifest),
       })
     } else {
       self.rules.to_css(&mut printer)?;
@@ -290,12 +680,196 @@ where
 
       Ok(ToCssResult {
         dependencies: printer.dependencies,
+        assets: printer.assets,
+        coverage_map: printer.coverage_map,
         code: dest,
         exports: None,
         references: None,
+        manifest: None,
       })
     }
   }
+
+  /// Computes statistics about the style sheet, such as the number of rules of each type,
+  /// the specificity of each selector, and the distinct colors that are used.
+  #[cfg(feature = "visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+  pub fn stats(&self) -> Result<StyleSheetStats, Error<PrinterErrorKind>> {
+    let mut stats = StyleSheetStats::default();
+    stats.add_rules(&self.rules, Default::default());
+    stats.size = self.to_css(PrinterOptions::default())?.code.len();
+    stats.minified_size = self
+      .to_css(PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      })?
+      .code
+      .len();
+    Ok(stats)
+  }
+
+  /// Reports the features used by the style sheet that have browser compatibility data, with
+  /// their locations, and the minimum browser versions that support all of them without any transforms.
+  ///
+  /// This should be called before [minify](StyleSheet::minify), which may compile features away.
+  #[cfg(feature = "visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+  pub fn browser_support(&self) -> BrowserSupportReport {
+    let mut support = BrowserSupport::default();
+    support.add_rules(&self.rules);
+    support.report()
+  }
+
+  /// Reports custom properties that are defined but never referenced by a `var()`, and `var()`
+  /// references without a fallback to custom properties that are never defined.
+  #[cfg(feature = "visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+  pub fn analyze_custom_properties(&self) -> CustomPropertyReport {
+    let mut analysis = CustomPropertyAnalysis::default();
+    analysis.add_rules(&self.rules);
+    analysis.report()
+  }
+
+  /// Returns every color literal used in the declarations of the style sheet, normalized to an
+  /// sRGB hex color, along with the properties that use it and their locations.
+  #[cfg(feature = "visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+  pub fn color_usage(&self) -> Vec<ColorUsage> {
+    let mut collector = ColorUsageCollector::default();
+    collector.add_rules(&self.rules);
+    collector.colors
+  }
+
+  /// Reports the font families referenced by the style sheet, the `@font-face` rules that provide
+  /// them and the unicode ranges they cover, and `@font-face` rules for font families that are never referenced.
+  #[cfg(feature = "visitor")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+  pub fn font_usage(&self) -> FontUsageReport {
+    let mut usage = FontUsage::default();
+    usage.add_rules(&self.rules);
+    usage.report()
+  }
+
+  /// Reports selectors that may be slow to match, such as long chains of descendant combinators,
+  /// key selectors that match any element, and deeply nested `:has()` and `:not()` pseudo classes.
+  pub fn lint_selectors(&self, options: &SelectorLintOptions) -> Vec<SelectorLint> {
+    let mut linter = SelectorLinter::new(options);
+    linter.add_rules(&self.rules, 0);
+    linter.lints
+  }
+
+  /// Compiles the selectors of the style sheet's style rules, including nested rules, into a
+  /// [SelectorMatcher](SelectorMatcher) that can be matched against the elements of a document.
+  /// See the [matching](crate::matching) module for more details.
+  pub fn selector_matcher(&self) -> SelectorMatcher<'i> {
+    let mut matcher = SelectorMatcher::default();
+    matcher.add_rules(&self.rules, None, &mut Vec::new());
+    matcher
+  }
+
+  /// Compares the style sheet with a newer version of it, and reports the rules and declarations that
+  /// were added, removed, or changed. Formatting differences are ignored.
+  pub fn diff(&self, new: &StyleSheet<'_, '_, T>) -> Result<StyleSheetDiff, Error<PrinterErrorKind>> {
+    let mut old_entries = Vec::new();
+    diff::flatten(&self.rules, &mut Vec::new(), &mut old_entries)?;
+    let mut new_entries = Vec::new();
+    diff::flatten(&new.rules, &mut Vec::new(), &mut new_entries)?;
+    Ok(diff::diff(old_entries, new_entries))
+  }
+
+  /// Finds the style rules with a selector equal to one of the selectors in the given selector list,
+  /// including nested rules. Selectors of nested rules include the nesting selector, e.g. `& .foo`.
+  pub fn query_selector<'s>(&self, selector: &'s str) -> Result<Vec<RuleHandle>, Error<ParserError<'s>>> {
+    let selectors = SelectorList::parse_string_with_options(selector, ParserOptions::default())
+      .map_err(|e| Error::from(e, "".into()))?;
+    let selectors = selectors
+      .0
+      .iter()
+      .filter_map(|selector| selector.to_css_string(PrinterOptions::default()).ok())
+      .collect::<Vec<_>>();
+    let mut res = Vec::new();
+    query::query_selector(&self.rules, &selectors, &mut Vec::new(), &mut res);
+    Ok(res)
+  }
+
+  /// Finds the declarations of the given property, including nested rules. Vendor prefixed
+  /// declarations of the property are included.
+  pub fn query_property(&self, property: &str) -> Vec<DeclarationHandle> {
+    let mut res = Vec::new();
+    query::query_property(&self.rules, property, &mut Vec::new(), &mut res);
+    res
+  }
+
+  /// Returns the rule for a handle returned by [query_selector](StyleSheet::query_selector).
+  pub fn rule(&self, handle: &RuleHandle) -> Option<&CssRule<'i, T>> {
+    query::rule(&self.rules, handle)
+  }
+
+  /// Returns a mutable reference to the rule for a handle returned by [query_selector](StyleSheet::query_selector).
+  pub fn rule_mut(&mut self, handle: &RuleHandle) -> Option<&mut CssRule<'i, T>> {
+    query::rule_mut(&mut self.rules, handle)
+  }
+
+  /// Returns the declaration for a handle returned by [query_property](StyleSheet::query_property).
+  pub fn declaration(&self, handle: &DeclarationHandle) -> Option<&Property<'i>> {
+    query::declaration(&self.rules, handle)
+  }
+
+  /// Returns a mutable reference to the declaration for a handle returned by [query_property](StyleSheet::query_property).
+  pub fn declaration_mut(&mut self, handle: &DeclarationHandle) -> Option<&mut Property<'i>> {
+    query::declaration_mut(&mut self.rules, handle)
+  }
+
+  /// Converts the style sheet to a PostCSS AST, which can be serialized to the JSON format used by
+  /// `postcss.fromJSON`. See the [postcss](crate::postcss) module for more details.
+  #[cfg(feature = "serde")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+  pub fn to_postcss(&self) -> Result<postcss::Root, Error<PrinterErrorKind>> {
+    let res = self.to_css(PrinterOptions::default())?;
+    Ok(postcss::parse(&res.code))
+  }
+
+  /// Encodes the style sheet as a zero-copy archive, which can be accessed without deserializing it.
+  /// See the [archive](crate::archive) module for more details.
+  #[cfg(feature = "archive")]
+  #[cfg_attr(docsrs, doc(cfg(feature = "archive")))]
+  pub fn to_archive(&self) -> Result<rkyv::AlignedVec, Error<PrinterErrorKind>> {
+    Ok(crate::archive::serialize(self.to_postcss()?))
+  }
+
+  /// Returns handles for every rule in the style sheet, including nested rules, in document order.
+  pub fn rule_handles(&self) -> Vec<RuleHandle> {
+    let mut res = Vec::new();
+    query::rule_handles(&self.rules, &mut Vec::new(), &mut res);
+    res
+  }
+
+  /// Returns a [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) to the rule for a handle within
+  /// the serde representation of the style sheet.
+  pub fn rule_pointer(&self, handle: &RuleHandle) -> Option<String> {
+    query::rule_pointer(&self.rules, handle)
+  }
+
+  /// Returns a [JSON pointer](https://www.rfc-editor.org/rfc/rfc6901) to the declaration for a handle
+  /// within the serde representation of the style sheet.
+  pub fn declaration_pointer(&self, handle: &DeclarationHandle) -> Option<String> {
+    query::declaration_pointer(&self.rules, handle)
+  }
+
+  /// Returns every `@import`, `url()`, and `image-set()` reference in the style sheet,
+  /// along with its source location. Unlike the `analyze_dependencies` printer option,
+  /// this does not rewrite any urls.
+  ///
+  /// This should be called before [minify](StyleSheet::minify), which may duplicate urls
+  /// when adding fallbacks.
+  pub fn analyze_dependencies(&self) -> Result<Vec<DependencyReference>, Error<PrinterErrorKind>> {
+    let mut dest = String::new();
+    let mut printer = Printer::new(&mut dest, PrinterOptions::default());
+    printer.sources = Some(&self.sources);
+    printer.dependency_references = Some(Vec::new());
+    self.rules.to_css(&mut printer)?;
+    Ok(printer.dependency_references.take().unwrap_or_default())
+  }
 }
 
 #[cfg(feature = "visitor")]
@@ -312,6 +886,111 @@ where
   }
 }
 
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+impl<'i, 'o, T: Send> StyleSheet<'i, 'o, T> {
+  /// Visits the top-level rules of the style sheet in parallel. See [ParallelVisitor](ParallelVisitor)
+  /// for details.
+  ///
+  /// Unlike [visit](Visit::visit), the visitor's `visit_rule_list` method is not called for the top-level
+  /// rule list. Rules nested within other rules are visited normally.
+  pub fn visit_parallel<V>(&mut self, visitor: &mut V) -> Result<(), V::Error>
+  where
+    T: Visit<'i, T, V>,
+    V: ParallelVisitor<'i, T>,
+    V::Error: Send,
+  {
+    use rayon::prelude::*;
+
+    let fork = &*visitor;
+    let res = self
+      .rules
+      .0
+      .par_iter_mut()
+      .try_fold(
+        || fork.fork(),
+        |mut visitor, rule| {
+          visit_in_context(&mut visitor, rule, |visitor, rule| rule.visit(visitor))?;
+          Ok(visitor)
+        },
+      )
+      .try_reduce(
+        || fork.fork(),
+        |mut a, b| {
+          a.join(b);
+          Ok(a)
+        },
+      )?;
+    visitor.join(res);
+    Ok(())
+  }
+}
+
+/// Converts a style sheet into one that owns all of its data, so that it can outlive the
+/// source string it was parsed from.
+///
+/// Parser warnings are not collected for the converted style sheet, since they borrow from
+/// the source string. Warnings that were already collected are unaffected.
+#[cfg(feature = "into_owned")]
+#[cfg_attr(docsrs, doc(cfg(feature = "into_owned")))]
+impl<'i, 'o, 'any, T> static_self::IntoOwned<'any> for StyleSheet<'i, 'o, T>
+where
+  'o: 'any,
+  T: static_self::IntoOwned<'any>,
+{
+  type Owned = StyleSheet<'any, 'o, T::Owned>;
+
+  fn into_owned(self) -> Self::Owned {
+    self.into_owned_with_options()
+  }
+}
+
+#[cfg(feature = "into_owned")]
+impl<'i, 'o, T> StyleSheet<'i, 'o, T> {
+  /// Converts the style sheet to one that owns all of its data, but may still borrow the
+  /// CSS modules config of its parser options.
+  fn into_owned_with_options<'any>(self) -> StyleSheet<'any, 'o, T::Owned>
+  where
+    T: static_self::IntoOwned<'any>,
+  {
+    use static_self::IntoOwned;
+
+    let options = self.options;
+    StyleSheet {
+      rules: self.rules.into_owned(),
+      sources: self.sources,
+      source_map_urls: self.source_map_urls,
+      source_map_indexes: self.source_map_indexes,
+      license_comments: self.license_comments.into_owned(),
+      options: ParserOptions {
+        filename: options.filename,
+        css_modules: options.css_modules,
+        source_index: options.source_index,
+        error_recovery: options.error_recovery,
+        warnings: None,
+        flags: options.flags,
+        disabled_drafts: options.disabled_drafts,
+        validation: options.validation,
+        registered_properties: options.registered_properties,
+      },
+    }
+  }
+}
+
+// Style sheets may be parsed on one thread, analyzed on a thread pool, and printed on another.
+// Fail to compile if a type that is not thread safe (e.g. `Rc` or `RefCell`) is added to the AST.
+const _: fn() = || {
+  fn assert_send_sync<T: Send + Sync>() {}
+  assert_send_sync::<StyleSheet<'static, 'static>>();
+  assert_send_sync::<StyleAttribute<'static>>();
+  assert_send_sync::<CssRuleList<'static>>();
+  assert_send_sync::<CssRule<'static>>();
+  assert_send_sync::<DeclarationBlock<'static>>();
+  assert_send_sync::<Property<'static>>();
+  assert_send_sync::<SelectorList<'static>>();
+  assert_send_sync::<ParserOptions<'static, 'static>>();
+};
+
 /// An inline style attribute, as in HTML or SVG.
 ///
 /// Style attributes can be parsed from a string, minified and transformed
@@ -362,7 +1041,12 @@ impl<'i> StyleAttribute<'i> {
 
   /// Minify and transform the style attribute for the provided browser targets.
   pub fn minify(&mut self, options: MinifyOptions) {
-    let mut context = PropertyHandlerContext::new(options.targets, &options.unused_symbols);
+    let mut context = PropertyHandlerContext::new(
+      options.targets,
+      &options.unused_symbols,
+      &options.prefix_rules,
+      &options.prefix_policies,
+    );
     let mut handler = DeclarationHandler::default();
     let mut important_handler = DeclarationHandler::default();
     context.context = DeclarationContext::StyleAttribute;
@@ -386,9 +1070,12 @@ impl<'i> StyleAttribute<'i> {
 
     Ok(ToCssResult {
       dependencies: printer.dependencies,
+      assets: printer.assets,
+      coverage_map: printer.coverage_map,
       code: dest,
       exports: None,
       references: None,
+      manifest: None,
     })
   }
 }
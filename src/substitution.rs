@@ -0,0 +1,233 @@
+//! Substitution of `var()` and `env()` references.
+//!
+//! [VariableSubstitution](VariableSubstitution) is a [Visitor](crate::visitor::Visitor) that replaces
+//! `var()` and `env()` references throughout a style sheet with values from a provided map, e.g. to
+//! compile a theme or to target an environment without custom property support. References follow the
+//! rules of the CSS specification:
+//!
+//! * When a variable is defined, it is replaced with its value, after substituting any references
+//!   within the value itself.
+//! * When a variable is not defined, or its value contains a cycle, the fallback is used instead.
+//! * When there is no fallback, the reference is left as is and reported in
+//!   [unresolved](VariableSubstitution::unresolved).
+//!
+//! Declarations whose references are all substituted are parsed again, so they can be minified as usual.
+//! Only the provided values are used: custom properties declared within the style sheet itself are not
+//! resolved, since their values depend on the element they apply to.
+//!
+//! # Example
+//!
+//! ```
+//! use lightningcss::{
+//!   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+//!   substitution::VariableSubstitution,
+//!   visitor::Visit,
+//! };
+//!
+//! let mut stylesheet = StyleSheet::parse(
+//!   ".foo { color: var(--accent); padding: env(safe-area-inset-top, 2px) var(--gap, 8px) }",
+//!   ParserOptions::default(),
+//! )
+//! .unwrap();
+//!
+//! let mut substitution = VariableSubstitution::new();
+//! substitution.set_var("--accent", "var(--brand)").unwrap();
+//! substitution.set_var("--brand", "rebeccapurple").unwrap();
+//! stylesheet.visit(&mut substitution).unwrap();
+//!
+//! let res = stylesheet.to_css(PrinterOptions { minify: true, ..Default::default() }).unwrap();
+//! assert_eq!(res.code, ".foo{color:#639;padding:2px 8px}");
+//! ```
+
+use crate::error::{Error, ParserError};
+use crate::printer::Printer;
+use crate::properties::custom::{
+  EnvironmentVariableName, TokenList, TokenOrValue, UnparsedProperty, UnresolvedColor,
+};
+use crate::properties::Property;
+use crate::stylesheet::{ParserOptions, PrinterOptions};
+use crate::traits::{ParseWithOptions, ToCss};
+use crate::visitor::{VisitTypes, Visitor};
+use static_self::IntoOwned;
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+/// A visitor that substitutes `var()` and `env()` references with provided values.
+/// See the [module documentation](self) for details.
+#[derive(Debug, Clone, Default)]
+pub struct VariableSubstitution {
+  vars: HashMap<String, TokenList<'static>>,
+  env: HashMap<String, TokenList<'static>>,
+  unresolved: Vec<String>,
+}
+
+impl VariableSubstitution {
+  /// Creates a substitution without any values.
+  pub fn new() -> VariableSubstitution {
+    VariableSubstitution::default()
+  }
+
+  /// Sets the value of a custom property, keyed by its name including the leading `--`.
+  /// The value may itself contain `var()` and `env()` references.
+  pub fn set_var(&mut self, name: &str, value: &str) -> Result<(), Error<ParserError<'static>>> {
+    let value = parse_value(value)?;
+    self.vars.insert(name.into(), value);
+    Ok(())
+  }
+
+  /// Sets the value of an environment variable, e.g. `safe-area-inset-top`.
+  /// Environment variables referenced with indices are not substituted.
+  pub fn set_env(&mut self, name: &str, value: &str) -> Result<(), Error<ParserError<'static>>> {
+    let value = parse_value(value)?;
+    self.env.insert(name.into(), value);
+    Ok(())
+  }
+
+  /// Returns the names of the references that were left as is because they were not defined, or
+  /// contained a cycle, and had no fallback. `env()` references are reported as `env(name)`.
+  pub fn unresolved(&self) -> &[String] {
+    &self.unresolved
+  }
+
+  /// Substitutes references within a token list, and returns whether all of them were resolved.
+  fn substitute<'i>(&mut self, tokens: &mut TokenList<'i>, stack: &mut Vec<String>) -> bool {
+    let mut resolved = true;
+    let mut i = 0;
+    while i < tokens.0.len() {
+      let (name, value, fallback) = match &mut tokens.0[i] {
+        // References to custom properties in other files are resolved by CSS modules.
+        TokenOrValue::Var(var) if var.name.from.is_none() => {
+          let name = var.name.ident.0.to_string();
+          let value = self.vars.get(&name).cloned();
+          (name, value, var.fallback.take())
+        }
+        TokenOrValue::Env(env) if env.indices.is_empty() => {
+          let name = match &env.name {
+            EnvironmentVariableName::Custom(name) if name.from.is_none() => name.ident.0.to_string(),
+            EnvironmentVariableName::Custom(..) => {
+              i += 1;
+              continue;
+            }
+            name => name.to_css_string(PrinterOptions::default()).unwrap(),
+          };
+          let value = self.env.get(&name).cloned();
+          (format!("env({})", name), value, env.fallback.take())
+        }
+        TokenOrValue::Var(var) => {
+          if let Some(fallback) = &mut var.fallback {
+            resolved &= self.substitute(fallback, stack);
+          }
+          i += 1;
+          continue;
+        }
+        TokenOrValue::Function(f) => {
+          resolved &= self.substitute(&mut f.arguments, stack);
+          i += 1;
+          continue;
+        }
+        TokenOrValue::UnresolvedColor(UnresolvedColor::RGB { alpha, .. })
+        | TokenOrValue::UnresolvedColor(UnresolvedColor::HSL { alpha, .. }) => {
+          resolved &= self.substitute(alpha, stack);
+          i += 1;
+          continue;
+        }
+        _ => {
+          i += 1;
+          continue;
+        }
+      };
+
+      // A value that references itself, directly or indirectly, is invalid.
+      let value = match value {
+        Some(value) if !stack.contains(&name) => {
+          let mut value: TokenList<'i> = value.into_owned();
+          stack.push(name.clone());
+          let value_resolved = self.substitute(&mut value, stack);
+          stack.pop();
+          if value_resolved {
+            Some(value)
+          } else {
+            None
+          }
+        }
+        _ => None,
+      };
+
+      let value = match (value, fallback) {
+        (Some(value), _) => value,
+        (None, Some(mut fallback)) => {
+          resolved &= self.substitute(&mut fallback, stack);
+          fallback
+        }
+        (None, None) => {
+          // Unresolved references within a value are reported by the reference to the value itself.
+          if stack.is_empty() && !self.unresolved.contains(&name) {
+            self.unresolved.push(name);
+          }
+          resolved = false;
+          i += 1;
+          continue;
+        }
+      };
+
+      i += tokens.splice_variable_value(i, value);
+    }
+
+    resolved
+  }
+}
+
+fn parse_value(value: &str) -> Result<TokenList<'static>, Error<ParserError<'static>>> {
+  let mut tokens = TokenList::parse_string_with_options(value, ParserOptions::default()).map_err(|err| {
+    let err = Error::from(err, String::new());
+    Error {
+      kind: err.kind.into_owned(),
+      loc: err.loc,
+    }
+  })?;
+  while tokens.0.last().map_or(false, |token| token.is_whitespace()) {
+    tokens.0.pop();
+  }
+  if tokens.0.first().map_or(false, |token| token.is_whitespace()) {
+    tokens.0.remove(0);
+  }
+  Ok(tokens.into_owned())
+}
+
+/// Parses an unparsed property again after its references have been substituted.
+fn reparse<'i>(property: &UnparsedProperty<'i>) -> Option<Property<'i>> {
+  let mut css = String::new();
+  let mut dest = Printer::new(&mut css, PrinterOptions::default());
+  property.value.to_css(&mut dest, false).ok()?;
+  let property = Property::parse_string(property.property_id.clone(), &css, ParserOptions::default()).ok()?;
+  Some(property.into_owned())
+}
+
+impl<'i> Visitor<'i> for VariableSubstitution {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::PROPERTIES
+  }
+
+  fn visit_property(&mut self, property: &mut Property<'i>) -> Result<(), Self::Error> {
+    match property {
+      Property::Unparsed(unparsed) => {
+        let mut stack = Vec::new();
+        if self.substitute(&mut unparsed.value, &mut stack) {
+          // Leave the property unparsed if the substituted value is invalid, as in a browser.
+          if let Some(parsed) = reparse(unparsed) {
+            *property = parsed;
+          }
+        }
+      }
+      Property::Custom(custom) => {
+        let mut stack = Vec::new();
+        self.substitute(&mut custom.value, &mut stack);
+      }
+      _ => {}
+    }
+
+    Ok(())
+  }
+}
@@ -39,6 +39,73 @@ pub struct Browsers {
   pub samsung: Option<u32>,
 }
 
+impl Browsers {
+  /// Returns the browsers in these targets that do not support the given feature.
+  pub(crate) fn unsupported(&self, feature: crate::compat::Feature) -> Vec<UnsupportedBrowser> {
+    let mut unsupported = Vec::new();
+    macro_rules! browser {
+      ($browser: ident) => {
+        if let Some(version) = self.$browser {
+          let browsers = |version| Browsers {
+            $browser: Some(version),
+            ..Browsers::default()
+          };
+
+          if !feature.is_compatible(browsers(version)) {
+            let supported_since = versions().find(|v| *v > version && feature.is_compatible(browsers(*v)));
+            unsupported.push(UnsupportedBrowser {
+              browser: stringify!($browser).into(),
+              version: format_version(version),
+              supported_since: supported_since.map(format_version),
+            });
+          }
+        }
+      };
+    }
+
+    browser!(android);
+    browser!(chrome);
+    browser!(edge);
+    browser!(firefox);
+    browser!(ie);
+    browser!(ios_saf);
+    browser!(opera);
+    browser!(safari);
+    browser!(samsung);
+    unsupported
+  }
+}
+
+/// Returns the browser versions that are checked when searching for the first version
+/// that supports a feature.
+pub(crate) fn versions() -> impl Iterator<Item = u32> {
+  (0..=255u32).flat_map(|major| (0..=15u32).map(move |minor| (major << 16) | (minor << 8)))
+}
+
+/// A browser target that does not support a feature.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), derive(Serialize))]
+#[cfg_attr(any(feature = "serde", feature = "nodejs"), serde(rename_all = "camelCase"))]
+pub struct UnsupportedBrowser {
+  /// The name of the browser, e.g. `safari`.
+  pub browser: String,
+  /// The targeted version of the browser.
+  pub version: String,
+  /// The first version of the browser that supports the feature, if any.
+  pub supported_since: Option<String>,
+}
+
+pub(crate) fn format_version(version: u32) -> String {
+  let (major, minor, patch) = (version >> 16, (version >> 8) & 0xff, version & 0xff);
+  if patch != 0 {
+    format!("{}.{}.{}", major, minor, patch)
+  } else if minor != 0 {
+    format!("{}.{}", major, minor)
+  } else {
+    major.to_string()
+  }
+}
+
 #[cfg(feature = "browserslist")]
 #[cfg_attr(docsrs, doc(cfg(feature = "browserslist")))]
 impl Browsers {
@@ -158,12 +225,38 @@ bitflags! {
     const DoublePositionGradients = 1 << 17;
     const VendorPrefixes = 1 << 18;
     const LogicalProperties = 1 << 19;
+    const PlaceShorthands = 1 << 20;
+    const ExtendedSystemFonts = 1 << 21;
     const Selectors = Self::Nesting.bits() | Self::NotSelectorList.bits() | Self::DirSelector.bits() | Self::LangSelectorList.bits() | Self::IsSelector.bits();
     const MediaQueries = Self::MediaIntervalSyntax.bits() | Self::MediaRangeSyntax.bits() | Self::CustomMediaQueries.bits();
     const Colors = Self::ColorFunction.bits() | Self::OklabColors.bits() | Self::LabColors.bits() | Self::P3Colors.bits() | Self::HexAlphaColors.bits() | Self::SpaceSeparatedColorNotation.bits();
   }
 }
 
+/// An error that occurred while parsing feature toggles with [Targets::set_feature_toggles](Targets::set_feature_toggles).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureToggleError {
+  /// The feature name is not known.
+  UnknownFeature(String),
+  /// The toggle is not of the form `name: compile` or `name: preserve`.
+  InvalidToggle(String),
+}
+
+impl std::fmt::Display for FeatureToggleError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      FeatureToggleError::UnknownFeature(name) => write!(f, "Unknown feature: {}", name),
+      FeatureToggleError::InvalidToggle(toggle) => write!(
+        f,
+        "Invalid feature toggle: {}. Expected `<feature>: compile` or `<feature>: preserve`",
+        toggle
+      ),
+    }
+  }
+}
+
+impl std::error::Error for FeatureToggleError {}
+
 /// Target browsers and features to compile.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Targets {
@@ -194,6 +287,58 @@ impl From<Option<Browsers>> for Targets {
 }
 
 impl Targets {
+  /// Overrides the decisions derived from the browser targets with a comma separated list of feature toggles,
+  /// e.g. `nesting: compile, oklab-colors: preserve, logical-properties: compile`.
+  ///
+  /// Feature names are the kebab-case names of the [Features](Features) flags, including the `selectors`,
+  /// `media-queries`, and `colors` groups. Features set to `compile` are added to `include`, and are always
+  /// compiled. Features set to `preserve` are added to `exclude`, and are never compiled.
+  ///
+  /// # Example
+  ///
+  /// ```
+  /// use lightningcss::targets::{Features, Targets};
+  ///
+  /// let mut targets = Targets::default();
+  /// targets.set_feature_toggles("nesting: compile, colors: preserve").unwrap();
+  /// assert!(targets.include.contains(Features::Nesting));
+  /// assert!(targets.exclude.contains(Features::Colors));
+  /// ```
+  pub fn set_feature_toggles(&mut self, toggles: &str) -> Result<(), FeatureToggleError> {
+    for toggle in toggles.split(',').map(str::trim).filter(|toggle| !toggle.is_empty()) {
+      let (name, value) = toggle
+        .split_once(':')
+        .ok_or_else(|| FeatureToggleError::InvalidToggle(toggle.to_owned()))?;
+      let name = name.trim();
+      let flag_name = name
+        .split('-')
+        .map(|part| {
+          let mut chars = part.chars();
+          chars
+            .next()
+            .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+            .unwrap_or_default()
+        })
+        .collect::<String>();
+      let feature =
+        Features::from_name(&flag_name).ok_or_else(|| FeatureToggleError::UnknownFeature(name.to_owned()))?;
+
+      match value.trim() {
+        "compile" => {
+          self.include.insert(feature);
+          self.exclude.remove(feature);
+        }
+        "preserve" => {
+          self.exclude.insert(feature);
+          self.include.remove(feature);
+        }
+        _ => return Err(FeatureToggleError::InvalidToggle(toggle.to_owned())),
+      }
+    }
+
+    Ok(())
+  }
+
   pub(crate) fn is_compatible(&self, feature: crate::compat::Feature) -> bool {
     self.browsers.map(|targets| feature.is_compatible(targets)).unwrap_or(true)
   }
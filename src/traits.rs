@@ -9,6 +9,8 @@ use crate::stylesheet::{ParserOptions, PrinterOptions};
 use crate::targets::{Browsers, Targets};
 use crate::vendor_prefix::VendorPrefix;
 use cssparser::*;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
 
 #[cfg(feature = "into_owned")]
 pub use static_self::IntoOwned;
@@ -76,6 +78,50 @@ pub trait ToCss {
     self.to_css(&mut printer)?;
     Ok(s)
   }
+
+  /// Returns a hash of the minified serialization of `self`, which can be used to deduplicate
+  /// values or detect changes regardless of formatting.
+  ///
+  /// Source locations are not included, and equivalent values such as `#ff0000` and `red`
+  /// have the same fingerprint. The serialization is hashed as it is printed, without
+  /// allocating a string.
+  fn fingerprint(&self) -> Result<u64, PrinterError> {
+    struct HashWriter(DefaultHasher);
+
+    impl std::fmt::Write for HashWriter {
+      fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        self.0.write(s.as_bytes());
+        Ok(())
+      }
+    }
+
+    let mut writer = HashWriter(DefaultHasher::new());
+    {
+      let mut printer = Printer::new(
+        &mut writer,
+        PrinterOptions {
+          minify: true,
+          ..PrinterOptions::default()
+        },
+      );
+      self.to_css(&mut printer)?;
+    }
+    Ok(writer.0.finish())
+  }
+
+  /// Returns whether `self` is semantically equivalent to `other`, e.g. `#ff0000` and `red`, or `0px`
+  /// and `0`. By default, values are equivalent if they have the same minified serialization.
+  /// Values that fail to serialize are not equivalent to anything.
+  fn semantic_eq(&self, other: &Self) -> bool {
+    let options = || PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    };
+    match (self.to_css_string(options()), other.to_css_string(options())) {
+      (Ok(a), Ok(b)) => a == b,
+      _ => false,
+    }
+  }
 }
 
 impl<'a, T> ToCss for &'a T
@@ -88,6 +134,10 @@ where
   {
     (*self).to_css(dest)
   }
+
+  fn semantic_eq(&self, other: &Self) -> bool {
+    (**self).semantic_eq(*other)
+  }
 }
 
 pub(crate) trait PropertyHandler<'i>: Sized {
@@ -108,6 +158,13 @@ pub(crate) mod private {
   pub trait AddInternal {
     fn add(self, other: Self) -> Self;
   }
+
+  pub trait TryToPx {
+    /// Attempts to resolve the value to pixels using the given context.
+    fn try_to_px(&self, _context: &crate::values::length::UnitContext) -> Option<f32> {
+      None
+    }
+  }
 }
 
 pub(crate) trait FromStandard<T>: Sized {
@@ -118,6 +175,12 @@ pub(crate) trait FallbackValues: Sized {
   fn get_fallbacks(&mut self, targets: Targets) -> Vec<Self>;
 }
 
+pub(crate) trait ViewportFallback {
+  /// Replaces small, large, and dynamic viewport units that are unsupported by the given browsers
+  /// with the equivalent default viewport unit, e.g. `dvh` with `vh`. Returns whether any were replaced.
+  fn replace_viewport_units(&mut self, browsers: Browsers) -> bool;
+}
+
 /// Trait for shorthand properties.
 pub(crate) trait Shorthand<'i>: Sized {
   /// Returns a shorthand from the longhand properties defined in the given declaration block.
@@ -8,7 +8,7 @@ use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
 use crate::traits::{
   impl_op,
-  private::{AddInternal, TryAdd},
+  private::{AddInternal, TryAdd, TryToPx},
   Map, Op, Parse, Sign, ToCss, Zero,
 };
 #[cfg(feature = "visitor")]
@@ -211,6 +211,8 @@ impl AddInternal for Angle {
   }
 }
 
+impl TryToPx for Angle {}
+
 impl TryAdd<Angle> for Angle {
   fn try_add(&self, other: &Angle) -> Option<Angle> {
     Some(Angle::Deg(self.to_degrees() + other.to_degrees()))
@@ -1,18 +1,21 @@
 //! Mathematical calculation functions and expressions.
 
+#![allow(non_upper_case_globals)]
+
 use crate::compat::Feature;
-use crate::error::{ParserError, PrinterError};
+use crate::error::{ParserError, PrinterError, UnitConversionError};
 use crate::macros::enum_property;
 use crate::printer::Printer;
 use crate::targets::{should_compile, Browsers};
-use crate::traits::private::AddInternal;
-use crate::traits::{IsCompatible, Parse, Sign, ToCss, TryMap, TryOp, TrySign};
+use crate::traits::private::{AddInternal, TryToPx};
+use crate::traits::{IsCompatible, Parse, Sign, ToCss, TryMap, TryOp, TrySign, ViewportFallback};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
+use bitflags::bitflags;
 use cssparser::*;
 
 use super::angle::Angle;
-use super::length::Length;
+use super::length::{serialize_dimension, Length, UnitContext};
 use super::number::CSSNumber;
 use super::percentage::Percentage;
 use super::time::Time;
@@ -122,11 +125,160 @@ fn modulo(a: f32, b: f32) -> f32 {
   ((a % b) + b) % b
 }
 
-impl<V: ToCss + std::ops::Mul<f32, Output = V> + TrySign + Clone + std::fmt::Debug> ToCss for MathFunction<V> {
+bitflags! {
+  /// Bit flags that represent the inputs from a [UnitContext](super::length::UnitContext)
+  /// that a value depends on.
+  ///
+  /// These are returned when evaluating a math expression, and can be used to determine
+  /// whether a resolved value remains valid when the environment changes, e.g. when the
+  /// viewport is resized.
+  #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy, Default)]
+  pub struct CalcDependencies: u16 {
+    /// The root font size, used by `rem` and related units.
+    const RootFontSize   = 0b000000001;
+    /// The font size of the element, used by `em` and related units.
+    const FontSize       = 0b000000010;
+    /// The line height of the element, used by `lh` units.
+    const LineHeight     = 0b000000100;
+    /// The line height of the root element, used by `rlh` units.
+    const RootLineHeight = 0b000001000;
+    /// The width of the viewport.
+    const ViewportWidth  = 0b000010000;
+    /// The height of the viewport.
+    const ViewportHeight = 0b000100000;
+    /// The width of the query container.
+    const ContainerWidth = 0b001000000;
+    /// The height of the query container.
+    const ContainerHeight = 0b010000000;
+    /// The size that percentages resolve against.
+    const PercentageBasis = 0b100000000;
+  }
+}
+
+/// The result of evaluating a math expression to a single number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalcEvaluation {
+  /// The resolved value, in the canonical unit of the value type, e.g. `px` for lengths.
+  pub value: CSSNumber,
+  /// The inputs from the context that the value depends on.
+  pub dependencies: CalcDependencies,
+}
+
+impl CalcEvaluation {
+  pub(crate) fn new(value: CSSNumber, dependencies: CalcDependencies) -> CalcEvaluation {
+    CalcEvaluation { value, dependencies }
+  }
+
+  fn map(self, op: impl FnOnce(CSSNumber) -> CSSNumber) -> CalcEvaluation {
+    CalcEvaluation::new(op(self.value), self.dependencies)
+  }
+
+  fn zip(self, other: CalcEvaluation, op: impl FnOnce(CSSNumber, CSSNumber) -> CSSNumber) -> CalcEvaluation {
+    CalcEvaluation::new(op(self.value, other.value), self.dependencies | other.dependencies)
+  }
+}
+
+impl<V> MathFunction<V> {
+  fn evaluate_with(
+    &self,
+    resolve: &dyn Fn(&V) -> Result<CalcEvaluation, UnitConversionError>,
+  ) -> Result<CalcEvaluation, UnitConversionError> {
+    let fold = |args: &Vec<Calc<V>>, init: CSSNumber, op: fn(CSSNumber, CSSNumber) -> CSSNumber| {
+      args
+        .iter()
+        .try_fold(CalcEvaluation::new(init, CalcDependencies::empty()), |res, arg| {
+          arg.evaluate_with(resolve).map(|v| res.zip(v, op))
+        })
+    };
+
+    Ok(match self {
+      MathFunction::Calc(c) => c.evaluate_with(resolve)?,
+      MathFunction::Min(args) => fold(args, CSSNumber::INFINITY, CSSNumber::min)?,
+      MathFunction::Max(args) => fold(args, CSSNumber::NEG_INFINITY, CSSNumber::max)?,
+      MathFunction::Clamp(a, b, c) => {
+        let (a, b, c) = (
+          a.evaluate_with(resolve)?,
+          b.evaluate_with(resolve)?,
+          c.evaluate_with(resolve)?,
+        );
+        a.zip(b.zip(c, CSSNumber::min), CSSNumber::max)
+      }
+      MathFunction::Round(strategy, a, b) => a
+        .evaluate_with(resolve)?
+        .zip(b.evaluate_with(resolve)?, |a, b| round(a, b, *strategy)),
+      MathFunction::Rem(a, b) => a.evaluate_with(resolve)?.zip(b.evaluate_with(resolve)?, |a, b| a % b),
+      MathFunction::Mod(a, b) => a.evaluate_with(resolve)?.zip(b.evaluate_with(resolve)?, modulo),
+      MathFunction::Abs(a) => a.evaluate_with(resolve)?.map(CSSNumber::abs),
+      MathFunction::Sign(a) => a.evaluate_with(resolve)?.map(|v| v.sign()),
+      MathFunction::Hypot(args) => fold(args, 0.0, |res, v| res + v * v)?.map(CSSNumber::sqrt),
+    })
+  }
+}
+
+/// Resolves a value within a math expression to pixels, for use with `evaluate_with`.
+fn evaluate_px<V: TryToPx>(value: &V, context: &UnitContext) -> Result<CalcEvaluation, UnitConversionError> {
+  value
+    .try_to_px(context)
+    .map(|px| CalcEvaluation::new(px, CalcDependencies::empty()))
+    .ok_or(UnitConversionError::UnsupportedMathFunction)
+}
+
+impl<V: TryToPx> TryToPx for MathFunction<V> {
+  fn try_to_px(&self, context: &UnitContext) -> Option<f32> {
+    self.evaluate_with(&|v| evaluate_px(v, context)).ok().map(|res| res.value)
+  }
+}
+
+impl<V: ViewportFallback> ViewportFallback for MathFunction<V> {
+  fn replace_viewport_units(&mut self, browsers: Browsers) -> bool {
+    match self {
+      MathFunction::Calc(a) | MathFunction::Abs(a) | MathFunction::Sign(a) => a.replace_viewport_units(browsers),
+      MathFunction::Min(args) | MathFunction::Max(args) | MathFunction::Hypot(args) => args
+        .iter_mut()
+        .fold(false, |replaced, arg| arg.replace_viewport_units(browsers) | replaced),
+      MathFunction::Clamp(a, b, c) => {
+        a.replace_viewport_units(browsers)
+          | b.replace_viewport_units(browsers)
+          | c.replace_viewport_units(browsers)
+      }
+      MathFunction::Round(_, a, b) | MathFunction::Rem(a, b) | MathFunction::Mod(a, b) => {
+        a.replace_viewport_units(browsers) | b.replace_viewport_units(browsers)
+      }
+    }
+  }
+}
+
+impl<V: ToCss + std::ops::Mul<f32, Output = V> + TrySign + TryToPx + Clone + std::fmt::Debug> ToCss
+  for MathFunction<V>
+{
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
   {
+    // When printing a static fallback, compute the value if possible, and otherwise use the preferred value.
+    if dest.static_math {
+      let preferred = match self {
+        MathFunction::Min(args) | MathFunction::Max(args) => args.first(),
+        MathFunction::Clamp(_, preferred, _) => Some(preferred),
+        _ => None,
+      };
+
+      if let Some(preferred) = preferred {
+        if let Some(px) = dest.math_fallback.and_then(|options| self.try_to_px(&options.unit_context())) {
+          return serialize_dimension(px, "px", dest);
+        }
+
+        return match preferred {
+          Calc::Sum(..) | Calc::Product(..) => {
+            dest.write_str("calc(")?;
+            preferred.to_css(dest)?;
+            dest.write_char(')')
+          }
+          _ => preferred.to_css(dest),
+        };
+      }
+    }
+
     match self {
       MathFunction::Calc(calc) => {
         dest.write_str("calc(")?;
@@ -917,7 +1069,42 @@ impl<V: AddInternal + std::convert::Into<Calc<V>> + std::convert::From<Calc<V>>
   }
 }
 
-impl<V: ToCss + std::ops::Mul<f32, Output = V> + TrySign + Clone + std::fmt::Debug> ToCss for Calc<V> {
+impl<V: TryToPx> TryToPx for Calc<V> {
+  fn try_to_px(&self, context: &UnitContext) -> Option<f32> {
+    self.evaluate_with(&|v| evaluate_px(v, context)).ok().map(|res| res.value)
+  }
+}
+
+impl<V: ViewportFallback> ViewportFallback for Calc<V> {
+  fn replace_viewport_units(&mut self, browsers: Browsers) -> bool {
+    match self {
+      Calc::Value(v) => v.replace_viewport_units(browsers),
+      Calc::Number(_) => false,
+      Calc::Sum(a, b) => a.replace_viewport_units(browsers) | b.replace_viewport_units(browsers),
+      Calc::Product(_, calc) => calc.replace_viewport_units(browsers),
+      Calc::Function(f) => f.replace_viewport_units(browsers),
+    }
+  }
+}
+
+impl<V> Calc<V> {
+  /// Evaluates the expression to a single number, resolving each value with the given function.
+  /// The dependencies of all values within the expression are combined.
+  pub(crate) fn evaluate_with(
+    &self,
+    resolve: &dyn Fn(&V) -> Result<CalcEvaluation, UnitConversionError>,
+  ) -> Result<CalcEvaluation, UnitConversionError> {
+    match self {
+      Calc::Value(v) => resolve(v),
+      Calc::Number(n) => Ok(CalcEvaluation::new(*n, CalcDependencies::empty())),
+      Calc::Sum(a, b) => Ok(a.evaluate_with(resolve)?.zip(b.evaluate_with(resolve)?, |a, b| a + b)),
+      Calc::Product(num, calc) => Ok(calc.evaluate_with(resolve)?.map(|v| num * v)),
+      Calc::Function(f) => f.evaluate_with(resolve),
+    }
+  }
+}
+
+impl<V: ToCss + std::ops::Mul<f32, Output = V> + TrySign + TryToPx + Clone + std::fmt::Debug> ToCss for Calc<V> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
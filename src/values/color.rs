@@ -114,6 +114,10 @@ where
 {
   use serde::Deserialize;
   match RGBColor::deserialize(deserializer)? {
+    // Colors with `none` components are represented by CssColor::Float instead.
+    RGBColor::RGB(srgb) if [srgb.r, srgb.g, srgb.b, srgb.alpha].iter().any(|c| c.is_nan()) => {
+      Err(serde::de::Error::custom("RGBA colors cannot contain `none` components"))
+    }
     RGBColor::RGB(srgb) => Ok(srgb.into()),
   }
 }
@@ -304,6 +308,45 @@ impl CssColor {
     Ok(P3::try_from(self)?.into())
   }
 
+  /// Converts the color to the given color space, e.g. `color.convert::<OKLCH>()`.
+  ///
+  /// The result may be outside the gamut of the color space. Use [to_gamut](CssColor::to_gamut)
+  /// to map it into the gamut. Returns an error for `currentColor`.
+  pub fn convert<'a, T>(&'a self) -> Result<CssColor, ()>
+  where
+    T: TryFrom<&'a CssColor, Error = ()> + Into<CssColor>,
+  {
+    Ok(T::try_from(self)?.into())
+  }
+
+  /// Converts the color to the given color space, and maps it into the gamut of the color space
+  /// using the [CSS gamut mapping algorithm](https://www.w3.org/TR/css-color-4/#gamut-mapping),
+  /// which reduces chroma while preserving lightness and hue. Missing components are resolved to zero.
+  /// Returns an error for `currentColor`.
+  pub fn to_gamut<'a, T>(&'a self) -> Result<CssColor, ()>
+  where
+    T: TryFrom<&'a CssColor, Error = ()> + Into<CssColor> + ColorSpace,
+  {
+    Ok(T::try_from(self)?.resolve().into())
+  }
+
+  /// Returns the [relative luminance](https://www.w3.org/TR/WCAG21/#dfn-relative-luminance) of the color,
+  /// from 0 for black to 1 for white. The color is mapped into the sRGB gamut first, and alpha is ignored.
+  /// Returns an error for `currentColor`.
+  pub fn luminance(&self) -> Result<f32, ()> {
+    let rgb = SRGBLinear::from(SRGB::try_from(self)?.resolve());
+    Ok(0.2126 * rgb.r + 0.7152 * rgb.g + 0.0722 * rgb.b)
+  }
+
+  /// Returns the [contrast ratio](https://www.w3.org/TR/WCAG21/#dfn-contrast-ratio) between two colors,
+  /// from 1 to 21. Returns an error if either color is `currentColor`.
+  pub fn contrast(&self, other: &CssColor) -> Result<f32, ()> {
+    let a = self.luminance()?;
+    let b = other.luminance()?;
+    let (lighter, darker) = if a > b { (a, b) } else { (b, a) };
+    Ok((lighter + 0.05) / (darker + 0.05))
+  }
+
   pub(crate) fn get_possible_fallbacks(&self, targets: Targets) -> ColorFallbackKind {
     // Fallbacks occur in levels: Oklab -> Lab -> P3 -> RGB. We start with all levels
     // below and including the authored color space, and remove the ones that aren't
@@ -387,22 +430,31 @@ impl CssColor {
   }
 }
 
-impl IsCompatible for CssColor {
-  fn is_compatible(&self, browsers: Browsers) -> bool {
+impl CssColor {
+  /// Returns the name of the color syntax, and its compatibility feature, if any.
+  pub(crate) fn feature(&self) -> Option<(&'static str, Feature)> {
     match self {
-      CssColor::CurrentColor | CssColor::RGBA(_) | CssColor::Float(..) => true,
+      CssColor::CurrentColor | CssColor::RGBA(_) | CssColor::Float(..) => None,
       CssColor::LAB(lab) => match &**lab {
-        LABColor::LAB(..) | LABColor::LCH(..) => Feature::LabColors.is_compatible(browsers),
-        LABColor::OKLAB(..) | LABColor::OKLCH(..) => Feature::OklabColors.is_compatible(browsers),
+        LABColor::LAB(..) => Some(("lab()", Feature::LabColors)),
+        LABColor::LCH(..) => Some(("lch()", Feature::LabColors)),
+        LABColor::OKLAB(..) => Some(("oklab()", Feature::OklabColors)),
+        LABColor::OKLCH(..) => Some(("oklch()", Feature::OklabColors)),
       },
       CssColor::Predefined(predefined) => match &**predefined {
-        PredefinedColor::DisplayP3(..) => Feature::P3Colors.is_compatible(browsers),
-        _ => Feature::ColorFunction.is_compatible(browsers),
+        PredefinedColor::DisplayP3(..) => Some(("color(display-p3)", Feature::P3Colors)),
+        _ => Some(("color()", Feature::ColorFunction)),
       },
     }
   }
 }
 
+impl IsCompatible for CssColor {
+  fn is_compatible(&self, browsers: Browsers) -> bool {
+    self.feature().map_or(true, |(_, feature)| feature.is_compatible(browsers))
+  }
+}
+
 impl FallbackValues for CssColor {
   fn get_fallbacks(&mut self, targets: Targets) -> Vec<CssColor> {
     let fallbacks = self.get_necessary_fallbacks(targets);
@@ -1323,6 +1375,31 @@ macro_rules! define_colorspace {
       $(#[$c_meta: meta])*
       $c: ident: $ct: ident
     }
+  ) => {
+    define_colorspace! {
+      @deserialize "deserialize_component"
+      $(#[$outer])*
+      $vis struct $name {
+        $(#[$a_meta])*
+        $a: $at,
+        $(#[$b_meta])*
+        $b: $bt,
+        $(#[$c_meta])*
+        $c: $ct
+      }
+    }
+  };
+  (
+    @deserialize $deserialize:literal
+    $(#[$outer:meta])*
+    $vis:vis struct $name:ident {
+      $(#[$a_meta: meta])*
+      $a: ident: $at: ident,
+      $(#[$b_meta: meta])*
+      $b: ident: $bt: ident,
+      $(#[$c_meta: meta])*
+      $c: ident: $ct: ident
+    }
   ) => {
     $(#[$outer])*
     #[derive(Debug, Clone, Copy, PartialEq)] #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -1330,12 +1407,16 @@ macro_rules! define_colorspace {
     #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
     pub struct $name {
       $(#[$a_meta])*
+      #[cfg_attr(feature = "serde", serde(deserialize_with = $deserialize))]
       pub $a: f32,
       $(#[$b_meta])*
+      #[cfg_attr(feature = "serde", serde(deserialize_with = $deserialize))]
       pub $b: f32,
       $(#[$c_meta])*
+      #[cfg_attr(feature = "serde", serde(deserialize_with = $deserialize))]
       pub $c: f32,
       /// The alpha component.
+      #[cfg_attr(feature = "serde", serde(deserialize_with = "deserialize_component"))]
       pub alpha: f32,
     }
 
@@ -1375,33 +1456,39 @@ macro_rules! define_colorspace {
 }
 
 define_colorspace! {
+  @deserialize "deserialize_rgb_component"
   /// A color in the [`sRGB`](https://www.w3.org/TR/css-color-4/#predefined-sRGB) color space.
   pub struct SRGB {
     /// The red component.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component", deserialize_with = "deserialize_rgb_component"))]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component"))]
     r: Percentage,
     /// The green component.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component", deserialize_with = "deserialize_rgb_component"))]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component"))]
     g: Percentage,
     /// The blue component.
-    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component", deserialize_with = "deserialize_rgb_component"))]
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_rgb_component"))]
     b: Percentage
   }
 }
 
+// Missing (i.e. `none`) components are NaN, which is serialized as `null` in JSON.
+#[cfg(feature = "serde")]
+fn deserialize_component<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+  D: serde::Deserializer<'de>,
+{
+  let v: Option<f32> = serde::Deserialize::deserialize(deserializer)?;
+  Ok(v.unwrap_or(f32::NAN))
+}
+
 // serialize RGB components in the 0-255 range as it is more common.
+// They are not rounded or clamped, so that colors round trip exactly.
 #[cfg(feature = "serde")]
 fn serialize_rgb_component<S>(v: &f32, serializer: S) -> Result<S::Ok, S::Error>
 where
   S: serde::Serializer,
 {
-  let v = if !v.is_nan() {
-    (v * 255.0).round().max(0.0).min(255.0)
-  } else {
-    *v
-  };
-
-  serializer.serialize_f32(v)
+  serializer.serialize_f32(v * 255.0)
 }
 
 #[cfg(feature = "serde")]
@@ -1409,8 +1496,7 @@ fn deserialize_rgb_component<'de, D>(deserializer: D) -> Result<f32, D::Error>
 where
   D: serde::Deserializer<'de>,
 {
-  let v: f32 = serde::Deserialize::deserialize(deserializer)?;
-  Ok(v / 255.0)
+  Ok(deserialize_component(deserializer)? / 255.0)
 }
 
 // Copied from an older version of cssparser.
@@ -4,6 +4,7 @@ use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
 use crate::traits::{Parse, ToCss};
 use crate::values::number::{CSSInteger, CSSNumber};
+use crate::values::percentage::Percentage;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
@@ -49,6 +50,24 @@ pub enum EasingFunction {
     #[cfg_attr(feature = "serde", serde(default))]
     position: StepPosition,
   },
+  /// A piecewise linear easing function, defined by the `linear()` function.
+  LinearFunction {
+    /// The stops of the function.
+    stops: Vec<LinearStop>,
+  },
+}
+
+/// A stop within a [`linear()`](https://www.w3.org/TR/css-easing-2/#the-linear-easing-function) easing function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
+pub struct LinearStop {
+  /// The output progress value.
+  pub output: CSSNumber,
+  /// The input progress value. If not specified, stops are spaced evenly between their neighbors.
+  pub input: Option<Percentage>,
 }
 
 impl EasingFunction {
@@ -63,6 +82,265 @@ impl EasingFunction {
           y2: 1.0,
         }
   }
+
+  /// Returns whether the easing function is equivalent to the `linear` keyword.
+  pub fn is_linear(&self) -> bool {
+    match self {
+      EasingFunction::Linear => true,
+      EasingFunction::LinearFunction { stops } => match stops.as_slice() {
+        [start, end] => {
+          start.output == 0.0
+            && !matches!(&start.input, Some(p) if p.0 != 0.0)
+            && end.output == 1.0
+            && !matches!(&end.input, Some(p) if p.0 != 1.0)
+        }
+        _ => false,
+      },
+      _ => false,
+    }
+  }
+
+  /// Evaluates the easing function at the given input progress value, returning the output progress value.
+  ///
+  /// Input values outside the range [0, 1] are extrapolated as defined by the spec.
+  pub fn evaluate(&self, t: f32) -> f32 {
+    match self {
+      EasingFunction::Linear => t,
+      EasingFunction::Ease => cubic_bezier(0.25, 0.1, 0.25, 1.0, t),
+      EasingFunction::EaseIn => cubic_bezier(0.42, 0.0, 1.0, 1.0, t),
+      EasingFunction::EaseOut => cubic_bezier(0.0, 0.0, 0.58, 1.0, t),
+      EasingFunction::EaseInOut => cubic_bezier(0.42, 0.0, 0.58, 1.0, t),
+      EasingFunction::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(*x1, *y1, *x2, *y2, t),
+      EasingFunction::Steps { count, position } => steps(*count, position, t),
+      EasingFunction::LinearFunction { stops } => linear(stops, t),
+    }
+  }
+}
+
+/// Evaluates a cubic Bézier curve with the given control points.
+/// See https://www.w3.org/TR/css-easing-1/#cubic-bezier-algo.
+fn cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32, t: f32) -> f32 {
+  let (x1, y1, x2, y2, t) = (x1 as f64, y1 as f64, x2 as f64, y2 as f64, t as f64);
+
+  // Extrapolate outside [0, 1] using the tangent at the nearest endpoint.
+  if t < 0.0 {
+    let tangent = if x1 > 0.0 {
+      y1 / x1
+    } else if y1 == 0.0 && x2 > 0.0 {
+      y2 / x2
+    } else {
+      0.0
+    };
+    return (tangent * t) as f32;
+  }
+
+  if t > 1.0 {
+    let tangent = if x2 < 1.0 {
+      (y2 - 1.0) / (x2 - 1.0)
+    } else if x2 == 1.0 && x1 < 1.0 {
+      (y1 - 1.0) / (x1 - 1.0)
+    } else {
+      0.0
+    };
+    return (1.0 + tangent * (t - 1.0)) as f32;
+  }
+
+  // Polynomial coefficients for each axis.
+  let cx = 3.0 * x1;
+  let bx = 3.0 * (x2 - x1) - cx;
+  let ax = 1.0 - cx - bx;
+  let cy = 3.0 * y1;
+  let by = 3.0 * (y2 - y1) - cy;
+  let ay = 1.0 - cy - by;
+
+  let sample_x = |s: f64| ((ax * s + bx) * s + cx) * s;
+  let sample_y = |s: f64| ((ay * s + by) * s + cy) * s;
+  let sample_dx = |s: f64| (3.0 * ax * s + 2.0 * bx) * s + cx;
+
+  // Find the curve parameter for x = t. Try Newton's method first, and fall back to bisection.
+  const EPSILON: f64 = 1e-7;
+  let mut s = t;
+  for _ in 0..8 {
+    let x = sample_x(s) - t;
+    if x.abs() < EPSILON {
+      return sample_y(s) as f32;
+    }
+    let dx = sample_dx(s);
+    if dx.abs() < 1e-6 {
+      break;
+    }
+    s -= x / dx;
+  }
+
+  let mut lo = 0.0;
+  let mut hi = 1.0;
+  s = t;
+  while lo < hi {
+    let x = sample_x(s);
+    if (x - t).abs() < EPSILON {
+      break;
+    }
+    if t > x {
+      lo = s;
+    } else {
+      hi = s;
+    }
+    s = (hi - lo) / 2.0 + lo;
+    if hi - lo < EPSILON {
+      break;
+    }
+  }
+
+  sample_y(s) as f32
+}
+
+/// Evaluates a step easing function.
+/// See https://www.w3.org/TR/css-easing-1/#step-easing-algo.
+fn steps(count: CSSInteger, position: &StepPosition, t: f32) -> f32 {
+  if count <= 0 {
+    return t;
+  }
+
+  let count = count as f32;
+  let mut step = (t * count).floor();
+  if matches!(position, StepPosition::Start | StepPosition::JumpBoth) {
+    step += 1.0;
+  }
+
+  if t >= 0.0 && step < 0.0 {
+    step = 0.0;
+  }
+
+  let jumps = match position {
+    StepPosition::Start | StepPosition::End => count,
+    StepPosition::JumpNone => count - 1.0,
+    StepPosition::JumpBoth => count + 1.0,
+  };
+
+  if jumps <= 0.0 {
+    return if t < 1.0 { 0.0 } else { 1.0 };
+  }
+
+  if t <= 1.0 && step > jumps {
+    step = jumps;
+  }
+
+  step / jumps
+}
+
+/// Evaluates a `linear()` easing function.
+/// See https://www.w3.org/TR/css-easing-2/#linear-easing-function-output.
+fn linear(stops: &[LinearStop], t: f32) -> f32 {
+  if stops.is_empty() {
+    return t;
+  }
+
+  // Resolve the input progress value of each stop.
+  let mut inputs: Vec<Option<f32>> = stops.iter().map(|stop| stop.input.as_ref().map(|p| p.0)).collect();
+  let last = inputs.len() - 1;
+  if inputs[0].is_none() {
+    inputs[0] = Some(0.0);
+  }
+  if inputs[last].is_none() {
+    inputs[last] = Some(inputs[0].unwrap().max(1.0));
+  }
+
+  // Input values must be non-decreasing.
+  let mut max = inputs[0].unwrap();
+  for input in inputs.iter_mut().flatten() {
+    if *input < max {
+      *input = max;
+    } else {
+      max = *input;
+    }
+  }
+
+  // Space runs of stops without an input value evenly between their neighbors.
+  let mut i = 1;
+  while i < last {
+    if inputs[i].is_some() {
+      i += 1;
+      continue;
+    }
+    let start = i - 1;
+    let mut end = i;
+    while inputs[end].is_none() {
+      end += 1;
+    }
+    let from = inputs[start].unwrap();
+    let to = inputs[end].unwrap();
+    let len = (end - start) as f32;
+    for (j, input) in inputs.iter_mut().enumerate().take(end).skip(i) {
+      *input = Some(from + (to - from) * (j - start) as f32 / len);
+    }
+    i = end + 1;
+  }
+
+  let points: Vec<(f32, f32)> = stops
+    .iter()
+    .zip(inputs)
+    .map(|(stop, input)| (input.unwrap(), stop.output))
+    .collect();
+
+  if points.len() == 1 {
+    return points[0].1;
+  }
+
+  if let Some(point) = points.iter().rev().find(|point| point.0 == t) {
+    return point.1;
+  }
+
+  // Find the segment containing the input, extrapolating from the first or last segment.
+  let (a, b) = if t < points[0].0 {
+    (points[0], points[1])
+  } else if t > points[last].0 {
+    (points[last - 1], points[last])
+  } else {
+    let index = points.iter().rposition(|point| point.0 < t).unwrap_or(0);
+    (points[index], points[(index + 1).min(last)])
+  };
+
+  if a.0 == b.0 {
+    return b.1;
+  }
+
+  a.1 + (t - a.0) / (b.0 - a.0) * (b.1 - a.1)
+}
+
+impl LinearStop {
+  /// Parses a stop with an optional input progress value, or a stop with two input progress values,
+  /// which is expanded into two stops with the same output.
+  fn parse_into<'i, 't>(
+    input: &mut Parser<'i, 't>,
+    stops: &mut Vec<LinearStop>,
+  ) -> Result<(), ParseError<'i, ParserError<'i>>> {
+    fn parse_inputs<'i, 't>(input: &mut Parser<'i, 't>) -> Vec<Percentage> {
+      let mut inputs = Vec::new();
+      while inputs.len() < 2 {
+        match input.try_parse(Percentage::parse) {
+          Ok(percentage) => inputs.push(percentage),
+          Err(_) => break,
+        }
+      }
+      inputs
+    }
+
+    let mut inputs = parse_inputs(input);
+    let output = CSSNumber::parse(input)?;
+    if inputs.is_empty() {
+      inputs = parse_inputs(input);
+    }
+
+    if inputs.is_empty() {
+      stops.push(LinearStop { output, input: None });
+    } else {
+      stops.extend(inputs.into_iter().map(|input| LinearStop {
+        output,
+        input: Some(input),
+      }));
+    }
+    Ok(())
+  }
 }
 
 impl<'i> Parse<'i> for EasingFunction {
@@ -103,6 +381,23 @@ impl<'i> Parse<'i> for EasingFunction {
           }).unwrap_or_default();
           Ok(EasingFunction::Steps { count, position })
         },
+        "linear" => {
+          let mut stops = Vec::new();
+          let mut count = 0;
+          loop {
+            LinearStop::parse_into(input, &mut stops)?;
+            count += 1;
+            if input.try_parse(|input| input.expect_comma()).is_err() {
+              break;
+            }
+          }
+
+          // At least two arguments are required.
+          if count < 2 {
+            return Err(input.new_custom_error(ParserError::InvalidValue));
+          }
+          Ok(EasingFunction::LinearFunction { stops })
+        },
         _ => return Err(location.new_unexpected_token_error(Token::Ident(function.clone())))
       }
     })
@@ -177,6 +472,27 @@ impl ToCss for EasingFunction {
         position.to_css(dest)?;
         dest.write_char(')')
       }
+      EasingFunction::LinearFunction { stops } => {
+        if self.is_linear() {
+          return dest.write_str("linear");
+        }
+
+        dest.write_str("linear(")?;
+        let mut first = true;
+        for stop in stops {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          stop.output.to_css(dest)?;
+          if let Some(input) = &stop.input {
+            dest.write_char(' ')?;
+            input.to_css(dest)?;
+          }
+        }
+        dest.write_char(')')
+      }
     }
   }
 }
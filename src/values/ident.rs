@@ -49,7 +49,21 @@ impl<'i> ToCss for CustomIdent<'i> {
   where
     W: std::fmt::Write,
   {
-    dest.write_ident(&self.0)
+    self.to_css_with_options(dest, true)
+  }
+}
+
+impl<'i> CustomIdent<'i> {
+  /// Write the custom ident to CSS, only applying the CSS module pattern if `enabled_css_modules` is true.
+  pub(crate) fn to_css_with_options<W>(
+    &self,
+    dest: &mut Printer<W>,
+    enabled_css_modules: bool,
+  ) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    dest.write_ident_with_css_module(&self.0, enabled_css_modules)
   }
 }
 
@@ -4,7 +4,7 @@ use super::color::ColorFallbackKind;
 use super::gradient::*;
 use super::resolution::Resolution;
 use crate::compat;
-use crate::dependencies::{Dependency, UrlDependency};
+use crate::dependencies::{Dependency, DependencyKind, DependencyReference, UrlDependency};
 use crate::error::{ParserError, PrinterError};
 use crate::prefixes::{is_webkit_gradient, Feature};
 use crate::printer::Printer;
@@ -180,6 +180,15 @@ impl<'i> FallbackValues for Image<'i> {
     // Prefixed properties only support RGB.
     let prefix_image = rgb.as_ref().unwrap_or(self);
 
+    // Plain image for browsers that don't support image-set() at all.
+    if let Image::ImageSet(image_set) = prefix_image {
+      if targets.browsers.map(needs_image_set_fallback).unwrap_or(false) {
+        if let Some(image) = image_set.get_fallback_image() {
+          res.push(image);
+        }
+      }
+    }
+
     // Legacy -webkit-gradient()
     if prefixes.contains(VendorPrefix::WebKit)
       && targets.browsers.map(is_webkit_gradient).unwrap_or(false)
@@ -250,6 +259,22 @@ impl<'i, T: ImageFallback<'i>> FallbackValues for SmallVec<[T; 1]> {
     // Prefixed properties only support RGB.
     let prefix_images = rgb.as_ref().unwrap_or(&self);
 
+    // Plain images for browsers that don't support image-set() at all.
+    if targets.browsers.map(needs_image_set_fallback).unwrap_or(false)
+      && prefix_images.iter().any(|item| matches!(item.get_image(), Image::ImageSet(..)))
+    {
+      let images = prefix_images
+        .iter()
+        .map(|item| match item.get_image() {
+          Image::ImageSet(image_set) => image_set.get_fallback_image().map(|image| item.with_image(image)),
+          image => Some(item.with_image(image.clone())),
+        })
+        .collect::<Option<SmallVec<[T; 1]>>>();
+      if let Some(images) = images {
+        res.push(images);
+      }
+    }
+
     // Legacy -webkit-gradient()
     if prefixes.contains(VendorPrefix::WebKit) && targets.browsers.map(is_webkit_gradient).unwrap_or(false) {
       let images: SmallVec<[T; 1]> = prefix_images
@@ -384,6 +409,46 @@ impl<'i> ImageSet<'i> {
       vendor_prefix: prefix,
     }
   }
+
+  /// Returns the image to use in browsers that do not support `image-set()`.
+  /// This is the `1x` option if there is one, and otherwise the lowest resolution option.
+  pub fn get_fallback_image(&self) -> Option<Image<'i>> {
+    self
+      .options
+      .iter()
+      .find(|option| option.resolution.to_dppx() == 1.0)
+      .or_else(|| {
+        self
+          .options
+          .iter()
+          .min_by(|a, b| a.resolution.to_dppx().total_cmp(&b.resolution.to_dppx()))
+      })
+      .map(|option| option.image.clone())
+  }
+}
+
+/// Returns whether any of the browsers support neither the unprefixed nor the prefixed `image-set()`.
+fn needs_image_set_fallback(browsers: Browsers) -> bool {
+  macro_rules! check {
+    ($($browser: ident),+) => {
+      $(
+        if let Some(version) = browsers.$browser {
+          let browser = Browsers {
+            $browser: Some(version),
+            ..Browsers::default()
+          };
+          if !compat::Feature::ImageSet.is_compatible(browser)
+            && !Feature::ImageSet.prefixes_for(browser).contains(VendorPrefix::WebKit)
+          {
+            return true;
+          }
+        }
+      )+
+    };
+  }
+
+  check!(android, chrome, edge, firefox, ie, ios_saf, opera, safari, samsung);
+  false
 }
 
 impl<'i> Parse<'i> for ImageSet<'i> {
@@ -410,6 +475,7 @@ impl<'i> ToCss for ImageSet<'i> {
   {
     self.vendor_prefix.to_css(dest)?;
     dest.write_str("image-set(")?;
+    dest.in_image_set = true;
     let mut first = true;
     for option in &self.options {
       if first {
@@ -419,6 +485,7 @@ impl<'i> ToCss for ImageSet<'i> {
       }
       option.to_css(dest, self.vendor_prefix != VendorPrefix::None)?;
     }
+    dest.in_image_set = false;
     dest.write_char(')')
   }
 }
@@ -432,7 +499,7 @@ impl<'i> IsCompatible for ImageSet<'i> {
 
 /// An image option within the `image-set()` function. See [ImageSet](ImageSet).
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "visitor", derive(Visit), visit_types(IMAGES | URLS | COLORS | LENGTHS | ANGLES | RESOLUTIONS))]
 #[cfg_attr(feature = "into_owned", derive(static_self::IntoOwned))]
 #[cfg_attr(
   feature = "serde",
@@ -489,6 +556,11 @@ impl<'i> ImageSetOption<'i> {
       // Prefixed syntax didn't allow strings, only url()
       Image::Url(url) if !is_prefixed => {
         // Add dependency if needed. Normally this is handled by the Url type.
+        if dest.dependency_references.is_some() {
+          let reference = DependencyReference::url(url, DependencyKind::ImageSet, dest.filename());
+          dest.dependency_references.as_mut().unwrap().push(reference);
+        }
+
         let dep = if dest.dependencies.is_some() {
           Some(UrlDependency::new(url, dest.filename()))
         } else {
@@ -513,9 +585,10 @@ impl<'i> ImageSetOption<'i> {
 
     // Safari only supports the x resolution unit in image-set().
     // In other places, x was added as an alias later.
-    // Temporarily ignore the targets while printing here.
+    // Temporarily ignore the targets while printing here, and
+    // normalize other units to x so all forms agree.
     let targets = std::mem::take(&mut dest.targets);
-    self.resolution.to_css(dest)?;
+    Resolution::Dppx(self.resolution.to_dppx()).to_css(dest)?;
     dest.targets = targets;
 
     if let Some(file_type) = &self.file_type {
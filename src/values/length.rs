@@ -1,17 +1,17 @@
 //! CSS length values.
 
 use super::angle::impl_try_from_angle;
-use super::calc::{Calc, MathFunction};
+use super::calc::{Calc, CalcDependencies, CalcEvaluation, MathFunction};
 use super::number::CSSNumber;
 use super::percentage::DimensionPercentage;
-use crate::error::{ParserError, PrinterError};
+use crate::error::{ParserError, PrinterError, UnitConversionError};
 use crate::printer::Printer;
 use crate::targets::Browsers;
+use crate::traits::{private::TryToPx, IsCompatible, TrySign, ViewportFallback};
 use crate::traits::{
   private::{AddInternal, TryAdd},
   Map, Parse, Sign, ToCss, TryMap, TryOp, Zero,
 };
-use crate::traits::{IsCompatible, TrySign};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use const_str;
@@ -27,6 +27,25 @@ impl LengthPercentage {
     LengthPercentage::Dimension(LengthValue::Px(val))
   }
 
+  /// Converts the value to pixels using the given context. Percentages are resolved against
+  /// the context's `percentage_basis`.
+  pub fn to_px_with_context(&self, context: &UnitContext) -> Result<CSSNumber, UnitConversionError> {
+    self.evaluate(context).map(|result| result.value)
+  }
+
+  /// Evaluates the value to pixels using the given context, including any `calc()` expressions,
+  /// and returns which inputs from the context the result depends on.
+  pub fn evaluate(&self, context: &UnitContext) -> Result<CalcEvaluation, UnitConversionError> {
+    match self {
+      DimensionPercentage::Dimension(d) => d.evaluate(context),
+      DimensionPercentage::Percentage(p) => context
+        .percentage_basis
+        .map(|basis| CalcEvaluation::new(p.0 * basis, CalcDependencies::PercentageBasis))
+        .ok_or(UnitConversionError::MissingPercentageBasis),
+      DimensionPercentage::Calc(c) => c.evaluate_with(&|v: &LengthPercentage| v.evaluate(context)),
+    }
+  }
+
   pub(crate) fn to_css_unitless<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
     W: std::fmt::Write,
@@ -180,27 +199,34 @@ macro_rules! define_length_units {
       }
     }
 
-    impl IsCompatible for LengthValue {
-      fn is_compatible(&self, browsers: Browsers) -> bool {
-        macro_rules! is_compatible {
+    impl LengthValue {
+      /// Returns the compatibility feature for the unit of the length value, if any.
+      pub(crate) fn feature(&self) -> Option<crate::compat::Feature> {
+        macro_rules! feature {
           ($f: ident) => {
-            crate::compat::Feature::$f.is_compatible(browsers)
+            Some(crate::compat::Feature::$f)
           };
           () => {
-            true
+            None
           };
         }
 
         match self {
           $(
             LengthValue::$name(_) => {
-              is_compatible!($($feature)?)
+              feature!($($feature)?)
             }
           )+
         }
       }
     }
 
+    impl IsCompatible for LengthValue {
+      fn is_compatible(&self, browsers: Browsers) -> bool {
+        self.feature().map_or(true, |feature| feature.is_compatible(browsers))
+      }
+    }
+
     impl TryAdd<LengthValue> for LengthValue {
       fn try_add(&self, other: &LengthValue) -> Option<LengthValue> {
         use LengthValue::*;
@@ -486,6 +512,10 @@ impl ToCss for LengthValue {
   where
     W: std::fmt::Write,
   {
+    if let Some(px) = dest.rem_to_px(self) {
+      return LengthValue::Px(px).to_css(dest);
+    }
+
     let (value, unit) = self.to_unit_value();
 
     // The unit can be omitted if the value is zero, except inside calc()
@@ -555,6 +585,136 @@ impl LengthValue {
   }
 }
 
+/// Context used to resolve relative lengths to pixels, e.g. for layout estimation.
+///
+/// Units that depend on context that is not provided cannot be resolved, and will
+/// return a [UnitConversionError](crate::error::UnitConversionError).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UnitContext {
+  /// The root font size in pixels, used to resolve `rem`, `rex`, `rch`, and `ric` units.
+  pub root_font_size: Option<CSSNumber>,
+  /// The font size of the element in pixels, used to resolve `em`, `ex`, `ch`, and `ic` units.
+  /// Defaults to the root font size.
+  pub font_size: Option<CSSNumber>,
+  /// The line height of the element in pixels, used to resolve `lh` units.
+  pub line_height: Option<CSSNumber>,
+  /// The line height of the root element in pixels, used to resolve `rlh` units.
+  pub root_line_height: Option<CSSNumber>,
+  /// The width of the viewport in pixels.
+  pub viewport_width: Option<CSSNumber>,
+  /// The height of the viewport in pixels.
+  pub viewport_height: Option<CSSNumber>,
+  /// The width of the query container in pixels, used to resolve `cqw` and `cqi` units.
+  pub container_width: Option<CSSNumber>,
+  /// The height of the query container in pixels, used to resolve `cqh` and `cqb` units.
+  pub container_height: Option<CSSNumber>,
+  /// The size in pixels that percentages resolve against.
+  pub percentage_basis: Option<CSSNumber>,
+}
+
+impl LengthValue {
+  /// Converts the value to pixels using the given context.
+  ///
+  /// `ex` and `ch` units are approximated as `0.5em`, and `ic` as `1em`.
+  /// Logical viewport and container units assume a horizontal writing mode. `cap` units
+  /// cannot be resolved.
+  pub fn to_px_with_context(&self, context: &UnitContext) -> Result<CSSNumber, UnitConversionError> {
+    self.evaluate(context).map(|result| result.value)
+  }
+
+  /// Converts the value to pixels using the given context, and returns which inputs from the
+  /// context the result depends on. See [to_px_with_context](LengthValue::to_px_with_context).
+  pub fn evaluate(&self, context: &UnitContext) -> Result<CalcEvaluation, UnitConversionError> {
+    use LengthValue::*;
+    if let Some(px) = self.to_px() {
+      return Ok(CalcEvaluation::new(px, CalcDependencies::empty()));
+    }
+
+    let input = |value: Option<CSSNumber>, dependency| value.map(|value| (value, dependency));
+    let min = |a: Option<(CSSNumber, CalcDependencies)>, b: Option<(CSSNumber, CalcDependencies)>| {
+      a.zip(b).map(|((a, da), (b, db))| (a.min(b), da | db))
+    };
+    let max = |a: Option<(CSSNumber, CalcDependencies)>, b: Option<(CSSNumber, CalcDependencies)>| {
+      a.zip(b).map(|((a, da), (b, db))| (a.max(b), da | db))
+    };
+
+    let rem = input(context.root_font_size, CalcDependencies::RootFontSize);
+    let em = input(context.font_size, CalcDependencies::FontSize).or(rem);
+    let lh = input(context.line_height, CalcDependencies::LineHeight);
+    let rlh = input(context.root_line_height, CalcDependencies::RootLineHeight);
+    let vw = input(
+      context.viewport_width.map(|size| size / 100.0),
+      CalcDependencies::ViewportWidth,
+    );
+    let vh = input(
+      context.viewport_height.map(|size| size / 100.0),
+      CalcDependencies::ViewportHeight,
+    );
+    let cqw = input(
+      context.container_width.map(|size| size / 100.0),
+      CalcDependencies::ContainerWidth,
+    );
+    let cqh = input(
+      context.container_height.map(|size| size / 100.0),
+      CalcDependencies::ContainerHeight,
+    );
+    let (basis, factor) = match self {
+      Em(v) | Ic(v) => (em, *v),
+      Ex(v) | Ch(v) => (em, v * 0.5),
+      Rem(v) | Ric(v) => (rem, *v),
+      Rex(v) | Rch(v) => (rem, v * 0.5),
+      Lh(v) => (lh, *v),
+      Rlh(v) => (rlh, *v),
+      Vw(v) | Lvw(v) | Svw(v) | Dvw(v) | Vi(v) | Lvi(v) | Svi(v) | Dvi(v) => (vw, *v),
+      Vh(v) | Lvh(v) | Svh(v) | Dvh(v) | Vb(v) | Lvb(v) | Svb(v) | Dvb(v) => (vh, *v),
+      Vmin(v) | Lvmin(v) | Svmin(v) | Dvmin(v) => (min(vw, vh), *v),
+      Vmax(v) | Lvmax(v) | Svmax(v) | Dvmax(v) => (max(vw, vh), *v),
+      Cqw(v) | Cqi(v) => (cqw, *v),
+      Cqh(v) | Cqb(v) => (cqh, *v),
+      Cqmin(v) => (min(cqw, cqh), *v),
+      Cqmax(v) => (max(cqw, cqh), *v),
+      _ => (None, 0.0),
+    };
+
+    basis
+      .map(|(basis, dependencies)| CalcEvaluation::new(factor * basis, dependencies))
+      .ok_or_else(|| UnitConversionError::MissingContext {
+        unit: self.to_unit_value().1.to_owned(),
+      })
+  }
+}
+
+impl ViewportFallback for LengthValue {
+  fn replace_viewport_units(&mut self, browsers: Browsers) -> bool {
+    use LengthValue::*;
+    if self.is_compatible(browsers) {
+      return false;
+    }
+
+    *self = match *self {
+      Lvw(v) | Svw(v) | Dvw(v) => Vw(v),
+      Lvh(v) | Svh(v) | Dvh(v) => Vh(v),
+      Lvi(v) | Svi(v) | Dvi(v) => Vi(v),
+      Lvb(v) | Svb(v) | Dvb(v) => Vb(v),
+      Lvmin(v) | Svmin(v) | Dvmin(v) => Vmin(v),
+      Lvmax(v) | Svmax(v) | Dvmax(v) => Vmax(v),
+      _ => return false,
+    };
+    true
+  }
+}
+
+impl TryToPx for LengthValue {
+  fn try_to_px(&self, context: &UnitContext) -> Option<CSSNumber> {
+    use LengthValue::*;
+    match self {
+      // The font of the element is unknown, so `rem` is the only font-relative unit that is resolved.
+      Em(..) | Ex(..) | Ch(..) | Ic(..) | Rex(..) | Rch(..) | Ric(..) => None,
+      _ => self.to_px_with_context(context).ok(),
+    }
+  }
+}
+
 /// A CSS [`<length>`](https://www.w3.org/TR/css-values-4/#lengths) value, with support for `calc()`.
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "visitor", derive(Visit))]
@@ -666,6 +826,21 @@ impl Length {
     }
   }
 
+  /// Converts the length to pixels using the given context, evaluating `calc()` expressions
+  /// where possible. See [LengthValue::to_px_with_context](LengthValue::to_px_with_context).
+  pub fn to_px_with_context(&self, context: &UnitContext) -> Result<CSSNumber, UnitConversionError> {
+    self.evaluate(context).map(|result| result.value)
+  }
+
+  /// Evaluates the length to pixels using the given context, including any `calc()` expressions,
+  /// and returns which inputs from the context the result depends on.
+  pub fn evaluate(&self, context: &UnitContext) -> Result<CalcEvaluation, UnitConversionError> {
+    match self {
+      Length::Value(v) => v.evaluate(context),
+      Length::Calc(c) => c.evaluate_with(&|v: &Length| v.evaluate(context)),
+    }
+  }
+
   fn add(self, other: Length) -> Length {
     let mut a = self;
     let mut b = other;
@@ -818,6 +993,15 @@ impl TryMap for Length {
   }
 }
 
+impl TryToPx for Length {
+  fn try_to_px(&self, context: &UnitContext) -> Option<CSSNumber> {
+    match self {
+      Length::Value(v) => v.try_to_px(context),
+      Length::Calc(c) => c.try_to_px(context),
+    }
+  }
+}
+
 impl TrySign for Length {
   fn try_sign(&self) -> Option<f32> {
     match self {
@@ -4,7 +4,7 @@ use super::angle::impl_try_from_angle;
 use super::calc::Calc;
 use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
-use crate::traits::private::AddInternal;
+use crate::traits::private::{AddInternal, TryToPx};
 use crate::traits::{Map, Op, Parse, Sign, ToCss, Zero};
 use cssparser::*;
 
@@ -73,6 +73,8 @@ impl AddInternal for CSSNumber {
   }
 }
 
+impl TryToPx for CSSNumber {}
+
 impl Op for CSSNumber {
   fn op<F: FnOnce(f32, f32) -> f32>(&self, to: &Self, op: F) -> Self {
     op(*self, *to)
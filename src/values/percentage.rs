@@ -2,11 +2,15 @@
 
 use super::angle::{impl_try_from_angle, Angle};
 use super::calc::{Calc, MathFunction};
+use super::length::UnitContext;
 use super::number::CSSNumber;
 use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
-use crate::traits::private::AddInternal;
-use crate::traits::{impl_op, private::TryAdd, Op, Parse, Sign, ToCss, TryMap, TryOp, TrySign, Zero};
+use crate::targets::Browsers;
+use crate::traits::private::{AddInternal, TryToPx};
+use crate::traits::{
+  impl_op, private::TryAdd, Op, Parse, Sign, ToCss, TryMap, TryOp, TrySign, ViewportFallback, Zero,
+};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
@@ -97,6 +101,8 @@ impl AddInternal for Percentage {
   }
 }
 
+impl TryToPx for Percentage {}
+
 impl std::cmp::PartialOrd<Percentage> for Percentage {
   fn partial_cmp(&self, other: &Percentage) -> Option<std::cmp::Ordering> {
     self.0.partial_cmp(&other.0)
@@ -474,6 +480,26 @@ impl<D: Zero> Zero for DimensionPercentage<D> {
   }
 }
 
+impl<D: TryToPx> TryToPx for DimensionPercentage<D> {
+  fn try_to_px(&self, context: &UnitContext) -> Option<CSSNumber> {
+    match self {
+      DimensionPercentage::Dimension(d) => d.try_to_px(context),
+      DimensionPercentage::Percentage(p) => context.percentage_basis.map(|basis| p.0 * basis),
+      DimensionPercentage::Calc(c) => c.try_to_px(context),
+    }
+  }
+}
+
+impl<D: ViewportFallback> ViewportFallback for DimensionPercentage<D> {
+  fn replace_viewport_units(&mut self, browsers: Browsers) -> bool {
+    match self {
+      DimensionPercentage::Dimension(d) => d.replace_viewport_units(browsers),
+      DimensionPercentage::Percentage(_) => false,
+      DimensionPercentage::Calc(c) => c.replace_viewport_units(browsers),
+    }
+  }
+}
+
 impl<D: TrySign> TrySign for DimensionPercentage<D> {
   fn try_sign(&self) -> Option<f32> {
     match self {
@@ -484,7 +510,7 @@ impl<D: TrySign> TrySign for DimensionPercentage<D> {
   }
 }
 
-impl<D: ToCss + std::ops::Mul<CSSNumber, Output = D> + TrySign + Clone + std::fmt::Debug> ToCss
+impl<D: ToCss + std::ops::Mul<CSSNumber, Output = D> + TrySign + TryToPx + Clone + std::fmt::Debug> ToCss
   for DimensionPercentage<D>
 {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
@@ -1,9 +1,9 @@
 //! CSS resolution values.
 
-use super::length::serialize_dimension;
+use super::length::{serialize_dimension, Length, UnitContext};
 use super::number::CSSNumber;
 use crate::compat::Feature;
-use crate::error::{ParserError, PrinterError};
+use crate::error::{ParserError, PrinterError, UnitConversionError};
 use crate::printer::Printer;
 use crate::traits::{Parse, ToCss};
 #[cfg(feature = "visitor")]
@@ -64,6 +64,36 @@ impl<'i> TryFrom<&Token<'i>> for Resolution {
   }
 }
 
+impl Resolution {
+  /// Returns the resolution in dots per px.
+  pub fn to_dppx(&self) -> CSSNumber {
+    match self {
+      Resolution::Dpi(dpi) => dpi / 96.0,
+      Resolution::Dpcm(dpcm) => dpcm * 2.54 / 96.0,
+      Resolution::Dppx(dppx) => *dppx,
+    }
+  }
+
+  /// Returns the resolution in dots per inch.
+  pub fn to_dpi(&self) -> CSSNumber {
+    match self {
+      Resolution::Dpi(dpi) => *dpi,
+      Resolution::Dpcm(dpcm) => dpcm * 2.54,
+      Resolution::Dppx(dppx) => dppx * 96.0,
+    }
+  }
+
+  /// Returns the resolution in dots per centimeter.
+  pub fn to_dpcm(&self) -> CSSNumber {
+    self.to_dpi() / 2.54
+  }
+
+  /// Returns the number of device pixels covered by the given length, resolved using the given context.
+  pub fn to_device_px(&self, length: &Length, context: &UnitContext) -> Result<CSSNumber, UnitConversionError> {
+    Ok(length.to_px_with_context(context)? * self.to_dppx())
+  }
+}
+
 impl ToCss for Resolution {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -1,13 +1,15 @@
 //! CSS shape values for masking and clipping.
 
-use super::length::LengthPercentage;
+use super::angle::Angle;
+use super::length::{LengthPercentage, LengthPercentageOrAuto};
+use super::number::CSSNumber;
 use super::position::Position;
 use super::rect::Rect;
 use crate::error::{ParserError, PrinterError};
 use crate::macros::enum_property;
 use crate::printer::Printer;
 use crate::properties::border_radius::BorderRadius;
-use crate::traits::{Parse, ToCss};
+use crate::traits::{Parse, ToCss, TrySign, Zero};
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
 use cssparser::*;
@@ -31,6 +33,14 @@ pub enum BasicShape {
   Ellipse(Ellipse),
   /// A polygon.
   Polygon(Polygon),
+  /// A rectangle defined by its position and size.
+  Xywh(XywhRect),
+  /// A rectangle defined by the offsets of its edges from the top and left of the reference box.
+  Rect(RectShape),
+  /// A path defined by SVG path data.
+  Path(Path),
+  /// A path defined by a list of shape commands.
+  Shape(Shape),
 }
 
 /// An [`inset()`](https://www.w3.org/TR/css-shapes-1/#funcdef-inset) rectangle shape.
@@ -119,9 +129,9 @@ pub struct Polygon {
 #[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
 pub struct Point {
   /// The x position of the point.
-  x: LengthPercentage,
+  pub x: LengthPercentage,
   /// the y position of the point.
-  y: LengthPercentage,
+  pub y: LengthPercentage,
 }
 
 enum_property! {
@@ -143,6 +153,271 @@ impl Default for FillRule {
   }
 }
 
+/// An [`xywh()`](https://drafts.csswg.org/css-shapes-1/#funcdef-basic-shape-xywh) rectangle shape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct XywhRect {
+  /// The distance of the left edge of the rectangle from the left edge of the reference box.
+  pub x: LengthPercentage,
+  /// The distance of the top edge of the rectangle from the top edge of the reference box.
+  pub y: LengthPercentage,
+  /// The width of the rectangle.
+  pub width: LengthPercentage,
+  /// The height of the rectangle.
+  pub height: LengthPercentage,
+  /// A corner radius for the rectangle.
+  pub radius: BorderRadius,
+}
+
+/// A [`rect()`](https://drafts.csswg.org/css-shapes-1/#funcdef-basic-shape-rect) rectangle shape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct RectShape {
+  /// The top, right, bottom, and left edges of the rectangle, relative to the top and left edges of
+  /// the reference box. `auto` refers to the corresponding edge of the reference box.
+  pub rect: Rect<LengthPercentageOrAuto>,
+  /// A corner radius for the rectangle.
+  pub radius: BorderRadius,
+}
+
+/// A [`path()`](https://drafts.csswg.org/css-shapes-1/#funcdef-basic-shape-path) shape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Path {
+  /// The fill rule used to determine the interior of the path.
+  pub fill_rule: FillRule,
+  /// The path data.
+  pub data: PathData,
+}
+
+/// [SVG path data](https://www.w3.org/TR/SVG2/paths.html#PathData), as used in the `path()` function.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct PathData(pub Vec<PathCommand>);
+
+/// A command within [SVG path data](PathData).
+///
+/// Coordinates of relative commands are relative to the current point.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+#[allow(missing_docs)]
+pub enum PathCommand {
+  /// Starts a new subpath at the given point (`M`).
+  Move { relative: bool, x: CSSNumber, y: CSSNumber },
+  /// Draws a line to the given point (`L`).
+  Line { relative: bool, x: CSSNumber, y: CSSNumber },
+  /// Draws a horizontal line to the given x position (`H`).
+  HorizontalLine { relative: bool, x: CSSNumber },
+  /// Draws a vertical line to the given y position (`V`).
+  VerticalLine { relative: bool, y: CSSNumber },
+  /// Draws a cubic Bézier curve to the given point (`C`).
+  CubicCurve {
+    relative: bool,
+    x1: CSSNumber,
+    y1: CSSNumber,
+    x2: CSSNumber,
+    y2: CSSNumber,
+    x: CSSNumber,
+    y: CSSNumber,
+  },
+  /// Draws a cubic Bézier curve to the given point, reflecting the previous control point (`S`).
+  SmoothCubicCurve {
+    relative: bool,
+    x2: CSSNumber,
+    y2: CSSNumber,
+    x: CSSNumber,
+    y: CSSNumber,
+  },
+  /// Draws a quadratic Bézier curve to the given point (`Q`).
+  QuadraticCurve {
+    relative: bool,
+    x1: CSSNumber,
+    y1: CSSNumber,
+    x: CSSNumber,
+    y: CSSNumber,
+  },
+  /// Draws a quadratic Bézier curve to the given point, reflecting the previous control point (`T`).
+  SmoothQuadraticCurve { relative: bool, x: CSSNumber, y: CSSNumber },
+  /// Draws an elliptical arc to the given point (`A`).
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Arc {
+    relative: bool,
+    rx: CSSNumber,
+    ry: CSSNumber,
+    angle: CSSNumber,
+    large_arc: bool,
+    sweep: bool,
+    x: CSSNumber,
+    y: CSSNumber,
+  },
+  /// Closes the current subpath (`Z`).
+  Close { relative: bool },
+}
+
+/// A [`shape()`](https://drafts.csswg.org/css-shapes-2/#shape-function) shape.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(rename_all = "camelCase")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub struct Shape {
+  /// The fill rule used to determine the interior of the shape.
+  pub fill_rule: FillRule,
+  /// The starting point of the shape.
+  pub start: Point,
+  /// The commands that make up the shape.
+  pub commands: Vec<ShapeCommand>,
+}
+
+/// A [`<shape-command>`](https://drafts.csswg.org/css-shapes-2/#typedef-shape-command) within a `shape()` function.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "visitor", derive(Visit))]
+#[cfg_attr(
+  feature = "serde",
+  derive(serde::Serialize, serde::Deserialize),
+  serde(tag = "type", rename_all = "kebab-case")
+)]
+#[cfg_attr(feature = "jsonschema", derive(schemars::JsonSchema))]
+pub enum ShapeCommand {
+  /// Starts a new subpath at the given point.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Move {
+    /// Whether the point is absolute or relative to the current point.
+    by_to: ByTo,
+    /// The point.
+    point: Point,
+  },
+  /// Draws a line to the given point.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Line {
+    /// Whether the point is absolute or relative to the current point.
+    by_to: ByTo,
+    /// The end point of the line.
+    point: Point,
+  },
+  /// Draws a horizontal line to the given x position.
+  #[cfg_attr(feature = "serde", serde(rename = "hline", rename_all = "camelCase"))]
+  HorizontalLine {
+    /// Whether the position is absolute or relative to the current point.
+    by_to: ByTo,
+    /// The x position of the end of the line.
+    x: LengthPercentage,
+  },
+  /// Draws a vertical line to the given y position.
+  #[cfg_attr(feature = "serde", serde(rename = "vline", rename_all = "camelCase"))]
+  VerticalLine {
+    /// Whether the position is absolute or relative to the current point.
+    by_to: ByTo,
+    /// The y position of the end of the line.
+    y: LengthPercentage,
+  },
+  /// Draws a quadratic or cubic Bézier curve to the given point.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Curve {
+    /// Whether the points are absolute or relative to the current point.
+    by_to: ByTo,
+    /// The end point of the curve.
+    point: Point,
+    /// The first control point.
+    control1: Point,
+    /// The second control point. If omitted, the curve is quadratic.
+    control2: Option<Point>,
+  },
+  /// Draws a smooth Bézier curve to the given point, reflecting the previous control point.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Smooth {
+    /// Whether the points are absolute or relative to the current point.
+    by_to: ByTo,
+    /// The end point of the curve.
+    point: Point,
+    /// The second control point. If omitted, the curve is quadratic.
+    control: Option<Point>,
+  },
+  /// Draws an elliptical arc to the given point.
+  #[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+  Arc {
+    /// Whether the point is absolute or relative to the current point.
+    by_to: ByTo,
+    /// The end point of the arc.
+    point: Point,
+    /// The x-radius of the ellipse.
+    radius_x: LengthPercentage,
+    /// The y-radius of the ellipse.
+    radius_y: LengthPercentage,
+    /// The direction in which the arc is drawn.
+    sweep: ArcSweep,
+    /// Whether the larger or smaller of the possible arcs is drawn.
+    size: ArcSize,
+    /// The rotation of the ellipse.
+    rotate: Angle,
+  },
+  /// Closes the current subpath.
+  Close,
+}
+
+enum_property! {
+  /// Whether the coordinates of a [shape command](ShapeCommand) are relative to the current point.
+  pub enum ByTo {
+    /// The coordinates are relative to the current point.
+    By,
+    /// The coordinates are relative to the reference box.
+    To,
+  }
+}
+
+enum_property! {
+  /// The direction in which an `arc` [shape command](ShapeCommand) is drawn.
+  pub enum ArcSweep {
+    /// The arc is drawn clockwise.
+    Cw,
+    /// The arc is drawn counter-clockwise.
+    Ccw,
+  }
+}
+
+impl Default for ArcSweep {
+  fn default() -> ArcSweep {
+    ArcSweep::Ccw
+  }
+}
+
+enum_property! {
+  /// Which of the possible arcs an `arc` [shape command](ShapeCommand) draws.
+  pub enum ArcSize {
+    /// The larger of the two arcs is drawn.
+    Large,
+    /// The smaller of the two arcs is drawn.
+    Small,
+  }
+}
+
+impl Default for ArcSize {
+  fn default() -> ArcSize {
+    ArcSize::Small
+  }
+}
+
 impl<'i> Parse<'i> for BasicShape {
   fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
@@ -152,6 +427,10 @@ impl<'i> Parse<'i> for BasicShape {
       "circle" => Ok(BasicShape::Circle(input.parse_nested_block(Circle::parse)?)),
       "ellipse" => Ok(BasicShape::Ellipse(input.parse_nested_block(Ellipse::parse)?)),
       "polygon" => Ok(BasicShape::Polygon(input.parse_nested_block(Polygon::parse)?)),
+      "xywh" => Ok(BasicShape::Xywh(input.parse_nested_block(XywhRect::parse)?)),
+      "rect" => Ok(BasicShape::Rect(input.parse_nested_block(RectShape::parse)?)),
+      "path" => Ok(BasicShape::Path(input.parse_nested_block(Path::parse)?)),
+      "shape" => Ok(BasicShape::Shape(input.parse_nested_block(Shape::parse)?)),
       _ => Err(location.new_unexpected_token_error(Token::Ident(f.clone()))),
     }
   }
@@ -248,6 +527,159 @@ impl<'i> Parse<'i> for Point {
   }
 }
 
+/// Parses an optional `round <'border-radius'>` component of a rectangle shape.
+fn parse_round<'i, 't>(input: &mut Parser<'i, 't>) -> Result<BorderRadius, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_ident_matching("round")).is_ok() {
+    BorderRadius::parse(input)
+  } else {
+    Ok(BorderRadius::default())
+  }
+}
+
+impl<'i> Parse<'i> for XywhRect {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let x = LengthPercentage::parse(input)?;
+    let y = LengthPercentage::parse(input)?;
+    let location = input.current_source_location();
+    let width = LengthPercentage::parse(input)?;
+    let height = LengthPercentage::parse(input)?;
+    if width.is_sign_negative() || height.is_sign_negative() {
+      return Err(location.new_custom_error(ParserError::InvalidValue));
+    }
+    let radius = parse_round(input)?;
+    Ok(XywhRect {
+      x,
+      y,
+      width,
+      height,
+      radius,
+    })
+  }
+}
+
+impl<'i> Parse<'i> for RectShape {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let rect = Rect::new(
+      LengthPercentageOrAuto::parse(input)?,
+      LengthPercentageOrAuto::parse(input)?,
+      LengthPercentageOrAuto::parse(input)?,
+      LengthPercentageOrAuto::parse(input)?,
+    );
+    let radius = parse_round(input)?;
+    Ok(RectShape { rect, radius })
+  }
+}
+
+impl<'i> Parse<'i> for Path {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let fill_rule = input.try_parse(FillRule::parse);
+    if fill_rule.is_ok() {
+      input.expect_comma()?;
+    }
+
+    let location = input.current_source_location();
+    let string = input.expect_string()?;
+    let data = PathData::from_svg(string).ok_or_else(|| location.new_custom_error(ParserError::InvalidValue))?;
+    Ok(Path {
+      fill_rule: fill_rule.unwrap_or_default(),
+      data,
+    })
+  }
+}
+
+impl<'i> Parse<'i> for Shape {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let fill_rule = input.try_parse(FillRule::parse).unwrap_or_default();
+    input.expect_ident_matching("from")?;
+    let start = Point::parse(input)?;
+    input.expect_comma()?;
+    let commands = input.parse_comma_separated(ShapeCommand::parse)?;
+    Ok(Shape {
+      fill_rule,
+      start,
+      commands,
+    })
+  }
+}
+
+impl<'i> Parse<'i> for ShapeCommand {
+  fn parse<'t>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident_cloned()?;
+    match_ignore_ascii_case! { &ident,
+      "move" => Ok(ShapeCommand::Move { by_to: ByTo::parse(input)?, point: Point::parse(input)? }),
+      "line" => Ok(ShapeCommand::Line { by_to: ByTo::parse(input)?, point: Point::parse(input)? }),
+      "hline" => Ok(ShapeCommand::HorizontalLine { by_to: ByTo::parse(input)?, x: LengthPercentage::parse(input)? }),
+      "vline" => Ok(ShapeCommand::VerticalLine { by_to: ByTo::parse(input)?, y: LengthPercentage::parse(input)? }),
+      "curve" => {
+        let by_to = ByTo::parse(input)?;
+        let point = Point::parse(input)?;
+        input.expect_ident_matching("with")?;
+        let control1 = Point::parse(input)?;
+        let control2 = input.try_parse(|input| {
+          input.expect_delim('/')?;
+          Point::parse(input)
+        }).ok();
+        Ok(ShapeCommand::Curve { by_to, point, control1, control2 })
+      },
+      "smooth" => {
+        let by_to = ByTo::parse(input)?;
+        let point = Point::parse(input)?;
+        let control = input.try_parse(|input| {
+          input.expect_ident_matching("with")?;
+          Point::parse(input)
+        }).ok();
+        Ok(ShapeCommand::Smooth { by_to, point, control })
+      },
+      "arc" => {
+        let by_to = ByTo::parse(input)?;
+        let point = Point::parse(input)?;
+        input.expect_ident_matching("of")?;
+        let radius_x = LengthPercentage::parse(input)?;
+        let radius_y = input.try_parse(LengthPercentage::parse).unwrap_or_else(|_| radius_x.clone());
+
+        let mut sweep = None;
+        let mut size = None;
+        let mut rotate = None;
+        loop {
+          if sweep.is_none() {
+            if let Ok(value) = input.try_parse(ArcSweep::parse) {
+              sweep = Some(value);
+              continue;
+            }
+          }
+
+          if size.is_none() {
+            if let Ok(value) = input.try_parse(ArcSize::parse) {
+              size = Some(value);
+              continue;
+            }
+          }
+
+          if rotate.is_none() && input.try_parse(|input| input.expect_ident_matching("rotate")).is_ok() {
+            rotate = Some(Angle::parse(input)?);
+            continue;
+          }
+
+          break;
+        }
+
+        Ok(ShapeCommand::Arc {
+          by_to,
+          point,
+          radius_x,
+          radius_y,
+          sweep: sweep.unwrap_or_default(),
+          size: size.unwrap_or_default(),
+          rotate: rotate.unwrap_or(Angle::Deg(0.0)),
+        })
+      },
+      "close" => Ok(ShapeCommand::Close),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
 impl ToCss for BasicShape {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -274,6 +706,26 @@ impl ToCss for BasicShape {
         poly.to_css(dest)?;
         dest.write_char(')')
       }
+      BasicShape::Xywh(rect) => {
+        dest.write_str("xywh(")?;
+        rect.to_css(dest)?;
+        dest.write_char(')')
+      }
+      BasicShape::Rect(rect) => {
+        dest.write_str("rect(")?;
+        rect.to_css(dest)?;
+        dest.write_char(')')
+      }
+      BasicShape::Path(path) => {
+        dest.write_str("path(")?;
+        path.to_css(dest)?;
+        dest.write_char(')')
+      }
+      BasicShape::Shape(shape) => {
+        dest.write_str("shape(")?;
+        shape.to_css(dest)?;
+        dest.write_char(')')
+      }
     }
   }
 }
@@ -387,3 +839,640 @@ impl ToCss for Point {
     self.y.to_css(dest)
   }
 }
+
+/// Writes an optional `round <'border-radius'>` component of a rectangle shape.
+fn write_round<W>(radius: &BorderRadius, dest: &mut Printer<W>) -> Result<(), PrinterError>
+where
+  W: std::fmt::Write,
+{
+  if *radius != BorderRadius::default() {
+    dest.write_str(" round ")?;
+    radius.to_css(dest)?;
+  }
+  Ok(())
+}
+
+impl ToCss for XywhRect {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    self.x.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.y.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.width.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.height.to_css(dest)?;
+    write_round(&self.radius, dest)
+  }
+}
+
+impl ToCss for RectShape {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    // All four edges are required, so the shorthand Rect serialization cannot be used.
+    self.rect.0.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.rect.1.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.rect.2.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.rect.3.to_css(dest)?;
+    write_round(&self.radius, dest)
+  }
+}
+
+impl ToCss for Path {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.fill_rule != FillRule::default() {
+      self.fill_rule.to_css(dest)?;
+      dest.delim(',', false)?;
+    }
+
+    serialize_string(&self.data.to_svg(dest.minify), dest)?;
+    Ok(())
+  }
+}
+
+impl ToCss for Shape {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    if self.fill_rule != FillRule::default() {
+      self.fill_rule.to_css(dest)?;
+      dest.write_char(' ')?;
+    }
+
+    dest.write_str("from ")?;
+    self.start.to_css(dest)?;
+    for command in &self.commands {
+      dest.delim(',', false)?;
+      command.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+impl ToCss for ShapeCommand {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
+  where
+    W: std::fmt::Write,
+  {
+    macro_rules! write_command {
+      ($name: literal, $by_to: expr) => {{
+        dest.write_str(concat!($name, " "))?;
+        $by_to.to_css(dest)?;
+        dest.write_char(' ')?;
+      }};
+    }
+
+    match self {
+      ShapeCommand::Move { by_to, point } => {
+        write_command!("move", by_to);
+        point.to_css(dest)
+      }
+      ShapeCommand::Line { by_to, point } => {
+        write_command!("line", by_to);
+        point.to_css(dest)
+      }
+      ShapeCommand::HorizontalLine { by_to, x } => {
+        write_command!("hline", by_to);
+        x.to_css(dest)
+      }
+      ShapeCommand::VerticalLine { by_to, y } => {
+        write_command!("vline", by_to);
+        y.to_css(dest)
+      }
+      ShapeCommand::Curve {
+        by_to,
+        point,
+        control1,
+        control2,
+      } => {
+        write_command!("curve", by_to);
+        point.to_css(dest)?;
+        dest.write_str(" with ")?;
+        control1.to_css(dest)?;
+        if let Some(control2) = control2 {
+          dest.delim('/', true)?;
+          control2.to_css(dest)?;
+        }
+        Ok(())
+      }
+      ShapeCommand::Smooth { by_to, point, control } => {
+        write_command!("smooth", by_to);
+        point.to_css(dest)?;
+        if let Some(control) = control {
+          dest.write_str(" with ")?;
+          control.to_css(dest)?;
+        }
+        Ok(())
+      }
+      ShapeCommand::Arc {
+        by_to,
+        point,
+        radius_x,
+        radius_y,
+        sweep,
+        size,
+        rotate,
+      } => {
+        write_command!("arc", by_to);
+        point.to_css(dest)?;
+        dest.write_str(" of ")?;
+        radius_x.to_css(dest)?;
+        if radius_y != radius_x {
+          dest.write_char(' ')?;
+          radius_y.to_css(dest)?;
+        }
+        if *sweep != ArcSweep::default() {
+          dest.write_char(' ')?;
+          sweep.to_css(dest)?;
+        }
+        if *size != ArcSize::default() {
+          dest.write_char(' ')?;
+          size.to_css(dest)?;
+        }
+        if !rotate.is_zero() {
+          dest.write_str(" rotate ")?;
+          rotate.to_css(dest)?;
+        }
+        Ok(())
+      }
+      ShapeCommand::Close => dest.write_str("close"),
+    }
+  }
+}
+
+impl PathCommand {
+  /// Returns whether the coordinates of the command are relative to the current point.
+  pub fn is_relative(&self) -> bool {
+    use PathCommand::*;
+    match self {
+      Move { relative, .. }
+      | Line { relative, .. }
+      | HorizontalLine { relative, .. }
+      | VerticalLine { relative, .. }
+      | CubicCurve { relative, .. }
+      | SmoothCubicCurve { relative, .. }
+      | QuadraticCurve { relative, .. }
+      | SmoothQuadraticCurve { relative, .. }
+      | Arc { relative, .. }
+      | Close { relative } => *relative,
+    }
+  }
+
+  fn letter(&self) -> char {
+    use PathCommand::*;
+    let letter = match self {
+      Move { .. } => 'M',
+      Line { .. } => 'L',
+      HorizontalLine { .. } => 'H',
+      VerticalLine { .. } => 'V',
+      CubicCurve { .. } => 'C',
+      SmoothCubicCurve { .. } => 'S',
+      QuadraticCurve { .. } => 'Q',
+      SmoothQuadraticCurve { .. } => 'T',
+      Arc { .. } => 'A',
+      Close { .. } => 'Z',
+    };
+    if self.is_relative() {
+      letter.to_ascii_lowercase()
+    } else {
+      letter
+    }
+  }
+
+  fn arguments(&self) -> Vec<CSSNumber> {
+    use PathCommand::*;
+    let flag = |f: bool| if f { 1.0 } else { 0.0 };
+    match *self {
+      Move { x, y, .. } | Line { x, y, .. } | SmoothQuadraticCurve { x, y, .. } => vec![x, y],
+      HorizontalLine { x, .. } => vec![x],
+      VerticalLine { y, .. } => vec![y],
+      CubicCurve {
+        x1, y1, x2, y2, x, y, ..
+      } => vec![x1, y1, x2, y2, x, y],
+      SmoothCubicCurve { x2, y2, x, y, .. } => vec![x2, y2, x, y],
+      QuadraticCurve { x1, y1, x, y, .. } => vec![x1, y1, x, y],
+      Arc {
+        rx,
+        ry,
+        angle,
+        large_arc,
+        sweep,
+        x,
+        y,
+        ..
+      } => vec![rx, ry, angle, flag(large_arc), flag(sweep), x, y],
+      Close { .. } => vec![],
+    }
+  }
+
+  /// Returns the absolute end point of the command, given the current point and the start of the current subpath.
+  fn end_point(&self, current: (f32, f32), start: (f32, f32)) -> (f32, f32) {
+    use PathCommand::*;
+    let (cx, cy) = if self.is_relative() { current } else { (0.0, 0.0) };
+    match *self {
+      Move { x, y, .. }
+      | Line { x, y, .. }
+      | CubicCurve { x, y, .. }
+      | SmoothCubicCurve { x, y, .. }
+      | QuadraticCurve { x, y, .. }
+      | SmoothQuadraticCurve { x, y, .. }
+      | Arc { x, y, .. } => (cx + x, cy + y),
+      HorizontalLine { x, .. } => (cx + x, current.1),
+      VerticalLine { y, .. } => (current.0, cy + y),
+      Close { .. } => start,
+    }
+  }
+
+  /// Converts the command between absolute and relative coordinates, given the current point.
+  /// Returns `None` if the conversion would lose precision.
+  fn toggle_relative(&self, current: (f32, f32)) -> Option<PathCommand> {
+    use PathCommand::*;
+    let relative = !self.is_relative();
+    let sign = if relative { -1.0 } else { 1.0 };
+    let convert = |value: f32, origin: f32| {
+      let converted = value + sign * origin;
+      if converted - sign * origin == value {
+        Some(converted)
+      } else {
+        None
+      }
+    };
+    let x = |value: f32| convert(value, current.0);
+    let y = |value: f32| convert(value, current.1);
+
+    Some(match *self {
+      Move { x: px, y: py, .. } => Move {
+        relative,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      Line { x: px, y: py, .. } => Line {
+        relative,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      HorizontalLine { x: px, .. } => HorizontalLine { relative, x: x(px)? },
+      VerticalLine { y: py, .. } => VerticalLine { relative, y: y(py)? },
+      CubicCurve {
+        x1: px1,
+        y1: py1,
+        x2: px2,
+        y2: py2,
+        x: px,
+        y: py,
+        ..
+      } => CubicCurve {
+        relative,
+        x1: x(px1)?,
+        y1: y(py1)?,
+        x2: x(px2)?,
+        y2: y(py2)?,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      SmoothCubicCurve {
+        x2: px2,
+        y2: py2,
+        x: px,
+        y: py,
+        ..
+      } => SmoothCubicCurve {
+        relative,
+        x2: x(px2)?,
+        y2: y(py2)?,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      QuadraticCurve {
+        x1: px1,
+        y1: py1,
+        x: px,
+        y: py,
+        ..
+      } => QuadraticCurve {
+        relative,
+        x1: x(px1)?,
+        y1: y(py1)?,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      SmoothQuadraticCurve { x: px, y: py, .. } => SmoothQuadraticCurve {
+        relative,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      Arc {
+        rx,
+        ry,
+        angle,
+        large_arc,
+        sweep,
+        x: px,
+        y: py,
+        ..
+      } => Arc {
+        relative,
+        rx,
+        ry,
+        angle,
+        large_arc,
+        sweep,
+        x: x(px)?,
+        y: y(py)?,
+      },
+      Close { .. } => return None,
+    })
+  }
+}
+
+/// Tracks the state of the output while serializing path data.
+#[derive(Default)]
+struct PathWriter {
+  output: String,
+  /// The last command letter, including commands whose letter was omitted.
+  command: Option<char>,
+  /// Whether the last item written was a number, and whether that number contained a decimal point or exponent.
+  number: Option<bool>,
+}
+
+impl PathWriter {
+  fn write_command(&mut self, command: &PathCommand, minify: bool) {
+    let letter = command.letter();
+    let implicit = match self.command {
+      Some('M') => Some('L'),
+      Some('m') => Some('l'),
+      Some('Z' | 'z') | None => None,
+      command => command,
+    };
+
+    if !minify || implicit != Some(letter) {
+      if !minify && !self.output.is_empty() {
+        self.output.push(' ');
+      }
+      self.output.push(letter);
+      self.number = None;
+    }
+    self.command = Some(letter);
+
+    for argument in command.arguments() {
+      let mut number = String::new();
+      let _ = argument.to_css(&mut Printer::new(&mut number, Default::default()));
+      let needs_separator = match self.number {
+        _ if !minify => true,
+        Some(decimal) => !(number.starts_with('-') || (number.starts_with('.') && decimal)),
+        None => false,
+      };
+      if needs_separator {
+        self.output.push(' ');
+      }
+      self.number = Some(number.contains(|c: char| matches!(c, '.' | 'e' | 'E')));
+      self.output.push_str(&number);
+    }
+  }
+}
+
+impl PathData {
+  /// Parses SVG path data. Returns `None` if the path data is invalid.
+  pub fn from_svg(data: &str) -> Option<PathData> {
+    let mut parser = PathDataParser {
+      data: data.as_bytes(),
+      pos: 0,
+    };
+    let mut commands = Vec::new();
+    let mut previous: Option<u8> = None;
+    loop {
+      parser.skip_whitespace();
+      let letter = match parser.peek() {
+        None => break,
+        Some(c) if c.is_ascii_alphabetic() => {
+          parser.pos += 1;
+          parser.skip_whitespace();
+          c
+        }
+        // Repeated arguments imply another command of the same type, or a line after a move.
+        Some(_) => match previous? {
+          b'M' => b'L',
+          b'm' => b'l',
+          b'Z' | b'z' => return None,
+          c => c,
+        },
+      };
+
+      if commands.is_empty() && !matches!(letter, b'M' | b'm') {
+        return None;
+      }
+
+      let relative = letter.is_ascii_lowercase();
+      let command = match letter.to_ascii_uppercase() {
+        b'M' => PathCommand::Move {
+          relative,
+          x: parser.number()?,
+          y: parser.next_number()?,
+        },
+        b'L' => PathCommand::Line {
+          relative,
+          x: parser.number()?,
+          y: parser.next_number()?,
+        },
+        b'H' => PathCommand::HorizontalLine {
+          relative,
+          x: parser.number()?,
+        },
+        b'V' => PathCommand::VerticalLine {
+          relative,
+          y: parser.number()?,
+        },
+        b'C' => PathCommand::CubicCurve {
+          relative,
+          x1: parser.number()?,
+          y1: parser.next_number()?,
+          x2: parser.next_number()?,
+          y2: parser.next_number()?,
+          x: parser.next_number()?,
+          y: parser.next_number()?,
+        },
+        b'S' => PathCommand::SmoothCubicCurve {
+          relative,
+          x2: parser.number()?,
+          y2: parser.next_number()?,
+          x: parser.next_number()?,
+          y: parser.next_number()?,
+        },
+        b'Q' => PathCommand::QuadraticCurve {
+          relative,
+          x1: parser.number()?,
+          y1: parser.next_number()?,
+          x: parser.next_number()?,
+          y: parser.next_number()?,
+        },
+        b'T' => PathCommand::SmoothQuadraticCurve {
+          relative,
+          x: parser.number()?,
+          y: parser.next_number()?,
+        },
+        b'A' => PathCommand::Arc {
+          relative,
+          rx: parser.number()?,
+          ry: parser.next_number()?,
+          angle: parser.next_number()?,
+          large_arc: parser.next_flag()?,
+          sweep: parser.next_flag()?,
+          x: parser.next_number()?,
+          y: parser.next_number()?,
+        },
+        b'Z' => PathCommand::Close { relative },
+        _ => return None,
+      };
+
+      commands.push(command);
+      previous = Some(letter);
+
+      // A comma may separate repeated arguments.
+      parser.skip_whitespace();
+      if parser.peek() == Some(b',') {
+        parser.pos += 1;
+        parser.skip_whitespace();
+        if matches!(letter, b'Z' | b'z') || !matches!(parser.peek(), Some(c) if is_number_start(c)) {
+          return None;
+        }
+      }
+    }
+
+    Some(PathData(commands))
+  }
+
+  /// Serializes the path data as an SVG path data string.
+  ///
+  /// When minifying, unnecessary separators and repeated command letters are removed,
+  /// and each command uses absolute or relative coordinates, whichever is shorter.
+  pub fn to_svg(&self, minify: bool) -> String {
+    let mut writer = PathWriter::default();
+    let mut current = (0.0, 0.0);
+    let mut start = (0.0, 0.0);
+    for command in &self.0 {
+      let alternative = if minify { command.toggle_relative(current) } else { None };
+
+      match alternative {
+        Some(alternative) => {
+          let mut a = PathWriter {
+            output: String::new(),
+            command: writer.command,
+            number: writer.number,
+          };
+          let mut b = PathWriter {
+            output: String::new(),
+            command: writer.command,
+            number: writer.number,
+          };
+          a.write_command(command, minify);
+          b.write_command(&alternative, minify);
+          let best = if b.output.len() < a.output.len() { b } else { a };
+          writer.output.push_str(&best.output);
+          writer.command = best.command;
+          writer.number = best.number;
+        }
+        None => writer.write_command(command, minify),
+      }
+
+      current = command.end_point(current, start);
+      if matches!(command, PathCommand::Move { .. }) {
+        start = current;
+      }
+    }
+    writer.output
+  }
+}
+
+fn is_number_start(c: u8) -> bool {
+  c.is_ascii_digit() || matches!(c, b'+' | b'-' | b'.')
+}
+
+struct PathDataParser<'a> {
+  data: &'a [u8],
+  pos: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+  fn peek(&self) -> Option<u8> {
+    self.data.get(self.pos).copied()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r' | b'\x0C')) {
+      self.pos += 1;
+    }
+  }
+
+  fn skip_digits(&mut self) -> usize {
+    let start = self.pos;
+    while matches!(self.peek(), Some(c) if c.is_ascii_digit()) {
+      self.pos += 1;
+    }
+    self.pos - start
+  }
+
+  fn number(&mut self) -> Option<f32> {
+    let start = self.pos;
+    if matches!(self.peek(), Some(b'+' | b'-')) {
+      self.pos += 1;
+    }
+
+    let mut digits = self.skip_digits();
+    if self.peek() == Some(b'.') {
+      self.pos += 1;
+      digits += self.skip_digits();
+    }
+
+    if digits == 0 {
+      return None;
+    }
+
+    if matches!(self.peek(), Some(b'e' | b'E')) {
+      let mantissa = self.pos;
+      self.pos += 1;
+      if matches!(self.peek(), Some(b'+' | b'-')) {
+        self.pos += 1;
+      }
+      if self.skip_digits() == 0 {
+        self.pos = mantissa;
+      }
+    }
+
+    std::str::from_utf8(&self.data[start..self.pos]).ok()?.parse().ok()
+  }
+
+  fn skip_separator(&mut self) {
+    self.skip_whitespace();
+    if self.peek() == Some(b',') {
+      self.pos += 1;
+      self.skip_whitespace();
+    }
+  }
+
+  fn next_number(&mut self) -> Option<f32> {
+    self.skip_separator();
+    self.number()
+  }
+
+  fn next_flag(&mut self) -> Option<bool> {
+    self.skip_separator();
+    let flag = match self.peek()? {
+      b'0' => false,
+      b'1' => true,
+      _ => return None,
+    };
+    self.pos += 1;
+    Some(flag)
+  }
+}
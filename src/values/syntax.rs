@@ -4,7 +4,8 @@ use super::ident::Ident;
 use super::number::{CSSInteger, CSSNumber};
 use crate::error::{ParserError, PrinterError};
 use crate::printer::Printer;
-use crate::traits::{Parse, ToCss};
+use crate::properties::custom::{TokenList, TokenOrValue};
+use crate::traits::{Parse, ToCss, Zero};
 use crate::values;
 #[cfg(feature = "visitor")]
 use crate::visitor::Visit;
@@ -249,7 +250,9 @@ impl<'i> SyntaxString {
 
             if let Ok(value) = value {
               match component.multiplier {
-                Multiplier::None => return Ok(value),
+                // The value must span the whole input, otherwise try the next component.
+                Multiplier::None if input.is_exhausted() => return Ok(value),
+                Multiplier::None => break,
                 Multiplier::Space => {
                   parsed.push(value);
                   if input.is_exhausted() {
@@ -452,6 +455,91 @@ impl ToCss for SyntaxComponentKind {
   }
 }
 
+impl<'i> ParsedComponent<'i> {
+  /// Converts the value to a token list, so that it is serialized and visited in the same way as
+  /// the value of an unregistered custom property. Returns `None` for values that cannot be
+  /// represented as tokens, e.g. images, transforms, and unresolved `calc()` expressions.
+  pub(crate) fn to_token_list(&self) -> Option<TokenList<'i>> {
+    let mut tokens = Vec::new();
+    self.add_tokens(&mut tokens)?;
+    Some(TokenList(tokens))
+  }
+
+  fn add_tokens(&self, tokens: &mut Vec<TokenOrValue<'i>>) -> Option<()> {
+    use crate::properties::custom::Token;
+    use values::length::{Length, LengthPercentage};
+
+    let int_value = |value: f32| {
+      if value as i32 as f32 == value {
+        Some(value as i32)
+      } else {
+        None
+      }
+    };
+    let token = match self {
+      ParsedComponent::Length(Length::Value(v))
+      | ParsedComponent::LengthPercentage(LengthPercentage::Dimension(v)) => {
+        // Units are kept for lengths in token lists, but zero lengths match the syntax without them.
+        if v.is_zero() {
+          TokenOrValue::Token(Token::Number {
+            has_sign: false,
+            value: 0.0,
+            int_value: Some(0),
+          })
+        } else {
+          TokenOrValue::Length(v.clone())
+        }
+      }
+      ParsedComponent::Percentage(values::percentage::Percentage(v))
+      | ParsedComponent::LengthPercentage(LengthPercentage::Percentage(values::percentage::Percentage(v))) => {
+        TokenOrValue::Token(Token::Percentage {
+          has_sign: false,
+          unit_value: *v,
+          int_value: int_value(v * 100.0),
+        })
+      }
+      ParsedComponent::Number(v) => TokenOrValue::Token(Token::Number {
+        has_sign: false,
+        value: *v,
+        int_value: int_value(*v),
+      }),
+      ParsedComponent::Integer(v) => TokenOrValue::Token(Token::Number {
+        has_sign: false,
+        value: *v as f32,
+        int_value: Some(*v),
+      }),
+      ParsedComponent::Color(v) => TokenOrValue::Color(v.clone()),
+      ParsedComponent::Url(v) => TokenOrValue::Url(v.clone()),
+      ParsedComponent::Angle(v) => TokenOrValue::Angle(v.clone()),
+      ParsedComponent::Time(v) => TokenOrValue::Time(v.clone()),
+      ParsedComponent::Resolution(v) => TokenOrValue::Resolution(v.clone()),
+      ParsedComponent::CustomIdent(values::ident::CustomIdent(v)) | ParsedComponent::Literal(Ident(v)) => {
+        TokenOrValue::Token(Token::Ident(v.clone()))
+      }
+      ParsedComponent::Token(t) => TokenOrValue::Token(t.clone()),
+      ParsedComponent::Repeated { components, multiplier } => {
+        for (i, component) in components.iter().enumerate() {
+          if i > 0 {
+            tokens.push(TokenOrValue::Token(match multiplier {
+              Multiplier::Comma => Token::Comma,
+              Multiplier::Space | Multiplier::None => Token::WhiteSpace(" ".into()),
+            }));
+          }
+          component.add_tokens(tokens)?;
+        }
+        return Some(());
+      }
+      ParsedComponent::Length(Length::Calc(..))
+      | ParsedComponent::LengthPercentage(LengthPercentage::Calc(..))
+      | ParsedComponent::Image(..)
+      | ParsedComponent::TransformFunction(..)
+      | ParsedComponent::TransformList(..) => return None,
+    };
+    tokens.push(token);
+    Some(())
+  }
+}
+
 impl<'i> ToCss for ParsedComponent<'i> {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
@@ -634,6 +722,20 @@ mod tests {
 
     error_test("<length> | <percentage>", "calc(100% - 25px)");
 
+    error_test("<length>", "25px 10px");
+
+    test(
+      "<length> | <length>+",
+      "25px 10px",
+      ParsedComponent::Repeated {
+        components: vec![
+          ParsedComponent::Length(values::length::Length::Value(values::length::LengthValue::Px(25.0))),
+          ParsedComponent::Length(values::length::Length::Value(values::length::LengthValue::Px(10.0))),
+        ],
+        multiplier: Multiplier::Space,
+      },
+    );
+
     test("foo | bar | baz", "bar", ParsedComponent::Literal("bar".into()));
 
     test(
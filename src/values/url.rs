@@ -1,13 +1,18 @@
 //! CSS url() values.
 
-use crate::dependencies::{Dependency, Location, UrlDependency};
+use crate::dependencies::{Dependency, DependencyKind, DependencyReference, Location, UrlDependency};
 use crate::error::{ParserError, PrinterError};
-use crate::printer::Printer;
+use crate::printer::{normalize_path, Printer};
+#[cfg(feature = "visitor")]
+use crate::rules::CssRule;
 use crate::traits::{Parse, ToCss};
 use crate::values::string::CowArcStr;
 #[cfg(feature = "visitor")]
-use crate::visitor::Visit;
+use crate::visitor::{Visit, VisitTypes, Visitor};
 use cssparser::*;
+#[cfg(feature = "visitor")]
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
 
 /// A CSS [url()](https://www.w3.org/TR/css-values-4/#urls) value and its source location.
 #[derive(Debug, Clone)]
@@ -43,6 +48,16 @@ impl<'i> ToCss for Url<'i> {
   where
     W: std::fmt::Write,
   {
+    if dest.dependency_references.is_some() {
+      let kind = if dest.in_image_set {
+        DependencyKind::ImageSet
+      } else {
+        DependencyKind::Url
+      };
+      let reference = DependencyReference::url(self, kind, dest.filename());
+      dest.dependency_references.as_mut().unwrap().push(reference);
+    }
+
     let dep = if dest.dependencies.is_some() {
       Some(UrlDependency::new(self, dest.filename()))
     } else {
@@ -63,16 +78,22 @@ impl<'i> ToCss for Url<'i> {
       return Ok(());
     }
 
+    let replaced = self
+      .inline_asset(dest)
+      .or_else(|| dest.emit_asset(self))
+      .or_else(|| dest.rebase_url(self));
+    let url = replaced.as_deref().unwrap_or(self.url.as_ref());
+
     use cssparser::ToCss;
     if dest.minify {
       let mut buf = String::new();
-      Token::UnquotedUrl(CowRcStr::from(self.url.as_ref())).to_css(&mut buf)?;
+      Token::UnquotedUrl(CowRcStr::from(url)).to_css(&mut buf)?;
 
       // If the unquoted url is longer than it would be quoted (e.g. `url("...")`)
       // then serialize as a string and choose the shorter version.
-      if buf.len() > self.url.len() + 7 {
+      if buf.len() > url.len() + 7 {
         let mut buf2 = String::new();
-        serialize_string(&self.url, &mut buf2)?;
+        serialize_string(url, &mut buf2)?;
         if buf2.len() + 5 < buf.len() {
           dest.write_str("url(")?;
           dest.write_str(&buf2)?;
@@ -83,7 +104,7 @@ impl<'i> ToCss for Url<'i> {
       dest.write_str(&buf)?;
     } else {
       dest.write_str("url(")?;
-      serialize_string(&self.url, dest)?;
+      serialize_string(url, dest)?;
       dest.write_char(')')?;
     }
 
@@ -92,6 +113,38 @@ impl<'i> ToCss for Url<'i> {
 }
 
 impl<'i> Url<'i> {
+  /// Returns a `data:` URI to print in place of a relative url when inlining assets.
+  fn inline_asset<W: std::fmt::Write>(&self, dest: &Printer<W>) -> Option<String> {
+    let options = dest.inline_assets.as_ref()?;
+    let (path, suffix) = dest.resolve_url(self)?;
+    if !suffix.is_empty() {
+      return None;
+    }
+
+    let filename = path.to_str()?.replace('\\', "/");
+    if (!options.include.is_empty() && !options.include.iter().any(|p| matches_pattern(p, &filename)))
+      || options.exclude.iter().any(|p| matches_pattern(p, &filename))
+    {
+      return None;
+    }
+
+    if std::fs::metadata(&path).ok()?.len() > options.max_size.unwrap_or(4096) {
+      return None;
+    }
+
+    let contents = std::fs::read(&path).ok()?;
+    let mime_type = mime_type(&path, &contents)?;
+    if mime_type == "image/svg+xml" {
+      Some(format!("data:{},{}", mime_type, percent_encode(&contents)))
+    } else {
+      Some(format!(
+        "data:{};base64,{}",
+        mime_type,
+        data_encoding::BASE64.encode(&contents)
+      ))
+    }
+  }
+
   /// Returns whether the URL is absolute, and not relative.
   pub fn is_absolute(&self) -> bool {
     let url = self.url.as_ref();
@@ -131,4 +184,162 @@ impl<'i> Url<'i> {
 
     false
   }
+  /// Resolves a relative URL against the directory of the given source file, returning the
+  /// resolved path along with its query string and fragment, if any.
+  pub(crate) fn resolve<'u>(&'u self, source_file: &Path) -> Option<(PathBuf, &'u str)> {
+    if self.url.is_empty() || self.is_absolute() {
+      return None;
+    }
+
+    let end = self.url.find(|c| c == '?' || c == '#').unwrap_or(self.url.len());
+    let (path, suffix) = self.url.split_at(end);
+    let source_dir = source_file.parent()?;
+    Some((normalize_path(&source_dir.join(path)), suffix))
+  }
+}
+
+/// Returns the MIME type of a file based on its extension, falling back to its contents.
+fn mime_type(path: &Path, contents: &[u8]) -> Option<&'static str> {
+  let extension = path.extension().and_then(|e| e.to_str()).map(|e| e.to_ascii_lowercase());
+  let mime_type = match extension.as_deref() {
+    Some("svg") => "image/svg+xml",
+    Some("png") => "image/png",
+    Some("apng") => "image/apng",
+    Some("jpg" | "jpeg") => "image/jpeg",
+    Some("gif") => "image/gif",
+    Some("webp") => "image/webp",
+    Some("avif") => "image/avif",
+    Some("bmp") => "image/bmp",
+    Some("ico" | "cur") => "image/x-icon",
+    Some("woff") => "font/woff",
+    Some("woff2") => "font/woff2",
+    Some("ttf") => "font/ttf",
+    Some("otf") => "font/otf",
+    Some("eot") => "application/vnd.ms-fontobject",
+    _ => match contents {
+      [0x89, b'P', b'N', b'G', ..] => "image/png",
+      [0xff, 0xd8, 0xff, ..] => "image/jpeg",
+      [b'G', b'I', b'F', b'8', ..] => "image/gif",
+      [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "image/webp",
+      [b'w', b'O', b'F', b'F', ..] => "font/woff",
+      [b'w', b'O', b'F', b'2', ..] => "font/woff2",
+      _ => {
+        let text = std::str::from_utf8(contents).ok()?.trim_start();
+        if text.starts_with("<svg") || (text.starts_with("<?xml") && text.contains("<svg")) {
+          "image/svg+xml"
+        } else {
+          return None;
+        }
+      }
+    },
+  };
+
+  Some(mime_type)
+}
+
+/// Percent-encodes the characters of a text file that are not safe within a `data:` URI.
+fn percent_encode(contents: &[u8]) -> String {
+  let mut res = String::with_capacity(contents.len());
+  for &b in contents {
+    match b {
+      b'"'
+      | b'%'
+      | b'#'
+      | b'<'
+      | b'>'
+      | b'?'
+      | b'['
+      | b'\\'
+      | b']'
+      | b'^'
+      | b'`'
+      | b'{'
+      | b'|'
+      | b'}'
+      | 0..=0x1f
+      | 0x7f.. => res.push_str(&format!("%{:02X}", b)),
+      _ => res.push(b as char),
+    }
+  }
+  res
+}
+
+/// Returns whether a path matches a pattern, where `*` matches any sequence of characters.
+pub(crate) fn matches_pattern(pattern: &str, path: &str) -> bool {
+  let mut parts = pattern.split('*');
+  let first = parts.next().unwrap_or_default();
+  let Some(mut rest) = path.strip_prefix(first) else {
+    return false;
+  };
+
+  let mut parts: Vec<&str> = parts.collect();
+  let Some(last) = parts.pop() else {
+    return rest.is_empty();
+  };
+
+  for part in parts {
+    match rest.find(part) {
+      Some(index) => rest = &rest[index + part.len()..],
+      None => return false,
+    }
+  }
+
+  rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// A [Visitor](crate::visitor::Visitor) that rewrites every url in a style sheet using a callback.
+///
+/// The callback receives the unescaped url and returns its replacement, which is escaped and quoted
+/// as needed when printing. This includes urls within properties such as `background` and `cursor`,
+/// `image-set()`, `@font-face` sources, unparsed and custom property values, and `@import` rules.
+///
+/// ```
+/// use lightningcss::{
+///   stylesheet::{ParserOptions, PrinterOptions, StyleSheet},
+///   values::url::UrlRewriter,
+///   visitor::Visit,
+/// };
+///
+/// let mut stylesheet =
+///   StyleSheet::parse(".foo { background: url(foo.png) }", ParserOptions::default()).unwrap();
+/// let mut rewriter = UrlRewriter::new(|url| format!("https://cdn.example.com/{}", url));
+/// stylesheet.visit(&mut rewriter).unwrap();
+///
+/// let res = stylesheet.to_css(PrinterOptions { minify: true, ..Default::default() }).unwrap();
+/// assert_eq!(res.code, ".foo{background:url(https://cdn.example.com/foo.png)}");
+/// ```
+#[cfg(feature = "visitor")]
+#[cfg_attr(docsrs, doc(cfg(feature = "visitor")))]
+pub struct UrlRewriter<F> {
+  rewrite: F,
+}
+
+#[cfg(feature = "visitor")]
+impl<F: FnMut(&str) -> String> UrlRewriter<F> {
+  /// Creates a url rewriter from a callback.
+  pub fn new(rewrite: F) -> UrlRewriter<F> {
+    UrlRewriter { rewrite }
+  }
+}
+
+#[cfg(feature = "visitor")]
+impl<'i, F: FnMut(&str) -> String> Visitor<'i> for UrlRewriter<F> {
+  type Error = Infallible;
+
+  fn visit_types(&self) -> VisitTypes {
+    VisitTypes::URLS | VisitTypes::RULES
+  }
+
+  fn visit_url(&mut self, url: &mut Url<'i>) -> Result<(), Self::Error> {
+    url.url = (self.rewrite)(url.url.as_ref()).into();
+    Ok(())
+  }
+
+  fn visit_rule(&mut self, rule: &mut CssRule<'i>) -> Result<(), Self::Error> {
+    // The url of an @import rule is a string rather than a Url.
+    if let CssRule::Import(import) = rule {
+      import.url = (self.rewrite)(import.url.as_ref()).into();
+    }
+    rule.visit_children(self)
+  }
 }
@@ -4,6 +4,7 @@
 
 use crate::error::PrinterError;
 use crate::printer::Printer;
+use crate::targets::{Browsers, Features, Targets};
 use crate::traits::ToCss;
 #[cfg(feature = "visitor")]
 use crate::visitor::{Visit, VisitTypes, Visitor};
@@ -68,6 +69,108 @@ impl VendorPrefix {
   }
 }
 
+/// A user-supplied vendor prefixing rule.
+///
+/// Prefix rules extend the built-in prefix data, e.g. to cover niche properties
+/// or proprietary WebView engines that are not tracked by browser compatibility data.
+/// Rules are passed to the minifier via [MinifyOptions](crate::stylesheet::MinifyOptions).
+///
+/// # Example
+///
+/// This example adds a `-webkit-` prefix to `touch-callout: none` for Safari versions below 15.
+///
+/// ```
+/// use lightningcss::targets::Browsers;
+/// use lightningcss::vendor_prefix::{PrefixRule, VendorPrefix};
+///
+/// let rule = PrefixRule {
+///   property: "touch-callout".into(),
+///   values: vec!["none".into()],
+///   browsers: Some(Browsers {
+///     safari: Some(15 << 16),
+///     ..Browsers::default()
+///   }),
+///   prefixes: VendorPrefix::WebKit,
+/// };
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PrefixRule {
+  /// The unprefixed name of the property the rule applies to.
+  pub property: String,
+  /// Values of the property that require a prefix. A trailing `*` matches any value
+  /// starting with the given text. If empty, all values match.
+  pub values: Vec<String>,
+  /// The first version of each browser that supports the property without a prefix.
+  /// Prefixes are added when a target browser is older than the version listed here.
+  /// If `None`, prefixes are always added.
+  pub browsers: Option<Browsers>,
+  /// The vendor prefixes to add.
+  pub prefixes: VendorPrefix,
+}
+
+impl PrefixRule {
+  /// Returns whether the rule requires prefixes for the given targets.
+  pub(crate) fn applies_to(&self, targets: &Targets) -> bool {
+    if targets.exclude.contains(Features::VendorPrefixes) {
+      return false;
+    }
+
+    if targets.include.contains(Features::VendorPrefixes) {
+      return true;
+    }
+
+    let required = match &self.browsers {
+      Some(required) => required,
+      None => return true,
+    };
+
+    let targets = match &targets.browsers {
+      Some(targets) => targets,
+      None => return false,
+    };
+
+    macro_rules! check {
+      ($($browser: ident),+) => {
+        $(
+          if let (Some(target), Some(version)) = (targets.$browser, required.$browser) {
+            if target < version {
+              return true;
+            }
+          }
+        )+
+      };
+    }
+
+    check!(android, chrome, edge, firefox, ie, ios_saf, opera, safari, samsung);
+    false
+  }
+
+  /// Returns whether the rule matches the given serialized property value.
+  pub(crate) fn matches_value(&self, value: &str) -> bool {
+    self.values.is_empty()
+      || self.values.iter().any(|pattern| match pattern.strip_suffix('*') {
+        Some(start) => value.get(..start.len()).map_or(false, |v| v.eq_ignore_ascii_case(start)),
+        None => value.eq_ignore_ascii_case(pattern),
+      })
+  }
+}
+
+/// A policy for adding vendor prefixes to a property, overriding the browser compatibility data.
+///
+/// Policies are passed to the minifier via [MinifyOptions](crate::stylesheet::MinifyOptions),
+/// keyed by the lowercase, unprefixed property name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PrefixPolicy {
+  /// Add vendor prefixes based on the browser targets.
+  #[default]
+  Auto,
+  /// Always add all vendor prefixes supported by the property, regardless of the browser targets.
+  /// For unknown properties, the prefixes from any matching [PrefixRule](PrefixRule) are added.
+  Always,
+  /// Never add vendor prefixes. Prefixed declarations are removed when the unprefixed property is also declared.
+  Never,
+}
+
 impl ToCss for VendorPrefix {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> Result<(), PrinterError>
   where
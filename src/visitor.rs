@@ -63,11 +63,17 @@ use crate::{
   media_query::{MediaFeature, MediaFeatureValue, MediaList, MediaQuery},
   parser::DefaultAtRule,
   properties::{
-    custom::{EnvironmentVariable, Function, TokenList, TokenOrValue, Variable},
+    custom::{EnvironmentVariable, Function, Token, TokenList, TokenOrValue, Variable},
     Property,
   },
-  rules::{supports::SupportsCondition, CssRule, CssRuleList},
-  selector::{Selector, SelectorList},
+  rules::{
+    container::{ContainerCondition, ContainerName},
+    layer::LayerName,
+    supports::SupportsCondition,
+    unknown::UnknownAtRule,
+    CssRule, CssRuleList,
+  },
+  selector::{Component, Selector, SelectorList},
   values::{
     angle::Angle,
     color::CssColor,
@@ -130,6 +136,10 @@ bitflags! {
     const FUNCTIONS = 1 << 17;
     /// Visit a token.
     const TOKENS = 1 << 18;
+    /// Visit raw tokens in unparsed values, along with their spans.
+    const RAW_TOKENS = 1 << 19;
+    /// Visit the components of selectors, including selectors nested within pseudo classes.
+    const SELECTOR_COMPONENTS = 1 << 20;
   }
 }
 
@@ -150,6 +160,18 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule> {
   /// `Self::TYPES`, but this can be overridden to change the value at runtime.
   fn visit_types(&self) -> VisitTypes;
 
+  /// Returns a stack in which to track the rules enclosing the values being visited. By default, it
+  /// returns `None`, and no context is tracked.
+  ///
+  /// When a stack is returned, a [RuleContext](RuleContext) is pushed before visiting each conditional
+  /// or style rule, and popped after it has been visited. This includes the rule currently being visited,
+  /// so e.g. while visiting the properties of a style rule nested in a `@media` rule, the stack contains
+  /// the media query followed by the selectors of the style rule.
+  #[inline]
+  fn context(&mut self) -> Option<&mut Vec<RuleContext<'i>>> {
+    None
+  }
+
   /// Visits a rule list.
   #[inline]
   fn visit_rule_list(&mut self, rules: &mut CssRuleList<'i, T>) -> Result<(), Self::Error> {
@@ -162,6 +184,53 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule> {
     rule.visit_children(self)
   }
 
+  /// Visits a rule within a rule list, and optionally replaces it. Returning `Some` replaces the rule
+  /// with the returned rules, which may be empty to remove it, or contain multiple rules to split it.
+  /// Returning `None` keeps the rule, including any changes made to it in place.
+  ///
+  /// The returned rules are not visited. By default, unknown and custom at-rules are passed to
+  /// [replace_unknown_at_rule](Visitor::replace_unknown_at_rule) and
+  /// [replace_custom_at_rule](Visitor::replace_custom_at_rule). Other rules, and at-rules that were not
+  /// replaced, are passed to [visit_rule](Visitor::visit_rule) and kept.
+  #[inline]
+  fn replace_rule(&mut self, rule: &mut CssRule<'i, T>) -> Result<Option<Vec<CssRule<'i, T>>>, Self::Error> {
+    let replacement = match rule {
+      CssRule::Unknown(unknown) => self.replace_unknown_at_rule(unknown)?,
+      CssRule::Custom(custom) => self.replace_custom_at_rule(custom)?,
+      _ => None,
+    };
+
+    if replacement.is_none() {
+      rule.visit(self)?;
+    }
+    Ok(replacement)
+  }
+
+  /// Visits an unknown at-rule within a rule list, and optionally replaces it, e.g. to expand an at-rule
+  /// defined by a plugin. The prelude and block of the rule are available as tokens. Returning `Some`
+  /// replaces the rule with the returned rules, and returning `None` visits it as usual.
+  ///
+  /// This is only called by the default implementation of [replace_rule](Visitor::replace_rule).
+  #[allow(unused_variables)]
+  fn replace_unknown_at_rule(
+    &mut self,
+    rule: &mut UnknownAtRule<'i>,
+  ) -> Result<Option<Vec<CssRule<'i, T>>>, Self::Error> {
+    Ok(None)
+  }
+
+  /// Visits a custom at-rule within a rule list, and optionally replaces it. Custom at-rules are
+  /// produced by the [AtRuleParser](crate::traits::AtRuleParser) passed to
+  /// [StyleSheet::parse_with](crate::stylesheet::StyleSheet::parse_with), so their prelude and block
+  /// are available as typed values. Returning `Some` replaces the rule with the
+  /// returned rules, and returning `None` visits it as usual.
+  ///
+  /// This is only called by the default implementation of [replace_rule](Visitor::replace_rule).
+  #[allow(unused_variables)]
+  fn replace_custom_at_rule(&mut self, rule: &mut T) -> Result<Option<Vec<CssRule<'i, T>>>, Self::Error> {
+    Ok(None)
+  }
+
   /// Visits a declaration block.
   #[inline]
   fn visit_declaration_block(&mut self, decls: &mut DeclarationBlock<'i>) -> Result<(), Self::Error> {
@@ -174,6 +243,19 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule> {
     property.visit_children(self)
   }
 
+  /// Visits a property within a declaration block, and optionally replaces it. Returning `Some` replaces
+  /// the property with the returned properties, which may be empty to remove it. The replacements keep
+  /// the `!important` flag of the original declaration. Returning `None` keeps the property, including
+  /// any changes made to it in place.
+  ///
+  /// The returned properties are not visited. By default, this calls [visit_property](Visitor::visit_property)
+  /// and keeps the property.
+  #[inline]
+  fn replace_property(&mut self, property: &mut Property<'i>) -> Result<Option<Vec<Property<'i>>>, Self::Error> {
+    property.visit(self)?;
+    Ok(None)
+  }
+
   /// Visits a url.
   fn visit_url(&mut self, _url: &mut Url<'i>) -> Result<(), Self::Error> {
     Ok(())
@@ -282,9 +364,19 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule> {
   }
 
   /// Visits a selector.
-  #[allow(unused_variables)]
+  #[inline]
   fn visit_selector(&mut self, selector: &mut Selector<'i>) -> Result<(), Self::Error> {
-    Ok(())
+    selector.visit_children(self)
+  }
+
+  /// Visits a component of a selector, such as a class, id, pseudo class, or combinator.
+  ///
+  /// Components are visited in matching order rather than source order, and the specificity of
+  /// the selector is updated afterward. Components may be modified, but combinators and pseudo
+  /// elements should not be added or removed, since they determine the structure of the selector.
+  #[inline]
+  fn visit_selector_component(&mut self, component: &mut Component<'i>) -> Result<(), Self::Error> {
+    component.visit_children(self)
   }
 
   /// Visits a custom function.
@@ -304,6 +396,126 @@ pub trait Visitor<'i, T: Visit<'i, T, Self> = DefaultAtRule> {
   fn visit_token(&mut self, token: &mut TokenOrValue<'i>) -> Result<(), Self::Error> {
     token.visit_children(self)
   }
+
+  /// Visits a raw token within a token list, such as a custom property value or an unparsed
+  /// declaration. The span describes the position of the token within its token list.
+  ///
+  /// Raw tokens are visited before the typed values in the same token list. Tokens nested
+  /// within functions are visited with the function's arguments.
+  #[inline]
+  fn visit_raw_token(&mut self, _token: &mut Token<'i>, _span: TokenSpan) -> Result<(), Self::Error> {
+    Ok(())
+  }
+}
+
+/// A rule enclosing the values being visited, as tracked in the stack returned by
+/// [Visitor::context](Visitor::context).
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleContext<'i> {
+  /// A style rule, or an `@nest` rule, with its selectors.
+  Style(SelectorList<'i>),
+  /// A `@media` rule.
+  Media(MediaList<'i>),
+  /// A `@supports` rule.
+  Supports(SupportsCondition<'i>),
+  /// A `@container` rule.
+  Container(Option<ContainerName<'i>>, ContainerCondition<'i>),
+  /// A `@layer` block rule, which may be anonymous.
+  Layer(Option<LayerName<'i>>),
+  /// A `@scope` rule.
+  Scope {
+    /// The scope root.
+    scope_start: Option<SelectorList<'i>>,
+    /// The scope limit.
+    scope_end: Option<SelectorList<'i>>,
+  },
+  /// A `@starting-style` rule.
+  StartingStyle,
+  /// A `@-moz-document` rule.
+  MozDocument,
+}
+
+impl<'i> RuleContext<'i> {
+  fn from_rule<T>(rule: &CssRule<'i, T>) -> Option<RuleContext<'i>> {
+    Some(match rule {
+      CssRule::Style(style) => RuleContext::Style(style.selectors.clone()),
+      CssRule::Nesting(nesting) => RuleContext::Style(nesting.style.selectors.clone()),
+      CssRule::Media(media) => RuleContext::Media(media.query.clone()),
+      CssRule::Supports(supports) => RuleContext::Supports(supports.condition.clone()),
+      CssRule::Container(container) => RuleContext::Container(container.name.clone(), container.condition.clone()),
+      CssRule::LayerBlock(layer) => RuleContext::Layer(layer.name.clone()),
+      CssRule::Scope(scope) => RuleContext::Scope {
+        scope_start: scope.scope_start.clone(),
+        scope_end: scope.scope_end.clone(),
+      },
+      CssRule::StartingStyle(..) => RuleContext::StartingStyle,
+      CssRule::MozDocument(..) => RuleContext::MozDocument,
+      _ => return None,
+    })
+  }
+}
+
+/// Calls `f` to visit a rule, with the rule pushed onto the visitor's context stack if it has one.
+pub(crate) fn visit_in_context<'i, T, V, R>(
+  visitor: &mut V,
+  rule: &mut CssRule<'i, T>,
+  f: impl FnOnce(&mut V, &mut CssRule<'i, T>) -> Result<R, V::Error>,
+) -> Result<R, V::Error>
+where
+  T: Visit<'i, T, V>,
+  V: ?Sized + Visitor<'i, T>,
+{
+  let pushed = match visitor.context() {
+    Some(stack) => match RuleContext::from_rule(rule) {
+      Some(context) => {
+        stack.push(context);
+        true
+      }
+      None => false,
+    },
+    None => false,
+  };
+
+  let res = f(visitor, rule);
+  if pushed {
+    if let Some(stack) = visitor.context() {
+      stack.pop();
+    }
+  }
+  res
+}
+
+/// The position of a raw token within its token list, as passed to
+/// [visit_raw_token](Visitor::visit_raw_token).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenSpan {
+  /// The index of the token within the token list.
+  pub index: usize,
+  /// The byte offset of the start of the token within the minified serialization of the token list.
+  pub start: usize,
+  /// The byte offset of the end of the token within the minified serialization of the token list.
+  pub end: usize,
+}
+
+/// A [Visitor](Visitor) that can visit the top-level rules of a style sheet in parallel,
+/// using [StyleSheet::visit_parallel](crate::stylesheet::StyleSheet::visit_parallel).
+///
+/// The rules are split into chunks, each of which is visited on a separate thread by a visitor
+/// created with [fork](ParallelVisitor::fork). The forked visitors are then merged back into the
+/// original visitor in document order with [join](ParallelVisitor::join). Each forked visitor has
+/// exclusive access to the rules it visits, so it may modify them, but it cannot observe state from
+/// rules visited by other forks. Analysis passes that only collect results are a natural fit.
+#[cfg(feature = "parallel")]
+#[cfg_attr(docsrs, doc(cfg(feature = "parallel")))]
+pub trait ParallelVisitor<'i, T: Visit<'i, T, Self> = DefaultAtRule>:
+  Visitor<'i, T> + Send + Sync + Sized
+{
+  /// Creates an empty visitor with the same configuration, to visit a chunk of rules.
+  fn fork(&self) -> Self;
+
+  /// Merges the results of a forked visitor, which visited the rules following the ones
+  /// already visited by this visitor.
+  fn join(&mut self, other: Self);
 }
 
 /// A trait for values that can be visited by a [Visitor](Visitor).
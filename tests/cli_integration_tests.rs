@@ -389,6 +389,264 @@ fn css_modules_pattern() -> Result<(), Box<dyn std::error::Error>> {
   Ok(())
 }
 
+#[test]
+fn css_modules_dts() -> Result<(), Box<dyn std::error::Error>> {
+  let (input, _, _) = css_module_test_vals();
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  let outdir = assert_fs::TempDir::new()?;
+  let outfile = outdir.child("out.css");
+  infile.write_str(&input)?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(infile.path().parent().unwrap());
+  cmd.arg(infile.path());
+  cmd.arg("--css-modules");
+  cmd.arg("--css-modules-dts");
+  cmd.arg("-o").arg(outfile.path());
+  cmd.assert().success();
+
+  outdir.child("out.css.d.ts").assert(indoc! {r#"
+    declare const styles: {
+      readonly "circles": string;
+      readonly "fade": string;
+      readonly "foo": string;
+      readonly "id": string;
+      readonly "test": string;
+    };
+    export default styles;
+  "#});
+
+  Ok(())
+}
+
+#[test]
+fn css_modules_manifest() -> Result<(), Box<dyn std::error::Error>> {
+  let (input, _, exports) = css_module_test_vals();
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  let outfile = assert_fs::NamedTempFile::new("out.css")?;
+  let manifest_file = assert_fs::NamedTempFile::new("manifest.json")?;
+  infile.write_str(&input)?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(infile.path().parent().unwrap());
+  cmd.arg(infile.path());
+  cmd.arg("-o").arg(outfile.path());
+  cmd.arg("--css-modules");
+  cmd.arg("--css-modules-manifest").arg(manifest_file.path());
+  cmd.assert().success();
+
+  let expected: serde_json::Value = serde_json::json!({
+    "test.css": serde_json::from_str::<serde_json::Value>(&exports)?
+  });
+  let actual: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(manifest_file.path())?)?;
+  assert_eq!(expected, actual);
+
+  Ok(())
+}
+
+#[test]
+fn css_modules_minimal_names() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  let outfile = assert_fs::NamedTempFile::new("out.css")?;
+  let names_file = assert_fs::NamedTempFile::new("names.json")?;
+  infile.write_str(".foo { color: red } .bar { color: green }")?;
+  names_file.write_str(r#"{"EgL3uq_bar": "a"}"#)?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(infile.path().parent().unwrap());
+  cmd.arg(infile.path());
+  cmd.arg("-o").arg(outfile.path());
+  cmd.arg("--css-modules");
+  cmd.arg("--css-modules-minimal-names").arg(names_file.path());
+  cmd.arg("--minify");
+  cmd.assert().success();
+
+  outfile.assert(".b{color:red}.a{color:green}");
+  let names: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(names_file.path())?)?;
+  assert_eq!(names, serde_json::json!({"EgL3uq_foo": "b", "EgL3uq_bar": "a"}));
+
+  Ok(())
+}
+
+#[test]
+fn css_modules_pure() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(".foo { color: red }\nbody { color: green }")?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--css-modules");
+  cmd.arg("--css-modules-pure");
+  cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("ImpureCssModulesSelector"));
+
+  Ok(())
+}
+
+#[test]
+fn css_modules_icss() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(".foo { color: red }")?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(infile.path().parent().unwrap());
+  cmd.arg(infile.path());
+  cmd.arg("--css-modules");
+  cmd.arg("--css-modules-icss");
+  cmd.arg("--minify");
+  cmd.assert().success().stdout(predicate::str::contains(
+    ".EgL3uq_foo{color:red}:export{foo:EgL3uq_foo}",
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn validation() -> Result<(), Box<dyn std::error::Error>> {
+  let infile = assert_fs::NamedTempFile::new("test.css")?;
+  infile.write_str(".foo { widht: 10px; color: red }")?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--validation=warn");
+  cmd.arg("--minify");
+  cmd
+    .assert()
+    .success()
+    .stdout(predicate::str::contains(".foo{widht:10px;color:red}"))
+    .stderr(predicate::str::contains("Unknown property: widht"));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.arg(infile.path());
+  cmd.arg("--validation=error");
+  cmd
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("UnknownProperty(\"widht\")"));
+
+  Ok(())
+}
+
+#[test]
+fn rebase_urls() -> Result<(), Box<dyn std::error::Error>> {
+  let dir = assert_fs::TempDir::new()?;
+  dir.child("src/index.css").write_str(
+    r#"
+      @import "components/button.css";
+      .a { background: url(images/bg.png) }
+    "#,
+  )?;
+  dir
+    .child("src/components/button.css")
+    .write_str(".button { background: url(../images/button.png) }")?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("src/index.css");
+  cmd.arg("--bundle");
+  cmd.arg("--minify");
+  cmd.arg("--rebase-urls");
+  cmd.arg("-o").arg("dist/out.css");
+  cmd.assert().success();
+  dir.child("dist/out.css").assert(predicate::str::contains(
+    ".button{background:url(../src/images/button.png)}.a{background:url(../src/images/bg.png)}",
+  ));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("src/index.css");
+  cmd.arg("--bundle");
+  cmd.arg("--minify");
+  cmd.arg("--rebase-urls");
+  cmd.arg("--public-path").arg("/assets/");
+  cmd.arg("-o").arg("src/out.css");
+  cmd.assert().success();
+  dir.child("src/out.css").assert(predicate::str::contains(
+    ".button{background:url(/assets/images/button.png)}.a{background:url(/assets/images/bg.png)}",
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn external_imports() -> Result<(), Box<dyn std::error::Error>> {
+  let dir = assert_fs::TempDir::new()?;
+  dir.child("index.css").write_str(
+    r#"
+      @import "https://example.com/reset.css";
+      @import "vendor/theme.css";
+      @import "b.css";
+      .a { color: red }
+    "#,
+  )?;
+  dir.child("b.css").write_str(".b { color: green }")?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("index.css");
+  cmd.arg("--bundle");
+  cmd.arg("--minify");
+  cmd.arg("--external-imports");
+  cmd.arg("--external-imports-include").arg("vendor/*");
+  cmd.assert().success().stdout(predicate::str::contains(
+    r#"@import "https://example.com/reset.css";@import "vendor/theme.css";.b{color:green}.a{color:red}"#,
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn inline_assets() -> Result<(), Box<dyn std::error::Error>> {
+  let dir = assert_fs::TempDir::new()?;
+  dir
+    .child("index.css")
+    .write_str(".a { background: url(icon.svg), url(dot.png) }")?;
+  dir.child("icon.svg").write_str("<svg></svg>")?;
+  dir.child("dot.png").write_binary(&[0x89, b'P', b'N', b'G'])?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("index.css");
+  cmd.arg("--minify");
+  cmd.arg("--inline-assets");
+  cmd.assert().success().stdout(predicate::str::contains(
+    ".a{background:url(data:image/svg+xml,%3Csvg%3E%3C/svg%3E),url(data:image/png;base64,iVBORw==)}",
+  ));
+
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("index.css");
+  cmd.arg("--minify");
+  cmd.arg("--inline-assets").arg("8");
+  cmd.arg("--inline-assets-exclude").arg("*.svg");
+  cmd.assert().success().stdout(predicate::str::contains(
+    ".a{background:url(icon.svg),url(data:image/png;base64,iVBORw==)}",
+  ));
+
+  Ok(())
+}
+
+#[test]
+fn emit_assets() -> Result<(), Box<dyn std::error::Error>> {
+  let dir = assert_fs::TempDir::new()?;
+  dir
+    .child("src/index.css")
+    .write_str(".a { background: url(images/bg.png) } .b { list-style-image: url(images/bg.png) }")?;
+  dir.child("src/images/bg.png").write_binary(&[0x89, b'P', b'N', b'G'])?;
+  let mut cmd = Command::cargo_bin("lightningcss")?;
+  cmd.current_dir(dir.path());
+  cmd.arg("src/index.css");
+  cmd.arg("--minify");
+  cmd.arg("--emit-assets");
+  cmd.arg("--asset-manifest").arg("assets.json");
+  cmd.arg("-o").arg("dist/index.css");
+  cmd.assert().success();
+
+  let manifest: HashMap<String, String> = serde_json::from_str(&fs::read_to_string(dir.child("assets.json"))?)?;
+  let emitted = &manifest["src/images/bg.png"];
+  assert!(emitted.starts_with("bg.") && emitted.ends_with(".png"));
+  assert_eq!(fs::read(dir.child("dist").child(emitted))?, [0x89, b'P', b'N', b'G']);
+  dir.child("dist/index.css").assert(predicate::str::contains(format!(
+    ".a{{background:url({0})}}.b{{list-style-image:url({0})}}",
+    emitted
+  )));
+
+  Ok(())
+}
+
 #[test]
 fn sourcemap() -> Result<(), Box<dyn std::error::Error>> {
   let (input, _, _) = css_module_test_vals();
@@ -12,12 +12,12 @@ const State = {
 // This uses Binaryen's Asyncify transform to suspend native code execution while a promise is resolving.
 // That allows synchronous Rust code to call async JavaScript functions without multi-threading.
 // When Rust wants to await a promise, it calls await_promise_sync, which saves the stack state and unwinds.
-// That causes the bundle function to return early. If a promise has been queued, we can then await it
-// and "rewind" the function back to where it was before by calling it again. This time the result of
-// the promise can be returned, and the function can continue where it left off.
+// That causes the called function (e.g. bundle) to return early. If a promise has been queued, we can then
+// await it and "rewind" the function back to where it was before by calling it again. This time the result
+// of the promise can be returned, and the function can continue where it left off.
 // See the docs in https://github.com/WebAssembly/binaryen/blob/main/src/passes/Asyncify.cpp
 // The code here is also partially based on https://github.com/GoogleChromeLabs/asyncify
-export function createBundleAsync(env) {
+export function createAsyncDriver(env) {
   let {instance, exports} = env;
   let {asyncify_get_state, asyncify_start_unwind, asyncify_stop_unwind, asyncify_start_rewind, asyncify_stop_rewind} = instance.exports;
 
@@ -53,9 +53,9 @@ export function createBundleAsync(env) {
     asyncify_start_unwind(DATA_ADDR);
   };
 
-  return async function bundleAsync(options) {
+  return async function callAsync(name, options) {
     assertNoneState();
-    let res = exports.bundle(options);
+    let res = exports[name](options);
     while (asyncify_get_state() === State.Unwinding) {
       asyncify_stop_unwind();
       try {
@@ -65,7 +65,7 @@ export function createBundleAsync(env) {
       }
       assertNoneState();
       asyncify_start_rewind(DATA_ADDR);
-      res = exports.bundle(options);
+      res = exports[name](options);
     }
 
     assertNoneState();
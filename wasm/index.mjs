@@ -1,7 +1,8 @@
 import { Environment, napi } from 'napi-wasm';
-import { await_promise_sync, createBundleAsync } from './async.mjs';
+import { await_promise_sync, createAsyncDriver } from './async.mjs';
+import { createQueryVisitor } from './queryVisitor.js';
 
-let wasm, initPromise, bundleAsyncInternal;
+let wasm, initPromise, callAsync;
 
 export default async function init(input) {
   if (wasm) return;
@@ -19,7 +20,7 @@ export default async function init(input) {
     .then(input => load(input, {env: {...napi, await_promise_sync}}))
     .then(({instance}) => {
       let env = new Environment(instance);
-      bundleAsyncInternal = createBundleAsync(env);
+      callAsync = createAsyncDriver(env);
       wasm = env.exports;
     });
 
@@ -30,21 +31,62 @@ export function transform(options) {
   return wasm.transform(options);
 }
 
+export function transformAsync(options) {
+  return callAsync('transformAsync', options);
+}
+
 export function transformStyleAttribute(options) {
   return wasm.transformStyleAttribute(options);
 }
 
+export function specificity(selector) {
+  return wasm.specificity(selector);
+}
+
+export function parseSelector(selector) {
+  return wasm.parseSelector(selector);
+}
+
+export function parseRule(code) {
+  return wasm.parseRule(code);
+}
+
+export function parseDeclarations(code) {
+  return wasm.parseDeclarations(code);
+}
+
+export function parseMediaQuery(code) {
+  return wasm.parseMediaQuery(code);
+}
+
+export function printRule(value, options) {
+  return wasm.printRule(value, options);
+}
+
+export function printDeclarations(value, options) {
+  return wasm.printDeclarations(value, options);
+}
+
+export function printSelector(value, options) {
+  return wasm.printSelector(value, options);
+}
+
+export function printMediaQuery(value, options) {
+  return wasm.printMediaQuery(value, options);
+}
+
 export function bundle(options) {
   return wasm.bundle(options);
 }
 
 export function bundleAsync(options) {
-  return bundleAsyncInternal(options);
+  return callAsync('bundle', options);
 }
 
 export { browserslistToTargets } from './browserslistToTargets.js';
 export { Features } from './flags.js';
 export { composeVisitors } from './composeVisitors.js';
+export const queryVisitor = createQueryVisitor(parseSelector);
 
 async function load(module, imports) {
   if (typeof Response === 'function' && module instanceof Response) {
@@ -1,5 +1,6 @@
 import { Environment, napi } from 'napi-wasm';
-import { await_promise_sync, createBundleAsync } from './async.mjs';
+import { await_promise_sync, createAsyncDriver } from './async.mjs';
+import { createQueryVisitor } from './queryVisitor.js';
 import fs from 'fs';
 
 let wasmBytes = fs.readFileSync(new URL('lightningcss_node.wasm', import.meta.url));
@@ -12,7 +13,7 @@ let instance = new WebAssembly.Instance(wasmModule, {
 });
 let env = new Environment(instance);
 let wasm = env.exports;
-let bundleAsyncInternal = createBundleAsync(env);
+let callAsync = createAsyncDriver(env);
 
 export default async function init() {
   // do nothing. for backward compatibility.
@@ -22,11 +23,55 @@ export function transform(options) {
   return wasm.transform(options);
 }
 
+export function transformAsync(options) {
+  return callAsync('transformAsync', options);
+}
+
 export function transformStyleAttribute(options) {
   return wasm.transformStyleAttribute(options);
 }
 
+export function specificity(selector) {
+  return wasm.specificity(selector);
+}
+
+export function parseSelector(selector) {
+  return wasm.parseSelector(selector);
+}
+
+export function parseRule(code) {
+  return wasm.parseRule(code);
+}
+
+export function parseDeclarations(code) {
+  return wasm.parseDeclarations(code);
+}
+
+export function parseMediaQuery(code) {
+  return wasm.parseMediaQuery(code);
+}
+
+export function printRule(value, options) {
+  return wasm.printRule(value, options);
+}
+
+export function printDeclarations(value, options) {
+  return wasm.printDeclarations(value, options);
+}
+
+export function printSelector(value, options) {
+  return wasm.printSelector(value, options);
+}
+
+export function printMediaQuery(value, options) {
+  return wasm.printMediaQuery(value, options);
+}
+
 export function bundle(options) {
+  if (options.files && !options.resolver) {
+    return wasm.bundle(options);
+  }
+
   return wasm.bundle({
     ...options,
     resolver: {
@@ -36,16 +81,17 @@ export function bundle(options) {
 }
 
 export async function bundleAsync(options) {
-  if (!options.resolver?.read) {
+  if (!options.files && !options.resolver?.read) {
     options.resolver = {
       ...options.resolver,
       read: (filePath) => fs.readFileSync(filePath, 'utf8')
     };
   }
 
-  return bundleAsyncInternal(options);
+  return callAsync('bundle', options);
 }
 
 export { browserslistToTargets } from './browserslistToTargets.js'
 export { Features } from './flags.js'
 export { composeVisitors } from './composeVisitors.js';
+export const queryVisitor = createQueryVisitor(parseSelector);